@@ -184,6 +184,7 @@ impl Form {
                     ssh: None,
                     socket,
                     session: None,
+                    sessions: Vec::new(),
                     extra: serde_json::Map::new(),
                 })
             }
@@ -216,6 +217,7 @@ impl Form {
                     }),
                     socket,
                     session: None,
+                    sessions: Vec::new(),
                     extra: serde_json::Map::new(),
                 })
             }
@@ -28,8 +28,8 @@ const FULL_SYNC: &str = concat!(
     "%session-changed $0 m\n",
     "%window-add @0\n",
     "%begin 2 2 1\n",
-    "%0,0,0,0,40,24,0,0,1,zsh,,0,0,0,0,@0,,0,0,0,0,0,100\n",
-    "%1,1,41,0,39,24,0,0,0,zsh,,0,0,0,0,@0,,0,0,0,0,0,100\n",
+    "%0,0,0,0,40,24,0,0,1,zsh,,0,0,0,0,@0,,0,0,0,0,0,0,0,100,,\n",
+    "%1,1,41,0,39,24,0,0,0,zsh,,0,0,0,0,@0,,0,0,0,0,0,0,0,100,,\n",
     "%end 2 2 1\n",
     "%layout-change @0 8205,80x24,0,0{40x24,0,0,0,39x24,41,0,1} ",
     "8205,80x24,0,0{40x24,0,0,0,39x24,41,0,1} *\n",
@@ -0,0 +1,15 @@
+//! Raw control-mode output, split into lines the way the connection reads
+//! it, through `Parser::parse_bytes`.
+
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use tmuxy_core::control_mode::Parser;
+
+fuzz_target!(|data: &[u8]| {
+    let mut parser = Parser::new();
+    for line in data.split(|&b| b == b'\n') {
+        let line = line.strip_suffix(b"\r").unwrap_or(line);
+        let _ = parser.parse_bytes(line);
+    }
+});
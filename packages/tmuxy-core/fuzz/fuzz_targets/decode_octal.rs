@@ -0,0 +1,11 @@
+//! Arbitrary bytes through the octal decoder, which must never read past
+//! the input or grow it.
+
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use tmuxy_core::control_mode::decode_octal_bytes;
+
+fuzz_target!(|data: &[u8]| {
+    assert!(decode_octal_bytes(data).len() <= data.len());
+});
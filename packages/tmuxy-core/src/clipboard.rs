@@ -0,0 +1,99 @@
+//! Moving text between tmux paste buffers and the OS clipboard.
+//!
+//! The clipboard itself belongs to the desktop app; this module builds the
+//! tmux side. Reads use `show-buffer` (read-only, safe while control mode is
+//! attached); writes are command strings for the control-mode connection.
+//! Control mode reads one command per line, so pasted text is written as a
+//! double-quoted string with its line breaks escaped.
+
+use crate::constants::tmux_options::CLIPBOARD_SYNC;
+use crate::executor::{execute_tmux_command, tmux_quote};
+
+/// Paste buffer the OS clipboard is loaded into.
+pub const CLIPBOARD_BUFFER: &str = "tmuxy-clipboard";
+
+/// The text of paste buffer `name`, or of the most recent buffer.
+pub fn read_buffer(name: Option<&str>) -> crate::error::Result<String> {
+    match name {
+        Some(name) => execute_tmux_command(&["show-buffer", "-b", name]),
+        None => execute_tmux_command(&["show-buffer"]),
+    }
+}
+
+/// Whether `@tmuxy-clipboard-sync` is `on`.
+pub fn sync_enabled() -> bool {
+    execute_tmux_command(&["show-options", "-gqv", CLIPBOARD_SYNC])
+        .is_ok_and(|v| matches!(v.trim(), "on" | "1" | "true" | "yes"))
+}
+
+/// `set-buffer` loading `text` into [`CLIPBOARD_BUFFER`].
+pub fn set_buffer_command(text: &str) -> String {
+    format!("set-buffer -b {CLIPBOARD_BUFFER} -- {}", double_quote(text))
+}
+
+/// `paste-buffer` pasting [`CLIPBOARD_BUFFER`] into `pane_id`, bracketed
+/// when the application asked for bracketed paste.
+pub fn paste_buffer_command(pane_id: &str) -> String {
+    format!(
+        "paste-buffer -p -b {CLIPBOARD_BUFFER} -t {}",
+        tmux_quote(pane_id)
+    )
+}
+
+/// Double-quote `text` for a tmux command line. Unlike [`tmux_quote`], the
+/// result never contains a raw line break: newlines and other control
+/// characters become escapes tmux's parser turns back into the bytes.
+fn double_quote(text: &str) -> String {
+    let mut out = String::with_capacity(text.len() + 2);
+    out.push('"');
+    for c in text.chars() {
+        match c {
+            '"' | '\\' | '$' => {
+                out.push('\\');
+                out.push(c);
+            }
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            c if c.is_control() && (c as u32) < 0x80 => {
+                out.push_str(&format!("\\{:03o}", c as u32));
+            }
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+    out
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used, clippy::expect_used)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn set_buffer_keeps_multiline_text_on_one_command_line() {
+        let cmd = set_buffer_command("echo \"$HOME\"\nls C:\\tmp\tx\x1b");
+        assert_eq!(
+            cmd,
+            r#"set-buffer -b tmuxy-clipboard -- "echo \"\$HOME\"\nls C:\\tmp\tx\033""#
+        );
+        assert!(!cmd.contains('\n'));
+    }
+
+    #[test]
+    fn set_buffer_cannot_smuggle_a_second_command() {
+        let cmd = set_buffer_command("x\" ; kill-server ; \"");
+        assert_eq!(
+            cmd,
+            r#"set-buffer -b tmuxy-clipboard -- "x\" ; kill-server ; \"""#
+        );
+    }
+
+    #[test]
+    fn paste_buffer_targets_the_quoted_pane() {
+        assert_eq!(
+            paste_buffer_command("%3"),
+            "paste-buffer -p -b tmuxy-clipboard -t '%3'"
+        );
+    }
+}
@@ -0,0 +1,398 @@
+//! Accessibility color transformations applied to extracted cells.
+//!
+//! A client CSS filter recolors everything, including the theme, and can't
+//! tell that a red `FAIL` and a green `ok` are a *pair* that must stay
+//! distinguishable. These transformations work on the cell styles instead,
+//! per connection, before the cells are sent:
+//!
+//! - `high-contrast` pushes each foreground away from its background until
+//!   the pair reaches WCAG AAA contrast (7:1) and drops faint text.
+//! - `deuteranopia-simulate` shows what a deuteranope sees (Machado et al.
+//!   2009, full severity), for checking a color scheme.
+//! - `deuteranopia` compensates for it by shifting the red/green information
+//!   a deuteranope loses into blue (daltonization).
+//! - `red-green-safe` replaces reds and greens with the Okabe–Ito vermillion
+//!   and blue, which stay apart under every common color-vision deficiency.
+//!
+//! Palette colors are resolved with the xterm defaults when a transformation
+//! needs RGB, so a transformed cell no longer follows the client theme's
+//! palette. Default (unset) colors are left to the theme, except where
+//! `high-contrast` needs a concrete background to measure against.
+
+use crate::{CellColor, CellStyle, StateUpdate, TerminalLine};
+
+/// Contrast ratio `high-contrast` raises foregrounds to (WCAG AAA).
+const MIN_CONTRAST: f64 = 7.0;
+
+/// Okabe–Ito replacements for `red-green-safe`: (normal, bright).
+const SAFE_RED: [(u8, u8, u8); 2] = [(213, 94, 0), (230, 159, 0)];
+const SAFE_GREEN: [(u8, u8, u8); 2] = [(0, 114, 178), (86, 180, 233)];
+
+/// One color transformation.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ColorTransform {
+    HighContrast,
+    SimulateDeuteranopia,
+    CompensateDeuteranopia,
+    RedGreenSafe,
+}
+
+impl ColorTransform {
+    pub fn parse(name: &str) -> Option<Self> {
+        match name {
+            "high-contrast" => Some(Self::HighContrast),
+            "deuteranopia-simulate" => Some(Self::SimulateDeuteranopia),
+            "deuteranopia" => Some(Self::CompensateDeuteranopia),
+            "red-green-safe" => Some(Self::RedGreenSafe),
+            _ => None,
+        }
+    }
+}
+
+/// The transformations one connection asked for, applied in order.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ColorTransforms {
+    pub steps: Vec<ColorTransform>,
+    /// The client theme's background is light; `high-contrast` measures
+    /// cells without an explicit background against white instead of black.
+    pub light_background: bool,
+}
+
+impl ColorTransforms {
+    /// Parse a comma-separated list such as `high-contrast,red-green-safe`.
+    /// An empty list is valid and transforms nothing.
+    pub fn parse(list: &str, light_background: bool) -> Result<Self, String> {
+        let steps = list
+            .split(',')
+            .map(str::trim)
+            .filter(|name| !name.is_empty())
+            .map(|name| {
+                ColorTransform::parse(name).ok_or_else(|| format!("unknown color mode {name:?}"))
+            })
+            .collect::<Result<_, _>>()?;
+        Ok(Self {
+            steps,
+            light_background,
+        })
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.steps.is_empty()
+    }
+
+    /// Transform one cell style in place.
+    pub fn apply_style(&self, style: &mut CellStyle) {
+        for step in &self.steps {
+            match step {
+                ColorTransform::HighContrast => self.raise_contrast(style),
+                ColorTransform::SimulateDeuteranopia => {
+                    map_colors(style, |c| Some(simulate_deuteranopia(c)))
+                }
+                ColorTransform::CompensateDeuteranopia => {
+                    map_colors(style, |c| Some(compensate_deuteranopia(c)))
+                }
+                ColorTransform::RedGreenSafe => map_colors(style, red_green_safe),
+            }
+        }
+    }
+
+    pub fn apply_line(&self, line: &mut TerminalLine) {
+        for style in line.iter_mut().filter_map(|cell| cell.style.as_mut()) {
+            self.apply_style(style);
+        }
+    }
+
+    /// Transform every cell a state update carries.
+    pub fn apply_update(&self, update: &mut StateUpdate) {
+        if self.is_empty() {
+            return;
+        }
+        match update {
+            StateUpdate::Full { state } => {
+                for pane in &mut state.panes {
+                    std::sync::Arc::make_mut(&mut pane.content)
+                        .iter_mut()
+                        .for_each(|line| self.apply_line(line));
+                }
+            }
+            StateUpdate::Delta { delta } => {
+                for pane in delta
+                    .panes
+                    .iter_mut()
+                    .flat_map(|p| p.values_mut().flatten())
+                {
+                    if let Some(content) = pane.content.as_mut() {
+                        content.values_mut().for_each(|line| self.apply_line(line));
+                    }
+                    if let Some(scroll) = pane.scroll.as_mut() {
+                        scroll
+                            .new_rows
+                            .iter_mut()
+                            .for_each(|line| self.apply_line(line));
+                    }
+                }
+                for pane in delta.new_panes.iter_mut().flatten() {
+                    std::sync::Arc::make_mut(&mut pane.content)
+                        .iter_mut()
+                        .for_each(|line| self.apply_line(line));
+                }
+            }
+        }
+    }
+
+    fn raise_contrast(&self, style: &mut CellStyle) {
+        style.dim = false;
+        if style.fg.is_none() && style.bg.is_none() {
+            // Theme foreground on theme background: the theme's business.
+            return;
+        }
+        let (default_fg, default_bg) = if self.light_background {
+            ((0, 0, 0), (255, 255, 255))
+        } else {
+            ((255, 255, 255), (0, 0, 0))
+        };
+        let bg = style.bg.as_ref().map_or(default_bg, to_rgb);
+        let fg = style.fg.as_ref().map_or(default_fg, to_rgb);
+        if contrast(fg, bg) >= MIN_CONTRAST {
+            return;
+        }
+        // Mix toward whichever of black or white stands out more, using the
+        // smallest step that reaches the target.
+        let target = if contrast((255, 255, 255), bg) >= contrast((0, 0, 0), bg) {
+            (255, 255, 255)
+        } else {
+            (0, 0, 0)
+        };
+        let (mut lo, mut hi) = (0.0, 1.0);
+        for _ in 0..16 {
+            let mid = (lo + hi) / 2.0;
+            if contrast(mix(fg, target, mid), bg) >= MIN_CONTRAST {
+                hi = mid;
+            } else {
+                lo = mid;
+            }
+        }
+        style.fg = Some(rgb(mix(fg, target, hi)));
+    }
+}
+
+type Rgb = (u8, u8, u8);
+
+fn rgb((r, g, b): Rgb) -> CellColor {
+    CellColor::Rgb { r, g, b }
+}
+
+/// Apply `f` to each explicit color of `style`; `None` leaves it as is.
+fn map_colors(style: &mut CellStyle, f: impl Fn(&CellColor) -> Option<CellColor>) {
+    for color in [&mut style.fg, &mut style.bg, &mut style.underline_color]
+        .into_iter()
+        .flatten()
+    {
+        if let Some(mapped) = f(color) {
+            *color = mapped;
+        }
+    }
+}
+
+/// A color as RGB, resolving palette indices with the xterm defaults.
+pub(crate) fn to_rgb(color: &CellColor) -> Rgb {
+    const ANSI: [Rgb; 16] = [
+        (0, 0, 0),
+        (205, 0, 0),
+        (0, 205, 0),
+        (205, 205, 0),
+        (0, 0, 238),
+        (205, 0, 205),
+        (0, 205, 205),
+        (229, 229, 229),
+        (127, 127, 127),
+        (255, 0, 0),
+        (0, 255, 0),
+        (255, 255, 0),
+        (92, 92, 255),
+        (255, 0, 255),
+        (0, 255, 255),
+        (255, 255, 255),
+    ];
+    const CUBE: [u8; 6] = [0, 95, 135, 175, 215, 255];
+    match *color {
+        CellColor::Rgb { r, g, b } => (r, g, b),
+        CellColor::Indexed(i @ 0..=15) => ANSI[usize::from(i)],
+        CellColor::Indexed(i @ 16..=231) => {
+            let i = usize::from(i - 16);
+            (CUBE[i / 36], CUBE[i / 6 % 6], CUBE[i % 6])
+        }
+        CellColor::Indexed(i) => {
+            let level = 8 + 10 * (i - 232);
+            (level, level, level)
+        }
+    }
+}
+
+fn to_linear(channel: u8) -> f64 {
+    let c = f64::from(channel) / 255.0;
+    if c <= 0.04045 {
+        c / 12.92
+    } else {
+        ((c + 0.055) / 1.055).powf(2.4)
+    }
+}
+
+fn from_linear(c: f64) -> u8 {
+    let c = c.clamp(0.0, 1.0);
+    let s = if c <= 0.003_130_8 {
+        c * 12.92
+    } else {
+        1.055 * c.powf(1.0 / 2.4) - 0.055
+    };
+    (s * 255.0).round() as u8
+}
+
+fn luminance((r, g, b): Rgb) -> f64 {
+    0.2126 * to_linear(r) + 0.7152 * to_linear(g) + 0.0722 * to_linear(b)
+}
+
+/// WCAG contrast ratio, 1.0 (none) to 21.0 (black on white).
+fn contrast(a: Rgb, b: Rgb) -> f64 {
+    let (la, lb) = (luminance(a), luminance(b));
+    (la.max(lb) + 0.05) / (la.min(lb) + 0.05)
+}
+
+fn mix(from: Rgb, to: Rgb, t: f64) -> Rgb {
+    let lerp = |a: u8, b: u8| (f64::from(a) + (f64::from(b) - f64::from(a)) * t).round() as u8;
+    (lerp(from.0, to.0), lerp(from.1, to.1), lerp(from.2, to.2))
+}
+
+fn deuteranope_view(color: Rgb) -> Rgb {
+    let (r, g, b) = (to_linear(color.0), to_linear(color.1), to_linear(color.2));
+    (
+        from_linear(0.367_322 * r + 0.860_646 * g - 0.227_968 * b),
+        from_linear(0.280_085 * r + 0.672_501 * g + 0.047_413 * b),
+        from_linear(-0.011_820 * r + 0.042_940 * g + 0.968_881 * b),
+    )
+}
+
+fn simulate_deuteranopia(color: &CellColor) -> CellColor {
+    rgb(deuteranope_view(to_rgb(color)))
+}
+
+fn compensate_deuteranopia(color: &CellColor) -> CellColor {
+    let original = to_rgb(color);
+    let seen = deuteranope_view(original);
+    let err = |a: u8, b: u8| f64::from(a) - f64::from(b);
+    let (er, eg, eb) = (
+        err(original.0, seen.0),
+        err(original.1, seen.1),
+        err(original.2, seen.2),
+    );
+    let shift = |c: u8, d: f64| (f64::from(c) + d).clamp(0.0, 255.0).round() as u8;
+    rgb((
+        original.0,
+        shift(original.1, 0.7 * er + eg),
+        shift(original.2, 0.7 * er + eb),
+    ))
+}
+
+/// Reds become vermillion and greens blue; anything else is left alone.
+fn red_green_safe(color: &CellColor) -> Option<CellColor> {
+    let pick = |pair: [Rgb; 2], bright: bool| Some(rgb(pair[usize::from(bright)]));
+    match *color {
+        CellColor::Indexed(1) => pick(SAFE_RED, false),
+        CellColor::Indexed(9) => pick(SAFE_RED, true),
+        CellColor::Indexed(2) => pick(SAFE_GREEN, false),
+        CellColor::Indexed(10) => pick(SAFE_GREEN, true),
+        CellColor::Indexed(0..=15) => None,
+        _ => {
+            let (r, g, b) = to_rgb(color);
+            let (r, g, b) = (u16::from(r), u16::from(g), u16::from(b));
+            let (max, min) = (r.max(g).max(b), r.min(g).min(b));
+            // Greys and near-greys carry no hue to confuse.
+            if max == 0 || (max - min) * 5 < max * 2 {
+                return None;
+            }
+            let bright = max > 220;
+            if r == max && g < r / 2 {
+                pick(SAFE_RED, bright)
+            } else if g == max && r < g * 3 / 4 && b < g * 3 / 4 {
+                pick(SAFE_GREEN, bright)
+            } else {
+                None
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used, clippy::expect_used)]
+mod tests {
+    use super::*;
+
+    fn style(fg: Option<CellColor>, bg: Option<CellColor>) -> CellStyle {
+        CellStyle {
+            fg,
+            bg,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn parses_modes_and_rejects_unknown_ones() {
+        let t = ColorTransforms::parse("high-contrast, red-green-safe,", false).unwrap();
+        assert_eq!(
+            t.steps,
+            [ColorTransform::HighContrast, ColorTransform::RedGreenSafe]
+        );
+        assert!(ColorTransforms::parse("", false).unwrap().is_empty());
+        assert!(ColorTransforms::parse("sepia", false).is_err());
+    }
+
+    #[test]
+    fn red_green_pairs_become_vermillion_and_blue() {
+        let t = ColorTransforms::parse("red-green-safe", false).unwrap();
+        let mut fail = style(Some(CellColor::Indexed(1)), None);
+        let mut pass = style(
+            Some(CellColor::Rgb {
+                r: 0,
+                g: 200,
+                b: 40,
+            }),
+            None,
+        );
+        let mut plain = style(Some(CellColor::Indexed(4)), None);
+        t.apply_style(&mut fail);
+        t.apply_style(&mut pass);
+        t.apply_style(&mut plain);
+        assert_eq!(fail.fg, Some(rgb(SAFE_RED[0])));
+        assert_eq!(pass.fg, Some(rgb(SAFE_GREEN[0])));
+        assert_eq!(plain.fg, Some(CellColor::Indexed(4)));
+    }
+
+    #[test]
+    fn high_contrast_reaches_aaa_and_drops_faint_text() {
+        let t = ColorTransforms::parse("high-contrast", false).unwrap();
+        // Dark grey on the (dark) theme background.
+        let mut s = style(Some(CellColor::Indexed(240)), None);
+        s.dim = true;
+        t.apply_style(&mut s);
+        assert!(!s.dim);
+        let fg = to_rgb(s.fg.as_ref().unwrap());
+        assert!(contrast(fg, (0, 0, 0)) >= MIN_CONTRAST);
+
+        // Already readable pairs are untouched.
+        let mut ok = style(Some(CellColor::Indexed(15)), Some(CellColor::Indexed(0)));
+        t.apply_style(&mut ok);
+        assert_eq!(ok.fg, Some(CellColor::Indexed(15)));
+    }
+
+    #[test]
+    fn deuteranopia_simulation_merges_red_and_green() {
+        let red = to_rgb(&simulate_deuteranopia(&CellColor::Indexed(9)));
+        let green = to_rgb(&simulate_deuteranopia(&CellColor::Indexed(10)));
+        // Both collapse onto the yellow/olive axis: red no longer trails
+        // green in the green primary.
+        assert!(red.0 >= red.1 && green.0 >= green.1, "{red:?} {green:?}");
+        // Compensation moves red's lost information into blue.
+        let fixed = to_rgb(&compensate_deuteranopia(&CellColor::Indexed(9)));
+        assert!(fixed.2 > red.2, "{fixed:?}");
+    }
+}
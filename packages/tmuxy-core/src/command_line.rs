@@ -0,0 +1,330 @@
+//! Reading tmux command lines.
+//!
+//! Clients send commands as they would type them at tmux's command prompt:
+//! `send-keys -t %1 -l 'it'\''s'`, `splitw \; breakp`. Run as an external
+//! `tmux`, they used to be pasted into `sh -c`, which read them with shell
+//! rules rather than tmux's and ran whatever `$(…)` or `&&` they carried.
+//! [`split_commands`] reads a line the way tmux's own parser does, into one
+//! argv per command, and [`join_commands`] writes argvs back out as a line
+//! for the control-mode connection.
+//!
+//! Read as tmux does: words split on spaces and tabs; single quotes take
+//! everything literally; double quotes and bare words take tmux's backslash
+//! escapes (`\n`, `\e`, `\033`, `\u20ac`, any other character as itself);
+//! `#` starts a comment; `;`, a newline or a `\;` word ends a command.
+//! Unlike tmux, `$VAR` and `~` are not expanded — the server's environment
+//! is not the client's to read — and `{ … }` blocks are plain words.
+
+use thiserror::Error;
+
+/// Why a command line couldn't be read.
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum CommandLineError {
+    #[error("unterminated {0} quote")]
+    UnterminatedQuote(&'static str),
+    #[error("trailing backslash")]
+    TrailingBackslash,
+}
+
+/// Split `line` into its commands, each as an argv. Empty commands (`;;`,
+/// blank lines, comments) are dropped.
+pub fn split_commands(line: &str) -> Result<Vec<Vec<String>>, CommandLineError> {
+    let chars: Vec<char> = line.chars().collect();
+    let mut commands = Vec::new();
+    let mut args = Vec::new();
+    let mut i = 0;
+    let mut end_command = |args: &mut Vec<String>| {
+        if !args.is_empty() {
+            commands.push(std::mem::take(args));
+        }
+    };
+    while let Some(&c) = chars.get(i) {
+        match c {
+            ' ' | '\t' => i += 1,
+            '\n' | ';' => {
+                i += 1;
+                end_command(&mut args);
+            }
+            '#' => {
+                while chars.get(i).is_some_and(|&c| c != '\n') {
+                    i += 1;
+                }
+            }
+            '\\' if chars.get(i + 1) == Some(&';') && chars.get(i + 2).is_none_or(is_blank) => {
+                i += 2;
+                end_command(&mut args);
+            }
+            _ => args.push(read_word(&chars, &mut i)?),
+        }
+    }
+    end_command(&mut args);
+    Ok(commands)
+}
+
+fn is_blank(c: &char) -> bool {
+    matches!(c, ' ' | '\t' | '\n')
+}
+
+/// Read the word starting at `chars[*i]`, leaving `*i` just past it.
+fn read_word(chars: &[char], i: &mut usize) -> Result<String, CommandLineError> {
+    let mut word = String::new();
+    while let Some(&c) = chars.get(*i) {
+        *i += 1;
+        match c {
+            ' ' | '\t' | '\n' | ';' => {
+                *i -= 1;
+                break;
+            }
+            '\'' => loop {
+                match chars.get(*i) {
+                    None => return Err(CommandLineError::UnterminatedQuote("single")),
+                    Some('\'') => {
+                        *i += 1;
+                        break;
+                    }
+                    Some(&c) => {
+                        word.push(c);
+                        *i += 1;
+                    }
+                }
+            },
+            '"' => loop {
+                match chars.get(*i) {
+                    None => return Err(CommandLineError::UnterminatedQuote("double")),
+                    Some('"') => {
+                        *i += 1;
+                        break;
+                    }
+                    Some('\\') => {
+                        *i += 1;
+                        read_escape(chars, i, &mut word)?;
+                    }
+                    Some(&c) => {
+                        word.push(c);
+                        *i += 1;
+                    }
+                }
+            },
+            '\\' => read_escape(chars, i, &mut word)?,
+            _ => word.push(c),
+        }
+    }
+    Ok(word)
+}
+
+/// Read the escape after a backslash into `word`.
+fn read_escape(chars: &[char], i: &mut usize, word: &mut String) -> Result<(), CommandLineError> {
+    let c = *chars.get(*i).ok_or(CommandLineError::TrailingBackslash)?;
+    *i += 1;
+    let simple = match c {
+        'a' => Some('\x07'),
+        'b' => Some('\x08'),
+        'e' => Some('\x1b'),
+        'f' => Some('\x0c'),
+        's' => Some(' '),
+        'v' => Some('\x0b'),
+        'r' => Some('\r'),
+        'n' => Some('\n'),
+        't' => Some('\t'),
+        _ => None,
+    };
+    if let Some(escaped) = simple {
+        word.push(escaped);
+        return Ok(());
+    }
+    // How many more characters the escape takes, and what they decode to.
+    let (len, code) = match c {
+        // Line continuation.
+        '\n' => return Ok(()),
+        '0'..='3' => (2, number(chars, *i - 1, 3, 8)),
+        'u' => (4, number(chars, *i, 4, 16)),
+        'U' => (8, number(chars, *i, 8, 16)),
+        _ => (0, None),
+    };
+    match code.and_then(char::from_u32) {
+        Some(decoded) => {
+            word.push(decoded);
+            *i += len;
+        }
+        None => word.push(c),
+    }
+    Ok(())
+}
+
+/// The number spelled by the `len` digits at `chars[start..]`, if there are
+/// that many.
+fn number(chars: &[char], start: usize, len: usize, radix: u32) -> Option<u32> {
+    let digits = chars.get(start..start + len)?;
+    digits
+        .iter()
+        .try_fold(0, |n, c| Some(n * radix + c.to_digit(radix)?))
+}
+
+/// `arg` as one word of a tmux command line: bare when it can be, single
+/// quoted when it holds no control characters, double quoted with escapes
+/// otherwise (a raw newline would end a control-mode command).
+pub fn quote(arg: &str) -> String {
+    let bare = |c: char| c.is_ascii_alphanumeric() || "-_./:%@,=+^".contains(c);
+    if !arg.is_empty() && arg.chars().all(bare) {
+        return arg.to_string();
+    }
+    if !arg.chars().any(char::is_control) {
+        return format!("'{}'", arg.replace('\'', r"'\''"));
+    }
+    let mut quoted = String::from("\"");
+    for c in arg.chars() {
+        match c {
+            '"' | '\\' | '$' => {
+                quoted.push('\\');
+                quoted.push(c);
+            }
+            '\n' => quoted.push_str("\\n"),
+            '\r' => quoted.push_str("\\r"),
+            '\t' => quoted.push_str("\\t"),
+            '\x1b' => quoted.push_str("\\e"),
+            c if c.is_control() && (c as u32) < 0x100 => {
+                quoted.push_str(&format!("\\{:03o}", c as u32));
+            }
+            c if c.is_control() => quoted.push_str(&format!("\\u{:04x}", c as u32)),
+            c => quoted.push(c),
+        }
+    }
+    quoted.push('"');
+    quoted
+}
+
+/// Write `commands` back out as one tmux command line.
+pub fn join_commands(commands: &[Vec<String>]) -> String {
+    commands
+        .iter()
+        .map(|args| args.iter().map(|a| quote(a)).collect::<Vec<_>>().join(" "))
+        .collect::<Vec<_>>()
+        .join(" ; ")
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used, clippy::expect_used)]
+mod tests {
+    use super::*;
+
+    fn split(line: &str) -> Vec<Vec<String>> {
+        split_commands(line).unwrap()
+    }
+
+    fn one(line: &str) -> Vec<String> {
+        let mut commands = split(line);
+        assert_eq!(commands.len(), 1, "{line:?} is one command: {commands:?}");
+        commands.remove(0)
+    }
+
+    #[test]
+    fn splits_words_and_commands() {
+        assert_eq!(one("  select-pane\t-t %1 "), ["select-pane", "-t", "%1"]);
+        assert_eq!(
+            split("splitw \\; breakp ; resizew -x 80\nselectw -t @1"),
+            [
+                vec!["splitw"],
+                vec!["breakp"],
+                vec!["resizew", "-x", "80"],
+                vec!["selectw", "-t", "@1"],
+            ]
+        );
+        // A word ending in `;` ends its command too, as in tmux.
+        assert_eq!(split("splitw; breakp"), [vec!["splitw"], vec!["breakp"]]);
+        assert!(split(" ;; \n # just a comment").is_empty());
+        assert_eq!(one("list-panes # -a"), ["list-panes"]);
+    }
+
+    #[test]
+    fn quotes_keep_payloads_whole() {
+        assert_eq!(
+            one("send-keys -t %1 -l 'a; b \\; c \"d\" $HOME #x'"),
+            ["send-keys", "-t", "%1", "-l", "a; b \\; c \"d\" $HOME #x"]
+        );
+        assert_eq!(
+            one(r#"send-keys -l "it's \"quoted\" \\ \; here""#),
+            ["send-keys", "-l", "it's \"quoted\" \\ ; here"]
+        );
+        // `tmux_quote`'s close-escape-reopen form for a single quote.
+        assert_eq!(one(r"send-keys -l 'it'\''s'"), ["send-keys", "-l", "it's"]);
+        // Empty quotes are an empty argument, not none.
+        assert_eq!(one("send-keys -l ''"), ["send-keys", "-l", ""]);
+        // Shell syntax is just text.
+        assert_eq!(
+            one("list-panes -a && kill-server $(reboot) `id` | sh > /tmp/x"),
+            [
+                "list-panes",
+                "-a",
+                "&&",
+                "kill-server",
+                "$(reboot)",
+                "`id`",
+                "|",
+                "sh",
+                ">",
+                "/tmp/x"
+            ]
+        );
+        assert_eq!(
+            one("display -p '#{pane_id} #{?pane_active,a,b}'"),
+            ["display", "-p", "#{pane_id} #{?pane_active,a,b}"]
+        );
+    }
+
+    #[test]
+    fn escapes_decode_like_tmux() {
+        assert_eq!(one(r"send-keys -l a\;b\ c"), ["send-keys", "-l", "a;b c"]);
+        assert_eq!(
+            one(r#"send-keys -l "\e[A\n\t\033\u20ac\U0001F600\q\s""#),
+            ["send-keys", "-l", "\x1b[A\n\t\x1b€😀q "]
+        );
+        // Incomplete numeric escapes fall back to the letter.
+        assert_eq!(one(r#"send-keys "\u20""#), ["send-keys", "u20"]);
+        assert_eq!(one("send-keys a\\\nb"), ["send-keys", "ab"]);
+    }
+
+    #[test]
+    fn rejects_unterminated_lines() {
+        assert_eq!(
+            split_commands("send-keys -l 'abc"),
+            Err(CommandLineError::UnterminatedQuote("single"))
+        );
+        assert_eq!(
+            split_commands("send-keys -l \"abc\\\""),
+            Err(CommandLineError::UnterminatedQuote("double"))
+        );
+        assert_eq!(
+            split_commands("send-keys abc\\"),
+            Err(CommandLineError::TrailingBackslash)
+        );
+    }
+
+    #[test]
+    fn joined_commands_read_back_the_same() {
+        let commands = vec![
+            vec![
+                "send-keys".to_string(),
+                "-t".into(),
+                "%1".into(),
+                "-l".into(),
+            ],
+            vec!["display".into(), "-p".into(), "#{pane_id}".into()],
+        ];
+        let mut payloads = vec![
+            "plain".to_string(),
+            String::new(),
+            "it's; $HOME \\; ~".into(),
+            "line\nbreak\r\t\x1b[1m\x01\x7f \"q\" \\".into(),
+            "{ block }".into(),
+        ];
+        for payload in payloads.drain(..) {
+            let mut commands = commands.clone();
+            commands[0].push(payload);
+            let line = join_commands(&commands);
+            assert!(!line.contains('\n'), "{line:?}");
+            assert_eq!(split(&line), commands, "{line:?}");
+        }
+        assert_eq!(quote("%1"), "%1");
+        assert_eq!(quote("a b"), "'a b'");
+    }
+}
@@ -34,6 +34,13 @@ pub mod tmux_options {
     pub const FLOAT_BG: &str = "@tmuxy-float-bg";
     /// `1` to suppress the float's header chrome.
     pub const FLOAT_NOHEADER: &str = "@tmuxy-float-noheader";
+    /// Float position in cells from the window's top-left; unset = centered.
+    pub const FLOAT_X: &str = "@tmuxy-float-x";
+    pub const FLOAT_Y: &str = "@tmuxy-float-y";
+    /// Stacking order among floats; the highest is on top.
+    pub const FLOAT_Z: &str = "@tmuxy-float-z";
+    /// `1` while the float is toggled out of view (its pane keeps running).
+    pub const FLOAT_HIDDEN: &str = "@tmuxy-float-hidden";
 
     /// Space-separated pane IDs belonging to a group window (e.g. `%4 %6 %7`).
     /// Space-joined specifically so the value can't collide with the
@@ -44,6 +51,39 @@ pub mod tmux_options {
     pub const THEME: &str = "@tmuxy-theme";
     /// Theme mode: `dark` / `light`.
     pub const THEME_MODE: &str = "@tmuxy-theme-mode";
+
+    /// Per-pane "focus follows output" rule: `bell` raises the pane when it
+    /// rings the bell, `any` on any output. Unset (the default) disables it.
+    /// Only panes hidden in a background group tab or an inactive window are
+    /// ever raised.
+    pub const FOCUS_FOLLOWS_OUTPUT: &str = "@tmuxy-focus-follows-output";
+
+    /// Per-pane switch for answering capability queries tmux drops
+    /// (XTGETTCAP): `off` disables it, unset leaves it on.
+    pub const ANSWERBACK: &str = "@tmuxy-answerback";
+
+    /// Global switch for the desktop app's OS clipboard sync: `on` writes
+    /// every copy-mode copy and OSC 52 request to the system clipboard
+    /// natively. Unset leaves it to the WebView.
+    pub const CLIPBOARD_SYNC: &str = "@tmuxy-clipboard-sync";
+
+    /// Sandbox profile a pane's command runs in, set by `tmuxy pane float
+    /// --sandbox`. Shown on the pane; unset for unsandboxed panes.
+    pub const SANDBOX: &str = "@tmuxy-sandbox";
+
+    /// Widget a pane displays, as an encoded [`WidgetSpec`](crate::widget::WidgetSpec).
+    /// Set by the widget launcher itself; unset on ordinary panes.
+    pub const WIDGET: &str = "@tmuxy-widget";
+
+    /// Session do-not-disturb mode: `on` / `off`, unset follows the
+    /// configured quiet hours. Session-scoped (never `-g`) so each session
+    /// has its own.
+    pub const DND: &str = "@tmuxy-dnd";
+
+    /// Window status format tmuxy evaluates itself, with tmuxy-only
+    /// variables (see `control_mode::window_status`). Per window, or `-g`
+    /// for every window.
+    pub const STATUS: &str = "@tmuxy-status";
 }
 
 /// Compile-time format strings the monitor passes to `list-windows -F` and
@@ -63,7 +103,9 @@ pub mod tmux_formats {
         "#{window_id},#{window_index},#{window_active},#{@tmuxy-window-type},",
         "#{@tmuxy-float-parent},#{@tmuxy-float-width},#{@tmuxy-float-height},",
         "#{@tmuxy-float-drawer},#{@tmuxy-float-bg},#{@tmuxy-float-noheader},",
-        "#{@tmuxy-group-panes},#{window_zoomed_flag},#{window_name}'",
+        "#{@tmuxy-group-panes},#{window_zoomed_flag},",
+        "#{@tmuxy-float-x},#{@tmuxy-float-y},#{@tmuxy-float-z},#{@tmuxy-float-hidden},",
+        "#{window_activity},#{window_name}'",
     );
 
     /// `list-panes -s -F '<...>'` format. The session-scope flag (`-s`) is
@@ -80,7 +122,9 @@ pub mod tmux_formats {
         "#{window_id},#{T:pane-border-format},",
         "#{alternate_on},#{mouse_any_flag},",
         "#{selection_present},",
-        "#{selection_start_x},#{selection_start_y},#{history_size}'",
+        "#{selection_start_x},#{selection_start_y},",
+        "#{selection_end_x},#{selection_end_y},#{history_size},",
+        "#{@tmuxy-focus-follows-output},#{@tmuxy-answerback},#{@tmuxy-sandbox},#{@tmuxy-widget}'",
     );
 }
 
@@ -109,19 +153,22 @@ pub mod control_events {
     pub const SESSIONS_CHANGED: &str = "%sessions-changed";
     pub const SESSION_WINDOW_CHANGED: &str = "%session-window-changed ";
     pub const PASTE_BUFFER_CHANGED: &str = "%paste-buffer-changed ";
+    pub const SUBSCRIPTION_CHANGED: &str = "%subscription-changed ";
     pub const PAUSE: &str = "%pause ";
     pub const CONTINUE: &str = "%continue ";
     pub const EXIT: &str = "%exit";
 }
 
-/// Rows of emulator-side scrollback kept per pane.
+/// Default, and minimum, rows of emulator-side scrollback kept per pane.
 ///
-/// This is NOT user-facing history (copy mode fetches that from tmux on
-/// demand). It exists so a pane that SHRINKS can push its top rows somewhere
+/// The floor exists so a pane that SHRINKS can push its top rows somewhere
 /// and pull them back when it GROWS again — which is what tmux does on
 /// reflow. With zero scrollback those rows are destroyed, and the pane renders
 /// permanently offset until a capture-pane refill or `clear`. A pane can never
 /// grow by more than one screen height, so a couple of hundred rows is ample.
+///
+/// Raising it (`MonitorConfig::scrollback_rows`) also lets clients read recent
+/// history straight from the emulator; full history still comes from tmux.
 pub const REFLOW_SCROLLBACK_ROWS: usize = 256;
 
 #[cfg(test)]
@@ -143,6 +190,10 @@ mod tests {
             tmux_options::FLOAT_DRAWER,
             tmux_options::FLOAT_BG,
             tmux_options::FLOAT_NOHEADER,
+            tmux_options::FLOAT_X,
+            tmux_options::FLOAT_Y,
+            tmux_options::FLOAT_Z,
+            tmux_options::FLOAT_HIDDEN,
             tmux_options::GROUP_PANES,
         ] {
             assert!(
@@ -152,4 +203,28 @@ mod tests {
             );
         }
     }
+
+    #[test]
+    fn list_panes_cmd_embeds_focus_follows_output_option() {
+        let option = tmux_options::FOCUS_FOLLOWS_OUTPUT;
+        assert!(
+            tmux_formats::LIST_PANES_CMD.contains(&format!("#{{{option}}}")),
+            "LIST_PANES_CMD is missing #{{{option}}}"
+        );
+    }
+
+    #[test]
+    fn list_panes_cmd_ends_with_answerback_sandbox_then_widget_option() {
+        let (answerback, sandbox, widget) = (
+            tmux_options::ANSWERBACK,
+            tmux_options::SANDBOX,
+            tmux_options::WIDGET,
+        );
+        assert!(
+            tmux_formats::LIST_PANES_CMD
+                .ends_with(&format!(",#{{{answerback}}},#{{{sandbox}}},#{{{widget}}}'")),
+            "LIST_PANES_CMD must end with #{{{answerback}}},#{{{sandbox}}},#{{{widget}}} \
+             — the parser reads them as the last three fields"
+        );
+    }
 }
@@ -0,0 +1,253 @@
+//! Container shells: panes running `docker exec -it <container> sh` (or the
+//! podman/kubectl equivalent), so containers sit in the layout alongside
+//! regular shells.
+//!
+//! [`list_containers`] asks every runtime on the host for its running
+//! containers; a runtime that isn't installed, or whose daemon is down,
+//! just contributes none. [`exec_command`] and [`exec_window_command`]
+//! build the tmux command that opens a shell in one of them.
+
+use serde::Serialize;
+use std::process::Command;
+use tracing::debug;
+
+use crate::control_mode::SplitDirection;
+use crate::executor::{self, tmux_quote};
+
+pub use tmuxy_protocol::{ContainerExec, ContainerRuntime};
+
+/// Arguments listing running containers as tab-separated
+/// id, name, image, status (and namespace first, for kubectl).
+fn list_args(runtime: ContainerRuntime) -> Vec<&'static str> {
+    match runtime {
+        ContainerRuntime::Docker | ContainerRuntime::Podman => vec![
+            "ps",
+            "--format",
+            "{{.ID}}\t{{.Names}}\t{{.Image}}\t{{.Status}}",
+        ],
+        // An unreachable cluster would otherwise hang the listing.
+        ContainerRuntime::Kubectl => vec![
+            "get",
+            "pods",
+            "--all-namespaces",
+            "--request-timeout=5s",
+            "--field-selector=status.phase=Running",
+            "-o",
+            concat!(
+                "jsonpath={range .items[*]}{.metadata.namespace}{\"\\t\"}",
+                "{.metadata.name}{\"\\t\"}{.spec.containers[0].image}{\"\\t\"}",
+                "{.status.phase}{\"\\n\"}{end}",
+            ),
+        ],
+    }
+}
+
+/// A running container, as [`list_containers`] reports it.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ContainerInfo {
+    pub runtime: ContainerRuntime,
+    /// What `exec` takes: the container id, or the pod name for kubectl
+    pub id: String,
+    pub name: String,
+    pub image: String,
+    pub status: String,
+    /// The pod's namespace (kubectl only)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub namespace: Option<String>,
+}
+
+/// Parse a runtime's listing (see `list_args`). Lines
+/// with the wrong number of fields are skipped.
+pub fn parse_containers(runtime: ContainerRuntime, output: &str) -> Vec<ContainerInfo> {
+    output
+        .lines()
+        .filter_map(|line| {
+            let fields: Vec<&str> = line.split('\t').collect();
+            match (runtime, fields.as_slice()) {
+                (ContainerRuntime::Kubectl, [namespace, name, image, status]) => {
+                    Some(ContainerInfo {
+                        runtime,
+                        id: name.to_string(),
+                        name: name.to_string(),
+                        image: image.to_string(),
+                        status: status.to_string(),
+                        namespace: Some(namespace.to_string()),
+                    })
+                }
+                (
+                    ContainerRuntime::Docker | ContainerRuntime::Podman,
+                    [id, name, image, status],
+                ) => Some(ContainerInfo {
+                    runtime,
+                    id: id.to_string(),
+                    name: name.to_string(),
+                    image: image.to_string(),
+                    status: status.to_string(),
+                    namespace: None,
+                }),
+                _ => None,
+            }
+        })
+        .collect()
+}
+
+/// Running containers across every runtime on this host. Blocking: it
+/// shells out to each runtime in turn.
+pub fn list_containers() -> Vec<ContainerInfo> {
+    ContainerRuntime::ALL
+        .into_iter()
+        .flat_map(|runtime| {
+            match Command::new(runtime.binary())
+                .args(list_args(runtime))
+                .output()
+            {
+                Ok(output) if output.status.success() => {
+                    parse_containers(runtime, &String::from_utf8_lossy(&output.stdout))
+                }
+                Ok(output) => {
+                    debug!(
+                        runtime = runtime.binary(),
+                        stderr = %String::from_utf8_lossy(&output.stderr).trim(),
+                        "container listing failed"
+                    );
+                    Vec::new()
+                }
+                Err(e) => {
+                    debug!(runtime = runtime.binary(), error = %e, "container runtime unavailable");
+                    Vec::new()
+                }
+            }
+        })
+        .collect()
+}
+
+/// The `exec` argv, e.g. `docker exec -it <container> sh`.
+pub fn exec_argv(exec: &ContainerExec) -> crate::TmuxResult<Vec<String>> {
+    // Anything leading with `-` would read as an option to the runtime.
+    for value in [Some(&exec.container), exec.namespace.as_ref()]
+        .into_iter()
+        .flatten()
+    {
+        if value.is_empty() || value.starts_with('-') {
+            return Err(crate::TmuxError::other(format!(
+                "invalid container '{value}'"
+            )));
+        }
+    }
+    let shell = exec.shell.as_deref().unwrap_or("sh");
+    let mut argv: Vec<String> = vec![exec.runtime.binary().into(), "exec".into(), "-it".into()];
+    let kubectl = exec.runtime == ContainerRuntime::Kubectl;
+    if let (true, Some(namespace)) = (kubectl, &exec.namespace) {
+        argv.extend(["-n".to_string(), namespace.clone()]);
+    }
+    argv.push(exec.container.clone());
+    if kubectl {
+        argv.push("--".to_string());
+    }
+    argv.extend(shell.split_whitespace().map(str::to_string));
+    Ok(argv)
+}
+
+/// `exec`'s argv as one shell command line.
+fn exec_program(exec: &ContainerExec) -> crate::TmuxResult<String> {
+    Ok(exec_argv(exec)?
+        .iter()
+        .map(|arg| tmux_quote(arg))
+        .collect::<Vec<_>>()
+        .join(" "))
+}
+
+/// The tmux command opening `exec` in a split next to `pane_id`.
+pub fn exec_command(pane_id: &str, exec: &ContainerExec) -> crate::TmuxResult<String> {
+    let program = exec_program(exec)?;
+    Ok(executor::split_with_command(
+        pane_id,
+        SplitDirection::Horizontal,
+        None,
+        Some(&program),
+        None,
+    ))
+}
+
+/// The tmux command opening `exec` in a tab of its own, built like any other
+/// new window (see [`executor::new_window_rewrite`]) from a split of
+/// `pane_id` and sized to `size` when the client reported one.
+pub fn exec_window_command(
+    pane_id: &str,
+    exec: &ContainerExec,
+    size: Option<(u32, u32)>,
+) -> crate::TmuxResult<String> {
+    let program = exec_program(exec)?;
+    Ok(executor::new_window_with_command(
+        pane_id,
+        size,
+        None,
+        Some(&program),
+        None,
+    ))
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used, clippy::expect_used)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_runtime_listings() {
+        let docker = "3f2a\tweb\tnginx:1.27\tUp 2 hours\nbroken line\n";
+        let containers = parse_containers(ContainerRuntime::Docker, docker);
+        assert_eq!(containers.len(), 1);
+        assert_eq!(containers[0].id, "3f2a");
+        assert_eq!(containers[0].name, "web");
+        assert_eq!(containers[0].namespace, None);
+
+        let pods = "default\tapi-7d9\tapi:2\tRunning\n";
+        let containers = parse_containers(ContainerRuntime::Kubectl, pods);
+        assert_eq!(containers[0].id, "api-7d9");
+        assert_eq!(containers[0].namespace.as_deref(), Some("default"));
+    }
+
+    #[test]
+    fn builds_exec_commands() {
+        let exec = ContainerExec {
+            runtime: ContainerRuntime::Docker,
+            container: "web".into(),
+            namespace: None,
+            shell: None,
+        };
+        assert_eq!(
+            exec_command("%1", &exec).unwrap(),
+            r"split-window -h -t '%1' ''\''docker'\'' '\''exec'\'' '\''-it'\'' '\''web'\'' '\''sh'\'''"
+        );
+        let tab = exec_window_command("%1", &exec, Some((120, 40))).unwrap();
+        assert!(tab.starts_with(r"splitw -t '%1' ''\''docker'\''"), "{tab}");
+        assert!(
+            tab.ends_with(
+                " ; breakp ; resizew -x 120 -y 40 ; set-option -w @tmuxy-window-type tab"
+            ),
+            "{tab}"
+        );
+
+        let pod = ContainerExec {
+            runtime: ContainerRuntime::Kubectl,
+            container: "api-7d9".into(),
+            namespace: Some("prod".into()),
+            shell: Some("bash -l".into()),
+        };
+        assert_eq!(
+            exec_argv(&pod).unwrap(),
+            ["kubectl", "exec", "-it", "-n", "prod", "api-7d9", "--", "bash", "-l"]
+        );
+    }
+
+    #[test]
+    fn refuses_option_like_containers() {
+        let exec = ContainerExec {
+            runtime: ContainerRuntime::Podman,
+            container: "--privileged".into(),
+            namespace: None,
+            shell: None,
+        };
+        assert!(exec_argv(&exec).is_err());
+    }
+}
@@ -0,0 +1,234 @@
+//! Answers to terminal queries tmux leaves unanswered.
+//!
+//! Applications ask their terminal questions by writing them to the pane:
+//! `ESC [ c` (DA), `ESC [ 6 n` (DSR), `ESC P + q … ESC \` (XTGETTCAP). The
+//! terminal they talk to is tmux, whose own emulator already answers DA, DSR,
+//! XTVERSION and the OSC colour queries before the bytes reach us as
+//! `%output` — answering those again would hand the application a second
+//! reply that it reads as typed input.
+//!
+//! XTGETTCAP is the exception: tmux drops it, so editors that probe
+//! capabilities that way stall until their timeout on every start. The kitty
+//! graphics query (`ESC _ G a=q … ESC \`) goes the same way. tmuxy renders
+//! the pane itself, so it answers for the capabilities it actually draws —
+//! truecolor, styled underlines, the image formats `images` decodes —
+//! writing the reply into the pane with `send-keys -H`. A pane opts out with
+//! `@tmuxy-answerback off`.
+//!
+//! Sixel support is only discoverable through DA, which stays tmux's to
+//! answer.
+
+/// An unterminated DCS or APC longer than this is abandoned — no query comes
+/// close (an image transfer does, and is not one), and it bounds what a stray
+/// `ESC P` can buffer.
+const MAX_STRING_LEN: usize = 1024;
+
+/// Capabilities answered, by terminfo name. `None` is a boolean capability.
+const CAPABILITIES: &[(&str, Option<&str>)] = &[
+    ("Co", Some("256")),
+    ("colors", Some("256")),
+    // Truecolor, under both spellings applications look for.
+    ("RGB", None),
+    ("Tc", None),
+    ("setrgbf", Some("\x1b[38;2;%p1%d;%p2%d;%p3%dm")),
+    ("setrgbb", Some("\x1b[48;2;%p1%d;%p2%d;%p3%dm")),
+    // Cursor shape (DECSCUSR), reported as the pane's cursor_shape.
+    ("Ss", Some("\x1b[%p1%d q")),
+    ("Se", Some("\x1b[2 q")),
+    // Styled and coloured underlines.
+    ("Smulx", Some("\x1b[4:%p1%dm")),
+    (
+        "Setulc",
+        Some("\x1b[58:2::%p1%{65536}%/%d:%p1%{256}%/%{255}%&%d:%p1%{255}%&%d%;m"),
+    ),
+    // OSC 52 clipboard writes.
+    ("Ms", Some("\x1b]52;%p1%s;%p2%s\x07")),
+];
+
+/// Finds XTGETTCAP and kitty graphics queries in a pane's output, across
+/// chunk boundaries.
+#[derive(Debug, Default)]
+pub(crate) struct QueryScanner {
+    /// The introducer (`P` for DCS, `_` for APC) and the bytes after it of an
+    /// unterminated control string; `None` outside one.
+    string: Option<(u8, Vec<u8>)>,
+    /// The previous byte was an ESC.
+    after_esc: bool,
+}
+
+impl QueryScanner {
+    /// Scan the next chunk of output; returns the bytes to write back to the
+    /// pane, empty when it asked nothing we answer.
+    pub(crate) fn feed(&mut self, bytes: &[u8]) -> Vec<u8> {
+        let mut reply = Vec::new();
+        for &b in bytes {
+            let after_esc = std::mem::replace(&mut self.after_esc, b == 0x1b);
+            if b == 0x1b {
+                continue;
+            }
+            if after_esc {
+                // ST closes the string; any other escape abandons it, and
+                // `ESC P` or `ESC _` opens a new one.
+                match (b, self.string.take()) {
+                    (b'\\', Some((b'P', body))) => reply.extend(answer_dcs(&body)),
+                    (b'\\', Some((_, body))) => reply.extend(answer_apc(&body)),
+                    (b'P' | b'_', _) => self.string = Some((b, Vec::new())),
+                    _ => {}
+                }
+                continue;
+            }
+            if let Some((_, body)) = &mut self.string {
+                if body.len() < MAX_STRING_LEN {
+                    body.push(b);
+                } else {
+                    self.string = None;
+                }
+            }
+        }
+        reply
+    }
+}
+
+/// Reply to one DCS body (between `ESC P` and `ESC \`): one `DCS 1 + r`
+/// per known capability and `DCS 0 + r` per unknown one, each echoing the
+/// hex-encoded name as it was asked. Anything but XTGETTCAP gets nothing.
+fn answer_dcs(body: &[u8]) -> Vec<u8> {
+    let Some(names) = body.strip_prefix(b"+q") else {
+        return Vec::new();
+    };
+    let mut reply = Vec::new();
+    for hex_name in names.split(|&b| b == b';').filter(|n| !n.is_empty()) {
+        let known = decode_hex(hex_name)
+            .and_then(|name| CAPABILITIES.iter().find(|(cap, _)| cap.as_bytes() == name));
+        reply.extend_from_slice(if known.is_some() {
+            b"\x1bP1+r"
+        } else {
+            b"\x1bP0+r"
+        });
+        reply.extend_from_slice(hex_name);
+        if let Some((_, Some(value))) = known {
+            reply.push(b'=');
+            reply.extend(encode_hex(value.as_bytes()).bytes());
+        }
+        reply.extend_from_slice(b"\x1b\\");
+    }
+    reply
+}
+
+/// Reply to one APC body (between `ESC _` and `ESC \\`). Only a kitty
+/// graphics query (`a=q`) is answered: `OK` when `images` can decode what it
+/// describes, an error otherwise. As in kitty, a query without an image id
+/// gets nothing, and `q=1` / `q=2` silence the `OK` / every reply.
+fn answer_apc(body: &[u8]) -> Vec<u8> {
+    let Some(body) = body.strip_prefix(b"G") else {
+        return Vec::new();
+    };
+    let keys = body.split(|&b| b == b';').next().unwrap_or_default();
+    let key = |name: &[u8]| {
+        keys.split(|&b| b == b',')
+            .find_map(|kv| kv.strip_prefix(name)?.strip_prefix(b"="))
+    };
+    if key(b"a") != Some(b"q") {
+        return Vec::new();
+    }
+    let ids: Vec<String> = [b"i", b"I"]
+        .into_iter()
+        .filter_map(|name| {
+            let id = key(name).and_then(|v| std::str::from_utf8(v).ok())?;
+            Some(format!("{}={id}", char::from(name[0])))
+        })
+        .collect();
+    let quiet = key(b"q").unwrap_or(b"0");
+    if ids.is_empty() || quiet == b"2" {
+        return Vec::new();
+    }
+    let status = if !matches!(key(b"t"), None | Some(b"d")) {
+        "EINVAL:unsupported transmission medium"
+    } else if !matches!(key(b"f"), None | Some(b"24" | b"32" | b"100")) {
+        "EINVAL:unsupported format"
+    } else if quiet == b"1" {
+        return Vec::new();
+    } else {
+        "OK"
+    };
+    format!("\x1b_G{};{status}\x1b\\", ids.join(",")).into_bytes()
+}
+
+fn decode_hex(hex: &[u8]) -> Option<Vec<u8>> {
+    if !hex.len().is_multiple_of(2) {
+        return None;
+    }
+    hex.chunks(2)
+        .map(|pair| u8::from_str_radix(std::str::from_utf8(pair).ok()?, 16).ok())
+        .collect()
+}
+
+fn encode_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{b:02X}")).collect()
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used, clippy::expect_used)]
+mod tests {
+    use super::*;
+
+    fn query(names: &[&str]) -> Vec<u8> {
+        let hex: Vec<String> = names.iter().map(|n| encode_hex(n.as_bytes())).collect();
+        format!("\x1bP+q{}\x1b\\", hex.join(";")).into_bytes()
+    }
+
+    #[test]
+    fn known_and_unknown_capabilities_are_answered_separately() {
+        let mut scanner = QueryScanner::default();
+        let reply = scanner.feed(&query(&["Tc", "Ss", "kbs"]));
+        let expected = format!(
+            "\x1bP1+r{tc}\x1b\\\x1bP1+r{ss}={value}\x1b\\\x1bP0+r{kbs}\x1b\\",
+            tc = encode_hex(b"Tc"),
+            ss = encode_hex(b"Ss"),
+            value = encode_hex(b"\x1b[%p1%d q"),
+            kbs = encode_hex(b"kbs"),
+        );
+        assert_eq!(String::from_utf8(reply).unwrap(), expected);
+    }
+
+    #[test]
+    fn query_split_across_chunks_is_answered_once_complete() {
+        let mut scanner = QueryScanner::default();
+        let bytes = query(&["RGB"]);
+        let (head, tail) = bytes.split_at(bytes.len() - 1);
+        assert!(scanner.feed(head).is_empty());
+        assert!(scanner.feed(tail).starts_with(b"\x1bP1+r"));
+    }
+
+    #[test]
+    fn queries_tmux_answers_and_other_dcs_are_left_alone() {
+        let mut scanner = QueryScanner::default();
+        assert!(scanner.feed(b"\x1b[c\x1b[6n\x1b[>q").is_empty());
+        // Sixel and tmux passthrough are DCS too.
+        assert!(scanner.feed(b"\x1bPq#0;2;0;0;0~\x1b\\").is_empty());
+        assert!(scanner.feed(b"\x1bPtmux;\x1b\x1b]0;x\x07\x1b\\").is_empty());
+        // An escape inside the DCS abandons it rather than terminating it.
+        assert!(scanner.feed(b"\x1bP+q\x1b[0m5463\x1b\\").is_empty());
+    }
+
+    #[test]
+    fn kitty_graphics_queries_are_answered_for_decodable_images() {
+        let mut scanner = QueryScanner::default();
+        let reply = |scanner: &mut QueryScanner, keys: &str| {
+            String::from_utf8(scanner.feed(format!("\x1b_G{keys};AAAA\x1b\\").as_bytes())).unwrap()
+        };
+        assert_eq!(
+            reply(&mut scanner, "i=31,s=1,v=1,a=q,t=d,f=24"),
+            "\x1b_Gi=31;OK\x1b\\"
+        );
+        assert_eq!(
+            reply(&mut scanner, "a=q,t=f,i=7"),
+            "\x1b_Gi=7;EINVAL:unsupported transmission medium\x1b\\"
+        );
+        // No id, a silenced reply, or a transfer rather than a query.
+        assert_eq!(reply(&mut scanner, "a=q,t=d,f=100"), "");
+        assert_eq!(reply(&mut scanner, "a=q,i=1,q=1"), "");
+        assert_eq!(reply(&mut scanner, "a=q,i=1,t=s,q=2"), "");
+        assert_eq!(reply(&mut scanner, "a=T,i=1,f=100"), "");
+    }
+}
@@ -0,0 +1,437 @@
+//! The commands run in a pane, from shell integration marks (OSC 133).
+//!
+//! A shell with semantic prompt integration marks where each prompt starts
+//! (`A`), where the command line starts (`B`), where the command's output
+//! starts (`C`) and where the command finished, with its exit status (`D`).
+//! `PaneState` resolves each mark to a position with the emulator and feeds
+//! it to the pane's [`CommandHistory`], which keeps the most recent
+//! [`MAX_COMMANDS`] commands: their text, exit status, duration and the rows
+//! they and their output cover. That is what "jump to previous prompt" and
+//! "copy last command output" work from (`get_command_history`).
+//!
+//! The commands on screen also go out with every pane as
+//! [`CommandBlock`]s, so the frontend can colour each one's gutter by its
+//! exit status.
+//!
+//! Positions are kept as absolute rows (rows scrolled off the top of the
+//! main screen plus the screen row) and reported as capture-pane line
+//! numbers. A resize reflows the rows under them, so they can be off after
+//! one.
+
+use super::osc::SemanticMark;
+use crate::PaneContent;
+use std::collections::VecDeque;
+use tmuxy_protocol::CommandBlock;
+
+#[cfg(not(target_arch = "wasm32"))]
+use std::time::Instant;
+#[cfg(target_arch = "wasm32")]
+use web_time::Instant;
+
+/// Commands kept per pane; the oldest is dropped past this.
+pub const MAX_COMMANDS: usize = 500;
+
+/// A command run in a pane, positioned in capture-pane line numbers
+/// (negative in history, 0 for the first visible row).
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CommandRecord {
+    /// Increases with every command the pane runs.
+    pub id: u64,
+    /// The command line as the shell sent it, or as it showed on screen.
+    pub command: String,
+    /// Line the command's prompt starts on.
+    pub prompt_line: i64,
+    /// First line of the command's output.
+    pub output_start: i64,
+    /// Last line of the command's output (inclusive); `None` while it runs
+    /// or when it printed nothing.
+    pub output_end: Option<i64>,
+    /// `None` while it runs, or when the shell didn't say.
+    pub exit_status: Option<i32>,
+    /// From output start to finish; `None` while it runs.
+    pub duration_ms: Option<u64>,
+    pub running: bool,
+}
+
+#[derive(Debug)]
+struct Entry {
+    id: u64,
+    prompt_line: u64,
+    /// Where the command line starts, from `B`.
+    input: Option<(u64, u16)>,
+    /// Set by `C`; an entry without one never ran a command.
+    command: Option<String>,
+    output_start: u64,
+    output_end: Option<u64>,
+    exit_status: Option<i32>,
+    finished: bool,
+    started_at: Option<Instant>,
+    finished_at: Option<Instant>,
+}
+
+/// A pane's recent commands, oldest first.
+#[derive(Debug, Default)]
+pub(crate) struct CommandHistory {
+    entries: VecDeque<Entry>,
+    next_id: u64,
+    /// Some entry started or finished since the last `stamp`.
+    unstamped: bool,
+}
+
+impl CommandHistory {
+    /// The entry marks are still arriving for.
+    fn open(&mut self) -> Option<&mut Entry> {
+        self.entries.back_mut().filter(|entry| !entry.finished)
+    }
+
+    /// Where the open entry's command line starts, for reading it off the
+    /// screen when `C` doesn't carry it.
+    pub(crate) fn input_start(&self) -> Option<(u64, u16)> {
+        self.entries
+            .back()
+            .filter(|entry| !entry.finished && entry.command.is_none())
+            .and_then(|entry| entry.input)
+    }
+
+    /// Apply `mark`, which arrived with the cursor at absolute row `line`,
+    /// column `col`.
+    pub(crate) fn apply(&mut self, mark: SemanticMark, line: u64, col: u16) {
+        match mark {
+            SemanticMark::PromptStart => {
+                self.close(line.checked_sub(1));
+                self.push(line);
+            }
+            SemanticMark::CommandStart => {
+                if self.open().is_none_or(|entry| entry.command.is_some()) {
+                    self.close(line.checked_sub(1));
+                    self.push(line);
+                }
+                if let Some(entry) = self.open() {
+                    entry.input = Some((line, col));
+                }
+            }
+            SemanticMark::OutputStart { command } => {
+                if self.open().is_none_or(|entry| entry.command.is_some()) {
+                    self.close(line.checked_sub(1));
+                    self.push(line);
+                }
+                if let Some(entry) = self.open() {
+                    entry.command = Some(command.unwrap_or_default().trim().to_string());
+                    entry.output_start = line;
+                    self.unstamped = true;
+                }
+            }
+            SemanticMark::CommandEnd { exit_status } => {
+                let last = if col == 0 {
+                    line.checked_sub(1)
+                } else {
+                    Some(line)
+                };
+                if let Some(entry) = self.open() {
+                    entry.exit_status = exit_status;
+                }
+                self.close(last);
+            }
+        }
+    }
+
+    fn push(&mut self, line: u64) {
+        if self.entries.len() == MAX_COMMANDS {
+            self.entries.pop_front();
+        }
+        self.next_id += 1;
+        self.entries.push_back(Entry {
+            id: self.next_id,
+            prompt_line: line,
+            input: None,
+            command: None,
+            output_start: line,
+            output_end: None,
+            exit_status: None,
+            finished: false,
+            started_at: None,
+            finished_at: None,
+        });
+    }
+
+    /// Finish the open entry, its output ending on row `last`. One that
+    /// never ran a command (an empty line, Ctrl-C at the prompt) is dropped.
+    fn close(&mut self, last: Option<u64>) {
+        let Some(entry) = self.open() else {
+            return;
+        };
+        if entry.command.is_none() {
+            self.entries.pop_back();
+            return;
+        }
+        entry.output_end = last.filter(|last| *last >= entry.output_start);
+        entry.finished = true;
+        self.unstamped = true;
+    }
+
+    /// Time the commands that started or finished since the last call.
+    pub(crate) fn stamp(&mut self, now: Instant) {
+        if !std::mem::take(&mut self.unstamped) {
+            return;
+        }
+        for entry in self.entries.iter_mut().rev() {
+            if entry.command.is_some() && entry.started_at.is_none() {
+                entry.started_at = Some(now);
+            }
+            if entry.finished && entry.finished_at.is_none() {
+                entry.finished_at = Some(now);
+            }
+        }
+    }
+
+    /// The commands run, oldest first, with `scrolled_rows` rows now
+    /// scrolled off the top of the screen.
+    pub(crate) fn list(&self, scrolled_rows: u64) -> Vec<CommandRecord> {
+        let to_line = |row: u64| row as i64 - scrolled_rows as i64;
+        self.entries
+            .iter()
+            .filter_map(|entry| {
+                Some(CommandRecord {
+                    id: entry.id,
+                    command: entry.command.clone()?,
+                    prompt_line: to_line(entry.prompt_line),
+                    output_start: to_line(entry.output_start),
+                    output_end: entry.output_end.map(to_line),
+                    exit_status: entry.exit_status,
+                    duration_ms: entry.started_at.zip(entry.finished_at).map(|(start, end)| {
+                        end.saturating_duration_since(start).as_millis() as u64
+                    }),
+                    running: !entry.finished,
+                })
+            })
+            .collect()
+    }
+}
+
+impl CommandHistory {
+    /// The commands whose rows show on a `height`-row screen, oldest first.
+    /// `top_row` is the absolute row at the top of the screen and
+    /// `cursor_row` the absolute row of the cursor, where a running
+    /// command's block ends.
+    pub(crate) fn blocks(&self, top_row: i64, height: u32, cursor_row: i64) -> Vec<CommandBlock> {
+        let mut blocks: Vec<CommandBlock> = self
+            .entries
+            .iter()
+            .rev()
+            .filter(|entry| entry.command.is_some())
+            .map(|entry| {
+                let end = match entry.output_end {
+                    Some(end) => end as i64,
+                    None if !entry.finished => cursor_row,
+                    None => entry.output_start as i64 - 1,
+                };
+                CommandBlock {
+                    id: entry.id,
+                    start_row: (entry.prompt_line as i64 - top_row) as i32,
+                    end_row: (end.max(entry.prompt_line as i64) - top_row) as i32,
+                    exit_status: entry.exit_status,
+                    running: !entry.finished,
+                }
+            })
+            .take_while(|block| block.end_row >= 0)
+            .filter(|block| block.start_row < height as i32)
+            .collect();
+        blocks.reverse();
+        blocks
+    }
+}
+
+/// The text of `content` from `start` up to `end`, both `(row, col)`: a
+/// command line the shell echoed. Rows filled to the last column are taken
+/// as wrapped onto the next rather than ended with a newline.
+pub(crate) fn screen_text(
+    content: &PaneContent,
+    start: (usize, usize),
+    end: (usize, usize),
+) -> String {
+    let mut text = String::new();
+    for row in start.0..=end.0 {
+        let Some(line) = content.get(row) else {
+            break;
+        };
+        let from = if row == start.0 { start.1 } else { 0 };
+        let to = if row == end.0 { end.1 } else { line.len() };
+        let cells = line.get(from..to.min(line.len())).unwrap_or_default();
+        let row_text: String = cells.iter().map(|cell| cell.char.as_str()).collect();
+        text.push_str(row_text.trim_end());
+        let wrapped =
+            to >= line.len() && line.last().is_some_and(|cell| !cell.char.trim().is_empty());
+        if row != end.0 && !wrapped {
+            text.push('\n');
+        }
+    }
+    text.trim().to_string()
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used, clippy::expect_used)]
+mod tests {
+    use super::*;
+    use crate::TerminalCell;
+
+    fn apply(history: &mut CommandHistory, marks: &[(SemanticMark, u64, u16)]) {
+        for (mark, line, col) in marks {
+            history.apply(mark.clone(), *line, *col);
+        }
+    }
+
+    fn output_start(command: &str) -> SemanticMark {
+        SemanticMark::OutputStart {
+            command: Some(command.to_string()),
+        }
+    }
+
+    #[test]
+    fn a_command_runs_from_its_prompt_to_its_end_mark() {
+        let mut history = CommandHistory::default();
+        let now = Instant::now();
+        apply(
+            &mut history,
+            &[
+                (SemanticMark::PromptStart, 10, 0),
+                (SemanticMark::CommandStart, 10, 2),
+                (output_start(" make "), 11, 0),
+            ],
+        );
+        history.stamp(now);
+        let running = history.list(5);
+        assert_eq!(running.len(), 1);
+        assert!(running[0].running);
+        assert_eq!(
+            (
+                running[0].prompt_line,
+                running[0].output_start,
+                running[0].output_end
+            ),
+            (5, 6, None)
+        );
+
+        apply(
+            &mut history,
+            &[(
+                SemanticMark::CommandEnd {
+                    exit_status: Some(1),
+                },
+                14,
+                0,
+            )],
+        );
+        history.stamp(now + std::time::Duration::from_millis(1500));
+        let done = &history.list(20)[0];
+        assert_eq!(done.command, "make");
+        assert_eq!((done.output_start, done.output_end), (-9, Some(-7)));
+        assert_eq!((done.exit_status, done.duration_ms), (Some(1), Some(1500)));
+        assert!(!done.running);
+    }
+
+    #[test]
+    fn prompts_that_never_ran_a_command_are_dropped() {
+        let mut history = CommandHistory::default();
+        apply(
+            &mut history,
+            &[
+                (SemanticMark::PromptStart, 0, 0),
+                (SemanticMark::CommandStart, 0, 2),
+                (SemanticMark::CommandEnd { exit_status: None }, 1, 0),
+                (SemanticMark::PromptStart, 1, 0),
+                (output_start("true"), 2, 0),
+                // No `D`: the next prompt ends it, without an exit status.
+                (SemanticMark::PromptStart, 2, 0),
+                (output_start("ls"), 3, 0),
+                (
+                    SemanticMark::CommandEnd {
+                        exit_status: Some(0),
+                    },
+                    5,
+                    3,
+                ),
+            ],
+        );
+        let commands = history.list(0);
+        let found: Vec<_> = commands
+            .iter()
+            .map(|c| (c.id, &*c.command, c.output_end, c.exit_status))
+            .collect();
+        assert_eq!(
+            found,
+            [(2, "true", None, None), (3, "ls", Some(5), Some(0))]
+        );
+        assert_eq!(history.input_start(), None);
+    }
+
+    #[test]
+    fn history_is_bounded() {
+        let mut history = CommandHistory::default();
+        for line in 0..MAX_COMMANDS as u64 + 5 {
+            history.apply(output_start("x"), line, 0);
+        }
+        let commands = history.list(0);
+        assert_eq!(commands.len(), MAX_COMMANDS);
+        assert_eq!(commands[0].id, 6);
+    }
+
+    #[test]
+    fn blocks_cover_the_commands_on_screen() {
+        let mut history = CommandHistory::default();
+        apply(
+            &mut history,
+            &[
+                (SemanticMark::PromptStart, 0, 0),
+                (output_start("make"), 1, 0),
+                (
+                    SemanticMark::CommandEnd {
+                        exit_status: Some(2),
+                    },
+                    8,
+                    0,
+                ),
+                (SemanticMark::PromptStart, 8, 0),
+                (output_start("true"), 9, 0),
+                (
+                    SemanticMark::CommandEnd {
+                        exit_status: Some(0),
+                    },
+                    9,
+                    0,
+                ),
+                (SemanticMark::PromptStart, 9, 0),
+                (output_start("sleep 5"), 10, 0),
+            ],
+        );
+        let found: Vec<_> = history
+            .blocks(5, 10, 10)
+            .into_iter()
+            .map(|b| (b.id, b.start_row, b.end_row, b.exit_status, b.running))
+            .collect();
+        assert_eq!(
+            found,
+            [
+                (1, -5, 2, Some(2), false),
+                (2, 3, 3, Some(0), false),
+                (3, 4, 5, None, true),
+            ]
+        );
+        // Scrolled past the first command, and a screen above the last.
+        assert_eq!(history.blocks(8, 10, 10).len(), 2);
+        assert!(history.blocks(-20, 10, 10).is_empty());
+    }
+
+    #[test]
+    fn echoed_command_lines_are_read_off_the_screen() {
+        let row = |text: &str| -> Vec<TerminalCell> {
+            format!("{text:<8}")
+                .chars()
+                .map(|c| TerminalCell::new(c.to_string()))
+                .collect()
+        };
+        let content = vec![row("$ echo a"), row("bc"), row("> d"), row("")];
+        assert_eq!(screen_text(&content, (0, 2), (3, 0)), "echo abc\n> d");
+        assert_eq!(screen_text(&content, (0, 2), (0, 6)), "echo");
+    }
+}
@@ -5,6 +5,7 @@
 use super::log::{LogKind, LogSink};
 use super::parser::{ControlModeEvent, Parser};
 use crate::error::TmuxError;
+use crate::session::TmuxTransport;
 use std::sync::Arc;
 use std::time::Duration;
 use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
@@ -87,19 +88,25 @@ pub struct ControlModeConnection {
 /// Build the argv passed to `tmux` plus a human-readable description that's
 /// safe to log (one socket flag pair only — `tmux_bin()` already includes
 /// the socket, so omit it from the args for the log line). `tmux_args`
-/// carries the actual argv used by `spawn`.
-fn build_tmux_args(session_name: &str, create_if_missing: bool) -> (Vec<String>, String) {
+/// carries the actual argv used by `spawn`. `creating` says a local session
+/// is missing, so the `new-session -A` will make it rather than attach.
+fn build_tmux_args(
+    transport: &TmuxTransport,
+    session_name: &str,
+    create_if_missing: bool,
+    creating: bool,
+) -> (Vec<String>, String) {
     // Full argv including the program token: the local tmux path, or
     // `ssh -tt <dest> tmux` when tunneled to a remote host. `-tt` is required
     // for `-CC` control mode's remote pty.
-    let mut tmux_args: Vec<String> = crate::session::tmux_argv(true);
+    let mut tmux_args: Vec<String> = transport.argv(true);
     // Apply the user's tmuxy config at server-startup time. tmux only reads
     // `-f` when it forks a new server, so this only affects the create path;
     // the monitor's `sync_initial_state()` source-files the same config after
     // attach to cover the attach path. Skipped over SSH — the local config
     // path doesn't exist on the remote host (the remote server uses its own
     // tmux config, and sync_initial_state's remote source-file is a no-op).
-    if crate::session::ssh_target().is_none() {
+    if !transport.is_remote() {
         if let Some(path) = crate::session::get_config_path() {
             tmux_args.push("-f".to_string());
             tmux_args.push(path.to_string_lossy().into_owned());
@@ -116,6 +123,26 @@ fn build_tmux_args(session_name: &str, create_if_missing: bool) -> (Vec<String>,
             "-s".to_string(),
             session_name.to_string(),
         ]);
+        // Runs in the first pane when the session is created; ignored when
+        // -A attaches. A session this creates also gets it as its
+        // `default-command`, so later panes follow it; one it attaches to
+        // keeps its own. Not over SSH, where the remote shell would re-split
+        // the command line.
+        if !transport.is_remote() {
+            if let Some(command) = crate::session::default_command(session_name) {
+                tmux_args.push(command.clone());
+                if creating {
+                    tmux_args.extend([
+                        ";".to_string(),
+                        "set-option".to_string(),
+                        "-t".to_string(),
+                        session_name.to_string(),
+                        "default-command".to_string(),
+                        command,
+                    ]);
+                }
+            }
+        }
     } else {
         tmux_args.extend([
             "-CC".to_string(),
@@ -197,7 +224,7 @@ fn spawn_parser_task(
                         }
                     }
 
-                    if let Some(event) = parser.parse_line(&line) {
+                    if let Some(event) = parser.parse_bytes(&buf) {
                         // Signal readiness on first parsed event
                         if let Some(rtx) = ready_tx.take() {
                             let _ = rtx.send(());
@@ -235,6 +262,7 @@ impl ControlModeConnection {
     /// `log` receives streaming entries for each tmux invocation and its
     /// output.
     pub async fn connect(
+        transport: &TmuxTransport,
         session_name: &str,
         working_dir: Option<&std::path::Path>,
         log: Option<&Arc<dyn LogSink>>,
@@ -247,8 +275,11 @@ impl ControlModeConnection {
         // create-on-attach behavior. Skipped when `create_if_missing` so
         // `tmux -CC new-session -A` can handle the dual semantics atomically.
         if !create_if_missing {
-            Self::preflight_session(session_name, log)?;
+            Self::preflight_session(transport, session_name, log)?;
         }
+        let creating = create_if_missing
+            && !transport.is_remote()
+            && !Self::session_exists(transport, session_name);
 
         // Allocate the PTY pair we'll feed to tmux. `INITIAL_PTY_ROWS/COLS`
         // are intentionally larger than typical terminal viewports so vt100
@@ -260,7 +291,8 @@ impl ControlModeConnection {
         // logs. Description matters because the .app launched from Finder
         // gets a different `PATH` than the same binary in a terminal, so
         // operators need to see exactly what we spawned.
-        let (tmux_args, shell_desc) = build_tmux_args(session_name, create_if_missing);
+        let (tmux_args, shell_desc) =
+            build_tmux_args(transport, session_name, create_if_missing, creating);
         crate::debug_log::log(&format!("connect(): pty spawn: {}", shell_desc));
         log_to(log, LogKind::Command, shell_desc.clone());
 
@@ -296,7 +328,17 @@ impl ControlModeConnection {
     /// Returns `Err(SessionNotFound)` enriched with the running session list
     /// for diagnostics — operators almost always need to see "what *do* you
     /// have" when this fires.
+    /// Whether `session_name` exists; a server that isn't running has none.
+    fn session_exists(transport: &TmuxTransport, session_name: &str) -> bool {
+        transport
+            .command()
+            .args(["has-session", "-t", session_name])
+            .output()
+            .is_ok_and(|o| o.status.success())
+    }
+
     fn preflight_session(
+        transport: &TmuxTransport,
         session_name: &str,
         log: Option<&Arc<dyn LogSink>>,
     ) -> Result<(), TmuxError> {
@@ -304,7 +346,8 @@ impl ControlModeConnection {
         crate::debug_log::log(&format!("connect(): checking session '{}'", session_name));
         let has_session_cmd = format!("{} has-session -t {}", tmux_path, session_name);
         log_to(log, LogKind::Command, has_session_cmd.clone());
-        let check = crate::session::tmux_command()
+        let check = transport
+            .command()
             .args(["has-session", "-t", session_name])
             .output()
             .map_err(|e| {
@@ -324,7 +367,8 @@ impl ControlModeConnection {
         let stderr = String::from_utf8_lossy(&check.stderr);
         let list_cmd = format!("{} list-sessions -F '#{{session_name}}'", tmux_path);
         log_to(log, LogKind::Command, list_cmd);
-        let list_output = crate::session::tmux_command()
+        let list_output = transport
+            .command()
             .args(["list-sessions", "-F", "#{session_name}"])
             .output();
         let sessions = match &list_output {
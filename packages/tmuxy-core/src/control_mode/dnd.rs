@@ -0,0 +1,107 @@
+//! Session-level do-not-disturb.
+//!
+//! While do-not-disturb is on, the aggregator drops everything that would
+//! interrupt the user: bells, focus-follows-output raises, and the monitor's
+//! stale-pane suggestions. Pane output itself is unaffected.
+//!
+//! Each session carries a [`DndMode`] in its `@tmuxy-dnd` option: `on` and
+//! `off` pin the state, `auto` (the option unset) follows the configured
+//! [`DndSchedule`], if any. The schedule is evaluated against local wall-clock
+//! time by the monitor; everything here takes the minute of the day as input
+//! so it stays sans-IO.
+
+pub use tmuxy_protocol::DndMode;
+
+/// Whether do-not-disturb is in effect under `mode` at `minute_of_day`
+/// (minutes since local midnight).
+pub fn is_active(mode: DndMode, schedule: Option<&DndSchedule>, minute_of_day: u16) -> bool {
+    match mode {
+        DndMode::On => true,
+        DndMode::Off => false,
+        DndMode::Auto => schedule.is_some_and(|s| s.contains(minute_of_day)),
+    }
+}
+
+/// Daily quiet hours, e.g. `22:00-07:00`. The start is inclusive and the end
+/// exclusive; a start after the end spans midnight.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DndSchedule {
+    start: u16,
+    end: u16,
+}
+
+impl DndSchedule {
+    /// Parse `HH:MM-HH:MM`. Equal start and end is rejected: it is ambiguous
+    /// between "never" and "all day", and `DndMode::On` covers the latter.
+    pub fn parse(s: &str) -> Option<Self> {
+        let (start, end) = s.split_once('-')?;
+        let (start, end) = (parse_clock(start.trim())?, parse_clock(end.trim())?);
+        (start != end).then_some(Self { start, end })
+    }
+
+    /// Whether `minute_of_day` falls inside the quiet hours.
+    pub fn contains(&self, minute_of_day: u16) -> bool {
+        if self.start < self.end {
+            (self.start..self.end).contains(&minute_of_day)
+        } else {
+            minute_of_day >= self.start || minute_of_day < self.end
+        }
+    }
+}
+
+/// `HH:MM` as minutes since midnight.
+fn parse_clock(s: &str) -> Option<u16> {
+    let (h, m) = s.split_once(':')?;
+    let (h, m): (u16, u16) = (h.parse().ok()?, m.parse().ok()?);
+    (h < 24 && m < 60).then_some(h * 60 + m)
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used, clippy::expect_used)]
+mod tests {
+    use super::*;
+
+    fn at(h: u16, m: u16) -> u16 {
+        h * 60 + m
+    }
+
+    #[test]
+    fn overnight_schedule_spans_midnight() {
+        let night = DndSchedule::parse("22:00-07:00").unwrap();
+        assert!(night.contains(at(22, 0)));
+        assert!(night.contains(at(3, 30)));
+        assert!(!night.contains(at(7, 0)));
+        assert!(!night.contains(at(12, 0)));
+
+        let lunch = DndSchedule::parse("12:00 - 13:30").unwrap();
+        assert!(lunch.contains(at(13, 29)));
+        assert!(!lunch.contains(at(13, 30)));
+        assert!(!lunch.contains(at(11, 59)));
+    }
+
+    #[test]
+    fn malformed_schedules_are_rejected() {
+        for bad in [
+            "",
+            "22:00",
+            "24:00-07:00",
+            "22:60-07:00",
+            "9-17",
+            "08:00-08:00",
+        ] {
+            assert_eq!(DndSchedule::parse(bad), None, "{bad:?}");
+        }
+    }
+
+    #[test]
+    fn mode_pins_or_follows_the_schedule() {
+        let night = DndSchedule::parse("22:00-07:00").unwrap();
+        assert!(is_active(DndMode::Auto, Some(&night), at(23, 0)));
+        assert!(!is_active(DndMode::Auto, Some(&night), at(9, 0)));
+        assert!(!is_active(DndMode::Auto, None, at(23, 0)));
+        assert!(is_active(DndMode::On, None, at(9, 0)));
+        assert!(!is_active(DndMode::Off, Some(&night), at(23, 0)));
+        assert_eq!(DndMode::parse(""), Some(DndMode::Auto));
+        assert_eq!(DndMode::parse("loud"), None);
+    }
+}
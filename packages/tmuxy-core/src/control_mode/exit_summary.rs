@@ -0,0 +1,122 @@
+//! What a pane showed when it went away.
+//!
+//! When a pane's process exits, tmux closes the pane and the aggregator drops
+//! it, and with it the only copy of the error the process printed on its way
+//! out. Every pane that leaves the session now leaves behind an
+//! [`ExitSummary`]: its final screen and the end of its scrollback as plain
+//! text, kept for the most recent [`MAX_EXIT_SUMMARIES`] panes.
+//!
+//! A pane that only moved (break-pane, join-pane) leaves its window's layout
+//! before it shows up in the next one. tmux never reuses a pane id, so a
+//! summary whose pane is back in the session is dropped again.
+
+use super::state::PaneState;
+use crate::PaneContent;
+use std::collections::VecDeque;
+
+#[cfg(not(target_arch = "wasm32"))]
+use std::time::Instant;
+#[cfg(target_arch = "wasm32")]
+use web_time::Instant;
+
+/// Summaries kept; the oldest is dropped past this.
+pub const MAX_EXIT_SUMMARIES: usize = 20;
+
+/// Rows of history kept above the final screen.
+pub const EXIT_SUMMARY_SCROLLBACK: usize = 100;
+
+/// A pane's last words.
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ExitSummary {
+    pub pane_id: String,
+    pub window_id: String,
+    pub window_name: String,
+    /// The pane's foreground command when it was last listed.
+    pub command: String,
+    pub title: String,
+    /// Seconds since the pane went away, as of the request.
+    pub exited_secs_ago: u64,
+    /// Up to `EXIT_SUMMARY_SCROLLBACK` rows scrolled off above the screen.
+    pub scrollback: Vec<String>,
+    /// The final screen, trailing blank rows dropped.
+    pub screen: Vec<String>,
+}
+
+impl ExitSummary {
+    pub(crate) fn capture(pane: &mut PaneState, window_name: String) -> Self {
+        let mut screen = plain_rows(&pane.get_content());
+        while screen.last().is_some_and(|row| row.is_empty()) {
+            screen.pop();
+        }
+        Self {
+            pane_id: pane.id.clone(),
+            window_id: pane.window_id.clone(),
+            window_name,
+            command: pane.command.clone(),
+            title: pane.title.clone(),
+            exited_secs_ago: 0,
+            scrollback: plain_rows(&pane.scrollback(EXIT_SUMMARY_SCROLLBACK)),
+            screen,
+        }
+    }
+}
+
+/// Plain text of each row, trailing blanks trimmed.
+fn plain_rows(content: &PaneContent) -> Vec<String> {
+    content
+        .iter()
+        .map(|line| {
+            let text: String = line.iter().map(|c| c.char.as_str()).collect();
+            text.trim_end().to_string()
+        })
+        .collect()
+}
+
+/// The most recent summaries, oldest first.
+#[derive(Debug, Default)]
+pub(crate) struct ExitSummaries {
+    /// Each summary with when its pane went away; `None` until the step
+    /// that removed it is over (see `settle`).
+    entries: VecDeque<(Option<Instant>, ExitSummary)>,
+}
+
+impl ExitSummaries {
+    pub(crate) fn record(&mut self, summary: ExitSummary) {
+        if self.entries.len() == MAX_EXIT_SUMMARIES {
+            self.entries.pop_front();
+        }
+        self.entries.push_back((None, summary));
+    }
+
+    /// Date the summaries recorded since the last call at `now`, and drop
+    /// those whose pane `is_live` again. Returns the ones just dated: the
+    /// panes that went away for good in this step.
+    pub(crate) fn settle(
+        &mut self,
+        now: Instant,
+        is_live: impl Fn(&str) -> bool,
+    ) -> Vec<ExitSummary> {
+        self.entries.retain(|(_, s)| !is_live(&s.pane_id));
+        let mut exited = Vec::new();
+        for (at, summary) in &mut self.entries {
+            if at.is_none() {
+                *at = Some(now);
+                exited.push(summary.clone());
+            }
+        }
+        exited
+    }
+
+    /// Every summary as of `now`, most recent first.
+    pub(crate) fn list(&self, now: Instant) -> Vec<ExitSummary> {
+        self.entries
+            .iter()
+            .rev()
+            .map(|(at, summary)| ExitSummary {
+                exited_secs_ago: at.map_or(0, |at| now.saturating_duration_since(at).as_secs()),
+                ..summary.clone()
+            })
+            .collect()
+    }
+}
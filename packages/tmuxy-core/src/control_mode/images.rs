@@ -19,33 +19,8 @@
 //! a tmux session (verified empirically — see docs/RICH-RENDERING.md).
 
 use base64::Engine;
-use serde::{Deserialize, Serialize};
-
-/// Image protocol that produced this image.
-#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
-#[serde(rename_all = "lowercase")]
-pub enum ImageProtocol {
-    ITerm2,
-    Kitty,
-    Sixel,
-}
 
-/// An image placement on the terminal grid.
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
-pub struct ImagePlacement {
-    /// Unique image ID (auto-incremented within this parser).
-    pub id: u32,
-    /// Row where the image starts (0-indexed, screen-relative).
-    pub row: u16,
-    /// Column where the image starts (0-indexed).
-    pub col: u16,
-    /// Width in terminal cells.
-    pub width_cells: u16,
-    /// Height in terminal cells.
-    pub height_cells: u16,
-    /// Which protocol produced this image.
-    pub protocol: ImageProtocol,
-}
+pub use tmuxy_protocol::{ImagePlacement, ImageProtocol};
 
 /// Stored image blob.
 #[derive(Debug, Clone)]
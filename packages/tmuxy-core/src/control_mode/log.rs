@@ -7,20 +7,7 @@
 //! error string. The sink is threaded through connection.rs and monitor.rs
 //! so each step can be reported as it happens.
 
-use serde::{Deserialize, Serialize};
-
-#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
-#[serde(rename_all = "lowercase")]
-pub enum LogKind {
-    /// A command about to be executed (e.g. `tmux has-session -t foo`)
-    Command,
-    /// Output produced by a previously-logged command (stdout/stderr/exit)
-    Output,
-    /// Informational progress message (no associated command)
-    Info,
-    /// A non-fatal error encountered mid-flow
-    Error,
-}
+pub use tmuxy_protocol::LogKind;
 
 /// Receives streaming progress entries during connection setup.
 ///
@@ -10,14 +10,42 @@
 //! - `state` - Aggregate events into coherent state
 //! - `monitor` - High-level API with adapter pattern
 //! - `osc` - OSC (Operating System Command) sequence parser
+//! - `terminal` - Terminal emulator backends behind each pane
+//! - `spoken` - Plain-text line stream for screen readers
+//! - `dnd` - Session-level do-not-disturb mode and quiet hours
+//! - `answerback` - Replies to terminal queries tmux leaves unanswered
+//! - `palette` - Default colours and palette, from the server theme and OSC
+//! - `secret_mask` - Hides injected secrets in pane content
+//! - `ssh_agent` - Liveness and re-pointing of a session's SSH agent socket
+//! - `window_status` - Per-window status formats with tmuxy-only variables
+//! - `exit_summary` - What a pane showed when it went away
+//! - `command_history` - Commands run in a pane, from OSC 133 shell marks
+//! - `window_mru` - Most-recently-used window order and Alt-Tab cycling
+//! - `output_budget` - Per-pane output budgets and "output trimmed" markers
+//! - `sync_fields` - Extra per-pane fields fetched by the periodic sync
 
 // Sans-IO parse + state layer (wasm-safe).
+mod answerback;
+mod command_history;
+mod dnd;
+mod exit_summary;
 pub mod images;
 mod log;
 mod octal;
 mod osc;
+mod output_budget;
+mod palette;
 mod parser;
+mod secret_mask;
+pub mod session_group;
+mod spoken;
+mod ssh_agent;
 mod state;
+pub(crate) mod status_line;
+mod sync_fields;
+pub mod terminal;
+mod window_mru;
+mod window_status;
 
 // Native async/pty transport, gated behind the `native` feature.
 #[cfg(feature = "native")]
@@ -25,16 +53,39 @@ mod connection;
 #[cfg(feature = "native")]
 mod monitor;
 
+pub use command_history::{CommandRecord, MAX_COMMANDS};
 #[cfg(feature = "native")]
 pub use connection::{ControlModeConnection, INITIAL_PTY_COLS, INITIAL_PTY_ROWS};
+pub use dnd::{DndMode, DndSchedule};
+pub use exit_summary::{ExitSummary, EXIT_SUMMARY_SCROLLBACK, MAX_EXIT_SUMMARIES};
 pub use images::{ImageParser, ImagePlacement, ImageProtocol, StoredImage};
 pub use log::{LogKind, LogSink};
+#[cfg(all(feature = "native", feature = "test-support"))]
+pub use monitor::TestHook;
 #[cfg(feature = "native")]
-pub use monitor::{MonitorCommand, MonitorCommandSender, MonitorConfig, StateEmitter, TmuxMonitor};
-pub use octal::decode_octal;
-pub use osc::OscParser;
+pub use monitor::{
+    accessible_output_from_env, auto_resume_from_env, bell_policy_from_env, dnd_schedule_from_env,
+    heartbeat_interval_from_env, hibernate_after_from_env, liveness_timeout_from_env, new_epoch,
+    output_budget_from_env, pause_after_from_env, recreate_lost_session_from_env,
+    scrollback_rows_from_env, stale_pane_notice_from_env, sync_fields_from_env,
+    terminal_backend_from_env, terminal_theme_from_env, BellPolicy, MonitorCommand,
+    MonitorCommandSender, MonitorConfig, MonitorMetric, MonitorTuning, StateEmitter, TmuxMonitor,
+};
+pub use octal::{decode_octal, decode_octal_bytes};
+pub use osc::{OscParser, PaneNotification, PromptMark, SemanticMark, WIDGET_DATA_OSC};
+pub use output_budget::TRIMMED_FRAME_INTERVAL;
+pub use palette::{parse_color, TerminalColors};
 pub use parser::{ControlModeEvent, Parser};
+pub use session_group::SessionGroup;
+pub use ssh_agent::SshAgentStatus;
 pub use state::{
-    capture_command, capture_command_range, normalize_capture_bytes, ChangeType, SideEffect,
-    StateAggregator, StepResult,
+    capture_command, capture_command_range, normalize_capture_bytes, ChangeType, DropEdge,
+    PaneDropTarget, PaneRect, SideEffect, SplitDirection, SplitPreview, StalePane, StateAggregator,
+    StepResult,
 };
+pub use status_line::{StatusPosition, StatusSegments, StatusWindow};
+pub use sync_fields::SyncField;
+pub use terminal::{TerminalBackend, TerminalBackendKind};
+pub use window_mru::{WindowMru, WINDOW_CYCLE_TIMEOUT};
+
+pub use tmuxy_protocol::{KeyBindings, ServerEvent};
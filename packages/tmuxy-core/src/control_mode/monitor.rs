@@ -5,20 +5,45 @@
 //! - SSE (tmuxy-server)
 //! - Tauri events (tauri-app)
 
+use super::command_history::CommandRecord;
 use super::connection::{ControlModeConnection, INITIAL_PTY_COLS, INITIAL_PTY_ROWS};
+use super::dnd::{self, DndMode, DndSchedule};
+use super::exit_summary::ExitSummary;
+use super::output_budget::TRIMMED_FRAME_INTERVAL;
+use super::palette::TerminalColors;
 use super::parser::ControlModeEvent;
+use super::ssh_agent::{self, SshAgentStatus};
 use super::state::{
-    capture_command, capture_command_range, ChangeType, SideEffect, StateAggregator,
+    capture_command, capture_command_range, drop_command, run_command_lines, ChangeType,
+    PaneDropTarget, SideEffect, SplitDirection, SplitPreview, StalePane, StateAggregator,
+    LIVENESS_MARKER,
 };
-use crate::constants::tmux_formats;
+use super::sync_fields::{self, SyncField};
+use super::terminal::TerminalBackendKind;
+use super::window_mru::WindowMru;
+use super::window_status::{self, Probe, StatusData};
+use crate::constants::{tmux_formats, tmux_options};
 use crate::ctx::Ctx;
 use crate::error::TmuxError;
-use crate::StateUpdate;
+use crate::float::{FloatContext, FloatOp};
+use crate::keyboard::{key_commands, KeyEvent};
+use crate::mouse::{mouse_commands, MouseEvent};
+use crate::options::{self, OptionChange, OptionRef, OptionScope};
+use crate::pane_group::{GroupContext, GroupOp};
+use crate::text_input::text_commands;
+use crate::usage::{self, SessionRecorder};
+use crate::widget::{self, PlacedWidget, WidgetManager};
+use crate::{PaneContent, StateUpdate};
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
 use std::sync::Arc;
-use std::time::{Duration, Instant};
-use tokio::sync::mpsc;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+use tmuxy_protocol::ServerEvent;
+use tokio::sync::{mpsc, oneshot};
 use tracing::{debug, info, instrument, trace, warn};
 
+pub use tmuxy_protocol::MonitorTuning;
+
 /// Commands that can be sent to the monitor from external code
 #[derive(Debug)]
 pub enum MonitorCommand {
@@ -26,38 +51,226 @@ pub enum MonitorCommand {
     ResizeWindow { cols: u32, rows: u32 },
     /// Run an arbitrary tmux command through control mode
     /// Use this for commands that crash when run externally with control mode attached (e.g., new-window)
-    RunCommand { command: String },
+    ///
+    /// With `reply`, the command's output is sent back once tmux has run it:
+    /// every response block joined, or `Err` with them when one failed.
+    /// Without, a failure goes to [`StateEmitter::command_error`].
+    RunCommand {
+        command: String,
+        reply: Option<oneshot::Sender<Result<String, String>>>,
+    },
+    /// Deliver raw mouse events to a pane. Routing (SGR report, arrow keys or
+    /// copy-mode scroll) is decided from the pane's live flags; see `crate::mouse`.
+    MouseEvents {
+        pane_id: String,
+        events: Vec<MouseEvent>,
+    },
+    /// Deliver one raw key press to a pane, encoded for the keyboard protocol
+    /// the pane's application requested; see `crate::keyboard`.
+    SendKey { pane_id: String, key: KeyEvent },
+    /// Type composed text (IME, emoji) into a pane exactly; see
+    /// `crate::text_input`.
+    SendText { pane_id: String, text: String },
+    /// Move the pane border under `from` to `to` (window-relative cells)
+    DragResize {
+        window_id: String,
+        from: (u32, u32),
+        to: (u32, u32),
+    },
+    /// Start dragging a pane to rearrange it; clears any earlier drag
+    BeginPaneDrag { pane_id: String },
+    /// The dragged pane is over window-relative cell `(x, y)`; emits a
+    /// drag-target hint when the would-be drop changes
+    DragPaneOver { x: u32, y: u32 },
+    /// Drop the dragged pane at window-relative cell `(x, y)`, swapping or
+    /// joining it with the pane there. A drop with no target cancels the drag.
+    DropPaneAt { x: u32, y: u32 },
+    /// Geometry splitting a pane would produce (see
+    /// `StateAggregator::preview_split`); nothing is run
+    PreviewSplit {
+        pane_id: String,
+        direction: SplitDirection,
+        percent: u32,
+        reply: oneshot::Sender<Option<SplitPreview>>,
+    },
+    /// Read up to `lines` of a pane's most recent history from its emulator;
+    /// `None` for an unknown pane
+    GetScrollback {
+        pane_id: String,
+        lines: usize,
+        reply: oneshot::Sender<Option<PaneContent>>,
+    },
+    /// Hide a pane's live secret masks in `content` captured with
+    /// capture-pane, replying with the result (see
+    /// `StateAggregator::mask_captured`)
+    MaskCaptured {
+        pane_id: String,
+        content: PaneContent,
+        reply: oneshot::Sender<PaneContent>,
+    },
+    /// A pane's commands from shell integration marks, oldest first (see
+    /// `command_history`); `None` for an unknown pane
+    GetCommandHistory {
+        pane_id: String,
+        reply: oneshot::Sender<Option<Vec<CommandRecord>>>,
+    },
+    /// Set the session's do-not-disturb mode; persisted in `@tmuxy-dnd`
+    SetDoNotDisturb { mode: DndMode },
+    /// Replace the default colours panes are drawn and reported with
+    SetTerminalTheme { theme: TerminalColors },
+    /// Whether any client has focus, for panes that enabled focus
+    /// reporting (`CSI ? 1004 h`)
+    SetClientFocus { focused: bool },
+    /// Let a pane's output flow again after flow control paused it or
+    /// `DiscardPaneOutput` turned it off, recapturing what it missed
+    ResumePane {
+        pane_id: String,
+        reply: oneshot::Sender<Result<(), String>>,
+    },
+    /// Stop sending a pane's output (`refresh-client -A '%p:off'`). With no
+    /// other client reading it, tmux stops reading the pane too, which holds
+    /// a runaway command until `ResumePane`
+    DiscardPaneOutput {
+        pane_id: String,
+        reply: oneshot::Sender<Result<(), String>>,
+    },
+    /// Type the keyring secret `name` into a pane, masking its echo (see
+    /// `crate::secrets`)
+    InjectSecret {
+        pane_id: String,
+        name: String,
+        reply: oneshot::Sender<Result<(), String>>,
+    },
+    /// Point the session's `SSH_AUTH_SOCK` at a live agent through the stable
+    /// link (see `ssh_agent`), replying with the resulting status
+    FixSshAgent {
+        reply: oneshot::Sender<Result<SshAgentStatus, String>>,
+    },
+    /// Report changes of an option to every client (see `crate::options`),
+    /// starting with its current value. Watching it again is a no-op.
+    WatchOption {
+        option: OptionRef,
+        reply: oneshot::Sender<Result<(), String>>,
+    },
+    /// Re-emit the last emitted state as a full update, for a client that
+    /// fell too far behind to catch up on deltas
+    ResendFull,
+    /// The last emitted state as a full update, with the seq of the last
+    /// delta it includes, for one client that fell behind; nothing is emitted
+    GetFullState {
+        reply: oneshot::Sender<Option<(StateUpdate, u64)>>,
+    },
+    /// Retune the emit scheduler, replying with the settings now in effect
+    SetTuning {
+        tuning: MonitorTuning,
+        reply: oneshot::Sender<MonitorTuning>,
+    },
+    /// Number of clients watching the session, for `#{tmuxy_clients}` in
+    /// window status formats (see `window_status`)
+    SetClientCount { count: usize },
+    /// Report panes idle for at least `min_idle` (see `StateAggregator::stale_panes`)
+    GetStalePanes {
+        min_idle: Duration,
+        reply: oneshot::Sender<Vec<StalePane>>,
+    },
+    /// What recently closed panes showed, most recent first (see
+    /// `exit_summary`)
+    GetExitSummaries {
+        reply: oneshot::Sender<Vec<ExitSummary>>,
+    },
+    /// Tab windows, most recently visited first (see `window_mru`)
+    GetWindowMru { reply: oneshot::Sender<WindowMru> },
+    /// Step the Alt-Tab cycle and select the window it lands on, or end it
+    /// with `release` — replying with the order and selection after
+    SwitchLastWindowCycle {
+        reverse: bool,
+        release: bool,
+        reply: oneshot::Sender<WindowMru>,
+    },
+    /// Create, move, resize, toggle or close a float (see `crate::float`),
+    /// replying once tmux has the command list
+    Float {
+        op: FloatOp,
+        reply: oneshot::Sender<Result<(), String>>,
+    },
+    /// Create a pane group or add, switch, close or reorder its tabs (see
+    /// `crate::pane_group`), replying once tmux has the command list
+    PaneGroup {
+        op: GroupOp,
+        reply: oneshot::Sender<Result<(), String>>,
+    },
+    /// Deterministic hooks for end-to-end tests (see [`TestHook`])
+    #[cfg(feature = "test-support")]
+    Test(TestHook),
     /// Gracefully shutdown the monitor
     /// Sends detach-client and waits for the connection to close cleanly
     Shutdown,
 }
 
+/// Hooks the E2E suite drives through the server's `test-hooks` routes, so
+/// a test can reach a state without racing real tmux output.
+#[cfg(feature = "test-support")]
+#[derive(Debug)]
+pub enum TestHook {
+    /// Feed `data` to the pane as if tmux had sent it as `%output`.
+    InjectOutput { pane_id: String, data: Vec<u8> },
+    /// The shared `OffsetClock` moved: flush whatever is now due.
+    ClockAdvanced,
+    /// Hold pane output until the clock is advanced past its throttle or
+    /// debounce deadline, instead of flushing it on a timer.
+    FreezeThrottle(bool),
+}
+
+/// A measurement the monitor reports through [`StateEmitter::record_metric`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum MonitorMetric {
+    /// A control-mode event was read from the connection.
+    ControlEvent,
+    /// A command response block completed; `success` is false for `%error`.
+    CommandResponse { success: bool },
+    /// A capture-pane the monitor issued was answered after this long.
+    CaptureLatency(Duration),
+}
+
 /// Trait for emitting state changes (adapter pattern).
 ///
 /// Implement this trait in tmuxy-server (SseEmitter) and tauri-app (TauriEmitter)
-/// to receive state updates from the monitor.
+/// to receive the monitor's events. Each one is a [`ServerEvent`]; an emitter
+/// only has to carry them to its clients (see `control_mode::event` for the
+/// shared wire format).
 ///
 /// `StateEmitter: LogSink` — emitters may also override [`LogSink::log`] to
 /// surface connection-time command/output progress to the UI.
 pub trait StateEmitter: super::log::LogSink {
-    /// Called when tmux state changes (full or delta update)
-    fn emit_state(&self, update: StateUpdate);
+    /// Called with each event for clients: state updates, errors, clipboard
+    /// writes, bells, notifications, drag hints and so on.
+    fn emit(&self, event: ServerEvent);
 
-    /// Called when an error occurs
-    fn emit_error(&self, error: String);
+    /// Emit a state change (full or delta update).
+    fn emit_state(&self, update: StateUpdate) {
+        self.emit(ServerEvent::StateUpdate(Box::new(update)));
+    }
+
+    /// Emit an error.
+    fn emit_error(&self, error: String) {
+        self.emit(ServerEvent::Error { message: error });
+    }
 
     /// Called when new images are decoded from terminal output.
     /// Default implementation discards images (for emitters that don't need them).
     fn store_images(&self, _pane_id: &str, _images: Vec<(u32, super::images::StoredImage)>) {}
 
-    /// Called when an OSC 52 clipboard request is decoded from terminal output.
-    /// `text` is the UTF-8 string the application asked to place on the system
-    /// clipboard. Default implementation discards the request.
-    fn write_clipboard(&self, _pane_id: &str, _text: String) {}
+    /// Called when a pane goes away for good, with what it showed last.
+    /// Default implementation discards it.
+    fn pane_exited(&self, _summary: &ExitSummary) {}
 
     /// Called after initial state sync completes (config sourced, settings enforced).
     /// Default implementation does nothing.
     fn on_initial_sync_complete(&self) {}
+
+    /// Called with each measurement the monitor takes, for hosts that export
+    /// metrics. Default implementation discards it.
+    fn record_metric(&self, _metric: MonitorMetric) {}
 }
 
 /// Configuration for TmuxMonitor
@@ -66,9 +279,18 @@ pub struct MonitorConfig {
     /// Session name to connect to
     pub session: String,
 
-    /// Interval for periodic state sync (e.g., list-panes for cursor position)
+    /// Delay before the first periodic sync tick (plus a second for the
+    /// initial sync to land); later ticks follow `heartbeat_interval`.
     pub sync_interval: Duration,
 
+    /// Period of the idle heartbeat sync (`list-windows`, `list-panes`),
+    /// which catches tmux changes made outside control mode.
+    pub heartbeat_interval: Duration,
+
+    /// Extra per-pane fields fetched on every sync, beyond `LIST_PANES_CMD`
+    /// (see `sync_fields`). Empty fetches none.
+    pub sync_fields: Vec<SyncField>,
+
     /// Whether to create the session if it doesn't exist
     pub create_session: bool,
 
@@ -77,16 +299,444 @@ pub struct MonitorConfig {
     /// Recommended: 16ms (60fps) for smooth updates during bulk output.
     pub throttle_interval: Duration,
 
-    /// Number of events in rate window that triggers throttle mode.
-    /// Below this threshold, events emit immediately for low latency.
+    /// Number of output events in rate window that puts a pane in throttle
+    /// mode. Counted per pane: one flooding pane doesn't slow the others.
     pub throttle_threshold: u32,
 
     /// Window for counting events to detect high-frequency output.
     pub rate_window: Duration,
 
+    /// Quiet period that ends a burst of output from a pane that isn't
+    /// throttled (typing, a prompt redraw) before it is emitted.
+    pub output_debounce: Duration,
+
+    /// Longest any pane output waits to be emitted, whatever the throttle
+    /// and debounce settings. Layout and focus changes never wait on output.
+    pub max_latency: Duration,
+
     /// Working directory for the tmux control mode process.
     /// run-shell commands resolve relative paths from this directory.
     pub working_dir: Option<std::path::PathBuf>,
+
+    /// When set, the idle heartbeat logs a one-time cleanup suggestion for
+    /// panes that have been idle at least this long. `None` disables it.
+    pub stale_pane_notice: Option<Duration>,
+
+    /// What to do when a pane rings the terminal bell.
+    pub bell_policy: BellPolicy,
+
+    /// Emulator each pane's output is rendered with.
+    pub terminal_backend: TerminalBackendKind,
+
+    /// History rows each pane's emulator keeps (at least
+    /// `REFLOW_SCROLLBACK_ROWS`), readable with `GetScrollback`.
+    pub scrollback_rows: usize,
+
+    /// Stream newly completed lines of each pane as plain text for screen
+    /// readers (`StateEmitter::spoken_text`).
+    pub accessible_output: bool,
+
+    /// Daily quiet hours during which do-not-disturb turns on by itself,
+    /// unless the session's mode pins it (see `DndMode`).
+    pub dnd_schedule: Option<DndSchedule>,
+
+    /// Default foreground, background and palette, until changed with
+    /// `SetTerminalTheme` (see `TerminalColors`).
+    pub terminal_theme: TerminalColors,
+
+    /// Database recording which commands run in the session's panes (see
+    /// `crate::usage`); `None` records nothing.
+    pub usage_db: Option<std::path::PathBuf>,
+
+    /// Flow control (tmux 3.2+): seconds a pane's output may fall behind
+    /// before tmux pauses it (`refresh-client -f pause-after=N`). `None`
+    /// leaves flow control off.
+    pub pause_after: Option<u32>,
+
+    /// Resume a paused pane straight away, skipping the output it missed.
+    /// Off, it stays paused until a client sends `ResumePane`.
+    pub auto_resume: bool,
+
+    /// Bytes per second a pane may write before its frames are trimmed
+    /// (see `output_budget`); `None` never trims.
+    pub output_budget: Option<u64>,
+
+    /// Hibernate once the session has had no clients and no pane output for
+    /// this long: `run` detaches from tmux and returns, with
+    /// [`TmuxMonitor::hibernated`] set, so the runtime can reconnect when a
+    /// client comes back. `None` never hibernates.
+    pub hibernate_after: Option<Duration>,
+
+    /// How long tmux may stay silent before `run` asks it for a no-op reply,
+    /// and how long that reply may then take. Without one `run` gives up on
+    /// the connection and returns with [`TmuxMonitor::lost`] set. `None`
+    /// never probes.
+    pub liveness_timeout: Option<Duration>,
+}
+
+/// How pane bells reach the frontend.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum BellPolicy {
+    /// Drop bells.
+    Ignore,
+    /// Emit a bell event for a visual indicator only.
+    #[default]
+    Notify,
+    /// Emit a bell event and ask the frontend to play a sound.
+    Sound,
+}
+
+impl BellPolicy {
+    pub fn parse(s: &str) -> Option<Self> {
+        match s {
+            "ignore" | "none" | "off" => Some(Self::Ignore),
+            "notify" => Some(Self::Notify),
+            "sound" => Some(Self::Sound),
+            _ => None,
+        }
+    }
+}
+
+/// Environment variable selecting the bell policy (`ignore`/`notify`/`sound`).
+pub const BELL_POLICY_ENV: &str = "TMUXY_BELL";
+
+/// Read the bell policy from `TMUXY_BELL`; unset or unrecognised values keep
+/// the default (`notify`).
+pub fn bell_policy_from_env() -> BellPolicy {
+    std::env::var(BELL_POLICY_ENV)
+        .ok()
+        .and_then(|v| BellPolicy::parse(v.trim()))
+        .unwrap_or_default()
+}
+
+/// Environment variable selecting the pane emulator (`vt100`, or `alacritty`
+/// when built with the `alacritty` feature).
+pub const TERMINAL_BACKEND_ENV: &str = "TMUXY_TERMINAL_BACKEND";
+
+/// Read the pane emulator from `TMUXY_TERMINAL_BACKEND`; unset keeps vt100,
+/// and an unknown or not-compiled-in name falls back to it with a warning.
+pub fn terminal_backend_from_env() -> TerminalBackendKind {
+    let Ok(value) = std::env::var(TERMINAL_BACKEND_ENV) else {
+        return TerminalBackendKind::default();
+    };
+    TerminalBackendKind::parse(value.trim()).unwrap_or_else(|| {
+        warn!(%value, "unknown {TERMINAL_BACKEND_ENV}, using vt100");
+        TerminalBackendKind::default()
+    })
+}
+
+/// Environment variable setting how many history rows each pane's emulator
+/// keeps.
+pub const SCROLLBACK_ROWS_ENV: &str = "TMUXY_SCROLLBACK_ROWS";
+
+/// Read `scrollback_rows` from `TMUXY_SCROLLBACK_ROWS`; unset or unparsable
+/// keeps the default.
+pub fn scrollback_rows_from_env() -> usize {
+    std::env::var(SCROLLBACK_ROWS_ENV)
+        .ok()
+        .and_then(|v| v.trim().parse().ok())
+        .unwrap_or(crate::constants::REFLOW_SCROLLBACK_ROWS)
+}
+
+/// Environment variable enabling the screen-reader text stream (`1`/`true`).
+pub const ACCESSIBLE_OUTPUT_ENV: &str = "TMUXY_ACCESSIBLE_OUTPUT";
+
+/// Read `accessible_output` from `TMUXY_ACCESSIBLE_OUTPUT`; anything but
+/// `1`, `true`, `yes` or `on` leaves it off.
+pub fn accessible_output_from_env() -> bool {
+    std::env::var(ACCESSIBLE_OUTPUT_ENV).is_ok_and(|v| {
+        matches!(
+            v.trim().to_ascii_lowercase().as_str(),
+            "1" | "true" | "yes" | "on"
+        )
+    })
+}
+
+/// Environment variable setting flow control's `pause-after` in seconds
+/// (`off` or `0` disables it).
+pub const PAUSE_AFTER_ENV: &str = "TMUXY_PAUSE_AFTER";
+
+/// Read `pause_after` from `TMUXY_PAUSE_AFTER`; unset or unparsable keeps
+/// the default of 5 seconds.
+pub fn pause_after_from_env() -> Option<u32> {
+    let Ok(value) = std::env::var(PAUSE_AFTER_ENV) else {
+        return MonitorConfig::default().pause_after;
+    };
+    match value.trim() {
+        "off" | "0" => None,
+        secs => secs.parse().ok().or_else(|| {
+            warn!(%value, "invalid {PAUSE_AFTER_ENV}, expected seconds or off");
+            MonitorConfig::default().pause_after
+        }),
+    }
+}
+
+/// Environment variable that, set to `0`/`false`/`no`/`off`, keeps paused
+/// panes paused until a client resumes them.
+pub const AUTO_RESUME_ENV: &str = "TMUXY_AUTO_RESUME";
+
+/// Read `auto_resume` from `TMUXY_AUTO_RESUME`; on unless explicitly off.
+pub fn auto_resume_from_env() -> bool {
+    !std::env::var(AUTO_RESUME_ENV).is_ok_and(|v| {
+        matches!(
+            v.trim().to_ascii_lowercase().as_str(),
+            "0" | "false" | "no" | "off"
+        )
+    })
+}
+
+/// Default per-pane output budget: 1 MiB a second.
+pub const DEFAULT_OUTPUT_BUDGET: u64 = 1 << 20;
+
+/// Environment variable setting the per-pane output budget in bytes per
+/// second (`off` or `0` disables it).
+pub const OUTPUT_BUDGET_ENV: &str = "TMUXY_OUTPUT_BUDGET";
+
+/// Read `output_budget` from `TMUXY_OUTPUT_BUDGET`; unset or unparsable
+/// keeps the default.
+pub fn output_budget_from_env() -> Option<u64> {
+    let Ok(value) = std::env::var(OUTPUT_BUDGET_ENV) else {
+        return Some(DEFAULT_OUTPUT_BUDGET);
+    };
+    match value.trim() {
+        "off" | "0" => None,
+        bytes => Some(bytes.parse().unwrap_or_else(|_| {
+            warn!(%value, "invalid {OUTPUT_BUDGET_ENV}, expected bytes per second or off");
+            DEFAULT_OUTPUT_BUDGET
+        })),
+    }
+}
+
+/// Environment variable setting the idle heartbeat sync's period in
+/// milliseconds.
+pub const SYNC_INTERVAL_ENV: &str = "TMUXY_SYNC_INTERVAL_MS";
+
+/// Read `heartbeat_interval` from `TMUXY_SYNC_INTERVAL_MS`; unset or
+/// unparsable keeps the default of 15 seconds.
+pub fn heartbeat_interval_from_env() -> Duration {
+    let default = MonitorConfig::default().heartbeat_interval;
+    let Ok(value) = std::env::var(SYNC_INTERVAL_ENV) else {
+        return default;
+    };
+    match value.trim().parse() {
+        Ok(ms) if ms > 0 => Duration::from_millis(ms),
+        _ => {
+            warn!(%value, "invalid {SYNC_INTERVAL_ENV}, expected milliseconds");
+            default
+        }
+    }
+}
+
+/// Environment variable listing the extra per-pane fields every sync
+/// fetches, comma-separated (`cwd`, `pid`).
+pub const SYNC_FIELDS_ENV: &str = "TMUXY_SYNC_FIELDS";
+
+/// Read `sync_fields` from `TMUXY_SYNC_FIELDS`; unknown names are skipped.
+pub fn sync_fields_from_env() -> Vec<SyncField> {
+    let Ok(value) = std::env::var(SYNC_FIELDS_ENV) else {
+        return Vec::new();
+    };
+    let mut fields = Vec::new();
+    for name in value.split(',').filter(|name| !name.trim().is_empty()) {
+        match SyncField::parse(name) {
+            Some(field) if !fields.contains(&field) => fields.push(field),
+            Some(_) => {}
+            None => warn!(%name, "unknown field in {SYNC_FIELDS_ENV}, expected cwd or pid"),
+        }
+    }
+    fields
+}
+
+/// Environment variable setting, in seconds, how long a session without
+/// clients or output keeps its monitor before hibernating (`off` or `0`
+/// disables hibernation).
+pub const HIBERNATE_AFTER_ENV: &str = "TMUXY_HIBERNATE_AFTER";
+
+/// Read `hibernate_after` from `TMUXY_HIBERNATE_AFTER`; unset or unparsable
+/// leaves hibernation off.
+pub fn hibernate_after_from_env() -> Option<Duration> {
+    let value = std::env::var(HIBERNATE_AFTER_ENV).ok()?;
+    match value.trim() {
+        "off" | "0" => None,
+        secs => match secs.parse() {
+            Ok(secs) => Some(Duration::from_secs(secs)),
+            Err(_) => {
+                warn!(%value, "invalid {HIBERNATE_AFTER_ENV}, expected seconds or off");
+                None
+            }
+        },
+    }
+}
+
+/// Default `liveness_timeout`.
+pub const DEFAULT_LIVENESS_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Environment variable setting `liveness_timeout` in seconds (`off` or `0`
+/// disables the probe).
+pub const LIVENESS_TIMEOUT_ENV: &str = "TMUXY_LIVENESS_TIMEOUT";
+
+/// Read `liveness_timeout` from `TMUXY_LIVENESS_TIMEOUT`; unset or
+/// unparsable keeps the default of 30 seconds.
+pub fn liveness_timeout_from_env() -> Option<Duration> {
+    let Ok(value) = std::env::var(LIVENESS_TIMEOUT_ENV) else {
+        return Some(DEFAULT_LIVENESS_TIMEOUT);
+    };
+    match value.trim() {
+        "off" | "0" => None,
+        secs => match secs.parse() {
+            Ok(secs) => Some(Duration::from_secs(secs)),
+            Err(_) => {
+                warn!(%value, "invalid {LIVENESS_TIMEOUT_ENV}, expected seconds or off");
+                Some(DEFAULT_LIVENESS_TIMEOUT)
+            }
+        },
+    }
+}
+
+/// Environment variable that, set to `1`/`true`/`yes`/`on`, has the server
+/// recreate a session whose tmux went away under a running monitor instead
+/// of stopping it.
+pub const RECREATE_LOST_SESSION_ENV: &str = "TMUXY_RECREATE_LOST_SESSION";
+
+/// Read `TMUXY_RECREATE_LOST_SESSION`; off unless set.
+pub fn recreate_lost_session_from_env() -> bool {
+    std::env::var(RECREATE_LOST_SESSION_ENV).is_ok_and(|v| {
+        matches!(
+            v.trim().to_ascii_lowercase().as_str(),
+            "1" | "true" | "yes" | "on"
+        )
+    })
+}
+
+/// Environment variable setting do-not-disturb quiet hours (`22:00-07:00`).
+pub const DND_SCHEDULE_ENV: &str = "TMUXY_DND_SCHEDULE";
+
+/// Read `dnd_schedule` from `TMUXY_DND_SCHEDULE`; unset leaves it off, and a
+/// malformed value is ignored with a warning.
+pub fn dnd_schedule_from_env() -> Option<DndSchedule> {
+    let value = std::env::var(DND_SCHEDULE_ENV).ok()?;
+    let schedule = DndSchedule::parse(value.trim());
+    if schedule.is_none() {
+        warn!(%value, "invalid {DND_SCHEDULE_ENV}, expected HH:MM-HH:MM");
+    }
+    schedule
+}
+
+/// Environment variable setting the default terminal colours
+/// (`fg=#c0c0c0,bg=#1e1e1e,1=#cc6666`).
+pub const TERMINAL_THEME_ENV: &str = "TMUXY_TERMINAL_THEME";
+
+/// Read `terminal_theme` from `TMUXY_TERMINAL_THEME`; unset leaves every
+/// colour to the client's theme, and a malformed value is ignored with a
+/// warning.
+pub fn terminal_theme_from_env() -> TerminalColors {
+    let Ok(value) = std::env::var(TERMINAL_THEME_ENV) else {
+        return TerminalColors::default();
+    };
+    TerminalColors::parse(&value).unwrap_or_else(|| {
+        warn!(%value, "invalid {TERMINAL_THEME_ENV}, expected fg=#rrggbb,bg=#rrggbb,<index>=#rrggbb");
+        TerminalColors::default()
+    })
+}
+
+/// `-t <session>` for external commands, when the monitor has one.
+/// A fresh epoch, e.g. for a new control-mode connection. Wall-clock
+/// milliseconds rather than a counter, so epochs stay distinct across server
+/// restarts too — a client reconnecting to a restarted server must not take
+/// its deltas for the ones it was following. Strictly increasing within the
+/// process.
+pub fn new_epoch() -> u64 {
+    static LAST: AtomicU64 = AtomicU64::new(0);
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |d| u64::try_from(d.as_millis()).unwrap_or(u64::MAX));
+    let prev = LAST
+        .fetch_update(Ordering::Relaxed, Ordering::Relaxed, |last| {
+            Some(now.max(last + 1))
+        })
+        .unwrap_or_default();
+    now.max(prev + 1)
+}
+
+fn session_target(session: &str) -> Vec<String> {
+    if session.is_empty() {
+        Vec::new()
+    } else {
+        vec!["-t".to_string(), session.to_string()]
+    }
+}
+
+/// The session's `SSH_AUTH_SOCK`, if it has one.
+async fn session_agent_socket(ctx: &Ctx, session: &str) -> Option<String> {
+    let mut args = vec!["show-environment".to_string()];
+    args.extend(session_target(session));
+    args.push("SSH_AUTH_SOCK".to_string());
+    let output = ctx.tmux_call(args, "ssh-agent:env").await.ok()?;
+    ssh_agent::session_socket(&output)
+}
+
+/// Run a shell snippet on the tmux host and return its output.
+async fn run_shell(ctx: &Ctx, script: String, op_name: &str) -> Result<String, TmuxError> {
+    // ssh joins its argv into one remote command line, so through a tunnel
+    // the script needs one more layer of quoting.
+    let script = if ctx.is_remote() {
+        crate::executor::tmux_quote(&script)
+    } else {
+        script
+    };
+    ctx.tmux_call(vec!["run-shell".to_string(), script], op_name)
+        .await
+}
+
+/// Point `~/.ssh/ssh_auth_sock` at a live agent and the session's
+/// `SSH_AUTH_SOCK` at the link.
+async fn repoint_ssh_agent(ctx: &Ctx, session: &str) -> Result<(), String> {
+    let current = session_agent_socket(ctx, session).await;
+    let output = run_shell(
+        ctx,
+        ssh_agent::repoint_script(current.as_deref()),
+        "ssh-agent:repoint",
+    )
+    .await
+    .map_err(|e| format!("Failed to look for an SSH agent: {}", e))?;
+    let link = output.trim();
+    if link.is_empty() {
+        return Err("No live SSH agent found".to_string());
+    }
+    let mut args = vec!["set-environment".to_string()];
+    args.extend(session_target(session));
+    args.extend(["SSH_AUTH_SOCK".to_string(), link.to_string()]);
+    ctx.tmux_call(args, "ssh-agent:set")
+        .await
+        .map_err(|e| format!("Failed to set SSH_AUTH_SOCK: {}", e))?;
+    Ok(())
+}
+
+/// Minutes since local midnight, for matching `DndSchedule`.
+fn local_minute_of_day() -> u16 {
+    // SAFETY: `time` accepts a null out-pointer, and `localtime_r` only
+    // writes the caller-owned `tm`.
+    let tm = unsafe {
+        let now = libc::time(std::ptr::null_mut());
+        let mut tm: libc::tm = std::mem::zeroed();
+        libc::localtime_r(&now, &mut tm);
+        tm
+    };
+    (tm.tm_hour * 60 + tm.tm_min).clamp(0, 24 * 60 - 1) as u16
+}
+
+/// Environment variable enabling the stale-pane cleanup suggestion, in days.
+pub const STALE_PANE_DAYS_ENV: &str = "TMUXY_STALE_PANE_DAYS";
+
+/// Read `stale_pane_notice` from `TMUXY_STALE_PANE_DAYS`; unset, unparsable,
+/// zero or too large leaves it disabled.
+pub fn stale_pane_notice_from_env() -> Option<Duration> {
+    std::env::var(STALE_PANE_DAYS_ENV)
+        .ok()
+        .and_then(|v| v.trim().parse::<u64>().ok())
+        .filter(|days| *days > 0)
+        .and_then(|days| days.checked_mul(24 * 60 * 60))
+        .map(Duration::from_secs)
 }
 
 impl Default for MonitorConfig {
@@ -94,11 +744,70 @@ impl Default for MonitorConfig {
         Self {
             session: String::new(),
             sync_interval: Duration::from_millis(500),
+            heartbeat_interval: Duration::from_secs(15),
+            sync_fields: Vec::new(),
             create_session: false,
             throttle_interval: Duration::from_millis(32), // ~30fps during high throughput
             throttle_threshold: 20,                       // >20 events/100ms triggers throttle
             rate_window: Duration::from_millis(100),
+            output_debounce: Duration::from_millis(16),
+            max_latency: Duration::from_millis(50),
             working_dir: None,
+            stale_pane_notice: None,
+            bell_policy: BellPolicy::default(),
+            terminal_backend: TerminalBackendKind::default(),
+            scrollback_rows: crate::constants::REFLOW_SCROLLBACK_ROWS,
+            accessible_output: false,
+            dnd_schedule: None,
+            terminal_theme: TerminalColors::default(),
+            usage_db: None,
+            pause_after: Some(5),
+            auto_resume: true,
+            output_budget: Some(DEFAULT_OUTPUT_BUDGET),
+            hibernate_after: None,
+            liveness_timeout: Some(DEFAULT_LIVENESS_TIMEOUT),
+        }
+    }
+}
+
+impl MonitorConfig {
+    /// Apply the set fields of `tuning`.
+    pub fn apply_tuning(&mut self, tuning: &MonitorTuning) {
+        let ms = Duration::from_millis;
+        if let Some(v) = tuning.throttle_interval_ms {
+            self.throttle_interval = ms(v);
+        }
+        if let Some(v) = tuning.throttle_threshold {
+            self.throttle_threshold = v;
+        }
+        if let Some(v) = tuning.rate_window_ms {
+            self.rate_window = ms(v);
+        }
+        if let Some(v) = tuning.output_debounce_ms {
+            self.output_debounce = ms(v);
+        }
+        if let Some(v) = tuning.max_latency_ms {
+            self.max_latency = ms(v);
+        }
+        if let Some(v) = tuning.heartbeat_interval_ms {
+            self.heartbeat_interval = ms(v);
+        }
+        if let Some(v) = &tuning.sync_fields {
+            self.sync_fields = v.clone();
+        }
+    }
+
+    /// The scheduler settings in effect, every field set.
+    pub fn tuning(&self) -> MonitorTuning {
+        let ms = |d: Duration| Some(d.as_millis() as u64);
+        MonitorTuning {
+            throttle_interval_ms: ms(self.throttle_interval),
+            throttle_threshold: Some(self.throttle_threshold),
+            rate_window_ms: ms(self.rate_window),
+            output_debounce_ms: ms(self.output_debounce),
+            max_latency_ms: ms(self.max_latency),
+            heartbeat_interval_ms: ms(self.heartbeat_interval),
+            sync_fields: Some(self.sync_fields.clone()),
         }
     }
 }
@@ -112,6 +821,27 @@ pub type MonitorCommandSender = mpsc::Sender<MonitorCommand>;
 /// so the future has *some* await point when the guard is false.
 const LONG_SLEEP: Duration = Duration::from_secs(3600);
 
+/// How often a `dnd_schedule` is checked against the clock, which bounds how
+/// late do-not-disturb follows the start or end of quiet hours.
+const DND_CHECK_INTERVAL: Duration = Duration::from_secs(30);
+
+/// How often the session's SSH agent is checked, which bounds how long a dead
+/// agent goes unnoticed (and un-repointed) after an SSH reconnect.
+const SSH_AGENT_CHECK_INTERVAL: Duration = Duration::from_secs(60);
+
+/// How often window status formats are re-read and their git state and ports
+/// probed, which bounds how stale a window's status gets.
+const WINDOW_STATUS_INTERVAL: Duration = Duration::from_secs(5);
+
+/// How often pane commands are sampled for usage statistics. Runs shorter
+/// than this may go unseen.
+const USAGE_SAMPLE_INTERVAL: Duration = Duration::from_secs(2);
+
+/// How many options a session's clients may watch at once. Each one is a
+/// format tmux re-evaluates every second, per window or pane for untargeted
+/// window and pane options.
+const MAX_OPTION_WATCHES: usize = 64;
+
 /// All the per-invocation runtime state that used to live as locals in
 /// `TmuxMonitor::run`. Extracting it lets `run`'s body shrink to a ~50-line
 /// dispatch over `tokio::select!`, with each branch delegating to a small
@@ -124,24 +854,30 @@ struct RunState {
     idle_threshold: Duration,
     /// Copy-mode poll interval (cursor needs sub-100ms updates).
     copy_mode_sync_interval: Duration,
-    /// Heartbeat interval when fully idle.
-    heartbeat_interval: Duration,
     /// Timestamp of the last control-mode event (for idle classification).
     last_event_at: tokio::time::Instant,
+    /// Timestamp of the last pane output (for hibernation).
+    last_output_at: tokio::time::Instant,
+    /// When the unanswered liveness probe went out, if one is out.
+    probe_sent_at: Option<tokio::time::Instant>,
     /// Next scheduled sync tick.
     next_sync_at: tokio::time::Instant,
-
-    // Output throttling / debouncing
+    /// Next time the do-not-disturb schedule is re-evaluated.
+    dnd_check_at: tokio::time::Instant,
+    /// Next time the session's SSH agent is checked.
+    ssh_agent_check_at: tokio::time::Instant,
+    /// Next time window status formats are re-evaluated.
+    window_status_at: tokio::time::Instant,
+    /// Next usage statistics sample.
+    usage_sample_at: tokio::time::Instant,
+    /// Next `status-interval` refresh of the status line.
+    status_refresh_at: tokio::time::Instant,
+
+    // Output throttling / debouncing. An emit carries every pane's pending
+    // output, so `last_output_emit` is shared; rates are per pane.
     last_output_emit: Instant,
     pending_output_emit: bool,
-    last_output_event_at: Option<Instant>,
-    pending_output_first_at: Option<Instant>,
-    low_throughput_debounce: Duration,
-    pending_output_max: Duration,
-    rate_window_start: Instant,
-    rate_event_count: u32,
-    throttle_enabled: bool,
-    in_throttle_mode: bool,
+    pane_output: HashMap<String, PaneOutputRate>,
 
     // Metadata sync after output settles
     metadata_sync_at: Option<tokio::time::Instant>,
@@ -150,6 +886,10 @@ struct RunState {
     // Layout debouncing
     pending_layout_emit: bool,
     layout_debounce: Duration,
+
+    /// Pending output waits for the test clock instead of a timer; only the
+    /// E2E test hooks set it.
+    throttle_frozen: bool,
 }
 
 impl RunState {
@@ -163,88 +903,204 @@ impl RunState {
         Self {
             idle_threshold: Duration::from_secs(10),
             copy_mode_sync_interval: Duration::from_millis(50),
-            heartbeat_interval: Duration::from_secs(15),
             last_event_at: now_async,
+            last_output_at: now_async,
+            probe_sent_at: None,
             next_sync_at: now_async + config.sync_interval + Duration::from_secs(1),
+            dnd_check_at: now_async + DND_CHECK_INTERVAL,
+            ssh_agent_check_at: now_async + SSH_AGENT_CHECK_INTERVAL,
+            // Right away: windows only exist once the initial sync landed.
+            window_status_at: now_async,
+            usage_sample_at: now_async + USAGE_SAMPLE_INTERVAL,
+            // Right away once the first refresh has read status-interval.
+            status_refresh_at: now_async,
 
             last_output_emit: now_std - config.throttle_interval,
             pending_output_emit: false,
-            last_output_event_at: None,
-            pending_output_first_at: None,
-            low_throughput_debounce: Duration::from_millis(16),
-            pending_output_max: Duration::from_millis(100),
-            rate_window_start: now_std,
-            rate_event_count: 0,
-            throttle_enabled: !config.throttle_interval.is_zero(),
-            in_throttle_mode: false,
+            pane_output: HashMap::new(),
 
             metadata_sync_at: None,
             metadata_sync_delay: Duration::from_millis(500),
 
             pending_layout_emit: false,
             layout_debounce: Duration::from_millis(16),
+
+            throttle_frozen: false,
         }
     }
 
+    /// When to hibernate: `hibernate_after` past the later of the last pane
+    /// output and the last client leaving (`clients_left_at`). `None` while
+    /// a client is watching or with hibernation off.
+    fn hibernate_deadline(
+        &self,
+        config: &MonitorConfig,
+        clients_left_at: Option<tokio::time::Instant>,
+    ) -> Option<tokio::time::Instant> {
+        let idle_since = clients_left_at?.max(self.last_output_at);
+        Some(idle_since + config.hibernate_after?)
+    }
+
+    /// When the liveness branch fires next: `liveness_timeout` after the last
+    /// event, or after the probe when it is still unanswered. `None` with
+    /// probing off.
+    fn liveness_deadline(&self, config: &MonitorConfig) -> Option<tokio::time::Instant> {
+        let timeout = config.liveness_timeout?;
+        Some(match self.probe_sent_at {
+            Some(sent) if self.last_event_at < sent => sent + timeout,
+            _ => self.last_event_at + timeout,
+        })
+    }
+
     /// Compute the sleep duration for the throttle-tick branch.
     /// `Duration::from_secs(3600)` is the "effectively infinite" sentinel; the
     /// `if pending_output_emit` guard on the branch is what actually parks us.
     /// `now` comes from `Ctx::clock` so tests can drive the deadline math.
     fn compute_throttle_sleep(&self, config: &MonitorConfig, now: Instant) -> Duration {
-        if !(self.pending_output_emit && self.throttle_enabled) {
+        if !self.pending_output_emit || config.throttle_interval.is_zero() {
             return LONG_SLEEP;
         }
-        if self.in_throttle_mode {
-            let elapsed = now.saturating_duration_since(self.last_output_emit);
-            if elapsed >= config.throttle_interval {
-                Duration::ZERO
-            } else {
-                config.throttle_interval - elapsed
-            }
-        } else {
-            let since_last_event = self
-                .last_output_event_at
-                .map(|t| now.duration_since(t))
-                .unwrap_or(Duration::ZERO);
-            let since_first_pending = self
-                .pending_output_first_at
-                .map(|t| now.duration_since(t))
-                .unwrap_or(Duration::ZERO);
-            let remaining_debounce = self
-                .low_throughput_debounce
-                .saturating_sub(since_last_event);
-            let remaining_max = self.pending_output_max.saturating_sub(since_first_pending);
-            remaining_debounce.min(remaining_max)
-        }
+        self.pane_output
+            .values()
+            .filter_map(|pane| pane.deadline(config, self.last_output_emit))
+            .min()
+            .map_or(Duration::ZERO, |deadline| {
+                deadline.saturating_duration_since(now)
+            })
     }
 
     /// Mark a fresh output emission — reset the debounce / rate-tracking trackers.
     fn mark_emitted(&mut self, now: Instant) {
         self.last_output_emit = now;
         self.pending_output_emit = false;
-        self.pending_output_first_at = None;
-        self.last_output_event_at = None;
+        for pane in self.pane_output.values_mut() {
+            pane.pending_since = None;
+            pane.last_event_at = None;
+        }
+    }
+
+    /// Count an output event from `pane_id` and queue it for emission.
+    /// `trimmed` is whether the pane is over its output budget. Returns
+    /// whether the pane is in throttle mode (always, while trimmed).
+    fn record_output(
+        &mut self,
+        config: &MonitorConfig,
+        pane_id: &str,
+        trimmed: bool,
+        now: Instant,
+    ) -> bool {
+        // Drop panes whose rate has lapsed (a fresh entry behaves the same),
+        // so closed panes don't accumulate.
+        if !self.pane_output.contains_key(pane_id) {
+            self.pane_output.retain(|_, pane| {
+                pane.in_throttle_mode
+                    || pane.pending_since.is_some()
+                    || now.duration_since(pane.window_start) <= config.rate_window
+            });
+        }
+        let pane = self
+            .pane_output
+            .entry(pane_id.to_string())
+            .or_insert_with(|| PaneOutputRate::new(now));
+        pane.update_rate(config, now);
+        pane.trimmed = trimmed;
+        pane.pending_since.get_or_insert(now);
+        pane.last_event_at = Some(now);
+        self.pending_output_emit = true;
+        pane.in_throttle_mode || trimmed
+    }
+
+    /// Shortest gap between output emits for `pane_id`: the throttle
+    /// interval, or `TRIMMED_FRAME_INTERVAL` while it is over its budget.
+    fn frame_interval(&self, config: &MonitorConfig, pane_id: &str) -> Duration {
+        match self.pane_output.get(pane_id) {
+            Some(pane) if pane.trimmed => TRIMMED_FRAME_INTERVAL,
+            _ => config.throttle_interval,
+        }
+    }
+
+    /// Queue one more frame for panes whose last frame carried a trimmed
+    /// marker, so the marker goes once the output calms down.
+    fn queue_trim_followup(&mut self, pane_ids: &[String], now: Instant) {
+        for pane_id in pane_ids {
+            let pane = self
+                .pane_output
+                .entry(pane_id.clone())
+                .or_insert_with(|| PaneOutputRate::new(now));
+            pane.pending_since.get_or_insert(now);
+            self.pending_output_emit = true;
+        }
+    }
+}
+
+/// One pane's output rate and unsent output, for [`RunState`].
+#[derive(Debug)]
+struct PaneOutputRate {
+    window_start: Instant,
+    event_count: u32,
+    in_throttle_mode: bool,
+    /// Over its output budget (see `output_budget`)
+    trimmed: bool,
+    /// First output since the last emit, while there is unsent output.
+    pending_since: Option<Instant>,
+    last_event_at: Option<Instant>,
+}
+
+impl PaneOutputRate {
+    fn new(now: Instant) -> Self {
+        Self {
+            window_start: now,
+            event_count: 0,
+            in_throttle_mode: false,
+            trimmed: false,
+            pending_since: None,
+            last_event_at: None,
+        }
     }
 
     /// Slide the rate-tracking window and toggle high/low throughput mode based
     /// on the hysteresis threshold.
     fn update_rate(&mut self, config: &MonitorConfig, now: Instant) {
-        if now.duration_since(self.rate_window_start) > config.rate_window {
+        if now.duration_since(self.window_start) > config.rate_window {
             let exit_threshold = config.throttle_threshold / 2;
-            if self.in_throttle_mode && self.rate_event_count <= exit_threshold {
+            if self.in_throttle_mode && self.event_count <= exit_threshold {
                 self.in_throttle_mode = false;
-            } else if !self.in_throttle_mode && self.rate_event_count > config.throttle_threshold {
+            } else if !self.in_throttle_mode && self.event_count > config.throttle_threshold {
                 self.in_throttle_mode = true;
             }
-            self.rate_window_start = now;
-            self.rate_event_count = 1;
+            self.window_start = now;
+            self.event_count = 1;
         } else {
-            self.rate_event_count += 1;
-            if !self.in_throttle_mode && self.rate_event_count > config.throttle_threshold {
+            self.event_count += 1;
+            if !self.in_throttle_mode && self.event_count > config.throttle_threshold {
                 self.in_throttle_mode = true;
             }
         }
     }
+
+    /// When this pane's pending output is due: the next throttle slot for a
+    /// throttled pane, the end of the debounce otherwise, and never later
+    /// than `max_latency` after it arrived. A trimmed pane waits for its
+    /// slowed slot regardless. `None` with nothing pending.
+    fn deadline(&self, config: &MonitorConfig, last_emit: Instant) -> Option<Instant> {
+        let since = self.pending_since?;
+        if self.trimmed {
+            return Some(last_emit + TRIMMED_FRAME_INTERVAL);
+        }
+        let due = if self.in_throttle_mode {
+            last_emit + config.throttle_interval
+        } else {
+            self.last_event_at.unwrap_or(since) + config.output_debounce
+        };
+        Some(due.min(since + config.max_latency))
+    }
+}
+
+/// A client's `RunCommand` waiting for its `TMUXY_RUN_END` marker.
+struct PendingRun {
+    /// The command as the client sent it, for `StateEmitter::command_error`.
+    command: String,
+    reply: Option<oneshot::Sender<Result<String, String>>>,
 }
 
 /// The main tmux control mode monitor.
@@ -289,6 +1145,62 @@ pub struct TmuxMonitor {
     /// grows (initial list-windows landing, a new tab) gets sized.
     sized_window_count: usize,
 
+    /// Panes the stale-pane suggestion has already named, so each idle pane is
+    /// mentioned once rather than on every heartbeat.
+    stale_noticed: std::collections::HashSet<String>,
+
+    /// Pane being dragged by `BeginPaneDrag`, with the drop target last
+    /// hinted to clients.
+    pane_drag: Option<(String, Option<PaneDropTarget>)>,
+
+    /// The session's do-not-disturb mode, mirrored from `@tmuxy-dnd`.
+    dnd_mode: DndMode,
+
+    /// Options clients watch, indexed by their subscription number.
+    option_watches: Vec<OptionRef>,
+
+    /// Widget panes last written to the session's widget file; `None` until
+    /// the remembered ones have been restored after connecting.
+    remembered_widgets: Option<Vec<PlacedWidget>>,
+
+    /// When each in-flight capture-pane was sent, for `CaptureLatency`.
+    capture_sent_at: HashMap<String, Instant>,
+
+    /// Clients watching the session (see `SetClientCount`). One until told
+    /// otherwise: the desktop app is always a single client.
+    client_count: usize,
+
+    /// When the client count last dropped to zero; `None` while any client
+    /// is watching.
+    clients_left_at: Option<tokio::time::Instant>,
+
+    /// `run` returned because the session hibernated (see
+    /// `MonitorConfig::hibernate_after`).
+    hibernated: bool,
+
+    /// Why `run` gave up on tmux: the connection closed under it, or a
+    /// liveness probe went unanswered. `None` after a shutdown or
+    /// hibernation.
+    lost: Option<String>,
+
+    /// Each window's status format with what it was last evaluated against,
+    /// so a new client count re-evaluates without probing again.
+    window_status: HashMap<String, (String, StatusData)>,
+
+    /// Finished status-line `#()` jobs, `(command, output)`, sent back by the
+    /// tasks running them.
+    status_jobs_tx: mpsc::UnboundedSender<(String, String)>,
+    status_jobs_rx: mpsc::UnboundedReceiver<(String, String)>,
+
+    /// Usage statistics recording, when `config.usage_db` is set and the
+    /// database could be opened.
+    usage: Option<SessionRecorder>,
+
+    /// `RunCommand`s waiting for their output, by run id (see
+    /// `run_command_lines`).
+    pending_runs: HashMap<u64, PendingRun>,
+    next_run_id: u64,
+
     /// Execution context — `ctx.clock.now()` replaces every `Instant::now()`
     /// inside the loop so tests can advance time with `FakeClock`.
     ctx: Arc<Ctx>,
@@ -312,9 +1224,16 @@ impl TmuxMonitor {
         // `tmux -CC new-session -A` (when create_session is true) handles
         // both create and attach atomically — no clientless gap for the
         // macOS launchd reaper to hit.
+        // `ctx` names the tmux server: the environment's, or another host's
+        // for a session the web server fronts over ssh.
+        let transport = ctx
+            .transport
+            .clone()
+            .unwrap_or_else(crate::session::TmuxTransport::from_env);
         let connection = {
             let _lock = super::connection::session_creation_lock().await;
             ControlModeConnection::connect(
+                &transport,
                 &config.session,
                 config.working_dir.as_deref(),
                 log,
@@ -324,16 +1243,41 @@ impl TmuxMonitor {
         };
 
         let (command_tx, command_rx) = mpsc::channel(32);
+        let (status_jobs_tx, status_jobs_rx) = mpsc::unbounded_channel();
+        let mut aggregator = StateAggregator::new();
+        aggregator.set_terminal_backend(config.terminal_backend);
+        aggregator.set_scrollback_rows(config.scrollback_rows);
+        aggregator.set_accessible_output(config.accessible_output);
+        aggregator.set_auto_resume(config.auto_resume);
+        aggregator.set_output_budget(config.output_budget);
+        aggregator.set_terminal_theme(config.terminal_theme.clone());
+        aggregator.begin_epoch(new_epoch());
 
         Ok((
             Self {
                 connection,
-                aggregator: StateAggregator::new(),
+                aggregator,
                 config,
                 command_rx,
                 window_tags_migrated: false,
                 client_size: None,
                 sized_window_count: 0,
+                stale_noticed: std::collections::HashSet::new(),
+                pane_drag: None,
+                dnd_mode: DndMode::Auto,
+                option_watches: Vec::new(),
+                remembered_widgets: None,
+                capture_sent_at: HashMap::new(),
+                client_count: 1,
+                clients_left_at: None,
+                hibernated: false,
+                lost: None,
+                window_status: HashMap::new(),
+                status_jobs_tx,
+                status_jobs_rx,
+                usage: None,
+                pending_runs: HashMap::new(),
+                next_run_id: 0,
                 ctx,
             },
             command_tx,
@@ -356,7 +1300,9 @@ impl TmuxMonitor {
         self.connection
             .send_command(&format!(
                 "resizew -t {} -x {} -y {}",
-                self.config.session, INITIAL_PTY_COLS, INITIAL_PTY_ROWS
+                crate::executor::tmux_quote(&self.config.session),
+                INITIAL_PTY_COLS,
+                INITIAL_PTY_ROWS
             ))
             .await?;
 
@@ -371,12 +1317,18 @@ impl TmuxMonitor {
         // breaks (missing rows), input fails, or content is corrupted.
         self.enforce_settings().await?;
 
-        // Enable flow control (tmux 3.2+)
-        // pause-after=5 means pause output if client is 5+ seconds behind
-        // This prevents unbounded memory growth during heavy output
-        self.connection
-            .send_command("refresh-client -f pause-after=5")
-            .await?;
+        // Enable flow control (tmux 3.2+): pause-after=N pauses a pane whose
+        // output is N+ seconds behind, which bounds memory during heavy output
+        if let Some(secs) = self.config.pause_after {
+            self.connection
+                .send_command(&format!("refresh-client -f pause-after={secs}"))
+                .await?;
+        }
+
+        // Before the first list-panes lands, so the initial full state
+        // already carries the session's do-not-disturb flag.
+        self.load_do_not_disturb().await;
+        self.check_ssh_agent().await;
 
         // Panes BEFORE windows — the same load-bearing order
         // `refresh_after_window_add` documents: emitting window state before
@@ -390,6 +1342,10 @@ impl TmuxMonitor {
             .send_command(tmux_formats::LIST_WINDOWS_CMD)
             .await?;
 
+        if let Some(cmd) = sync_fields::details_command(&self.config.sync_fields) {
+            self.connection.send_command(&cmd).await?;
+        }
+
         // Capture current content of each pane
         // We'll do this after we receive the list-panes response
         // to know which panes exist
@@ -446,6 +1402,292 @@ impl TmuxMonitor {
         Ok(())
     }
 
+    /// Read the session's `@tmuxy-dnd` mode and apply it. An unreadable or
+    /// unknown value keeps `auto`.
+    async fn load_do_not_disturb(&mut self) {
+        let mut args = vec!["show-options".to_string(), "-qv".to_string()];
+        if !self.config.session.is_empty() {
+            args.extend(["-t".to_string(), self.config.session.clone()]);
+        }
+        args.push(tmux_options::DND.to_string());
+        let value = self
+            .ctx
+            .tmux_call(args, "dnd:get")
+            .await
+            .unwrap_or_default();
+        self.dnd_mode = DndMode::parse(value.trim()).unwrap_or_else(|| {
+            warn!(
+                value = value.trim(),
+                "unknown {}, using auto",
+                tmux_options::DND
+            );
+            DndMode::Auto
+        });
+        self.aggregator.set_do_not_disturb(self.dnd_active());
+    }
+
+    /// Subscribe to an option's changes, unless it is watched already.
+    async fn watch_option(&mut self, option: OptionRef) -> Result<(), String> {
+        if self.option_watches.contains(&option) {
+            return Ok(());
+        }
+        if self.option_watches.len() >= MAX_OPTION_WATCHES {
+            return Err(format!(
+                "too many watched options (max {MAX_OPTION_WATCHES})"
+            ));
+        }
+        let name = options::subscription_name(self.option_watches.len());
+        let cmd = options::subscribe_command(&option, &name)?;
+        self.connection
+            .send_command(&cmd)
+            .await
+            .map_err(|e| format!("Failed to watch option: {}", e))?;
+        debug!(option = %option.name, %name, "watching option");
+        self.option_watches.push(option);
+        Ok(())
+    }
+
+    /// Whether do-not-disturb is in effect right now.
+    fn dnd_active(&self) -> bool {
+        dnd::is_active(
+            self.dnd_mode,
+            self.config.dnd_schedule.as_ref(),
+            local_minute_of_day(),
+        )
+    }
+
+    /// Re-evaluate do-not-disturb and emit state when it flipped.
+    fn refresh_do_not_disturb<E: StateEmitter>(&mut self, emitter: &E) {
+        let active = self.dnd_active();
+        if self.aggregator.set_do_not_disturb(active) {
+            info!(active, "do-not-disturb changed");
+            if let Some(update) = self.aggregator.to_state_update() {
+                emitter.emit_state(update);
+            }
+        }
+    }
+
+    /// Re-read every window's `@tmuxy-status` format, probe the git state and
+    /// ports the formats use, and re-evaluate them. Returns whether any
+    /// window's status changed.
+    async fn refresh_window_status(&mut self) -> bool {
+        let mut args = vec!["list-panes".to_string(), "-s".to_string()];
+        args.extend(session_target(&self.config.session));
+        args.extend(["-F".to_string(), window_status::list_panes_format()]);
+        let windows = match self.ctx.tmux_call(args, "window-status:list").await {
+            Ok(output) => window_status::status_windows(&output),
+            Err(e) => {
+                debug!(error = %e, "window status formats not read");
+                return false;
+            }
+        };
+
+        let ports = windows.iter().any(|w| window_status::uses_ports(&w.format));
+        let mut dirs: Vec<String> = Vec::new();
+        for window in &windows {
+            if let Some(path) = window.active_path.as_ref() {
+                if window_status::uses_git(&window.format) && !dirs.contains(path) {
+                    dirs.push(path.clone());
+                }
+            }
+        }
+        let probe = if ports || !dirs.is_empty() {
+            let script = window_status::probe_script(ports, &dirs);
+            match run_shell(&self.ctx, script, "window-status:probe").await {
+                Ok(output) => Probe::parse(&output),
+                Err(e) => {
+                    debug!(error = %e, "window status probe failed");
+                    Probe::default()
+                }
+            }
+        } else {
+            Probe::default()
+        };
+
+        self.window_status = windows
+            .into_iter()
+            .map(|window| {
+                let git = window
+                    .active_path
+                    .as_ref()
+                    .and_then(|path| dirs.iter().position(|d| d == path))
+                    .and_then(|index| probe.git(index));
+                let data = StatusData {
+                    clients: self.client_count,
+                    git,
+                    ports: probe.ports_under(&window.pane_pids),
+                };
+                (window.id, (window.format, data))
+            })
+            .collect();
+        self.apply_window_status()
+    }
+
+    /// Evaluate the remembered window status formats into the aggregator.
+    fn apply_window_status(&mut self) -> bool {
+        let statuses: HashMap<String, String> = self
+            .window_status
+            .iter()
+            .map(|(id, (format, data))| (id.clone(), window_status::evaluate(format, data)))
+            .collect();
+        self.aggregator.set_window_statuses(&statuses)
+    }
+
+    /// Check the session's SSH agent, re-pointing it when it is dead, and
+    /// record the result. Returns whether the status changed.
+    async fn check_ssh_agent(&mut self) -> bool {
+        let ctx = Arc::clone(&self.ctx);
+        let session = self.config.session.clone();
+        let status = match session_agent_socket(&ctx, &session).await {
+            None => SshAgentStatus::Unset,
+            Some(socket) => {
+                match run_shell(&ctx, ssh_agent::check_script(&socket), "ssh-agent:check").await {
+                    Ok(output) => ssh_agent::parse_status(&output),
+                    Err(e) => {
+                        debug!(error = %e, "ssh agent check failed");
+                        return false;
+                    }
+                }
+            }
+        };
+        let status = match status {
+            SshAgentStatus::Dead => match repoint_ssh_agent(&ctx, &session).await {
+                Ok(()) => SshAgentStatus::Live,
+                Err(e) => {
+                    debug!(error = %e, "ssh agent not re-pointed");
+                    SshAgentStatus::Dead
+                }
+            },
+            status => status,
+        };
+        let changed = self.aggregator.set_ssh_agent(status);
+        if changed {
+            info!(?status, "ssh agent status changed");
+        }
+        changed
+    }
+
+    /// Send the command list for a float operation, then re-list windows:
+    /// tmux has no notification for a changed window option, so this is how
+    /// the new geometry reaches the state.
+    async fn run_float_op(&mut self, op: &FloatOp) -> Result<(), String> {
+        let floats = self.aggregator.floats();
+        let ctx = FloatContext {
+            floats: &floats,
+            active_window: self.aggregator.active_window_id(),
+            bounds: self.client_size,
+        };
+        let cmd = op.to_command(ctx).map_err(|e| e.to_string())?;
+        debug!(%cmd, "float operation");
+        self.connection
+            .send_commands_batch(&[cmd, tmux_formats::LIST_WINDOWS_CMD.to_string()])
+            .await
+            .map_err(|e| format!("Failed to update float: {}", e))
+    }
+
+    async fn run_group_op(&mut self, op: &GroupOp) -> Result<(), String> {
+        let groups = self.aggregator.pane_groups();
+        let pane = match op {
+            GroupOp::Create { pane_id } => self.aggregator.group_member(pane_id),
+            _ => None,
+        };
+        let ctx = GroupContext {
+            groups: &groups,
+            pane: pane.as_ref(),
+        };
+        let cmd = op.to_command(ctx).map_err(|e| e.to_string())?;
+        debug!(%cmd, "pane group operation");
+        // Splits, breaks and swaps each send their own layout events; a
+        // reorder only touches options.
+        let moves_panes = !matches!(op, GroupOp::Reorder { .. });
+        if moves_panes {
+            self.aggregator.arm_settling(self.ctx.clock.now());
+        }
+        self.aggregator.check_pane_groups();
+        let sent = self
+            .connection
+            .send_commands_batch(&[
+                cmd,
+                tmux_formats::LIST_PANES_CMD.to_string(),
+                tmux_formats::LIST_WINDOWS_CMD.to_string(),
+            ])
+            .await;
+        if sent.is_err() && moves_panes {
+            self.aggregator.clear_settling();
+        }
+        sent.map_err(|e| format!("Failed to update pane group: {}", e))
+    }
+
+    /// Type the keyring secret `name` into `pane_id`. The pane's echo is
+    /// masked before the keys go out, so no state update ever carries it; the
+    /// value itself is neither logged nor emitted.
+    async fn inject_secret(&mut self, pane_id: &str, name: String) -> Result<(), String> {
+        let secret = tokio::task::spawn_blocking(move || crate::secrets::read_secret(&name))
+            .await
+            .map_err(|e| format!("Secret lookup failed: {}", e))??;
+        if !self
+            .aggregator
+            .mask_secret(pane_id, &secret, self.ctx.clock.now())
+        {
+            return Err(format!("Unknown pane '{}'", pane_id));
+        }
+        let cmd =
+            zeroize::Zeroizing::new(crate::text_input::hex_command(pane_id, secret.as_bytes()));
+        self.connection
+            .send_command(&cmd)
+            .await
+            .map_err(|e| format!("Failed to type secret: {}", e))?;
+        info!(pane_id, "secret injected");
+        Ok(())
+    }
+
+    /// Turn a pane's output off (`held`) or back on, then `continue` it in
+    /// case flow control paused it. Resuming recaptures the pane, since tmux
+    /// dropped what it wrote in the meantime. Only panes the aggregator
+    /// knows are accepted, so the id is safe to put in the command.
+    async fn set_pane_flow<E: StateEmitter>(
+        &mut self,
+        emitter: &E,
+        pane_id: &str,
+        held: bool,
+    ) -> Result<(), String> {
+        if !self.aggregator.has_pane(pane_id) {
+            return Err(format!("Unknown pane '{}'", pane_id));
+        }
+        let commands = if held {
+            vec![format!("refresh-client -A '{pane_id}:off'")]
+        } else {
+            vec![
+                format!("refresh-client -A '{pane_id}:on'"),
+                format!("refresh-client -A '{pane_id}:continue'"),
+            ]
+        };
+        self.connection
+            .send_commands_batch(&commands)
+            .await
+            .map_err(|e| format!("Failed to set pane output: {}", e))?;
+        if self.aggregator.set_pane_paused(pane_id, held) {
+            if let Some(update) = self.aggregator.to_state_update() {
+                emitter.emit_state(update);
+            }
+        }
+        if !held {
+            self.refresh_panes(emitter, &[pane_id.to_string()]).await;
+        }
+        Ok(())
+    }
+
+    /// Whether the last `run` ended in hibernation rather than because the
+    /// connection closed or the monitor was shut down.
+    pub fn hibernated(&self) -> bool {
+        self.hibernated
+    }
+
+    /// Why the last `run` lost tmux, if it did (see `lost`).
+    pub fn lost(&self) -> Option<&str> {
+        self.lost.as_deref()
+    }
+
     /// Run the monitor event loop.
     ///
     /// This is the main loop that processes control mode events and emits state changes.
@@ -462,6 +1704,13 @@ impl TmuxMonitor {
         // SseEmitter uses this to broadcast keybindings with correct prefix key.
         emitter.on_initial_sync_complete();
 
+        if let Some(path) = self.config.usage_db.as_deref() {
+            match SessionRecorder::open(path, &self.config.session, usage::now_millis()) {
+                Ok(recorder) => self.usage = Some(recorder),
+                Err(e) => warn!(path = %path.display(), error = %e, "usage statistics disabled"),
+            }
+        }
+
         let mut rs = RunState::new(&self.config, self.ctx.clock.now());
 
         loop {
@@ -474,6 +1723,8 @@ impl TmuxMonitor {
             let metadata_deadline = rs
                 .metadata_sync_at
                 .unwrap_or_else(|| tokio::time::Instant::now() + LONG_SLEEP);
+            let hibernate_at = rs.hibernate_deadline(&self.config, self.clients_left_at);
+            let liveness_at = rs.liveness_deadline(&self.config);
 
             tokio::select! {
                 // Process control mode events
@@ -484,42 +1735,207 @@ impl TmuxMonitor {
                 }
 
                 // Throttle timer - emit pending output when in high-throughput mode
-                _ = tokio::time::sleep(throttle_sleep), if rs.pending_output_emit => {
+                _ = tokio::time::sleep(throttle_sleep), if rs.pending_output_emit && !rs.throttle_frozen => {
                     self.on_throttle_tick(emitter, &mut rs);
                 }
 
-                // Layout debounce timer - coalesce rapid layout changes (zoom-out)
-                _ = tokio::time::sleep(rs.layout_debounce), if rs.pending_layout_emit => {
-                    self.on_layout_debounce(emitter, &mut rs);
+                // Layout debounce timer - coalesce rapid layout changes (zoom-out)
+                _ = tokio::time::sleep(rs.layout_debounce), if rs.pending_layout_emit => {
+                    self.on_layout_debounce(emitter, &mut rs);
+                }
+
+                // Settling timer — `aggregator.tick(now)` drains the consolidated
+                // emit after compound-command events settle.
+                _ = tokio::time::sleep(settling_sleep), if self.aggregator.is_settling() => {
+                    self.on_settling_tick(emitter, &mut rs);
+                }
+
+                // Deferred metadata sync: refresh pane commands after output settles
+                _ = tokio::time::sleep_until(metadata_deadline), if rs.metadata_sync_at.is_some() => {
+                    self.on_metadata_sync(emitter, &mut rs).await;
+                }
+
+                // Event-driven sync: fast polling in copy mode, heartbeat when idle
+                _ = tokio::time::sleep_until(rs.next_sync_at) => {
+                    self.on_sync_tick(emitter, &mut rs).await;
+                }
+
+                // Nobody watching and nothing written: detach until a client
+                // comes back.
+                _ = tokio::time::sleep_until(hibernate_at.unwrap_or_else(|| tokio::time::Instant::now() + LONG_SLEEP)), if hibernate_at.is_some() => {
+                    info!("no clients or output, hibernating");
+                    self.hibernated = true;
+                    self.connection.graceful_close().await;
+                    break;
+                }
+
+                // tmux silent for a while: probe it, or give up on it when
+                // the last probe went unanswered.
+                _ = tokio::time::sleep_until(liveness_at.unwrap_or_else(|| tokio::time::Instant::now() + LONG_SLEEP)), if liveness_at.is_some() => {
+                    if !self.on_liveness_tick(emitter, &mut rs).await {
+                        break;
+                    }
+                }
+
+                // Quiet hours starting or ending.
+                _ = tokio::time::sleep_until(rs.dnd_check_at), if self.config.dnd_schedule.is_some() => {
+                    rs.dnd_check_at = tokio::time::Instant::now() + DND_CHECK_INTERVAL;
+                    self.refresh_do_not_disturb(emitter);
+                }
+
+                // SSH agent gone away (or back).
+                _ = tokio::time::sleep_until(rs.ssh_agent_check_at) => {
+                    rs.ssh_agent_check_at = tokio::time::Instant::now() + SSH_AGENT_CHECK_INTERVAL;
+                    if self.check_ssh_agent().await {
+                        if let Some(update) = self.aggregator.to_state_update() {
+                            emitter.emit_state(update);
+                        }
+                    }
+                }
+
+                // Git state and ports behind window status formats.
+                _ = tokio::time::sleep_until(rs.window_status_at) => {
+                    rs.window_status_at = tokio::time::Instant::now() + WINDOW_STATUS_INTERVAL;
+                    if self.refresh_window_status().await {
+                        if let Some(update) = self.aggregator.to_state_update() {
+                            emitter.emit_state(update);
+                        }
+                    }
                 }
 
-                // Settling timer — `aggregator.tick(now)` drains the consolidated
-                // emit after compound-command events settle.
-                _ = tokio::time::sleep(settling_sleep), if self.aggregator.is_settling() => {
-                    self.on_settling_tick(emitter, &mut rs);
+                // The status line's clock and `#()` jobs, every status-interval.
+                _ = tokio::time::sleep_until(rs.status_refresh_at), if self.aggregator.status_interval().is_some() => {
+                    self.on_status_interval(emitter, &mut rs).await;
                 }
 
-                // Deferred metadata sync: refresh pane commands after output settles
-                _ = tokio::time::sleep_until(metadata_deadline), if rs.metadata_sync_at.is_some() => {
-                    self.on_metadata_sync(emitter, &mut rs).await;
+                // A status-line job finished.
+                Some((command, output)) = self.status_jobs_rx.recv() => {
+                    let now = self.ctx.clock.now();
+                    if let Some(cmd) = self.aggregator.status_job_done(&command, &output, now) {
+                        self.send_status_refresh(emitter, &cmd).await;
+                    }
                 }
 
-                // Event-driven sync: fast polling in copy mode, heartbeat when idle
-                _ = tokio::time::sleep_until(rs.next_sync_at) => {
-                    self.on_sync_tick(emitter, &mut rs).await;
+                // Which commands the panes are running, for usage statistics.
+                _ = tokio::time::sleep_until(rs.usage_sample_at), if self.usage.is_some() => {
+                    rs.usage_sample_at = tokio::time::Instant::now() + USAGE_SAMPLE_INTERVAL;
+                    self.sample_usage();
                 }
 
                 // Handle external commands (resize, etc.)
                 cmd = self.command_rx.recv() => {
+                    #[cfg(feature = "test-support")]
+                    let cmd = match cmd {
+                        Some(MonitorCommand::Test(hook)) => {
+                            self.on_test_hook(emitter, &mut rs, hook).await;
+                            continue;
+                        }
+                        cmd => cmd,
+                    };
                     if !self.on_command(emitter, cmd).await {
                         break;
                     }
                 }
             }
+            self.report_output_trims(emitter, &mut rs);
+        }
+        if let Some(mut recorder) = self.usage.take() {
+            if let Err(e) = recorder.finish(usage::now_millis()) {
+                warn!(error = %e, "usage statistics not saved");
+            }
         }
         info!("run() exiting");
     }
 
+    /// Tell clients about lines trimmed from the frames just emitted, and
+    /// keep frames coming until one goes out without a marker.
+    fn report_output_trims<E: StateEmitter>(&mut self, emitter: &E, rs: &mut RunState) {
+        let trims = self.aggregator.take_output_trims();
+        if trims.is_empty() {
+            return;
+        }
+        for (pane_id, lines) in &trims {
+            emitter.emit(ServerEvent::OutputTrimmed {
+                pane_id: pane_id.clone(),
+                lines: *lines,
+            });
+        }
+        let pane_ids: Vec<String> = trims.into_iter().map(|(pane_id, _)| pane_id).collect();
+        rs.queue_trim_followup(&pane_ids, self.ctx.clock.now());
+    }
+
+    /// Probe tmux with a no-op after `liveness_timeout` of silence. Returns
+    /// `false`, with `lost` set, when the previous probe got no reply.
+    async fn on_liveness_tick<E: StateEmitter>(&mut self, emitter: &E, rs: &mut RunState) -> bool {
+        if rs.probe_sent_at.is_some_and(|sent| rs.last_event_at < sent) {
+            let reason = "tmux stopped responding".to_string();
+            warn!(%reason, "liveness probe unanswered, dropping the connection");
+            emitter.emit_error(format!("Control mode lost: {reason}"));
+            self.lost = Some(reason);
+            // Detach rather than kill, as everywhere: a hung tmux may still
+            // be alive enough to crash on a killed client.
+            self.connection.graceful_close().await;
+            return false;
+        }
+        rs.probe_sent_at = Some(tokio::time::Instant::now());
+        let probe = format!("display-message -p '{LIVENESS_MARKER}'");
+        if let Err(e) = self.connection.send_command(&probe).await {
+            let reason = format!("probe failed: {e}");
+            emitter.emit_error(format!("Control mode lost: {reason}"));
+            self.lost = Some(reason);
+            return false;
+        }
+        true
+    }
+
+    /// Refresh the status line and schedule the next refresh.
+    async fn on_status_interval<E: StateEmitter>(&mut self, emitter: &E, rs: &mut RunState) {
+        let interval = self.aggregator.status_interval().unwrap_or(LONG_SLEEP);
+        rs.status_refresh_at = tokio::time::Instant::now() + interval;
+        if let Some(cmd) = self.aggregator.refresh_status_line(self.ctx.clock.now()) {
+            self.send_status_refresh(emitter, &cmd).await;
+        }
+    }
+
+    async fn send_status_refresh<E: StateEmitter>(&mut self, emitter: &E, cmd: &str) {
+        if let Err(e) = self.connection.send_command(cmd).await {
+            emitter.emit_error(format!("Failed to refresh status line: {}", e));
+        }
+    }
+
+    /// Run status-line `#()` jobs in the background, through `run-shell` so
+    /// they run on the tmux host like tmux's own. Output comes back on
+    /// `status_jobs_rx`; a failed job reads as empty.
+    fn run_status_jobs(&self, commands: Vec<String>) {
+        for command in commands {
+            let ctx = Arc::clone(&self.ctx);
+            let tx = self.status_jobs_tx.clone();
+            tokio::spawn(async move {
+                let output = match run_shell(&ctx, command.clone(), "status-line:job").await {
+                    Ok(output) => output,
+                    Err(e) => {
+                        debug!(%command, error = %e, "status line job failed");
+                        String::new()
+                    }
+                };
+                let _ = tx.send((command, output));
+            });
+        }
+    }
+
+    /// Record a usage sample. A failing database stops recording rather than
+    /// warning every couple of seconds.
+    fn sample_usage(&mut self) {
+        let Some(recorder) = self.usage.as_mut() else {
+            return;
+        };
+        let panes = self.aggregator.pane_commands();
+        if let Err(e) = recorder.sample(&panes, usage::now_millis()) {
+            warn!(error = %e, "usage statistics disabled");
+            self.usage = None;
+        }
+    }
+
     /// Dispatch a single control-mode event. Returns `false` to stop the loop.
     ///
     /// Drives the sans-IO aggregator via `step(event) -> StepResult` and runs
@@ -543,17 +1959,29 @@ impl TmuxMonitor {
                 let msg = reason.unwrap_or_else(|| "disconnected".to_string());
                 warn!(reason = %msg, "control mode exit event");
                 emitter.emit_error(format!("Control mode exited: {}", msg));
+                self.lost = Some(format!("control mode exited: {msg}"));
                 return false;
             }
             None => {
                 warn!("control mode recv() returned None - connection closed");
                 emitter.emit_error("Control mode connection closed".to_string());
+                self.lost = Some("control mode connection closed".to_string());
                 return false;
             }
             Some(ev) => ev,
         };
 
         rs.last_event_at = tokio::time::Instant::now();
+        if matches!(
+            event,
+            ControlModeEvent::Output { .. } | ControlModeEvent::ExtendedOutput { .. }
+        ) {
+            rs.last_output_at = rs.last_event_at;
+        }
+        emitter.record_metric(MonitorMetric::ControlEvent);
+        if let ControlModeEvent::CommandResponse { success, .. } = event {
+            emitter.record_metric(MonitorMetric::CommandResponse { success });
+        }
 
         // Guard: suppress %session-changed events for other sessions. Creating
         // a new tmux session (even from a separate process) fires
@@ -574,13 +2002,41 @@ impl TmuxMonitor {
             }
         }
 
+        if let ControlModeEvent::SubscriptionChanged {
+            name,
+            window_id,
+            pane_id,
+            value,
+        } = &event
+        {
+            let watched =
+                options::subscription_index(name).and_then(|i| self.option_watches.get(i));
+            if let Some(option) = watched {
+                let target = match option.scope {
+                    OptionScope::Window => window_id.clone(),
+                    OptionScope::Pane => pane_id.clone(),
+                    OptionScope::Server | OptionScope::Session => None,
+                };
+                emitter.emit(ServerEvent::OptionChanged(OptionChange {
+                    scope: option.scope,
+                    name: option.name.clone(),
+                    target,
+                    value: value.clone(),
+                }));
+            }
+            return true;
+        }
+
         // tmux does not forward OSC 52 to a control-mode client, so a copy-mode
         // yank never reaches the per-pane OSC parser. Instead tmux fires
         // %paste-buffer-changed; read the buffer (read-only) and mirror it to the
         // web clipboard through the same emitter path as application OSC 52.
         if let ControlModeEvent::PasteBufferChanged { buffer_name } = &event {
             match crate::executor::show_buffer_named(buffer_name) {
-                Ok(text) if !text.is_empty() => emitter.write_clipboard("", text),
+                Ok(text) if !text.is_empty() => emitter.emit(ServerEvent::Clipboard {
+                    pane_id: String::new(),
+                    text,
+                }),
                 Ok(_) => {}
                 Err(e) => debug!(buffer = %buffer_name, error = %e, "show-buffer failed"),
             }
@@ -595,6 +2051,7 @@ impl TmuxMonitor {
         }
 
         let step = self.aggregator.step_at(event, self.ctx.clock.now());
+        self.report_capture_latencies(emitter);
 
         for effect in step.effects {
             match effect {
@@ -613,7 +2070,60 @@ impl TmuxMonitor {
                     }
                 }
                 SideEffect::WriteClipboard { pane_id, text } => {
-                    emitter.write_clipboard(&pane_id, text);
+                    emitter.emit(ServerEvent::Clipboard { pane_id, text });
+                }
+                SideEffect::Bell { pane_id } => match self.config.bell_policy {
+                    BellPolicy::Ignore => {}
+                    BellPolicy::Notify => emitter.emit(ServerEvent::Bell {
+                        pane_id,
+                        sound: false,
+                    }),
+                    BellPolicy::Sound => emitter.emit(ServerEvent::Bell {
+                        pane_id,
+                        sound: true,
+                    }),
+                },
+                SideEffect::SpokenText { pane_id, lines } => {
+                    emitter.emit(ServerEvent::SpokenText { pane_id, lines });
+                }
+                SideEffect::WidgetData { pane_id, data } => {
+                    emitter.emit(ServerEvent::WidgetData { pane_id, data });
+                }
+                SideEffect::Notify {
+                    pane_id,
+                    notification,
+                } => emitter.emit(ServerEvent::PaneNotify {
+                    pane_id,
+                    title: notification.title,
+                    body: notification.body,
+                }),
+                SideEffect::RunStatusJobs(commands) => self.run_status_jobs(commands),
+                SideEffect::PaneExited(summary) => emitter.pane_exited(&summary),
+                SideEffect::CommandOutput {
+                    run_id,
+                    output,
+                    success,
+                } => {
+                    let result = if success {
+                        Ok(output)
+                    } else {
+                        Err(output.trim_end().to_string())
+                    };
+                    match (self.pending_runs.remove(&run_id), result) {
+                        (
+                            Some(PendingRun {
+                                reply: Some(reply), ..
+                            }),
+                            result,
+                        ) => {
+                            let _ = reply.send(result);
+                        }
+                        (Some(PendingRun { command, .. }), Err(error)) => {
+                            warn!(%command, %error, "tmux command failed");
+                            emitter.emit(ServerEvent::CommandError { command, error });
+                        }
+                        _ => {}
+                    }
                 }
                 SideEffect::RefreshAfterWindowAdd => {
                     self.refresh_after_window_add(emitter).await;
@@ -702,6 +2212,7 @@ impl TmuxMonitor {
     /// the updated `tmux_cursor_x/y` when capture responses arrive.
     async fn refresh_panes<E: StateEmitter>(&mut self, emitter: &E, pane_ids: &[String]) {
         let queued_panes = self.aggregator.queue_captures(pane_ids);
+        self.note_captures_sent(&queued_panes);
 
         let mut commands: Vec<String> = vec![tmux_formats::LIST_PANES_CMD.to_string()];
         commands.extend(queued_panes.iter().map(|pane_id| capture_command(pane_id)));
@@ -711,6 +2222,34 @@ impl TmuxMonitor {
         }
     }
 
+    /// Start the latency clock for captures just queued.
+    fn note_captures_sent(&mut self, pane_ids: &[String]) {
+        let now = self.ctx.clock.now();
+        for pane_id in pane_ids {
+            self.capture_sent_at.insert(pane_id.clone(), now);
+        }
+    }
+
+    /// Report the latency of every capture the aggregator has stopped
+    /// waiting for. A capture dropped with its pane leaves too, unmeasured.
+    fn report_capture_latencies<E: StateEmitter>(&mut self, emitter: &E) {
+        if self.capture_sent_at.is_empty() {
+            return;
+        }
+        let now = self.ctx.clock.now();
+        let aggregator = &self.aggregator;
+        self.capture_sent_at.retain(|pane_id, sent_at| {
+            if aggregator.capture_pending(pane_id) {
+                return true;
+            }
+            if aggregator.has_pane(pane_id) {
+                let latency = now.saturating_duration_since(*sent_at);
+                emitter.record_metric(MonitorMetric::CaptureLatency(latency));
+            }
+            false
+        });
+    }
+
     /// Apply the throttle / debounce / immediate-emit policy for a state change.
     fn handle_state_change<E: StateEmitter>(
         &mut self,
@@ -718,31 +2257,28 @@ impl TmuxMonitor {
         rs: &mut RunState,
         change: &ChangeType,
     ) {
-        let is_output_event = matches!(change, ChangeType::PaneOutput { .. });
+        let output_pane = match change {
+            ChangeType::PaneOutput { pane_id } => Some(pane_id),
+            _ => None,
+        };
         let now = self.ctx.clock.now();
 
-        if is_output_event {
+        if output_pane.is_some() {
             rs.metadata_sync_at = Some(tokio::time::Instant::now() + rs.metadata_sync_delay);
         }
 
-        if is_output_event && rs.throttle_enabled {
-            rs.update_rate(&self.config, now);
-            if rs.in_throttle_mode {
-                rs.pending_output_emit = true;
-                if now.saturating_duration_since(rs.last_output_emit)
-                    >= self.config.throttle_interval
-                {
-                    if let Some(update) = self.aggregator.to_state_update() {
-                        emitter.emit_state(update);
-                    }
-                    rs.mark_emitted(now);
-                }
-            } else {
-                if rs.pending_output_first_at.is_none() {
-                    rs.pending_output_first_at = Some(now);
+        if let (Some(pane_id), false) = (output_pane, self.config.throttle_interval.is_zero()) {
+            let trimmed = self.aggregator.output_trimmed(pane_id);
+            let throttled = rs.record_output(&self.config, pane_id, trimmed, now);
+            if throttled
+                && !rs.throttle_frozen
+                && now.saturating_duration_since(rs.last_output_emit)
+                    >= rs.frame_interval(&self.config, pane_id)
+            {
+                if let Some(update) = self.aggregator.to_state_update() {
+                    emitter.emit_state(update);
                 }
-                rs.last_output_event_at = Some(now);
-                rs.pending_output_emit = true;
+                rs.mark_emitted(now);
             }
         } else if matches!(change, ChangeType::PaneLayout) {
             rs.pending_layout_emit = true;
@@ -755,6 +2291,39 @@ impl TmuxMonitor {
         }
     }
 
+    #[cfg(feature = "test-support")]
+    async fn on_test_hook<E: StateEmitter>(
+        &mut self,
+        emitter: &E,
+        rs: &mut RunState,
+        hook: TestHook,
+    ) {
+        match hook {
+            TestHook::InjectOutput { pane_id, data } => {
+                let event = ControlModeEvent::Output {
+                    pane_id,
+                    content: data,
+                };
+                self.on_control_event(emitter, rs, Some(event)).await;
+            }
+            TestHook::ClockAdvanced => {
+                let now = self.ctx.clock.now();
+                if rs.pending_output_emit && rs.compute_throttle_sleep(&self.config, now).is_zero()
+                {
+                    self.on_throttle_tick(emitter, rs);
+                }
+                if self
+                    .aggregator
+                    .settling_deadline()
+                    .is_some_and(|deadline| deadline <= now)
+                {
+                    self.on_settling_tick(emitter, rs);
+                }
+            }
+            TestHook::FreezeThrottle(frozen) => rs.throttle_frozen = frozen,
+        }
+    }
+
     /// High-throughput throttle window expired — flush whatever is pending.
     fn on_throttle_tick<E: StateEmitter>(&mut self, emitter: &E, rs: &mut RunState) {
         if let Some(update) = self.aggregator.to_state_update() {
@@ -797,11 +2366,9 @@ impl TmuxMonitor {
     /// `pane_current_command` reflects the post-exit shell prompt.
     async fn on_metadata_sync<E: StateEmitter>(&mut self, emitter: &E, rs: &mut RunState) {
         rs.metadata_sync_at = None;
-        if let Err(e) = self
-            .connection
-            .send_command(tmux_formats::LIST_PANES_CMD)
-            .await
-        {
+        let mut cmds = vec![tmux_formats::LIST_PANES_CMD.to_string()];
+        cmds.extend(sync_fields::details_command(&self.config.sync_fields));
+        if let Err(e) = self.connection.send_commands_batch(&cmds).await {
             emitter.emit_error(format!("Failed to sync metadata: {}", e));
         }
     }
@@ -809,6 +2376,8 @@ impl TmuxMonitor {
     /// Idle / copy-mode sync tick. Fast-polls copy mode (50ms) for cursor updates,
     /// otherwise heartbeats (15s) to catch out-of-band tmux mutations.
     async fn on_sync_tick<E: StateEmitter>(&mut self, emitter: &E, rs: &mut RunState) {
+        self.sync_widgets(emitter).await;
+
         let in_copy_mode = self.aggregator.has_pane_in_copy_mode();
         let is_idle = rs.last_event_at.elapsed() > rs.idle_threshold;
 
@@ -822,6 +2391,7 @@ impl TmuxMonitor {
             // cadence a slow response piled duplicate captures onto the
             // connection for panes whose capture was already in flight.
             let queued = self.aggregator.queue_captures(&copy_pane_ids);
+            self.note_captures_sent(&queued);
             let mut cmds = vec![tmux_formats::LIST_PANES_CMD.to_string()];
             for (pane_id, scroll_pos, height) in &copy_pane_info {
                 if !queued.contains(pane_id) {
@@ -840,20 +2410,91 @@ impl TmuxMonitor {
             }
             rs.next_sync_at = tokio::time::Instant::now() + rs.copy_mode_sync_interval;
         } else if is_idle {
-            let cmds = vec![
+            let mut cmds = vec![
                 tmux_formats::LIST_WINDOWS_CMD.to_string(),
                 tmux_formats::LIST_PANES_CMD.to_string(),
             ];
+            cmds.extend(sync_fields::details_command(&self.config.sync_fields));
             if let Err(e) = self.connection.send_commands_batch(&cmds).await {
                 emitter.emit_error(format!("Failed to heartbeat sync: {}", e));
             }
-            rs.next_sync_at = tokio::time::Instant::now() + rs.heartbeat_interval;
+            rs.next_sync_at = tokio::time::Instant::now() + self.config.heartbeat_interval;
+            self.notice_stale_panes(emitter);
         } else {
             let time_until_idle = rs.idle_threshold.saturating_sub(rs.last_event_at.elapsed());
             rs.next_sync_at = tokio::time::Instant::now() + time_until_idle;
         }
     }
 
+    /// Restart the session's remembered widgets once its panes are known,
+    /// then keep the widget file in step with the widget panes.
+    async fn sync_widgets<E: StateEmitter>(&mut self, emitter: &E) {
+        if self.aggregator.window_ids().is_empty() {
+            return;
+        }
+        let Some(remembered) = &self.remembered_widgets else {
+            let placed = widget::read_widgets(&self.config.session);
+            let restore = self.aggregator.widgets_to_restore(&placed);
+            if !restore.is_empty() {
+                let manager = WidgetManager::bundled();
+                let mut cmds: Vec<String> = restore
+                    .iter()
+                    .filter_map(|(pane_id, spec)| manager.restore_command(pane_id, spec).ok())
+                    .collect();
+                info!(count = cmds.len(), "restoring widget panes");
+                cmds.push(tmux_formats::LIST_PANES_CMD.to_string());
+                if let Err(e) = self.connection.send_commands_batch(&cmds).await {
+                    emitter.emit_error(format!("Failed to restore widgets: {}", e));
+                }
+            }
+            // Keep the file as read until the next tick sees the restored tags.
+            self.remembered_widgets = Some(placed);
+            return;
+        };
+        let placed = self.aggregator.placed_widgets();
+        if placed != *remembered {
+            if let Err(e) = widget::write_widgets(&self.config.session, &placed) {
+                warn!(error = %e, "could not remember widget panes");
+            }
+            self.remembered_widgets = Some(placed);
+        }
+    }
+
+    /// Log a cleanup suggestion naming panes that crossed the configured
+    /// `stale_pane_notice` threshold since the last heartbeat.
+    fn notice_stale_panes<E: StateEmitter>(&mut self, emitter: &E) {
+        let Some(threshold) = self.config.stale_pane_notice else {
+            return;
+        };
+        // Held back, not dropped: the panes are still stale afterwards.
+        if self.aggregator.do_not_disturb() {
+            return;
+        }
+        let stale =
+            self.aggregator
+                .stale_panes(self.ctx.clock.now(), self.ctx.clock.unix_now(), threshold);
+        self.stale_noticed
+            .retain(|id| stale.iter().any(|p| &p.pane_id == id));
+        let fresh: Vec<String> = stale
+            .iter()
+            .filter(|p| self.stale_noticed.insert(p.pane_id.clone()))
+            .map(|p| format!("{} ({} in {})", p.pane_id, p.command, p.window_name))
+            .collect();
+        if fresh.is_empty() {
+            return;
+        }
+        let days = threshold.as_secs() / (24 * 60 * 60);
+        emitter.log(
+            super::log::LogKind::Info,
+            format!(
+                "{} pane(s) idle for more than {} day(s), consider closing them: {}",
+                fresh.len(),
+                days,
+                fresh.join(", ")
+            ),
+        );
+    }
+
     /// Handle a `MonitorCommand` from external code. Returns false to stop the loop.
     async fn on_command<E: StateEmitter>(
         &mut self,
@@ -885,25 +2526,326 @@ impl TmuxMonitor {
                 }
                 true
             }
-            Some(MonitorCommand::RunCommand { command }) => {
+            Some(MonitorCommand::RunCommand { command, reply }) => {
                 debug!(%command, "processing RunCommand");
-                let unescaped = command.replace(" \\; ", " ; ");
+                // Re-written from the parsed argv so `\;` separators and
+                // quoting read the same here as through the executor.
+                let unescaped = match crate::command_line::split_commands(&command) {
+                    Ok(commands) if commands.is_empty() => return true,
+                    Ok(commands) => crate::command_line::join_commands(&commands),
+                    Err(e) => {
+                        emitter.emit_error(format!("Failed to run command: {e}"));
+                        return true;
+                    }
+                };
                 let is_compound = is_multi_step_run_shell(&unescaped);
                 if is_compound {
                     self.aggregator.arm_settling(self.ctx.clock.now());
                     debug!("settling armed for multi-step run-shell");
                 }
 
-                if let Err(e) = self.connection.send_command(&unescaped).await {
+                // Bracketed with a run id, so its output or error can be
+                // told apart from every self-issued command's.
+                self.next_run_id += 1;
+                let run_id = self.next_run_id;
+                let lines = run_command_lines(run_id, &unescaped);
+                if let Err(e) = self.connection.send_commands_batch(&lines).await {
+                    if let Some(reply) = reply {
+                        let _ = reply.send(Err(e.to_string()));
+                    }
                     emitter.emit_error(format!("Failed to run command: {}", e));
                     if is_compound {
                         self.aggregator.clear_settling();
                     }
                 } else {
-                    trace!(cmd = %unescaped, "sent command via control mode");
+                    trace!(cmd = %unescaped, run_id, "sent command via control mode");
+                    self.pending_runs
+                        .insert(run_id, PendingRun { command, reply });
+                }
+                true
+            }
+            Some(MonitorCommand::MouseEvents { pane_id, events }) => {
+                let Some(mode) = self.aggregator.pane_mouse_mode(&pane_id) else {
+                    debug!(%pane_id, "dropping mouse events for unknown pane");
+                    return true;
+                };
+                let cmds = mouse_commands(&pane_id, &events, mode);
+                if cmds.is_empty() {
+                    return true;
+                }
+                if let Err(e) = self.connection.send_commands_batch(&cmds).await {
+                    emitter.emit_error(format!("Failed to send mouse events: {}", e));
+                }
+                true
+            }
+            Some(MonitorCommand::SendKey { pane_id, key }) => {
+                let Some(mode) = self.aggregator.pane_keyboard_mode(&pane_id) else {
+                    debug!(%pane_id, "dropping key for unknown pane");
+                    return true;
+                };
+                let cmds = key_commands(&pane_id, &key, mode);
+                if cmds.is_empty() {
+                    return true;
+                }
+                if let Err(e) = self.connection.send_commands_batch(&cmds).await {
+                    emitter.emit_error(format!("Failed to send key: {}", e));
+                }
+                true
+            }
+            Some(MonitorCommand::SendText { pane_id, text }) => {
+                let cmds = text_commands(&pane_id, &text);
+                if cmds.is_empty() {
+                    return true;
+                }
+                if let Err(e) = self.connection.send_commands_batch(&cmds).await {
+                    emitter.emit_error(format!("Failed to send text: {}", e));
+                }
+                true
+            }
+            Some(MonitorCommand::DragResize {
+                window_id,
+                from,
+                to,
+            }) => {
+                let cmds = self.aggregator.drag_resize_commands(&window_id, from, to);
+                if cmds.is_empty() {
+                    trace!(%window_id, ?from, "drag did not start on a pane border");
+                    return true;
+                }
+                if let Err(e) = self.connection.send_commands_batch(&cmds).await {
+                    emitter.emit_error(format!("Failed to resize pane: {}", e));
+                }
+                true
+            }
+            Some(MonitorCommand::BeginPaneDrag { pane_id }) => {
+                if let Some((_, Some(_))) = self.pane_drag.replace((pane_id, None)) {
+                    emitter.emit(ServerEvent::DragTarget(None));
+                }
+                true
+            }
+            Some(MonitorCommand::DragPaneOver { x, y }) => {
+                if let Some((source, hint)) = &mut self.pane_drag {
+                    let target = self.aggregator.pane_drop_target(source, x, y);
+                    if target != *hint {
+                        emitter.emit(ServerEvent::DragTarget(target.clone()));
+                        *hint = target;
+                    }
+                }
+                true
+            }
+            Some(MonitorCommand::DropPaneAt { x, y }) => {
+                let Some((source, hint)) = self.pane_drag.take() else {
+                    debug!("drop with no pane drag in progress");
+                    return true;
+                };
+                if hint.is_some() {
+                    emitter.emit(ServerEvent::DragTarget(None));
+                }
+                let Some(target) = self.aggregator.pane_drop_target(&source, x, y) else {
+                    trace!(%source, x, y, "pane drop had no target");
+                    return true;
+                };
+                if let Err(e) = self.connection.send_command(&drop_command(&target)).await {
+                    emitter.emit_error(format!("Failed to move pane: {}", e));
+                }
+                true
+            }
+            Some(MonitorCommand::PreviewSplit {
+                pane_id,
+                direction,
+                percent,
+                reply,
+            }) => {
+                let _ = reply.send(self.aggregator.preview_split(&pane_id, direction, percent));
+                true
+            }
+            Some(MonitorCommand::GetScrollback {
+                pane_id,
+                lines,
+                reply,
+            }) => {
+                let _ = reply.send(self.aggregator.pane_scrollback(&pane_id, lines));
+                true
+            }
+            Some(MonitorCommand::MaskCaptured {
+                pane_id,
+                mut content,
+                reply,
+            }) => {
+                self.aggregator.mask_captured(&pane_id, &mut content);
+                let _ = reply.send(content);
+                true
+            }
+            Some(MonitorCommand::GetCommandHistory { pane_id, reply }) => {
+                let _ = reply.send(self.aggregator.command_history(&pane_id));
+                true
+            }
+            Some(MonitorCommand::SetDoNotDisturb { mode }) => {
+                // Session-scoped (no -g), like the settings in `enforce_settings`.
+                let cmd = match mode {
+                    DndMode::Auto => format!("set -u {}", tmux_options::DND),
+                    DndMode::On => format!("set {} on", tmux_options::DND),
+                    DndMode::Off => format!("set {} off", tmux_options::DND),
+                };
+                if let Err(e) = self.connection.send_command(&cmd).await {
+                    emitter.emit_error(format!("Failed to set do-not-disturb: {}", e));
+                }
+                self.dnd_mode = mode;
+                self.refresh_do_not_disturb(emitter);
+                true
+            }
+            Some(MonitorCommand::SetTerminalTheme { theme }) => {
+                if self.aggregator.set_terminal_theme(theme) {
+                    for cmd in self.aggregator.collect_theme_report_commands() {
+                        if let Err(e) = self.connection.send_command(&cmd).await {
+                            emitter.emit_error(format!("Failed to report terminal colours: {}", e));
+                        }
+                    }
+                    if let Some(update) = self.aggregator.to_state_update() {
+                        emitter.emit_state(update);
+                    }
+                }
+                true
+            }
+            Some(MonitorCommand::SetClientFocus { focused }) => {
+                self.aggregator.set_client_focus(focused);
+                for cmd in self.aggregator.collect_focus_commands() {
+                    if let Err(e) = self.connection.send_command(&cmd).await {
+                        emitter.emit_error(format!("Failed to report focus: {}", e));
+                    }
+                }
+                true
+            }
+            Some(MonitorCommand::ResumePane { pane_id, reply }) => {
+                let _ = reply.send(self.set_pane_flow(emitter, &pane_id, false).await);
+                true
+            }
+            Some(MonitorCommand::DiscardPaneOutput { pane_id, reply }) => {
+                let _ = reply.send(self.set_pane_flow(emitter, &pane_id, true).await);
+                true
+            }
+            Some(MonitorCommand::InjectSecret {
+                pane_id,
+                name,
+                reply,
+            }) => {
+                let _ = reply.send(self.inject_secret(&pane_id, name).await);
+                true
+            }
+            Some(MonitorCommand::FixSshAgent { reply }) => {
+                let ctx = Arc::clone(&self.ctx);
+                let result = repoint_ssh_agent(&ctx, &self.config.session).await;
+                if self.check_ssh_agent().await {
+                    if let Some(update) = self.aggregator.to_state_update() {
+                        emitter.emit_state(update);
+                    }
+                }
+                let _ = reply.send(result.map(|()| self.aggregator.ssh_agent()));
+                true
+            }
+            Some(MonitorCommand::SetTuning { tuning, reply }) => {
+                self.config.apply_tuning(&tuning);
+                info!(tuning = ?self.config.tuning(), "monitor retuned");
+                // New sync fields apply now rather than at the next sync.
+                if tuning.sync_fields.is_some() {
+                    match sync_fields::details_command(&self.config.sync_fields) {
+                        Some(cmd) => {
+                            if let Err(e) = self.connection.send_command(&cmd).await {
+                                emitter.emit_error(format!("Failed to sync pane details: {}", e));
+                            }
+                        }
+                        None => {
+                            self.aggregator.clear_pane_details();
+                            if let Some(update) = self.aggregator.to_state_update() {
+                                emitter.emit_state(update);
+                            }
+                        }
+                    }
+                }
+                let _ = reply.send(self.config.tuning());
+                true
+            }
+            Some(MonitorCommand::SetClientCount { count }) => {
+                self.client_count = count;
+                self.clients_left_at = match count {
+                    0 => self
+                        .clients_left_at
+                        .or_else(|| Some(tokio::time::Instant::now())),
+                    _ => None,
+                };
+                for (_, data) in self.window_status.values_mut() {
+                    data.clients = count;
+                }
+                if self.apply_window_status() {
+                    if let Some(update) = self.aggregator.to_state_update() {
+                        emitter.emit_state(update);
+                    }
+                }
+                true
+            }
+            Some(MonitorCommand::ResendFull) => {
+                if let Some(update) = self.aggregator.full_update() {
+                    emitter.emit_state(update);
+                }
+                true
+            }
+            Some(MonitorCommand::GetFullState { reply }) => {
+                let full = self
+                    .aggregator
+                    .full_update()
+                    .map(|update| (update, self.aggregator.delta_seq()));
+                let _ = reply.send(full);
+                true
+            }
+            Some(MonitorCommand::WatchOption { option, reply }) => {
+                let _ = reply.send(self.watch_option(option).await);
+                true
+            }
+            Some(MonitorCommand::GetStalePanes { min_idle, reply }) => {
+                let stale = self.aggregator.stale_panes(
+                    self.ctx.clock.now(),
+                    self.ctx.clock.unix_now(),
+                    min_idle,
+                );
+                let _ = reply.send(stale);
+                true
+            }
+            Some(MonitorCommand::GetExitSummaries { reply }) => {
+                let _ = reply.send(self.aggregator.exit_summaries(self.ctx.clock.now()));
+                true
+            }
+            Some(MonitorCommand::GetWindowMru { reply }) => {
+                let _ = reply.send(self.aggregator.window_mru(self.ctx.clock.now()));
+                true
+            }
+            Some(MonitorCommand::SwitchLastWindowCycle {
+                reverse,
+                release,
+                reply,
+            }) => {
+                let now = self.ctx.clock.now();
+                if release {
+                    self.aggregator.end_window_cycle();
+                } else if let Some(window_id) = self.aggregator.step_window_cycle(reverse, now) {
+                    let cmd = format!("select-window -t {window_id}");
+                    if let Err(e) = self.connection.send_command(&cmd).await {
+                        emitter.emit_error(format!("Failed to switch window: {}", e));
+                    }
                 }
+                let _ = reply.send(self.aggregator.window_mru(now));
+                true
+            }
+            Some(MonitorCommand::Float { op, reply }) => {
+                let _ = reply.send(self.run_float_op(&op).await);
                 true
             }
+            Some(MonitorCommand::PaneGroup { op, reply }) => {
+                let _ = reply.send(self.run_group_op(&op).await);
+                true
+            }
+            // Taken off the channel by the run loop.
+            #[cfg(feature = "test-support")]
+            Some(MonitorCommand::Test(_)) => true,
             Some(MonitorCommand::Shutdown) => {
                 info!("received shutdown command, gracefully closing");
                 self.connection.graceful_close().await;
@@ -943,7 +2885,6 @@ fn is_multi_step_run_shell(command: &str) -> bool {
 #[allow(clippy::unwrap_used, clippy::expect_used)]
 mod tests {
     use super::*;
-    use std::sync::{Arc, Mutex};
 
     #[test]
     fn test_config_default() {
@@ -952,6 +2893,14 @@ mod tests {
         assert!(!config.create_session);
     }
 
+    #[test]
+    fn epochs_strictly_increase() {
+        let a = new_epoch();
+        let b = new_epoch();
+        assert!(b > a);
+        assert!(a > 0);
+    }
+
     #[test]
     fn test_is_multi_step_run_shell_matches_pane_group_scripts() {
         // Real-world commands the frontend sends through SEND_TMUX_COMMAND.
@@ -999,6 +2948,46 @@ mod tests {
         (cfg, rs)
     }
 
+    #[test]
+    fn hibernates_after_clients_and_output_stop() {
+        let (mut cfg, mut rs) = run_state_with_now(Instant::now());
+        let left = tokio::time::Instant::now();
+        assert_eq!(rs.hibernate_deadline(&cfg, Some(left)), None);
+
+        cfg.hibernate_after = Some(Duration::from_secs(60));
+        assert_eq!(rs.hibernate_deadline(&cfg, None), None);
+        rs.last_output_at = left - Duration::from_secs(5);
+        assert_eq!(
+            rs.hibernate_deadline(&cfg, Some(left)),
+            Some(left + Duration::from_secs(60))
+        );
+        // Output after the last client left pushes it back.
+        rs.last_output_at = left + Duration::from_secs(30);
+        assert_eq!(
+            rs.hibernate_deadline(&cfg, Some(left)),
+            Some(left + Duration::from_secs(90))
+        );
+    }
+
+    #[test]
+    fn liveness_probe_waits_for_silence_then_for_its_reply() {
+        let (mut cfg, mut rs) = run_state_with_now(Instant::now());
+        cfg.liveness_timeout = None;
+        assert_eq!(rs.liveness_deadline(&cfg), None);
+
+        let timeout = Duration::from_secs(30);
+        cfg.liveness_timeout = Some(timeout);
+        let last = rs.last_event_at;
+        assert_eq!(rs.liveness_deadline(&cfg), Some(last + timeout));
+        // Probe out and unanswered: the deadline runs from the probe.
+        let sent = last + timeout;
+        rs.probe_sent_at = Some(sent);
+        assert_eq!(rs.liveness_deadline(&cfg), Some(sent + timeout));
+        // Any event after it is an answer.
+        rs.last_event_at = sent + Duration::from_millis(5);
+        assert_eq!(rs.liveness_deadline(&cfg), Some(rs.last_event_at + timeout));
+    }
+
     #[test]
     fn compute_throttle_sleep_returns_long_sleep_when_nothing_pending() {
         let now = Instant::now();
@@ -1006,12 +2995,31 @@ mod tests {
         assert_eq!(rs.compute_throttle_sleep(&cfg, now), LONG_SLEEP);
     }
 
+    /// Queue output from `pane_id`, flooding it into throttle mode first when
+    /// `throttled`.
+    fn queue_output(
+        cfg: &MonitorConfig,
+        rs: &mut RunState,
+        pane_id: &str,
+        now: Instant,
+        throttled: bool,
+    ) {
+        let events = if throttled {
+            cfg.throttle_threshold + 1
+        } else {
+            1
+        };
+        for _ in 0..events {
+            rs.record_output(cfg, pane_id, false, now);
+        }
+        assert_eq!(rs.pane_output[pane_id].in_throttle_mode, throttled);
+    }
+
     #[test]
     fn compute_throttle_sleep_zero_when_throttle_interval_already_passed() {
         let now = Instant::now();
         let (cfg, mut rs) = run_state_with_now(now);
-        rs.pending_output_emit = true;
-        rs.in_throttle_mode = true;
+        queue_output(&cfg, &mut rs, "%1", now, true);
         // Pretend we last emitted >>throttle_interval ago.
         rs.last_output_emit = now - cfg.throttle_interval - Duration::from_millis(10);
         assert_eq!(
@@ -1025,42 +3033,107 @@ mod tests {
     fn compute_throttle_sleep_remaining_interval_when_recent_emit() {
         let now = Instant::now();
         let (cfg, mut rs) = run_state_with_now(now);
-        rs.pending_output_emit = true;
-        rs.in_throttle_mode = true;
+        queue_output(&cfg, &mut rs, "%1", now, true);
         rs.last_output_emit = now - Duration::from_millis(10);
         let remaining = rs.compute_throttle_sleep(&cfg, now);
         assert!(remaining > Duration::ZERO);
         assert!(remaining <= cfg.throttle_interval);
     }
 
+    #[test]
+    fn quiet_pane_output_is_not_held_back_by_a_flooding_pane() {
+        let now = Instant::now();
+        let (cfg, mut rs) = run_state_with_now(now);
+        rs.last_output_emit = now;
+        queue_output(&cfg, &mut rs, "%1", now, true);
+        assert_eq!(rs.compute_throttle_sleep(&cfg, now), cfg.throttle_interval);
+
+        // A keystroke echo in another pane goes out after the debounce,
+        // ahead of the flooding pane's next throttle slot.
+        queue_output(&cfg, &mut rs, "%2", now, false);
+        assert_eq!(rs.compute_throttle_sleep(&cfg, now), cfg.output_debounce);
+    }
+
+    #[test]
+    fn max_latency_bounds_every_pending_pane() {
+        let now = Instant::now();
+        let (mut cfg, mut rs) = run_state_with_now(now);
+        cfg.throttle_interval = Duration::from_millis(200);
+        cfg.output_debounce = Duration::from_millis(30);
+        rs.last_output_emit = now;
+        queue_output(&cfg, &mut rs, "%1", now, true);
+        assert_eq!(rs.compute_throttle_sleep(&cfg, now), cfg.max_latency);
+
+        // Output that keeps extending the debounce is still bounded.
+        let (cfg, mut rs) = run_state_with_now(now);
+        for ms in [0, 10, 20, 30, 40] {
+            rs.record_output(&cfg, "%2", false, now + Duration::from_millis(ms));
+        }
+        assert_eq!(
+            rs.compute_throttle_sleep(&cfg, now + Duration::from_millis(40)),
+            cfg.max_latency - Duration::from_millis(40)
+        );
+    }
+
+    #[test]
+    fn tuning_overrides_only_the_fields_it_sets() {
+        let mut cfg = MonitorConfig::default();
+        let tuning: MonitorTuning = serde_json::from_value(
+            serde_json::json!({ "throttle_interval_ms": 100, "max_latency_ms": 250 }),
+        )
+        .unwrap();
+        cfg.apply_tuning(&tuning);
+        assert_eq!(cfg.throttle_interval, Duration::from_millis(100));
+        assert_eq!(cfg.max_latency, Duration::from_millis(250));
+        assert_eq!(
+            cfg.throttle_threshold,
+            MonitorConfig::default().throttle_threshold
+        );
+        assert_eq!(cfg.tuning().output_debounce_ms, Some(16));
+
+        let tuning: MonitorTuning = serde_json::from_value(serde_json::json!({
+            "heartbeat_interval_ms": 2000,
+            "sync_fields": ["cwd", "pid"],
+        }))
+        .unwrap();
+        cfg.apply_tuning(&tuning);
+        assert_eq!(cfg.heartbeat_interval, Duration::from_secs(2));
+        assert_eq!(cfg.sync_fields, [SyncField::Cwd, SyncField::Pid]);
+        assert_eq!(cfg.throttle_interval, Duration::from_millis(100));
+    }
+
     #[test]
     fn mark_emitted_resets_pending_trackers() {
         let now = Instant::now();
-        let (_cfg, mut rs) = run_state_with_now(now);
-        rs.pending_output_emit = true;
-        rs.pending_output_first_at = Some(now);
-        rs.last_output_event_at = Some(now);
+        let (cfg, mut rs) = run_state_with_now(now);
+        queue_output(&cfg, &mut rs, "%1", now, false);
+        queue_output(&cfg, &mut rs, "%2", now, true);
         let later = now + Duration::from_millis(50);
         rs.mark_emitted(later);
         assert_eq!(rs.last_output_emit, later);
         assert!(!rs.pending_output_emit);
-        assert_eq!(rs.pending_output_first_at, None);
-        assert_eq!(rs.last_output_event_at, None);
+        assert!(rs
+            .pane_output
+            .values()
+            .all(|pane| pane.pending_since.is_none() && pane.last_event_at.is_none()));
+        assert!(
+            rs.pane_output["%2"].in_throttle_mode,
+            "rates outlive the emit"
+        );
     }
 
     #[test]
     fn update_rate_enters_throttle_above_threshold() {
         let now = Instant::now();
-        let (cfg, mut rs) = run_state_with_now(now);
-        assert!(!rs.in_throttle_mode);
+        let (cfg, _rs) = run_state_with_now(now);
+        let mut rate = PaneOutputRate::new(now);
         // Push the counter past `throttle_threshold` within the same window.
-        // The first call resets `rate_window_start` to `now`, so subsequent
-        // calls accumulate. We loop one past the threshold to cross it.
+        // We loop one past the threshold to cross it.
         for _ in 0..=cfg.throttle_threshold {
-            rs.update_rate(&cfg, now);
+            rate.update_rate(&cfg, now);
         }
         assert!(
-            rs.in_throttle_mode,
+            rate.in_throttle_mode,
             "exceeding throttle_threshold should flip the mode"
         );
     }
@@ -1068,17 +3141,24 @@ mod tests {
     #[test]
     fn update_rate_exits_throttle_when_rate_drops() {
         let now = Instant::now();
-        let (cfg, mut rs) = run_state_with_now(now);
-        rs.in_throttle_mode = true;
-        rs.rate_event_count = 0;
-        rs.rate_window_start = now;
+        let (cfg, _rs) = run_state_with_now(now);
+        let mut rate = PaneOutputRate::new(now);
+        rate.in_throttle_mode = true;
         // Advance the clock past the rate window so update_rate evaluates
         // the hysteresis on the previous window's count (zero) and exits.
         let after_window = now + cfg.rate_window + Duration::from_millis(10);
-        rs.update_rate(&cfg, after_window);
+        rate.update_rate(&cfg, after_window);
         assert!(
-            !rs.in_throttle_mode,
+            !rate.in_throttle_mode,
             "a fully-quiet rate window should exit throttle mode"
         );
     }
+
+    #[test]
+    fn bell_policy_parses_known_names() {
+        assert_eq!(BellPolicy::parse("sound"), Some(BellPolicy::Sound));
+        assert_eq!(BellPolicy::parse("off"), Some(BellPolicy::Ignore));
+        assert_eq!(BellPolicy::parse("loud"), None);
+        assert_eq!(BellPolicy::default(), BellPolicy::Notify);
+    }
 }
@@ -22,8 +22,15 @@
 /// assert_eq!(decode_octal(r"Hello\033[1mWorld"), b"Hello\x1b[1mWorld".to_vec());
 /// ```
 pub fn decode_octal(input: &str) -> Vec<u8> {
-    let mut result = Vec::with_capacity(input.len());
-    let bytes = input.as_bytes();
+    decode_octal_bytes(input.as_bytes())
+}
+
+/// [`decode_octal`] over raw bytes. tmux passes bytes from 0x80 up through
+/// unescaped, so a line of pane output need not be valid UTF-8. Anything
+/// that isn't a complete `\xxx` escape (truncated, non-octal, or above
+/// `\377`) is copied through as-is.
+pub fn decode_octal_bytes(bytes: &[u8]) -> Vec<u8> {
+    let mut result = Vec::with_capacity(bytes.len());
     let mut i = 0;
 
     while i < bytes.len() {
@@ -121,6 +128,16 @@ mod tests {
         assert_eq!(decode_octal(r"\999"), b"\\999".to_vec());
     }
 
+    #[test]
+    fn test_decode_raw_bytes() {
+        // Half of a UTF-8 sequence, as when tmux splits output mid-character
+        assert_eq!(
+            decode_octal_bytes(b"\xe2\x94\\134"),
+            vec![0xe2, 0x94, b'\\']
+        );
+        assert_eq!(decode_octal_bytes(b"\\777"), b"\\777".to_vec());
+    }
+
     #[test]
     fn test_decode_bell() {
         // BEL = 007 octal = 0x07
@@ -3,6 +3,11 @@
 //! Parses OSC sequences from raw terminal output:
 //! - OSC 8: Hyperlinks (URL associations per text region)
 //! - OSC 52: Clipboard operations
+//! - OSC 4/10/11 and 104/110/111: Palette and default colour overrides
+//! - OSC 22: Mouse pointer shape
+//! - OSC 9 and OSC 777: Desktop notifications (see [`PaneNotification`])
+//! - OSC 7331: Widget data (see [`WIDGET_DATA_OSC`])
+//! - OSC 133: Shell integration prompt/command/output marks (see [`PromptMark`])
 
 use std::collections::HashMap;
 
@@ -13,6 +18,172 @@ use std::collections::HashMap;
 /// without bound.
 const MAX_PENDING_OSC: usize = 64 * 1024;
 
+/// tmuxy's private OSC for widget data: `ESC ] 7331 ; <json> BEL`. A widget
+/// program writes it to send the frontend structured data alongside (or
+/// instead of) what it draws. JSON escapes every control character, so the
+/// body can't end the sequence early.
+pub const WIDGET_DATA_OSC: &str = "7331";
+
+/// Deepest OSC 22 push stack kept; older entries fall off the bottom.
+const MAX_POINTER_STACK: usize = 16;
+
+/// Notifications held between takes; a program spamming them loses the
+/// oldest.
+const MAX_PENDING_NOTIFICATIONS: usize = 8;
+
+/// Characters kept of a notification's title or body.
+const MAX_NOTIFICATION_CHARS: usize = 1024;
+
+/// Shell integration marks held between takes. A shell sends four per
+/// command, so this only fills when nothing takes them.
+const MAX_PENDING_MARKS: usize = 64;
+
+/// A boundary a shell's OSC 133 ("semantic prompt") integration marks.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SemanticMark {
+    /// `A`: a prompt is about to be drawn.
+    PromptStart,
+    /// `B`: the prompt is drawn and the command line starts.
+    CommandStart,
+    /// `C`: the command line was entered and its output starts. Carries the
+    /// command line when the shell sent one (`cmdline_url=` or `cmdline=`).
+    OutputStart { command: Option<String> },
+    /// `D`: the command finished, with its exit status when given.
+    CommandEnd { exit_status: Option<i32> },
+}
+
+impl SemanticMark {
+    /// Parse the body of an OSC 133 sequence after `133;`.
+    fn parse(osc: &str) -> Option<Self> {
+        let (kind, params) = osc.split_once(';').unwrap_or((osc, ""));
+        Some(match kind {
+            "A" => Self::PromptStart,
+            "B" => Self::CommandStart,
+            "C" => {
+                let command = params
+                    .split(';')
+                    .find_map(|param| param.strip_prefix("cmdline_url=").and_then(percent_decode));
+                // kitty's `cmdline=` runs to the end, semicolons and all.
+                let command = command.or_else(|| {
+                    params
+                        .find("cmdline=")
+                        .filter(|at| *at == 0 || params[..*at].ends_with(';'))
+                        .map(|at| params[at + "cmdline=".len()..].to_string())
+                });
+                Self::OutputStart { command }
+            }
+            "D" => Self::CommandEnd {
+                exit_status: params.split(';').next().and_then(|s| s.parse().ok()),
+            },
+            _ => return None,
+        })
+    }
+}
+
+/// Where in a `process()` call's output a [`SemanticMark`] arrived. The
+/// emulator, fed the output up to `offset`, knows the exact cursor position;
+/// `scrolled_rows` is how many rows had scrolled off the top by then.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PromptMark {
+    pub mark: SemanticMark,
+    pub offset: usize,
+    pub scrolled_rows: u64,
+}
+
+/// A desktop notification a pane's application asked for, with iTerm2's
+/// `OSC 9 ; body` (no title) or urxvt's `OSC 777 ; notify ; title ; body`.
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize)]
+pub struct PaneNotification {
+    pub title: String,
+    pub body: String,
+}
+
+impl PaneNotification {
+    /// Parse the body of an OSC 9 or OSC 777 sequence. ConEmu reuses OSC 9
+    /// for numbered commands (`9 ; 4 ; 1 ; 50` is a progress report), which
+    /// aren't notifications.
+    fn parse(osc: &str) -> Option<Self> {
+        let (title, body) = if let Some(body) = osc.strip_prefix("9;") {
+            let command = body.split(';').next().unwrap_or_default();
+            if body.contains(';') && command.bytes().all(|b| b.is_ascii_digit()) {
+                return None;
+            }
+            ("", body)
+        } else {
+            let rest = osc.strip_prefix("777;notify;")?;
+            rest.split_once(';').unwrap_or((rest, ""))
+        };
+        let clean = |text: &str| -> String {
+            text.chars()
+                .filter(|c| !c.is_control())
+                .take(MAX_NOTIFICATION_CHARS)
+                .collect()
+        };
+        let notification = Self {
+            title: clean(title),
+            body: clean(body),
+        };
+        (!notification.title.is_empty() || !notification.body.is_empty()).then_some(notification)
+    }
+}
+
+/// CSS cursor for an OSC 22 shape name. Applications send either CSS names
+/// (kitty, foot) or X11 cursor-font names (xterm); anything else is ignored.
+fn css_pointer_shape(name: &str) -> Option<&'static str> {
+    const CSS: &[&str] = &[
+        "default",
+        "text",
+        "pointer",
+        "crosshair",
+        "move",
+        "wait",
+        "progress",
+        "help",
+        "not-allowed",
+        "no-drop",
+        "grab",
+        "grabbing",
+        "cell",
+        "copy",
+        "alias",
+        "context-menu",
+        "vertical-text",
+        "zoom-in",
+        "zoom-out",
+        "all-scroll",
+        "col-resize",
+        "row-resize",
+        "e-resize",
+        "w-resize",
+        "n-resize",
+        "s-resize",
+        "ne-resize",
+        "nw-resize",
+        "se-resize",
+        "sw-resize",
+        "ew-resize",
+        "ns-resize",
+        "nesw-resize",
+        "nwse-resize",
+    ];
+    if let Some(css) = CSS.iter().find(|css| **css == name) {
+        return Some(css);
+    }
+    Some(match name {
+        "xterm" => "text",
+        "left_ptr" | "arrow" | "top_left_arrow" => "default",
+        "hand1" | "hand2" => "pointer",
+        "watch" => "wait",
+        "left_ptr_watch" => "progress",
+        "question_arrow" => "help",
+        "fleur" => "move",
+        "cross" | "tcross" => "crosshair",
+        "sb_h_double_arrow" => "ew-resize",
+        "sb_v_double_arrow" => "ns-resize",
+        _ => return None,
+    })
+}
+
 /// OSC parser state for a single pane
 #[derive(Debug, Default)]
 pub struct OscParser {
@@ -23,12 +194,32 @@ pub struct OscParser {
     /// the vt100 rows `extract_cells_with_urls` queries.
     cursor_row: u32,
     cursor_col: u32,
+    /// Rows that have scrolled off the top of the main screen since the pane
+    /// was created, counted as `cursor_row` is. Anchors command history
+    /// positions (OSC 133) as the screen scrolls under them.
+    scrolled_rows: u64,
+    /// Whether the pane was on the alternate screen as of the last
+    /// `sync_cursor`; its scrolling doesn't move the main screen's rows.
+    alternate_screen: bool,
     /// Visible height of the pane, in rows. Used to scroll `cell_urls` when
     /// output pushes the cursor past the bottom row, keeping the map aligned
     /// with the vt100 screen and bounded to the viewport.
     viewport_height: u32,
     /// Pending clipboard content (from OSC 52)
     pub pending_clipboard: Option<String>,
+    /// Widget data not yet handed on, oldest first (from OSC 7331)
+    pending_widget_data: Vec<serde_json::Value>,
+    /// Notifications not yet handed on, oldest first (from OSC 9/777)
+    pending_notifications: Vec<PaneNotification>,
+    /// Colours the application set with OSC 4/10/11
+    colors: super::palette::TerminalColors,
+    /// CSS cursor the application asked for with OSC 22; empty for the
+    /// terminal's default
+    pointer_shape: String,
+    /// Shapes saved by OSC 22 `>` pushes, restored by `<` pops
+    pointer_stack: Vec<String>,
+    /// Shell integration marks not yet taken, oldest first (from OSC 133)
+    pending_marks: Vec<PromptMark>,
     /// Hyperlink URL per cell coordinate: (row, col) -> url
     pub cell_urls: HashMap<(u32, u32), String>,
     /// An incomplete OSC sequence split across `%output` chunks, carried into
@@ -44,8 +235,9 @@ impl OscParser {
 
     /// Reset parser state (called on pane resize and full capture refresh so
     /// stale URL mappings don't attach to new content at the same coordinates,
-    /// and `cell_urls` can't grow across a reflow). Preserves `viewport_height`,
-    /// which is a property of the pane, not the content.
+    /// and `cell_urls` can't grow across a reflow). Preserves `viewport_height`
+    /// and the colour and pointer overrides, which are properties of the
+    /// pane, not the content.
     pub fn reset(&mut self) {
         self.active_hyperlink = None;
         self.cursor_row = 0;
@@ -61,6 +253,20 @@ impl OscParser {
         self.viewport_height = height;
     }
 
+    /// Align the tracked cursor with the emulator's after a `process()`
+    /// call. Cursor addressing, which this parser doesn't follow, would
+    /// otherwise leave `cursor_row` off and the scroll count with it.
+    pub fn sync_cursor(&mut self, row: u16, col: u16, alternate_screen: bool) {
+        self.cursor_row = u32::from(row);
+        self.cursor_col = u32::from(col);
+        self.alternate_screen = alternate_screen;
+    }
+
+    /// Rows scrolled off the top of the main screen so far.
+    pub fn scrolled_rows(&self) -> u64 {
+        self.scrolled_rows
+    }
+
     /// Scroll the cell→URL map up by one row: row 0 falls off, every other row
     /// shifts up one. Mirrors what the vt100 screen does when output overflows
     /// the bottom, keeping `cell_urls` aligned with visible rows and bounded.
@@ -94,7 +300,7 @@ impl OscParser {
                 // OSC sequence: ESC ] ... ST or ESC ] ... BEL
                 match self.find_osc_end(&content[i..]) {
                     Some((osc_end, osc_content)) => {
-                        self.parse_osc(osc_content);
+                        self.parse_osc(osc_content, output.len());
                         i += osc_end;
                         continue;
                     }
@@ -122,6 +328,9 @@ impl OscParser {
                 if self.viewport_height > 0 && self.cursor_row >= self.viewport_height {
                     self.scroll_up();
                     self.cursor_row = self.viewport_height - 1;
+                    if !self.alternate_screen {
+                        self.scrolled_rows += 1;
+                    }
                 }
                 self.cursor_col = 0;
             } else if content[i] == b'\r' {
@@ -155,8 +364,9 @@ impl OscParser {
         super::images::find_osc_end(&content[2..]).map(|(consumed, body)| (consumed + 2, body))
     }
 
-    /// Parse an OSC sequence content
-    fn parse_osc(&mut self, content: &[u8]) {
+    /// Parse an OSC sequence content. `offset` is where it was cut out of
+    /// the output `process()` returns.
+    fn parse_osc(&mut self, content: &[u8], offset: usize) {
         let content_str = String::from_utf8_lossy(content);
 
         // OSC 8 (Hyperlinks): 8 ; params ; url
@@ -168,6 +378,84 @@ impl OscParser {
         // OSC 52 (Clipboard): 52 ; Pc ; Pd
         if let Some(rest) = content_str.strip_prefix("52;") {
             self.parse_osc52(rest);
+            return;
+        }
+
+        // OSC 22 (Pointer shape): 22 ; [> | < | ?] names
+        if let Some(rest) = content_str.strip_prefix("22;") {
+            self.parse_osc22(rest);
+            return;
+        }
+
+        // OSC 9 / OSC 777 (Notifications): 9 ; body, 777 ; notify ; title ; body
+        if content_str.starts_with("9;") || content_str.starts_with("777;") {
+            if let Some(notification) = PaneNotification::parse(&content_str) {
+                if self.pending_notifications.len() == MAX_PENDING_NOTIFICATIONS {
+                    self.pending_notifications.remove(0);
+                }
+                self.pending_notifications.push(notification);
+            }
+            return;
+        }
+
+        // OSC 133 (Semantic prompt): 133 ; A|B|C|D [; params]
+        if let Some(rest) = content_str.strip_prefix("133;") {
+            if let Some(mark) = SemanticMark::parse(rest) {
+                if self.pending_marks.len() == MAX_PENDING_MARKS {
+                    self.pending_marks.remove(0);
+                }
+                self.pending_marks.push(PromptMark {
+                    mark,
+                    offset,
+                    scrolled_rows: self.scrolled_rows,
+                });
+            }
+            return;
+        }
+
+        // OSC 7331 (Widget data): 7331 ; json
+        if let Some(rest) = content_str
+            .strip_prefix(WIDGET_DATA_OSC)
+            .and_then(|rest| rest.strip_prefix(';'))
+        {
+            // Not JSON: dropped, like an undecodable OSC 52.
+            if let Ok(data) = serde_json::from_str(rest) {
+                self.pending_widget_data.push(data);
+            }
+            return;
+        }
+
+        super::palette::apply_osc(&mut self.colors, &content_str);
+    }
+
+    /// Parse OSC 22 pointer shape sequence
+    /// Format: `name` sets, `>name` pushes, `<` pops, `?names` queries (not
+    /// answered). `name` may list comma-separated fallbacks; the first one
+    /// recognised wins, and an empty name or `default` restores the default.
+    fn parse_osc22(&mut self, content: &str) {
+        let names = match content.as_bytes().first() {
+            Some(b'<') => {
+                self.pointer_shape = self.pointer_stack.pop().unwrap_or_default();
+                return;
+            }
+            Some(b'?') => return,
+            Some(b'>') => {
+                if self.pointer_stack.len() == MAX_POINTER_STACK {
+                    self.pointer_stack.remove(0);
+                }
+                self.pointer_stack.push(self.pointer_shape.clone());
+                &content[1..]
+            }
+            _ => content,
+        };
+        if names.is_empty() {
+            self.pointer_shape.clear();
+        } else if let Some(css) = names.split(',').find_map(css_pointer_shape) {
+            self.pointer_shape = if css == "default" {
+                String::new()
+            } else {
+                css.to_string()
+            };
         }
     }
 
@@ -219,10 +507,60 @@ impl OscParser {
         self.cell_urls.get(&(row, col))
     }
 
+    /// Colours the application has overridden
+    pub fn colors(&self) -> &super::palette::TerminalColors {
+        &self.colors
+    }
+
+    /// CSS cursor the application asked for, empty for the default
+    pub fn pointer_shape(&self) -> &str {
+        &self.pointer_shape
+    }
+
     /// Take pending clipboard content (clears it)
     pub fn take_clipboard(&mut self) -> Option<String> {
         self.pending_clipboard.take()
     }
+
+    /// Take the widget data sent since the last call
+    pub fn take_widget_data(&mut self) -> Vec<serde_json::Value> {
+        std::mem::take(&mut self.pending_widget_data)
+    }
+
+    /// Take the notifications asked for since the last call
+    pub fn take_notifications(&mut self) -> Vec<PaneNotification> {
+        std::mem::take(&mut self.pending_notifications)
+    }
+
+    /// Take the shell integration marks seen since the last call
+    pub fn take_marks(&mut self) -> Vec<PromptMark> {
+        std::mem::take(&mut self.pending_marks)
+    }
+}
+
+/// Decode `%XX` escapes; `None` if the result isn't UTF-8.
+fn percent_decode(input: &str) -> Option<String> {
+    let bytes = input.as_bytes();
+    let mut output = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        let escaped = (bytes[i] == b'%')
+            .then(|| input.get(i + 1..i + 3))
+            .flatten()
+            .filter(|hex| hex.bytes().all(|b| b.is_ascii_hexdigit()))
+            .and_then(|hex| u8::from_str_radix(hex, 16).ok());
+        match escaped {
+            Some(byte) => {
+                output.push(byte);
+                i += 3;
+            }
+            None => {
+                output.push(bytes[i]);
+                i += 1;
+            }
+        }
+    }
+    String::from_utf8(output).ok()
 }
 
 /// Simple base64 decoder (standard alphabet)
@@ -328,6 +666,38 @@ mod tests {
         );
     }
 
+    #[test]
+    fn colour_overrides_are_captured_and_survive_reset() {
+        let mut parser = OscParser::new();
+        let out = parser.process(b"\x1b]11;rgb:1d1d/1f1f/2121\x1b\\x\x1b]4;1;#cc6666\x07");
+        assert_eq!(out, b"x");
+        assert_eq!(parser.colors().background.as_deref(), Some("#1d1f21"));
+        assert_eq!(parser.colors().palette["1"], "#cc6666");
+        parser.reset();
+        assert_eq!(parser.colors().background.as_deref(), Some("#1d1f21"));
+    }
+
+    #[test]
+    fn pointer_shape_follows_osc22_sets_and_pushes() {
+        let mut parser = OscParser::new();
+        assert_eq!(parser.process(b"\x1b]22;xterm\x07x"), b"x");
+        assert_eq!(parser.pointer_shape(), "text");
+
+        // Push a CSS name, falling back past one we don't know; pop restores.
+        parser.process(b"\x1b]22;>hyperlink-hand,pointer\x1b\\");
+        assert_eq!(parser.pointer_shape(), "pointer");
+        parser.process(b"\x1b]22;<\x07");
+        assert_eq!(parser.pointer_shape(), "text");
+
+        // Unknown names leave the shape alone; empty or `default` reset it.
+        parser.process(b"\x1b]22;url(evil)\x07");
+        assert_eq!(parser.pointer_shape(), "text");
+        parser.process(b"\x1b]22;\x07");
+        assert_eq!(parser.pointer_shape(), "");
+        parser.process(b"\x1b]22;hand2\x07\x1b]22;default\x07");
+        assert_eq!(parser.pointer_shape(), "");
+    }
+
     #[test]
     fn test_osc52_clipboard() {
         let mut parser = OscParser::new();
@@ -339,6 +709,112 @@ mod tests {
         assert_eq!(parser.take_clipboard(), Some("hello".to_string()));
     }
 
+    #[test]
+    fn widget_data_is_taken_in_order_and_kept_off_the_screen() {
+        let mut parser = OscParser::new();
+        let output = parser
+            .process(b"cpu\x1b]7331;{\"cpu\":3}\x07 \x1b]7331;not json\x07\x1b]7331;[1,2]\x1b\\ok");
+        assert_eq!(output, b"cpu ok");
+        assert_eq!(
+            parser.take_widget_data(),
+            [serde_json::json!({ "cpu": 3 }), serde_json::json!([1, 2])]
+        );
+        assert!(parser.take_widget_data().is_empty());
+    }
+
+    #[test]
+    fn notifications_are_parsed_and_kept_off_the_screen() {
+        let mut parser = OscParser::new();
+        let output = parser.process(
+            b"a\x1b]9;Build done\x07b\x1b]777;notify;make;2 errors; 1 warning\x1b\\c\x1b]9;4;1;50\x07",
+        );
+        assert_eq!(output, b"abc");
+        assert_eq!(
+            parser.take_notifications(),
+            [
+                PaneNotification {
+                    title: String::new(),
+                    body: "Build done".to_string(),
+                },
+                PaneNotification {
+                    title: "make".to_string(),
+                    body: "2 errors; 1 warning".to_string(),
+                },
+            ]
+        );
+        assert!(parser.take_notifications().is_empty());
+
+        // Title only, and nothing to say at all.
+        parser.process(b"\x1b]777;notify;Done\x07\x1b]9;\x07\x1b]777;preexec\x07");
+        assert_eq!(
+            parser.take_notifications(),
+            [PaneNotification {
+                title: "Done".to_string(),
+                body: String::new(),
+            }]
+        );
+    }
+
+    #[test]
+    fn semantic_prompt_marks_record_where_they_fall() {
+        let mut parser = OscParser::new();
+        parser.set_viewport_height(2);
+        let output = parser.process(
+            b"\x1b]133;A\x07$ \x1b]133;B\x07ls\r\n\x1b]133;C\x07a\r\nb\r\n\x1b]133;D;2\x07",
+        );
+        assert_eq!(output, b"$ ls\r\na\r\nb\r\n");
+
+        let marks = parser.take_marks();
+        let found: Vec<_> = marks
+            .iter()
+            .map(|m| (&m.mark, m.offset, m.scrolled_rows))
+            .collect();
+        assert_eq!(
+            found,
+            [
+                (&SemanticMark::PromptStart, 0, 0),
+                (&SemanticMark::CommandStart, 2, 0),
+                (&SemanticMark::OutputStart { command: None }, 6, 0),
+                (
+                    &SemanticMark::CommandEnd {
+                        exit_status: Some(2)
+                    },
+                    12,
+                    2
+                ),
+            ]
+        );
+        assert!(parser.take_marks().is_empty());
+        assert_eq!(parser.scrolled_rows(), 2);
+    }
+
+    #[test]
+    fn semantic_prompt_command_lines_and_alternate_screen() {
+        let mut parser = OscParser::new();
+        parser.set_viewport_height(1);
+        parser.process(b"\x1b]133;C;cmdline_url=echo%20a%3Bb\x07\x1b]133;C;aid=1;cmdline=ls; pwd\x07\x1b]133;D\x07\x1b]133;Z\x07");
+        let marks: Vec<_> = parser.take_marks().into_iter().map(|m| m.mark).collect();
+        assert_eq!(
+            marks,
+            [
+                SemanticMark::OutputStart {
+                    command: Some("echo a;b".to_string())
+                },
+                SemanticMark::OutputStart {
+                    command: Some("ls; pwd".to_string())
+                },
+                SemanticMark::CommandEnd { exit_status: None },
+            ]
+        );
+
+        parser.sync_cursor(0, 0, true);
+        parser.process(b"\n\n");
+        assert_eq!(parser.scrolled_rows(), 0);
+        parser.sync_cursor(0, 0, false);
+        parser.process(b"\n\n");
+        assert_eq!(parser.scrolled_rows(), 2);
+    }
+
     #[test]
     fn test_base64_decode() {
         assert_eq!(base64_decode("aGVsbG8=").unwrap(), b"hello");
@@ -0,0 +1,122 @@
+//! Per-pane output budgets.
+//!
+//! A pane writing more than its budget of bytes per second (`yes`, a
+//! runaway log) is *trimmed*: the monitor sends its frames at most every
+//! [`TRIMMED_FRAME_INTERVAL`] instead of at the throttle rate, and a frame
+//! after which more lines scrolled by than the pane shows gets a marker on
+//! its top row — "…output trimmed (N lines skipped)…" — so the gap is
+//! visible rather than silent. Trimming ends once a second's output fits
+//! the budget again.
+
+use std::time::Duration;
+
+#[cfg(not(target_arch = "wasm32"))]
+use std::time::Instant;
+#[cfg(target_arch = "wasm32")]
+use web_time::Instant;
+
+use crate::{CellStyle, TerminalCell, TerminalLine};
+
+/// Slowest frame rate for a trimmed pane's output.
+pub const TRIMMED_FRAME_INTERVAL: Duration = Duration::from_millis(250);
+
+/// Period the budget is counted over.
+const BUDGET_WINDOW: Duration = Duration::from_secs(1);
+
+/// One pane's output against its budget.
+#[derive(Debug, Default)]
+pub(crate) struct OutputBudget {
+    window_start: Option<Instant>,
+    /// Bytes written since `window_start`
+    bytes: u64,
+    /// Over budget in this window or the one before it
+    trimmed: bool,
+    /// Lines written since the last frame
+    lines: u64,
+}
+
+impl OutputBudget {
+    /// Count `content` written at `now` against `budget` bytes per second.
+    pub fn record(&mut self, content: &[u8], budget: u64, now: Instant) {
+        let start = *self.window_start.get_or_insert(now);
+        let elapsed = now.saturating_duration_since(start);
+        if elapsed >= BUDGET_WINDOW {
+            // The window just ended decides, unless the pane then went quiet
+            // for a whole window.
+            self.trimmed = self.bytes > budget && elapsed < 2 * BUDGET_WINDOW;
+            self.window_start = Some(now);
+            self.bytes = 0;
+        }
+        self.bytes += content.len() as u64;
+        self.trimmed |= self.bytes > budget;
+        self.lines += content.iter().filter(|&&b| b == b'\n').count() as u64;
+    }
+
+    pub fn trimmed(&self) -> bool {
+        self.trimmed
+    }
+
+    /// Lines a frame of a `height`-row pane skips: those written since the
+    /// previous frame beyond what fits on screen, while trimmed. Starts the
+    /// count for the next frame.
+    pub fn take_skipped(&mut self, height: u32) -> u64 {
+        let lines = std::mem::take(&mut self.lines);
+        if self.trimmed {
+            lines.saturating_sub(u64::from(height))
+        } else {
+            0
+        }
+    }
+}
+
+/// The marker row standing in for `skipped` lines, `width` cells wide.
+pub(crate) fn marker_line(skipped: u64, width: u32) -> TerminalLine {
+    let style = CellStyle {
+        dim: true,
+        italic: true,
+        ..Default::default()
+    };
+    format!("…output trimmed ({skipped} lines skipped)…")
+        .chars()
+        .take(width as usize)
+        .map(|c| TerminalCell {
+            style: Some(style.clone()),
+            ..TerminalCell::new(c.to_string())
+        })
+        .collect()
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used, clippy::expect_used)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn trims_over_budget_and_recovers() {
+        let t0 = Instant::now();
+        let mut budget = OutputBudget::default();
+        budget.record(&[b'\n'; 40], 100, t0);
+        assert!(!budget.trimmed());
+        assert_eq!(budget.take_skipped(24), 0);
+
+        budget.record(&[b'\n'; 80], 100, t0 + Duration::from_millis(500));
+        assert!(budget.trimmed());
+        assert_eq!(budget.take_skipped(24), 56);
+
+        // The next window is over too by the previous one's count, then a
+        // quiet window ends it.
+        budget.record(b"x", 100, t0 + Duration::from_millis(1100));
+        assert!(budget.trimmed());
+        budget.record(b"x", 100, t0 + Duration::from_millis(2200));
+        assert!(!budget.trimmed());
+    }
+
+    #[test]
+    fn marker_fits_the_pane() {
+        let line = marker_line(1200, 12);
+        assert_eq!(line.len(), 12);
+        let text: String = line.iter().map(|cell| cell.char.as_str()).collect();
+        assert_eq!(text, "…output trim");
+        assert!(line[0].style.as_ref().unwrap().dim);
+    }
+}
@@ -0,0 +1,120 @@
+//! Terminal colours: default foreground, background and the 256-colour
+//! palette.
+//!
+//! Two sources feed them. The server's theme (`TMUXY_TERMINAL_THEME` or the
+//! `set_terminal_theme` command) is what panes are drawn with when nothing
+//! overrides it. Applications override it per pane with OSC 4/10/11 and drop
+//! their overrides with OSC 104/110/111; the OSC parser captures those.
+//!
+//! Queries (`ESC ] 11 ; ?`) are answered by tmux, not here. tmux knows a
+//! pane's colours once the pane sets them; until then it replies with what
+//! its control client reported through `refresh-client -r`, so reporting the
+//! theme for every pane makes the answer match what the renderer paints.
+
+pub use tmuxy_protocol::{parse_color, TerminalColors};
+
+/// Apply one OSC body (without `ESC ]` and terminator) to `colors` if it sets
+/// or resets a colour. Queries (`?`) and other OSCs are ignored.
+pub(crate) fn apply_osc(colors: &mut TerminalColors, body: &str) {
+    let (code, params) = body.split_once(';').unwrap_or((body, ""));
+    match code {
+        // 4 ; index ; spec [; index ; spec ...]
+        "4" => {
+            let mut params = params.split(';');
+            while let (Some(index), Some(spec)) = (params.next(), params.next()) {
+                if let (Ok(index), Some(color)) = (index.parse::<u8>(), parse_color(spec)) {
+                    colors.palette.insert(index.to_string(), color);
+                }
+            }
+        }
+        // 104 with no indices resets the whole palette.
+        "104" if params.is_empty() => colors.palette.clear(),
+        "104" => {
+            for index in params.split(';').filter_map(|i| i.parse::<u8>().ok()) {
+                colors.palette.remove(&index.to_string());
+            }
+        }
+        // Each extra parameter of 10 sets the next dynamic colour, so
+        // `10;fg;bg` sets both.
+        "10" | "11" => {
+            let first = if code == "10" { 0 } else { 1 };
+            for (slot, spec) in params.split(';').enumerate() {
+                let Some(color) = parse_color(spec) else {
+                    continue;
+                };
+                match first + slot {
+                    0 => colors.foreground = Some(color),
+                    1 => colors.background = Some(color),
+                    _ => {}
+                }
+            }
+        }
+        "110" => colors.foreground = None,
+        "111" => colors.background = None,
+        _ => {}
+    }
+}
+
+/// `refresh-client -r` commands telling tmux the default colours in `colors`
+/// for `pane_id`, so it answers OSC 10/11 queries with them.
+pub(crate) fn report_commands(colors: &TerminalColors, pane_id: &str) -> Vec<String> {
+    [(10, &colors.foreground), (11, &colors.background)]
+        .into_iter()
+        .filter_map(|(code, color)| {
+            let rgb = x11_rgb(color.as_deref()?)?;
+            // Inside tmux double quotes `\e` is ESC and `\\` a backslash.
+            Some(format!(
+                "refresh-client -r \"{pane_id}:\\e]{code};{rgb}\\e\\\\\""
+            ))
+        })
+        .collect()
+}
+
+/// `#rrggbb` as the 16-bit `rgb:rrrr/gggg/bbbb` form terminals report.
+fn x11_rgb(hex: &str) -> Option<String> {
+    let hex = hex
+        .strip_prefix('#')
+        .filter(|h| h.len() == 6 && h.is_ascii())?;
+    Some(format!(
+        "rgb:{r}{r}/{g}{g}/{b}{b}",
+        r = &hex[0..2],
+        g = &hex[2..4],
+        b = &hex[4..6]
+    ))
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used, clippy::expect_used)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn osc_sets_and_resets_overrides() {
+        let mut colors = TerminalColors::default();
+        apply_osc(&mut colors, "4;1;rgb:cc/66/66;2;#b5bd68;300;#000000");
+        apply_osc(&mut colors, "10;#c5c8c6;rgb:1d/1f/21");
+        apply_osc(&mut colors, "11;?");
+        assert_eq!(colors.foreground.as_deref(), Some("#c5c8c6"));
+        assert_eq!(colors.background.as_deref(), Some("#1d1f21"));
+        assert_eq!(colors.palette.len(), 2);
+        assert_eq!(colors.palette["1"], "#cc6666");
+
+        apply_osc(&mut colors, "104;1");
+        apply_osc(&mut colors, "110");
+        assert_eq!(colors.foreground, None);
+        assert_eq!(colors.palette.keys().collect::<Vec<_>>(), ["2"]);
+        apply_osc(&mut colors, "104");
+        apply_osc(&mut colors, "111");
+        assert!(colors.is_empty());
+    }
+
+    #[test]
+    fn defaults_are_reported_to_tmux_as_osc_replies() {
+        let theme = TerminalColors::parse("bg=#1e1e1e").unwrap();
+        assert_eq!(
+            report_commands(&theme, "%4"),
+            [r#"refresh-client -r "%4:\e]11;rgb:1e1e/1e1e/1e1e\e\\""#]
+        );
+        assert!(report_commands(&TerminalColors::default(), "%4").is_empty());
+    }
+}
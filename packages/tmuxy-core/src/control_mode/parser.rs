@@ -6,7 +6,7 @@
 //! - `%begin/%end/%error` - Command response blocks
 //! - etc.
 
-use super::octal::decode_octal;
+use super::octal::decode_octal_bytes;
 
 /// Events parsed from control mode output
 #[derive(Debug, Clone)]
@@ -89,6 +89,15 @@ pub enum ControlModeEvent {
 
     /// Unlinked window closed
     UnlinkedWindowClose { window_id: String },
+
+    /// A `refresh-client -B` format subscription changed value. Window and
+    /// pane are set for subscriptions on windows or panes.
+    SubscriptionChanged {
+        name: String,
+        window_id: Option<String>,
+        pane_id: Option<String>,
+        value: String,
+    },
 }
 
 /// Parser for control mode notifications
@@ -145,6 +154,28 @@ impl Parser {
         self.parse_notification(line)
     }
 
+    /// Parse a single raw line (without its newline), for input straight
+    /// off the control-mode connection.
+    ///
+    /// tmux escapes only control characters and backslash, so `%output` can
+    /// carry bytes that aren't UTF-8 (a character split across two
+    /// notifications, or a program writing binary). Pane output keeps those
+    /// bytes as they are; everything else is read as lossy UTF-8. Never
+    /// panics, whatever the input.
+    pub fn parse_bytes(&mut self, line: &[u8]) -> Option<ControlModeEvent> {
+        use crate::constants::control_events as ev;
+
+        if !self.in_response {
+            if line.starts_with(ev::OUTPUT.as_bytes()) {
+                return self.parse_output(line);
+            }
+            if line.starts_with(ev::EXTENDED_OUTPUT.as_bytes()) {
+                return self.parse_extended_output(line);
+            }
+        }
+        self.parse_line(&String::from_utf8_lossy(line))
+    }
+
     fn handle_begin(&mut self, line: &str) -> Option<ControlModeEvent> {
         // Format: %begin timestamp command-number flags
         let parts: Vec<&str> = line.split_whitespace().collect();
@@ -180,12 +211,12 @@ impl Parser {
 
         // %output %pane-id value
         if line.starts_with(ev::OUTPUT) {
-            return self.parse_output(line);
+            return self.parse_output(line.as_bytes());
         }
 
         // %extended-output %pane-id age ... : value
         if line.starts_with(ev::EXTENDED_OUTPUT) {
-            return self.parse_extended_output(line);
+            return self.parse_extended_output(line.as_bytes());
         }
 
         // %layout-change @window layout visible-layout flags
@@ -231,6 +262,11 @@ impl Parser {
             });
         }
 
+        // %subscription-changed name $session @window index %pane ... : value
+        if let Some(rest) = line.strip_prefix(ev::SUBSCRIPTION_CHANGED) {
+            return self.parse_subscription_changed(rest);
+        }
+
         // %session-changed $session name
         if line.starts_with(ev::SESSION_CHANGED) {
             return self.parse_session_changed(line);
@@ -301,45 +337,42 @@ impl Parser {
         None
     }
 
-    fn parse_output(&self, line: &str) -> Option<ControlModeEvent> {
+    fn parse_output(&self, line: &[u8]) -> Option<ControlModeEvent> {
         // %output %pane-id value
         let rest = &line["%output ".len()..];
 
         // Find the space after pane-id
-        if let Some(space_idx) = rest.find(' ') {
-            let pane_id = rest[..space_idx].to_string();
-            let value = &rest[space_idx + 1..];
-            let content = decode_octal(value);
+        if let Some(space_idx) = rest.iter().position(|&b| b == b' ') {
+            let pane_id = String::from_utf8_lossy(&rest[..space_idx]).into_owned();
+            let content = decode_octal_bytes(&rest[space_idx + 1..]);
             return Some(ControlModeEvent::Output { pane_id, content });
         }
 
         // No content (empty output)
         Some(ControlModeEvent::Output {
-            pane_id: rest.trim().to_string(),
+            pane_id: String::from_utf8_lossy(rest).trim().to_string(),
             content: Vec::new(),
         })
     }
 
-    fn parse_extended_output(&self, line: &str) -> Option<ControlModeEvent> {
+    fn parse_extended_output(&self, line: &[u8]) -> Option<ControlModeEvent> {
         // %extended-output %pane-id age ... : value
+        // Like %subscription-changed, an empty value may arrive with the
+        // separator's trailing space gone.
         let rest = &line["%extended-output ".len()..];
-        let parts: Vec<&str> = rest.splitn(2, " : ").collect();
-
-        if parts.len() < 2 {
-            return None;
-        }
-
-        let header_parts: Vec<&str> = parts[0].split_whitespace().collect();
-        if header_parts.is_empty() {
-            return None;
-        }
+        let (header, value) = match rest.windows(3).position(|w| w == b" : ") {
+            Some(sep) => (&rest[..sep], &rest[sep + 3..]),
+            None => (rest.strip_suffix(b" :")?, &[][..]),
+        };
 
-        let pane_id = header_parts[0].to_string();
+        let header = String::from_utf8_lossy(header);
+        let mut header_parts = header.split_whitespace();
+        let pane_id = header_parts.next()?.to_string();
         let age_ms = header_parts
-            .get(1)
+            .next()
             .and_then(|s| s.parse().ok())
             .unwrap_or(0);
-        let content = decode_octal(parts[1]);
+        let content = decode_octal_bytes(value);
 
         Some(ControlModeEvent::ExtendedOutput {
             pane_id,
@@ -348,6 +381,28 @@ impl Parser {
         })
     }
 
+    fn parse_subscription_changed(&self, rest: &str) -> Option<ControlModeEvent> {
+        // Unset fields are `-`: `name $1 - - - : value` for a session
+        // subscription, `name $1 @2 0 %3 : value` for a pane.
+        // An empty value may arrive with the separator's trailing space gone.
+        let (header, value) = rest
+            .split_once(" : ")
+            .or_else(|| rest.strip_suffix(" :").map(|header| (header, "")))?;
+        let mut fields = header.split_whitespace();
+        let name = fields.next()?.to_string();
+        let _session = fields.next();
+        let id = |field: Option<&str>| field.filter(|f| *f != "-").map(str::to_string);
+        let window_id = id(fields.next());
+        let _index = fields.next();
+        let pane_id = id(fields.next());
+        Some(ControlModeEvent::SubscriptionChanged {
+            name,
+            window_id,
+            pane_id,
+            value: value.to_string(),
+        })
+    }
+
     fn parse_layout_change(&self, line: &str) -> Option<ControlModeEvent> {
         // %layout-change @window layout visible-layout flags
         let rest = &line["%layout-change ".len()..];
@@ -581,6 +636,41 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_parse_subscription_changed() {
+        let mut parser = Parser::new();
+        match parser.parse_line("%subscription-changed tmuxy-opt-0 $1 - - - : bottom") {
+            Some(ControlModeEvent::SubscriptionChanged {
+                name,
+                window_id,
+                pane_id,
+                value,
+            }) => {
+                assert_eq!(name, "tmuxy-opt-0");
+                assert_eq!((window_id, pane_id), (None, None));
+                assert_eq!(value, "bottom");
+            }
+            other => panic!("Expected SubscriptionChanged, got {:?}", other),
+        }
+        match parser.parse_line("%subscription-changed tmuxy-opt-1 $1 @2 0 %5 : a : b") {
+            Some(ControlModeEvent::SubscriptionChanged {
+                window_id,
+                pane_id,
+                value,
+                ..
+            }) => {
+                assert_eq!(window_id.as_deref(), Some("@2"));
+                assert_eq!(pane_id.as_deref(), Some("%5"));
+                assert_eq!(value, "a : b");
+            }
+            other => panic!("Expected SubscriptionChanged, got {:?}", other),
+        }
+        match parser.parse_line("%subscription-changed tmuxy-opt-2 $1 - - - :") {
+            Some(ControlModeEvent::SubscriptionChanged { value, .. }) => assert_eq!(value, ""),
+            other => panic!("Expected SubscriptionChanged, got {:?}", other),
+        }
+    }
+
     #[test]
     fn test_parse_pane_mode_changed() {
         let mut parser = Parser::new();
@@ -606,4 +696,50 @@ mod tests {
             _ => panic!("Expected PasteBufferChanged event"),
         }
     }
+
+    #[test]
+    fn test_parse_bytes_keeps_raw_output() {
+        let mut parser = Parser::new();
+        // The first two bytes of `─`, with the third due in the next line
+        match parser.parse_bytes(b"%output %1 ab\xe2\x94") {
+            Some(ControlModeEvent::Output { pane_id, content }) => {
+                assert_eq!(pane_id, "%1");
+                assert_eq!(content, b"ab\xe2\x94");
+            }
+            other => panic!("Expected Output, got {:?}", other),
+        }
+
+        // Inside a response block, output-looking lines are response text
+        assert!(parser.parse_bytes(b"%begin 1 2 0").is_none());
+        assert!(parser.parse_bytes(b"%output %1 x").is_none());
+        match parser.parse_bytes(b"%end 1 2 0") {
+            Some(ControlModeEvent::CommandResponse { output, .. }) => {
+                assert_eq!(output, "%output %1 x");
+            }
+            other => panic!("Expected CommandResponse, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_parse_extended_output() {
+        let mut parser = Parser::new();
+        match parser.parse_line(r"%extended-output %3 250 : hi\015\012") {
+            Some(ControlModeEvent::ExtendedOutput {
+                pane_id,
+                age_ms,
+                content,
+            }) => {
+                assert_eq!(pane_id, "%3");
+                assert_eq!(age_ms, 250);
+                assert_eq!(content, b"hi\r\n");
+            }
+            other => panic!("Expected ExtendedOutput, got {:?}", other),
+        }
+        match parser.parse_line("%extended-output %3 0 :") {
+            Some(ControlModeEvent::ExtendedOutput { content, .. }) => assert!(content.is_empty()),
+            other => panic!("Expected ExtendedOutput, got {:?}", other),
+        }
+        assert!(parser.parse_line("%extended-output %3 0").is_none());
+        assert!(parser.parse_line("%extended-output  : x").is_none());
+    }
 }
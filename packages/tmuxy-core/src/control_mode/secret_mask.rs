@@ -0,0 +1,218 @@
+//! Masking of injected secrets in pane content.
+//!
+//! `inject_secret` types a secret into a pane, and whatever the application
+//! echoes lands in the emulator like any other output. While a mask is live,
+//! every run of cells spelling the secret is drawn as `*` in the content the
+//! aggregator hands out — state updates, copy-mode captures, scrollback reads
+//! and the screen-reader stream all go through it, as do the capture-pane
+//! reads behind pane export, pane text and pane search
+//! (`StateAggregator::mask_captured`).
+//!
+//! A mask is temporary. Once its echo has been seen and has then left the
+//! screen, it is dropped; a secret typed at a prompt that never echoes it is
+//! dropped after a short deadline instead, and every mask after a longer one,
+//! so an echo that stays on screen doesn't keep masking the user's own typing
+//! forever. History tmux serves directly (the copy mode scrollback fetch) is
+//! tmux's own and is not masked.
+
+use crate::{PaneContent, TerminalCell};
+
+#[cfg(not(target_arch = "wasm32"))]
+use std::time::Instant;
+#[cfg(target_arch = "wasm32")]
+use web_time::Instant;
+
+use zeroize::Zeroizing;
+
+/// What a masked cell shows.
+const MASK_CHAR: &str = "*";
+
+/// One injected secret being watched for in a pane's output.
+pub(crate) struct SecretMask {
+    /// The secret, one entry per character (as a cell would hold it).
+    chars: Zeroizing<Vec<String>>,
+    /// The echo has appeared on screen at least once.
+    seen: bool,
+    /// Dropped at this point if never seen.
+    expires: Instant,
+    /// Dropped at this point, seen or not.
+    deadline: Instant,
+}
+
+impl std::fmt::Debug for SecretMask {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("SecretMask")
+            .field("seen", &self.seen)
+            .field("expires", &self.expires)
+            .field("deadline", &self.deadline)
+            .finish_non_exhaustive()
+    }
+}
+
+impl SecretMask {
+    pub(crate) fn new(secret: &str, expires: Instant, deadline: Instant) -> Self {
+        Self {
+            chars: Zeroizing::new(secret.chars().map(String::from).collect()),
+            seen: false,
+            expires,
+            deadline,
+        }
+    }
+
+    /// Whether the echo has appeared on screen.
+    pub(crate) fn seen(&self) -> bool {
+        self.seen
+    }
+
+    /// Whether the mask has outlived its deadline, or its shorter one
+    /// without its echo appearing.
+    pub(crate) fn expired(&self, now: Instant) -> bool {
+        now >= self.deadline || (!self.seen && now >= self.expires)
+    }
+
+    /// Mask every occurrence in `content`; returns whether there was one.
+    /// Runs are matched within a logical line, across the rows it
+    /// soft-wraps onto, skipping the blank continuation cell after a wide
+    /// character.
+    pub(crate) fn apply(&mut self, content: &mut PaneContent) -> bool {
+        if self.chars.is_empty() {
+            return false;
+        }
+        let width = content.iter().map(Vec::len).max().unwrap_or(0);
+        let mut found = false;
+        let mut row = 0;
+        while row < content.len() {
+            let mut end = row;
+            while end + 1 < content.len() && wraps(&content[end], width) {
+                end += 1;
+            }
+            let cells: Vec<(usize, usize)> = (row..=end)
+                .flat_map(|r| {
+                    content[r]
+                        .iter()
+                        .enumerate()
+                        .filter(|(_, cell)| cell.width != 0)
+                        .map(move |(i, _)| (r, i))
+                })
+                .collect();
+            let mut start = 0;
+            while start + self.chars.len() <= cells.len() {
+                let run = &cells[start..start + self.chars.len()];
+                if run
+                    .iter()
+                    .zip(self.chars.iter())
+                    .all(|(&(r, i), ch)| content[r][i].char == *ch)
+                {
+                    for &(r, i) in run {
+                        content[r][i].char = MASK_CHAR.to_string();
+                    }
+                    found = true;
+                    start += self.chars.len();
+                } else {
+                    start += 1;
+                }
+            }
+            row = end + 1;
+        }
+        self.seen |= found;
+        found
+    }
+}
+
+/// Whether `line` soft-wraps onto the next row: it is as long as the widest
+/// row and its last character isn't blank.
+fn wraps(line: &[TerminalCell], width: usize) -> bool {
+    line.len() >= width
+        && line
+            .iter()
+            .rev()
+            .find(|cell| cell.width != 0)
+            .is_some_and(|cell| !cell.char.trim().is_empty())
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used, clippy::expect_used)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    fn line(text: &str) -> Vec<TerminalCell> {
+        text.chars()
+            .map(|c| TerminalCell {
+                char: c.to_string(),
+                style: None,
+                width: 1,
+            })
+            .collect()
+    }
+
+    fn text(content: &PaneContent) -> Vec<String> {
+        content
+            .iter()
+            .map(|l| l.iter().map(|c| c.char.as_str()).collect())
+            .collect()
+    }
+
+    #[test]
+    fn every_echo_of_the_secret_is_masked() {
+        let now = Instant::now();
+        let mut mask = SecretMask::new("hunter2", now, now + Duration::from_secs(300));
+        let mut content = vec![
+            line("$ login hunter2    "),
+            line("hunter2hunter2 ok  "),
+            line("hunter             "),
+        ];
+        assert!(mask.apply(&mut content));
+        assert_eq!(
+            text(&content),
+            [
+                "$ login *******    ",
+                "************** ok  ",
+                "hunter             "
+            ]
+        );
+        // Seen masks outlive the short deadline; they go once the echo
+        // leaves the screen.
+        assert!(!mask.expired(now + Duration::from_secs(60)));
+        assert!(!mask.apply(&mut vec![line("$ ")]));
+    }
+
+    #[test]
+    fn echo_soft_wrapped_across_rows_is_masked() {
+        let now = Instant::now();
+        let mut mask = SecretMask::new("hunter2", now, now);
+        let mut content = vec![line("$ login hunt"), line("er2 ok      ")];
+        assert!(mask.apply(&mut content));
+        assert_eq!(text(&content), ["$ login ****", "*** ok      "]);
+
+        // A row ending in blanks is a hard line break, not a wrap.
+        let mut content = vec![line("hunt        "), line("er2 ok      ")];
+        assert!(!mask.apply(&mut content));
+    }
+
+    #[test]
+    fn unseen_mask_expires() {
+        let now = Instant::now();
+        let mut mask = SecretMask::new(
+            "s3cret",
+            now + Duration::from_secs(5),
+            now + Duration::from_secs(300),
+        );
+        assert!(!mask.apply(&mut vec![line("Password: ")]));
+        assert!(!mask.expired(now));
+        assert!(mask.expired(now + Duration::from_secs(5)));
+    }
+
+    #[test]
+    fn seen_mask_expires_at_its_deadline() {
+        let now = Instant::now();
+        let mut mask = SecretMask::new(
+            "s3cret",
+            now + Duration::from_secs(5),
+            now + Duration::from_secs(300),
+        );
+        assert!(mask.apply(&mut vec![line("s3cret")]));
+        assert!(!mask.expired(now + Duration::from_secs(299)));
+        assert!(mask.expired(now + Duration::from_secs(300)));
+    }
+}
@@ -0,0 +1,122 @@
+//! Session groups: sessions made with `new-session -t` share one set of
+//! windows, each with its own current window — the same windows on two
+//! monitors, say, each showing a different one.
+//!
+//! The aggregator reads its session's group ([`query_command`]) on attach and
+//! whenever sessions come and go. Control mode reports every session's
+//! `%session-window-changed`, so knowing its own session id also lets it
+//! ignore a grouped session switching windows, which used to switch ours.
+
+use super::status_line::quote_escape;
+pub use tmuxy_protocol::SessionGroup;
+
+/// First line of a group reply, which is how the aggregator routes it.
+pub const GROUP_MARKER: &str = "TMUXY_GROUP";
+
+/// A group reply: the session's id, and its group if it is in one.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GroupReply {
+    pub session_id: String,
+    pub group: Option<SessionGroup>,
+}
+
+/// The `display-message` reading `session`'s id and group.
+pub fn query_command(session: &str) -> String {
+    format!(
+        "display-message -p -t \"{}\" \"{}\"",
+        quote_escape(session),
+        quote_escape(&format!(
+            "{GROUP_MARKER}\n#{{session_id}}\n#{{session_grouped}}\n#{{session_group}}\n#{{session_group_list}}"
+        ))
+    )
+}
+
+/// Parse a reply to [`query_command`]; `None` unless it is one.
+pub fn parse_reply(output: &str) -> Option<GroupReply> {
+    let mut lines = output.lines();
+    if lines.next()?.trim_end() != GROUP_MARKER {
+        return None;
+    }
+    let session_id = lines.next()?.trim().to_string();
+    let grouped = lines.next().is_some_and(|l| l.trim() == "1");
+    let name = lines.next().unwrap_or("").to_string();
+    let sessions = lines
+        .next()
+        .unwrap_or("")
+        .split(',')
+        .filter(|s| !s.is_empty())
+        .map(str::to_string)
+        .collect();
+    Some(GroupReply {
+        session_id,
+        group: grouped.then_some(SessionGroup { name, sessions }),
+    })
+}
+
+/// The command creating `name` in `session`'s group (making the group if
+/// there is none yet).
+pub fn create_command(session: &str, name: &str) -> Result<String, String> {
+    crate::discovery::validate_session_name(name)?;
+    Ok(format!(
+        "new-session -d -t \"{}\" -s \"{}\"",
+        quote_escape(session),
+        quote_escape(name)
+    ))
+}
+
+/// A name for a new session in `session`'s group: `session-2`, `session-3`,
+/// ..., the first not in `existing`.
+pub fn next_name(session: &str, existing: &[String]) -> String {
+    (2..)
+        .map(|n| format!("{session}-{n}"))
+        .find(|name| !existing.contains(name))
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used, clippy::expect_used)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn query_is_one_display_message() {
+        assert_eq!(
+            query_command("my \"s\""),
+            r#"display-message -p -t "my \"s\"" "TMUXY_GROUP\n#{session_id}\n#{session_grouped}\n#{session_group}\n#{session_group_list}""#
+        );
+    }
+
+    #[test]
+    fn parses_grouped_and_lone_sessions() {
+        let grouped = parse_reply("TMUXY_GROUP\n$0\n1\nmain\nmain,main-2\n").unwrap();
+        assert_eq!(grouped.session_id, "$0");
+        assert_eq!(
+            grouped.group,
+            Some(SessionGroup {
+                name: "main".to_string(),
+                sessions: vec!["main".to_string(), "main-2".to_string()],
+            })
+        );
+        let lone = parse_reply("TMUXY_GROUP\n$2\n0\n\n\n").unwrap();
+        assert_eq!((lone.session_id.as_str(), lone.group), ("$2", None));
+        assert!(parse_reply("TMUXY_STATUS\n").is_none());
+    }
+
+    #[test]
+    fn create_command_validates_the_name() {
+        assert_eq!(
+            create_command("main", "main-2").unwrap(),
+            r#"new-session -d -t "main" -s "main-2""#
+        );
+        for bad in ["", "a:b", "a.b", "=main", "a\nb"] {
+            assert!(create_command("main", bad).is_err(), "{bad:?}");
+        }
+    }
+
+    #[test]
+    fn next_name_skips_taken_names() {
+        let existing = vec!["main".to_string(), "main-2".to_string()];
+        assert_eq!(next_name("main", &existing), "main-3");
+        assert_eq!(next_name("work", &existing), "work-2");
+    }
+}
@@ -0,0 +1,103 @@
+//! Plain-text "spoken" stream for screen readers.
+//!
+//! Announcing a terminal means deciding which text is *new*. Re-reading the
+//! grid after every `%output` would repeat the whole screen; diffing cells
+//! would announce every spinner frame and prompt redraw. Instead only
+//! *completed* rows count — rows above the cursor, which a shell has moved
+//! past with a newline — and each snapshot of them is matched against the
+//! previous one. Whatever extends past the overlap is what was appended.
+//!
+//! Spinners and progress bars redraw the cursor row with `\r`, so they are
+//! never announced until the line is finished. A redraw that leaves the
+//! completed rows as they were (a prompt repaint, re-entering the main
+//! screen) finds a full overlap and announces nothing.
+
+use crate::PaneContent;
+
+/// Plain text of each row above `cursor_row`, trailing blanks trimmed.
+pub(crate) fn completed_rows(content: &PaneContent, cursor_row: usize) -> Vec<String> {
+    content
+        .iter()
+        .take(cursor_row)
+        .map(|line| {
+            let text: String = line.iter().map(|c| c.char.as_str()).collect();
+            text.trim_end().to_string()
+        })
+        .collect()
+}
+
+/// Completed rows already accounted for, per pane.
+#[derive(Debug, Default)]
+pub(crate) struct SpokenText {
+    rows: Vec<String>,
+}
+
+impl SpokenText {
+    pub(crate) fn new(rows: Vec<String>) -> Self {
+        Self { rows }
+    }
+
+    /// Accept `rows` as already heard — after a resize reflow or a capture
+    /// refill, which rewrite the screen without producing new output.
+    pub(crate) fn rebase(&mut self, rows: Vec<String>) {
+        self.rows = rows;
+    }
+
+    /// Non-blank lines in `rows` that were not in the previous snapshot.
+    ///
+    /// The previous rows may have scrolled up by any amount: the smallest
+    /// shift whose remaining rows are a prefix of `rows` wins, and everything
+    /// after that prefix is new. With no overlap at all (the screen was
+    /// cleared and refilled) every row is new. Output that scrolls more than
+    /// a screen in one chunk is announced from its last screen.
+    pub(crate) fn advance(&mut self, rows: Vec<String>) -> Vec<String> {
+        let kept = (0..=self.rows.len())
+            .map(|shift| &self.rows[shift..])
+            .find(|tail| rows.starts_with(tail))
+            .map_or(0, <[String]>::len);
+        let new = rows[kept..]
+            .iter()
+            .filter(|line| !line.is_empty())
+            .cloned()
+            .collect();
+        self.rows = rows;
+        new
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used, clippy::expect_used)]
+mod tests {
+    use super::*;
+
+    fn rows(lines: &[&str]) -> Vec<String> {
+        lines.iter().map(|s| s.to_string()).collect()
+    }
+
+    #[test]
+    fn appended_lines_are_new() {
+        let mut spoken = SpokenText::new(rows(&["$ ls"]));
+        assert_eq!(
+            spoken.advance(rows(&["$ ls", "a.txt", "", "b.txt"])),
+            ["a.txt", "b.txt"]
+        );
+        // Nothing changed above the cursor: a prompt repaint.
+        assert!(spoken
+            .advance(rows(&["$ ls", "a.txt", "", "b.txt"]))
+            .is_empty());
+    }
+
+    #[test]
+    fn scrolled_screen_announces_only_the_tail() {
+        let mut spoken = SpokenText::new(rows(&["1", "2", "3"]));
+        assert_eq!(spoken.advance(rows(&["2", "3", "4"])), ["4"]);
+        assert_eq!(spoken.advance(rows(&["4", "5", "6"])), ["5", "6"]);
+    }
+
+    #[test]
+    fn cleared_screen_announces_nothing_until_refilled() {
+        let mut spoken = SpokenText::new(rows(&["old", "output"]));
+        assert!(spoken.advance(Vec::new()).is_empty());
+        assert_eq!(spoken.advance(rows(&["fresh"])), ["fresh"]);
+    }
+}
@@ -0,0 +1,101 @@
+//! SSH agent forwarding status of a session.
+//!
+//! A session's `SSH_AUTH_SOCK` is fixed when the session is created, so after
+//! the SSH connection that created it drops, every pane keeps pointing at a
+//! socket nobody listens on. The classic fix is a stable symlink: point the
+//! session at `~/.ssh/ssh_auth_sock` and re-point the link at whichever agent
+//! socket is live. The monitor does that whenever it finds the agent dead —
+//! on connect and on a periodic check — and on demand with `fix_ssh_agent`.
+//!
+//! Both checks run through `run-shell`, so they happen on the host tmux runs
+//! on — the only place the socket path means anything. Panes started before
+//! the first re-point keep the socket they were started with; every pane
+//! started after it follows the link.
+
+pub use tmuxy_protocol::SshAgentStatus;
+
+/// Read the output of [`check_script`].
+pub(crate) fn parse_status(output: &str) -> SshAgentStatus {
+    match output.trim() {
+        "live" => SshAgentStatus::Live,
+        _ => SshAgentStatus::Dead,
+    }
+}
+
+/// The socket in `show-environment -t <session> SSH_AUTH_SOCK` output, if the
+/// variable is set (`-SSH_AUTH_SOCK` marks it removed).
+pub(crate) fn session_socket(output: &str) -> Option<String> {
+    output
+        .lines()
+        .find_map(|line| line.strip_prefix("SSH_AUTH_SOCK="))
+        .filter(|socket| !socket.is_empty())
+        .map(String::from)
+}
+
+/// `sh` function: whether an agent answers on `$1`. `ssh-add -l` exits 0 or 1
+/// (no identities) when it reached an agent and 2 when it could not.
+#[cfg(feature = "native")]
+const ALIVE_FN: &str = r#"alive() { [ -S "$1" ] && { SSH_AUTH_SOCK="$1" ssh-add -l >/dev/null 2>&1; [ $? -le 1 ]; }; }"#;
+
+/// Shell command printing `live` or `dead` for `socket`.
+#[cfg(feature = "native")]
+pub(crate) fn check_script(socket: &str) -> String {
+    format!(
+        "{ALIVE_FN}; alive {} && echo live || echo dead",
+        crate::executor::tmux_quote(socket)
+    )
+}
+
+/// Shell command pointing `~/.ssh/ssh_auth_sock` at a live agent and printing
+/// the link's path; prints nothing when no agent is reachable. Candidates, in
+/// order: the session's socket, the tmux server's own, then forwarded sockets
+/// newest first.
+#[cfg(feature = "native")]
+pub(crate) fn repoint_script(current: Option<&str>) -> String {
+    let current = current.map(crate::executor::tmux_quote).unwrap_or_default();
+    format!(
+        r#"{ALIVE_FN}; link="$HOME/.ssh/ssh_auth_sock"; for s in {current} "$SSH_AUTH_SOCK" $(ls -t /tmp/ssh-*/agent.* 2>/dev/null); do [ "$s" = "$link" ] && continue; if alive "$s"; then mkdir -p "$HOME/.ssh" && ln -sfn "$s" "$link" && echo "$link"; exit; fi; done; alive "$link" && echo "$link""#
+    )
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used, clippy::expect_used)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn session_socket_reads_set_variables_only() {
+        assert_eq!(
+            session_socket("SSH_AUTH_SOCK=/tmp/ssh-x/agent.1\n").as_deref(),
+            Some("/tmp/ssh-x/agent.1")
+        );
+        assert_eq!(session_socket("-SSH_AUTH_SOCK\n"), None);
+        assert_eq!(session_socket("SSH_AUTH_SOCK=\n"), None);
+        assert_eq!(session_socket(""), None);
+    }
+
+    #[test]
+    fn status_is_dead_unless_the_check_says_live() {
+        assert_eq!(parse_status("live\n"), SshAgentStatus::Live);
+        assert_eq!(parse_status("dead\n"), SshAgentStatus::Dead);
+        assert_eq!(parse_status(""), SshAgentStatus::Dead);
+    }
+
+    #[cfg(all(feature = "native", unix))]
+    #[test]
+    fn scripts_run_under_sh() {
+        let run = |script: String| {
+            let out = std::process::Command::new("sh")
+                .arg("-c")
+                .arg(script)
+                .env("HOME", "/nonexistent/home")
+                .env_remove("SSH_AUTH_SOCK")
+                .output()
+                .unwrap();
+            String::from_utf8(out.stdout).unwrap()
+        };
+        assert_eq!(run(check_script("/nonexistent/it's gone")), "dead\n");
+        // Nothing reachable: no link is made and nothing is printed.
+        assert_eq!(run(repoint_script(Some("/nonexistent/agent"))), "");
+    }
+}
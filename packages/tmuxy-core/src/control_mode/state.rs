@@ -1,14 +1,23 @@
 //! State aggregator for tmux control mode
 //!
-//! Aggregates control mode events into coherent state using vt100 terminal emulation.
+//! Aggregates control mode events into coherent state using per-pane terminal
+//! emulation (see `terminal`).
 
+use super::command_history::CommandRecord;
+use super::exit_summary::ExitSummary;
+use super::osc::SemanticMark;
 use super::parser::ControlModeEvent;
-use crate::{
-    extract_cells_from_screen, extract_cells_with_urls, PaneContent, TmuxPane, TmuxState,
-    TmuxWindow, WindowType,
-};
+use super::session_group::{self, SessionGroup, GROUP_MARKER};
+use super::status_line::{StatusLine, StatusReply, STATUS_MARKER};
+use super::terminal::{TerminalBackend, TerminalBackendKind};
+use super::window_mru::WindowMru;
+use crate::keyboard::KeyboardMode;
+use crate::mouse::PaneMouseMode;
+use crate::widget::{PlacedWidget, WidgetSpec};
+use crate::{CommandBlock, PaneContent, TmuxPane, TmuxState, TmuxWindow, WindowType};
 use std::collections::HashMap;
-use tracing::warn;
+use std::time::Duration;
+use tracing::{debug, warn};
 
 // The settling debounce uses a monotonic clock. `std::time::Instant::now()`
 // panics on wasm32; web-time backs it with performance.now() in the browser.
@@ -17,22 +26,6 @@ use std::time::Instant;
 #[cfg(target_arch = "wasm32")]
 use web_time::Instant;
 
-/// Safe wrapper around vt100::Parser::process that catches panics from
-/// internal vt100 bugs (e.g., subtract overflow in grid.rs col_wrap).
-fn safe_process(terminal: &mut vt100::Parser, data: &[u8]) {
-    let terminal_ptr = terminal as *mut vt100::Parser;
-    // SAFETY: We have exclusive access to the parser (&mut self in callers).
-    // catch_unwind requires FnOnce: UnwindSafe, which &mut vt100::Parser isn't.
-    // We use AssertUnwindSafe because after a panic the parser state may be
-    // inconsistent, but the caller will recreate it on next capture-pane refresh.
-    let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
-        unsafe { &mut *terminal_ptr }.process(data);
-    }));
-    if result.is_err() {
-        warn!("vt100 caught panic during process(), terminal state may be stale");
-    }
-}
-
 /// Extract DECSCUSR (Set Cursor Style) from raw terminal output.
 /// Format: ESC [ Ps SP q  where Ps is 0-6.
 /// Updates `shape` with the last DECSCUSR value found in the data.
@@ -67,6 +60,44 @@ fn extract_cursor_shape(data: &[u8], shape: &mut u8) {
     }
 }
 
+/// Whether `data` only moves (or shows/hides/reshapes) the cursor without
+/// touching any cell — the common case while typing over a static prompt,
+/// where the shell echoes nothing but a CR or a cursor motion. Deliberately
+/// conservative: anything not recognised (printable bytes, LF, erase or
+/// mode sequences) counts as a cell change.
+fn is_cursor_only(data: &[u8]) -> bool {
+    let len = data.len();
+    let mut i = 0;
+    while i < len {
+        match data[i] {
+            // CR, BS, BEL
+            b'\r' | 0x08 | 0x07 => i += 1,
+            0x1b if i + 1 < len && matches!(data[i + 1], b'7' | b'8') => i += 2,
+            0x1b if i + 1 < len && data[i + 1] == b'[' => {
+                let params_start = i + 2;
+                let mut j = params_start;
+                while j < len && (data[j].is_ascii_digit() || data[j] == b';' || data[j] == b'?') {
+                    j += 1;
+                }
+                let params = &data[params_start..j];
+                match data.get(j) {
+                    Some(b'A' | b'B' | b'C' | b'D' | b'E' | b'F' | b'G' | b'H' | b'f' | b'd') => {
+                        if params.contains(&b'?') {
+                            return false;
+                        }
+                        i = j + 1;
+                    }
+                    Some(b'h' | b'l') if params == b"?25" => i = j + 1,
+                    Some(b' ') if data.get(j + 1) == Some(&b'q') => i = j + 2,
+                    _ => return false,
+                }
+            }
+            _ => return false,
+        }
+    }
+    true
+}
+
 /// Type of change that occurred
 #[derive(Debug, Clone, PartialEq, Eq, Default)]
 pub enum ChangeType {
@@ -111,6 +142,9 @@ pub struct ProcessEventResult {
     /// push-based (wasm) path, e.g. reading a paste buffer after
     /// %paste-buffer-changed.
     pub commands: Vec<String>,
+    /// A client command run with `run_command_lines` finished: its run id,
+    /// every response block it produced, and whether all of them succeeded.
+    pub command_output: Option<(u64, String, bool)>,
 }
 
 /// Outcome of a single `StateAggregator::step` call.
@@ -162,6 +196,61 @@ pub enum SideEffect {
     },
     /// Forward an OSC 52 clipboard write to the system clipboard.
     WriteClipboard { pane_id: String, text: String },
+    /// The pane rang the terminal bell. At most one per pane per
+    /// `BELL_MIN_INTERVAL`; the runtime applies its bell policy.
+    Bell { pane_id: String },
+    /// Lines of plain text the pane just completed, for screen readers. Only
+    /// produced with accessible output on.
+    SpokenText { pane_id: String, lines: Vec<String> },
+    /// A widget in the pane sent data on its channel (OSC 7331).
+    WidgetData {
+        pane_id: String,
+        data: serde_json::Value,
+    },
+    /// The pane asked for a desktop notification (OSC 9/777). Not produced
+    /// under do-not-disturb.
+    Notify {
+        pane_id: String,
+        notification: super::osc::PaneNotification,
+    },
+    /// Run these status-line `#()` commands and report each one's output
+    /// with `StateAggregator::status_job_done`.
+    RunStatusJobs(Vec<String>),
+    /// A pane went away: its process exited or it was killed, and it did not
+    /// turn up elsewhere (a `break-pane`) by the end of the step.
+    PaneExited(super::exit_summary::ExitSummary),
+    /// A client command run with `run_command_lines` finished. `output` is
+    /// its response blocks joined; with `success` false it ends with the
+    /// error of the command that failed.
+    CommandOutput {
+        run_id: u64,
+        output: String,
+        success: bool,
+    },
+}
+
+/// Per-pane "focus follows output" rule, parsed from the
+/// `@tmuxy-focus-follows-output` pane option (see
+/// `constants::tmux_options::FOCUS_FOLLOWS_OUTPUT`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum FocusFollowsOutput {
+    /// Never raise the pane (option unset or unrecognised).
+    #[default]
+    Off,
+    /// Raise when the pane rings the terminal bell.
+    Bell,
+    /// Raise on any output.
+    Any,
+}
+
+impl FocusFollowsOutput {
+    pub fn parse(value: &str) -> Self {
+        match value.trim() {
+            "bell" => Self::Bell,
+            "any" => Self::Any,
+            _ => Self::Off,
+        }
+    }
 }
 
 /// State of a single pane with terminal emulation
@@ -176,7 +265,13 @@ pub struct PaneState {
     pub window_id: String,
 
     /// Terminal emulator for this pane
-    pub terminal: vt100::Parser,
+    pub terminal: Box<dyn TerminalBackend>,
+
+    /// Emulator `terminal` is recreated with on reset
+    pub backend: TerminalBackendKind,
+
+    /// Rows of history `terminal` keeps above the screen
+    pub scrollback_rows: usize,
 
     /// OSC sequence parser for hyperlinks and clipboard
     pub osc_parser: super::osc::OscParser,
@@ -239,6 +334,12 @@ pub struct PaneState {
     /// Selection start Y (row) - absolute history coordinate
     pub selection_start_y: u64,
 
+    /// Selection end X (column) - absolute, from tmux
+    pub selection_end_x: u32,
+
+    /// Selection end Y (row) - absolute history coordinate
+    pub selection_end_y: u64,
+
     /// History size (number of lines scrolled off the top)
     pub history_size: u64,
 
@@ -252,6 +353,52 @@ pub struct PaneState {
     /// Whether the cursor is hidden (DECTCEM mode 25 off / ESC[?25l)
     pub cursor_hidden: bool,
 
+    /// Whether the application asked for an extended keyboard protocol.
+    pub extended_keys: bool,
+
+    /// Attention rule from `@tmuxy-focus-follows-output`.
+    pub focus_follows_output: FocusFollowsOutput,
+
+    /// Answer the pane's capability queries; off with `@tmuxy-answerback off`.
+    pub answerback: bool,
+
+    /// Sandbox profile from `@tmuxy-sandbox`, empty if none.
+    pub sandbox: String,
+
+    /// Widget the pane displays, from `@tmuxy-widget`.
+    pub widget: Option<WidgetSpec>,
+
+    /// Working directory and process id, from the sync's details
+    /// `list-panes` (see `sync_fields`); `None` when not fetched.
+    pub cwd: Option<String>,
+    pub pid: Option<u32>,
+
+    /// Partial query carried between `%output` chunks.
+    queries: super::answerback::QueryScanner,
+
+    /// Keyboard protocol the application requested.
+    keyboard: crate::keyboard::KeyboardScanner,
+
+    /// tmux has been told the current theme colours for this pane.
+    theme_reported: bool,
+
+    /// Injected secrets masked out of this pane's content.
+    secret_masks: Vec<super::secret_mask::SecretMask>,
+
+    /// Output written against the aggregator's output budget.
+    output_budget: super::output_budget::OutputBudget,
+
+    /// Commands the pane's shell marked with OSC 133 (see `command_history`).
+    command_history: super::command_history::CommandHistory,
+
+    /// Whether the most recent `process_output` chunk contained a BEL that
+    /// was not an OSC terminator.
+    pub rang_bell: bool,
+
+    /// Completed rows already announced to screen readers; `None` unless
+    /// the aggregator's accessible output is on.
+    spoken: Option<super::spoken::SpokenText>,
+
     /// Whether terminal content has changed since last extraction
     content_dirty: bool,
 
@@ -262,17 +409,22 @@ pub struct PaneState {
 }
 
 impl PaneState {
-    pub fn new(id: &str, width: u32, height: u32) -> Self {
-        // Guard: vt100 panics on zero dimensions
-        let w = (width as u16).max(1);
-        let h = (height as u16).max(1);
+    pub fn new(
+        id: &str,
+        width: u32,
+        height: u32,
+        backend: TerminalBackendKind,
+        scrollback_rows: usize,
+    ) -> Self {
         let mut osc_parser = super::osc::OscParser::new();
         osc_parser.set_viewport_height(height);
         Self {
             id: id.to_string(),
             index: 0,
             window_id: String::new(),
-            terminal: vt100::Parser::new(h, w, crate::constants::REFLOW_SCROLLBACK_ROWS),
+            terminal: backend.create(height as u16, width as u16, scrollback_rows),
+            backend,
+            scrollback_rows,
             osc_parser,
             image_parser: super::images::ImageParser::new(),
             image_store: HashMap::new(),
@@ -296,10 +448,27 @@ impl PaneState {
             selection_present: false,
             selection_start_x: 0,
             selection_start_y: 0,
+            selection_end_x: 0,
+            selection_end_y: 0,
             history_size: 0,
             copy_mode_content: None,
             cursor_shape: 0,
             cursor_hidden: false,
+            extended_keys: false,
+            focus_follows_output: FocusFollowsOutput::Off,
+            answerback: true,
+            sandbox: String::new(),
+            cwd: None,
+            pid: None,
+            widget: None,
+            queries: super::answerback::QueryScanner::default(),
+            keyboard: crate::keyboard::KeyboardScanner::default(),
+            theme_reported: false,
+            secret_masks: Vec::new(),
+            output_budget: Default::default(),
+            command_history: Default::default(),
+            rang_bell: false,
+            spoken: None,
             content_dirty: true,
             cached_content: None,
         }
@@ -307,8 +476,6 @@ impl PaneState {
 
     /// Process new output for this pane (appends to existing buffer)
     pub fn process_output(&mut self, content: &[u8]) {
-        self.content_dirty = true;
-
         // Extract DECSCUSR (Set Cursor Style) before other processing.
         // Format: CSI Ps SP q  (e.g., \x1b[5 q for blinking bar)
         // We scan for the last occurrence since only the final state matters.
@@ -323,24 +490,112 @@ impl PaneState {
         // Process remaining bytes through OSC parser to extract hyperlinks/clipboard
         // Returns content with OSC sequences stripped for vt100
         let processed = self.osc_parser.process(&image_result.clean_bytes);
+        // OSC sequences (whose BEL terminators would false-positive) are
+        // already stripped, so any BEL left is a real bell.
+        self.rang_bell = processed.contains(&0x07);
+        // Cursor-only chunks leave every cell as it was: keep the cached
+        // content so the delta skips extraction and diffing and carries just
+        // the cursor fields.
+        if !is_cursor_only(&processed) {
+            self.content_dirty = true;
+        }
 
-        // Process through terminal emulator
-        safe_process(&mut self.terminal, &processed);
+        // Process through terminal emulator, stopping at each shell
+        // integration mark to read where it fell.
+        let mut fed = 0;
+        for mark in self.osc_parser.take_marks() {
+            self.terminal.process(&processed[fed..mark.offset]);
+            fed = mark.offset;
+            self.record_command_mark(mark);
+        }
+        self.terminal.process(&processed[fed..]);
 
-        // Derive alternate_on and mouse_any_flag from the vt100 parser state.
+        // Derive alternate_on and mouse_any_flag from the emulator state.
         // This is more reliable than polling list-panes, as it updates immediately
         // when the application sends the escape sequence.
-        self.alternate_on = self.terminal.screen().alternate_screen();
-        self.mouse_any_flag = !matches!(
-            self.terminal.screen().mouse_protocol_mode(),
-            vt100::MouseProtocolMode::None
-        );
-        self.cursor_hidden = self.terminal.screen().hide_cursor();
+        self.alternate_on = self.terminal.alternate_screen();
+        self.mouse_any_flag = self.terminal.mouse_tracking();
+        self.cursor_hidden = self.terminal.hide_cursor();
 
-        // Update image parser cursor position from vt100 state
-        let screen = self.terminal.screen();
-        let (row, col) = screen.cursor_position();
+        // Update image parser cursor position from emulator state
+        let (row, col) = self.terminal.cursor_position();
         self.image_parser.update_cursor(row, col);
+        self.osc_parser.sync_cursor(row, col, self.alternate_on);
+    }
+
+    /// Record an OSC 133 mark in the command history at the emulator's
+    /// cursor. A command line the shell didn't send along is read off the
+    /// screen, between where it started and where the output starts. Marks
+    /// from full-screen applications are ignored.
+    fn record_command_mark(&mut self, mark: super::osc::PromptMark) {
+        if self.terminal.alternate_screen() {
+            return;
+        }
+        let (row, col) = self.terminal.cursor_position();
+        let line = mark.scrolled_rows + u64::from(row);
+        let mark = match mark.mark {
+            SemanticMark::OutputStart { command: None } => SemanticMark::OutputStart {
+                command: self
+                    .command_history
+                    .input_start()
+                    .and_then(|(start_line, start_col)| {
+                        let start_row = start_line.checked_sub(mark.scrolled_rows)?;
+                        let mut content = self.terminal.cells(None);
+                        self.mask_secrets(&mut content, false);
+                        Some(super::command_history::screen_text(
+                            &content,
+                            (start_row as usize, start_col as usize),
+                            (row as usize, col as usize),
+                        ))
+                    }),
+            },
+            mark => mark,
+        };
+        self.command_history.apply(mark, line, col);
+    }
+
+    /// The commands run in this pane, oldest first (see `command_history`).
+    pub fn command_history(&self) -> Vec<CommandRecord> {
+        self.command_history.list(self.osc_parser.scrolled_rows())
+    }
+
+    /// The commands on screen, in the rows the pane shows: copy mode's view
+    /// when scrolled back. None while a full-screen application covers them.
+    fn command_blocks(&self, cursor_row: u16) -> Vec<CommandBlock> {
+        if self.alternate_on {
+            return Vec::new();
+        }
+        let scrolled = self.osc_parser.scrolled_rows() as i64;
+        let scrolled_back = if self.in_mode {
+            i64::from(self.scroll_position)
+        } else {
+            0
+        };
+        self.command_history.blocks(
+            scrolled - scrolled_back,
+            self.height,
+            scrolled + i64::from(cursor_row),
+        )
+    }
+
+    /// Bytes to write back to the pane in answer to queries in `content`
+    /// (see `answerback`), which also tracks the keyboard protocol the
+    /// application asks for. Nothing while answerback is off or the pane is in
+    /// copy mode, where tmux would read the reply as copy-mode keys.
+    pub fn answer_queries(&mut self, content: &[u8]) -> Vec<u8> {
+        let mut reply = self.queries.feed(content);
+        reply.extend(self.keyboard.feed(content));
+        self.extended_keys = !self.keyboard.mode().is_legacy();
+        if !self.answerback || self.in_mode {
+            return Vec::new();
+        }
+        reply
+    }
+
+    /// Whether the application asked for focus in/out reports and can take
+    /// them now; in copy mode tmux would read them as copy-mode keys.
+    pub fn wants_focus_reports(&self) -> bool {
+        self.keyboard.focus_events() && !self.in_mode
     }
 
     /// Reset terminal and process capture-pane output.
@@ -351,9 +606,7 @@ impl PaneState {
         self.cached_content = None;
 
         // Create fresh terminal to clear all state
-        let w = (self.width as u16).max(1);
-        let h = (self.height as u16).max(1);
-        self.terminal = vt100::Parser::new(h, w, crate::constants::REFLOW_SCROLLBACK_ROWS);
+        self.reset_terminal();
         // Keep image placements: the capture text can't recreate them (tmux
         // strips image escapes from captured history).
         self.image_parser.reset_for_capture();
@@ -363,7 +616,48 @@ impl PaneState {
         self.osc_parser.reset();
 
         let normalized = normalize_capture_bytes(content);
-        safe_process(&mut self.terminal, &normalized);
+        self.terminal.process(&normalized);
+        self.rebase_spoken_text();
+    }
+
+    /// Replace the emulator with a blank one at the pane's current size.
+    /// Its history goes with it.
+    pub fn reset_terminal(&mut self) {
+        self.terminal =
+            self.backend
+                .create(self.height as u16, self.width as u16, self.scrollback_rows);
+    }
+
+    /// Up to `lines` of the most recent rows scrolled off the top of the
+    /// screen, oldest first — the history the emulator holds, without a
+    /// capture-pane round-trip. Emptied whenever the emulator is reset (a
+    /// capture refresh or a move to another window).
+    pub fn scrollback(&mut self, lines: usize) -> PaneContent {
+        let mut content = self.terminal.scrollback(lines);
+        self.mask_secrets(&mut content, false);
+        content
+    }
+
+    /// Apply the live secret masks to `content` read from elsewhere (a
+    /// capture-pane of this pane), leaving the masks as they are.
+    pub fn mask_captured(&mut self, content: &mut PaneContent) {
+        self.mask_secrets(content, false);
+    }
+
+    /// Mask `secret` in this pane's content from now on (see `secret_mask`).
+    pub fn mask_secret(&mut self, secret: &str, expires: Instant, deadline: Instant) {
+        self.secret_masks.push(super::secret_mask::SecretMask::new(
+            secret, expires, deadline,
+        ));
+        self.content_dirty = true;
+    }
+
+    /// Apply the live secret masks to `content`. With `prune` (`content` is
+    /// the live screen), masks whose echo was seen but has since left it are
+    /// dropped.
+    fn mask_secrets(&mut self, content: &mut PaneContent, prune: bool) {
+        self.secret_masks
+            .retain_mut(|mask| mask.apply(content) || !prune || !mask.seen());
     }
 
     /// Resize the terminal.
@@ -374,33 +668,73 @@ impl PaneState {
             self.height = height;
             self.content_dirty = true;
             self.cached_content = None;
-            // Guard: vt100 panics on zero dimensions (subtract overflow in grid.rs)
-            let w = (width as u16).max(1);
-            let h = (height as u16).max(1);
             // Reflow the existing grid IN PLACE, preserving content and cursor
             // anchoring. The previous approach recreated the parser and replayed
             // the whole accumulated raw %output buffer, which re-scrolled that
             // output through a fresh grid — leaving short content BOTTOM-anchored
             // (blank rows prepended, prompt glued to the last row) after a
             // swap/resize in the fully client-side (v86) path, where there is no
-            // authoritative capture-pane pass to correct the replay. vt100's
-            // `set_size` rewraps each row to the new width and grows/shrinks the
-            // grid from the bottom, so top-anchored content stays where it is and
-            // the cursor is clamped — matching what a real terminal does on
-            // SIGWINCH. This also subsumes the original %layout-change case the
-            // replay was added for (content is reflowed, never lost).
-            self.terminal.screen_mut().set_size(h, w);
+            // authoritative capture-pane pass to correct the replay. The
+            // emulator rewraps soft-wrapped lines (and their scrollback) to the
+            // new width and grows/shrinks the grid around the cursor, so the
+            // pane keeps showing its text until the follow-up capture-pane
+            // lands — matching what a real terminal does on SIGWINCH. This also
+            // subsumes the original %layout-change case the replay was added
+            // for (content is reflowed, never lost).
+            self.terminal.set_size(height as u16, width as u16);
             self.image_parser.reset();
             // Drop stale hyperlink cell mappings (reflowed coordinates no longer
             // match) and realign the scroll compensation to the new height.
             self.osc_parser.reset();
             self.osc_parser.set_viewport_height(height);
+            self.rebase_spoken_text();
             true
         } else {
             false
         }
     }
 
+    /// Rows above the cursor as plain text.
+    fn completed_rows(&mut self) -> Vec<String> {
+        let (cursor_row, _) = self.terminal.cursor_position();
+        super::spoken::completed_rows(&self.get_content(), usize::from(cursor_row))
+    }
+
+    /// Start or stop the screen-reader stream. Starting treats what is on
+    /// screen now as already heard.
+    pub fn set_spoken_text(&mut self, enabled: bool) {
+        self.spoken = if enabled {
+            let rows = self.completed_rows();
+            Some(super::spoken::SpokenText::new(rows))
+        } else {
+            None
+        };
+    }
+
+    fn rebase_spoken_text(&mut self) {
+        if self.spoken.is_some() {
+            let rows = self.completed_rows();
+            if let Some(spoken) = self.spoken.as_mut() {
+                spoken.rebase(rows);
+            }
+        }
+    }
+
+    /// Lines of plain text completed since the last call, for screen
+    /// readers (see the `spoken` module). Empty unless enabled, and while
+    /// the alternate screen is up — full-screen apps redraw rather than
+    /// append.
+    pub fn take_spoken_lines(&mut self) -> Vec<String> {
+        if self.spoken.is_none() || self.alternate_on {
+            return Vec::new();
+        }
+        let rows = self.completed_rows();
+        self.spoken
+            .as_mut()
+            .map(|spoken| spoken.advance(rows))
+            .unwrap_or_default()
+    }
+
     /// Get the rendered screen content as structured cells.
     /// Uses cached content when terminal hasn't changed since last extraction.
     /// Returns an `Arc` so a clean cache hit is a refcount bump — repeated
@@ -411,10 +745,11 @@ impl PaneState {
                 return std::sync::Arc::clone(cached);
             }
         }
-        let content = std::sync::Arc::new(extract_cells_with_urls(
-            self.terminal.screen(),
-            Some(&self.osc_parser),
-        ));
+        let mut cells = self.terminal.cells(Some(&self.osc_parser));
+        if !self.secret_masks.is_empty() {
+            self.mask_secrets(&mut cells, true);
+        }
+        let content = std::sync::Arc::new(cells);
         self.cached_content = Some(std::sync::Arc::clone(&content));
         self.content_dirty = false;
         content
@@ -424,36 +759,39 @@ impl PaneState {
     /// Uses a temporary terminal to avoid corrupting the main terminal state,
     /// since %output events from background processes continue arriving during copy mode.
     pub fn process_copy_mode_capture(&mut self, content: &[u8]) {
-        let w = (self.width as u16).max(1);
-        let h = (self.height as u16).max(1);
-        let mut temp_terminal = vt100::Parser::new(h, w, 0);
+        let mut temp_terminal = self
+            .backend
+            .create(self.height as u16, self.width as u16, 0);
 
         let normalized = normalize_capture_bytes(content);
-        safe_process(&mut temp_terminal, &normalized);
-        self.copy_mode_content = Some(std::sync::Arc::new(extract_cells_from_screen(
-            temp_terminal.screen(),
-        )));
+        temp_terminal.process(&normalized);
+        let mut cells = temp_terminal.cells(None);
+        self.mask_secrets(&mut cells, false);
+        self.copy_mode_content = Some(std::sync::Arc::new(cells));
     }
 
     /// Build TmuxPane struct (uses &mut self for content caching)
     pub fn build_tmux_pane(&mut self) -> TmuxPane {
-        // Use vt100 emulator cursor for immediate feedback on output events.
-        // The vt100 cursor is updated on every %output event, while tmux_cursor_x/y
+        // Use the emulator cursor for immediate feedback on output events.
+        // It is updated on every %output event, while tmux_cursor_x/y
         // are only updated on periodic list-panes responses (every 500ms).
-        let screen = self.terminal.screen();
-        let vt100_cursor_x = screen.cursor_position().1 as u32;
-        let vt100_cursor_y = screen.cursor_position().0 as u32;
+        let (cursor_row, cursor_col) = self.terminal.cursor_position();
+        let vt100_cursor_x = cursor_col as u32;
+        let vt100_cursor_y = cursor_row as u32;
 
         // Convert absolute selection start Y to visible-area-relative coordinate
         // history_size = lines above the visible area
         // scroll_position = lines scrolled back from the bottom
         // view_start = history_size - scroll_position (absolute line at top of visible area)
-        let (sel_start_x, sel_start_y) = if self.selection_present {
+        let ((sel_start_x, sel_start_y), (sel_end_x, sel_end_y)) = if self.selection_present {
             let view_start = self.history_size as i64 - self.scroll_position as i64;
-            let visible_y = self.selection_start_y as i64 - view_start;
-            (self.selection_start_x, visible_y as i32)
+            let visible = |y: u64| (y as i64 - view_start) as i32;
+            (
+                (self.selection_start_x, visible(self.selection_start_y)),
+                (self.selection_end_x, visible(self.selection_end_y)),
+            )
         } else {
-            (0, 0)
+            ((0, 0), (0, 0))
         };
 
         TmuxPane {
@@ -485,12 +823,23 @@ impl PaneState {
             mouse_any_flag: self.mouse_any_flag,
             paused: self.paused,
             history_size: self.history_size,
+            scroll_position: self.scroll_position,
             selection_present: self.selection_present,
             selection_start_x: sel_start_x,
             selection_start_y: sel_start_y,
+            selection_end_x: sel_end_x,
+            selection_end_y: sel_end_y,
             images: self.image_parser.placements.clone(),
+            command_blocks: self.command_blocks(cursor_row),
             cursor_shape: self.cursor_shape,
             cursor_hidden: self.cursor_hidden,
+            extended_keys: self.extended_keys,
+            colors: self.osc_parser.colors().clone(),
+            sandbox: self.sandbox.clone(),
+            pointer_shape: self.osc_parser.pointer_shape().to_string(),
+            widget: self.widget.clone(),
+            cwd: self.cwd.clone(),
+            pid: self.pid,
         }
     }
 }
@@ -537,11 +886,29 @@ pub struct WindowState {
     /// True if float hides its header chrome (@tmuxy-float-noheader = 1).
     pub float_noheader: bool,
 
+    /// Float position in cells (@tmuxy-float-x/y); None = centered.
+    pub float_x: Option<u32>,
+    pub float_y: Option<u32>,
+
+    /// Stacking order among floats (@tmuxy-float-z).
+    pub float_z: Option<u32>,
+
+    /// True while the float is toggled out of view (@tmuxy-float-hidden = 1).
+    pub float_hidden: bool,
+
     /// Active pane ID in this window (tracked from %window-pane-changed events)
     pub active_pane_id: Option<String>,
 
     /// Whether this window has a zoomed pane (from %layout-change flags containing 'Z')
     pub zoomed: bool,
+
+    /// Evaluated `@tmuxy-status` format, set by the monitor (see
+    /// `StateAggregator::set_window_statuses`).
+    pub status: Option<String>,
+
+    /// Unix time of the window's last output (`#{window_activity}`), which
+    /// tmux keeps across tmuxy restarts. None until list-windows reports it.
+    pub activity: Option<u64>,
 }
 
 impl WindowState {
@@ -560,8 +927,14 @@ impl WindowState {
             float_drawer: None,
             float_bg: None,
             float_noheader: false,
+            float_x: None,
+            float_y: None,
+            float_z: None,
+            float_hidden: false,
             active_pane_id: None,
             zoomed: false,
+            status: None,
+            activity: None,
         }
     }
 
@@ -579,7 +952,12 @@ impl WindowState {
             float_drawer: self.float_drawer.clone(),
             float_bg: self.float_bg.clone(),
             float_noheader: self.float_noheader,
+            float_x: self.float_x,
+            float_y: self.float_y,
+            float_z: self.float_z,
+            float_hidden: self.float_hidden,
             zoomed: self.zoomed,
+            status: self.status.clone(),
         }
     }
 }
@@ -698,10 +1076,53 @@ fn parse_layout_node(bytes: &[u8], pos: &mut usize, panes: &mut Vec<LayoutPane>)
     // else: end of input or unexpected char — return gracefully
 }
 
+/// Scroll offsets tried per diff. Each costs one pass over the rows; blank
+/// screens match the top row at many offsets, and the nearest ones win anyway.
+const MAX_SCROLL_CANDIDATES: usize = 4;
+
+/// Best explanation of `prev → curr` as "scrolled up by n rows": returns `n`
+/// and the surviving rows that still differ after the shift (typically just
+/// the old prompt/cursor row that received text). Candidates are the offsets
+/// where `curr`'s top row appears in `prev`; the one needing the fewest rows
+/// resent wins.
+fn detect_scroll(
+    prev: &[crate::TerminalLine],
+    curr: &[crate::TerminalLine],
+) -> Option<(usize, Vec<usize>)> {
+    if prev.len() != curr.len() || curr.len() < 2 {
+        return None;
+    }
+    let len = curr.len();
+    (1..len)
+        .filter(|&n| prev[n] == curr[0])
+        .take(MAX_SCROLL_CANDIDATES)
+        .map(|n| {
+            let rewritten: Vec<usize> = (0..len - n).filter(|&i| curr[i] != prev[i + n]).collect();
+            (n, rewritten)
+        })
+        .min_by_key(|(n, rewritten)| n + rewritten.len())
+}
+
 /// Aggregates control mode events into coherent state
+/// Where the membership check that follows a pane group operation stands.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+enum GroupCheck {
+    #[default]
+    Idle,
+    /// An operation went out; check once its list-windows lands.
+    Armed,
+    /// The list-windows landed; check on this step.
+    Due,
+}
+
 pub struct StateAggregator {
     /// Session name (e.g., "tmuxy")
     session_name: String,
+    /// Session id (e.g., "$0"), once tmux has told us
+    session_id: Option<String>,
+    /// The session's group, when it was made with `new-session -t` or another
+    /// session was grouped with it (see `session_group`)
+    session_group: Option<SessionGroup>,
 
     /// Pane states indexed by pane ID
     panes: HashMap<String, PaneState>,
@@ -731,12 +1152,14 @@ pub struct StateAggregator {
     /// response that immediately follows it (each command in a control-mode
     /// command list gets its own %begin/%end block).
     buffer_read_armed: bool,
+    /// Client command output being collected between `TMUXY_RUN_BEGIN <id>`
+    /// and `TMUXY_RUN_END <id>`: the run id, the blocks so far, and whether
+    /// they all succeeded.
+    run_output: Option<(u64, String, bool)>,
 
-    /// Cached status line (optimization: only refresh on window events or periodic sync)
-    cached_status_line: String,
-
-    /// Whether status line needs refresh
-    status_line_dirty: bool,
+    /// The status line, refreshed through control mode on window events and
+    /// periodic syncs (see `status_line`).
+    status_line: StatusLine,
 
     // Delta state tracking
     /// Previous state snapshot for delta computation
@@ -745,6 +1168,9 @@ pub struct StateAggregator {
     /// Sequence number for delta updates
     delta_seq: u64,
 
+    /// Stamped on every update (see `begin_epoch`).
+    epoch: u64,
+
     /// When true, window/layout change events update internal state but
     /// return `state_changed: false` to suppress emission. Pane output
     /// events still emit immediately. Used during command-aware settling
@@ -770,12 +1196,141 @@ pub struct StateAggregator {
     settling_until: Option<Instant>,
     settling_started: Option<Instant>,
     settling_awaiting_first_event: bool,
+
+    /// Panes a focus-follows-output raise has been sent for. Output keeps
+    /// arriving while the raise round-trips, so without this every chunk
+    /// would queue another `select-window` / group switch. Cleared when
+    /// the active window changes or the next list-panes sync lands.
+    raise_pending: std::collections::HashSet<String>,
+
+    /// Pane group memberships to check against the next list-windows (see
+    /// `check_pane_groups`).
+    group_check: GroupCheck,
+
+    /// Untagged windows the first list-windows found: the session's own
+    /// windows from before tmuxy attached (all of them, for a session tmuxy
+    /// didn't create). They are adopted as tabs whatever their names, so a
+    /// user's window called `float` stays a window. `None` until then.
+    preexisting_windows: Option<std::collections::HashSet<String>>,
+
+    /// When each pane last showed signs of use — output, focus, or a mode
+    /// change — and whether it has since the aggregator first saw it. A pane
+    /// not used since is timed from then, with its window's
+    /// `window_activity` as a floor for how long it had been quiet before.
+    last_used: HashMap<String, (Instant, bool)>,
+
+    /// When each pane's last reported bell rang, for rate limiting.
+    last_bell: HashMap<String, Instant>,
+
+    /// Emulator new panes are created with.
+    terminal_backend: TerminalBackendKind,
+
+    /// History rows new panes' emulators keep.
+    scrollback_rows: usize,
+
+    /// Whether panes produce the screen-reader text stream
+    /// (`SideEffect::SpokenText`).
+    accessible_output: bool,
+
+    /// Whether a pane tmux pauses (flow control) is resumed straight away
+    /// (see `set_auto_resume`).
+    auto_resume: bool,
+
+    /// Bytes per second a pane may write before it is trimmed (see
+    /// `output_budget`); `None` leaves output untrimmed.
+    output_budget: Option<u64>,
+
+    /// Lines trimmed from emitted frames per pane, not yet taken with
+    /// `take_output_trims`.
+    output_trims: std::collections::BTreeMap<String, u64>,
+
+    /// Do-not-disturb is in effect (see `set_do_not_disturb`).
+    do_not_disturb: bool,
+
+    /// Server default colours (see `set_terminal_theme`).
+    terminal_theme: super::palette::TerminalColors,
+
+    /// Some client has focus (see `set_client_focus`).
+    client_focused: bool,
+
+    /// The pane last sent a focus-in report and not yet a focus-out.
+    focus_reported: Option<String>,
+
+    /// Whether the session's SSH agent socket answers (see `set_ssh_agent`).
+    ssh_agent: super::ssh_agent::SshAgentStatus,
+
+    /// What recently closed panes showed (see `exit_summary`).
+    exit_summaries: super::exit_summary::ExitSummaries,
+
+    /// Tab windows by when they were last visited (see `window_mru`).
+    window_mru: super::window_mru::MruOrder,
+}
+
+/// A pane that has been idle past a caller-chosen threshold.
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StalePane {
+    pub pane_id: String,
+    pub window_id: String,
+    pub window_name: String,
+    pub command: String,
+    pub idle_secs: u64,
+}
+
+pub use tmuxy_protocol::{DropEdge, PaneDropTarget, SplitDirection};
+
+/// The tmux command that performs a drop.
+pub fn drop_command(target: &PaneDropTarget) -> String {
+    match target {
+        PaneDropTarget::Swap { source, target } => format!("swap-pane -s {source} -t {target}"),
+        PaneDropTarget::Join {
+            source,
+            target,
+            edge,
+        } => {
+            let flags = match edge {
+                DropEdge::Left => "-b -h",
+                DropEdge::Right => "-h",
+                DropEdge::Top => "-b -v",
+                DropEdge::Bottom => "-v",
+            };
+            format!("join-pane {flags} -s {source} -t {target}")
+        }
+    }
+}
+
+/// A pane rectangle in window-relative cells.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize)]
+pub struct PaneRect {
+    pub x: u32,
+    pub y: u32,
+    pub width: u32,
+    pub height: u32,
+}
+
+/// Geometry a split would produce, for drawing a preview before running it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SplitPreview {
+    /// What is left of the split pane.
+    pub pane: PaneRect,
+    /// The pane the split creates.
+    pub new_pane: PaneRect,
 }
 
 /// Per-event debounce window during settling.
 pub(crate) const SETTLING_DEBOUNCE: std::time::Duration = std::time::Duration::from_millis(100);
 /// Safety ceiling — settling cannot extend past this from the arm point.
 pub(crate) const SETTLING_MAX: std::time::Duration = std::time::Duration::from_millis(500);
+/// Minimum gap between two reported bells from the same pane. A tab-completion
+/// miss or a `yes $'\a'` loop can ring hundreds of times a second.
+pub(crate) const BELL_MIN_INTERVAL: std::time::Duration = std::time::Duration::from_millis(500);
+/// How long an injected secret stays masked when its echo never shows (a
+/// password prompt that doesn't echo).
+pub(crate) const SECRET_MASK_TTL: std::time::Duration = std::time::Duration::from_secs(10);
+/// How long an injected secret stays masked at most, even while its echo
+/// is still on screen.
+pub(crate) const SECRET_MASK_MAX_AGE: std::time::Duration = std::time::Duration::from_secs(300);
 
 /// Marker printed (via `display-message -p`) immediately BEFORE a self-issued
 /// capture-pane command, carrying the target pane id. Routing captures by
@@ -835,6 +1390,28 @@ pub fn capture_command_range(pane_id: &str, start: i64, end: i64) -> String {
     marker_wrapped_capture(pane_id, &format!(" -S {start} -E {end}"))
 }
 
+/// Marker the monitor's liveness probe prints; the reply only proves tmux
+/// is still answering.
+pub const LIVENESS_MARKER: &str = "TMUXY_ALIVE";
+
+/// Marker printed before a client command (see `run_command_lines`).
+pub const RUN_BEGIN_MARKER: &str = "TMUXY_RUN_BEGIN";
+/// Marker printed after it.
+pub const RUN_END_MARKER: &str = "TMUXY_RUN_END";
+
+/// The control-mode lines that run a client's `command` and bracket its
+/// responses with `run_id`, for `SideEffect::CommandOutput`. The markers are
+/// lines of their own rather than `;`-joined: tmux drops the rest of a
+/// command list after a failing command, and the END marker must print
+/// regardless.
+pub fn run_command_lines(run_id: u64, command: &str) -> Vec<String> {
+    vec![
+        format!("display-message -p '{RUN_BEGIN_MARKER} {run_id}'"),
+        command.to_string(),
+        format!("display-message -p '{RUN_END_MARKER} {run_id}'"),
+    ]
+}
+
 /// Shared marker-bracket format for both capture commands, so the BEGIN/END
 /// bracketing can't drift between the plain and ranged variants.
 fn marker_wrapped_capture(pane_id: &str, range: &str) -> String {
@@ -853,6 +1430,8 @@ impl StateAggregator {
     pub fn with_session_name(session_name: &str) -> Self {
         Self {
             session_name: session_name.to_string(),
+            session_id: None,
+            session_group: None,
             panes: HashMap::new(),
             windows: HashMap::new(),
             active_window_id: None,
@@ -860,20 +1439,275 @@ impl StateAggregator {
             capture_armed: None,
             pending_buffer_reads: std::collections::VecDeque::new(),
             buffer_read_armed: false,
+            run_output: None,
 
-            cached_status_line: String::new(),
-            status_line_dirty: true, // Fetch on first state request
+            status_line: StatusLine::default(),
             prev_state: None,
             delta_seq: 0,
+            epoch: 0,
             suppress_window_emissions: false,
             panes_moved_window: std::collections::HashSet::new(),
             early_output: HashMap::new(),
             settling_until: None,
             settling_started: None,
             settling_awaiting_first_event: false,
+            raise_pending: std::collections::HashSet::new(),
+            group_check: GroupCheck::Idle,
+            preexisting_windows: None,
+            last_used: HashMap::new(),
+            last_bell: HashMap::new(),
+            terminal_backend: TerminalBackendKind::default(),
+            scrollback_rows: crate::constants::REFLOW_SCROLLBACK_ROWS,
+            accessible_output: false,
+            auto_resume: true,
+            output_budget: None,
+            output_trims: Default::default(),
+            do_not_disturb: false,
+            terminal_theme: Default::default(),
+            client_focused: false,
+            focus_reported: None,
+            ssh_agent: Default::default(),
+            exit_summaries: Default::default(),
+            window_mru: Default::default(),
+        }
+    }
+
+    /// Choose the emulator for panes created from now on. Existing panes
+    /// keep theirs until they are recreated.
+    pub fn set_terminal_backend(&mut self, backend: TerminalBackendKind) {
+        self.terminal_backend = backend;
+    }
+
+    /// Set how many history rows new panes keep. Never below
+    /// `REFLOW_SCROLLBACK_ROWS`, which resize reflow depends on.
+    pub fn set_scrollback_rows(&mut self, rows: usize) {
+        self.scrollback_rows = rows.max(crate::constants::REFLOW_SCROLLBACK_ROWS);
+    }
+
+    /// Turn the screen-reader text stream on or off for every pane, current
+    /// and future.
+    pub fn set_accessible_output(&mut self, enabled: bool) {
+        self.accessible_output = enabled;
+        for pane in self.panes.values_mut() {
+            pane.set_spoken_text(enabled);
+        }
+    }
+
+    /// Whether `%pause` is answered with `SideEffect::ResumePane` at once
+    /// (the default). Off, a paused pane stays paused until the runtime
+    /// resumes it, so a flood of output stops reaching clients.
+    pub fn set_auto_resume(&mut self, on: bool) {
+        self.auto_resume = on;
+    }
+
+    /// Limit each pane's output to `bytes_per_sec`; a pane over it is
+    /// trimmed (see `output_budget`). `None` turns budgets off.
+    pub fn set_output_budget(&mut self, bytes_per_sec: Option<u64>) {
+        self.output_budget = bytes_per_sec;
+    }
+
+    /// Forget every pane's working directory and process id, once the sync
+    /// no longer fetches them (see `sync_fields`).
+    pub fn clear_pane_details(&mut self) {
+        for pane in self.panes.values_mut() {
+            pane.cwd = None;
+            pane.pid = None;
+        }
+    }
+
+    /// Whether a pane is over its output budget, so its frames should go
+    /// out at most every `TRIMMED_FRAME_INTERVAL`.
+    pub fn output_trimmed(&self, pane_id: &str) -> bool {
+        self.output_budget.is_some()
+            && self
+                .panes
+                .get(pane_id)
+                .is_some_and(|pane| pane.output_budget.trimmed())
+    }
+
+    /// Panes whose emitted frames skipped lines since the last call, with
+    /// how many, for the runtime to report (e.g. to offer the full log).
+    pub fn take_output_trims(&mut self) -> Vec<(String, u64)> {
+        std::mem::take(&mut self.output_trims).into_iter().collect()
+    }
+
+    /// Mark a pane's output paused or flowing, for flow-control actions the
+    /// runtime takes itself (`refresh-client -A`). Returns whether it
+    /// changed; unknown panes are left alone.
+    pub fn set_pane_paused(&mut self, pane_id: &str, paused: bool) -> bool {
+        match self.panes.get_mut(pane_id) {
+            Some(pane) => std::mem::replace(&mut pane.paused, paused) != paused,
+            None => false,
+        }
+    }
+
+    /// Turn do-not-disturb on or off. While it is on, bells are dropped and
+    /// focus-follows-output never raises a pane; the flag also rides along in
+    /// `TmuxState` so clients can show it. Returns whether it changed.
+    pub fn set_do_not_disturb(&mut self, on: bool) -> bool {
+        std::mem::replace(&mut self.do_not_disturb, on) != on
+    }
+
+    pub fn do_not_disturb(&self) -> bool {
+        self.do_not_disturb
+    }
+
+    /// Record the session's SSH agent status, checked by the monitor; it
+    /// rides along in `TmuxState`. Returns whether it changed.
+    pub fn set_ssh_agent(&mut self, status: super::ssh_agent::SshAgentStatus) -> bool {
+        std::mem::replace(&mut self.ssh_agent, status) != status
+    }
+
+    pub fn ssh_agent(&self) -> super::ssh_agent::SshAgentStatus {
+        self.ssh_agent
+    }
+
+    /// Set each window's evaluated status format; windows missing from
+    /// `statuses` have none. Returns whether any window's status changed.
+    pub fn set_window_statuses(&mut self, statuses: &HashMap<String, String>) -> bool {
+        let mut changed = false;
+        for window in self.windows.values_mut() {
+            let status = statuses.get(&window.id).filter(|s| !s.is_empty()).cloned();
+            if window.status != status {
+                window.status = status;
+                changed = true;
+            }
+        }
+        changed
+    }
+
+    /// Start a new epoch: the next update is a full state stamped with
+    /// `epoch`, and every delta after it carries the same stamp. The monitor
+    /// picks a fresh epoch per control-mode connection, so a client holding
+    /// state from an earlier connection can tell its deltas no longer apply.
+    pub fn begin_epoch(&mut self, epoch: u64) {
+        self.epoch = epoch;
+        self.prev_state = None;
+    }
+
+    pub fn epoch(&self) -> u64 {
+        self.epoch
+    }
+
+    /// The last emitted state again, as a full update: exactly the base the
+    /// next delta applies to, so a client that takes it can carry on with the
+    /// live stream. `None` before the first update, which is full anyway.
+    pub fn full_update(&self) -> Option<crate::StateUpdate> {
+        self.prev_state
+            .clone()
+            .map(|state| crate::StateUpdate::Full { state })
+    }
+
+    /// Seq of the last emitted update: the delta [`Self::full_update`] is
+    /// up to date with. The next delta takes the one after it.
+    pub fn delta_seq(&self) -> u64 {
+        self.delta_seq
+    }
+
+    /// Set the server's default colours, sent to clients as `TmuxState::theme`
+    /// and reported to tmux for every pane so it answers OSC 10/11 queries
+    /// with them. Returns whether it changed.
+    pub fn set_terminal_theme(&mut self, theme: super::palette::TerminalColors) -> bool {
+        let theme = theme.normalized();
+        if theme == self.terminal_theme {
+            return false;
+        }
+        self.terminal_theme = theme;
+        for pane in self.panes.values_mut() {
+            pane.theme_reported = false;
+        }
+        true
+    }
+
+    /// `refresh-client -r` reports of the theme for panes tmux hasn't been
+    /// told about yet. Run on every step, so new panes are covered as they
+    /// appear.
+    pub fn collect_theme_report_commands(&mut self) -> Vec<String> {
+        let mut cmds = Vec::new();
+        for pane in self.panes.values_mut().filter(|p| !p.theme_reported) {
+            pane.theme_reported = true;
+            cmds.extend(super::palette::report_commands(
+                &self.terminal_theme,
+                &pane.id,
+            ));
+        }
+        cmds
+    }
+
+    /// Record whether any client has focus. Panes that enabled focus
+    /// reporting hear about it on the next step (see
+    /// `collect_focus_commands`).
+    pub fn set_client_focus(&mut self, focused: bool) {
+        self.client_focused = focused;
+    }
+
+    /// `CSI O` to the pane that last got `CSI I` and `CSI I` to the one that
+    /// has focus now, for panes that asked for focus reports. A pane has
+    /// focus while it is the active window's active pane and some client
+    /// has focus. Run on every step, so pane switches, window switches and
+    /// applications turning reporting on are all covered.
+    pub fn collect_focus_commands(&mut self) -> Vec<String> {
+        let focused = self
+            .client_focused
+            .then_some(self.active_window_id.as_deref())
+            .flatten()
+            .and_then(|window_id| {
+                self.panes
+                    .values()
+                    .find(|p| p.active && p.window_id == window_id)
+            })
+            .filter(|p| p.wants_focus_reports())
+            .map(|p| p.id.clone());
+        if focused == self.focus_reported {
+            return Vec::new();
+        }
+        let mut cmds = Vec::new();
+        let previous = std::mem::replace(&mut self.focus_reported, focused.clone());
+        if let Some(pane) = previous.and_then(|id| self.panes.get(&id)) {
+            if pane.wants_focus_reports() {
+                cmds.push(crate::text_input::hex_command(&pane.id, b"\x1b[O"));
+            }
+        }
+        if let Some(pane_id) = focused {
+            cmds.push(crate::text_input::hex_command(&pane_id, b"\x1b[I"));
+        }
+        cmds
+    }
+
+    /// Type-ahead mask for a secret about to be written to `pane_id`: its
+    /// echo is hidden from every content this aggregator hands out until it
+    /// leaves the screen, or for `SECRET_MASK_TTL` if it never shows, and for
+    /// no longer than `SECRET_MASK_MAX_AGE`. Returns false for an unknown
+    /// pane.
+    pub fn mask_secret(&mut self, pane_id: &str, secret: &str, now: Instant) -> bool {
+        let Some(pane) = self.panes.get_mut(pane_id) else {
+            return false;
+        };
+        pane.mask_secret(secret, now + SECRET_MASK_TTL, now + SECRET_MASK_MAX_AGE);
+        true
+    }
+
+    /// Recent history held by a pane's emulator (see `PaneState::scrollback`),
+    /// or `None` if the pane is unknown.
+    pub fn pane_scrollback(&mut self, pane_id: &str, lines: usize) -> Option<PaneContent> {
+        self.panes.get_mut(pane_id).map(|p| p.scrollback(lines))
+    }
+
+    /// Hide `pane_id`'s live secret masks in `content` captured from tmux,
+    /// which the emulator never saw (see `PaneState::mask_captured`). Content
+    /// of an unknown pane is left as is.
+    pub fn mask_captured(&mut self, pane_id: &str, content: &mut PaneContent) {
+        if let Some(pane) = self.panes.get_mut(pane_id) {
+            pane.mask_captured(content);
         }
     }
 
+    /// The commands run in `pane_id`, oldest first (see `command_history`);
+    /// `None` for an unknown pane.
+    pub fn command_history(&self, pane_id: &str) -> Option<Vec<CommandRecord>> {
+        self.panes.get(pane_id).map(PaneState::command_history)
+    }
+
     /// Enable or disable window/layout emission suppression.
     /// When suppressed, window/layout events still update internal state
     /// but `process_event()` returns `state_changed: false` for those events.
@@ -945,33 +1779,37 @@ impl StateAggregator {
         self.settling_until = Some(debounced.min(max_deadline));
     }
 
-    /// Refresh status line if dirty, otherwise use cached value.
-    /// Width is the total terminal width from pane layout, used for padding.
+    /// The status line from the last refresh. Width is the total terminal
+    /// width from pane layout, used for padding.
     fn get_status_line(&mut self, width: usize) -> String {
-        if self.status_line_dirty {
-            // Native refreshes the status line via a `capture-pane` on the status
-            // window. On wasm there is no tmux to call — the host supplies it via
-            // `set_status_line`, so we keep the cached value here.
-            #[cfg(feature = "native")]
-            {
-                self.cached_status_line =
-                    crate::executor::capture_status_line(&self.session_name, width)
-                        .unwrap_or_default();
-            }
-            #[cfg(not(feature = "native"))]
-            {
-                let _ = width;
-            }
-            self.status_line_dirty = false;
-        }
-        self.cached_status_line.clone()
+        self.status_line.render(width)
     }
 
-    /// Set the status-line text directly (used by non-native hosts that fetch it
-    /// out-of-band, e.g. the wasm/v86 path).
+    /// Set the status-line text directly, for hosts that fetch it
+    /// out-of-band. The aggregator stops refreshing it.
     pub fn set_status_line(&mut self, status: String) {
-        self.cached_status_line = status;
-        self.status_line_dirty = false;
+        self.status_line.set_fixed(status);
+    }
+
+    /// The command refreshing the status line now, unless one is in flight.
+    /// For the runtime's `status-interval` timer.
+    pub fn refresh_status_line(&mut self, now: Instant) -> Option<String> {
+        self.status_line.mark_dirty();
+        self.status_line.refresh_command(&self.session_name, now)
+    }
+
+    /// `status-interval` as of the last refresh; `None` when it is 0.
+    pub fn status_interval(&self) -> Option<Duration> {
+        self.status_line.interval()
+    }
+
+    /// Record the output of a job from `SideEffect::RunStatusJobs`. Returns
+    /// the command refreshing the status line when the output changed.
+    pub fn status_job_done(&mut self, command: &str, output: &str, now: Instant) -> Option<String> {
+        if !self.status_line.job_done(command, output, now) {
+            return None;
+        }
+        self.status_line.refresh_command(&self.session_name, now)
     }
 
     /// Register in-flight capture-pane commands and return only pane IDs that
@@ -989,20 +1827,515 @@ impl StateAggregator {
         queued
     }
 
-    /// Get the list of window IDs
-    pub fn window_ids(&self) -> Vec<String> {
-        self.windows.keys().cloned().collect()
+    /// Whether `pane_id` is a known pane.
+    pub fn has_pane(&self, pane_id: &str) -> bool {
+        self.panes.contains_key(pane_id)
     }
 
-    /// Provisional positional index for a brand-new window: one past the
-    /// current highest. tmux window IDs (`@N`, monotonic allocation) and
-    /// window indices (positional) are independent, so `WindowState::new`'s
-    /// fallback of parsing the index out of the id is wrong the moment they
-    /// diverge (any window close/create churn). `%window-add`/`%window-renamed`
-    /// carry only the id; a correct index otherwise waits for the follow-up
-    /// list-windows. A new window is almost always appended at the end, so
-    /// max+1 is right immediately; list-windows corrects the rare
-    /// insert-in-the-middle case.
+    /// Whether a capture-pane for `pane_id` is still awaiting its response.
+    pub fn capture_pending(&self, pane_id: &str) -> bool {
+        self.pending_captures.iter().any(|id| id == pane_id)
+    }
+
+    /// The session's active window, once known.
+    pub fn active_window_id(&self) -> Option<&str> {
+        self.active_window_id.as_deref()
+    }
+
+    /// Every float window with its pane, for [`crate::float::FloatOp`].
+    pub fn floats(&self) -> Vec<crate::float::FloatInfo> {
+        self.windows
+            .values()
+            .filter(|w| w.window_type == Some(WindowType::Float))
+            .filter_map(|w| {
+                let pane = self.panes.values().find(|p| p.window_id == w.id)?;
+                Some(crate::float::FloatInfo {
+                    window_id: w.id.clone(),
+                    pane_id: pane.id.clone(),
+                    width: w.float_width,
+                    height: w.float_height,
+                    z: w.float_z,
+                    hidden: w.float_hidden,
+                })
+            })
+            .collect()
+    }
+
+    /// Every pane group with its members in tab order, for
+    /// [`crate::pane_group::GroupOp`]. Members tmux no longer has are left out.
+    pub fn pane_groups(&self) -> Vec<crate::pane_group::GroupInfo> {
+        let mut groups: Vec<crate::pane_group::GroupInfo> = Vec::new();
+        let group_windows = self
+            .windows
+            .values()
+            .filter(|w| w.window_type == Some(WindowType::Group));
+        for order in group_windows.filter_map(|w| w.group_panes.as_ref()) {
+            let known = |id: &String| {
+                groups
+                    .iter()
+                    .flat_map(|g| &g.members)
+                    .any(|m| m.pane_id == *id)
+            };
+            if order.iter().any(known) {
+                continue;
+            }
+            let members = order
+                .iter()
+                .filter_map(|id| self.group_member(id))
+                .collect();
+            groups.push(crate::pane_group::GroupInfo { members });
+        }
+        groups
+    }
+
+    /// Check every group's membership once the next list-windows lands,
+    /// after an operation that may have raced a script or another client.
+    pub fn check_pane_groups(&mut self) {
+        self.group_check = GroupCheck::Armed;
+    }
+
+    /// Repairs for memberships out of step with the panes tmux reports,
+    /// when a check is due (see [`crate::pane_group::repair_commands`]).
+    /// Followed by a list-windows, since tmux doesn't announce option
+    /// changes.
+    pub fn collect_group_repair_commands(&mut self) -> Vec<String> {
+        if self.group_check != GroupCheck::Due {
+            return Vec::new();
+        }
+        self.group_check = GroupCheck::Idle;
+        let mut group_windows: Vec<&WindowState> = self
+            .windows
+            .values()
+            .filter(|w| w.window_type == Some(WindowType::Group))
+            .collect();
+        group_windows.sort_by_key(|w| w.index);
+        let windows: Vec<crate::pane_group::GroupWindow> = group_windows
+            .into_iter()
+            .map(|w| crate::pane_group::GroupWindow {
+                window_id: w.id.clone(),
+                pane_id: self
+                    .panes
+                    .values()
+                    .find(|p| p.window_id == w.id)
+                    .map(|p| p.id.clone()),
+                panes: w.group_panes.clone().unwrap_or_default(),
+            })
+            .collect();
+        let mut cmds =
+            crate::pane_group::repair_commands(&windows, |id| self.panes.contains_key(id));
+        if !cmds.is_empty() {
+            warn!(count = cmds.len(), "repairing pane group memberships");
+            cmds.push(crate::constants::tmux_formats::LIST_WINDOWS_CMD.to_string());
+        }
+        cmds
+    }
+
+    /// `pane_id` as a group member, whether or not it is in a group.
+    pub fn group_member(&self, pane_id: &str) -> Option<crate::pane_group::GroupMember> {
+        let pane = self.panes.get(pane_id)?;
+        let window_type = self.windows.get(&pane.window_id)?.window_type;
+        Some(crate::pane_group::GroupMember {
+            pane_id: pane.id.clone(),
+            window_id: pane.window_id.clone(),
+            width: pane.width,
+            height: pane.height,
+            visible: window_type != Some(WindowType::Group),
+        })
+    }
+
+    /// Get the list of window IDs
+    pub fn window_ids(&self) -> Vec<String> {
+        self.windows.keys().cloned().collect()
+    }
+
+    /// Widget panes by window and pane index, in that order, for
+    /// remembering across tmux restarts.
+    pub fn placed_widgets(&self) -> Vec<PlacedWidget> {
+        let mut placed: Vec<PlacedWidget> = self
+            .panes
+            .values()
+            .filter_map(|pane| {
+                Some(PlacedWidget {
+                    window_index: self.windows.get(&pane.window_id)?.index,
+                    pane_index: pane.index,
+                    spec: pane.widget.clone()?,
+                })
+            })
+            .collect();
+        placed.sort_by_key(|w| (w.window_index, w.pane_index));
+        placed
+    }
+
+    /// Remembered widgets whose pane is back but sits at a shell prompt
+    /// instead of running the widget, paired with that pane's id.
+    pub fn widgets_to_restore(&self, placed: &[PlacedWidget]) -> Vec<(String, WidgetSpec)> {
+        placed
+            .iter()
+            .filter_map(|w| {
+                let window = self
+                    .windows
+                    .values()
+                    .find(|win| win.index == w.window_index)?;
+                let (pane_id, pane) = self
+                    .panes
+                    .iter()
+                    .find(|(_, pane)| pane.window_id == window.id && pane.index == w.pane_index)?;
+                let at_shell = crate::widget::SHELL_COMMANDS.contains(&pane.command.as_str());
+                (pane.widget.is_none() && at_shell).then(|| (pane_id.clone(), w.spec.clone()))
+            })
+            .collect()
+    }
+
+    /// Each pane's foreground command, as `(pane_id, command)` pairs.
+    pub fn pane_commands(&self) -> Vec<(String, String)> {
+        self.panes
+            .iter()
+            .map(|(pane_id, pane)| (pane_id.clone(), pane.command.clone()))
+            .collect()
+    }
+
+    /// Panes idle for at least `min_idle` as of `now` (`unix_now` in Unix
+    /// seconds), longest-idle first. A pane that hasn't been used since
+    /// tmuxy first saw it has been idle for at least as long as its window
+    /// (`window_activity`, which tmux keeps across tmuxy restarts).
+    pub fn stale_panes(
+        &self,
+        now: Instant,
+        unix_now: u64,
+        min_idle: std::time::Duration,
+    ) -> Vec<StalePane> {
+        let mut stale: Vec<StalePane> = self
+            .last_used
+            .iter()
+            .filter_map(|(pane_id, (at, used))| {
+                let pane = self.panes.get(pane_id)?;
+                let window = self.windows.get(&pane.window_id);
+                let mut idle_secs = now.saturating_duration_since(*at).as_secs();
+                if !used {
+                    if let Some(activity) = window.and_then(|w| w.activity) {
+                        idle_secs = idle_secs.max(unix_now.saturating_sub(activity));
+                    }
+                }
+                if idle_secs < min_idle.as_secs() {
+                    return None;
+                }
+                Some(StalePane {
+                    pane_id: pane_id.clone(),
+                    window_id: pane.window_id.clone(),
+                    window_name: window.map(|w| w.name.clone()).unwrap_or_default(),
+                    command: pane.command.clone(),
+                    idle_secs,
+                })
+            })
+            .collect();
+        stale.sort_by(|a, b| {
+            b.idle_secs
+                .cmp(&a.idle_secs)
+                .then_with(|| a.pane_id.cmp(&b.pane_id))
+        });
+        stale
+    }
+
+    /// Drop the panes matching `gone`, keeping what each showed (see
+    /// `exit_summary`).
+    fn remove_panes(&mut self, gone: impl Fn(&PaneState) -> bool) {
+        let ids: Vec<String> = self
+            .panes
+            .values()
+            .filter(|p| gone(p))
+            .map(|p| p.id.clone())
+            .collect();
+        for id in ids {
+            let Some(mut pane) = self.panes.remove(&id) else {
+                continue;
+            };
+            let window_name = self
+                .windows
+                .get(&pane.window_id)
+                .map(|w| w.name.clone())
+                .unwrap_or_default();
+            self.exit_summaries
+                .record(ExitSummary::capture(&mut pane, window_name));
+        }
+    }
+
+    /// What the most recently closed panes showed, most recent first.
+    pub fn exit_summaries(&self, now: Instant) -> Vec<ExitSummary> {
+        self.exit_summaries.list(now)
+    }
+
+    /// Tab windows, most recently visited first, and where a cycle in
+    /// progress is as of `now`.
+    pub fn window_mru(&self, now: Instant) -> WindowMru {
+        self.window_mru.view(now)
+    }
+
+    /// Step the Alt-Tab cycle (starting one if needed), returning the window
+    /// to select. Windows passed through aren't promoted until
+    /// `end_window_cycle`.
+    pub fn step_window_cycle(&mut self, reverse: bool, now: Instant) -> Option<String> {
+        self.window_mru.step(reverse, now)
+    }
+
+    /// End the Alt-Tab cycle, promoting the window it landed on.
+    pub fn end_window_cycle(&mut self) {
+        self.window_mru.end();
+    }
+
+    /// Bring the MRU order up to date with the tab windows and the active
+    /// one.
+    fn track_window_use(&mut self, now: Instant) {
+        let mut tabs: Vec<&WindowState> = self
+            .windows
+            .values()
+            .filter(|w| w.window_type == Some(WindowType::Tab))
+            .collect();
+        tabs.sort_by_key(|w| w.index);
+        let tabs: Vec<&str> = tabs.iter().map(|w| w.id.as_str()).collect();
+        self.window_mru
+            .update(&tabs, self.active_window_id.as_deref(), now);
+    }
+
+    /// Mark `used_pane` as used at `now` and start the clock for newly seen
+    /// panes (dropping closed ones).
+    fn track_pane_use(&mut self, used_pane: Option<String>, now: Instant) {
+        if let Some(pane_id) = used_pane {
+            if self.panes.contains_key(&pane_id) {
+                self.last_used.insert(pane_id, (now, true));
+            }
+        }
+        if self.last_used.len() != self.panes.len() {
+            let panes = &self.panes;
+            self.last_used.retain(|id, _| panes.contains_key(id));
+            for id in self.panes.keys() {
+                self.last_used.entry(id.clone()).or_insert((now, false));
+            }
+        }
+    }
+
+    /// Whether the output just processed for `pane_id` rang a bell that is
+    /// outside the pane's rate limit; records the ring when it is.
+    fn take_bell(&mut self, pane_id: &str, now: Instant) -> bool {
+        if !self.panes.get(pane_id).is_some_and(|p| p.rang_bell) {
+            return false;
+        }
+        if let Some(prev) = self.last_bell.get(pane_id) {
+            if now.duration_since(*prev) < BELL_MIN_INTERVAL {
+                return false;
+            }
+        }
+        self.last_bell.insert(pane_id.to_string(), now);
+        true
+    }
+
+    /// Translate a drag on a pane border into `resize-pane` commands.
+    ///
+    /// `from` must sit on a border cell of `window_id` (window-relative, as
+    /// in the layout geometry); the border is found from the pane rectangles
+    /// the layout parser produced, and moved by the `from → to` delta in one
+    /// command per axis. A corner where a vertical and a horizontal border
+    /// meet yields both. Callers coalesce motion by passing either the whole
+    /// gesture or the previous and current pointer cell — each call lands the
+    /// border on `to`, so the next segment starts on it again.
+    pub fn drag_resize_commands(
+        &self,
+        window_id: &str,
+        from: (u32, u32),
+        to: (u32, u32),
+    ) -> Vec<String> {
+        if self.windows.get(window_id).is_some_and(|w| w.zoomed) {
+            return Vec::new();
+        }
+        let panes: Vec<&PaneState> = self
+            .panes
+            .values()
+            .filter(|p| p.window_id == window_id)
+            .collect();
+        let (fx, fy) = from;
+        let (tx, ty) = to;
+        let mut cmds = Vec::new();
+
+        // Vertical border: the column just right of a pane that has a
+        // neighbour on the other side.
+        let left_of_border = panes.iter().find(|p| {
+            p.x + p.width == fx
+                && (p.y..=p.y + p.height).contains(&fy)
+                && panes.iter().any(|q| q.x == fx + 1)
+        });
+        if let Some(p) = left_of_border {
+            match tx.cmp(&fx) {
+                std::cmp::Ordering::Greater => {
+                    cmds.push(format!("resize-pane -t {} -R {}", p.id, tx - fx))
+                }
+                std::cmp::Ordering::Less => {
+                    cmds.push(format!("resize-pane -t {} -L {}", p.id, fx - tx))
+                }
+                std::cmp::Ordering::Equal => {}
+            }
+        }
+
+        // Horizontal border: the row just below a pane with a neighbour under it.
+        let above_border = panes.iter().find(|p| {
+            p.y + p.height == fy
+                && (p.x..=p.x + p.width).contains(&fx)
+                && panes.iter().any(|q| q.y == fy + 1)
+        });
+        if let Some(p) = above_border {
+            match ty.cmp(&fy) {
+                std::cmp::Ordering::Greater => {
+                    cmds.push(format!("resize-pane -t {} -D {}", p.id, ty - fy))
+                }
+                std::cmp::Ordering::Less => {
+                    cmds.push(format!("resize-pane -t {} -U {}", p.id, fy - ty))
+                }
+                std::cmp::Ordering::Equal => {}
+            }
+        }
+        cmds
+    }
+
+    /// Resolve dropping pane `source` at window-relative cell `(x, y)`.
+    ///
+    /// The pane under the point in `source`'s window is the target. The outer
+    /// quarter of the target on each side docks the source against that edge
+    /// (`join-pane`); anywhere else swaps the two. Returns `None` when the
+    /// point is on a border, outside the window, on `source` itself, or the
+    /// window is zoomed.
+    pub fn pane_drop_target(&self, source: &str, x: u32, y: u32) -> Option<PaneDropTarget> {
+        let window_id = &self.panes.get(source)?.window_id;
+        if self.windows.get(window_id).is_some_and(|w| w.zoomed) {
+            return None;
+        }
+        let target = self.panes.values().find(|p| {
+            p.window_id == *window_id
+                && (p.x..p.x + p.width).contains(&x)
+                && (p.y..p.y + p.height).contains(&y)
+        })?;
+        if target.id == source {
+            return None;
+        }
+        // Distance to each edge as a fraction of the pane's extent on that axis.
+        let (dx, dy) = (x - target.x, y - target.y);
+        let fraction = |dist: u32, span: u32| f64::from(dist) / f64::from(span.max(1));
+        let edge = [
+            (DropEdge::Left, fraction(dx, target.width)),
+            (
+                DropEdge::Right,
+                fraction(target.width - 1 - dx, target.width),
+            ),
+            (DropEdge::Top, fraction(dy, target.height)),
+            (
+                DropEdge::Bottom,
+                fraction(target.height - 1 - dy, target.height),
+            ),
+        ]
+        .into_iter()
+        .filter(|(_, f)| *f < 0.25)
+        .min_by(|a, b| a.1.total_cmp(&b.1))
+        .map(|(edge, _)| edge);
+
+        let (source, target) = (source.to_string(), target.id.clone());
+        Some(match edge {
+            Some(edge) => PaneDropTarget::Join {
+                source,
+                target,
+                edge,
+            },
+            None => PaneDropTarget::Swap { source, target },
+        })
+    }
+
+    /// Geometry of splitting `pane_id` with `split-window -h/-v -l {percent}%`,
+    /// without running it. Mirrors tmux's arithmetic: the new pane gets
+    /// `percent` of the pane's extent (rounded down, at least one cell), one
+    /// cell goes to the border, and the original keeps the rest. Returns
+    /// `None` for an unknown pane, a zoomed window (tmux unzooms first), a
+    /// percentage outside `1..=99`, or a pane too small to split.
+    pub fn preview_split(
+        &self,
+        pane_id: &str,
+        direction: SplitDirection,
+        percent: u32,
+    ) -> Option<SplitPreview> {
+        let pane = self.panes.get(pane_id)?;
+        if self.windows.get(&pane.window_id).is_some_and(|w| w.zoomed)
+            || !(1..=99).contains(&percent)
+        {
+            return None;
+        }
+        let span = match direction {
+            SplitDirection::Horizontal => pane.width,
+            SplitDirection::Vertical => pane.height,
+        };
+        // One cell for each pane plus the border between them.
+        if span < 3 {
+            return None;
+        }
+        let new_span = (span * percent / 100).clamp(1, span - 2);
+        let kept_span = span - 1 - new_span;
+        let rect = PaneRect {
+            x: pane.x,
+            y: pane.y,
+            width: pane.width,
+            height: pane.height,
+        };
+        Some(match direction {
+            SplitDirection::Horizontal => SplitPreview {
+                pane: PaneRect {
+                    width: kept_span,
+                    ..rect
+                },
+                new_pane: PaneRect {
+                    x: rect.x + kept_span + 1,
+                    width: new_span,
+                    ..rect
+                },
+            },
+            SplitDirection::Vertical => SplitPreview {
+                pane: PaneRect {
+                    height: kept_span,
+                    ..rect
+                },
+                new_pane: PaneRect {
+                    y: rect.y + kept_span + 1,
+                    height: new_span,
+                    ..rect
+                },
+            },
+        })
+    }
+
+    /// Live mouse-routing flags for a pane, or `None` if it is unknown.
+    pub fn pane_mouse_mode(&self, pane_id: &str) -> Option<PaneMouseMode> {
+        self.panes.get(pane_id).map(|p| PaneMouseMode {
+            alternate_on: p.alternate_on,
+            mouse_any_flag: p.mouse_any_flag,
+            in_mode: p.in_mode,
+            height: p.height,
+        })
+    }
+
+    /// Keyboard protocol to encode keys for `pane_id` with. A pane in copy
+    /// mode reads keys as copy-mode commands, so it gets the legacy encoding.
+    pub fn pane_keyboard_mode(&self, pane_id: &str) -> Option<KeyboardMode> {
+        self.panes.get(pane_id).map(|p| {
+            if p.in_mode {
+                KeyboardMode::default()
+            } else {
+                p.keyboard.mode()
+            }
+        })
+    }
+
+    /// Provisional positional index for a brand-new window: one past the
+    /// current highest. tmux window IDs (`@N`, monotonic allocation) and
+    /// window indices (positional) are independent, so `WindowState::new`'s
+    /// fallback of parsing the index out of the id is wrong the moment they
+    /// diverge (any window close/create churn). `%window-add`/`%window-renamed`
+    /// carry only the id; a correct index otherwise waits for the follow-up
+    /// list-windows. A new window is almost always appended at the end, so
+    /// max+1 is right immediately; list-windows corrects the rare
+    /// insert-in-the-middle case.
     fn next_window_index(&self) -> u32 {
         self.windows
             .values()
@@ -1017,8 +2350,9 @@ impl StateAggregator {
     /// while the set-option round-trip is in flight). Idempotent — windows
     /// with `@tmuxy-window-type` already set are skipped.
     ///
-    /// Name-based inference (defensive, in case set-option from a tmuxy script
-    /// hasn't propagated yet):
+    /// Windows the session had before tmuxy attached become tabs. For the
+    /// rest, name-based inference (defensive, in case set-option from a tmuxy
+    /// script hasn't propagated yet):
     /// - `float` or `__float_*` → Float
     /// - `group` or `__group_*` → Group
     /// - `__sidebar` → Sidebar
@@ -1029,7 +2363,13 @@ impl StateAggregator {
             if window.window_type.is_some() {
                 continue;
             }
-            let inferred = if window.name == "float" || window.name.starts_with("__float_") {
+            let preexisting = self
+                .preexisting_windows
+                .as_ref()
+                .is_some_and(|ids| ids.contains(&window.id));
+            let inferred = if preexisting {
+                WindowType::Tab
+            } else if window.name == "float" || window.name.starts_with("__float_") {
                 WindowType::Float
             } else if window.name == "group" || window.name.starts_with("__group_") {
                 WindowType::Group
@@ -1122,7 +2462,60 @@ impl StateAggregator {
             &event,
             ControlModeEvent::WindowAdd { .. } | ControlModeEvent::UnlinkedWindowAdd { .. }
         );
+        let used_pane = match &event {
+            ControlModeEvent::Output { pane_id, .. }
+            | ControlModeEvent::ExtendedOutput { pane_id, .. }
+            | ControlModeEvent::WindowPaneChanged { pane_id, .. }
+            | ControlModeEvent::PaneModeChanged { pane_id } => Some(pane_id.clone()),
+            _ => None,
+        };
+        let output_pane = match &event {
+            ControlModeEvent::Output { pane_id, .. }
+            | ControlModeEvent::ExtendedOutput { pane_id, .. } => Some(pane_id.clone()),
+            _ => None,
+        };
+        if let (Some(budget), Some(pane_id)) = (self.output_budget, output_pane.as_ref()) {
+            if let ControlModeEvent::Output { content, .. }
+            | ControlModeEvent::ExtendedOutput { content, .. } = &event
+            {
+                if let Some(pane) = self.panes.get_mut(pane_id) {
+                    pane.output_budget.record(content, budget, now);
+                }
+            }
+        }
         let mut result = self.process_event(event);
+        let spoken = output_pane
+            .as_ref()
+            .filter(|_| self.accessible_output)
+            .and_then(|id| {
+                let lines = self.panes.get_mut(id)?.take_spoken_lines();
+                (!lines.is_empty()).then(|| (id.clone(), lines))
+            });
+        let widget_data = output_pane.as_ref().and_then(|id| {
+            let data = self.panes.get_mut(id)?.osc_parser.take_widget_data();
+            (!data.is_empty()).then(|| (id.clone(), data))
+        });
+        let notifications = output_pane.as_ref().and_then(|id| {
+            let notifications = self.panes.get_mut(id)?.osc_parser.take_notifications();
+            (!notifications.is_empty() && !self.do_not_disturb).then(|| (id.clone(), notifications))
+        });
+        if let Some(pane) = output_pane.as_ref().and_then(|id| self.panes.get_mut(id)) {
+            pane.command_history.stamp(now);
+        }
+        let bell_pane = output_pane.filter(|id| !self.do_not_disturb && self.take_bell(id, now));
+        self.track_pane_use(used_pane, now);
+        let panes = &self.panes;
+        self.last_bell.retain(|id, _| panes.contains_key(id));
+        let exited = self
+            .exit_summaries
+            .settle(now, |pane_id| panes.contains_key(pane_id));
+        for pane in self.panes.values_mut() {
+            let masks = pane.secret_masks.len();
+            pane.secret_masks.retain(|mask| !mask.expired(now));
+            if pane.secret_masks.len() != masks {
+                pane.content_dirty = true;
+            }
+        }
         let mut effects = Vec::new();
 
         // Auto-adopt before anything else so emissions reflect tagged state.
@@ -1133,6 +2526,9 @@ impl StateAggregator {
         // state-changing event arrives — which can be 15s+ in CI under tmux
         // 3.4 when the CC stream is busy with sync_initial_state.
         let tag_cmds = self.collect_window_tag_commands();
+        let theme_reports = self.collect_theme_report_commands();
+        let focus_reports = self.collect_focus_commands();
+        let group_repairs = self.collect_group_repair_commands();
         let tagged_any = !tag_cmds.is_empty();
         if tagged_any {
             effects.push(SideEffect::AdoptUntaggedWindows(tag_cmds));
@@ -1141,6 +2537,7 @@ impl StateAggregator {
                 result.change_type = ChangeType::Window;
             }
         }
+        self.track_window_use(now);
 
         // Image / clipboard side effects fire before list-pane refreshes so
         // that consumers see the same ordering as the legacy monitor path.
@@ -1158,22 +2555,70 @@ impl StateAggregator {
                 text: text.clone(),
             });
         }
-        for cmd in result.commands.iter() {
+        if let Some(pane_id) = bell_pane {
+            effects.push(SideEffect::Bell { pane_id });
+        }
+        if let Some((run_id, output, success)) = result.command_output.take() {
+            effects.push(SideEffect::CommandOutput {
+                run_id,
+                output,
+                success,
+            });
+        }
+        if let Some((pane_id, notifications)) = notifications {
+            effects.extend(
+                notifications
+                    .into_iter()
+                    .map(|notification| SideEffect::Notify {
+                        pane_id: pane_id.clone(),
+                        notification,
+                    }),
+            );
+        }
+        if let Some((pane_id, lines)) = spoken {
+            effects.push(SideEffect::SpokenText { pane_id, lines });
+        }
+        effects.extend(exited.into_iter().map(SideEffect::PaneExited));
+        if let Some((pane_id, data)) = widget_data {
+            effects.extend(data.into_iter().map(|data| SideEffect::WidgetData {
+                pane_id: pane_id.clone(),
+                data,
+            }));
+        }
+        for cmd in result
+            .commands
+            .iter()
+            .chain(&theme_reports)
+            .chain(&focus_reports)
+            .chain(&group_repairs)
+        {
             effects.push(SideEffect::SendTmuxCommand(cmd.clone()));
         }
 
+        let status_jobs = self.status_line.due_jobs(now);
+        if !status_jobs.is_empty() {
+            effects.push(SideEffect::RunStatusJobs(status_jobs));
+        }
+        if let Some(cmd) = self.status_line.refresh_command(&self.session_name, now) {
+            effects.push(SideEffect::SendTmuxCommand(cmd));
+        }
+
         if is_window_add {
             effects.push(SideEffect::RefreshAfterWindowAdd);
         }
 
         if !result.panes_needing_refresh.is_empty() {
-            effects.push(SideEffect::RefreshPanes {
-                pane_ids: result.panes_needing_refresh.clone(),
-            });
+            // Collected from hash maps; sorted so captures go out in a
+            // repeatable order.
+            let mut pane_ids = result.panes_needing_refresh.clone();
+            pane_ids.sort();
+            effects.push(SideEffect::RefreshPanes { pane_ids });
         }
 
         if let ChangeType::FlowPause { ref pane_id } = result.change_type {
-            effects.push(SideEffect::ResumePane(pane_id.clone()));
+            if self.auto_resume {
+                effects.push(SideEffect::ResumePane(pane_id.clone()));
+            }
         }
 
         if result.state_changed {
@@ -1232,6 +2677,18 @@ impl StateAggregator {
         let clipboard_writes = clipboard
             .map(|text| vec![(pane_id.clone(), text)])
             .unwrap_or_default();
+        let mut commands = Vec::new();
+        if changed {
+            let reply = self
+                .panes
+                .get_mut(&pane_id)
+                .map(|p| p.answer_queries(content))
+                .unwrap_or_default();
+            if !reply.is_empty() {
+                commands.push(crate::text_input::hex_command(&pane_id, &reply));
+            }
+            commands.extend(self.focus_follows_output_commands(&pane_id));
+        }
         ProcessEventResult {
             state_changed: changed,
             panes_needing_refresh: Vec::new(),
@@ -1242,8 +2699,70 @@ impl StateAggregator {
             },
             new_images,
             clipboard_writes,
-            commands: Vec::new(),
+            commands,
+            command_output: None,
+        }
+    }
+
+    /// Commands that raise `pane_id` when its focus-follows-output rule
+    /// matched the output just processed. Only hidden panes are raised: a
+    /// background member of a pane group is switched into its group's visible
+    /// slot (and that slot's tab selected), a pane in an inactive tab has its
+    /// window selected. Visible panes, floats and foreign windows are left
+    /// alone.
+    fn focus_follows_output_commands(&mut self, pane_id: &str) -> Vec<String> {
+        let Some(pane) = self.panes.get(pane_id) else {
+            return Vec::new();
+        };
+        let matched = match pane.focus_follows_output {
+            FocusFollowsOutput::Off => false,
+            FocusFollowsOutput::Bell => pane.rang_bell,
+            FocusFollowsOutput::Any => true,
+        };
+        if !matched || self.do_not_disturb || self.raise_pending.contains(pane_id) {
+            return Vec::new();
+        }
+        let Some(window) = self.windows.get(&pane.window_id) else {
+            return Vec::new();
+        };
+
+        let mut cmds = Vec::new();
+        match window.window_type {
+            Some(WindowType::Group) => {
+                let switch = crate::pane_group::GroupOp::Switch {
+                    pane_id: pane_id.to_string(),
+                };
+                let groups = self.pane_groups();
+                let ctx = crate::pane_group::GroupContext {
+                    groups: &groups,
+                    pane: None,
+                };
+                match switch.to_command(ctx) {
+                    Ok(cmd) => cmds.push(cmd),
+                    Err(e) => debug!(pane_id, error = %e, "focus follows output: no group slot"),
+                }
+                // The group's visible slot is whichever member sits in a tab.
+                let host = window
+                    .group_panes
+                    .iter()
+                    .flatten()
+                    .filter_map(|id| self.panes.get(id))
+                    .filter_map(|p| self.windows.get(&p.window_id))
+                    .find(|w| w.window_type == Some(WindowType::Tab));
+                if let Some(host) = host.filter(|w| !w.active) {
+                    cmds.push(format!("select-window -t {}", host.id));
+                }
+            }
+            Some(WindowType::Tab) if !window.active => {
+                cmds.push(format!("select-window -t {}", window.id));
+            }
+            _ => {}
+        }
+        if !cmds.is_empty() {
+            debug!(pane_id, "focus follows output: raising pane");
+            self.raise_pending.insert(pane_id.to_string());
         }
+        cmds
     }
 
     /// Process a control mode event.
@@ -1297,11 +2816,11 @@ impl StateAggregator {
             // If not, it's from another session — ignore.
             ControlModeEvent::UnlinkedWindowClose { window_id } => {
                 if self.windows.contains_key(&window_id) {
+                    self.remove_panes(|p| p.window_id == window_id);
                     self.windows.remove(&window_id);
-                    self.panes.retain(|_, p| p.window_id != window_id);
                     self.pending_captures
                         .retain(|id| self.panes.contains_key(id));
-                    self.status_line_dirty = true;
+                    self.status_line.mark_dirty();
                     ProcessEventResult {
                         state_changed: !self.suppress_window_emissions,
                         change_type: ChangeType::Window,
@@ -1323,7 +2842,7 @@ impl StateAggregator {
                     w.index = provisional_index;
                     w
                 });
-                self.status_line_dirty = true;
+                self.status_line.mark_dirty();
                 // Don't emit state yet - wait for WindowRenamed or list-windows
                 // to populate the window name. This prevents brief flashes of
                 // windows appearing with empty names (which breaks stack detection).
@@ -1331,11 +2850,11 @@ impl StateAggregator {
             }
 
             ControlModeEvent::WindowClose { window_id } => {
+                self.remove_panes(|p| p.window_id == window_id);
                 self.windows.remove(&window_id);
-                self.panes.retain(|_, p| p.window_id != window_id);
                 self.pending_captures
                     .retain(|id| self.panes.contains_key(id));
-                self.status_line_dirty = true;
+                self.status_line.mark_dirty();
                 ProcessEventResult {
                     state_changed: !self.suppress_window_emissions,
                     change_type: ChangeType::Window,
@@ -1354,7 +2873,7 @@ impl StateAggregator {
                     w
                 });
                 window.name = name;
-                self.status_line_dirty = true;
+                self.status_line.mark_dirty();
                 ProcessEventResult {
                     state_changed: !self.suppress_window_emissions,
                     change_type: ChangeType::Window,
@@ -1410,13 +2929,23 @@ impl StateAggregator {
                 }
             }
 
-            ControlModeEvent::SessionWindowChanged { window_id, .. } => {
+            ControlModeEvent::SessionWindowChanged {
+                session_id,
+                window_id,
+            } => {
+                // Control mode reports every session's current window. A
+                // grouped session shares our windows, so its switches name
+                // windows we have; they still aren't ours to follow.
+                if self.session_id.as_ref().is_some_and(|id| *id != session_id) {
+                    return ProcessEventResult::default();
+                }
                 // Update active window
                 for (id, window) in self.windows.iter_mut() {
                     window.active = *id == window_id;
                 }
                 self.active_window_id = Some(window_id.clone());
-                self.status_line_dirty = true; // Active window changed - refresh status line
+                self.status_line.mark_dirty(); // Active window changed - refresh status line
+                self.raise_pending.clear();
 
                 // Refresh capture for every pane in the newly active window so
                 // long-idle tabs don't show stale content after a switch. The
@@ -1445,7 +2974,53 @@ impl StateAggregator {
                 // responses: BEGIN marker → buffer content → END marker. The
                 // marker blocks are unambiguous, so this can never be misread
                 // as (or steal) a capture-pane response.
+                if output.starts_with(STATUS_MARKER) {
+                    if let Some(reply) = StatusReply::parse(&output).filter(|_| success) {
+                        return ProcessEventResult {
+                            state_changed: self.status_line.on_reply(reply),
+                            change_type: ChangeType::Session,
+                            ..Default::default()
+                        };
+                    }
+                }
+                if output.starts_with(GROUP_MARKER) {
+                    if let Some(reply) = session_group::parse_reply(&output).filter(|_| success) {
+                        self.session_id = Some(reply.session_id);
+                        let changed = self.session_group != reply.group;
+                        self.session_group = reply.group;
+                        return ProcessEventResult {
+                            state_changed: changed,
+                            change_type: ChangeType::Session,
+                            ..Default::default()
+                        };
+                    }
+                }
                 let marker_line = output.trim_end_matches(['\r', '\n']);
+                if marker_line == LIVENESS_MARKER {
+                    return ProcessEventResult::default();
+                }
+                if let Some(id) = marker_line.strip_prefix(RUN_BEGIN_MARKER) {
+                    if let Ok(run_id) = id.trim().parse() {
+                        self.run_output = Some((run_id, String::new(), true));
+                    }
+                    return ProcessEventResult::default();
+                }
+                if let Some(id) = marker_line.strip_prefix(RUN_END_MARKER) {
+                    let run_id = id.trim().parse::<u64>().ok();
+                    return ProcessEventResult {
+                        command_output: self
+                            .run_output
+                            .take()
+                            .filter(|(run, ..)| Some(*run) == run_id),
+                        ..Default::default()
+                    };
+                }
+                // A client command's own blocks are collected, then handled
+                // like any other response below.
+                if let Some((_, collected, all_ok)) = &mut self.run_output {
+                    collected.push_str(&output);
+                    *all_ok &= success;
+                }
                 if marker_line == "TMUXY_BUF_BEGIN" {
                     self.buffer_read_armed = !self.pending_buffer_reads.is_empty();
                     return ProcessEventResult::default();
@@ -1511,7 +3086,7 @@ impl StateAggregator {
                                 pane.tmux_cursor_y + 1,
                                 pane.tmux_cursor_x + 1
                             );
-                            safe_process(&mut pane.terminal, cursor_seq.as_bytes());
+                            pane.terminal.process(cursor_seq.as_bytes());
                         }
                         // Capture arrived — clear window-move suppression
                         self.panes_moved_window.remove(&pane_id);
@@ -1559,14 +3134,32 @@ impl StateAggregator {
                 // clients when ANY session is created/destroyed. It does NOT mean
                 // the current session's state changed. Suppress state emission to
                 // prevent cross-session interference (e.g., E2E test sessions
-                // causing spurious updates in the user's UI).
-                ProcessEventResult::default()
+                // causing spurious updates in the user's UI). Once attached,
+                // our group may have gained or lost a session, though: re-read
+                // it, which changes state only if it did.
+                ProcessEventResult {
+                    commands: self
+                        .session_id
+                        .is_some()
+                        .then(|| session_group::query_command(&self.session_name))
+                        .into_iter()
+                        .collect(),
+                    ..Default::default()
+                }
             }
-            ControlModeEvent::SessionChanged { session_name, .. } => {
+            // Option watches: the native monitor handles these before the
+            // aggregator sees them; they never change pane or window state.
+            ControlModeEvent::SubscriptionChanged { .. } => ProcessEventResult::default(),
+            ControlModeEvent::SessionChanged {
+                session_id,
+                session_name,
+            } => {
+                self.session_id = Some(session_id);
                 self.session_name = session_name;
                 ProcessEventResult {
                     state_changed: true,
                     change_type: ChangeType::Session,
+                    commands: vec![session_group::query_command(&self.session_name)],
                     ..Default::default()
                 }
             }
@@ -1575,6 +3168,7 @@ impl StateAggregator {
                 ProcessEventResult {
                     state_changed: true,
                     change_type: ChangeType::Session,
+                    commands: vec![session_group::query_command(&self.session_name)],
                     ..Default::default()
                 }
             }
@@ -1602,8 +3196,11 @@ impl StateAggregator {
             ControlModeEvent::Continue { pane_id } => {
                 if let Some(pane) = self.panes.get_mut(&pane_id) {
                     pane.paused = false;
+                    // tmux dropped whatever the pane wrote while paused, so
+                    // the emulator's screen is stale until recaptured.
                     return ProcessEventResult {
                         state_changed: true,
+                        panes_needing_refresh: vec![pane_id.clone()],
                         change_type: ChangeType::FlowContinue { pane_id },
                         ..Default::default()
                     };
@@ -1737,12 +3334,9 @@ impl StateAggregator {
                 pane.index = lp.index;
                 let was_resized = pane.resize(lp.width, lp.height);
                 if moved_window && !was_resized {
-                    // resize() already resets VT100 when dimensions change.
+                    // A resized pane is reflowed in place and re-captured.
                     // When only the window changed (same dimensions), reset manually.
-                    let w = (pane.width as u16).max(1);
-                    let h = (pane.height as u16).max(1);
-                    pane.terminal =
-                        vt100::Parser::new(h, w, crate::constants::REFLOW_SCROLLBACK_ROWS);
+                    pane.reset_terminal();
                     pane.image_parser.reset();
                     pane.content_dirty = true;
                     pane.cached_content = None;
@@ -1757,7 +3351,16 @@ impl StateAggregator {
                 }
             } else {
                 // New pane discovered in layout: create with geometry
-                let mut pane = PaneState::new(&lp.id, lp.width, lp.height);
+                let mut pane = PaneState::new(
+                    &lp.id,
+                    lp.width,
+                    lp.height,
+                    self.terminal_backend,
+                    self.scrollback_rows,
+                );
+                if self.accessible_output {
+                    pane.set_spoken_text(true);
+                }
                 pane.window_id = window_id.to_string();
                 pane.index = lp.index;
                 pane.x = lp.x;
@@ -1779,13 +3382,7 @@ impl StateAggregator {
         }
 
         // Reconcile: remove panes from this window that are no longer in the layout
-        self.panes.retain(|pane_id, pane| {
-            if pane.window_id == window_id {
-                seen_panes.contains(pane_id)
-            } else {
-                true // keep panes from other windows
-            }
-        });
+        self.remove_panes(|pane| pane.window_id == window_id && !seen_panes.contains(&pane.id));
 
         // Prune in-flight captures for panes that no longer exist — their
         // marker-routed responses are discarded on arrival, and a dead entry
@@ -1798,11 +3395,23 @@ impl StateAggregator {
 
     /// Handle command response (list-panes, list-windows) and return list of panes that were resized.
     fn handle_command_response(&mut self, output: &str) -> Vec<String> {
-        // Track which panes we see in this response
-        let mut seen_panes: std::collections::HashSet<String> = std::collections::HashSet::new();
-        let mut resized_panes: Vec<String> = Vec::new();
-        let mut is_list_panes_response = false;
-
+        if super::sync_fields::is_details_response(output) {
+            for line in output.lines() {
+                if let Some((pane_id, details)) = super::sync_fields::parse_details_line(line) {
+                    if let Some(pane) = self.panes.get_mut(pane_id) {
+                        pane.cwd = details.cwd;
+                        pane.pid = details.pid;
+                    }
+                }
+            }
+            return Vec::new();
+        }
+
+        // Track which panes we see in this response
+        let mut seen_panes: std::collections::HashSet<String> = std::collections::HashSet::new();
+        let mut resized_panes: Vec<String> = Vec::new();
+        let mut is_list_panes_response = false;
+
         // Try to parse as list-panes output. Require the shape tmux actually
         // emits — `%<digits>,` at the start of the line — rather than "contains
         // a % and a comma" anywhere, so arbitrary RunCommand output flowing
@@ -1820,23 +3429,19 @@ impl StateAggregator {
             }
         }
 
+        // Pane placement is now authoritative again, so a raise that has
+        // landed (or failed) no longer needs to block the next one.
+        if is_list_panes_response {
+            self.raise_pending.clear();
+        }
+
         // If this was a list-panes response, remove panes that weren't seen
         // (they were deleted in tmux)
         if is_list_panes_response && !seen_panes.is_empty() {
-            self.panes.retain(|pane_id, pane| {
-                // Keep panes that were seen in this response
-                if seen_panes.contains(pane_id) {
-                    return true;
-                }
-                // Keep panes with empty window_id (from other sessions' output events)
-                // They'll be filtered out in to_tmux_state anyway
-                if pane.window_id.is_empty() {
-                    return true;
-                }
-                // Remove panes that have a window_id but weren't in the list-panes response
-                // (they were deleted)
-                false
-            });
+            // Panes with an empty window_id come from other sessions' output
+            // events and are filtered out in to_tmux_state anyway; the rest
+            // weren't in the response because they were deleted.
+            self.remove_panes(|pane| !seen_panes.contains(&pane.id) && !pane.window_id.is_empty());
             self.pending_captures
                 .retain(|id| self.panes.contains_key(id));
         }
@@ -1844,20 +3449,38 @@ impl StateAggregator {
         // Try to parse as list-windows output
         let mut is_list_windows_response = false;
         let mut seen_windows: std::collections::HashSet<String> = std::collections::HashSet::new();
+        let mut untagged_windows: std::collections::HashSet<String> =
+            std::collections::HashSet::new();
         for line in output.lines() {
             if line.contains('@') && line.contains(',') {
                 // Extract window_id before parsing (first field starts with @)
-                if let Some(wid) = line.split(',').next() {
-                    let wid = wid.trim();
-                    if wid.starts_with('@') {
-                        seen_windows.insert(wid.to_string());
+                let mut fields = line.split(',');
+                let wid = fields.next().map(str::trim).unwrap_or("");
+                if wid.starts_with('@') && line.split(',').nth(3) == Some("") {
+                    untagged_windows.insert(wid.to_string());
+                }
+                if wid.starts_with('@') && !seen_windows.insert(wid.to_string()) {
+                    // A window linked into the session twice (`link-window`)
+                    // is listed at each index. Keep the first listing; the
+                    // window is current if either of them is.
+                    if fields.nth(1) == Some("1") {
+                        self.active_window_id = Some(wid.to_string());
+                        if let Some(window) = self.windows.get_mut(wid) {
+                            window.active = true;
+                        }
                     }
+                    is_list_windows_response = true;
+                    continue;
                 }
                 self.parse_list_windows_line(line);
                 is_list_windows_response = true;
             }
         }
 
+        if is_list_windows_response && self.preexisting_windows.is_none() {
+            self.preexisting_windows = Some(untagged_windows);
+        }
+
         // Remove windows that weren't in the list-windows response (deleted in tmux).
         if is_list_windows_response && !seen_windows.is_empty() {
             self.windows
@@ -1866,14 +3489,17 @@ impl StateAggregator {
 
         // Refresh status line on periodic sync (list-windows response)
         if is_list_windows_response {
-            self.status_line_dirty = true;
+            self.status_line.mark_dirty();
+            if self.group_check == GroupCheck::Armed {
+                self.group_check = GroupCheck::Due;
+            }
         }
 
         resized_panes
     }
 
     /// Parse a line from list-panes output.
-    /// Expected format: `%pane_id,pane_index,x,y,width,height,cursor_x,cursor_y,active,command,title,in_mode,copy_x,copy_y,scroll_position,window_id,border_title,alternate_on,mouse_any_flag,selection_present,selection_start_x,selection_start_y,history_size`
+    /// Expected format: `%pane_id,pane_index,x,y,width,height,cursor_x,cursor_y,active,command,title,in_mode,copy_x,copy_y,scroll_position,window_id,border_title,alternate_on,mouse_any_flag,selection_present,selection_start_x,selection_start_y,selection_end_x,selection_end_y,history_size,focus_follows_output,answerback,sandbox,widget`
     /// Returns (pane_id, needs_capture) if successfully parsed.
     /// needs_capture is true if pane is new OR was resized.
     fn parse_list_panes_line(&mut self, line: &str) -> Option<(String, bool)> {
@@ -1904,30 +3530,59 @@ impl StateAggregator {
         // dynamically: it is immediately preceded by in_mode, copy_cursor_x,
         // copy_cursor_y, scroll_position. Everything between command and those
         // four fields is pane_title; everything between window_id and the fixed
-        // 6-field tail is border_title.
-        let num_tail_fields = 6;
+        // 12-field tail is border_title.
+        let num_tail_fields = 12;
 
         // Tail fields (fixed, never free-text): alternate_on, mouse_any_flag,
-        // selection_present, selection_start_x, selection_start_y, history_size.
+        // selection_present, selection_start_x, selection_start_y,
+        // selection_end_x, selection_end_y, history_size, focus_follows_output,
+        // answerback, sandbox (a profile name, which `tmuxy pane float`
+        // keeps free of commas), widget (a `WidgetSpec` encoding, which
+        // escapes them).
         let (
             alternate_on,
             mouse_any_flag,
             selection_present,
-            selection_start_x,
-            selection_start_y,
+            (selection_start_x, selection_start_y),
+            (selection_end_x, selection_end_y),
             history_size,
-        ) = if parts.len() >= 17 {
+            focus_follows_output,
+            answerback,
+            sandbox,
+            widget,
+        ) = if parts.len() >= 23 {
             let last = parts.len() - 1;
             (
-                parts[last - 5] == "1",
-                parts[last - 4] == "1",
-                parts[last - 3] == "1",
-                parts[last - 2].parse::<u32>().unwrap_or(0),
-                parts[last - 1].parse::<u64>().unwrap_or(0),
-                parts[last].parse::<u64>().unwrap_or(0),
+                parts[last - 11] == "1",
+                parts[last - 10] == "1",
+                parts[last - 9] == "1",
+                (
+                    parts[last - 8].parse::<u32>().unwrap_or(0),
+                    parts[last - 7].parse::<u64>().unwrap_or(0),
+                ),
+                (
+                    parts[last - 6].parse::<u32>().unwrap_or(0),
+                    parts[last - 5].parse::<u64>().unwrap_or(0),
+                ),
+                parts[last - 4].parse::<u64>().unwrap_or(0),
+                FocusFollowsOutput::parse(parts[last - 3]),
+                parts[last - 2] != "off",
+                parts[last - 1].to_string(),
+                WidgetSpec::decode(parts[last]),
             )
         } else {
-            (false, false, false, 0u32, 0u64, 0u64)
+            (
+                false,
+                false,
+                false,
+                (0u32, 0u64),
+                (0u32, 0u64),
+                0u64,
+                FocusFollowsOutput::Off,
+                true,
+                String::new(),
+                None,
+            )
         };
 
         let mut title = String::new();
@@ -1988,10 +3643,15 @@ impl StateAggregator {
         // Check if this is a new pane
         let is_new_pane = !self.panes.contains_key(&pane_id_string);
 
-        let pane = self
-            .panes
-            .entry(pane_id_string.clone())
-            .or_insert_with(|| PaneState::new(pane_id, width, height));
+        let (backend, scrollback_rows) = (self.terminal_backend, self.scrollback_rows);
+        let accessible_output = self.accessible_output;
+        let pane = self.panes.entry(pane_id_string.clone()).or_insert_with(|| {
+            let mut pane = PaneState::new(pane_id, width, height, backend, scrollback_rows);
+            if accessible_output {
+                pane.set_spoken_text(true);
+            }
+            pane
+        });
 
         // Replay any early %output that arrived before this pane was created
         if is_new_pane {
@@ -2022,7 +3682,13 @@ impl StateAggregator {
         pane.selection_present = selection_present;
         pane.selection_start_x = selection_start_x;
         pane.selection_start_y = selection_start_y;
+        pane.selection_end_x = selection_end_x;
+        pane.selection_end_y = selection_end_y;
         pane.history_size = history_size;
+        pane.focus_follows_output = focus_follows_output;
+        pane.answerback = answerback;
+        pane.sandbox = sandbox;
+        pane.widget = widget;
 
         // Store tmux's authoritative cursor position
         pane.tmux_cursor_x = cursor_x;
@@ -2038,14 +3704,14 @@ impl StateAggregator {
 
     /// Parse a line from list-windows output. Expected format (comma-separated,
     /// see constants::LIST_WINDOWS_CMD):
-    /// `@id,index,active,window_type,float_parent,float_width,float_height,float_drawer,float_bg,float_noheader,group_panes,zoomed,name`
+    /// `@id,index,active,window_type,float_parent,float_width,float_height,float_drawer,float_bg,float_noheader,group_panes,zoomed,float_x,float_y,float_z,float_hidden,activity,name`
     /// `window_name` is LAST and free text — we `splitn` so its own commas stay
     /// in the trailing field and can't shift any parsed column. Every column
     /// after `active` is a `@tmuxy-*` user option that may be empty.
     fn parse_list_windows_line(&mut self, line: &str) {
-        // 13 fields; splitn keeps window_name (the 13th) intact even with commas.
-        let parts: Vec<&str> = line.splitn(13, ',').collect();
-        if parts.len() < 12 {
+        // 18 fields; splitn keeps window_name (the 18th) intact even with commas.
+        let parts: Vec<&str> = line.splitn(18, ',').collect();
+        if parts.len() < 17 {
             return;
         }
 
@@ -2056,7 +3722,7 @@ impl StateAggregator {
 
         let index: u32 = parts[1].parse().unwrap_or(0);
         let active = parts[2] == "1";
-        let name = parts.get(12).map(|s| s.to_string()).unwrap_or_default();
+        let name = parts.get(17).map(|s| s.to_string()).unwrap_or_default();
 
         let opt = |idx: usize| -> Option<String> {
             parts
@@ -2085,6 +3751,11 @@ impl StateAggregator {
         // e.g. every fresh client connect, which is exactly when a client
         // attaching to an already-zoomed window needs it.
         let zoomed = opt(11).is_some_and(|s| s == "1");
+        let float_x = opt(12).and_then(|s| s.parse::<u32>().ok());
+        let float_y = opt(13).and_then(|s| s.parse::<u32>().ok());
+        let float_z = opt(14).and_then(|s| s.parse::<u32>().ok());
+        let float_hidden = opt(15).is_some_and(|s| s == "1");
+        let activity = opt(16).and_then(|s| s.parse::<u64>().ok());
 
         let window = self
             .windows
@@ -2103,6 +3774,11 @@ impl StateAggregator {
         window.float_drawer = float_drawer;
         window.float_bg = float_bg;
         window.float_noheader = float_noheader;
+        window.float_x = float_x;
+        window.float_y = float_y;
+        window.float_z = float_z;
+        window.float_hidden = float_hidden;
+        window.activity = activity;
 
         if active {
             self.active_window_id = Some(window_id.to_string());
@@ -2116,10 +3792,11 @@ impl StateAggregator {
     pub fn to_state_update(&mut self) -> Option<crate::StateUpdate> {
         let mut current = self.to_tmux_state();
 
-        // Preserve previous content for panes with pending captures.
-        // After resize, pane.resize() clears the VT100 parser but capture-pane
-        // hasn't arrived yet. Without this, to_tmux_state() extracts empty/truncated
-        // content from the cleared parser and emits it to the frontend.
+        // Preserve previous content for panes with pending captures, so an
+        // emulator that may be out of sync (copy-mode exit) isn't shown before
+        // the authoritative capture-pane arrives. A resized pane is exempt:
+        // its emulator reflowed the old content to the new size, which fits
+        // the pane where the previous snapshot no longer does.
         if !self.pending_captures.is_empty() {
             if let Some(ref prev) = self.prev_state {
                 let prev_panes: std::collections::HashMap<&str, &crate::TmuxPane> =
@@ -2133,7 +3810,10 @@ impl StateAggregator {
                         if self.panes_moved_window.contains(&pane.tmux_id) {
                             continue;
                         }
-                        if let Some(prev_pane) = prev_panes.get(pane.tmux_id.as_str()) {
+                        if let Some(prev_pane) = prev_panes
+                            .get(pane.tmux_id.as_str())
+                            .filter(|p| p.width == pane.width && p.height == pane.height)
+                        {
                             pane.content = prev_pane.content.clone();
                             pane.cursor_x = prev_pane.cursor_x;
                             pane.cursor_y = prev_pane.cursor_y;
@@ -2143,6 +3823,24 @@ impl StateAggregator {
             }
         }
 
+        // Trimmed panes: lines that scrolled by unseen since the last frame
+        // are summarised on the top row, until a frame skips none.
+        if self.output_budget.is_some() {
+            for pane in &mut current.panes {
+                let Some(state) = self.panes.get_mut(&pane.tmux_id) else {
+                    continue;
+                };
+                let skipped = state.output_budget.take_skipped(pane.height);
+                if skipped == 0 || pane.alternate_on || pane.content.is_empty() {
+                    continue;
+                }
+                let mut content = (*pane.content).clone();
+                content[0] = super::output_budget::marker_line(skipped, pane.width);
+                pane.content = std::sync::Arc::new(content);
+                *self.output_trims.entry(pane.tmux_id.clone()).or_default() += skipped;
+            }
+        }
+
         // First state or no previous state - send full
         let prev = match &self.prev_state {
             None => {
@@ -2155,6 +3853,7 @@ impl StateAggregator {
 
         // Compute delta (seq assigned after empty check)
         let mut delta = crate::TmuxDelta::new(0);
+        delta.epoch = self.epoch;
 
         // Check for dimension changes
         if current.total_width != prev.total_width {
@@ -2176,6 +3875,27 @@ impl StateAggregator {
         if current.status_line != prev.status_line {
             delta.status_line = Some(current.status_line.clone());
         }
+        if current.status_segments != prev.status_segments {
+            delta.status_segments = Some(current.status_segments.clone());
+        }
+        if current.status_lines != prev.status_lines {
+            delta.status_lines = Some(current.status_lines.clone());
+        }
+        if current.status_position != prev.status_position {
+            delta.status_position = Some(current.status_position);
+        }
+        if current.session_group != prev.session_group {
+            delta.session_group = Some(current.session_group.clone());
+        }
+        if current.do_not_disturb != prev.do_not_disturb {
+            delta.do_not_disturb = Some(current.do_not_disturb);
+        }
+        if current.theme != prev.theme {
+            delta.theme = Some(current.theme.clone());
+        }
+        if current.ssh_agent != prev.ssh_agent {
+            delta.ssh_agent = Some(current.ssh_agent);
+        }
 
         // Build maps for efficient lookup
         let prev_panes: std::collections::HashMap<&str, &crate::TmuxPane> =
@@ -2309,8 +4029,24 @@ impl StateAggregator {
                     changed_lines.insert(i, curr_line.cloned().unwrap_or_default());
                 }
             }
-            if !changed_lines.is_empty() {
-                delta.content = Some(changed_lines);
+            match detect_scroll(&prev.content, &curr.content) {
+                // Only worth it when it beats resending the changed lines.
+                Some((lines, rewritten)) if lines + rewritten.len() < changed_lines.len() => {
+                    delta.scroll = Some(crate::ScrollDelta {
+                        lines: lines as u32,
+                        new_rows: curr.content[curr.content.len() - lines..].to_vec(),
+                    });
+                    if !rewritten.is_empty() {
+                        delta.content = Some(
+                            rewritten
+                                .into_iter()
+                                .map(|i| (i, curr.content[i].clone()))
+                                .collect(),
+                        );
+                    }
+                }
+                _ if !changed_lines.is_empty() => delta.content = Some(changed_lines),
+                _ => {}
             }
         }
         if prev.cursor_x != curr.cursor_x {
@@ -2361,6 +4097,9 @@ impl StateAggregator {
         if prev.history_size != curr.history_size {
             delta.history_size = Some(curr.history_size);
         }
+        if prev.scroll_position != curr.scroll_position {
+            delta.scroll_position = Some(curr.scroll_position);
+        }
         if prev.selection_present != curr.selection_present {
             delta.selection_present = Some(curr.selection_present);
         }
@@ -2370,15 +4109,45 @@ impl StateAggregator {
         if prev.selection_start_y != curr.selection_start_y {
             delta.selection_start_y = Some(curr.selection_start_y);
         }
+        if prev.selection_end_x != curr.selection_end_x {
+            delta.selection_end_x = Some(curr.selection_end_x);
+        }
+        if prev.selection_end_y != curr.selection_end_y {
+            delta.selection_end_y = Some(curr.selection_end_y);
+        }
         if prev.images != curr.images {
             delta.images = Some(curr.images.clone());
         }
+        if prev.command_blocks != curr.command_blocks {
+            delta.command_blocks = Some(curr.command_blocks.clone());
+        }
         if prev.cursor_shape != curr.cursor_shape {
             delta.cursor_shape = Some(curr.cursor_shape);
         }
         if prev.cursor_hidden != curr.cursor_hidden {
             delta.cursor_hidden = Some(curr.cursor_hidden);
         }
+        if prev.extended_keys != curr.extended_keys {
+            delta.extended_keys = Some(curr.extended_keys);
+        }
+        if prev.colors != curr.colors {
+            delta.colors = Some(curr.colors.clone());
+        }
+        if prev.sandbox != curr.sandbox {
+            delta.sandbox = Some(curr.sandbox.clone());
+        }
+        if prev.pointer_shape != curr.pointer_shape {
+            delta.pointer_shape = Some(curr.pointer_shape.clone());
+        }
+        if prev.widget != curr.widget {
+            delta.widget = Some(curr.widget.clone());
+        }
+        if prev.cwd != curr.cwd {
+            delta.cwd = Some(curr.cwd.clone());
+        }
+        if prev.pid != curr.pid {
+            delta.pid = Some(curr.pid);
+        }
         delta
     }
 
@@ -2420,9 +4189,24 @@ impl StateAggregator {
         if prev.float_noheader != curr.float_noheader {
             delta.float_noheader = Some(curr.float_noheader);
         }
+        if prev.float_x != curr.float_x {
+            delta.float_x = Some(curr.float_x);
+        }
+        if prev.float_y != curr.float_y {
+            delta.float_y = Some(curr.float_y);
+        }
+        if prev.float_z != curr.float_z {
+            delta.float_z = Some(curr.float_z);
+        }
+        if prev.float_hidden != curr.float_hidden {
+            delta.float_hidden = Some(curr.float_hidden);
+        }
         if prev.zoomed != curr.zoomed {
             delta.zoomed = Some(curr.zoomed);
         }
+        if prev.status != curr.status {
+            delta.status = Some(curr.status.clone());
+        }
 
         delta
     }
@@ -2452,7 +4236,7 @@ impl StateAggregator {
         // keyboard routing, optimistic-prediction lookups, focus indicators).
         // Without this collapse, multiple panes report active=true and any
         // downstream code that assumes "at most one active pane" misbehaves.
-        let panes: Vec<TmuxPane> = matching_pane_ids
+        let mut panes: Vec<TmuxPane> = matching_pane_ids
             .iter()
             .filter_map(|id| {
                 self.panes.get_mut(id).map(|p| {
@@ -2464,7 +4248,12 @@ impl StateAggregator {
             })
             .collect();
 
-        let windows: Vec<TmuxWindow> = self.windows.values().map(|w| w.to_tmux_window()).collect();
+        let mut windows: Vec<TmuxWindow> =
+            self.windows.values().map(|w| w.to_tmux_window()).collect();
+        // Both come out of hash maps: order them so the same state always
+        // serializes the same way.
+        panes.sort_by_key(|p| p.id);
+        windows.sort_by(|a, b| (a.index, &a.id).cmp(&(b.index, &b.id)));
 
         // Calculate total dimensions
         let total_width = panes.iter().map(|p| p.x + p.width).max().unwrap_or(80);
@@ -2480,6 +4269,8 @@ impl StateAggregator {
 
         // Get status line (uses cache if not dirty)
         let status_line = self.get_status_line(total_width as usize);
+        let status_segments = self.status_line.segments();
+        let status_lines = self.status_line.extra_lines(total_width as usize);
 
         TmuxState {
             session_name: self.session_name.clone(),
@@ -2490,6 +4281,14 @@ impl StateAggregator {
             total_width,
             total_height,
             status_line,
+            status_segments,
+            status_lines,
+            status_position: self.status_line.position(),
+            session_group: self.session_group.clone(),
+            do_not_disturb: self.do_not_disturb,
+            theme: self.terminal_theme.clone(),
+            ssh_agent: self.ssh_agent,
+            epoch: self.epoch,
         }
     }
 }
@@ -2501,13 +4300,20 @@ impl Default for StateAggregator {
 }
 
 #[cfg(test)]
+#[allow(clippy::unwrap_used, clippy::expect_used)]
 mod tests {
     use super::*;
 
     /// Manually seat a pane in the aggregator so handle_output() processes it
     /// (handle_output rejects panes that haven't been seen in list-panes).
     fn seed_pane(agg: &mut StateAggregator, pane_id: &str, window_id: &str) {
-        let mut pane = PaneState::new(pane_id, 80, 24);
+        let mut pane = PaneState::new(
+            pane_id,
+            80,
+            24,
+            TerminalBackendKind::default(),
+            crate::constants::REFLOW_SCROLLBACK_ROWS,
+        );
         pane.window_id = window_id.to_string();
         agg.panes.insert(pane_id.to_string(), pane);
     }
@@ -2567,6 +4373,85 @@ mod tests {
         assert!(!is_list_panes_line(""));
     }
 
+    #[test]
+    fn capture_pending_clears_when_the_response_lands() {
+        let mut agg = StateAggregator::new();
+        seed_pane(&mut agg, "%0", "@0");
+        agg.queue_captures(&["%0".to_string()]);
+        assert!(agg.capture_pending("%0"));
+
+        let response = |output: String| ControlModeEvent::CommandResponse {
+            timestamp: 0,
+            command_num: 0,
+            output,
+            success: true,
+        };
+        agg.process_event(response(format!("{CAPTURE_BEGIN_MARKER} 0\n")));
+        assert!(agg.capture_pending("%0"));
+        agg.process_event(response("content\n".to_string()));
+        assert!(!agg.capture_pending("%0"));
+        assert!(agg.has_pane("%0"));
+        assert!(!agg.has_pane("%1"));
+    }
+
+    /// A status reply sets the status line and starts its `#()` jobs, whose
+    /// output the refresh after they finish inlines.
+    #[test]
+    fn status_replies_drive_refreshes_and_jobs() {
+        let mut agg = StateAggregator::with_session_name("main");
+        let now = Instant::now();
+        let response = |output: &str| ControlModeEvent::CommandResponse {
+            timestamp: 0,
+            command_num: 0,
+            output: output.to_string(),
+            success: true,
+        };
+        let sent = |effects: &[SideEffect]| -> Vec<String> {
+            effects
+                .iter()
+                .filter_map(|e| match e {
+                    SideEffect::SendTmuxCommand(cmd) => Some(cmd.clone()),
+                    _ => None,
+                })
+                .collect()
+        };
+
+        let first = agg.refresh_status_line(now).expect("refresh");
+        assert!(first.contains("#{T:status-right}"));
+        let reply =
+            "TMUXY_STATUS\n10\n40\n15\non\nbottom\n[#S] \n#(uptime -p) %H\n[main] \n 12\n\n\n\n\n@0 1 0:bash*\n";
+        let step = agg.step_at(response(reply), now);
+        assert!(step.effects.iter().any(
+            |e| matches!(e, SideEffect::RunStatusJobs(jobs) if jobs == &["uptime -p".to_string()])
+        ));
+        // Re-read with the job inlined (still empty).
+        let second = sent(&step.effects);
+        assert_eq!(second.len(), 1);
+        assert!(
+            second[0].contains(r#"\n %H\n#{T:status-format[1]}"#),
+            "{}",
+            second[0]
+        );
+        assert_eq!(agg.get_status_line(20), "[main] 0:bash*    12");
+        let segments = agg.to_tmux_state().status_segments;
+        assert_eq!(segments.windows.len(), 1);
+        assert_eq!(
+            (
+                segments.windows[0].window_id.as_str(),
+                segments.windows[0].active
+            ),
+            ("@0", true)
+        );
+
+        // The job finishes while that read is in flight: the read after it
+        // carries the output.
+        assert_eq!(agg.status_job_done("uptime -p", "up 2 #days\n", now), None);
+        let step = agg.step_at(response(reply), now);
+        let third = sent(&step.effects);
+        assert!(third[0].contains("up 2 ##days %H"), "{}", third[0]);
+        assert_eq!(agg.status_interval(), Some(Duration::from_secs(15)));
+    }
+
     /// A response between the capture markers is attributed to exactly the
     /// pane named by the BEGIN marker — and unmarked responses (e.g. a
     /// send-keys ack with EMPTY output, indistinguishable in shape from
@@ -2721,11 +4606,24 @@ mod tests {
     /// exact field order of `constants::tmux_formats::LIST_PANES_CMD`.
     fn list_panes_line(title: &str, window_id: &str, border_title: &str) -> String {
         format!(
-            // id,idx,x,y,w,h,cx,cy,active,command,TITLE,in_mode,copy_x,copy_y,scroll,WIN,BORDER,alt,mouse,sel,sx,sy,hist
-            "%3,0,0,0,80,24,0,0,1,zsh,{title},0,0,0,0,{window_id},{border_title},0,0,0,0,0,100"
+            // id,idx,x,y,w,h,cx,cy,active,command,TITLE,in_mode,copy_x,copy_y,scroll,WIN,BORDER,alt,mouse,sel,sx,sy,ex,ey,hist,ffo,answerback,sandbox,widget
+            "%3,0,0,0,80,24,0,0,1,zsh,{title},0,0,0,0,{window_id},{border_title},0,0,0,0,0,0,0,100,,,,"
         )
     }
 
+    /// `list_panes_line` with the pane options set.
+    fn list_panes_line_with_options(
+        focus_follows_output: &str,
+        answerback: &str,
+        sandbox: &str,
+    ) -> String {
+        let line = list_panes_line("zsh", "@4", "");
+        let head = line
+            .strip_suffix(",,,,")
+            .expect("options are the last fields");
+        format!("{head},{focus_follows_output},{answerback},{sandbox},")
+    }
+
     #[test]
     fn list_panes_plain_title_parses_window_id() {
         let mut agg = StateAggregator::new();
@@ -2769,8 +4667,8 @@ mod tests {
         // LIST_WINDOWS_CMD) means a name like "build, test" stays in the
         // trailing field and can't shift window_active/@tmuxy-window-type/floats.
         let name = "build, test";
-        // @id,index,active,type,float_parent,fw,fh,drawer,bg,noheader,group,zoomed,name
-        let line = format!("@7,3,1,tab,,,,,,,,0,{name}");
+        // @id,index,active,type,float_parent,fw,fh,drawer,bg,noheader,group,zoomed,x,y,z,hidden,activity,name
+        let line = format!("@7,3,1,tab,,,,,,,,0,,,,,,{name}");
         let mut agg = StateAggregator::new();
         agg.parse_list_windows_line(&line);
         let w = agg.windows.get("@7").expect("window parsed");
@@ -2782,6 +4680,97 @@ mod tests {
         assert_eq!(agg.active_window_id.as_deref(), Some("@7"));
     }
 
+    #[test]
+    fn linked_window_listed_twice_keeps_its_first_listing() {
+        let mut agg = StateAggregator::new();
+        agg.handle_command_response(
+            "@1,0,0,tab,,,,,,,,0,,,,,,shell\n@2,1,0,tab,,,,,,,,0,,,,,,logs\n@2,5,1,tab,,,,,,,,0,,,,,,logs",
+        );
+        assert_eq!(agg.windows.len(), 2);
+        let logs = &agg.windows["@2"];
+        assert_eq!(logs.index, 1, "the second listing must not move the window");
+        assert!(logs.active, "current through its second index");
+        assert_eq!(agg.active_window_id.as_deref(), Some("@2"));
+    }
+
+    #[test]
+    fn preexisting_windows_are_adopted_as_tabs_whatever_their_names() {
+        let mut agg = StateAggregator::new();
+        // A session tmuxy didn't create: nothing is tagged, and one of the
+        // user's windows happens to be called `float`.
+        agg.handle_command_response("@0,0,1,,,,,,,,,0,,,,,,float\n@1,1,0,tab,,,,,,,,0,,,,,,shell");
+        let cmds = agg.collect_window_tag_commands();
+        assert_eq!(agg.windows["@0"].window_type, Some(WindowType::Tab));
+        assert!(cmds.contains(&"set-option -w -t @0 @tmuxy-window-type tab".to_string()));
+
+        // Windows made after attach still get tmuxy's name-based roles.
+        agg.handle_command_response(
+            "@0,0,1,tab,,,,,,,,0,,,,,,float\n@1,1,0,tab,,,,,,,,0,,,,,,shell\n@2,2,0,,,,,,,,,0,,,,,,float",
+        );
+        agg.collect_window_tag_commands();
+        assert_eq!(agg.windows["@2"].window_type, Some(WindowType::Float));
+    }
+
+    #[test]
+    fn session_group_is_read_on_attach_and_tracked() {
+        let mut agg = StateAggregator::new();
+        let attach = agg.process_event(ControlModeEvent::SessionChanged {
+            session_id: "$0".to_string(),
+            session_name: "main".to_string(),
+        });
+        assert_eq!(attach.commands, vec![session_group::query_command("main")]);
+        let reply = |output: &str| ControlModeEvent::CommandResponse {
+            timestamp: 0,
+            command_num: 0,
+            output: output.to_string(),
+            success: true,
+        };
+        let grouped = agg.process_event(reply("TMUXY_GROUP\n$0\n1\nmain\nmain,main-2\n"));
+        assert!(grouped.state_changed);
+        let state = agg.to_tmux_state();
+        let group = state.session_group.expect("grouped");
+        assert_eq!(group.sessions, ["main", "main-2"]);
+
+        // The same answer again is not a change.
+        let same = agg.process_event(reply("TMUXY_GROUP\n$0\n1\nmain\nmain,main-2\n"));
+        assert!(!same.state_changed);
+
+        // Sessions coming and going re-read the group.
+        let sessions = agg.process_event(ControlModeEvent::SessionsChanged);
+        assert!(!sessions.state_changed);
+        assert_eq!(
+            sessions.commands,
+            vec![session_group::query_command("main")]
+        );
+        let alone = agg.process_event(reply("TMUXY_GROUP\n$0\n0\n\n\n"));
+        assert!(alone.state_changed);
+        assert!(agg.to_tmux_state().session_group.is_none());
+    }
+
+    #[test]
+    fn grouped_session_switching_windows_leaves_ours_alone() {
+        let mut agg = StateAggregator::new();
+        seed_window(&mut agg, "@0", WindowType::Tab, true);
+        seed_window(&mut agg, "@1", WindowType::Tab, false);
+        agg.active_window_id = Some("@0".to_string());
+        agg.process_event(ControlModeEvent::SessionChanged {
+            session_id: "$0".to_string(),
+            session_name: "main".to_string(),
+        });
+        let theirs = agg.process_event(ControlModeEvent::SessionWindowChanged {
+            session_id: "$1".to_string(),
+            window_id: "@1".to_string(),
+        });
+        assert!(!theirs.state_changed);
+        assert_eq!(agg.active_window_id.as_deref(), Some("@0"));
+
+        agg.process_event(ControlModeEvent::SessionWindowChanged {
+            session_id: "$0".to_string(),
+            window_id: "@1".to_string(),
+        });
+        assert_eq!(agg.active_window_id.as_deref(), Some("@1"));
+    }
+
     /// Zoom has to come from `list-windows`, not only from `%layout-change`
     /// flags: window state is rebuilt from list-windows on every fresh client
     /// connect, which is exactly when a client attaching to an already-zoomed
@@ -2789,14 +4778,32 @@ mod tests {
     #[test]
     fn list_windows_carries_the_zoom_flag() {
         let mut agg = StateAggregator::new();
-        agg.parse_list_windows_line("@9,2,1,tab,,,,,,,,1,editor");
+        agg.parse_list_windows_line("@9,2,1,tab,,,,,,,,1,,,,,,editor");
         assert!(agg.windows.get("@9").expect("window parsed").zoomed);
 
         // ...and clears it again when the window is no longer zoomed.
-        agg.parse_list_windows_line("@9,2,1,tab,,,,,,,,0,editor");
+        agg.parse_list_windows_line("@9,2,1,tab,,,,,,,,0,,,,,,editor");
         assert!(!agg.windows.get("@9").expect("window parsed").zoomed);
     }
 
+    #[test]
+    fn list_windows_carries_float_position_stacking_and_visibility() {
+        let mut agg = StateAggregator::new();
+        agg.parse_list_windows_line("@5,3,0,float,@1,40,10,,,,,0,8,4,3,1,,float");
+        let w = agg.windows.get("@5").expect("window parsed");
+        assert_eq!(
+            (w.float_x, w.float_y, w.float_z),
+            (Some(8), Some(4), Some(3))
+        );
+        assert!(w.float_hidden);
+        assert_eq!(w.name, "float");
+
+        agg.parse_list_windows_line("@5,3,0,float,@1,40,10,,,,,0,,,3,,,float");
+        let w = agg.windows.get("@5").expect("window parsed");
+        assert_eq!((w.float_x, w.float_y), (None, None));
+        assert!(!w.float_hidden);
+    }
+
     #[test]
     fn window_add_assigns_provisional_index_past_the_highest() {
         // The tmuxy guest snapshot already has window id and index diverged:
@@ -2892,6 +4899,91 @@ mod tests {
         }
     }
 
+    #[test]
+    fn new_epoch_restarts_with_a_stamped_full_state() {
+        let mut agg = StateAggregator::new();
+        seed_pane(&mut agg, "%0", "@0");
+        agg.windows.insert("@0".to_string(), WindowState::new("@0"));
+        agg.set_status_line(String::new());
+        agg.begin_epoch(7);
+        match agg.to_state_update() {
+            Some(crate::StateUpdate::Full { state }) => assert_eq!(state.epoch, 7),
+            other => panic!("expected Full, got {other:?}"),
+        }
+
+        agg.set_do_not_disturb(true);
+        match agg.to_state_update() {
+            Some(crate::StateUpdate::Delta { delta }) => {
+                assert_eq!((delta.epoch, delta.seq), (7, 2));
+            }
+            other => panic!("expected Delta, got {other:?}"),
+        }
+
+        // A reconnect keeps the aggregator's view but clients need a base
+        // they can trust: the next update is full again, restamped.
+        agg.begin_epoch(8);
+        match agg.to_state_update() {
+            Some(crate::StateUpdate::Full { state }) => assert_eq!(state.epoch, 8),
+            other => panic!("expected Full, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn window_status_changes_travel_as_window_deltas() {
+        let mut agg = StateAggregator::new();
+        seed_pane(&mut agg, "%0", "@0");
+        agg.windows.insert("@0".to_string(), WindowState::new("@0"));
+        agg.set_status_line(String::new());
+        agg.to_state_update();
+
+        let statuses = HashMap::from([("@0".to_string(), "main +2".to_string())]);
+        assert!(agg.set_window_statuses(&statuses));
+        assert!(!agg.set_window_statuses(&statuses));
+        match agg.to_state_update() {
+            Some(crate::StateUpdate::Delta { delta }) => {
+                let window = &delta.windows.as_ref().unwrap()["@0"];
+                let window = window.as_ref().unwrap();
+                assert_eq!(window.status, Some(Some("main +2".to_string())));
+            }
+            other => panic!("expected Delta, got {other:?}"),
+        }
+
+        // An empty result is no status at all.
+        let empty = HashMap::from([("@0".to_string(), String::new())]);
+        assert!(agg.set_window_statuses(&empty));
+        assert_eq!(agg.to_tmux_state().windows[0].status, None);
+    }
+
+    #[test]
+    fn full_update_resends_the_base_of_the_next_delta() {
+        let mut agg = StateAggregator::new();
+        seed_pane(&mut agg, "%0", "@0");
+        agg.windows.insert("@0".to_string(), WindowState::new("@0"));
+        agg.set_status_line(String::new());
+        assert!(agg.full_update().is_none());
+        agg.to_state_update();
+
+        agg.set_do_not_disturb(true);
+        agg.to_state_update();
+        // Not yet emitted: the resend must not include it.
+        agg.set_ssh_agent(super::super::ssh_agent::SshAgentStatus::Live);
+        match agg.full_update() {
+            Some(crate::StateUpdate::Full { state }) => {
+                assert!(state.do_not_disturb);
+                assert_eq!(state.ssh_agent, Default::default());
+            }
+            other => panic!("expected Full, got {other:?}"),
+        }
+        assert_eq!(agg.delta_seq(), 2);
+        match agg.to_state_update() {
+            Some(crate::StateUpdate::Delta { delta }) => {
+                assert_eq!(delta.seq, 3);
+                assert!(delta.ssh_agent.is_some());
+            }
+            other => panic!("expected Delta, got {other:?}"),
+        }
+    }
+
     #[test]
     fn list_windows_still_corrects_a_wrong_provisional_index() {
         // Provisional is just a good default for the gap; the authoritative
@@ -2911,7 +5003,7 @@ mod tests {
         agg.process_event(ControlModeEvent::CommandResponse {
             timestamp: 0,
             command_num: 0,
-            output: "@1,5,1,tab,,,,,,,,shell".to_string(),
+            output: "@1,5,1,tab,,,,,,,,0,,,,,,shell".to_string(),
             success: true,
         });
         assert_eq!(
@@ -2920,4 +5012,1204 @@ mod tests {
             "authoritative list-windows index must overwrite the provisional"
         );
     }
+
+    /// Seat a tagged window so focus-follows-output can classify its panes.
+    fn seed_window(agg: &mut StateAggregator, window_id: &str, kind: WindowType, active: bool) {
+        let mut window = WindowState::new(window_id);
+        window.window_type = Some(kind);
+        window.active = active;
+        agg.windows.insert(window_id.to_string(), window);
+    }
+
+    fn output(pane_id: &str, content: &[u8]) -> ControlModeEvent {
+        ControlModeEvent::Output {
+            pane_id: pane_id.to_string(),
+            content: content.to_vec(),
+        }
+    }
+
+    #[test]
+    fn focus_follows_output_is_off_by_default() {
+        let mut agg = StateAggregator::new();
+        seed_window(&mut agg, "@0", WindowType::Tab, true);
+        seed_window(&mut agg, "@1", WindowType::Tab, false);
+        seed_pane(&mut agg, "%1", "@1");
+
+        let result = agg.process_event(output("%1", b"done\x07"));
+        assert!(result.commands.is_empty());
+    }
+
+    #[test]
+    fn bell_rule_selects_the_inactive_window_once() {
+        let mut agg = StateAggregator::new();
+        seed_window(&mut agg, "@0", WindowType::Tab, true);
+        seed_window(&mut agg, "@1", WindowType::Tab, false);
+        seed_pane(&mut agg, "%1", "@1");
+        agg.panes.get_mut("%1").unwrap().focus_follows_output = FocusFollowsOutput::Bell;
+
+        // Plain output and an OSC title (BEL-terminated) are not bells.
+        let plain = agg.process_event(output("%1", b"building\r\n\x1b]2;title\x07"));
+        assert!(plain.commands.is_empty());
+
+        let bell = agg.process_event(output("%1", b"done\x07"));
+        assert_eq!(bell.commands, vec!["select-window -t @1".to_string()]);
+
+        // The raise is in flight — more bells must not queue another one.
+        let again = agg.process_event(output("%1", b"\x07"));
+        assert!(again.commands.is_empty());
+
+        // Once the active window changes the pane can be raised again later.
+        agg.process_event(ControlModeEvent::SessionWindowChanged {
+            session_id: "$0".to_string(),
+            window_id: "@0".to_string(),
+        });
+        let later = agg.process_event(output("%1", b"\x07"));
+        assert_eq!(later.commands, vec!["select-window -t @1".to_string()]);
+    }
+
+    #[test]
+    fn any_rule_switches_a_background_group_member_into_view() {
+        let mut agg = StateAggregator::new();
+        seed_window(&mut agg, "@0", WindowType::Tab, true);
+        seed_window(&mut agg, "@1", WindowType::Tab, false);
+        seed_window(&mut agg, "@2", WindowType::Group, false);
+        agg.windows.get_mut("@2").unwrap().group_panes =
+            Some(vec!["%1".to_string(), "%2".to_string()]);
+        seed_pane(&mut agg, "%1", "@1");
+        seed_pane(&mut agg, "%2", "@2");
+        agg.panes.get_mut("%2").unwrap().focus_follows_output = FocusFollowsOutput::Any;
+
+        let result = agg.process_event(output("%2", b"tick"));
+        assert_eq!(
+            result.commands,
+            vec![
+                "resizew -t @2 -x 80 -y 24 ; swap-pane -s %2 -t %1".to_string(),
+                "select-window -t @1".to_string(),
+            ]
+        );
+    }
+
+    #[test]
+    fn group_check_repairs_memberships_on_the_next_list_windows_only() {
+        let mut agg = StateAggregator::new();
+        seed_pane(&mut agg, "%1", "@1");
+        seed_pane(&mut agg, "%2", "@2");
+        seed_pane(&mut agg, "%3", "@3");
+        let listed = || ControlModeEvent::CommandResponse {
+            timestamp: 0,
+            command_num: 0,
+            output: "@1,0,1,tab,,,,,,,,0,,,,,,bash\n\
+                     @2,1,0,group,,,,,,,%1 %2 %3,0,,,,,,group\n\
+                     @3,2,0,group,,,,,,,%1 %3,0,,,,,,group\n"
+                .to_string(),
+            success: true,
+        };
+        let sent = |step: StepResult| -> Vec<String> {
+            step.effects
+                .into_iter()
+                .filter_map(|e| match e {
+                    // Theme reports and status-line reads ride along.
+                    SideEffect::SendTmuxCommand(cmd)
+                        if !cmd.starts_with("refresh-client")
+                            && !cmd.starts_with("display-message") =>
+                    {
+                        Some(cmd)
+                    }
+                    _ => None,
+                })
+                .collect()
+        };
+        let now = Instant::now();
+
+        assert!(sent(agg.step_at(listed(), now)).is_empty());
+        agg.check_pane_groups();
+        assert_eq!(
+            sent(agg.step_at(listed(), now)),
+            [
+                "set -w -t @3 @tmuxy-group-panes \"%1 %2 %3\"",
+                crate::constants::tmux_formats::LIST_WINDOWS_CMD,
+            ]
+        );
+        assert!(sent(agg.step_at(listed(), now)).is_empty());
+    }
+
+    #[test]
+    fn pane_groups_follow_the_membership_order_once_each() {
+        let mut agg = StateAggregator::new();
+        seed_window(&mut agg, "@1", WindowType::Tab, true);
+        for (window, pane) in [("@2", "%2"), ("@3", "%3")] {
+            seed_window(&mut agg, window, WindowType::Group, false);
+            agg.windows.get_mut(window).unwrap().group_panes = Some(
+                ["%2", "%1", "%3", "%9"]
+                    .iter()
+                    .map(|id| id.to_string())
+                    .collect(),
+            );
+            seed_pane(&mut agg, pane, window);
+        }
+        seed_pane(&mut agg, "%1", "@1");
+
+        let groups = agg.pane_groups();
+        assert_eq!(groups.len(), 1);
+        let members: Vec<(&str, bool)> = groups[0]
+            .members
+            .iter()
+            .map(|m| (m.pane_id.as_str(), m.visible))
+            .collect();
+        assert_eq!(members, [("%2", false), ("%1", true), ("%3", false)]);
+    }
+
+    #[test]
+    fn visible_panes_are_never_raised() {
+        let mut agg = StateAggregator::new();
+        seed_window(&mut agg, "@0", WindowType::Tab, true);
+        seed_pane(&mut agg, "%0", "@0");
+        agg.panes.get_mut("%0").unwrap().focus_follows_output = FocusFollowsOutput::Any;
+
+        let result = agg.process_event(output("%0", b"tick"));
+        assert!(result.commands.is_empty());
+    }
+
+    #[test]
+    fn list_panes_parses_focus_follows_output_rule() {
+        let mut agg = StateAggregator::new();
+        agg.parse_list_panes_line(&list_panes_line_with_options("bell", "", ""));
+        let pane = agg.panes.get("%3").expect("pane parsed");
+        assert_eq!(pane.focus_follows_output, FocusFollowsOutput::Bell);
+        assert_eq!(pane.history_size, 100);
+    }
+
+    #[test]
+    fn list_panes_parses_selection_bounds() {
+        let line = "%3,0,0,0,80,24,0,0,1,zsh,t,1,7,12,0,@4,,0,0,1,2,110,7,112,100,,,,";
+        let mut agg = StateAggregator::new();
+        agg.parse_list_panes_line(line);
+        let pane = agg.panes.get_mut("%3").expect("pane parsed");
+        assert!(pane.selection_present);
+        assert_eq!((pane.selection_start_x, pane.selection_start_y), (2, 110));
+        assert_eq!((pane.selection_end_x, pane.selection_end_y), (7, 112));
+
+        // history_size 100, not scrolled: absolute row 100 is visible row 0.
+        let tmux_pane = pane.build_tmux_pane();
+        assert_eq!(tmux_pane.selection_start_y, 10);
+        assert_eq!(tmux_pane.selection_end_y, 12);
+    }
+
+    #[test]
+    fn stale_panes_reports_only_panes_idle_past_the_threshold() {
+        let day = std::time::Duration::from_secs(24 * 60 * 60);
+        let mut agg = StateAggregator::new();
+        seed_window(&mut agg, "@0", WindowType::Tab, true);
+        seed_pane(&mut agg, "%1", "@0");
+        seed_pane(&mut agg, "%2", "@0");
+
+        // %2 is an idle shell next to a busy pane in the same window.
+        let t0 = Instant::now();
+        agg.step_at(output("%1", b"a"), t0);
+        agg.step_at(output("%1", b"b"), t0 + 3 * day);
+
+        let stale = agg.stale_panes(t0 + 3 * day, 0, 2 * day);
+        assert_eq!(stale.len(), 1);
+        assert_eq!(stale[0].pane_id, "%2");
+        assert_eq!(stale[0].idle_secs, (3 * day).as_secs());
+        assert!(agg.stale_panes(t0 + 3 * day, 0, 4 * day).is_empty());
+    }
+
+    #[test]
+    fn window_activity_floors_the_idle_time_of_panes_unused_since_attach() {
+        let day = 24 * 60 * 60;
+        let unix_t0 = 1_700_000_000;
+        let mut agg = StateAggregator::new();
+        agg.parse_list_windows_line(&format!("@0,0,1,tab,,,,,,,,0,,,,,{unix_t0},old"));
+        seed_pane(&mut agg, "%1", "@0");
+        seed_pane(&mut agg, "%2", "@0");
+
+        // Attached five days after the window's last output.
+        let t0 = Instant::now();
+        agg.step_at(output("%1", b"a"), t0);
+        let min_idle = std::time::Duration::from_secs(2 * day);
+        let stale = agg.stale_panes(t0, unix_t0 + 5 * day, min_idle);
+        assert_eq!(stale.len(), 1);
+        assert_eq!(stale[0].pane_id, "%2");
+        assert_eq!(stale[0].window_name, "old");
+        assert_eq!(stale[0].idle_secs, 5 * day);
+    }
+
+    fn seed_rect(agg: &mut StateAggregator, pane_id: &str, rect: (u32, u32, u32, u32)) {
+        seed_pane(agg, pane_id, "@0");
+        let pane = agg.panes.get_mut(pane_id).expect("seeded");
+        (pane.x, pane.y, pane.width, pane.height) = rect;
+    }
+
+    #[test]
+    fn drag_on_vertical_border_resizes_the_left_pane() {
+        // %0 | %1 side by side, border at column 40.
+        let mut agg = StateAggregator::new();
+        seed_window(&mut agg, "@0", WindowType::Tab, true);
+        seed_rect(&mut agg, "%0", (0, 0, 40, 24));
+        seed_rect(&mut agg, "%1", (41, 0, 39, 24));
+
+        assert_eq!(
+            agg.drag_resize_commands("@0", (40, 10), (45, 12)),
+            vec!["resize-pane -t %0 -R 5".to_string()]
+        );
+        assert_eq!(
+            agg.drag_resize_commands("@0", (40, 10), (37, 10)),
+            vec!["resize-pane -t %0 -L 3".to_string()]
+        );
+        // Inside a pane, not on a border.
+        assert!(agg
+            .drag_resize_commands("@0", (20, 10), (25, 10))
+            .is_empty());
+    }
+
+    #[test]
+    fn drag_on_border_corner_resizes_both_axes() {
+        // 2x2 grid; the borders cross at (40, 12).
+        let mut agg = StateAggregator::new();
+        seed_window(&mut agg, "@0", WindowType::Tab, true);
+        seed_rect(&mut agg, "%0", (0, 0, 40, 12));
+        seed_rect(&mut agg, "%1", (41, 0, 39, 12));
+        seed_rect(&mut agg, "%2", (0, 13, 40, 11));
+        seed_rect(&mut agg, "%3", (41, 13, 39, 11));
+
+        assert_eq!(
+            agg.drag_resize_commands("@0", (40, 12), (44, 8)),
+            vec![
+                "resize-pane -t %0 -R 4".to_string(),
+                "resize-pane -t %0 -U 4".to_string(),
+            ]
+        );
+        assert_eq!(
+            agg.drag_resize_commands("@0", (20, 12), (20, 15)),
+            vec!["resize-pane -t %0 -D 3".to_string()]
+        );
+    }
+
+    #[test]
+    fn drag_in_zoomed_window_is_ignored() {
+        let mut agg = StateAggregator::new();
+        seed_window(&mut agg, "@0", WindowType::Tab, true);
+        seed_rect(&mut agg, "%0", (0, 0, 40, 24));
+        seed_rect(&mut agg, "%1", (41, 0, 39, 24));
+        agg.windows.get_mut("@0").expect("seeded").zoomed = true;
+
+        assert!(agg
+            .drag_resize_commands("@0", (40, 10), (45, 10))
+            .is_empty());
+    }
+
+    #[test]
+    fn pane_drop_swaps_in_the_middle_and_joins_near_an_edge() {
+        // %0 | %1 side by side.
+        let mut agg = StateAggregator::new();
+        seed_window(&mut agg, "@0", WindowType::Tab, true);
+        seed_rect(&mut agg, "%0", (0, 0, 40, 24));
+        seed_rect(&mut agg, "%1", (41, 0, 39, 24));
+
+        let swap = agg.pane_drop_target("%0", 60, 12).expect("over %1");
+        assert_eq!(
+            swap,
+            PaneDropTarget::Swap {
+                source: "%0".into(),
+                target: "%1".into(),
+            }
+        );
+        assert_eq!(drop_command(&swap), "swap-pane -s %0 -t %1");
+
+        let bottom = agg.pane_drop_target("%0", 60, 22).expect("over %1");
+        assert_eq!(drop_command(&bottom), "join-pane -v -s %0 -t %1");
+        let left = agg.pane_drop_target("%1", 2, 12).expect("over %0");
+        assert_eq!(drop_command(&left), "join-pane -b -h -s %1 -t %0");
+
+        // Over itself, on the border, or outside the window.
+        assert!(agg.pane_drop_target("%0", 10, 12).is_none());
+        assert!(agg.pane_drop_target("%0", 40, 12).is_none());
+        assert!(agg.pane_drop_target("%0", 90, 12).is_none());
+    }
+
+    #[test]
+    fn widget_data_becomes_an_effect_for_its_pane() {
+        let mut agg = StateAggregator::new();
+        seed_pane(&mut agg, "%0", "@0");
+        let step = agg.step(output("%0", b"\x1b]7331;{\"value\":3}\x07"));
+        let data: Vec<_> = step
+            .effects
+            .iter()
+            .filter_map(|e| match e {
+                SideEffect::WidgetData { pane_id, data } => Some((pane_id.as_str(), data.clone())),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(data, [("%0", serde_json::json!({ "value": 3 }))]);
+    }
+
+    #[test]
+    fn accessible_output_speaks_completed_lines_once() {
+        let mut agg = StateAggregator::new();
+        seed_pane(&mut agg, "%0", "@0");
+        let spoken = |effects: &[SideEffect]| -> Vec<String> {
+            effects
+                .iter()
+                .filter_map(|e| match e {
+                    SideEffect::SpokenText { lines, .. } => Some(lines.clone()),
+                    _ => None,
+                })
+                .flatten()
+                .collect()
+        };
+        // Off by default.
+        assert!(spoken(&agg.step(output("%0", b"quiet\r\n")).effects).is_empty());
+
+        agg.set_accessible_output(true);
+        let done = agg.step(output("%0", b"$ make\r\nbuilding\r\n"));
+        assert_eq!(spoken(&done.effects), ["$ make", "building"]);
+        // A spinner rewriting the cursor row says nothing until the line ends.
+        for frame in [&b"\r|"[..], b"\r/", b"\r-"] {
+            assert!(spoken(&agg.step(output("%0", frame)).effects).is_empty());
+        }
+        assert_eq!(
+            spoken(&agg.step(output("%0", b"\rdone\r\n")).effects),
+            ["done"]
+        );
+        // Full-screen apps are not narrated.
+        let alt = agg.step(output("%0", b"\x1b[?1049hmenu\r\nitem\r\n"));
+        assert!(spoken(&alt.effects).is_empty());
+        let back = agg.step(output("%0", b"\x1b[?1049l"));
+        assert!(spoken(&back.effects).is_empty());
+    }
+
+    #[test]
+    fn split_preview_follows_tmux_sizing() {
+        let mut agg = StateAggregator::new();
+        seed_window(&mut agg, "@0", WindowType::Tab, true);
+        seed_rect(&mut agg, "%0", (0, 0, 40, 24));
+        seed_rect(&mut agg, "%1", (41, 0, 39, 24));
+
+        let side = agg
+            .preview_split("%1", SplitDirection::Horizontal, 50)
+            .expect("splittable");
+        assert_eq!(
+            side,
+            SplitPreview {
+                pane: PaneRect {
+                    x: 41,
+                    y: 0,
+                    width: 19,
+                    height: 24
+                },
+                new_pane: PaneRect {
+                    x: 61,
+                    y: 0,
+                    width: 19,
+                    height: 24
+                },
+            }
+        );
+        let below = agg
+            .preview_split("%0", SplitDirection::Vertical, 25)
+            .expect("splittable");
+        assert_eq!((below.pane.y, below.pane.height), (0, 17));
+        assert_eq!((below.new_pane.y, below.new_pane.height), (18, 6));
+
+        assert!(agg
+            .preview_split("%0", SplitDirection::Vertical, 0)
+            .is_none());
+        assert!(agg
+            .preview_split("%9", SplitDirection::Vertical, 50)
+            .is_none());
+        agg.windows.get_mut("@0").expect("seeded").zoomed = true;
+        assert!(agg
+            .preview_split("%0", SplitDirection::Vertical, 50)
+            .is_none());
+    }
+
+    #[test]
+    fn scrolled_off_rows_are_readable_from_the_emulator() {
+        let mut agg = StateAggregator::new();
+        seed_pane(&mut agg, "%0", "@0");
+        let text = |line: &crate::TerminalLine| -> String {
+            line.iter().map(|c| c.char.as_str()).collect()
+        };
+        let output: String = (0..30).map(|i| format!("line {i}\r\n")).collect();
+        agg.panes
+            .get_mut("%0")
+            .expect("seeded")
+            .process_output(output.as_bytes());
+
+        // 24 rows show lines 7..=29 plus the blank cursor row; 0..=6 scrolled off.
+        let history = agg.pane_scrollback("%0", 100).expect("known pane");
+        assert_eq!(history.len(), 7);
+        assert_eq!(text(&history[0]), "line 0");
+        assert_eq!(text(&history[6]), "line 6");
+        let recent = agg.pane_scrollback("%0", 2).expect("known pane");
+        assert_eq!(
+            recent.iter().map(text).collect::<Vec<_>>(),
+            ["line 5", "line 6"]
+        );
+
+        // Shrinking pushes more rows into history instead of dropping them.
+        agg.panes.get_mut("%0").expect("seeded").resize(80, 20);
+        let history = agg.pane_scrollback("%0", 100).expect("known pane");
+        assert_eq!(history.len(), 11);
+        assert_eq!(text(&history[10]), "line 10");
+        assert!(agg.pane_scrollback("%9", 10).is_none());
+    }
+
+    #[test]
+    fn scrolling_output_is_sent_as_a_scroll_delta() {
+        let mut agg = StateAggregator::new();
+        seed_pane(&mut agg, "%0", "@0");
+        // A second, quiet pane keeps the update under the full-state cutoff.
+        seed_pane(&mut agg, "%1", "@0");
+        agg.windows.insert("@0".to_string(), WindowState::new("@0"));
+        let mut fill = Vec::new();
+        for i in 0..40 {
+            fill.extend_from_slice(format!("line {i}\r\n").as_bytes());
+        }
+        agg.step(output("%0", &fill));
+        agg.set_status_line(String::new());
+        assert!(matches!(
+            agg.to_state_update(),
+            Some(crate::StateUpdate::Full { .. })
+        ));
+
+        agg.step(output("%0", b"line 40\r\n"));
+        agg.set_status_line(String::new());
+        match agg.to_state_update() {
+            Some(crate::StateUpdate::Delta { delta }) => {
+                let panes = delta.panes.expect("pane delta present");
+                let pane = panes
+                    .get("%0")
+                    .expect("%0 in delta")
+                    .as_ref()
+                    .expect("modified, not removed");
+                let scroll = pane.scroll.as_ref().expect("sent as a scroll");
+                assert_eq!(scroll.lines, 1);
+                assert_eq!(scroll.new_rows.len(), 1);
+                // Only the old cursor row, which received "line 40", is resent.
+                let content = pane.content.as_ref().expect("rewritten row");
+                assert_eq!(content.keys().copied().collect::<Vec<_>>(), vec![22]);
+            }
+            other => panic!("expected Delta, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn editing_the_bottom_line_resends_only_that_row() {
+        let mut agg = StateAggregator::new();
+        seed_pane(&mut agg, "%0", "@0");
+        seed_pane(&mut agg, "%1", "@0");
+        agg.windows.insert("@0".to_string(), WindowState::new("@0"));
+        let mut fill = Vec::new();
+        for i in 0..23 {
+            fill.extend_from_slice(format!("message {i}\r\n").as_bytes());
+        }
+        fill.extend_from_slice(b"> ");
+        agg.step(output("%0", &fill));
+        agg.set_status_line(String::new());
+        agg.to_state_update();
+
+        agg.step(output("%0", b"hel"));
+        match agg.to_state_update() {
+            Some(crate::StateUpdate::Delta { delta }) => {
+                let panes = delta.panes.expect("pane delta present");
+                let pane = panes["%0"].as_ref().expect("modified, not removed");
+                assert!(pane.scroll.is_none());
+                let content = pane.content.as_ref().expect("changed row");
+                assert_eq!(content.keys().copied().collect::<Vec<_>>(), vec![23]);
+                assert_eq!(pane.cursor_x, Some(5));
+            }
+            other => panic!("expected Delta, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn detect_scroll_rejects_unrelated_screens() {
+        let line = |s: &str| -> crate::TerminalLine {
+            s.chars()
+                .map(|c| crate::TerminalCell::new(c.to_string()))
+                .collect()
+        };
+        let prev = vec![line("a"), line("b"), line("c")];
+        assert_eq!(
+            detect_scroll(&prev, &[line("b"), line("c"), line("d")]),
+            Some((1, vec![]))
+        );
+        assert_eq!(
+            detect_scroll(&prev, &[line("x"), line("y"), line("z")]),
+            None
+        );
+    }
+
+    #[test]
+    fn is_cursor_only_accepts_motion_and_rejects_cell_writes() {
+        assert!(is_cursor_only(b"\x1b[5D"));
+        assert!(is_cursor_only(b"\r\x1b[12;3H\x08"));
+        assert!(is_cursor_only(b"\x1b[?25l\x1b7\x1b8\x1b[?25h"));
+        assert!(is_cursor_only(b"\x1b[5 q"));
+        assert!(!is_cursor_only(b"a"));
+        assert!(!is_cursor_only(b"\n"));
+        assert!(!is_cursor_only(b"\x1b[K"));
+        assert!(!is_cursor_only(b"\x1b[?1049h"));
+        assert!(!is_cursor_only(b"\x1b[5"));
+    }
+
+    #[test]
+    fn cursor_only_output_sends_just_the_cursor() {
+        let mut agg = StateAggregator::new();
+        seed_pane(&mut agg, "%0", "@0");
+        seed_pane(&mut agg, "%1", "@0");
+        agg.windows.insert("@0".to_string(), WindowState::new("@0"));
+        agg.step(output("%0", b"$ echo hi"));
+        agg.set_status_line(String::new());
+        let _ = agg.to_state_update();
+
+        agg.step(output("%0", b"\x1b[3D"));
+        assert!(!agg.panes["%0"].content_dirty);
+        agg.set_status_line(String::new());
+        match agg.to_state_update() {
+            Some(crate::StateUpdate::Delta { delta }) => {
+                let panes = delta.panes.expect("pane delta present");
+                let pane = panes
+                    .get("%0")
+                    .expect("%0 in delta")
+                    .as_ref()
+                    .expect("modified, not removed");
+                assert_eq!(pane.cursor_x, Some(6));
+                assert!(pane.content.is_none());
+                assert!(pane.scroll.is_none());
+            }
+            other => panic!("expected Delta, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn bells_are_reported_once_per_interval() {
+        let mut agg = StateAggregator::new();
+        seed_pane(&mut agg, "%0", "@0");
+        let t0 = Instant::now();
+        let bells = |effects: &[SideEffect]| {
+            effects
+                .iter()
+                .filter(|e| matches!(e, SideEffect::Bell { pane_id } if pane_id == "%0"))
+                .count()
+        };
+
+        assert_eq!(bells(&agg.step_at(output("%0", b"x\x07"), t0).effects), 1);
+        let soon = t0 + BELL_MIN_INTERVAL / 2;
+        assert_eq!(bells(&agg.step_at(output("%0", b"\x07"), soon).effects), 0);
+        // An OSC title terminated by BEL is not a bell.
+        let later = t0 + BELL_MIN_INTERVAL;
+        let title = agg.step_at(output("%0", b"\x1b]2;title\x07"), later);
+        assert_eq!(bells(&title.effects), 0);
+        assert_eq!(bells(&agg.step_at(output("%0", b"\x07"), later).effects), 1);
+    }
+
+    #[test]
+    fn notifications_become_effects_outside_do_not_disturb() {
+        let mut agg = StateAggregator::new();
+        seed_pane(&mut agg, "%0", "@0");
+        let notified = |effects: &[SideEffect]| -> Vec<String> {
+            effects
+                .iter()
+                .filter_map(|e| match e {
+                    SideEffect::Notify {
+                        pane_id,
+                        notification,
+                    } if pane_id == "%0" => Some(notification.body.clone()),
+                    _ => None,
+                })
+                .collect()
+        };
+
+        let effects = agg
+            .step(output("%0", b"\x1b]9;one\x07\x1b]9;two\x07"))
+            .effects;
+        assert_eq!(notified(&effects), ["one", "two"]);
+        // Not a bell either.
+        assert!(!effects.iter().any(|e| matches!(e, SideEffect::Bell { .. })));
+
+        agg.set_do_not_disturb(true);
+        assert!(notified(&agg.step(output("%0", b"\x1b]9;three\x07")).effects).is_empty());
+        agg.set_do_not_disturb(false);
+        assert!(notified(&agg.step(output("%0", b"x")).effects).is_empty());
+    }
+
+    #[test]
+    fn run_markers_route_a_client_command_output_back() {
+        let mut agg = StateAggregator::new();
+        let mut reply = |output: &str, success: bool| {
+            agg.step(ControlModeEvent::CommandResponse {
+                timestamp: 0,
+                command_num: 0,
+                output: output.to_string(),
+                success,
+            })
+            .effects
+            .into_iter()
+            .find_map(|e| match e {
+                SideEffect::CommandOutput {
+                    run_id,
+                    output,
+                    success,
+                } => Some((run_id, output, success)),
+                _ => None,
+            })
+        };
+        let lines = run_command_lines(7, "lsw ; display -p hi");
+        assert_eq!(lines.len(), 3);
+        assert_eq!(lines[1], "lsw ; display -p hi");
+
+        // Every block between the markers is collected.
+        assert_eq!(reply(&format!("{RUN_BEGIN_MARKER} 7\n"), true), None);
+        assert_eq!(reply("0: zsh\n", true), None);
+        assert_eq!(reply("hi\n", true), None);
+        assert_eq!(
+            reply(&format!("{RUN_END_MARKER} 7\n"), true),
+            Some((7, "0: zsh\nhi\n".to_string(), true))
+        );
+
+        // A failed command fails the run; the END marker still closes it.
+        reply(&format!("{RUN_BEGIN_MARKER} 8\n"), true);
+        reply("unknown command: nope\n", false);
+        assert_eq!(
+            reply(&format!("{RUN_END_MARKER} 8\n"), true),
+            Some((8, "unknown command: nope\n".to_string(), false))
+        );
+
+        // An END for another run, or with none open, produces nothing.
+        reply(&format!("{RUN_BEGIN_MARKER} 9\n"), true);
+        assert_eq!(reply(&format!("{RUN_END_MARKER} 10\n"), true), None);
+        assert_eq!(reply(&format!("{RUN_END_MARKER} 9\n"), true), None);
+    }
+
+    #[test]
+    fn liveness_probe_reply_changes_nothing() {
+        let mut agg = StateAggregator::new();
+        let result = agg.process_event(ControlModeEvent::CommandResponse {
+            timestamp: 0,
+            command_num: 0,
+            output: format!("{LIVENESS_MARKER}\n"),
+            success: true,
+        });
+        assert!(!result.state_changed);
+    }
+
+    #[test]
+    fn do_not_disturb_silences_bells_and_raises() {
+        let mut agg = StateAggregator::new();
+        seed_window(&mut agg, "@0", WindowType::Tab, true);
+        seed_window(&mut agg, "@1", WindowType::Tab, false);
+        seed_pane(&mut agg, "%1", "@1");
+        agg.panes.get_mut("%1").unwrap().focus_follows_output = FocusFollowsOutput::Bell;
+        agg.set_status_line(String::new());
+        let _ = agg.to_state_update();
+
+        assert!(agg.set_do_not_disturb(true));
+        assert!(!agg.set_do_not_disturb(true));
+        agg.set_status_line(String::new());
+        match agg.to_state_update() {
+            Some(crate::StateUpdate::Delta { delta }) => {
+                assert_eq!(delta.do_not_disturb, Some(true));
+                assert!(delta.panes.is_none());
+            }
+            other => panic!("expected Delta, got {other:?}"),
+        }
+
+        let quiet = agg.step_at(output("%1", b"done\x07"), Instant::now());
+        assert!(!quiet
+            .effects
+            .iter()
+            .any(|e| matches!(e, SideEffect::Bell { .. } | SideEffect::SendTmuxCommand(_))));
+
+        agg.set_do_not_disturb(false);
+        let loud = agg.step_at(output("%1", b"\x07"), Instant::now());
+        assert!(loud
+            .effects
+            .iter()
+            .any(|e| matches!(e, SideEffect::Bell { pane_id } if pane_id == "%1")));
+        assert!(!agg.to_tmux_state().do_not_disturb);
+    }
+
+    #[test]
+    fn capability_queries_are_answered_unless_the_pane_opts_out() {
+        let mut agg = StateAggregator::new();
+        agg.parse_list_panes_line(&list_panes_line_with_options("", "", ""));
+        // XTGETTCAP for `Tc`.
+        let query = b"\x1bP+q5463\x1b\\";
+        let replies = |agg: &mut StateAggregator| -> Vec<String> {
+            agg.step(output("%3", query))
+                .effects
+                .into_iter()
+                .filter_map(|e| match e {
+                    SideEffect::SendTmuxCommand(cmd) if cmd.starts_with("send-keys") => Some(cmd),
+                    _ => None,
+                })
+                .collect()
+        };
+
+        assert_eq!(
+            replies(&mut agg),
+            ["send-keys -t '%3' -H 1b 50 31 2b 72 35 34 36 33 1b 5c"]
+        );
+
+        agg.panes.get_mut("%3").unwrap().in_mode = true;
+        assert!(replies(&mut agg).is_empty());
+
+        agg.parse_list_panes_line(&list_panes_line_with_options("", "off", ""));
+        assert!(!agg.panes["%3"].answerback);
+        assert!(replies(&mut agg).is_empty());
+    }
+
+    #[test]
+    fn closed_panes_leave_an_exit_summary() {
+        let mut agg = StateAggregator::new();
+        agg.parse_list_panes_line(&list_panes_line_with_options("", "", ""));
+        agg.step(output("%3", b"make: *** [all] Error 2\r\n"));
+        agg.step(ControlModeEvent::WindowClose {
+            window_id: "@4".to_string(),
+        });
+        assert!(!agg.has_pane("%3"));
+
+        let summaries = agg.exit_summaries(Instant::now());
+        assert_eq!(summaries.len(), 1);
+        assert_eq!(summaries[0].pane_id, "%3");
+        assert_eq!(summaries[0].window_id, "@4");
+        assert_eq!(summaries[0].screen, ["make: *** [all] Error 2"]);
+
+        // The same id back in the session means the pane only moved.
+        agg.parse_list_panes_line(&list_panes_line_with_options("", "", ""));
+        agg.step(output("%3", b"$ "));
+        assert!(agg.exit_summaries(Instant::now()).is_empty());
+    }
+
+    #[test]
+    fn shell_marks_build_the_pane_command_history() {
+        let mut agg = StateAggregator::new();
+        agg.parse_list_panes_line(&list_panes_line_with_options("", "", ""));
+        let start = Instant::now();
+        // A coloured prompt: the command line is read from where the
+        // emulator put the cursor, not from a count of the bytes.
+        agg.step_at(
+            output(
+                "%3",
+                b"\x1b]133;A\x07\x1b[32m~\x1b[0m $ \x1b]133;B\x07echo hi\r\n\x1b]133;C\x07hi\r\n\x1b]133;D;0\x07",
+            ),
+            start,
+        );
+        agg.step_at(
+            output(
+                "%3",
+                b"\x1b]133;A\x07$ \x1b]133;B\x07false\r\n\x1b]133;C;cmdline_url=false\x07",
+            ),
+            start,
+        );
+        let running = agg.command_history("%3").unwrap();
+        assert_eq!(running.len(), 2);
+        assert!(running[1].running);
+
+        agg.step_at(
+            output("%3", b"\x1b]133;D;1\x07"),
+            start + Duration::from_millis(250),
+        );
+        let commands = agg.command_history("%3").unwrap();
+        let found: Vec<_> = commands
+            .iter()
+            .map(|c| {
+                (
+                    &*c.command,
+                    c.prompt_line,
+                    c.output_start,
+                    c.output_end,
+                    c.exit_status,
+                    c.duration_ms,
+                )
+            })
+            .collect();
+        assert_eq!(
+            found,
+            [
+                ("echo hi", 0, 1, Some(1), Some(0), Some(0)),
+                ("false", 2, 3, None, Some(1), Some(250)),
+            ]
+        );
+        assert!(agg.command_history("%9").is_none());
+
+        // The same commands as gutter blocks on the pane.
+        let pane = agg.panes.get_mut("%3").unwrap().build_tmux_pane();
+        let blocks: Vec<_> = pane
+            .command_blocks
+            .iter()
+            .map(|b| (b.id, b.start_row, b.end_row, b.exit_status))
+            .collect();
+        assert_eq!(blocks, [(1, 0, 1, Some(0)), (2, 2, 2, Some(1))]);
+    }
+
+    #[test]
+    fn a_pane_gone_for_good_is_reported_once() {
+        let exited = |effects: &[SideEffect]| -> Vec<String> {
+            effects
+                .iter()
+                .filter_map(|e| match e {
+                    SideEffect::PaneExited(summary) => Some(summary.pane_id.clone()),
+                    _ => None,
+                })
+                .collect()
+        };
+        let mut agg = StateAggregator::new();
+        agg.parse_list_panes_line(&list_panes_line_with_options("", "", ""));
+        let close = ControlModeEvent::WindowClose {
+            window_id: "@4".to_string(),
+        };
+        assert_eq!(exited(&agg.step(close).effects), ["%3"]);
+        assert!(exited(&agg.step(output("%1", b"x")).effects).is_empty());
+    }
+
+    #[test]
+    fn keyboard_protocol_requests_mark_the_pane() {
+        let mut agg = StateAggregator::new();
+        agg.parse_list_panes_line(&list_panes_line_with_options("", "", ""));
+        let effects = agg.step(output("%3", b"\x1b[>1u\x1b[?u")).effects;
+        assert!(effects.iter().any(
+            |e| matches!(e, SideEffect::SendTmuxCommand(cmd) if cmd == "send-keys -t '%3' -H 1b 5b 3f 31 75")
+        ));
+        assert!(agg.panes["%3"].extended_keys);
+        assert_eq!(agg.pane_keyboard_mode("%3").unwrap().kitty_flags, 1);
+
+        // Copy mode reads keys as commands: no encoding there.
+        agg.panes.get_mut("%3").unwrap().in_mode = true;
+        assert!(agg.pane_keyboard_mode("%3").unwrap().is_legacy());
+        agg.panes.get_mut("%3").unwrap().in_mode = false;
+
+        agg.step(output("%3", b"\x1b[<u"));
+        assert!(!agg.panes["%3"].extended_keys);
+    }
+
+    #[test]
+    fn focus_reports_follow_client_focus_and_the_active_pane() {
+        const FOCUS_IN: &str = "send-keys -t '%3' -H 1b 5b 49";
+        const FOCUS_OUT: &str = "send-keys -t '%3' -H 1b 5b 4f";
+        let sent = |effects: &[SideEffect]| -> Vec<String> {
+            effects
+                .iter()
+                .filter_map(|e| match e {
+                    SideEffect::SendTmuxCommand(cmd) if cmd.contains(" -H 1b 5b 4") => {
+                        Some(cmd.clone())
+                    }
+                    _ => None,
+                })
+                .collect()
+        };
+        let mut agg = StateAggregator::new();
+        agg.parse_list_panes_line(&list_panes_line("zsh", "@4", ""));
+        agg.active_window_id = Some("@4".to_string());
+
+        // No client has focus yet.
+        assert!(sent(&agg.step(output("%3", b"\x1b[?1004h")).effects).is_empty());
+        agg.set_client_focus(true);
+        assert_eq!(agg.collect_focus_commands(), [FOCUS_IN]);
+        assert!(agg.collect_focus_commands().is_empty());
+
+        // Switching away from the pane's window takes its focus.
+        let effects = agg
+            .step(ControlModeEvent::SessionWindowChanged {
+                session_id: "$0".to_string(),
+                window_id: "@5".to_string(),
+            })
+            .effects;
+        assert_eq!(sent(&effects), [FOCUS_OUT]);
+
+        // A pane that turned reporting off hears nothing.
+        agg.active_window_id = Some("@4".to_string());
+        assert_eq!(agg.collect_focus_commands(), [FOCUS_IN]);
+        agg.step(output("%3", b"\x1b[?1004l"));
+        agg.set_client_focus(false);
+        assert!(agg.collect_focus_commands().is_empty());
+    }
+
+    #[test]
+    fn session_window_changes_reorder_the_window_mru() {
+        let mut agg = StateAggregator::new();
+        for (id, index) in [("@0", 0), ("@1", 1), ("@2", 2)] {
+            seed_window(&mut agg, id, WindowType::Tab, id == "@0");
+            agg.windows.get_mut(id).unwrap().index = index;
+        }
+        seed_window(&mut agg, "@9", WindowType::Float, false);
+        let visit = |agg: &mut StateAggregator, window_id: &str| {
+            agg.step(ControlModeEvent::SessionWindowChanged {
+                session_id: "$0".to_string(),
+                window_id: window_id.to_string(),
+            });
+        };
+        visit(&mut agg, "@2");
+        visit(&mut agg, "@1");
+        let now = Instant::now();
+        assert_eq!(agg.window_mru(now).windows, ["@1", "@2", "@0"]);
+
+        assert_eq!(agg.step_window_cycle(false, now).as_deref(), Some("@2"));
+        visit(&mut agg, "@2");
+        assert_eq!(agg.window_mru(now).windows, ["@1", "@2", "@0"]);
+        agg.end_window_cycle();
+        assert_eq!(agg.window_mru(now).windows, ["@2", "@1", "@0"]);
+    }
+
+    #[test]
+    fn sandbox_tag_rides_the_pane() {
+        let mut agg = StateAggregator::new();
+        agg.parse_list_panes_line(&list_panes_line_with_options("", "", "docker:alpine:3"));
+        assert_eq!(agg.panes["%3"].sandbox, "docker:alpine:3");
+        assert_eq!(agg.panes["%3"].window_id, "@4");
+
+        // Commas in the free-text titles don't shift the tail.
+        let line = list_panes_line("a,b", "@4", "c,d");
+        let line = format!("{},,,bwrap,", line.strip_suffix(",,,,").unwrap());
+        agg.parse_list_panes_line(&line);
+        assert_eq!(agg.panes["%3"].sandbox, "bwrap");
+        assert_eq!(agg.panes["%3"].border_title, "c,d");
+        assert_eq!(agg.panes["%3"].history_size, 100);
+
+        agg.parse_list_panes_line(&list_panes_line_with_options("", "", ""));
+        assert_eq!(agg.panes["%3"].sandbox, "");
+    }
+
+    #[test]
+    fn remembered_widgets_restore_only_into_idle_shells() {
+        let mut agg = StateAggregator::new();
+        seed_window(&mut agg, "@1", WindowType::Tab, true);
+        agg.windows.get_mut("@1").unwrap().index = 2;
+        for (pane_id, index, command) in [("%5", 0, "zsh"), ("%6", 1, "zsh"), ("%7", 2, "vim")] {
+            seed_pane(&mut agg, pane_id, "@1");
+            let pane = agg.panes.get_mut(pane_id).unwrap();
+            pane.index = index;
+            pane.command = command.to_string();
+        }
+        let image = WidgetSpec::new("image", "/a.png");
+        agg.panes.get_mut("%6").unwrap().widget = Some(image.clone());
+
+        let placed = agg.placed_widgets();
+        assert_eq!(
+            placed,
+            [PlacedWidget {
+                window_index: 2,
+                pane_index: 1,
+                spec: image.clone(),
+            }]
+        );
+        // Still running: nothing to restore.
+        assert!(agg.widgets_to_restore(&placed).is_empty());
+
+        // After a restore every pane is a fresh shell with no tag.
+        agg.panes.get_mut("%6").unwrap().widget = None;
+        let remembered = [
+            placed[0].clone(),
+            PlacedWidget {
+                window_index: 2,
+                pane_index: 2,
+                spec: WidgetSpec::new("markdown", "/b.md"),
+            },
+            PlacedWidget {
+                window_index: 9,
+                pane_index: 0,
+                spec: WidgetSpec::new("markdown", "/c.md"),
+            },
+        ];
+        // %7 now runs something else and window 9 is gone.
+        assert_eq!(
+            agg.widgets_to_restore(&remembered),
+            [("%6".to_string(), image)]
+        );
+    }
+
+    #[test]
+    fn widget_tag_rides_the_pane() {
+        let mut agg = StateAggregator::new();
+        // The widget is the last field, empty in `list_panes_line`.
+        let line = list_panes_line("a,b", "@4", "c,d") + "markdown;source=/notes/x%2Cy.md";
+        agg.parse_list_panes_line(&line);
+        assert_eq!(
+            agg.panes["%3"].widget,
+            Some(WidgetSpec::new("markdown", "/notes/x,y.md"))
+        );
+        assert_eq!(agg.panes["%3"].border_title, "c,d");
+        assert_eq!(
+            agg.panes.get_mut("%3").unwrap().build_tmux_pane().widget,
+            Some(WidgetSpec::new("markdown", "/notes/x,y.md"))
+        );
+
+        // The launcher clears the option when the widget exits.
+        agg.parse_list_panes_line(&list_panes_line("a,b", "@4", "c,d"));
+        assert_eq!(agg.panes["%3"].widget, None);
+    }
+
+    #[test]
+    fn theme_is_reported_per_pane_and_app_colours_ride_the_pane() {
+        let mut agg = StateAggregator::new();
+        seed_window(&mut agg, "@0", WindowType::Tab, true);
+        seed_pane(&mut agg, "%1", "@0");
+        let theme = crate::control_mode::TerminalColors::parse("bg=#1e1e1e,1=#cc6666").unwrap();
+        assert!(agg.set_terminal_theme(theme.clone()));
+        assert!(!agg.set_terminal_theme(theme));
+        assert_eq!(
+            agg.collect_theme_report_commands(),
+            [r#"refresh-client -r "%1:\e]11;rgb:1e1e/1e1e/1e1e\e\\""#]
+        );
+        assert!(agg.collect_theme_report_commands().is_empty());
+
+        let step = agg.step_at(output("%1", b"\x1b]10;#c5c8c6\x07x"), Instant::now());
+        assert!(!step
+            .effects
+            .iter()
+            .any(|e| matches!(e, SideEffect::SendTmuxCommand(_))));
+        let state = agg.to_tmux_state();
+        assert_eq!(state.panes[0].colors.foreground.as_deref(), Some("#c5c8c6"));
+        assert_eq!(state.theme.palette["1"], "#cc6666");
+
+        // A pane appearing later is reported on the step that creates it.
+        seed_pane(&mut agg, "%2", "@0");
+        let step = agg.step_at(output("%2", b"y"), Instant::now());
+        assert!(step
+            .effects
+            .iter()
+            .any(|e| matches!(e, SideEffect::SendTmuxCommand(cmd) if cmd.contains("\"%2:"))));
+    }
+
+    #[test]
+    fn pointer_shape_rides_the_pane_delta() {
+        let mut agg = StateAggregator::new();
+        // A change to most panes goes out as a full state instead.
+        for pane in ["%1", "%2", "%3"] {
+            seed_pane(&mut agg, pane, "@0");
+        }
+        agg.windows.insert("@0".to_string(), WindowState::new("@0"));
+        agg.set_status_line(String::new());
+        agg.to_state_update();
+
+        agg.step(output("%1", b"\x1b]22;hand2\x07"));
+        match agg.to_state_update() {
+            Some(crate::StateUpdate::Delta { delta }) => {
+                let panes = delta.panes.expect("pane delta present");
+                let pane = panes["%1"].as_ref().expect("modified, not removed");
+                assert_eq!(pane.pointer_shape.as_deref(), Some("pointer"));
+            }
+            other => panic!("expected Delta, got {other:?}"),
+        }
+        assert_eq!(
+            agg.panes
+                .get_mut("%1")
+                .unwrap()
+                .build_tmux_pane()
+                .pointer_shape,
+            "pointer"
+        );
+    }
+
+    #[test]
+    fn pane_details_ride_the_pane_delta() {
+        let mut agg = StateAggregator::new();
+        for pane in ["%1", "%2", "%3"] {
+            seed_pane(&mut agg, pane, "@0");
+        }
+        agg.windows.insert("@0".to_string(), WindowState::new("@0"));
+        agg.set_status_line(String::new());
+        agg.to_state_update();
+
+        // The details lines aren't pane records: no panes removed or added.
+        agg.step(ControlModeEvent::CommandResponse {
+            timestamp: 0,
+            command_num: 0,
+            output: "=tmuxy-details %1,4121,/srv/app\n=tmuxy-details %9,1,/".to_string(),
+            success: true,
+        });
+        assert_eq!(agg.panes.len(), 3);
+        match agg.to_state_update() {
+            Some(crate::StateUpdate::Delta { delta }) => {
+                let panes = delta.panes.expect("pane delta present");
+                let pane = panes["%1"].as_ref().expect("modified, not removed");
+                assert_eq!(pane.cwd, Some(Some("/srv/app".to_string())));
+                assert_eq!(pane.pid, Some(Some(4121)));
+                assert!(!panes.contains_key("%2"));
+            }
+            other => panic!("expected Delta, got {other:?}"),
+        }
+
+        agg.clear_pane_details();
+        let pane = agg.panes.get_mut("%1").unwrap().build_tmux_pane();
+        assert_eq!((pane.cwd, pane.pid), (None, None));
+    }
+
+    #[test]
+    fn injected_secret_is_masked_until_it_leaves_the_screen() {
+        fn screen(agg: &mut StateAggregator) -> String {
+            let state = agg.to_tmux_state();
+            state.panes[0]
+                .content
+                .iter()
+                .map(|line| line.iter().map(|c| c.char.as_str()).collect::<String>())
+                .collect::<Vec<_>>()
+                .join("\n")
+        }
+
+        let mut agg = StateAggregator::new();
+        seed_window(&mut agg, "@0", WindowType::Tab, true);
+        seed_pane(&mut agg, "%1", "@0");
+        let now = Instant::now();
+        assert!(!agg.mask_secret("%9", "hunter2", now));
+        assert!(agg.mask_secret("%1", "hunter2", now));
+
+        agg.step_at(output("%1", b"token: hunter2\r\n"), now);
+        let text = screen(&mut agg);
+        assert!(text.contains("token: *******"), "{text:?}");
+        assert!(!text.contains("hunter2"));
+
+        // Cleared off the screen, the mask is gone: a later echo is the
+        // user's own typing and shows.
+        agg.step_at(output("%1", b"\x1b[2J\x1b[H"), now);
+        screen(&mut agg);
+        agg.step_at(output("%1", b"hunter2"), now);
+        assert!(screen(&mut agg).contains("hunter2"));
+
+        // Never echoed, the mask expires.
+        assert!(agg.mask_secret("%1", "s3cret", now));
+        agg.step_at(output("%1", b"\r\nPassword: "), now + SECRET_MASK_TTL);
+        agg.step_at(output("%1", b"s3cret"), now + SECRET_MASK_TTL);
+        assert!(screen(&mut agg).contains("s3cret"));
+
+        // An echo that stays on screen is masked only up to the cap.
+        assert!(agg.mask_secret("%1", "pa55", now));
+        agg.step_at(output("%1", b"\r\npa55"), now);
+        assert!(!screen(&mut agg).contains("pa55"));
+        agg.step_at(output("%1", b"!"), now + SECRET_MASK_MAX_AGE);
+        assert!(screen(&mut agg).contains("pa55!"));
+    }
+
+    #[test]
+    fn captured_content_is_masked_like_the_screen() {
+        let mut agg = StateAggregator::new();
+        seed_window(&mut agg, "@0", WindowType::Tab, true);
+        seed_pane(&mut agg, "%1", "@0");
+        let now = Instant::now();
+        assert!(agg.mask_secret("%1", "hunter2", now));
+        agg.step_at(output("%1", b"token: hunter2\r\n"), now);
+
+        let text = |content: &PaneContent| {
+            content
+                .iter()
+                .map(|line| line.iter().map(|c| c.char.as_str()).collect::<String>())
+                .collect::<Vec<_>>()
+                .join("\n")
+        };
+        let mut captured = crate::parse_scrollback_to_cells("$ echo\ntoken: hunter2\n", 80);
+        agg.mask_captured("%1", &mut captured);
+        assert!(
+            text(&captured).contains("token: *******"),
+            "{:?}",
+            text(&captured)
+        );
+
+        // Another pane's capture is not its business.
+        let mut other = crate::parse_scrollback_to_cells("hunter2\n", 80);
+        agg.mask_captured("%2", &mut other);
+        assert!(text(&other).contains("hunter2"));
+    }
 }
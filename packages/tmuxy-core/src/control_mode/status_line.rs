@@ -0,0 +1,950 @@
+//! The tmux status line, read through the control-mode connection.
+//!
+//! A refresh is one `display-message` ([`StatusLine::refresh_command`])
+//! whose reply carries everything the bar needs: the length limits,
+//! `status-interval`, how many rows the bar has (`status` is `off`, `on` or
+//! 2 to 5) and where (`status-position`), the raw `status-left` /
+//! `status-right` formats, the expanded left side and right side, the rows
+//! below the first (`status-format[1]` and on), and one line per window with
+//! its id, whether it is current, and its expanded status format.
+//!
+//! Besides the ANSI text, the reply becomes [`StatusSegments`]: the left
+//! side, each window's entry and the right side as styled cells, so the
+//! frontend can lay the bar out and map a click on a window tab straight to
+//! its window.
+//!
+//! A one-off `display-message` leaves `#()` jobs blank, so the runtime runs
+//! them itself (`SideEffect::RunStatusJobs`, through `run-shell` so they run
+//! where tmux would) and the next refresh inlines their cached output into
+//! the formats. Output has its `#` escaped: it may carry `#[...]` styles, as
+//! in tmux, but cannot expand formats or start jobs of its own. Like tmux, a
+//! job runs again once its output is `status-interval` seconds old. Jobs in
+//! the extra rows' formats are not run and stay blank.
+
+use crate::{CellColor, CellStyle, TerminalCell, TerminalLine};
+use std::collections::HashMap;
+use std::time::Duration;
+#[cfg(not(target_arch = "wasm32"))]
+use std::time::Instant;
+pub use tmuxy_protocol::{StatusPosition, StatusSegments, StatusWindow};
+use unicode_width::UnicodeWidthChar;
+#[cfg(target_arch = "wasm32")]
+use web_time::Instant;
+
+/// First line of a status reply, which is how the aggregator routes it.
+pub const STATUS_MARKER: &str = "TMUXY_STATUS";
+
+/// How long a refresh may go unanswered before another one is sent. A
+/// failed `display-message` has no marker to route its error by.
+const REPLY_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// The most rows tmux's `status` option allows.
+const MAX_STATUS_ROWS: usize = 5;
+
+/// The window list, one line per window: its id, `1` for the current window
+/// (`0` otherwise) and its status format.
+const WINDOWS_FORMAT: &str = "#{W:#{window_id} 0 #{T:window-status-format}\n,#{window_id} 1 #{T:window-status-current-format}\n}";
+
+/// The `display-message` format of a refresh. `left` and `right` are
+/// formats of their own: the options themselves, or the raw formats with
+/// job output inlined.
+fn format(left: &str, right: &str) -> String {
+    let rows = (1..MAX_STATUS_ROWS).map(|row| format!("#{{T:status-format[{row}]}}"));
+    [
+        STATUS_MARKER,
+        "#{status-left-length}",
+        "#{status-right-length}",
+        "#{status-interval}",
+        "#{status}",
+        "#{status-position}",
+        "#{status-left}",
+        "#{status-right}",
+        left,
+        right,
+    ]
+    .into_iter()
+    .map(str::to_string)
+    .chain(rows)
+    .chain([WINDOWS_FORMAT.to_string()])
+    .collect::<Vec<_>>()
+    .join("\n")
+}
+
+/// The format for a one-off read, `#()` jobs left blank.
+pub fn snapshot_format() -> String {
+    format("#{T:status-left}", "#{T:status-right}")
+}
+
+/// `s` as the inside of a double-quoted tmux command argument.
+pub(super) fn quote_escape(s: &str) -> String {
+    let mut out = String::with_capacity(s.len());
+    for c in s.chars() {
+        match c {
+            '\\' | '"' | '$' => {
+                out.push('\\');
+                out.push(c);
+            }
+            '\n' => out.push_str("\\n"),
+            '\r' => {}
+            _ => out.push(c),
+        }
+    }
+    out
+}
+
+/// A window list line of a reply.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct WindowEntry {
+    id: String,
+    active: bool,
+    text: String,
+}
+
+/// A status reply, parsed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StatusReply {
+    left_length: usize,
+    right_length: usize,
+    interval: u64,
+    /// Rows the bar takes, 0 with `status off`
+    rows: usize,
+    position: StatusPosition,
+    raw_left: String,
+    raw_right: String,
+    left: String,
+    right: String,
+    /// The rows below the first, as many as `rows` asks for
+    extra: Vec<String>,
+    windows: Vec<WindowEntry>,
+}
+
+impl StatusReply {
+    /// Parse a `display-message` reply; `None` unless it is one.
+    pub fn parse(output: &str) -> Option<Self> {
+        let mut lines = output.lines();
+        if lines.next()?.trim_end() != STATUS_MARKER {
+            return None;
+        }
+        let mut next = || lines.next().unwrap_or("").to_string();
+        let left_length = next().trim().parse().unwrap_or(30);
+        let right_length = next().trim().parse().unwrap_or(50);
+        let interval = next().trim().parse().unwrap_or(15);
+        let rows = match next().trim() {
+            "off" => 0,
+            "on" => 1,
+            rows => rows.parse().unwrap_or(1).min(MAX_STATUS_ROWS),
+        };
+        let position = match next().trim() {
+            "top" => StatusPosition::Top,
+            _ => StatusPosition::Bottom,
+        };
+        let raw_left = next();
+        let raw_right = next();
+        let left = next();
+        let right = next();
+        let mut extra: Vec<String> = (1..MAX_STATUS_ROWS).map(|_| next()).collect();
+        extra.truncate(rows.saturating_sub(1));
+        let windows = lines
+            .filter_map(|line| {
+                let (id, rest) = line.split_once(' ')?;
+                let (active, text) = rest.split_once(' ').unwrap_or((rest, ""));
+                Some(WindowEntry {
+                    id: id.to_string(),
+                    active: active == "1",
+                    text: text.to_string(),
+                })
+            })
+            .collect();
+        Some(Self {
+            left_length,
+            right_length,
+            interval,
+            rows,
+            position,
+            raw_left,
+            raw_right,
+            left,
+            right,
+            extra,
+            windows,
+        })
+    }
+
+    /// Where the bar goes.
+    pub fn position(&self) -> StatusPosition {
+        self.position
+    }
+
+    /// The rows below the first as styled cells, at most `width` columns
+    /// each. Alignment (`#[align=...]`) is not applied.
+    pub fn extra_lines(&self, width: usize) -> Vec<TerminalLine> {
+        self.extra
+            .iter()
+            .map(|row| clip(styled_cells(row), width))
+            .collect()
+    }
+
+    /// The bar as styled cells; empty with `status off`.
+    pub fn segments(&self) -> StatusSegments {
+        if self.rows == 0 {
+            return StatusSegments::default();
+        }
+        StatusSegments {
+            left: clip(styled_cells(&self.left), self.left_length),
+            windows: self
+                .windows
+                .iter()
+                .map(|w| StatusWindow {
+                    window_id: w.id.clone(),
+                    active: w.active,
+                    cells: styled_cells(&w.text),
+                })
+                .collect(),
+            right: clip(styled_cells(&self.right), self.right_length),
+        }
+    }
+
+    /// The bar as ANSI text `width` columns wide: left side and windows, then
+    /// the right side pushed to the edge (at least one space between).
+    /// Empty with `status off`.
+    pub fn render(&self, width: usize) -> String {
+        if self.rows == 0 {
+            return String::new();
+        }
+        let left = convert_tmux_style_to_ansi(&self.left);
+        let windows = self
+            .windows
+            .iter()
+            .map(|w| convert_tmux_style_to_ansi(&w.text))
+            .collect::<Vec<_>>()
+            .join(" ");
+        let right = convert_tmux_style_to_ansi(&self.right);
+
+        let left_len = visible_len(&left).min(self.left_length);
+        let right_len = visible_len(&right).min(self.right_length);
+        let left_windows_len = left_len + visible_len(&windows);
+        let padding = if left_windows_len + right_len < width {
+            width - left_windows_len - right_len
+        } else {
+            1
+        };
+
+        format!(
+            "{}{}{}{}",
+            truncate_ansi(&left, self.left_length),
+            windows,
+            " ".repeat(padding),
+            truncate_ansi(&right, self.right_length)
+        )
+    }
+}
+
+/// The `#(...)` commands in a format, in order.
+fn jobs(format: &str) -> Vec<String> {
+    let mut found = Vec::new();
+    substitute_jobs(format, |cmd| {
+        if !found.iter().any(|c| c == cmd) {
+            found.push(cmd.to_string());
+        }
+        String::new()
+    });
+    found
+}
+
+/// `format` with every `#(...)` replaced by `output(command)`.
+fn substitute_jobs(format: &str, mut output: impl FnMut(&str) -> String) -> String {
+    let mut result = String::new();
+    let mut chars = format.chars().peekable();
+    while let Some(c) = chars.next() {
+        if c == '#' && chars.peek() == Some(&'#') {
+            // `##(` is a literal `#(`, not a job.
+            chars.next();
+            result.push_str("##");
+        } else if c == '#' && chars.peek() == Some(&'(') {
+            chars.next();
+            let mut cmd = String::new();
+            let mut depth = 1;
+            for ch in chars.by_ref() {
+                match ch {
+                    '(' => depth += 1,
+                    ')' => {
+                        depth -= 1;
+                        if depth == 0 {
+                            break;
+                        }
+                    }
+                    _ => {}
+                }
+                cmd.push(ch);
+            }
+            result.push_str(&output(&cmd));
+        } else {
+            result.push(c);
+        }
+    }
+    result
+}
+
+/// Job output made safe to inline into a format: the first line, with every
+/// `#` but those opening a `#[...]` style doubled.
+fn escape_output(output: &str) -> String {
+    let line = output.lines().next().unwrap_or("");
+    let mut escaped = String::with_capacity(line.len());
+    let mut chars = line.chars().peekable();
+    while let Some(c) = chars.next() {
+        escaped.push(c);
+        if c == '#' && chars.peek() != Some(&'[') {
+            escaped.push('#');
+        }
+    }
+    escaped
+}
+
+#[derive(Debug, Default)]
+struct Job {
+    output: String,
+    ran_at: Option<Instant>,
+    running: bool,
+}
+
+/// The status line's state: the last reply, the `#()` job cache and the
+/// refresh in flight.
+#[derive(Debug, Default)]
+pub struct StatusLine {
+    reply: Option<StatusReply>,
+    dirty: bool,
+    /// When the refresh in flight was sent, with the raw formats it inlined
+    /// (`None` for a side read through its option).
+    in_flight: Option<(Instant, Option<String>, Option<String>)>,
+    jobs: HashMap<String, Job>,
+    /// Text a host that reads the status line itself has set.
+    fixed: Option<String>,
+    rendered: Option<(usize, String)>,
+    segments: Option<StatusSegments>,
+}
+
+impl StatusLine {
+    /// Ask for a refresh with the next [`Self::refresh_command`].
+    pub fn mark_dirty(&mut self) {
+        self.dirty = true;
+    }
+
+    /// Use `text` as the status line from now on instead of reading it.
+    pub fn set_fixed(&mut self, text: String) {
+        self.fixed = Some(text);
+        self.dirty = false;
+    }
+
+    /// The command refreshing the status line, when one is due and none is
+    /// in flight.
+    pub fn refresh_command(&mut self, session: &str, now: Instant) -> Option<String> {
+        if !self.dirty || self.fixed.is_some() {
+            return None;
+        }
+        if let Some((sent, ..)) = &self.in_flight {
+            if now.duration_since(*sent) < REPLY_TIMEOUT {
+                return None;
+            }
+        }
+        self.dirty = false;
+        let (left, raw_left) = self.side("#{T:status-left}", |r| &r.raw_left);
+        let (right, raw_right) = self.side("#{T:status-right}", |r| &r.raw_right);
+        self.in_flight = Some((now, raw_left, raw_right));
+        Some(format!(
+            "display-message -p -t \"{}\" \"{}\"",
+            quote_escape(session),
+            quote_escape(&format(&left, &right))
+        ))
+    }
+
+    /// One side's format for a refresh: the option itself, or, when its last
+    /// raw format runs jobs, that format with their output inlined.
+    fn side(
+        &self,
+        option: &str,
+        raw: impl Fn(&StatusReply) -> &String,
+    ) -> (String, Option<String>) {
+        match self.reply.as_ref().map(raw) {
+            Some(raw) if raw.contains("#(") => {
+                let inlined = substitute_jobs(raw, |cmd| {
+                    self.jobs
+                        .get(cmd)
+                        .map(|job| escape_output(&job.output))
+                        .unwrap_or_default()
+                });
+                (inlined, Some(raw.clone()))
+            }
+            _ => (option.to_string(), None),
+        }
+    }
+
+    /// Take a reply in. Returns whether the status line changed.
+    pub fn on_reply(&mut self, reply: StatusReply) -> bool {
+        if let Some((_, left, right)) = self.in_flight.take() {
+            let with_jobs = |raw: &String| raw.contains("#(").then(|| raw.clone());
+            if (left, right) != (with_jobs(&reply.raw_left), with_jobs(&reply.raw_right)) {
+                // Read with stale formats (or before the jobs were known):
+                // read again with the current ones.
+                self.dirty = true;
+            }
+        }
+        let commands: Vec<String> = jobs(&reply.raw_left)
+            .into_iter()
+            .chain(jobs(&reply.raw_right))
+            .collect();
+        self.jobs.retain(|cmd, _| commands.contains(cmd));
+        for cmd in commands {
+            self.jobs.entry(cmd).or_default();
+        }
+        if self.reply.as_ref() == Some(&reply) {
+            return false;
+        }
+        self.reply = Some(reply);
+        self.rendered = None;
+        self.segments = None;
+        true
+    }
+
+    /// Jobs to run now: never run, or older than `status-interval` (never
+    /// again with an interval of 0). They count as running until
+    /// [`Self::job_done`].
+    pub fn due_jobs(&mut self, now: Instant) -> Vec<String> {
+        let interval = self.interval();
+        let mut due: Vec<String> = self
+            .jobs
+            .iter_mut()
+            .filter(|(_, job)| {
+                !job.running
+                    && match (job.ran_at, interval) {
+                        (None, _) => true,
+                        (Some(ran), Some(interval)) => now.duration_since(ran) >= interval,
+                        (Some(_), None) => false,
+                    }
+            })
+            .map(|(cmd, job)| {
+                job.running = true;
+                cmd.clone()
+            })
+            .collect();
+        due.sort();
+        due
+    }
+
+    /// Record a job's output. Returns whether it changed, in which case a
+    /// refresh is due.
+    pub fn job_done(&mut self, command: &str, output: &str, now: Instant) -> bool {
+        let Some(job) = self.jobs.get_mut(command) else {
+            return false;
+        };
+        job.running = false;
+        job.ran_at = Some(now);
+        if job.output == output {
+            return false;
+        }
+        job.output = output.to_string();
+        self.dirty = true;
+        true
+    }
+
+    /// `status-interval`, unless it is 0 (no periodic refresh).
+    pub fn interval(&self) -> Option<Duration> {
+        self.reply
+            .as_ref()
+            .filter(|r| r.interval > 0)
+            .map(|r| Duration::from_secs(r.interval))
+    }
+
+    /// The status line `width` columns wide.
+    pub fn render(&mut self, width: usize) -> String {
+        if let Some(fixed) = &self.fixed {
+            return fixed.clone();
+        }
+        let Some(reply) = &self.reply else {
+            return String::new();
+        };
+        match &self.rendered {
+            Some((w, text)) if *w == width => text.clone(),
+            _ => {
+                let text = reply.render(width);
+                self.rendered = Some((width, text.clone()));
+                text
+            }
+        }
+    }
+
+    /// The status line as segments; empty before the first reply, or when a
+    /// host has set its text.
+    pub fn segments(&mut self) -> StatusSegments {
+        if self.fixed.is_some() {
+            return StatusSegments::default();
+        }
+        let Some(reply) = &self.reply else {
+            return StatusSegments::default();
+        };
+        self.segments
+            .get_or_insert_with(|| reply.segments())
+            .clone()
+    }
+
+    /// Where the bar goes, as of the last reply.
+    pub fn position(&self) -> StatusPosition {
+        self.reply
+            .as_ref()
+            .map(StatusReply::position)
+            .unwrap_or_default()
+    }
+
+    /// The rows below the first (`status 2` and up), `width` columns at most.
+    pub fn extra_lines(&self, width: usize) -> Vec<TerminalLine> {
+        match &self.reply {
+            Some(reply) if self.fixed.is_none() => reply.extra_lines(width),
+            _ => Vec::new(),
+        }
+    }
+}
+
+/// `text`, with `#[...]` styles and `##` escapes, as cells.
+fn styled_cells(text: &str) -> TerminalLine {
+    let mut cells: TerminalLine = Vec::new();
+    let mut style = CellStyle::default();
+    let mut chars = text.chars().peekable();
+    while let Some(c) = chars.next() {
+        if c == '#' {
+            match chars.peek() {
+                Some('[') => {
+                    chars.next();
+                    let spec: String = chars.by_ref().take_while(|&ch| ch != ']').collect();
+                    apply_style(&mut style, &spec);
+                    continue;
+                }
+                Some('#') => {
+                    chars.next();
+                }
+                _ => {}
+            }
+        }
+        match c.width().unwrap_or(0) {
+            0 => {
+                // Combining marks join the character before them.
+                if let Some(last) = cells.last_mut().filter(|_| !c.is_control()) {
+                    last.char.push(c);
+                }
+            }
+            2 => {
+                cells.push(TerminalCell::with_style(c.to_string(), style.clone()).with_width(2));
+                cells.push(TerminalCell::with_style(" ".to_string(), style.clone()).with_width(0));
+            }
+            _ => cells.push(TerminalCell::with_style(c.to_string(), style.clone())),
+        }
+    }
+    cells
+}
+
+/// `line` cut to `max` columns, never through a wide character.
+fn clip(mut line: TerminalLine, max: usize) -> TerminalLine {
+    let mut columns = 0;
+    let keep = line
+        .iter()
+        .take_while(|cell| {
+            columns += cell.width as usize;
+            columns <= max
+        })
+        .count();
+    line.truncate(keep);
+    line
+}
+
+/// Apply one `#[...]` style specification to `style`.
+fn apply_style(style: &mut CellStyle, spec: &str) {
+    for part in spec.split(',').map(str::trim) {
+        match part {
+            "default" => *style = CellStyle::default(),
+            "none" => {
+                *style = CellStyle {
+                    fg: style.fg.take(),
+                    bg: style.bg.take(),
+                    ..Default::default()
+                }
+            }
+            "bold" | "bright" => style.bold = true,
+            "dim" => style.dim = true,
+            "italics" | "italic" => style.italic = true,
+            "underscore" | "underline" => style.underline = true,
+            "blink" => style.blink = true,
+            "reverse" => style.inverse = true,
+            "strikethrough" => style.strikethrough = true,
+            "nobold" | "nobright" => style.bold = false,
+            "nodim" => style.dim = false,
+            "noitalics" | "noitalic" => style.italic = false,
+            "nounderscore" | "nounderline" => style.underline = false,
+            "noblink" => style.blink = false,
+            "noreverse" => style.inverse = false,
+            "nostrikethrough" => style.strikethrough = false,
+            _ => {
+                if let Some(color) = part.strip_prefix("fg=") {
+                    style.fg = cell_color(color);
+                } else if let Some(color) = part.strip_prefix("bg=") {
+                    style.bg = cell_color(color);
+                } else if let Some(color) = part.strip_prefix("us=") {
+                    style.underline_color = cell_color(color);
+                }
+                // `align=`, `range=`, `list=` and the like only matter to
+                // tmux's own layout.
+            }
+        }
+    }
+}
+
+/// A tmux color (`red`, `brightblue`, `colour33`, `#1e1e2e`); `None` for
+/// `default` and anything unrecognised.
+fn cell_color(color: &str) -> Option<CellColor> {
+    if let Some(hex) = color.strip_prefix('#') {
+        if hex.len() != 6 {
+            return None;
+        }
+        let channel = |i: usize| u8::from_str_radix(hex.get(i..i + 2)?, 16).ok();
+        return Some(CellColor::Rgb {
+            r: channel(0)?,
+            g: channel(2)?,
+            b: channel(4)?,
+        });
+    }
+    let color = color.to_lowercase();
+    let index = color
+        .strip_prefix("colour")
+        .or_else(|| color.strip_prefix("color"))
+        .unwrap_or(&color);
+    if let Ok(idx) = index.parse::<u8>() {
+        return Some(CellColor::Indexed(idx));
+    }
+    let named = [
+        "black", "red", "green", "yellow", "blue", "magenta", "cyan", "white",
+    ];
+    let (bright, name) = match color.strip_prefix("bright") {
+        Some(name) => (8, name),
+        None => (0, color.as_str()),
+    };
+    named
+        .iter()
+        .position(|n| *n == name)
+        .map(|i| CellColor::Indexed(i as u8 + bright))
+}
+
+/// Calculate visible length of a string (strips ANSI escape codes)
+fn visible_len(s: &str) -> usize {
+    let mut len = 0;
+    let mut in_escape = false;
+    for c in s.chars() {
+        if in_escape {
+            if c == 'm' {
+                in_escape = false;
+            }
+        } else if c == '\x1b' {
+            in_escape = true;
+        } else {
+            len += 1;
+        }
+    }
+    len
+}
+
+/// Truncate a string with ANSI codes to a maximum visible length
+fn truncate_ansi(s: &str, max_visible: usize) -> String {
+    let mut result = String::new();
+    let mut visible_count = 0;
+    let mut in_escape = false;
+
+    for c in s.chars() {
+        if in_escape {
+            result.push(c);
+            if c == 'm' {
+                in_escape = false;
+            }
+        } else if c == '\x1b' {
+            in_escape = true;
+            result.push(c);
+        } else {
+            if visible_count >= max_visible {
+                break;
+            }
+            result.push(c);
+            visible_count += 1;
+        }
+    }
+
+    result
+}
+
+/// Convert tmux style codes like #[fg=#89b4fa,bold] to ANSI escape codes.
+/// Also unescapes ## → # (tmux's escape for literal # in format output).
+fn convert_tmux_style_to_ansi(input: &str) -> String {
+    let mut result = String::new();
+    let mut chars = input.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        if c == '#' {
+            match chars.peek() {
+                Some(&'[') => {
+                    // Parse tmux style code #[...]
+                    chars.next(); // consume '['
+                    let mut style = String::new();
+                    while let Some(&ch) = chars.peek() {
+                        if ch == ']' {
+                            chars.next();
+                            break;
+                        }
+                        // peek() returned Some, so next() is guaranteed Some.
+                        if let Some(c) = chars.next() {
+                            style.push(c);
+                        }
+                    }
+                    let ansi = tmux_style_to_ansi(&style);
+                    result.push_str(&ansi);
+                }
+                Some(&'#') => {
+                    // ## is tmux's escape for a literal #
+                    chars.next(); // consume second '#'
+                    result.push('#');
+                }
+                _ => {
+                    result.push(c);
+                }
+            }
+        } else {
+            result.push(c);
+        }
+    }
+
+    result
+}
+
+/// Convert a single tmux style specification to ANSI escape sequence
+fn tmux_style_to_ansi(style: &str) -> String {
+    if style.is_empty() || style == "default" {
+        return "\x1b[0m".to_string();
+    }
+
+    let mut codes = Vec::new();
+
+    for part in style.split(',') {
+        let part = part.trim();
+
+        if part == "bold" {
+            codes.push("1".to_string());
+        } else if part == "dim" {
+            codes.push("2".to_string());
+        } else if part == "italic" {
+            codes.push("3".to_string());
+        } else if part == "underscore" || part == "underline" {
+            codes.push("4".to_string());
+        } else if part == "blink" {
+            codes.push("5".to_string());
+        } else if part == "reverse" {
+            codes.push("7".to_string());
+        } else if part == "hidden" {
+            codes.push("8".to_string());
+        } else if part == "strikethrough" {
+            codes.push("9".to_string());
+        } else if part == "nobold" || part == "nodim" {
+            codes.push("22".to_string());
+        } else if part == "noitalic" {
+            codes.push("23".to_string());
+        } else if part == "nounderscore" || part == "nounderline" {
+            codes.push("24".to_string());
+        } else if part == "noblink" {
+            codes.push("25".to_string());
+        } else if part == "noreverse" {
+            codes.push("27".to_string());
+        } else if part == "nohidden" {
+            codes.push("28".to_string());
+        } else if part == "nostrikethrough" {
+            codes.push("29".to_string());
+        } else if let Some(color) = part.strip_prefix("fg=") {
+            if let Some(ansi) = color_to_ansi(color, true) {
+                codes.push(ansi);
+            }
+        } else if let Some(color) = part.strip_prefix("bg=") {
+            if let Some(ansi) = color_to_ansi(color, false) {
+                codes.push(ansi);
+            }
+        }
+    }
+
+    if codes.is_empty() {
+        String::new()
+    } else {
+        format!("\x1b[{}m", codes.join(";"))
+    }
+}
+
+/// Convert a tmux color specification to ANSI code
+fn color_to_ansi(color: &str, is_fg: bool) -> Option<String> {
+    let base = if is_fg { 38 } else { 48 };
+    if color == "default" {
+        return Some(format!("{}", base + 1));
+    }
+    Some(match cell_color(color)? {
+        CellColor::Indexed(idx) => format!("{};5;{}", base, idx),
+        CellColor::Rgb { r, g, b } => format!("{};2;{};{};{}", base, r, g, b),
+    })
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used, clippy::expect_used)]
+mod tests {
+    use super::*;
+
+    fn reply(raw_right: &str, right: &str) -> String {
+        format!("{STATUS_MARKER}\n10\n20\n5\non\nbottom\n#S\n{raw_right}\n#[fg=red][s]\n{right}\n\n\n\n\n@0 0 0:a\n@1 1 1:b*\n")
+    }
+
+    #[test]
+    fn renders_sides_apart_and_clips_them() {
+        let parsed = StatusReply::parse(&reply("%H", "12:00")).unwrap();
+        assert_eq!(parsed.render(20), "\x1b[38;5;1m[s]0:a 1:b*    12:00");
+        // Too narrow: one space between, the right side still whole.
+        assert_eq!(parsed.render(5), "\x1b[38;5;1m[s]0:a 1:b* 12:00");
+        let long = StatusReply::parse(&reply("", &"x".repeat(30))).unwrap();
+        assert!(long.render(80).ends_with(&"x".repeat(20)));
+        assert!(StatusReply::parse("other\n").is_none());
+    }
+
+    #[test]
+    fn segments_carry_styles_and_window_ids() {
+        let parsed = StatusReply::parse(&format!(
+            "{STATUS_MARKER}\n4\n50\n5\non\nbottom\n\n\n#[fg=colour33,bold]main##1\n#[bg=#1e1e2e]日x \n\n\n\n\n@2 0 #[fg=brightred]2:vim\n@5 1 5:zsh*\n"
+        ))
+        .unwrap();
+        let segments = parsed.segments();
+        let text = |line: &TerminalLine| line.iter().map(|c| c.char.as_str()).collect::<String>();
+        // Clipped to status-left-length, `##` unescaped to `#`.
+        assert_eq!(text(&segments.left), "main");
+        let bold_blue = CellStyle {
+            fg: Some(CellColor::Indexed(33)),
+            bold: true,
+            ..Default::default()
+        };
+        assert_eq!(segments.left[0].style.as_ref(), Some(&bold_blue));
+        // A wide character takes its continuation cell along.
+        assert_eq!(
+            segments.right.iter().map(|c| c.width).collect::<Vec<_>>(),
+            [2, 0, 1, 1]
+        );
+        assert_eq!(
+            segments.right[0].style.as_ref().and_then(|s| s.bg.clone()),
+            Some(CellColor::Rgb {
+                r: 0x1e,
+                g: 0x1e,
+                b: 0x2e
+            })
+        );
+        let windows: Vec<_> = segments
+            .windows
+            .iter()
+            .map(|w| (w.window_id.as_str(), w.active, text(&w.cells)))
+            .collect();
+        assert_eq!(
+            windows,
+            [
+                ("@2", false, "2:vim".to_string()),
+                ("@5", true, "5:zsh*".to_string())
+            ]
+        );
+        assert_eq!(
+            segments.windows[0].cells[0]
+                .style
+                .as_ref()
+                .and_then(|s| s.fg.clone()),
+            Some(CellColor::Indexed(9))
+        );
+        assert_eq!(clip(styled_cells("日日"), 3).len(), 2);
+    }
+
+    #[test]
+    fn extra_rows_follow_the_status_option() {
+        let reply = |status: &str, position: &str| {
+            format!(
+                "{STATUS_MARKER}\n10\n20\n5\n{status}\n{position}\n\n\n[s]\nR\n#[bold]row two\nrow three\n\n\n@0 1 0:a\n"
+            )
+        };
+        let two = StatusReply::parse(&reply("2", "top")).unwrap();
+        assert_eq!(two.position(), StatusPosition::Top);
+        let lines = two.extra_lines(5);
+        assert_eq!(lines.len(), 1);
+        assert_eq!(
+            lines[0].iter().map(|c| c.char.as_str()).collect::<String>(),
+            "row t"
+        );
+        assert!(lines[0][0].style.as_ref().is_some_and(|s| s.bold));
+        assert_eq!(two.segments().windows.len(), 1);
+
+        let three = StatusReply::parse(&reply("3", "bottom")).unwrap();
+        assert_eq!(three.extra_lines(80).len(), 2);
+        assert_eq!(three.position(), StatusPosition::Bottom);
+
+        let off = StatusReply::parse(&reply("off", "bottom")).unwrap();
+        assert!(off.extra_lines(80).is_empty());
+        assert_eq!(off.render(80), "");
+        assert_eq!(off.segments(), StatusSegments::default());
+    }
+
+    #[test]
+    fn refresh_command_is_one_quoted_display_message() {
+        let mut status = StatusLine::default();
+        let now = Instant::now();
+        assert_eq!(status.refresh_command("main", now), None, "not dirty");
+        status.mark_dirty();
+        let cmd = status.refresh_command("my \"s\"", now).unwrap();
+        assert!(cmd.starts_with(
+            r#"display-message -p -t "my \"s\"" "TMUXY_STATUS\n#{status-left-length}\n"#
+        ));
+        assert!(cmd.contains(r#"\n#{T:status-right}\n#{T:status-format[1]}\n"#));
+        assert!(!cmd.contains('\n'));
+        // In flight: no second read until it lands or times out.
+        status.mark_dirty();
+        assert_eq!(status.refresh_command("main", now), None);
+        assert!(status
+            .refresh_command("main", now + REPLY_TIMEOUT)
+            .is_some());
+    }
+
+    #[test]
+    fn job_output_is_inlined_escaped() {
+        let mut status = StatusLine::default();
+        let now = Instant::now();
+        status.mark_dirty();
+        status.refresh_command("main", now);
+        let raw = r#"#(git branch --show-current) "$USER" #(echo (a))"#;
+        assert!(status.on_reply(StatusReply::parse(&reply(raw, "")).unwrap()));
+        assert_eq!(
+            status.due_jobs(now),
+            ["echo (a)", "git branch --show-current"]
+        );
+        assert!(status.due_jobs(now).is_empty(), "already running");
+
+        assert!(status.job_done(
+            "git branch --show-current",
+            "#[bold]main #{x} #(rm)\nmore",
+            now
+        ));
+        assert!(!status.job_done("unknown", "x", now));
+        let cmd = status.refresh_command("main", now).unwrap();
+        assert!(
+            cmd.contains(r#"\n#[bold]main ##{x} ##(rm) \"\$USER\" \n#{T:status-format[1]}"#),
+            "{cmd}"
+        );
+    }
+
+    #[test]
+    fn jobs_rerun_after_status_interval() {
+        let mut status = StatusLine::default();
+        let now = Instant::now();
+        status.on_reply(StatusReply::parse(&reply("#(date)", "")).unwrap());
+        assert_eq!(status.due_jobs(now), ["date"]);
+        status.job_done("date", "Mon", now);
+        assert!(status.due_jobs(now + Duration::from_secs(4)).is_empty());
+        assert_eq!(status.due_jobs(now + Duration::from_secs(5)), ["date"]);
+        // `##(` is literal text, not a job.
+        assert!(jobs("##(no) #(yes)").eq(&["yes"]));
+    }
+}
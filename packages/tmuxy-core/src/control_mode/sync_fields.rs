@@ -0,0 +1,85 @@
+//! Extra per-pane fields the periodic sync can fetch.
+//!
+//! `LIST_PANES_CMD` carries what every client needs on every sync, in a
+//! column layout the aggregator reads positionally. Fields only some
+//! deployments want — the working directory, the pane's process id — come
+//! from a second `list-panes` whose lines are tagged, so they can't be
+//! mistaken for pane records. Which fields, if any, is the monitor's
+//! `sync_fields` setting.
+
+pub use tmuxy_protocol::SyncField;
+
+/// Tag starting each line of the details `list-panes`.
+const DETAILS_TAG: &str = "=tmuxy-details ";
+
+/// The `list-panes` fetching `fields`, `None` when there are none. Every
+/// line has the same columns, empty for fields not asked for, with the
+/// working directory last since it may contain commas.
+pub fn details_command(fields: &[SyncField]) -> Option<String> {
+    if fields.is_empty() {
+        return None;
+    }
+    let column = |field, format| if fields.contains(&field) { format } else { "" };
+    Some(format!(
+        "list-panes -s -F '{DETAILS_TAG}#{{pane_id}},{},{}'",
+        column(SyncField::Pid, "#{pane_pid}"),
+        column(SyncField::Cwd, "#{pane_current_path}"),
+    ))
+}
+
+/// One pane's fetched fields; `None` for those not fetched.
+#[derive(Debug, Default, PartialEq)]
+pub(crate) struct PaneDetails {
+    pub pid: Option<u32>,
+    pub cwd: Option<String>,
+}
+
+/// Whether a command response came from [`details_command`].
+pub(crate) fn is_details_response(output: &str) -> bool {
+    output.starts_with(DETAILS_TAG)
+}
+
+/// Parse one line of a [`details_command`] response.
+pub(crate) fn parse_details_line(line: &str) -> Option<(&str, PaneDetails)> {
+    let mut columns = line.strip_prefix(DETAILS_TAG)?.splitn(3, ',');
+    let pane_id = columns.next().filter(|id| id.starts_with('%'))?;
+    let pid = columns.next()?.parse().ok();
+    let cwd = columns
+        .next()
+        .filter(|cwd| !cwd.is_empty())
+        .map(String::from);
+    Some((pane_id, PaneDetails { pid, cwd }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn command_leaves_unrequested_columns_empty() {
+        assert_eq!(details_command(&[]), None);
+        assert_eq!(
+            details_command(&[SyncField::Cwd]).as_deref(),
+            Some("list-panes -s -F '=tmuxy-details #{pane_id},,#{pane_current_path}'")
+        );
+    }
+
+    #[test]
+    fn parses_details_lines() {
+        assert_eq!(
+            parse_details_line("=tmuxy-details %3,4121,/home/me/a,b"),
+            Some((
+                "%3",
+                PaneDetails {
+                    pid: Some(4121),
+                    cwd: Some("/home/me/a,b".to_string()),
+                }
+            ))
+        );
+        assert_eq!(
+            parse_details_line("=tmuxy-details %3,,"),
+            Some(("%3", PaneDetails::default()))
+        );
+        assert_eq!(parse_details_line("%3,0,0,0"), None);
+    }
+}
@@ -0,0 +1,373 @@
+//! Terminal emulation backends behind `PaneState`.
+//!
+//! Every pane feeds its `%output` through an emulator and reads the rendered
+//! grid back out as [`PaneContent`]. The emulator used to be a hard-wired
+//! `vt100::Parser`; [`TerminalBackend`] is the narrow surface the aggregator
+//! actually needs, so a higher-fidelity emulator can stand in for it.
+//!
+//! - [`Vt100Backend`] — the default. Small, wasm-safe, and what the reflow and
+//!   capture paths were tuned against.
+//! - `AlacrittyBackend` — behind the `alacritty` feature. Covers sequences
+//!   vt100 drops (insert mode, DECALN, and the long tail of xterm private
+//!   modes), at the cost of a heavier dependency that does not build for
+//!   wasm32.
+//!
+//! `tests/terminal_backend_conformance.rs` feeds the same byte streams to both
+//! and compares the extracted grids.
+
+use super::osc::OscParser;
+use crate::PaneContent;
+use tracing::warn;
+
+/// The emulator operations `PaneState` relies on. Rows and columns are
+/// 0-indexed; `(rows, cols)` ordering follows vt100.
+pub trait TerminalBackend: Send {
+    /// Feed raw pane output through the emulator.
+    fn process(&mut self, data: &[u8]);
+
+    /// Reflow the grid in place to the new size.
+    fn set_size(&mut self, rows: u16, cols: u16);
+
+    /// Cursor position as `(row, col)`.
+    fn cursor_position(&self) -> (u16, u16);
+
+    /// Whether the application switched to the alternate screen.
+    fn alternate_screen(&self) -> bool;
+
+    /// Whether the application enabled any mouse reporting mode.
+    fn mouse_tracking(&self) -> bool;
+
+    /// Whether the application hid the cursor (DECTCEM).
+    fn hide_cursor(&self) -> bool;
+
+    /// The visible grid as structured cells. When `osc_parser` is given, its
+    /// OSC 8 hyperlinks are attached to the cells they cover.
+    fn cells(&self, osc_parser: Option<&OscParser>) -> PaneContent;
+
+    /// Up to `lines` of the most recent rows that scrolled off the top of the
+    /// main screen, oldest first. Takes `&mut self` because vt100 can only
+    /// read history through its scrollback viewport.
+    fn scrollback(&mut self, lines: usize) -> PaneContent;
+}
+
+/// Which emulator new panes are created with.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum TerminalBackendKind {
+    #[default]
+    Vt100,
+    #[cfg(feature = "alacritty")]
+    Alacritty,
+}
+
+impl TerminalBackendKind {
+    /// Parse a backend name. `alacritty` is only recognised when the
+    /// `alacritty` feature is compiled in.
+    pub fn parse(s: &str) -> Option<Self> {
+        match s {
+            "vt100" => Some(Self::Vt100),
+            #[cfg(feature = "alacritty")]
+            "alacritty" => Some(Self::Alacritty),
+            _ => None,
+        }
+    }
+
+    /// Create an emulator of this kind. Zero dimensions are clamped to 1
+    /// (vt100 panics on an empty grid).
+    pub fn create(self, rows: u16, cols: u16, scrollback: usize) -> Box<dyn TerminalBackend> {
+        let (rows, cols) = (rows.max(1), cols.max(1));
+        match self {
+            Self::Vt100 => Box::new(Vt100Backend::new(rows, cols, scrollback)),
+            #[cfg(feature = "alacritty")]
+            Self::Alacritty => Box::new(AlacrittyBackend::new(rows, cols, scrollback)),
+        }
+    }
+}
+
+/// The vendored `vt100` emulator.
+pub struct Vt100Backend {
+    parser: vt100::Parser,
+}
+
+impl Vt100Backend {
+    pub fn new(rows: u16, cols: u16, scrollback: usize) -> Self {
+        Self {
+            parser: vt100::Parser::new(rows, cols, scrollback),
+        }
+    }
+
+    pub fn screen(&self) -> &vt100::Screen {
+        self.parser.screen()
+    }
+}
+
+impl TerminalBackend for Vt100Backend {
+    /// Catches panics from internal vt100 bugs (e.g., subtract overflow in
+    /// grid.rs col_wrap).
+    fn process(&mut self, data: &[u8]) {
+        let parser = &mut self.parser;
+        // AssertUnwindSafe: after a panic the parser state may be
+        // inconsistent, but the caller will recreate it on the next
+        // capture-pane refresh.
+        let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+            parser.process(data);
+        }));
+        if result.is_err() {
+            warn!("vt100 caught panic during process(), terminal state may be stale");
+        }
+    }
+
+    fn set_size(&mut self, rows: u16, cols: u16) {
+        self.parser.screen_mut().set_size(rows.max(1), cols.max(1));
+    }
+
+    fn cursor_position(&self) -> (u16, u16) {
+        self.parser.screen().cursor_position()
+    }
+
+    fn alternate_screen(&self) -> bool {
+        self.parser.screen().alternate_screen()
+    }
+
+    fn mouse_tracking(&self) -> bool {
+        !matches!(
+            self.parser.screen().mouse_protocol_mode(),
+            vt100::MouseProtocolMode::None
+        )
+    }
+
+    fn hide_cursor(&self) -> bool {
+        self.parser.screen().hide_cursor()
+    }
+
+    fn cells(&self, osc_parser: Option<&OscParser>) -> PaneContent {
+        crate::extract_cells_with_urls(self.parser.screen(), osc_parser)
+    }
+
+    fn scrollback(&mut self, lines: usize) -> PaneContent {
+        let screen = self.parser.screen_mut();
+        let rows = usize::from(screen.size().0);
+        // The offset is clamped to the history held, so this reads its size.
+        screen.set_scrollback(usize::MAX);
+        let mut offset = lines.min(screen.scrollback());
+        let mut out = Vec::with_capacity(offset);
+        // At offset `k` the viewport's top `min(k, rows)` rows are history,
+        // starting `k` rows back; page forward until the live screen.
+        while offset > 0 {
+            screen.set_scrollback(offset);
+            let take = offset.min(rows);
+            out.extend(
+                crate::extract_cells_from_screen(screen)
+                    .into_iter()
+                    .take(take),
+            );
+            offset -= take;
+        }
+        screen.set_scrollback(0);
+        out
+    }
+}
+
+#[cfg(feature = "alacritty")]
+pub use self::alacritty::AlacrittyBackend;
+
+#[cfg(feature = "alacritty")]
+mod alacritty {
+    use super::TerminalBackend;
+    use crate::control_mode::osc::OscParser;
+    use crate::{CellColor, CellStyle, PaneContent, TerminalCell, TerminalLine, UnderlineStyle};
+    use alacritty_terminal::event::VoidListener;
+    use alacritty_terminal::grid::{Dimensions, Row};
+    use alacritty_terminal::index::{Column, Line};
+    use alacritty_terminal::term::cell::{Cell, Flags};
+    use alacritty_terminal::term::{Config, Term, TermMode};
+    use alacritty_terminal::vte::ansi::{Color, NamedColor, Processor};
+
+    /// Grid size handed to `Term::new` / `Term::resize`.
+    struct Size {
+        rows: usize,
+        cols: usize,
+    }
+
+    impl Dimensions for Size {
+        fn total_lines(&self) -> usize {
+            self.rows
+        }
+
+        fn screen_lines(&self) -> usize {
+            self.rows
+        }
+
+        fn columns(&self) -> usize {
+            self.cols
+        }
+    }
+
+    /// `alacritty_terminal`'s emulator, driven by its own VTE processor.
+    pub struct AlacrittyBackend {
+        term: Term<VoidListener>,
+        processor: Processor,
+    }
+
+    impl AlacrittyBackend {
+        pub fn new(rows: u16, cols: u16, scrollback: usize) -> Self {
+            let config = Config {
+                scrolling_history: scrollback,
+                ..Config::default()
+            };
+            let size = Size {
+                rows: rows.into(),
+                cols: cols.into(),
+            };
+            Self {
+                term: Term::new(config, &size, VoidListener),
+                processor: Processor::new(),
+            }
+        }
+    }
+
+    impl TerminalBackend for AlacrittyBackend {
+        fn process(&mut self, data: &[u8]) {
+            self.processor.advance(&mut self.term, data);
+        }
+
+        fn set_size(&mut self, rows: u16, cols: u16) {
+            self.term.resize(Size {
+                rows: rows.max(1).into(),
+                cols: cols.max(1).into(),
+            });
+        }
+
+        fn cursor_position(&self) -> (u16, u16) {
+            let point = self.term.grid().cursor.point;
+            (
+                u16::try_from(point.line.0.max(0)).unwrap_or(u16::MAX),
+                u16::try_from(point.column.0).unwrap_or(u16::MAX),
+            )
+        }
+
+        fn alternate_screen(&self) -> bool {
+            self.term.mode().contains(TermMode::ALT_SCREEN)
+        }
+
+        fn mouse_tracking(&self) -> bool {
+            self.term.mode().intersects(TermMode::MOUSE_MODE)
+        }
+
+        fn hide_cursor(&self) -> bool {
+            !self.term.mode().contains(TermMode::SHOW_CURSOR)
+        }
+
+        fn cells(&self, osc_parser: Option<&OscParser>) -> PaneContent {
+            let grid = self.term.grid();
+            (0..grid.screen_lines())
+                .map(|row| extract_row(&grid[Line(row as i32)], row, osc_parser))
+                .collect()
+        }
+
+        fn scrollback(&mut self, lines: usize) -> PaneContent {
+            let grid = self.term.grid();
+            // History rows sit at negative lines, -1 being the most recent.
+            // The alternate screen keeps none.
+            let count = lines.min(grid.history_size());
+            (1..=count)
+                .rev()
+                .map(|back| extract_row(&grid[Line(-(back as i32))], 0, None))
+                .collect()
+        }
+    }
+
+    /// Extract one grid row; `row` locates OSC 8 links on the visible screen.
+    fn extract_row(
+        grid_row: &Row<Cell>,
+        row: usize,
+        osc_parser: Option<&OscParser>,
+    ) -> TerminalLine {
+        let mut line: Vec<TerminalCell> = Vec::with_capacity(grid_row.len());
+        for col in 0..grid_row.len() {
+            let cell = &grid_row[Column(col)];
+            let flags = cell.flags;
+            // Same conventions as the vt100 extraction: the spacer
+            // half of a wide character renders as a space, combining
+            // marks ride on their base character. alacritty keeps a
+            // literal `\t` in the cell a tab started from.
+            let mut text = if flags.contains(Flags::WIDE_CHAR_SPACER) || cell.c == '\t' {
+                " ".to_string()
+            } else {
+                cell.c.to_string()
+            };
+            if let Some(marks) = cell.zerowidth() {
+                text.extend(marks);
+            }
+            let underline_style = if flags.contains(Flags::DOUBLE_UNDERLINE) {
+                Some(UnderlineStyle::Double)
+            } else if flags.contains(Flags::UNDERCURL) {
+                Some(UnderlineStyle::Curly)
+            } else if flags.contains(Flags::DOTTED_UNDERLINE) {
+                Some(UnderlineStyle::Dotted)
+            } else if flags.contains(Flags::DASHED_UNDERLINE) {
+                Some(UnderlineStyle::Dashed)
+            } else {
+                None
+            };
+            let style = CellStyle {
+                fg: cell_color(cell.fg),
+                bg: cell_color(cell.bg),
+                bold: flags.contains(Flags::BOLD),
+                dim: flags.contains(Flags::DIM),
+                italic: flags.contains(Flags::ITALIC),
+                underline: flags.intersects(Flags::ALL_UNDERLINES),
+                underline_style,
+                underline_color: cell.underline_color().and_then(cell_color),
+                strikethrough: flags.contains(Flags::STRIKEOUT),
+                // alacritty parses SGR 5/6 but does not keep it.
+                blink: false,
+                inverse: flags.contains(Flags::INVERSE),
+                url: osc_parser.and_then(|p| p.get_url(row as u32, col as u32).cloned()),
+            };
+            let width = if flags.contains(Flags::WIDE_CHAR) {
+                2
+            } else if flags.contains(Flags::WIDE_CHAR_SPACER) {
+                0
+            } else {
+                1
+            };
+            line.push(TerminalCell::with_style(text, style).with_width(width));
+        }
+        while let Some(last) = line.last() {
+            if last.char.trim().is_empty() && last.style.is_none() {
+                line.pop();
+            } else {
+                break;
+            }
+        }
+        line
+    }
+
+    /// Map alacritty's cell color onto the wire color. The 16 named ANSI
+    /// colors are palette indices, like vt100's `Idx`; the default
+    /// foreground/background report as unset.
+    fn cell_color(color: Color) -> Option<CellColor> {
+        match color {
+            Color::Spec(rgb) => Some(CellColor::Rgb {
+                r: rgb.r,
+                g: rgb.g,
+                b: rgb.b,
+            }),
+            Color::Indexed(idx) => Some(CellColor::Indexed(idx)),
+            Color::Named(named) => {
+                let idx = named as usize;
+                if idx < 16 {
+                    Some(CellColor::Indexed(idx as u8))
+                } else {
+                    match named.to_bright() {
+                        NamedColor::Foreground
+                        | NamedColor::Background
+                        | NamedColor::BrightForeground
+                        | NamedColor::Cursor => None,
+                        base => Some(CellColor::Indexed(base as u8)),
+                    }
+                }
+            }
+        }
+    }
+}
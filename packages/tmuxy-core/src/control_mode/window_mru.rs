@@ -0,0 +1,208 @@
+//! Most-recently-used window order, for an Alt-Tab style switcher.
+//!
+//! The tab bar lists windows by index; a switcher lists them by when they
+//! were last visited. Each time the session's active window changes, that
+//! window moves to the front of the order. A cycle
+//! (`switch_last_window_cycle`) steps through a snapshot of the order while
+//! the modifier is held: the windows it passes through are visited but not
+//! promoted, so three presses of Tab reach the fourth most recent window
+//! instead of bouncing between two. Releasing ends the cycle and promotes the
+//! window it landed on. A client that goes away mid-hold can't freeze the
+//! order — a cycle with no step for [`WINDOW_CYCLE_TIMEOUT`] ends by itself.
+
+use std::time::Duration;
+
+#[cfg(not(target_arch = "wasm32"))]
+use std::time::Instant;
+#[cfg(target_arch = "wasm32")]
+use web_time::Instant;
+
+/// How long a cycle lasts without a step before it ends on its own.
+pub const WINDOW_CYCLE_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Tab windows, most recently used first.
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WindowMru {
+    pub windows: Vec<String>,
+    /// The window a cycle in progress is on, `None` when not cycling.
+    pub selected: Option<String>,
+}
+
+#[derive(Debug)]
+struct Cycle {
+    /// The MRU order when the cycle started.
+    order: Vec<String>,
+    at: usize,
+    stepped: Instant,
+}
+
+#[derive(Debug, Default)]
+pub(crate) struct MruOrder {
+    order: Vec<String>,
+    cycle: Option<Cycle>,
+}
+
+impl MruOrder {
+    /// Bring the order up to date with the session's `tabs` (in index
+    /// order) and its `active` window. Windows seen for the first time go
+    /// last; the active one goes first unless a cycle is in progress.
+    pub(crate) fn update(&mut self, tabs: &[&str], active: Option<&str>, now: Instant) {
+        self.expire(now);
+        self.order.retain(|id| tabs.contains(&id.as_str()));
+        for id in tabs {
+            if !self.order.iter().any(|known| known == id) {
+                self.order.push(id.to_string());
+            }
+        }
+        if let Some(cycle) = &mut self.cycle {
+            let current = cycle.order.get(cycle.at).cloned();
+            cycle.order.retain(|id| tabs.contains(&id.as_str()));
+            cycle.at = current
+                .and_then(|current| cycle.order.iter().position(|id| *id == current))
+                .unwrap_or(0);
+            return;
+        }
+        if let Some(active) = active {
+            self.promote(active);
+        }
+    }
+
+    /// Move one step through the cycle, starting one if none is in
+    /// progress, and return the window to switch to.
+    pub(crate) fn step(&mut self, reverse: bool, now: Instant) -> Option<String> {
+        self.expire(now);
+        if self.cycle.is_none() {
+            if self.order.len() < 2 {
+                return None;
+            }
+            self.cycle = Some(Cycle {
+                order: self.order.clone(),
+                at: 0,
+                stepped: now,
+            });
+        }
+        let cycle = self.cycle.as_mut()?;
+        let len = cycle.order.len();
+        if len == 0 {
+            return None;
+        }
+        cycle.at = if reverse {
+            (cycle.at + len - 1) % len
+        } else {
+            (cycle.at + 1) % len
+        };
+        cycle.stepped = now;
+        cycle.order.get(cycle.at).cloned()
+    }
+
+    /// End the cycle in progress, promoting the window it is on.
+    pub(crate) fn end(&mut self) {
+        if let Some(cycle) = self.cycle.take() {
+            if let Some(id) = cycle.order.get(cycle.at) {
+                self.promote(id);
+            }
+        }
+    }
+
+    pub(crate) fn view(&self, now: Instant) -> WindowMru {
+        WindowMru {
+            windows: self.order.clone(),
+            selected: self
+                .cycle
+                .as_ref()
+                .filter(|cycle| !cycle.expired(now))
+                .and_then(|cycle| cycle.order.get(cycle.at).cloned()),
+        }
+    }
+
+    fn expire(&mut self, now: Instant) {
+        if self.cycle.as_ref().is_some_and(|cycle| cycle.expired(now)) {
+            self.end();
+        }
+    }
+
+    fn promote(&mut self, id: &str) {
+        if let Some(pos) = self.order.iter().position(|known| known == id) {
+            let id = self.order.remove(pos);
+            self.order.insert(0, id);
+        }
+    }
+}
+
+impl Cycle {
+    fn expired(&self, now: Instant) -> bool {
+        now.saturating_duration_since(self.stepped) >= WINDOW_CYCLE_TIMEOUT
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used, clippy::expect_used)]
+mod tests {
+    use super::*;
+
+    const TABS: [&str; 4] = ["@1", "@2", "@3", "@4"];
+
+    fn visited(now: Instant, order: &[&str]) -> MruOrder {
+        let mut mru = MruOrder::default();
+        for id in order {
+            mru.update(&TABS, Some(id), now);
+        }
+        mru
+    }
+
+    #[test]
+    fn visiting_a_window_moves_it_to_the_front() {
+        let now = Instant::now();
+        let mut mru = visited(now, &["@1", "@3"]);
+        assert_eq!(mru.view(now).windows, ["@3", "@1", "@2", "@4"]);
+        mru.update(&["@1", "@2", "@3"], Some("@2"), now);
+        assert_eq!(mru.view(now).windows, ["@2", "@3", "@1"]);
+        assert_eq!(mru.view(now).selected, None);
+    }
+
+    #[test]
+    fn holding_cycles_through_the_snapshot_and_release_promotes() {
+        let now = Instant::now();
+        let mut mru = visited(now, &["@1", "@2", "@3"]);
+        // [@3, @2, @1, @4]: each step's select-window lands mid-cycle.
+        for expected in ["@2", "@1"] {
+            let target = mru.step(false, now).unwrap();
+            assert_eq!(target, expected);
+            mru.update(&TABS, Some(&target), now);
+        }
+        assert_eq!(mru.view(now).windows, ["@3", "@2", "@1", "@4"]);
+        assert_eq!(mru.view(now).selected.as_deref(), Some("@1"));
+        assert_eq!(mru.step(true, now).as_deref(), Some("@2"));
+        assert_eq!(mru.step(false, now).as_deref(), Some("@1"));
+
+        mru.end();
+        assert_eq!(mru.view(now).windows, ["@1", "@3", "@2", "@4"]);
+        assert_eq!(mru.view(now).selected, None);
+        // A quick tap goes back to the previous window.
+        assert_eq!(mru.step(false, now).as_deref(), Some("@3"));
+        mru.end();
+        assert_eq!(mru.view(now).windows, ["@3", "@1", "@2", "@4"]);
+    }
+
+    #[test]
+    fn an_abandoned_cycle_ends_on_its_own() {
+        let now = Instant::now();
+        let mut mru = visited(now, &["@1", "@2"]);
+        assert_eq!(mru.step(false, now).as_deref(), Some("@1"));
+        let later = now + WINDOW_CYCLE_TIMEOUT;
+        assert_eq!(mru.view(later).selected, None);
+        mru.update(&TABS, Some("@1"), later);
+        assert_eq!(mru.view(later).windows, ["@1", "@2", "@3", "@4"]);
+        assert_eq!(mru.step(false, later).as_deref(), Some("@2"));
+    }
+
+    #[test]
+    fn a_single_window_has_nothing_to_cycle_to() {
+        let now = Instant::now();
+        let mut mru = MruOrder::default();
+        mru.update(&["@1"], Some("@1"), now);
+        assert_eq!(mru.step(false, now), None);
+        assert_eq!(mru.view(now).selected, None);
+    }
+}
@@ -0,0 +1,535 @@
+//! Per-window status segments evaluated by tmuxy.
+//!
+//! A window's `@tmuxy-status` option (per window with `set -w`, for every
+//! window with `set -g`) is a format tmuxy expands itself, apart from tmux's
+//! own status line, so it can show what tmux doesn't know: how many clients
+//! watch the session, the git state of the active pane's directory and the
+//! TCP ports the window's processes listen on. The result rides on the window
+//! as `status`.
+//!
+//! The syntax follows tmux formats: `#{name}`, `#{?name,then,else}` (true
+//! when the value is neither empty nor `0`), `##` for a literal `#` and `#,`
+//! for a comma inside a conditional. Variables:
+//!
+//! - `tmuxy_clients` — clients connected to the session
+//! - `tmuxy_git_branch` — branch of the active pane's directory, empty
+//!   outside a repository
+//! - `tmuxy_git_dirty` — changed and untracked files
+//! - `tmuxy_git_ahead`, `tmuxy_git_behind` — commits ahead of and behind
+//!   the upstream
+//! - `tmuxy_ports` — listening TCP ports, space separated
+//!
+//! Unknown names expand to nothing, as in tmux. Git state and ports come from
+//! one `run-shell` per refresh (see [`probe_script`]), so they are read on the
+//! host tmux runs on, and only when some window's format asks for them.
+
+use std::collections::{HashMap, HashSet};
+
+/// Marks the start of each section of [`probe_script`] output.
+const SECTION: &str = "--tmuxy-status--";
+
+/// `git status` of a directory, as far as status formats show it.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub(crate) struct GitStatus {
+    pub branch: String,
+    /// Changed, staged and untracked files.
+    pub dirty: usize,
+    pub ahead: u32,
+    pub behind: u32,
+}
+
+/// What a window's status format is evaluated against.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub(crate) struct StatusData {
+    pub clients: usize,
+    /// `None` outside a repository, or when the format doesn't use git.
+    pub git: Option<GitStatus>,
+    pub ports: Vec<u16>,
+}
+
+/// A window with a status format, from [`list_panes_format`] output.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct StatusWindow {
+    pub id: String,
+    pub format: String,
+    /// Working directory of the window's active pane.
+    pub active_path: Option<String>,
+    pub pane_pids: Vec<u32>,
+}
+
+/// `list-panes -s -F` format: one line per pane with what its window's
+/// status needs. The format is free text, so it comes last.
+#[cfg(feature = "native")]
+pub(crate) fn list_panes_format() -> String {
+    format!(
+        "#{{window_id}}\t#{{pane_pid}}\t#{{pane_active}}\t#{{pane_current_path}}\t#{{{}}}",
+        crate::constants::tmux_options::STATUS
+    )
+}
+
+/// Windows that have a status format, in the order tmux listed them.
+pub(crate) fn status_windows(output: &str) -> Vec<StatusWindow> {
+    let mut windows: Vec<StatusWindow> = Vec::new();
+    for line in output.lines() {
+        let mut fields = line.splitn(5, '\t');
+        let (Some(id), Some(pid), Some(active), Some(path), Some(format)) = (
+            fields.next(),
+            fields.next(),
+            fields.next(),
+            fields.next(),
+            fields.next(),
+        ) else {
+            continue;
+        };
+        if format.is_empty() {
+            continue;
+        }
+        let index = match windows.iter().position(|w| w.id == id) {
+            Some(index) => index,
+            None => {
+                windows.push(StatusWindow {
+                    id: id.to_string(),
+                    format: format.to_string(),
+                    active_path: None,
+                    pane_pids: Vec::new(),
+                });
+                windows.len() - 1
+            }
+        };
+        let window = &mut windows[index];
+        if let Ok(pid) = pid.parse() {
+            window.pane_pids.push(pid);
+        }
+        if active == "1" && !path.is_empty() {
+            window.active_path = Some(path.to_string());
+        }
+    }
+    windows
+}
+
+/// Whether `format` shows git state.
+pub(crate) fn uses_git(format: &str) -> bool {
+    format.contains("tmuxy_git_")
+}
+
+/// Whether `format` shows listening ports.
+pub(crate) fn uses_ports(format: &str) -> bool {
+    format.contains("tmuxy_ports")
+}
+
+/// Shell command printing the process table and listening sockets (with
+/// `ports`) and the `git status` of each of `dirs`, each section headed by a
+/// marker line. `ss` is tried before `lsof`, which is what macOS has.
+#[cfg(feature = "native")]
+pub(crate) fn probe_script(ports: bool, dirs: &[String]) -> String {
+    let mut parts = Vec::new();
+    if ports {
+        parts.push(format!("echo '{SECTION} ps'; ps -A -o pid= -o ppid="));
+        parts.push(format!(
+            "echo '{SECTION} listen'; ss -Hltnp 2>/dev/null || lsof -nP -iTCP -sTCP:LISTEN -Fpn 2>/dev/null"
+        ));
+    }
+    for (i, dir) in dirs.iter().enumerate() {
+        parts.push(format!(
+            "echo '{SECTION} git {i}'; git -C {} status --porcelain=v1 --branch 2>/dev/null",
+            crate::executor::tmux_quote(dir)
+        ));
+    }
+    // A directory outside a repository fails `git status`; that's an answer.
+    parts.push("true".to_string());
+    parts.join("; ")
+}
+
+/// [`probe_script`] output, parsed.
+#[derive(Debug, Default, PartialEq)]
+pub(crate) struct Probe {
+    /// `(pid, parent pid)` of every process.
+    processes: Vec<(u32, u32)>,
+    /// `(pid, port)` of every listening socket whose owner is known.
+    listeners: Vec<(u32, u16)>,
+    /// Git status by index into the probed directories.
+    git: HashMap<usize, GitStatus>,
+}
+
+impl Probe {
+    pub(crate) fn parse(output: &str) -> Self {
+        let mut probe = Self::default();
+        let mut sections: Vec<(&str, Vec<&str>)> = Vec::new();
+        for line in output.lines() {
+            match line.strip_prefix(SECTION) {
+                Some(name) => sections.push((name.trim(), Vec::new())),
+                None => {
+                    if let Some((_, lines)) = sections.last_mut() {
+                        lines.push(line);
+                    }
+                }
+            }
+        }
+        for (name, lines) in sections {
+            match name {
+                "ps" => probe.processes = lines.iter().filter_map(|l| parse_process(l)).collect(),
+                "listen" => probe.listeners = parse_listeners(&lines),
+                _ => {
+                    let index = name.strip_prefix("git ").and_then(|i| i.parse().ok());
+                    if let (Some(index), Some(git)) = (index, parse_git_status(&lines)) {
+                        probe.git.insert(index, git);
+                    }
+                }
+            }
+        }
+        probe
+    }
+
+    /// Git status of the `index`th probed directory.
+    pub(crate) fn git(&self, index: usize) -> Option<GitStatus> {
+        self.git.get(&index).cloned()
+    }
+
+    /// Ports listened on by `roots` or any of their descendants, sorted.
+    pub(crate) fn ports_under(&self, roots: &[u32]) -> Vec<u16> {
+        let mut children: HashMap<u32, Vec<u32>> = HashMap::new();
+        for &(pid, ppid) in &self.processes {
+            children.entry(ppid).or_default().push(pid);
+        }
+        let mut tree: HashSet<u32> = HashSet::new();
+        let mut stack = roots.to_vec();
+        while let Some(pid) = stack.pop() {
+            if tree.insert(pid) {
+                stack.extend(children.get(&pid).into_iter().flatten());
+            }
+        }
+        let mut ports: Vec<u16> = self
+            .listeners
+            .iter()
+            .filter(|(pid, _)| tree.contains(pid))
+            .map(|&(_, port)| port)
+            .collect();
+        ports.sort_unstable();
+        ports.dedup();
+        ports
+    }
+}
+
+/// A `ps -o pid= -o ppid=` line.
+fn parse_process(line: &str) -> Option<(u32, u32)> {
+    let mut fields = line.split_whitespace();
+    Some((fields.next()?.parse().ok()?, fields.next()?.parse().ok()?))
+}
+
+/// The port at the end of an address: `0.0.0.0:3000`, `[::]:80`, `*:8080`.
+fn address_port(address: &str) -> Option<u16> {
+    address.rsplit(':').next()?.parse().ok()
+}
+
+/// Listening sockets from `ss -Hltnp` or `lsof -Fpn` output.
+fn parse_listeners(lines: &[&str]) -> Vec<(u32, u16)> {
+    let mut listeners = Vec::new();
+    let mut lsof_pid = None;
+    for line in lines {
+        if line.contains("users:(") {
+            // LISTEN 0 511 0.0.0.0:3000 0.0.0.0:* users:(("node",pid=42,fd=21))
+            let Some(port) = line.split_whitespace().nth(3).and_then(address_port) else {
+                continue;
+            };
+            for owner in line.split("pid=").skip(1) {
+                let digits: String = owner.chars().take_while(char::is_ascii_digit).collect();
+                if let Ok(pid) = digits.parse() {
+                    listeners.push((pid, port));
+                }
+            }
+        } else if let Some(pid) = line.strip_prefix('p') {
+            lsof_pid = pid.parse().ok();
+        } else if let (Some(address), Some(pid)) = (line.strip_prefix('n'), lsof_pid) {
+            if let Some(port) = address_port(address) {
+                listeners.push((pid, port));
+            }
+        }
+    }
+    listeners
+}
+
+/// `git status --porcelain=v1 --branch` output; `None` when git printed no
+/// branch line (not a repository).
+fn parse_git_status(lines: &[&str]) -> Option<GitStatus> {
+    let header = lines.iter().find_map(|l| l.strip_prefix("## "))?;
+    let (head, tracking) = match header.split_once(" [") {
+        Some((head, tracking)) => (head, tracking.trim_end_matches(']')),
+        None => (header, ""),
+    };
+    let branch = if let Some(branch) = head.strip_prefix("No commits yet on ") {
+        branch
+    } else if head.starts_with("HEAD (no branch)") {
+        "HEAD"
+    } else {
+        head.split("...").next().unwrap_or(head)
+    };
+    let mut status = GitStatus {
+        branch: branch.to_string(),
+        dirty: lines
+            .iter()
+            .filter(|l| !l.is_empty() && !l.starts_with("## "))
+            .count(),
+        ..GitStatus::default()
+    };
+    for part in tracking.split(", ") {
+        if let Some(n) = part.strip_prefix("ahead ") {
+            status.ahead = n.parse().unwrap_or(0);
+        } else if let Some(n) = part.strip_prefix("behind ") {
+            status.behind = n.parse().unwrap_or(0);
+        }
+    }
+    Some(status)
+}
+
+/// Expand `format` against `data`.
+pub(crate) fn evaluate(format: &str, data: &StatusData) -> String {
+    let mut out = String::new();
+    let mut rest = format;
+    while let Some(i) = rest.find('#') {
+        out.push_str(&rest[..i]);
+        rest = &rest[i + 1..];
+        if let Some(body) = rest.strip_prefix('{') {
+            match closing_brace(body) {
+                Some(end) => {
+                    out.push_str(&expand(&body[..end], data));
+                    rest = &body[end + 1..];
+                }
+                None => {
+                    // Unterminated: keep it as typed.
+                    out.push_str("#{");
+                    rest = body;
+                }
+            }
+        } else if let Some(c @ ('#' | ',' | '}')) = rest.chars().next() {
+            out.push(c);
+            rest = &rest[1..];
+        } else {
+            out.push('#');
+        }
+    }
+    out.push_str(rest);
+    out
+}
+
+/// Offset of the `}` ending a `#{` whose body starts `body`, past nested
+/// `#{…}` and escapes.
+fn closing_brace(body: &str) -> Option<usize> {
+    let bytes = body.as_bytes();
+    let mut depth = 0;
+    let mut i = 0;
+    while i < bytes.len() {
+        match bytes[i] {
+            b'#' => {
+                if bytes.get(i + 1) == Some(&b'{') {
+                    depth += 1;
+                }
+                i += 2;
+                continue;
+            }
+            b'}' if depth == 0 => return Some(i),
+            b'}' => depth -= 1,
+            _ => {}
+        }
+        i += 1;
+    }
+    None
+}
+
+/// Split a conditional's body at its top-level commas into at most three
+/// parts.
+fn split_arms(body: &str) -> Vec<&str> {
+    let bytes = body.as_bytes();
+    let mut arms = Vec::new();
+    let mut depth = 0usize;
+    let mut start = 0;
+    let mut i = 0;
+    while i < bytes.len() && arms.len() < 2 {
+        match bytes[i] {
+            b'#' => {
+                if bytes.get(i + 1) == Some(&b'{') {
+                    depth += 1;
+                }
+                i += 2;
+                continue;
+            }
+            b'}' => depth = depth.saturating_sub(1),
+            b',' if depth == 0 => {
+                arms.push(&body[start..i]);
+                start = i + 1;
+            }
+            _ => {}
+        }
+        i += 1;
+    }
+    arms.push(&body[start.min(body.len())..]);
+    arms
+}
+
+/// Expand the body of one `#{…}`.
+fn expand(body: &str, data: &StatusData) -> String {
+    let Some(condition) = body.strip_prefix('?') else {
+        return variable(body, data);
+    };
+    let arms = split_arms(condition);
+    let value = variable(arms[0], data);
+    let arm = if !value.is_empty() && value != "0" {
+        arms.get(1)
+    } else {
+        arms.get(2)
+    };
+    arm.map(|arm| evaluate(arm, data)).unwrap_or_default()
+}
+
+fn variable(name: &str, data: &StatusData) -> String {
+    let git = data.git.as_ref();
+    match name {
+        "tmuxy_clients" => data.clients.to_string(),
+        "tmuxy_git_branch" => git.map(|g| g.branch.clone()).unwrap_or_default(),
+        "tmuxy_git_dirty" => git.map(|g| g.dirty.to_string()).unwrap_or_default(),
+        "tmuxy_git_ahead" => git.map(|g| g.ahead.to_string()).unwrap_or_default(),
+        "tmuxy_git_behind" => git.map(|g| g.behind.to_string()).unwrap_or_default(),
+        "tmuxy_ports" => data
+            .ports
+            .iter()
+            .map(u16::to_string)
+            .collect::<Vec<_>>()
+            .join(" "),
+        _ => String::new(),
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used, clippy::expect_used)]
+mod tests {
+    use super::*;
+
+    fn data() -> StatusData {
+        StatusData {
+            clients: 2,
+            git: Some(GitStatus {
+                branch: "main".to_string(),
+                dirty: 3,
+                ahead: 1,
+                behind: 0,
+            }),
+            ports: vec![3000, 8080],
+        }
+    }
+
+    #[test]
+    fn evaluates_variables_conditionals_and_escapes() {
+        let data = data();
+        assert_eq!(
+            evaluate("#{tmuxy_clients} clients | #{tmuxy_git_branch}", &data),
+            "2 clients | main"
+        );
+        assert_eq!(
+            evaluate("#{?tmuxy_git_dirty,*#{tmuxy_git_dirty},clean}", &data),
+            "*3"
+        );
+        assert_eq!(evaluate("#{?tmuxy_git_behind,behind,even}", &data), "even");
+        assert_eq!(
+            evaluate("#{?tmuxy_ports,ports#, #{tmuxy_ports},}", &data),
+            "ports, 3000 8080"
+        );
+        assert_eq!(
+            evaluate("## #{nope}#{tmuxy_clients", &data),
+            "# #{tmuxy_clients"
+        );
+        // Outside a repository the git variables are empty, so false.
+        let plain = StatusData::default();
+        assert_eq!(
+            evaluate("#{?tmuxy_git_branch,git,no git}", &plain),
+            "no git"
+        );
+    }
+
+    #[test]
+    fn groups_panes_by_window_and_skips_windows_without_a_format() {
+        let output = "@1\t100\t0\t/a\tgit #{tmuxy_git_branch}\n\
+                      @1\t101\t1\t/b\tgit #{tmuxy_git_branch}\n\
+                      @2\t200\t1\t/c\t\n";
+        assert_eq!(
+            status_windows(output),
+            vec![StatusWindow {
+                id: "@1".to_string(),
+                format: "git #{tmuxy_git_branch}".to_string(),
+                active_path: Some("/b".to_string()),
+                pane_pids: vec![100, 101],
+            }]
+        );
+    }
+
+    #[test]
+    fn parses_git_branch_lines() {
+        let status = |lines: &[&str]| parse_git_status(lines);
+        assert_eq!(
+            status(&[
+                "## main...origin/main [ahead 2, behind 1]",
+                " M a.rs",
+                "?? b"
+            ]),
+            Some(GitStatus {
+                branch: "main".to_string(),
+                dirty: 2,
+                ahead: 2,
+                behind: 1,
+            })
+        );
+        assert_eq!(
+            status(&["## No commits yet on trunk"]).unwrap().branch,
+            "trunk"
+        );
+        assert_eq!(status(&["## HEAD (no branch)"]).unwrap().branch, "HEAD");
+        assert_eq!(status(&[]), None);
+    }
+
+    #[test]
+    fn finds_ports_of_a_pane_process_tree() {
+        let output = format!(
+            "{SECTION} ps\n  10     1\n  11    10\n  12    11\n  20     1\n\
+             {SECTION} listen\n\
+             LISTEN 0 511 0.0.0.0:3000 0.0.0.0:* users:((\"node\",pid=12,fd=21))\n\
+             LISTEN 0 128 [::]:22 [::]:*\n\
+             LISTEN 0 5 127.0.0.1:9000 0.0.0.0:* users:((\"py\",pid=20,fd=3))\n\
+             {SECTION} git 0\n## dev\n"
+        );
+        let probe = Probe::parse(&output);
+        assert_eq!(probe.ports_under(&[10]), vec![3000]);
+        assert_eq!(probe.ports_under(&[10, 20]), vec![3000, 9000]);
+        assert_eq!(probe.git(0).unwrap().branch, "dev");
+        assert_eq!(probe.git(1), None);
+    }
+
+    #[test]
+    fn reads_lsof_listeners() {
+        let lines = [
+            "p42",
+            "f5",
+            "n*:8080",
+            "f6",
+            "n[::1]:8081",
+            "p43",
+            "n127.0.0.1:5432",
+        ];
+        assert_eq!(
+            parse_listeners(&lines),
+            vec![(42, 8080), (42, 8081), (43, 5432)]
+        );
+    }
+
+    #[cfg(all(feature = "native", unix))]
+    #[test]
+    fn probe_script_runs_under_sh() {
+        let script = probe_script(true, &["/nonexistent/it's gone".to_string()]);
+        let out = std::process::Command::new("sh")
+            .arg("-c")
+            .arg(script)
+            .output()
+            .unwrap();
+        assert!(out.status.success());
+        let probe = Probe::parse(&String::from_utf8(out.stdout).unwrap());
+        assert!(!probe.processes.is_empty());
+        assert_eq!(probe.git(0), None);
+    }
+}
@@ -0,0 +1,148 @@
+//! Typed copy-mode actions.
+//!
+//! The web UI drives tmux copy mode by name (`cursor-up`, `begin-selection`,
+//! `search-forward "text"`, …) rather than by crafting raw key sequences, which
+//! depend on the user's `mode-keys` (vi vs emacs) and any custom
+//! `copy-mode-vi` bindings. Each action maps 1:1 onto a `send-keys -X`
+//! copy-mode command, so behaviour is identical whatever the key table says.
+
+use crate::executor::tmux_quote;
+
+pub use tmuxy_protocol::CopyModeAction;
+
+/// The tmux copy-mode command name.
+fn tmux_name(action: &CopyModeAction) -> &'static str {
+    match action {
+        CopyModeAction::Enter => "copy-mode",
+        CopyModeAction::Cancel => "cancel",
+        CopyModeAction::CursorUp => "cursor-up",
+        CopyModeAction::CursorDown => "cursor-down",
+        CopyModeAction::CursorLeft => "cursor-left",
+        CopyModeAction::CursorRight => "cursor-right",
+        CopyModeAction::StartOfLine => "start-of-line",
+        CopyModeAction::EndOfLine => "end-of-line",
+        CopyModeAction::NextWord => "next-word",
+        CopyModeAction::NextWordEnd => "next-word-end",
+        CopyModeAction::PreviousWord => "previous-word",
+        CopyModeAction::TopLine => "top-line",
+        CopyModeAction::MiddleLine => "middle-line",
+        CopyModeAction::BottomLine => "bottom-line",
+        CopyModeAction::HalfpageUp => "halfpage-up",
+        CopyModeAction::HalfpageDown => "halfpage-down",
+        CopyModeAction::PageUp => "page-up",
+        CopyModeAction::PageDown => "page-down",
+        CopyModeAction::ScrollUp => "scroll-up",
+        CopyModeAction::ScrollDown => "scroll-down",
+        CopyModeAction::HistoryTop => "history-top",
+        CopyModeAction::HistoryBottom => "history-bottom",
+        CopyModeAction::BeginSelection => "begin-selection",
+        CopyModeAction::SelectLine => "select-line",
+        CopyModeAction::RectangleToggle => "rectangle-toggle",
+        CopyModeAction::ClearSelection => "clear-selection",
+        CopyModeAction::CopySelection => "copy-selection",
+        CopyModeAction::CopySelectionAndCancel => "copy-selection-and-cancel",
+        CopyModeAction::SearchForward(_) => "search-forward",
+        CopyModeAction::SearchBackward(_) => "search-backward",
+        CopyModeAction::SearchAgain => "search-again",
+        CopyModeAction::SearchReverse => "search-reverse",
+        CopyModeAction::JumpForward(_) => "jump-forward",
+        CopyModeAction::JumpBackward(_) => "jump-backward",
+    }
+}
+
+/// Build the tmux command applying `action` `count` times (default 1)
+/// to `pane_id`.
+pub fn action_command(action: &CopyModeAction, pane_id: &str, count: Option<u32>) -> String {
+    let pane = tmux_quote(pane_id);
+    if *action == CopyModeAction::Enter {
+        return format!("copy-mode -t {pane}");
+    }
+    let mut cmd = format!("send-keys -t {pane} -X");
+    if let Some(n) = count.filter(|n| *n > 1) {
+        cmd.push_str(&format!(" -N {n}"));
+    }
+    cmd.push(' ');
+    cmd.push_str(tmux_name(action));
+    match action {
+        CopyModeAction::SearchForward(arg)
+        | CopyModeAction::SearchBackward(arg)
+        | CopyModeAction::JumpForward(arg)
+        | CopyModeAction::JumpBackward(arg) => {
+            cmd.push(' ');
+            cmd.push_str(&tmux_quote(arg));
+        }
+        _ => {}
+    }
+    cmd
+}
+
+/// Build the tmux command scrolling `pane_id` to `offset` lines above the live
+/// bottom (the `scroll_position` the pane reports), entering copy mode first.
+/// tmux clamps an offset past the top of history to the top.
+pub fn scroll_to_command(pane_id: &str, offset: u32) -> String {
+    let pane = tmux_quote(pane_id);
+    format!("copy-mode -t {pane} \\; send-keys -t {pane} -X goto-line {offset}")
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used, clippy::expect_used)]
+mod tests {
+    use super::*;
+
+    fn parse(json: &str) -> CopyModeAction {
+        serde_json::from_str(json).unwrap()
+    }
+
+    #[test]
+    fn unit_actions_map_to_send_keys_x() {
+        assert_eq!(
+            action_command(&parse(r#"{"name":"halfpage-down"}"#), "%2", None),
+            "send-keys -t '%2' -X halfpage-down"
+        );
+        assert_eq!(
+            action_command(
+                &parse(r#"{"name":"copy-selection-and-cancel"}"#),
+                "%2",
+                None
+            ),
+            "send-keys -t '%2' -X copy-selection-and-cancel"
+        );
+    }
+
+    #[test]
+    fn count_becomes_repeat_flag() {
+        assert_eq!(
+            action_command(&CopyModeAction::CursorUp, "%0", Some(5)),
+            "send-keys -t '%0' -X -N 5 cursor-up"
+        );
+        assert_eq!(
+            action_command(&CopyModeAction::CursorUp, "%0", Some(1)),
+            "send-keys -t '%0' -X cursor-up"
+        );
+    }
+
+    #[test]
+    fn search_text_is_quoted() {
+        let action = parse(r#"{"name":"search-forward","arg":"it's; kill-server"}"#);
+        assert_eq!(
+            action_command(&action, "%1", None),
+            r"send-keys -t '%1' -X search-forward 'it'\''s; kill-server'"
+        );
+    }
+
+    #[test]
+    fn enter_uses_copy_mode_command() {
+        assert_eq!(
+            action_command(&parse(r#"{"name":"enter"}"#), "%1", Some(3)),
+            "copy-mode -t '%1'"
+        );
+    }
+
+    #[test]
+    fn scroll_to_enters_copy_mode_then_goes_to_line() {
+        assert_eq!(
+            scroll_to_command("%3", 120),
+            r"copy-mode -t '%3' \; send-keys -t '%3' -X goto-line 120"
+        );
+    }
+}
@@ -11,8 +11,9 @@
 
 use crate::error::TmuxError;
 use crate::retry::RetryPolicy;
-use std::sync::Arc;
-use std::time::Instant;
+use crate::session::TmuxTransport;
+use std::sync::{Arc, OnceLock};
+use std::time::{Instant, SystemTime, UNIX_EPOCH};
 
 /// Run-arbitrary-tmux-command capability.
 ///
@@ -28,6 +29,24 @@ pub trait TmuxCommand: Send + Sync {
 /// deterministically without actually sleeping.
 pub trait Clock: Send + Sync {
     fn now(&self) -> Instant;
+
+    /// Unix seconds at [`Clock::now`], for comparing against the wall-clock
+    /// times tmux reports (`window_activity`). Anchored to the system clock
+    /// once per process, so a fake clock's steps carry over.
+    fn unix_now(&self) -> u64 {
+        static ANCHOR: OnceLock<(Instant, u64)> = OnceLock::new();
+        let (at, unix) = *ANCHOR.get_or_init(|| {
+            let unix = SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map_or(0, |d| d.as_secs());
+            (Instant::now(), unix)
+        });
+        let now = self.now();
+        match now.checked_duration_since(at) {
+            Some(ahead) => unix.saturating_add(ahead.as_secs()),
+            None => unix.saturating_sub(at.duration_since(now).as_secs()),
+        }
+    }
 }
 
 /// The execution context threaded through the codebase.
@@ -39,15 +58,52 @@ pub struct Ctx {
     pub tmux: Arc<dyn TmuxCommand>,
     pub clock: Arc<dyn Clock>,
     pub retry_policy: RetryPolicy,
+    /// The tmux server `tmux` talks to, when it isn't the environment's
+    /// (`TMUX_SOCKET` / `TMUXY_SSH`, resolved per call).
+    pub transport: Option<TmuxTransport>,
 }
 
 impl Ctx {
     /// Build a production context using the real tmux binary and system clock.
     pub fn live() -> Arc<Self> {
         Arc::new(Self {
-            tmux: Arc::new(LiveTmux),
+            tmux: Arc::new(LiveTmux { transport: None }),
+            clock: Arc::new(LiveClock),
+            retry_policy: RetryPolicy::standard(),
+            transport: None,
+        })
+    }
+
+    /// A production context for the tmux server `transport` reaches, for a
+    /// session that lives on another host than the environment's.
+    pub fn live_on(transport: TmuxTransport) -> Arc<Self> {
+        Arc::new(Self {
+            tmux: Arc::new(LiveTmux {
+                transport: Some(transport.clone()),
+            }),
             clock: Arc::new(LiveClock),
             retry_policy: RetryPolicy::standard(),
+            transport: Some(transport),
+        })
+    }
+
+    /// Whether tmux runs on another host, through ssh.
+    pub fn is_remote(&self) -> bool {
+        match &self.transport {
+            Some(transport) => transport.is_remote(),
+            None => crate::session::ssh_target().is_some(),
+        }
+    }
+
+    /// The production context on a different clock. The E2E test hooks run
+    /// the real server on an [`OffsetClock`].
+    #[cfg(any(test, feature = "test-support"))]
+    pub fn live_with_clock(clock: Arc<dyn Clock>) -> Arc<Self> {
+        Arc::new(Self {
+            tmux: Arc::new(LiveTmux { transport: None }),
+            clock,
+            retry_policy: RetryPolicy::standard(),
+            transport: None,
         })
     }
 
@@ -93,7 +149,10 @@ impl Ctx {
 /// Production tmux backend — delegates to the existing
 /// `executor::execute_tmux_command`. Wrapped in `spawn_blocking` so the
 /// async-trait future doesn't park on a blocking subprocess wait.
-struct LiveTmux;
+struct LiveTmux {
+    /// `None` resolves the environment's transport on every call.
+    transport: Option<TmuxTransport>,
+}
 
 #[async_trait::async_trait]
 impl TmuxCommand for LiveTmux {
@@ -101,9 +160,13 @@ impl TmuxCommand for LiveTmux {
         // Materialise into owned strings so the spawn_blocking closure can
         // capture them — &str doesn't live long enough across the boundary.
         let owned: Vec<String> = args.iter().map(|s| s.to_string()).collect();
+        let transport = self.transport.clone();
         tokio::task::spawn_blocking(move || {
             let refs: Vec<&str> = owned.iter().map(String::as_str).collect();
-            crate::executor::execute_tmux_command(&refs)
+            match transport {
+                Some(transport) => crate::executor::execute_tmux_command_on(&transport, &refs),
+                None => crate::executor::execute_tmux_command(&refs),
+            }
         })
         .await
         .map_err(|e| TmuxError::other(format!("spawn_blocking failure: {}", e)))?
@@ -208,6 +271,35 @@ impl Clock for FakeClock {
     }
 }
 
+/// The system clock plus an offset a test can only grow. Unlike a
+/// `FakeClock`, time keeps passing: a live monitor sleeps on deadlines it
+/// computes from this clock, and a frozen clock would leave them stuck.
+/// `advance` jumps every such deadline forward at once.
+#[cfg(any(test, feature = "test-support"))]
+#[derive(Default)]
+pub struct OffsetClock {
+    offset: std::sync::Mutex<std::time::Duration>,
+}
+
+#[cfg(any(test, feature = "test-support"))]
+#[allow(clippy::unwrap_used)]
+impl OffsetClock {
+    /// Move the clock forward by `by`, returning the total offset.
+    pub fn advance(&self, by: std::time::Duration) -> std::time::Duration {
+        let mut offset = self.offset.lock().unwrap();
+        *offset += by;
+        *offset
+    }
+}
+
+#[cfg(any(test, feature = "test-support"))]
+#[allow(clippy::unwrap_used)]
+impl Clock for OffsetClock {
+    fn now(&self) -> Instant {
+        Instant::now() + *self.offset.lock().unwrap()
+    }
+}
+
 /// Build a fully-substituted `Ctx` for tests. Defaults to retry-disabled so a
 /// test asserting failure doesn't get masked.
 #[cfg(any(test, feature = "test-support"))]
@@ -218,6 +310,7 @@ pub fn test_ctx() -> (Arc<Ctx>, Arc<MockTmux>, Arc<FakeClock>) {
         tmux: tmux.clone(),
         clock: clock.clone(),
         retry_policy: RetryPolicy::none(),
+        transport: None,
     });
     (ctx, tmux, clock)
 }
@@ -252,5 +345,18 @@ mod tests {
         assert_eq!(clock.now(), base);
         clock.advance(Duration::from_millis(250));
         assert_eq!(clock.now() - base, Duration::from_millis(250));
+        let unix = clock.unix_now();
+        clock.advance(Duration::from_secs(3 * 24 * 60 * 60));
+        assert_eq!(clock.unix_now() - unix, 3 * 24 * 60 * 60);
+    }
+
+    #[test]
+    fn offset_clock_runs_ahead_of_the_system_clock() {
+        let clock = OffsetClock::default();
+        assert_eq!(
+            clock.advance(Duration::from_secs(5)),
+            Duration::from_secs(5)
+        );
+        assert!(clock.now() >= Instant::now() + Duration::from_secs(4));
     }
 }
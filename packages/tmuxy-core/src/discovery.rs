@@ -0,0 +1,142 @@
+//! Session discovery: every session on the tmux server, tmuxy's or not
+//! (`GET /api/sessions`), so a client can pick one to attach to.
+//!
+//! A session counts as tmuxy's when its current window carries the
+//! `@tmuxy-window-type` tag. Other sessions attach all the same: the monitor
+//! adopts their windows as plain tabs (see `collect_window_tag_commands`)
+//! instead of reading float or group roles into their window names.
+
+use serde::Serialize;
+
+/// `list-sessions -F` columns, comma-separated. `session_name` is free text,
+/// so it goes last and keeps its commas (see `LIST_WINDOWS_CMD`).
+const LIST_SESSIONS_FORMAT: &str = concat!(
+    "#{session_id},#{session_windows},#{session_attached},#{session_created},",
+    "#{window_width},#{window_height},#{@tmuxy-window-type},#{session_group},",
+    "#{session_name}",
+);
+
+/// A tmux session, as discovery lists it.
+#[derive(Debug, Clone, Serialize, PartialEq, Eq)]
+pub struct SessionInfo {
+    /// tmux's session id (`$3`)
+    pub id: String,
+    pub name: String,
+    pub windows: u32,
+    /// Clients attached to it, tmuxy's own control-mode clients included
+    pub attached: u32,
+    /// Creation time, in seconds since the epoch
+    pub created: u64,
+    /// Size of its current window
+    pub width: u32,
+    pub height: u32,
+    /// Its session group (`new-session -t`), if it is in one
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub group: Option<String>,
+    /// Whether tmuxy made it (or has attached to it before)
+    pub tmuxy: bool,
+}
+
+/// `list-sessions` arguments for [`parse_sessions`].
+pub fn list_sessions_args() -> Vec<String> {
+    vec![
+        "list-sessions".to_string(),
+        "-F".to_string(),
+        LIST_SESSIONS_FORMAT.to_string(),
+    ]
+}
+
+/// Parse `list-sessions` output into sessions sorted by name. Lines that
+/// aren't session records are skipped.
+pub fn parse_sessions(output: &str) -> Vec<SessionInfo> {
+    let mut sessions: Vec<SessionInfo> = output
+        .lines()
+        .filter_map(|line| {
+            let parts: Vec<&str> = line.splitn(9, ',').collect();
+            let [id, windows, attached, created, width, height, tag, group, name] = parts[..]
+            else {
+                return None;
+            };
+            if !id.starts_with('$') {
+                return None;
+            }
+            Some(SessionInfo {
+                id: id.to_string(),
+                name: name.to_string(),
+                windows: windows.parse().unwrap_or(0),
+                attached: attached.parse().unwrap_or(0),
+                created: created.parse().unwrap_or(0),
+                width: width.parse().unwrap_or(0),
+                height: height.parse().unwrap_or(0),
+                group: (!group.is_empty()).then(|| group.to_string()),
+                tmuxy: !tag.is_empty(),
+            })
+        })
+        .collect();
+    sessions.sort_by(|a, b| a.name.cmp(&b.name));
+    sessions
+}
+
+/// Whether a failed tmux call failed only because no tmux server is
+/// running, which for discovery just means there are no sessions.
+pub fn is_no_server_error(message: &str) -> bool {
+    message.contains("no server running") || message.contains("error connecting to")
+}
+
+/// Whether `name` can name a session tmuxy attaches to or creates. tmux
+/// turns `:` and `.` into `_` in session names (so the session would never
+/// be found under the name asked for), and a leading `=` reads as an
+/// exact-match target. Control characters can't appear in a name at all.
+pub fn validate_session_name(name: &str) -> Result<(), String> {
+    let valid = !name.is_empty()
+        && !name.starts_with('=')
+        && !name.chars().any(|c| c == ':' || c == '.' || c.is_control());
+    if valid {
+        Ok(())
+    } else {
+        Err(format!("invalid session name '{name}'"))
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used, clippy::expect_used)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_sessions_with_and_without_tmuxy_tags() {
+        let output = "$1,1,0,1792172669,80,24,,other,other\n\
+                      $0,3,2,1792172600,100,30,tab,,my proj, v2\n\
+                      not a session\n";
+        let sessions = parse_sessions(output);
+        assert_eq!(sessions.len(), 2);
+        let mine = &sessions[0];
+        assert_eq!(mine.name, "my proj, v2");
+        assert_eq!((mine.windows, mine.attached), (3, 2));
+        assert_eq!((mine.width, mine.height), (100, 30));
+        assert!(mine.tmuxy);
+        assert_eq!(mine.group, None);
+        let other = &sessions[1];
+        assert_eq!(other.id, "$1");
+        assert!(!other.tmuxy);
+        assert_eq!(other.group.as_deref(), Some("other"));
+    }
+
+    #[test]
+    fn validates_session_names() {
+        for name in ["tmuxy", "my proj", "work-2", "naïve"] {
+            assert!(validate_session_name(name).is_ok(), "{name:?}");
+        }
+        for name in ["", "a:b", "a.b", "=main", "a\nb"] {
+            assert!(validate_session_name(name).is_err(), "{name:?}");
+        }
+    }
+
+    #[test]
+    fn no_server_errors_are_recognised() {
+        assert!(is_no_server_error(
+            "tmux error: no server running on /tmp/tmux-0/tmuxy"
+        ));
+        assert!(!is_no_server_error("tmux error: unknown format"));
+    }
+}
@@ -0,0 +1,1200 @@
+//! The one place client commands are carried out.
+//!
+//! The SSE server and the desktop app take the same `ClientCommand`s, from a
+//! `POST /commands` body and a Tauri `invoke` respectively. [`dispatch`] runs
+//! them against a [`CommandHost`], which supplies what differs between the
+//! two: how to reach the session's monitor, the per-client state (viewport,
+//! prefix mode, focus) each keeps its own way, and the log filter and
+//! template root each process sets up. A new command is a variant in
+//! tmuxy-protocol's `command.rs` and an arm here; both transports get it.
+
+use std::sync::{Arc, Mutex as StdMutex};
+use std::time::Duration;
+
+use crate::control_mode::{CommandRecord, MonitorCommand, ServerEvent};
+use crate::copy_mode::{action_command, scroll_to_command};
+use crate::float::FloatOp;
+use crate::key_table::{KeyAction, KeyTables};
+use crate::keyboard::KeyEvent;
+use crate::options;
+use crate::pane_group::GroupOp;
+use crate::pane_search::{history_chunks, Matcher, PaneSearches, MAX_MATCHES};
+use crate::pane_text::TextSelection;
+use crate::session::TmuxTransport;
+use crate::table::TableCache;
+use crate::template::SessionTemplate;
+use crate::widget::WidgetManager;
+use crate::{executor, Ctx, RetryPolicy};
+use tmuxy_protocol::ClientCommand;
+use tracing::{info, trace, warn};
+
+/// How long `run_tmux_command` waits for a command's control-mode response
+/// when the client asked for its output (or it is a `source-file`, whose
+/// completion gates re-reading keybindings).
+const COMMAND_OUTPUT_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Sends one event to a client.
+pub type EventSink = Arc<dyn Fn(ServerEvent) + Send + Sync>;
+
+/// What a transport provides for [`dispatch`].
+#[async_trait::async_trait]
+pub trait CommandHost: Send + Sync {
+    /// The session commands act on.
+    fn session(&self) -> &str;
+
+    /// The app's context, which theme settings are read and written through.
+    fn ctx(&self) -> &Arc<Ctx>;
+
+    /// The context for reaching the session's tmux, which differs from
+    /// [`ctx`](Self::ctx) for a session on a saved remote server.
+    async fn session_ctx(&self) -> Arc<Ctx> {
+        self.ctx().clone()
+    }
+
+    /// Hand a command to the session's monitor.
+    async fn send(&self, cmd: MonitorCommand) -> Result<(), String>;
+
+    /// Row indexes of the files `table_query` has paged through.
+    fn tables(&self) -> Arc<StdMutex<TableCache>>;
+
+    /// The pane searches in progress, for `pane_search`.
+    fn searches(&self) -> Arc<PaneSearches>;
+
+    /// This client's id among the session's, which keys its pane searches.
+    fn client_id(&self) -> Result<u64, String>;
+
+    /// Where events for this client alone go, for results that follow a
+    /// command's reply.
+    async fn reply_sink(&self) -> Result<EventSink, String>;
+
+    /// Record this client's viewport and resize the session to fit.
+    async fn set_client_size(&self, cols: u32, rows: u32);
+
+    /// The viewport a new window is sized to, once a client has reported one.
+    async fn viewport(&self) -> Option<(u32, u32)>;
+
+    /// The key tables `key_input` resolves keys against.
+    async fn key_tables(&self) -> Arc<KeyTables>;
+
+    /// Run `key` through this client's prefix state, returning what to do
+    /// with it and whether the client is left in prefix mode.
+    async fn resolve_key(&self, key: &KeyEvent) -> Result<(KeyAction, bool), String>;
+
+    /// The session's key bindings may have changed: drop the cached tables
+    /// and send clients the new bindings.
+    async fn keybindings_changed(&self);
+
+    /// Record whether this client has focus.
+    async fn set_focus(&self, focused: bool) -> Result<(), String>;
+
+    /// Cap the rate this client's event stream carries frames at; `0` lifts
+    /// the cap.
+    async fn set_frame_budget(&self, fps: u32) -> Result<(), String>;
+
+    /// A session was built from a template.
+    fn session_created(&self, _name: &str) {}
+
+    /// The directory relative paths in a session template resolve against.
+    fn workspace_root(&self) -> std::path::PathBuf;
+
+    /// The log filter in effect, in `RUST_LOG` syntax; `None` before
+    /// logging is set up.
+    fn log_level(&self) -> Option<String>;
+
+    /// Replace the log filter, returning the one now in effect.
+    fn set_log_level(&self, directives: &str) -> Result<String, String>;
+}
+
+/// Carry out `cmd` for `host`'s client.
+pub async fn dispatch<H: CommandHost + ?Sized>(
+    host: &H,
+    cmd: ClientCommand,
+) -> Result<serde_json::Value, String> {
+    let session = host.session();
+    match cmd {
+        ClientCommand::GetInitialState { cols, rows } => {
+            // Apply client size before capturing state
+            if let (Some(c), Some(r)) = (cols, rows) {
+                if c > 0 && r > 0 {
+                    host.set_client_size(c, r).await;
+                }
+            }
+            // capture_window_state_for_session shells several synchronous tmux
+            // subprocesses; run it off the async worker threads so a slow
+            // capture on connect doesn't stall the runtime under multi-client load.
+            let session_owned = session.to_string();
+            let snapshot = tokio::task::spawn_blocking(move || {
+                crate::capture_window_state_for_session(&session_owned)
+            })
+            .await
+            .map_err(|e| format!("capture task failed: {}", e))??;
+            serde_json::to_value(snapshot).map_err(|e| format!("Failed to serialize state: {}", e))
+        }
+        ClientCommand::SetClientSize { cols, rows } => {
+            if cols > 0 && rows > 0 {
+                host.set_client_size(cols, rows).await;
+            }
+            Ok(serde_json::json!(null))
+        }
+        ClientCommand::SetFrameBudget { fps } => {
+            host.set_frame_budget(fps).await?;
+            Ok(serde_json::json!(null))
+        }
+        ClientCommand::RunTmuxCommand {
+            command,
+            output: wants_output,
+        } => {
+            // Block raw resize-window commands from clients — resize must go through
+            // set_client_size to prevent stale SSE connections from overriding sizes.
+            if command.starts_with("resize-window") || command.starts_with("resizew") {
+                warn!(%command, "blocked resize command (use set_client_size)");
+                return Ok(serde_json::json!(null));
+            }
+
+            // neww crashes tmux 3.5a control mode — use split+break workaround
+            if command.starts_with("new-window") || command.starts_with("neww") {
+                let cmd = new_window_command(host).await;
+                run(host, &cmd).await?;
+                return Ok(serde_json::json!(null));
+            }
+
+            // Read-only session/window/pane enumeration is safe to run as a
+            // one-off external subprocess even while control mode is attached
+            // (docs/TMUX.md "Commands Safe to Run"). Run it synchronously and
+            // return stdout: the fire-and-forget control-mode path below can't
+            // return output, so a caller that needs it — the sidebar's sessions
+            // poll runs `list-windows -a` / `list-panes -a` to enumerate every
+            // session on the socket — would otherwise get null.
+            if is_readonly_query(&command) {
+                let transport = host
+                    .session_ctx()
+                    .await
+                    .transport
+                    .clone()
+                    .unwrap_or_else(TmuxTransport::from_env);
+                return executor::run_tmux_command_for_session_on(&transport, session, &command)
+                    .map(|out| serde_json::json!(out))
+                    .map_err(|e| e.to_string());
+            }
+
+            // Detect source-file commands — keybindings may change
+            let is_source_file =
+                command.starts_with("source-file") || command.starts_with("source ");
+
+            // Only wait for the response when it's needed: most commands are
+            // keystrokes, and the frontend sends them one POST at a time.
+            let (reply, output_rx) = if wants_output || is_source_file {
+                let (reply, rx) = tokio::sync::oneshot::channel();
+                (Some(reply), Some(rx))
+            } else {
+                (None, None)
+            };
+            host.send(MonitorCommand::RunCommand {
+                command: command.clone(),
+                reply,
+            })
+            .await?;
+            trace!(%command, "client sent command via control mode");
+
+            let output = match output_rx {
+                Some(rx) => Some(
+                    tokio::time::timeout(COMMAND_OUTPUT_TIMEOUT, rx)
+                        .await
+                        .map_err(|_| format!("Timed out waiting for output of {command}"))?
+                        .map_err(|_| "Monitor dropped run-tmux-command request".to_string())?,
+                ),
+                None => None,
+            };
+
+            // After source-file, re-broadcast keybindings (prefix key may have changed)
+            if is_source_file {
+                host.keybindings_changed().await;
+            }
+
+            match output {
+                Some(Ok(output)) if wants_output => Ok(serde_json::json!(output)),
+                Some(Err(e)) if wants_output => Err(e),
+                _ => Ok(serde_json::json!(null)),
+            }
+        }
+        ClientCommand::SendMouseEvents { pane_id, events } => {
+            if !events.is_empty() {
+                host.send(MonitorCommand::MouseEvents { pane_id, events })
+                    .await?;
+            }
+            Ok(serde_json::json!(null))
+        }
+        ClientCommand::SendKey { pane_id, key } => {
+            host.send(MonitorCommand::SendKey { pane_id, key }).await?;
+            Ok(serde_json::json!(null))
+        }
+        ClientCommand::KeyInput { pane_id, key } => {
+            let (action, prefix) = host.resolve_key(&key).await?;
+            match action {
+                // Same split+break workaround as run_tmux_command.
+                KeyAction::Run(command)
+                    if command.starts_with("new-window") || command.starts_with("neww") =>
+                {
+                    let cmd = new_window_command(host).await;
+                    run(host, &cmd).await?;
+                }
+                KeyAction::Run(command) => {
+                    // Pin the binding to the client's pane, as the UI does for
+                    // the bindings it runs itself.
+                    let command = format!(
+                        "select-pane -t {} \\; {command}",
+                        executor::tmux_quote(&pane_id)
+                    );
+                    run(host, &command).await?;
+                }
+                KeyAction::Send => {
+                    host.send(MonitorCommand::SendKey { pane_id, key }).await?;
+                }
+                KeyAction::Consumed => {}
+            }
+            Ok(serde_json::json!({ "prefix": prefix }))
+        }
+        ClientCommand::GetEffectiveBindings => {
+            let tables = host.key_tables().await;
+            serde_json::to_value(tables.effective_bindings()).map_err(|e| e.to_string())
+        }
+        ClientCommand::SendText { pane_id, text } => {
+            if !text.is_empty() {
+                host.send(MonitorCommand::SendText { pane_id, text })
+                    .await?;
+            }
+            Ok(serde_json::json!(null))
+        }
+        ClientCommand::RerunCommand { pane_id, id } => {
+            let commands = command_history(host, &pane_id).await?;
+            if commands.last().is_some_and(|c| c.running) {
+                return Err(format!("Pane {pane_id} is still running a command"));
+            }
+            let command = commands
+                .into_iter()
+                .find(|c| c.id == id)
+                .ok_or_else(|| format!("No command {id} in pane {pane_id}"))?;
+            host.send(MonitorCommand::SendText {
+                pane_id,
+                text: format!("{}\r", command.command),
+            })
+            .await?;
+            Ok(serde_json::json!(null))
+        }
+        ClientCommand::CopyModeAction {
+            pane_id,
+            action,
+            count,
+        } => {
+            run(host, &action_command(&action, &pane_id, count)).await?;
+            Ok(serde_json::json!(null))
+        }
+        ClientCommand::ScrollTo { pane_id, offset } => {
+            run(host, &scroll_to_command(&pane_id, offset)).await?;
+            Ok(serde_json::json!(null))
+        }
+        ClientCommand::DuplicatePane { pane_id, rerun } => {
+            // Reads the pane's process from /proc, which only works on this host.
+            if host.session_ctx().await.is_remote() {
+                return Err("Duplicating panes is not supported on remote sessions".to_string());
+            }
+            let cmd =
+                executor::duplicate_pane_command(&pane_id, rerun).map_err(|e| e.to_string())?;
+            run(host, &cmd).await?;
+            Ok(serde_json::json!(null))
+        }
+        ClientCommand::NewWindowWithCommand {
+            cwd,
+            command,
+            sandbox,
+        } => {
+            let cmd = executor::new_window_with_command(
+                host.session(),
+                host.viewport().await,
+                cwd.as_deref(),
+                command.as_deref(),
+                sandbox.as_deref(),
+            );
+            run(host, &cmd).await?;
+            Ok(serde_json::json!(null))
+        }
+        ClientCommand::SplitWithCommand {
+            pane_id,
+            direction,
+            cwd,
+            command,
+            sandbox,
+        } => {
+            let cmd = executor::split_with_command(
+                &pane_id,
+                direction,
+                cwd.as_deref(),
+                command.as_deref(),
+                sandbox.as_deref(),
+            );
+            run(host, &cmd).await?;
+            Ok(serde_json::json!(null))
+        }
+        ClientCommand::CreateWidget { pane_id, widget } => {
+            let cmd = WidgetManager::bundled()
+                .create_command(&pane_id, &widget)
+                .map_err(|e| e.to_string())?;
+            run(host, &cmd).await?;
+            Ok(serde_json::json!(null))
+        }
+        ClientCommand::CreateGitWidget { pane_id } => {
+            let cmd = WidgetManager::bundled()
+                .create_git_command(&pane_id)
+                .map_err(|e| e.to_string())?;
+            run(host, &cmd).await?;
+            Ok(serde_json::json!(null))
+        }
+        ClientCommand::ListContainers => {
+            let containers = tokio::task::spawn_blocking(crate::container::list_containers)
+                .await
+                .map_err(|e| e.to_string())?;
+            Ok(serde_json::json!(containers))
+        }
+        ClientCommand::OpenContainerShell {
+            pane_id,
+            exec,
+            new_window,
+        } => {
+            let cmd = if new_window {
+                crate::container::exec_window_command(&pane_id, &exec, host.viewport().await)
+            } else {
+                crate::container::exec_command(&pane_id, &exec)
+            }
+            .map_err(|e| e.to_string())?;
+            run(host, &cmd).await?;
+            Ok(serde_json::json!(null))
+        }
+        ClientCommand::CreateFromTemplate {
+            template,
+            format,
+            session: name,
+        } => {
+            let template = SessionTemplate::parse(&template, format).map_err(|e| e.to_string())?;
+            let name = name
+                .or_else(|| template.name.clone())
+                .ok_or("The template names no session; pass one")?;
+            let plan = template
+                .plan(&name, &host.workspace_root())
+                .map_err(|e| e.to_string())?;
+            // Built over this session's control-mode client and awaited, so a
+            // duplicate name or a refused command comes back as the error.
+            let (reply, rx) = tokio::sync::oneshot::channel();
+            host.send(MonitorCommand::RunCommand {
+                command: plan.command_list(
+                    crate::control_mode::INITIAL_PTY_COLS,
+                    crate::control_mode::INITIAL_PTY_ROWS,
+                ),
+                reply: Some(reply),
+            })
+            .await?;
+            tokio::time::timeout(COMMAND_OUTPUT_TIMEOUT, rx)
+                .await
+                .map_err(|_| format!("Timed out building session {name}"))?
+                .map_err(|_| "Monitor dropped create-from-template request".to_string())??;
+            info!(%session, %name, "created session from template");
+            host.session_created(&name);
+            Ok(serde_json::json!(name))
+        }
+        ClientCommand::TableQuery { query } => {
+            // File reads, and a full pass over the file the first time it is
+            // queried or sorted: keep them off the async workers.
+            let tables = host.tables();
+            let page = tokio::task::spawn_blocking(move || {
+                let mut tables = tables.lock().unwrap_or_else(|e| e.into_inner());
+                tables.query(&query).map_err(|e| e.to_string())
+            })
+            .await
+            .map_err(|e| format!("table query failed: {}", e))??;
+            serde_json::to_value(page).map_err(|e| e.to_string())
+        }
+        ClientCommand::UpdateWidget { pane_id, widget } => {
+            let cmd = WidgetManager::bundled()
+                .update_command(&pane_id, &widget)
+                .map_err(|e| e.to_string())?;
+            run(host, &cmd).await?;
+            Ok(serde_json::json!(null))
+        }
+        ClientCommand::DestroyWidget { pane_id } => {
+            let cmd = WidgetManager::bundled().destroy_command(&pane_id);
+            run(host, &cmd).await?;
+            Ok(serde_json::json!(null))
+        }
+        ClientCommand::DragResize {
+            window_id,
+            start_x,
+            start_y,
+            end_x,
+            end_y,
+        } => {
+            host.send(MonitorCommand::DragResize {
+                window_id,
+                from: (start_x, start_y),
+                to: (end_x, end_y),
+            })
+            .await?;
+            Ok(serde_json::json!(null))
+        }
+        ClientCommand::BeginPaneDrag { pane_id } => {
+            host.send(MonitorCommand::BeginPaneDrag { pane_id }).await?;
+            Ok(serde_json::json!(null))
+        }
+        ClientCommand::DragPaneOver { x, y } => {
+            host.send(MonitorCommand::DragPaneOver { x, y }).await?;
+            Ok(serde_json::json!(null))
+        }
+        ClientCommand::DropPaneAt { x, y } => {
+            host.send(MonitorCommand::DropPaneAt { x, y }).await?;
+            Ok(serde_json::json!(null))
+        }
+        ClientCommand::SetDoNotDisturb { mode } => {
+            host.send(MonitorCommand::SetDoNotDisturb { mode }).await?;
+            Ok(serde_json::json!(null))
+        }
+        ClientCommand::SetTerminalTheme { theme } => {
+            host.send(MonitorCommand::SetTerminalTheme { theme })
+                .await?;
+            Ok(serde_json::json!(null))
+        }
+        ClientCommand::SetFocus { focused } => {
+            host.set_focus(focused).await?;
+            Ok(serde_json::json!(null))
+        }
+        ClientCommand::FixSshAgent => {
+            let (reply, rx) = tokio::sync::oneshot::channel();
+            host.send(MonitorCommand::FixSshAgent { reply }).await?;
+            let status = rx
+                .await
+                .map_err(|_| "Monitor dropped fix-ssh-agent request".to_string())??;
+            Ok(serde_json::json!({ "status": status }))
+        }
+        ClientCommand::SetMonitorTuning { tuning } => {
+            let (reply, rx) = tokio::sync::oneshot::channel();
+            host.send(MonitorCommand::SetTuning { tuning, reply })
+                .await?;
+            let tuning = rx
+                .await
+                .map_err(|_| "Monitor dropped set-monitor-tuning request".to_string())?;
+            serde_json::to_value(tuning).map_err(|e| e.to_string())
+        }
+        ClientCommand::SetLogLevel { level } => {
+            let level = match level {
+                Some(level) => {
+                    let applied = host.set_log_level(&level)?;
+                    info!(level = %applied, "log level changed");
+                    applied
+                }
+                None => host
+                    .log_level()
+                    .ok_or_else(|| "logging is not initialized".to_string())?,
+            };
+            Ok(serde_json::json!({ "level": level }))
+        }
+        ClientCommand::ResumePane { pane_id } => {
+            let (reply, rx) = tokio::sync::oneshot::channel();
+            host.send(MonitorCommand::ResumePane { pane_id, reply })
+                .await?;
+            rx.await
+                .map_err(|_| "Monitor dropped resume-pane request".to_string())??;
+            Ok(serde_json::json!(null))
+        }
+        ClientCommand::DiscardPaneOutput { pane_id } => {
+            let (reply, rx) = tokio::sync::oneshot::channel();
+            host.send(MonitorCommand::DiscardPaneOutput { pane_id, reply })
+                .await?;
+            rx.await
+                .map_err(|_| "Monitor dropped discard-pane-output request".to_string())??;
+            Ok(serde_json::json!(null))
+        }
+        ClientCommand::InjectSecret { pane_id, name } => {
+            let (reply, rx) = tokio::sync::oneshot::channel();
+            host.send(MonitorCommand::InjectSecret {
+                pane_id,
+                name,
+                reply,
+            })
+            .await?;
+            rx.await
+                .map_err(|_| "Monitor dropped inject-secret request".to_string())??;
+            Ok(serde_json::json!(null))
+        }
+        ClientCommand::PreviewSplit {
+            pane_id,
+            direction,
+            percent,
+        } => {
+            let (reply, rx) = tokio::sync::oneshot::channel();
+            host.send(MonitorCommand::PreviewSplit {
+                pane_id,
+                direction,
+                percent,
+                reply,
+            })
+            .await?;
+            let preview = rx
+                .await
+                .map_err(|_| "Monitor dropped split-preview request".to_string())?;
+            serde_json::to_value(preview).map_err(|e| e.to_string())
+        }
+        ClientCommand::GetStalePanes { days } => {
+            let (reply, rx) = tokio::sync::oneshot::channel();
+            let min_idle = Duration::from_secs(u64::from(days) * 24 * 60 * 60);
+            host.send(MonitorCommand::GetStalePanes { min_idle, reply })
+                .await?;
+            let stale = rx
+                .await
+                .map_err(|_| "Monitor dropped stale-pane request".to_string())?;
+            serde_json::to_value(stale).map_err(|e| e.to_string())
+        }
+        ClientCommand::GetExitSummaries => {
+            let (reply, rx) = tokio::sync::oneshot::channel();
+            host.send(MonitorCommand::GetExitSummaries { reply })
+                .await?;
+            let summaries = rx
+                .await
+                .map_err(|_| "Monitor dropped exit-summary request".to_string())?;
+            serde_json::to_value(summaries).map_err(|e| e.to_string())
+        }
+        ClientCommand::GetWindowMru => {
+            let (reply, rx) = tokio::sync::oneshot::channel();
+            host.send(MonitorCommand::GetWindowMru { reply }).await?;
+            let mru = rx
+                .await
+                .map_err(|_| "Monitor dropped window MRU request".to_string())?;
+            serde_json::to_value(mru).map_err(|e| e.to_string())
+        }
+        ClientCommand::FloatCreate { spec } => float_op(host, FloatOp::Create(spec)).await,
+        ClientCommand::FloatToggle { pane_id } => float_op(host, FloatOp::Toggle { pane_id }).await,
+        ClientCommand::FloatMove {
+            pane_id,
+            x,
+            y,
+            snap,
+        } => {
+            float_op(
+                host,
+                FloatOp::Move {
+                    pane_id,
+                    x,
+                    y,
+                    snap,
+                },
+            )
+            .await
+        }
+        ClientCommand::FloatResize {
+            pane_id,
+            width,
+            height,
+            snap,
+        } => {
+            let op = FloatOp::Resize {
+                pane_id,
+                width,
+                height,
+                snap,
+            };
+            float_op(host, op).await
+        }
+        ClientCommand::FloatClose { pane_id } => float_op(host, FloatOp::Close { pane_id }).await,
+        ClientCommand::GroupCreate { pane_id } => group_op(host, GroupOp::Create { pane_id }).await,
+        ClientCommand::GroupAdd { pane_id } => group_op(host, GroupOp::Add { pane_id }).await,
+        ClientCommand::GroupSwitch { pane_id } => group_op(host, GroupOp::Switch { pane_id }).await,
+        ClientCommand::GroupSwitchTab { pane_id, index } => {
+            group_op(host, GroupOp::SwitchTab { pane_id, index }).await
+        }
+        ClientCommand::GroupClose { pane_id } => group_op(host, GroupOp::Close { pane_id }).await,
+        ClientCommand::GroupReorder { pane_id, index } => {
+            group_op(host, GroupOp::Reorder { pane_id, index }).await
+        }
+        ClientCommand::SwitchLastWindowCycle { reverse, release } => {
+            let (reply, rx) = tokio::sync::oneshot::channel();
+            host.send(MonitorCommand::SwitchLastWindowCycle {
+                reverse,
+                release,
+                reply,
+            })
+            .await?;
+            let mru = rx
+                .await
+                .map_err(|_| "Monitor dropped window cycle request".to_string())?;
+            serde_json::to_value(mru).map_err(|e| e.to_string())
+        }
+        ClientCommand::GetRecentScrollback { pane_id, lines } => {
+            let (reply, rx) = tokio::sync::oneshot::channel();
+            host.send(MonitorCommand::GetScrollback {
+                pane_id: pane_id.clone(),
+                lines,
+                reply,
+            })
+            .await?;
+            let cells = rx
+                .await
+                .map_err(|_| "Monitor dropped scrollback request".to_string())?
+                .ok_or_else(|| format!("Unknown pane {pane_id}"))?;
+            Ok(serde_json::json!({ "cells": cells }))
+        }
+        ClientCommand::GetCommandHistory { pane_id } => {
+            let commands = command_history(host, &pane_id).await?;
+            Ok(serde_json::json!({ "commands": commands }))
+        }
+        ClientCommand::GetScrollbackCells {
+            pane_id,
+            start,
+            end,
+        } => {
+            // Route the three queries that build one scrollback response through
+            // the Tower stack — picks up the standard retry policy, a 5s
+            // per-call deadline, and tracing in one place. Capture-pane in
+            // particular sometimes races a pending layout change and returns
+            // transient io::Error; the retry layer absorbs those.
+            let policy = RetryPolicy::standard();
+            let width_output = tmux_call(
+                host,
+                vec![
+                    "display-message".into(),
+                    "-t".into(),
+                    pane_id.clone(),
+                    "-p".into(),
+                    "#{pane_width}".into(),
+                ],
+                "scrollback:pane_width",
+                policy,
+            )
+            .await
+            .map_err(|e| format!("Failed to get pane width: {}", e))?;
+            // Don't fall back to a default: a wrong width silently re-wraps
+            // every captured line at the wrong column. Fail so the client can
+            // retry instead of rendering corrupted scrollback.
+            let width: u32 = width_output.trim().parse().map_err(|_| {
+                format!(
+                    "Failed to parse pane width from tmux: {:?}",
+                    width_output.trim()
+                )
+            })?;
+
+            let history_output = tmux_call(
+                host,
+                vec![
+                    "display-message".into(),
+                    "-t".into(),
+                    pane_id.clone(),
+                    "-p".into(),
+                    "#{history_size}".into(),
+                ],
+                "scrollback:history_size",
+                policy,
+            )
+            .await
+            .map_err(|e| format!("Failed to get history size: {}", e))?;
+            let history_size: u32 = history_output.trim().parse().map_err(|_| {
+                format!(
+                    "Failed to parse history size from tmux: {:?}",
+                    history_output.trim()
+                )
+            })?;
+
+            // capture-pane wants the special `-S start -E end` form built
+            // inline so dispatch directly through the stack rather than the
+            // sync `capture_pane_range` helper.
+            let start_s = start.to_string();
+            let end_s = end.to_string();
+            let raw = tmux_call(
+                host,
+                vec![
+                    "capture-pane".into(),
+                    "-t".into(),
+                    pane_id.clone(),
+                    "-p".into(),
+                    "-e".into(),
+                    "-S".into(),
+                    start_s,
+                    "-E".into(),
+                    end_s,
+                ],
+                "scrollback:capture",
+                policy,
+            )
+            .await
+            .map_err(|e| format!("Failed to capture pane range: {}", e))?;
+
+            // Parse into cells
+            let cells = crate::parse_scrollback_to_cells(&raw, width);
+
+            Ok(serde_json::json!({
+                "cells": cells,
+                "historySize": history_size,
+                "start": start,
+                "end": end,
+                "width": width
+            }))
+        }
+        ClientCommand::ExportPane {
+            pane_id,
+            format,
+            start,
+            end,
+        } => {
+            let (cells, width) = capture_cells(host, &pane_id, start, end, "export").await?;
+            // Rasterizing a long range takes a while: off the async workers.
+            let export = tokio::task::spawn_blocking(move || {
+                crate::export::export(&pane_id, &cells, width as usize, format)
+                    .map_err(|e| e.to_string())
+            })
+            .await
+            .map_err(|e| format!("export failed: {}", e))??;
+            serde_json::to_value(export).map_err(|e| e.to_string())
+        }
+        ClientCommand::GetPaneText {
+            pane_id,
+            start,
+            end,
+            selection,
+        } => {
+            // A selection names its own rows.
+            let selection = selection.map(TextSelection::normalized);
+            let (start, end) = match selection {
+                Some(s) => (Some(s.start_row), Some(s.end_row)),
+                None => (start, end),
+            };
+            let (cells, width) = capture_cells(host, &pane_id, start, end, "pane_text").await?;
+            let text = crate::pane_text::pane_text(&cells, width as usize, selection.as_ref());
+            serde_json::to_value(text).map_err(|e| e.to_string())
+        }
+        ClientCommand::PaneSearch { pane_id, search } => {
+            let client = host.client_id()?;
+            let searches = host.searches();
+            let search_id = searches.begin(client, &pane_id);
+            if search.query.is_empty() {
+                searches.finish(client, &pane_id, search_id);
+                return Ok(serde_json::json!({
+                    "searchId": search_id,
+                    "matches": [],
+                    "historySize": 0,
+                    "done": true
+                }));
+            }
+            let matcher = Matcher::new(&search)?;
+            let ctx = host.session_ctx().await;
+            let history_size = pane_history_size(&ctx, &pane_id).await?;
+            let visible = capture_rows(&ctx, &pane_id, None).await?;
+            // The emulator's masks cover the screen; hide the same cells here.
+            let visible = mask_captured_rows(host, &pane_id, &visible).await?;
+            let matches = matcher.search_rows(&visible, 0);
+            let done = history_size == 0 || matches.len() >= MAX_MATCHES;
+            if done {
+                searches.finish(client, &pane_id, search_id);
+            } else {
+                let walk = HistoryWalk {
+                    ctx,
+                    searches,
+                    emit: host.reply_sink().await?,
+                    client,
+                    pane_id: pane_id.clone(),
+                    search_id,
+                    matcher,
+                    found: matches.len(),
+                };
+                tokio::spawn(walk.run(history_size));
+            }
+            Ok(serde_json::json!({
+                "searchId": search_id,
+                "matches": matches,
+                "historySize": history_size,
+                "done": done
+            }))
+        }
+        ClientCommand::GetOption { option } => {
+            let value = tmux_call(
+                host,
+                options::get_args(&option, session)?,
+                "option:get",
+                RetryPolicy::standard(),
+            )
+            .await
+            .map_err(|e| format!("Failed to read option: {}", e))?;
+            Ok(serde_json::json!({ "value": value.trim_end_matches('\n') }))
+        }
+        ClientCommand::SetOption { option, value } => {
+            let cmd = options::set_command(&option, session, value.as_deref())?;
+            run(host, &cmd).await?;
+            Ok(serde_json::json!(null))
+        }
+        ClientCommand::WatchOption { option } => {
+            let (reply, rx) = tokio::sync::oneshot::channel();
+            host.send(MonitorCommand::WatchOption { option, reply })
+                .await?;
+            rx.await
+                .map_err(|_| "Monitor dropped watch-option request".to_string())??;
+            Ok(serde_json::json!(null))
+        }
+        ClientCommand::CreateGroupedSession { name } => {
+            let name = match name {
+                Some(name) => name,
+                None => {
+                    let existing = tmux_call(
+                        host,
+                        vec![
+                            "list-sessions".to_string(),
+                            "-F".to_string(),
+                            "#{session_name}".to_string(),
+                        ],
+                        "session:list",
+                        RetryPolicy::standard(),
+                    )
+                    .await
+                    .map_err(|e| format!("Failed to list sessions: {}", e))?;
+                    let existing: Vec<String> = existing.lines().map(str::to_string).collect();
+                    crate::control_mode::session_group::next_name(session, &existing)
+                }
+            };
+            let cmd = crate::control_mode::session_group::create_command(session, &name)?;
+            run(host, &cmd).await?;
+            Ok(serde_json::json!({ "session": name }))
+        }
+        ClientCommand::GetThemeSettings => Ok(crate::theme::get_theme_settings(host.ctx()).await),
+        ClientCommand::SetTheme { name, mode } => {
+            crate::theme::set_theme(host.ctx(), &name, mode.as_deref()).await?;
+            Ok(serde_json::json!(null))
+        }
+        ClientCommand::GetThemesList => Ok(crate::theme::get_themes_list()),
+        ClientCommand::SetThemeMode { mode } => {
+            crate::theme::set_theme_mode(host.ctx(), &mode).await?;
+            Ok(serde_json::json!(null))
+        }
+    }
+}
+
+// ============================================
+
+/// Send a tmux command through control mode, without waiting for it.
+async fn run<H: CommandHost + ?Sized>(host: &H, command: &str) -> Result<(), String> {
+    host.send(MonitorCommand::RunCommand {
+        command: command.to_string(),
+        reply: None,
+    })
+    .await
+}
+
+/// `tmux_call_with_policy` against the session's tmux.
+async fn tmux_call<H: CommandHost + ?Sized>(
+    host: &H,
+    args: Vec<String>,
+    op_name: &str,
+    policy: RetryPolicy,
+) -> Result<String, crate::TmuxError> {
+    host.session_ctx()
+        .await
+        .tmux_call_with_policy(args, op_name, policy)
+        .await
+}
+
+/// The `new-window` rewrite (splitw + breakp + resizew + window-tag), sized
+/// to the host's viewport.
+async fn new_window_command<H: CommandHost + ?Sized>(host: &H) -> String {
+    executor::new_window_rewrite(host.session(), host.viewport().await)
+}
+
+/// The commands a pane's shell marked, from its monitor.
+async fn command_history<H: CommandHost + ?Sized>(
+    host: &H,
+    pane_id: &str,
+) -> Result<Vec<CommandRecord>, String> {
+    let (reply, rx) = tokio::sync::oneshot::channel();
+    host.send(MonitorCommand::GetCommandHistory {
+        pane_id: pane_id.to_string(),
+        reply,
+    })
+    .await?;
+    rx.await
+        .map_err(|_| "Monitor dropped command history request".to_string())?
+        .ok_or_else(|| format!("Unknown pane {pane_id}"))
+}
+
+/// A pane's cells, with its width: the visible area, or the `start..=end`
+/// range (capture-pane line numbers) when given. capture-pane reads tmux's
+/// grid, not the emulator, so the pane's secret masks are applied here.
+async fn capture_cells<H: CommandHost + ?Sized>(
+    host: &H,
+    pane_id: &str,
+    start: Option<i64>,
+    end: Option<i64>,
+    label: &str,
+) -> Result<(crate::PaneContent, u32), String> {
+    let policy = RetryPolicy::standard();
+    let width_output = tmux_call(
+        host,
+        vec![
+            "display-message".into(),
+            "-t".into(),
+            pane_id.to_string(),
+            "-p".into(),
+            "#{pane_width}".into(),
+        ],
+        &format!("{label}:pane_width"),
+        policy,
+    )
+    .await
+    .map_err(|e| format!("Failed to get pane width: {}", e))?;
+    let width: u32 = width_output.trim().parse().map_err(|_| {
+        format!(
+            "Failed to parse pane width from tmux: {:?}",
+            width_output.trim()
+        )
+    })?;
+
+    let mut args: Vec<String> = vec![
+        "capture-pane".into(),
+        "-t".into(),
+        pane_id.to_string(),
+        "-p".into(),
+        "-e".into(),
+    ];
+    if let Some(start) = start {
+        args.extend(["-S".into(), start.to_string()]);
+    }
+    if let Some(end) = end {
+        args.extend(["-E".into(), end.to_string()]);
+    }
+    let raw = tmux_call(host, args, &format!("{label}:capture"), policy)
+        .await
+        .map_err(|e| format!("Failed to capture pane: {}", e))?;
+    let content = crate::parse_scrollback_to_cells(&raw, width);
+    Ok((mask_captured(host, pane_id, content).await?, width))
+}
+
+/// `content` captured from `pane_id` with the pane's live secret masks
+/// applied (see `StateAggregator::mask_captured`).
+async fn mask_captured<H: CommandHost + ?Sized>(
+    host: &H,
+    pane_id: &str,
+    content: crate::PaneContent,
+) -> Result<crate::PaneContent, String> {
+    let (reply, rx) = tokio::sync::oneshot::channel();
+    host.send(MonitorCommand::MaskCaptured {
+        pane_id: pane_id.to_string(),
+        content,
+        reply,
+    })
+    .await?;
+    rx.await
+        .map_err(|_| "Monitor dropped mask request".to_string())
+}
+
+/// Plain capture-pane rows with `pane_id`'s live secret masks applied, one
+/// cell per character so columns are kept.
+async fn mask_captured_rows<H: CommandHost + ?Sized>(
+    host: &H,
+    pane_id: &str,
+    rows: &str,
+) -> Result<String, String> {
+    let content = rows
+        .lines()
+        .map(|row| {
+            row.chars()
+                .map(|c| crate::TerminalCell::new(c.to_string()))
+                .collect()
+        })
+        .collect();
+    let content = mask_captured(host, pane_id, content).await?;
+    Ok(content
+        .iter()
+        .map(|line| line.iter().map(|c| c.char.as_str()).collect::<String>())
+        .collect::<Vec<_>>()
+        .join("\n"))
+}
+
+/// A pane's `#{history_size}`.
+async fn pane_history_size(ctx: &Ctx, pane_id: &str) -> Result<u32, String> {
+    let output = ctx
+        .tmux_call_with_policy(
+            vec![
+                "display-message".into(),
+                "-t".into(),
+                pane_id.to_string(),
+                "-p".into(),
+                "#{history_size}".into(),
+            ],
+            "search:history_size",
+            RetryPolicy::standard(),
+        )
+        .await
+        .map_err(|e| format!("Failed to get history size: {}", e))?;
+    output.trim().parse().map_err(|_| {
+        format!(
+            "Failed to parse history size from tmux: {:?}",
+            output.trim()
+        )
+    })
+}
+
+/// A pane's rows as plain text: the visible area, or the `start..=end`
+/// range.
+async fn capture_rows(
+    ctx: &Ctx,
+    pane_id: &str,
+    range: Option<(i64, i64)>,
+) -> Result<String, String> {
+    let mut args: Vec<String> = vec![
+        "capture-pane".into(),
+        "-t".into(),
+        pane_id.to_string(),
+        "-p".into(),
+    ];
+    if let Some((start, end)) = range {
+        args.extend(["-S".into(), start.to_string(), "-E".into(), end.to_string()]);
+    }
+    ctx.tmux_call_with_policy(args, "search:capture", RetryPolicy::standard())
+        .await
+        .map_err(|e| format!("Failed to capture pane: {}", e))
+}
+
+/// The history half of a `pane_search`, run after the reply: one
+/// `search-results` event per chunk, until the history runs out, the
+/// matches reach [`MAX_MATCHES`], or the client's newer search on the pane
+/// takes over.
+struct HistoryWalk {
+    ctx: Arc<Ctx>,
+    searches: Arc<PaneSearches>,
+    /// Where the searching client's `search-results` go.
+    emit: EventSink,
+    client: u64,
+    pane_id: String,
+    search_id: u64,
+    matcher: Matcher,
+    /// Matches reported so far, the visible area's included.
+    found: usize,
+}
+
+impl HistoryWalk {
+    async fn run(mut self, history_size: u32) {
+        let mut chunks = history_chunks(history_size).peekable();
+        while let Some((start, end)) = chunks.next() {
+            if !self
+                .searches
+                .is_current(self.client, &self.pane_id, self.search_id)
+            {
+                return;
+            }
+            let (mut matches, failed) =
+                match capture_rows(&self.ctx, &self.pane_id, Some((start, end))).await {
+                    Ok(rows) => (self.matcher.search_rows(&rows, start), false),
+                    Err(e) => {
+                        warn!(pane_id = %self.pane_id, error = %e, "pane search stopped");
+                        (Vec::new(), true)
+                    }
+                };
+            // Over the cap, keep the matches nearest the visible area.
+            let room = MAX_MATCHES - self.found;
+            if matches.len() > room {
+                matches.drain(..matches.len() - room);
+            }
+            self.found += matches.len();
+            let done = failed || self.found >= MAX_MATCHES || chunks.peek().is_none();
+            (self.emit)(ServerEvent::SearchResults {
+                pane_id: self.pane_id.clone(),
+                search_id: self.search_id,
+                matches,
+                done,
+            });
+            if done {
+                break;
+            }
+        }
+        self.searches
+            .finish(self.client, &self.pane_id, self.search_id);
+    }
+}
+
+/// Run a float operation on the session's monitor.
+async fn float_op<H: CommandHost + ?Sized>(
+    host: &H,
+    op: FloatOp,
+) -> Result<serde_json::Value, String> {
+    let (reply, rx) = tokio::sync::oneshot::channel();
+    host.send(MonitorCommand::Float { op, reply }).await?;
+    rx.await
+        .map_err(|_| "Monitor dropped float request".to_string())??;
+    Ok(serde_json::json!(null))
+}
+
+async fn group_op<H: CommandHost + ?Sized>(
+    host: &H,
+    op: GroupOp,
+) -> Result<serde_json::Value, String> {
+    let (reply, rx) = tokio::sync::oneshot::channel();
+    host.send(MonitorCommand::PaneGroup { op, reply }).await?;
+    rx.await
+        .map_err(|_| "Monitor dropped pane group request".to_string())??;
+    Ok(serde_json::json!(null))
+}
+
+/// True for read-only tmux queries that are safe to run as a one-off external
+/// subprocess while a control-mode client is attached (docs/TMUX.md). These
+/// return stdout the fire-and-forget control-mode path can't.
+///
+/// `run_tmux_command_for_session` splits the command on tmux's separators
+/// (`;`, `\;`, newlines), any of which would chain a mutating command onto
+/// a read. It no longer goes through a shell, but shell control/expansion
+/// characters stay rejected too — the only legitimate callers (the sidebar's
+/// `list-* -a -F '…'` poll) use just alphanumerics, spaces, `-`, single
+/// quotes, `#{…}`, `@`, and tabs, so anything else is not a plain read.
+fn is_readonly_query(command: &str) -> bool {
+    // Command separators, plus characters no plain read needs.
+    const SHELL_METACHARS: &[char] = &[
+        ';', '\n', '\r', '&', '|', '$', '`', '<', '>', '(', ')', '\\',
+    ];
+    if command.contains(SHELL_METACHARS) {
+        return false;
+    }
+    const READONLY_PREFIXES: &[&str] = &["list-windows", "list-panes", "list-sessions"];
+    let head = command.trim_start();
+    READONLY_PREFIXES
+        .iter()
+        .any(|p| head == *p || head.starts_with(&format!("{p} ")))
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used, clippy::expect_used)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn readonly_query_allows_session_enumeration_reads() {
+        // The exact commands the sidebar sessions poll issues, including the
+        // tab-joined multi-field format (literal tabs, not metacharacters).
+        assert!(is_readonly_query("list-windows -a -F '#{session_name}'"));
+        assert!(is_readonly_query("list-panes -a -F '#{pane_id}'"));
+        assert!(is_readonly_query("list-sessions"));
+        assert!(is_readonly_query(
+            "list-windows -a -F '#{session_name}\t#{window_id}\t#{@tmuxy-window-type}'"
+        ));
+    }
+
+    #[test]
+    fn readonly_query_rejects_mutations_and_smuggling() {
+        // Mutating tmux commands must keep flowing through the control-mode
+        // channel.
+        assert!(!is_readonly_query("split-window -h"));
+        assert!(!is_readonly_query("kill-session -t foo"));
+        // A read must not chain a mutation past the guard with the
+        // tokenizer's separators.
+        assert!(!is_readonly_query("list-windows -a ; kill-server"));
+        assert!(!is_readonly_query("list-windows -a \\; kill-server"));
+        assert!(!is_readonly_query("list-panes\nkill-session -t foo"));
+        // The query is tokenized into argv and never reaches a shell; these
+        // stay rejected because no plain read needs them.
+        assert!(!is_readonly_query("list-panes -a && kill-server"));
+        assert!(!is_readonly_query("list-panes -a | sh"));
+        assert!(!is_readonly_query("list-panes -a $(kill-server)"));
+        assert!(!is_readonly_query("list-panes -a `kill-server`"));
+        assert!(!is_readonly_query("list-panes -a > /etc/passwd"));
+        assert!(!is_readonly_query("list-panes -a & kill-server"));
+        // Prefix-only match must not let `list-windows-evil` style names through.
+        assert!(!is_readonly_query("list-windowsX"));
+    }
+}
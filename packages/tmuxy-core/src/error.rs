@@ -101,7 +101,7 @@ impl From<&str> for TmuxError {
 }
 
 /// Bridge in the opposite direction for the remaining `Result<_, String>`
-/// call sites (notably `tmuxy-server/src/sse.rs::handle_command`). Lets the
+/// call sites (notably `dispatch::dispatch`). Lets the
 /// `?` operator stringify a `TmuxError` so the SSE handler can keep its
 /// existing String-typed wire contract. a typed-progress channel would replace that contract if it is ever needed.
 /// outright; until then, this preserves the JSON error shape the frontend
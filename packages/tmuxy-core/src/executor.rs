@@ -1,7 +1,7 @@
-use std::process::Command;
 use tracing::{debug, trace};
 
 use crate::constants::tmux_options;
+use crate::control_mode::SplitDirection;
 use crate::error::TmuxError;
 use crate::WindowType;
 
@@ -53,7 +53,15 @@ pub struct WindowInfo {
 }
 
 pub fn execute_tmux_command(args: &[&str]) -> Result<String> {
-    let output = crate::session::tmux_command().args(args).output()?;
+    execute_tmux_command_on(&crate::session::TmuxTransport::from_env(), args)
+}
+
+/// [`execute_tmux_command`] against the tmux server `transport` reaches.
+pub fn execute_tmux_command_on(
+    transport: &crate::session::TmuxTransport,
+    args: &[&str],
+) -> Result<String> {
+    let output = transport.command().args(args).output()?;
 
     if !output.status.success() {
         let stderr = String::from_utf8_lossy(&output.stderr).into_owned();
@@ -344,346 +352,26 @@ pub fn get_windows(session_name: &str) -> Result<Vec<WindowInfo>> {
     Ok(windows)
 }
 
-/// Capture the rendered tmux status line with ANSI escape sequences.
-/// Produces a full-width string with spaces between left+windows and right sections,
-/// matching tmux's actual rendered status bar output.
-pub fn capture_status_line(session_name: &str, width: usize) -> Result<String> {
-    // Get status-left-length and status-right-length from tmux options
-    let meta = execute_tmux_command(&[
-        "display-message",
-        "-t",
-        session_name,
-        "-p",
-        "#{status-left-length}\n#{status-right-length}",
-    ])?;
-    let meta_lines: Vec<&str> = meta.trim_end().lines().collect();
-    let max_left_len: usize = meta_lines
-        .first()
-        .and_then(|s| s.parse().ok())
-        .unwrap_or(30);
-    let max_right_len: usize = meta_lines.get(1).and_then(|s| s.parse().ok()).unwrap_or(50);
-
-    // Get status-left (rendered) - preserve trailing spaces from format
-    let left_raw = execute_tmux_command(&[
-        "display-message",
-        "-t",
-        session_name,
-        "-p",
-        "#{T:status-left}",
-    ])?;
-    let left_raw = left_raw.trim_end_matches('\n').to_string();
-
-    // Get window list - add separator space after each window format, then trim
-    // the trailing one (separator only goes between windows, not after the last)
-    let windows_raw = execute_tmux_command(&[
-        "display-message",
-        "-t",
-        session_name,
-        "-p",
-        "#{W:#{T:window-status-format} ,#{T:window-status-current-format} }",
-    ])?;
-    let windows_raw = windows_raw
-        .trim_end_matches('\n')
-        .strip_suffix(' ')
-        .unwrap_or(windows_raw.trim_end_matches('\n'))
-        .to_string();
-
-    // Get status-right: first get the raw format, evaluate #(cmd) patterns,
-    // then pass back through display-message for variable expansion
-    let right_format = execute_tmux_command(&[
+/// Read the tmux status line in one `display-message`, to render as ANSI
+/// text, segments and extra rows. This is the initial snapshot's copy; the
+/// monitor keeps it current through control mode (see
+/// `control_mode::status_line`), which also fills in `#()` jobs — they are
+/// blank here.
+pub(crate) fn capture_status_line(
+    session_name: &str,
+) -> Result<Option<crate::control_mode::status_line::StatusReply>> {
+    let output = execute_tmux_command(&[
         "display-message",
         "-t",
         session_name,
         "-p",
-        "#{status-right}",
+        &crate::control_mode::status_line::snapshot_format(),
     ])?;
-    let right_format = evaluate_shell_commands(right_format.trim_end_matches('\n'));
-    let right_raw =
-        execute_tmux_command(&["display-message", "-t", session_name, "-p", &right_format])?;
-    let right_raw = right_raw.trim_end_matches('\n').to_string();
-
-    // Convert tmux style codes to ANSI and unescape ## → #
-    let left_ansi = convert_tmux_style_to_ansi(&left_raw);
-    let windows_ansi = convert_tmux_style_to_ansi(&windows_raw);
-    let right_ansi = convert_tmux_style_to_ansi(&right_raw);
-
-    // Measure visible lengths (strip ANSI codes)
-    let left_visible_len = visible_len(&left_ansi).min(max_left_len);
-    let windows_visible_len = visible_len(&windows_ansi);
-    let right_visible_len = visible_len(&right_ansi).min(max_right_len);
-
-    // Truncate left/right sections to their max lengths if needed
-    let left_ansi = truncate_ansi(&left_ansi, max_left_len);
-    let right_ansi = truncate_ansi(&right_ansi, max_right_len);
-
-    // Calculate padding between left+windows and right
-    let left_windows_len = left_visible_len + windows_visible_len;
-    let padding = if left_windows_len + right_visible_len < width {
-        width - left_windows_len - right_visible_len
-    } else {
-        1 // At least one space separator
-    };
-
-    Ok(format!(
-        "{}{}{}{}",
-        left_ansi,
-        windows_ansi,
-        " ".repeat(padding),
-        right_ansi
+    Ok(crate::control_mode::status_line::StatusReply::parse(
+        &output,
     ))
 }
 
-/// Evaluate #(cmd) patterns in a tmux format string by running the shell commands
-fn evaluate_shell_commands(input: &str) -> String {
-    // When attached to a remote server (TMUXY_SSH), the `#(cmd)` snippets come
-    // from the REMOTE tmux config and must not run on the local host — doing so
-    // produces wrong results at best and executes remote-controlled command
-    // strings locally at worst. Skip evaluation (drop the `#(...)` segment)
-    // rather than shelling out.
-    let allow_local_exec = crate::session::ssh_target().is_none();
-    let mut result = String::new();
-    let mut chars = input.chars().peekable();
-
-    while let Some(c) = chars.next() {
-        if c == '#' && chars.peek() == Some(&'(') {
-            chars.next(); // consume '('
-            let mut cmd = String::new();
-            let mut depth = 1;
-            while let Some(&ch) = chars.peek() {
-                chars.next();
-                if ch == '(' {
-                    depth += 1;
-                    cmd.push(ch);
-                } else if ch == ')' {
-                    depth -= 1;
-                    if depth == 0 {
-                        break;
-                    }
-                    cmd.push(ch);
-                } else {
-                    cmd.push(ch);
-                }
-            }
-            // Execute the command and use its output (local server only).
-            if allow_local_exec {
-                if let Ok(output) = std::process::Command::new("sh")
-                    .arg("-c")
-                    .arg(&cmd)
-                    .output()
-                {
-                    let stdout = String::from_utf8_lossy(&output.stdout);
-                    result.push_str(stdout.trim_end_matches('\n'));
-                }
-            }
-        } else {
-            result.push(c);
-        }
-    }
-
-    result
-}
-
-/// Calculate visible length of a string (strips ANSI escape codes)
-fn visible_len(s: &str) -> usize {
-    let mut len = 0;
-    let mut in_escape = false;
-    for c in s.chars() {
-        if in_escape {
-            if c == 'm' {
-                in_escape = false;
-            }
-        } else if c == '\x1b' {
-            in_escape = true;
-        } else {
-            len += 1;
-        }
-    }
-    len
-}
-
-/// Truncate a string with ANSI codes to a maximum visible length
-fn truncate_ansi(s: &str, max_visible: usize) -> String {
-    let mut result = String::new();
-    let mut visible_count = 0;
-    let mut in_escape = false;
-
-    for c in s.chars() {
-        if in_escape {
-            result.push(c);
-            if c == 'm' {
-                in_escape = false;
-            }
-        } else if c == '\x1b' {
-            in_escape = true;
-            result.push(c);
-        } else {
-            if visible_count >= max_visible {
-                break;
-            }
-            result.push(c);
-            visible_count += 1;
-        }
-    }
-
-    result
-}
-
-/// Convert tmux style codes like #[fg=#89b4fa,bold] to ANSI escape codes.
-/// Also unescapes ## → # (tmux's escape for literal # in format output).
-fn convert_tmux_style_to_ansi(input: &str) -> String {
-    let mut result = String::new();
-    let mut chars = input.chars().peekable();
-
-    while let Some(c) = chars.next() {
-        if c == '#' {
-            match chars.peek() {
-                Some(&'[') => {
-                    // Parse tmux style code #[...]
-                    chars.next(); // consume '['
-                    let mut style = String::new();
-                    while let Some(&ch) = chars.peek() {
-                        if ch == ']' {
-                            chars.next();
-                            break;
-                        }
-                        // peek() returned Some, so next() is guaranteed Some.
-                        if let Some(c) = chars.next() {
-                            style.push(c);
-                        }
-                    }
-                    let ansi = tmux_style_to_ansi(&style);
-                    result.push_str(&ansi);
-                }
-                Some(&'#') => {
-                    // ## is tmux's escape for a literal #
-                    chars.next(); // consume second '#'
-                    result.push('#');
-                }
-                _ => {
-                    result.push(c);
-                }
-            }
-        } else {
-            result.push(c);
-        }
-    }
-
-    result
-}
-
-/// Convert a single tmux style specification to ANSI escape sequence
-fn tmux_style_to_ansi(style: &str) -> String {
-    if style.is_empty() || style == "default" {
-        return "\x1b[0m".to_string();
-    }
-
-    let mut codes = Vec::new();
-
-    for part in style.split(',') {
-        let part = part.trim();
-
-        if part == "bold" {
-            codes.push("1".to_string());
-        } else if part == "dim" {
-            codes.push("2".to_string());
-        } else if part == "italic" {
-            codes.push("3".to_string());
-        } else if part == "underscore" || part == "underline" {
-            codes.push("4".to_string());
-        } else if part == "blink" {
-            codes.push("5".to_string());
-        } else if part == "reverse" {
-            codes.push("7".to_string());
-        } else if part == "hidden" {
-            codes.push("8".to_string());
-        } else if part == "strikethrough" {
-            codes.push("9".to_string());
-        } else if part == "nobold" || part == "nodim" {
-            codes.push("22".to_string());
-        } else if part == "noitalic" {
-            codes.push("23".to_string());
-        } else if part == "nounderscore" || part == "nounderline" {
-            codes.push("24".to_string());
-        } else if part == "noblink" {
-            codes.push("25".to_string());
-        } else if part == "noreverse" {
-            codes.push("27".to_string());
-        } else if part == "nohidden" {
-            codes.push("28".to_string());
-        } else if part == "nostrikethrough" {
-            codes.push("29".to_string());
-        } else if let Some(color) = part.strip_prefix("fg=") {
-            if let Some(ansi) = color_to_ansi(color, true) {
-                codes.push(ansi);
-            }
-        } else if let Some(color) = part.strip_prefix("bg=") {
-            if let Some(ansi) = color_to_ansi(color, false) {
-                codes.push(ansi);
-            }
-        }
-    }
-
-    if codes.is_empty() {
-        String::new()
-    } else {
-        format!("\x1b[{}m", codes.join(";"))
-    }
-}
-
-/// Convert a tmux color specification to ANSI code
-fn color_to_ansi(color: &str, is_fg: bool) -> Option<String> {
-    let base = if is_fg { 38 } else { 48 };
-
-    if color == "default" {
-        return Some(if is_fg {
-            "39".to_string()
-        } else {
-            "49".to_string()
-        });
-    }
-
-    // Hex color: #RRGGBB
-    if let Some(hex) = color.strip_prefix('#') {
-        if hex.len() == 6 {
-            if let (Ok(r), Ok(g), Ok(b)) = (
-                u8::from_str_radix(&hex[0..2], 16),
-                u8::from_str_radix(&hex[2..4], 16),
-                u8::from_str_radix(&hex[4..6], 16),
-            ) {
-                return Some(format!("{};2;{};{};{}", base, r, g, b));
-            }
-        }
-    }
-
-    // Color index (0-255)
-    if let Ok(idx) = color.parse::<u8>() {
-        return Some(format!("{};5;{}", base, idx));
-    }
-
-    // Named colors
-    let color_code = match color.to_lowercase().as_str() {
-        "black" => Some(0),
-        "red" => Some(1),
-        "green" => Some(2),
-        "yellow" => Some(3),
-        "blue" => Some(4),
-        "magenta" => Some(5),
-        "cyan" => Some(6),
-        "white" => Some(7),
-        "brightblack" => Some(8),
-        "brightred" => Some(9),
-        "brightgreen" => Some(10),
-        "brightyellow" => Some(11),
-        "brightblue" => Some(12),
-        "brightmagenta" => Some(13),
-        "brightcyan" => Some(14),
-        "brightwhite" => Some(15),
-        _ => None,
-    };
-
-    color_code.map(|idx| format!("{};5;{}", base, idx))
-}
-
 /// Execute a tmux command string, ensuring it targets the specified session.
 /// This function automatically adds session targeting to commands that need it,
 /// making it nearly impossible to accidentally affect the wrong session.
@@ -691,6 +379,20 @@ fn color_to_ansi(color: &str, is_fg: bool) -> Option<String> {
 /// Commands that operate on panes/windows will be targeted to the session.
 /// Pane IDs (%N) and window IDs (@N) are validated to belong to the session.
 pub fn run_tmux_command_for_session(session_name: &str, cmd: &str) -> Result<String> {
+    run_tmux_command_for_session_on(
+        &crate::session::TmuxTransport::from_env(),
+        session_name,
+        cmd,
+    )
+}
+
+/// [`run_tmux_command_for_session`] against the tmux server `transport`
+/// reaches.
+pub fn run_tmux_command_for_session_on(
+    transport: &crate::session::TmuxTransport,
+    session_name: &str,
+    cmd: &str,
+) -> Result<String> {
     if cmd.trim().is_empty() {
         return Err(TmuxError::other("Empty command"));
     }
@@ -729,18 +431,21 @@ pub fn run_tmux_command_for_session(session_name: &str, cmd: &str) -> Result<Str
         "respawn-window",
     ];
 
-    // Process compound commands (split by \;)
-    let processed_cmd = process_compound_command(session_name, cmd, SESSION_TARGETED_COMMANDS)?;
+    // Process compound commands (split by `;`, `\;` or newlines)
+    let commands = process_compound_command(session_name, cmd, SESSION_TARGETED_COMMANDS)?;
+    if commands.is_empty() {
+        return Err(TmuxError::other("Empty command"));
+    }
 
-    // Use shell to handle command parsing. We pass the resolved tmux path
-    // (plus -L socket if set) instead of bare `tmux`, because launchd-spawned
-    // GUI apps inherit a sparse PATH that does NOT include Homebrew dirs.
-    // A bare `tmux` would fail with "command not found" and the user would
-    // see typing/operations silently no-op.
-    let tmux_bin = crate::session::tmux_bin();
-    let output = Command::new("sh")
-        .args(["-c", &format!("{} {}", tmux_bin, processed_cmd)])
-        .output()?;
+    // Run tmux directly with the parsed argv: no shell sees the client's
+    // text, so `$(…)`, `&&` or a stray quote can't do more than fail the
+    // tmux command. Over ssh the remote shell re-joins the argv, so each
+    // argument is quoted for it.
+    let mut args = tmux_argv(&commands);
+    if transport.is_remote() {
+        args = args.iter().map(|arg| tmux_quote(arg)).collect();
+    }
+    let output = transport.command().args(&args).output()?;
 
     if !output.status.success() {
         let stderr = String::from_utf8_lossy(&output.stderr).into_owned();
@@ -770,64 +475,255 @@ pub fn tmux_quote(value: &str) -> String {
 /// Shared by the SSE server and the Tauri app so the rewrite shape and the
 /// window tag can't drift apart between transports.
 pub fn new_window_rewrite(session: &str, size: Option<(u32, u32)>) -> String {
+    new_window_with_command(session, size, None, None, None)
+}
+
+/// [`new_window_rewrite`] with the new window's pane started in `cwd`
+/// running `command`. Either left out falls back to tmux's own choice: the
+/// split pane's directory, and the session's `default-command`. With
+/// `sandbox`, the pane runs inside that profile (see [`sandboxed`]).
+pub fn new_window_with_command(
+    session: &str,
+    size: Option<(u32, u32)>,
+    cwd: Option<&str>,
+    command: Option<&str>,
+    sandbox: Option<&str>,
+) -> String {
     let session = tmux_quote(session);
-    let tag = format!(
+    let spawn = spawn_args(cwd, command, sandbox);
+    let mut tag = format!(
         "set-option -w {} {}",
         tmux_options::WINDOW_TYPE,
         WindowType::Tab.as_str()
     );
+    tag.push_str(&sandbox_tag(sandbox));
     match size {
         Some((cols, rows)) => {
-            format!("splitw -t {session} ; breakp ; resizew -x {cols} -y {rows} ; {tag}")
+            format!("splitw -t {session}{spawn} ; breakp ; resizew -x {cols} -y {rows} ; {tag}")
         }
-        None => format!("splitw -t {session} ; breakp ; {tag}"),
+        None => format!("splitw -t {session}{spawn} ; breakp ; {tag}"),
     }
 }
 
-/// Split a compound tmux command on the `\;` separators that are *outside*
-/// quotes.
+/// Build the `split-window` that splits `pane_id` with the new pane started
+/// in `cwd` running `command`, falling back and sandboxed as
+/// [`new_window_with_command`] does.
+pub fn split_with_command(
+    pane_id: &str,
+    direction: SplitDirection,
+    cwd: Option<&str>,
+    command: Option<&str>,
+    sandbox: Option<&str>,
+) -> String {
+    let flag = match direction {
+        SplitDirection::Horizontal => "-h",
+        SplitDirection::Vertical => "-v",
+    };
+    format!(
+        "split-window {flag} -t {}{}{}",
+        tmux_quote(pane_id),
+        spawn_args(cwd, command, sandbox),
+        sandbox_tag(sandbox)
+    )
+}
+
+/// The ` -c <cwd> <command>` tail of a pane-creating command. Both come from
+/// clients, so they are quoted with [`crate::command_line::quote`]: a raw
+/// newline would otherwise end the control-mode command early.
+fn spawn_args(cwd: Option<&str>, command: Option<&str>, sandbox: Option<&str>) -> String {
+    let mut args = String::new();
+    if let Some(cwd) = cwd.filter(|c| !c.is_empty()) {
+        args.push_str(" -c ");
+        args.push_str(&crate::command_line::quote(cwd));
+    }
+    let command = command.filter(|c| !c.trim().is_empty());
+    let command = match sandbox {
+        Some(profile) => Some(sandboxed(profile, command)),
+        None => command.map(str::to_string),
+    };
+    if let Some(command) = command {
+        args.push(' ');
+        args.push_str(&crate::command_line::quote(&command));
+    }
+    args
+}
+
+/// `command` (or, without one, the user's shell) run inside sandbox
+/// `profile` by the bundled `sandbox-exec`, as one shell command line.
+fn sandboxed(profile: &str, command: Option<&str>) -> String {
+    let script = crate::session::bin_dir().join("tmuxy").join("sandbox-exec");
+    let mut argv = vec![
+        "bash".to_string(),
+        script.to_string_lossy().into_owned(),
+        profile.to_string(),
+    ];
+    if let Some(command) = command {
+        argv.extend(["sh".to_string(), "-c".to_string(), command.to_string()]);
+    }
+    argv.iter()
+        .map(|arg| tmux_quote(arg))
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// The ` ; set-option -p @tmuxy-sandbox <profile>` marking the new (current)
+/// pane as sandboxed, as `tmuxy pane float --sandbox` does.
+fn sandbox_tag(sandbox: Option<&str>) -> String {
+    sandbox
+        .map(|profile| {
+            format!(
+                " ; set-option -p {} {}",
+                tmux_options::SANDBOX,
+                tmux_quote(profile)
+            )
+        })
+        .unwrap_or_default()
+}
+
+/// Variables that describe the old pane itself rather than its environment;
+/// tmux sets them afresh for the new pane.
+const DUPLICATE_ENV_SKIP: &[&str] = &["TMUX", "TMUX_PANE", "PWD", "OLDPWD", "SHLVL", "_"];
+
+/// Build the `split-window` that duplicates `pane_id`: same cwd, the pane's
+/// own environment, and (with `rerun`) the command running in it.
 ///
-/// A plain `cmd.split("\\;")` also splits inside quoted payloads, so
-/// `send-keys -l 'a\;b'` was torn into two bogus commands.
-fn split_compound(cmd: &str) -> Vec<String> {
-    let mut parts = Vec::new();
-    let mut current = String::new();
-    let mut in_single = false;
-    let mut in_double = false;
-    let mut chars = cmd.chars().peekable();
-
-    while let Some(c) = chars.next() {
-        match c {
-            '\'' if !in_double => {
-                in_single = !in_single;
-                current.push(c);
-            }
-            '"' if !in_single => {
-                in_double = !in_double;
-                current.push(c);
-            }
-            '\\' if !in_single && !in_double && chars.peek() == Some(&';') => {
-                chars.next();
-                parts.push(std::mem::take(&mut current));
-            }
-            _ => current.push(c),
+/// Metadata is read with external read-only queries (safe alongside control
+/// mode); the returned command is meant to be sent through the control-mode
+/// connection. The environment comes from the pane shell's
+/// `/proc/<pid>/environ` where the platform allows it; only entries that
+/// differ from the session's `show-environment` are passed as `-e`, because
+/// the new pane inherits the session environment anyway.
+pub fn duplicate_pane_command(pane_id: &str, rerun: bool) -> Result<String> {
+    let info = execute_tmux_command(&[
+        "display-message",
+        "-p",
+        "-t",
+        pane_id,
+        "#{pane_current_path}\t#{pane_pid}\t#{session_name}\t#{pane_start_command}",
+    ])?;
+    let mut fields = info.trim_end_matches('\n').split('\t');
+    let cwd = fields.next().unwrap_or_default().to_string();
+    let pid: u32 = fields.next().and_then(|p| p.parse().ok()).unwrap_or(0);
+    let session = fields.next().unwrap_or_default().to_string();
+    let start_command = fields.next().unwrap_or_default().to_string();
+
+    let session_env = execute_tmux_command(&["show-environment", "-t", &session])
+        .map(|out| parse_environment(&out))
+        .unwrap_or_default();
+    let pane_env = std::fs::read(format!("/proc/{pid}/environ"))
+        .map(|bytes| parse_environ(&bytes))
+        .unwrap_or_default();
+
+    let command = if rerun {
+        foreground_cmdline(pid).or_else(|| (!start_command.is_empty()).then_some(start_command))
+    } else {
+        None
+    };
+    Ok(build_duplicate_command(
+        pane_id,
+        &cwd,
+        &pane_env,
+        &session_env,
+        command.as_deref(),
+    ))
+}
+
+/// Assemble the duplicate `split-window` from already-gathered pane metadata.
+fn build_duplicate_command(
+    pane_id: &str,
+    cwd: &str,
+    pane_env: &[(String, String)],
+    session_env: &[(String, String)],
+    command: Option<&str>,
+) -> String {
+    let mut cmd = format!("split-window -t {}", tmux_quote(pane_id));
+    if !cwd.is_empty() {
+        cmd.push_str(&format!(" -c {}", tmux_quote(cwd)));
+    }
+    for (key, value) in pane_env {
+        if DUPLICATE_ENV_SKIP.contains(&key.as_str())
+            || session_env.iter().any(|(k, v)| k == key && v == value)
+        {
+            continue;
         }
+        cmd.push_str(&format!(" -e {}", tmux_quote(&format!("{key}={value}"))));
+    }
+    if let Some(command) = command {
+        cmd.push(' ');
+        cmd.push_str(&tmux_quote(command));
     }
-    parts.push(current);
-    parts
+    cmd
+}
+
+/// Parse `show-environment` output. Removed variables (`-NAME`) are skipped.
+fn parse_environment(output: &str) -> Vec<(String, String)> {
+    output
+        .lines()
+        .filter(|line| !line.starts_with('-'))
+        .filter_map(|line| line.split_once('='))
+        .map(|(k, v)| (k.to_string(), v.to_string()))
+        .collect()
+}
+
+/// Parse a NUL-separated `/proc/<pid>/environ` block.
+fn parse_environ(bytes: &[u8]) -> Vec<(String, String)> {
+    bytes
+        .split(|b| *b == 0)
+        .filter_map(|entry| std::str::from_utf8(entry).ok())
+        .filter_map(|entry| entry.split_once('='))
+        .map(|(k, v)| (k.to_string(), v.to_string()))
+        .collect()
+}
+
+/// Command line of the newest child of the pane shell — the foreground job
+/// in the common case. Linux-only; `None` when `/proc` can't tell us.
+fn foreground_cmdline(shell_pid: u32) -> Option<String> {
+    let children =
+        std::fs::read_to_string(format!("/proc/{shell_pid}/task/{shell_pid}/children")).ok()?;
+    let child = children.split_whitespace().last()?;
+    let raw = std::fs::read(format!("/proc/{child}/cmdline")).ok()?;
+    let args: Vec<&[u8]> = raw.split(|b| *b == 0).filter(|a| !a.is_empty()).collect();
+    if args.is_empty() {
+        return None;
+    }
+    // The whole line becomes one `shell-command` argument, so each word is
+    // shell-quoted inside it.
+    Some(
+        args.iter()
+            .map(|a| tmux_quote(&String::from_utf8_lossy(a)))
+            .collect::<Vec<_>>()
+            .join(" "),
+    )
+}
+
+/// Flatten parsed commands into the argv tmux takes after its own flags.
+///
+/// tmux reads a `;` argument, or an argument ending in an unescaped `;`, as
+/// the end of a command; a literal trailing `;` is written `\;`.
+fn tmux_argv(commands: &[Vec<String>]) -> Vec<String> {
+    let mut argv = Vec::new();
+    for (i, args) in commands.iter().enumerate() {
+        if i > 0 {
+            argv.push(";".to_string());
+        }
+        argv.extend(args.iter().map(|arg| match arg.strip_suffix(';') {
+            Some(head) => format!("{head}\\;"),
+            None => arg.clone(),
+        }));
+    }
+    argv
 }
 
 /// Is `-t` present as the target flag (rather than as literal payload)?
 ///
 /// `send-keys -l` switches the rest of the line to literal text, so a `-t`
 /// after it is content the user is typing, not a target selector.
-fn has_target_flag(parts: &[&str]) -> bool {
+fn has_target_flag(parts: &[impl AsRef<str>]) -> bool {
     for part in parts {
-        if *part == "-l" {
-            return false;
-        }
-        if *part == "-t" {
-            return true;
+        match part.as_ref() {
+            "-l" => return false,
+            "-t" => return true,
+            _ => {}
         }
     }
     false
@@ -838,83 +734,56 @@ fn process_compound_command(
     session_name: &str,
     cmd: &str,
     targeted_commands: &[&str],
-) -> Result<String> {
-    let parts = split_compound(cmd);
-
-    let mut processed_parts = Vec::new();
-
-    for part in &parts {
-        let part = part.trim();
-        if part.is_empty() {
-            continue;
-        }
-
-        let processed = add_session_target_if_needed(session_name, part, targeted_commands)?;
-        processed_parts.push(processed);
+) -> Result<Vec<Vec<String>>> {
+    let mut commands = crate::command_line::split_commands(cmd)
+        .map_err(|e| TmuxError::other(format!("{e} in tmux command: {cmd}")))?;
+    for args in &mut commands {
+        add_session_target_if_needed(session_name, args, targeted_commands);
     }
-
-    Ok(processed_parts.join(" \\; "))
+    Ok(commands)
 }
 
 /// Add session targeting to a single tmux command if needed
 fn add_session_target_if_needed(
     session_name: &str,
-    cmd: &str,
+    args: &mut Vec<String>,
     targeted_commands: &[&str],
-) -> Result<String> {
-    let parts: Vec<&str> = cmd.split_whitespace().collect();
-    if parts.is_empty() {
-        return Ok(cmd.to_string());
-    }
-
-    let command_name = parts[0];
+) {
+    let Some(command_name) = args.first().cloned() else {
+        return;
+    };
 
     // Check if this command needs session targeting
-    if !targeted_commands.contains(&command_name) {
-        return Ok(cmd.to_string());
+    if !targeted_commands.contains(&command_name.as_str()) {
+        return;
     }
 
-    // Check if -t is already specified
-    let has_target = has_target_flag(&parts);
-
-    if has_target {
+    if has_target_flag(args) {
         // Validate and potentially fix existing targets
-        return validate_and_fix_target(session_name, cmd, command_name);
+        validate_and_fix_target(session_name, args, &command_name);
+        return;
     }
 
-    // Add session targeting based on command type
-    match command_name {
-        "select-window" => {
-            // select-window needs session:window format
-            // Check if there's a window index/id argument
-            if let Some(window_arg) = find_window_arg(&parts) {
-                // If it's just a number, prepend session
-                if window_arg.parse::<u32>().is_ok() || window_arg.starts_with('@') {
-                    let target = format!("{}:{}", session_name, window_arg);
-                    // replacen: rewrite only the argument occurrence, not
-                    // every later occurrence of the same substring.
-                    return Ok(cmd.replacen(
-                        &format!(" {}", window_arg),
-                        &format!(" -t {}", target),
-                        1,
-                    ));
-                }
-            }
-            // Default: add -t session_name
-            Ok(format!("{} -t {}", cmd, session_name))
+    // select-window needs session:window format, so a window index/id
+    // argument becomes the target.
+    if command_name == "select-window" {
+        if let Some(i) = find_window_arg(args) {
+            let target = format!("{}:{}", session_name, args[i]);
+            args.splice(i..=i, ["-t".to_string(), target]);
+            return;
         }
-        // Everything else — including resize-window and send-keys/send-prefix,
-        // which used to have their own byte-identical arms — defaults to
-        // targeting the session.
-        _ => Ok(format!("{} -t {}", cmd, session_name)),
     }
+
+    // Everything else targets the session. The flag goes right after the
+    // command name: appended, it would be typed as text after send-keys -l.
+    args.splice(1..1, ["-t".to_string(), session_name.to_string()]);
 }
 
-/// Find a window argument in command parts (index or @id)
-fn find_window_arg<'a>(parts: &'a [&'a str]) -> Option<&'a str> {
+/// Find the index of a window argument (index or @id) in command parts
+fn find_window_arg(parts: &[String]) -> Option<usize> {
     // Look for a bare number or @id that's not a flag value
     let mut prev_was_flag = false;
-    for part in parts.iter().skip(1) {
+    for (i, part) in parts.iter().enumerate().skip(1) {
         if part.starts_with('-') {
             prev_was_flag = true;
             continue;
@@ -925,38 +794,29 @@ fn find_window_arg<'a>(parts: &'a [&'a str]) -> Option<&'a str> {
         }
         // This might be a window argument
         if part.parse::<u32>().is_ok() || part.starts_with('@') {
-            return Some(part);
+            return Some(i);
         }
     }
     None
 }
 
 /// Validate that targets in the command belong to our session, and fix if needed
-fn validate_and_fix_target(session_name: &str, cmd: &str, command_name: &str) -> Result<String> {
+fn validate_and_fix_target(session_name: &str, args: &mut [String], command_name: &str) {
     // For commands with -t, check if the target includes the session
     // If it's just a pane ID (%N) or window ID (@N), those are global and fine
     // If it's a window index without session (e.g., :1234), prepend the session
-
-    let parts: Vec<&str> = cmd.split_whitespace().collect();
-    let mut new_parts: Vec<String> = Vec::new();
     let mut i = 0;
-
-    while i < parts.len() {
-        if parts[i] == "-t" && i + 1 < parts.len() {
-            let target = parts[i + 1];
-            new_parts.push("-t".to_string());
-
-            // Check if target needs session prefix
-            let fixed_target = fix_target_session(session_name, target, command_name);
-            new_parts.push(fixed_target);
-            i += 2;
-            continue;
+    while i + 1 < args.len() {
+        match args[i].as_str() {
+            // Anything after send-keys -l is text, not flags.
+            "-l" => break,
+            "-t" => {
+                args[i + 1] = fix_target_session(session_name, &args[i + 1], command_name);
+                i += 2;
+            }
+            _ => i += 1,
         }
-        new_parts.push(parts[i].to_string());
-        i += 1;
     }
-
-    Ok(new_parts.join(" "))
 }
 
 /// Fix a target string to include session name if needed
@@ -1000,16 +860,16 @@ fn fix_target_session(session_name: &str, target: &str, command_name: &str) -> S
     target.to_string()
 }
 
-/// Key binding info returned by get_prefix_bindings
-#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
-pub struct KeyBinding {
-    pub key: String,
-    pub command: String,
-    pub description: String,
-    /// Whether this binding has the `-r` (repeat) flag.
-    /// Repeat bindings auto-re-enter prefix mode after execution.
-    #[serde(default)]
-    pub repeat: bool,
+pub use tmuxy_protocol::{KeyBinding, KeyBindings};
+
+/// Snapshot the live tmux bindings, falling back to the `C-b` prefix and no
+/// bindings when tmux can't be asked.
+pub fn get_key_bindings() -> KeyBindings {
+    KeyBindings {
+        prefix_key: get_prefix_key().unwrap_or_else(|_| "C-b".into()),
+        prefix_bindings: get_prefix_bindings().unwrap_or_default(),
+        root_bindings: get_root_bindings().unwrap_or_default(),
+    }
 }
 
 /// Get all prefix key bindings from tmux
@@ -1125,6 +985,36 @@ pub fn get_root_bindings() -> Result<Vec<KeyBinding>> {
     Ok(parse_bindings("root", &output))
 }
 
+/// Bindings in the tmuxy-only key table (`bind -T tmuxy`); empty when the
+/// config defines none (tmux then has no such table).
+pub fn get_tmuxy_bindings() -> Vec<KeyBinding> {
+    let table = crate::key_table::TMUXY_KEY_TABLE;
+    execute_tmux_command(&["list-keys", "-T", table])
+        .map(|output| parse_bindings(table, &output))
+        .unwrap_or_default()
+}
+
+/// How long a `-r` binding keeps the prefix table active (`repeat-time`).
+pub fn get_repeat_time() -> Result<std::time::Duration> {
+    let output = execute_tmux_command(&["show-options", "-gv", "repeat-time"])?;
+    Ok(output
+        .trim()
+        .parse()
+        .map(std::time::Duration::from_millis)
+        .unwrap_or(crate::key_table::DEFAULT_REPEAT_TIME))
+}
+
+/// The live prefix key and key tables, with the standard fallbacks.
+pub fn get_key_tables() -> crate::key_table::KeyTables {
+    crate::key_table::KeyTables::new(
+        &get_prefix_key().unwrap_or_else(|_| "C-b".into()),
+        get_prefix_bindings().unwrap_or_default(),
+        get_root_bindings().unwrap_or_default(),
+        get_tmuxy_bindings(),
+        get_repeat_time().unwrap_or(crate::key_table::DEFAULT_REPEAT_TIME),
+    )
+}
+
 #[cfg(test)]
 #[allow(clippy::unwrap_used, clippy::expect_used)]
 mod tests {
@@ -1187,32 +1077,169 @@ bind-key    -T prefix \\% send-keys %";
     }
 
     #[test]
-    fn split_compound_respects_quotes() {
-        // Unquoted separators split.
+    fn new_window_with_command_starts_the_split_in_cwd() {
+        let out =
+            new_window_with_command("tmuxy", None, Some("/srv/my app"), Some("ssh box"), None);
+        assert!(
+            out.starts_with("splitw -t 'tmuxy' -c '/srv/my app' 'ssh box' ; breakp ;"),
+            "{out}"
+        );
         assert_eq!(
-            split_compound("splitw \\; breakp"),
-            vec!["splitw ".to_string(), " breakp".to_string()]
+            new_window_with_command("tmuxy", None, Some(""), Some(" "), None),
+            new_window_rewrite("tmuxy", None)
         );
+    }
+
+    #[test]
+    fn split_with_command_keeps_each_argument_one_word() {
+        assert_eq!(
+            split_with_command("%3", SplitDirection::Horizontal, Some("/tmp"), None, None),
+            "split-window -h -t '%3' -c /tmp"
+        );
+        // A newline would end the control-mode command and run the rest.
+        let out = split_with_command(
+            "%3",
+            SplitDirection::Vertical,
+            None,
+            Some("top\nkill-server"),
+            None,
+        );
+        assert_eq!(out, r#"split-window -v -t '%3' "top\nkill-server""#);
+    }
+
+    #[test]
+    fn sandboxed_splits_run_through_sandbox_exec_and_tag_the_pane() {
+        let out = split_with_command(
+            "%3",
+            SplitDirection::Horizontal,
+            None,
+            Some("make test"),
+            Some("bwrap"),
+        );
+        let words = crate::command_line::split_commands(&out).unwrap();
+        assert_eq!(words.len(), 2, "{out}");
+        let program = words[0].last().unwrap();
+        assert!(
+            program.ends_with("/tmuxy/sandbox-exec' 'bwrap' 'sh' '-c' 'make test'"),
+            "{program}"
+        );
+        assert!(program.starts_with("'bash' '"), "{program}");
+        assert_eq!(words[1], ["set-option", "-p", "@tmuxy-sandbox", "bwrap"]);
+
+        // Without a command the profile runs the user's shell; the tag comes
+        // after the window's own.
+        let out = new_window_with_command("tmuxy", None, None, None, Some("firejail"));
+        let words = crate::command_line::split_commands(&out).unwrap();
+        let program = words[0].last().unwrap();
+        assert!(
+            program.ends_with("/tmuxy/sandbox-exec' 'firejail'"),
+            "{program}"
+        );
+        assert!(
+            out.ends_with("@tmuxy-window-type tab ; set-option -p @tmuxy-sandbox 'firejail'"),
+            "{out}"
+        );
+    }
+
+    /// `cmd` as processed for session `tmuxy`, one string per command.
+    fn processed(cmd: &str, targeted: &[&str]) -> Vec<String> {
+        process_compound_command("tmuxy", cmd, targeted)
+            .unwrap()
+            .iter()
+            .map(|args| args.join(" "))
+            .collect()
+    }
+
+    #[test]
+    fn compound_commands_split_outside_quotes() {
+        // Unquoted separators split.
+        assert_eq!(processed("splitw \\; breakp", &[]), ["splitw", "breakp"]);
         // A separator inside single quotes is payload, not a separator.
         assert_eq!(
-            split_compound("send-keys -l 'a\\;b'"),
-            vec!["send-keys -l 'a\\;b'".to_string()]
+            processed("send-keys -l 'a\\;b'", &[]),
+            ["send-keys -l a\\;b"]
         );
-        // Same for double quotes.
+        // Double quotes read `\;` as an escaped `;`.
         assert_eq!(
-            split_compound("send-keys -l \"a\\;b\""),
-            vec!["send-keys -l \"a\\;b\"".to_string()]
+            processed("send-keys -l \"a\\;b\"", &[]),
+            ["send-keys -l a;b"]
         );
         // Mixed: quoted payload preserved, real separator still splits.
         assert_eq!(
-            split_compound("send-keys -l 'a\\;b' \\; selectp -t %1"),
+            processed("send-keys -l 'a\\;b' \\; selectp -t %1", &[]),
+            ["send-keys -l a\\;b", "selectp -t %1"]
+        );
+        assert!(process_compound_command("tmuxy", "send-keys -l 'a", &[]).is_err());
+    }
+
+    #[test]
+    fn shell_syntax_reaches_tmux_as_plain_arguments() {
+        let commands = process_compound_command(
+            "tmuxy",
+            "send-keys -l '$(touch /tmp/x) && `id` | sh; echo \"hi\"' \\; send-keys Enter",
+            &["send-keys"],
+        )
+        .unwrap();
+        assert_eq!(
+            tmux_argv(&commands),
+            [
+                "send-keys",
+                "-t",
+                "tmuxy",
+                "-l",
+                "$(touch /tmp/x) && `id` | sh; echo \"hi\"",
+                ";",
+                "send-keys",
+                "-t",
+                "tmuxy",
+                "Enter",
+            ]
+        );
+    }
+
+    #[test]
+    fn tmux_argv_escapes_trailing_semicolons() {
+        let commands = vec![
+            vec!["send-keys".to_string(), "-l".into(), "a;".into()],
             vec![
-                "send-keys -l 'a\\;b' ".to_string(),
-                " selectp -t %1".to_string()
+                "send-keys".to_string(),
+                "-l".into(),
+                "b\\;".into(),
+                ";".into(),
+            ],
+        ];
+        assert_eq!(
+            tmux_argv(&commands),
+            [
+                "send-keys",
+                "-l",
+                "a\\;",
+                ";",
+                "send-keys",
+                "-l",
+                "b\\\\;",
+                "\\;"
             ]
         );
     }
 
+    #[test]
+    fn session_target_goes_before_literal_text() {
+        // Appended, `-t tmuxy` would be typed into the pane.
+        assert_eq!(
+            processed("send-keys -l 'ls -t'", &["send-keys"]),
+            ["send-keys -t tmuxy -l ls -t"]
+        );
+        assert_eq!(
+            processed("select-window 3", &["select-window"]),
+            ["select-window -t tmuxy:3"]
+        );
+        assert_eq!(
+            processed("select-window -T", &["select-window"]),
+            ["select-window -t tmuxy -T"]
+        );
+    }
+
     #[test]
     fn has_target_flag_ignores_literal_payload() {
         assert!(has_target_flag(&["select-pane", "-t", "%1"]));
@@ -1228,11 +1255,16 @@ bind-key    -T prefix \\% send-keys %";
         // Previously the quoted `\;` split this into two bogus commands.
         let out =
             process_compound_command("tmuxy", "send-keys -l 'a\\;b'", &["send-keys"]).unwrap();
-        assert!(
-            !out.contains("\\; b'"),
-            "quoted separator must not split the command: {out}"
+        assert_eq!(
+            out.len(),
+            1,
+            "quoted separator must not split the command: {out:?}"
+        );
+        assert_eq!(
+            out[0].last().map(String::as_str),
+            Some("a\\;b"),
+            "payload must survive"
         );
-        assert!(out.contains("'a\\;b'"), "payload must survive: {out}");
     }
 
     #[test]
@@ -1286,28 +1318,81 @@ bind-key    -T prefix \\% send-keys %";
         assert_eq!(fix_target_session("tmuxy", "5", "kill-window"), "tmuxy:5");
     }
 
+    fn fixed_target(cmd: &str) -> String {
+        let mut args: Vec<String> = cmd.split_whitespace().map(String::from).collect();
+        let command_name = args[0].clone();
+        validate_and_fix_target("tmuxy", &mut args, &command_name);
+        args.join(" ")
+    }
+
     #[test]
     fn test_validate_and_fix_target_new_window() {
         // new-window with :N target should get session prepended
-        let result =
-            validate_and_fix_target("tmuxy", "new-window -d -t :1234 -n \"test\"", "new-window")
-                .unwrap();
-        assert_eq!(result, "new-window -d -t tmuxy:1234 -n \"test\"");
+        assert_eq!(
+            fixed_target("new-window -d -t :1234 -n test"),
+            "new-window -d -t tmuxy:1234 -n test"
+        );
     }
 
     #[test]
     fn test_validate_and_fix_target_select_window() {
         // select-window with bare number should get session prepended
-        let result =
-            validate_and_fix_target("tmuxy", "select-window -t 5", "select-window").unwrap();
-        assert_eq!(result, "select-window -t tmuxy:5");
+        assert_eq!(
+            fixed_target("select-window -t 5"),
+            "select-window -t tmuxy:5"
+        );
     }
 
     #[test]
     fn test_validate_and_fix_target_pane_commands() {
         // Commands with pane IDs should not modify the target
-        let result =
-            validate_and_fix_target("tmuxy", "swap-pane -s %0 -t %1", "swap-pane").unwrap();
-        assert_eq!(result, "swap-pane -s %0 -t %1");
+        assert_eq!(
+            fixed_target("swap-pane -s %0 -t %1"),
+            "swap-pane -s %0 -t %1"
+        );
+        // A `-t` typed after send-keys -l is text, not a target.
+        assert_eq!(
+            fixed_target("send-keys -t 1 -l -t 2"),
+            "send-keys -t 1 -l -t 2"
+        );
+    }
+
+    #[test]
+    fn duplicate_command_keeps_cwd_and_pane_specific_env() {
+        let pane_env = vec![
+            ("PATH".to_string(), "/usr/bin".to_string()),
+            ("VIRTUAL_ENV".to_string(), "/src/app/.venv".to_string()),
+            ("TMUX_PANE".to_string(), "%3".to_string()),
+        ];
+        let session_env = vec![("PATH".to_string(), "/usr/bin".to_string())];
+        let cmd = build_duplicate_command("%3", "/src/it's", &pane_env, &session_env, None);
+        assert_eq!(
+            cmd,
+            r"split-window -t '%3' -c '/src/it'\''s' -e 'VIRTUAL_ENV=/src/app/.venv'"
+        );
+    }
+
+    #[test]
+    fn duplicate_command_appends_rerun_command() {
+        let cmd = build_duplicate_command("%1", "", &[], &[], Some("htop -d 5"));
+        assert_eq!(cmd, "split-window -t '%1' 'htop -d 5'");
+    }
+
+    #[test]
+    fn environment_parsers_skip_removed_and_malformed_entries() {
+        assert_eq!(
+            parse_environment("A=1\n-B\nC=x=y\n"),
+            vec![
+                ("A".to_string(), "1".to_string()),
+                ("C".to_string(), "x=y".to_string())
+            ]
+        );
+        assert_eq!(
+            parse_environ(b"A=1\0junk\0B=2\0"),
+            vec![
+                ("A".to_string(), "1".to_string()),
+                ("B".to_string(), "2".to_string())
+            ]
+        );
     }
 }
@@ -0,0 +1,519 @@
+//! Pane export as an image (`export_pane`): a pane's cells drawn as SVG
+//! (selectable text, scales cleanly) or PNG (rasterized with the DejaVu Sans
+//! Mono bundled under `assets/fonts`, so it looks the same wherever it's
+//! opened).
+//!
+//! The image can't follow the viewer's client theme: palette colors resolve
+//! with the xterm defaults and unset colors with a fixed dark scheme.
+
+use crate::color_transform::to_rgb;
+use crate::{CellStyle, PaneContent, TerminalCell, TerminalLine};
+use base64::Engine;
+use fontdue::{Font, FontSettings, Metrics};
+use serde::Serialize;
+use std::collections::HashMap;
+use std::fmt::Write as _;
+use std::io;
+use std::sync::OnceLock;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+pub use tmuxy_protocol::ExportFormat;
+
+/// Most lines one export draws; a PNG of a whole 50k-line history would
+/// need gigabytes of pixels.
+pub const MAX_ROWS: usize = 2000;
+
+/// CSS font stack for the text formats, the bundled font first.
+pub(crate) const FONT_FAMILY: &str = "'DejaVu Sans Mono', Menlo, Consolas, monospace";
+/// Font size in pixels.
+const FONT_SIZE: f32 = 14.0;
+/// Space around the grid, in pixels.
+const PADDING: usize = 12;
+/// SVG cell size: DejaVu Sans Mono's advance (0.602 em) and a 1.2 line height.
+const SVG_CELL_WIDTH: f32 = 8.43;
+const SVG_LINE_HEIGHT: f32 = 17.0;
+/// Horizontal shift per pixel of height for synthesized italics.
+const ITALIC_SLANT: f32 = 0.2;
+
+pub(crate) const DEFAULT_FG: Rgb = (229, 229, 229);
+pub(crate) const DEFAULT_BG: Rgb = (24, 24, 27);
+
+const REGULAR: &[u8] = include_bytes!("../assets/fonts/DejaVuSansMono.ttf");
+const BOLD: &[u8] = include_bytes!("../assets/fonts/DejaVuSansMono-Bold.ttf");
+
+pub(crate) type Rgb = (u8, u8, u8);
+
+/// A rendered export, as `export_pane` returns it to the client.
+#[derive(Debug, Clone, Serialize)]
+pub struct PaneExport {
+    /// Suggested download name, e.g. `tmuxy-pane-3-1760000000.png`.
+    pub filename: String,
+    pub mime: &'static str,
+    /// The image, base64-encoded.
+    pub data: String,
+}
+
+/// Render `content` (a pane `cols` wide) and package it for download.
+pub fn export(
+    pane_id: &str,
+    content: &PaneContent,
+    cols: usize,
+    format: ExportFormat,
+) -> io::Result<PaneExport> {
+    let content = trim_trailing_blank_lines(content);
+    if content.len() > MAX_ROWS {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!(
+                "{} lines is too many to export (max {MAX_ROWS})",
+                content.len()
+            ),
+        ));
+    }
+    let bytes = match format {
+        ExportFormat::Png => to_png(content, cols)?,
+        ExportFormat::Svg => to_svg(content, cols).into_bytes(),
+    };
+    let secs = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0);
+    Ok(PaneExport {
+        filename: format!(
+            "tmuxy-pane-{}-{secs}.{}",
+            pane_id.trim_start_matches('%'),
+            format.extension()
+        ),
+        mime: format.mime(),
+        data: base64::engine::general_purpose::STANDARD.encode(bytes),
+    })
+}
+
+/// A capture of the visible area ends with the empty rows below the prompt;
+/// they'd only pad the picture.
+pub(crate) fn trim_trailing_blank_lines(content: &[TerminalLine]) -> &[TerminalLine] {
+    let used = content
+        .iter()
+        .rposition(|line| line.iter().any(|cell| !is_blank(cell)))
+        .map_or(0, |last| last + 1);
+    &content[..used]
+}
+
+pub(crate) fn is_blank(cell: &TerminalCell) -> bool {
+    cell.char.trim().is_empty()
+        && cell
+            .style
+            .as_ref()
+            .is_none_or(|s| s.bg.is_none() && !s.inverse)
+}
+
+/// A cell's style with colors resolved and inverse/dim applied.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct Look {
+    pub(crate) fg: Rgb,
+    pub(crate) bg: Option<Rgb>,
+    pub(crate) bold: bool,
+    pub(crate) italic: bool,
+    pub(crate) underline: bool,
+    pub(crate) strikethrough: bool,
+}
+
+impl Look {
+    pub(crate) fn of(style: Option<&CellStyle>) -> Self {
+        let Some(style) = style else {
+            return Look {
+                fg: DEFAULT_FG,
+                bg: None,
+                bold: false,
+                italic: false,
+                underline: false,
+                strikethrough: false,
+            };
+        };
+        let mut fg = style.fg.as_ref().map_or(DEFAULT_FG, to_rgb);
+        let mut bg = style.bg.as_ref().map(to_rgb);
+        if style.inverse {
+            (fg, bg) = (bg.unwrap_or(DEFAULT_BG), Some(fg));
+        }
+        if style.dim {
+            fg = mix(fg, bg.unwrap_or(DEFAULT_BG), 0.5);
+        }
+        Look {
+            fg,
+            bg,
+            bold: style.bold,
+            italic: style.italic,
+            underline: style.underline,
+            strikethrough: style.strikethrough,
+        }
+    }
+}
+
+/// `from` moved `amount` (0..=1) of the way to `to`.
+fn mix(from: Rgb, to: Rgb, amount: f32) -> Rgb {
+    let channel = |a: u8, b: u8| (f32::from(a) + (f32::from(b) - f32::from(a)) * amount) as u8;
+    (
+        channel(from.0, to.0),
+        channel(from.1, to.1),
+        channel(from.2, to.2),
+    )
+}
+
+pub(crate) fn hex((r, g, b): Rgb) -> String {
+    format!("#{r:02x}{g:02x}{b:02x}")
+}
+
+pub(crate) fn escape_xml(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => out.push_str("&amp;"),
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            '"' => out.push_str("&quot;"),
+            // Not allowed in XML 1.0 at all.
+            c if c.is_control() => out.push(' '),
+            c => out.push(c),
+        }
+    }
+    out
+}
+
+/// Columns the grid needs: the pane width, or wider if a line overflows it.
+fn grid_cols(content: &[TerminalLine], cols: usize) -> usize {
+    content.iter().map(Vec::len).max().unwrap_or(0).max(cols)
+}
+
+/// Runs of cells sharing a look, as (first column, columns, look, text).
+/// Continuation cells of wide characters add a column but no text.
+fn runs(line: &[TerminalCell]) -> Vec<(usize, usize, Look, String)> {
+    let mut runs: Vec<(usize, usize, Look, String)> = Vec::new();
+    for (col, cell) in line.iter().enumerate() {
+        let look = Look::of(cell.style.as_ref());
+        match runs.last_mut() {
+            Some((_, len, last, text)) if *last == look => {
+                *len += 1;
+                text.push_str(&cell.char);
+            }
+            _ => runs.push((col, 1, look, cell.char.clone())),
+        }
+    }
+    runs
+}
+
+/// The pane as an SVG document. Each run is a `<text>` stretched to its
+/// columns with `textLength`, so the grid stays aligned even where the
+/// viewer substitutes another monospace font.
+pub fn to_svg(content: &[TerminalLine], cols: usize) -> String {
+    let cols = grid_cols(content, cols);
+    let pad = PADDING as f32;
+    let width = pad * 2.0 + cols as f32 * SVG_CELL_WIDTH;
+    let height = pad * 2.0 + content.len() as f32 * SVG_LINE_HEIGHT;
+    let mut svg = String::new();
+    let _ = write!(
+        svg,
+        "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{width:.0}\" height=\"{height:.0}\" \
+         viewBox=\"0 0 {width:.2} {height:.2}\" \
+         font-family=\"{FONT_FAMILY}\" font-size=\"{FONT_SIZE}\">\
+         <rect width=\"100%\" height=\"100%\" fill=\"{}\"/>",
+        hex(DEFAULT_BG)
+    );
+    for (row, line) in content.iter().enumerate() {
+        let top = pad + row as f32 * SVG_LINE_HEIGHT;
+        let line_runs = runs(line);
+        for (col, len, look, _) in &line_runs {
+            if let Some(bg) = look.bg {
+                let _ = write!(
+                    svg,
+                    "<rect x=\"{:.2}\" y=\"{top:.2}\" width=\"{:.2}\" height=\"{SVG_LINE_HEIGHT}\" fill=\"{}\"/>",
+                    pad + *col as f32 * SVG_CELL_WIDTH,
+                    *len as f32 * SVG_CELL_WIDTH,
+                    hex(bg)
+                );
+            }
+        }
+        // Text baseline at ~80% of the line, matching the font's ascent.
+        let baseline = top + SVG_LINE_HEIGHT * 0.8;
+        for (col, len, look, text) in &line_runs {
+            if text.trim().is_empty() {
+                continue;
+            }
+            let _ = write!(
+                svg,
+                "<text x=\"{:.2}\" y=\"{baseline:.2}\" textLength=\"{:.2}\" \
+                 lengthAdjust=\"spacingAndGlyphs\" xml:space=\"preserve\" fill=\"{}\"",
+                pad + *col as f32 * SVG_CELL_WIDTH,
+                *len as f32 * SVG_CELL_WIDTH,
+                hex(look.fg)
+            );
+            if look.bold {
+                svg.push_str(" font-weight=\"bold\"");
+            }
+            if look.italic {
+                svg.push_str(" font-style=\"italic\"");
+            }
+            match (look.underline, look.strikethrough) {
+                (true, true) => svg.push_str(" text-decoration=\"underline line-through\""),
+                (true, false) => svg.push_str(" text-decoration=\"underline\""),
+                (false, true) => svg.push_str(" text-decoration=\"line-through\""),
+                (false, false) => {}
+            }
+            let _ = write!(svg, ">{}</text>", escape_xml(text));
+        }
+    }
+    svg.push_str("</svg>");
+    svg
+}
+
+struct Fonts {
+    regular: Font,
+    bold: Font,
+}
+
+/// Parsing the fonts takes a few milliseconds: do it once.
+fn fonts() -> io::Result<&'static Fonts> {
+    static FONTS: OnceLock<Result<Fonts, String>> = OnceLock::new();
+    FONTS
+        .get_or_init(|| {
+            let load = |bytes| {
+                Font::from_bytes(bytes, FontSettings::default())
+                    .map_err(|e| format!("bundled font: {e}"))
+            };
+            Ok(Fonts {
+                regular: load(REGULAR)?,
+                bold: load(BOLD)?,
+            })
+        })
+        .as_ref()
+        .map_err(|e| io::Error::other(e.clone()))
+}
+
+/// An RGB canvas the glyphs are blended onto.
+struct Canvas {
+    width: usize,
+    height: usize,
+    pixels: Vec<u8>,
+}
+
+impl Canvas {
+    fn new(width: usize, height: usize, fill: Rgb) -> Self {
+        let pixels = [fill.0, fill.1, fill.2].repeat(width * height);
+        Canvas {
+            width,
+            height,
+            pixels,
+        }
+    }
+
+    /// Blend `color` over the pixel at (x, y) with `alpha` (0..=255).
+    fn blend(&mut self, x: isize, y: isize, color: Rgb, alpha: u8) {
+        let (Ok(x), Ok(y)) = (usize::try_from(x), usize::try_from(y)) else {
+            return;
+        };
+        if x >= self.width || y >= self.height || alpha == 0 {
+            return;
+        }
+        let i = (y * self.width + x) * 3;
+        let amount = f32::from(alpha) / 255.0;
+        let (r, g, b) = mix(
+            (self.pixels[i], self.pixels[i + 1], self.pixels[i + 2]),
+            color,
+            amount,
+        );
+        self.pixels[i..i + 3].copy_from_slice(&[r, g, b]);
+    }
+
+    fn fill(&mut self, x: usize, y: usize, width: usize, height: usize, color: Rgb) {
+        for py in y..(y + height).min(self.height) {
+            for px in x..(x + width).min(self.width) {
+                let i = (py * self.width + px) * 3;
+                self.pixels[i..i + 3].copy_from_slice(&[color.0, color.1, color.2]);
+            }
+        }
+    }
+}
+
+/// The pane as a PNG, drawn cell by cell with the bundled font. Italics are
+/// the regular glyphs slanted; cells holding a character the font lacks
+/// show its missing-glyph box.
+pub fn to_png(content: &[TerminalLine], cols: usize) -> io::Result<Vec<u8>> {
+    let fonts = fonts()?;
+    let line_metrics = fonts
+        .regular
+        .horizontal_line_metrics(FONT_SIZE)
+        .ok_or_else(|| io::Error::other("bundled font has no horizontal metrics"))?;
+    let ascent = line_metrics.ascent.ceil() as isize;
+    let cell_height = line_metrics.new_line_size.ceil() as usize;
+    let cell_width = fonts.regular.metrics('M', FONT_SIZE).advance_width.round() as usize;
+
+    let cols = grid_cols(content, cols);
+    let width = PADDING * 2 + cols * cell_width;
+    let height = PADDING * 2 + content.len() * cell_height;
+    let mut canvas = Canvas::new(width, height, DEFAULT_BG);
+    let mut glyphs: HashMap<(char, bool), (Metrics, Vec<u8>)> = HashMap::new();
+
+    for (row, line) in content.iter().enumerate() {
+        let top = PADDING + row * cell_height;
+        for (col, cell) in line.iter().enumerate() {
+            let look = Look::of(cell.style.as_ref());
+            let left = PADDING + col * cell_width;
+            if let Some(bg) = look.bg {
+                canvas.fill(left, top, cell_width, cell_height, bg);
+            }
+            let span = cell_width * usize::from(cell.width.max(1));
+            if look.underline {
+                let y = top + (ascent as usize + 2).min(cell_height - 1);
+                canvas.fill(left, y, span, 1, look.fg);
+            }
+            if look.strikethrough {
+                canvas.fill(left, top + cell_height / 2, span, 1, look.fg);
+            }
+
+            let Some(c) = cell.char.chars().next().filter(|c| !c.is_whitespace()) else {
+                continue;
+            };
+            let (metrics, bitmap) = glyphs.entry((c, look.bold)).or_insert_with(|| {
+                let font = if look.bold {
+                    &fonts.bold
+                } else {
+                    &fonts.regular
+                };
+                font.rasterize(c, FONT_SIZE)
+            });
+            // Bitmap rows run top-down from the glyph's top edge.
+            let glyph_top = top as isize + ascent - (metrics.ymin + metrics.height as i32) as isize;
+            let glyph_left = left as isize + metrics.xmin as isize;
+            for gy in 0..metrics.height {
+                let slant = if look.italic {
+                    let above_baseline = ascent - (glyph_top - top as isize + gy as isize);
+                    (above_baseline as f32 * ITALIC_SLANT).round() as isize
+                } else {
+                    0
+                };
+                for gx in 0..metrics.width {
+                    canvas.blend(
+                        glyph_left + gx as isize + slant,
+                        glyph_top + gy as isize,
+                        look.fg,
+                        bitmap[gy * metrics.width + gx],
+                    );
+                }
+            }
+        }
+    }
+
+    let mut out = Vec::new();
+    {
+        let mut encoder = png::Encoder::new(&mut out, width as u32, height as u32);
+        encoder.set_color(png::ColorType::Rgb);
+        encoder.set_depth(png::BitDepth::Eight);
+        let mut writer = encoder.write_header()?;
+        writer.write_image_data(&canvas.pixels)?;
+    }
+    Ok(out)
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used, clippy::expect_used)]
+mod tests {
+    use super::*;
+    use crate::CellColor;
+
+    fn line(text: &str, style: Option<CellStyle>) -> Vec<TerminalCell> {
+        text.chars()
+            .map(|c| TerminalCell {
+                char: c.to_string(),
+                style: style.clone(),
+                width: 1,
+            })
+            .collect()
+    }
+
+    fn red_bold() -> CellStyle {
+        CellStyle {
+            fg: Some(CellColor::Indexed(1)),
+            bold: true,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn svg_groups_runs_and_escapes_text() {
+        let mut first = line("a<b ", None);
+        first.extend(line("FAIL", Some(red_bold())));
+        let svg = to_svg(&[first], 10);
+        assert!(svg.starts_with("<svg "));
+        assert!(svg.contains(">a&lt;b </text>"));
+        assert!(svg.contains("fill=\"#cd0000\" font-weight=\"bold\">FAIL</text>"));
+        assert!(svg.ends_with("</svg>"));
+    }
+
+    #[test]
+    fn svg_draws_backgrounds_and_inverse() {
+        let inverse = CellStyle {
+            inverse: true,
+            ..Default::default()
+        };
+        let svg = to_svg(&[line("x", Some(inverse))], 1);
+        // The default foreground becomes the background rect...
+        assert!(svg.contains(&format!("fill=\"{}\"/>", hex(DEFAULT_FG))));
+        // ...and the text takes the default background.
+        assert!(svg.contains(&format!("fill=\"{}\">x</text>", hex(DEFAULT_BG))));
+    }
+
+    #[test]
+    fn png_is_sized_from_the_grid() {
+        let content = vec![line("hello", None), line("world", Some(red_bold()))];
+        let png = to_png(&content, 80).unwrap();
+        let decoder = png::Decoder::new(std::io::Cursor::new(png));
+        let reader = decoder.read_info().unwrap();
+        let info = reader.info();
+        assert!(info.width as usize > 80 * 7);
+        assert!(info.height as usize > 2 * 14);
+        assert!((info.height as usize) < 3 * 24 + PADDING * 2);
+    }
+
+    #[test]
+    fn png_draws_glyphs_in_the_foreground_color() {
+        let style = CellStyle {
+            fg: Some(CellColor::Rgb { r: 255, g: 0, b: 0 }),
+            ..Default::default()
+        };
+        let png = to_png(&[line("█", Some(style))], 1).unwrap();
+        let mut reader = png::Decoder::new(std::io::Cursor::new(png))
+            .read_info()
+            .unwrap();
+        let mut pixels = vec![0; reader.output_buffer_size().unwrap()];
+        reader.next_frame(&mut pixels).unwrap();
+        assert!(pixels.chunks(3).any(|p| p == [255, 0, 0]));
+    }
+
+    #[test]
+    fn export_trims_blank_rows_and_names_the_file() {
+        let content = vec![line("$ make", None), line("   ", None), vec![]];
+        let export = export("%3", &content, 20, ExportFormat::Svg).unwrap();
+        assert!(export.filename.starts_with("tmuxy-pane-3-"));
+        assert!(export.filename.ends_with(".svg"));
+        assert_eq!(export.mime, "image/svg+xml");
+        let svg = base64::engine::general_purpose::STANDARD
+            .decode(&export.data)
+            .unwrap();
+        let svg = String::from_utf8(svg).unwrap();
+        let height = format!("height=\"{:.0}\"", PADDING as f32 * 2.0 + SVG_LINE_HEIGHT);
+        assert!(svg.contains(&height), "{svg}");
+    }
+
+    #[test]
+    fn export_refuses_huge_ranges() {
+        let content = vec![line("x", None); MAX_ROWS + 1];
+        let err = export("%0", &content, 1, ExportFormat::Png).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidInput);
+    }
+
+    #[test]
+    fn format_parses_lowercase() {
+        let format: ExportFormat = serde_json::from_str("\"png\"").unwrap();
+        assert_eq!(format, ExportFormat::Png);
+        assert_eq!(ExportFormat::Svg.extension(), "svg");
+    }
+}
@@ -0,0 +1,387 @@
+//! Float windows as clients manage them.
+//!
+//! A float is a one-pane window tagged `@tmuxy-window-type float` and drawn
+//! over its parent window (`@tmuxy-float-parent`). What the UI needs to place
+//! it lives in `@tmuxy-float-*` window options: size, position, stacking
+//! order and whether it is toggled out of view. `bin/tmuxy/float-create`
+//! opens floats from a shell; [`FloatOp`] does the same for clients and adds
+//! moving, resizing, toggling and closing. Each operation is a single tmux
+//! command list, so no client ever sees a float with half its options set.
+//!
+//! Sizes and positions are in cells. With `snap`, they round to multiples of
+//! it so dragged floats line up. The float an operation touches goes to the
+//! top of the stack (`@tmuxy-float-z`).
+
+pub use tmuxy_protocol::FloatSpec;
+
+use crate::constants::tmux_options;
+use crate::error::{Result, TmuxError};
+use crate::executor::tmux_quote;
+use crate::WindowType;
+
+/// Smallest float that still fits a header and a prompt line.
+pub const FLOAT_MIN_WIDTH: u32 = 10;
+pub const FLOAT_MIN_HEIGHT: u32 = 3;
+
+/// Size of a new float when none is given, as `tmuxy pane float` has it.
+const DEFAULT_WIDTH: u32 = 60;
+const DEFAULT_HEIGHT: u32 = 40;
+
+const DRAWERS: [&str; 4] = ["left", "right", "top", "bottom"];
+const BACKDROPS: [&str; 3] = ["dim", "blur", "none"];
+
+/// An operation on a float, named by its pane.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FloatOp {
+    Create(FloatSpec),
+    /// Hide a float, or show a hidden one on top.
+    Toggle {
+        pane_id: String,
+    },
+    Move {
+        pane_id: String,
+        x: u32,
+        y: u32,
+        snap: Option<u32>,
+    },
+    Resize {
+        pane_id: String,
+        width: u32,
+        height: u32,
+        snap: Option<u32>,
+    },
+    Close {
+        pane_id: String,
+    },
+}
+
+/// A float the session has now.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FloatInfo {
+    pub window_id: String,
+    pub pane_id: String,
+    pub width: Option<u32>,
+    pub height: Option<u32>,
+    pub z: Option<u32>,
+    pub hidden: bool,
+}
+
+/// Where an operation runs: the session's floats, its active window and the
+/// window size floats are kept inside, if known.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct FloatContext<'a> {
+    pub floats: &'a [FloatInfo],
+    pub active_window: Option<&'a str>,
+    pub bounds: Option<(u32, u32)>,
+}
+
+impl FloatOp {
+    /// The tmux command list that carries out this operation.
+    pub fn to_command(&self, ctx: FloatContext<'_>) -> Result<String> {
+        match self {
+            FloatOp::Create(spec) => create_command(spec, ctx),
+            FloatOp::Toggle { pane_id } => {
+                let float = find(ctx.floats, pane_id)?;
+                let mut cmds = Vec::new();
+                if float.hidden {
+                    cmds.push(unset(pane_id, tmux_options::FLOAT_HIDDEN));
+                    cmds.extend(raise(ctx.floats, float));
+                } else {
+                    cmds.push(set(pane_id, tmux_options::FLOAT_HIDDEN, 1));
+                }
+                Ok(cmds.join(" ; "))
+            }
+            FloatOp::Move {
+                pane_id,
+                x,
+                y,
+                snap,
+            } => {
+                let float = find(ctx.floats, pane_id)?;
+                let (x, y) = (snap_to(*x, *snap), snap_to(*y, *snap));
+                let (x, y) = match ctx.bounds {
+                    Some((cols, rows)) => (
+                        x.min(cols.saturating_sub(float.width.unwrap_or(FLOAT_MIN_WIDTH))),
+                        y.min(rows.saturating_sub(float.height.unwrap_or(FLOAT_MIN_HEIGHT))),
+                    ),
+                    None => (x, y),
+                };
+                let mut cmds = vec![
+                    set(pane_id, tmux_options::FLOAT_X, x),
+                    set(pane_id, tmux_options::FLOAT_Y, y),
+                ];
+                cmds.extend(raise(ctx.floats, float));
+                Ok(cmds.join(" ; "))
+            }
+            FloatOp::Resize {
+                pane_id,
+                width,
+                height,
+                snap,
+            } => {
+                let float = find(ctx.floats, pane_id)?;
+                let (width, height) = size(*width, *height, *snap, ctx.bounds);
+                let mut cmds = vec![
+                    set(pane_id, tmux_options::FLOAT_WIDTH, width),
+                    set(pane_id, tmux_options::FLOAT_HEIGHT, height),
+                ];
+                cmds.extend(raise(ctx.floats, float));
+                Ok(cmds.join(" ; "))
+            }
+            FloatOp::Close { pane_id } => {
+                let float = find(ctx.floats, pane_id)?;
+                Ok(format!("kill-window -t {}", tmux_quote(&float.window_id)))
+            }
+        }
+    }
+}
+
+/// `splitw ; breakp` rather than `new-window`, which crashes tmux 3.5a with
+/// control mode attached. `breakp` makes the float the current window, so
+/// the untargeted `set -w`s tag it; the list ends back on the parent.
+fn create_command(spec: &FloatSpec, ctx: FloatContext<'_>) -> Result<String> {
+    let parent = spec
+        .parent
+        .as_deref()
+        .or(ctx.active_window)
+        .ok_or_else(|| TmuxError::other("no window to open the float over"))?;
+    if !parent.starts_with('@') || parent[1..].parse::<u32>().is_err() {
+        return Err(TmuxError::other(format!("invalid window id: {parent}")));
+    }
+    if let Some(drawer) = spec.drawer.as_deref().filter(|d| !DRAWERS.contains(d)) {
+        return Err(TmuxError::other(format!("invalid float drawer: {drawer}")));
+    }
+    if let Some(bg) = spec.bg.as_deref().filter(|bg| !BACKDROPS.contains(bg)) {
+        return Err(TmuxError::other(format!("invalid float backdrop: {bg}")));
+    }
+    // A drawer spans its edge, so only the size across it is set.
+    let (width, height) = match spec.drawer.as_deref() {
+        Some("left" | "right") => (Some(spec.width.unwrap_or(DEFAULT_WIDTH)), spec.height),
+        Some(_) => (spec.width, Some(spec.height.unwrap_or(DEFAULT_HEIGHT))),
+        None => (
+            Some(spec.width.unwrap_or(DEFAULT_WIDTH)),
+            Some(spec.height.unwrap_or(DEFAULT_HEIGHT)),
+        ),
+    };
+
+    let mut split = format!("splitw -t {}", tmux_quote(parent));
+    if let Some(command) = spec.command.as_deref().filter(|c| !c.trim().is_empty()) {
+        split.push(' ');
+        split.push_str(&tmux_quote(command));
+    }
+    let mut cmds = vec![
+        split,
+        "breakp -n float".to_string(),
+        format!(
+            "set -w {} {}",
+            tmux_options::WINDOW_TYPE,
+            WindowType::Float.as_str()
+        ),
+        format!("set -w {} {parent}", tmux_options::FLOAT_PARENT),
+    ];
+    let mut tag = |option: &str, value: u32| cmds.push(format!("set -w {option} {value}"));
+    if let Some(width) = width {
+        let (width, _) = size(width, FLOAT_MIN_HEIGHT, spec.snap, ctx.bounds);
+        tag(tmux_options::FLOAT_WIDTH, width);
+    }
+    if let Some(height) = height {
+        let (_, height) = size(FLOAT_MIN_WIDTH, height, spec.snap, ctx.bounds);
+        tag(tmux_options::FLOAT_HEIGHT, height);
+    }
+    if spec.drawer.is_none() {
+        if let (Some(x), Some(y)) = (spec.x, spec.y) {
+            tag(tmux_options::FLOAT_X, snap_to(x, spec.snap));
+            tag(tmux_options::FLOAT_Y, snap_to(y, spec.snap));
+        }
+    }
+    tag(tmux_options::FLOAT_Z, top_z(ctx.floats) + 1);
+    if let Some(drawer) = &spec.drawer {
+        cmds.push(format!("set -w {} {drawer}", tmux_options::FLOAT_DRAWER));
+    }
+    if let Some(bg) = &spec.bg {
+        cmds.push(format!("set -w {} {bg}", tmux_options::FLOAT_BG));
+    }
+    if spec.hide_header {
+        cmds.push(format!("set -w {} 1", tmux_options::FLOAT_NOHEADER));
+    }
+    cmds.push(format!("select-window -t {}", tmux_quote(parent)));
+    Ok(cmds.join(" ; "))
+}
+
+fn find<'a>(floats: &'a [FloatInfo], pane_id: &str) -> Result<&'a FloatInfo> {
+    floats
+        .iter()
+        .find(|f| f.pane_id == pane_id)
+        .ok_or_else(|| TmuxError::PaneNotFound {
+            id: pane_id.to_string(),
+        })
+}
+
+fn set(pane_id: &str, option: &str, value: u32) -> String {
+    format!("set -w -t {} {option} {value}", tmux_quote(pane_id))
+}
+
+fn unset(pane_id: &str, option: &str) -> String {
+    format!("set -wu -t {} {option}", tmux_quote(pane_id))
+}
+
+fn top_z(floats: &[FloatInfo]) -> u32 {
+    floats.iter().filter_map(|f| f.z).max().unwrap_or(0)
+}
+
+/// Put `float` above every other float, unless it already is.
+fn raise(floats: &[FloatInfo], float: &FloatInfo) -> Option<String> {
+    let top = top_z(floats);
+    let on_top = float.z == Some(top)
+        && floats
+            .iter()
+            .all(|f| f.pane_id == float.pane_id || f.z != Some(top));
+    (!on_top).then(|| set(&float.pane_id, tmux_options::FLOAT_Z, top + 1))
+}
+
+fn snap_to(value: u32, snap: Option<u32>) -> u32 {
+    match snap {
+        Some(grid) if grid > 1 => (value + grid / 2) / grid * grid,
+        _ => value,
+    }
+}
+
+fn size(width: u32, height: u32, snap: Option<u32>, bounds: Option<(u32, u32)>) -> (u32, u32) {
+    let (mut width, mut height) = (
+        snap_to(width, snap).max(FLOAT_MIN_WIDTH),
+        snap_to(height, snap).max(FLOAT_MIN_HEIGHT),
+    );
+    if let Some((cols, rows)) = bounds {
+        width = width.min(cols.max(FLOAT_MIN_WIDTH));
+        height = height.min(rows.max(FLOAT_MIN_HEIGHT));
+    }
+    (width, height)
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used, clippy::expect_used)]
+mod tests {
+    use super::*;
+
+    fn float(pane_id: &str, window_id: &str, z: Option<u32>, hidden: bool) -> FloatInfo {
+        FloatInfo {
+            window_id: window_id.to_string(),
+            pane_id: pane_id.to_string(),
+            width: Some(40),
+            height: Some(10),
+            z,
+            hidden,
+        }
+    }
+
+    #[test]
+    fn create_tags_the_new_window_in_one_list_and_stacks_it_on_top() {
+        let floats = [float("%5", "@5", Some(2), false)];
+        let spec = FloatSpec {
+            width: Some(41),
+            x: Some(7),
+            y: Some(3),
+            snap: Some(2),
+            bg: Some("blur".to_string()),
+            command: Some("htop -d 5".to_string()),
+            ..Default::default()
+        };
+        let ctx = FloatContext {
+            floats: &floats,
+            active_window: Some("@1"),
+            bounds: Some((120, 40)),
+        };
+        assert_eq!(
+            FloatOp::Create(spec).to_command(ctx).unwrap(),
+            "splitw -t '@1' 'htop -d 5' ; breakp -n float ; set -w @tmuxy-window-type float ; \
+             set -w @tmuxy-float-parent @1 ; set -w @tmuxy-float-width 42 ; \
+             set -w @tmuxy-float-height 40 ; set -w @tmuxy-float-x 8 ; set -w @tmuxy-float-y 4 ; \
+             set -w @tmuxy-float-z 3 ; set -w @tmuxy-float-bg blur ; select-window -t '@1'"
+        );
+    }
+
+    #[test]
+    fn create_rejects_what_the_ui_cannot_draw() {
+        let ctx = FloatContext::default();
+        assert!(FloatOp::Create(FloatSpec::default())
+            .to_command(ctx)
+            .is_err());
+        let spec = FloatSpec {
+            parent: Some("@1".to_string()),
+            drawer: Some("middle".to_string()),
+            ..Default::default()
+        };
+        assert!(FloatOp::Create(spec).to_command(ctx).is_err());
+        let spec = FloatSpec {
+            parent: Some("@1 ; kill-server".to_string()),
+            ..Default::default()
+        };
+        assert!(FloatOp::Create(spec).to_command(ctx).is_err());
+    }
+
+    #[test]
+    fn moving_snaps_clamps_and_raises() {
+        let floats = [
+            float("%5", "@5", Some(1), false),
+            float("%6", "@6", Some(2), false),
+        ];
+        let ctx = FloatContext {
+            floats: &floats,
+            active_window: None,
+            bounds: Some((100, 30)),
+        };
+        let op = FloatOp::Move {
+            pane_id: "%5".to_string(),
+            x: 97,
+            y: 9,
+            snap: Some(4),
+        };
+        assert_eq!(
+            op.to_command(ctx).unwrap(),
+            "set -w -t '%5' @tmuxy-float-x 60 ; set -w -t '%5' @tmuxy-float-y 8 ; \
+             set -w -t '%5' @tmuxy-float-z 3"
+        );
+        // The top float stays where it is in the stack.
+        let op = FloatOp::Resize {
+            pane_id: "%6".to_string(),
+            width: 2,
+            height: 200,
+            snap: None,
+        };
+        assert_eq!(
+            op.to_command(ctx).unwrap(),
+            "set -w -t '%6' @tmuxy-float-width 10 ; set -w -t '%6' @tmuxy-float-height 30"
+        );
+    }
+
+    #[test]
+    fn toggle_hides_then_shows_on_top_and_close_kills_the_window() {
+        let floats = [
+            float("%5", "@5", Some(4), true),
+            float("%6", "@6", Some(2), false),
+        ];
+        let ctx = FloatContext {
+            floats: &floats,
+            ..Default::default()
+        };
+        let toggle = |pane: &str| FloatOp::Toggle {
+            pane_id: pane.to_string(),
+        };
+        assert_eq!(
+            toggle("%6").to_command(ctx).unwrap(),
+            "set -w -t '%6' @tmuxy-float-hidden 1"
+        );
+        assert_eq!(
+            toggle("%5").to_command(ctx).unwrap(),
+            "set -wu -t '%5' @tmuxy-float-hidden"
+        );
+        let close = FloatOp::Close {
+            pane_id: "%6".to_string(),
+        };
+        assert_eq!(close.to_command(ctx).unwrap(), "kill-window -t '@6'");
+        assert!(matches!(
+            toggle("%9").to_command(ctx),
+            Err(TmuxError::PaneNotFound { .. })
+        ));
+    }
+}
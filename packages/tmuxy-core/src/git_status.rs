@@ -0,0 +1,296 @@
+//! A repository's status for the git widget: branch, upstream, ahead/behind
+//! and counts of changed files.
+//!
+//! Everything comes from one `git status --porcelain=v2 --branch -z` run,
+//! whose output is stable across git versions and locales, plus
+//! `rev-parse --show-toplevel` for the repository root.
+
+use serde::Serialize;
+use std::path::Path;
+use std::process::Command;
+
+/// Changed files listed per status; the counts cover all of them.
+pub const MAX_FILES: usize = 50;
+
+/// Status of the repository containing a directory.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct GitStatus {
+    /// Top-level directory of the work tree.
+    pub root: String,
+    /// Checked-out branch, `None` on a detached HEAD.
+    pub branch: Option<String>,
+    /// Abbreviated HEAD commit, empty before the first commit.
+    pub head: String,
+    /// Upstream branch (`origin/main`), when one is configured.
+    pub upstream: Option<String>,
+    /// Commits the branch has that its upstream doesn't.
+    pub ahead: u32,
+    /// Commits the upstream has that the branch doesn't.
+    pub behind: u32,
+    /// Files with changes in the index.
+    pub staged: u32,
+    /// Tracked files with changes not yet in the index.
+    pub modified: u32,
+    pub untracked: u32,
+    /// Files with unresolved merge conflicts.
+    pub conflicted: u32,
+    /// The first [`MAX_FILES`] changed files.
+    pub files: Vec<GitFile>,
+}
+
+/// One changed file: its path relative to the root and git's two-letter
+/// `XY` code (index, work tree; `.` for unchanged, `?` for untracked).
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct GitFile {
+    pub path: String,
+    pub status: String,
+}
+
+impl GitStatus {
+    pub fn is_clean(&self) -> bool {
+        self.staged == 0 && self.modified == 0 && self.untracked == 0 && self.conflicted == 0
+    }
+
+    /// One line for a plain terminal: `main ↑1 ↓2  +3 ~1 ?4 !1`, or
+    /// `main  clean`.
+    pub fn render(&self) -> String {
+        let mut line = match &self.branch {
+            Some(branch) => branch.clone(),
+            None if self.head.is_empty() => "(no commits)".to_string(),
+            None => format!("({})", self.head),
+        };
+        if self.ahead > 0 {
+            line.push_str(&format!(" ↑{}", self.ahead));
+        }
+        if self.behind > 0 {
+            line.push_str(&format!(" ↓{}", self.behind));
+        }
+        if self.is_clean() {
+            line.push_str("  clean");
+            return line;
+        }
+        line.push(' ');
+        for (count, mark) in [
+            (self.staged, '+'),
+            (self.modified, '~'),
+            (self.untracked, '?'),
+            (self.conflicted, '!'),
+        ] {
+            if count > 0 {
+                line.push_str(&format!(" {mark}{count}"));
+            }
+        }
+        line
+    }
+}
+
+/// Parse `git status --porcelain=v2 --branch -z`. `root` is left empty.
+pub fn parse_porcelain_v2(output: &str) -> GitStatus {
+    let mut status = GitStatus::default();
+    let mut fields = output.split('\0').filter(|field| !field.is_empty());
+    while let Some(field) = fields.next() {
+        if let Some(header) = field.strip_prefix("# ") {
+            let (key, value) = header.split_once(' ').unwrap_or((header, ""));
+            match key {
+                "branch.oid" if value != "(initial)" => {
+                    status.head = value.chars().take(7).collect();
+                }
+                "branch.head" if value != "(detached)" => status.branch = Some(value.to_string()),
+                "branch.upstream" => status.upstream = Some(value.to_string()),
+                "branch.ab" => {
+                    for part in value.split(' ') {
+                        if let Some(n) = part.strip_prefix('+') {
+                            status.ahead = n.parse().unwrap_or(0);
+                        } else if let Some(n) = part.strip_prefix('-') {
+                            status.behind = n.parse().unwrap_or(0);
+                        }
+                    }
+                }
+                _ => {}
+            }
+            continue;
+        }
+        // `<kind> <XY> …fields… <path>`: ordinary (1) and unmerged (u)
+        // entries have 8 and 10 fields before the path; renames (2) have 9
+        // and carry the original path as the next NUL-separated field.
+        let (kind, rest) = field.split_at_checked(2).unwrap_or((field, ""));
+        let path = match kind {
+            "1 " => rest.splitn(8, ' ').nth(7),
+            "2 " => {
+                fields.next();
+                rest.splitn(9, ' ').nth(8)
+            }
+            "u " => rest.splitn(10, ' ').nth(9),
+            "? " => Some(rest),
+            _ => None,
+        };
+        let code = if kind == "? " {
+            Some("??")
+        } else {
+            rest.get(..2)
+        };
+        let (Some(path), Some(code)) = (path, code) else {
+            continue;
+        };
+        match kind {
+            "u " => status.conflicted += 1,
+            "? " => status.untracked += 1,
+            _ => {
+                let mut xy = code.chars();
+                if xy.next().is_some_and(|x| x != '.') {
+                    status.staged += 1;
+                }
+                if xy.next().is_some_and(|y| y != '.') {
+                    status.modified += 1;
+                }
+            }
+        }
+        if status.files.len() < MAX_FILES {
+            status.files.push(GitFile {
+                path: path.to_string(),
+                status: code.to_string(),
+            });
+        }
+    }
+    status
+}
+
+/// Status of the repository containing `dir`, or `None` when `dir` isn't
+/// inside one. Fails only when git itself can't be run.
+pub fn git_status(dir: &Path) -> crate::TmuxResult<Option<GitStatus>> {
+    let git = |args: &[&str]| {
+        Command::new("git")
+            .arg("-C")
+            .arg(dir)
+            .args(args)
+            .output()
+            .map_err(|e| crate::TmuxError::other(format!("failed to run git: {e}")))
+    };
+    let root = git(&["rev-parse", "--show-toplevel"])?;
+    if !root.status.success() {
+        return Ok(None);
+    }
+    let output = git(&[
+        "--no-optional-locks",
+        "status",
+        "--porcelain=v2",
+        "--branch",
+        "-z",
+    ])?;
+    if !output.status.success() {
+        return Ok(None);
+    }
+    let mut status = parse_porcelain_v2(&String::from_utf8_lossy(&output.stdout));
+    status.root = String::from_utf8_lossy(&root.stdout).trim_end().to_string();
+    Ok(Some(status))
+}
+
+/// Files whose change means the status may have changed: the index and HEAD
+/// of the repository containing `dir`. Cheap to stat between full refreshes.
+pub fn watched_paths(dir: &Path) -> Vec<std::path::PathBuf> {
+    let Ok(output) = Command::new("git")
+        .arg("-C")
+        .arg(dir)
+        .args(["rev-parse", "--absolute-git-dir"])
+        .output()
+    else {
+        return Vec::new();
+    };
+    if !output.status.success() {
+        return Vec::new();
+    }
+    let git_dir = std::path::PathBuf::from(String::from_utf8_lossy(&output.stdout).trim_end());
+    vec![git_dir.join("index"), git_dir.join("HEAD")]
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used, clippy::expect_used)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn porcelain_v2_counts_each_kind_of_change() {
+        let output = [
+            "# branch.oid 1234567890abcdef",
+            "# branch.head main",
+            "# branch.upstream origin/main",
+            "# branch.ab +2 -1",
+            "1 M. N... 100644 100644 100644 aaa bbb src/lib.rs",
+            "1 .M N... 100644 100644 100644 aaa aaa docs/a file.md",
+            "1 MM N... 100644 100644 100644 aaa bbb both.rs",
+            "2 R. N... 100644 100644 100644 aaa aaa R100 new.rs",
+            "old.rs",
+            "u UU N... 100644 100644 100644 100644 aaa bbb ccc merge.rs",
+            "? notes.txt",
+            "! target",
+            "",
+        ]
+        .join("\0");
+        let status = parse_porcelain_v2(&output);
+        assert_eq!(status.branch.as_deref(), Some("main"));
+        assert_eq!(status.head, "1234567");
+        assert_eq!(status.upstream.as_deref(), Some("origin/main"));
+        assert_eq!((status.ahead, status.behind), (2, 1));
+        assert_eq!(
+            (
+                status.staged,
+                status.modified,
+                status.untracked,
+                status.conflicted
+            ),
+            (3, 2, 1, 1)
+        );
+        let files: Vec<_> = status
+            .files
+            .iter()
+            .map(|f| (f.status.as_str(), f.path.as_str()))
+            .collect();
+        assert_eq!(
+            files,
+            [
+                ("M.", "src/lib.rs"),
+                (".M", "docs/a file.md"),
+                ("MM", "both.rs"),
+                ("R.", "new.rs"),
+                ("UU", "merge.rs"),
+                ("??", "notes.txt"),
+            ]
+        );
+        assert_eq!(status.render(), "main ↑2 ↓1  +3 ~2 ?1 !1");
+    }
+
+    #[test]
+    fn detached_and_unborn_heads_have_no_branch() {
+        let detached = parse_porcelain_v2("# branch.oid abcdef0123\0# branch.head (detached)\0");
+        assert_eq!(detached.branch, None);
+        assert_eq!(detached.render(), "(abcdef0)  clean");
+
+        let unborn = parse_porcelain_v2("# branch.oid (initial)\0# branch.head main\0");
+        assert_eq!(unborn.head, "");
+        assert_eq!(unborn.render(), "main  clean");
+    }
+
+    #[test]
+    fn git_status_reads_a_real_repository() {
+        let dir = std::env::temp_dir().join(format!("tmuxy-git-status-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        assert_eq!(git_status(&dir).unwrap(), None);
+
+        let ok = Command::new("git")
+            .args(["init", "-q", "-b", "trunk"])
+            .current_dir(&dir)
+            .status()
+            .is_ok_and(|s| s.success());
+        if ok {
+            std::fs::write(dir.join("a.txt"), "a").unwrap();
+            let status = git_status(&dir).unwrap().expect("a repository");
+            assert_eq!(status.branch.as_deref(), Some("trunk"));
+            assert_eq!(status.untracked, 1);
+            assert!(status
+                .root
+                .ends_with(dir.file_name().unwrap().to_str().unwrap()));
+            assert_eq!(watched_paths(&dir).len(), 2);
+        }
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,385 @@
+//! Prefix handling on the server, from the user's own key tables.
+//!
+//! Keys sent with `key_input` go through the same state machine tmux runs
+//! for an attached terminal client. The prefix key switches the client to
+//! the `prefix` table; the next key runs its binding there (or is dropped
+//! when unbound). A binding made with `-r` keeps the client in the table for
+//! `repeat-time`, so `prefix o o o` cycles panes; a key that isn't a repeat
+//! binding ends the repeat and is handled from `root` instead. In `root`,
+//! `bind -n` bindings run and every other key goes to the pane.
+//!
+//! The tables come from `list-keys`, so a tmux.conf that moves the prefix,
+//! rebinds `%` or adds its own bindings works the same as in a terminal.
+//! A third table, [`TMUXY_KEY_TABLE`], holds bindings for tmuxy clients
+//! only (`bind -T tmuxy C-S-t new-window` in tmuxy.conf): chords a terminal
+//! can't send, checked before `root` so they win over tmux's own bindings.
+//! Each client keeps its own [`PrefixState`]: one browser tab pressing the
+//! prefix doesn't put another one in prefix mode.
+
+use crate::executor::KeyBinding;
+use crate::keyboard::{tmux_key_name, KeyEvent};
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+
+/// tmux's default `repeat-time`.
+pub const DEFAULT_REPEAT_TIME: Duration = Duration::from_millis(500);
+
+/// The key table tmuxy-only bindings live in.
+pub const TMUXY_KEY_TABLE: &str = "tmuxy";
+
+/// The prefix key and the `prefix`, `root` and `tmuxy` tables, keyed by
+/// tmux key name.
+#[derive(Debug, Clone, Default)]
+pub struct KeyTables {
+    prefix_key: String,
+    prefix: HashMap<String, KeyBinding>,
+    root: HashMap<String, KeyBinding>,
+    tmuxy: HashMap<String, KeyBinding>,
+    repeat_time: Duration,
+}
+
+/// A binding as a help overlay lists it.
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct EffectiveBinding {
+    /// `tmuxy`, `root` or `prefix`.
+    pub table: &'static str,
+    #[serde(flatten)]
+    pub binding: KeyBinding,
+    /// A `root` binding a `tmuxy` one shadows for tmuxy clients.
+    pub overridden: bool,
+}
+
+impl KeyTables {
+    pub fn new(
+        prefix_key: &str,
+        prefix: Vec<KeyBinding>,
+        root: Vec<KeyBinding>,
+        tmuxy: Vec<KeyBinding>,
+        repeat_time: Duration,
+    ) -> Self {
+        let table = |bindings: Vec<KeyBinding>| {
+            bindings
+                .into_iter()
+                .map(|b| (canonical_key(&b.key), b))
+                .collect::<HashMap<_, _>>()
+        };
+        Self {
+            prefix_key: canonical_key(prefix_key),
+            prefix: table(prefix),
+            root: table(root),
+            tmuxy: table(tmuxy),
+            repeat_time,
+        }
+    }
+
+    /// Every binding a tmuxy client has, `tmuxy` first, then `root` and
+    /// `prefix`, each sorted by key.
+    pub fn effective_bindings(&self) -> Vec<EffectiveBinding> {
+        let mut bindings = Vec::new();
+        for (name, table) in [
+            (TMUXY_KEY_TABLE, &self.tmuxy),
+            ("root", &self.root),
+            ("prefix", &self.prefix),
+        ] {
+            let mut keys: Vec<&String> = table.keys().collect();
+            keys.sort();
+            bindings.extend(keys.into_iter().map(|key| EffectiveBinding {
+                table: name,
+                binding: table[key].clone(),
+                overridden: name == "root" && self.tmuxy.contains_key(key),
+            }));
+        }
+        bindings
+    }
+}
+
+/// The name `event` is bound under. `tmux_key_name` keeps Shift only on
+/// named keys, so Ctrl+Shift+T comes out as `C-t`; tables spell that chord
+/// `C-S-t`, as tmux does.
+pub fn binding_key_name(event: &KeyEvent) -> Option<String> {
+    let name = tmux_key_name(event)?;
+    let shifted_letter = event.shift
+        && (event.ctrl || event.alt || event.meta)
+        && event.key.chars().count() == 1
+        && event.key.chars().all(char::is_alphabetic);
+    Some(if shifted_letter {
+        canonical_key(&format!("S-{name}"))
+    } else {
+        name
+    })
+}
+
+/// `key` with its modifiers in tmux's order (`C-M-S-`), and a shifted letter
+/// chord spelled lowercase with `S-` (`C-T` → `C-S-t`).
+fn canonical_key(key: &str) -> String {
+    let (mut ctrl, mut meta, mut shift) = (false, false, false);
+    let mut rest = key;
+    while rest.len() > 2 {
+        match rest.get(..2) {
+            Some("C-") => ctrl = true,
+            Some("M-") => meta = true,
+            Some("S-") => shift = true,
+            _ => break,
+        }
+        rest = &rest[2..];
+    }
+    let mut base = rest.to_string();
+    let mut letters = rest.chars();
+    if let (Some(c), None) = (letters.next(), letters.next()) {
+        if (ctrl || meta) && c.is_uppercase() {
+            shift = true;
+        }
+        if shift && c.is_alphabetic() {
+            base = c.to_lowercase().collect();
+        }
+    }
+    let mut name = String::new();
+    for (held, prefix) in [(ctrl, "C-"), (meta, "M-"), (shift, "S-")] {
+        if held {
+            name.push_str(prefix);
+        }
+    }
+    name + &base
+}
+
+/// What to do with a key.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum KeyAction {
+    /// Run this bound tmux command.
+    Run(String),
+    /// Not bound: send the key to the pane.
+    Send,
+    /// Taken by the state machine (the prefix itself, or an unbound key
+    /// after it).
+    Consumed,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+enum Table {
+    #[default]
+    Root,
+    Prefix,
+    /// Back in `prefix` after a `-r` binding, until the deadline.
+    Repeat(Instant),
+}
+
+/// One client's place in the key tables.
+#[derive(Debug, Clone, Default)]
+pub struct PrefixState {
+    table: Table,
+}
+
+impl PrefixState {
+    /// Feed the key named `key` (as `binding_key_name` spells it) at `now`.
+    /// `auto_repeat` marks a key the OS repeats while it is held down.
+    pub fn handle(
+        &mut self,
+        tables: &KeyTables,
+        key: &str,
+        auto_repeat: bool,
+        now: Instant,
+    ) -> KeyAction {
+        self.expire(now);
+        match self.table {
+            Table::Root => {
+                if key == tables.prefix_key {
+                    self.table = Table::Prefix;
+                    return KeyAction::Consumed;
+                }
+                match tables.tmuxy.get(key).or_else(|| tables.root.get(key)) {
+                    Some(binding) => KeyAction::Run(binding.command.clone()),
+                    None => KeyAction::Send,
+                }
+            }
+            // Holding the prefix down a moment too long mustn't count as
+            // pressing it twice (which usually sends a literal prefix).
+            Table::Prefix if auto_repeat && key == tables.prefix_key => KeyAction::Consumed,
+            Table::Prefix => {
+                let Some(binding) = tables.prefix.get(key) else {
+                    self.table = Table::Root;
+                    return KeyAction::Consumed;
+                };
+                self.table = if binding.repeat {
+                    Table::Repeat(now + tables.repeat_time)
+                } else {
+                    Table::Root
+                };
+                KeyAction::Run(binding.command.clone())
+            }
+            Table::Repeat(_) => match tables.prefix.get(key).filter(|b| b.repeat) {
+                Some(binding) => {
+                    self.table = Table::Repeat(now + tables.repeat_time);
+                    KeyAction::Run(binding.command.clone())
+                }
+                None => {
+                    self.table = Table::Root;
+                    self.handle(tables, key, auto_repeat, now)
+                }
+            },
+        }
+    }
+
+    /// Whether the next key is looked up in the `prefix` table.
+    pub fn in_prefix(&self, now: Instant) -> bool {
+        match self.table {
+            Table::Root => false,
+            Table::Prefix => true,
+            Table::Repeat(until) => now < until,
+        }
+    }
+
+    fn expire(&mut self, now: Instant) {
+        if !self.in_prefix(now) {
+            self.table = Table::Root;
+        }
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used, clippy::expect_used)]
+mod tests {
+    use super::*;
+
+    fn binding(key: &str, command: &str, repeat: bool) -> KeyBinding {
+        KeyBinding {
+            key: key.to_string(),
+            command: command.to_string(),
+            description: String::new(),
+            repeat,
+        }
+    }
+
+    fn tables() -> KeyTables {
+        KeyTables::new(
+            "C-a",
+            vec![
+                binding("C-a", "send-prefix", false),
+                binding("|", "split-window -h", false),
+                binding("o", "select-pane -t :.+", true),
+            ],
+            vec![
+                binding("M-h", "select-pane -L", false),
+                binding("C-S-Tab", "previous-window", false),
+            ],
+            vec![
+                binding("C-S-t", "new-window", false),
+                binding("C-S-Tab", "last-window", false),
+            ],
+            DEFAULT_REPEAT_TIME,
+        )
+    }
+
+    #[test]
+    fn prefix_then_a_user_binding_runs_it() {
+        let tables = tables();
+        let now = Instant::now();
+        let mut state = PrefixState::default();
+        assert_eq!(state.handle(&tables, "x", false, now), KeyAction::Send);
+        assert_eq!(
+            state.handle(&tables, "C-a", false, now),
+            KeyAction::Consumed
+        );
+        assert!(state.in_prefix(now));
+        assert_eq!(
+            state.handle(&tables, "|", false, now),
+            KeyAction::Run("split-window -h".to_string())
+        );
+        assert!(!state.in_prefix(now));
+        // Stock `%` isn't bound in this config: dropped, like tmux does.
+        state.handle(&tables, "C-a", false, now);
+        assert_eq!(state.handle(&tables, "%", false, now), KeyAction::Consumed);
+        assert_eq!(
+            state.handle(&tables, "M-h", false, now),
+            KeyAction::Run("select-pane -L".to_string())
+        );
+    }
+
+    #[test]
+    fn double_prefix_runs_the_prefix_tables_own_binding() {
+        let tables = tables();
+        let now = Instant::now();
+        let mut state = PrefixState::default();
+        state.handle(&tables, "C-a", false, now);
+        // Auto-repeat of the held prefix key is swallowed.
+        assert_eq!(state.handle(&tables, "C-a", true, now), KeyAction::Consumed);
+        assert_eq!(
+            state.handle(&tables, "C-a", false, now),
+            KeyAction::Run("send-prefix".to_string())
+        );
+    }
+
+    #[test]
+    fn repeat_bindings_hold_the_prefix_for_repeat_time() {
+        let tables = tables();
+        let now = Instant::now();
+        let mut state = PrefixState::default();
+        state.handle(&tables, "C-a", false, now);
+        let mut at = now;
+        for _ in 0..3 {
+            assert_eq!(
+                state.handle(&tables, "o", false, at),
+                KeyAction::Run("select-pane -t :.+".to_string())
+            );
+            at += Duration::from_millis(400);
+        }
+        // A key that isn't a repeat binding ends the repeat and is typed.
+        assert_eq!(state.handle(&tables, "x", false, at), KeyAction::Send);
+
+        state.handle(&tables, "C-a", false, now);
+        state.handle(&tables, "o", false, now);
+        let later = now + DEFAULT_REPEAT_TIME;
+        assert!(!state.in_prefix(later));
+        assert_eq!(state.handle(&tables, "o", false, later), KeyAction::Send);
+    }
+
+    #[test]
+    fn tmuxy_bindings_take_chords_first() {
+        let tables = tables();
+        let now = Instant::now();
+        let mut state = PrefixState::default();
+        let ctrl_shift_t = KeyEvent {
+            key: "T".to_string(),
+            code: "KeyT".to_string(),
+            ctrl: true,
+            shift: true,
+            ..Default::default()
+        };
+        let name = binding_key_name(&ctrl_shift_t).unwrap();
+        assert_eq!(name, "C-S-t");
+        assert_eq!(
+            state.handle(&tables, &name, false, now),
+            KeyAction::Run("new-window".to_string())
+        );
+        assert_eq!(
+            state.handle(&tables, "C-S-Tab", false, now),
+            KeyAction::Run("last-window".to_string())
+        );
+        assert_eq!(canonical_key("C-T"), "C-S-t");
+        assert_eq!(canonical_key("S-C-Up"), "C-S-Up");
+        assert_eq!(canonical_key("%"), "%");
+    }
+
+    #[test]
+    fn effective_bindings_mark_shadowed_root_bindings() {
+        let effective = tables().effective_bindings();
+        let listed: Vec<(&str, &str, bool)> = effective
+            .iter()
+            .map(|b| (b.table, b.binding.key.as_str(), b.overridden))
+            .collect();
+        assert_eq!(
+            listed,
+            [
+                ("tmuxy", "C-S-Tab", false),
+                ("tmuxy", "C-S-t", false),
+                ("root", "C-S-Tab", true),
+                ("root", "M-h", false),
+                ("prefix", "C-a", false),
+                ("prefix", "o", false),
+                ("prefix", "|", false),
+            ]
+        );
+        let json = serde_json::to_value(&effective[0]).unwrap();
+        assert_eq!(json["table"], "tmuxy");
+        assert_eq!(json["command"], "last-window");
+    }
+}
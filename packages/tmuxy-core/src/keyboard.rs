@@ -0,0 +1,662 @@
+//! Keyboard protocol encoding.
+//!
+//! Clients used to turn a browser `KeyboardEvent` into a tmux key name and
+//! `send-keys` it, which loses every chord the legacy encoding can't express:
+//! Ctrl+Shift+A arrives as Ctrl+A, Ctrl+Enter as Enter, Ctrl+I as Tab.
+//! Applications that want those chords ask the terminal for a richer encoding
+//! — xterm's `modifyOtherKeys` (`CSI > 4 ; N m`) or the kitty keyboard
+//! protocol's progressive enhancements (`CSI > flags u`). The requests reach
+//! us untouched in the pane's `%output`; [`KeyboardScanner`] tracks them per
+//! pane, and clients send raw [`KeyEvent`]s that [`key_commands`] encodes the
+//! way the pane asked.
+//!
+//! Wherever the requested protocol leaves a key in its legacy encoding (an
+//! unmodified arrow, plain text under `modifyOtherKeys`), the key still goes
+//! through tmux by name or as literal text, so application cursor mode and
+//! the rest of tmux's key handling apply as before.
+//!
+//! Clients report presses and repeats only, never releases. Kitty keeps one
+//! flags stack per screen; a pane here has a single stack.
+//!
+//! The scanner also notes focus reporting (`CSI ? 1004 h`), since an
+//! application that asks for it expects `CSI I` / `CSI O` as the pane gains
+//! and loses focus, which only the clients know about.
+
+use crate::executor::tmux_quote;
+use crate::text_input::{hex_command, literal_command};
+
+pub use tmuxy_protocol::KeyEvent;
+
+/// Kitty flag: report Escape and modified keys with `CSI u`.
+const KITTY_DISAMBIGUATE: u8 = 1;
+/// Kitty flag: report event types (repeat as `:2`).
+const KITTY_EVENT_TYPES: u8 = 2;
+/// Kitty flag: report the shifted key alongside the base key.
+const KITTY_ALTERNATE_KEYS: u8 = 4;
+/// Kitty flag: report every key, text included, as an escape code.
+const KITTY_ALL_KEYS: u8 = 8;
+/// Kitty flag: append the text a key produces.
+const KITTY_ASSOCIATED_TEXT: u8 = 16;
+/// Every kitty flag defined.
+const KITTY_FLAGS: u8 = 31;
+
+/// Saved kitty flag sets kept; pushing past this drops the oldest, as in kitty.
+const MAX_KITTY_STACK: usize = 16;
+/// An unterminated CSI longer than this is abandoned.
+const MAX_CSI_LEN: usize = 32;
+
+/// Modifier bits shared by both protocols (sent as 1 + the sum).
+const MOD_SHIFT: u32 = 1;
+const MOD_ALT: u32 = 2;
+const MOD_CTRL: u32 = 4;
+const MOD_META: u32 = 8;
+
+/// The keyboard protocol a pane's application asked for.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct KeyboardMode {
+    /// xterm `modifyOtherKeys` level, 0–2.
+    pub modify_other_keys: u8,
+    /// Current kitty progressive enhancement flags.
+    pub kitty_flags: u8,
+}
+
+impl KeyboardMode {
+    /// Whether keys are encoded as in a plain terminal.
+    pub fn is_legacy(&self) -> bool {
+        self.modify_other_keys == 0 && self.kitty_flags == 0
+    }
+}
+
+/// Tracks keyboard protocol requests in a pane's output, across chunk
+/// boundaries.
+#[derive(Debug, Default)]
+pub struct KeyboardScanner {
+    mode: KeyboardMode,
+    /// Kitty flags saved by `CSI > flags u`, newest last.
+    kitty_stack: Vec<u8>,
+    /// The application enabled focus reporting (DECSET 1004).
+    focus_events: bool,
+    /// Parameter and intermediate bytes of an unterminated CSI; `None`
+    /// outside one.
+    csi: Option<Vec<u8>>,
+    /// The previous byte was an ESC.
+    after_esc: bool,
+}
+
+impl KeyboardScanner {
+    pub fn mode(&self) -> KeyboardMode {
+        self.mode
+    }
+
+    /// Whether the application wants focus in/out reports.
+    pub fn focus_events(&self) -> bool {
+        self.focus_events
+    }
+
+    /// Scan the next chunk of output; returns the answer to a kitty flags
+    /// query (`CSI ? u`), empty when there was none.
+    pub fn feed(&mut self, bytes: &[u8]) -> Vec<u8> {
+        let mut reply = Vec::new();
+        for &b in bytes {
+            let after_esc = std::mem::replace(&mut self.after_esc, b == 0x1b);
+            if b == 0x1b {
+                self.csi = None;
+                continue;
+            }
+            if after_esc {
+                match b {
+                    b'[' => self.csi = Some(Vec::new()),
+                    // RIS: a full reset drops every enhancement.
+                    b'c' => *self = Self::default(),
+                    _ => {}
+                }
+                continue;
+            }
+            let Some(body) = &mut self.csi else {
+                continue;
+            };
+            match b {
+                0x20..=0x3f if body.len() < MAX_CSI_LEN => body.push(b),
+                0x40..=0x7e => {
+                    let body = std::mem::take(body);
+                    self.csi = None;
+                    reply.extend(self.dispatch(&body, b));
+                }
+                _ => self.csi = None,
+            }
+        }
+        reply
+    }
+
+    /// Apply one complete CSI sequence.
+    fn dispatch(&mut self, body: &[u8], final_byte: u8) -> Vec<u8> {
+        let (prefix, params) = match body.split_first() {
+            Some((&p @ (b'>' | b'<' | b'=' | b'?'), rest)) => (p, rest),
+            _ => return Vec::new(),
+        };
+        let params: Vec<Option<u32>> = params
+            .split(|&b| b == b';')
+            .map(|p| std::str::from_utf8(p).ok()?.parse().ok())
+            .collect();
+        let param = |i: usize| params.get(i).copied().flatten();
+        let flags = |i: usize| param(i).unwrap_or(0).min(u32::from(KITTY_FLAGS)) as u8;
+        match (prefix, final_byte) {
+            // `CSI > 4 ; N m` sets the level; `CSI > 4 m` and `CSI > m` reset it.
+            (b'>', b'm') if matches!(param(0), None | Some(4)) => {
+                self.mode.modify_other_keys = param(1).unwrap_or(0).min(2) as u8;
+            }
+            (b'>', b'n') if param(0) == Some(4) => self.mode.modify_other_keys = 0,
+            (b'>', b'u') => {
+                if self.kitty_stack.len() == MAX_KITTY_STACK {
+                    self.kitty_stack.remove(0);
+                }
+                self.kitty_stack.push(self.mode.kitty_flags);
+                self.mode.kitty_flags = flags(0);
+            }
+            (b'<', b'u') => {
+                for _ in 0..param(0).unwrap_or(1).max(1) {
+                    self.mode.kitty_flags = self.kitty_stack.pop().unwrap_or(0);
+                }
+            }
+            (b'=', b'u') => {
+                let flags = flags(0);
+                self.mode.kitty_flags = match param(1).unwrap_or(1) {
+                    1 => flags,
+                    2 => self.mode.kitty_flags | flags,
+                    3 => self.mode.kitty_flags & !flags,
+                    _ => self.mode.kitty_flags,
+                };
+            }
+            (b'?', b'u') => return format!("\x1b[?{}u", self.mode.kitty_flags).into_bytes(),
+            // DECSET/DECRST may carry several modes at once.
+            (b'?', b'h' | b'l') if params.contains(&Some(1004)) => {
+                self.focus_events = final_byte == b'h';
+            }
+            _ => {}
+        }
+        Vec::new()
+    }
+}
+
+/// The character the key types, if it is a text key.
+fn key_text(event: &KeyEvent) -> Option<char> {
+    let mut chars = event.key.chars();
+    match (chars.next(), chars.next()) {
+        (Some(c), None) => Some(c),
+        _ => None,
+    }
+}
+
+fn modifiers(event: &KeyEvent) -> u32 {
+    [
+        (event.shift, MOD_SHIFT),
+        (event.alt, MOD_ALT),
+        (event.ctrl, MOD_CTRL),
+        (event.meta, MOD_META),
+    ]
+    .iter()
+    .filter(|(held, _)| *held)
+    .map(|(_, bit)| bit)
+    .sum()
+}
+
+/// Whether a modifier other than Shift is held — the key no longer just
+/// types its character.
+fn is_chord(event: &KeyEvent) -> bool {
+    event.ctrl || event.alt || event.meta
+}
+
+/// The key `c` was typed with, before Shift: from the physical key where
+/// the US layout knows it, else `c` lowercased.
+fn unshifted(event: &KeyEvent, c: char) -> char {
+    if !event.shift {
+        return c;
+    }
+    let code = event.code.as_str();
+    if let Some(letter) = code.strip_prefix("Key").filter(|l| l.len() == 1) {
+        return letter.to_ascii_lowercase().chars().next().unwrap_or(c);
+    }
+    if let Some(digit) = code.strip_prefix("Digit").filter(|d| d.len() == 1) {
+        return digit.chars().next().unwrap_or(c);
+    }
+    match code {
+        "Minus" => '-',
+        "Equal" => '=',
+        "BracketLeft" => '[',
+        "BracketRight" => ']',
+        "Backslash" => '\\',
+        "Semicolon" => ';',
+        "Quote" => '\'',
+        "Comma" => ',',
+        "Period" => '.',
+        "Slash" => '/',
+        "Backquote" => '`',
+        _ => c.to_lowercase().next().unwrap_or(c),
+    }
+}
+
+/// How a non-text key is encoded once it leaves the legacy encoding.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Functional {
+    /// `CSI 1 ; mods X` (arrows, Home, End, F1–F4).
+    Letter(u8),
+    /// `CSI n ; mods ~`
+    Tilde(u32),
+    /// A C0 key: its byte in `CSI 27 ; mods ; n ~` or `CSI n ; mods u`.
+    Control(u32),
+}
+
+fn functional(key: &str) -> Option<Functional> {
+    use Functional::*;
+    Some(match key {
+        "ArrowUp" => Letter(b'A'),
+        "ArrowDown" => Letter(b'B'),
+        "ArrowRight" => Letter(b'C'),
+        "ArrowLeft" => Letter(b'D'),
+        "Home" => Letter(b'H'),
+        "End" => Letter(b'F'),
+        "F1" => Letter(b'P'),
+        "F2" => Letter(b'Q'),
+        "F3" => Letter(b'R'),
+        "F4" => Letter(b'S'),
+        "Insert" => Tilde(2),
+        "Delete" => Tilde(3),
+        "PageUp" => Tilde(5),
+        "PageDown" => Tilde(6),
+        "F5" => Tilde(15),
+        "F6" => Tilde(17),
+        "F7" => Tilde(18),
+        "F8" => Tilde(19),
+        "F9" => Tilde(20),
+        "F10" => Tilde(21),
+        "F11" => Tilde(23),
+        "F12" => Tilde(24),
+        "Enter" => Control(13),
+        "Tab" => Control(9),
+        "Backspace" => Control(127),
+        "Escape" => Control(27),
+        _ => return None,
+    })
+}
+
+/// The bytes `event` sends under `mode`, or `None` where the mode leaves the
+/// key in its legacy encoding. Kitty flags win over `modifyOtherKeys` when
+/// an application sets both.
+pub fn encode_key(event: &KeyEvent, mode: KeyboardMode) -> Option<Vec<u8>> {
+    if mode.kitty_flags != 0 {
+        encode_kitty(event, mode.kitty_flags)
+    } else if mode.modify_other_keys != 0 {
+        encode_modify_other_keys(event, mode.modify_other_keys)
+    } else {
+        None
+    }
+    .map(String::into_bytes)
+}
+
+fn encode_kitty(event: &KeyEvent, flags: u8) -> Option<String> {
+    let mods = modifiers(event);
+    let all_keys = flags & KITTY_ALL_KEYS != 0;
+    let disambiguate = all_keys || flags & KITTY_DISAMBIGUATE != 0;
+    let event_type = if flags & KITTY_EVENT_TYPES != 0 && event.repeat {
+        ":2"
+    } else {
+        ""
+    };
+    let mods_field = if mods == 0 && event_type.is_empty() {
+        String::new()
+    } else {
+        format!("{}{event_type}", mods + 1)
+    };
+
+    if let Some(c) = key_text(event) {
+        // Shift alone still types the character unless every key is reported.
+        if !(all_keys || disambiguate && is_chord(event)) {
+            return None;
+        }
+        let base = unshifted(event, c);
+        let mut key = u32::from(base).to_string();
+        if flags & KITTY_ALTERNATE_KEYS != 0 && event.shift && base != c {
+            key.push_str(&format!(":{}", u32::from(c)));
+        }
+        if all_keys && flags & KITTY_ASSOCIATED_TEXT != 0 && !is_chord(event) {
+            return Some(format!("\x1b[{key};{mods_field};{}u", u32::from(c)));
+        }
+        return Some(csi_u(&key, &mods_field));
+    }
+
+    match functional(&event.key)? {
+        // Enter, Tab and Backspace keep their legacy bytes unmodified, so a
+        // shell can still run `reset` after an application exits without
+        // popping its flags; Escape is the key flag 1 exists for.
+        Functional::Control(27) if disambiguate => Some(csi_u("27", &mods_field)),
+        Functional::Control(n) if all_keys || disambiguate && mods != 0 => {
+            Some(csi_u(&n.to_string(), &mods_field))
+        }
+        Functional::Control(_) => None,
+        _ if mods_field.is_empty() && !all_keys => None,
+        // CSI R would read as a cursor position report.
+        Functional::Letter(b'R') => Some(format!("\x1b[13{}~", field(&mods_field))),
+        Functional::Letter(c) if mods_field.is_empty() => Some(format!("\x1b[{}", char::from(c))),
+        Functional::Letter(c) => Some(format!("\x1b[1;{mods_field}{}", char::from(c))),
+        Functional::Tilde(n) => Some(format!("\x1b[{n}{}~", field(&mods_field))),
+    }
+}
+
+/// `CSI key [; mods] u`
+fn csi_u(key: &str, mods_field: &str) -> String {
+    format!("\x1b[{key}{}u", field(mods_field))
+}
+
+/// `; value`, or nothing for an empty value.
+fn field(value: &str) -> String {
+    if value.is_empty() {
+        String::new()
+    } else {
+        format!(";{value}")
+    }
+}
+
+fn encode_modify_other_keys(event: &KeyEvent, level: u8) -> Option<String> {
+    let mods = modifiers(event);
+    if mods == 0 {
+        return None;
+    }
+    if let Some(c) = key_text(event) {
+        let encode = if level >= 2 {
+            is_chord(event)
+        } else {
+            // Level 1 only covers chords legacy encoding can't tell apart:
+            // Ctrl with a key that has no control character of its own, or
+            // Ctrl+Shift on a letter (which legacy sends as plain Ctrl).
+            event.ctrl && (!has_control_char(c) || event.shift && c.is_ascii_alphabetic())
+        };
+        return encode.then(|| format!("\x1b[27;{};{}~", mods + 1, u32::from(c)));
+    }
+    match functional(&event.key)? {
+        Functional::Control(n) => {
+            let encode = level >= 2 || n == 13;
+            encode.then(|| format!("\x1b[27;{};{n}~", mods + 1))
+        }
+        Functional::Letter(c) => Some(format!("\x1b[1;{}{}", mods + 1, char::from(c))),
+        Functional::Tilde(n) => Some(format!("\x1b[{n};{}~", mods + 1)),
+    }
+}
+
+/// Whether Ctrl+`c` has a control character of its own in legacy encoding.
+fn has_control_char(c: char) -> bool {
+    c.is_ascii_alphabetic() || matches!(c, '@' | '[' | '\\' | ']' | '^' | '_' | ' ' | '?')
+}
+
+/// tmux's name for `event` (`C-M-x`, `S-Up`, `BTab`), as the frontend
+/// formats keys for `send-keys`.
+pub fn tmux_key_name(event: &KeyEvent) -> Option<String> {
+    // `S-Tab` is a literal Tab in tmux; back-tab is `BTab`.
+    if event.key == "Tab" && event.shift && !is_chord(event) {
+        return Some("BTab".to_string());
+    }
+    let named = match event.key.as_str() {
+        "Enter" => "Enter",
+        "Backspace" => "BSpace",
+        "Delete" => "DC",
+        "ArrowUp" => "Up",
+        "ArrowDown" => "Down",
+        "ArrowLeft" => "Left",
+        "ArrowRight" => "Right",
+        "Tab" => "Tab",
+        "Escape" => "Escape",
+        "Home" => "Home",
+        "End" => "End",
+        "PageUp" => "PPage",
+        "PageDown" => "NPage",
+        "Insert" => "IC",
+        " " => "Space",
+        key if key.len() > 1 && key.starts_with('F') && key[1..].parse::<u8>().is_ok() => key,
+        _ => "",
+    };
+    let mut name = String::new();
+    if event.ctrl {
+        name.push_str("C-");
+    }
+    if event.alt || event.meta {
+        name.push_str("M-");
+    }
+    if !named.is_empty() {
+        if event.shift {
+            name.push_str("S-");
+        }
+        name.push_str(named);
+    } else {
+        let c = key_text(event)?;
+        if name.is_empty() {
+            name.push(c);
+        } else {
+            name.extend(c.to_lowercase());
+        }
+    }
+    Some(name)
+}
+
+/// Build the control-mode commands delivering `event` to `pane_id` under
+/// `mode`: the encoded bytes via `send-keys -H`, or, where the mode leaves
+/// the key alone, literal text or the tmux key name.
+pub fn key_commands(pane_id: &str, event: &KeyEvent, mode: KeyboardMode) -> Vec<String> {
+    if let Some(bytes) = encode_key(event, mode) {
+        return vec![hex_command(pane_id, &bytes)];
+    }
+    if let Some(c) = key_text(event).filter(|_| !is_chord(event)) {
+        return vec![literal_command(pane_id, &c.to_string())];
+    }
+    tmux_key_name(event)
+        .map(|name| vec![format!("send-keys -t {} {name}", tmux_quote(pane_id))])
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used, clippy::expect_used)]
+mod tests {
+    use super::*;
+
+    fn key(key: &str, code: &str, mods: &str) -> KeyEvent {
+        KeyEvent {
+            key: key.to_string(),
+            code: code.to_string(),
+            ctrl: mods.contains('C'),
+            alt: mods.contains('M'),
+            shift: mods.contains('S'),
+            meta: mods.contains('D'),
+            repeat: mods.contains('r'),
+        }
+    }
+
+    fn kitty(flags: u8) -> KeyboardMode {
+        KeyboardMode {
+            modify_other_keys: 0,
+            kitty_flags: flags,
+        }
+    }
+
+    fn mok(level: u8) -> KeyboardMode {
+        KeyboardMode {
+            modify_other_keys: level,
+            kitty_flags: 0,
+        }
+    }
+
+    fn encoded(event: &KeyEvent, mode: KeyboardMode) -> Option<String> {
+        encode_key(event, mode).map(|b| String::from_utf8(b).unwrap())
+    }
+
+    #[test]
+    fn tracks_modify_other_keys_across_chunks() {
+        let mut scanner = KeyboardScanner::default();
+        assert!(scanner.feed(b"hello\x1b[>4").is_empty());
+        assert!(scanner.mode().is_legacy());
+        scanner.feed(b";2m");
+        assert_eq!(scanner.mode().modify_other_keys, 2);
+        scanner.feed(b"\x1b[>4m");
+        assert!(scanner.mode().is_legacy());
+        scanner.feed(b"\x1b[>4;1m\x1b[>4n");
+        assert!(scanner.mode().is_legacy());
+        // Other resources leave it alone.
+        scanner.feed(b"\x1b[>4;2m\x1b[>1;2m");
+        assert_eq!(scanner.mode().modify_other_keys, 2);
+        scanner.feed(b"\x1bc");
+        assert!(scanner.mode().is_legacy());
+    }
+
+    #[test]
+    fn kitty_flags_push_pop_set_and_query() {
+        let mut scanner = KeyboardScanner::default();
+        assert_eq!(scanner.feed(b"\x1b[?u"), b"\x1b[?0u");
+        scanner.feed(b"\x1b[>1u\x1b[>11u");
+        assert_eq!(scanner.mode().kitty_flags, 11);
+        scanner.feed(b"\x1b[=4;2u");
+        assert_eq!(scanner.mode().kitty_flags, 15);
+        scanner.feed(b"\x1b[=2;3u");
+        assert_eq!(scanner.feed(b"\x1b[?u"), b"\x1b[?13u");
+        scanner.feed(b"\x1b[<u");
+        assert_eq!(scanner.mode().kitty_flags, 1);
+        scanner.feed(b"\x1b[<5u");
+        assert!(scanner.mode().is_legacy());
+        // `CSI u` without a prefix restores the cursor, nothing more.
+        scanner.feed(b"\x1b[>1u\x1b[u");
+        assert_eq!(scanner.mode().kitty_flags, 1);
+    }
+
+    #[test]
+    fn tracks_focus_reporting() {
+        let mut scanner = KeyboardScanner::default();
+        assert!(!scanner.focus_events());
+        scanner.feed(b"\x1b[?1004h");
+        assert!(scanner.focus_events());
+        scanner.feed(b"\x1b[?25;1004l");
+        assert!(!scanner.focus_events());
+        scanner.feed(b"\x1b[?1049;1004");
+        scanner.feed(b"h");
+        assert!(scanner.focus_events());
+        // Other modes leave it alone.
+        scanner.feed(b"\x1b[?1049l\x1b[?2004l");
+        assert!(scanner.focus_events());
+        assert!(scanner.mode().is_legacy());
+        scanner.feed(b"\x1bc");
+        assert!(!scanner.focus_events());
+    }
+
+    #[test]
+    fn kitty_disambiguates_chords_and_escape() {
+        let mode = kitty(KITTY_DISAMBIGUATE);
+        assert_eq!(
+            encoded(&key("A", "KeyA", "CS"), mode).unwrap(),
+            "\x1b[97;6u"
+        );
+        assert_eq!(
+            encoded(&key("i", "KeyI", "C"), mode).unwrap(),
+            "\x1b[105;5u"
+        );
+        assert_eq!(
+            encoded(&key("Escape", "Escape", ""), mode).unwrap(),
+            "\x1b[27u"
+        );
+        assert_eq!(
+            encoded(&key("Enter", "Enter", "C"), mode).unwrap(),
+            "\x1b[13;5u"
+        );
+        assert_eq!(
+            encoded(&key("ArrowUp", "ArrowUp", "CS"), mode).unwrap(),
+            "\x1b[1;6A"
+        );
+        assert_eq!(encoded(&key("F3", "F3", "S"), mode).unwrap(), "\x1b[13;2~");
+        // Text, Shift+text and the unmodified legacy keys stay with tmux.
+        assert_eq!(encoded(&key("a", "KeyA", ""), mode), None);
+        assert_eq!(encoded(&key("A", "KeyA", "S"), mode), None);
+        assert_eq!(encoded(&key("Enter", "Enter", ""), mode), None);
+        assert_eq!(encoded(&key("ArrowUp", "ArrowUp", ""), mode), None);
+    }
+
+    #[test]
+    fn kitty_enhancements_report_repeats_alternates_and_text() {
+        let mode = kitty(KITTY_DISAMBIGUATE | KITTY_EVENT_TYPES | KITTY_ALTERNATE_KEYS);
+        assert_eq!(
+            encoded(&key("!", "Digit1", "CSr"), mode).unwrap(),
+            "\x1b[49:33;6:2u"
+        );
+        assert_eq!(
+            encoded(&key("ArrowLeft", "ArrowLeft", "r"), mode).unwrap(),
+            "\x1b[1;1:2D"
+        );
+        let mode = kitty(KITTY_ALL_KEYS | KITTY_ASSOCIATED_TEXT);
+        assert_eq!(
+            encoded(&key("a", "KeyA", ""), mode).unwrap(),
+            "\x1b[97;;97u"
+        );
+        assert_eq!(
+            encoded(&key("A", "KeyA", "S"), mode).unwrap(),
+            "\x1b[97;2;65u"
+        );
+        assert_eq!(
+            encoded(&key("Enter", "Enter", ""), mode).unwrap(),
+            "\x1b[13u"
+        );
+        assert_eq!(
+            encoded(&key("ArrowUp", "ArrowUp", ""), mode).unwrap(),
+            "\x1b[A"
+        );
+    }
+
+    #[test]
+    fn modify_other_keys_levels() {
+        let ctrl_shift_a = key("A", "KeyA", "CS");
+        assert_eq!(encoded(&ctrl_shift_a, mok(1)).unwrap(), "\x1b[27;6;65~");
+        assert_eq!(encoded(&ctrl_shift_a, mok(2)).unwrap(), "\x1b[27;6;65~");
+        // Ctrl+A has its own control character: only level 2 encodes it.
+        assert_eq!(encoded(&key("a", "KeyA", "C"), mok(1)), None);
+        assert_eq!(
+            encoded(&key("a", "KeyA", "C"), mok(2)).unwrap(),
+            "\x1b[27;5;97~"
+        );
+        assert_eq!(
+            encoded(&key("1", "Digit1", "C"), mok(1)).unwrap(),
+            "\x1b[27;5;49~"
+        );
+        assert_eq!(encoded(&key("Tab", "Tab", "C"), mok(1)), None);
+        assert_eq!(
+            encoded(&key("Tab", "Tab", "C"), mok(2)).unwrap(),
+            "\x1b[27;5;9~"
+        );
+        assert_eq!(
+            encoded(&key("Enter", "Enter", "S"), mok(1)).unwrap(),
+            "\x1b[27;2;13~"
+        );
+        assert_eq!(
+            encoded(&key("ArrowRight", "ArrowRight", "CS"), mok(1)).unwrap(),
+            "\x1b[1;6C"
+        );
+        assert_eq!(encoded(&key("A", "KeyA", "S"), mok(2)), None);
+    }
+
+    #[test]
+    fn legacy_keys_fall_back_to_text_and_names() {
+        let legacy = KeyboardMode::default();
+        assert_eq!(
+            key_commands("%1", &key("'", "Quote", ""), legacy),
+            ["send-keys -t '%1' -l ''\\'''"]
+        );
+        assert_eq!(
+            key_commands("%1", &key("ArrowUp", "ArrowUp", "CS"), legacy),
+            ["send-keys -t '%1' C-S-Up"]
+        );
+        assert_eq!(
+            key_commands("%1", &key("Tab", "Tab", "S"), legacy),
+            ["send-keys -t '%1' BTab"]
+        );
+        assert_eq!(
+            key_commands("%1", &key("X", "KeyX", "CMS"), legacy),
+            ["send-keys -t '%1' C-M-x"]
+        );
+        assert!(key_commands("%1", &key("Unidentified", "", ""), legacy).is_empty());
+        assert_eq!(
+            key_commands("%1", &key("a", "KeyA", "C"), mok(2)),
+            ["send-keys -t '%1' -H 1b 5b 32 37 3b 35 3b 39 37 7e"]
+        );
+    }
+}
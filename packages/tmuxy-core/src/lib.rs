@@ -1,25 +1,71 @@
+pub mod color_transform;
+pub mod command_line;
 pub mod constants;
 pub mod control_mode;
+pub mod discovery;
 pub mod error;
+pub mod float;
+pub mod keyboard;
+pub mod mouse;
+pub mod pane_group;
+pub mod pane_view;
+pub mod text_input;
+pub mod widget;
 
 // Native (non-wasm) transport + tmux-command layer, gated behind `native`.
 #[cfg(feature = "native")]
+pub mod clipboard;
+#[cfg(feature = "native")]
+pub mod container;
+#[cfg(feature = "native")]
+pub mod copy_mode;
+#[cfg(feature = "native")]
 pub mod ctx;
 #[cfg(feature = "native")]
 pub mod debug_log;
 #[cfg(feature = "native")]
+pub mod dispatch;
+#[cfg(feature = "native")]
 pub mod executor;
 #[cfg(feature = "native")]
+pub mod export;
+#[cfg(feature = "native")]
+pub mod git_status;
+#[cfg(feature = "native")]
+pub mod key_table;
+#[cfg(feature = "native")]
+pub mod markdown;
+#[cfg(feature = "native")]
+pub mod options;
+#[cfg(feature = "native")]
+pub mod pane_search;
+#[cfg(feature = "native")]
+pub mod pane_text;
+#[cfg(feature = "native")]
 pub mod retry;
 #[cfg(feature = "native")]
+pub mod secrets;
+#[cfg(feature = "native")]
 pub mod servers;
 #[cfg(feature = "native")]
 pub mod session;
 #[cfg(feature = "native")]
+pub mod snapshot;
+#[cfg(feature = "native")]
+pub mod table;
+#[cfg(feature = "native")]
+pub mod template;
+#[cfg(any(test, feature = "testing"))]
+pub mod testing;
+#[cfg(feature = "native")]
 pub mod theme;
 #[cfg(feature = "native")]
 pub mod tmux_service;
+#[cfg(feature = "native")]
+pub mod usage;
 
+#[cfg(all(feature = "native", feature = "test-support"))]
+pub use ctx::OffsetClock;
 #[cfg(feature = "native")]
 pub use ctx::{Clock, Ctx, TmuxCommand};
 #[cfg(feature = "native")]
@@ -29,102 +75,23 @@ pub use error::{Result as TmuxResult, TmuxError};
 #[cfg(feature = "native")]
 pub use retry::{retry_with, RetryPolicy};
 
-use serde::{Deserialize, Serialize};
+// The wire types clients see; see the tmuxy-protocol crate.
+pub use tmuxy_protocol::{
+    CellColor, CellStyle, CommandBlock, PaneContent, PaneDelta, ScrollDelta, StateUpdate,
+    TerminalCell, TerminalLine, TmuxDelta, TmuxPane, TmuxState, TmuxWindow, UnderlineStyle,
+    WindowDelta, WindowType, PROTOCOL_VERSION,
+};
 
 // Re-export key binding types and functions
 #[cfg(feature = "native")]
-pub use executor::{get_prefix_bindings, get_prefix_key, get_root_bindings, KeyBinding};
+pub use executor::{
+    get_key_bindings, get_key_tables, get_prefix_bindings, get_prefix_key, get_repeat_time,
+    get_root_bindings, KeyBinding, KeyBindings,
+};
 
 /// Default session name for tmuxy
 pub const DEFAULT_SESSION_NAME: &str = "tmuxy";
 
-// ============================================
-// Structured Cell Types (for eliminating double ANSI parsing)
-// ============================================
-
-/// Color representation for terminal cells
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
-#[serde(untagged)]
-pub enum CellColor {
-    /// Indexed color (0-255)
-    Indexed(u8),
-    /// RGB color
-    Rgb { r: u8, g: u8, b: u8 },
-}
-
-/// Cell style attributes (only present if cell has non-default styling)
-#[derive(Debug, Clone, Serialize, Deserialize, Default, PartialEq, Eq)]
-pub struct CellStyle {
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub fg: Option<CellColor>,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub bg: Option<CellColor>,
-    #[serde(skip_serializing_if = "is_false")]
-    #[serde(default)]
-    pub bold: bool,
-    /// SGR 2: faint/dim text. Apps like Claude Code use this for autosuggestions.
-    #[serde(skip_serializing_if = "is_false")]
-    #[serde(default)]
-    pub dim: bool,
-    #[serde(skip_serializing_if = "is_false")]
-    #[serde(default)]
-    pub italic: bool,
-    #[serde(skip_serializing_if = "is_false")]
-    #[serde(default)]
-    pub underline: bool,
-    #[serde(skip_serializing_if = "is_false")]
-    #[serde(default)]
-    pub inverse: bool,
-    /// OSC 8 hyperlink URL (if cell is part of a hyperlink)
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub url: Option<String>,
-}
-
-fn is_false(b: &bool) -> bool {
-    !*b
-}
-
-impl CellStyle {
-    pub fn is_empty(&self) -> bool {
-        self.fg.is_none()
-            && self.bg.is_none()
-            && !self.bold
-            && !self.dim
-            && !self.italic
-            && !self.underline
-            && !self.inverse
-            && self.url.is_none()
-    }
-}
-
-/// A single terminal cell with character and optional styling
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
-pub struct TerminalCell {
-    /// The character(s) in this cell (usually single char, but can be multi-byte)
-    #[serde(rename = "c")]
-    pub char: String,
-    /// Style attributes (only present if cell has styling)
-    #[serde(rename = "s", skip_serializing_if = "Option::is_none")]
-    pub style: Option<CellStyle>,
-}
-
-impl TerminalCell {
-    pub fn new(char: String) -> Self {
-        Self { char, style: None }
-    }
-
-    pub fn with_style(char: String, style: CellStyle) -> Self {
-        let style = if style.is_empty() { None } else { Some(style) };
-        Self { char, style }
-    }
-}
-
-/// A line of terminal cells
-pub type TerminalLine = Vec<TerminalCell>;
-
-/// Pane content as structured cells (pre-parsed from ANSI)
-pub type PaneContent = Vec<TerminalLine>;
-
 /// Extract structured cells from a vt100 screen.
 /// This is the single source of truth for cell extraction, used by both
 /// parse_ansi_to_cells (polling mode) and PaneState::get_content (control mode).
@@ -150,8 +117,11 @@ pub fn extract_cells_with_urls(
             let Some(cell) = screen.cell(row, col) else {
                 continue;
             };
-            // vt100 returns empty string for unwritten cells; use space to preserve
-            // column alignment when characters are joined on the frontend
+            // vt100 returns empty string for unwritten cells and for the
+            // continuation half of a wide character; use space to preserve
+            // column alignment when characters are joined on the frontend.
+            // Combining marks and zero-width joiners are already appended to
+            // the preceding cell's contents by vt100, so they take no column.
             let raw_content = cell.contents();
             let char_content = if raw_content.is_empty() {
                 " ".to_string()
@@ -159,16 +129,14 @@ pub fn extract_cells_with_urls(
                 raw_content.to_string()
             };
 
-            let fg = match cell.fgcolor() {
-                vt100::Color::Default => None,
-                vt100::Color::Idx(idx) => Some(CellColor::Indexed(idx)),
-                vt100::Color::Rgb(r, g, b) => Some(CellColor::Rgb { r, g, b }),
-            };
-
-            let bg = match cell.bgcolor() {
-                vt100::Color::Default => None,
-                vt100::Color::Idx(idx) => Some(CellColor::Indexed(idx)),
-                vt100::Color::Rgb(r, g, b) => Some(CellColor::Rgb { r, g, b }),
+            let fg = cell_color(cell.fgcolor());
+            let bg = cell_color(cell.bgcolor());
+            let underline_style = match cell.underline_style() {
+                vt100::UnderlineStyle::Double => Some(UnderlineStyle::Double),
+                vt100::UnderlineStyle::Curly => Some(UnderlineStyle::Curly),
+                vt100::UnderlineStyle::Dotted => Some(UnderlineStyle::Dotted),
+                vt100::UnderlineStyle::Dashed => Some(UnderlineStyle::Dashed),
+                vt100::UnderlineStyle::None | vt100::UnderlineStyle::Single => None,
             };
 
             // Get URL from OSC parser if available
@@ -181,11 +149,24 @@ pub fn extract_cells_with_urls(
                 dim: cell.dim(),
                 italic: cell.italic(),
                 underline: cell.underline(),
+                underline_style,
+                underline_color: cell_color(cell.underline_color()),
+                strikethrough: cell.strikethrough(),
+                blink: cell.blink(),
                 inverse: cell.inverse(),
                 url,
             };
 
-            line.push(TerminalCell::with_style(char_content, style));
+            // Widths come from vt100, which sizes characters with
+            // unicode-width — the same table tmux lays the pane out with.
+            let width = if cell.is_wide() {
+                2
+            } else if cell.is_wide_continuation() {
+                0
+            } else {
+                1
+            };
+            line.push(TerminalCell::with_style(char_content, style).with_width(width));
         }
 
         // Trim trailing empty cells
@@ -203,6 +184,14 @@ pub fn extract_cells_with_urls(
     lines
 }
 
+fn cell_color(color: vt100::Color) -> Option<CellColor> {
+    match color {
+        vt100::Color::Default => None,
+        vt100::Color::Idx(idx) => Some(CellColor::Indexed(idx)),
+        vt100::Color::Rgb(r, g, b) => Some(CellColor::Rgb { r, g, b }),
+    }
+}
+
 /// Parse scrollback content into structured cells.
 /// Uses the line count from the content itself as the height.
 pub fn parse_scrollback_to_cells(content: &str, width: u32) -> PaneContent {
@@ -219,412 +208,6 @@ pub fn parse_ansi_to_cells(content: &str, width: u32, height: u32) -> PaneConten
     extract_cells_from_screen(parser.screen())
 }
 
-// ============================================
-// Tmux State Types
-// ============================================
-
-/// A single tmux pane
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct TmuxPane {
-    pub id: u32,
-    pub tmux_id: String,   // actual tmux pane ID (e.g., "%0")
-    pub window_id: String, // window this pane belongs to (e.g., "@0")
-    /// Rendered cell grid. `Arc`-shared so building a state snapshot, storing
-    /// `prev_state`, and diffing unchanged panes never deep-copies the grid —
-    /// the cost that made a one-field delta as expensive as a full sync.
-    /// Serializes transparently (serde `rc`), so the wire shape is unchanged.
-    pub content: std::sync::Arc<PaneContent>,
-    pub cursor_x: u32,
-    pub cursor_y: u32,
-    pub width: u32,
-    pub height: u32,
-    pub x: u32,
-    pub y: u32,
-    pub active: bool,
-    pub command: String,
-    pub title: String,        // pane title (set by shell/application)
-    pub border_title: String, // evaluated pane-border-format from tmux config
-    pub in_mode: bool,        // true if in copy mode
-    pub copy_cursor_x: u32,
-    pub copy_cursor_y: u32,
-    /// True if the application is in alternate screen mode (vim, less, htop)
-    /// Used to determine scroll behavior (wheel -> arrow keys vs copy mode)
-    #[serde(default)]
-    pub alternate_on: bool,
-    /// True if the application has mouse tracking enabled
-    /// When true, mouse events should be forwarded as SGR sequences
-    #[serde(default)]
-    pub mouse_any_flag: bool,
-    /// True if this pane's output is paused due to flow control
-    /// When true, UI should show a pause indicator
-    #[serde(default)]
-    pub paused: bool,
-    /// Number of history lines (scrollback above the visible area)
-    #[serde(default)]
-    pub history_size: u64,
-    /// Whether a selection is active in copy mode
-    #[serde(default)]
-    pub selection_present: bool,
-    /// Selection start X (visible-area-relative column), only meaningful when selection_present
-    #[serde(default)]
-    pub selection_start_x: u32,
-    /// Selection start Y (visible-area-relative row, can be negative if off-screen)
-    #[serde(default)]
-    pub selection_start_y: i32,
-    /// Image placements on this pane's terminal grid
-    #[serde(default, skip_serializing_if = "Vec::is_empty")]
-    pub images: Vec<control_mode::images::ImagePlacement>,
-    /// Cursor shape from DECSCUSR: 0/1=block_blink, 2=block, 3=underline_blink, 4=underline, 5=bar_blink, 6=bar
-    #[serde(default)]
-    pub cursor_shape: u8,
-    /// Whether the cursor is hidden (DECTCEM mode 25 off / ESC[?25l)
-    #[serde(default)]
-    pub cursor_hidden: bool,
-}
-
-/// Window type discriminator. Set on windows tmuxy created or has adopted.
-/// Windows without a type are foreign and tmuxy ignores them everywhere.
-#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
-#[serde(rename_all = "kebab-case")]
-pub enum WindowType {
-    Tab,
-    Float,
-    FloatBackdrop,
-    Group,
-    /// The left sidebar's hidden window (runs the `tmuxy tree` TUI). Excluded
-    /// from the tab bar like floats/groups; rendered in the UI as a left drawer.
-    Sidebar,
-}
-
-impl WindowType {
-    pub fn parse(s: &str) -> Option<Self> {
-        match s {
-            "tab" => Some(WindowType::Tab),
-            "float" => Some(WindowType::Float),
-            "float-backdrop" => Some(WindowType::FloatBackdrop),
-            "group" => Some(WindowType::Group),
-            "sidebar" => Some(WindowType::Sidebar),
-            _ => None,
-        }
-    }
-
-    pub fn as_str(self) -> &'static str {
-        match self {
-            WindowType::Tab => "tab",
-            WindowType::Float => "float",
-            WindowType::FloatBackdrop => "float-backdrop",
-            WindowType::Group => "group",
-            WindowType::Sidebar => "sidebar",
-        }
-    }
-}
-
-/// A single tmux window (tab/float/group/foreign)
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct TmuxWindow {
-    /// Window ID (e.g., "@0")
-    pub id: String,
-    pub index: u32,
-    pub name: String,
-    pub active: bool,
-    /// Window type as set via @tmuxy-window-type. None = foreign window.
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub window_type: Option<WindowType>,
-    /// Group pane membership (from @tmuxy-group-panes), e.g. ["%4","%6","%7"].
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub group_panes: Option<Vec<String>>,
-    /// Parent window ID for a float (the launcher window) or backdrop (the float).
-    /// Sourced from @tmuxy-float-parent.
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub float_parent: Option<String>,
-    /// Float width in cells (from @tmuxy-float-width).
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub float_width: Option<u32>,
-    /// Float height in cells (from @tmuxy-float-height).
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub float_height: Option<u32>,
-    /// Drawer-style float direction (from @tmuxy-float-drawer).
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub float_drawer: Option<String>,
-    /// Float backdrop style (from @tmuxy-float-bg).
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub float_bg: Option<String>,
-    /// True if the float hides its header chrome (from @tmuxy-float-noheader).
-    #[serde(default, skip_serializing_if = "is_false")]
-    pub float_noheader: bool,
-    /// True while a pane in this window is zoomed. tmux hides every other pane
-    /// when zoomed; the frontend must not keep painting them underneath.
-    #[serde(default)]
-    pub zoomed: bool,
-}
-
-/// Full tmux state with all panes and windows
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct TmuxState {
-    /// Session name (e.g., "tmuxy")
-    pub session_name: String,
-    /// Active window ID (e.g., "@0")
-    pub active_window_id: Option<String>,
-    /// Active pane ID (e.g., "%0")
-    pub active_pane_id: Option<String>,
-    pub panes: Vec<TmuxPane>,
-    pub windows: Vec<TmuxWindow>,
-    pub total_width: u32,
-    pub total_height: u32,
-    /// Rendered tmux status line with ANSI escape sequences
-    pub status_line: String,
-}
-
-/// Serialize a line-number-keyed map with STRING keys. serde_json does this
-/// implicitly (JSON object keys are strings — the wire shape the frontend
-/// already speaks), but serde-wasm-bindgen's maps-as-objects mode REFUSES
-/// non-string keys ("Map key is not a string"), which aborted serialization of
-/// every content-carrying delta on the wasm path.
-fn ser_line_map<S: serde::Serializer>(
-    v: &Option<std::collections::HashMap<usize, TerminalLine>>,
-    s: S,
-) -> Result<S::Ok, S::Error> {
-    use serde::ser::SerializeMap;
-    match v {
-        None => s.serialize_none(),
-        Some(m) => {
-            let mut map = s.serialize_map(Some(m.len()))?;
-            for (k, val) in m {
-                map.serialize_entry(&k.to_string(), val)?;
-            }
-            map.end()
-        }
-    }
-}
-
-/// Delta update for a single pane (only changed fields)
-#[derive(Debug, Clone, Serialize, Deserialize, Default)]
-pub struct PaneDelta {
-    /// Window ID (only if changed, e.g. after swap-pane across windows)
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub window_id: Option<String>,
-    /// Content (only changed lines) - line index → line content
-    /// Only lines that differ from the previous state are included.
-    #[serde(
-        skip_serializing_if = "Option::is_none",
-        serialize_with = "ser_line_map"
-    )]
-    pub content: Option<std::collections::HashMap<usize, TerminalLine>>,
-    /// Cursor position (only if changed)
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub cursor_x: Option<u32>,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub cursor_y: Option<u32>,
-    /// Dimensions (only if changed)
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub width: Option<u32>,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub height: Option<u32>,
-    /// Position (only if changed)
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub x: Option<u32>,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub y: Option<u32>,
-    /// Active state (only if changed)
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub active: Option<bool>,
-    /// Command (only if changed)
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub command: Option<String>,
-    /// Title (only if changed)
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub title: Option<String>,
-    /// Border title (only if changed)
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub border_title: Option<String>,
-    /// Copy mode state (only if changed)
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub in_mode: Option<bool>,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub copy_cursor_x: Option<u32>,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub copy_cursor_y: Option<u32>,
-    /// Alternate screen mode (only if changed)
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub alternate_on: Option<bool>,
-    /// Mouse any flag (only if changed)
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub mouse_any_flag: Option<bool>,
-    /// Flow control pause state (only if changed)
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub paused: Option<bool>,
-    /// History size (only if changed)
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub history_size: Option<u64>,
-    /// Selection present (only if changed)
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub selection_present: Option<bool>,
-    /// Selection start X (only if changed)
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub selection_start_x: Option<u32>,
-    /// Selection start Y (only if changed)
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub selection_start_y: Option<i32>,
-    /// Image placements (only if changed)
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub images: Option<Vec<control_mode::images::ImagePlacement>>,
-    /// Cursor shape (only if changed)
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub cursor_shape: Option<u8>,
-    /// Cursor hidden (only if changed)
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub cursor_hidden: Option<bool>,
-}
-
-impl PaneDelta {
-    pub fn is_empty(&self) -> bool {
-        self.window_id.is_none()
-            && self.content.is_none()
-            && self.cursor_x.is_none()
-            && self.cursor_y.is_none()
-            && self.width.is_none()
-            && self.height.is_none()
-            && self.x.is_none()
-            && self.y.is_none()
-            && self.active.is_none()
-            && self.command.is_none()
-            && self.title.is_none()
-            && self.border_title.is_none()
-            && self.in_mode.is_none()
-            && self.copy_cursor_x.is_none()
-            && self.copy_cursor_y.is_none()
-            && self.alternate_on.is_none()
-            && self.mouse_any_flag.is_none()
-            && self.paused.is_none()
-            && self.history_size.is_none()
-            && self.selection_present.is_none()
-            && self.selection_start_x.is_none()
-            && self.selection_start_y.is_none()
-            && self.images.is_none()
-            && self.cursor_shape.is_none()
-            && self.cursor_hidden.is_none()
-    }
-}
-
-/// Delta update for a single window (only changed fields)
-#[derive(Debug, Clone, Serialize, Deserialize, Default)]
-pub struct WindowDelta {
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub name: Option<String>,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub active: Option<bool>,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub window_type: Option<Option<WindowType>>,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub group_panes: Option<Option<Vec<String>>>,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub float_parent: Option<Option<String>>,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub float_width: Option<Option<u32>>,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub float_height: Option<Option<u32>>,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub float_drawer: Option<Option<String>>,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub float_bg: Option<Option<String>>,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub float_noheader: Option<bool>,
-    /// True while this window has a zoomed pane. tmux hides the other panes
-    /// entirely when zoomed, so the frontend needs this to do the same.
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub zoomed: Option<bool>,
-}
-
-impl WindowDelta {
-    pub fn is_empty(&self) -> bool {
-        self.name.is_none()
-            && self.active.is_none()
-            && self.window_type.is_none()
-            && self.group_panes.is_none()
-            && self.float_parent.is_none()
-            && self.float_width.is_none()
-            && self.float_height.is_none()
-            && self.float_drawer.is_none()
-            && self.float_bg.is_none()
-            && self.float_noheader.is_none()
-            && self.zoomed.is_none()
-    }
-}
-
-/// Delta state update - only includes what changed
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct TmuxDelta {
-    /// Sequence number for ordering
-    pub seq: u64,
-    /// Changed panes: pane_id -> delta (None = pane removed)
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub panes: Option<std::collections::HashMap<String, Option<PaneDelta>>>,
-    /// Changed windows: window_id -> delta (None = window removed)
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub windows: Option<std::collections::HashMap<String, Option<WindowDelta>>>,
-    /// New panes (full data for newly added panes)
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub new_panes: Option<Vec<TmuxPane>>,
-    /// New windows (full data for newly added windows)
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub new_windows: Option<Vec<TmuxWindow>>,
-    /// Active window changed
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub active_window_id: Option<String>,
-    /// Active pane changed
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub active_pane_id: Option<String>,
-    /// Status line changed
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub status_line: Option<String>,
-    /// Total dimensions changed
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub total_width: Option<u32>,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub total_height: Option<u32>,
-}
-
-impl TmuxDelta {
-    pub fn new(seq: u64) -> Self {
-        Self {
-            seq,
-            panes: None,
-            windows: None,
-            new_panes: None,
-            new_windows: None,
-            active_window_id: None,
-            active_pane_id: None,
-            status_line: None,
-            total_width: None,
-            total_height: None,
-        }
-    }
-
-    pub fn is_empty(&self) -> bool {
-        self.panes.is_none()
-            && self.windows.is_none()
-            && self.new_panes.is_none()
-            && self.new_windows.is_none()
-            && self.active_window_id.is_none()
-            && self.active_pane_id.is_none()
-            && self.status_line.is_none()
-            && self.total_width.is_none()
-            && self.total_height.is_none()
-    }
-}
-
-/// Message type for state updates (full or delta)
-#[derive(Debug, Clone, Serialize, Deserialize)]
-#[serde(tag = "type")]
-pub enum StateUpdate {
-    /// Full state (used for initial sync and reconnection)
-    #[serde(rename = "full")]
-    Full { state: TmuxState },
-    /// Delta update (used for incremental updates)
-    #[serde(rename = "delta")]
-    Delta { delta: TmuxDelta },
-}
-
 /// Capture the state of all panes in a specific session's current window, via
 /// one-off external tmux reads (the polling/snapshot fallback path — the live
 /// server/Tauri paths get state from the control-mode aggregator instead).
@@ -695,12 +278,23 @@ pub fn capture_window_state_for_session(session_name: &str) -> Result<TmuxState,
             // lands — copy mode entered immediately after page load now asks
             // for the correct FETCH_SCROLLBACK_CELLS range instead of `start: -0`.
             history_size: info.history_size,
+            scroll_position: 0,
             selection_present: false,
             selection_start_x: 0,
             selection_start_y: 0,
+            selection_end_x: 0,
+            selection_end_y: 0,
             images: Vec::new(),
+            command_blocks: Vec::new(),
             cursor_shape: 0,
             cursor_hidden: false,
+            extended_keys: false,
+            colors: Default::default(),
+            sandbox: String::new(),
+            pointer_shape: String::new(),
+            widget: None,
+            cwd: None,
+            pid: None,
         });
     }
 
@@ -729,7 +323,12 @@ pub fn capture_window_state_for_session(session_name: &str) -> Result<TmuxState,
             float_drawer: None,
             float_bg: None,
             float_noheader: false,
+            float_x: None,
+            float_y: None,
+            float_z: None,
+            float_hidden: false,
             zoomed: w.zoomed,
+            status: None,
         })
         .collect();
 
@@ -737,8 +336,8 @@ pub fn capture_window_state_for_session(session_name: &str) -> Result<TmuxState,
     let active_pane_id = panes.iter().find(|p| p.active).map(|p| p.tmux_id.clone());
 
     // Capture status line (use total_width from pane layout for proper padding)
-    let status_line =
-        executor::capture_status_line(session_name, total_width as usize).unwrap_or_default();
+    let status = executor::capture_status_line(session_name).ok().flatten();
+    let width = total_width as usize;
 
     Ok(TmuxState {
         session_name: session_name.to_string(),
@@ -748,7 +347,18 @@ pub fn capture_window_state_for_session(session_name: &str) -> Result<TmuxState,
         windows,
         total_width,
         total_height,
-        status_line,
+        status_line: status.as_ref().map(|s| s.render(width)).unwrap_or_default(),
+        status_segments: status.as_ref().map(|s| s.segments()).unwrap_or_default(),
+        status_lines: status
+            .as_ref()
+            .map(|s| s.extra_lines(width))
+            .unwrap_or_default(),
+        status_position: status.as_ref().map(|s| s.position()).unwrap_or_default(),
+        session_group: None,
+        do_not_disturb: false,
+        theme: Default::default(),
+        ssh_agent: Default::default(),
+        epoch: 0,
     })
 }
 
@@ -773,6 +383,93 @@ mod tests {
         assert_eq!(WindowType::parse(""), None);
     }
 
+    fn line(text: &str) -> TerminalLine {
+        text.chars()
+            .map(|c| TerminalCell {
+                char: c.to_string(),
+                style: None,
+                width: 1,
+            })
+            .collect()
+    }
+
+    fn pane_delta(content: &[(usize, &str)]) -> PaneDelta {
+        PaneDelta {
+            content: Some(
+                content
+                    .iter()
+                    .map(|&(row, text)| (row, line(text)))
+                    .collect(),
+            ),
+            ..Default::default()
+        }
+    }
+
+    fn delta_with(seq: u64, panes: Vec<(&str, Option<PaneDelta>)>) -> TmuxDelta {
+        let mut delta = TmuxDelta::new(seq);
+        delta.panes = Some(
+            panes
+                .into_iter()
+                .map(|(id, d)| (id.to_string(), d))
+                .collect(),
+        );
+        delta
+    }
+
+    #[test]
+    fn merged_pane_delta_moves_changed_rows_with_a_later_scroll() {
+        let mut first = pane_delta(&[(1, "b"), (5, "a")]);
+        first.cursor_x = Some(3);
+        let mut second = pane_delta(&[(0, "c")]);
+        second.scroll = Some(ScrollDelta {
+            lines: 2,
+            new_rows: vec![line("y"), line("z")],
+        });
+        second.title = Some("vim".to_string());
+        first.merge(second);
+
+        let content = first.content.unwrap();
+        assert_eq!(content.len(), 2);
+        assert_eq!(content[&3], line("a"));
+        assert_eq!(content[&0], line("c"));
+        assert_eq!(first.scroll.unwrap().lines, 2);
+        assert_eq!(
+            (first.cursor_x, first.title.as_deref()),
+            (Some(3), Some("vim"))
+        );
+    }
+
+    #[test]
+    fn deltas_merge_unless_the_result_would_be_ambiguous() {
+        let scrolled = || PaneDelta {
+            scroll: Some(ScrollDelta {
+                lines: 1,
+                new_rows: vec![line("x")],
+            }),
+            ..Default::default()
+        };
+        let first = delta_with(1, vec![("%1", Some(scrolled()))]);
+        assert!(!first.can_merge(&delta_with(2, vec![("%1", Some(scrolled()))])));
+        assert!(first.can_merge(&delta_with(2, vec![("%2", Some(scrolled()))])));
+        let mut other_epoch = delta_with(2, vec![]);
+        other_epoch.epoch = 9;
+        assert!(!first.can_merge(&other_epoch));
+
+        // A pane added in full can be dropped again, but not patched.
+        let mut added = TmuxDelta::new(1);
+        let pane = r#"{"id":7,"tmux_id":"%7","window_id":"@0","content":[],"cursor_x":0,
+            "cursor_y":0,"width":80,"height":24,"x":0,"y":0,"active":false,"command":"",
+            "title":"","border_title":"","in_mode":false,"copy_cursor_x":0,"copy_cursor_y":0}"#;
+        added.new_panes = Some(vec![serde_json::from_str(pane).unwrap()]);
+        assert!(!added.can_merge(&delta_with(2, vec![("%7", Some(pane_delta(&[])))])));
+        let removed = delta_with(3, vec![("%7", None)]);
+        assert!(added.can_merge(&removed));
+        added.merge(removed);
+        assert_eq!(added.seq, 3);
+        assert!(added.new_panes.unwrap().is_empty());
+        assert!(added.panes.unwrap()["%7"].is_none());
+    }
+
     #[test]
     fn window_type_serializes_as_kebab() {
         let ty = WindowType::FloatBackdrop;
@@ -826,6 +523,52 @@ mod vt100_capture_test {
         assert_eq!(content[2][0].char, "3", "Third row should start with '3'");
     }
 
+    #[test]
+    fn wide_and_combining_characters_keep_column_alignment() {
+        // "漢" is double-width; "e\u{301}" is e + combining acute (one column).
+        let mut terminal = vt100::Parser::new(1, 16, 0);
+        terminal.process("a漢e\u{301}b".as_bytes());
+        let row = &crate::extract_cells_from_screen(terminal.screen())[0];
+
+        let chars: Vec<&str> = row.iter().map(|c| c.char.as_str()).collect();
+        assert_eq!(chars, vec!["a", "漢", " ", "e\u{301}", "b"]);
+        let widths: Vec<u8> = row.iter().map(|c| c.width).collect();
+        assert_eq!(widths, vec![1, 2, 0, 1, 1]);
+
+        let json = serde_json::to_value(&row[1]).unwrap();
+        assert_eq!(json["w"], 2);
+        assert!(serde_json::to_value(&row[0]).unwrap().get("w").is_none());
+    }
+
+    #[test]
+    fn extended_sgr_attributes_reach_cell_style() {
+        // Curly red underline (colon form), double underline via SGR 21,
+        // strikethrough and blink, then each reset individually.
+        let bytes = b"\x1b[4:3;58:2::255:0:0mc\x1b[4:0;59m\x1b[21md\x1b[24;9;5ms\x1b[29;25mp";
+        let mut terminal = vt100::Parser::new(1, 16, 0);
+        terminal.process(bytes);
+        let row = &crate::extract_cells_from_screen(terminal.screen())[0];
+        let style = |col: usize| row[col].style.clone().unwrap_or_default();
+
+        let curly = style(0);
+        assert!(curly.underline);
+        assert_eq!(curly.underline_style, Some(crate::UnderlineStyle::Curly));
+        assert_eq!(
+            curly.underline_color,
+            Some(crate::CellColor::Rgb { r: 255, g: 0, b: 0 })
+        );
+
+        let double = style(1);
+        assert_eq!(double.underline_style, Some(crate::UnderlineStyle::Double));
+        assert_eq!(double.underline_color, None);
+
+        let struck = style(2);
+        assert!(!struck.underline);
+        assert!(struck.strikethrough && struck.blink);
+
+        assert!(row[3].style.is_none());
+    }
+
     #[test]
     fn test_sgr_dim_faint_propagates_to_cell_style() {
         // SGR 2 (faint/dim) — used by Claude Code's TUI for autosuggestion text.
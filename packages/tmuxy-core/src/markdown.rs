@@ -0,0 +1,692 @@
+//! Markdown rendering shared by the markdown viewer (HTML, served at
+//! `/api/markdown`) and `tmuxy md` (styled cells, printed as ANSI).
+//!
+//! Both outputs come from one pulldown-cmark parse with the GFM extensions
+//! the viewer supports (tables, strikethrough, task lists), and code fences
+//! in both are highlighted by the same syntect theme. Raw HTML in the source
+//! is escaped rather than passed through: the viewer inserts the HTML into
+//! the page as-is.
+//!
+//! Mermaid fences aren't highlighted: the HTML carries their source in a
+//! `<pre class="tmuxy-mermaid">` for the frontend to draw.
+
+use crate::{CellColor, CellStyle, PaneContent, TerminalCell, TerminalLine};
+use pulldown_cmark::{CodeBlockKind, Event, HeadingLevel, Options, Parser, Tag, TagEnd};
+use std::sync::OnceLock;
+use syntect::easy::HighlightLines;
+use syntect::highlighting::{FontStyle, Theme, ThemeSet};
+use syntect::parsing::SyntaxSet;
+use syntect::util::LinesWithEndings;
+use unicode_width::UnicodeWidthChar;
+
+/// Class of the `<pre>` holding a mermaid fence's source in the HTML.
+pub const MERMAID_CLASS: &str = "tmuxy-mermaid";
+
+/// Code fence theme, from syntect's bundled set.
+const THEME: &str = "base16-ocean.dark";
+
+fn options() -> Options {
+    Options::ENABLE_TABLES | Options::ENABLE_STRIKETHROUGH | Options::ENABLE_TASKLISTS
+}
+
+/// Loading the syntax definitions takes tens of milliseconds: do it once.
+fn highlighter() -> &'static (SyntaxSet, Theme) {
+    static HIGHLIGHTER: OnceLock<(SyntaxSet, Theme)> = OnceLock::new();
+    HIGHLIGHTER.get_or_init(|| {
+        let mut themes = ThemeSet::load_defaults();
+        let theme = themes.themes.remove(THEME).unwrap_or_default();
+        (SyntaxSet::load_defaults_newlines(), theme)
+    })
+}
+
+/// A code block as lines of styled runs. Unknown languages come back as
+/// plain text in the theme's foreground color.
+fn highlight(code: &str, lang: &str) -> Vec<Vec<(CellStyle, String)>> {
+    let (syntaxes, theme) = highlighter();
+    let syntax = syntaxes
+        .find_syntax_by_token(lang)
+        .unwrap_or_else(|| syntaxes.find_syntax_plain_text());
+    let mut lines = HighlightLines::new(syntax, theme);
+    LinesWithEndings::from(code)
+        .map(|line| {
+            let ranges = lines.highlight_line(line, syntaxes).unwrap_or_default();
+            ranges
+                .into_iter()
+                .map(|(style, text)| {
+                    let fg = style.foreground;
+                    let cell_style = CellStyle {
+                        fg: Some(CellColor::Rgb {
+                            r: fg.r,
+                            g: fg.g,
+                            b: fg.b,
+                        }),
+                        bold: style.font_style.contains(FontStyle::BOLD),
+                        italic: style.font_style.contains(FontStyle::ITALIC),
+                        underline: style.font_style.contains(FontStyle::UNDERLINE),
+                        ..Default::default()
+                    };
+                    (cell_style, text.trim_end_matches(['\n', '\r']).to_string())
+                })
+                .collect()
+        })
+        .collect()
+}
+
+fn fence_lang(kind: &CodeBlockKind) -> String {
+    match kind {
+        // Only the first word names the language: ```rust ignore
+        CodeBlockKind::Fenced(info) => info.split_whitespace().next().unwrap_or("").to_string(),
+        CodeBlockKind::Indented => String::new(),
+    }
+}
+
+fn escape_html(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => out.push_str("&amp;"),
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            '"' => out.push_str("&quot;"),
+            _ => out.push(c),
+        }
+    }
+    out
+}
+
+fn code_block_html(code: &str, lang: &str) -> String {
+    if lang == "mermaid" {
+        return format!(
+            "<pre class=\"{MERMAID_CLASS}\">{}</pre>\n",
+            escape_html(code)
+        );
+    }
+    let (_, theme) = highlighter();
+    let background = theme
+        .settings
+        .background
+        .map(|c| {
+            format!(
+                " style=\"background-color:#{:02x}{:02x}{:02x}\"",
+                c.r, c.g, c.b
+            )
+        })
+        .unwrap_or_default();
+    let class = if lang.is_empty() {
+        String::new()
+    } else {
+        format!(" class=\"language-{}\"", escape_html(lang))
+    };
+    let mut html = format!("<pre{background}><code{class}>");
+    for (i, line) in highlight(code, lang).into_iter().enumerate() {
+        if i > 0 {
+            html.push('\n');
+        }
+        for (style, text) in line {
+            let mut css = String::new();
+            if let Some(CellColor::Rgb { r, g, b }) = style.fg {
+                css.push_str(&format!("color:#{r:02x}{g:02x}{b:02x};"));
+            }
+            if style.bold {
+                css.push_str("font-weight:bold;");
+            }
+            if style.italic {
+                css.push_str("font-style:italic;");
+            }
+            html.push_str(&format!(
+                "<span style=\"{css}\">{}</span>",
+                escape_html(&text)
+            ));
+        }
+    }
+    html.push_str("</code></pre>\n");
+    html
+}
+
+/// Render markdown as an HTML fragment.
+pub fn to_html(markdown: &str) -> String {
+    let mut events = Vec::new();
+    let mut code: Option<(String, String)> = None;
+    for event in Parser::new_ext(markdown, options()) {
+        match (&mut code, event) {
+            (None, Event::Start(Tag::CodeBlock(kind))) => {
+                code = Some((fence_lang(&kind), String::new()));
+            }
+            (Some((_, text)), Event::Text(t)) => text.push_str(&t),
+            (Some((lang, text)), Event::End(TagEnd::CodeBlock)) => {
+                events.push(Event::Html(code_block_html(text, lang).into()));
+                code = None;
+            }
+            (_, Event::Html(html) | Event::InlineHtml(html)) => events.push(Event::Text(html)),
+            (_, event) => events.push(event),
+        }
+    }
+    let mut html = String::new();
+    pulldown_cmark::html::push_html(&mut html, events.into_iter());
+    html
+}
+
+/// Styles of headings by level: bold, in a color per level.
+fn heading_style(level: HeadingLevel) -> CellStyle {
+    let color = match level {
+        HeadingLevel::H1 => 5,
+        HeadingLevel::H2 => 4,
+        HeadingLevel::H3 => 6,
+        _ => 2,
+    };
+    CellStyle {
+        fg: Some(CellColor::Indexed(color)),
+        bold: true,
+        underline: level == HeadingLevel::H1,
+        ..Default::default()
+    }
+}
+
+/// One open container that indents its lines: a block quote's bar or a list
+/// item's marker (printed on the item's first line, spaces after).
+struct Indent {
+    first: Vec<(CellStyle, String)>,
+    rest: Vec<(CellStyle, String)>,
+    used: bool,
+}
+
+/// Builds the cell grid of [`to_cells`]: inline text collects into `inline`
+/// and is word-wrapped into lines when its block ends.
+struct CellWriter {
+    width: usize,
+    lines: PaneContent,
+    inline: Vec<(CellStyle, char)>,
+    styles: Vec<CellStyle>,
+    indents: Vec<Indent>,
+    /// Counters of the open lists; `None` for bullet lists.
+    lists: Vec<Option<u64>>,
+    /// Cells of the table row being built.
+    row: Vec<Vec<(CellStyle, char)>>,
+    in_table_head: bool,
+}
+
+impl CellWriter {
+    fn style(&self) -> CellStyle {
+        self.styles.last().cloned().unwrap_or_default()
+    }
+
+    fn push_style(&mut self, f: impl FnOnce(&mut CellStyle)) {
+        let mut style = self.style();
+        f(&mut style);
+        self.styles.push(style);
+    }
+
+    fn text(&mut self, text: &str) {
+        let style = self.style();
+        self.inline.extend(text.chars().map(|c| (style.clone(), c)));
+    }
+
+    /// The indent for the next line, marking first-line markers as used.
+    fn prefix(&mut self) -> TerminalLine {
+        let mut line = Vec::new();
+        for indent in &mut self.indents {
+            let part = if indent.used {
+                &indent.rest
+            } else {
+                &indent.first
+            };
+            for (style, text) in part {
+                push_str(&mut line, style, text);
+            }
+            indent.used = true;
+        }
+        line
+    }
+
+    fn blank_line(&mut self) {
+        if self.lines.last().is_some_and(|line| !line.is_empty()) {
+            self.lines.push(Vec::new());
+        }
+    }
+
+    /// Word-wrap the collected inline text into lines.
+    fn flush(&mut self) {
+        let inline = std::mem::take(&mut self.inline);
+        let mut line = self.prefix();
+        let mut start = line_width(&line);
+        let mut empty = true;
+        for word in split_words(&inline) {
+            let spaces = word.iter().take_while(|(_, c)| *c == ' ').count();
+            let (leading, rest) = word.split_at(spaces);
+            let width: usize = rest.iter().map(|(_, c)| char_width(*c)).sum();
+            if !empty && start + leading.len() + width > self.width {
+                self.lines.push(std::mem::take(&mut line));
+                line = self.prefix();
+                start = line_width(&line);
+            } else {
+                for (style, c) in leading {
+                    push_char(&mut line, style, *c);
+                }
+                start += leading.len();
+            }
+            for (style, c) in rest {
+                push_char(&mut line, style, *c);
+            }
+            start += width;
+            empty = false;
+        }
+        if !empty || self.indents.iter().any(|i| !i.used) {
+            self.lines.push(line);
+        }
+    }
+
+    fn code_block(&mut self, code: &str, lang: &str) {
+        for runs in highlight(code, lang) {
+            let mut line = self.prefix();
+            push_str(&mut line, &CellStyle::default(), "  ");
+            for (style, text) in runs {
+                push_str(&mut line, &style, &text);
+            }
+            self.lines.push(line);
+        }
+    }
+
+    fn table_row(&mut self) {
+        let cells = std::mem::take(&mut self.row);
+        let separator = CellStyle {
+            dim: true,
+            ..Default::default()
+        };
+        for (i, cell) in cells.into_iter().enumerate() {
+            if i > 0 {
+                self.inline
+                    .extend(" │ ".chars().map(|c| (separator.clone(), c)));
+            }
+            self.inline.extend(cell);
+        }
+        let inline = std::mem::take(&mut self.inline);
+        let mut line = self.prefix();
+        for (style, c) in inline {
+            push_char(&mut line, &style, c);
+        }
+        self.lines.push(line);
+    }
+}
+
+/// Split into words, each carrying the spaces before it.
+fn split_words(inline: &[(CellStyle, char)]) -> Vec<&[(CellStyle, char)]> {
+    let mut words = Vec::new();
+    let mut start = 0;
+    for i in 1..inline.len() {
+        if inline[i].1 == ' ' && inline[i - 1].1 != ' ' {
+            words.push(&inline[start..i]);
+            start = i;
+        }
+    }
+    if start < inline.len() {
+        words.push(&inline[start..]);
+    }
+    words
+}
+
+fn char_width(c: char) -> usize {
+    c.width().unwrap_or(0)
+}
+
+fn line_width(line: &TerminalLine) -> usize {
+    line.iter().map(|cell| cell.width as usize).sum()
+}
+
+fn push_char(line: &mut TerminalLine, style: &CellStyle, c: char) {
+    match char_width(c) {
+        // Combining marks join the previous cell, as vt100 does.
+        0 => match line.last_mut() {
+            Some(cell) => cell.char.push(c),
+            None => line.push(TerminalCell::with_style(c.to_string(), style.clone())),
+        },
+        2 => {
+            line.push(TerminalCell::with_style(c.to_string(), style.clone()).with_width(2));
+            line.push(TerminalCell::new(" ".to_string()).with_width(0));
+        }
+        _ => line.push(TerminalCell::with_style(c.to_string(), style.clone())),
+    }
+}
+
+fn push_str(line: &mut TerminalLine, style: &CellStyle, text: &str) {
+    for c in text.chars() {
+        push_char(line, style, c);
+    }
+}
+
+/// Render markdown as styled lines wrapped to `width` columns, as the pane
+/// content types carry them. Code blocks aren't wrapped.
+pub fn to_cells(markdown: &str, width: usize) -> PaneContent {
+    let mut w = CellWriter {
+        width: width.max(20),
+        lines: Vec::new(),
+        inline: Vec::new(),
+        styles: Vec::new(),
+        indents: Vec::new(),
+        lists: Vec::new(),
+        row: Vec::new(),
+        in_table_head: false,
+    };
+    let mut code: Option<(String, String)> = None;
+    for event in Parser::new_ext(markdown, options()) {
+        if let Some((lang, text)) = &mut code {
+            match event {
+                Event::Text(t) => text.push_str(&t),
+                Event::End(TagEnd::CodeBlock) => {
+                    let (lang, text) = (lang.clone(), std::mem::take(text));
+                    w.code_block(&text, &lang);
+                    code = None;
+                    w.blank_line();
+                }
+                _ => {}
+            }
+            continue;
+        }
+        match event {
+            Event::Start(tag) => match tag {
+                Tag::Heading { level, .. } => {
+                    let style = heading_style(level);
+                    w.styles.push(style);
+                }
+                Tag::BlockQuote(_) => {
+                    let bar = vec![(
+                        CellStyle {
+                            dim: true,
+                            ..Default::default()
+                        },
+                        "│ ".to_string(),
+                    )];
+                    w.indents.push(Indent {
+                        first: bar.clone(),
+                        rest: bar,
+                        used: false,
+                    });
+                    w.push_style(|s| s.italic = true);
+                }
+                Tag::CodeBlock(kind) => code = Some((fence_lang(&kind), String::new())),
+                Tag::List(start) => {
+                    if !w.inline.is_empty() {
+                        w.flush();
+                    }
+                    w.lists.push(start);
+                }
+                Tag::Item => {
+                    if !w.inline.is_empty() {
+                        w.flush();
+                    }
+                    let marker = match w.lists.last_mut() {
+                        Some(Some(n)) => {
+                            *n += 1;
+                            format!("{}. ", *n - 1)
+                        }
+                        _ => "• ".to_string(),
+                    };
+                    let pad = " ".repeat(marker.chars().count());
+                    let style = CellStyle {
+                        fg: Some(CellColor::Indexed(4)),
+                        ..Default::default()
+                    };
+                    w.indents.push(Indent {
+                        first: vec![(style, marker)],
+                        rest: vec![(CellStyle::default(), pad)],
+                        used: false,
+                    });
+                }
+                Tag::Emphasis => w.push_style(|s| s.italic = true),
+                Tag::Strong => w.push_style(|s| s.bold = true),
+                Tag::Strikethrough => w.push_style(|s| s.strikethrough = true),
+                Tag::Link { dest_url, .. } => {
+                    let url = dest_url.to_string();
+                    w.push_style(|s| {
+                        s.fg = Some(CellColor::Indexed(4));
+                        s.underline = true;
+                        s.url = Some(url);
+                    });
+                }
+                Tag::Image { .. } => {
+                    w.push_style(|s| s.dim = true);
+                    w.text("[image: ");
+                }
+                Tag::TableHead => w.in_table_head = true,
+                Tag::TableCell => {
+                    if w.in_table_head {
+                        w.push_style(|s| s.bold = true);
+                    } else {
+                        w.styles.push(w.style());
+                    }
+                }
+                _ => {}
+            },
+            Event::End(tag) => match tag {
+                TagEnd::Paragraph => {
+                    w.flush();
+                    // Items of tight lists have no paragraphs; loose ones
+                    // keep a blank line between them.
+                    w.blank_line();
+                }
+                TagEnd::Heading(_) => {
+                    w.flush();
+                    w.styles.pop();
+                    w.blank_line();
+                }
+                TagEnd::BlockQuote(_) => {
+                    w.indents.pop();
+                    w.styles.pop();
+                    w.blank_line();
+                }
+                TagEnd::List(_) => {
+                    w.lists.pop();
+                    if w.lists.is_empty() {
+                        w.blank_line();
+                    }
+                }
+                TagEnd::Item => {
+                    if !w.inline.is_empty() {
+                        w.flush();
+                    }
+                    w.indents.pop();
+                }
+                TagEnd::Emphasis | TagEnd::Strong | TagEnd::Strikethrough | TagEnd::Link => {
+                    w.styles.pop();
+                }
+                TagEnd::Image => {
+                    w.text("]");
+                    w.styles.pop();
+                }
+                TagEnd::TableCell => {
+                    let cell = std::mem::take(&mut w.inline);
+                    w.row.push(cell);
+                    w.styles.pop();
+                }
+                TagEnd::TableHead => {
+                    w.table_row();
+                    w.in_table_head = false;
+                }
+                TagEnd::TableRow => w.table_row(),
+                TagEnd::Table => w.blank_line(),
+                _ => {}
+            },
+            Event::Text(text) => w.text(&text),
+            Event::Code(text) => {
+                w.push_style(|s| s.fg = Some(CellColor::Indexed(3)));
+                w.text(&text);
+                w.styles.pop();
+            }
+            Event::Html(html) | Event::InlineHtml(html) => {
+                w.push_style(|s| s.dim = true);
+                w.text(html.trim_end_matches('\n'));
+                w.styles.pop();
+            }
+            Event::SoftBreak => w.text(" "),
+            Event::HardBreak => w.flush(),
+            Event::Rule => {
+                let mut line = w.prefix();
+                let room = w.width.saturating_sub(line_width(&line));
+                let style = CellStyle {
+                    dim: true,
+                    ..Default::default()
+                };
+                push_str(&mut line, &style, &"─".repeat(room));
+                w.lines.push(line);
+                w.blank_line();
+            }
+            Event::TaskListMarker(done) => w.text(if done { "[x] " } else { "[ ] " }),
+            _ => {}
+        }
+    }
+    while w.lines.last().is_some_and(|line| line.is_empty()) {
+        w.lines.pop();
+    }
+    w.lines
+}
+
+/// SGR parameters for a style, after a reset.
+fn sgr(style: &CellStyle) -> String {
+    let mut codes: Vec<String> = vec!["0".into()];
+    for (on, code) in [
+        (style.bold, "1"),
+        (style.dim, "2"),
+        (style.italic, "3"),
+        (style.underline, "4"),
+        (style.blink, "5"),
+        (style.inverse, "7"),
+        (style.strikethrough, "9"),
+    ] {
+        if on {
+            codes.push(code.into());
+        }
+    }
+    for (color, base) in [(&style.fg, 30), (&style.bg, 40)] {
+        match color {
+            Some(CellColor::Indexed(n)) if *n < 8 => codes.push((base + *n as u32).to_string()),
+            Some(CellColor::Indexed(n)) if *n < 16 => {
+                codes.push((base + 60 + (*n - 8) as u32).to_string())
+            }
+            Some(CellColor::Indexed(n)) => codes.push(format!("{};5;{n}", base + 8)),
+            Some(CellColor::Rgb { r, g, b }) => codes.push(format!("{};2;{r};{g};{b}", base + 8)),
+            None => {}
+        }
+    }
+    format!("\x1b[{}m", codes.join(";"))
+}
+
+/// Render markdown for a terminal: [`to_cells`] as ANSI escapes, with
+/// links as OSC 8 hyperlinks. Every line ends with a newline.
+pub fn to_ansi(markdown: &str, width: usize) -> String {
+    let mut out = String::new();
+    for line in to_cells(markdown, width) {
+        let mut current: Option<CellStyle> = None;
+        for cell in line.iter().filter(|cell| cell.width > 0) {
+            let style = cell.style.clone().unwrap_or_default();
+            if current.as_ref() != Some(&style) {
+                let url = |s: &Option<CellStyle>| s.as_ref().and_then(|s| s.url.clone());
+                if url(&current) != style.url {
+                    out.push_str(&format!(
+                        "\x1b]8;;{}\x1b\\",
+                        style.url.as_deref().unwrap_or("")
+                    ));
+                }
+                out.push_str(&sgr(&style));
+                current = Some(style);
+            }
+            out.push_str(&cell.char);
+        }
+        if current.as_ref().is_some_and(|s| s.url.is_some()) {
+            out.push_str("\x1b]8;;\x1b\\");
+        }
+        if current.is_some() {
+            out.push_str("\x1b[0m");
+        }
+        out.push('\n');
+    }
+    out
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used, clippy::expect_used)]
+mod tests {
+    use super::*;
+
+    fn text(line: &TerminalLine) -> String {
+        line.iter()
+            .filter(|cell| cell.width > 0)
+            .map(|cell| cell.char.as_str())
+            .collect()
+    }
+
+    fn texts(markdown: &str, width: usize) -> Vec<String> {
+        to_cells(markdown, width).iter().map(text).collect()
+    }
+
+    #[test]
+    fn html_highlights_fences_and_escapes_raw_html() {
+        let html = to_html("# Title\n\n<script>x()</script>\n\n```rust\nfn main() {}\n```\n");
+        assert!(html.contains("<h1>Title</h1>"));
+        assert!(html.contains("&lt;script&gt;"));
+        assert!(!html.contains("<script>"));
+        assert!(html.contains("<code class=\"language-rust\">"));
+        assert!(html.contains("<span style=\"color:#"));
+        assert!(html.contains(">fn</span>"));
+    }
+
+    #[test]
+    fn html_keeps_mermaid_source_for_the_frontend() {
+        let html = to_html("```mermaid\ngraph TD; A-->B\n```\n");
+        assert_eq!(
+            html,
+            "<pre class=\"tmuxy-mermaid\">graph TD; A--&gt;B\n</pre>\n"
+        );
+    }
+
+    #[test]
+    fn html_supports_the_gfm_extensions() {
+        let html = to_html("| a | b |\n|---|---|\n| 1 | 2 |\n\n- [x] done\n\n~~gone~~\n");
+        assert!(html.contains("<table>"));
+        assert!(html.contains("checkbox"));
+        assert!(html.contains("<del>gone</del>"));
+    }
+
+    #[test]
+    fn cells_wrap_paragraphs_and_style_inline_spans() {
+        let cells = to_cells("Some **bold** words that wrap around here", 20);
+        let lines: Vec<String> = cells.iter().map(text).collect();
+        assert_eq!(lines, ["Some bold words that", "wrap around here"]);
+        assert!(cells[0][5].style.as_ref().unwrap().bold);
+        assert!(cells[0][0].style.is_none());
+    }
+
+    #[test]
+    fn cells_indent_lists_and_quotes() {
+        assert_eq!(
+            texts("- one\n- two\n  1. nested\n\n> quoted\n", 40),
+            ["• one", "• two", "  1. nested", "", "│ quoted"]
+        );
+    }
+
+    #[test]
+    fn cells_highlight_code_without_wrapping() {
+        let cells = to_cells("```rust\nlet x = 1;\n```\n", 20);
+        assert_eq!(text(&cells[0]), "  let x = 1;");
+        let keyword = cells[0][2].style.as_ref().unwrap();
+        assert!(matches!(keyword.fg, Some(CellColor::Rgb { .. })));
+    }
+
+    #[test]
+    fn cells_lay_out_wide_characters_in_two_columns() {
+        let cells = to_cells("漢字", 20);
+        assert_eq!(
+            cells[0].iter().map(|c| c.width).collect::<Vec<_>>(),
+            [2, 0, 2, 0]
+        );
+    }
+
+    #[test]
+    fn ansi_styles_links_and_resets_each_line() {
+        let ansi = to_ansi("# Hi\n\nsee [docs](https://x.dev)\n", 40);
+        let lines: Vec<&str> = ansi.lines().collect();
+        assert_eq!(lines[0], "\x1b[0;1;4;35mHi\x1b[0m");
+        assert_eq!(lines[1], "");
+        assert!(lines[2].starts_with("\x1b[0msee "));
+        assert!(lines[2].contains("\x1b]8;;https://x.dev\x1b\\\x1b[0;4;34mdocs"));
+        assert!(lines[2].ends_with("\x1b]8;;\x1b\\\x1b[0m"));
+    }
+}
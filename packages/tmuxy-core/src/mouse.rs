@@ -0,0 +1,358 @@
+//! Mouse event routing.
+//!
+//! The frontend used to decide on its own how a wheel tick or a drag reached a
+//! pane (SGR report, arrow keys, or nothing), reading `alternate_on` /
+//! `mouse_any_flag` off a snapshot that can lag the pane by a sync interval.
+//! The decision now lives here, next to the aggregator that owns the live
+//! flags: clients send raw [`MouseEvent`]s and [`mouse_commands`] turns them
+//! into the control-mode commands for the pane's current mode.
+//!
+//! Routing, in precedence order:
+//! 1. Pane in copy mode → wheel scrolls the copy-mode view.
+//! 2. Mouse tracking (`mouse_any_flag`) → SGR reports, injected via
+//!    `send-keys -H` (see docs/TMUX.md for why not `-l` or paste-buffer).
+//! 3. Alternate screen without tracking (less, vim `mouse=`) → wheel becomes
+//!    Up/Down arrow keys.
+//! 4. Plain shell → wheel-up enters copy mode and scrolls; wheel-down at the
+//!    live bottom is a no-op.
+//!
+//! Press/release/drag are only forwarded in case 2 — outside mouse tracking
+//! the frontend owns focus and selection.
+
+pub use tmuxy_protocol::MouseEvent;
+
+use crate::executor::tmux_quote;
+use crate::text_input::hex_command;
+
+/// SGR button code for a wheel-up tick.
+const SGR_WHEEL_UP: u8 = 64;
+/// SGR button code for a wheel-down tick.
+const SGR_WHEEL_DOWN: u8 = 65;
+/// Added to the button code of a motion-with-button-held report.
+const SGR_MOTION: u8 = 32;
+
+/// The pane flags the routing decision depends on.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct PaneMouseMode {
+    pub alternate_on: bool,
+    pub mouse_any_flag: bool,
+    pub in_mode: bool,
+    /// Rows in the pane: one wheel event scrolls at most a screenful.
+    pub height: u32,
+}
+
+/// Collapse each run of consecutive drags of the same button into its last
+/// position. Motion arrives far faster than a terminal app can consume it, and
+/// only the final position of a run matters; presses, releases and wheel ticks
+/// are never dropped.
+pub fn coalesce_drags(events: &[MouseEvent]) -> Vec<MouseEvent> {
+    let mut out: Vec<MouseEvent> = Vec::with_capacity(events.len());
+    for event in events {
+        if let (
+            MouseEvent::Drag { button, .. },
+            Some(MouseEvent::Drag {
+                button: prev_button,
+                ..
+            }),
+        ) = (event, out.last())
+        {
+            if button == prev_button {
+                out.pop();
+            }
+        }
+        out.push(*event);
+    }
+    out
+}
+
+/// Build the control-mode commands delivering `events` to `pane_id` in its
+/// current `mode`. Drags are coalesced first, and consecutive SGR reports share
+/// one `send-keys -H` so a burst costs a single round-trip.
+pub fn mouse_commands(pane_id: &str, events: &[MouseEvent], mode: PaneMouseMode) -> Vec<String> {
+    let mut cmds = Vec::new();
+    let mut sgr = String::new();
+    for event in coalesce_drags(events) {
+        let before = sgr.len();
+        let routed = route(pane_id, event, mode, &mut sgr);
+        if sgr.len() == before && !routed.is_empty() {
+            flush_sgr(pane_id, &mut sgr, &mut cmds);
+            cmds.extend(routed);
+        }
+    }
+    flush_sgr(pane_id, &mut sgr, &mut cmds);
+    cmds
+}
+
+/// Route one event: SGR reports are appended to `sgr`, anything else is
+/// returned as standalone commands.
+fn route(pane_id: &str, event: MouseEvent, mode: PaneMouseMode, sgr: &mut String) -> Vec<String> {
+    if let MouseEvent::Wheel { lines, .. } = event {
+        if lines == 0 {
+            return Vec::new();
+        }
+        let pane = tmux_quote(pane_id);
+        let count = lines.unsigned_abs().min(mode.height.max(1));
+        let up = lines < 0;
+        if mode.in_mode {
+            let dir = if up { "scroll-up" } else { "scroll-down" };
+            return vec![format!("send-keys -t {pane} -X -N {count} {dir}")];
+        }
+        if !mode.mouse_any_flag {
+            if mode.alternate_on {
+                let key = if up { "Up" } else { "Down" };
+                return vec![format!("send-keys -t {pane} -N {count} {key}")];
+            }
+            if !up {
+                return Vec::new();
+            }
+            // `-e` exits copy mode again once scrolled back to the bottom.
+            return vec![
+                format!("copy-mode -e -t {pane}"),
+                format!("send-keys -t {pane} -X -N {count} scroll-up"),
+            ];
+        }
+    }
+    if !mode.mouse_any_flag || mode.in_mode {
+        return Vec::new();
+    }
+    match event {
+        MouseEvent::Press { button, x, y } => push_sgr(sgr, button, x, y, false),
+        MouseEvent::Release { button, x, y } => push_sgr(sgr, button, x, y, true),
+        MouseEvent::Drag { button, x, y } => {
+            push_sgr(sgr, button.saturating_add(SGR_MOTION), x, y, false)
+        }
+        MouseEvent::Wheel { lines, x, y } => {
+            let button = if lines < 0 {
+                SGR_WHEEL_UP
+            } else {
+                SGR_WHEEL_DOWN
+            };
+            for _ in 0..lines.unsigned_abs().min(mode.height.max(1)) {
+                push_sgr(sgr, button, x, y, false);
+            }
+        }
+    }
+    Vec::new()
+}
+
+/// Append one SGR report (`ESC [ < b ; x ; y M|m`, 1-indexed) to `sgr`.
+fn push_sgr(sgr: &mut String, button: u8, x: u32, y: u32, release: bool) {
+    let end = if release { 'm' } else { 'M' };
+    sgr.push_str(&format!(
+        "\x1b[<{};{};{}{}",
+        button,
+        x.saturating_add(1),
+        y.saturating_add(1),
+        end
+    ));
+}
+
+/// Emit the pending SGR bytes as one `send-keys -H` and clear the buffer.
+fn flush_sgr(pane_id: &str, sgr: &mut String, cmds: &mut Vec<String>) {
+    if sgr.is_empty() {
+        return;
+    }
+    cmds.push(hex_command(pane_id, sgr.as_bytes()));
+    sgr.clear();
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used, clippy::expect_used)]
+mod tests {
+    use super::*;
+
+    const SHELL: PaneMouseMode = PaneMouseMode {
+        alternate_on: false,
+        mouse_any_flag: false,
+        in_mode: false,
+        height: 24,
+    };
+
+    const TRACKING: PaneMouseMode = PaneMouseMode {
+        alternate_on: true,
+        mouse_any_flag: true,
+        ..SHELL
+    };
+
+    fn hex(s: &str) -> String {
+        s.bytes()
+            .map(|b| format!("{b:02x}"))
+            .collect::<Vec<_>>()
+            .join(" ")
+    }
+
+    #[test]
+    fn wheel_in_tracking_pane_batches_sgr_reports() {
+        let cmds = mouse_commands(
+            "%1",
+            &[MouseEvent::Wheel {
+                lines: -2,
+                x: 4,
+                y: 9,
+            }],
+            TRACKING,
+        );
+        let expected = format!(
+            "send-keys -t '%1' -H {}",
+            hex("\x1b[<64;5;10M\x1b[<64;5;10M")
+        );
+        assert_eq!(cmds, vec![expected]);
+    }
+
+    #[test]
+    fn wheel_in_alternate_screen_becomes_arrow_keys() {
+        let mode = PaneMouseMode {
+            alternate_on: true,
+            ..SHELL
+        };
+        let cmds = mouse_commands(
+            "%1",
+            &[MouseEvent::Wheel {
+                lines: 3,
+                x: 0,
+                y: 0,
+            }],
+            mode,
+        );
+        assert_eq!(cmds, vec!["send-keys -t '%1' -N 3 Down".to_string()]);
+    }
+
+    #[test]
+    fn wheel_up_in_shell_enters_copy_mode() {
+        let up = MouseEvent::Wheel {
+            lines: -5,
+            x: 0,
+            y: 0,
+        };
+        let cmds = mouse_commands("%1", &[up], SHELL);
+        assert_eq!(
+            cmds,
+            vec![
+                "copy-mode -e -t '%1'".to_string(),
+                "send-keys -t '%1' -X -N 5 scroll-up".to_string(),
+            ]
+        );
+
+        let down = MouseEvent::Wheel {
+            lines: 5,
+            x: 0,
+            y: 0,
+        };
+        assert!(mouse_commands("%1", &[down], SHELL).is_empty());
+    }
+
+    #[test]
+    fn copy_mode_takes_precedence_over_mouse_tracking() {
+        let mode = PaneMouseMode {
+            in_mode: true,
+            ..TRACKING
+        };
+        let wheel = MouseEvent::Wheel {
+            lines: 2,
+            x: 0,
+            y: 0,
+        };
+        let press = MouseEvent::Press {
+            button: 0,
+            x: 0,
+            y: 0,
+        };
+        assert_eq!(
+            mouse_commands("%1", &[wheel, press], mode),
+            vec!["send-keys -t '%1' -X -N 2 scroll-down".to_string()]
+        );
+    }
+
+    #[test]
+    fn clicks_are_not_forwarded_without_mouse_tracking() {
+        let press = MouseEvent::Press {
+            button: 0,
+            x: 1,
+            y: 1,
+        };
+        assert!(mouse_commands("%1", &[press], SHELL).is_empty());
+    }
+
+    #[test]
+    fn drag_runs_collapse_to_their_last_position() {
+        let events = [
+            MouseEvent::Press {
+                button: 0,
+                x: 0,
+                y: 0,
+            },
+            MouseEvent::Drag {
+                button: 0,
+                x: 1,
+                y: 0,
+            },
+            MouseEvent::Drag {
+                button: 0,
+                x: 2,
+                y: 0,
+            },
+            MouseEvent::Drag {
+                button: 0,
+                x: 3,
+                y: 1,
+            },
+            MouseEvent::Release {
+                button: 0,
+                x: 3,
+                y: 1,
+            },
+        ];
+        let coalesced = coalesce_drags(&events);
+        assert_eq!(coalesced.len(), 3);
+        assert_eq!(coalesced[1], events[3]);
+
+        let cmds = mouse_commands("%2", &events, TRACKING);
+        let expected = format!(
+            "send-keys -t '%2' -H {}",
+            hex("\x1b[<0;1;1M\x1b[<32;4;2M\x1b[<0;4;2m")
+        );
+        assert_eq!(cmds, vec![expected]);
+    }
+
+    #[test]
+    fn wheel_counts_and_coordinates_are_bounded() {
+        let flood = MouseEvent::Wheel {
+            lines: i32::MIN,
+            x: u32::MAX,
+            y: 0,
+        };
+        let cmds = mouse_commands("%1", &[flood], TRACKING);
+        let report = hex(&format!("\x1b[<64;{};1M", u32::MAX));
+        assert_eq!(cmds.len(), 1);
+        assert_eq!(cmds[0].matches(&report).count(), 24);
+        assert_eq!(
+            mouse_commands("%1", &[flood], SHELL)[1],
+            "send-keys -t '%1' -X -N 24 scroll-up"
+        );
+
+        let drag = MouseEvent::Drag {
+            button: u8::MAX,
+            x: 0,
+            y: 0,
+        };
+        let cmds = mouse_commands("%1", &[drag], TRACKING);
+        assert_eq!(
+            cmds,
+            vec![format!("send-keys -t '%1' -H {}", hex("\x1b[<255;1;1M"))]
+        );
+    }
+
+    #[test]
+    fn events_deserialize_from_tagged_json() {
+        let event: MouseEvent =
+            serde_json::from_str(r#"{"kind":"wheel","lines":-1,"x":3,"y":4}"#).unwrap();
+        assert_eq!(
+            event,
+            MouseEvent::Wheel {
+                lines: -1,
+                x: 3,
+                y: 4
+            }
+        );
+    }
+}
@@ -0,0 +1,235 @@
+//! tmux option access for the frontend: `get_option`, `set_option` and
+//! `watch_option`.
+//!
+//! Options are named by scope, an optional target and the option name
+//! (built-in, like `status-position`, or a `@user` option). Reads go through
+//! `show-options -qv`, so an unset option reads as an empty string; writes
+//! go through the session's control-mode connection like any other command.
+//!
+//! Watching uses control-mode format subscriptions (`refresh-client -B`):
+//! tmux re-evaluates `#{<name>}` about once a second and sends
+//! `%subscription-changed` when the value differs, which the monitor turns
+//! into an [`OptionChange`] for every client of the session. Window and pane
+//! options without a target are watched on every window or pane.
+
+use crate::executor::tmux_quote;
+
+/// Prefix of the subscription names the monitor registers.
+pub const SUBSCRIPTION_PREFIX: &str = "tmuxy-opt-";
+
+pub use tmuxy_protocol::{OptionChange, OptionRef, OptionScope};
+
+/// The `show-options`/`set-option` flag selecting `scope`'s table.
+fn scope_flag(scope: OptionScope) -> Option<&'static str> {
+    match scope {
+        OptionScope::Server => Some("-s"),
+        OptionScope::Session => None,
+        OptionScope::Window => Some("-w"),
+        OptionScope::Pane => Some("-p"),
+    }
+}
+
+/// Whether `name` is a plain option name: letters, digits, `-` and `_`,
+/// an optional leading `@`, and an optional array index (`status-format[1]`).
+/// Anything else could smuggle a second command into a command string.
+pub fn validate_name(name: &str) -> Result<(), String> {
+    let (base, index) = match name.strip_suffix(']').and_then(|n| n.split_once('[')) {
+        Some((base, index)) => (base, Some(index)),
+        None => (name, None),
+    };
+    let bare = base.strip_prefix('@').unwrap_or(base);
+    let valid = !bare.is_empty()
+        && bare
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+        && index.is_none_or(|i| !i.is_empty() && i.chars().all(|c| c.is_ascii_digit()));
+    if valid {
+        Ok(())
+    } else {
+        Err(format!("invalid option name '{name}'"))
+    }
+}
+
+fn target_or<'a>(option: &'a OptionRef, session: &'a str) -> Option<&'a str> {
+    match option.scope {
+        OptionScope::Server => None,
+        _ => option
+            .target
+            .as_deref()
+            .or((!session.is_empty()).then_some(session)),
+    }
+}
+
+/// `show-options` arguments reading `option`'s value in `session`.
+pub fn get_args(option: &OptionRef, session: &str) -> Result<Vec<String>, String> {
+    validate_name(&option.name)?;
+    let mut args = vec!["show-options".to_string(), "-qv".to_string()];
+    args.extend(scope_flag(option.scope).map(str::to_string));
+    if let Some(target) = target_or(option, session) {
+        args.extend(["-t".to_string(), target.to_string()]);
+    }
+    args.push(option.name.clone());
+    Ok(args)
+}
+
+/// The command setting `option` to `value`, or unsetting it (back to the
+/// inherited value) for `None`.
+pub fn set_command(
+    option: &OptionRef,
+    session: &str,
+    value: Option<&str>,
+) -> Result<String, String> {
+    validate_name(&option.name)?;
+    let mut cmd = "set-option".to_string();
+    if let Some(flag) = scope_flag(option.scope) {
+        cmd.push(' ');
+        cmd.push_str(flag);
+    }
+    if value.is_none() {
+        cmd.push_str(" -u");
+    }
+    if let Some(target) = target_or(option, session) {
+        cmd.push_str(" -t ");
+        cmd.push_str(&tmux_quote(target));
+    }
+    cmd.push(' ');
+    cmd.push_str(&option.name);
+    if let Some(value) = value {
+        cmd.push(' ');
+        cmd.push_str(&tmux_quote(value));
+    }
+    Ok(cmd)
+}
+
+/// The `refresh-client -B` command subscribing to `option` under `name`
+/// (see [`subscription_name`]).
+pub fn subscribe_command(option: &OptionRef, name: &str) -> Result<String, String> {
+    validate_name(&option.name)?;
+    let what = match (option.scope, option.target.as_deref()) {
+        (OptionScope::Server | OptionScope::Session, _) => "",
+        (_, Some(target)) => target,
+        (OptionScope::Window, None) => "@*",
+        (OptionScope::Pane, None) => "%*",
+    };
+    if what.contains(':') {
+        return Err(format!("invalid target '{what}'"));
+    }
+    Ok(format!(
+        "refresh-client -B {}",
+        tmux_quote(&format!("{name}:{what}:#{{{}}}", option.name))
+    ))
+}
+
+/// The subscription name for the `index`th watched option.
+pub fn subscription_name(index: usize) -> String {
+    format!("{SUBSCRIPTION_PREFIX}{index}")
+}
+
+/// The watch index behind a subscription name, for names this module made.
+pub fn subscription_index(name: &str) -> Option<usize> {
+    name.strip_prefix(SUBSCRIPTION_PREFIX)?.parse().ok()
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used, clippy::expect_used)]
+mod tests {
+    use super::*;
+
+    fn option(scope: OptionScope, target: Option<&str>, name: &str) -> OptionRef {
+        OptionRef {
+            scope,
+            target: target.map(str::to_string),
+            name: name.to_string(),
+        }
+    }
+
+    #[test]
+    fn validates_names() {
+        for name in [
+            "status-position",
+            "@tmuxy_theme",
+            "@tmuxy-dnd",
+            "status-format[1]",
+        ] {
+            assert!(validate_name(name).is_ok(), "{name}");
+        }
+        for name in [
+            "",
+            "@",
+            "status ; kill-server",
+            "a'b",
+            "x[]",
+            "x[a]",
+            "#{x}",
+        ] {
+            assert!(validate_name(name).is_err(), "{name}");
+        }
+    }
+
+    #[test]
+    fn get_args_pick_the_table_and_target() {
+        let session = option(OptionScope::Session, None, "status-position");
+        assert_eq!(
+            get_args(&session, "main").unwrap(),
+            ["show-options", "-qv", "-t", "main", "status-position"]
+        );
+        let pane = option(OptionScope::Pane, Some("%3"), "@tmuxy-widget");
+        assert_eq!(
+            get_args(&pane, "main").unwrap(),
+            ["show-options", "-qv", "-p", "-t", "%3", "@tmuxy-widget"]
+        );
+        let server = option(OptionScope::Server, Some("ignored"), "escape-time");
+        assert_eq!(
+            get_args(&server, "main").unwrap(),
+            ["show-options", "-qv", "-s", "escape-time"]
+        );
+    }
+
+    #[test]
+    fn set_command_quotes_values_and_unsets() {
+        let window = option(OptionScope::Window, Some("@2"), "pane-border-style");
+        assert_eq!(
+            set_command(&window, "main", Some("fg=red'x")).unwrap(),
+            r"set-option -w -t '@2' pane-border-style 'fg=red'\''x'"
+        );
+        let user = option(OptionScope::Session, None, "@tmuxy_layout");
+        assert_eq!(
+            set_command(&user, "main", None).unwrap(),
+            "set-option -u -t 'main' @tmuxy_layout"
+        );
+        let bad = option(OptionScope::Session, None, "x ; kill-server");
+        assert!(set_command(&bad, "main", Some("1")).is_err());
+    }
+
+    #[test]
+    fn subscriptions_cover_every_window_or_pane_without_a_target() {
+        let session = option(OptionScope::Session, None, "status-position");
+        assert_eq!(
+            subscribe_command(&session, "tmuxy-opt-0").unwrap(),
+            "refresh-client -B 'tmuxy-opt-0::#{status-position}'"
+        );
+        let panes = option(OptionScope::Pane, None, "@tmuxy_note");
+        assert_eq!(
+            subscribe_command(&panes, "tmuxy-opt-1").unwrap(),
+            "refresh-client -B 'tmuxy-opt-1:%*:#{@tmuxy_note}'"
+        );
+        let window = option(OptionScope::Window, Some("@4"), "pane-border-style");
+        assert_eq!(
+            subscribe_command(&window, "tmuxy-opt-2").unwrap(),
+            "refresh-client -B 'tmuxy-opt-2:@4:#{pane-border-style}'"
+        );
+    }
+
+    #[test]
+    fn subscription_names_round_trip() {
+        assert_eq!(subscription_index(&subscription_name(7)), Some(7));
+        assert_eq!(subscription_index("other-sub"), None);
+    }
+
+    #[test]
+    fn option_ref_parses_without_target() {
+        let parsed: OptionRef =
+            serde_json::from_str(r#"{"scope":"window","name":"@tmuxy-status"}"#).unwrap();
+        assert_eq!(parsed, option(OptionScope::Window, None, "@tmuxy-status"));
+    }
+}
@@ -0,0 +1,508 @@
+//! Pane groups as clients manage them.
+//!
+//! A group is a set of panes that share one slot in a layout, shown one at a
+//! time like tabs. The member in the slot sits in an ordinary tab window;
+//! every other member waits alone in a hidden window tagged
+//! `@tmuxy-window-type group`. Each of those windows carries the whole
+//! membership, in tab order, in `@tmuxy-group-panes`. The `bin/tmuxy/pane-group-*`
+//! scripts manage groups from a shell; [`GroupOp`] does the same for clients,
+//! one tmux command list per operation, so no client sees a member swapped in
+//! with the membership not yet updated.
+//!
+//! Scripts and clients can still race each other, so after an operation the
+//! monitor checks the lists against the panes tmux reports and rewrites any
+//! that went out of step ([`repair_commands`]).
+
+use crate::constants::tmux_options;
+use crate::error::{Result, TmuxError};
+use crate::WindowType;
+
+/// Session option holding the id of a tab's new pane while the rest of the
+/// list tags windows with it; the id isn't known until `splitw` runs.
+const NEW_PANE_OPTION: &str = "@tmuxy-group-new";
+
+/// An operation on a pane group, named by one of its panes.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum GroupOp {
+    /// Start a group from a pane not in one: a new shell takes the pane's
+    /// place and the pane becomes the group's first hidden tab.
+    Create { pane_id: String },
+    /// Open a new tab in the pane's group, in the group's slot.
+    Add { pane_id: String },
+    /// Show this member in the group's slot.
+    Switch { pane_id: String },
+    /// Show the group's tab at `index`, naming the group by any member.
+    SwitchTab { pane_id: String, index: usize },
+    /// Close this member; the next one (or the previous, for the last)
+    /// takes the slot if it was showing.
+    Close { pane_id: String },
+    /// Move this member to `index` in the group's tab order.
+    Reorder { pane_id: String, index: usize },
+}
+
+/// A pane in a group and where it is now.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GroupMember {
+    pub pane_id: String,
+    pub window_id: String,
+    pub width: u32,
+    pub height: u32,
+    /// In the group's slot rather than a hidden `group` window.
+    pub visible: bool,
+}
+
+/// A group the session has now, members in tab order.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GroupInfo {
+    pub members: Vec<GroupMember>,
+}
+
+/// A hidden `group` window as tmux reports it: its pane, if known, and the
+/// membership it carries.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GroupWindow {
+    pub window_id: String,
+    pub pane_id: Option<String>,
+    pub panes: Vec<String>,
+}
+
+/// Where an operation runs: the session's groups, and the pane it names as
+/// a would-be member, for a pane not in a group yet.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct GroupContext<'a> {
+    pub groups: &'a [GroupInfo],
+    pub pane: Option<&'a GroupMember>,
+}
+
+impl GroupInfo {
+    fn member(&self, pane_id: &str) -> Option<&GroupMember> {
+        self.members.iter().find(|m| m.pane_id == pane_id)
+    }
+
+    fn visible(&self) -> Result<&GroupMember> {
+        self.members
+            .iter()
+            .find(|m| m.visible)
+            .ok_or_else(|| TmuxError::other("pane group has no visible member"))
+    }
+
+    fn hidden(&self) -> impl Iterator<Item = &GroupMember> {
+        self.members.iter().filter(|m| !m.visible)
+    }
+}
+
+impl GroupOp {
+    /// The tmux command list that carries out this operation.
+    pub fn to_command(&self, ctx: GroupContext<'_>) -> Result<String> {
+        match self {
+            GroupOp::Create { pane_id } => {
+                if find(ctx.groups, pane_id).is_ok() {
+                    return Err(TmuxError::other(format!(
+                        "{pane_id} is already in a pane group"
+                    )));
+                }
+                let pane = ctx.pane.filter(|p| p.pane_id == *pane_id).ok_or_else(|| {
+                    TmuxError::PaneNotFound {
+                        id: pane_id.to_string(),
+                    }
+                })?;
+                Ok(add_command(pane, &[], std::slice::from_ref(pane_id)))
+            }
+            GroupOp::Add { pane_id } => {
+                let group = find(ctx.groups, pane_id)?;
+                let visible = group.visible()?;
+                let hidden: Vec<&GroupMember> = group.hidden().collect();
+                let order: Vec<String> = group.members.iter().map(|m| m.pane_id.clone()).collect();
+                Ok(add_command(visible, &hidden, &order))
+            }
+            GroupOp::Switch { pane_id } => {
+                let group = find(ctx.groups, pane_id)?;
+                let target = group
+                    .member(pane_id)
+                    .ok_or_else(|| TmuxError::PaneNotFound {
+                        id: pane_id.to_string(),
+                    })?;
+                switch_command(group, target)
+            }
+            GroupOp::SwitchTab { pane_id, index } => {
+                let group = find(ctx.groups, pane_id)?;
+                let target = group.members.get(*index).ok_or_else(|| {
+                    TmuxError::other(format!("pane group of {pane_id} has no tab {index}"))
+                })?;
+                switch_command(group, target)
+            }
+            GroupOp::Close { pane_id } => {
+                let group = find(ctx.groups, pane_id)?;
+                let Some(pos) = group.members.iter().position(|m| m.pane_id == *pane_id) else {
+                    return Err(TmuxError::PaneNotFound {
+                        id: pane_id.to_string(),
+                    });
+                };
+                let closing = &group.members[pos];
+                let mut cmds = Vec::new();
+                // The window that goes away with the pane.
+                let killed = if !closing.visible {
+                    cmds.push(format!("kill-window -t {}", closing.window_id));
+                    Some(&closing.window_id)
+                } else if let Some(next) = group
+                    .members
+                    .get(pos + 1)
+                    .or_else(|| pos.checked_sub(1).and_then(|i| group.members.get(i)))
+                {
+                    cmds.push(format!(
+                        "swap-pane -s {pane_id} -t {} ; kill-window -t {}",
+                        next.pane_id, next.window_id
+                    ));
+                    Some(&next.window_id)
+                } else {
+                    cmds.push(format!("kill-pane -t {pane_id}"));
+                    None
+                };
+                let order: Vec<String> = group
+                    .members
+                    .iter()
+                    .filter(|m| m.pane_id != *pane_id)
+                    .map(|m| m.pane_id.clone())
+                    .collect();
+                for member in group.hidden().filter(|m| Some(&m.window_id) != killed) {
+                    cmds.push(set_panes(&member.window_id, &order.join(" ")));
+                }
+                Ok(cmds.join(" ; "))
+            }
+            GroupOp::Reorder { pane_id, index } => {
+                let group = find(ctx.groups, pane_id)?;
+                let mut order: Vec<String> = group
+                    .members
+                    .iter()
+                    .filter(|m| m.pane_id != *pane_id)
+                    .map(|m| m.pane_id.clone())
+                    .collect();
+                order.insert((*index).min(order.len()), pane_id.clone());
+                let cmds: Vec<String> = group
+                    .hidden()
+                    .map(|m| set_panes(&m.window_id, &order.join(" ")))
+                    .collect();
+                if cmds.is_empty() {
+                    return Err(TmuxError::other("pane group has no hidden members"));
+                }
+                Ok(cmds.join(" ; "))
+            }
+        }
+    }
+}
+
+fn switch_command(group: &GroupInfo, target: &GroupMember) -> Result<String> {
+    let visible = group.visible()?;
+    if visible.pane_id == target.pane_id {
+        return Ok(format!("select-pane -t {}", target.pane_id));
+    }
+    Ok(format!(
+        "resizew -t {} -x {} -y {} ; swap-pane -s {} -t {}",
+        target.window_id, visible.width, visible.height, target.pane_id, visible.pane_id
+    ))
+}
+
+/// `set -w` commands that bring every hidden window's membership back in
+/// line, for groups an interleaved script or client left out of step.
+///
+/// Windows sharing a member belong to one group, and they all get the list
+/// most of them carry, less panes `live` doesn't know, plus any window's own
+/// pane the list lost. Lists that already agree are left alone, so a
+/// consistent session yields nothing.
+pub fn repair_commands(windows: &[GroupWindow], live: impl Fn(&str) -> bool) -> Vec<String> {
+    let mut cmds = Vec::new();
+    let mut done = vec![false; windows.len()];
+    for start in 0..windows.len() {
+        if done[start] {
+            continue;
+        }
+        // Gather the group: every window whose list overlaps one already in.
+        let mut group = vec![start];
+        done[start] = true;
+        let mut i = 0;
+        while i < group.len() {
+            let members = &windows[group[i]].panes;
+            for (j, other) in windows.iter().enumerate() {
+                if !done[j] && other.panes.iter().any(|p| members.contains(p)) {
+                    done[j] = true;
+                    group.push(j);
+                }
+            }
+            i += 1;
+        }
+
+        let mut lists: Vec<(&Vec<String>, usize)> = Vec::new();
+        for &w in &group {
+            match lists
+                .iter_mut()
+                .find(|(list, _)| **list == windows[w].panes)
+            {
+                Some((_, count)) => *count += 1,
+                None => lists.push((&windows[w].panes, 1)),
+            }
+        }
+        let Some(&(base, _)) = lists.iter().rev().max_by_key(|(_, count)| *count) else {
+            continue;
+        };
+        let mut order: Vec<String> = Vec::new();
+        let own = group.iter().filter_map(|&w| windows[w].pane_id.as_ref());
+        for pane in base.iter().chain(own) {
+            if live(pane) && !order.contains(pane) {
+                order.push(pane.clone());
+            }
+        }
+        for &w in &group {
+            if windows[w].panes != order {
+                cmds.push(set_panes(&windows[w].window_id, &order.join(" ")));
+            }
+        }
+    }
+    cmds
+}
+
+/// A new pane splits off the visible member and takes its place once the
+/// member breaks out into a hidden window, so no `swap-pane` is needed.
+/// `splitw` leaves the new pane active in its window, which is where
+/// `set -F -t WINDOW` reads its id from for the membership lists.
+fn add_command(visible: &GroupMember, hidden: &[&GroupMember], order: &[String]) -> String {
+    let pane = &visible.pane_id;
+    let window = &visible.window_id;
+    let mut cmds = vec![
+        format!("splitw -t {pane} -c '#{{pane_current_path}}'"),
+        format!("set -F -t {window} {NEW_PANE_OPTION} '#{{pane_id}}'"),
+        format!("breakp -d -s {pane} -n group"),
+        format!(
+            "resizew -t {pane} -x {} -y {}",
+            visible.width, visible.height
+        ),
+        format!(
+            "set -w -t {pane} {} {}",
+            tmux_options::WINDOW_TYPE,
+            WindowType::Group.as_str()
+        ),
+    ];
+    let panes = format!("{} #{{{NEW_PANE_OPTION}}}", order.join(" "));
+    for target in hidden.iter().map(|m| &m.pane_id).chain([pane]) {
+        cmds.push(format!(
+            "set -wF -t {target} {} \"{panes}\"",
+            tmux_options::GROUP_PANES
+        ));
+    }
+    cmds.push(format!("set -u -t {window} {NEW_PANE_OPTION}"));
+    cmds.join(" ; ")
+}
+
+fn find<'a>(groups: &'a [GroupInfo], pane_id: &str) -> Result<&'a GroupInfo> {
+    groups
+        .iter()
+        .find(|g| g.member(pane_id).is_some())
+        .ok_or_else(|| TmuxError::other(format!("{pane_id} is not in a pane group")))
+}
+
+fn set_panes(window_id: &str, panes: &str) -> String {
+    format!(
+        "set -w -t {window_id} {} \"{panes}\"",
+        tmux_options::GROUP_PANES
+    )
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used, clippy::expect_used)]
+mod tests {
+    use super::*;
+
+    fn member(pane_id: &str, window_id: &str, visible: bool) -> GroupMember {
+        GroupMember {
+            pane_id: pane_id.to_string(),
+            window_id: window_id.to_string(),
+            width: 59,
+            height: 40,
+            visible,
+        }
+    }
+
+    /// `%1` showing in `@0`, `%3` and `%4` hidden.
+    fn group() -> Vec<GroupInfo> {
+        vec![GroupInfo {
+            members: vec![
+                member("%3", "@2", false),
+                member("%1", "@0", true),
+                member("%4", "@3", false),
+            ],
+        }]
+    }
+
+    #[test]
+    fn create_breaks_the_pane_out_and_tags_its_window_with_the_new_pane() {
+        let pane = member("%1", "@0", true);
+        let ctx = GroupContext {
+            groups: &[],
+            pane: Some(&pane),
+        };
+        let create = |pane: &str| GroupOp::Create {
+            pane_id: pane.to_string(),
+        };
+        assert_eq!(
+            create("%1").to_command(ctx).unwrap(),
+            "splitw -t %1 -c '#{pane_current_path}' ; \
+             set -F -t @0 @tmuxy-group-new '#{pane_id}' ; breakp -d -s %1 -n group ; \
+             resizew -t %1 -x 59 -y 40 ; set -w -t %1 @tmuxy-window-type group ; \
+             set -wF -t %1 @tmuxy-group-panes \"%1 #{@tmuxy-group-new}\" ; \
+             set -u -t @0 @tmuxy-group-new"
+        );
+        let groups = group();
+        let pane = member("%3", "@2", false);
+        let ctx = GroupContext {
+            groups: &groups,
+            pane: Some(&pane),
+        };
+        assert!(create("%3").to_command(ctx).is_err());
+    }
+
+    #[test]
+    fn add_appends_a_tab_and_updates_every_hidden_window() {
+        let groups = group();
+        let ctx = GroupContext {
+            groups: &groups,
+            pane: None,
+        };
+        let add = |pane: &str| GroupOp::Add {
+            pane_id: pane.to_string(),
+        };
+        let cmd = add("%4").to_command(ctx).unwrap();
+        assert!(cmd.starts_with("splitw -t %1 "));
+        assert!(cmd.contains("breakp -d -s %1 -n group ; resizew -t %1 -x 59 -y 40 ;"));
+        for target in ["%3", "%4", "%1"] {
+            assert!(cmd.contains(&format!(
+                "set -wF -t {target} @tmuxy-group-panes \"%3 %1 %4 #{{@tmuxy-group-new}}\""
+            )));
+        }
+        assert!(add("%9").to_command(ctx).is_err());
+    }
+
+    #[test]
+    fn switch_swaps_the_member_into_the_slot_at_its_size() {
+        let groups = group();
+        let ctx = GroupContext {
+            groups: &groups,
+            pane: None,
+        };
+        let switch = |pane: &str| GroupOp::Switch {
+            pane_id: pane.to_string(),
+        };
+        assert_eq!(
+            switch("%4").to_command(ctx).unwrap(),
+            "resizew -t @3 -x 59 -y 40 ; swap-pane -s %4 -t %1"
+        );
+        assert_eq!(switch("%1").to_command(ctx).unwrap(), "select-pane -t %1");
+    }
+
+    #[test]
+    fn switch_tab_finds_the_member_by_position() {
+        let groups = group();
+        let ctx = GroupContext {
+            groups: &groups,
+            pane: None,
+        };
+        let switch_tab = |index| GroupOp::SwitchTab {
+            pane_id: "%1".to_string(),
+            index,
+        };
+        assert_eq!(
+            switch_tab(0).to_command(ctx).unwrap(),
+            "resizew -t @2 -x 59 -y 40 ; swap-pane -s %3 -t %1"
+        );
+        assert!(switch_tab(3).to_command(ctx).is_err());
+    }
+
+    #[test]
+    fn repairs_bring_stray_lists_back_to_the_majority() {
+        let window = |window_id: &str, pane_id: &str, panes: &str| GroupWindow {
+            window_id: window_id.to_string(),
+            pane_id: Some(pane_id.to_string()),
+            panes: panes.split_whitespace().map(str::to_string).collect(),
+        };
+        let live = |id: &str| id != "%8";
+        let consistent = [
+            window("@2", "%3", "%3 %1 %4"),
+            window("@3", "%4", "%3 %1 %4"),
+        ];
+        assert!(repair_commands(&consistent, live).is_empty());
+
+        // @4 missed a reorder and lost its own pane; %8 is gone. @7 is a
+        // separate group that's fine.
+        let windows = [
+            window("@2", "%3", "%1 %3 %4 %8"),
+            window("@3", "%4", "%1 %3 %4 %8"),
+            window("@4", "%5", "%3 %1"),
+            window("@7", "%7", "%6 %7"),
+        ];
+        assert_eq!(
+            repair_commands(&windows, live),
+            [
+                "set -w -t @2 @tmuxy-group-panes \"%1 %3 %4 %5\"",
+                "set -w -t @3 @tmuxy-group-panes \"%1 %3 %4 %5\"",
+                "set -w -t @4 @tmuxy-group-panes \"%1 %3 %4 %5\"",
+            ]
+        );
+    }
+
+    #[test]
+    fn close_hands_the_slot_on_and_drops_the_member_everywhere() {
+        let groups = group();
+        let ctx = GroupContext {
+            groups: &groups,
+            pane: None,
+        };
+        let close = |pane: &str| GroupOp::Close {
+            pane_id: pane.to_string(),
+        };
+        assert_eq!(
+            close("%1").to_command(ctx).unwrap(),
+            "swap-pane -s %1 -t %4 ; kill-window -t @3 ; \
+             set -w -t @2 @tmuxy-group-panes \"%3 %4\""
+        );
+        assert_eq!(
+            close("%3").to_command(ctx).unwrap(),
+            "kill-window -t @2 ; set -w -t @3 @tmuxy-group-panes \"%1 %4\""
+        );
+        // The last tab's slot goes to the one before it.
+        let two = vec![GroupInfo {
+            members: vec![member("%3", "@2", false), member("%1", "@0", true)],
+        }];
+        let ctx = GroupContext {
+            groups: &two,
+            pane: None,
+        };
+        assert_eq!(
+            close("%1").to_command(ctx).unwrap(),
+            "swap-pane -s %1 -t %3 ; kill-window -t @2"
+        );
+    }
+
+    #[test]
+    fn reorder_rewrites_the_membership_and_clamps_the_index() {
+        let groups = group();
+        let ctx = GroupContext {
+            groups: &groups,
+            pane: None,
+        };
+        let reorder = GroupOp::Reorder {
+            pane_id: "%4".to_string(),
+            index: 0,
+        };
+        assert_eq!(
+            reorder.to_command(ctx).unwrap(),
+            "set -w -t @2 @tmuxy-group-panes \"%4 %3 %1\" ; \
+             set -w -t @3 @tmuxy-group-panes \"%4 %3 %1\""
+        );
+        let reorder = GroupOp::Reorder {
+            pane_id: "%3".to_string(),
+            index: 9,
+        };
+        assert!(reorder
+            .to_command(ctx)
+            .unwrap()
+            .ends_with("@tmuxy-group-panes \"%1 %4 %3\""));
+    }
+}
@@ -0,0 +1,249 @@
+//! Incremental search over a pane's text, for the find overlay
+//! (`pane_search`).
+//!
+//! A search answers with the visible area's matches straight away, then
+//! walks the history upward in chunks of [`HISTORY_CHUNK_LINES`] rows, each
+//! chunk's matches going to the client as a `search-results` event. Every
+//! keystroke in the overlay starts a new search on the pane; [`PaneSearches`]
+//! lets the walk of the one it replaced notice and stop. Each client's
+//! searches are its own, and so are their results.
+//!
+//! Rows are searched one at a time as capture-pane prints them, so a match
+//! can't span a wrapped row.
+
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+
+use regex::{Regex, RegexBuilder};
+use unicode_width::UnicodeWidthStr;
+
+pub use tmuxy_protocol::{SearchMatch, SearchQuery};
+
+/// Rows of history captured per `search-results` chunk.
+pub const HISTORY_CHUNK_LINES: i64 = 1000;
+
+/// Matches a search reports before it stops, so a query like `.` on a long
+/// history doesn't flood clients.
+pub const MAX_MATCHES: usize = 5000;
+
+/// Characters of the row kept either side of a match.
+const SNIPPET_CONTEXT: usize = 40;
+
+/// A compiled [`SearchQuery`].
+#[derive(Debug, Clone)]
+pub struct Matcher(Regex);
+
+impl Matcher {
+    pub fn new(query: &SearchQuery) -> Result<Self, String> {
+        if query.query.is_empty() {
+            return Err("Empty search query".to_string());
+        }
+        let pattern = if query.regex {
+            query.query.clone()
+        } else {
+            regex::escape(&query.query)
+        };
+        RegexBuilder::new(&pattern)
+            .case_insensitive(!query.case_sensitive)
+            .build()
+            .map(Matcher)
+            .map_err(|e| format!("Invalid search pattern: {e}"))
+    }
+
+    /// The matches in capture-pane output whose first row is line
+    /// `first_line`. Empty matches (`x*` between two other characters) are
+    /// skipped.
+    pub fn search_rows(&self, rows: &str, first_line: i64) -> Vec<SearchMatch> {
+        let mut matches = Vec::new();
+        for (line, row) in (first_line..).zip(rows.lines()) {
+            for m in self.0.find_iter(row).filter(|m| !m.is_empty()) {
+                let before = &row[..m.start()];
+                let after = &row[m.end()..];
+                matches.push(SearchMatch {
+                    line,
+                    col: before.width(),
+                    width: m.as_str().width(),
+                    text: m.as_str().to_string(),
+                    before: last_chars(before, SNIPPET_CONTEXT).to_string(),
+                    after: after.chars().take(SNIPPET_CONTEXT).collect(),
+                });
+            }
+        }
+        matches
+    }
+}
+
+/// The last `n` (at least one) characters of `s`.
+fn last_chars(s: &str, n: usize) -> &str {
+    let start = s
+        .char_indices()
+        .rev()
+        .nth(n.saturating_sub(1))
+        .map_or(0, |(i, _)| i);
+    &s[start..]
+}
+
+/// The `start..=end` capture-pane ranges covering `history_size` rows of
+/// history, nearest the visible area first.
+pub fn history_chunks(history_size: u32) -> impl Iterator<Item = (i64, i64)> {
+    let oldest = -i64::from(history_size);
+    std::iter::successors(Some(-1), |end| Some(end - HISTORY_CHUNK_LINES))
+        .take_while(move |end| *end >= oldest)
+        .map(move |end| ((end - HISTORY_CHUNK_LINES + 1).max(oldest), end))
+}
+
+/// The latest search each client runs on each pane. One search per client
+/// and pane runs at a time: a client's new one supersedes its last, and
+/// leaves other clients' searches of the pane alone.
+#[derive(Debug, Default)]
+pub struct PaneSearches {
+    next_id: AtomicU64,
+    latest: Mutex<HashMap<(u64, String), u64>>,
+}
+
+impl PaneSearches {
+    /// Start `client`'s search on `pane_id`, superseding its last there;
+    /// returns the search's id.
+    pub fn begin(&self, client: u64, pane_id: &str) -> u64 {
+        let id = self.next_id.fetch_add(1, Ordering::Relaxed) + 1;
+        self.latest
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .insert((client, pane_id.to_string()), id);
+        id
+    }
+
+    /// Whether search `id` is still `client`'s latest on `pane_id`.
+    pub fn is_current(&self, client: u64, pane_id: &str, id: u64) -> bool {
+        self.latest
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .get(&(client, pane_id.to_string()))
+            == Some(&id)
+    }
+
+    /// Search `id` on `pane_id` has finished; forget it unless `client`
+    /// already superseded it.
+    pub fn finish(&self, client: u64, pane_id: &str, id: u64) {
+        let mut latest = self.latest.lock().unwrap_or_else(|e| e.into_inner());
+        let key = (client, pane_id.to_string());
+        if latest.get(&key) == Some(&id) {
+            latest.remove(&key);
+        }
+    }
+
+    /// `client` went away: its searches stop at their next chunk.
+    pub fn forget(&self, client: u64) {
+        self.latest
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .retain(|(c, _), _| *c != client);
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used, clippy::expect_used)]
+mod tests {
+    use super::*;
+
+    fn query(q: &str) -> SearchQuery {
+        SearchQuery {
+            query: q.to_string(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn literal_search_ignores_case_and_escapes_metacharacters() {
+        let matcher = Matcher::new(&query("a.b")).unwrap();
+        let matches = matcher.search_rows("xA.B axb\n\na.b", -3);
+        let found: Vec<_> = matches.iter().map(|m| (m.line, m.col, &*m.text)).collect();
+        assert_eq!(found, [(-3, 1, "A.B"), (-1, 0, "a.b")]);
+        assert_eq!(matches[0].before, "x");
+        assert_eq!(matches[0].after, " axb");
+    }
+
+    #[test]
+    fn regex_and_case_sensitive_flags() {
+        let search = SearchQuery {
+            query: r"err(or)?\b".to_string(),
+            case_sensitive: true,
+            regex: true,
+        };
+        let matcher = Matcher::new(&search).unwrap();
+        let found: Vec<_> = matcher
+            .search_rows("Error: err\nerrors error", 0)
+            .into_iter()
+            .map(|m| (m.line, m.col, m.text))
+            .collect();
+        assert_eq!(
+            found,
+            [(0, 7, "err".to_string()), (1, 7, "error".to_string())]
+        );
+
+        assert!(Matcher::new(&SearchQuery {
+            regex: true,
+            ..query("(")
+        })
+        .is_err());
+        assert!(Matcher::new(&query("")).is_err());
+    }
+
+    #[test]
+    fn columns_count_cells_and_empty_matches_are_skipped() {
+        let search = SearchQuery {
+            regex: true,
+            ..query("b*")
+        };
+        let matches = Matcher::new(&search).unwrap().search_rows("日本 abb", 0);
+        assert_eq!(matches.len(), 1);
+        assert_eq!((matches[0].col, matches[0].width), (6, 2));
+        assert_eq!(matches[0].before, "日本 a");
+    }
+
+    #[test]
+    fn snippets_keep_the_context_nearest_the_match() {
+        let row = format!("{}needle{}", "x".repeat(50), "y".repeat(50));
+        let matches = Matcher::new(&query("needle")).unwrap().search_rows(&row, 0);
+        assert_eq!(matches[0].before, "x".repeat(SNIPPET_CONTEXT));
+        assert_eq!(matches[0].after, "y".repeat(SNIPPET_CONTEXT));
+    }
+
+    #[test]
+    fn history_is_walked_upward_in_chunks() {
+        let chunks: Vec<_> = history_chunks(2500).collect();
+        assert_eq!(chunks, [(-1000, -1), (-2000, -1001), (-2500, -2001)]);
+        assert_eq!(history_chunks(0).count(), 0);
+        assert_eq!(history_chunks(1000).collect::<Vec<_>>(), [(-1000, -1)]);
+    }
+
+    #[test]
+    fn a_new_search_supersedes_the_clients_last_on_its_pane() {
+        let searches = PaneSearches::default();
+        let first = searches.begin(1, "%1");
+        let other = searches.begin(1, "%2");
+        let second = searches.begin(1, "%1");
+        assert!(!searches.is_current(1, "%1", first));
+        assert!(searches.is_current(1, "%1", second));
+        assert!(searches.is_current(1, "%2", other));
+
+        searches.finish(1, "%1", first);
+        assert!(searches.is_current(1, "%1", second));
+        searches.finish(1, "%1", second);
+        assert!(!searches.is_current(1, "%1", second));
+    }
+
+    #[test]
+    fn clients_search_the_same_pane_side_by_side() {
+        let searches = PaneSearches::default();
+        let mine = searches.begin(1, "%1");
+        let theirs = searches.begin(2, "%1");
+        assert!(searches.is_current(1, "%1", mine));
+        assert!(searches.is_current(2, "%1", theirs));
+
+        searches.forget(2);
+        assert!(searches.is_current(1, "%1", mine));
+        assert!(!searches.is_current(2, "%1", theirs));
+    }
+}
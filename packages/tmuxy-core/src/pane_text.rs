@@ -0,0 +1,301 @@
+//! Pane content as plain text and as styled HTML (`get_pane_text`), built
+//! from the cells on the server so "copy as HTML" and plain copies don't
+//! depend on the frontend piecing text back together from cells.
+//!
+//! Text follows copy mode's rules (`extractSelectedText` in the UI):
+//! trailing blanks are trimmed, a row that fills the pane width joins the
+//! next one without a newline, and the blank cell after a wide character
+//! is dropped. The HTML is one `<pre>` of inline-styled `<span>` runs,
+//! colored like the image export (see export.rs) so it pastes the same into
+//! any editor.
+
+use crate::export::{
+    escape_xml, hex, is_blank, trim_trailing_blank_lines, Look, DEFAULT_BG, DEFAULT_FG, FONT_FAMILY,
+};
+use crate::{TerminalCell, TerminalLine};
+use serde::Serialize;
+use std::fmt::Write as _;
+
+pub use tmuxy_protocol::TextSelection;
+
+/// `get_pane_text`'s answer.
+#[derive(Debug, Clone, Serialize, PartialEq, Eq)]
+pub struct PaneText {
+    pub text: String,
+    pub html: String,
+}
+
+/// One row's share of the copy: its cells, and whether a line break
+/// follows (no break after a wrapped row or the last row).
+struct Segment<'a> {
+    cells: &'a [TerminalCell],
+    newline: bool,
+}
+
+fn segments<'a>(
+    lines: &'a [TerminalLine],
+    width: usize,
+    selection: Option<&TextSelection>,
+) -> Vec<Segment<'a>> {
+    let selection = selection.map(|s| s.normalized());
+    let last = lines.len().saturating_sub(1);
+    lines
+        .iter()
+        .enumerate()
+        .map(|(i, line)| {
+            let (from, to) = match selection {
+                Some(s) if !s.line_mode => (
+                    if i == 0 { s.start_col } else { 0 },
+                    if i == last { s.end_col + 1 } else { line.len() },
+                ),
+                _ => (0, line.len()),
+            };
+            let to = to.min(line.len());
+            let from = from.min(to);
+            let wrapped = i < last && line.len() >= width;
+            let mut cells = &line[from..to];
+            if !wrapped {
+                while let Some((tail, rest)) = cells.split_last() {
+                    if !is_blank(tail) {
+                        break;
+                    }
+                    cells = rest;
+                }
+            }
+            Segment {
+                cells,
+                newline: i < last && !wrapped,
+            }
+        })
+        .collect()
+}
+
+/// Cells that take a column of their own (not a wide character's tail).
+fn visible(cells: &[TerminalCell]) -> impl Iterator<Item = &TerminalCell> {
+    cells.iter().filter(|cell| cell.width != 0)
+}
+
+/// Links that are safe to put in an `href` of HTML pasted elsewhere.
+fn safe_url(url: &str) -> bool {
+    ["http://", "https://", "mailto:", "file://"]
+        .iter()
+        .any(|scheme| url.starts_with(scheme))
+}
+
+fn css(look: &Look) -> String {
+    let mut css = String::new();
+    if look.fg != DEFAULT_FG {
+        let _ = write!(css, "color:{};", hex(look.fg));
+    }
+    if let Some(bg) = look.bg {
+        let _ = write!(css, "background-color:{};", hex(bg));
+    }
+    if look.bold {
+        css.push_str("font-weight:bold;");
+    }
+    if look.italic {
+        css.push_str("font-style:italic;");
+    }
+    match (look.underline, look.strikethrough) {
+        (true, true) => css.push_str("text-decoration:underline line-through;"),
+        (true, false) => css.push_str("text-decoration:underline;"),
+        (false, true) => css.push_str("text-decoration:line-through;"),
+        (false, false) => {}
+    }
+    css
+}
+
+fn push_html_run(html: &mut String, look: &Look, url: Option<&str>, text: &str) {
+    let css = css(look);
+    let text = escape_xml(text);
+    match url.filter(|url| safe_url(url)) {
+        Some(url) => {
+            let _ = write!(
+                html,
+                "<a href=\"{}\" style=\"color:inherit;{css}\">{text}</a>",
+                escape_xml(url)
+            );
+        }
+        None if css.is_empty() => html.push_str(&text),
+        None => {
+            let _ = write!(html, "<span style=\"{css}\">{text}</span>");
+        }
+    }
+}
+
+/// `lines` (a pane `width` wide) as text and HTML. With a selection,
+/// `lines` are the selected rows, the first being `start_row`, and the
+/// first and last are cut to its columns; without one, the empty rows
+/// below the last output are left out.
+pub fn pane_text(
+    lines: &[TerminalLine],
+    width: usize,
+    selection: Option<&TextSelection>,
+) -> PaneText {
+    let lines = match selection {
+        Some(_) => lines,
+        None => trim_trailing_blank_lines(lines),
+    };
+    let segments = segments(lines, width, selection);
+
+    let mut text = String::new();
+    for segment in &segments {
+        for cell in visible(segment.cells) {
+            text.push_str(&cell.char);
+        }
+        if segment.newline {
+            text.push('\n');
+        }
+    }
+
+    let mut html = format!(
+        "<pre style=\"font-family:{};background-color:{};color:{};padding:8px\">",
+        FONT_FAMILY.replace('"', "'"),
+        hex(DEFAULT_BG),
+        hex(DEFAULT_FG)
+    );
+    for segment in &segments {
+        let mut run: Option<(Look, Option<&str>, String)> = None;
+        for cell in visible(segment.cells) {
+            let style = cell.style.as_ref();
+            let look = Look::of(style);
+            let url = style.and_then(|s| s.url.as_deref());
+            match &mut run {
+                Some((last, last_url, chars)) if *last == look && *last_url == url => {
+                    chars.push_str(&cell.char);
+                }
+                _ => {
+                    if let Some((look, url, chars)) = run.take() {
+                        push_html_run(&mut html, &look, url, &chars);
+                    }
+                    run = Some((look, url, cell.char.clone()));
+                }
+            }
+        }
+        if let Some((look, url, chars)) = run {
+            push_html_run(&mut html, &look, url, &chars);
+        }
+        if segment.newline {
+            html.push('\n');
+        }
+    }
+    html.push_str("</pre>");
+
+    PaneText { text, html }
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used, clippy::expect_used)]
+mod tests {
+    use super::*;
+    use crate::{CellColor, CellStyle};
+
+    fn line(text: &str) -> TerminalLine {
+        text.chars()
+            .map(|c| TerminalCell::new(c.to_string()))
+            .collect()
+    }
+
+    fn selection(start: (i64, usize), end: (i64, usize)) -> TextSelection {
+        TextSelection {
+            start_row: start.0,
+            start_col: start.1,
+            end_row: end.0,
+            end_col: end.1,
+            line_mode: false,
+        }
+    }
+
+    #[test]
+    fn whole_pane_trims_rows_and_joins_wrapped_ones() {
+        let lines = vec![line("$ ls   "), line("0123456789"), line("abc"), line("")];
+        let out = pane_text(&lines, 10, None);
+        assert_eq!(out.text, "$ ls\n0123456789abc");
+    }
+
+    #[test]
+    fn selection_cuts_the_first_and_last_rows() {
+        let lines = vec![line("hello world"), line("second"), line("third row")];
+        let sel = selection((-3, 6), (-1, 4));
+        assert_eq!(
+            pane_text(&lines, 20, Some(&sel)).text,
+            "world\nsecond\nthird"
+        );
+        // Dragged upwards: the same text.
+        let reversed = selection((-1, 4), (-3, 6));
+        assert_eq!(
+            pane_text(&lines, 20, Some(&reversed)).text,
+            "world\nsecond\nthird"
+        );
+        let lines_mode = TextSelection {
+            line_mode: true,
+            ..sel
+        };
+        assert_eq!(
+            pane_text(&lines, 20, Some(&lines_mode)).text,
+            "hello world\nsecond\nthird row"
+        );
+    }
+
+    #[test]
+    fn wide_characters_copy_without_their_blank_tail() {
+        let lines = vec![vec![
+            TerminalCell::new("日".into()).with_width(2),
+            TerminalCell::new(" ".into()).with_width(0),
+            TerminalCell::new("x".into()),
+        ]];
+        let out = pane_text(&lines, 10, None);
+        assert_eq!(out.text, "日x");
+        assert!(out.html.contains(">日x</pre>"));
+    }
+
+    #[test]
+    fn html_reproduces_styles_in_runs() {
+        let red = CellStyle {
+            fg: Some(CellColor::Indexed(1)),
+            bold: true,
+            ..Default::default()
+        };
+        let mut row = line("ok ");
+        row.extend(
+            "FAIL<1>"
+                .chars()
+                .map(|c| TerminalCell::with_style(c.to_string(), red.clone())),
+        );
+        let out = pane_text(&[row], 20, None);
+        assert_eq!(out.text, "ok FAIL<1>");
+        assert!(out
+            .html
+            .starts_with("<pre style=\"font-family:'DejaVu Sans Mono'"));
+        assert!(out.html.ends_with(
+            ">ok <span style=\"color:#cd0000;font-weight:bold;\">FAIL&lt;1&gt;</span></pre>"
+        ));
+    }
+
+    #[test]
+    fn html_links_only_safe_urls() {
+        let link = |url: &str| CellStyle {
+            url: Some(url.to_string()),
+            ..Default::default()
+        };
+        let row = vec![
+            TerminalCell::with_style("a".into(), link("https://example.com/?a=1&b=2")),
+            TerminalCell::new(" ".into()),
+            TerminalCell::with_style("b".into(), link("javascript:alert(1)")),
+        ];
+        let html = pane_text(&[row], 10, None).html;
+        assert!(html.contains(
+            "<a href=\"https://example.com/?a=1&amp;b=2\" style=\"color:inherit;\">a</a>"
+        ));
+        assert!(!html.contains("javascript:"));
+    }
+
+    #[test]
+    fn selection_parses_camel_case() {
+        let sel: TextSelection = serde_json::from_str(
+            r#"{"startRow":-5,"startCol":2,"endRow":0,"endCol":9,"lineMode":true}"#,
+        )
+        .unwrap();
+        assert_eq!((sel.start_row, sel.end_col, sel.line_mode), (-5, 9, true));
+    }
+}
@@ -0,0 +1,316 @@
+//! One pane presented as if it were the whole session, for embedding a
+//! single terminal.
+//!
+//! A [`PaneView`] narrows each state update a connection receives to one
+//! pane: the pane sits at the origin of its own coordinate space, the total
+//! size is the pane's, the only window is the one holding it, and the status
+//! line and pane groups are gone. Updates that say nothing about the pane are
+//! dropped, so the view renumbers delta `seq`s to keep them contiguous for
+//! the client's gap check. The view follows the pane when it moves to another
+//! window.
+
+use crate::{StateUpdate, TmuxDelta, TmuxPane, TmuxState};
+
+/// Per-connection state for narrowing updates to one pane.
+#[derive(Debug, Clone)]
+pub struct PaneView {
+    pane: String,
+    /// The window holding the pane, once an update has said.
+    window: Option<String>,
+    /// `seq` of the last delta let through since the last full state.
+    seq: Option<u64>,
+}
+
+impl PaneView {
+    /// A view of `pane` (a tmux pane id such as `%5`).
+    pub fn new(pane: impl Into<String>) -> Self {
+        Self {
+            pane: pane.into(),
+            window: None,
+            seq: None,
+        }
+    }
+
+    pub fn pane(&self) -> &str {
+        &self.pane
+    }
+
+    /// Narrow `update` to the pane; false when nothing is left of it.
+    pub fn apply_update(&mut self, update: &mut StateUpdate) -> bool {
+        match update {
+            StateUpdate::Full { state } => {
+                self.apply_state(state);
+                true
+            }
+            StateUpdate::Delta { delta } => self.apply_delta(delta),
+        }
+    }
+
+    /// Narrow a full state to the pane. It has no panes when the pane is
+    /// not in it.
+    pub fn apply_state(&mut self, state: &mut TmuxState) {
+        self.seq = None;
+        state.panes.retain(|pane| pane.tmux_id == self.pane);
+        if let Some(pane) = state.panes.first_mut() {
+            self.place(pane);
+            state.total_width = pane.width;
+            state.total_height = pane.height;
+        }
+        state
+            .windows
+            .retain(|window| Some(&window.id) == self.window.as_ref());
+        for window in &mut state.windows {
+            window.group_panes = None;
+        }
+        state.active_window_id = self.window.clone();
+        state.active_pane_id = Some(self.pane.clone());
+        state.status_line.clear();
+        state.status_segments = Default::default();
+        state.status_lines.clear();
+        state.session_group = None;
+    }
+
+    fn apply_delta(&mut self, delta: &mut TmuxDelta) -> bool {
+        delta.active_window_id = None;
+        delta.active_pane_id = None;
+        delta.status_line = None;
+        delta.status_segments = None;
+        delta.status_lines = None;
+        delta.status_position = None;
+        delta.session_group = None;
+        delta.total_width = None;
+        delta.total_height = None;
+
+        let mine = delta
+            .new_panes
+            .take()
+            .into_iter()
+            .flatten()
+            .find(|pane| pane.tmux_id == self.pane);
+        if let Some(mut pane) = mine {
+            self.place(&mut pane);
+            delta.total_width = Some(pane.width);
+            delta.total_height = Some(pane.height);
+            delta.active_window_id = self.window.clone();
+            delta.active_pane_id = Some(self.pane.clone());
+            delta.new_panes = Some(vec![pane]);
+        }
+        if let Some(mut panes) = delta.panes.take() {
+            if let Some(mut pane) = panes.remove(&self.pane) {
+                if let Some(pane) = pane.as_mut() {
+                    if let Some(window) = &pane.window_id {
+                        self.window = Some(window.clone());
+                        delta.active_window_id = Some(window.clone());
+                    }
+                    pane.x = None;
+                    pane.y = None;
+                    pane.active = None;
+                    delta.total_width = delta.total_width.or(pane.width);
+                    delta.total_height = delta.total_height.or(pane.height);
+                }
+                delta.panes = Some([(self.pane.clone(), pane)].into());
+            }
+        }
+        if let Some(windows) = &mut delta.windows {
+            windows.retain(|id, window| {
+                // A window delta that only regrouped panes is dropped.
+                if let Some(window) = window {
+                    if window.group_panes.take().is_some() && window.is_empty() {
+                        return false;
+                    }
+                }
+                Some(id) == self.window.as_ref()
+            });
+            if windows.is_empty() {
+                delta.windows = None;
+            }
+        }
+        if let Some(new_windows) = &mut delta.new_windows {
+            new_windows.retain(|window| Some(&window.id) == self.window.as_ref());
+            for window in new_windows.iter_mut() {
+                window.group_panes = None;
+            }
+            if new_windows.is_empty() {
+                delta.new_windows = None;
+            }
+        }
+
+        if delta.is_empty() {
+            return false;
+        }
+        if let Some(last) = self.seq {
+            delta.seq = last + 1;
+        }
+        self.seq = Some(delta.seq);
+        true
+    }
+
+    /// Move `pane` to the origin, make it the active pane, and remember its
+    /// window.
+    fn place(&mut self, pane: &mut TmuxPane) {
+        pane.x = 0;
+        pane.y = 0;
+        pane.active = true;
+        self.window = Some(pane.window_id.clone());
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used, clippy::expect_used)]
+mod tests {
+    use super::*;
+    use crate::PaneDelta;
+    use serde_json::json;
+
+    fn pane(id: &str, window: &str, x: u32) -> TmuxPane {
+        serde_json::from_value(json!({
+            "id": id[1..].parse::<u32>().unwrap(),
+            "tmux_id": id,
+            "window_id": window,
+            "content": [],
+            "cursor_x": 0, "cursor_y": 0,
+            "width": 40, "height": 20, "x": x, "y": 0,
+            "active": false,
+            "command": "sh", "title": "", "border_title": "",
+            "in_mode": false, "copy_cursor_x": 0, "copy_cursor_y": 0,
+        }))
+        .unwrap()
+    }
+
+    fn state() -> TmuxState {
+        serde_json::from_value(json!({
+            "session_name": "main",
+            "active_window_id": "@1",
+            "active_pane_id": "%1",
+            "panes": [pane("%1", "@1", 0), pane("%2", "@1", 41), pane("%3", "@2", 0)],
+            "windows": [
+                { "id": "@1", "index": 0, "name": "a", "active": true, "group_panes": ["%1", "%2"] },
+                { "id": "@2", "index": 1, "name": "b", "active": false },
+            ],
+            "total_width": 81,
+            "total_height": 21,
+            "status_line": "[main] 0:a* 1:b",
+        }))
+        .unwrap()
+    }
+
+    fn delta(seq: u64, panes: &[(&str, PaneDelta)]) -> StateUpdate {
+        let mut delta = TmuxDelta::new(seq);
+        delta.panes = Some(
+            panes
+                .iter()
+                .map(|(id, d)| (id.to_string(), Some(d.clone())))
+                .collect(),
+        );
+        StateUpdate::Delta { delta }
+    }
+
+    #[test]
+    fn a_full_state_shrinks_to_the_pane() {
+        let mut view = PaneView::new("%2");
+        let mut update = StateUpdate::Full { state: state() };
+        assert!(view.apply_update(&mut update));
+        let StateUpdate::Full { state } = update else {
+            unreachable!()
+        };
+        assert_eq!(state.panes.len(), 1);
+        let pane = &state.panes[0];
+        assert_eq!((pane.tmux_id.as_str(), pane.x, pane.y), ("%2", 0, 0));
+        assert!(pane.active);
+        assert_eq!((state.total_width, state.total_height), (40, 20));
+        assert_eq!(state.windows.len(), 1);
+        assert_eq!(state.windows[0].group_panes, None);
+        assert_eq!(state.active_window_id.as_deref(), Some("@1"));
+        assert_eq!(state.active_pane_id.as_deref(), Some("%2"));
+        assert!(state.status_line.is_empty());
+    }
+
+    #[test]
+    fn deltas_for_other_panes_are_dropped_and_seqs_stay_contiguous() {
+        let mut view = PaneView::new("%2");
+        view.apply_update(&mut StateUpdate::Full { state: state() });
+        let moved = PaneDelta {
+            cursor_x: Some(3),
+            x: Some(10),
+            ..PaneDelta::default()
+        };
+
+        let mut first = delta(7, &[("%1", moved.clone()), ("%2", moved.clone())]);
+        assert!(view.apply_update(&mut first));
+        let StateUpdate::Delta { delta: first } = first else {
+            unreachable!()
+        };
+        assert_eq!(first.seq, 7);
+        let panes = first.panes.unwrap();
+        assert_eq!(panes.len(), 1);
+        let mine = panes["%2"].as_ref().unwrap();
+        assert_eq!((mine.cursor_x, mine.x), (Some(3), None));
+
+        assert!(!view.apply_update(&mut delta(8, &[("%1", moved.clone())])));
+        let mut next = delta(9, &[("%2", moved)]);
+        assert!(view.apply_update(&mut next));
+        let StateUpdate::Delta { delta: next } = next else {
+            unreachable!()
+        };
+        assert_eq!(next.seq, 8);
+    }
+
+    #[test]
+    fn the_view_follows_the_pane_to_another_window() {
+        let mut view = PaneView::new("%2");
+        view.apply_update(&mut StateUpdate::Full { state: state() });
+        let swapped = PaneDelta {
+            window_id: Some("@2".into()),
+            ..PaneDelta::default()
+        };
+        let mut update = delta(4, &[("%2", swapped)]);
+        assert!(view.apply_update(&mut update));
+        let StateUpdate::Delta { delta } = update else {
+            unreachable!()
+        };
+        assert_eq!(delta.active_window_id.as_deref(), Some("@2"));
+
+        let mut later = TmuxDelta::new(5);
+        later.windows = Some(
+            [
+                ("@1".to_string(), None),
+                ("@2".to_string(), Some(Default::default())),
+            ]
+            .into(),
+        );
+        let mut later = StateUpdate::Delta { delta: later };
+        assert!(view.apply_update(&mut later));
+        let StateUpdate::Delta { delta: later } = later else {
+            unreachable!()
+        };
+        let windows = later.windows.unwrap();
+        assert!(windows.contains_key("@2") && !windows.contains_key("@1"));
+
+        // Its group's other panes stay hidden; a change to the group alone
+        // says nothing.
+        let grouped = |name: Option<&str>| {
+            let mut delta = TmuxDelta::new(6);
+            delta.windows = Some(
+                [(
+                    "@2".to_string(),
+                    Some(crate::WindowDelta {
+                        group_panes: Some(Some(vec!["%2".into(), "%3".into()])),
+                        name: name.map(String::from),
+                        ..Default::default()
+                    }),
+                )]
+                .into(),
+            );
+            StateUpdate::Delta { delta }
+        };
+        assert!(!view.apply_update(&mut grouped(None)));
+        let mut renamed = grouped(Some("c"));
+        assert!(view.apply_update(&mut renamed));
+        let StateUpdate::Delta { delta: renamed } = renamed else {
+            unreachable!()
+        };
+        let window = renamed.windows.unwrap()["@2"].clone().unwrap();
+        assert_eq!(window.group_panes, None);
+        assert_eq!(window.name.as_deref(), Some("c"));
+    }
+}
@@ -0,0 +1,26 @@
+//! Secrets typed into panes by `inject_secret`.
+//!
+//! Secrets live in the OS credential store (Secret Service, Keychain or
+//! Credential Manager), under the service [`SECRET_SERVICE`] and the name the
+//! command gives — store one with e.g. `secret-tool store --label=tmuxy
+//! service tmuxy username <name>` or `security add-generic-password -s tmuxy
+//! -a <name> -w`. The value is read at injection time and never leaves the
+//! backend: the client sends only the name, the monitor writes the bytes to
+//! the pane with `send-keys -H`, and the pane's echo is masked (see
+//! `control_mode::secret_mask`).
+
+use zeroize::Zeroizing;
+
+/// Keyring service secrets are stored under.
+pub const SECRET_SERVICE: &str = "tmuxy";
+
+/// Read the secret stored as `name`. Errors name the entry, never the value.
+pub fn read_secret(name: &str) -> Result<Zeroizing<String>, String> {
+    let entry = keyring::Entry::new(SECRET_SERVICE, name)
+        .map_err(|e| format!("Invalid secret name '{}': {}", name, e))?;
+    match entry.get_password() {
+        Ok(secret) => Ok(Zeroizing::new(secret)),
+        Err(keyring::Error::NoEntry) => Err(format!("No secret named '{}'", name)),
+        Err(e) => Err(format!("Failed to read secret '{}': {}", name, e)),
+    }
+}
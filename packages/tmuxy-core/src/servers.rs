@@ -3,19 +3,24 @@
 //! A "server" is a tmux server tmuxy drives in control mode: the local machine
 //! (the default) or a remote host reached over SSH. Entries are persisted to
 //! `~/.config/tmuxy/servers.json` by the `tmuxy connect` TUI and read by the
-//! desktop app's sidebar server picker. This is a desktop-only concept — the
-//! web server always uses whatever socket/host it was launched against.
+//! desktop app's sidebar server picker.
 //!
-//! Attaching to a server means pointing the monitor at its socket and, for a
-//! remote, its SSH tunnel. Both are surfaced as the `TMUX_SOCKET` / `TMUXY_SSH`
-//! env vars that [`crate::session::tmux_argv`] already resolves — so a saved
-//! server maps cleanly onto the existing invocation path with no special-casing
-//! downstream. See [`Server::connect_env`].
+//! Attaching the desktop app to a server means pointing the monitor at its
+//! socket and, for a remote, its SSH tunnel. Both are surfaced as the
+//! `TMUX_SOCKET` / `TMUXY_SSH` env vars that [`crate::session::tmux_argv`]
+//! already resolves — so a saved server maps cleanly onto the existing
+//! invocation path with no special-casing downstream. See
+//! [`Server::connect_env`].
+//!
+//! The web server runs against the socket/host it was launched with, except
+//! for sessions a saved server lists in `sessions`: those it fronts on that
+//! server, each through its own [`TmuxTransport`] (see [`server_for_session`]),
+//! so one web server can serve sessions on several machines.
 
 use serde::{Deserialize, Serialize};
 use std::path::PathBuf;
 
-use crate::session::{config_dir, DEFAULT_TMUX_SOCKET};
+use crate::session::{config_dir, TmuxTransport, DEFAULT_TMUX_SOCKET};
 
 /// Where the local machine's server sits in the picker.
 pub const LOCALHOST_ID: &str = "localhost";
@@ -91,6 +96,11 @@ pub struct Server {
     /// Optional preferred session to attach to on this server.
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub session: Option<String>,
+    /// Sessions the web server fronts on this server: a client asking for
+    /// one of these names (`/events?session=`) is attached to it here
+    /// rather than on the web server's own tmux (see [`server_for_session`]).
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub sessions: Vec<String>,
     /// Preserve unknown keys across roundtrips so a newer build's file isn't
     /// truncated when read+written by an older one (mirrors `ManagedState`).
     #[serde(flatten)]
@@ -107,6 +117,7 @@ impl Server {
             ssh: None,
             socket: default_socket(),
             session: None,
+            sessions: Vec::new(),
             extra: serde_json::Map::new(),
         }
     }
@@ -121,6 +132,15 @@ impl Server {
         };
         (self.socket.clone(), ssh)
     }
+
+    /// The transport reaching this server's tmux.
+    pub fn transport(&self) -> TmuxTransport {
+        let (socket, ssh) = self.connect_env();
+        TmuxTransport {
+            socket,
+            ssh: ssh.map(|tail| tail.split_whitespace().map(String::from).collect()),
+        }
+    }
 }
 
 /// Path to the servers file inside the user's config dir.
@@ -193,6 +213,18 @@ pub fn find_server(id: &str) -> Option<Server> {
     read_servers().into_iter().find(|s| s.id == id)
 }
 
+/// The saved server fronting `session` for the web server (the first whose
+/// `sessions` lists it), if any.
+pub fn server_for_session(session: &str) -> Option<Server> {
+    find_session_server(read_servers(), session)
+}
+
+fn find_session_server(servers: Vec<Server>, session: &str) -> Option<Server> {
+    servers
+        .into_iter()
+        .find(|s| s.sessions.iter().any(|name| name == session))
+}
+
 /// The id of the saved server matching the live `TMUX_SOCKET`/`TMUXY_SSH` env,
 /// or [`LOCALHOST_ID`] when none matches. Lets the sidebar picker mark which
 /// server the app is currently attached to.
@@ -210,6 +242,7 @@ pub fn current_server_id() -> String {
 }
 
 #[cfg(test)]
+#[allow(clippy::unwrap_used, clippy::expect_used)]
 mod tests {
     use super::*;
 
@@ -259,6 +292,7 @@ mod tests {
             }),
             socket: "tmuxy".to_string(),
             session: None,
+            sessions: Vec::new(),
             extra: serde_json::Map::new(),
         };
         let (socket, ssh) = server.connect_env();
@@ -266,6 +300,26 @@ mod tests {
         assert_eq!(ssh.as_deref(), Some("felipe@box"));
     }
 
+    #[test]
+    fn sessions_map_to_the_server_listing_them() {
+        let json = r#"[
+            {"id":"localhost","label":"localhost"},
+            {"id":"build","label":"build box","kind":"ssh","ssh":{"host":"build","user":"ci"},
+             "sessions":["ci","release"]}
+        ]"#;
+        let servers: Vec<Server> = serde_json::from_str(json).unwrap();
+        let found = find_session_server(servers.clone(), "release").unwrap();
+        assert_eq!(found.id, "build");
+        assert_eq!(
+            found.transport(),
+            TmuxTransport {
+                socket: DEFAULT_TMUX_SOCKET.to_string(),
+                ssh: Some(vec!["ci@build".to_string()]),
+            }
+        );
+        assert!(find_session_server(servers, "tmuxy").is_none());
+    }
+
     #[test]
     fn unknown_keys_survive_a_roundtrip() {
         let json = r#"[{"id":"x","label":"X","kind":"ssh","socket":"tmuxy","ssh":{"host":"h"},"futureField":42}]"#;
@@ -283,6 +337,7 @@ mod tests {
             ssh: None,
             socket: "tmuxy".to_string(),
             session: None,
+            sessions: Vec::new(),
             extra: serde_json::Map::new(),
         };
         assert_eq!(server.connect_env().1, None);
@@ -79,9 +79,12 @@ pub fn tmux_socket() -> String {
 /// tmuxy behaves the same whether or not it was launched from inside a tmux
 /// pane — and never touches the user's default tmux server.
 pub fn tmux_socket_args() -> [String; 2] {
-    let socket = tmux_socket();
+    socket_args(&tmux_socket())
+}
+
+fn socket_args(socket: &str) -> [String; 2] {
     let flag = if socket.contains('/') { "-S" } else { "-L" };
-    [flag.to_string(), socket]
+    [flag.to_string(), socket.to_string()]
 }
 
 /// The SSH tunnel tmuxy runs tmux through, read from `TMUXY_SSH`. When set and
@@ -97,44 +100,126 @@ pub fn ssh_target() -> Option<Vec<String>> {
     }
 }
 
-/// Build the argv to invoke tmux, honoring an optional SSH tunnel
-/// ([`ssh_target`]). `pty` selects whether the ssh hop allocates a remote tty
-/// (`-tt`) — required for `-CC` control mode, but harmful for one-off reads
-/// (it echoes CRs into captured output), so pass `false` for those.
-///
-/// Returns e.g.:
-///   local:  `["/opt/homebrew/bin/tmux", "-L", "tmuxy"]`
-///   ssh:    `["ssh", "-tt", "user@host", "tmux", "-L", "tmuxy"]`
-///
-/// The remote tmux is invoked as bare `tmux` (resolved by the remote login
-/// shell's PATH) — the local [`tmux_path`] absolute path is meaningless there.
-pub fn tmux_argv(pty: bool) -> Vec<String> {
-    match ssh_target() {
-        Some(ssh) => {
-            let mut v = vec!["ssh".to_string()];
-            if pty {
-                v.push("-tt".to_string());
-            }
-            v.extend(ssh);
-            v.push("tmux".to_string());
-            v.extend(tmux_socket_args());
-            v
+/// The command new panes in `session` run instead of the login shell tmux
+/// would start: the session's `name=command` line in `TMUXY_SESSION_COMMANDS`
+/// (one per line), else `TMUXY_DEFAULT_COMMAND` — e.g. `bash -l`,
+/// `cd ~/src && exec $SHELL`, or `ssh build-box`. `None` when neither names
+/// one, leaving tmux's own `default-command`.
+pub fn default_command(session: &str) -> Option<String> {
+    resolve_default_command(
+        session,
+        std::env::var("TMUXY_SESSION_COMMANDS").ok().as_deref(),
+        std::env::var("TMUXY_DEFAULT_COMMAND").ok().as_deref(),
+    )
+}
+
+fn resolve_default_command(
+    session: &str,
+    per_session: Option<&str>,
+    fallback: Option<&str>,
+) -> Option<String> {
+    per_session
+        .into_iter()
+        .flat_map(str::lines)
+        .filter_map(|line| line.split_once('='))
+        .find(|(name, _)| name.trim() == session)
+        .map(|(_, command)| command)
+        .or(fallback)
+        .map(str::trim)
+        .filter(|command| !command.is_empty())
+        .map(String::from)
+}
+
+/// ssh options that keep a tunnel from hanging on a dead link: probe every
+/// 15s and give up after three unanswered probes, so a control-mode monitor
+/// sees its connection end (and reconnects) within a minute instead of
+/// waiting on TCP. Left out when the ssh options already set them.
+const SSH_KEEPALIVE: [&str; 4] = [
+    "-o",
+    "ServerAliveInterval=15",
+    "-o",
+    "ServerAliveCountMax=3",
+];
+
+/// Where a tmux server is: a socket ([`tmux_socket_args`] semantics), on this
+/// machine or at the end of an SSH tunnel. The process-wide default comes
+/// from `TMUX_SOCKET` / `TMUXY_SSH` ([`TmuxTransport::from_env`]); the web
+/// server can front sessions on other hosts with transports of their own
+/// (see `servers::server_for_session`).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TmuxTransport {
+    pub socket: String,
+    /// The ssh argv tail (options then destination), `None` for local
+    pub ssh: Option<Vec<String>>,
+}
+
+impl TmuxTransport {
+    /// The transport the environment names.
+    pub fn from_env() -> Self {
+        TmuxTransport {
+            socket: tmux_socket(),
+            ssh: ssh_target(),
         }
-        None => {
-            let mut v = vec![tmux_path().to_string()];
-            v.extend(tmux_socket_args());
-            v
+    }
+
+    pub fn is_remote(&self) -> bool {
+        self.ssh.is_some()
+    }
+
+    /// The argv to invoke tmux. `pty` selects whether the ssh hop allocates
+    /// a remote tty (`-tt`) — required for `-CC` control mode, but harmful
+    /// for one-off reads (it echoes CRs into captured output), so pass
+    /// `false` for those.
+    ///
+    /// Returns e.g.:
+    ///   local:  `["/opt/homebrew/bin/tmux", "-L", "tmuxy"]`
+    ///   ssh:    `["ssh", "-tt", "-o", "ServerAliveInterval=15", ..., "user@host", "tmux", "-L", "tmuxy"]`
+    ///
+    /// The remote tmux is invoked as bare `tmux` (resolved by the remote login
+    /// shell's PATH) — the local [`tmux_path`] absolute path is meaningless there.
+    pub fn argv(&self, pty: bool) -> Vec<String> {
+        let socket = socket_args(&self.socket);
+        match &self.ssh {
+            Some(ssh) => {
+                let mut v = vec!["ssh".to_string()];
+                if pty {
+                    v.push("-tt".to_string());
+                }
+                if !ssh.iter().any(|arg| arg.contains("ServerAlive")) {
+                    v.extend(SSH_KEEPALIVE.map(String::from));
+                }
+                v.extend(ssh.iter().cloned());
+                v.push("tmux".to_string());
+                v.extend(socket);
+                v
+            }
+            None => {
+                let mut v = vec![tmux_path().to_string()];
+                v.extend(socket);
+                v
+            }
         }
     }
+
+    /// A `Command` for one-off tmux calls over this transport.
+    pub fn command(&self) -> Command {
+        let argv = self.argv(false);
+        let mut cmd = Command::new(&argv[0]);
+        cmd.args(&argv[1..]);
+        cmd
+    }
+}
+
+/// Build the argv to invoke tmux over the environment's transport, honoring
+/// an optional SSH tunnel ([`ssh_target`]). See [`TmuxTransport::argv`].
+pub fn tmux_argv(pty: bool) -> Vec<String> {
+    TmuxTransport::from_env().argv(pty)
 }
 
 /// Create a `Command` for tmux targeting the resolved socket (and SSH tunnel,
 /// if any). Used for one-off reads/writes — no remote tty (`pty = false`).
 pub fn tmux_command() -> Command {
-    let argv = tmux_argv(false);
-    let mut cmd = Command::new(&argv[0]);
-    cmd.args(&argv[1..]);
-    cmd
+    TmuxTransport::from_env().command()
 }
 
 /// Build the tmux shell command string with the socket flag for use in shell
@@ -208,6 +293,10 @@ const BUNDLED_THEMES: &[(&str, &str)] = &[
 const BUNDLED_BIN_SCRIPTS: &[(&str, &str)] = &[
     ("tmuxy-cli", include_str!("../../../bin/tmuxy-cli")),
     ("tmuxy/_lib", include_str!("../../../bin/tmuxy/_lib")),
+    (
+        "tmuxy/dashboard",
+        include_str!("../../../bin/tmuxy/dashboard"),
+    ),
     (
         "tmuxy/event-emit",
         include_str!("../../../bin/tmuxy/event-emit"),
@@ -245,6 +334,10 @@ const BUNDLED_BIN_SCRIPTS: &[(&str, &str)] = &[
         "tmuxy/pane-group-switch",
         include_str!("../../../bin/tmuxy/pane-group-switch"),
     ),
+    (
+        "tmuxy/sandbox-exec",
+        include_str!("../../../bin/tmuxy/sandbox-exec"),
+    ),
     ("tmuxy/stack", include_str!("../../../bin/tmuxy/stack")),
     (
         "tmuxy/session-connect",
@@ -258,6 +351,10 @@ const BUNDLED_BIN_SCRIPTS: &[(&str, &str)] = &[
         "tmuxy/tmuxy-widget",
         include_str!("../../../bin/tmuxy/tmuxy-widget"),
     ),
+    (
+        "tmuxy/tmuxy-widget-git",
+        include_str!("../../../bin/tmuxy/tmuxy-widget-git"),
+    ),
     (
         "tmuxy/tmuxy-widget-image",
         include_str!("../../../bin/tmuxy/tmuxy-widget-image"),
@@ -266,6 +363,23 @@ const BUNDLED_BIN_SCRIPTS: &[(&str, &str)] = &[
         "tmuxy/tmuxy-widget-markdown",
         include_str!("../../../bin/tmuxy/tmuxy-widget-markdown"),
     ),
+    (
+        "tmuxy/tmuxy-widget-metric",
+        include_str!("../../../bin/tmuxy/tmuxy-widget-metric"),
+    ),
+    (
+        "tmuxy/tmuxy-widget-sysmon",
+        include_str!("../../../bin/tmuxy/tmuxy-widget-sysmon"),
+    ),
+    (
+        "tmuxy/tmuxy-widget-table",
+        include_str!("../../../bin/tmuxy/tmuxy-widget-table"),
+    ),
+    (
+        "tmuxy/tmuxy-widget-timer",
+        include_str!("../../../bin/tmuxy/tmuxy-widget-timer"),
+    ),
+    ("tmuxy/notify", include_str!("../../../bin/tmuxy/notify")),
 ];
 
 /// Resolve the user's tmuxy config directory: $XDG_CONFIG_HOME/tmuxy
@@ -851,8 +965,13 @@ pub fn session_exists(session_name: &str) -> Result<bool> {
 
 pub fn create_session(session_name: &str) -> Result<()> {
     let config_path = get_config_path();
+    let default_command = default_command(session_name);
 
     let mut args = vec!["new-session", "-d", "-s", session_name];
+    // The first window's pane; later ones read the session option set below.
+    if let Some(ref command) = default_command {
+        args.push(command);
+    }
 
     // Use custom config if it exists
     let config_str = config_path
@@ -898,6 +1017,18 @@ pub fn create_session(session_name: &str) -> Result<()> {
         ])
         .output();
 
+    if let Some(command) = default_command {
+        let _ = tmux_command()
+            .args([
+                "set-option",
+                "-t",
+                session_name,
+                "default-command",
+                &command,
+            ])
+            .output();
+    }
+
     Ok(())
 }
 
@@ -937,6 +1068,78 @@ pub fn create_or_attach(session_name: &str) -> Result<()> {
 mod tests {
     use super::*;
 
+    #[test]
+    fn default_command_prefers_the_session_entry() {
+        let per_session = "work=ssh build-box\nnotes = cd ~/notes && exec $SHELL -l";
+        assert_eq!(
+            resolve_default_command("work", Some(per_session), Some("bash -l")).as_deref(),
+            Some("ssh build-box")
+        );
+        assert_eq!(
+            resolve_default_command("notes", Some(per_session), None).as_deref(),
+            Some("cd ~/notes && exec $SHELL -l")
+        );
+        assert_eq!(
+            resolve_default_command("tmuxy", Some(per_session), Some("bash -l")).as_deref(),
+            Some("bash -l")
+        );
+        assert_eq!(resolve_default_command("tmuxy", None, Some("  ")), None);
+    }
+
+    #[test]
+    fn ssh_transport_adds_keepalive_unless_configured() {
+        let remote = TmuxTransport {
+            socket: "tmuxy".to_string(),
+            ssh: Some(vec![
+                "-p".to_string(),
+                "2222".to_string(),
+                "me@box".to_string(),
+            ]),
+        };
+        assert_eq!(
+            remote.argv(true),
+            [
+                "ssh",
+                "-tt",
+                "-o",
+                "ServerAliveInterval=15",
+                "-o",
+                "ServerAliveCountMax=3",
+                "-p",
+                "2222",
+                "me@box",
+                "tmux",
+                "-L",
+                "tmuxy"
+            ]
+        );
+        let tuned = TmuxTransport {
+            socket: "/tmp/tmux.sock".to_string(),
+            ssh: Some(vec![
+                "-o".to_string(),
+                "ServerAliveInterval=5".to_string(),
+                "box".to_string(),
+            ]),
+        };
+        assert_eq!(
+            tuned.argv(false),
+            [
+                "ssh",
+                "-o",
+                "ServerAliveInterval=5",
+                "box",
+                "tmux",
+                "-S",
+                "/tmp/tmux.sock"
+            ]
+        );
+        assert!(!TmuxTransport {
+            socket: "tmuxy".to_string(),
+            ssh: None
+        }
+        .is_remote());
+    }
+
     #[test]
     fn migrate_bin_paths_rewrites_stale_relative_paths() {
         let input =
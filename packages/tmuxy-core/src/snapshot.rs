@@ -0,0 +1,191 @@
+//! Session snapshots: the screen a terminal attached to a session shows,
+//! status line included, as plain text and as styled cells.
+//!
+//! A read-only `attach-session -r` runs on a pty sized to the session's
+//! window, and whatever it draws within the timeout goes through vt100. This
+//! is tmux's own rendering, which makes it the reference the frontend's
+//! layout is compared against (`GET /api/snapshot`, `tmux-capture`).
+
+use serde::Serialize;
+use std::io::Read;
+use std::sync::mpsc;
+use std::time::{Duration, Instant};
+
+use crate::session::TmuxTransport;
+use crate::{extract_cells_from_screen, PaneContent, TmuxError, TmuxResult};
+
+/// How long `capture` reads the attached client's output by default.
+pub const DEFAULT_TIMEOUT: Duration = Duration::from_millis(100);
+
+/// A rendered session screen.
+#[derive(Debug, Clone, Serialize)]
+pub struct Snapshot {
+    pub rows: u16,
+    pub cols: u16,
+    /// One string per row, padded to `cols`
+    pub lines: Vec<String>,
+    pub cells: PaneContent,
+}
+
+/// Rows the status line takes, from the `status` option's value.
+fn status_rows(value: &str) -> u16 {
+    match value {
+        "off" => 0,
+        "on" => 1,
+        rows => rows.parse().unwrap_or(1),
+    }
+}
+
+/// The terminal size showing `session` whole: its window plus status rows.
+fn session_size(transport: &TmuxTransport, session: &str) -> TmuxResult<(u16, u16)> {
+    let output = transport
+        .command()
+        .args([
+            "display-message",
+            "-t",
+            session,
+            "-p",
+            "#{window_width} #{window_height} #{status}",
+        ])
+        .output()?;
+    if !output.status.success() {
+        return Err(TmuxError::other(
+            String::from_utf8_lossy(&output.stderr).trim().to_string(),
+        ));
+    }
+    let dims = String::from_utf8_lossy(&output.stdout);
+    let parse = |field: Option<&str>| field.and_then(|v| v.parse::<u16>().ok());
+    let mut fields = dims.split_whitespace();
+    match (parse(fields.next()), parse(fields.next()), fields.next()) {
+        (Some(cols), Some(rows), status) => Ok((cols, rows + status.map_or(1, status_rows))),
+        _ => Err(TmuxError::other(format!(
+            "unexpected session size: {:?}",
+            dims.trim()
+        ))),
+    }
+}
+
+/// Attach read-only to `session` on a `cols`x`rows` pty and collect what it
+/// draws until `timeout` passes or the client exits.
+fn attach_output(
+    transport: &TmuxTransport,
+    session: &str,
+    cols: u16,
+    rows: u16,
+    timeout: Duration,
+) -> TmuxResult<Vec<u8>> {
+    let pty_error = |e: pty_process::Error| TmuxError::other(format!("pty: {e}"));
+    let (mut pty, pts) = pty_process::blocking::open().map_err(pty_error)?;
+    pty.resize(pty_process::Size::new(rows, cols))
+        .map_err(pty_error)?;
+
+    let argv = transport.argv(true);
+    let mut child = pty_process::blocking::Command::new(&argv[0])
+        .args(&argv[1..])
+        .args(["-u", "attach-session", "-r", "-t", session])
+        .env("TERM", "xterm-256color")
+        .env("LANG", "en_US.UTF-8")
+        .env("LC_ALL", "en_US.UTF-8")
+        .spawn(pts)
+        .map_err(pty_error)?;
+
+    // Reads block, so they run on their own thread; it ends when the
+    // client is killed below and the pty reports EOF (or EIO).
+    let (tx, rx) = mpsc::channel();
+    std::thread::spawn(move || {
+        let mut buf = [0u8; 4096];
+        loop {
+            match pty.read(&mut buf) {
+                Ok(0) | Err(_) => break,
+                Ok(n) => {
+                    if tx.send(buf[..n].to_vec()).is_err() {
+                        break;
+                    }
+                }
+            }
+        }
+    });
+
+    let deadline = Instant::now() + timeout;
+    let mut output = Vec::new();
+    while let Some(left) = deadline.checked_duration_since(Instant::now()) {
+        match rx.recv_timeout(left) {
+            Ok(chunk) => output.extend_from_slice(&chunk),
+            Err(_) => break,
+        }
+    }
+    let _ = child.kill();
+    let _ = child.wait();
+    Ok(output)
+}
+
+/// Render a client's output on a `cols`x`rows` screen.
+pub fn render(output: &[u8], cols: u16, rows: u16) -> Snapshot {
+    let mut parser = vt100::Parser::new(rows, cols, 0);
+    parser.process(output);
+    let screen = parser.screen();
+    let lines = (0..rows)
+        .map(|row| {
+            (0..cols)
+                .map(|col| {
+                    screen
+                        .cell(row, col)
+                        .and_then(|cell| cell.contents().chars().next())
+                        .unwrap_or(' ')
+                })
+                .collect()
+        })
+        .collect();
+    Snapshot {
+        rows,
+        cols,
+        lines,
+        cells: extract_cells_from_screen(screen),
+    }
+}
+
+/// Snapshot `session`'s screen over `transport`. Blocking: it runs tmux
+/// and reads its output for up to `timeout`.
+pub fn capture(
+    transport: &TmuxTransport,
+    session: &str,
+    timeout: Duration,
+) -> TmuxResult<Snapshot> {
+    let exists = transport
+        .command()
+        .args(["has-session", "-t", session])
+        .output()?
+        .status
+        .success();
+    if !exists {
+        return Err(TmuxError::SessionNotFound {
+            name: session.to_string(),
+        });
+    }
+    let (cols, rows) = session_size(transport, session)?;
+    let output = attach_output(transport, session, cols, rows, timeout)?;
+    Ok(render(&output, cols, rows))
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used, clippy::expect_used)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn renders_padded_lines_and_styled_cells() {
+        let snapshot = render(b"ab\r\n\x1b[1mc\x1b[0m", 4, 2);
+        assert_eq!((snapshot.cols, snapshot.rows), (4, 2));
+        assert_eq!(snapshot.lines, ["ab  ", "c   "]);
+        assert_eq!(snapshot.cells.len(), 2);
+        let bold = snapshot.cells[1][0].style.as_ref().expect("styled");
+        assert!(bold.bold);
+    }
+
+    #[test]
+    fn status_rows_follow_the_status_option() {
+        assert_eq!(status_rows("off"), 0);
+        assert_eq!(status_rows("on"), 1);
+        assert_eq!(status_rows("3"), 3);
+    }
+}
@@ -0,0 +1,401 @@
+//! Delimited text files (CSV, TSV and friends) as pageable, sortable tables
+//! for the table widget (`tmuxy view data.csv`).
+//!
+//! A file is never held in memory. The first query streams through it once,
+//! remembering where each row starts; a page is then read by seeking to its
+//! rows. Sorting by a column streams the file once more for that column's
+//! values and keeps only the resulting row order. [`TableCache`] keeps these
+//! indexes for the most recently viewed files and rebuilds one when its file
+//! changes.
+//!
+//! The delimiter is sniffed from the start of the file ([`detect_delimiter`])
+//! unless the extension is `.tsv`/`.tab`.
+
+use serde::Serialize;
+use std::collections::HashMap;
+use std::fs::File;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+pub use tmuxy_protocol::{TableQuery, TableSort};
+
+/// Most rows one query returns.
+pub const MAX_PAGE_ROWS: usize = 1000;
+
+/// Files whose indexes a [`TableCache`] keeps.
+const CACHED_TABLES: usize = 8;
+
+/// How much of the file [`detect_delimiter`] looks at.
+const SNIFF_BYTES: usize = 64 * 1024;
+
+const DELIMITERS: [u8; 4] = [b',', b'\t', b';', b'|'];
+
+/// The rows a [`TableQuery`] asked for, with what the viewer needs to page.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct TablePage {
+    /// The first row of the file.
+    pub headers: Vec<String>,
+    pub rows: Vec<Vec<String>>,
+    pub offset: usize,
+    /// Data rows in the file, not counting the header.
+    pub total_rows: usize,
+    pub delimiter: char,
+}
+
+/// Guess the delimiter of delimited text from its start: the candidate that
+/// splits the most lines into the same number of fields, preferring more
+/// fields on a tie. Plain text with no candidate in it comes out as `,`.
+pub fn detect_delimiter(sample: &[u8]) -> u8 {
+    // Only whole lines: a cut-off last line would skew its field count.
+    let sample = match sample.iter().rposition(|&b| b == b'\n') {
+        Some(end) if sample.len() >= SNIFF_BYTES => &sample[..end],
+        _ => sample,
+    };
+    let mut best = (b',', 0usize, 0usize);
+    for delimiter in DELIMITERS {
+        let mut reader = csv::ReaderBuilder::new()
+            .delimiter(delimiter)
+            .has_headers(false)
+            .flexible(true)
+            .from_reader(sample);
+        let counts: Vec<usize> = reader
+            .byte_records()
+            .take(50)
+            .filter_map(Result::ok)
+            .map(|record| record.len())
+            .collect();
+        let Some(&fields) = counts.first() else {
+            continue;
+        };
+        if fields < 2 {
+            continue;
+        }
+        let agreeing = counts.iter().filter(|&&n| n == fields).count();
+        if (agreeing, fields) > (best.1, best.2) {
+            best = (delimiter, agreeing, fields);
+        }
+    }
+    best.0
+}
+
+fn read_error(path: &Path, e: impl std::fmt::Display) -> crate::TmuxError {
+    crate::TmuxError::other(format!("{}: {e}", path.display()))
+}
+
+fn open_reader(file: File, delimiter: u8) -> csv::Reader<File> {
+    csv::ReaderBuilder::new()
+        .delimiter(delimiter)
+        .has_headers(false)
+        .flexible(true)
+        .from_reader(file)
+}
+
+fn strings(record: &csv::ByteRecord) -> Vec<String> {
+    record
+        .iter()
+        .map(|field| String::from_utf8_lossy(field).into_owned())
+        .collect()
+}
+
+/// Ascending row order for a column's values, by number when every
+/// non-empty value is one, and how many rows are non-empty. Empty cells come
+/// after the rest.
+fn sort_order(values: Vec<String>) -> (Vec<u32>, usize) {
+    let filled = values.iter().filter(|v| !v.trim().is_empty()).count();
+    let mut order: Vec<u32> = (0..values.len() as u32).collect();
+    let numbers: Option<Vec<Option<f64>>> = values
+        .iter()
+        .map(|v| match v.trim() {
+            "" => Some(None),
+            v => v.parse::<f64>().ok().filter(|n| n.is_finite()).map(Some),
+        })
+        .collect();
+    match numbers {
+        Some(numbers) => order.sort_by(|&a, &b| match (numbers[a as usize], numbers[b as usize]) {
+            (Some(x), Some(y)) => x.total_cmp(&y),
+            (x, y) => y.is_some().cmp(&x.is_some()),
+        }),
+        None => {
+            let keys: Vec<String> = values.iter().map(|v| v.trim().to_lowercase()).collect();
+            order.sort_by(|&a, &b| {
+                let (x, y) = (&keys[a as usize], &keys[b as usize]);
+                x.is_empty().cmp(&y.is_empty()).then_with(|| x.cmp(y))
+            });
+        }
+    }
+    (order, filled)
+}
+
+/// Where every row of one file starts, and the row orders sorted so far.
+struct TableIndex {
+    stamp: (SystemTime, u64),
+    delimiter: u8,
+    headers: Vec<String>,
+    /// Byte offset of each data row.
+    rows: Vec<u64>,
+    /// Ascending row order per sorted column, and its non-empty rows.
+    sorted: HashMap<usize, (Vec<u32>, usize)>,
+    last_used: u64,
+}
+
+impl TableIndex {
+    fn build(path: &Path, stamp: (SystemTime, u64)) -> crate::TmuxResult<Self> {
+        let delimiter = match path.extension().and_then(|e| e.to_str()) {
+            Some(ext) if ext.eq_ignore_ascii_case("tsv") || ext.eq_ignore_ascii_case("tab") => {
+                b'\t'
+            }
+            _ => {
+                use std::io::Read as _;
+                let mut sample = Vec::with_capacity(SNIFF_BYTES);
+                File::open(path)
+                    .and_then(|f| f.take(SNIFF_BYTES as u64).read_to_end(&mut sample))
+                    .map_err(|e| read_error(path, e))?;
+                detect_delimiter(&sample)
+            }
+        };
+        let file = File::open(path).map_err(|e| read_error(path, e))?;
+        let mut reader = open_reader(file, delimiter);
+        let mut record = csv::ByteRecord::new();
+        let mut headers = Vec::new();
+        let mut rows = Vec::new();
+        let mut first = true;
+        while reader
+            .read_byte_record(&mut record)
+            .map_err(|e| read_error(path, e))?
+        {
+            if first {
+                headers = strings(&record);
+                first = false;
+            } else {
+                rows.push(record.position().map_or(0, |p| p.byte()));
+            }
+        }
+        Ok(Self {
+            stamp,
+            delimiter,
+            headers,
+            rows,
+            sorted: HashMap::new(),
+            last_used: 0,
+        })
+    }
+
+    fn sort(&mut self, path: &Path, column: usize) -> crate::TmuxResult<&(Vec<u32>, usize)> {
+        if !self.sorted.contains_key(&column) {
+            let file = File::open(path).map_err(|e| read_error(path, e))?;
+            let mut reader = open_reader(file, self.delimiter);
+            let mut record = csv::ByteRecord::new();
+            let mut values = Vec::with_capacity(self.rows.len());
+            let mut first = true;
+            while reader
+                .read_byte_record(&mut record)
+                .map_err(|e| read_error(path, e))?
+            {
+                if first {
+                    first = false;
+                    continue;
+                }
+                let value = record.get(column).unwrap_or_default();
+                values.push(String::from_utf8_lossy(value).into_owned());
+            }
+            self.sorted.insert(column, sort_order(values));
+        }
+        self.sorted
+            .get(&column)
+            .ok_or_else(|| crate::TmuxError::other("sort order missing"))
+    }
+
+    fn page(&mut self, path: &Path, query: &TableQuery) -> crate::TmuxResult<TablePage> {
+        let total = self.rows.len();
+        let start = query.offset.min(total);
+        let end = start
+            .saturating_add(query.limit.min(MAX_PAGE_ROWS))
+            .min(total);
+        let wanted: Vec<usize> = match query.sort {
+            None => (start..end).collect(),
+            Some(sort) => {
+                let (order, filled) = self.sort(path, sort.column)?;
+                // Descending reverses the non-empty rows only; empty cells
+                // stay last.
+                (start..end)
+                    .map(|i| match sort.descending && i < *filled {
+                        true => order[filled - 1 - i] as usize,
+                        false => order[i] as usize,
+                    })
+                    .collect()
+            }
+        };
+
+        let file = File::open(path).map_err(|e| read_error(path, e))?;
+        let mut reader = open_reader(file, self.delimiter);
+        let mut record = csv::ByteRecord::new();
+        let mut rows = Vec::with_capacity(wanted.len());
+        // Consecutive rows are read in one pass; anything else is a seek.
+        let mut next = None;
+        for row in wanted {
+            if next != Some(row) {
+                let mut position = csv::Position::new();
+                position.set_byte(self.rows[row]);
+                reader.seek(position).map_err(|e| read_error(path, e))?;
+            }
+            if !reader
+                .read_byte_record(&mut record)
+                .map_err(|e| read_error(path, e))?
+            {
+                break;
+            }
+            rows.push(strings(&record));
+            next = Some(row + 1);
+        }
+        Ok(TablePage {
+            headers: self.headers.clone(),
+            rows,
+            offset: start,
+            total_rows: total,
+            delimiter: char::from(self.delimiter),
+        })
+    }
+}
+
+/// Indexes of recently queried table files.
+#[derive(Default)]
+pub struct TableCache {
+    tables: HashMap<PathBuf, TableIndex>,
+    uses: u64,
+}
+
+impl TableCache {
+    /// Answer `query`, indexing the file first if it is new or has changed.
+    pub fn query(&mut self, query: &TableQuery) -> crate::TmuxResult<TablePage> {
+        let path = PathBuf::from(&query.path);
+        if !path.is_absolute() {
+            return Err(crate::TmuxError::other(format!(
+                "table path must be absolute: {}",
+                query.path
+            )));
+        }
+        let meta = std::fs::metadata(&path).map_err(|e| read_error(&path, e))?;
+        let stamp = (
+            meta.modified().unwrap_or(SystemTime::UNIX_EPOCH),
+            meta.len(),
+        );
+
+        if self.tables.get(&path).is_none_or(|t| t.stamp != stamp) {
+            let index = TableIndex::build(&path, stamp)?;
+            if self.tables.len() >= CACHED_TABLES && !self.tables.contains_key(&path) {
+                let oldest = self
+                    .tables
+                    .iter()
+                    .min_by_key(|(_, t)| t.last_used)
+                    .map(|(p, _)| p.clone());
+                if let Some(oldest) = oldest {
+                    self.tables.remove(&oldest);
+                }
+            }
+            self.tables.insert(path.clone(), index);
+        }
+        self.uses += 1;
+        let uses = self.uses;
+        let table = self
+            .tables
+            .get_mut(&path)
+            .ok_or_else(|| crate::TmuxError::other("table index missing"))?;
+        table.last_used = uses;
+        table.page(&path, query)
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used, clippy::expect_used)]
+mod tests {
+    use super::*;
+
+    fn write(name: &str, contents: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("tmuxy-table-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join(name);
+        std::fs::write(&path, contents).unwrap();
+        path
+    }
+
+    fn query(path: &Path, offset: usize, limit: usize, sort: Option<TableSort>) -> TableQuery {
+        TableQuery {
+            path: path.to_string_lossy().into_owned(),
+            offset,
+            limit,
+            sort,
+        }
+    }
+
+    #[test]
+    fn delimiters_are_sniffed_from_consistent_lines() {
+        assert_eq!(detect_delimiter(b"a,b,c\n1,2,3\n"), b',');
+        assert_eq!(detect_delimiter(b"a\tb\n1\t2\n"), b'\t');
+        // Decimal commas don't fool it: every line has three `;` fields.
+        assert_eq!(
+            detect_delimiter(b"name;price;qty\nx;1,5;2\ny;2,25;1\n"),
+            b';'
+        );
+        assert_eq!(detect_delimiter(b"a|b\n\"x|y\"|z\n"), b'|');
+        assert_eq!(detect_delimiter(b"just text\n"), b',');
+    }
+
+    #[test]
+    fn pages_come_from_the_requested_rows() {
+        let path = write("pages.csv", "id,name\n1,a\n2,\"b, quoted\"\n3,c\n4,d\n");
+        let mut cache = TableCache::default();
+        let page = cache.query(&query(&path, 1, 2, None)).unwrap();
+        assert_eq!(page.headers, ["id", "name"]);
+        assert_eq!(page.rows, [vec!["2", "b, quoted"], vec!["3", "c"]]);
+        assert_eq!((page.offset, page.total_rows, page.delimiter), (1, 4, ','));
+
+        // Past the end is an empty page, not an error.
+        let page = cache.query(&query(&path, 10, 5, None)).unwrap();
+        assert!(page.rows.is_empty());
+        assert_eq!(page.offset, 4);
+    }
+
+    #[test]
+    fn sorting_is_numeric_when_the_column_is() {
+        let path = write("sort.tsv", "n\tword\n10\tb\n9\tA\n\tc\n100\ta\n");
+        let mut cache = TableCache::default();
+        let column = |page: TablePage, i: usize| -> Vec<String> {
+            page.rows.into_iter().map(|r| r[i].clone()).collect()
+        };
+        let up = Some(TableSort {
+            column: 0,
+            descending: false,
+        });
+        assert_eq!(
+            column(cache.query(&query(&path, 0, 10, up)).unwrap(), 0),
+            ["9", "10", "100", ""]
+        );
+        let down = Some(TableSort {
+            column: 1,
+            descending: true,
+        });
+        assert_eq!(
+            column(cache.query(&query(&path, 0, 2, down)).unwrap(), 1),
+            ["c", "b"]
+        );
+    }
+
+    #[test]
+    fn a_changed_file_is_indexed_again() {
+        let path = write("changed.csv", "a\n1\n");
+        let mut cache = TableCache::default();
+        assert_eq!(
+            cache.query(&query(&path, 0, 10, None)).unwrap().total_rows,
+            1
+        );
+        std::fs::write(&path, "a\n1\n2\n").unwrap();
+        assert_eq!(
+            cache.query(&query(&path, 0, 10, None)).unwrap().total_rows,
+            2
+        );
+
+        assert!(cache
+            .query(&query(Path::new("rel.csv"), 0, 1, None))
+            .is_err());
+    }
+}
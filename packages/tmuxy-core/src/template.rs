@@ -0,0 +1,751 @@
+//! Session templates, tmuxinator-style.
+//!
+//! A template file describes a session: its windows, how each window's
+//! panes split, what runs in them, and the pane groups and floats tmuxy
+//! layers on top. `tmuxy up <template>` and the server's
+//! `create_from_template` command both build it the same way: a
+//! `new-session`, then a [`TemplatePlan`] of commands that run in order
+//! over a control-mode connection.
+//!
+//! ```yaml
+//! name: app
+//! root: ~/code/app
+//! env:
+//!   RUST_LOG: debug
+//! windows:
+//!   - name: editor
+//!     layout: main-vertical
+//!     panes:
+//!       - vim
+//!       - command: cargo watch -x test
+//!         split: right
+//!         size: 40
+//!       - command: htop
+//!         group: [btop, "journalctl -f"]
+//!     floats:
+//!       - command: lazygit
+//!         width: 120
+//!         height: 40
+//!   - name: logs
+//!     root: log
+//!     panes: [tail -F app.log]
+//! ```
+//!
+//! The same in TOML uses `[[windows]]`, `[[windows.panes]]` and so on; a
+//! pane given as a bare string is just its command. Commands are typed into
+//! the pane's shell, so the shell is still there when they exit.
+//!
+//! No pane id is known until tmux makes the pane, so the plan never names
+//! one. Every command targets the session's current window and its active
+//! pane (`=<session>:`), which each `splitw` and `breakp` moves to the pane
+//! or window it made. A group's membership is gathered in a session option
+//! while its members are made, and their windows carry temporary names
+//! until it is complete, like the parked pane id in `pane_group`.
+
+use std::collections::BTreeMap;
+use std::io::{Read as _, Write as _};
+use std::path::{Path, PathBuf};
+use std::process::Stdio;
+
+use serde::Deserialize;
+pub use tmuxy_protocol::TemplateFormat;
+
+use crate::constants::tmux_options;
+use crate::error::{Result, TmuxError};
+use crate::session::TmuxTransport;
+use crate::WindowType;
+
+/// Session option holding a group's membership while its members are made.
+const GROUP_OPTION: &str = "@tmuxy-template-group";
+/// Session option holding the window a float is opened over.
+const PARENT_OPTION: &str = "@tmuxy-template-parent";
+
+const LAYOUTS: [&str; 5] = [
+    "even-horizontal",
+    "even-vertical",
+    "main-horizontal",
+    "main-vertical",
+    "tiled",
+];
+const DRAWERS: [&str; 4] = ["left", "right", "top", "bottom"];
+
+/// A session template as read from its file.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct SessionTemplate {
+    /// Session name; the caller's choice wins, then the file's name.
+    pub name: Option<String>,
+    /// Working directory for every pane; relative to the template file.
+    pub root: Option<String>,
+    /// Environment for every pane.
+    pub env: BTreeMap<String, String>,
+    pub windows: Vec<WindowTemplate>,
+}
+
+/// One tab.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct WindowTemplate {
+    pub name: Option<String>,
+    /// Working directory, relative to the session's.
+    pub root: Option<String>,
+    /// A tmux layout preset applied once the panes exist (`tiled`, …).
+    pub layout: Option<String>,
+    /// Panes in split order; none means one shell.
+    pub panes: Vec<PaneTemplate>,
+    pub floats: Vec<FloatTemplate>,
+}
+
+/// One pane, split off the one before it.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+#[serde(from = "PaneEntry")]
+pub struct PaneTemplate {
+    pub command: Option<String>,
+    /// Which side of the previous pane this one opens on.
+    pub split: Split,
+    /// Percent of the previous pane this one takes; tmux's half when unset.
+    pub size: Option<u32>,
+    /// Working directory, relative to the window's.
+    pub root: Option<String>,
+    pub title: Option<String>,
+    /// Commands for further tabs of a pane group in this pane's slot; the
+    /// pane itself is the group's first tab and the one shown.
+    pub group: Vec<String>,
+}
+
+/// Where a pane opens relative to the one it splits.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Split {
+    #[default]
+    Below,
+    Right,
+}
+
+/// A bare string is a pane running that command.
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum PaneEntry {
+    Command(String),
+    Pane(PaneFields),
+}
+
+#[derive(Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+struct PaneFields {
+    command: Option<String>,
+    split: Split,
+    size: Option<u32>,
+    root: Option<String>,
+    title: Option<String>,
+    group: Vec<String>,
+}
+
+impl From<PaneEntry> for PaneTemplate {
+    fn from(entry: PaneEntry) -> Self {
+        match entry {
+            PaneEntry::Command(command) => PaneTemplate {
+                command: Some(command),
+                ..Default::default()
+            },
+            PaneEntry::Pane(fields) => PaneTemplate {
+                command: fields.command,
+                split: fields.split,
+                size: fields.size,
+                root: fields.root,
+                title: fields.title,
+                group: fields.group,
+            },
+        }
+    }
+}
+
+/// A float over its window, sized in cells like `tmuxy pane float`.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct FloatTemplate {
+    pub command: Option<String>,
+    pub width: Option<u32>,
+    pub height: Option<u32>,
+    /// Dock to an edge (`left`, `right`, `top`, `bottom`) as a drawer.
+    pub drawer: Option<String>,
+    /// Working directory, relative to the window's.
+    pub root: Option<String>,
+}
+
+/// What builds a session once `new-session` has made it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TemplatePlan {
+    pub session: String,
+    /// `new-session`'s arguments: name, first window, directory and
+    /// environment. Callers add `-d` and a size as they need.
+    pub new_session: Vec<String>,
+    /// The commands that build the rest, each an argv, in order.
+    pub commands: Vec<Vec<String>>,
+}
+
+impl TemplatePlan {
+    /// The whole build as one command list, for a control-mode client
+    /// attached to some other session: a detached `new-session` of
+    /// `cols`x`rows`, then the plan. tmux drops the rest of a list at the
+    /// first command that fails, so nothing runs against an existing session.
+    pub fn command_list(&self, cols: u32, rows: u32) -> String {
+        let mut new_session = vec![
+            "new-session".to_string(),
+            "-d".to_string(),
+            "-x".to_string(),
+            cols.to_string(),
+            "-y".to_string(),
+            rows.to_string(),
+        ];
+        new_session.extend(self.new_session.iter().cloned());
+        let mut commands = vec![new_session];
+        commands.extend(self.commands.iter().cloned());
+        crate::command_line::join_commands(&commands)
+    }
+}
+
+impl SessionTemplate {
+    /// Read a template written in `format`.
+    pub fn parse(text: &str, format: TemplateFormat) -> Result<Self> {
+        let template: Self = match format {
+            TemplateFormat::Yaml => serde_yaml_ng::from_str(text).map_err(|e| e.to_string())?,
+            TemplateFormat::Toml => toml::from_str(text).map_err(|e| e.to_string())?,
+        };
+        template.validate()?;
+        Ok(template)
+    }
+
+    /// Read a template file, in the format its extension names.
+    pub fn load(path: &Path) -> Result<Self> {
+        let format = TemplateFormat::from_path(path).ok_or_else(|| {
+            TmuxError::other(format!(
+                "{}: a template is a .yml, .yaml or .toml file",
+                path.display()
+            ))
+        })?;
+        let text = std::fs::read_to_string(path)?;
+        Self::parse(&text, format).map_err(|e| TmuxError::other(format!("{}: {e}", path.display())))
+    }
+
+    fn validate(&self) -> Result<()> {
+        if self.windows.is_empty() {
+            return Err(TmuxError::other("template has no windows"));
+        }
+        if let Some(key) = self.env.keys().find(|key| {
+            key.is_empty()
+                || key.starts_with(|c: char| c.is_ascii_digit())
+                || !key.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
+        }) {
+            return Err(TmuxError::other(format!(
+                "invalid environment variable name: {key}"
+            )));
+        }
+        for (i, window) in self.windows.iter().enumerate() {
+            let what = format!("window {}", i + 1);
+            if let Some(layout) = window.layout.as_deref() {
+                if !LAYOUTS.contains(&layout) {
+                    return Err(TmuxError::other(format!(
+                        "{what}: unknown layout {layout}, expected one of {}",
+                        LAYOUTS.join(", ")
+                    )));
+                }
+            }
+            if let Some(size) = window
+                .panes
+                .iter()
+                .filter_map(|p| p.size)
+                .find(|size| !(1..100).contains(size))
+            {
+                return Err(TmuxError::other(format!(
+                    "{what}: pane size {size} is not a percentage between 1 and 99"
+                )));
+            }
+            if let Some(drawer) = window
+                .floats
+                .iter()
+                .filter_map(|f| f.drawer.as_deref())
+                .find(|d| !DRAWERS.contains(d))
+            {
+                return Err(TmuxError::other(format!(
+                    "{what}: invalid float drawer {drawer}"
+                )));
+            }
+        }
+        Ok(())
+    }
+
+    /// The session this template makes, named `session`, with relative
+    /// directories read from `base_dir`.
+    pub fn plan(&self, session: &str, base_dir: &Path) -> Result<TemplatePlan> {
+        crate::discovery::validate_session_name(session).map_err(TmuxError::other)?;
+        let root = resolve(base_dir, self.root.as_deref());
+        let target = format!("={session}:");
+        let mut plan = Planner {
+            target: &target,
+            commands: Vec::new(),
+            groups: 0,
+        };
+
+        let mut new_session = vec!["-s".to_string(), session.to_string()];
+        for (i, window) in self.windows.iter().enumerate() {
+            let window_root = resolve(&root, window.root.as_deref());
+            if i == 0 {
+                if let Some(name) = &window.name {
+                    new_session.extend(["-n".to_string(), name.clone()]);
+                }
+                new_session.extend(["-c".to_string(), path_arg(&window_root)]);
+                for (key, value) in &self.env {
+                    new_session.extend(["-e".to_string(), format!("{key}={value}")]);
+                }
+            } else {
+                // A new tab the way `tmuxy tab create` makes one.
+                plan.push(["splitw", "-t", &target, "-c", &path_arg(&window_root)]);
+                let mut breakp = vec!["breakp", "-s", &target];
+                if let Some(name) = &window.name {
+                    breakp.extend(["-n", name]);
+                }
+                plan.push(breakp);
+            }
+            plan.push([
+                "set",
+                "-w",
+                "-t",
+                &target,
+                tmux_options::WINDOW_TYPE,
+                WindowType::Tab.as_str(),
+            ]);
+            plan.window(window, &window_root);
+        }
+        plan.push(["select-window", "-t", &format!("={session}:^")]);
+        Ok(TemplatePlan {
+            session: session.to_string(),
+            new_session,
+            commands: plan.commands,
+        })
+    }
+}
+
+/// Builds a plan's commands for one window after another.
+struct Planner<'a> {
+    target: &'a str,
+    commands: Vec<Vec<String>>,
+    /// Groups made so far, for their windows' temporary names.
+    groups: usize,
+}
+
+impl Planner<'_> {
+    fn push<'s>(&mut self, args: impl IntoIterator<Item = &'s str>) {
+        self.commands
+            .push(args.into_iter().map(str::to_string).collect());
+    }
+
+    /// Type `command` into the active pane's shell.
+    fn run(&mut self, command: Option<&str>) {
+        if let Some(command) = command.filter(|c| !c.trim().is_empty()) {
+            let target = self.target;
+            self.push(["send-keys", "-t", target, "-l", command]);
+            self.push(["send-keys", "-t", target, "Enter"]);
+        }
+    }
+
+    /// The window's panes, groups and floats; the window is current and its
+    /// first pane exists.
+    fn window(&mut self, window: &WindowTemplate, root: &Path) {
+        let target = self.target;
+        let first = PaneTemplate::default();
+        let panes = if window.panes.is_empty() {
+            std::slice::from_ref(&first)
+        } else {
+            &window.panes[..]
+        };
+        for (i, pane) in panes.iter().enumerate() {
+            let pane_root = path_arg(&resolve(root, pane.root.as_deref()));
+            if i > 0 {
+                let mut split = vec!["splitw", "-t", target, "-c", &pane_root];
+                if pane.split == Split::Right {
+                    split.push("-h");
+                }
+                let size = pane.size.map(|size| format!("{size}%"));
+                if let Some(size) = &size {
+                    split.extend(["-l", size]);
+                }
+                self.push(split);
+            }
+            if let Some(title) = &pane.title {
+                self.push(["select-pane", "-t", target, "-T", title]);
+            }
+            self.run(pane.command.as_deref());
+            if !pane.group.is_empty() {
+                self.group(&pane.group, &pane_root);
+            }
+        }
+        if let Some(layout) = &window.layout {
+            self.push(["select-layout", "-t", target, layout]);
+        }
+        for (i, float) in window.floats.iter().enumerate() {
+            self.float(
+                float,
+                i as u32 + 1,
+                &path_arg(&resolve(root, float.root.as_deref())),
+            );
+        }
+    }
+
+    /// Give the active pane hidden group tabs running `commands`.
+    fn group(&mut self, commands: &[String], root: &str) {
+        let target = self.target;
+        self.groups += 1;
+        self.push(["set", "-F", "-t", target, GROUP_OPTION, "#{pane_id}"]);
+        let names: Vec<String> = (1..=commands.len())
+            .map(|tab| format!("tmuxy-template-{}-{tab}", self.groups))
+            .collect();
+        let members = format!("#{{{GROUP_OPTION}}} #{{pane_id}}");
+        for (command, name) in commands.iter().zip(&names) {
+            self.push(["splitw", "-t", target, "-c", root]);
+            self.run(Some(command));
+            self.push(["set", "-F", "-t", target, GROUP_OPTION, &members]);
+            // The group's pane gets the slot back, as the last pane active.
+            self.push(["breakp", "-s", target, "-n", name]);
+            self.push([
+                "set",
+                "-w",
+                "-t",
+                target,
+                tmux_options::WINDOW_TYPE,
+                WindowType::Group.as_str(),
+            ]);
+            self.push(["last-window", "-t", target]);
+        }
+        let session = target.trim_end_matches(':');
+        let members = format!("#{{{GROUP_OPTION}}}");
+        let windows = names.iter().map(|name| format!("{session}:={name}"));
+        // The slot's window carries the list too, as `GroupOp::Create` leaves it.
+        for window in windows.chain([target.to_string()]) {
+            self.push([
+                "set",
+                "-wF",
+                "-t",
+                &window,
+                tmux_options::GROUP_PANES,
+                &members,
+            ]);
+            if window != target {
+                self.push(["rename-window", "-t", &window, "group"]);
+            }
+        }
+        self.push(["set", "-u", "-t", target, GROUP_OPTION]);
+    }
+
+    /// Open a float over the current window, `z` from the bottom.
+    fn float(&mut self, float: &FloatTemplate, z: u32, root: &str) {
+        let target = self.target;
+        self.push(["set", "-F", "-t", target, PARENT_OPTION, "#{window_id}"]);
+        self.push(["splitw", "-t", target, "-c", root]);
+        self.run(float.command.as_deref());
+        self.push(["breakp", "-s", target, "-n", "float"]);
+        let parent = format!("#{{{PARENT_OPTION}}}");
+        let mut tags = vec![
+            (
+                tmux_options::WINDOW_TYPE,
+                WindowType::Float.as_str().to_string(),
+            ),
+            (tmux_options::FLOAT_Z, z.to_string()),
+        ];
+        // A drawer spans its edge, so only the size across it is set.
+        let (width, height) = match float.drawer.as_deref() {
+            Some("left" | "right") => (float.width.or(Some(60)), None),
+            Some(_) => (None, float.height.or(Some(40))),
+            None => (float.width.or(Some(60)), float.height.or(Some(40))),
+        };
+        if let Some(width) = width {
+            tags.push((tmux_options::FLOAT_WIDTH, width.to_string()));
+        }
+        if let Some(height) = height {
+            tags.push((tmux_options::FLOAT_HEIGHT, height.to_string()));
+        }
+        if let Some(drawer) = &float.drawer {
+            tags.push((tmux_options::FLOAT_DRAWER, drawer.clone()));
+        }
+        self.push([
+            "set",
+            "-wF",
+            "-t",
+            target,
+            tmux_options::FLOAT_PARENT,
+            &parent,
+        ]);
+        for (option, value) in &tags {
+            self.push(["set", "-w", "-t", target, option, value]);
+        }
+        self.push(["last-window", "-t", target]);
+        self.push(["set", "-u", "-t", target, PARENT_OPTION]);
+    }
+}
+
+/// `path` read from `base`, with a leading `~` for the home directory.
+fn resolve(base: &Path, path: Option<&str>) -> PathBuf {
+    match path {
+        None => base.to_path_buf(),
+        Some("~") => dirs::home_dir().unwrap_or_else(|| base.to_path_buf()),
+        Some(path) => match (path.strip_prefix("~/"), dirs::home_dir()) {
+            (Some(rest), Some(home)) => home.join(rest),
+            _ => base.join(path),
+        },
+    }
+}
+
+fn path_arg(path: &Path) -> String {
+    path.to_string_lossy().into_owned()
+}
+
+/// Build `plan`'s session over a control-mode client of its own (`tmux -C
+/// new-session`), which stays attached while the commands run and then
+/// detaches. Fails when the session exists, or with the first command tmux
+/// refused; the session stays as far as it got.
+pub fn build_session(transport: &TmuxTransport, plan: &TemplatePlan) -> Result<()> {
+    let mut args = vec!["-C".to_string(), "new-session".to_string()];
+    args.extend(plan.new_session.iter().map(|arg| {
+        if transport.is_remote() {
+            crate::executor::tmux_quote(arg)
+        } else {
+            arg.clone()
+        }
+    }));
+    let mut child = transport
+        .command()
+        .args(&args)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()?;
+    if let Some(mut stdin) = child.stdin.take() {
+        let mut script = String::new();
+        for command in &plan.commands {
+            script.push_str(&crate::command_line::join_commands(std::slice::from_ref(
+                command,
+            )));
+            script.push('\n');
+        }
+        script.push_str("detach-client\n");
+        // A refused new-session exits before reading; its error says why.
+        let _ = stdin.write_all(script.as_bytes());
+    }
+    let mut stdout = String::new();
+    if let Some(mut out) = child.stdout.take() {
+        out.read_to_string(&mut stdout)?;
+    }
+    let output = child.wait_with_output()?;
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    let failed = first_error(&stdout);
+    match failed {
+        // The first block is new-session's own.
+        Some((0, message)) => Err(TmuxError::other(message)),
+        Some((block, message)) => {
+            let command = plan
+                .commands
+                .get(block - 1)
+                .map(|args| crate::command_line::join_commands(std::slice::from_ref(args)))
+                .unwrap_or_default();
+            Err(TmuxError::other(format!("{command}: {message}")))
+        }
+        None if !stdout.contains("%begin") => Err(TmuxError::other(
+            stderr
+                .trim()
+                .strip_prefix("%exit")
+                .unwrap_or(stderr.trim())
+                .to_string(),
+        )),
+        None => Ok(()),
+    }
+}
+
+/// The first `%error` block in control-mode output: its place among the
+/// command blocks and tmux's message.
+fn first_error(output: &str) -> Option<(usize, String)> {
+    let mut block = 0;
+    let mut body = Vec::new();
+    let mut inside = false;
+    for line in output.lines() {
+        if line.starts_with("%begin ") {
+            inside = true;
+            body.clear();
+        } else if inside && line.starts_with("%end ") {
+            inside = false;
+            block += 1;
+        } else if inside && line.starts_with("%error ") {
+            return Some((block, body.join("\n")));
+        } else if inside {
+            body.push(line);
+        }
+    }
+    None
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used, clippy::expect_used)]
+mod tests {
+    use super::*;
+
+    const YAML: &str = r#"
+name: app
+root: /code/app
+env:
+  RUST_LOG: debug
+windows:
+  - name: editor
+    layout: main-vertical
+    panes:
+      - vim
+      - command: cargo test
+        split: right
+        size: 40
+        title: tests
+  - name: logs
+    root: log
+    panes: ["tail -F app.log"]
+"#;
+
+    fn lines(plan: &TemplatePlan) -> Vec<String> {
+        plan.commands
+            .iter()
+            .map(|args| crate::command_line::join_commands(std::slice::from_ref(args)))
+            .collect()
+    }
+
+    #[test]
+    fn yaml_and_toml_read_the_same() {
+        let toml = r#"
+name = "app"
+root = "/code/app"
+
+[env]
+RUST_LOG = "debug"
+
+[[windows]]
+name = "editor"
+layout = "main-vertical"
+panes = ["vim", { command = "cargo test", split = "right", size = 40, title = "tests" }]
+
+[[windows]]
+name = "logs"
+root = "log"
+panes = ["tail -F app.log"]
+"#;
+        let yaml = SessionTemplate::parse(YAML, TemplateFormat::Yaml).unwrap();
+        assert_eq!(
+            SessionTemplate::parse(toml, TemplateFormat::Toml).unwrap(),
+            yaml
+        );
+        assert_eq!(yaml.windows[0].panes[1].split, Split::Right);
+        assert_eq!(
+            yaml.windows[1].panes[0].command.as_deref(),
+            Some("tail -F app.log")
+        );
+        assert_eq!(
+            TemplateFormat::from_path(Path::new("dev.yml")),
+            Some(TemplateFormat::Yaml)
+        );
+        assert_eq!(TemplateFormat::from_path(Path::new("dev.json")), None);
+    }
+
+    #[test]
+    fn plan_splits_windows_and_types_commands() {
+        let template = SessionTemplate::parse(YAML, TemplateFormat::Yaml).unwrap();
+        let plan = template.plan("app", Path::new("/")).unwrap();
+        assert_eq!(
+            plan.new_session,
+            [
+                "-s",
+                "app",
+                "-n",
+                "editor",
+                "-c",
+                "/code/app",
+                "-e",
+                "RUST_LOG=debug"
+            ]
+        );
+        assert_eq!(
+            lines(&plan),
+            [
+                "set -w -t =app: @tmuxy-window-type tab",
+                "send-keys -t =app: -l vim",
+                "send-keys -t =app: Enter",
+                "splitw -t =app: -c /code/app -h -l 40%",
+                "select-pane -t =app: -T tests",
+                "send-keys -t =app: -l 'cargo test'",
+                "send-keys -t =app: Enter",
+                "select-layout -t =app: main-vertical",
+                "splitw -t =app: -c /code/app/log",
+                "breakp -s =app: -n logs",
+                "set -w -t =app: @tmuxy-window-type tab",
+                "send-keys -t =app: -l 'tail -F app.log'",
+                "send-keys -t =app: Enter",
+                "select-window -t =app:^",
+            ]
+        );
+        assert!(plan.command_list(80, 24).starts_with(
+            "new-session -d -x 80 -y 24 -s app -n editor -c /code/app -e RUST_LOG=debug ; set -w"
+        ));
+    }
+
+    #[test]
+    fn groups_and_floats_are_tagged_as_tmuxy_makes_them() {
+        let template = SessionTemplate::parse(
+            "windows:\n  - panes:\n      - command: htop\n        group: [btop]\n    floats:\n      - command: lazygit\n        drawer: right\n",
+            TemplateFormat::Yaml,
+        )
+        .unwrap();
+        let plan = template.plan("s", Path::new("/w")).unwrap();
+        let lines = lines(&plan);
+        let expected = [
+            "set -F -t =s: @tmuxy-template-group '#{pane_id}'",
+            "splitw -t =s: -c /w",
+            "send-keys -t =s: -l btop",
+            "set -F -t =s: @tmuxy-template-group '#{@tmuxy-template-group} #{pane_id}'",
+            "breakp -s =s: -n tmuxy-template-1-1",
+            "set -w -t =s: @tmuxy-window-type group",
+            "last-window -t =s:",
+            "set -wF -t =s:=tmuxy-template-1-1 @tmuxy-group-panes '#{@tmuxy-template-group}'",
+            "rename-window -t =s:=tmuxy-template-1-1 group",
+            "set -wF -t =s: @tmuxy-group-panes '#{@tmuxy-template-group}'",
+            "set -u -t =s: @tmuxy-template-group",
+            "set -F -t =s: @tmuxy-template-parent '#{window_id}'",
+            "breakp -s =s: -n float",
+            "set -wF -t =s: @tmuxy-float-parent '#{@tmuxy-template-parent}'",
+            "set -w -t =s: @tmuxy-window-type float",
+            "set -w -t =s: @tmuxy-float-width 60",
+            "set -w -t =s: @tmuxy-float-drawer right",
+            "last-window -t =s:",
+        ];
+        for line in expected {
+            assert!(lines.iter().any(|l| l == line), "{line:?} in {lines:#?}");
+        }
+        assert!(!lines.iter().any(|l| l.contains("float-height")));
+    }
+
+    #[test]
+    fn rejects_what_it_cannot_build() {
+        let parse = |yaml: &str| SessionTemplate::parse(yaml, TemplateFormat::Yaml);
+        assert!(parse("name: x\n").is_err());
+        assert!(parse("windows:\n  - layout: sideways\n").is_err());
+        assert!(parse("windows:\n  - panes:\n      - size: 100\n").is_err());
+        assert!(parse("windows:\n  - floats:\n      - drawer: middle\n").is_err());
+        assert!(parse("env:\n  1X: y\nwindows: [{}]\n").is_err());
+        assert!(parse("windows:\n  - pains: []\n").is_err());
+        let template = parse("windows: [{}]\n").unwrap();
+        assert!(template.plan("a:b", Path::new("/")).is_err());
+    }
+
+    #[test]
+    fn first_error_names_the_failing_block() {
+        let output = "%begin 1 1 0\n%end 1 1 0\n%begin 1 2 1\n%end 1 2 1\n\
+                      %begin 1 3 1\nno such window: x\n%error 1 3 1\n";
+        assert_eq!(
+            first_error(output),
+            Some((2, "no such window: x".to_string()))
+        );
+        assert_eq!(first_error("%begin 1 1 0\n%end 1 1 0\n"), None);
+    }
+}
@@ -0,0 +1,377 @@
+//! Deterministic harness for the control-mode engine, so monitor and
+//! aggregator changes can be tested without a live tmux.
+//!
+//! [`Harness`] plays the monitor's part around the sans-IO core: control-mode
+//! lines go through `Parser` into `StateAggregator::step_at`, side effects
+//! turn into the commands the monitor would send, and `EmitState` collects
+//! the `StateUpdate` the monitor would broadcast. A scripted fake tmux
+//! answers each sent command with a `%begin`/`%end` block the way control
+//! mode does — one block per `;`-separated segment — from canned responses,
+//! pane contents, and `display-message -p` echoes (which covers the marker
+//! commands). Time is virtual: it only moves on [`Harness::advance`].
+//!
+//! Recorded notification streams replay with [`Harness::play`]; see its
+//! docs for the transcript format. [`assert_golden`] pins a JSON rendering of
+//! the result (typically the updates, i.e. delta generation) to a file.
+//!
+//! Enabled by the `testing` feature.
+
+use std::path::Path;
+use std::time::Duration;
+
+#[cfg(not(target_arch = "wasm32"))]
+use std::time::Instant;
+#[cfg(target_arch = "wasm32")]
+use web_time::Instant;
+
+use crate::constants::tmux_formats;
+use crate::control_mode::{capture_command, Parser, SideEffect, StateAggregator};
+use crate::{StateUpdate, TmuxState};
+
+/// Rounds of command/response a single step may trigger before the harness
+/// assumes the engine is looping and panics.
+const MAX_RESPONSE_ROUNDS: usize = 64;
+
+/// A canned reply for commands starting with a prefix.
+#[derive(Debug, Clone)]
+struct Response {
+    prefix: String,
+    success: bool,
+    lines: Vec<String>,
+}
+
+/// The scripted tmux on the other end of the connection.
+#[derive(Debug, Default)]
+pub struct FakeTmux {
+    responses: Vec<Response>,
+    pane_contents: Vec<(String, Vec<String>)>,
+    command_number: u64,
+}
+
+impl FakeTmux {
+    /// Answer commands starting with `prefix` with `lines`. Later calls for
+    /// the same prefix replace earlier ones; the longest matching prefix wins.
+    pub fn respond(&mut self, prefix: &str, lines: &[&str]) {
+        self.set_response(prefix, true, lines);
+    }
+
+    /// Fail commands starting with `prefix` with an `%error` block.
+    pub fn fail(&mut self, prefix: &str, message: &str) {
+        self.set_response(prefix, false, &[message]);
+    }
+
+    /// What `capture-pane -t <pane_id>` prints.
+    pub fn set_pane_content(&mut self, pane_id: &str, lines: &[&str]) {
+        let lines = lines.iter().map(|l| l.to_string()).collect();
+        self.pane_contents.retain(|(id, _)| id != pane_id);
+        self.pane_contents.push((pane_id.to_string(), lines));
+    }
+
+    fn set_response(&mut self, prefix: &str, success: bool, lines: &[&str]) {
+        self.responses.retain(|r| r.prefix != prefix);
+        self.responses.push(Response {
+            prefix: prefix.to_string(),
+            success,
+            lines: lines.iter().map(|l| l.to_string()).collect(),
+        });
+    }
+
+    /// The control-mode reply to one command line: a block per segment.
+    pub fn reply(&mut self, command: &str) -> Vec<String> {
+        let mut out = Vec::new();
+        for segment in command.split(" ; ") {
+            let (success, body) = self.answer(segment.trim());
+            self.command_number += 1;
+            let n = self.command_number;
+            out.push(format!("%begin 0 {n} 1"));
+            out.extend(body);
+            out.push(format!(
+                "%{} 0 {n} 1",
+                if success { "end" } else { "error" }
+            ));
+        }
+        out
+    }
+
+    fn answer(&self, segment: &str) -> (bool, Vec<String>) {
+        if let Some(response) = self
+            .responses
+            .iter()
+            .filter(|r| segment.starts_with(&r.prefix))
+            .max_by_key(|r| r.prefix.len())
+        {
+            return (response.success, response.lines.clone());
+        }
+        if let Some(rest) = segment.strip_prefix("capture-pane -t ") {
+            let pane_id = rest.split_whitespace().next().unwrap_or_default();
+            let lines = self
+                .pane_contents
+                .iter()
+                .find(|(id, _)| id == pane_id)
+                .map(|(_, lines)| lines.clone())
+                .unwrap_or_default();
+            return (true, lines);
+        }
+        // A literal message prints as itself; one with formats needs a
+        // canned response.
+        if let Some(message) = segment.strip_prefix("display-message -p ") {
+            let message = message.trim_matches('\'');
+            if !message.contains("#{") {
+                return (true, vec![message.to_string()]);
+            }
+        }
+        (true, Vec::new())
+    }
+}
+
+/// The control-mode engine wired to a [`FakeTmux`], on a virtual clock.
+pub struct Harness {
+    parser: Parser,
+    aggregator: StateAggregator,
+    /// The scripted tmux answering sent commands
+    pub tmux: FakeTmux,
+    /// Answer sent commands as they are sent (on by default). Off, they only
+    /// pile up in [`Harness::sent`].
+    pub auto_respond: bool,
+    now: Instant,
+    sent: Vec<String>,
+    updates: Vec<StateUpdate>,
+    effects: Vec<SideEffect>,
+}
+
+impl Harness {
+    pub fn new(session_name: &str) -> Self {
+        Self {
+            parser: Parser::new(),
+            aggregator: StateAggregator::with_session_name(session_name),
+            tmux: FakeTmux::default(),
+            auto_respond: true,
+            now: Instant::now(),
+            sent: Vec::new(),
+            updates: Vec::new(),
+            effects: Vec::new(),
+        }
+    }
+
+    pub fn aggregator(&mut self) -> &mut StateAggregator {
+        &mut self.aggregator
+    }
+
+    /// The state as a full `TmuxState`.
+    pub fn state(&mut self) -> TmuxState {
+        self.aggregator.to_tmux_state()
+    }
+
+    /// Every command sent so far, one entry per command line.
+    pub fn sent(&self) -> &[String] {
+        &self.sent
+    }
+
+    /// Every update emitted so far.
+    pub fn updates(&self) -> &[StateUpdate] {
+        &self.updates
+    }
+
+    /// Take the updates emitted since the last call.
+    pub fn take_updates(&mut self) -> Vec<StateUpdate> {
+        std::mem::take(&mut self.updates)
+    }
+
+    /// Take the side effects produced since the last call.
+    pub fn take_effects(&mut self) -> Vec<SideEffect> {
+        std::mem::take(&mut self.effects)
+    }
+
+    /// Feed one line tmux wrote, then answer whatever it made the engine send.
+    pub fn feed_line(&mut self, line: &str) {
+        let mut pending = self.process_line(line);
+        for _ in 0..MAX_RESPONSE_ROUNDS {
+            if pending.is_empty() || !self.auto_respond {
+                return;
+            }
+            let replies: Vec<String> = pending
+                .iter()
+                .flat_map(|command| self.tmux.reply(command))
+                .collect();
+            pending = replies
+                .iter()
+                .flat_map(|reply| self.process_line(reply))
+                .collect();
+        }
+        panic!("commands still pending after {MAX_RESPONSE_ROUNDS} rounds of responses");
+    }
+
+    /// Feed several lines (see [`Harness::feed_line`]).
+    pub fn feed(&mut self, lines: &str) {
+        for line in lines.lines() {
+            self.feed_line(line);
+        }
+    }
+
+    /// Move the virtual clock forward and run the aggregator's timers.
+    pub fn advance(&mut self, by: Duration) {
+        self.now += by;
+        let effects = self.aggregator.tick(self.now);
+        for command in self.apply(effects) {
+            self.answer(&command);
+        }
+    }
+
+    /// Send a command as the monitor would on a client's behalf, answering
+    /// it like any other.
+    pub fn send(&mut self, command: &str) {
+        self.sent.push(command.to_string());
+        self.answer(command);
+    }
+
+    /// Feed the fake tmux's reply to `command`, unless `auto_respond` is off.
+    fn answer(&mut self, command: &str) {
+        if self.auto_respond {
+            for reply in self.tmux.reply(command) {
+                self.feed_line(&reply);
+            }
+        }
+    }
+
+    /// Replay a transcript: control-mode lines exactly as tmux wrote them.
+    /// Outside `%begin`…`%end` blocks, blank lines and `#` comments are
+    /// skipped and `!advance <ms>` moves the clock. Blocks in a transcript
+    /// are fed as recorded, so a recording made with the commands' replies
+    /// inline replays with `auto_respond` off.
+    pub fn play(&mut self, transcript: &str) {
+        let mut in_block = false;
+        for line in transcript.lines() {
+            if !in_block {
+                let trimmed = line.trim();
+                if trimmed.is_empty() || trimmed.starts_with('#') {
+                    continue;
+                }
+                if let Some(ms) = trimmed.strip_prefix("!advance ") {
+                    let ms: u64 = ms
+                        .trim()
+                        .parse()
+                        .unwrap_or_else(|_| panic!("bad transcript directive: {line:?}"));
+                    self.advance(Duration::from_millis(ms));
+                    continue;
+                }
+            }
+            if line.starts_with("%begin ") {
+                in_block = true;
+            } else if line.starts_with("%end ") || line.starts_with("%error ") {
+                in_block = false;
+            }
+            self.feed_line(line);
+        }
+    }
+
+    /// Parse and step one line; returns the commands it made the engine send.
+    fn process_line(&mut self, line: &str) -> Vec<String> {
+        let Some(event) = self.parser.parse_line(line) else {
+            return Vec::new();
+        };
+        let effects = self.aggregator.step_at(event, self.now).effects;
+        self.apply(effects)
+    }
+
+    /// Carry out side effects the way the monitor does; returns the command
+    /// lines sent.
+    fn apply(&mut self, effects: Vec<SideEffect>) -> Vec<String> {
+        let mut commands = Vec::new();
+        for effect in &effects {
+            match effect {
+                SideEffect::SendTmuxCommand(cmd) => commands.push(cmd.clone()),
+                SideEffect::AdoptUntaggedWindows(cmds) => commands.extend(cmds.iter().cloned()),
+                SideEffect::RefreshAfterWindowAdd => {
+                    commands.push(tmux_formats::LIST_PANES_CMD.to_string());
+                    commands.push(tmux_formats::LIST_WINDOWS_CMD.to_string());
+                }
+                SideEffect::RefreshPanes { pane_ids } => {
+                    let queued = self.aggregator.queue_captures(pane_ids);
+                    commands.push(tmux_formats::LIST_PANES_CMD.to_string());
+                    commands.extend(queued.iter().map(|id| capture_command(id)));
+                }
+                SideEffect::ResumePane(id) => {
+                    commands.push(format!("refresh-client -A '{id}:continue'"));
+                }
+                SideEffect::EmitState { .. } => {
+                    if let Some(update) = self.aggregator.to_state_update() {
+                        self.updates.push(update);
+                    }
+                }
+                _ => {}
+            }
+        }
+        self.effects.extend(effects);
+        self.sent.extend(commands.iter().cloned());
+        commands
+    }
+}
+
+/// Compare `actual`, pretty-printed, with the golden file at `path`. With
+/// `TMUXY_UPDATE_GOLDEN` set the file is (re)written instead.
+pub fn assert_golden(path: impl AsRef<Path>, actual: &serde_json::Value) {
+    let path = path.as_ref();
+    let mut rendered = serde_json::to_string_pretty(actual).unwrap_or_default();
+    rendered.push('\n');
+    if std::env::var_os("TMUXY_UPDATE_GOLDEN").is_some() {
+        if let Some(dir) = path.parent() {
+            let _ = std::fs::create_dir_all(dir);
+        }
+        if let Err(e) = std::fs::write(path, &rendered) {
+            panic!("failed to write golden file {}: {e}", path.display());
+        }
+        return;
+    }
+    let expected = std::fs::read_to_string(path).unwrap_or_else(|e| {
+        panic!(
+            "failed to read golden file {} ({e}); run with TMUXY_UPDATE_GOLDEN=1 to create it",
+            path.display()
+        )
+    });
+    if expected != rendered {
+        panic!(
+            "{} does not match (rerun with TMUXY_UPDATE_GOLDEN=1 to accept):\n--- expected\n{expected}\n--- actual\n{rendered}",
+            path.display()
+        );
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used, clippy::expect_used)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn fake_tmux_answers_each_segment_in_its_own_block() {
+        let mut tmux = FakeTmux::default();
+        tmux.set_pane_content("%1", &["hello"]);
+        tmux.fail("kill-pane", "can't find pane");
+        let reply = tmux.reply(&capture_command("%1"));
+        assert_eq!(
+            reply,
+            [
+                "%begin 0 1 1",
+                "TMUXY_CAP_BEGIN 1",
+                "%end 0 1 1",
+                "%begin 0 2 1",
+                "hello",
+                "%end 0 2 1",
+                "%begin 0 3 1",
+                "TMUXY_CAP_END",
+                "%end 0 3 1",
+            ]
+        );
+        assert_eq!(
+            tmux.reply("kill-pane -t %9"),
+            ["%begin 0 4 1", "can't find pane", "%error 0 4 1"]
+        );
+    }
+
+    #[test]
+    fn transcript_directives_are_only_read_outside_blocks() {
+        let mut harness = Harness::new("t");
+        harness.auto_respond = false;
+        harness.play("# a comment\n\n%begin 1 1 0\n# kept\n%end 1 1 0\n!advance 10\n");
+        assert!(harness.sent().is_empty());
+    }
+}
@@ -0,0 +1,113 @@
+//! Typing text into a pane.
+//!
+//! Composed text — an IME commit, an emoji from the system picker, a mobile
+//! keyboard's autocorrect — arrives as a string, not as key events. Sent
+//! through the raw-command path it was at the mercy of everything that path
+//! does to a command line: tmux key-name parsing without `-l`, the ` \; `
+//! unescaping `RunCommand` applies, and a newline ending the control-mode
+//! command half-way. [`text_commands`] types it exactly: printable runs go
+//! out single-quoted under `send-keys -l`, split on character boundaries so
+//! a chunk never ends inside a multibyte character; line breaks become Enter
+//! and other control characters their raw byte.
+
+use crate::executor::tmux_quote;
+
+/// Longest literal run put in one `send-keys -l`, in bytes.
+const TEXT_CHUNK_BYTES: usize = 512;
+
+/// `send-keys -l` typing `text` as is.
+pub(crate) fn literal_command(pane_id: &str, text: &str) -> String {
+    format!(
+        "send-keys -t {} -l {}",
+        tmux_quote(pane_id),
+        tmux_quote(text)
+    )
+}
+
+/// `send-keys -H` writing `bytes` to the pane as if typed.
+pub(crate) fn hex_command(pane_id: &str, bytes: &[u8]) -> String {
+    let hex: Vec<String> = bytes.iter().map(|b| format!("{b:02x}")).collect();
+    format!("send-keys -t {} -H {}", tmux_quote(pane_id), hex.join(" "))
+}
+
+/// Build the control-mode commands typing `text` into `pane_id`.
+pub fn text_commands(pane_id: &str, text: &str) -> Vec<String> {
+    let mut cmds = Vec::new();
+    let mut run = String::new();
+    let mut after_cr = false;
+    for c in text.chars() {
+        let cr = std::mem::replace(&mut after_cr, c == '\r');
+        if !c.is_ascii_control() || c == '\t' {
+            if run.len() + c.len_utf8() > TEXT_CHUNK_BYTES {
+                cmds.push(literal_command(pane_id, &run));
+                run.clear();
+            }
+            run.push(c);
+            continue;
+        }
+        if !run.is_empty() {
+            cmds.push(literal_command(pane_id, &run));
+            run.clear();
+        }
+        match c {
+            // CRLF is one line break.
+            '\n' if cr => {}
+            '\r' | '\n' => cmds.push(format!("send-keys -t {} Enter", tmux_quote(pane_id))),
+            _ => cmds.push(hex_command(pane_id, &[c as u8])),
+        }
+    }
+    if !run.is_empty() {
+        cmds.push(literal_command(pane_id, &run));
+    }
+    cmds
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used, clippy::expect_used)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn composed_text_is_sent_literally_and_quoted() {
+        assert_eq!(
+            text_commands("%1", "日本語 🎉 it's \\; done"),
+            ["send-keys -t '%1' -l '日本語 🎉 it'\\''s \\; done'"]
+        );
+        assert!(text_commands("%1", "").is_empty());
+    }
+
+    #[test]
+    fn line_breaks_and_controls_are_keys() {
+        assert_eq!(
+            text_commands("%1", "a\r\nb\nc\x03"),
+            [
+                "send-keys -t '%1' -l 'a'",
+                "send-keys -t '%1' Enter",
+                "send-keys -t '%1' -l 'b'",
+                "send-keys -t '%1' Enter",
+                "send-keys -t '%1' -l 'c'",
+                "send-keys -t '%1' -H 03",
+            ]
+        );
+    }
+
+    #[test]
+    fn chunks_end_on_character_boundaries() {
+        // 3-byte characters don't divide the chunk size evenly.
+        let text = "語".repeat(200);
+        let cmds = text_commands("%1", &text);
+        assert_eq!(cmds.len(), 2);
+        let typed: String = cmds
+            .iter()
+            .map(|cmd| {
+                cmd.strip_prefix("send-keys -t '%1' -l '")
+                    .and_then(|rest| rest.strip_suffix('\''))
+                    .unwrap()
+            })
+            .collect();
+        assert_eq!(typed, text);
+        assert!(cmds
+            .iter()
+            .all(|cmd| cmd.len() <= TEXT_CHUNK_BYTES + "send-keys -t '%1' -l ''".len()));
+    }
+}
@@ -0,0 +1,574 @@
+//! Local usage statistics.
+//!
+//! The monitor samples each pane's foreground command and records every run
+//! of a program other than the shell — its name, pane, session and how long
+//! it ran — along with how long tmuxy watched each session, in a SQLite
+//! database under the config dir. Only process names are kept: no arguments,
+//! paths or output, and nothing leaves the machine. `tmuxy stats` and
+//! `GET /api/stats` summarise it. `TMUXY_USAGE_STATS=off` stops recording.
+
+use crate::session::config_dir;
+use crate::widget::SHELL_COMMANDS;
+use rusqlite::{params, Connection, OptionalExtension};
+use std::collections::HashMap;
+use std::fmt::Write as _;
+use std::path::{Path, PathBuf};
+
+/// Environment variable turning usage recording off (`off`/`0`/`false`/`no`).
+pub const USAGE_STATS_ENV: &str = "TMUXY_USAGE_STATS";
+
+/// Default location of the usage database.
+pub fn usage_db_path() -> PathBuf {
+    config_dir().join("usage.db")
+}
+
+/// Where monitors record usage: the default database, unless
+/// `TMUXY_USAGE_STATS` turns recording off.
+pub fn usage_db_from_env() -> Option<PathBuf> {
+    let off = std::env::var(USAGE_STATS_ENV).is_ok_and(|v| {
+        matches!(
+            v.trim().to_ascii_lowercase().as_str(),
+            "0" | "false" | "no" | "off"
+        )
+    });
+    (!off).then(usage_db_path)
+}
+
+const SCHEMA: &str = "
+CREATE TABLE IF NOT EXISTS command_runs (
+    id INTEGER PRIMARY KEY,
+    session TEXT NOT NULL,
+    pane_id TEXT NOT NULL,
+    command TEXT NOT NULL,
+    started_at INTEGER NOT NULL,
+    duration_ms INTEGER NOT NULL
+);
+CREATE INDEX IF NOT EXISTS command_runs_started_at ON command_runs (started_at);
+CREATE TABLE IF NOT EXISTS session_spans (
+    id INTEGER PRIMARY KEY,
+    session TEXT NOT NULL,
+    started_at INTEGER NOT NULL,
+    ended_at INTEGER NOT NULL
+);
+";
+
+/// A finished run of a command in a pane. Times are Unix milliseconds.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CommandRun {
+    pub pane_id: String,
+    pub command: String,
+    pub started_at: i64,
+    pub duration_ms: i64,
+}
+
+/// Turns successive samples of each pane's foreground command into finished
+/// runs. A run ends when the pane is back at a shell, runs something else,
+/// or is gone.
+#[derive(Debug, Default)]
+pub struct UsageTracker {
+    running: HashMap<String, (String, i64)>,
+}
+
+impl UsageTracker {
+    /// Take a sample of `(pane_id, command)` pairs at `now` (Unix ms) and
+    /// return the runs it finished.
+    pub fn observe(&mut self, panes: &[(String, String)], now: i64) -> Vec<CommandRun> {
+        let mut finished = Vec::new();
+        let mut running = HashMap::new();
+        for (pane_id, command) in panes {
+            let previous = self.running.remove(pane_id);
+            if let Some((prev_command, started_at)) = previous {
+                if &prev_command == command {
+                    running.insert(pane_id.clone(), (prev_command, started_at));
+                    continue;
+                }
+                finished.push(run(pane_id, prev_command, started_at, now));
+            }
+            if !command.is_empty() && !SHELL_COMMANDS.contains(&command.as_str()) {
+                running.insert(pane_id.clone(), (command.clone(), now));
+            }
+        }
+        finished.extend(
+            self.running
+                .drain()
+                .map(|(pane_id, (command, started_at))| run(&pane_id, command, started_at, now)),
+        );
+        self.running = running;
+        finished.sort_by(|a, b| a.pane_id.cmp(&b.pane_id));
+        finished
+    }
+
+    /// End every run in progress, e.g. when the monitor stops.
+    pub fn finish(&mut self, now: i64) -> Vec<CommandRun> {
+        self.observe(&[], now)
+    }
+}
+
+fn run(pane_id: &str, command: String, started_at: i64, now: i64) -> CommandRun {
+    CommandRun {
+        pane_id: pane_id.to_string(),
+        command,
+        started_at,
+        duration_ms: (now - started_at).max(0),
+    }
+}
+
+/// Unix milliseconds now.
+pub fn now_millis() -> i64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map_or(0, |d| d.as_millis() as i64)
+}
+
+/// What a monitor records for its session: finished runs, and one span of
+/// watching the session that grows with every sample.
+pub struct SessionRecorder {
+    store: UsageStore,
+    tracker: UsageTracker,
+    session: String,
+    span: i64,
+}
+
+impl SessionRecorder {
+    /// Open the database at `path` and start a span of watching `session`.
+    pub fn open(path: &Path, session: &str, now: i64) -> rusqlite::Result<Self> {
+        let store = UsageStore::open(path)?;
+        let span = store.begin_session(session, now)?;
+        Ok(Self {
+            store,
+            tracker: UsageTracker::default(),
+            session: session.to_string(),
+            span,
+        })
+    }
+
+    /// Record a sample of `(pane_id, command)` pairs taken at `now`.
+    pub fn sample(&mut self, panes: &[(String, String)], now: i64) -> rusqlite::Result<()> {
+        let runs = self.tracker.observe(panes, now);
+        self.store.record_runs(&self.session, &runs)?;
+        self.store.extend_session(self.span, now)
+    }
+
+    /// End the runs in progress and the span, when the monitor stops.
+    pub fn finish(&mut self, now: i64) -> rusqlite::Result<()> {
+        self.sample(&[], now)
+    }
+}
+
+/// How often a command ran, and for how long in total.
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CommandStat {
+    pub command: String,
+    pub runs: u64,
+    pub total_secs: u64,
+}
+
+/// Commands run in one pane.
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PaneStat {
+    pub session: String,
+    pub pane_id: String,
+    pub runs: u64,
+    pub total_secs: u64,
+}
+
+/// How long tmuxy watched a session, over how many connections.
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SessionStat {
+    pub session: String,
+    pub spans: u64,
+    pub total_secs: u64,
+}
+
+/// What `tmuxy stats` and `GET /api/stats` report.
+#[derive(Debug, Clone, Default, PartialEq, Eq, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UsageSummary {
+    /// Start of the period covered (Unix seconds); `None` for all time.
+    pub since: Option<i64>,
+    /// Oldest run recorded in the period (Unix seconds).
+    pub first_run_at: Option<i64>,
+    pub commands_run: u64,
+    pub top_commands: Vec<CommandStat>,
+    pub busiest_panes: Vec<PaneStat>,
+    pub sessions: Vec<SessionStat>,
+    /// Quick commands run again and again: candidates for a key binding,
+    /// alias or script.
+    pub automation_candidates: Vec<CommandStat>,
+}
+
+/// Runs before a command counts as an automation candidate.
+const CANDIDATE_MIN_RUNS: u64 = 5;
+
+/// Longest average run, in seconds, of an automation candidate.
+const CANDIDATE_MAX_AVG_SECS: u64 = 30;
+
+/// The usage database.
+pub struct UsageStore {
+    conn: Connection,
+}
+
+impl UsageStore {
+    /// Open (creating if needed) the database at `path`.
+    pub fn open(path: &Path) -> rusqlite::Result<Self> {
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir).ok();
+        }
+        Self::init(Connection::open(path)?)
+    }
+
+    /// Open the database at `path` only if it exists, for readers that
+    /// shouldn't create one.
+    pub fn open_existing(path: &Path) -> rusqlite::Result<Option<Self>> {
+        if !path.exists() {
+            return Ok(None);
+        }
+        Self::open(path).map(Some)
+    }
+
+    /// A throwaway in-memory database.
+    pub fn open_in_memory() -> rusqlite::Result<Self> {
+        Self::init(Connection::open_in_memory()?)
+    }
+
+    fn init(conn: Connection) -> rusqlite::Result<Self> {
+        // Several monitors (one per session) write to the same file.
+        conn.busy_timeout(std::time::Duration::from_secs(2))?;
+        conn.execute_batch(SCHEMA)?;
+        Ok(Self { conn })
+    }
+
+    /// Record finished runs in `session`.
+    pub fn record_runs(&mut self, session: &str, runs: &[CommandRun]) -> rusqlite::Result<()> {
+        if runs.is_empty() {
+            return Ok(());
+        }
+        let tx = self.conn.transaction()?;
+        {
+            let mut insert = tx.prepare_cached(
+                "INSERT INTO command_runs (session, pane_id, command, started_at, duration_ms)
+                 VALUES (?1, ?2, ?3, ?4, ?5)",
+            )?;
+            for run in runs {
+                insert.execute(params![
+                    session,
+                    run.pane_id,
+                    run.command,
+                    run.started_at,
+                    run.duration_ms
+                ])?;
+            }
+        }
+        tx.commit()
+    }
+
+    /// Start a span of watching `session` at `now` (Unix ms). Returns its id
+    /// for `extend_session`.
+    pub fn begin_session(&self, session: &str, now: i64) -> rusqlite::Result<i64> {
+        self.conn.execute(
+            "INSERT INTO session_spans (session, started_at, ended_at) VALUES (?1, ?2, ?2)",
+            params![session, now],
+        )?;
+        Ok(self.conn.last_insert_rowid())
+    }
+
+    /// Move the end of span `id` to `now` (Unix ms).
+    pub fn extend_session(&self, id: i64, now: i64) -> rusqlite::Result<()> {
+        self.conn.execute(
+            "UPDATE session_spans SET ended_at = ?2 WHERE id = ?1",
+            params![id, now],
+        )?;
+        Ok(())
+    }
+
+    /// Summarise what was recorded since `since` (Unix seconds; `None` for
+    /// all time), listing up to `limit` entries per table.
+    pub fn summary(&self, since: Option<i64>, limit: usize) -> rusqlite::Result<UsageSummary> {
+        let from_ms = since.map_or(i64::MIN, |s| s.saturating_mul(1000));
+        let limit = limit as i64;
+
+        let (commands_run, first_run_at) = self.conn.query_row(
+            "SELECT COUNT(*), MIN(started_at) FROM command_runs WHERE started_at >= ?1",
+            params![from_ms],
+            |row| Ok((row.get::<_, i64>(0)?, row.get::<_, Option<i64>>(1)?)),
+        )?;
+
+        let command_stats = |sql: &str, args: &[&dyn rusqlite::ToSql]| {
+            let mut stmt = self.conn.prepare(sql)?;
+            let rows = stmt.query_map(args, |row| {
+                Ok(CommandStat {
+                    command: row.get(0)?,
+                    runs: row.get::<_, i64>(1)? as u64,
+                    total_secs: (row.get::<_, i64>(2)? / 1000) as u64,
+                })
+            })?;
+            rows.collect::<rusqlite::Result<Vec<_>>>()
+        };
+
+        let top_commands = command_stats(
+            "SELECT command, COUNT(*) AS runs, SUM(duration_ms) FROM command_runs
+             WHERE started_at >= ?1 GROUP BY command
+             ORDER BY runs DESC, command LIMIT ?2",
+            &[&from_ms, &limit],
+        )?;
+        let automation_candidates = command_stats(
+            "SELECT command, COUNT(*) AS runs, SUM(duration_ms) FROM command_runs
+             WHERE started_at >= ?1 GROUP BY command
+             HAVING runs >= ?2 AND AVG(duration_ms) <= ?3
+             ORDER BY runs DESC, command LIMIT ?4",
+            &[
+                &from_ms,
+                &(CANDIDATE_MIN_RUNS as i64),
+                &(CANDIDATE_MAX_AVG_SECS as i64 * 1000),
+                &limit,
+            ],
+        )?;
+
+        let mut stmt = self.conn.prepare(
+            "SELECT session, pane_id, COUNT(*) AS runs, SUM(duration_ms) FROM command_runs
+             WHERE started_at >= ?1 GROUP BY session, pane_id
+             ORDER BY runs DESC, session, pane_id LIMIT ?2",
+        )?;
+        let busiest_panes = stmt
+            .query_map(params![from_ms, limit], |row| {
+                Ok(PaneStat {
+                    session: row.get(0)?,
+                    pane_id: row.get(1)?,
+                    runs: row.get::<_, i64>(2)? as u64,
+                    total_secs: (row.get::<_, i64>(3)? / 1000) as u64,
+                })
+            })?
+            .collect::<rusqlite::Result<Vec<_>>>()?;
+
+        // Spans that started before `since` count from `since`.
+        let mut stmt = self.conn.prepare(
+            "SELECT session, COUNT(*), SUM(ended_at - MAX(started_at, ?1)) AS total
+             FROM session_spans WHERE ended_at >= ?1 GROUP BY session
+             ORDER BY total DESC, session LIMIT ?2",
+        )?;
+        let sessions = stmt
+            .query_map(params![from_ms, limit], |row| {
+                Ok(SessionStat {
+                    session: row.get(0)?,
+                    spans: row.get::<_, i64>(1)? as u64,
+                    total_secs: (row.get::<_, i64>(2)?.max(0) / 1000) as u64,
+                })
+            })?
+            .collect::<rusqlite::Result<Vec<_>>>()?;
+
+        Ok(UsageSummary {
+            since,
+            first_run_at: first_run_at.map(|ms| ms / 1000),
+            commands_run: commands_run as u64,
+            top_commands,
+            busiest_panes,
+            sessions,
+            automation_candidates,
+        })
+    }
+
+    /// Whether anything has been recorded at all.
+    pub fn is_empty(&self) -> rusqlite::Result<bool> {
+        let any: Option<i64> = self
+            .conn
+            .query_row(
+                "SELECT 1 FROM command_runs UNION ALL SELECT 1 FROM session_spans LIMIT 1",
+                [],
+                |row| row.get(0),
+            )
+            .optional()?;
+        Ok(any.is_none())
+    }
+}
+
+/// Entries per list in a summary, unless asked otherwise.
+pub const SUMMARY_LIMIT: usize = 10;
+
+/// Summarise the database at `path` over the last `days` days (all time when
+/// `None`). A database not created yet summarises as empty.
+pub fn summarize(path: &Path, days: Option<u64>, limit: usize) -> rusqlite::Result<UsageSummary> {
+    let since = days.map(|days| now_millis() / 1000 - (days as i64).saturating_mul(24 * 60 * 60));
+    match UsageStore::open_existing(path)? {
+        Some(store) => store.summary(since, limit),
+        None => Ok(UsageSummary {
+            since,
+            ..UsageSummary::default()
+        }),
+    }
+}
+
+/// `3725` → `1h 2m`, `95` → `1m 35s`.
+fn format_duration(secs: u64) -> String {
+    let (h, m, s) = (secs / 3600, secs % 3600 / 60, secs % 60);
+    if h > 0 {
+        format!("{h}h {m}m")
+    } else if m > 0 {
+        format!("{m}m {s}s")
+    } else {
+        format!("{s}s")
+    }
+}
+
+impl UsageSummary {
+    /// The summary as text for the terminal.
+    pub fn render(&self) -> String {
+        let mut out = String::new();
+        let period = match self.since {
+            Some(_) => "in this period",
+            None => "so far",
+        };
+        let _ = writeln!(out, "{} commands run {}", self.commands_run, period);
+
+        if !self.top_commands.is_empty() {
+            let _ = writeln!(out, "\nMost-run commands");
+            for stat in &self.top_commands {
+                let _ = writeln!(
+                    out,
+                    "  {:<20} {:>6} runs  {:>9}",
+                    stat.command,
+                    stat.runs,
+                    format_duration(stat.total_secs)
+                );
+            }
+        }
+        if !self.busiest_panes.is_empty() {
+            let _ = writeln!(out, "\nBusiest panes");
+            for stat in &self.busiest_panes {
+                let pane = format!("{} {}", stat.session, stat.pane_id);
+                let _ = writeln!(
+                    out,
+                    "  {:<20} {:>6} runs  {:>9}",
+                    pane,
+                    stat.runs,
+                    format_duration(stat.total_secs)
+                );
+            }
+        }
+        if !self.sessions.is_empty() {
+            let _ = writeln!(out, "\nSessions");
+            for stat in &self.sessions {
+                let _ = writeln!(
+                    out,
+                    "  {:<20} {:>9}  over {} connection(s)",
+                    stat.session,
+                    format_duration(stat.total_secs),
+                    stat.spans
+                );
+            }
+        }
+        if !self.automation_candidates.is_empty() {
+            let _ = writeln!(
+                out,
+                "\nAutomation candidates (quick commands you run often)"
+            );
+            for stat in &self.automation_candidates {
+                let _ = writeln!(out, "  {:<20} {:>6} runs", stat.command, stat.runs);
+            }
+        }
+        out
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used, clippy::expect_used)]
+mod tests {
+    use super::*;
+
+    fn panes(pairs: &[(&str, &str)]) -> Vec<(String, String)> {
+        pairs
+            .iter()
+            .map(|(p, c)| (p.to_string(), c.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn tracker_ends_runs_at_the_shell_a_new_command_or_a_closed_pane() {
+        let mut tracker = UsageTracker::default();
+        assert!(tracker
+            .observe(&panes(&[("%0", "bash"), ("%1", "vim")]), 0)
+            .is_empty());
+        assert!(tracker
+            .observe(&panes(&[("%0", "cargo"), ("%1", "vim")]), 1_000)
+            .is_empty());
+
+        let done = tracker.observe(&panes(&[("%0", "bash"), ("%1", "less")]), 4_000);
+        assert_eq!(
+            done,
+            vec![
+                run("%0", "cargo".into(), 1_000, 4_000),
+                run("%1", "vim".into(), 0, 4_000),
+            ]
+        );
+
+        let done = tracker.observe(&panes(&[("%0", "bash")]), 5_000);
+        assert_eq!(done, vec![run("%1", "less".into(), 4_000, 5_000)]);
+        assert!(tracker.finish(6_000).is_empty());
+    }
+
+    #[test]
+    fn summary_ranks_commands_panes_and_sessions() {
+        let mut store = UsageStore::open_in_memory().unwrap();
+        assert!(store.is_empty().unwrap());
+        let mut runs = Vec::new();
+        for i in 0..6 {
+            runs.push(run("%1", "git".into(), i * 1_000, i * 1_000 + 500));
+        }
+        runs.push(run("%2", "vim".into(), 10_000, 610_000));
+        store.record_runs("work", &runs).unwrap();
+        let span = store.begin_session("work", 0).unwrap();
+        store.extend_session(span, 3_600_000).unwrap();
+        store.begin_session("scratch", 5_000).unwrap();
+
+        let summary = store.summary(None, 10).unwrap();
+        assert_eq!(summary.commands_run, 7);
+        assert_eq!(summary.first_run_at, Some(0));
+        assert_eq!(
+            summary.top_commands,
+            vec![
+                CommandStat {
+                    command: "git".into(),
+                    runs: 6,
+                    total_secs: 3
+                },
+                CommandStat {
+                    command: "vim".into(),
+                    runs: 1,
+                    total_secs: 600
+                },
+            ]
+        );
+        assert_eq!(summary.busiest_panes[0].pane_id, "%1");
+        assert_eq!(summary.sessions[0].session, "work");
+        assert_eq!(summary.sessions[0].total_secs, 3_600);
+        assert_eq!(summary.automation_candidates.len(), 1);
+        assert_eq!(summary.automation_candidates[0].command, "git");
+
+        let text = summary.render();
+        assert!(text.starts_with("7 commands run so far"));
+        assert!(text.contains("Automation candidates"));
+    }
+
+    #[test]
+    fn summary_since_clips_older_runs_and_spans() {
+        let mut store = UsageStore::open_in_memory().unwrap();
+        store
+            .record_runs(
+                "work",
+                &[
+                    run("%1", "old".into(), 0, 1_000),
+                    run("%1", "new".into(), 20_000, 21_000),
+                ],
+            )
+            .unwrap();
+        let span = store.begin_session("work", 0).unwrap();
+        store.extend_session(span, 30_000).unwrap();
+
+        let summary = store.summary(Some(10), 10).unwrap();
+        assert_eq!(summary.commands_run, 1);
+        assert_eq!(summary.top_commands[0].command, "new");
+        assert_eq!(summary.sessions[0].total_secs, 20);
+    }
+}
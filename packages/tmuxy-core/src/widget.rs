@@ -0,0 +1,267 @@
+//! Widget panes: one place for what a widget is and how it is created,
+//! updated and destroyed.
+//!
+//! A widget is a pane whose program draws for the frontend instead of a
+//! terminal (`tmuxy widget image`, `tmuxy widget markdown`). The pane carries
+//! its [`WidgetSpec`] in the `@tmuxy-widget` user option, which rides
+//! `list-panes` into [`TmuxPane::widget`](crate::TmuxPane::widget), so the
+//! frontend reads the type, source and options from state rather than
+//! scraping them out of the pane's output.
+//!
+//! Every widget launcher ends in the bundled `tmuxy-widget` script, which
+//! tags its own pane on start and clears the tag on exit. The CLI runs the
+//! launchers directly; the server and the desktop app go through
+//! [`WidgetManager`], which runs the same launchers and hands the spec over in
+//! `TMUXY_WIDGET` so a spec with options reaches the pane intact.
+//!
+//! A widget that has structured state to show sends it as JSON on its own
+//! escape sequence ([`WIDGET_DATA_OSC`](crate::control_mode::WIDGET_DATA_OSC)),
+//! which the monitor lifts out of the output and forwards to the frontend as
+//! widget data.
+//!
+//! Pane options die with the tmux server, so the monitor also remembers each
+//! session's widget panes on disk ([`write_widgets`]). When a session comes
+//! back with those panes sitting at a bare shell, which is how a restored
+//! session or a restarted tmux leaves them, the monitor restarts the widgets.
+
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+pub use tmuxy_protocol::WidgetSpec;
+
+/// Widget types shipped with tmuxy and the bundled launcher for each.
+pub const BUILTIN_WIDGETS: &[(&str, &str)] = &[
+    ("git", "tmuxy-widget-git"),
+    ("image", "tmuxy-widget-image"),
+    ("markdown", "tmuxy-widget-markdown"),
+    ("metric", "tmuxy-widget-metric"),
+    ("sysmon", "tmuxy-widget-sysmon"),
+    ("table", "tmuxy-widget-table"),
+    ("timer", "tmuxy-widget-timer"),
+];
+
+/// A widget pane as remembered across tmux restarts. It is located by window
+/// and pane index, which a restored session keeps, rather than by pane id,
+/// which it doesn't.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PlacedWidget {
+    pub window_index: u32,
+    pub pane_index: u32,
+    pub spec: WidgetSpec,
+}
+
+/// Commands a pane shows while sitting at a shell prompt. A remembered
+/// widget is only restarted into a pane running one of these, never over
+/// a program the user started there since.
+pub const SHELL_COMMANDS: &[&str] = &["bash", "zsh", "sh", "fish", "dash", "ksh", "tcsh", "nu"];
+
+/// Path to the file remembering `session`'s widget panes.
+#[cfg(feature = "native")]
+pub fn widgets_path(session: &str) -> std::path::PathBuf {
+    let name: String = session
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || c == '-' || c == '_' {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect();
+    crate::session::config_dir()
+        .join("widgets")
+        .join(format!("{name}.json"))
+}
+
+/// The widget panes remembered for `session`. A missing or unreadable file
+/// yields none: at worst a widget comes back as a shell.
+#[cfg(feature = "native")]
+pub fn read_widgets(session: &str) -> Vec<PlacedWidget> {
+    std::fs::read_to_string(widgets_path(session))
+        .ok()
+        .and_then(|text| serde_json::from_str(&text).ok())
+        .unwrap_or_default()
+}
+
+/// Overwrite the widget panes remembered for `session`.
+#[cfg(feature = "native")]
+pub fn write_widgets(session: &str, widgets: &[PlacedWidget]) -> std::io::Result<()> {
+    let path = widgets_path(session);
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir)?;
+    }
+    let body = serde_json::to_string_pretty(widgets).map_err(std::io::Error::other)?;
+    std::fs::write(&path, format!("{body}\n"))
+}
+
+/// Environment variable a launched widget reads its full spec from.
+#[cfg(feature = "native")]
+const SPEC_ENV: &str = "TMUXY_WIDGET";
+
+/// Registry of widget types and the tmux commands for their lifecycle.
+///
+/// The commands are meant for the control-mode connection, like the other
+/// command builders shared by the SSE server and the Tauri app.
+#[cfg(feature = "native")]
+#[derive(Debug, Clone)]
+pub struct WidgetManager {
+    launcher_dir: std::path::PathBuf,
+    launchers: BTreeMap<String, String>,
+}
+
+#[cfg(feature = "native")]
+impl WidgetManager {
+    /// A manager with no widget types, launching from `launcher_dir`.
+    pub fn new(launcher_dir: impl Into<std::path::PathBuf>) -> Self {
+        Self {
+            launcher_dir: launcher_dir.into(),
+            launchers: BTreeMap::new(),
+        }
+    }
+
+    /// The built-in widget types, launched from the bundled scripts under
+    /// `~/.config/tmuxy/bin/tmuxy/`.
+    pub fn bundled() -> Self {
+        let mut manager = Self::new(crate::session::bin_dir().join("tmuxy"));
+        for (kind, launcher) in BUILTIN_WIDGETS {
+            manager.register(*kind, *launcher);
+        }
+        manager
+    }
+
+    /// Register `kind`, launched by `launcher` (a file in the launcher dir).
+    /// Re-registering a kind replaces its launcher.
+    pub fn register(&mut self, kind: impl Into<String>, launcher: impl Into<String>) {
+        self.launchers.insert(kind.into(), launcher.into());
+    }
+
+    pub fn kinds(&self) -> impl Iterator<Item = &str> {
+        self.launchers.keys().map(String::as_str)
+    }
+
+    fn launcher(&self, kind: &str) -> crate::TmuxResult<std::path::PathBuf> {
+        self.launchers
+            .get(kind)
+            .map(|launcher| self.launcher_dir.join(launcher))
+            .ok_or_else(|| crate::TmuxError::other(format!("unknown widget type '{kind}'")))
+    }
+
+    /// `-e TMUXY_WIDGET=… 'launcher source'`: the tail of a command that
+    /// starts a pane running `spec`.
+    fn launch_args(&self, spec: &WidgetSpec) -> crate::TmuxResult<String> {
+        use crate::executor::tmux_quote;
+
+        let launcher = self.launcher(&spec.kind)?;
+        let mut program = tmux_quote(&launcher.to_string_lossy());
+        if !spec.source.is_empty() {
+            program.push(' ');
+            program.push_str(&tmux_quote(&spec.source));
+        }
+        Ok(format!(
+            "-e {} {}",
+            tmux_quote(&format!("{SPEC_ENV}={}", spec.encode())),
+            tmux_quote(&program),
+        ))
+    }
+
+    /// Split `pane_id` and run the widget in the new pane.
+    pub fn create_command(&self, pane_id: &str, spec: &WidgetSpec) -> crate::TmuxResult<String> {
+        Ok(format!(
+            "split-window -h -t {} {}",
+            crate::executor::tmux_quote(pane_id),
+            self.launch_args(spec)?,
+        ))
+    }
+
+    /// Split `pane_id` and run a git widget on the repository the pane is
+    /// in. The pane's directory is read with an external read-only query,
+    /// as `duplicate_pane_command` reads it.
+    pub fn create_git_command(&self, pane_id: &str) -> crate::TmuxResult<String> {
+        let cwd = crate::executor::execute_tmux_command(&[
+            "display-message",
+            "-p",
+            "-t",
+            pane_id,
+            "#{pane_current_path}",
+        ])?;
+        self.create_command(pane_id, &WidgetSpec::new("git", cwd.trim_end_matches('\n')))
+    }
+
+    /// Restart the widget in `pane_id`, replacing whatever runs there. The
+    /// pane is tagged up front rather than by the launcher, so a `list-panes`
+    /// sent right behind this already sees the widget.
+    pub fn restore_command(&self, pane_id: &str, spec: &WidgetSpec) -> crate::TmuxResult<String> {
+        let launch = self.launch_args(spec)?;
+        Ok(format!(
+            "{} ; respawn-pane -k -t {} {launch}",
+            self.update_command(pane_id, spec)?,
+            crate::executor::tmux_quote(pane_id),
+        ))
+    }
+
+    /// Replace a running widget's spec. The widget keeps its process; the
+    /// frontend picks the new source and options up from the pane.
+    pub fn update_command(&self, pane_id: &str, spec: &WidgetSpec) -> crate::TmuxResult<String> {
+        use crate::executor::tmux_quote;
+
+        self.launcher(&spec.kind)?;
+        Ok(format!(
+            "set-option -p -t {} {} {}",
+            tmux_quote(pane_id),
+            crate::constants::tmux_options::WIDGET,
+            tmux_quote(&spec.encode()),
+        ))
+    }
+
+    /// Stop the widget in `pane_id`, as Ctrl+C in the widget does: its
+    /// launcher clears the screen and leaves a shell in the pane.
+    pub fn destroy_command(&self, pane_id: &str) -> String {
+        let pane = crate::executor::tmux_quote(pane_id);
+        format!(
+            "set-option -pu -t {pane} {} ; send-keys -t {pane} C-c",
+            crate::constants::tmux_options::WIDGET,
+        )
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used, clippy::expect_used)]
+mod tests {
+    use super::*;
+
+    #[cfg(feature = "native")]
+    #[test]
+    fn manager_builds_lifecycle_commands_for_registered_types() {
+        let mut manager = WidgetManager::new("/bin/w");
+        manager.register("image", "tmuxy-widget-image");
+        let spec = WidgetSpec::new("image", "it's.png");
+
+        assert_eq!(
+            manager.create_command("%3", &spec).unwrap(),
+            "split-window -h -t '%3' -e 'TMUXY_WIDGET=image;source=it'\\''s.png' \
+             ''\\''/bin/w/tmuxy-widget-image'\\'' '\\''it'\\''\\'\\'''\\''s.png'\\'''"
+        );
+        assert_eq!(
+            manager.update_command("%3", &spec).unwrap(),
+            "set-option -p -t '%3' @tmuxy-widget 'image;source=it'\\''s.png'"
+        );
+        assert_eq!(
+            manager.destroy_command("%3"),
+            "set-option -pu -t '%3' @tmuxy-widget ; send-keys -t '%3' C-c"
+        );
+
+        assert_eq!(
+            manager
+                .restore_command("%3", &WidgetSpec::new("image", "a.png"))
+                .unwrap(),
+            "set-option -p -t '%3' @tmuxy-widget 'image;source=a.png' ; \
+             respawn-pane -k -t '%3' -e 'TMUXY_WIDGET=image;source=a.png' \
+             ''\\''/bin/w/tmuxy-widget-image'\\'' '\\''a.png'\\'''"
+        );
+
+        let chart = WidgetSpec::new("chart", "");
+        assert!(manager.create_command("%3", &chart).is_err());
+        assert!(manager.update_command("%3", &chart).is_err());
+        assert!(manager.restore_command("%3", &chart).is_err());
+        assert_eq!(manager.kinds().collect::<Vec<_>>(), ["image"]);
+    }
+}
@@ -0,0 +1,128 @@
+//! Property-based tests for the control-mode parser and octal decoder.
+//!
+//! The parser sits directly on the connection's byte stream, so a line it
+//! can't make sense of (a newer tmux's notification, a truncated escape, a
+//! program writing binary into a pane) must produce `None` or a best-effort
+//! event, never a panic in the monitor task. The same entry points are
+//! fuzzed by the cargo-fuzz targets in `packages/tmuxy-core/fuzz`.
+
+#![allow(clippy::unwrap_used, clippy::expect_used)]
+
+use proptest::prelude::*;
+use tmuxy_core::control_mode::{decode_octal_bytes, ControlModeEvent, Parser};
+
+/// Escape bytes the way tmux does for control mode: control characters and
+/// backslash become `\xxx`, everything else (including 0x80 and up) is
+/// passed through.
+fn tmux_escape(bytes: &[u8]) -> Vec<u8> {
+    let mut out = Vec::with_capacity(bytes.len());
+    for &b in bytes {
+        if b < 32 || b == b'\\' {
+            out.extend(format!("\\{b:03o}").bytes());
+        } else {
+            out.push(b);
+        }
+    }
+    out
+}
+
+/// Lines that start like real notifications, followed by arbitrary bytes.
+fn notification_line_strategy() -> impl Strategy<Value = Vec<u8>> {
+    let prefixes = vec![
+        "%output ",
+        "%output %1 ",
+        "%extended-output ",
+        "%extended-output %1 ",
+        "%extended-output %1 10 : ",
+        "%layout-change ",
+        "%window-renamed ",
+        "%window-pane-changed ",
+        "%session-changed ",
+        "%session-window-changed ",
+        "%subscription-changed ",
+        "%begin ",
+        "%end ",
+        "%error ",
+        "%exit",
+        "%",
+        "",
+    ];
+    (
+        prop::sample::select(prefixes),
+        prop::collection::vec(any::<u8>(), 0..48),
+    )
+        .prop_map(|(prefix, tail)| {
+            let mut line = prefix.as_bytes().to_vec();
+            line.extend(tail.into_iter().filter(|&b| b != b'\n'));
+            line
+        })
+}
+
+proptest! {
+    /// No sequence of lines makes the parser panic, whether they arrive as
+    /// raw bytes or as text.
+    #[test]
+    fn parse_never_panics(lines in prop::collection::vec(notification_line_strategy(), 1..24)) {
+        let mut bytes_parser = Parser::new();
+        let mut text_parser = Parser::new();
+        for line in &lines {
+            let _ = bytes_parser.parse_bytes(line);
+            let _ = text_parser.parse_line(&String::from_utf8_lossy(line));
+        }
+    }
+
+    /// Decoding tmux's escaping gives back the original bytes.
+    #[test]
+    fn octal_round_trips(bytes in prop::collection::vec(any::<u8>(), 0..64)) {
+        prop_assert_eq!(decode_octal_bytes(&tmux_escape(&bytes)), bytes);
+    }
+
+    /// An escape cut short at the end of a line is kept literally, after
+    /// the correctly decoded prefix.
+    #[test]
+    fn truncated_escape_is_kept(
+        bytes in prop::collection::vec(any::<u8>(), 0..32),
+        digits in "[0-7]{0,2}",
+    ) {
+        let mut line = tmux_escape(&bytes);
+        let tail = format!("\\{digits}");
+        line.extend(tail.bytes());
+        let mut expected = bytes;
+        expected.extend(tail.bytes());
+        prop_assert_eq!(decode_octal_bytes(&line), expected);
+    }
+
+    /// `%output` carries any byte string through unchanged, valid UTF-8 or
+    /// not.
+    #[test]
+    fn output_content_survives_parsing(bytes in prop::collection::vec(any::<u8>(), 0..64)) {
+        let mut line = b"%output %7 ".to_vec();
+        line.extend(tmux_escape(&bytes));
+        match Parser::new().parse_bytes(&line) {
+            Some(ControlModeEvent::Output { pane_id, content }) => {
+                prop_assert_eq!(pane_id, "%7");
+                prop_assert_eq!(content, bytes);
+            }
+            other => prop_assert!(false, "expected Output, got {:?}", other),
+        }
+    }
+
+    /// The same holds for `%extended-output`, with the age parsed from the
+    /// header.
+    #[test]
+    fn extended_output_content_survives_parsing(
+        age in any::<u32>(),
+        bytes in prop::collection::vec(any::<u8>(), 0..64),
+    ) {
+        let mut line = format!("%extended-output %7 {age} : ").into_bytes();
+        line.extend(tmux_escape(&bytes));
+        match Parser::new().parse_bytes(&line) {
+            Some(ControlModeEvent::ExtendedOutput { pane_id, age_ms, content }) => {
+                prop_assert_eq!(pane_id, "%7");
+                prop_assert_eq!(age_ms, u64::from(age));
+                prop_assert_eq!(content, bytes);
+            }
+            other => prop_assert!(false, "expected ExtendedOutput, got {:?}", other),
+        }
+    }
+}
@@ -3,6 +3,8 @@
 //! `StateAggregator::step` → `TmuxState`. Runs in native CI (no browser, no
 //! tokio) and exercises the same code the WASM binding reuses verbatim.
 
+#![allow(clippy::unwrap_used, clippy::expect_used)]
+
 use tmuxy_core::control_mode::{Parser, StateAggregator};
 use tmuxy_core::TmuxState;
 
@@ -27,8 +29,8 @@ const STREAM: &str = concat!(
     "%session-changed $0 m\n",
     "%window-add @0\n",
     "%begin 2 2 1\n",
-    "%0,0,0,0,40,24,0,0,1,zsh,,0,0,0,0,@0,,0,0,0,0,0,100\n",
-    "%1,1,41,0,39,24,0,0,0,zsh,,0,0,0,0,@0,,0,0,0,0,0,100\n",
+    "%0,0,0,0,40,24,0,0,1,zsh,,0,0,0,0,@0,,0,0,0,0,0,0,0,100,,,,\n",
+    "%1,1,41,0,39,24,0,0,0,zsh,,0,0,0,0,@0,,0,0,0,0,0,0,0,100,,,,\n",
     "%end 2 2 1\n",
     "%window-pane-changed @0 %0\n",
     "%layout-change @0 8205,80x24,0,0{40x24,0,0,0,39x24,41,0,1} ",
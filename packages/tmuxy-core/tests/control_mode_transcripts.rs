@@ -0,0 +1,159 @@
+//! Recorded control-mode transcripts played through the scripted fake tmux
+//! (`tmuxy_core::testing`): events in, commands out, and the exact
+//! `StateUpdate`s clients would receive, compared against golden files.
+//!
+//! Regenerate the goldens after an intended change with
+//! `TMUXY_UPDATE_GOLDEN=1 cargo test -p tmuxy-core --test control_mode_transcripts`
+//! and review the diff.
+
+#![allow(clippy::unwrap_used, clippy::expect_used)]
+
+use tmuxy_core::testing::{assert_golden, Harness};
+
+const GOLDEN_DIR: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/golden");
+
+/// `list-panes` rows for the two-pane session, split at `left` columns with
+/// the left pane's cursor on row `cursor_y`.
+fn two_pane_rows(left: u32, cursor_y: u32) -> [String; 2] {
+    let right = 80 - left - 1;
+    [
+        format!("%0,0,0,0,{left},24,2,{cursor_y},1,bash,,0,0,0,0,@0,,0,0,0,0,0,0,0,0,,,,"),
+        format!(
+            "%1,1,{},0,{right},24,2,0,0,bash,,0,0,0,0,@0,,0,0,0,0,0,0,0,0,,,,",
+            left + 1
+        ),
+    ]
+}
+
+/// A harness whose fake tmux knows the two-pane session.
+fn two_pane_harness() -> Harness {
+    let mut harness = Harness::new("m");
+    let rows = two_pane_rows(40, 0);
+    harness.tmux.respond("list-panes", &[&rows[0], &rows[1]]);
+    harness
+        .tmux
+        .respond("list-windows", &["@0,0,1,tab,,,,,,,,0,,,,,,bash"]);
+    harness.tmux.set_pane_content("%0", &["$ "]);
+    harness.tmux.set_pane_content("%1", &["$ "]);
+    harness
+}
+
+/// Play the two-pane transcripts against a fake tmux that knows the session.
+fn play_two_panes() -> Harness {
+    let mut harness = two_pane_harness();
+    harness.play(include_str!("fixtures/transcripts/two_panes.txt"));
+
+    let rows = two_pane_rows(45, 2);
+    harness.tmux.respond("list-panes", &[&rows[0], &rows[1]]);
+    harness
+        .tmux
+        .set_pane_content("%0", &["$ ls", "a.txt  b.txt", "$ "]);
+    harness.play(include_str!("fixtures/transcripts/two_panes_resize.txt"));
+    harness
+}
+
+#[test]
+fn two_panes_updates_match_golden() {
+    let harness = play_two_panes();
+    assert_golden(
+        format!("{GOLDEN_DIR}/two_panes_updates.json"),
+        &serde_json::to_value(harness.updates()).unwrap(),
+    );
+}
+
+#[test]
+fn two_panes_final_state() {
+    let mut harness = play_two_panes();
+
+    // Each pane sync captures the panes left to right.
+    let captures: Vec<&String> = harness
+        .sent()
+        .iter()
+        .filter(|command| command.contains("capture-pane"))
+        .collect();
+    assert!(!captures.is_empty(), "sent: {:?}", harness.sent());
+    for pair in captures.chunks(2) {
+        assert!(pair[0].contains("-t %0") && pair[1].contains("-t %1"));
+    }
+
+    let state = harness.state();
+    assert_eq!(state.active_pane_id.as_deref(), Some("%0"));
+    let widths: Vec<u32> = state.panes.iter().map(|pane| pane.width).collect();
+    assert_eq!(widths, [45, 34]);
+    assert_eq!(state.panes[1].x, 46);
+
+    let text: Vec<String> = state.panes[0]
+        .content
+        .iter()
+        .take(3)
+        .map(|row| row.iter().map(|cell| cell.char.as_str()).collect())
+        .collect();
+    assert_eq!(text, ["$ ls", "a.txt  b.txt", "$"]);
+}
+
+#[test]
+fn paused_panes_resume_unless_held() {
+    const ATTACH: &str = "%begin 1 1 0\n%end 1 1 0\n%session-changed $0 m\n%window-add @0";
+
+    let mut harness = two_pane_harness();
+    harness.feed(ATTACH);
+    harness.feed_line("%pause %0");
+    assert!(harness
+        .sent()
+        .iter()
+        .any(|command| command == "refresh-client -A '%0:continue'"));
+
+    let mut harness = two_pane_harness();
+    harness.aggregator().set_auto_resume(false);
+    harness.feed(ATTACH);
+    harness.feed_line("%pause %0");
+    assert!(!harness
+        .sent()
+        .iter()
+        .any(|command| command.contains(":continue")));
+    assert!(harness.state().panes[0].paused);
+
+    // Output missed while paused is recaptured once tmux continues.
+    let before = harness.sent().len();
+    harness.feed_line("%continue %0");
+    assert!(!harness.state().panes[0].paused);
+    assert!(harness.sent()[before..]
+        .iter()
+        .any(|command| command.contains("capture-pane -t %0")));
+}
+
+/// Text of `%0`'s top row in the last of `updates` that changed it.
+fn top_row_text(updates: &[tmuxy_core::StateUpdate]) -> Option<String> {
+    let updates = serde_json::to_value(updates).unwrap();
+    updates.as_array()?.iter().rev().find_map(|update| {
+        let row = update["delta"]["panes"]["%0"]["content"]["0"].as_array()?;
+        Some(row.iter().filter_map(|cell| cell["c"].as_str()).collect())
+    })
+}
+
+#[test]
+fn output_over_budget_is_marked_trimmed() {
+    let mut harness = two_pane_harness();
+    harness.aggregator().set_output_budget(Some(100));
+    harness.feed("%begin 1 1 0\n%end 1 1 0\n%session-changed $0 m\n%window-add @0");
+    harness.take_updates();
+
+    // 50 lines at 4 bytes each: over budget, and 26 more than %0 shows.
+    harness.feed_line(&format!("%output %0 {}", "ab\\015\\012".repeat(50)));
+    assert!(harness.aggregator().output_trimmed("%0"));
+    assert_eq!(
+        top_row_text(&harness.take_updates()).as_deref(),
+        Some("…output trimmed (26 lines skipped)…")
+    );
+    assert_eq!(
+        harness.aggregator().take_output_trims(),
+        [("%0".to_string(), 26)]
+    );
+
+    // Once output calms down, the next frame drops the marker.
+    harness.advance(std::time::Duration::from_secs(3));
+    harness.feed_line("%output %0 ok");
+    assert!(!harness.aggregator().output_trimmed("%0"));
+    assert!(harness.aggregator().take_output_trims().is_empty());
+    assert_eq!(top_row_text(&harness.take_updates()).as_deref(), Some("ab"));
+}
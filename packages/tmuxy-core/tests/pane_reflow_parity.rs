@@ -161,3 +161,49 @@ fn shrinking_a_full_pane_still_scrolls_off_the_top() {
     assert_eq!(rows[0], "d", "a full screen keeps its bottom, not its top");
     assert_eq!(rows[2], "f");
 }
+
+/// A width change rewraps soft-wrapped lines instead of truncating them, so a
+/// narrow/wide round trip (a divider dragged back and forth) loses nothing.
+#[test]
+fn narrowing_rewraps_long_lines_and_widening_joins_them_back() {
+    let mut parser = vt100::Parser::new(6, 20, REFLOW_SCROLLBACK_ROWS);
+    parser.process(b"$ echo\r\n0123456789abcdefghij-tail\r\n$ ");
+    let before = emulated(&parser);
+
+    parser.screen_mut().set_size(6, 10);
+    let narrow = emulated(&parser);
+    assert_eq!(
+        narrow[..5],
+        ["$ echo", "0123456789", "abcdefghij", "-tail", "$"],
+        "the long line must wrap onto new rows, not be cut at column 10"
+    );
+    assert_eq!(parser.screen().cursor_position(), (4, 2));
+
+    parser.screen_mut().set_size(6, 20);
+    assert_eq!(emulated(&parser), before);
+    assert_eq!(parser.screen().cursor_position(), (3, 2));
+}
+
+/// Narrowing a full screen pushes the extra wrapped rows into scrollback
+/// rather than off the bottom, keeping the cursor row where it was.
+#[test]
+fn narrowing_a_full_screen_scrolls_wrapped_rows_into_history() {
+    let mut parser = vt100::Parser::new(3, 8, REFLOW_SCROLLBACK_ROWS);
+    parser.process(b"abcdefgh12\r\nxy\r\n$ ");
+
+    parser.screen_mut().set_size(3, 4);
+    assert_eq!(emulated(&parser), ["12", "xy", "$"]);
+    parser.screen_mut().set_scrollback(2);
+    assert_eq!(emulated(&parser)[..2], ["abcd", "efgh"]);
+}
+
+/// A wide character that no longer fits at the end of a row moves whole to
+/// the next one instead of being split across the wrap.
+#[test]
+fn rewrap_never_splits_a_wide_character() {
+    let mut parser = vt100::Parser::new(3, 6, REFLOW_SCROLLBACK_ROWS);
+    parser.process("ab漢字".as_bytes());
+
+    parser.screen_mut().set_size(3, 5);
+    assert_eq!(emulated(&parser)[..2], ["ab漢", "字"]);
+}
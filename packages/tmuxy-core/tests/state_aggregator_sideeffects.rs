@@ -20,6 +20,13 @@ fn variant_names(effects: &[SideEffect]) -> Vec<&'static str> {
             SideEffect::ResumePane(_) => "ResumePane",
             SideEffect::StoreImages { .. } => "StoreImages",
             SideEffect::WriteClipboard { .. } => "WriteClipboard",
+            SideEffect::Bell { .. } => "Bell",
+            SideEffect::SpokenText { .. } => "SpokenText",
+            SideEffect::WidgetData { .. } => "WidgetData",
+            SideEffect::Notify { .. } => "Notify",
+            SideEffect::CommandOutput { .. } => "CommandOutput",
+            SideEffect::PaneExited(_) => "PaneExited",
+            SideEffect::RunStatusJobs(_) => "RunStatusJobs",
         })
         .collect()
 }
@@ -0,0 +1,141 @@
+//! Differential conformance between the pane emulator backends.
+//!
+//! Every byte stream below is fed to both the default vt100 backend and the
+//! optional alacritty backend; the extracted grids must agree cell for cell
+//! (character, width and style). A divergence here means switching
+//! `TMUXY_TERMINAL_BACKEND` would change what users see for ordinary output.
+//!
+//! The second group pins the sequences only alacritty gets right, so the
+//! reason for carrying the extra backend stays visible.
+//!
+//! Run with `cargo test -p tmuxy-core --features alacritty`.
+#![cfg(feature = "alacritty")]
+
+use tmuxy_core::control_mode::TerminalBackendKind;
+use tmuxy_core::PaneContent;
+
+fn render(kind: TerminalBackendKind, input: &str, rows: u16, cols: u16) -> PaneContent {
+    let mut term = kind.create(rows, cols, 0);
+    term.process(input.as_bytes());
+    term.cells(None)
+}
+
+fn assert_backends_agree(name: &str, input: &str) {
+    let vt100 = render(TerminalBackendKind::Vt100, input, 6, 24);
+    let alacritty = render(TerminalBackendKind::Alacritty, input, 6, 24);
+    assert_eq!(
+        vt100, alacritty,
+        "{name}: backends rendered different grids"
+    );
+}
+
+fn text(content: &PaneContent, row: usize) -> String {
+    content[row]
+        .iter()
+        .map(|c| c.char.as_str())
+        .collect::<String>()
+        .trim_end()
+        .to_string()
+}
+
+#[test]
+fn plain_text_and_line_wrapping() {
+    assert_backends_agree("text", "hello\r\nworld");
+    assert_backends_agree("wrap", "abcdefghijklmnopqrstuvwxyz0123");
+    assert_backends_agree("tabs", "a\tb\tc");
+}
+
+#[test]
+fn cursor_movement_and_erase() {
+    assert_backends_agree("cup", "\x1b[3;5Hx\x1b[1;1Hy");
+    assert_backends_agree("relative", "abc\x1b[2Dz\x1b[1Bq\x1b[3Cr");
+    assert_backends_agree("el", "abcdef\x1b[3G\x1b[K");
+    assert_backends_agree("ed", "line1\r\nline2\r\nline3\x1b[2;3H\x1b[J");
+    assert_backends_agree("ech", "abcdef\x1b[2G\x1b[3X");
+    assert_backends_agree("dch/ich", "abcdef\x1b[2G\x1b[2P\x1b[1@");
+}
+
+#[test]
+fn scrolling_and_scroll_regions() {
+    assert_backends_agree("scroll", "1\r\n2\r\n3\r\n4\r\n5\r\n6\r\n7\r\n8");
+    assert_backends_agree("region", "top\x1b[2;4r\x1b[4;1Ha\r\nb\r\nc\x1b[r");
+    assert_backends_agree("il/dl", "a\r\nb\r\nc\x1b[2;1H\x1b[L\x1b[3;1H\x1b[M");
+}
+
+#[test]
+fn sgr_colors_and_attributes() {
+    assert_backends_agree("indexed", "\x1b[31mred\x1b[42mbg\x1b[0m");
+    assert_backends_agree("bright", "\x1b[91mx\x1b[105my\x1b[0m");
+    assert_backends_agree("256", "\x1b[38;5;208mo\x1b[48;5;17mb\x1b[0m");
+    assert_backends_agree("rgb", "\x1b[38;2;10;20;30mt\x1b[0m");
+    assert_backends_agree(
+        "attrs",
+        "\x1b[1mb\x1b[0m\x1b[2md\x1b[0m\x1b[3mi\x1b[0m\x1b[4mu\x1b[0m\x1b[7mr\x1b[0m\x1b[9ms",
+    );
+    assert_backends_agree("curly", "\x1b[4:3mc\x1b[58;5;1mu\x1b[0m");
+}
+
+#[test]
+fn wide_characters() {
+    assert_backends_agree("cjk", "a漢字b");
+}
+
+#[test]
+fn modes_match() {
+    for kind in [TerminalBackendKind::Vt100, TerminalBackendKind::Alacritty] {
+        let mut term = kind.create(4, 10, 0);
+        term.process(b"\x1b[?1049h\x1b[?1000h\x1b[?25l\x1b[2;4H");
+        assert!(term.alternate_screen(), "{kind:?}");
+        assert!(term.mouse_tracking(), "{kind:?}");
+        assert!(term.hide_cursor(), "{kind:?}");
+        assert_eq!(term.cursor_position(), (1, 3), "{kind:?}");
+    }
+}
+
+#[test]
+fn resize_keeps_top_anchored_content() {
+    for kind in [TerminalBackendKind::Vt100, TerminalBackendKind::Alacritty] {
+        let mut term = kind.create(4, 10, 16);
+        term.process(b"one\r\ntwo");
+        term.set_size(6, 12);
+        let cells = term.cells(None);
+        assert_eq!(cells.len(), 6, "{kind:?}");
+        assert_eq!(text(&cells, 0), "one", "{kind:?}");
+        assert_eq!(text(&cells, 1), "two", "{kind:?}");
+    }
+}
+
+#[test]
+fn alacritty_honours_insert_mode() {
+    // IRM (CSI 4 h): vt100 overwrites, alacritty shifts the line right.
+    let input = "abcd\x1b[2G\x1b[4hXY\x1b[4l";
+    let alacritty = render(TerminalBackendKind::Alacritty, input, 1, 10);
+    assert_eq!(text(&alacritty, 0), "aXYbcd");
+    let vt100 = render(TerminalBackendKind::Vt100, input, 1, 10);
+    assert_eq!(text(&vt100, 0), "aXYd");
+}
+
+#[test]
+fn alacritty_honours_screen_alignment_test() {
+    // DECALN (ESC # 8) fills the screen with `E`; vt100 ignores it.
+    let alacritty = render(TerminalBackendKind::Alacritty, "\x1b#8", 2, 4);
+    assert_eq!(text(&alacritty, 0), "EEEE");
+    assert_eq!(text(&alacritty, 1), "EEEE");
+    let vt100 = render(TerminalBackendKind::Vt100, "\x1b#8", 2, 4);
+    assert_eq!(text(&vt100, 0), "");
+}
+
+#[test]
+fn scrollback_matches() {
+    let input: String = (0..10).map(|i| format!("row {i}\r\n")).collect();
+    let history = |kind: TerminalBackendKind| {
+        let mut term = kind.create(4, 12, 32);
+        term.process(input.as_bytes());
+        term.scrollback(5)
+    };
+    let vt100 = history(TerminalBackendKind::Vt100);
+    assert_eq!(vt100, history(TerminalBackendKind::Alacritty));
+    assert_eq!(vt100.len(), 5);
+    assert_eq!(text(&vt100, 0), "row 2");
+    assert_eq!(text(&vt100, 4), "row 6");
+}
@@ -0,0 +1,376 @@
+//! The argument types [`ClientCommand`](crate::ClientCommand)s carry. What a
+//! server does with them (encoding a key for the pane's keyboard protocol,
+//! building the tmux command for a copy-mode action) lives in tmuxy-core,
+//! next to the code that runs it.
+
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+
+use crate::OptionScope;
+
+/// One key press from a client, as the browser reported it.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[serde(rename_all = "camelCase", default)]
+pub struct KeyEvent {
+    /// `KeyboardEvent.key`: the character typed, or a key name (`ArrowUp`).
+    pub key: String,
+    /// `KeyboardEvent.code`: the physical key (`KeyA`, `Digit1`), which
+    /// gives the unshifted key of a shifted chord.
+    pub code: String,
+    pub ctrl: bool,
+    pub alt: bool,
+    pub shift: bool,
+    pub meta: bool,
+    /// Auto-repeat of a held key.
+    pub repeat: bool,
+}
+
+/// One raw mouse event, in 0-indexed pane-relative cell coordinates.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum MouseEvent {
+    Press {
+        button: u8,
+        x: u32,
+        y: u32,
+    },
+    Release {
+        button: u8,
+        x: u32,
+        y: u32,
+    },
+    Drag {
+        button: u8,
+        x: u32,
+        y: u32,
+    },
+    /// Positive `lines` scrolls down, negative scrolls up.
+    Wheel {
+        lines: i32,
+        x: u32,
+        y: u32,
+    },
+}
+
+/// A copy-mode command. Wire form is `{ "name": "<kebab-case>" }`, with an
+/// `"arg"` for the actions that take one (search text, jump target).
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[serde(tag = "name", content = "arg", rename_all = "kebab-case")]
+pub enum CopyModeAction {
+    /// Enter copy mode (`copy-mode`), a no-op if already in it.
+    Enter,
+    Cancel,
+    CursorUp,
+    CursorDown,
+    CursorLeft,
+    CursorRight,
+    StartOfLine,
+    EndOfLine,
+    NextWord,
+    NextWordEnd,
+    PreviousWord,
+    TopLine,
+    MiddleLine,
+    BottomLine,
+    HalfpageUp,
+    HalfpageDown,
+    PageUp,
+    PageDown,
+    ScrollUp,
+    ScrollDown,
+    HistoryTop,
+    HistoryBottom,
+    BeginSelection,
+    SelectLine,
+    RectangleToggle,
+    ClearSelection,
+    CopySelection,
+    CopySelectionAndCancel,
+    SearchForward(String),
+    SearchBackward(String),
+    SearchAgain,
+    SearchReverse,
+    JumpForward(String),
+    JumpBackward(String),
+}
+
+/// A copy-mode selection in capture-pane line numbers: negative rows are
+/// history, 0 is the first visible row. Columns are inclusive.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[serde(rename_all = "camelCase")]
+pub struct TextSelection {
+    pub start_row: i64,
+    pub start_col: usize,
+    pub end_row: i64,
+    pub end_col: usize,
+    /// Whole rows, like copy mode's line selection.
+    #[serde(default)]
+    pub line_mode: bool,
+}
+
+impl TextSelection {
+    /// The same selection with its start before its end, whichever end the
+    /// user dragged from.
+    pub fn normalized(self) -> Self {
+        if (self.start_row, self.start_col) <= (self.end_row, self.end_col) {
+            return self;
+        }
+        TextSelection {
+            start_row: self.end_row,
+            start_col: self.end_col,
+            end_row: self.start_row,
+            end_col: self.start_col,
+            line_mode: self.line_mode,
+        }
+    }
+}
+
+/// What to search for.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[serde(rename_all = "camelCase")]
+pub struct SearchQuery {
+    pub query: String,
+    /// Match case exactly; by default the search ignores case.
+    #[serde(default)]
+    pub case_sensitive: bool,
+    /// Treat `query` as a regular expression rather than literal text.
+    #[serde(default)]
+    pub regex: bool,
+}
+
+/// Image format of an export.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[serde(rename_all = "lowercase")]
+pub enum ExportFormat {
+    Png,
+    Svg,
+}
+
+impl ExportFormat {
+    pub fn mime(self) -> &'static str {
+        match self {
+            ExportFormat::Png => "image/png",
+            ExportFormat::Svg => "image/svg+xml",
+        }
+    }
+
+    pub fn extension(self) -> &'static str {
+        match self {
+            ExportFormat::Png => "png",
+            ExportFormat::Svg => "svg",
+        }
+    }
+}
+
+/// Orientation of a split, as tmux spells it: `horizontal` (`split-window -h`)
+/// puts the new pane to the right, `vertical` (`-v`) below.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[serde(rename_all = "lowercase")]
+pub enum SplitDirection {
+    Horizontal,
+    Vertical,
+}
+
+/// Options for a new float; the wire form mirrors `tmuxy pane float`'s flags.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[serde(rename_all = "camelCase", default)]
+pub struct FloatSpec {
+    /// Window to float over; the session's active window when unset.
+    pub parent: Option<String>,
+    pub width: Option<u32>,
+    pub height: Option<u32>,
+    /// Position of the top-left corner; centered when unset.
+    pub x: Option<u32>,
+    pub y: Option<u32>,
+    /// Dock to an edge (`left`, `right`, `top`, `bottom`) as a drawer.
+    pub drawer: Option<String>,
+    /// Backdrop behind the float: `dim`, `blur` or `none`.
+    pub bg: Option<String>,
+    pub hide_header: bool,
+    /// Run this instead of a shell.
+    pub command: Option<String>,
+    pub snap: Option<u32>,
+}
+
+/// Per-session do-not-disturb setting.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[serde(rename_all = "lowercase")]
+pub enum DndMode {
+    /// Follow the configured schedule; off when there is none.
+    #[default]
+    Auto,
+    /// Always on.
+    On,
+    /// Always off, even inside scheduled hours.
+    Off,
+}
+
+impl DndMode {
+    /// Parse the `@tmuxy-dnd` option value. Empty (unset) is `Auto`.
+    pub fn parse(s: &str) -> Option<Self> {
+        match s {
+            "" | "auto" => Some(Self::Auto),
+            "on" => Some(Self::On),
+            "off" => Some(Self::Off),
+            _ => None,
+        }
+    }
+}
+
+/// Runtime overrides for the emit scheduler and the periodic sync, for
+/// debugging high-CPU or laggy sessions without a restart. `None` keeps the
+/// current value.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct MonitorTuning {
+    #[serde(default)]
+    pub throttle_interval_ms: Option<u64>,
+    #[serde(default)]
+    pub throttle_threshold: Option<u32>,
+    #[serde(default)]
+    pub rate_window_ms: Option<u64>,
+    #[serde(default)]
+    pub output_debounce_ms: Option<u64>,
+    #[serde(default)]
+    pub max_latency_ms: Option<u64>,
+    #[serde(default)]
+    pub heartbeat_interval_ms: Option<u64>,
+    #[serde(default)]
+    pub sync_fields: Option<Vec<SyncField>>,
+}
+
+/// A field the periodic sync can fetch for every pane.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[serde(rename_all = "kebab-case")]
+pub enum SyncField {
+    /// `#{pane_current_path}`
+    Cwd,
+    /// `#{pane_pid}`
+    Pid,
+}
+
+impl SyncField {
+    pub fn parse(name: &str) -> Option<Self> {
+        match name.trim() {
+            "cwd" => Some(Self::Cwd),
+            "pid" => Some(Self::Pid),
+            _ => None,
+        }
+    }
+}
+
+/// The file formats a template can be written in.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[serde(rename_all = "lowercase")]
+pub enum TemplateFormat {
+    #[default]
+    Yaml,
+    Toml,
+}
+
+impl TemplateFormat {
+    /// The format a file's extension names.
+    pub fn from_path(path: &Path) -> Option<Self> {
+        match path.extension()?.to_str()? {
+            "yml" | "yaml" => Some(TemplateFormat::Yaml),
+            "toml" => Some(TemplateFormat::Toml),
+            _ => None,
+        }
+    }
+}
+
+/// A page of rows from a table file, as the `table_query` command takes it.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct TableQuery {
+    /// Absolute path of the file.
+    pub path: String,
+    /// First row of the page, after sorting.
+    #[serde(default)]
+    pub offset: usize,
+    /// Rows in the page, capped by the server (`table::MAX_PAGE_ROWS` in
+    /// tmuxy-core).
+    #[serde(default = "default_limit")]
+    pub limit: usize,
+    #[serde(default)]
+    pub sort: Option<TableSort>,
+}
+
+fn default_limit() -> usize {
+    100
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct TableSort {
+    /// Zero-based column index.
+    pub column: usize,
+    #[serde(default)]
+    pub descending: bool,
+}
+
+/// A container runtime with an `exec` subcommand.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[serde(rename_all = "lowercase")]
+pub enum ContainerRuntime {
+    Docker,
+    Podman,
+    Kubectl,
+}
+
+impl ContainerRuntime {
+    pub const ALL: [ContainerRuntime; 3] = [
+        ContainerRuntime::Docker,
+        ContainerRuntime::Podman,
+        ContainerRuntime::Kubectl,
+    ];
+
+    pub fn binary(self) -> &'static str {
+        match self {
+            ContainerRuntime::Docker => "docker",
+            ContainerRuntime::Podman => "podman",
+            ContainerRuntime::Kubectl => "kubectl",
+        }
+    }
+}
+
+/// A shell to open in a container.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct ContainerExec {
+    pub runtime: ContainerRuntime,
+    /// Container id or name (the pod, for kubectl)
+    pub container: String,
+    /// The pod's namespace (kubectl only)
+    #[serde(default)]
+    pub namespace: Option<String>,
+    /// Program to run; `sh` when not given
+    #[serde(default)]
+    pub shell: Option<String>,
+}
+
+/// An option, as the frontend names it.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct OptionRef {
+    pub scope: OptionScope,
+    /// Session, window (`@3`) or pane (`%3`) to read or set it on. Unset
+    /// means the session the client is attached to, or its current window
+    /// or pane. Ignored for server options.
+    #[serde(default)]
+    pub target: Option<String>,
+    pub name: String,
+}
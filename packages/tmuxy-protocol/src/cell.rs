@@ -0,0 +1,153 @@
+//! Styled terminal cells, the unit of pane content and the status line.
+
+use serde::{Deserialize, Serialize};
+
+// ============================================
+// Structured Cell Types (for eliminating double ANSI parsing)
+// ============================================
+
+/// Color representation for terminal cells
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[serde(untagged)]
+pub enum CellColor {
+    /// Indexed color (0-255)
+    Indexed(u8),
+    /// RGB color
+    Rgb { r: u8, g: u8, b: u8 },
+}
+
+/// Cell style attributes (only present if cell has non-default styling)
+#[derive(Debug, Clone, Serialize, Deserialize, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct CellStyle {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub fg: Option<CellColor>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub bg: Option<CellColor>,
+    #[serde(skip_serializing_if = "is_false")]
+    #[serde(default)]
+    pub bold: bool,
+    /// SGR 2: faint/dim text. Apps like Claude Code use this for autosuggestions.
+    #[serde(skip_serializing_if = "is_false")]
+    #[serde(default)]
+    pub dim: bool,
+    #[serde(skip_serializing_if = "is_false")]
+    #[serde(default)]
+    pub italic: bool,
+    /// Set for every underline shape, so a renderer that ignores
+    /// `underline_style` still draws a plain underline.
+    #[serde(skip_serializing_if = "is_false")]
+    #[serde(default)]
+    pub underline: bool,
+    /// SGR 4:2..4:5 / SGR 21: underline shape other than a single line.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub underline_style: Option<UnderlineStyle>,
+    /// SGR 58: underline color (unset means the foreground color).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub underline_color: Option<CellColor>,
+    #[serde(skip_serializing_if = "is_false")]
+    #[serde(default)]
+    pub strikethrough: bool,
+    #[serde(skip_serializing_if = "is_false")]
+    #[serde(default)]
+    pub blink: bool,
+    #[serde(skip_serializing_if = "is_false")]
+    #[serde(default)]
+    pub inverse: bool,
+    /// OSC 8 hyperlink URL (if cell is part of a hyperlink)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub url: Option<String>,
+}
+
+/// Underline shapes beyond the plain single line. Curly is what editors and
+/// LSP clients use for diagnostics.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[serde(rename_all = "lowercase")]
+pub enum UnderlineStyle {
+    Double,
+    Curly,
+    Dotted,
+    Dashed,
+}
+
+pub(crate) fn is_false(b: &bool) -> bool {
+    !*b
+}
+
+impl CellStyle {
+    pub fn is_empty(&self) -> bool {
+        self.fg.is_none()
+            && self.bg.is_none()
+            && !self.bold
+            && !self.dim
+            && !self.italic
+            && !self.underline
+            && self.underline_style.is_none()
+            && self.underline_color.is_none()
+            && !self.strikethrough
+            && !self.blink
+            && !self.inverse
+            && self.url.is_none()
+    }
+}
+
+/// A single terminal cell with character and optional styling
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct TerminalCell {
+    /// The character(s) in this cell (usually single char, but can be multi-byte)
+    #[serde(rename = "c")]
+    pub char: String,
+    /// Style attributes (only present if cell has styling)
+    #[serde(rename = "s", skip_serializing_if = "Option::is_none")]
+    pub style: Option<CellStyle>,
+    /// Display width in columns: 2 for the first cell of a double-width
+    /// character (CJK, emoji), 0 for the blank continuation cell after it,
+    /// 1 otherwise (only present if not 1)
+    #[serde(
+        rename = "w",
+        default = "single_width",
+        skip_serializing_if = "is_single_width"
+    )]
+    pub width: u8,
+}
+
+fn single_width() -> u8 {
+    1
+}
+
+fn is_single_width(width: &u8) -> bool {
+    *width == 1
+}
+
+impl TerminalCell {
+    pub fn new(char: String) -> Self {
+        Self {
+            char,
+            style: None,
+            width: 1,
+        }
+    }
+
+    pub fn with_style(char: String, style: CellStyle) -> Self {
+        let style = if style.is_empty() { None } else { Some(style) };
+        Self {
+            char,
+            style,
+            width: 1,
+        }
+    }
+
+    pub fn with_width(mut self, width: u8) -> Self {
+        self.width = width;
+        self
+    }
+}
+
+/// A line of terminal cells
+pub type TerminalLine = Vec<TerminalCell>;
+
+/// Pane content as structured cells (pre-parsed from ANSI)
+pub type PaneContent = Vec<TerminalLine>;
@@ -0,0 +1,134 @@
+//! Terminal colours: default foreground, background and the 256-colour
+//! palette, as a theme or as the overrides a pane's applications set.
+
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+
+/// A set of colour overrides. Every colour is `#rrggbb`; anything left out
+/// falls through to the layer below (pane → server theme → CSS theme).
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct TerminalColors {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub foreground: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub background: Option<String>,
+    /// Palette entries by index. Keyed by the index in decimal, the shape a
+    /// JSON object (and serde-wasm-bindgen) can carry.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub palette: BTreeMap<String, String>,
+}
+
+impl TerminalColors {
+    pub fn is_empty(&self) -> bool {
+        self.foreground.is_none() && self.background.is_none() && self.palette.is_empty()
+    }
+
+    /// Parse a theme spec: comma-separated `fg=`, `bg=` and `<index>=`
+    /// entries, e.g. `fg=#c0c0c0,bg=#1e1e1e,1=#cc6666`. Colours take any
+    /// form [`parse_color`] accepts. `None` if any entry is malformed.
+    pub fn parse(spec: &str) -> Option<Self> {
+        let mut colors = Self::default();
+        for entry in spec.split(',').map(str::trim).filter(|e| !e.is_empty()) {
+            let (key, value) = entry.split_once('=')?;
+            let color = parse_color(value.trim())?;
+            match key.trim() {
+                "fg" => colors.foreground = Some(color),
+                "bg" => colors.background = Some(color),
+                index => {
+                    let index: u8 = index.parse().ok()?;
+                    colors.palette.insert(index.to_string(), color);
+                }
+            }
+        }
+        Some(colors)
+    }
+
+    /// The same colours with every entry normalized to `#rrggbb`; entries
+    /// that don't parse (or palette keys that aren't an index) are dropped.
+    pub fn normalized(self) -> Self {
+        Self {
+            foreground: self.foreground.as_deref().and_then(parse_color),
+            background: self.background.as_deref().and_then(parse_color),
+            palette: self
+                .palette
+                .iter()
+                .filter_map(|(index, color)| {
+                    let index: u8 = index.parse().ok()?;
+                    Some((index.to_string(), parse_color(color)?))
+                })
+                .collect(),
+        }
+    }
+}
+
+/// Normalize a colour to `#rrggbb`. Accepts `#rgb`, `#rrggbb` and
+/// `rgb:r/g/b` with one to four hex digits per component, as xterm does.
+pub fn parse_color(spec: &str) -> Option<String> {
+    let components: Vec<&str> = if let Some(hex) = spec.strip_prefix('#') {
+        if !hex.is_ascii() || (hex.len() != 3 && hex.len() != 6) {
+            return None;
+        }
+        let width = hex.len() / 3;
+        (0..3).map(|i| &hex[i * width..(i + 1) * width]).collect()
+    } else {
+        spec.strip_prefix("rgb:")?.split('/').collect()
+    };
+    if components.len() != 3 {
+        return None;
+    }
+    let mut out = String::from("#");
+    for component in components {
+        if component.is_empty()
+            || component.len() > 4
+            || !component.bytes().all(|b| b.is_ascii_hexdigit())
+        {
+            return None;
+        }
+        let value = u32::from_str_radix(component, 16).ok()?;
+        let max = (1u32 << (4 * component.len())) - 1;
+        out.push_str(&format!("{:02x}", (value * 255 + max / 2) / max));
+    }
+    Some(out)
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used, clippy::expect_used)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn colours_normalize_from_xterm_forms() {
+        assert_eq!(parse_color("#1E1e1e").as_deref(), Some("#1e1e1e"));
+        assert_eq!(parse_color("#f80").as_deref(), Some("#ff8800"));
+        assert_eq!(
+            parse_color("rgb:ffff/8080/0000").as_deref(),
+            Some("#ff8000")
+        );
+        assert_eq!(parse_color("rgb:f/8/0").as_deref(), Some("#ff8800"));
+        for bad in [
+            "",
+            "?",
+            "#aé",
+            "#12345",
+            "rgb:+f/0/0",
+            "rgb:ff/ff",
+            "rgb:fffff/0/0",
+            "red",
+        ] {
+            assert_eq!(parse_color(bad), None, "{bad:?}");
+        }
+    }
+
+    #[test]
+    fn theme_spec_parses_or_is_rejected_whole() {
+        let theme = TerminalColors::parse("fg=#c0c0c0, bg=rgb:00/00/00 ,15=#fff").unwrap();
+        assert_eq!(theme.foreground.as_deref(), Some("#c0c0c0"));
+        assert_eq!(theme.background.as_deref(), Some("#000000"));
+        assert_eq!(theme.palette["15"], "#ffffff");
+        assert_eq!(TerminalColors::parse("fg=#c0c0c0,256=#000000"), None);
+        assert_eq!(TerminalColors::parse("fg"), None);
+        assert_eq!(TerminalColors::parse(""), Some(TerminalColors::default()));
+    }
+}
@@ -0,0 +1,1453 @@
+//! Typed wire schema for client → server commands.
+//!
+//! `ClientCommand` is the canonical Rust mirror of the JSON payload the
+//! frontend POSTs to `/commands`. The old code parsed each branch by
+//! reaching into `serde_json::Value` (`args.get("paneId").and_then(...)`),
+//! which made every typo silent and every signature change a runtime hunt
+//! through the SSE handler.
+//!
+//! The wire shape is preserved exactly:
+//!
+//! ```json
+//! { "cmd": "send_keys_to_tmux", "args": { "keys": "ls Enter" } }
+//! ```
+//!
+//! `#[serde(tag = "cmd", content = "args", rename_all = "snake_case")]`
+//! matches the frontend's existing format. Field names that the TS adapter
+//! sends in camelCase (`paneId`, `eventType`) are remapped explicitly with
+//! `#[serde(rename = "...")]`.
+//!
+//! Adding a new command becomes a single-place change: add a variant here,
+//! match it in tmuxy-core's `dispatch.rs`. The compiler enforces the rest.
+
+use serde::Deserialize;
+use serde_json::Value;
+
+use crate::{
+    ContainerExec, CopyModeAction, DndMode, ExportFormat, FloatSpec, KeyEvent, MonitorTuning,
+    MouseEvent, OptionRef, SearchQuery, SplitDirection, TableQuery, TemplateFormat, TerminalColors,
+    TextSelection, WidgetSpec,
+};
+
+/// All client → server commands the frontend actually sends. The wire JSON
+/// looks like `{ "cmd": "...", "args": { ... } }`. Variants with no fields
+/// require no `args` key; the TS adapter still sends an empty `args` object for
+/// them, which [`ClientCommand::decode`] strips before deserializing (serde's
+/// adjacently-tagged rules reject a `{}` map for a unit variant on their own).
+#[derive(Debug, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[serde(tag = "cmd", content = "args", rename_all = "snake_case")]
+pub enum ClientCommand {
+    GetInitialState {
+        #[serde(default)]
+        cols: Option<u32>,
+        #[serde(default)]
+        rows: Option<u32>,
+    },
+    SetClientSize {
+        cols: u32,
+        rows: u32,
+    },
+    RunTmuxCommand {
+        command: String,
+        /// Wait for the command to run and return its output (the error, if
+        /// it failed) instead of `null`.
+        #[serde(default)]
+        output: bool,
+    },
+    /// Pace this connection's events to `fps` frames per second; 0 removes
+    /// the budget.
+    SetFrameBudget {
+        fps: u32,
+    },
+    /// Raw mouse events for one pane; the monitor decides how each reaches
+    /// the pane (SGR report, arrow keys, copy-mode scroll).
+    SendMouseEvents {
+        #[serde(rename = "paneId")]
+        pane_id: String,
+        events: Vec<MouseEvent>,
+    },
+    /// One raw key press for a pane whose application asked for an extended
+    /// keyboard protocol; the monitor encodes it for that protocol.
+    SendKey {
+        #[serde(rename = "paneId")]
+        pane_id: String,
+        key: KeyEvent,
+    },
+    /// One key press resolved on the server against the user's key tables:
+    /// the prefix, a bound command, or a key for the pane. Replies with
+    /// whether this connection is now in prefix mode.
+    KeyInput {
+        #[serde(rename = "paneId")]
+        pane_id: String,
+        key: KeyEvent,
+    },
+    /// The `tmuxy`, `root` and `prefix` key tables as `key_input` resolves
+    /// them, for a help overlay.
+    GetEffectiveBindings,
+    /// Composed text (IME, emoji, mobile input) to type into a pane as is.
+    SendText {
+        #[serde(rename = "paneId")]
+        pane_id: String,
+        text: String,
+    },
+    /// Type command `id` from the pane's command history (see
+    /// `get_command_history`) back in and run it. Refused while the pane's
+    /// last command is still running.
+    RerunCommand {
+        #[serde(rename = "paneId")]
+        pane_id: String,
+        id: u64,
+    },
+    /// Run one copy-mode command on a pane, `count` times.
+    CopyModeAction {
+        #[serde(rename = "paneId")]
+        pane_id: String,
+        action: CopyModeAction,
+        #[serde(default)]
+        count: Option<u32>,
+    },
+    /// Scroll a pane's copy-mode view to `offset` lines above the live bottom.
+    ScrollTo {
+        #[serde(rename = "paneId")]
+        pane_id: String,
+        offset: u32,
+    },
+    /// Split `pane_id` into a new pane with the same cwd and environment,
+    /// optionally re-running its foreground command.
+    DuplicatePane {
+        #[serde(rename = "paneId")]
+        pane_id: String,
+        #[serde(default)]
+        rerun: bool,
+    },
+    /// Open a tab whose pane starts in `cwd` running `command`; either left
+    /// out falls back to the current pane's directory and the session's
+    /// default command. With `sandbox`, the command runs inside that
+    /// `sandbox-exec` profile and the pane is tagged with it.
+    NewWindowWithCommand {
+        #[serde(default)]
+        cwd: Option<String>,
+        #[serde(default)]
+        command: Option<String>,
+        #[serde(default)]
+        sandbox: Option<String>,
+    },
+    /// Split `pane_id`, starting the new pane as `new_window_with_command`
+    /// does.
+    SplitWithCommand {
+        #[serde(rename = "paneId")]
+        pane_id: String,
+        direction: SplitDirection,
+        #[serde(default)]
+        cwd: Option<String>,
+        #[serde(default)]
+        command: Option<String>,
+        #[serde(default)]
+        sandbox: Option<String>,
+    },
+    /// Drag a pane border from one window-relative cell to another.
+    DragResize {
+        #[serde(rename = "windowId")]
+        window_id: String,
+        #[serde(rename = "startX")]
+        start_x: u32,
+        #[serde(rename = "startY")]
+        start_y: u32,
+        #[serde(rename = "endX")]
+        end_x: u32,
+        #[serde(rename = "endY")]
+        end_y: u32,
+    },
+    /// Start dragging `pane_id` to rearrange it (see `drop_pane_at`).
+    BeginPaneDrag {
+        #[serde(rename = "paneId")]
+        pane_id: String,
+    },
+    /// The dragged pane is over a window-relative cell; the server answers
+    /// with `drag-target` events as the would-be drop changes.
+    DragPaneOver {
+        x: u32,
+        y: u32,
+    },
+    /// Drop the dragged pane on a window-relative cell.
+    DropPaneAt {
+        x: u32,
+        y: u32,
+    },
+    /// Set the session's do-not-disturb mode: `on`, `off`, or `auto` to
+    /// follow the server's quiet hours.
+    SetDoNotDisturb {
+        mode: DndMode,
+    },
+    /// Replace the session's default terminal colours. Not `set_theme`, which
+    /// picks the CSS theme.
+    SetTerminalTheme {
+        theme: TerminalColors,
+    },
+    /// Report whether this client's window has focus. Panes that enabled
+    /// focus reporting get `CSI I` / `CSI O` as the session gains its first
+    /// focused client or loses its last.
+    SetFocus {
+        focused: bool,
+    },
+    /// Type the keyring secret `name` into a pane. Only the name crosses the
+    /// wire; the value is looked up and typed by the backend.
+    InjectSecret {
+        #[serde(rename = "paneId")]
+        pane_id: String,
+        name: String,
+    },
+    /// Let a pane's output flow again after flow control paused it or
+    /// `discard_pane_output` turned it off.
+    ResumePane {
+        #[serde(rename = "paneId")]
+        pane_id: String,
+    },
+    /// Stop a pane's output reaching clients (and, with no other client
+    /// reading it, stop tmux reading the pane) until `resume_pane`.
+    DiscardPaneOutput {
+        #[serde(rename = "paneId")]
+        pane_id: String,
+    },
+    /// Re-point the session's `SSH_AUTH_SOCK` at a live agent.
+    FixSshAgent,
+    /// Retune the session's emit scheduler (throttle, debounce, latency
+    /// bound) and periodic sync (heartbeat period, extra pane fields);
+    /// answers with the settings now in effect.
+    SetMonitorTuning {
+        #[serde(flatten)]
+        tuning: MonitorTuning,
+    },
+    /// Replace the server's log filter (a level such as `debug`, or
+    /// `RUST_LOG` directives); with no `level`, only report the current one.
+    SetLogLevel {
+        #[serde(default)]
+        level: Option<String>,
+    },
+    /// Split `pane_id` and run `widget` in the new pane.
+    CreateWidget {
+        #[serde(rename = "paneId")]
+        pane_id: String,
+        widget: WidgetSpec,
+    },
+    /// Split `pane_id` and run a git widget on the repository it is in.
+    CreateGitWidget {
+        #[serde(rename = "paneId")]
+        pane_id: String,
+    },
+    /// Build a new session from a session template's text; returns the
+    /// session's name, `session` if given, else the one the template names.
+    CreateFromTemplate {
+        template: String,
+        #[serde(default)]
+        format: TemplateFormat,
+        #[serde(default)]
+        session: Option<String>,
+    },
+    /// A page of rows from a CSV/TSV file, optionally sorted by a column.
+    TableQuery {
+        query: TableQuery,
+    },
+    /// Replace the spec of the widget running in `pane_id`.
+    UpdateWidget {
+        #[serde(rename = "paneId")]
+        pane_id: String,
+        widget: WidgetSpec,
+    },
+    /// Stop the widget in `pane_id`, leaving a shell in the pane.
+    DestroyWidget {
+        #[serde(rename = "paneId")]
+        pane_id: String,
+    },
+    /// Running containers across docker, podman and kubectl.
+    ListContainers,
+    /// Split `pane_id` (or, with `newWindow`, open a tab) running a shell
+    /// inside a container.
+    OpenContainerShell {
+        #[serde(rename = "paneId")]
+        pane_id: String,
+        #[serde(flatten)]
+        exec: ContainerExec,
+        #[serde(default, rename = "newWindow")]
+        new_window: bool,
+    },
+    /// Geometry splitting a pane would produce, for a live preview overlay.
+    /// Nothing is split; `percent` is the new pane's share.
+    PreviewSplit {
+        #[serde(rename = "paneId")]
+        pane_id: String,
+        direction: SplitDirection,
+        #[serde(default = "default_split_percent")]
+        percent: u32,
+    },
+    /// Panes idle for at least `days` days, longest-idle first.
+    GetStalePanes {
+        #[serde(default = "default_stale_days")]
+        days: u32,
+    },
+    /// What recently closed panes showed on the way out, most recent first.
+    GetExitSummaries,
+    /// Tab windows, most recently visited first, and the window an Alt-Tab
+    /// cycle in progress is on.
+    GetWindowMru,
+    /// Alt-Tab: step through the MRU order while the modifier is held
+    /// (`reverse` for Shift), then `release` to settle on the window.
+    SwitchLastWindowCycle {
+        #[serde(default)]
+        reverse: bool,
+        #[serde(default)]
+        release: bool,
+    },
+    /// Open a float over a window (the active one by default).
+    FloatCreate {
+        #[serde(default)]
+        spec: FloatSpec,
+    },
+    /// Hide a float, or show a hidden one on top of the others.
+    FloatToggle {
+        #[serde(rename = "paneId")]
+        pane_id: String,
+    },
+    /// Move a float's top-left corner to cell `(x, y)`, rounded to `snap`.
+    FloatMove {
+        #[serde(rename = "paneId")]
+        pane_id: String,
+        x: u32,
+        y: u32,
+        #[serde(default)]
+        snap: Option<u32>,
+    },
+    /// Resize a float to `width` x `height` cells, rounded to `snap`.
+    FloatResize {
+        #[serde(rename = "paneId")]
+        pane_id: String,
+        width: u32,
+        height: u32,
+        #[serde(default)]
+        snap: Option<u32>,
+    },
+    FloatClose {
+        #[serde(rename = "paneId")]
+        pane_id: String,
+    },
+    /// Turn a pane into a group with a new tab in its place.
+    GroupCreate {
+        #[serde(rename = "paneId")]
+        pane_id: String,
+    },
+    /// Open a new tab in the group `paneId` belongs to.
+    GroupAdd {
+        #[serde(rename = "paneId")]
+        pane_id: String,
+    },
+    /// Show a group member in its group's slot.
+    GroupSwitch {
+        #[serde(rename = "paneId")]
+        pane_id: String,
+    },
+    /// Show the tab at `index` of the group `paneId` belongs to.
+    GroupSwitchTab {
+        #[serde(rename = "paneId")]
+        pane_id: String,
+        index: usize,
+    },
+    GroupClose {
+        #[serde(rename = "paneId")]
+        pane_id: String,
+    },
+    /// Move a group member to `index` in its group's tab order.
+    GroupReorder {
+        #[serde(rename = "paneId")]
+        pane_id: String,
+        index: usize,
+    },
+    /// Recent history held by the pane's emulator — no tmux round-trip, but
+    /// bounded by the server's scrollback setting.
+    GetRecentScrollback {
+        #[serde(rename = "paneId")]
+        pane_id: String,
+        #[serde(default = "default_recent_scrollback_lines")]
+        lines: usize,
+    },
+    /// The commands a pane's shell marked with OSC 133 shell integration,
+    /// oldest first: text, exit status, duration, and the prompt and output
+    /// lines (same line numbers as capture-pane).
+    GetCommandHistory {
+        #[serde(rename = "paneId")]
+        pane_id: String,
+    },
+    GetScrollbackCells {
+        #[serde(rename = "paneId")]
+        pane_id: String,
+        #[serde(default = "default_scrollback_start")]
+        start: i64,
+        #[serde(default = "default_scrollback_end")]
+        end: i64,
+    },
+    /// Render a pane as a PNG or SVG for download: the visible area, or the
+    /// `start..=end` range when given (same line numbers as capture-pane).
+    ExportPane {
+        #[serde(rename = "paneId")]
+        pane_id: String,
+        format: ExportFormat,
+        #[serde(default)]
+        start: Option<i64>,
+        #[serde(default)]
+        end: Option<i64>,
+    },
+    /// A pane as plain text and styled HTML: the visible area, the
+    /// `start..=end` range, or a copy-mode selection.
+    GetPaneText {
+        #[serde(rename = "paneId")]
+        pane_id: String,
+        #[serde(default)]
+        start: Option<i64>,
+        #[serde(default)]
+        end: Option<i64>,
+        #[serde(default)]
+        selection: Option<TextSelection>,
+    },
+    /// Search a pane's text. Answers with the visible area's matches; the
+    /// history's follow as `search-results` events. An empty query matches
+    /// nothing and stops the pane's search in progress.
+    PaneSearch {
+        #[serde(rename = "paneId")]
+        pane_id: String,
+        #[serde(flatten)]
+        search: SearchQuery,
+    },
+    /// Read a tmux option; unset options read as "".
+    GetOption {
+        #[serde(flatten)]
+        option: OptionRef,
+    },
+    /// Set a tmux option, or unset it when `value` is absent.
+    SetOption {
+        #[serde(flatten)]
+        option: OptionRef,
+        #[serde(default)]
+        value: Option<String>,
+    },
+    /// Report the option's changes as `option-changed` events.
+    WatchOption {
+        #[serde(flatten)]
+        option: OptionRef,
+    },
+    /// Create a session in this session's group (the same windows, its own
+    /// current window), named `name` or `<session>-<n>`.
+    CreateGroupedSession {
+        #[serde(default)]
+        name: Option<String>,
+    },
+    GetThemeSettings,
+    SetTheme {
+        name: String,
+        #[serde(default)]
+        mode: Option<String>,
+    },
+    GetThemesList,
+    SetThemeMode {
+        mode: String,
+    },
+}
+
+impl ClientCommand {
+    /// Decode a `/commands` request body into a [`ClientCommand`].
+    ///
+    /// The TS adapter always sends `{ "cmd": ..., "args": ... }`, defaulting
+    /// `args` to an empty object `{}` even for commands that take no
+    /// arguments (`get_theme_settings`, `get_themes_list`, `ping`, …). serde's
+    /// adjacently-tagged representation rejects an empty map where a unit
+    /// variant is expected ("invalid type: map, expected unit variant"), which
+    /// broke those commands on the wire.
+    ///
+    /// We honor the documented contract — unit variants accept an empty `args`
+    /// object — by stripping an empty `args` map and retrying. Payloads that
+    /// already parse (struct variants whose fields are all defaulted still
+    /// accept `{}`) succeed on the first attempt and never reach the retry,
+    /// so this only rescues the unit-variant case.
+    ///
+    /// Pane and window ids are checked to be `%N` and `@N`: the handlers put
+    /// them into control-mode command lines, where anything else (a `;`, say)
+    /// could run a second command.
+    pub fn decode(body: &[u8]) -> Result<Self, serde_json::Error> {
+        let cmd = Self::parse(body)?;
+        if let Some(pane_id) = cmd.pane_id() {
+            if !is_id(pane_id, '%') {
+                return Err(serde::de::Error::custom(format!(
+                    "invalid pane id '{pane_id}'"
+                )));
+            }
+        }
+        if let Self::DragResize { window_id, .. } = &cmd {
+            if !is_id(window_id, '@') {
+                return Err(serde::de::Error::custom(format!(
+                    "invalid window id '{window_id}'"
+                )));
+            }
+        }
+        if let Self::NewWindowWithCommand {
+            sandbox: Some(profile),
+            ..
+        }
+        | Self::SplitWithCommand {
+            sandbox: Some(profile),
+            ..
+        } = &cmd
+        {
+            if !is_sandbox_profile(profile) {
+                return Err(serde::de::Error::custom(format!(
+                    "invalid sandbox profile '{profile}'"
+                )));
+            }
+        }
+        Ok(cmd)
+    }
+
+    fn parse(body: &[u8]) -> Result<Self, serde_json::Error> {
+        match serde_json::from_slice(body) {
+            Ok(cmd) => Ok(cmd),
+            Err(first_err) => {
+                let mut value: Value = serde_json::from_slice(body)?;
+                let stripped = value
+                    .as_object_mut()
+                    .and_then(|obj| match obj.get("args") {
+                        Some(Value::Object(map)) if map.is_empty() => obj.remove("args"),
+                        _ => None,
+                    })
+                    .is_some();
+                if stripped {
+                    serde_json::from_value(value)
+                } else {
+                    Err(first_err)
+                }
+            }
+        }
+    }
+
+    /// The command's wire name, as sent in `cmd`.
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::GetInitialState { .. } => "get_initial_state",
+            Self::SetClientSize { .. } => "set_client_size",
+            Self::RunTmuxCommand { .. } => "run_tmux_command",
+            Self::SetFrameBudget { .. } => "set_frame_budget",
+            Self::SendMouseEvents { .. } => "send_mouse_events",
+            Self::SendKey { .. } => "send_key",
+            Self::KeyInput { .. } => "key_input",
+            Self::GetEffectiveBindings => "get_effective_bindings",
+            Self::SendText { .. } => "send_text",
+            Self::RerunCommand { .. } => "rerun_command",
+            Self::CopyModeAction { .. } => "copy_mode_action",
+            Self::ScrollTo { .. } => "scroll_to",
+            Self::DuplicatePane { .. } => "duplicate_pane",
+            Self::NewWindowWithCommand { .. } => "new_window_with_command",
+            Self::SplitWithCommand { .. } => "split_with_command",
+            Self::DragResize { .. } => "drag_resize",
+            Self::BeginPaneDrag { .. } => "begin_pane_drag",
+            Self::DragPaneOver { .. } => "drag_pane_over",
+            Self::DropPaneAt { .. } => "drop_pane_at",
+            Self::SetDoNotDisturb { .. } => "set_do_not_disturb",
+            Self::SetTerminalTheme { .. } => "set_terminal_theme",
+            Self::SetFocus { .. } => "set_focus",
+            Self::InjectSecret { .. } => "inject_secret",
+            Self::ResumePane { .. } => "resume_pane",
+            Self::DiscardPaneOutput { .. } => "discard_pane_output",
+            Self::FixSshAgent => "fix_ssh_agent",
+            Self::SetMonitorTuning { .. } => "set_monitor_tuning",
+            Self::SetLogLevel { .. } => "set_log_level",
+            Self::CreateWidget { .. } => "create_widget",
+            Self::CreateGitWidget { .. } => "create_git_widget",
+            Self::CreateFromTemplate { .. } => "create_from_template",
+            Self::TableQuery { .. } => "table_query",
+            Self::UpdateWidget { .. } => "update_widget",
+            Self::DestroyWidget { .. } => "destroy_widget",
+            Self::ListContainers => "list_containers",
+            Self::OpenContainerShell { .. } => "open_container_shell",
+            Self::PreviewSplit { .. } => "preview_split",
+            Self::GetStalePanes { .. } => "get_stale_panes",
+            Self::GetExitSummaries => "get_exit_summaries",
+            Self::GetWindowMru => "get_window_mru",
+            Self::SwitchLastWindowCycle { .. } => "switch_last_window_cycle",
+            Self::FloatCreate { .. } => "float_create",
+            Self::FloatToggle { .. } => "float_toggle",
+            Self::FloatMove { .. } => "float_move",
+            Self::FloatResize { .. } => "float_resize",
+            Self::FloatClose { .. } => "float_close",
+            Self::GroupCreate { .. } => "group_create",
+            Self::GroupAdd { .. } => "group_add",
+            Self::GroupSwitch { .. } => "group_switch",
+            Self::GroupSwitchTab { .. } => "group_switch_tab",
+            Self::GroupClose { .. } => "group_close",
+            Self::GroupReorder { .. } => "group_reorder",
+            Self::GetRecentScrollback { .. } => "get_recent_scrollback",
+            Self::GetCommandHistory { .. } => "get_command_history",
+            Self::GetScrollbackCells { .. } => "get_scrollback_cells",
+            Self::ExportPane { .. } => "export_pane",
+            Self::GetPaneText { .. } => "get_pane_text",
+            Self::PaneSearch { .. } => "pane_search",
+            Self::GetOption { .. } => "get_option",
+            Self::SetOption { .. } => "set_option",
+            Self::WatchOption { .. } => "watch_option",
+            Self::CreateGroupedSession { .. } => "create_grouped_session",
+            Self::GetThemeSettings => "get_theme_settings",
+            Self::SetTheme { .. } => "set_theme",
+            Self::GetThemesList => "get_themes_list",
+            Self::SetThemeMode { .. } => "set_theme_mode",
+        }
+    }
+
+    /// The pane the command acts on, when it names one.
+    pub fn pane_id(&self) -> Option<&str> {
+        match self {
+            Self::SendMouseEvents { pane_id, .. }
+            | Self::SendKey { pane_id, .. }
+            | Self::KeyInput { pane_id, .. }
+            | Self::SendText { pane_id, .. }
+            | Self::RerunCommand { pane_id, .. }
+            | Self::CopyModeAction { pane_id, .. }
+            | Self::ScrollTo { pane_id, .. }
+            | Self::DuplicatePane { pane_id, .. }
+            | Self::SplitWithCommand { pane_id, .. }
+            | Self::BeginPaneDrag { pane_id, .. }
+            | Self::InjectSecret { pane_id, .. }
+            | Self::ResumePane { pane_id, .. }
+            | Self::DiscardPaneOutput { pane_id, .. }
+            | Self::CreateWidget { pane_id, .. }
+            | Self::CreateGitWidget { pane_id, .. }
+            | Self::UpdateWidget { pane_id, .. }
+            | Self::DestroyWidget { pane_id, .. }
+            | Self::OpenContainerShell { pane_id, .. }
+            | Self::PreviewSplit { pane_id, .. }
+            | Self::FloatToggle { pane_id, .. }
+            | Self::FloatMove { pane_id, .. }
+            | Self::FloatResize { pane_id, .. }
+            | Self::FloatClose { pane_id, .. }
+            | Self::GroupCreate { pane_id, .. }
+            | Self::GroupAdd { pane_id, .. }
+            | Self::GroupSwitch { pane_id, .. }
+            | Self::GroupSwitchTab { pane_id, .. }
+            | Self::GroupClose { pane_id, .. }
+            | Self::GroupReorder { pane_id, .. }
+            | Self::GetRecentScrollback { pane_id, .. }
+            | Self::GetCommandHistory { pane_id }
+            | Self::GetScrollbackCells { pane_id, .. }
+            | Self::ExportPane { pane_id, .. }
+            | Self::GetPaneText { pane_id, .. }
+            | Self::PaneSearch { pane_id, .. } => Some(pane_id),
+            _ => None,
+        }
+    }
+}
+
+/// Whether `id` is `sigil` followed by digits, as tmux pane (`%3`) and
+/// window (`@3`) ids are.
+fn is_id(id: &str, sigil: char) -> bool {
+    id.strip_prefix(sigil)
+        .is_some_and(|n| !n.is_empty() && n.bytes().all(|b| b.is_ascii_digit()))
+}
+
+/// Whether `profile` is a `sandbox-exec` profile name (`bwrap`,
+/// `docker:alpine`): the characters `float-create --sandbox` accepts, so it
+/// can go into the `@tmuxy-sandbox` tag unquoted.
+fn is_sandbox_profile(profile: &str) -> bool {
+    !profile.is_empty()
+        && profile
+            .bytes()
+            .all(|b| b.is_ascii_alphanumeric() || b"._:/@-".contains(&b))
+}
+
+fn default_stale_days() -> u32 {
+    7
+}
+
+fn default_split_percent() -> u32 {
+    50
+}
+
+fn default_recent_scrollback_lines() -> usize {
+    200
+}
+
+fn default_scrollback_start() -> i64 {
+    -200
+}
+
+fn default_scrollback_end() -> i64 {
+    -1
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used, clippy::expect_used)]
+mod tests {
+    use super::*;
+    use crate::{OptionScope, SyncField};
+    use serde_json::json;
+
+    fn parse(v: serde_json::Value) -> ClientCommand {
+        serde_json::from_value(v).expect("should parse")
+    }
+
+    #[test]
+    fn unit_variant_accepts_empty_args_object() {
+        // The TS adapter sends `args: {}` for no-arg commands. serde rejects
+        // an empty map for a unit variant, so `decode` must strip it.
+        for cmd_name in ["get_theme_settings", "get_themes_list"] {
+            let body = serde_json::to_vec(&json!({ "cmd": cmd_name, "args": {} })).unwrap();
+            ClientCommand::decode(&body)
+                .unwrap_or_else(|e| panic!("'{cmd_name}' with empty args should decode: {e}"));
+        }
+    }
+
+    #[test]
+    fn struct_variant_still_decodes_with_empty_args() {
+        // All-defaulted struct variants accept `{}` directly; `decode` must
+        // not regress that path while rescuing unit variants.
+        let body = serde_json::to_vec(&json!({ "cmd": "get_initial_state", "args": {} })).unwrap();
+        match ClientCommand::decode(&body).expect("should decode") {
+            ClientCommand::GetInitialState { cols, rows } => {
+                assert_eq!(cols, None);
+                assert_eq!(rows, None);
+            }
+            other => panic!("expected GetInitialState, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn pane_and_window_ids_must_be_tmux_ids() {
+        let send = |pane: &str| {
+            serde_json::to_vec(
+                &json!({ "cmd": "send_text", "args": { "paneId": pane, "text": "x" } }),
+            )
+            .unwrap()
+        };
+        assert!(ClientCommand::decode(&send("%12")).is_ok());
+        for pane in ["%1 ; run-shell id", "%", "1", "@1", "%1'"] {
+            assert!(ClientCommand::decode(&send(pane)).is_err(), "{pane}");
+        }
+        let drag = |window: &str| {
+            serde_json::to_vec(&json!({
+                "cmd": "drag_resize",
+                "args": { "windowId": window, "startX": 0, "startY": 0, "endX": 1, "endY": 1 }
+            }))
+            .unwrap()
+        };
+        assert!(ClientCommand::decode(&drag("@2")).is_ok());
+        assert!(ClientCommand::decode(&drag("@2;kill-server")).is_err());
+    }
+
+    #[test]
+    fn struct_variant_decodes_with_populated_args() {
+        let body =
+            serde_json::to_vec(&json!({ "cmd": "set_theme", "args": { "name": "solarized" } }))
+                .unwrap();
+        match ClientCommand::decode(&body).expect("should decode") {
+            ClientCommand::SetTheme { name, mode } => {
+                assert_eq!(name, "solarized");
+                assert_eq!(mode, None);
+            }
+            other => panic!("expected SetTheme, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn decode_rejects_genuinely_invalid_payload() {
+        let body = serde_json::to_vec(&json!({ "cmd": "no_such_command" })).unwrap();
+        assert!(ClientCommand::decode(&body).is_err());
+    }
+
+    #[test]
+    fn camel_case_paneid_rename_round_trips() {
+        let cmd = parse(json!({
+            "cmd": "get_scrollback_cells",
+            "args": { "paneId": "%3" }
+        }));
+        match cmd {
+            ClientCommand::GetScrollbackCells { pane_id, .. } => assert_eq!(pane_id, "%3"),
+            other => panic!("expected GetScrollbackCells, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn scrollback_defaults_match_legacy_handler() {
+        let cmd = parse(json!({
+            "cmd": "get_scrollback_cells",
+            "args": { "paneId": "%0" }
+        }));
+        match cmd {
+            ClientCommand::GetScrollbackCells { start, end, .. } => {
+                assert_eq!(start, -200);
+                assert_eq!(end, -1);
+            }
+            other => panic!("expected GetScrollbackCells, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn parse_export_pane() {
+        let cmd = parse(json!({
+            "cmd": "export_pane",
+            "args": { "paneId": "%2", "format": "svg", "start": -500 }
+        }));
+        match cmd {
+            ClientCommand::ExportPane {
+                pane_id,
+                format,
+                start,
+                end,
+            } => {
+                assert_eq!(pane_id, "%2");
+                assert_eq!(format, ExportFormat::Svg);
+                assert_eq!((start, end), (Some(-500), None));
+            }
+            other => panic!("expected ExportPane, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn parse_get_pane_text_with_selection() {
+        let cmd = parse(json!({
+            "cmd": "get_pane_text",
+            "args": {
+                "paneId": "%1",
+                "selection": { "startRow": -3, "startCol": 4, "endRow": 0, "endCol": 10 }
+            }
+        }));
+        match cmd {
+            ClientCommand::GetPaneText {
+                pane_id,
+                start,
+                selection,
+                ..
+            } => {
+                assert_eq!(pane_id, "%1");
+                assert_eq!(start, None);
+                let selection = selection.expect("selection");
+                assert_eq!((selection.start_row, selection.end_col), (-3, 10));
+                assert!(!selection.line_mode);
+            }
+            other => panic!("expected GetPaneText, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn parse_pane_search_flattens_the_query() {
+        let cmd = parse(json!({
+            "cmd": "pane_search",
+            "args": { "paneId": "%4", "query": "err.*", "regex": true }
+        }));
+        match cmd {
+            ClientCommand::PaneSearch { pane_id, search } => {
+                assert_eq!(pane_id, "%4");
+                assert_eq!(search.query, "err.*");
+                assert!(search.regex && !search.case_sensitive);
+            }
+            other => panic!("expected PaneSearch, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn parse_set_option_flattens_the_option() {
+        let cmd = parse(json!({
+            "cmd": "set_option",
+            "args": { "scope": "pane", "target": "%2", "name": "@tmuxy_note", "value": "hi" }
+        }));
+        match cmd {
+            ClientCommand::SetOption { option, value } => {
+                assert_eq!(option.scope, OptionScope::Pane);
+                assert_eq!(option.target.as_deref(), Some("%2"));
+                assert_eq!(option.name, "@tmuxy_note");
+                assert_eq!(value.as_deref(), Some("hi"));
+            }
+            other => panic!("expected SetOption, got {:?}", other),
+        }
+        let cmd = parse(json!({
+            "cmd": "get_option",
+            "args": { "scope": "session", "name": "status-position" }
+        }));
+        assert!(matches!(
+            cmd,
+            ClientCommand::GetOption { option } if option.target.is_none()
+        ));
+    }
+
+    #[test]
+    fn parse_create_grouped_session_name_is_optional() {
+        let cmd = parse(json!({ "cmd": "create_grouped_session", "args": { "name": "right" } }));
+        assert!(matches!(
+            cmd,
+            ClientCommand::CreateGroupedSession { name } if name.as_deref() == Some("right")
+        ));
+        let cmd = parse(json!({ "cmd": "create_grouped_session", "args": {} }));
+        assert!(matches!(
+            cmd,
+            ClientCommand::CreateGroupedSession { name: None }
+        ));
+    }
+
+    #[test]
+    fn parse_open_container_shell() {
+        let cmd = parse(json!({
+            "cmd": "open_container_shell",
+            "args": { "paneId": "%2", "runtime": "kubectl", "container": "api", "namespace": "prod" }
+        }));
+        match cmd {
+            ClientCommand::OpenContainerShell {
+                pane_id,
+                exec,
+                new_window,
+            } => {
+                assert_eq!(pane_id, "%2");
+                assert_eq!(exec.namespace.as_deref(), Some("prod"));
+                assert_eq!(exec.shell, None);
+                assert!(!new_window);
+            }
+            other => panic!("expected OpenContainerShell, got {:?}", other),
+        }
+        let body = br#"{"cmd":"list_containers","args":{}}"#;
+        assert!(matches!(
+            ClientCommand::decode(body).expect("should decode"),
+            ClientCommand::ListContainers
+        ));
+    }
+
+    #[test]
+    fn send_mouse_events_decodes_tagged_events() {
+        let cmd = parse(json!({
+            "cmd": "send_mouse_events",
+            "args": {
+                "paneId": "%4",
+                "events": [
+                    { "kind": "press", "button": 0, "x": 1, "y": 2 },
+                    { "kind": "wheel", "lines": -3, "x": 1, "y": 2 }
+                ]
+            }
+        }));
+        match cmd {
+            ClientCommand::SendMouseEvents { pane_id, events } => {
+                assert_eq!(pane_id, "%4");
+                assert_eq!(
+                    events[1],
+                    MouseEvent::Wheel {
+                        lines: -3,
+                        x: 1,
+                        y: 2
+                    }
+                );
+            }
+            other => panic!("expected SendMouseEvents, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn send_key_decodes_a_browser_key_event() {
+        let cmd = parse(json!({
+            "cmd": "send_key",
+            "args": {
+                "paneId": "%4",
+                "key": { "key": "A", "code": "KeyA", "ctrl": true, "shift": true }
+            }
+        }));
+        match cmd {
+            ClientCommand::SendKey { pane_id, key } => {
+                assert_eq!(pane_id, "%4");
+                assert_eq!(key.code, "KeyA");
+                assert!(key.ctrl && key.shift && !key.alt && !key.repeat);
+            }
+            other => panic!("expected SendKey, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn send_text_keeps_multibyte_text_intact() {
+        let cmd = parse(json!({
+            "cmd": "send_text",
+            "args": { "paneId": "%4", "text": "你好 👋" }
+        }));
+        match cmd {
+            ClientCommand::SendText { pane_id, text } => {
+                assert_eq!(pane_id, "%4");
+                assert_eq!(text, "你好 👋");
+            }
+            other => panic!("expected SendText, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn get_stale_panes_defaults_to_a_week() {
+        let body = serde_json::to_vec(&json!({ "cmd": "get_stale_panes", "args": {} })).unwrap();
+        match ClientCommand::decode(&body).expect("should decode") {
+            ClientCommand::GetStalePanes { days } => assert_eq!(days, 7),
+            other => panic!("expected GetStalePanes, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn get_exit_summaries_takes_no_args() {
+        let body = serde_json::to_vec(&json!({ "cmd": "get_exit_summaries", "args": {} })).unwrap();
+        assert!(matches!(
+            ClientCommand::decode(&body).expect("should decode"),
+            ClientCommand::GetExitSummaries
+        ));
+    }
+
+    #[test]
+    fn switch_last_window_cycle_defaults_to_a_forward_step() {
+        let body =
+            serde_json::to_vec(&json!({ "cmd": "switch_last_window_cycle", "args": {} })).unwrap();
+        assert!(matches!(
+            ClientCommand::decode(&body).expect("should decode"),
+            ClientCommand::SwitchLastWindowCycle {
+                reverse: false,
+                release: false
+            }
+        ));
+        assert!(matches!(
+            parse(json!({ "cmd": "switch_last_window_cycle", "args": { "release": true } })),
+            ClientCommand::SwitchLastWindowCycle { release: true, .. }
+        ));
+    }
+
+    #[test]
+    fn drag_resize_uses_camel_case_coordinates() {
+        let cmd = parse(json!({
+            "cmd": "drag_resize",
+            "args": { "windowId": "@2", "startX": 40, "startY": 3, "endX": 44, "endY": 3 }
+        }));
+        match cmd {
+            ClientCommand::DragResize {
+                window_id,
+                start_x,
+                end_x,
+                ..
+            } => {
+                assert_eq!(window_id, "@2");
+                assert_eq!((start_x, end_x), (40, 44));
+            }
+            other => panic!("expected DragResize, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn copy_mode_action_decodes_nested_action() {
+        let cmd = parse(json!({
+            "cmd": "copy_mode_action",
+            "args": {
+                "paneId": "%1",
+                "action": { "name": "search-backward", "arg": "error" },
+                "count": 2
+            }
+        }));
+        match cmd {
+            ClientCommand::CopyModeAction { action, count, .. } => {
+                assert_eq!(action, CopyModeAction::SearchBackward("error".to_string()));
+                assert_eq!(count, Some(2));
+            }
+            other => panic!("expected CopyModeAction, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn scroll_to_decodes_pane_and_offset() {
+        let cmd = parse(json!({
+            "cmd": "scroll_to",
+            "args": { "paneId": "%4", "offset": 250 }
+        }));
+        match cmd {
+            ClientCommand::ScrollTo { pane_id, offset } => {
+                assert_eq!(pane_id, "%4");
+                assert_eq!(offset, 250);
+            }
+            other => panic!("expected ScrollTo, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn key_input_decodes_a_browser_key_event() {
+        let cmd = parse(json!({
+            "cmd": "key_input",
+            "args": { "paneId": "%2", "key": { "key": "a", "ctrl": true } }
+        }));
+        match cmd {
+            ClientCommand::KeyInput { pane_id, key } => {
+                assert_eq!(pane_id, "%2");
+                assert_eq!(key.key, "a");
+                assert!(key.ctrl && !key.repeat);
+            }
+            other => panic!("expected KeyInput, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn float_commands_decode_their_geometry() {
+        let cmd = parse(json!({
+            "cmd": "float_create",
+            "args": { "spec": { "width": 80, "drawer": "right", "hideHeader": true } }
+        }));
+        match cmd {
+            ClientCommand::FloatCreate { spec } => {
+                assert_eq!(spec.width, Some(80));
+                assert_eq!(spec.drawer.as_deref(), Some("right"));
+                assert!(spec.hide_header);
+                assert_eq!(spec.parent, None);
+            }
+            other => panic!("expected FloatCreate, got {:?}", other),
+        }
+        assert!(matches!(
+            parse(json!({ "cmd": "float_create", "args": {} })),
+            ClientCommand::FloatCreate { .. }
+        ));
+        match parse(json!({
+            "cmd": "float_move",
+            "args": { "paneId": "%7", "x": 12, "y": 4, "snap": 2 }
+        })) {
+            ClientCommand::FloatMove {
+                pane_id,
+                x,
+                y,
+                snap,
+            } => {
+                assert_eq!((pane_id.as_str(), x, y, snap), ("%7", 12, 4, Some(2)));
+            }
+            other => panic!("expected FloatMove, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn group_commands_name_a_member() {
+        assert!(matches!(
+            parse(json!({ "cmd": "group_switch", "args": { "paneId": "%4" } })),
+            ClientCommand::GroupSwitch { pane_id } if pane_id == "%4"
+        ));
+        match parse(json!({
+            "cmd": "group_reorder",
+            "args": { "paneId": "%4", "index": 0 }
+        })) {
+            ClientCommand::GroupReorder { pane_id, index } => {
+                assert_eq!((pane_id.as_str(), index), ("%4", 0));
+            }
+            other => panic!("expected GroupReorder, got {:?}", other),
+        }
+        assert!(matches!(
+            parse(json!({
+                "cmd": "group_switch_tab",
+                "args": { "paneId": "%4", "index": 2 }
+            })),
+            ClientCommand::GroupSwitchTab { index: 2, .. }
+        ));
+    }
+
+    #[test]
+    fn get_effective_bindings_takes_no_args() {
+        let cmd = parse(json!({ "cmd": "get_effective_bindings" }));
+        assert!(matches!(cmd, ClientCommand::GetEffectiveBindings));
+    }
+
+    #[test]
+    fn set_frame_budget_decodes_fps() {
+        let cmd = parse(json!({ "cmd": "set_frame_budget", "args": { "fps": 30 } }));
+        assert!(matches!(cmd, ClientCommand::SetFrameBudget { fps: 30 }));
+    }
+
+    #[test]
+    fn pane_drag_commands_decode() {
+        let cmd = parse(json!({ "cmd": "begin_pane_drag", "args": { "paneId": "%3" } }));
+        assert!(matches!(cmd, ClientCommand::BeginPaneDrag { pane_id } if pane_id == "%3"));
+        let cmd = parse(json!({ "cmd": "drop_pane_at", "args": { "x": 12, "y": 7 } }));
+        assert!(matches!(cmd, ClientCommand::DropPaneAt { x: 12, y: 7 }));
+    }
+
+    #[test]
+    fn get_recent_scrollback_defaults_lines() {
+        let cmd = parse(json!({ "cmd": "get_recent_scrollback", "args": { "paneId": "%1" } }));
+        match cmd {
+            ClientCommand::GetRecentScrollback { pane_id, lines } => {
+                assert_eq!(pane_id, "%1");
+                assert_eq!(lines, 200);
+            }
+            other => panic!("expected GetRecentScrollback, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn get_command_history_is_a_pane_read() {
+        let cmd = parse(json!({ "cmd": "get_command_history", "args": { "paneId": "%3" } }));
+        assert_eq!(cmd.pane_id(), Some("%3"));
+    }
+
+    #[test]
+    fn rerun_command_writes_to_its_pane() {
+        let cmd = parse(json!({ "cmd": "rerun_command", "args": { "paneId": "%3", "id": 7 } }));
+        assert!(matches!(cmd, ClientCommand::RerunCommand { id: 7, .. }));
+        assert_eq!(cmd.pane_id(), Some("%3"));
+    }
+
+    #[test]
+    fn with_command_variants_default_cwd_and_command() {
+        let cmd = parse(json!({ "cmd": "new_window_with_command", "args": {} }));
+        assert!(matches!(
+            cmd,
+            ClientCommand::NewWindowWithCommand {
+                cwd: None,
+                command: None,
+                sandbox: None
+            }
+        ));
+        assert_eq!(cmd.pane_id(), None);
+
+        let cmd = parse(json!({
+            "cmd": "split_with_command",
+            "args": { "paneId": "%4", "direction": "horizontal", "cwd": "/srv", "command": "htop" }
+        }));
+        match &cmd {
+            ClientCommand::SplitWithCommand {
+                direction,
+                cwd,
+                command,
+                ..
+            } => {
+                assert_eq!(*direction, SplitDirection::Horizontal);
+                assert_eq!(cwd.as_deref(), Some("/srv"));
+                assert_eq!(command.as_deref(), Some("htop"));
+            }
+            other => panic!("expected SplitWithCommand, got {:?}", other),
+        }
+        assert_eq!(cmd.pane_id(), Some("%4"));
+    }
+
+    #[test]
+    fn with_command_variants_take_only_plain_sandbox_profiles() {
+        let cmd = parse(json!({
+            "cmd": "new_window_with_command",
+            "args": { "sandbox": "docker:alpine" }
+        }));
+        assert!(matches!(
+            cmd,
+            ClientCommand::NewWindowWithCommand { sandbox: Some(ref p), .. } if p == "docker:alpine"
+        ));
+        for profile in ["", "bwrap;kill-server", "bwrap,x", "a b"] {
+            let body = serde_json::to_vec(&json!({
+                "cmd": "split_with_command",
+                "args": { "paneId": "%4", "direction": "vertical", "sandbox": profile }
+            }))
+            .unwrap();
+            assert!(ClientCommand::decode(&body).is_err(), "{profile:?}");
+        }
+    }
+
+    #[test]
+    fn preview_split_decodes_direction_and_defaults_percent() {
+        let cmd = parse(json!({
+            "cmd": "preview_split",
+            "args": { "paneId": "%2", "direction": "vertical" }
+        }));
+        match cmd {
+            ClientCommand::PreviewSplit {
+                pane_id,
+                direction,
+                percent,
+            } => {
+                assert_eq!(pane_id, "%2");
+                assert_eq!(direction, SplitDirection::Vertical);
+                assert_eq!(percent, 50);
+            }
+            other => panic!("expected PreviewSplit, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn set_do_not_disturb_parses_lowercase_mode() {
+        let cmd = parse(json!({
+            "cmd": "set_do_not_disturb",
+            "args": { "mode": "auto" }
+        }));
+        match cmd {
+            ClientCommand::SetDoNotDisturb { mode } => assert_eq!(mode, DndMode::Auto),
+            other => panic!("expected SetDoNotDisturb, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn run_tmux_command_output_defaults_off() {
+        let cmd = parse(json!({ "cmd": "run_tmux_command", "args": { "command": "lsw" } }));
+        match cmd {
+            ClientCommand::RunTmuxCommand { command, output } => {
+                assert_eq!(command, "lsw");
+                assert!(!output);
+            }
+            other => panic!("expected RunTmuxCommand, got {:?}", other),
+        }
+        let cmd = parse(json!({
+            "cmd": "run_tmux_command",
+            "args": { "command": "display -p x", "output": true }
+        }));
+        assert!(matches!(
+            cmd,
+            ClientCommand::RunTmuxCommand { output: true, .. }
+        ));
+    }
+
+    #[test]
+    fn set_focus_parses() {
+        let cmd = parse(json!({ "cmd": "set_focus", "args": { "focused": true } }));
+        match cmd {
+            ClientCommand::SetFocus { focused } => assert!(focused),
+            other => panic!("expected SetFocus, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn set_terminal_theme_parses_palette_by_index() {
+        let cmd = parse(json!({
+            "cmd": "set_terminal_theme",
+            "args": { "theme": { "background": "#1e1e1e", "palette": { "1": "#cc6666" } } }
+        }));
+        match cmd {
+            ClientCommand::SetTerminalTheme { theme } => {
+                assert_eq!(theme.foreground, None);
+                assert_eq!(theme.background.as_deref(), Some("#1e1e1e"));
+                assert_eq!(theme.palette["1"], "#cc6666");
+            }
+            other => panic!("expected SetTerminalTheme, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn inject_secret_carries_only_the_name() {
+        let cmd = parse(json!({
+            "cmd": "inject_secret",
+            "args": { "paneId": "%2", "name": "github-token" }
+        }));
+        match cmd {
+            ClientCommand::InjectSecret { pane_id, name } => {
+                assert_eq!(pane_id, "%2");
+                assert_eq!(name, "github-token");
+            }
+            other => panic!("expected InjectSecret, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn flow_control_commands_name_the_pane() {
+        let cmd = parse(json!({ "cmd": "resume_pane", "args": { "paneId": "%4" } }));
+        assert!(matches!(cmd, ClientCommand::ResumePane { pane_id } if pane_id == "%4"));
+        let cmd = parse(json!({ "cmd": "discard_pane_output", "args": { "paneId": "%4" } }));
+        assert!(matches!(cmd, ClientCommand::DiscardPaneOutput { pane_id } if pane_id == "%4"));
+    }
+
+    #[test]
+    fn widget_commands_carry_the_spec() {
+        let cmd = parse(json!({
+            "cmd": "create_widget",
+            "args": {
+                "paneId": "%1",
+                "widget": { "kind": "markdown", "source": "notes.md", "options": { "theme": "dark" } }
+            }
+        }));
+        match cmd {
+            ClientCommand::CreateWidget { pane_id, widget } => {
+                assert_eq!(pane_id, "%1");
+                assert_eq!(widget.kind, "markdown");
+                assert_eq!(widget.source, "notes.md");
+                assert_eq!(widget.options["theme"], "dark");
+            }
+            other => panic!("expected CreateWidget, got {:?}", other),
+        }
+
+        let cmd = parse(json!({ "cmd": "destroy_widget", "args": { "paneId": "%1" } }));
+        assert!(matches!(cmd, ClientCommand::DestroyWidget { pane_id } if pane_id == "%1"));
+
+        let cmd = parse(json!({ "cmd": "create_git_widget", "args": { "paneId": "%2" } }));
+        assert!(matches!(cmd, ClientCommand::CreateGitWidget { pane_id } if pane_id == "%2"));
+    }
+
+    #[test]
+    fn parse_create_from_template() {
+        let cmd = parse(json!({
+            "cmd": "create_from_template",
+            "args": { "template": "windows = [{}]", "format": "toml", "session": "dev" }
+        }));
+        assert!(matches!(
+            cmd,
+            ClientCommand::CreateFromTemplate { format: TemplateFormat::Toml, session: Some(s), .. }
+                if s == "dev"
+        ));
+        let cmd = parse(json!({
+            "cmd": "create_from_template",
+            "args": { "template": "windows: [{}]" }
+        }));
+        assert!(matches!(
+            cmd,
+            ClientCommand::CreateFromTemplate {
+                format: TemplateFormat::Yaml,
+                session: None,
+                ..
+            }
+        ));
+    }
+
+    #[test]
+    fn parse_table_query() {
+        let cmd = parse(json!({
+            "cmd": "table_query",
+            "args": { "query": {
+                "path": "/d/a.csv", "offset": 50, "sort": { "column": 2, "descending": true }
+            } }
+        }));
+        match cmd {
+            ClientCommand::TableQuery { query } => {
+                assert_eq!(query.path, "/d/a.csv");
+                assert_eq!((query.offset, query.limit), (50, 100));
+                let sort = query.sort.expect("sorted");
+                assert_eq!((sort.column, sort.descending), (2, true));
+            }
+            other => panic!("expected TableQuery, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn set_monitor_tuning_takes_a_partial_tuning() {
+        let cmd = parse(json!({
+            "cmd": "set_monitor_tuning",
+            "args": { "max_latency_ms": 100, "throttle_threshold": 50 }
+        }));
+        match cmd {
+            ClientCommand::SetMonitorTuning { tuning } => {
+                assert_eq!(tuning.max_latency_ms, Some(100));
+                assert_eq!(tuning.throttle_threshold, Some(50));
+                assert_eq!(tuning.throttle_interval_ms, None);
+            }
+            other => panic!("expected SetMonitorTuning, got {:?}", other),
+        }
+        let cmd = parse(json!({
+            "cmd": "set_monitor_tuning",
+            "args": { "heartbeat_interval_ms": 1000, "sync_fields": ["pid"] }
+        }));
+        match cmd {
+            ClientCommand::SetMonitorTuning { tuning } => {
+                assert_eq!(tuning.heartbeat_interval_ms, Some(1000));
+                assert_eq!(tuning.sync_fields, Some(vec![SyncField::Pid]));
+            }
+            other => panic!("expected SetMonitorTuning, got {:?}", other),
+        }
+        let cmd = parse(json!({ "cmd": "set_monitor_tuning", "args": {} }));
+        assert!(
+            matches!(cmd, ClientCommand::SetMonitorTuning { tuning } if tuning == Default::default())
+        );
+    }
+
+    #[test]
+    fn set_log_level_level_is_optional() {
+        let cmd = parse(json!({ "cmd": "set_log_level", "args": { "level": "debug" } }));
+        assert!(matches!(cmd, ClientCommand::SetLogLevel { level: Some(l) } if l == "debug"));
+        let cmd = parse(json!({ "cmd": "set_log_level", "args": {} }));
+        assert!(matches!(cmd, ClientCommand::SetLogLevel { level: None }));
+    }
+
+    #[test]
+    fn fix_ssh_agent_takes_no_args() {
+        let cmd = parse(json!({ "cmd": "fix_ssh_agent" }));
+        assert!(matches!(cmd, ClientCommand::FixSshAgent));
+    }
+}
@@ -0,0 +1,330 @@
+//! Typed events for clients, and the one wire format every transport uses.
+//!
+//! Everything a host pushes to a frontend is a [`ServerEvent`]. The SSE
+//! stream sends [`ServerEvent::encode`], `{"event": <name>, "data":
+//! <payload>}`. The desktop app emits [`ServerEvent::payload`] as the Tauri
+//! event `tmux-<name>`. A new event type is a variant here plus a listener in
+//! each frontend adapter; the emitters don't change.
+
+use serde::{Deserialize, Serialize};
+
+use crate::StateUpdate;
+
+/// The prefix key and the bindings of the prefix and root tables.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct KeyBindings {
+    pub prefix_key: String,
+    pub prefix_bindings: Vec<KeyBinding>,
+    pub root_bindings: Vec<KeyBinding>,
+}
+
+/// One binding of a key table.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct KeyBinding {
+    pub key: String,
+    pub command: String,
+    pub description: String,
+    /// Whether this binding has the `-r` (repeat) flag.
+    /// Repeat bindings auto-re-enter prefix mode after execution.
+    #[serde(default)]
+    pub repeat: bool,
+}
+
+/// What a log entry reports.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[serde(rename_all = "lowercase")]
+pub enum LogKind {
+    /// A command about to be executed (e.g. `tmux has-session -t foo`)
+    Command,
+    /// Output produced by a previously-logged command (stdout/stderr/exit)
+    Output,
+    /// Informational progress message (no associated command)
+    Info,
+    /// A non-fatal error encountered mid-flow
+    Error,
+}
+
+/// Which options table an option lives in.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[serde(rename_all = "lowercase")]
+pub enum OptionScope {
+    Server,
+    Session,
+    Window,
+    Pane,
+}
+
+/// A watched option's new value.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct OptionChange {
+    pub scope: OptionScope,
+    pub name: String,
+    /// The window or pane whose value changed, for window and pane options.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub target: Option<String>,
+    pub value: String,
+}
+
+/// Side of a pane a dragged pane is docked against.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[serde(rename_all = "lowercase")]
+pub enum DropEdge {
+    Left,
+    Right,
+    Top,
+    Bottom,
+}
+
+/// Where a dragged pane lands when dropped at a point; sent to clients as
+/// the live drag-target hint.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[serde(tag = "action", rename_all = "snake_case")]
+pub enum PaneDropTarget {
+    /// Dropped on the middle of `target`: the two panes trade places.
+    Swap { source: String, target: String },
+    /// Dropped near an edge of `target`: `source` is split in on that side.
+    Join {
+        source: String,
+        target: String,
+        edge: DropEdge,
+    },
+}
+
+/// One match of a pane search.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct SearchMatch {
+    /// capture-pane line number: negative lines are history, 0 is the first
+    /// visible row.
+    pub line: i64,
+    /// Cell column the match starts at.
+    pub col: usize,
+    /// Width of the match in cells.
+    pub width: usize,
+    /// The matched text.
+    pub text: String,
+    /// The row's text just before the match, up to 40 characters.
+    pub before: String,
+    /// The row's text just after the match, up to 40 characters.
+    pub after: String,
+}
+
+/// An event for clients.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[serde(tag = "event", content = "data")]
+pub enum ServerEvent {
+    /// Sent first on every connection.
+    #[serde(rename = "connection-info")]
+    ConnectionInfo {
+        /// The wire format version, `PROTOCOL_VERSION`.
+        protocol_version: u32,
+        connection_id: u64,
+        default_shell: String,
+    },
+    #[serde(rename = "state-update")]
+    StateUpdate(Box<StateUpdate>),
+    #[serde(rename = "error")]
+    Error { message: String },
+    #[serde(rename = "keybindings")]
+    KeyBindings(KeyBindings),
+    #[serde(rename = "log")]
+    Log { kind: LogKind, message: String },
+    /// The host gave up on the session; no further events follow.
+    #[serde(rename = "fatal")]
+    Fatal { message: String },
+    /// OSC 52 clipboard request from a terminal application.
+    /// Frontend mirrors the text into the system clipboard via navigator.clipboard.
+    #[serde(rename = "clipboard")]
+    Clipboard { pane_id: String, text: String },
+    /// A pane rang the terminal bell; `sound` follows the server's bell policy.
+    #[serde(rename = "bell")]
+    Bell { pane_id: String, sound: bool },
+    /// Where the pane being dragged would land; `null` clears the hint.
+    #[serde(rename = "drag-target")]
+    DragTarget(Option<PaneDropTarget>),
+    /// Lines a pane just completed, as plain text for screen readers.
+    #[serde(rename = "spoken-text")]
+    SpokenText { pane_id: String, lines: Vec<String> },
+    /// A pane over its output budget had `lines` lines skipped in a frame.
+    #[serde(rename = "output-trimmed")]
+    OutputTrimmed { pane_id: String, lines: u64 },
+    /// A pane asked for a desktop notification (OSC 9/777); `title` is
+    /// empty for OSC 9.
+    #[serde(rename = "pane-notify")]
+    PaneNotify {
+        pane_id: String,
+        title: String,
+        body: String,
+    },
+    /// A client's tmux command failed in control mode (`%error`) and no
+    /// request was waiting on its output.
+    #[serde(rename = "command-error")]
+    CommandError { command: String, error: String },
+    /// The session's tmux went away under a running monitor; `recreating`
+    /// says whether the host is bringing it back.
+    #[serde(rename = "session-lost")]
+    SessionLost { reason: String, recreating: bool },
+    /// Data a widget sent on its own OSC channel.
+    #[serde(rename = "widget-data")]
+    WidgetData {
+        pane_id: String,
+        data: serde_json::Value,
+    },
+    /// A watched tmux option changed.
+    #[serde(rename = "option-changed")]
+    OptionChanged(OptionChange),
+    /// History matches of a `pane_search`, a chunk at a time from the
+    /// bottom up; `done` marks the last chunk.
+    #[serde(rename = "search-results")]
+    SearchResults {
+        pane_id: String,
+        search_id: u64,
+        matches: Vec<SearchMatch>,
+        done: bool,
+    },
+}
+
+impl ServerEvent {
+    /// Every event name, as [`ServerEvent::name`] gives them.
+    pub const NAMES: &'static [&'static str] = &[
+        "connection-info",
+        "state-update",
+        "error",
+        "keybindings",
+        "log",
+        "fatal",
+        "clipboard",
+        "bell",
+        "drag-target",
+        "spoken-text",
+        "output-trimmed",
+        "pane-notify",
+        "command-error",
+        "session-lost",
+        "widget-data",
+        "option-changed",
+        "search-results",
+    ];
+
+    /// The event's name on the wire (`state-update`, `bell`, …).
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::ConnectionInfo { .. } => "connection-info",
+            Self::StateUpdate(_) => "state-update",
+            Self::Error { .. } => "error",
+            Self::KeyBindings(_) => "keybindings",
+            Self::Log { .. } => "log",
+            Self::Fatal { .. } => "fatal",
+            Self::Clipboard { .. } => "clipboard",
+            Self::Bell { .. } => "bell",
+            Self::DragTarget(_) => "drag-target",
+            Self::SpokenText { .. } => "spoken-text",
+            Self::OutputTrimmed { .. } => "output-trimmed",
+            Self::PaneNotify { .. } => "pane-notify",
+            Self::CommandError { .. } => "command-error",
+            Self::SessionLost { .. } => "session-lost",
+            Self::WidgetData { .. } => "widget-data",
+            Self::OptionChanged(_) => "option-changed",
+            Self::SearchResults { .. } => "search-results",
+        }
+    }
+
+    /// The pane the event is about, for events about one pane.
+    pub fn pane_id(&self) -> Option<&str> {
+        match self {
+            Self::Clipboard { pane_id, .. }
+            | Self::Bell { pane_id, .. }
+            | Self::SpokenText { pane_id, .. }
+            | Self::OutputTrimmed { pane_id, .. }
+            | Self::PaneNotify { pane_id, .. }
+            | Self::WidgetData { pane_id, .. }
+            | Self::SearchResults { pane_id, .. } => Some(pane_id),
+            _ => None,
+        }
+    }
+
+    /// The event as one JSON object, `{"event": <name>, "data": <payload>}`.
+    pub fn encode(&self) -> serde_json::Result<String> {
+        serde_json::to_string(self)
+    }
+
+    /// Just the payload, for transports that carry the name alongside.
+    pub fn payload(&self) -> serde_json::Result<serde_json::Value> {
+        let mut event = serde_json::to_value(self)?;
+        Ok(event
+            .get_mut("data")
+            .map(serde_json::Value::take)
+            .unwrap_or_default())
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used, clippy::expect_used)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn events_encode_as_a_named_envelope_around_their_payload() {
+        let bell = ServerEvent::Bell {
+            pane_id: "%3".into(),
+            sound: false,
+        };
+        assert_eq!(bell.name(), "bell");
+        assert_eq!(bell.pane_id(), Some("%3"));
+        let encoded: serde_json::Value = serde_json::from_str(&bell.encode().unwrap()).unwrap();
+        assert_eq!(
+            encoded,
+            json!({ "event": "bell", "data": { "pane_id": "%3", "sound": false } })
+        );
+        assert_eq!(bell.payload().unwrap(), encoded["data"]);
+
+        let cleared = ServerEvent::DragTarget(None);
+        assert_eq!(cleared.payload().unwrap(), serde_json::Value::Null);
+        assert!(ServerEvent::NAMES.contains(&cleared.name()));
+    }
+
+    #[test]
+    fn names_match_the_serde_tags() {
+        let events = [
+            ServerEvent::Error {
+                message: "x".into(),
+            },
+            ServerEvent::Fatal {
+                message: "x".into(),
+            },
+            ServerEvent::SessionLost {
+                reason: "x".into(),
+                recreating: true,
+            },
+            ServerEvent::CommandError {
+                command: "x".into(),
+                error: "y".into(),
+            },
+            ServerEvent::WidgetData {
+                pane_id: "%1".into(),
+                data: json!({}),
+            },
+            ServerEvent::SearchResults {
+                pane_id: "%1".into(),
+                search_id: 2,
+                matches: Vec::new(),
+                done: true,
+            },
+        ];
+        for event in events {
+            let encoded: serde_json::Value =
+                serde_json::from_str(&event.encode().unwrap()).unwrap();
+            assert_eq!(encoded["event"], event.name());
+            assert!(ServerEvent::NAMES.contains(&event.name()));
+        }
+    }
+}
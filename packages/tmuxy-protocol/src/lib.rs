@@ -0,0 +1,53 @@
+//! The wire types tmuxy servers and clients exchange.
+//!
+//! A client receives [`ServerEvent`]s: over SSE each is one JSON object,
+//! `{"event": <name>, "data": <payload>}`, and the desktop app emits the
+//! payload as the Tauri event `tmux-<name>`. Session state arrives in
+//! `state-update` events as a [`StateUpdate`], either a full [`TmuxState`]
+//! or a [`TmuxDelta`] against the previous one.
+//!
+//! Clients send [`ClientCommand`]s: a `POST /commands` body of
+//! `{"cmd": <name>, "args": {...}}`, or a Tauri `invoke` in the desktop
+//! app. Their argument types are plain data here; what the server does with
+//! them lives in tmuxy-core.
+//!
+//! With the `schema` feature every type derives `schemars::JsonSchema`.
+//! [`schema::server_event`] describes the whole stream and
+//! [`schema::client_command`] every command; `schema.json` and
+//! `command-schema.json` at the crate root are those schemas, checked in for
+//! generating client types.
+
+mod args;
+mod cell;
+mod colors;
+mod command;
+mod event;
+#[cfg(feature = "schema")]
+pub mod schema;
+mod state;
+mod widget;
+
+pub use args::{
+    ContainerExec, ContainerRuntime, CopyModeAction, DndMode, ExportFormat, FloatSpec, KeyEvent,
+    MonitorTuning, MouseEvent, OptionRef, SearchQuery, SplitDirection, SyncField, TableQuery,
+    TableSort, TemplateFormat, TextSelection,
+};
+pub use cell::{CellColor, CellStyle, PaneContent, TerminalCell, TerminalLine, UnderlineStyle};
+pub use colors::{parse_color, TerminalColors};
+pub use command::ClientCommand;
+pub use event::{
+    DropEdge, KeyBinding, KeyBindings, LogKind, OptionChange, OptionScope, PaneDropTarget,
+    SearchMatch, ServerEvent,
+};
+pub use state::{
+    CommandBlock, ImagePlacement, ImageProtocol, PaneDelta, ScrollDelta, SessionGroup,
+    SshAgentStatus, StateUpdate, StatusPosition, StatusSegments, StatusWindow, TmuxDelta, TmuxPane,
+    TmuxState, TmuxWindow, WindowDelta, WindowType,
+};
+pub use widget::WidgetSpec;
+
+/// Version of the wire format, sent in every `connection-info` event.
+/// Bumped when a change would break an existing client: a field removed or
+/// retyped, or an event's meaning changed. New optional fields and new
+/// events don't bump it; clients ignore what they don't know.
+pub const PROTOCOL_VERSION: u32 = 1;
@@ -0,0 +1,64 @@
+//! JSON Schema for the wire types.
+
+use schemars::generate::SchemaSettings;
+use schemars::Schema;
+
+use crate::{ClientCommand, ServerEvent};
+
+/// Schema of one event on the stream, as the server writes it. Every other
+/// wire type is reachable from it, under `$defs`.
+pub fn server_event() -> Schema {
+    let mut schema = SchemaSettings::default()
+        .for_serialize()
+        .into_generator()
+        .into_root_schema_for::<ServerEvent>();
+    schema.insert(
+        "$comment".into(),
+        format!("tmuxy protocol version {}", crate::PROTOCOL_VERSION).into(),
+    );
+    schema
+}
+
+/// Schema of one command, as the server reads it: a field it fills in when
+/// missing is optional. The argument types are under `$defs`.
+pub fn client_command() -> Schema {
+    let mut schema = SchemaSettings::default()
+        .for_deserialize()
+        .into_generator()
+        .into_root_schema_for::<ClientCommand>();
+    schema.insert(
+        "$comment".into(),
+        format!("tmuxy protocol version {}", crate::PROTOCOL_VERSION).into(),
+    );
+    schema
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used, clippy::expect_used)]
+mod tests {
+    use super::*;
+
+    /// Compare `schema` with the checked-in `file`, rewriting it first under
+    /// `UPDATE_SCHEMA`.
+    fn check(file: &str, schema: &Schema) {
+        let path = format!("{}/{file}", env!("CARGO_MANIFEST_DIR"));
+        let current = serde_json::to_string_pretty(schema).unwrap() + "\n";
+        if std::env::var_os("UPDATE_SCHEMA").is_some() {
+            std::fs::write(&path, &current).unwrap();
+        }
+        let checked_in = std::fs::read_to_string(&path).unwrap_or_default();
+        assert!(
+            checked_in == current,
+            "{file} is stale; rerun with UPDATE_SCHEMA=1"
+        );
+    }
+
+    /// `schema.json` and `command-schema.json` are what client type
+    /// generators read; they must match the types. Regenerate with
+    /// `UPDATE_SCHEMA=1 cargo test -p tmuxy-protocol --features schema`.
+    #[test]
+    fn checked_in_schemas_are_current() {
+        check("schema.json", &server_event());
+        check("command-schema.json", &client_command());
+    }
+}
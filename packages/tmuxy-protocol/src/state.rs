@@ -0,0 +1,932 @@
+//! Session state as clients see it: full snapshots and the deltas between
+//! them.
+
+use serde::{Deserialize, Serialize};
+
+use crate::cell::{is_false, PaneContent, TerminalLine};
+use crate::colors::TerminalColors;
+use crate::widget::WidgetSpec;
+
+// ============================================
+// Tmux State Types
+// ============================================
+
+/// A single tmux pane
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct TmuxPane {
+    pub id: u32,
+    pub tmux_id: String,   // actual tmux pane ID (e.g., "%0")
+    pub window_id: String, // window this pane belongs to (e.g., "@0")
+    /// Rendered cell grid. `Arc`-shared so building a state snapshot, storing
+    /// `prev_state`, and diffing unchanged panes never deep-copies the grid —
+    /// the cost that made a one-field delta as expensive as a full sync.
+    /// Serializes transparently (serde `rc`), so the wire shape is unchanged.
+    pub content: std::sync::Arc<PaneContent>,
+    pub cursor_x: u32,
+    pub cursor_y: u32,
+    pub width: u32,
+    pub height: u32,
+    pub x: u32,
+    pub y: u32,
+    pub active: bool,
+    pub command: String,
+    pub title: String,        // pane title (set by shell/application)
+    pub border_title: String, // evaluated pane-border-format from tmux config
+    pub in_mode: bool,        // true if in copy mode
+    pub copy_cursor_x: u32,
+    pub copy_cursor_y: u32,
+    /// True if the application is in alternate screen mode (vim, less, htop)
+    /// Used to determine scroll behavior (wheel -> arrow keys vs copy mode)
+    #[serde(default)]
+    pub alternate_on: bool,
+    /// True if the application has mouse tracking enabled
+    /// When true, mouse events should be forwarded as SGR sequences
+    #[serde(default)]
+    pub mouse_any_flag: bool,
+    /// True if this pane's output is paused due to flow control
+    /// When true, UI should show a pause indicator
+    #[serde(default)]
+    pub paused: bool,
+    /// Number of history lines (scrollback above the visible area)
+    #[serde(default)]
+    pub history_size: u64,
+    /// Lines scrolled back from the live bottom while in copy mode; with
+    /// `history_size` this places a scrollbar thumb
+    #[serde(default)]
+    pub scroll_position: u32,
+    /// Whether a selection is active in copy mode
+    #[serde(default)]
+    pub selection_present: bool,
+    /// Selection start X (visible-area-relative column), only meaningful when selection_present
+    #[serde(default)]
+    pub selection_start_x: u32,
+    /// Selection start Y (visible-area-relative row, can be negative if off-screen)
+    #[serde(default)]
+    pub selection_start_y: i32,
+    /// Selection end X (visible-area-relative column); tracks the copy cursor
+    /// while selecting, only meaningful when selection_present
+    #[serde(default)]
+    pub selection_end_x: u32,
+    /// Selection end Y (visible-area-relative row, can be negative if off-screen)
+    #[serde(default)]
+    pub selection_end_y: i32,
+    /// Image placements on this pane's terminal grid
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub images: Vec<ImagePlacement>,
+    /// Commands shown on screen, from the shell's OSC 133 integration, for
+    /// exit-status gutters
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub command_blocks: Vec<CommandBlock>,
+    /// Cursor shape from DECSCUSR: 0/1=block_blink, 2=block, 3=underline_blink, 4=underline, 5=bar_blink, 6=bar
+    #[serde(default)]
+    pub cursor_shape: u8,
+    /// Whether the cursor is hidden (DECTCEM mode 25 off / ESC[?25l)
+    #[serde(default)]
+    pub cursor_hidden: bool,
+    /// Whether the application asked for an extended keyboard protocol
+    /// (`modifyOtherKeys` or kitty flags); clients then send raw key events
+    #[serde(default)]
+    pub extended_keys: bool,
+    /// Colours the application set with OSC 4/10/11, layered over `theme`
+    #[serde(default, skip_serializing_if = "TerminalColors::is_empty")]
+    pub colors: TerminalColors,
+    /// Sandbox profile the pane's command runs in (`@tmuxy-sandbox`)
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub sandbox: String,
+    /// CSS cursor the application asked for with OSC 22, empty for the default
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub pointer_shape: String,
+    /// Widget the pane displays (`@tmuxy-widget`), `None` for a terminal
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub widget: Option<WidgetSpec>,
+    /// Working directory, when the monitor's sync fetches it (`SyncField::Cwd`)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cwd: Option<String>,
+    /// Process id of the pane's command, when the monitor's sync fetches it
+    /// (`SyncField::Pid`)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pid: Option<u32>,
+}
+
+/// Window type discriminator. Set on windows tmuxy created or has adopted.
+/// Windows without a type are foreign and tmuxy ignores them everywhere.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[serde(rename_all = "kebab-case")]
+pub enum WindowType {
+    Tab,
+    Float,
+    FloatBackdrop,
+    Group,
+    /// The left sidebar's hidden window (runs the `tmuxy tree` TUI). Excluded
+    /// from the tab bar like floats/groups; rendered in the UI as a left drawer.
+    Sidebar,
+}
+
+impl WindowType {
+    pub fn parse(s: &str) -> Option<Self> {
+        match s {
+            "tab" => Some(WindowType::Tab),
+            "float" => Some(WindowType::Float),
+            "float-backdrop" => Some(WindowType::FloatBackdrop),
+            "group" => Some(WindowType::Group),
+            "sidebar" => Some(WindowType::Sidebar),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            WindowType::Tab => "tab",
+            WindowType::Float => "float",
+            WindowType::FloatBackdrop => "float-backdrop",
+            WindowType::Group => "group",
+            WindowType::Sidebar => "sidebar",
+        }
+    }
+}
+
+/// A single tmux window (tab/float/group/foreign)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct TmuxWindow {
+    /// Window ID (e.g., "@0")
+    pub id: String,
+    pub index: u32,
+    pub name: String,
+    pub active: bool,
+    /// Window type as set via @tmuxy-window-type. None = foreign window.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub window_type: Option<WindowType>,
+    /// Group pane membership (from @tmuxy-group-panes), e.g. ["%4","%6","%7"].
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub group_panes: Option<Vec<String>>,
+    /// Parent window ID for a float (the launcher window) or backdrop (the float).
+    /// Sourced from @tmuxy-float-parent.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub float_parent: Option<String>,
+    /// Float width in cells (from @tmuxy-float-width).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub float_width: Option<u32>,
+    /// Float height in cells (from @tmuxy-float-height).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub float_height: Option<u32>,
+    /// Drawer-style float direction (from @tmuxy-float-drawer).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub float_drawer: Option<String>,
+    /// Float backdrop style (from @tmuxy-float-bg).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub float_bg: Option<String>,
+    /// True if the float hides its header chrome (from @tmuxy-float-noheader).
+    #[serde(default, skip_serializing_if = "is_false")]
+    pub float_noheader: bool,
+    /// Float position in cells (from @tmuxy-float-x/y); None = centered.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub float_x: Option<u32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub float_y: Option<u32>,
+    /// Stacking order among floats, highest on top (from @tmuxy-float-z).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub float_z: Option<u32>,
+    /// True while the float is toggled out of view (from @tmuxy-float-hidden).
+    #[serde(default, skip_serializing_if = "is_false")]
+    pub float_hidden: bool,
+    /// True while a pane in this window is zoomed. tmux hides every other pane
+    /// when zoomed; the frontend must not keep painting them underneath.
+    #[serde(default)]
+    pub zoomed: bool,
+    /// Evaluated `@tmuxy-status` format (see tmuxy-core's `control_mode::window_status`).
+    /// None when the window has none, or it came out empty.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub status: Option<String>,
+}
+
+/// Full tmux state with all panes and windows
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct TmuxState {
+    /// Session name (e.g., "tmuxy")
+    pub session_name: String,
+    /// Active window ID (e.g., "@0")
+    pub active_window_id: Option<String>,
+    /// Active pane ID (e.g., "%0")
+    pub active_pane_id: Option<String>,
+    pub panes: Vec<TmuxPane>,
+    pub windows: Vec<TmuxWindow>,
+    pub total_width: u32,
+    pub total_height: u32,
+    /// Rendered tmux status line with ANSI escape sequences
+    pub status_line: String,
+    /// The same status line as styled cells: left side, window list entries
+    /// (with their window IDs) and right side
+    #[serde(default)]
+    pub status_segments: StatusSegments,
+    /// The status bar's rows below the first, with `status` set to 2 or
+    /// more. They are not part of `total_height`: windows are sized to the
+    /// pane area (see `resize_window`), so the frontend makes room for them
+    /// the same way it does for the first row.
+    #[serde(default)]
+    pub status_lines: Vec<TerminalLine>,
+    /// Whether the status bar sits above or below the panes
+    #[serde(default)]
+    pub status_position: StatusPosition,
+    /// The session's group (`new-session -t`), if it is in one
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub session_group: Option<SessionGroup>,
+    /// Do-not-disturb is in effect: bells and auto-raise are suppressed
+    #[serde(default)]
+    pub do_not_disturb: bool,
+    /// Server's default foreground, background and palette for every pane
+    #[serde(default)]
+    pub theme: TerminalColors,
+    /// Whether the session's `SSH_AUTH_SOCK` reaches a live agent
+    #[serde(default)]
+    pub ssh_agent: SshAgentStatus,
+    /// Identifies the aggregator that produced this state. Deltas apply only
+    /// on top of a state of the same epoch; 0 means unversioned (a snapshot
+    /// captured outside the update stream).
+    #[serde(default)]
+    pub epoch: u64,
+}
+
+/// Serialize a line-number-keyed map with STRING keys. serde_json does this
+/// implicitly (JSON object keys are strings — the wire shape the frontend
+/// already speaks), but serde-wasm-bindgen's maps-as-objects mode REFUSES
+/// non-string keys ("Map key is not a string"), which aborted serialization of
+/// every content-carrying delta on the wasm path.
+fn ser_line_map<S: serde::Serializer>(
+    v: &Option<std::collections::HashMap<usize, TerminalLine>>,
+    s: S,
+) -> Result<S::Ok, S::Error> {
+    use serde::ser::SerializeMap;
+    match v {
+        None => s.serialize_none(),
+        Some(m) => {
+            let mut map = s.serialize_map(Some(m.len()))?;
+            for (k, val) in m {
+                map.serialize_entry(&k.to_string(), val)?;
+            }
+            map.end()
+        }
+    }
+}
+
+/// Inverse of `ser_line_map`. serde_json only turns `"3"` back into a `usize`
+/// key when it deserializes the map directly; inside the internally tagged
+/// `StateUpdate` the map is buffered first and the coercion is lost.
+fn de_line_map<'de, D: serde::Deserializer<'de>>(
+    d: D,
+) -> Result<Option<std::collections::HashMap<usize, TerminalLine>>, D::Error> {
+    let Some(m) = Option::<std::collections::HashMap<String, TerminalLine>>::deserialize(d)? else {
+        return Ok(None);
+    };
+    m.into_iter()
+        .map(|(k, line)| {
+            k.parse()
+                .map(|row| (row, line))
+                .map_err(serde::de::Error::custom)
+        })
+        .collect::<Result<_, _>>()
+        .map(Some)
+}
+
+/// Content shifted up by `lines` rows; `new_rows` fill the bottom.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct ScrollDelta {
+    pub lines: u32,
+    pub new_rows: Vec<TerminalLine>,
+}
+
+/// Delta update for a single pane (only changed fields)
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct PaneDelta {
+    /// Window ID (only if changed, e.g. after swap-pane across windows)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub window_id: Option<String>,
+    /// Content (only changed lines) - line index → line content
+    /// Only lines that differ from the previous state are included.
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        serialize_with = "ser_line_map",
+        deserialize_with = "de_line_map"
+    )]
+    #[cfg_attr(
+        feature = "schema",
+        schemars(with = "Option<std::collections::HashMap<String, TerminalLine>>")
+    )]
+    pub content: Option<std::collections::HashMap<usize, TerminalLine>>,
+    /// Scroll (only when the content moved up with fresh rows below). The
+    /// client drops the top `lines` rows and appends `new_rows` *before*
+    /// applying `content`, which then only carries rows that changed beyond
+    /// the shift — `tail -f` style output costs about one row per line.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub scroll: Option<ScrollDelta>,
+    /// Cursor position (only if changed)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cursor_x: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cursor_y: Option<u32>,
+    /// Dimensions (only if changed)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub width: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub height: Option<u32>,
+    /// Position (only if changed)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub x: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub y: Option<u32>,
+    /// Active state (only if changed)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub active: Option<bool>,
+    /// Command (only if changed)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub command: Option<String>,
+    /// Title (only if changed)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub title: Option<String>,
+    /// Border title (only if changed)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub border_title: Option<String>,
+    /// Copy mode state (only if changed)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub in_mode: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub copy_cursor_x: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub copy_cursor_y: Option<u32>,
+    /// Alternate screen mode (only if changed)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub alternate_on: Option<bool>,
+    /// Mouse any flag (only if changed)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub mouse_any_flag: Option<bool>,
+    /// Flow control pause state (only if changed)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub paused: Option<bool>,
+    /// History size (only if changed)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub history_size: Option<u64>,
+    /// Copy-mode scroll position (only if changed)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub scroll_position: Option<u32>,
+    /// Selection present (only if changed)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub selection_present: Option<bool>,
+    /// Selection start X (only if changed)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub selection_start_x: Option<u32>,
+    /// Selection start Y (only if changed)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub selection_start_y: Option<i32>,
+    /// Selection end X (only if changed)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub selection_end_x: Option<u32>,
+    /// Selection end Y (only if changed)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub selection_end_y: Option<i32>,
+    /// Image placements (only if changed)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub images: Option<Vec<ImagePlacement>>,
+    /// Command blocks (only if changed)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub command_blocks: Option<Vec<CommandBlock>>,
+    /// Cursor shape (only if changed)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cursor_shape: Option<u8>,
+    /// Cursor hidden (only if changed)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cursor_hidden: Option<bool>,
+    /// Extended keyboard protocol (only if changed)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub extended_keys: Option<bool>,
+    /// Application colour overrides (only if changed)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub colors: Option<TerminalColors>,
+    /// Sandbox profile (only if changed; empty when removed)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub sandbox: Option<String>,
+    /// Pointer shape (only if changed; empty for the default)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub pointer_shape: Option<String>,
+    /// Widget spec (only if changed; `Some(None)` when the widget exited)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub widget: Option<Option<WidgetSpec>>,
+    /// Working directory (only if changed; `Some(None)` when no longer fetched)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cwd: Option<Option<String>>,
+    /// Process id (only if changed; `Some(None)` when no longer fetched)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub pid: Option<Option<u32>>,
+}
+
+impl PaneDelta {
+    pub fn is_empty(&self) -> bool {
+        self.window_id.is_none()
+            && self.content.is_none()
+            && self.scroll.is_none()
+            && self.cursor_x.is_none()
+            && self.cursor_y.is_none()
+            && self.width.is_none()
+            && self.height.is_none()
+            && self.x.is_none()
+            && self.y.is_none()
+            && self.active.is_none()
+            && self.command.is_none()
+            && self.title.is_none()
+            && self.border_title.is_none()
+            && self.in_mode.is_none()
+            && self.copy_cursor_x.is_none()
+            && self.copy_cursor_y.is_none()
+            && self.alternate_on.is_none()
+            && self.mouse_any_flag.is_none()
+            && self.paused.is_none()
+            && self.history_size.is_none()
+            && self.scroll_position.is_none()
+            && self.selection_present.is_none()
+            && self.selection_start_x.is_none()
+            && self.selection_start_y.is_none()
+            && self.selection_end_x.is_none()
+            && self.selection_end_y.is_none()
+            && self.images.is_none()
+            && self.command_blocks.is_none()
+            && self.cursor_shape.is_none()
+            && self.cursor_hidden.is_none()
+            && self.extended_keys.is_none()
+            && self.colors.is_none()
+            && self.sandbox.is_none()
+            && self.pointer_shape.is_none()
+            && self.widget.is_none()
+            && self.cwd.is_none()
+            && self.pid.is_none()
+    }
+
+    /// Fold `next` (the delta sent right after this one) into this one, so
+    /// applying the result equals applying both. Content rows this delta
+    /// changed move up with a scroll in `next`; two scrolls can't be folded,
+    /// since where the second one's new rows land depends on the pane height.
+    pub fn merge(&mut self, next: PaneDelta) {
+        debug_assert!(self.scroll.is_none() || next.scroll.is_none());
+        if let Some(scroll) = next.scroll {
+            let lines = scroll.lines as usize;
+            self.content = self
+                .content
+                .take()
+                .map(|content| {
+                    content
+                        .into_iter()
+                        .filter_map(|(row, line)| Some((row.checked_sub(lines)?, line)))
+                        .collect::<std::collections::HashMap<_, _>>()
+                })
+                .filter(|content| !content.is_empty());
+            self.scroll = Some(scroll);
+        }
+        if let Some(content) = next.content {
+            self.content.get_or_insert_default().extend(content);
+        }
+        newer(&mut self.window_id, next.window_id);
+        newer(&mut self.cursor_x, next.cursor_x);
+        newer(&mut self.cursor_y, next.cursor_y);
+        newer(&mut self.width, next.width);
+        newer(&mut self.height, next.height);
+        newer(&mut self.x, next.x);
+        newer(&mut self.y, next.y);
+        newer(&mut self.active, next.active);
+        newer(&mut self.command, next.command);
+        newer(&mut self.title, next.title);
+        newer(&mut self.border_title, next.border_title);
+        newer(&mut self.in_mode, next.in_mode);
+        newer(&mut self.copy_cursor_x, next.copy_cursor_x);
+        newer(&mut self.copy_cursor_y, next.copy_cursor_y);
+        newer(&mut self.alternate_on, next.alternate_on);
+        newer(&mut self.mouse_any_flag, next.mouse_any_flag);
+        newer(&mut self.paused, next.paused);
+        newer(&mut self.history_size, next.history_size);
+        newer(&mut self.scroll_position, next.scroll_position);
+        newer(&mut self.selection_present, next.selection_present);
+        newer(&mut self.selection_start_x, next.selection_start_x);
+        newer(&mut self.selection_start_y, next.selection_start_y);
+        newer(&mut self.selection_end_x, next.selection_end_x);
+        newer(&mut self.selection_end_y, next.selection_end_y);
+        newer(&mut self.images, next.images);
+        newer(&mut self.command_blocks, next.command_blocks);
+        newer(&mut self.cursor_shape, next.cursor_shape);
+        newer(&mut self.cursor_hidden, next.cursor_hidden);
+        newer(&mut self.extended_keys, next.extended_keys);
+        newer(&mut self.colors, next.colors);
+        newer(&mut self.sandbox, next.sandbox);
+        newer(&mut self.pointer_shape, next.pointer_shape);
+        newer(&mut self.widget, next.widget);
+        newer(&mut self.cwd, next.cwd);
+        newer(&mut self.pid, next.pid);
+    }
+}
+
+/// Take `next` over `prev` when it carries a value.
+fn newer<T>(prev: &mut Option<T>, next: Option<T>) {
+    if next.is_some() {
+        *prev = next;
+    }
+}
+
+/// Delta update for a single window (only changed fields)
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct WindowDelta {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub active: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub window_type: Option<Option<WindowType>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub group_panes: Option<Option<Vec<String>>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub float_parent: Option<Option<String>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub float_width: Option<Option<u32>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub float_height: Option<Option<u32>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub float_drawer: Option<Option<String>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub float_bg: Option<Option<String>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub float_noheader: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub float_x: Option<Option<u32>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub float_y: Option<Option<u32>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub float_z: Option<Option<u32>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub float_hidden: Option<bool>,
+    /// True while this window has a zoomed pane. tmux hides the other panes
+    /// entirely when zoomed, so the frontend needs this to do the same.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub zoomed: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub status: Option<Option<String>>,
+}
+
+impl WindowDelta {
+    pub fn is_empty(&self) -> bool {
+        self.name.is_none()
+            && self.active.is_none()
+            && self.window_type.is_none()
+            && self.group_panes.is_none()
+            && self.float_parent.is_none()
+            && self.float_width.is_none()
+            && self.float_height.is_none()
+            && self.float_drawer.is_none()
+            && self.float_bg.is_none()
+            && self.float_noheader.is_none()
+            && self.float_x.is_none()
+            && self.float_y.is_none()
+            && self.float_z.is_none()
+            && self.float_hidden.is_none()
+            && self.zoomed.is_none()
+            && self.status.is_none()
+    }
+
+    /// Fold `next` (the delta sent right after this one) into this one.
+    pub fn merge(&mut self, next: WindowDelta) {
+        newer(&mut self.name, next.name);
+        newer(&mut self.active, next.active);
+        newer(&mut self.window_type, next.window_type);
+        newer(&mut self.group_panes, next.group_panes);
+        newer(&mut self.float_parent, next.float_parent);
+        newer(&mut self.float_width, next.float_width);
+        newer(&mut self.float_height, next.float_height);
+        newer(&mut self.float_drawer, next.float_drawer);
+        newer(&mut self.float_bg, next.float_bg);
+        newer(&mut self.float_noheader, next.float_noheader);
+        newer(&mut self.float_x, next.float_x);
+        newer(&mut self.float_y, next.float_y);
+        newer(&mut self.float_z, next.float_z);
+        newer(&mut self.float_hidden, next.float_hidden);
+        newer(&mut self.zoomed, next.zoomed);
+        newer(&mut self.status, next.status);
+    }
+}
+
+/// Delta state update - only includes what changed
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct TmuxDelta {
+    /// Sequence number for ordering
+    pub seq: u64,
+    /// Epoch of the state this delta applies on top of
+    #[serde(default)]
+    pub epoch: u64,
+    /// Changed panes: pane_id -> delta (None = pane removed)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub panes: Option<std::collections::HashMap<String, Option<PaneDelta>>>,
+    /// Changed windows: window_id -> delta (None = window removed)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub windows: Option<std::collections::HashMap<String, Option<WindowDelta>>>,
+    /// New panes (full data for newly added panes)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub new_panes: Option<Vec<TmuxPane>>,
+    /// New windows (full data for newly added windows)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub new_windows: Option<Vec<TmuxWindow>>,
+    /// Active window changed
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub active_window_id: Option<String>,
+    /// Active pane changed
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub active_pane_id: Option<String>,
+    /// Status line changed
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub status_line: Option<String>,
+    /// Status line segments changed
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub status_segments: Option<StatusSegments>,
+    /// Extra status rows changed
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub status_lines: Option<Vec<TerminalLine>>,
+    /// Status position changed
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub status_position: Option<StatusPosition>,
+    /// Session group changed (`Some(None)` when the session left its group)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub session_group: Option<Option<SessionGroup>>,
+    /// Total dimensions changed
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub total_width: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub total_height: Option<u32>,
+    /// Do-not-disturb turned on or off
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub do_not_disturb: Option<bool>,
+    /// Server theme changed
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub theme: Option<TerminalColors>,
+    /// SSH agent status changed
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub ssh_agent: Option<SshAgentStatus>,
+}
+
+impl TmuxDelta {
+    pub fn new(seq: u64) -> Self {
+        Self {
+            seq,
+            epoch: 0,
+            panes: None,
+            windows: None,
+            new_panes: None,
+            new_windows: None,
+            active_window_id: None,
+            active_pane_id: None,
+            status_line: None,
+            status_segments: None,
+            status_lines: None,
+            status_position: None,
+            session_group: None,
+            total_width: None,
+            total_height: None,
+            do_not_disturb: None,
+            theme: None,
+            ssh_agent: None,
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.panes.is_none()
+            && self.windows.is_none()
+            && self.new_panes.is_none()
+            && self.new_windows.is_none()
+            && self.active_window_id.is_none()
+            && self.active_pane_id.is_none()
+            && self.status_line.is_none()
+            && self.status_segments.is_none()
+            && self.status_lines.is_none()
+            && self.status_position.is_none()
+            && self.session_group.is_none()
+            && self.total_width.is_none()
+            && self.total_height.is_none()
+            && self.do_not_disturb.is_none()
+            && self.theme.is_none()
+            && self.ssh_agent.is_none()
+    }
+
+    /// Whether `next`, the delta sent right after this one, can be folded
+    /// into it with [`TmuxDelta::merge`]. Not when they belong to different
+    /// epochs, when both scroll the same pane, or when `next` changes a pane
+    /// or window this delta introduces in full.
+    pub fn can_merge(&self, next: &TmuxDelta) -> bool {
+        let added_pane = |id: &str| {
+            self.new_panes
+                .iter()
+                .flatten()
+                .any(|pane| pane.tmux_id == id)
+        };
+        let added_window = |id: &str| self.new_windows.iter().flatten().any(|w| w.id == id);
+        let panes_ok = next.panes.iter().flatten().all(|(id, delta)| {
+            let Some(delta) = delta else { return true };
+            let both_scroll = delta.scroll.is_some()
+                && self
+                    .panes
+                    .as_ref()
+                    .and_then(|panes| panes.get(id)?.as_ref())
+                    .is_some_and(|prev| prev.scroll.is_some());
+            !both_scroll && !added_pane(id)
+        });
+        let windows_ok = next
+            .windows
+            .iter()
+            .flatten()
+            .all(|(id, delta)| delta.is_none() || !added_window(id));
+        self.epoch == next.epoch && panes_ok && windows_ok
+    }
+
+    /// Fold `next` into this delta, so applying the result equals applying
+    /// both in order. Only valid when [`TmuxDelta::can_merge`] allows it.
+    pub fn merge(&mut self, next: TmuxDelta) {
+        self.seq = next.seq;
+        for (id, delta) in next.panes.into_iter().flatten() {
+            let panes = self.panes.get_or_insert_default();
+            match (delta, panes.get_mut(&id)) {
+                (Some(delta), Some(Some(prev))) => prev.merge(delta),
+                (delta, _) => {
+                    if delta.is_none() {
+                        if let Some(new_panes) = &mut self.new_panes {
+                            new_panes.retain(|pane| pane.tmux_id != id);
+                        }
+                    }
+                    panes.insert(id, delta);
+                }
+            }
+        }
+        for pane in next.new_panes.into_iter().flatten() {
+            if let Some(panes) = &mut self.panes {
+                panes.remove(&pane.tmux_id);
+            }
+            let new_panes = self.new_panes.get_or_insert_default();
+            new_panes.retain(|p| p.tmux_id != pane.tmux_id);
+            new_panes.push(pane);
+        }
+        for (id, delta) in next.windows.into_iter().flatten() {
+            let windows = self.windows.get_or_insert_default();
+            match (delta, windows.get_mut(&id)) {
+                (Some(delta), Some(Some(prev))) => prev.merge(delta),
+                (delta, _) => {
+                    if delta.is_none() {
+                        if let Some(new_windows) = &mut self.new_windows {
+                            new_windows.retain(|w| w.id != id);
+                        }
+                    }
+                    windows.insert(id, delta);
+                }
+            }
+        }
+        for window in next.new_windows.into_iter().flatten() {
+            if let Some(windows) = &mut self.windows {
+                windows.remove(&window.id);
+            }
+            let new_windows = self.new_windows.get_or_insert_default();
+            new_windows.retain(|w| w.id != window.id);
+            new_windows.push(window);
+        }
+        newer(&mut self.active_window_id, next.active_window_id);
+        newer(&mut self.active_pane_id, next.active_pane_id);
+        newer(&mut self.status_line, next.status_line);
+        newer(&mut self.status_segments, next.status_segments);
+        newer(&mut self.status_lines, next.status_lines);
+        newer(&mut self.status_position, next.status_position);
+        newer(&mut self.session_group, next.session_group);
+        newer(&mut self.total_width, next.total_width);
+        newer(&mut self.total_height, next.total_height);
+        newer(&mut self.do_not_disturb, next.do_not_disturb);
+        newer(&mut self.theme, next.theme);
+        newer(&mut self.ssh_agent, next.ssh_agent);
+    }
+}
+
+/// Message type for state updates (full or delta)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[serde(tag = "type")]
+pub enum StateUpdate {
+    /// Full state (used for initial sync and reconnection)
+    #[serde(rename = "full")]
+    Full { state: TmuxState },
+    /// Delta update (used for incremental updates)
+    #[serde(rename = "delta")]
+    Delta { delta: TmuxDelta },
+}
+
+// ============================================
+// Pane and Session Details
+// ============================================
+
+/// Image protocol that produced this image.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[serde(rename_all = "lowercase")]
+pub enum ImageProtocol {
+    ITerm2,
+    Kitty,
+    Sixel,
+}
+
+/// An image placement on the terminal grid.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct ImagePlacement {
+    /// Unique image ID (auto-incremented within this parser).
+    pub id: u32,
+    /// Row where the image starts (0-indexed, screen-relative).
+    pub row: u16,
+    /// Column where the image starts (0-indexed).
+    pub col: u16,
+    /// Width in terminal cells.
+    pub width_cells: u16,
+    /// Height in terminal cells.
+    pub height_cells: u16,
+    /// Which protocol produced this image.
+    pub protocol: ImageProtocol,
+}
+
+/// The rows a command and its output cover on a pane's screen, as marked by
+/// the shell's OSC 133 integration.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct CommandBlock {
+    /// The command's id in `get_command_history`, for `rerun_command`.
+    pub id: u64,
+    /// Row of the command's prompt (visible-area-relative, negative when it
+    /// has scrolled off the top).
+    pub start_row: i32,
+    /// Last row of the command's output, or of its command line when it
+    /// printed nothing; the cursor's row while it runs.
+    pub end_row: i32,
+    /// `None` while it runs, or when the shell didn't say.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub exit_status: Option<i32>,
+    #[serde(default, skip_serializing_if = "is_false")]
+    pub running: bool,
+}
+
+/// Where tmux draws the status line (`status-position`).
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[serde(rename_all = "lowercase")]
+pub enum StatusPosition {
+    Top,
+    #[default]
+    Bottom,
+}
+
+/// The status line as styled cells, split where the bar is laid out.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct StatusSegments {
+    /// `status-left`, clipped to `status-left-length`
+    pub left: TerminalLine,
+    /// The window list, in window order
+    pub windows: Vec<StatusWindow>,
+    /// `status-right`, clipped to `status-right-length`
+    pub right: TerminalLine,
+}
+
+/// One window's entry in the status line's window list.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct StatusWindow {
+    /// Window ID (e.g., "@1")
+    pub window_id: String,
+    /// Whether this is the current window (drawn with
+    /// `window-status-current-format`)
+    pub active: bool,
+    pub cells: TerminalLine,
+}
+
+/// The session group a session belongs to.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct SessionGroup {
+    /// Group name (the name of the session the group was made from)
+    pub name: String,
+    /// Every session in the group, this one included
+    pub sessions: Vec<String>,
+}
+
+/// What the session's `SSH_AUTH_SOCK` points at.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[serde(rename_all = "lowercase")]
+pub enum SshAgentStatus {
+    /// The session has no `SSH_AUTH_SOCK`.
+    #[default]
+    Unset,
+    /// An agent answers on it.
+    Live,
+    /// Nothing answers: the connection that forwarded it is gone.
+    Dead,
+}
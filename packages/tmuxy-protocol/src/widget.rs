@@ -0,0 +1,134 @@
+//! What a widget pane shows, and its encoding in the `@tmuxy-widget` pane
+//! option.
+
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+
+/// What a widget pane shows: its type, the source it renders, and any
+/// widget-specific options.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct WidgetSpec {
+    /// Widget type, matching a registered launcher and frontend component.
+    pub kind: String,
+    /// What the widget renders: a path, a URL, or `-` for stdin.
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub source: String,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub options: BTreeMap<String, String>,
+}
+
+impl WidgetSpec {
+    pub fn new(kind: impl Into<String>, source: impl Into<String>) -> Self {
+        Self {
+            kind: kind.into(),
+            source: source.into(),
+            options: BTreeMap::new(),
+        }
+    }
+
+    /// The `@tmuxy-widget` value: `kind;source=…;key=value…`.
+    ///
+    /// Separators inside values are percent-encoded, and so are commas:
+    /// the option is one field of the comma-separated `list-panes` format,
+    /// where a raw comma would shift every field after it.
+    pub fn encode(&self) -> String {
+        let mut out = escape(&self.kind);
+        if !self.source.is_empty() {
+            out.push_str(";source=");
+            out.push_str(&escape(&self.source));
+        }
+        for (key, value) in &self.options {
+            out.push(';');
+            out.push_str(&escape(key));
+            out.push('=');
+            out.push_str(&escape(value));
+        }
+        out
+    }
+
+    /// Parse an `@tmuxy-widget` value; `None` for an empty (unset) option.
+    pub fn decode(value: &str) -> Option<Self> {
+        let mut parts = value.split(';');
+        let kind = unescape(parts.next()?);
+        if kind.is_empty() {
+            return None;
+        }
+        let mut spec = Self::new(kind, String::new());
+        for part in parts {
+            let (key, value) = part.split_once('=').unwrap_or((part, ""));
+            let (key, value) = (unescape(key), unescape(value));
+            if key == "source" {
+                spec.source = value;
+            } else if !key.is_empty() {
+                spec.options.insert(key, value);
+            }
+        }
+        Some(spec)
+    }
+}
+
+fn escape(value: &str) -> String {
+    let mut out = String::with_capacity(value.len());
+    for c in value.chars() {
+        match c {
+            '%' | ';' | '=' | ',' | '\n' | '\r' => out.push_str(&format!("%{:02X}", c as u32)),
+            _ => out.push(c),
+        }
+    }
+    out
+}
+
+fn unescape(value: &str) -> String {
+    let bytes = value.as_bytes();
+    let mut out = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        let hex = bytes
+            .get(i + 1..i + 3)
+            .and_then(|h| std::str::from_utf8(h).ok())
+            .and_then(|h| u8::from_str_radix(h, 16).ok());
+        match (bytes[i], hex) {
+            (b'%', Some(byte)) => {
+                out.push(byte);
+                i += 3;
+            }
+            (byte, _) => {
+                out.push(byte);
+                i += 1;
+            }
+        }
+    }
+    String::from_utf8_lossy(&out).into_owned()
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used, clippy::expect_used)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn spec_round_trips_through_the_user_option() {
+        let mut spec = WidgetSpec::new("markdown", "/notes/a;b=c,d%.md");
+        spec.options.insert("theme".into(), "dark\nmode".into());
+        let encoded = spec.encode();
+        assert!(!encoded.contains(','), "{encoded}");
+        assert_eq!(
+            encoded,
+            "markdown;source=/notes/a%3Bb%3Dc%2Cd%25.md;theme=dark%0Amode"
+        );
+        assert_eq!(WidgetSpec::decode(&encoded), Some(spec));
+
+        assert_eq!(WidgetSpec::decode(""), None);
+        assert_eq!(
+            WidgetSpec::decode("image"),
+            Some(WidgetSpec::new("image", ""))
+        );
+        // A stray `%` that isn't an escape is kept as-is.
+        assert_eq!(
+            WidgetSpec::decode("image;source=100%").unwrap().source,
+            "100%"
+        );
+    }
+}
@@ -0,0 +1,279 @@
+//! Admin API under `/api/admin`: inspect sessions and their clients, drop a
+//! client, force a full-state resync, stop a session's monitor, and mint or
+//! revoke scoped tokens (see [`crate::tokens`]).
+//!
+//! The routes sit behind the server's Basic auth like every other route, and
+//! answer 403 when the server runs without a password: an admin API nobody
+//! has to log into would let any page that can reach the port kick users.
+
+use axum::{
+    body::Body,
+    extract::{Path, State},
+    http::{Request, StatusCode},
+    middleware::Next,
+    response::{IntoResponse, Response},
+    routing::{delete, get, post},
+    Json, Router,
+};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
+use tmuxy_core::control_mode::MonitorCommand;
+use tracing::info;
+
+use crate::state::{AppState, MonitorRestarts, SessionConnections};
+use crate::tokens::Scope;
+
+/// One connected client, as listed by `GET /api/admin/sessions`.
+#[derive(Debug, Serialize, PartialEq)]
+pub struct ClientSummary {
+    pub id: u64,
+    /// Viewport the client last reported; `None` before its first resize.
+    pub cols: Option<u32>,
+    pub rows: Option<u32>,
+}
+
+/// One session, as listed by `GET /api/admin/sessions`.
+#[derive(Debug, Serialize, PartialEq)]
+pub struct SessionSummary {
+    pub session: String,
+    pub clients: Vec<ClientSummary>,
+    /// Size tmux was last resized to: the smallest client viewport.
+    pub size: Option<(u32, u32)>,
+    pub monitor_running: bool,
+    /// The monitor is detached from tmux until a client connects.
+    pub hibernating: bool,
+    /// Messages queued in the session broadcast for its slowest client.
+    pub queue_depth: usize,
+    /// The monitor's reconnects and backoff.
+    pub restarts: MonitorRestarts,
+}
+
+/// Admin routes; `password_set` says whether the server runs with Basic auth.
+pub fn routes(password_set: bool) -> Router<Arc<AppState>> {
+    Router::new()
+        .route("/api/admin/sessions", get(list_sessions))
+        .route("/api/admin/clients/{conn_id}", delete(disconnect_client))
+        .route("/api/admin/sessions/{session}/resync", post(resync_session))
+        .route("/api/admin/sessions/{session}/stop", post(stop_session))
+        .route("/api/admin/tokens", get(list_tokens).post(mint_token))
+        .route("/api/admin/tokens/{id}", delete(revoke_token))
+        .layer(axum::middleware::from_fn_with_state(
+            password_set,
+            require_password,
+        ))
+}
+
+async fn require_password(
+    State(password_set): State<bool>,
+    req: Request<Body>,
+    next: Next,
+) -> Response {
+    if password_set {
+        next.run(req).await
+    } else {
+        error_response(
+            StatusCode::FORBIDDEN,
+            "the admin API needs a server password (--password or TMUXY_PASSWORD)",
+        )
+    }
+}
+
+fn error_response(status: StatusCode, message: &str) -> Response {
+    (status, Json(serde_json::json!({ "error": message }))).into_response()
+}
+
+/// Every session with its clients, sorted by name.
+pub fn summarize(sessions: &HashMap<String, SessionConnections>) -> Vec<SessionSummary> {
+    let mut summaries: Vec<SessionSummary> = sessions
+        .iter()
+        .map(|(name, conns)| SessionSummary {
+            session: name.clone(),
+            clients: conns
+                .connections
+                .iter()
+                .map(|&id| {
+                    let size = conns.client_sizes.get(&id);
+                    ClientSummary {
+                        id,
+                        cols: size.map(|s| s.0),
+                        rows: size.map(|s| s.1),
+                    }
+                })
+                .collect(),
+            size: conns.last_resize,
+            monitor_running: conns
+                .monitor_handle
+                .as_ref()
+                .is_some_and(|h| !h.is_finished()),
+            hibernating: conns.hibernating,
+            queue_depth: conns.broadcast.tx.len(),
+            restarts: conns.restarts.clone(),
+        })
+        .collect();
+    summaries.sort_by(|a, b| a.session.cmp(&b.session));
+    summaries
+}
+
+async fn list_sessions(State(state): State<Arc<AppState>>) -> Response {
+    let sessions = state.sessions.read().await;
+    Json(summarize(&sessions)).into_response()
+}
+
+/// End a client's stream. The client gets a `fatal` event first, so it
+/// doesn't reconnect on its own; its cleanup runs as for any disconnect.
+async fn disconnect_client(
+    State(state): State<Arc<AppState>>,
+    Path(conn_id): Path<u64>,
+) -> Response {
+    let sessions = state.sessions.read().await;
+    let found = sessions
+        .iter()
+        .find_map(|(name, conns)| Some((name, conns.disconnects.get(&conn_id)?)));
+    match found {
+        Some((session, token)) => {
+            info!(conn_id, %session, "admin disconnected client");
+            token.cancel();
+            Json(serde_json::json!({ "session": session, "connection": conn_id })).into_response()
+        }
+        None => error_response(StatusCode::NOT_FOUND, "no such connection"),
+    }
+}
+
+/// Have the session's monitor broadcast its state in full, repainting every
+/// client from scratch.
+async fn resync_session(
+    State(state): State<Arc<AppState>>,
+    Path(session): Path<String>,
+) -> Response {
+    if !state.sessions.read().await.contains_key(&session) {
+        return error_response(StatusCode::NOT_FOUND, "no such session");
+    }
+    match crate::sse::send_to_monitor(&state, &session, MonitorCommand::ResendFull).await {
+        Ok(()) => {
+            info!(%session, "admin requested a full resync");
+            Json(serde_json::json!({ "session": session })).into_response()
+        }
+        Err(e) => error_response(StatusCode::CONFLICT, &e),
+    }
+}
+
+/// Disconnect every client of the session and shut its monitor down. The
+/// tmux session itself keeps running; the next client to connect starts a
+/// fresh monitor.
+async fn stop_session(State(state): State<Arc<AppState>>, Path(session): Path<String>) -> Response {
+    let Some(conns) = state.sessions.write().await.remove(&session) else {
+        return error_response(StatusCode::NOT_FOUND, "no such session");
+    };
+    info!(%session, clients = conns.connections.len(), "admin stopped session");
+    for token in conns.disconnects.values() {
+        token.cancel();
+    }
+    // A hibernating monitor has nothing to shut down; woken, it finds its
+    // session gone and stops.
+    conns.wake.notify_one();
+    if let Some(handle) = conns.monitor_handle {
+        crate::sse::shutdown_monitor(handle, conns.monitor_command_tx).await;
+    }
+    Json(serde_json::json!({
+        "session": session,
+        "disconnected": conns.connections.len(),
+    }))
+    .into_response()
+}
+
+/// Body of `POST /api/admin/tokens`: the scope's fields, plus a label and
+/// a lifetime.
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MintRequest {
+    #[serde(flatten)]
+    pub scope: Scope,
+    #[serde(default)]
+    pub label: Option<String>,
+    /// Seconds until the token expires; never when absent.
+    #[serde(default)]
+    pub ttl_secs: Option<u64>,
+}
+
+async fn list_tokens(State(state): State<Arc<AppState>>) -> Response {
+    Json(state.tokens.list()).into_response()
+}
+
+/// Mint a token. Its secret is in this response only.
+async fn mint_token(
+    State(state): State<Arc<AppState>>,
+    Json(request): Json<MintRequest>,
+) -> Response {
+    let ttl = request.ttl_secs.map(Duration::from_secs);
+    match state.tokens.mint(request.scope, request.label, ttl) {
+        Ok((token, info)) => {
+            info!(id = %info.id, scope = ?info.scope, "admin minted a token");
+            Json(serde_json::json!({ "token": token, "info": info })).into_response()
+        }
+        Err(e) => error_response(StatusCode::INTERNAL_SERVER_ERROR, &e),
+    }
+}
+
+/// Revoke a token. Streams it already opened stay open until they drop.
+async fn revoke_token(State(state): State<Arc<AppState>>, Path(id): Path<String>) -> Response {
+    if state.tokens.revoke(&id) {
+        info!(%id, "admin revoked a token");
+        Json(serde_json::json!({ "id": id })).into_response()
+    } else {
+        error_response(StatusCode::NOT_FOUND, "no such token")
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used, clippy::expect_used)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn summaries_list_clients_with_their_sizes() {
+        let mut work = SessionConnections::new();
+        work.connections = vec![3, 1];
+        work.client_sizes.insert(1, (120, 40));
+        work.last_resize = Some((120, 40));
+        let mut sessions = HashMap::new();
+        sessions.insert("work".to_string(), work);
+        sessions.insert("a".to_string(), SessionConnections::new());
+
+        let summaries = summarize(&sessions);
+        assert_eq!(summaries[0].session, "a");
+        assert!(summaries[0].clients.is_empty());
+        assert!(!summaries[0].monitor_running);
+        assert!(!summaries[0].hibernating);
+        assert_eq!(
+            summaries[1].clients,
+            vec![
+                ClientSummary {
+                    id: 3,
+                    cols: None,
+                    rows: None
+                },
+                ClientSummary {
+                    id: 1,
+                    cols: Some(120),
+                    rows: Some(40)
+                },
+            ]
+        );
+        assert_eq!(summaries[1].size, Some((120, 40)));
+    }
+
+    #[test]
+    fn summaries_serialize_sizes_as_pairs() {
+        let mut conns = SessionConnections::new();
+        conns.last_resize = Some((80, 24));
+        let mut sessions = HashMap::new();
+        sessions.insert("s".to_string(), conns);
+        let json = serde_json::to_value(summarize(&sessions)).unwrap();
+        assert_eq!(json[0]["size"], serde_json::json!([80, 24]));
+        assert_eq!(json[0]["queue_depth"], 0);
+        assert_eq!(json[0]["restarts"]["count"], 0);
+        assert_eq!(json[0]["restarts"]["last_lost"], serde_json::Value::Null);
+    }
+}
@@ -14,6 +14,10 @@
 //! `EventSource` SSE connection and `fetch` POSTs — carries the `Authorization`
 //! header automatically. No frontend change is required. The Tauri desktop app
 //! talks over local IPC, not HTTP, so it is unaffected.
+//!
+//! A request without the password may instead carry a scoped token (see
+//! [`crate::tokens`]); it gets through with its [`Scope`] as a request
+//! extension, for `/commands` to check.
 
 use axum::{
     body::Body,
@@ -25,13 +29,15 @@ use axum::{
 use base64::Engine as _;
 use std::sync::Arc;
 
+use crate::tokens::{self, SameSite, Scope, TokenStore};
+
 /// Realm shown in the browser's Basic-auth prompt.
 const REALM: &str = "tmuxy";
 
 /// Constant-time comparison so a wrong password can't be recovered by timing
 /// the response. The length check leaks only the password's length, which is
 /// not sensitive.
-fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+pub(crate) fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
     if a.len() != b.len() {
         return false;
     }
@@ -53,10 +59,17 @@ fn password_from_header(value: &str) -> Option<Vec<u8>> {
     Some(decoded[colon + 1..].to_vec())
 }
 
-/// Axum middleware enforcing HTTP Basic auth against `expected`.
+/// What [`require_basic_auth`] accepts: the password, or a live token.
+pub struct Credentials {
+    pub password: String,
+    pub tokens: Arc<TokenStore>,
+    pub same_site: SameSite,
+}
+
+/// Axum middleware enforcing HTTP Basic auth against `credentials`.
 pub async fn require_basic_auth(
-    State(expected): State<Arc<String>>,
-    req: Request<Body>,
+    State(credentials): State<Arc<Credentials>>,
+    mut req: Request<Body>,
     next: Next,
 ) -> Response {
     // Let CORS preflight through unauthenticated — an OPTIONS request carries
@@ -70,10 +83,31 @@ pub async fn require_basic_auth(
         .get(header::AUTHORIZATION)
         .and_then(|v| v.to_str().ok())
         .and_then(password_from_header)
-        .is_some_and(|pw| constant_time_eq(&pw, expected.as_bytes()));
+        .is_some_and(|pw| constant_time_eq(&pw, credentials.password.as_bytes()));
 
     if ok {
-        next.run(req).await
+        return next.run(req).await;
+    }
+    let token = tokens::presented(req.headers(), req.uri().query()).and_then(|(token, on_url)| {
+        let scope = credentials.tokens.verify(&token)?;
+        Some((token, on_url, scope))
+    });
+    if let Some((token, on_url, scope)) = token {
+        if !tokens::reachable(req.method(), req.uri().path(), &scope) {
+            return (
+                StatusCode::FORBIDDEN,
+                axum::Json(serde_json::json!({ "error": "not available to this token" })),
+            )
+                .into_response();
+        }
+        req.extensions_mut().insert::<Scope>(scope);
+        let mut response = next.run(req).await;
+        if on_url {
+            if let Ok(cookie) = credentials.same_site.cookie(&token).parse() {
+                response.headers_mut().append(header::SET_COOKIE, cookie);
+            }
+        }
+        response
     } else {
         (
             StatusCode::UNAUTHORIZED,
@@ -0,0 +1,361 @@
+//! `GET /api/file`: file contents for widget panes and the file picker.
+//!
+//! The content type is sniffed from the file's first bytes rather than
+//! trusted from its extension. Text, images and PDFs are served; anything
+//! else gets a 415 with a JSON error so the client can show "no preview"
+//! instead of rendering binary as text. Files are streamed by tower-http's
+//! `ServeFile`, which answers `Range` requests (the PDF viewer and `<video>`
+//! style consumers fetch in pieces) and conditional requests.
+//!
+//! `?thumbnail=<px>` returns a PNG no larger than `px` on either side for
+//! the images the server can decode (PNG, JPEG); other images are served
+//! as-is, since the browser scales them just as well.
+//!
+//! `GET /api/markdown` renders a markdown file to HTML with
+//! [`tmuxy_core::markdown::to_html`], the same pipeline as `tmuxy md`.
+//!
+//! Errors are `{"error": <message>, "code": <code>}` with `code` one of
+//! `not_found`, `forbidden`, `not_a_file`, `unsupported_type`,
+//! `no_thumbnail` or `read_failed`.
+
+use axum::{
+    body::Body,
+    extract::Query,
+    http::{header, HeaderValue, Request, StatusCode},
+    response::Response,
+};
+use std::io::Read as _;
+use std::path::{Path, PathBuf};
+use tower::ServiceExt as _;
+use tower_http::services::ServeFile;
+
+use crate::state::{build_response, json_response};
+
+/// Bytes read to decide a file's type.
+const SNIFF_LEN: usize = 512;
+
+/// Bounds on the `thumbnail` edge length, in pixels.
+const THUMBNAIL_MIN: u32 = 16;
+const THUMBNAIL_MAX: u32 = 1024;
+
+#[derive(Debug, serde::Deserialize)]
+pub struct MarkdownQuery {
+    path: String,
+}
+
+#[derive(Debug, serde::Deserialize)]
+pub struct FileQuery {
+    path: String,
+    /// Longest edge of the thumbnail to return instead of the file.
+    thumbnail: Option<u32>,
+}
+
+/// The content type of a file from its first bytes, or `None` for binary
+/// formats the viewers can't show. `path` only tells SVG apart from other
+/// text.
+pub fn sniff(path: &Path, head: &[u8]) -> Option<&'static str> {
+    const SIGNATURES: &[(&[u8], &str)] = &[
+        (b"%PDF-", "application/pdf"),
+        (b"\x89PNG\r\n\x1a\n", "image/png"),
+        (b"\xff\xd8\xff", "image/jpeg"),
+        (b"GIF87a", "image/gif"),
+        (b"GIF89a", "image/gif"),
+        (b"BM", "image/bmp"),
+        (b"\x00\x00\x01\x00", "image/x-icon"),
+    ];
+    if let Some((_, mime)) = SIGNATURES.iter().find(|(magic, _)| head.starts_with(magic)) {
+        return Some(mime);
+    }
+    if head.len() >= 12 && head.starts_with(b"RIFF") && &head[8..12] == b"WEBP" {
+        return Some("image/webp");
+    }
+    if !is_text(head) {
+        return None;
+    }
+    let svg = path
+        .extension()
+        .and_then(|e| e.to_str())
+        .is_some_and(|e| e.eq_ignore_ascii_case("svg"));
+    Some(if svg {
+        "image/svg+xml"
+    } else {
+        "text/plain; charset=utf-8"
+    })
+}
+
+/// UTF-8 without NULs. A multi-byte character cut off by the end of the
+/// sniffed prefix doesn't count against it.
+fn is_text(head: &[u8]) -> bool {
+    if head.contains(&0) {
+        return false;
+    }
+    match std::str::from_utf8(head) {
+        Ok(_) => true,
+        Err(e) => e.error_len().is_none(),
+    }
+}
+
+fn error_response(status: StatusCode, code: &str, message: impl std::fmt::Display) -> Response {
+    json_response(
+        status,
+        &serde_json::json!({ "error": message.to_string(), "code": code }),
+    )
+}
+
+fn io_error_response(e: &std::io::Error) -> Response {
+    match e.kind() {
+        std::io::ErrorKind::NotFound => error_response(StatusCode::NOT_FOUND, "not_found", e),
+        std::io::ErrorKind::PermissionDenied => {
+            error_response(StatusCode::FORBIDDEN, "forbidden", e)
+        }
+        _ => error_response(StatusCode::INTERNAL_SERVER_ERROR, "read_failed", e),
+    }
+}
+
+/// The first [`SNIFF_LEN`] bytes of the file, `None` when it isn't a
+/// regular file.
+fn read_head(path: &Path) -> std::io::Result<Option<Vec<u8>>> {
+    if !std::fs::metadata(path)?.is_file() {
+        return Ok(None);
+    }
+    let mut head = Vec::with_capacity(SNIFF_LEN);
+    std::fs::File::open(path)?
+        .take(SNIFF_LEN as u64)
+        .read_to_end(&mut head)?;
+    Ok(Some(head))
+}
+
+/// A PNG of the image at `path` scaled to fit `size`×`size`.
+fn thumbnail(path: &Path, size: u32) -> Result<Vec<u8>, String> {
+    let image = image::ImageReader::open(path)
+        .map_err(|e| e.to_string())?
+        .with_guessed_format()
+        .map_err(|e| e.to_string())?
+        .decode()
+        .map_err(|e| e.to_string())?;
+    let mut png = std::io::Cursor::new(Vec::new());
+    image
+        .thumbnail(size, size)
+        .write_to(&mut png, image::ImageFormat::Png)
+        .map_err(|e| e.to_string())?;
+    Ok(png.into_inner())
+}
+
+pub async fn file_handler(Query(query): Query<FileQuery>, request: Request<Body>) -> Response {
+    let path = PathBuf::from(&query.path);
+    let reader = path.clone();
+    let head = match tokio::task::spawn_blocking(move || read_head(&reader)).await {
+        Ok(Ok(Some(head))) => head,
+        Ok(Ok(None)) => {
+            return error_response(
+                StatusCode::BAD_REQUEST,
+                "not_a_file",
+                format!("{} is not a regular file", path.display()),
+            )
+        }
+        Ok(Err(e)) => return io_error_response(&e),
+        Err(e) => return error_response(StatusCode::INTERNAL_SERVER_ERROR, "read_failed", e),
+    };
+    let Some(mime) = sniff(&path, &head) else {
+        return error_response(
+            StatusCode::UNSUPPORTED_MEDIA_TYPE,
+            "unsupported_type",
+            format!("{} is a binary file with no preview", path.display()),
+        );
+    };
+
+    if let Some(size) = query.thumbnail {
+        if !mime.starts_with("image/") {
+            return error_response(
+                StatusCode::UNSUPPORTED_MEDIA_TYPE,
+                "no_thumbnail",
+                format!("no thumbnail for {mime}"),
+            );
+        }
+        if matches!(mime, "image/png" | "image/jpeg") {
+            let size = size.clamp(THUMBNAIL_MIN, THUMBNAIL_MAX);
+            return match tokio::task::spawn_blocking(move || thumbnail(&path, size)).await {
+                Ok(Ok(png)) => build_response(StatusCode::OK, "image/png", png),
+                Ok(Err(e)) => error_response(StatusCode::UNPROCESSABLE_ENTITY, "no_thumbnail", e),
+                Err(e) => error_response(StatusCode::INTERNAL_SERVER_ERROR, "read_failed", e),
+            };
+        }
+    }
+
+    let response = match ServeFile::new(&path).oneshot(request).await {
+        Ok(response) => response,
+        Err(never) => match never {},
+    };
+    let mut response = response.map(Body::new);
+    // ServeFile guesses from the extension; the sniffed type wins.
+    if response.status().is_success() {
+        response
+            .headers_mut()
+            .insert(header::CONTENT_TYPE, HeaderValue::from_static(mime));
+    }
+    response
+}
+
+pub async fn markdown_handler(Query(query): Query<MarkdownQuery>) -> Response {
+    let path = PathBuf::from(&query.path);
+    let rendered = tokio::task::spawn_blocking(move || {
+        std::fs::read(&path).map(|bytes| {
+            String::from_utf8(bytes)
+                .map(|text| tmuxy_core::markdown::to_html(&text))
+                .map_err(|_| path)
+        })
+    })
+    .await;
+    match rendered {
+        Ok(Ok(Ok(html))) => build_response(StatusCode::OK, "text/html; charset=utf-8", html),
+        Ok(Ok(Err(path))) => error_response(
+            StatusCode::UNSUPPORTED_MEDIA_TYPE,
+            "unsupported_type",
+            format!("{} is not UTF-8 text", path.display()),
+        ),
+        Ok(Err(e)) if e.kind() == std::io::ErrorKind::IsADirectory => error_response(
+            StatusCode::BAD_REQUEST,
+            "not_a_file",
+            format!("{} is not a regular file", query.path),
+        ),
+        Ok(Err(e)) => io_error_response(&e),
+        Err(e) => error_response(StatusCode::INTERNAL_SERVER_ERROR, "read_failed", e),
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used, clippy::expect_used)]
+mod tests {
+    use super::*;
+    use axum::{routing::get, Router};
+
+    fn app() -> Router {
+        Router::new()
+            .route("/api/file", get(file_handler))
+            .route("/api/markdown", get(markdown_handler))
+    }
+
+    fn temp_file(name: &str, content: &[u8]) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("tmuxy-files-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join(name);
+        std::fs::write(&path, content).unwrap();
+        path
+    }
+
+    async fn get_file(query: &str, range: Option<&str>) -> Response {
+        let mut request = Request::get(format!("/api/file?{query}"));
+        if let Some(range) = range {
+            request = request.header(header::RANGE, range);
+        }
+        app()
+            .oneshot(request.body(Body::empty()).unwrap())
+            .await
+            .unwrap()
+    }
+
+    async fn body_bytes(response: Response) -> Vec<u8> {
+        axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap()
+            .to_vec()
+    }
+
+    fn content_type(response: &Response) -> &str {
+        response.headers()[header::CONTENT_TYPE].to_str().unwrap()
+    }
+
+    #[test]
+    fn sniffing_trusts_content_over_extension() {
+        let path = Path::new("/x/file.txt");
+        assert_eq!(sniff(path, b"%PDF-1.7\n"), Some("application/pdf"));
+        assert_eq!(
+            sniff(Path::new("/x/photo.png"), b"\xff\xd8\xff\xe0"),
+            Some("image/jpeg")
+        );
+        assert_eq!(
+            sniff(path, b"RIFF\x10\x00\x00\x00WEBPVP8 "),
+            Some("image/webp")
+        );
+        assert_eq!(
+            sniff(Path::new("/x/logo.svg"), b"<svg xmlns="),
+            Some("image/svg+xml")
+        );
+        assert_eq!(sniff(path, b""), Some("text/plain; charset=utf-8"));
+        // A multi-byte character cut by the sniff window is still text.
+        assert_eq!(sniff(path, b"caf\xc3"), Some("text/plain; charset=utf-8"));
+        assert_eq!(sniff(path, b"\x7fELF\x02\x01\x01\x00"), None);
+        assert_eq!(sniff(path, b"\xfe\xfe\xfe"), None);
+    }
+
+    #[tokio::test]
+    async fn text_and_ranges_are_served() {
+        let path = temp_file("notes.md", b"# hello world");
+        let query = format!("path={}", path.display());
+
+        let response = get_file(&query, None).await;
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(content_type(&response), "text/plain; charset=utf-8");
+        assert_eq!(body_bytes(response).await, b"# hello world");
+
+        let response = get_file(&query, Some("bytes=2-6")).await;
+        assert_eq!(response.status(), StatusCode::PARTIAL_CONTENT);
+        assert_eq!(content_type(&response), "text/plain; charset=utf-8");
+        assert_eq!(body_bytes(response).await, b"hello");
+    }
+
+    #[tokio::test]
+    async fn binaries_and_missing_files_get_structured_errors() {
+        let path = temp_file("blob.bin", b"\x7fELF\x00\x00\x00\x00");
+        let response = get_file(&format!("path={}", path.display()), None).await;
+        assert_eq!(response.status(), StatusCode::UNSUPPORTED_MEDIA_TYPE);
+        let json: serde_json::Value = serde_json::from_slice(&body_bytes(response).await).unwrap();
+        assert_eq!(json["code"], "unsupported_type");
+
+        let response = get_file("path=/nonexistent/tmuxy/file", None).await;
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+        let json: serde_json::Value = serde_json::from_slice(&body_bytes(response).await).unwrap();
+        assert_eq!(json["code"], "not_found");
+
+        let dir = path.parent().unwrap().display().to_string();
+        let response = get_file(&format!("path={dir}"), None).await;
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    }
+
+    #[tokio::test]
+    async fn markdown_renders_to_html() {
+        let path = temp_file("doc.md", b"# Doc\n\n```sh\nls\n```\n");
+        let response = app()
+            .oneshot(
+                Request::get(format!("/api/markdown?path={}", path.display()))
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(content_type(&response), "text/html; charset=utf-8");
+        let html = String::from_utf8(body_bytes(response).await).unwrap();
+        assert!(html.starts_with("<h1>Doc</h1>"));
+        assert!(html.contains("<code class=\"language-sh\">"));
+    }
+
+    #[tokio::test]
+    async fn thumbnails_fit_the_requested_size() {
+        let mut png = std::io::Cursor::new(Vec::new());
+        image::RgbImage::new(200, 100)
+            .write_to(&mut png, image::ImageFormat::Png)
+            .unwrap();
+        let path = temp_file("wide.png", &png.into_inner());
+
+        let response = get_file(&format!("path={}&thumbnail=50", path.display()), None).await;
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(content_type(&response), "image/png");
+        let thumb = image::load_from_memory(&body_bytes(response).await).unwrap();
+        assert_eq!((thumb.width(), thumb.height()), (50, 25));
+
+        let text = temp_file("plain.txt", b"hi");
+        let response = get_file(&format!("path={}&thumbnail=50", text.display()), None).await;
+        assert_eq!(response.status(), StatusCode::UNSUPPORTED_MEDIA_TYPE);
+    }
+}
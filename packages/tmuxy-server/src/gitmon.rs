@@ -0,0 +1,58 @@
+//! The refresher behind `tmuxy widget git`: the status of the repository a
+//! pane sits in, for a compact repo dashboard.
+//!
+//! Like the sysmon sampler it runs in the widget's pane and writes one text
+//! line per refresh plus the status as JSON on the widget data channel
+//! ([`WIDGET_DATA_OSC`](tmuxy_core::control_mode::WIDGET_DATA_OSC)); a
+//! directory outside any repository sends `null`. It refreshes every
+//! interval, and right away when the repository's index or HEAD changes
+//! (staging, committing, switching branches), which it checks twice a second.
+
+use std::io::Write as _;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant, SystemTime};
+use tmuxy_core::control_mode::WIDGET_DATA_OSC;
+use tmuxy_core::git_status::{git_status, watched_paths};
+
+/// How often the index and HEAD are checked for changes.
+const WATCH_TICK: Duration = Duration::from_millis(500);
+
+fn modified_times(paths: &[PathBuf]) -> Vec<Option<SystemTime>> {
+    paths
+        .iter()
+        .map(|path| std::fs::metadata(path).and_then(|m| m.modified()).ok())
+        .collect()
+}
+
+/// Refresh `dir`'s status every `interval` until stdout goes away (the
+/// widget was closed).
+pub fn run(dir: &Path, interval: Duration) -> std::io::Result<()> {
+    let mut out = std::io::stdout().lock();
+    let mut watched = Vec::new();
+    let mut stamps = Vec::new();
+    let mut last_refresh: Option<Instant> = None;
+    loop {
+        let changed = modified_times(&watched) != stamps;
+        if changed || last_refresh.is_none_or(|at| at.elapsed() >= interval) {
+            let status = git_status(dir).map_err(std::io::Error::other)?;
+            // The repository may have appeared, moved or gone since the
+            // last refresh, so look its files up again.
+            watched = if status.is_some() {
+                watched_paths(dir)
+            } else {
+                Vec::new()
+            };
+            stamps = modified_times(&watched);
+            last_refresh = Some(Instant::now());
+
+            let json = serde_json::to_string(&status)?;
+            let line = match &status {
+                Some(status) => status.render(),
+                None => format!("{}: not a git repository", dir.display()),
+            };
+            writeln!(out, "\x1b]{WIDGET_DATA_OSC};{json}\x07{line}")?;
+            out.flush()?;
+        }
+        std::thread::sleep(WATCH_TICK);
+    }
+}
@@ -0,0 +1,231 @@
+//! Operator hooks: shell commands the server runs on lifecycle events.
+//!
+//! A hook is a global user option in the tmuxy config, named for its event:
+//!
+//! ```tmux
+//! set -g @tmuxy-hook-on-client-connect 'logger -t tmuxy "client $TMUXY_CLIENT_ID joined $TMUXY_SESSION"'
+//! set -g @tmuxy-hook-on-pane-exit '~/bin/notify-chat "$TMUXY_PANE_COMMAND exited in $TMUXY_WINDOW_NAME"'
+//! ```
+//!
+//! It is read from the session's tmux server each time its event fires, so
+//! a `source-file` or `set -g` takes effect with the next event, and an
+//! event with no hook costs one `show-options`. A session fronted on another
+//! host over SSH takes its hooks from this machine's tmux server instead:
+//! the command runs here, so the remote host's config must not choose it
+//! (see [`hook_source`]). The command runs under
+//! `sh -c` on the server's machine, in the background, with the event's
+//! context in `TMUXY_*` environment variables; its output is dropped and a
+//! failure only logged. A hook still running after [`HOOK_TIMEOUT`] is
+//! killed.
+
+use std::process::Stdio;
+use std::sync::Arc;
+use std::time::Duration;
+
+use tmuxy_core::session::TmuxTransport;
+use tracing::{debug, warn};
+
+use crate::state::AppState;
+
+/// How long a hook may run before it is killed.
+pub const HOOK_TIMEOUT: Duration = Duration::from_secs(60);
+
+/// The lifecycle events a hook can run on.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HookEvent {
+    /// A client opened an event stream for the session.
+    ClientConnect,
+    /// A client's event stream closed.
+    ClientDisconnect,
+    /// The server made the session: a client asked for one that didn't
+    /// exist, or `create_from_template` built it.
+    SessionCreate,
+    /// A pane exited or was killed.
+    PaneExit,
+    /// A pane rang the bell (unless `TMUXY_BELL=ignore`); rate limited per
+    /// pane like the bell itself.
+    Bell,
+}
+
+impl HookEvent {
+    pub const ALL: [HookEvent; 5] = [
+        HookEvent::ClientConnect,
+        HookEvent::ClientDisconnect,
+        HookEvent::SessionCreate,
+        HookEvent::PaneExit,
+        HookEvent::Bell,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            HookEvent::ClientConnect => "on-client-connect",
+            HookEvent::ClientDisconnect => "on-client-disconnect",
+            HookEvent::SessionCreate => "on-session-create",
+            HookEvent::PaneExit => "on-pane-exit",
+            HookEvent::Bell => "on-bell",
+        }
+    }
+
+    /// The option holding the hook's command.
+    pub fn option(self) -> String {
+        format!("@tmuxy-hook-{}", self.name())
+    }
+}
+
+/// One firing of a hook: its event, session, and the rest of its context.
+#[derive(Debug, Clone)]
+pub struct Hook {
+    pub event: HookEvent,
+    pub session: String,
+    env: Vec<(&'static str, String)>,
+}
+
+impl Hook {
+    pub fn new(event: HookEvent, session: &str) -> Self {
+        Self {
+            event,
+            session: session.to_string(),
+            env: Vec::new(),
+        }
+    }
+
+    /// Add `TMUXY_<name>` to the hook's environment.
+    pub fn with(mut self, name: &'static str, value: impl ToString) -> Self {
+        self.env.push((name, value.to_string()));
+        self
+    }
+
+    /// Every variable the hook runs with: `TMUXY_HOOK`, `TMUXY_SESSION`,
+    /// then its own.
+    pub fn env(&self) -> Vec<(String, String)> {
+        [
+            ("HOOK", self.event.name().to_string()),
+            ("SESSION", self.session.clone()),
+        ]
+        .into_iter()
+        .chain(self.env.iter().map(|(name, value)| (*name, value.clone())))
+        .map(|(name, value)| (format!("TMUXY_{name}"), value))
+        .collect()
+    }
+}
+
+/// Run `hook`'s command, if the session's tmux has one, in the background.
+pub fn fire(state: &Arc<AppState>, hook: Hook) {
+    let state = Arc::clone(state);
+    tokio::spawn(async move {
+        let transport = hook_source(state.session_transport(&hook.session).await);
+        let Some(command) = configured(&transport, hook.event).await else {
+            return;
+        };
+        if let Err(error) = run(&command, &hook).await {
+            warn!(hook = hook.event.name(), session = %hook.session, %error, "hook failed");
+        }
+    });
+}
+
+/// The tmux server a session's hooks are read from: its own when that runs
+/// on this machine, else this machine's on the same socket. Hooks run locally,
+/// so a remote host's tmux config never picks a command for this machine.
+fn hook_source(transport: TmuxTransport) -> TmuxTransport {
+    TmuxTransport {
+        ssh: None,
+        ..transport
+    }
+}
+
+/// The command set for `event`, if any.
+async fn configured(transport: &TmuxTransport, event: HookEvent) -> Option<String> {
+    let mut show = tokio::process::Command::from(transport.command());
+    let output = show
+        .args(["show-options", "-gqv", &event.option()])
+        .stdin(Stdio::null())
+        .output()
+        .await
+        .ok()?;
+    let command = String::from_utf8_lossy(&output.stdout).trim().to_string();
+    (output.status.success() && !command.is_empty()).then_some(command)
+}
+
+/// Run `command` with `hook`'s environment and wait for it, up to
+/// [`HOOK_TIMEOUT`].
+pub async fn run(command: &str, hook: &Hook) -> Result<(), String> {
+    debug!(hook = hook.event.name(), session = %hook.session, %command, "running hook");
+    let child = tokio::process::Command::new("sh")
+        .arg("-c")
+        .arg(command)
+        .envs(hook.env())
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::piped())
+        .kill_on_drop(true)
+        .spawn()
+        .map_err(|e| e.to_string())?;
+    let output = tokio::time::timeout(HOOK_TIMEOUT, child.wait_with_output())
+        .await
+        .map_err(|_| format!("still running after {}s, killed", HOOK_TIMEOUT.as_secs()))?
+        .map_err(|e| e.to_string())?;
+    if output.status.success() {
+        return Ok(());
+    }
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    Err(match stderr.trim().lines().last() {
+        Some(line) => format!("{}: {line}", output.status),
+        None => output.status.to_string(),
+    })
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used, clippy::expect_used)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn hooks_are_options_named_for_their_event() {
+        let names: Vec<String> = HookEvent::ALL.iter().map(|e| e.option()).collect();
+        assert_eq!(
+            names,
+            [
+                "@tmuxy-hook-on-client-connect",
+                "@tmuxy-hook-on-client-disconnect",
+                "@tmuxy-hook-on-session-create",
+                "@tmuxy-hook-on-pane-exit",
+                "@tmuxy-hook-on-bell",
+            ]
+        );
+    }
+
+    #[test]
+    fn remote_sessions_read_hooks_from_the_local_server() {
+        let remote = TmuxTransport {
+            socket: "tmuxy".to_string(),
+            ssh: Some(vec!["me@box".to_string()]),
+        };
+        let source = hook_source(remote);
+        assert!(!source.is_remote());
+        assert_eq!(source.socket, "tmuxy");
+        assert_eq!(source.argv(false)[0], tmuxy_core::session::tmux_path());
+    }
+
+    #[tokio::test]
+    async fn hooks_run_with_their_context_in_the_environment() {
+        let hook = Hook::new(HookEvent::PaneExit, "main")
+            .with("PANE_ID", "%3")
+            .with("PANE_COMMAND", "make");
+        let dir = std::env::temp_dir().join(format!("tmuxy-hook-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let out = dir.join("env");
+        let command = format!(
+            r#"echo "$TMUXY_HOOK $TMUXY_SESSION $TMUXY_PANE_ID $TMUXY_PANE_COMMAND" > '{}'"#,
+            out.display()
+        );
+        run(&command, &hook).await.unwrap();
+        assert_eq!(
+            std::fs::read_to_string(&out).unwrap(),
+            "on-pane-exit main %3 make\n"
+        );
+        std::fs::remove_dir_all(&dir).unwrap();
+
+        let error = run("echo nope >&2; exit 3", &hook).await.unwrap_err();
+        assert!(error.ends_with(": nope"), "{error}");
+    }
+}
@@ -0,0 +1,221 @@
+//! Where the server listens: TCP addresses and Unix domain sockets.
+//!
+//! `--listen unix:/path/to/sock` serves the same routes (SSE, `/commands`,
+//! the API and the frontend) over a Unix domain socket, so local GUI wrappers
+//! and reverse proxies can reach the server without a TCP port. The socket is
+//! created owner-only (0600) and removed when the server exits.
+
+use std::fmt;
+use std::net::SocketAddr;
+use std::path::PathBuf;
+use std::str::FromStr;
+use tokio_util::sync::CancellationToken;
+use tracing::{error, warn};
+
+/// An address given to `--listen`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Listen {
+    /// `IP:PORT`
+    Tcp(SocketAddr),
+    /// `unix:PATH`
+    Unix(PathBuf),
+}
+
+impl FromStr for Listen {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if let Some(path) = s.strip_prefix("unix:") {
+            if path.is_empty() {
+                return Err("unix: needs a socket path, e.g. unix:/tmp/tmuxy.sock".to_string());
+            }
+            return Ok(Self::Unix(PathBuf::from(path)));
+        }
+        s.parse()
+            .map(Self::Tcp)
+            .map_err(|_| format!("expected unix:PATH or IP:PORT, got '{s}'"))
+    }
+}
+
+impl fmt::Display for Listen {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Tcp(addr) => write!(f, "http://{addr}"),
+            Self::Unix(path) => write!(f, "unix:{}", path.display()),
+        }
+    }
+}
+
+/// A bound listener, ready to serve.
+pub(crate) enum Listener {
+    Tcp(tokio::net::TcpListener),
+    #[cfg(unix)]
+    Unix(tokio::net::UnixListener, PathBuf),
+}
+
+impl Listener {
+    /// Bind `listen`. TCP ports in use are retried up to `max_retries` times
+    /// (a restarting server may still hold the port); a Unix socket left
+    /// behind by a server that is gone is replaced.
+    pub(crate) async fn bind(listen: &Listen, max_retries: u32) -> std::io::Result<Self> {
+        match listen {
+            Listen::Tcp(addr) => bind_with_retry(*addr, max_retries).await.map(Self::Tcp),
+            #[cfg(unix)]
+            Listen::Unix(path) => bind_unix(path).map(|l| Self::Unix(l, path.clone())),
+            #[cfg(not(unix))]
+            Listen::Unix(_) => Err(std::io::Error::new(
+                std::io::ErrorKind::Unsupported,
+                "Unix domain sockets are not supported on this platform",
+            )),
+        }
+    }
+
+    /// Serve `app` until `shutdown` is cancelled.
+    pub(crate) async fn serve(self, app: axum::Router, shutdown: CancellationToken) {
+        let result = match self {
+            Self::Tcp(listener) => {
+                // Peer addresses for the per-IP command rate limit.
+                axum::serve(
+                    listener,
+                    app.into_make_service_with_connect_info::<SocketAddr>(),
+                )
+                .with_graceful_shutdown(shutdown.cancelled_owned())
+                .await
+            }
+            #[cfg(unix)]
+            Self::Unix(listener, path) => {
+                let result = axum::serve(listener, app)
+                    .with_graceful_shutdown(shutdown.cancelled_owned())
+                    .await;
+                std::fs::remove_file(&path).ok();
+                result
+            }
+        };
+        if let Err(e) = result {
+            error!(error = %e, "axum serve loop exited with error");
+        }
+    }
+}
+
+/// Bind `listens`, exiting with a message when one can't be bound.
+pub(crate) async fn bind_all(listens: &[Listen]) -> Vec<Listener> {
+    let mut listeners = Vec::with_capacity(listens.len());
+    for listen in listens {
+        match Listener::bind(listen, 5).await {
+            Ok(listener) => listeners.push(listener),
+            Err(e) => {
+                error!(%listen, error = %e, "failed to bind");
+                eprintln!("Failed to listen on {listen}: {e}");
+                std::process::exit(1);
+            }
+        }
+    }
+    listeners
+}
+
+/// Bind to addr, retrying up to `max_retries` times with 1s delay if port is in use.
+async fn bind_with_retry(
+    addr: SocketAddr,
+    max_retries: u32,
+) -> std::io::Result<tokio::net::TcpListener> {
+    let mut attempt = 0;
+    loop {
+        match tokio::net::TcpListener::bind(addr).await {
+            Ok(listener) => return Ok(listener),
+            Err(e) if attempt < max_retries => {
+                attempt += 1;
+                warn!(
+                    port = addr.port(),
+                    attempt,
+                    max_retries,
+                    error = %e,
+                    "port in use, retrying in 1s"
+                );
+                tokio::time::sleep(std::time::Duration::from_secs(1)).await;
+            }
+            Err(e) => return Err(e),
+        }
+    }
+}
+
+/// Bind a Unix socket at `path`, owner-only: whoever can connect gets the
+/// same shell access as over TCP.
+#[cfg(unix)]
+fn bind_unix(path: &std::path::Path) -> std::io::Result<tokio::net::UnixListener> {
+    use std::os::unix::fs::PermissionsExt;
+
+    if path.exists() {
+        if std::os::unix::net::UnixStream::connect(path).is_ok() {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::AddrInUse,
+                "another server is listening on this socket",
+            ));
+        }
+        // Left behind by a server that didn't exit cleanly.
+        std::fs::remove_file(path)?;
+    }
+    if let Some(dir) = path.parent().filter(|d| !d.as_os_str().is_empty()) {
+        std::fs::create_dir_all(dir)?;
+    }
+    let listener = tokio::net::UnixListener::bind(path)?;
+    std::fs::set_permissions(path, std::fs::Permissions::from_mode(0o600))?;
+    Ok(listener)
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used, clippy::expect_used)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_unix_paths_and_socket_addresses() {
+        assert_eq!(
+            "unix:/run/tmuxy.sock".parse(),
+            Ok(Listen::Unix(PathBuf::from("/run/tmuxy.sock")))
+        );
+        assert_eq!(
+            "127.0.0.1:9000".parse(),
+            Ok(Listen::Tcp(SocketAddr::from(([127, 0, 0, 1], 9000))))
+        );
+        assert!("unix:".parse::<Listen>().is_err());
+        assert!("localhost".parse::<Listen>().is_err());
+        assert_eq!(
+            Listen::Unix(PathBuf::from("/run/tmuxy.sock")).to_string(),
+            "unix:/run/tmuxy.sock"
+        );
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn serves_http_over_a_unix_socket() {
+        use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+        let path = std::env::temp_dir().join(format!("tmuxy-listen-{}.sock", std::process::id()));
+        let listen = Listen::Unix(path.clone());
+        let listener = Listener::bind(&listen, 0).await.unwrap();
+        let mode = std::os::unix::fs::PermissionsExt::mode(
+            &std::fs::metadata(&path).unwrap().permissions(),
+        );
+        assert_eq!(mode & 0o777, 0o600);
+        // A live server's socket isn't taken over.
+        assert!(Listener::bind(&listen, 0).await.is_err());
+
+        let app = axum::Router::new().route("/api/health", axum::routing::get(|| async { "ok" }));
+        let shutdown = CancellationToken::new();
+        let server = tokio::spawn(listener.serve(app, shutdown.clone()));
+
+        let mut stream = tokio::net::UnixStream::connect(&path).await.unwrap();
+        stream
+            .write_all(b"GET /api/health HTTP/1.1\r\nHost: localhost\r\nConnection: close\r\n\r\n")
+            .await
+            .unwrap();
+        let mut response = String::new();
+        stream.read_to_string(&mut response).await.unwrap();
+        assert!(response.starts_with("HTTP/1.1 200"), "{response}");
+        assert!(response.ends_with("ok"), "{response}");
+
+        shutdown.cancel();
+        server.await.unwrap();
+        assert!(!path.exists());
+    }
+}
@@ -0,0 +1,298 @@
+//! Tracing setup shared by the standalone server and the desktop app.
+//!
+//! Output goes to stderr, as text by default or as one JSON object per line
+//! when `TMUXY_LOG_FORMAT=json`. The filter comes from `RUST_LOG` and can be
+//! replaced while running, through the `set_log_level` command or, on Unix,
+//! by sending the process SIGUSR1, which toggles debug logging for tmuxy's
+//! own crates.
+
+use serde_json::{Map, Value};
+use std::fmt;
+use std::sync::OnceLock;
+use tokio_util::sync::CancellationToken;
+use tracing::field::{Field, Visit};
+use tracing::{info, warn, Event, Subscriber};
+use tracing_subscriber::field::RecordFields;
+use tracing_subscriber::filter::LevelFilter;
+use tracing_subscriber::fmt::format::Writer;
+use tracing_subscriber::fmt::{FmtContext, FormatEvent, FormatFields, FormattedFields};
+use tracing_subscriber::layer::SubscriberExt;
+use tracing_subscriber::registry::LookupSpan;
+use tracing_subscriber::util::SubscriberInitExt;
+use tracing_subscriber::{reload, EnvFilter, Registry};
+
+/// Filter used when `RUST_LOG` is unset.
+const DEFAULT_FILTER: &str = "tmuxy_core=info,tmuxy_server=info,warn";
+
+/// Handle onto the installed filter, set by `init_logging`.
+static FILTER: OnceLock<reload::Handle<EnvFilter, Registry>> = OnceLock::new();
+
+/// Initialize the tracing subscriber for the server.
+///
+/// Called by both the standalone `tmuxy-server` binary and the combined
+/// `tmuxy server` CLI path in the Tauri app. Without this, `error!`/`warn!`
+/// logs (including the fatal dev-mode port-collision message) are silently
+/// dropped, leaving the server to exit with no diagnostic output.
+pub fn init_logging() {
+    use tracing_subscriber::fmt;
+
+    let filter =
+        EnvFilter::try_from_default_env().unwrap_or_else(|_| EnvFilter::new(DEFAULT_FILTER));
+    let (filter, handle) = reload::Layer::new(filter);
+    let registry = tracing_subscriber::registry().with(filter);
+    let installed = if std::env::var("TMUXY_LOG_FORMAT").is_ok_and(|f| f == "json") {
+        registry
+            .with(
+                fmt::layer()
+                    .event_format(JsonFormat)
+                    .fmt_fields(JsonFields)
+                    .with_writer(std::io::stderr),
+            )
+            .try_init()
+    } else {
+        registry
+            .with(
+                fmt::layer()
+                    .with_target(true)
+                    .with_thread_ids(false)
+                    .with_writer(std::io::stderr),
+            )
+            .try_init()
+    };
+    if installed.is_ok() {
+        let _ = FILTER.set(handle);
+    }
+}
+
+/// Expand a bare level (`debug`) to tmuxy's own crates, keeping everything
+/// else at `warn`; anything else is taken as `RUST_LOG` directives.
+fn expand_directives(directives: &str) -> String {
+    let directives = directives.trim();
+    match directives.parse::<LevelFilter>() {
+        Ok(level) => {
+            let level = level.to_string().to_lowercase();
+            format!("tmuxy_core={level},tmuxy_server={level},warn")
+        }
+        Err(_) => directives.to_string(),
+    }
+}
+
+/// The filter in effect, in `RUST_LOG` syntax.
+pub fn current_log_level() -> Option<String> {
+    FILTER.get()?.with_current(|f| f.to_string()).ok()
+}
+
+/// Replace the log filter; returns the one now in effect.
+pub fn set_log_level(directives: &str) -> Result<String, String> {
+    let handle = FILTER
+        .get()
+        .ok_or_else(|| "logging is not initialized".to_string())?;
+    let filter = EnvFilter::try_new(expand_directives(directives))
+        .map_err(|e| format!("invalid log filter '{directives}': {e}"))?;
+    handle.reload(filter).map_err(|e| e.to_string())?;
+    current_log_level().ok_or_else(|| "log filter is gone".to_string())
+}
+
+/// Toggle debug logging on every SIGUSR1, until `shutdown` fires. The second
+/// signal restores the filter the process had when this started.
+pub async fn toggle_debug_on_sigusr1(shutdown: CancellationToken) {
+    #[cfg(unix)]
+    {
+        use tokio::signal::unix::{signal, SignalKind};
+
+        let mut signals = match signal(SignalKind::user_defined1()) {
+            Ok(s) => s,
+            Err(e) => {
+                warn!(error = %e, "cannot listen for SIGUSR1");
+                return;
+            }
+        };
+        let Some(initial) = current_log_level() else {
+            return;
+        };
+        let mut debugging = false;
+        loop {
+            tokio::select! {
+                _ = signals.recv() => {
+                    debugging = !debugging;
+                    let target = if debugging { "debug" } else { initial.as_str() };
+                    match set_log_level(target) {
+                        Ok(level) => info!(%level, "log level toggled by SIGUSR1"),
+                        Err(e) => warn!(error = %e, "SIGUSR1 log level toggle failed"),
+                    }
+                }
+                _ = shutdown.cancelled() => break,
+            }
+        }
+    }
+    #[cfg(not(unix))]
+    shutdown.cancelled().await;
+}
+
+/// Collects an event's or span's fields into a JSON object.
+#[derive(Default)]
+struct JsonVisitor(Map<String, Value>);
+
+impl Visit for JsonVisitor {
+    fn record_str(&mut self, field: &Field, value: &str) {
+        self.0.insert(field.name().to_string(), value.into());
+    }
+    fn record_bool(&mut self, field: &Field, value: bool) {
+        self.0.insert(field.name().to_string(), value.into());
+    }
+    fn record_i64(&mut self, field: &Field, value: i64) {
+        self.0.insert(field.name().to_string(), value.into());
+    }
+    fn record_u64(&mut self, field: &Field, value: u64) {
+        self.0.insert(field.name().to_string(), value.into());
+    }
+    fn record_f64(&mut self, field: &Field, value: f64) {
+        self.0.insert(field.name().to_string(), value.into());
+    }
+    fn record_debug(&mut self, field: &Field, value: &dyn fmt::Debug) {
+        self.0
+            .insert(field.name().to_string(), format!("{value:?}").into());
+    }
+}
+
+/// Formats span fields as a JSON object, so `JsonFormat` can nest them.
+struct JsonFields;
+
+impl<'writer> FormatFields<'writer> for JsonFields {
+    fn format_fields<R: RecordFields>(
+        &self,
+        mut writer: Writer<'writer>,
+        fields: R,
+    ) -> fmt::Result {
+        let mut visitor = JsonVisitor::default();
+        fields.record(&mut visitor);
+        write!(writer, "{}", Value::Object(visitor.0))
+    }
+
+    fn add_fields(
+        &self,
+        current: &'writer mut FormattedFields<Self>,
+        fields: &tracing::span::Record<'_>,
+    ) -> fmt::Result {
+        let mut visitor = JsonVisitor(serde_json::from_str(&current.fields).unwrap_or_default());
+        fields.record(&mut visitor);
+        current.fields = Value::Object(visitor.0).to_string();
+        Ok(())
+    }
+}
+
+/// One JSON object per event: timestamp (Unix seconds), level, target, the
+/// event's fields and the spans it happened in, outermost first.
+struct JsonFormat;
+
+impl<S, N> FormatEvent<S, N> for JsonFormat
+where
+    S: Subscriber + for<'a> LookupSpan<'a>,
+    N: for<'a> FormatFields<'a> + 'static,
+{
+    fn format_event(
+        &self,
+        ctx: &FmtContext<'_, S, N>,
+        mut writer: Writer<'_>,
+        event: &Event<'_>,
+    ) -> fmt::Result {
+        let meta = event.metadata();
+        let mut fields = JsonVisitor::default();
+        event.record(&mut fields);
+
+        let mut spans = Vec::new();
+        if let Some(scope) = ctx.event_scope() {
+            for span in scope.from_root() {
+                let mut entry = span
+                    .extensions()
+                    .get::<FormattedFields<N>>()
+                    .and_then(|f| serde_json::from_str::<Map<String, Value>>(&f.fields).ok())
+                    .unwrap_or_default();
+                entry.insert("name".to_string(), span.name().into());
+                spans.push(Value::Object(entry));
+            }
+        }
+
+        let timestamp = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map(|d| d.as_secs_f64())
+            .unwrap_or_default();
+        let mut line = Map::new();
+        line.insert("timestamp".to_string(), timestamp.into());
+        line.insert("level".to_string(), meta.level().as_str().into());
+        line.insert("target".to_string(), meta.target().into());
+        line.insert("fields".to_string(), Value::Object(fields.0));
+        if !spans.is_empty() {
+            line.insert("spans".to_string(), Value::Array(spans));
+        }
+        writeln!(writer, "{}", Value::Object(line))
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used, clippy::expect_used)]
+mod tests {
+    use super::*;
+    use std::sync::{Arc, Mutex};
+    use tracing_subscriber::fmt::MakeWriter;
+
+    #[derive(Clone, Default)]
+    struct Captured(Arc<Mutex<Vec<u8>>>);
+
+    impl std::io::Write for Captured {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            self.0.lock().unwrap().extend_from_slice(buf);
+            Ok(buf.len())
+        }
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+
+    impl<'a> MakeWriter<'a> for Captured {
+        type Writer = Captured;
+        fn make_writer(&'a self) -> Self::Writer {
+            self.clone()
+        }
+    }
+
+    #[test]
+    fn bare_levels_cover_tmuxy_crates_only() {
+        assert_eq!(
+            expand_directives(" DEBUG "),
+            "tmuxy_core=debug,tmuxy_server=debug,warn"
+        );
+        assert_eq!(expand_directives("tmuxy_core=trace"), "tmuxy_core=trace");
+    }
+
+    #[test]
+    fn json_lines_carry_fields_and_spans() {
+        let out = Captured::default();
+        let subscriber = tracing_subscriber::registry().with(
+            tracing_subscriber::fmt::layer()
+                .event_format(JsonFormat)
+                .fmt_fields(JsonFields)
+                .with_writer(out.clone()),
+        );
+        tracing::subscriber::with_default(subscriber, || {
+            let session = tracing::info_span!("session", session = "work");
+            let _session = session.enter();
+            let client =
+                tracing::info_span!("sse_client", conn_id = 7u64, ready = tracing::field::Empty);
+            client.record("ready", true);
+            let _client = client.enter();
+            tracing::warn!(pane = "%1", lines = 3, "capture failed");
+        });
+
+        let text = String::from_utf8(out.0.lock().unwrap().clone()).unwrap();
+        let line: Value = serde_json::from_str(text.trim()).unwrap();
+        assert_eq!(line["level"], "WARN");
+        assert_eq!(line["fields"]["message"], "capture failed");
+        assert_eq!(line["fields"]["pane"], "%1");
+        assert_eq!(line["fields"]["lines"], 3);
+        assert_eq!(line["spans"][0]["name"], "session");
+        assert_eq!(line["spans"][0]["session"], "work");
+        assert_eq!(line["spans"][1]["conn_id"], 7);
+        assert_eq!(line["spans"][1]["ready"], true);
+    }
+}
@@ -11,10 +11,9 @@ struct Cli {
     server: server::ServerArgs,
 }
 
-#[tokio::main]
-async fn main() {
+fn main() {
     tmuxy_server::init_logging();
 
     let cli = Cli::parse();
-    server::run(cli.server).await;
+    server::run(cli.server);
 }
@@ -0,0 +1,337 @@
+//! Server metrics, exported at `/api/metrics` in the Prometheus text format
+//! and summarised at `/api/health`.
+//!
+//! Counters are process-wide and fed by every session's monitor through
+//! `StateEmitter::record_metric`; per-session gauges (clients, broadcast
+//! queue depth) are read from `AppState` at scrape time. Rates, such as
+//! control-mode events per second, are left to the scraper (`rate()`).
+
+use std::fmt::Write as _;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::{Duration, Instant};
+use tmuxy_core::control_mode::MonitorMetric;
+
+/// Upper bounds, in seconds, of the capture-pane latency histogram buckets.
+const CAPTURE_LATENCY_BUCKETS: [f64; 10] =
+    [0.001, 0.0025, 0.005, 0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1.0];
+
+/// A cumulative latency histogram in Prometheus' shape.
+struct LatencyHistogram {
+    /// Observations at or below each bound of `CAPTURE_LATENCY_BUCKETS`.
+    buckets: [AtomicU64; CAPTURE_LATENCY_BUCKETS.len()],
+    count: AtomicU64,
+    sum_micros: AtomicU64,
+}
+
+impl LatencyHistogram {
+    fn new() -> Self {
+        Self {
+            buckets: std::array::from_fn(|_| AtomicU64::new(0)),
+            count: AtomicU64::new(0),
+            sum_micros: AtomicU64::new(0),
+        }
+    }
+
+    fn observe(&self, latency: Duration) {
+        let secs = latency.as_secs_f64();
+        for (bound, bucket) in CAPTURE_LATENCY_BUCKETS.iter().zip(&self.buckets) {
+            if secs <= *bound {
+                bucket.fetch_add(1, Ordering::Relaxed);
+            }
+        }
+        self.count.fetch_add(1, Ordering::Relaxed);
+        self.sum_micros
+            .fetch_add(latency.as_micros() as u64, Ordering::Relaxed);
+    }
+}
+
+/// Per-session gauges, gathered from `AppState` when rendering.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SessionGauges {
+    pub session: String,
+    /// Connected SSE clients.
+    pub clients: usize,
+    /// Messages queued in the session broadcast for its slowest receiver.
+    pub queue_depth: usize,
+    /// Whether the session's monitor task is still running.
+    pub monitor_running: bool,
+}
+
+pub struct Metrics {
+    started_at: Instant,
+    control_events: AtomicU64,
+    state_updates: AtomicU64,
+    tmux_commands: AtomicU64,
+    tmux_command_errors: AtomicU64,
+    commands_refused: AtomicU64,
+    capture_latency: LatencyHistogram,
+}
+
+impl Default for Metrics {
+    fn default() -> Self {
+        Self {
+            started_at: Instant::now(),
+            control_events: AtomicU64::new(0),
+            state_updates: AtomicU64::new(0),
+            tmux_commands: AtomicU64::new(0),
+            tmux_command_errors: AtomicU64::new(0),
+            commands_refused: AtomicU64::new(0),
+            capture_latency: LatencyHistogram::new(),
+        }
+    }
+}
+
+impl Metrics {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Count a measurement reported by a monitor.
+    pub fn record(&self, metric: MonitorMetric) {
+        match metric {
+            MonitorMetric::ControlEvent => {
+                self.control_events.fetch_add(1, Ordering::Relaxed);
+            }
+            MonitorMetric::CommandResponse { success } => {
+                self.tmux_commands.fetch_add(1, Ordering::Relaxed);
+                if !success {
+                    self.tmux_command_errors.fetch_add(1, Ordering::Relaxed);
+                }
+            }
+            MonitorMetric::CaptureLatency(latency) => self.capture_latency.observe(latency),
+        }
+    }
+
+    /// Count a state update handed to a session broadcast.
+    pub fn record_state_update(&self) {
+        self.state_updates.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Count a client command refused by a rate or in-flight limit.
+    pub fn record_command_refused(&self) {
+        self.commands_refused.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub fn uptime(&self) -> Duration {
+        self.started_at.elapsed()
+    }
+
+    /// Render every metric in the Prometheus text exposition format.
+    pub fn render(&self, sessions: &[SessionGauges]) -> String {
+        let mut out = String::new();
+        let counter = |out: &mut String, name: &str, help: &str, value: &AtomicU64| {
+            let _ = writeln!(out, "# HELP {name} {help}\n# TYPE {name} counter");
+            let _ = writeln!(out, "{name} {}", value.load(Ordering::Relaxed));
+        };
+
+        let _ = writeln!(
+            out,
+            "# HELP tmuxy_uptime_seconds Seconds since the server started.\n\
+             # TYPE tmuxy_uptime_seconds gauge\n\
+             tmuxy_uptime_seconds {:.3}",
+            self.uptime().as_secs_f64()
+        );
+        counter(
+            &mut out,
+            "tmuxy_control_events_total",
+            "Control-mode events read from tmux.",
+            &self.control_events,
+        );
+        counter(
+            &mut out,
+            "tmuxy_state_updates_total",
+            "State updates broadcast to clients.",
+            &self.state_updates,
+        );
+        counter(
+            &mut out,
+            "tmuxy_tmux_commands_total",
+            "tmux command responses received in control mode.",
+            &self.tmux_commands,
+        );
+        counter(
+            &mut out,
+            "tmuxy_tmux_command_errors_total",
+            "tmux command responses that were errors.",
+            &self.tmux_command_errors,
+        );
+        counter(
+            &mut out,
+            "tmuxy_client_commands_refused_total",
+            "Client commands refused by a rate or in-flight limit.",
+            &self.commands_refused,
+        );
+
+        let h = &self.capture_latency;
+        let _ = writeln!(
+            out,
+            "# HELP tmuxy_capture_pane_seconds Latency of capture-pane round trips.\n\
+             # TYPE tmuxy_capture_pane_seconds histogram"
+        );
+        for (bound, bucket) in CAPTURE_LATENCY_BUCKETS.iter().zip(&h.buckets) {
+            let _ = writeln!(
+                out,
+                "tmuxy_capture_pane_seconds_bucket{{le=\"{bound}\"}} {}",
+                bucket.load(Ordering::Relaxed)
+            );
+        }
+        let count = h.count.load(Ordering::Relaxed);
+        let _ = writeln!(
+            out,
+            "tmuxy_capture_pane_seconds_bucket{{le=\"+Inf\"}} {count}\n\
+             tmuxy_capture_pane_seconds_sum {:.6}\n\
+             tmuxy_capture_pane_seconds_count {count}",
+            h.sum_micros.load(Ordering::Relaxed) as f64 / 1e6
+        );
+
+        let gauge =
+            |out: &mut String, name: &str, help: &str, value: fn(&SessionGauges) -> usize| {
+                let _ = writeln!(out, "# HELP {name} {help}\n# TYPE {name} gauge");
+                for s in sessions {
+                    let _ = writeln!(
+                        out,
+                        "{name}{{session=\"{}\"}} {}",
+                        escape_label(&s.session),
+                        value(s)
+                    );
+                }
+            };
+        gauge(
+            &mut out,
+            "tmuxy_session_clients",
+            "Connected clients per session.",
+            |s| s.clients,
+        );
+        gauge(
+            &mut out,
+            "tmuxy_broadcast_queue_depth",
+            "Messages queued in a session's broadcast for its slowest client.",
+            |s| s.queue_depth,
+        );
+        gauge(
+            &mut out,
+            "tmuxy_monitor_up",
+            "Whether a session's tmux monitor is running.",
+            |s| usize::from(s.monitor_running),
+        );
+        out
+    }
+
+    /// The `/api/health` body. `degraded` when a session with clients has
+    /// lost its monitor, since those clients no longer get updates.
+    pub fn health(&self, sessions: &[SessionGauges]) -> serde_json::Value {
+        let degraded = sessions.iter().any(|s| s.clients > 0 && !s.monitor_running);
+        serde_json::json!({
+            "status": if degraded { "degraded" } else { "ok" },
+            "version": env!("CARGO_PKG_VERSION"),
+            "uptime_seconds": self.uptime().as_secs(),
+            "sessions": sessions.len(),
+            "clients": sessions.iter().map(|s| s.clients).sum::<usize>(),
+        })
+    }
+}
+
+/// Escape a Prometheus label value (backslash, quote, newline).
+fn escape_label(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used, clippy::expect_used)]
+mod tests {
+    use super::*;
+
+    fn line<'a>(out: &'a str, prefix: &str) -> &'a str {
+        out.lines()
+            .find(|l| l.starts_with(prefix))
+            .unwrap_or_else(|| panic!("no line starting with {prefix} in\n{out}"))
+    }
+
+    #[test]
+    fn counts_monitor_metrics() {
+        let m = Metrics::new();
+        m.record(MonitorMetric::ControlEvent);
+        m.record(MonitorMetric::ControlEvent);
+        m.record(MonitorMetric::CommandResponse { success: true });
+        m.record(MonitorMetric::CommandResponse { success: false });
+        m.record_state_update();
+        m.record_command_refused();
+
+        let out = m.render(&[]);
+        assert_eq!(
+            line(&out, "tmuxy_control_events_total "),
+            "tmuxy_control_events_total 2"
+        );
+        assert_eq!(
+            line(&out, "tmuxy_state_updates_total "),
+            "tmuxy_state_updates_total 1"
+        );
+        assert_eq!(
+            line(&out, "tmuxy_tmux_commands_total "),
+            "tmuxy_tmux_commands_total 2"
+        );
+        assert_eq!(
+            line(&out, "tmuxy_tmux_command_errors_total "),
+            "tmuxy_tmux_command_errors_total 1"
+        );
+        assert_eq!(
+            line(&out, "tmuxy_client_commands_refused_total "),
+            "tmuxy_client_commands_refused_total 1"
+        );
+        assert!(out.contains("# TYPE tmuxy_control_events_total counter"));
+    }
+
+    #[test]
+    fn capture_latency_buckets_are_cumulative() {
+        let m = Metrics::new();
+        m.record(MonitorMetric::CaptureLatency(Duration::from_millis(3)));
+        m.record(MonitorMetric::CaptureLatency(Duration::from_millis(40)));
+        m.record(MonitorMetric::CaptureLatency(Duration::from_secs(2)));
+
+        let out = m.render(&[]);
+        assert!(out.contains("tmuxy_capture_pane_seconds_bucket{le=\"0.001\"} 0\n"));
+        assert!(out.contains("tmuxy_capture_pane_seconds_bucket{le=\"0.005\"} 1\n"));
+        assert!(out.contains("tmuxy_capture_pane_seconds_bucket{le=\"0.05\"} 2\n"));
+        assert!(out.contains("tmuxy_capture_pane_seconds_bucket{le=\"1\"} 2\n"));
+        assert!(out.contains("tmuxy_capture_pane_seconds_bucket{le=\"+Inf\"} 3\n"));
+        assert!(out.contains("tmuxy_capture_pane_seconds_count 3\n"));
+        assert!(out.contains("tmuxy_capture_pane_seconds_sum 2.043000\n"));
+    }
+
+    #[test]
+    fn session_gauges_carry_an_escaped_label() {
+        let m = Metrics::new();
+        let sessions = [SessionGauges {
+            session: "a\"b".to_string(),
+            clients: 2,
+            queue_depth: 7,
+            monitor_running: true,
+        }];
+        let out = m.render(&sessions);
+        assert!(out.contains("tmuxy_session_clients{session=\"a\\\"b\"} 2\n"));
+        assert!(out.contains("tmuxy_broadcast_queue_depth{session=\"a\\\"b\"} 7\n"));
+        assert!(out.contains("tmuxy_monitor_up{session=\"a\\\"b\"} 1\n"));
+    }
+
+    #[test]
+    fn health_degrades_when_a_watched_session_loses_its_monitor() {
+        let m = Metrics::new();
+        let mut sessions = vec![SessionGauges {
+            session: "tmuxy".to_string(),
+            clients: 1,
+            queue_depth: 0,
+            monitor_running: true,
+        }];
+        let health = m.health(&sessions);
+        assert_eq!(health["status"], "ok");
+        assert_eq!(health["clients"], 1);
+
+        sessions[0].monitor_running = false;
+        assert_eq!(m.health(&sessions)["status"], "degraded");
+        sessions[0].clients = 0;
+        assert_eq!(m.health(&sessions)["status"], "ok");
+    }
+}
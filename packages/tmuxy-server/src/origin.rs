@@ -0,0 +1,308 @@
+//! Origin checks and the CORS policy.
+//!
+//! A browser attaches cached Basic-auth credentials to any request for the
+//! server's origin, including a form POST from an unrelated page, so a
+//! password alone doesn't stop another site from driving `/commands`.
+//! [`check_origin`] refuses state-changing requests (anything but `GET`,
+//! `HEAD` and `OPTIONS`) whose `Origin` — or, lacking one, `Referer` — is
+//! neither the server's own host nor an allowed origin:
+//!
+//! - `strict` (the default with a password) also refuses requests that carry
+//!   neither header. Scripts talking to the API send `Origin` themselves.
+//! - `lax` (the default without one) lets them through; browsers always
+//!   send `Origin` on cross-site POSTs, so those are still caught.
+//! - `off` checks nothing.
+//!
+//! Allowed origins (`--allowed-origin`, `TMUXY_ALLOWED_ORIGINS`) are also
+//! the only ones CORS answers for, with credentials. With none, `strict`
+//! answers no cross-origin request and `lax`/`off` answer any, without
+//! credentials.
+
+use std::fmt;
+use std::str::FromStr;
+use std::sync::Arc;
+
+use axum::extract::{Request, State};
+use axum::http::{header, HeaderMap, HeaderValue, Method, StatusCode};
+use axum::middleware::Next;
+use axum::response::{IntoResponse, Response};
+use axum::Json;
+use tower_http::cors::{AllowHeaders, AllowMethods, AllowOrigin, Any, CorsLayer};
+use tracing::warn;
+
+/// Comma-separated allowed origins, when `--allowed-origin` isn't given.
+pub const ALLOWED_ORIGINS_ENV: &str = "TMUXY_ALLOWED_ORIGINS";
+/// The check mode, when `--origin-check` isn't given.
+pub const ORIGIN_CHECK_ENV: &str = "TMUXY_ORIGIN_CHECK";
+
+/// How strictly [`check_origin`] checks.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OriginCheck {
+    Off,
+    Lax,
+    Strict,
+}
+
+impl FromStr for OriginCheck {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim() {
+            "off" => Ok(Self::Off),
+            "lax" => Ok(Self::Lax),
+            "strict" => Ok(Self::Strict),
+            other => Err(format!("expected off, lax or strict, got '{other}'")),
+        }
+    }
+}
+
+impl fmt::Display for OriginCheck {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::Off => "off",
+            Self::Lax => "lax",
+            Self::Strict => "strict",
+        })
+    }
+}
+
+/// The origin part of a URL, `scheme://host[:port]`, lowercased and without
+/// a default port, as browsers send it in `Origin`.
+pub fn normalize(url: &str) -> Option<String> {
+    let (scheme, rest) = url.trim().split_once("://")?;
+    let scheme = scheme.to_ascii_lowercase();
+    let authority = rest
+        .split(['/', '?', '#'])
+        .next()
+        .unwrap_or_default()
+        .rsplit('@')
+        .next()
+        .unwrap_or_default()
+        .to_ascii_lowercase();
+    if authority.is_empty() || !matches!(scheme.as_str(), "http" | "https") {
+        return None;
+    }
+    let default_port = if scheme == "https" { ":443" } else { ":80" };
+    let authority = authority.strip_suffix(default_port).unwrap_or(&authority);
+    Some(format!("{scheme}://{authority}"))
+}
+
+/// Which requests [`check_origin`] and the CORS layer accept.
+#[derive(Debug, Clone, PartialEq)]
+pub struct OriginPolicy {
+    pub check: OriginCheck,
+    /// Normalized origins besides the server's own.
+    pub allowed: Vec<String>,
+}
+
+impl OriginPolicy {
+    /// The policy from the server flags, falling back to the environment; the
+    /// mode defaults to `strict` when `authenticated`, else `lax`.
+    pub fn resolve(
+        check: Option<OriginCheck>,
+        allowed: Vec<String>,
+        authenticated: bool,
+    ) -> Result<Self, String> {
+        let check = match check {
+            Some(check) => check,
+            None => match std::env::var(ORIGIN_CHECK_ENV)
+                .ok()
+                .filter(|v| !v.is_empty())
+            {
+                Some(value) => value
+                    .parse()
+                    .map_err(|e| format!("{ORIGIN_CHECK_ENV}: {e}"))?,
+                None if authenticated => OriginCheck::Strict,
+                None => OriginCheck::Lax,
+            },
+        };
+        let allowed = if allowed.is_empty() {
+            std::env::var(ALLOWED_ORIGINS_ENV)
+                .unwrap_or_default()
+                .split(',')
+                .map(str::trim)
+                .filter(|o| !o.is_empty())
+                .map(String::from)
+                .collect()
+        } else {
+            allowed
+        };
+        let allowed = allowed
+            .iter()
+            .map(|origin| {
+                normalize(origin).ok_or_else(|| {
+                    format!("invalid origin '{origin}', expected e.g. https://tmuxy.example.com")
+                })
+            })
+            .collect::<Result<_, _>>()?;
+        Ok(Self { check, allowed })
+    }
+
+    /// Whether a state-changing request with these headers may go ahead.
+    pub fn check(&self, headers: &HeaderMap) -> Result<(), String> {
+        if self.check == OriginCheck::Off {
+            return Ok(());
+        }
+        let text = |name| {
+            headers
+                .get(name)
+                .and_then(|v: &HeaderValue| v.to_str().ok())
+        };
+        let Some(source) = text(header::ORIGIN).or_else(|| text(header::REFERER)) else {
+            return match self.check {
+                OriginCheck::Strict => Err("missing Origin header".to_string()),
+                _ => Ok(()),
+            };
+        };
+        let Some(origin) = normalize(source) else {
+            return Err(format!("origin {source} not allowed"));
+        };
+        let same_host = text(header::HOST).is_some_and(|host| {
+            origin
+                .split_once("://")
+                .is_some_and(|(_, authority)| authority.eq_ignore_ascii_case(host))
+        });
+        if same_host || self.allowed.contains(&origin) {
+            Ok(())
+        } else {
+            Err(format!("origin {origin} not allowed"))
+        }
+    }
+
+    /// The CORS layer for this policy.
+    pub fn cors(&self) -> CorsLayer {
+        if !self.allowed.is_empty() {
+            let origins = self
+                .allowed
+                .iter()
+                .filter_map(|origin| HeaderValue::from_str(origin).ok());
+            return CorsLayer::new()
+                .allow_origin(AllowOrigin::list(origins))
+                .allow_methods(AllowMethods::mirror_request())
+                .allow_headers(AllowHeaders::mirror_request())
+                .allow_credentials(true);
+        }
+        match self.check {
+            OriginCheck::Strict => CorsLayer::new(),
+            OriginCheck::Lax | OriginCheck::Off => CorsLayer::new()
+                .allow_origin(Any)
+                .allow_methods(Any)
+                .allow_headers(Any),
+        }
+    }
+}
+
+/// Axum middleware refusing state-changing requests from other origins.
+pub async fn check_origin(
+    State(policy): State<Arc<OriginPolicy>>,
+    req: Request,
+    next: Next,
+) -> Response {
+    if matches!(*req.method(), Method::GET | Method::HEAD | Method::OPTIONS) {
+        return next.run(req).await;
+    }
+    match policy.check(req.headers()) {
+        Ok(()) => next.run(req).await,
+        Err(error) => {
+            warn!(method = %req.method(), path = req.uri().path(), %error, "request refused");
+            (
+                StatusCode::FORBIDDEN,
+                Json(serde_json::json!({ "error": error })),
+            )
+                .into_response()
+        }
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used, clippy::expect_used)]
+mod tests {
+    use super::*;
+
+    fn headers(pairs: &[(header::HeaderName, &str)]) -> HeaderMap {
+        pairs
+            .iter()
+            .map(|(name, value)| (name.clone(), HeaderValue::from_str(value).unwrap()))
+            .collect()
+    }
+
+    fn policy(check: OriginCheck, allowed: &[&str]) -> OriginPolicy {
+        OriginPolicy {
+            check,
+            allowed: allowed.iter().map(|o| normalize(o).unwrap()).collect(),
+        }
+    }
+
+    #[test]
+    fn origins_normalize_like_browsers_send_them() {
+        assert_eq!(
+            normalize("HTTPS://Tmuxy.Example.com:443/"),
+            Some("https://tmuxy.example.com".to_string())
+        );
+        assert_eq!(
+            normalize("http://localhost:9000/app?x=1"),
+            Some("http://localhost:9000".to_string())
+        );
+        assert_eq!(normalize("null"), None);
+        assert_eq!(normalize("file:///tmp/x.html"), None);
+    }
+
+    #[test]
+    fn the_servers_own_host_and_allowed_origins_pass() {
+        let policy = policy(OriginCheck::Strict, &["https://proxy.example"]);
+        let host = (header::HOST, "localhost:9000");
+        assert!(policy
+            .check(&headers(&[
+                host.clone(),
+                (header::ORIGIN, "http://localhost:9000")
+            ]))
+            .is_ok());
+        assert!(policy
+            .check(&headers(&[
+                host.clone(),
+                (header::ORIGIN, "https://proxy.example")
+            ]))
+            .is_ok());
+        assert!(policy
+            .check(&headers(&[
+                host.clone(),
+                (header::REFERER, "http://localhost:9000/?session=a")
+            ]))
+            .is_ok());
+        let err = policy
+            .check(&headers(&[
+                host.clone(),
+                (header::ORIGIN, "https://evil.example"),
+            ]))
+            .unwrap_err();
+        assert_eq!(err, "origin https://evil.example not allowed");
+        assert!(policy
+            .check(&headers(&[host, (header::ORIGIN, "null")]))
+            .is_err());
+    }
+
+    #[test]
+    fn only_strict_refuses_requests_without_an_origin() {
+        let bare = headers(&[(header::HOST, "localhost:9000")]);
+        assert!(policy(OriginCheck::Strict, &[]).check(&bare).is_err());
+        assert!(policy(OriginCheck::Lax, &[]).check(&bare).is_ok());
+        let foreign = headers(&[(header::ORIGIN, "https://evil.example")]);
+        assert!(policy(OriginCheck::Lax, &[]).check(&foreign).is_err());
+        assert!(policy(OriginCheck::Off, &[]).check(&foreign).is_ok());
+    }
+
+    #[test]
+    fn strict_is_the_default_with_a_password() {
+        let resolved = OriginPolicy::resolve(None, vec![], true).unwrap();
+        assert_eq!(resolved.check, OriginCheck::Strict);
+        let resolved = OriginPolicy::resolve(
+            Some(OriginCheck::Off),
+            vec!["https://a.example/".into()],
+            true,
+        )
+        .unwrap();
+        assert_eq!(resolved.check, OriginCheck::Off);
+        assert_eq!(resolved.allowed, ["https://a.example"]);
+        assert!(OriginPolicy::resolve(None, vec!["nope".into()], false).is_err());
+    }
+}
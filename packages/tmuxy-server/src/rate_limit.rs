@@ -0,0 +1,509 @@
+//! Limits on `POST /commands`, so one misbehaving client (or a script stuck
+//! in a loop) can't fill the monitor channel every client of a session
+//! shares.
+//!
+//! - Each connection (`X-Connection-Id`) and each client IP has a token
+//!   bucket, checked by [`limit_commands`] before the body is read. A
+//!   request over either is refused with `429` and a `Retry-After`.
+//!   The header is the client's to set, so it only counts when it names an
+//!   open event stream of the request's session. Requests over a Unix
+//!   socket have no IP and are keyed on the credentials they present
+//!   instead. Behind a reverse proxy every client shares the proxy's IP.
+//! - Bodies over [`LimitConfig::max_body_bytes`] are refused with `413`.
+//! - A client may have at most [`LimitConfig::max_in_flight`] commands of
+//!   one kind waiting on tmux at a time (see [`CommandLimits::begin`]); the
+//!   next one is refused with `429` rather than queued behind them.
+//!
+//! Each limit has an environment variable; `off` lifts a rate limit.
+
+use std::collections::HashMap;
+use std::hash::{BuildHasher, RandomState};
+use std::mem::Discriminant;
+use std::net::{IpAddr, SocketAddr};
+use std::sync::{Arc, Mutex, OnceLock};
+use std::time::{Duration, Instant};
+
+use axum::extract::{ConnectInfo, Query, Request, State};
+use axum::http::{header, HeaderMap, StatusCode, Uri};
+use axum::middleware::Next;
+use axum::response::{IntoResponse, Response};
+use axum::Json;
+use tmuxy_protocol::ClientCommand;
+use tracing::warn;
+
+use crate::sse::SessionQuery;
+use crate::state::AppState;
+
+/// Commands per second for one connection: `<rate>[/<burst>]`, or `off`.
+pub const COMMAND_RATE_ENV: &str = "TMUXY_COMMAND_RATE";
+/// Commands per second for one client IP (or, over a Unix socket, one set
+/// of credentials), across its connections.
+pub const COMMAND_RATE_PER_IP_ENV: &str = "TMUXY_COMMAND_RATE_PER_IP";
+/// Largest command body accepted, in bytes.
+pub const MAX_COMMAND_BYTES_ENV: &str = "TMUXY_MAX_COMMAND_BYTES";
+/// Commands of one kind a client may have waiting on tmux at once.
+pub const MAX_COMMANDS_IN_FLIGHT_ENV: &str = "TMUXY_MAX_COMMANDS_IN_FLIGHT";
+
+/// Buckets kept before idle (full) ones are dropped.
+const MAX_IDLE_BUCKETS: usize = 1024;
+
+/// A sustained rate with room for bursts above it.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Rate {
+    pub per_second: f64,
+    pub burst: f64,
+}
+
+impl Rate {
+    /// `<rate>[/<burst>]`, the burst defaulting to twice the rate; `None`
+    /// for `off` or `0`.
+    pub fn parse(text: &str) -> Result<Option<Rate>, String> {
+        let text = text.trim();
+        if text == "off" || text == "0" {
+            return Ok(None);
+        }
+        let number = |s: &str| {
+            s.trim()
+                .parse::<f64>()
+                .ok()
+                .filter(|n| n.is_finite() && *n > 0.0)
+                .ok_or_else(|| format!("invalid rate: {text}"))
+        };
+        let (rate, burst) = match text.split_once('/') {
+            Some((rate, burst)) => (number(rate)?, number(burst)?),
+            None => (number(text)?, 2.0 * number(text)?),
+        };
+        Ok(Some(Rate {
+            per_second: rate,
+            burst: burst.max(1.0),
+        }))
+    }
+}
+
+/// What [`CommandLimits`] allows.
+#[derive(Debug, Clone, PartialEq)]
+pub struct LimitConfig {
+    pub per_connection: Option<Rate>,
+    pub per_ip: Option<Rate>,
+    pub max_body_bytes: usize,
+    pub max_in_flight: usize,
+}
+
+impl Default for LimitConfig {
+    /// Far above typing, mouse drags and paste, which the frontend sends one
+    /// command at a time.
+    fn default() -> Self {
+        Self {
+            per_connection: Some(Rate {
+                per_second: 100.0,
+                burst: 200.0,
+            }),
+            per_ip: Some(Rate {
+                per_second: 300.0,
+                burst: 600.0,
+            }),
+            max_body_bytes: 1 << 20,
+            max_in_flight: 8,
+        }
+    }
+}
+
+impl LimitConfig {
+    /// The defaults, with any limit set in the environment instead; a value
+    /// that doesn't parse keeps the default, with a warning.
+    pub fn from_env() -> Self {
+        let mut config = Self::default();
+        let var = |name: &str| std::env::var(name).ok().filter(|v| !v.trim().is_empty());
+        let rate = |name: &str, default: Option<Rate>| match var(name).map(|v| Rate::parse(&v)) {
+            Some(Ok(rate)) => rate,
+            Some(Err(e)) => {
+                warn!(env = name, error = %e, "keeping the default command rate");
+                default
+            }
+            None => default,
+        };
+        let count = |name: &str, default: usize| match var(name).map(|v| v.trim().parse()) {
+            Some(Ok(n)) if n > 0 => n,
+            Some(_) => {
+                warn!(
+                    env = name,
+                    "expected a positive number, keeping the default"
+                );
+                default
+            }
+            None => default,
+        };
+        config.per_connection = rate(COMMAND_RATE_ENV, config.per_connection);
+        config.per_ip = rate(COMMAND_RATE_PER_IP_ENV, config.per_ip);
+        config.max_body_bytes = count(MAX_COMMAND_BYTES_ENV, config.max_body_bytes);
+        config.max_in_flight = count(MAX_COMMANDS_IN_FLIGHT_ENV, config.max_in_flight);
+        config
+    }
+}
+
+/// Who sent a command: its connection when it names a live one, else its
+/// peer (IP or credentials).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum CommandClient {
+    Connection(u64),
+    Ip(IpAddr),
+    /// A hash of the password or token a request with no IP presented;
+    /// unauthenticated ones share the hash of nothing.
+    Credentials(u64),
+}
+
+impl CommandClient {
+    /// The peer key for a request with no IP.
+    fn credentials(headers: &HeaderMap, query: Option<&str>) -> Self {
+        static KEYS: OnceLock<RandomState> = OnceLock::new();
+        let presented = crate::tokens::presented(headers, query)
+            .map(|(token, _)| token)
+            .or_else(|| {
+                headers
+                    .get(header::AUTHORIZATION)
+                    .and_then(|v| v.to_str().ok())
+                    .map(str::to_string)
+            });
+        Self::Credentials(KEYS.get_or_init(RandomState::new).hash_one(presented))
+    }
+}
+
+#[derive(Debug)]
+struct Bucket {
+    tokens: f64,
+    at: Instant,
+}
+
+impl Bucket {
+    fn refill(&mut self, rate: Rate, now: Instant) {
+        let elapsed = now.saturating_duration_since(self.at).as_secs_f64();
+        self.tokens = (self.tokens + elapsed * rate.per_second).min(rate.burst);
+        self.at = now;
+    }
+
+    /// How long until a token is due; zero when one is there now.
+    fn wait(&self, rate: Rate) -> Duration {
+        Duration::from_secs_f64((1.0 - self.tokens).max(0.0) / rate.per_second)
+    }
+}
+
+/// The state behind the limits, shared by every request.
+pub struct CommandLimits {
+    config: LimitConfig,
+    buckets: Mutex<HashMap<CommandClient, Bucket>>,
+    in_flight: Mutex<HashMap<(CommandClient, Discriminant<ClientCommand>), usize>>,
+}
+
+impl CommandLimits {
+    pub fn new(config: LimitConfig) -> Self {
+        Self {
+            config,
+            buckets: Mutex::default(),
+            in_flight: Mutex::default(),
+        }
+    }
+
+    pub fn config(&self) -> &LimitConfig {
+        &self.config
+    }
+
+    /// Take a token from the connection's bucket and the peer's at `now`,
+    /// or from neither and return how long until both have one.
+    pub fn admit(
+        &self,
+        connection: Option<u64>,
+        peer: CommandClient,
+        now: Instant,
+    ) -> Result<(), Duration> {
+        let checks: Vec<(CommandClient, Rate)> = [
+            connection
+                .map(CommandClient::Connection)
+                .zip(self.config.per_connection),
+            self.config.per_ip.map(|rate| (peer, rate)),
+        ]
+        .into_iter()
+        .flatten()
+        .collect();
+        if checks.is_empty() {
+            return Ok(());
+        }
+        let mut buckets = self.buckets.lock().unwrap_or_else(|e| e.into_inner());
+        if buckets.len() > MAX_IDLE_BUCKETS {
+            let rate = |client: &CommandClient| match client {
+                CommandClient::Connection(_) => self.config.per_connection,
+                CommandClient::Ip(_) | CommandClient::Credentials(_) => self.config.per_ip,
+            };
+            buckets.retain(|client, bucket| {
+                rate(client).is_some_and(|rate| {
+                    bucket.refill(rate, now);
+                    bucket.tokens < rate.burst
+                })
+            });
+        }
+        let mut wait = Duration::ZERO;
+        for (client, rate) in &checks {
+            let bucket = buckets.entry(*client).or_insert(Bucket {
+                tokens: rate.burst,
+                at: now,
+            });
+            bucket.refill(*rate, now);
+            wait = wait.max(bucket.wait(*rate));
+        }
+        if !wait.is_zero() {
+            return Err(wait);
+        }
+        for (client, _) in &checks {
+            if let Some(bucket) = buckets.get_mut(client) {
+                bucket.tokens -= 1.0;
+            }
+        }
+        Ok(())
+    }
+
+    /// Count `command` in flight for `client` until the guard drops; `None`
+    /// when the client already has the most of that kind allowed.
+    pub fn begin(&self, client: CommandClient, command: &ClientCommand) -> Option<InFlight<'_>> {
+        let key = (client, std::mem::discriminant(command));
+        let mut in_flight = self.in_flight.lock().unwrap_or_else(|e| e.into_inner());
+        let count = in_flight.entry(key).or_default();
+        if *count >= self.config.max_in_flight {
+            return None;
+        }
+        *count += 1;
+        Some(InFlight { limits: self, key })
+    }
+}
+
+/// A command counted by [`CommandLimits::begin`].
+pub struct InFlight<'a> {
+    limits: &'a CommandLimits,
+    key: (CommandClient, Discriminant<ClientCommand>),
+}
+
+impl Drop for InFlight<'_> {
+    fn drop(&mut self) {
+        let mut in_flight = self
+            .limits
+            .in_flight
+            .lock()
+            .unwrap_or_else(|e| e.into_inner());
+        if let Some(count) = in_flight.get_mut(&self.key) {
+            *count -= 1;
+            if *count == 0 {
+                in_flight.remove(&self.key);
+            }
+        }
+    }
+}
+
+/// The `X-Connection-Id` a command request names, if any.
+pub fn connection_id(headers: &axum::http::HeaderMap) -> Option<u64> {
+    headers
+        .get("x-connection-id")
+        .and_then(|v| v.to_str().ok())
+        .and_then(|s| s.parse().ok())
+}
+
+/// Whether `id` is an open event stream of the session `uri` asks for.
+/// Any other id would let a client spread its commands over made-up
+/// connections, or spend another session's client's budget.
+async fn is_live(state: &AppState, uri: &Uri, id: u64) -> bool {
+    let Ok(Query(query)) = Query::<SessionQuery>::try_from_uri(uri) else {
+        return false;
+    };
+    let Ok(session) = query.session() else {
+        return false;
+    };
+    state
+        .sessions
+        .read()
+        .await
+        .get(&session)
+        .is_some_and(|conns| conns.connections.contains(&id))
+}
+
+/// Refuse a command request over its connection's or peer's rate, and tell
+/// the handler who sent it.
+pub async fn limit_commands(
+    State(state): State<Arc<AppState>>,
+    mut req: Request,
+    next: Next,
+) -> Response {
+    let connection = match connection_id(req.headers()) {
+        Some(id) if is_live(&state, req.uri(), id).await => Some(id),
+        _ => None,
+    };
+    let peer = match req.extensions().get::<ConnectInfo<SocketAddr>>() {
+        Some(ConnectInfo(addr)) => CommandClient::Ip(addr.ip()),
+        None => CommandClient::credentials(req.headers(), req.uri().query()),
+    };
+    if let Err(wait) = state.command_limits.admit(connection, peer, Instant::now()) {
+        state.metrics.record_command_refused();
+        return refuse(
+            format!(
+                "rate limit exceeded, retry in {}ms",
+                wait.as_millis().max(1)
+            ),
+            Some(wait),
+        );
+    }
+    let client = connection.map_or(peer, CommandClient::Connection);
+    req.extensions_mut().insert(client);
+    next.run(req).await
+}
+
+/// A `429` in the `/commands` response shape, with when to retry.
+pub fn refuse(error: String, retry_after: Option<Duration>) -> Response {
+    let mut response = (
+        StatusCode::TOO_MANY_REQUESTS,
+        Json(serde_json::json!({ "error": error })),
+    )
+        .into_response();
+    if let Some(wait) = retry_after {
+        // Whole seconds, rounded up: `Retry-After: 0` reads as "now".
+        let secs = wait.as_secs() + u64::from(wait.subsec_nanos() > 0);
+        response
+            .headers_mut()
+            .insert(header::RETRY_AFTER, secs.max(1).into());
+    }
+    response
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used, clippy::expect_used)]
+mod tests {
+    use super::*;
+
+    fn limits(per_connection: &str, per_ip: &str) -> CommandLimits {
+        CommandLimits::new(LimitConfig {
+            per_connection: Rate::parse(per_connection).unwrap(),
+            per_ip: Rate::parse(per_ip).unwrap(),
+            max_in_flight: 2,
+            ..LimitConfig::default()
+        })
+    }
+
+    #[test]
+    fn rates_parse_with_an_optional_burst() {
+        assert_eq!(
+            Rate::parse("50").unwrap(),
+            Some(Rate {
+                per_second: 50.0,
+                burst: 100.0
+            })
+        );
+        assert_eq!(
+            Rate::parse("10/5").unwrap(),
+            Some(Rate {
+                per_second: 10.0,
+                burst: 5.0
+            })
+        );
+        assert_eq!(Rate::parse("off").unwrap(), None);
+        assert!(Rate::parse("fast").is_err());
+        assert!(Rate::parse("-1").is_err());
+    }
+
+    fn ip(addr: &str) -> CommandClient {
+        CommandClient::Ip(addr.parse().unwrap())
+    }
+
+    #[test]
+    fn a_connection_gets_its_burst_then_its_rate() {
+        let limits = limits("10/3", "off");
+        let peer = ip("10.0.0.7");
+        let start = Instant::now();
+        for _ in 0..3 {
+            assert_eq!(limits.admit(Some(1), peer, start), Ok(()));
+        }
+        assert_eq!(
+            limits.admit(Some(1), peer, start),
+            Err(Duration::from_millis(100))
+        );
+        // Other connections have buckets of their own.
+        assert_eq!(limits.admit(Some(2), peer, start), Ok(()));
+        assert_eq!(
+            limits.admit(Some(1), peer, start + Duration::from_millis(100)),
+            Ok(())
+        );
+    }
+
+    #[test]
+    fn an_ip_is_limited_across_its_connections() {
+        let limits = limits("off", "1/2");
+        let peer = ip("10.0.0.7");
+        let now = Instant::now();
+        assert!(limits.admit(Some(1), peer, now).is_ok());
+        assert!(limits.admit(Some(2), peer, now).is_ok());
+        assert!(limits.admit(Some(3), peer, now).is_err());
+        assert!(limits.admit(None, peer, now).is_err());
+        assert!(limits.admit(Some(3), ip("10.0.0.8"), now).is_ok());
+    }
+
+    #[test]
+    fn a_refused_request_takes_no_token() {
+        let limits = limits("1/1", "1/2");
+        let peer = ip("10.0.0.7");
+        let now = Instant::now();
+        assert!(limits.admit(Some(1), peer, now).is_ok());
+        // Connection 1 is out; the IP's second token stays for connection 2.
+        assert!(limits.admit(Some(1), peer, now).is_err());
+        assert!(limits.admit(Some(2), peer, now).is_ok());
+    }
+
+    #[test]
+    fn socket_callers_are_keyed_on_their_credentials() {
+        let headers = |auth: &str| {
+            let mut headers = HeaderMap::new();
+            headers.insert(header::AUTHORIZATION, auth.parse().unwrap());
+            headers
+        };
+        let alice = CommandClient::credentials(&headers("Bearer alice"), None);
+        assert_eq!(
+            alice,
+            CommandClient::credentials(&HeaderMap::new(), Some("token=alice"))
+        );
+        assert_ne!(
+            alice,
+            CommandClient::credentials(&headers("Bearer bob"), None)
+        );
+        assert_ne!(alice, CommandClient::credentials(&HeaderMap::new(), None));
+
+        let limits = limits("off", "1/1");
+        let now = Instant::now();
+        assert!(limits.admit(Some(1), alice, now).is_ok());
+        assert!(limits.admit(Some(2), alice, now).is_err());
+    }
+
+    #[tokio::test]
+    async fn only_live_connections_of_the_session_are_trusted() {
+        let state = AppState::new();
+        state
+            .sessions
+            .write()
+            .await
+            .entry("work".to_string())
+            .or_insert_with(crate::state::SessionConnections::new)
+            .connections
+            .push(7);
+        let uri = |q: &str| format!("/commands?{q}").parse::<Uri>().unwrap();
+        assert!(is_live(&state, &uri("session=work"), 7).await);
+        assert!(!is_live(&state, &uri("session=work"), 8).await);
+        assert!(!is_live(&state, &uri("session=other"), 7).await);
+    }
+
+    #[test]
+    fn in_flight_commands_are_capped_per_kind() {
+        let limits = limits("off", "off");
+        let client = CommandClient::Connection(1);
+        let list = ClientCommand::ListContainers;
+        let focus = ClientCommand::SetFocus { focused: true };
+        let first = limits.begin(client, &list).unwrap();
+        let _second = limits.begin(client, &list).unwrap();
+        assert!(limits.begin(client, &list).is_none());
+        assert!(limits.begin(client, &focus).is_some());
+        assert!(limits.begin(CommandClient::Connection(2), &list).is_some());
+        drop(first);
+        assert!(limits.begin(client, &list).is_some());
+    }
+}
@@ -3,12 +3,18 @@ use axum::http::StatusCode;
 use axum::response::{IntoResponse, Response};
 use clap::{Args, Subcommand};
 use rust_embed::Embed;
+use std::path::{Path, PathBuf};
 use std::sync::Arc;
+use std::time::{Duration, Instant};
 use tokio::signal;
-use tracing::{error, warn};
+use tracing::error;
 
+use crate::auth::Credentials;
 use crate::dev;
+use crate::listen::{self, Listen};
+use crate::origin::{OriginCheck, OriginPolicy};
 use crate::state::{build_response, AppState};
+use crate::tokens::{SameSite, TokenStore};
 
 #[derive(Embed)]
 #[folder = "../tmuxy-ui/dist/"]
@@ -20,25 +26,65 @@ pub struct ServerArgs {
     pub action: Option<ServerAction>,
 
     /// Port to listen on
-    #[arg(long, default_value = "9000")]
+    #[arg(long, default_value = "9000", global = true)]
     pub port: u16,
 
     /// Host to bind to
-    #[arg(long, default_value = "0.0.0.0")]
+    #[arg(long, default_value = "0.0.0.0", global = true)]
     pub host: String,
 
+    /// Listen here instead of --host/--port: `unix:PATH` for a Unix domain
+    /// socket, or `IP:PORT`. Repeat to listen on several addresses
+    #[arg(long, value_name = "ADDR", global = true)]
+    pub listen: Vec<Listen>,
+
     /// Require HTTP Basic auth with this password (any username is accepted).
     /// Falls back to the TMUXY_PASSWORD env var. When neither is set the server
     /// runs with NO authentication — anyone who can reach the port gets full
     /// shell access. Prefer TMUXY_PASSWORD to keep the secret out of `ps`.
-    #[arg(long)]
+    #[arg(long, global = true)]
     pub password: Option<String>,
 
+    /// Also accept commands from this origin (e.g. https://tmuxy.example.com
+    /// behind a reverse proxy), and answer CORS for it. Repeatable. Falls back
+    /// to the comma-separated TMUXY_ALLOWED_ORIGINS env var
+    #[arg(long, value_name = "ORIGIN", global = true)]
+    pub allowed_origin: Vec<String>,
+
+    /// How to check the Origin of state-changing requests: strict, lax or off
+    /// [default: strict with a password, else lax; env: TMUXY_ORIGIN_CHECK]
+    #[arg(long, value_name = "MODE", global = true)]
+    pub origin_check: Option<OriginCheck>,
+
+    /// Command new panes run instead of the login shell, e.g. `bash -l` or
+    /// `ssh build-box` [env: TMUXY_DEFAULT_COMMAND]
+    #[arg(long, value_name = "COMMAND", global = true)]
+    pub default_command: Option<String>,
+
+    /// Like --default-command, for one session. Repeatable [env:
+    /// TMUXY_SESSION_COMMANDS, one NAME=COMMAND per line]
+    #[arg(long, value_name = "NAME=COMMAND", value_parser = parse_session_command, global = true)]
+    pub session_command: Vec<(String, String)>,
+
     /// Run in development mode (proxy to Vite dev server)
-    #[arg(long)]
+    #[arg(long, global = true)]
     pub dev: bool,
 }
 
+/// How `start` and `restart` run the server.
+#[derive(Args, Debug, Default)]
+pub struct StartArgs {
+    /// Detach from the terminal and run in the background; output goes to
+    /// the log file
+    #[arg(long)]
+    pub daemon: bool,
+
+    /// Append the server's output to this file [default with --daemon:
+    /// ~/.tmuxy/tmuxy.log]
+    #[arg(long, value_name = "PATH")]
+    pub log_file: Option<PathBuf>,
+}
+
 /// Resolve the auth password: `--password` wins, else the `TMUXY_PASSWORD` env
 /// var; an empty value counts as unset (no auth).
 fn resolve_password(flag: Option<String>) -> Option<String> {
@@ -46,41 +92,119 @@ fn resolve_password(flag: Option<String>) -> Option<String> {
         .filter(|s| !s.is_empty())
 }
 
-/// Wrap the router in the Basic-auth layer when a password is configured.
-/// With no password the router is returned unchanged (server stays open).
-fn with_optional_auth(app: axum::Router, password: Option<String>) -> axum::Router {
+/// Parse a `--session-command` value: a session name, `=`, and its command.
+fn parse_session_command(value: &str) -> Result<(String, String), String> {
+    let (name, command) = value
+        .split_once('=')
+        .ok_or_else(|| "expected NAME=COMMAND".to_string())?;
+    let name = name.trim();
+    if name.is_empty() || command.trim().is_empty() {
+        return Err("expected NAME=COMMAND".to_string());
+    }
+    if command.contains('\n') {
+        return Err("the command must be one line".to_string());
+    }
+    Ok((name.to_string(), command.to_string()))
+}
+
+/// Publish `--default-command` and `--session-command` as the env vars
+/// `session::default_command` reads, so every session the server creates or
+/// attaches picks them up. Flags win over what the env already held.
+fn export_default_commands(default: Option<&str>, per_session: &[(String, String)]) {
+    if let Some(command) = default {
+        std::env::set_var("TMUXY_DEFAULT_COMMAND", command);
+    }
+    if !per_session.is_empty() {
+        // The first line naming a session wins, so the flags go first.
+        let mut lines: Vec<String> = per_session
+            .iter()
+            .map(|(name, command)| format!("{name}={command}"))
+            .collect();
+        lines.extend(std::env::var("TMUXY_SESSION_COMMANDS").ok());
+        std::env::set_var("TMUXY_SESSION_COMMANDS", lines.join("\n"));
+    }
+}
+
+/// Wrap the router in the Basic-auth layer when a password is configured;
+/// `tokens` are accepted in its place. With no password the router is
+/// returned unchanged (server stays open).
+fn with_optional_auth(
+    app: axum::Router,
+    password: Option<String>,
+    tokens: Arc<TokenStore>,
+) -> axum::Router {
     match password {
-        Some(pw) => app.layer(axum::middleware::from_fn_with_state(
-            std::sync::Arc::new(pw),
+        Some(password) => app.layer(axum::middleware::from_fn_with_state(
+            Arc::new(Credentials {
+                password,
+                tokens,
+                same_site: SameSite::from_env(),
+            }),
             crate::auth::require_basic_auth,
         )),
         None => app,
     }
 }
 
+/// Check the origin of state-changing requests and answer CORS per `policy`.
+fn with_origin_policy(app: axum::Router, policy: OriginPolicy) -> axum::Router {
+    let cors = policy.cors();
+    app.layer(axum::middleware::from_fn_with_state(
+        Arc::new(policy),
+        crate::origin::check_origin,
+    ))
+    .layer(cors)
+}
+
 /// Print the auth status, and warn loudly when the server is reachable off-box
-/// with no password — matching the threat model in docs/SECURITY.md.
-fn announce_security(host: &str, password_set: bool) {
+/// with no password — matching the threat model in docs/SECURITY.md. Unix
+/// sockets are owner-only, so only TCP addresses can warrant the warning.
+fn announce_security(listens: &[Listen], password_set: bool) {
     if password_set {
         println!(
             "tmuxy server: HTTP Basic auth enabled (any username; use the configured password)"
         );
         return;
     }
-    let localhost_only = host == "127.0.0.1" || host == "localhost" || host == "::1";
-    if !localhost_only {
-        eprintln!(
-            "warning: no password set and bound to {host} — anyone who can reach this port has \
-             full shell access. Set --password / TMUXY_PASSWORD, or bind --host 127.0.0.1."
-        );
+    for listen in listens {
+        let Listen::Tcp(addr) = listen else {
+            continue;
+        };
+        let host = addr.ip();
+        if !host.is_loopback() {
+            eprintln!(
+                "warning: no password set and bound to {host} — anyone who can reach this port has \
+                 full shell access. Set --password / TMUXY_PASSWORD, or bind --host 127.0.0.1."
+            );
+        }
     }
 }
 
+/// Serve `app` on every listener until shutdown.
+async fn serve(
+    listeners: Vec<listen::Listener>,
+    app: axum::Router,
+    state: Arc<AppState>,
+    children: Vec<Option<dev::ViteChild>>,
+) {
+    let shutdown = state.shutdown.clone();
+    let servers = futures_util::future::join_all(
+        listeners
+            .into_iter()
+            .map(|listener| listener.serve(app.clone(), shutdown.clone())),
+    );
+    tokio::join!(servers, shutdown_signal(state, children));
+}
+
 #[derive(Subcommand)]
 pub enum ServerAction {
-    /// Stop the running server
+    /// Start the server; the default when no action is given
+    Start(StartArgs),
+    /// Stop the running server, waiting for it to exit
     Stop,
-    /// Show server status
+    /// Stop the running server, if any, and start it again
+    Restart(StartArgs),
+    /// Show server status; exits 1 when it isn't running
     Status,
     /// Run the sidebar tree TUI (backs `tmuxy tree`). Hidden: meant to run
     /// inside a tmux pane, not invoked directly by users.
@@ -90,16 +214,126 @@ pub enum ServerAction {
     /// Hidden: meant to run inside a tmux float, not invoked directly.
     #[command(hide = true)]
     Connect,
+    /// Print the local usage statistics (backs `tmuxy stats`).
+    #[command(hide = true)]
+    Stats(StatsArgs),
+    /// Render markdown for the terminal (backs `tmuxy md`).
+    #[command(hide = true)]
+    Md(MdArgs),
+    /// Stream system stats to a sysmon widget (backs `tmuxy widget sysmon`).
+    /// Hidden: meant to run inside the widget's pane.
+    #[command(hide = true)]
+    Sysmon(SysmonArgs),
+    /// Stream a repository's status to a git widget (backs `tmuxy widget
+    /// git`). Hidden: meant to run inside the widget's pane.
+    #[command(hide = true)]
+    GitStatus(GitStatusArgs),
+    /// Build a session from a template file (backs `tmuxy up`).
+    #[command(hide = true)]
+    Up(UpArgs),
 }
 
-pub async fn run(args: ServerArgs) {
-    let dev_mode = args.dev || std::env::var("TMUXY_DEV").is_ok();
-    let password = resolve_password(args.password.clone());
-    match args.action {
-        None if dev_mode => start_dev_server(args.port, password).await,
-        None => start_server(args.port, args.host, password).await,
-        Some(ServerAction::Stop) => stop_server(),
-        Some(ServerAction::Status) => server_status(),
+/// The template `tmuxy up` builds, and the session it makes.
+#[derive(Args, Debug)]
+pub struct UpArgs {
+    /// Template file: .yml, .yaml or .toml
+    pub template: PathBuf,
+
+    /// Session name (default: the template's `name`, else the file's stem)
+    #[arg(long, short = 's')]
+    pub session: Option<String>,
+
+    /// Print the tmux commands instead of running them
+    #[arg(long)]
+    pub dry_run: bool,
+}
+
+/// How often `tmuxy widget sysmon` samples.
+#[derive(Args, Debug)]
+pub struct SysmonArgs {
+    /// Time between samples: 500ms, 2s, 1m, or bare seconds
+    #[arg(long, default_value = "2s", value_parser = parse_widget_interval)]
+    pub interval: Duration,
+}
+
+/// Which repository `tmuxy widget git` follows, and how often it refreshes
+/// when nothing in it signals a change.
+#[derive(Args, Debug)]
+pub struct GitStatusArgs {
+    /// A directory inside the repository
+    #[arg(long, default_value = ".")]
+    pub dir: PathBuf,
+
+    /// Time between refreshes: 500ms, 5s, 1m, or bare seconds
+    #[arg(long, default_value = "5s", value_parser = parse_widget_interval)]
+    pub interval: Duration,
+}
+
+fn parse_widget_interval(text: &str) -> Result<Duration, String> {
+    crate::sysmon::parse_interval(text).ok_or_else(|| format!("invalid interval: {text}"))
+}
+
+/// What `tmuxy stats` summarises.
+#[derive(Args, Debug)]
+pub struct StatsArgs {
+    /// Only the last N days
+    #[arg(long, value_name = "N")]
+    pub days: Option<u64>,
+
+    /// Entries per list
+    #[arg(long, default_value_t = tmuxy_core::usage::SUMMARY_LIMIT)]
+    pub limit: usize,
+
+    /// Print JSON, as GET /api/stats returns it
+    #[arg(long)]
+    pub json: bool,
+}
+
+/// What `tmuxy md` renders, and how.
+#[derive(Args, Debug)]
+pub struct MdArgs {
+    /// Markdown file, or - for stdin
+    #[arg(default_value = "-")]
+    pub file: PathBuf,
+
+    /// Wrap at this many columns (default: $COLUMNS, else 80)
+    #[arg(long)]
+    pub width: Option<usize>,
+
+    /// Print HTML, as the markdown viewer gets it from /api/markdown
+    #[arg(long)]
+    pub html: bool,
+}
+
+/// Run a `tmuxy server` invocation. Builds its own async runtime, and only
+/// once any daemonizing is done: forking is only safe while the process has
+/// a single thread.
+pub fn run(mut args: ServerArgs) {
+    match args.action.take() {
+        None => launch(args, StartArgs::default()),
+        Some(ServerAction::Start(start)) => {
+            if let Some(pid) = running_server_pid() {
+                eprintln!("Server is already running (pid {pid})");
+                std::process::exit(1);
+            }
+            launch(args, start);
+        }
+        Some(ServerAction::Restart(start)) => {
+            if !stop_server() {
+                std::process::exit(1);
+            }
+            launch(args, start);
+        }
+        Some(ServerAction::Stop) => {
+            if !stop_server() {
+                std::process::exit(1);
+            }
+        }
+        Some(ServerAction::Status) => {
+            if !server_status() {
+                std::process::exit(1);
+            }
+        }
         Some(ServerAction::Tree) => {
             if let Err(e) = crate::tree::run_tree_tui() {
                 eprintln!("tmuxy tree: {e}");
@@ -114,11 +348,168 @@ pub async fn run(args: ServerArgs) {
                 std::process::exit(1);
             }
         },
+        Some(ServerAction::Stats(stats)) => print_stats(&stats),
+        Some(ServerAction::Md(md)) => print_markdown(&md),
+        Some(ServerAction::Up(up)) => {
+            if let Err(e) = build_from_template(&up) {
+                eprintln!("tmuxy up: {e}");
+                std::process::exit(1);
+            }
+        }
+        Some(ServerAction::Sysmon(sysmon)) => {
+            // A write error means the pane's reader is gone: the widget closed.
+            if let Err(e) = crate::sysmon::run(sysmon.interval) {
+                if e.kind() != std::io::ErrorKind::BrokenPipe {
+                    eprintln!("tmuxy sysmon: {e}");
+                    std::process::exit(1);
+                }
+            }
+        }
+        Some(ServerAction::GitStatus(git)) => {
+            if let Err(e) = crate::gitmon::run(&git.dir, git.interval) {
+                if e.kind() != std::io::ErrorKind::BrokenPipe {
+                    eprintln!("tmuxy git: {e}");
+                    std::process::exit(1);
+                }
+            }
+        }
+    }
+}
+
+fn print_stats(args: &StatsArgs) {
+    use tmuxy_core::usage;
+    let summary = match usage::summarize(&usage::usage_db_path(), args.days, args.limit) {
+        Ok(summary) => summary,
+        Err(e) => {
+            eprintln!("tmuxy stats: {e}");
+            std::process::exit(1);
+        }
+    };
+    if args.json {
+        match serde_json::to_string_pretty(&summary) {
+            Ok(json) => println!("{json}"),
+            Err(e) => {
+                eprintln!("tmuxy stats: {e}");
+                std::process::exit(1);
+            }
+        }
+    } else {
+        print!("{}", summary.render());
+    }
+}
+
+fn print_markdown(args: &MdArgs) {
+    use std::io::{Read as _, Write as _};
+    let mut markdown = String::new();
+    let read = if args.file.as_os_str() == "-" {
+        std::io::stdin().read_to_string(&mut markdown).map(|_| ())
+    } else {
+        std::fs::read_to_string(&args.file).map(|text| markdown = text)
+    };
+    if let Err(e) = read {
+        eprintln!("tmuxy md: {}: {e}", args.file.display());
+        std::process::exit(1);
     }
+    let output = if args.html {
+        tmuxy_core::markdown::to_html(&markdown)
+    } else {
+        let width = args
+            .width
+            .or_else(|| std::env::var("COLUMNS").ok()?.parse().ok())
+            .unwrap_or(80);
+        tmuxy_core::markdown::to_ansi(&markdown, width)
+    };
+    // A closed pipe (`tmuxy md README.md | head`) is a normal way to stop.
+    let _ = std::io::stdout().write_all(output.as_bytes());
+}
+
+/// Build `args.template`'s session and print its name, or print the
+/// commands that would.
+fn build_from_template(args: &UpArgs) -> tmuxy_core::error::Result<()> {
+    use tmuxy_core::command_line::join_commands;
+    use tmuxy_core::template::{build_session, SessionTemplate};
+    let template = SessionTemplate::load(&args.template)?;
+    let name = args
+        .session
+        .clone()
+        .or_else(|| template.name.clone())
+        .or_else(|| {
+            let stem = args.template.file_stem()?;
+            Some(stem.to_string_lossy().into_owned())
+        })
+        .unwrap_or_default();
+    // Relative roots in the template are relative to the file.
+    let base_dir = std::env::current_dir()?.join(args.template.parent().unwrap_or(Path::new("")));
+    let plan = template.plan(&name, &base_dir)?;
+    if args.dry_run {
+        let mut new_session = vec!["new-session".to_string()];
+        new_session.extend(plan.new_session.iter().cloned());
+        for command in std::iter::once(&new_session).chain(&plan.commands) {
+            println!("{}", join_commands(std::slice::from_ref(command)));
+        }
+        return Ok(());
+    }
+    build_session(&tmuxy_core::session::TmuxTransport::from_env(), &plan)?;
+    println!("{name}");
+    Ok(())
+}
+
+/// Start the server in the foreground or, with `--daemon`, in the background.
+fn launch(args: ServerArgs, start: StartArgs) {
+    let dev_mode = args.dev || std::env::var("TMUXY_DEV").is_ok();
+    if start.daemon {
+        if dev_mode {
+            // The dev server doesn't write the pid file, so nothing could stop it.
+            eprintln!("--daemon is not supported with --dev");
+            std::process::exit(1);
+        }
+        let log = start
+            .log_file
+            .unwrap_or_else(|| tmuxy_dir().join("tmuxy.log"));
+        daemonize(&log);
+    } else if let Some(log) = start.log_file {
+        if let Err(e) = redirect_output(&log, false) {
+            eprintln!("Failed to open log file {}: {e}", log.display());
+            std::process::exit(1);
+        }
+    }
+
+    export_default_commands(args.default_command.as_deref(), &args.session_command);
+    let password = resolve_password(args.password);
+    let origins =
+        match OriginPolicy::resolve(args.origin_check, args.allowed_origin, password.is_some()) {
+            Ok(origins) => origins,
+            Err(e) => {
+                eprintln!("tmuxy server: {e}");
+                std::process::exit(2);
+            }
+        };
+    let runtime = match tokio::runtime::Builder::new_multi_thread()
+        .enable_all()
+        .build()
+    {
+        Ok(runtime) => runtime,
+        Err(e) => {
+            eprintln!("Failed to start the async runtime: {e}");
+            std::process::exit(1);
+        }
+    };
+    runtime.block_on(async move {
+        if dev_mode {
+            start_dev_server(args.port, args.listen, password, origins).await;
+        } else {
+            start_server(args.port, args.host, args.listen, password, origins).await;
+        }
+    });
 }
 
 /// Start the development server with Vite and demo proxies
-async fn start_dev_server(requested_port: u16, password: Option<String>) {
+async fn start_dev_server(
+    requested_port: u16,
+    listen: Vec<Listen>,
+    password: Option<String>,
+    origins: OriginPolicy,
+) {
     // Honor PORT env (legacy) when present, otherwise fall back to the CLI arg.
     let port = std::env::var("PORT")
         .ok()
@@ -158,6 +549,11 @@ async fn start_dev_server(requested_port: u16, password: Option<String>) {
     // `$HOME/.config/tmuxy/bin/tmuxy/…` path. Mirrors gui.rs setup().
     tmuxy_core::session::ensure_bin_scripts();
     let state = Arc::new(AppState::new());
+    state
+        .spawn(crate::logging::toggle_debug_on_sigusr1(
+            state.shutdown.clone(),
+        ))
+        .await;
 
     println!(
         "[dev] Starting Vite dev server on port {}...",
@@ -178,7 +574,8 @@ async fn start_dev_server(requested_port: u16, password: Option<String>) {
 
     tokio::time::sleep(tokio::time::Duration::from_secs(2)).await;
 
-    let app = crate::state::api_routes()
+    let app = crate::state::api_routes(&state)
+        .merge(crate::admin::routes(password.is_some()))
         .route(
             "/demo",
             axum::routing::any(|req: Request| async move { dev::proxy_to_demo(req).await }),
@@ -192,57 +589,88 @@ async fn start_dev_server(requested_port: u16, password: Option<String>) {
         }))
         .with_state(state.clone());
     let password_set = password.is_some();
-    let app = with_optional_auth(app, password);
+    let app = with_optional_auth(
+        with_origin_policy(app, origins),
+        password,
+        state.tokens.clone(),
+    );
 
-    let addr = std::net::SocketAddr::from(([0, 0, 0, 0], port));
-    println!("tmuxy dev server running at http://localhost:{}", port);
-    announce_security("0.0.0.0", password_set);
+    let listens = if listen.is_empty() {
+        vec![Listen::Tcp(std::net::SocketAddr::from((
+            [0, 0, 0, 0],
+            port,
+        )))]
+    } else {
+        listen
+    };
+    let listeners = listen::bind_all(&listens).await;
+    for listen in &listens {
+        match listen {
+            Listen::Tcp(addr) => println!(
+                "tmuxy dev server running at http://localhost:{}",
+                addr.port()
+            ),
+            Listen::Unix(_) => println!("tmuxy dev server running at {listen}"),
+        }
+    }
+    announce_security(&listens, password_set);
     println!(
         "[dev] Vite proxied from port {}, demo proxied from port {}",
         dev::VITE_PORT,
         dev::DEMO_PORT
     );
 
-    let listener = bind_with_retry(addr, 5).await;
-
-    if let Err(e) = axum::serve(listener, app)
-        .with_graceful_shutdown(shutdown_signal(state, vec![vite_child, demo_child]))
-        .await
-    {
-        error!(error = %e, "axum serve loop exited with error");
-    }
+    serve(listeners, app, state, vec![vite_child, demo_child]).await;
 }
 
 /// Start the production server with embedded frontend assets
-async fn start_server(port: u16, host: String, password: Option<String>) {
-    write_pid_file();
+async fn start_server(
+    port: u16,
+    host: String,
+    listen: Vec<Listen>,
+    password: Option<String>,
+    origins: OriginPolicy,
+) {
+    let listens = if listen.is_empty() {
+        let addr: std::net::SocketAddr = format!("{}:{}", host, port)
+            .parse()
+            .unwrap_or_else(|_| std::net::SocketAddr::from(([0, 0, 0, 0], port)));
+        vec![Listen::Tcp(addr)]
+    } else {
+        listen
+    };
+    let urls: Vec<String> = listens.iter().map(ToString::to_string).collect();
     tmuxy_core::session::ensure_config();
     tmuxy_core::session::ensure_themes();
     tmuxy_core::session::ensure_bin_scripts();
 
     let state = Arc::new(AppState::new());
+    state
+        .spawn(crate::logging::toggle_debug_on_sigusr1(
+            state.shutdown.clone(),
+        ))
+        .await;
 
-    let app = crate::state::api_routes()
+    let app = crate::state::api_routes(&state)
+        .merge(crate::admin::routes(password.is_some()))
         .fallback(serve_embedded)
         .with_state(state.clone());
     let password_set = password.is_some();
-    let app = with_optional_auth(app, password);
-
-    let addr: std::net::SocketAddr = format!("{}:{}", host, port)
-        .parse()
-        .unwrap_or_else(|_| std::net::SocketAddr::from(([0, 0, 0, 0], port)));
-
-    println!("tmuxy server running at http://{}:{}", host, port);
-    announce_security(&host, password_set);
-
-    let listener = bind_with_retry(addr, 5).await;
+    let app = with_optional_auth(
+        with_origin_policy(app, origins),
+        password,
+        state.tokens.clone(),
+    );
 
-    if let Err(e) = axum::serve(listener, app)
-        .with_graceful_shutdown(shutdown_signal(state, vec![]))
-        .await
-    {
-        error!(error = %e, "axum serve loop exited with error");
+    let listeners = listen::bind_all(&listens).await;
+    // Only now: `start --daemon` reports the server up once it appears.
+    write_pid_file(&urls.join(" "));
+    for url in &urls {
+        println!("tmuxy server running at {}", url);
     }
+    announce_security(&listens, password_set);
+
+    serve(listeners, app, state, vec![]).await;
 
     remove_pid_file();
 }
@@ -312,27 +740,53 @@ fn mime_for_path(path: &str) -> &'static str {
 // PID file management
 // ============================================
 
-fn pid_file_path() -> std::path::PathBuf {
+/// `~/.tmuxy`, home of the pid file and the daemon's default log.
+fn tmuxy_dir() -> PathBuf {
     let dir = dirs::home_dir()
-        .unwrap_or_else(|| std::path::PathBuf::from("/tmp"))
+        .unwrap_or_else(|| PathBuf::from("/tmp"))
         .join(".tmuxy");
     std::fs::create_dir_all(&dir).ok();
-    dir.join("tmuxy.pid")
+    dir
+}
+
+fn pid_file_path() -> PathBuf {
+    tmuxy_dir().join("tmuxy.pid")
 }
 
-fn write_pid_file() {
+/// The pid file holds the server's pid, then the URL it serves.
+fn write_pid_file(url: &str) {
     let pid = std::process::id();
-    std::fs::write(pid_file_path(), pid.to_string()).ok();
+    std::fs::write(pid_file_path(), format!("{pid}\n{url}\n")).ok();
 }
 
 fn remove_pid_file() {
     std::fs::remove_file(pid_file_path()).ok();
 }
 
-fn read_pid_file() -> Option<u32> {
+/// Pid and URL from pid file contents. Files written before the URL was
+/// recorded hold only the pid.
+fn parse_pid_file(contents: &str) -> Option<(u32, Option<String>)> {
+    let mut lines = contents.lines();
+    let pid = lines.next()?.trim().parse().ok()?;
+    let url = lines
+        .next()
+        .map(str::trim)
+        .filter(|url| !url.is_empty())
+        .map(String::from);
+    Some((pid, url))
+}
+
+fn read_pid_file() -> Option<(u32, Option<String>)> {
     std::fs::read_to_string(pid_file_path())
         .ok()
-        .and_then(|s| s.trim().parse().ok())
+        .and_then(|s| parse_pid_file(&s))
+}
+
+/// Pid of the server the pid file names, if it is alive.
+fn running_server_pid() -> Option<u32> {
+    read_pid_file()
+        .map(|(pid, _)| pid)
+        .filter(|&pid| is_process_alive(pid))
 }
 
 #[cfg(unix)]
@@ -345,70 +799,209 @@ fn is_process_alive(_pid: u32) -> bool {
     false
 }
 
-fn stop_server() {
-    match read_pid_file() {
-        Some(pid) => {
-            if !is_process_alive(pid) {
-                println!("Server is not running (stale PID file for pid {})", pid);
-                remove_pid_file();
-                return;
-            }
+/// How long `stop` waits for the server to finish its graceful shutdown.
+const STOP_TIMEOUT: Duration = Duration::from_secs(10);
 
-            #[cfg(unix)]
-            {
-                use nix::sys::signal::{self, Signal};
-                use nix::unistd::Pid;
-                match signal::kill(Pid::from_raw(pid as i32), Signal::SIGTERM) {
-                    Ok(_) => {
-                        println!("Sent SIGTERM to server (pid {})", pid);
-                        remove_pid_file();
-                    }
-                    Err(e) => error!(pid, error = %e, "failed to stop server"),
-                }
-            }
+/// SIGTERM the running server and wait for it to exit. Returns false when
+/// it couldn't be stopped; nothing running counts as stopped.
+fn stop_server() -> bool {
+    let Some((pid, _)) = read_pid_file() else {
+        println!("Server is not running (no PID file found)");
+        return true;
+    };
+    if !is_process_alive(pid) {
+        println!("Server is not running (stale PID file for pid {})", pid);
+        remove_pid_file();
+        return true;
+    }
 
-            #[cfg(not(unix))]
-            error!("Stop not supported on this platform");
+    #[cfg(unix)]
+    {
+        use nix::sys::signal::{self, Signal};
+        use nix::unistd::Pid;
+        if let Err(e) = signal::kill(Pid::from_raw(pid as i32), Signal::SIGTERM) {
+            error!(pid, error = %e, "failed to stop server");
+            return false;
         }
-        None => println!("Server is not running (no PID file found)"),
+        println!("Sent SIGTERM to server (pid {})", pid);
+        let deadline = Instant::now() + STOP_TIMEOUT;
+        while is_process_alive(pid) {
+            if Instant::now() >= deadline {
+                eprintln!(
+                    "Server (pid {}) is still running after {}s",
+                    pid,
+                    STOP_TIMEOUT.as_secs()
+                );
+                return false;
+            }
+            std::thread::sleep(Duration::from_millis(100));
+        }
+        // The server removes it on a clean exit; this covers the rest.
+        remove_pid_file();
+        println!("Server stopped");
+        true
+    }
+
+    #[cfg(not(unix))]
+    {
+        error!("Stop not supported on this platform");
+        false
     }
 }
 
-fn server_status() {
+/// Print whether the server is running. Returns whether it is.
+fn server_status() -> bool {
     match read_pid_file() {
-        Some(pid) => {
+        Some((pid, url)) => {
             if is_process_alive(pid) {
-                println!("Server is running (pid {})", pid);
+                match url {
+                    Some(url) => println!("Server is running (pid {}) at {}", pid, url),
+                    None => println!("Server is running (pid {})", pid),
+                }
+                true
             } else {
                 println!("Server is not running (stale PID file for pid {})", pid);
                 remove_pid_file();
+                false
             }
         }
-        None => println!("Server is not running"),
-    }
-}
-
-/// Bind to addr, retrying up to `max_retries` times with 1s delay if port is in use.
-async fn bind_with_retry(addr: std::net::SocketAddr, max_retries: u32) -> tokio::net::TcpListener {
-    for attempt in 0..=max_retries {
-        match tokio::net::TcpListener::bind(addr).await {
-            Ok(listener) => return listener,
-            Err(e) if attempt < max_retries => {
-                warn!(
-                    port = addr.port(),
-                    attempt = attempt + 1,
-                    max_retries,
-                    error = %e,
-                    "port in use, retrying in 1s"
-                );
-                tokio::time::sleep(std::time::Duration::from_secs(1)).await;
-            }
-            Err(e) => {
-                panic!("Failed to bind to {}: {}", addr, e);
-            }
+        None => {
+            println!("Server is not running");
+            false
+        }
+    }
+}
+
+// ============================================
+// Daemonization
+// ============================================
+
+/// How long the launching process waits for the daemon to write its pid file.
+const DAEMON_START_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Point stdout and stderr (and with `detach`, stdin: `/dev/null`) at the
+/// end of `log`.
+#[cfg(unix)]
+fn redirect_output(log: &Path, detach: bool) -> std::io::Result<()> {
+    use std::os::fd::AsRawFd;
+    let file = std::fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(log)?;
+    let mut redirects = vec![(file.as_raw_fd(), libc::STDOUT_FILENO)];
+    redirects.push((file.as_raw_fd(), libc::STDERR_FILENO));
+    let null = if detach {
+        Some(std::fs::File::open("/dev/null")?)
+    } else {
+        None
+    };
+    if let Some(null) = &null {
+        redirects.push((null.as_raw_fd(), libc::STDIN_FILENO));
+    }
+    for (from, to) in redirects {
+        // SAFETY: both descriptors are open; dup2 replaces `to` atomically and
+        // the originals are closed when `file` and `null` drop.
+        if unsafe { libc::dup2(from, to) } < 0 {
+            return Err(std::io::Error::last_os_error());
+        }
+    }
+    Ok(())
+}
+
+#[cfg(not(unix))]
+fn redirect_output(_log: &Path, _detach: bool) -> std::io::Result<()> {
+    Err(std::io::Error::new(
+        std::io::ErrorKind::Unsupported,
+        "log redirection is not supported on this platform",
+    ))
+}
+
+/// Carry on in the background, detached from the terminal, with output
+/// appended to `log`. The calling process waits for the daemon to write the
+/// pid file, reports it and exits; only the daemon returns.
+///
+/// Forks twice: the first child starts a new session, and its own child,
+/// not being a session leader, can never acquire a controlling terminal.
+#[cfg(unix)]
+fn daemonize(log: &Path) {
+    use nix::unistd::{fork, setsid, ForkResult};
+
+    // Open it up front, where the error can still be reported.
+    if let Err(e) = std::fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(log)
+    {
+        eprintln!("Failed to open log file {}: {e}", log.display());
+        std::process::exit(1);
+    }
+    let previous = read_pid_file().map(|(pid, _)| pid);
+
+    // SAFETY: `run` daemonizes before building the async runtime, so the
+    // process still has a single thread and the child inherits no lock
+    // another thread held.
+    match unsafe { fork() } {
+        Ok(ForkResult::Parent { child }) => {
+            let _ = nix::sys::wait::waitpid(child, None);
+            wait_for_daemon(previous, log);
+        }
+        Ok(ForkResult::Child) => {}
+        Err(e) => {
+            eprintln!("Failed to fork: {e}");
+            std::process::exit(1);
+        }
+    }
+
+    if let Err(e) = setsid() {
+        eprintln!("Failed to start a new session: {e}");
+        std::process::exit(1);
+    }
+    // SAFETY: as above; the first child is still single-threaded.
+    match unsafe { fork() } {
+        Ok(ForkResult::Parent { .. }) => std::process::exit(0),
+        Ok(ForkResult::Child) => {}
+        Err(e) => {
+            eprintln!("Failed to fork: {e}");
+            std::process::exit(1);
+        }
+    }
+
+    if let Err(e) = redirect_output(log, true) {
+        eprintln!("Failed to redirect output to {}: {e}", log.display());
+        std::process::exit(1);
+    }
+    // The working directory stays: new sessions start in the workspace
+    // found from it (see `find_workspace_root`).
+}
+
+#[cfg(not(unix))]
+fn daemonize(_log: &Path) {
+    eprintln!("--daemon is not supported on this platform");
+    std::process::exit(1);
+}
+
+/// In the launching process: report the daemon once its pid file appears,
+/// then exit.
+#[cfg(unix)]
+fn wait_for_daemon(previous: Option<u32>, log: &Path) -> ! {
+    let deadline = Instant::now() + DAEMON_START_TIMEOUT;
+    while Instant::now() < deadline {
+        if let Some(pid) = running_server_pid().filter(|&pid| Some(pid) != previous) {
+            println!(
+                "tmuxy server started (pid {}), logging to {}",
+                pid,
+                log.display()
+            );
+            std::process::exit(0);
         }
+        std::thread::sleep(Duration::from_millis(100));
     }
-    unreachable!()
+    eprintln!(
+        "tmuxy server did not start within {}s; see {}",
+        DAEMON_START_TIMEOUT.as_secs(),
+        log.display()
+    );
+    std::process::exit(1);
 }
 
 async fn shutdown_signal(state: Arc<AppState>, children: Vec<Option<dev::ViteChild>>) {
@@ -461,3 +1054,73 @@ async fn shutdown_signal(state: Arc<AppState>, children: Vec<Option<dev::ViteChi
         child.kill();
     }
 }
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used, clippy::expect_used)]
+mod tests {
+    use super::*;
+    use clap::Parser;
+
+    #[derive(Parser)]
+    struct Cli {
+        #[command(flatten)]
+        server: ServerArgs,
+    }
+
+    #[test]
+    fn options_may_follow_the_action() {
+        let cli = Cli::try_parse_from([
+            "tmuxy-server",
+            "start",
+            "--daemon",
+            "--port",
+            "9100",
+            "--log-file",
+            "/tmp/t.log",
+        ])
+        .unwrap();
+        assert_eq!(cli.server.port, 9100);
+        match cli.server.action {
+            Some(ServerAction::Start(start)) => {
+                assert!(start.daemon);
+                assert_eq!(start.log_file, Some(PathBuf::from("/tmp/t.log")));
+            }
+            _ => panic!("expected start"),
+        }
+        let cli = Cli::try_parse_from(["tmuxy-server", "--host", "127.0.0.1", "restart"]).unwrap();
+        assert_eq!(cli.server.host, "127.0.0.1");
+        assert!(matches!(
+            cli.server.action,
+            Some(ServerAction::Restart(StartArgs { daemon: false, .. }))
+        ));
+    }
+
+    #[test]
+    fn session_commands_split_at_the_first_equals() {
+        let cli = Cli::try_parse_from([
+            "tmuxy-server",
+            "--session-command",
+            "work=env EDITOR=vim bash -l",
+            "--default-command",
+            "ssh build-box",
+        ])
+        .unwrap();
+        assert_eq!(
+            cli.server.session_command,
+            [("work".to_string(), "env EDITOR=vim bash -l".to_string())]
+        );
+        assert_eq!(cli.server.default_command.as_deref(), Some("ssh build-box"));
+        assert!(Cli::try_parse_from(["tmuxy-server", "--session-command", "bash -l"]).is_err());
+        assert!(Cli::try_parse_from(["tmuxy-server", "--session-command", "=bash"]).is_err());
+    }
+
+    #[test]
+    fn pid_file_url_is_optional() {
+        assert_eq!(
+            parse_pid_file("42\nhttp://0.0.0.0:9000\n"),
+            Some((42, Some("http://0.0.0.0:9000".to_string())))
+        );
+        assert_eq!(parse_pid_file("42\n"), Some((42, None)));
+        assert_eq!(parse_pid_file("nope"), None);
+    }
+}
@@ -5,32 +5,44 @@ use axum::{
         sse::{Event, KeepAlive, Sse},
         IntoResponse, Response,
     },
-    Json,
+    Extension, Json,
 };
-use futures_util::stream::Stream;
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::VecDeque;
+use std::collections::{HashMap, HashSet};
 use std::convert::Infallible;
-use std::sync::atomic::Ordering;
-use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Arc, Mutex as StdMutex};
 use std::time::Duration;
+use tmuxy_core::color_transform::ColorTransforms;
 use tmuxy_core::control_mode::{
-    LogKind, LogSink, MonitorCommand, MonitorConfig, StateEmitter, TmuxMonitor,
+    ExitSummary, LogKind, LogSink, MonitorCommand, MonitorCommandSender, MonitorConfig,
+    MonitorMetric, ServerEvent, StateEmitter, TmuxMonitor,
 };
-use tmuxy_core::{executor, StateUpdate};
-use tokio::sync::broadcast;
-use tracing::{debug, error, info, instrument, trace, warn};
-
-use crate::command::ClientCommand;
-use crate::state::{AppState, SessionConnections};
+use tmuxy_core::key_table::{binding_key_name, KeyAction, KeyTables};
+use tmuxy_core::keyboard::KeyEvent;
+use tmuxy_core::pane_search::PaneSearches;
+use tmuxy_core::pane_view::PaneView;
+use tmuxy_core::session::TmuxTransport;
+use tmuxy_core::table::TableCache;
+use tmuxy_core::{executor, Ctx, StateUpdate, TmuxState};
+use tmuxy_protocol::ClientCommand;
+use tokio::sync::{broadcast, watch, Notify};
+use tokio::task::JoinHandle;
+use tokio_util::sync::CancellationToken;
+use tracing::{debug, error, info, info_span, instrument, trace, warn, Instrument};
+
+use crate::hooks::{self, Hook, HookEvent};
+use crate::rate_limit::CommandClient;
+use crate::state::{
+    AppState, SessionBroadcast, SessionConnections, TaggedEvent, EVENT_BUFFER_SIZE,
+};
+use crate::tokens::{Access, Scope};
+use tmuxy_core::dispatch::{dispatch, CommandHost, EventSink};
 
-/// How long to wait after a `source-file` before re-reading keybindings.
-///
-/// `RunCommand` is fire-and-forget into the monitor channel, so there is no
-/// response to key off — this is a settle window, not a guarantee. A slow
-/// `source-file` can still broadcast the pre-source bindings; the correct fix
-/// is to await the command's control-mode response.
-const SOURCE_FILE_SETTLE: Duration = Duration::from_millis(200);
+/// Highest frame rate a client may request with `set_frame_budget`. Above
+/// this the monitor's own throttle is the limit anyway.
+const MAX_FRAME_RATE: u32 = 120;
 
 // ============================================
 // SSE State Emitter (Adapter Pattern)
@@ -38,7 +50,7 @@ const SOURCE_FILE_SETTLE: Duration = Duration::from_millis(200);
 
 /// Extract the SSE `event:` discriminator from an already-serialised JSON
 /// payload. We peek at the `event` field rather than deserialising into the
-/// full `SseEvent` enum because the discriminator is the only thing we need
+/// full `ServerEvent` enum because the discriminator is the only thing we need
 /// and StateUpdate / Box<...> deserialisation is expensive on the hot path.
 /// Falls back to `"state-update"` for unknown shapes to match the legacy
 /// fallback behaviour.
@@ -49,30 +61,281 @@ fn sse_event_type(payload: &str) -> &'static str {
     if let Some(idx) = payload.find("\"event\":\"") {
         let rest = &payload[idx + "\"event\":\"".len()..];
         if let Some(end) = rest.find('"') {
-            return match &rest[..end] {
-                "state-update" => "state-update",
-                "error" => "error",
-                "connection-info" => "connection-info",
-                "keybindings" => "keybindings",
-                "log" => "log",
-                "fatal" => "fatal",
-                "clipboard" => "clipboard",
-                _ => "state-update",
-            };
+            if let Some(name) = ServerEvent::NAMES
+                .iter()
+                .find(|name| **name == &rest[..end])
+            {
+                return name;
+            }
         }
     }
     "state-update"
 }
 
-/// Serialize an `SseEvent` (or compatible serde value) into a wire-format
-/// JSON string, logging — rather than panicking — on failure.
+/// Whether an already-serialised payload is a full state snapshot. Same
+/// substring peek as [`sse_event_type`]: serde writes the `event` tag, then
+/// `data`, whose first field is the `StateUpdate` `type` tag.
+fn is_full_state(payload: &str) -> bool {
+    payload.starts_with(r#"{"event":"state-update","data":{"type":"full""#)
+}
+
+/// Whether an outbox event can have been queued for its client alone (see
+/// [`Outbox::push_full`] and [`Outbox::push_reply`]): a full state or a
+/// search's results, which the broadcast never carries.
+fn is_own_event(payload: &str) -> bool {
+    is_full_state(payload) || sse_event_type(payload) == "search-results"
+}
+
+/// Queue `event` for the next paced frame. A full snapshot supersedes every
+/// state update still queued — the client would only overwrite them — so
+/// those are dropped; other events (logs, clipboard, …) are kept in order.
+fn queue_frame_event(pending: &mut Vec<TaggedEvent>, event: TaggedEvent) {
+    if is_full_state(&event.1) {
+        pending.retain(|(_, msg)| sse_event_type(msg) != "state-update");
+    }
+    pending.push(event);
+}
+
+/// The seq of an encoded state-update delta; `None` for anything else.
+fn delta_seq(msg: &str) -> Option<u64> {
+    match serde_json::from_str::<ServerEvent>(msg) {
+        Ok(ServerEvent::StateUpdate(update)) => match *update {
+            StateUpdate::Delta { delta } => Some(delta.seq),
+            StateUpdate::Full { .. } => None,
+        },
+        _ => None,
+    }
+}
+
+/// Fold two encoded state-update deltas into one, or `None` if either isn't
+/// a delta or they can't be merged exactly (see `TmuxDelta::can_merge`).
+fn merge_delta_events(prev: &str, next: &str) -> Option<String> {
+    let decode = |msg: &str| match serde_json::from_str::<ServerEvent>(msg) {
+        Ok(ServerEvent::StateUpdate(update)) => match *update {
+            StateUpdate::Delta { delta } => Some(delta),
+            StateUpdate::Full { .. } => None,
+        },
+        _ => None,
+    };
+    let (mut merged, next) = (decode(prev)?, decode(next)?);
+    if !merged.can_merge(&next) {
+        return None;
+    }
+    merged.merge(next);
+    encode_event(&ServerEvent::StateUpdate(Box::new(StateUpdate::Delta {
+        delta: merged,
+    })))
+}
+
+/// One connection's queue between the session broadcast and its SSE stream.
+///
+/// A forwarding task drains the broadcast into it as events arrive, so a
+/// client whose socket drains slowly never makes the broadcast drop events
+/// for it. Instead, state updates pile up here and are coalesced: a delta is
+/// folded into the one still queued before it, and a full state supersedes
+/// every queued update. When deltas can't be folded and the backlog outgrows
+/// the ring buffer, the queued updates are dropped and the forwarder queues
+/// a full state for this client alone (see [`Outbox::push_full`]). A client
+/// keeping up never has more than one event queued, so it pays nothing for
+/// this.
+#[derive(Default)]
+pub(crate) struct Outbox {
+    events: StdMutex<VecDeque<TaggedEvent>>,
+    /// Seq of the last delta a full state from `push_full` includes, while
+    /// deltas up to it may still arrive from the broadcast.
+    resent_through: StdMutex<Option<u64>>,
+    /// Tag of the last event pushed, which a full state from `push_full`
+    /// takes.
+    last_tag: AtomicU64,
+    notify: Notify,
+    closed: AtomicBool,
+}
+
+impl Outbox {
+    /// Queue `event`, coalescing state updates. Returns true when the backlog
+    /// overflowed and queued state updates were dropped — the caller must get
+    /// the client a full state.
+    fn push(&self, event: TaggedEvent) -> bool {
+        let Ok(mut events) = self.events.lock() else {
+            return false;
+        };
+        self.last_tag.store(event.0, Ordering::Relaxed);
+        if self.already_resent(&event.1) {
+            return false;
+        }
+        let overflowed = push_coalesced(&mut events, event);
+        drop(events);
+        self.notify.notify_one();
+        overflowed
+    }
+
+    /// Queue `update`, the session's state in full as of delta `through`.
+    /// The broadcast never carries it, so other clients of the session don't
+    /// pay for this one falling behind; the deltas it already includes are
+    /// dropped as they arrive.
+    fn push_full(&self, update: StateUpdate, through: u64) {
+        let Some(msg) = encode_event(&ServerEvent::StateUpdate(Box::new(update))) else {
+            return;
+        };
+        let Ok(mut events) = self.events.lock() else {
+            return;
+        };
+        let tag = self.last_tag.load(Ordering::Relaxed);
+        push_coalesced(&mut events, (tag, msg));
+        if let Ok(mut resent) = self.resent_through.lock() {
+            *resent = Some(through);
+        }
+        drop(events);
+        self.notify.notify_one();
+    }
+
+    /// Queue `event`, which only this client gets (see
+    /// `CommandHost::reply_sink`). Like a `push_full` state, it takes the tag
+    /// of the event before it.
+    fn push_reply(&self, event: &ServerEvent) {
+        let Some(msg) = encode_event(event) else {
+            return;
+        };
+        let Ok(mut events) = self.events.lock() else {
+            return;
+        };
+        let tag = self.last_tag.load(Ordering::Relaxed);
+        push_coalesced(&mut events, (tag, msg));
+        drop(events);
+        self.notify.notify_one();
+    }
+
+    /// Whether `msg` is a delta the last `push_full` state already includes.
+    /// The first state update past it ends the check.
+    fn already_resent(&self, msg: &str) -> bool {
+        let Ok(mut resent) = self.resent_through.lock() else {
+            return false;
+        };
+        let Some(through) = *resent else {
+            return false;
+        };
+        if sse_event_type(msg) != "state-update" {
+            return false;
+        }
+        if delta_seq(msg).is_some_and(|seq| seq <= through) {
+            return true;
+        }
+        *resent = None;
+        false
+    }
+
+    /// The next queued event; `None` once the broadcast has closed and the
+    /// queue is drained.
+    async fn recv(&self) -> Option<TaggedEvent> {
+        loop {
+            if let Some(event) = self.events.lock().ok()?.pop_front() {
+                return Some(event);
+            }
+            if self.closed.load(Ordering::Acquire) {
+                return None;
+            }
+            self.notify.notified().await;
+        }
+    }
+
+    fn close(&self) {
+        self.closed.store(true, Ordering::Release);
+        self.notify.notify_one();
+    }
+}
+
+/// The queueing half of [`Outbox::push`].
+fn push_coalesced(events: &mut VecDeque<TaggedEvent>, event: TaggedEvent) -> bool {
+    let is_update = |msg: &str| sse_event_type(msg) == "state-update";
+    if is_full_state(&event.1) {
+        events.retain(|(_, msg)| !is_update(msg));
+    } else if is_update(&event.1) {
+        let last_update = events.iter().rposition(|(_, msg)| is_update(msg));
+        if let Some(i) = last_update {
+            if let Some(merged) = merge_delta_events(&events[i].1, &event.1) {
+                // The merged delta takes the later id, so it moves to the back:
+                // ids must keep increasing for Last-Event-Id to resume right.
+                events.remove(i);
+                events.push_back((event.0, merged));
+                return false;
+            }
+        }
+    }
+    events.push_back(event);
+    if events.len() <= EVENT_BUFFER_SIZE {
+        return false;
+    }
+    events.retain(|(_, msg)| !is_update(msg));
+    while events.len() > EVENT_BUFFER_SIZE {
+        events.pop_front();
+    }
+    true
+}
+
+/// Drain the session broadcast into `outbox` until the client goes away or
+/// the broadcast closes.
+async fn forward_to_outbox(
+    mut session_rx: broadcast::Receiver<TaggedEvent>,
+    outbox: &Outbox,
+    session_broadcast: &SessionBroadcast,
+    state: &Arc<AppState>,
+    session: &str,
+    conn_id: u64,
+    cancel: CancellationToken,
+) {
+    let mut last_seq: Option<u64> = None;
+    loop {
+        let received = tokio::select! {
+            _ = cancel.cancelled() => return,
+            received = session_rx.recv() => received,
+        };
+        match received {
+            Ok(event) => {
+                last_seq = Some(event.0);
+                if outbox.push(event) {
+                    warn!(
+                        conn_id,
+                        "client backlog overflowed; resending it a full state"
+                    );
+                    resend_full(state, session, outbox).await;
+                }
+            }
+            Err(broadcast::error::RecvError::Lagged(n)) => {
+                // Only this task's own stall (not the client's) gets here.
+                // Replay whatever is still in the ring buffer above what we've
+                // already queued; if the gap exceeds the buffer, a full state
+                // re-sent by the monitor fills the rest. The stream's
+                // `seq <= last_replayed` dedup absorbs any overlap.
+                warn!(
+                    conn_id,
+                    lagged = n,
+                    "event forwarder lagged; replaying ring buffer"
+                );
+                if session_broadcast.evicted_after(last_seq) {
+                    resend_full(state, session, outbox).await;
+                }
+                for event in session_broadcast.replay_after(last_seq) {
+                    last_seq = Some(event.0);
+                    outbox.push(event);
+                }
+            }
+            Err(broadcast::error::RecvError::Closed) => {
+                outbox.close();
+                return;
+            }
+        }
+    }
+}
+
+/// Serialize a `ServerEvent` into its wire-format JSON string, logging —
+/// rather than panicking — on failure.
 ///
 /// `serde_json::to_string` only fails on `serde::Serialize` impls that error
 /// out, which our event types can't do (every field is a plain type). If a
 /// future variant ever does, we'd rather drop one message than crash the
 /// monitor task that owns the broadcast channel.
-fn encode_event<T: Serialize>(event: &T) -> Option<String> {
-    match serde_json::to_string(event) {
+fn encode_event(event: &ServerEvent) -> Option<String> {
+    match event.encode() {
         Ok(s) => Some(s),
         Err(e) => {
             error!(error = %e, "failed to serialize SSE event");
@@ -81,23 +344,70 @@ fn encode_event<T: Serialize>(event: &T) -> Option<String> {
     }
 }
 
+/// Apply a connection's color transforms to an encoded state update. Other
+/// events, and connections without transforms, pass through untouched — the
+/// decode/re-encode is only paid by connections that asked for it.
+fn transform_event(msg: String, colors: &ColorTransforms) -> String {
+    if colors.is_empty() || sse_event_type(&msg) != "state-update" {
+        return msg;
+    }
+    match serde_json::from_str::<ServerEvent>(&msg) {
+        Ok(ServerEvent::StateUpdate(mut update)) => {
+            colors.apply_update(&mut update);
+            encode_event(&ServerEvent::StateUpdate(update)).unwrap_or(msg)
+        }
+        _ => msg,
+    }
+}
+
+/// Narrow an encoded event to a connection's pane view, if it has one: state
+/// updates are rewritten, or dropped when they say nothing about the pane.
+/// Otherwise only the connection's own lifecycle events and events about the
+/// pane get through; anything else (other clients' failed commands, option
+/// changes, logs) could show what happens outside the pane.
+fn focus_event(msg: String, view: Option<&mut PaneView>) -> Option<String> {
+    let Some(view) = view else {
+        return Some(msg);
+    };
+    if matches!(
+        sse_event_type(&msg),
+        "connection-info" | "fatal" | "session-lost"
+    ) {
+        return Some(msg);
+    }
+    match serde_json::from_str::<ServerEvent>(&msg).ok()? {
+        ServerEvent::StateUpdate(mut update) => view
+            .apply_update(&mut update)
+            .then(|| encode_event(&ServerEvent::StateUpdate(update)))
+            .flatten(),
+        event => (event.pane_id() == Some(view.pane())).then_some(msg),
+    }
+}
+
 /// Emitter that broadcasts state changes to SSE clients
 pub struct SseEmitter {
     broadcast: Arc<crate::state::SessionBroadcast>,
     app_state: Arc<AppState>,
+    /// The session being watched, for the hooks its events fire.
+    session: String,
 }
 
 impl SseEmitter {
-    pub fn new(broadcast: Arc<crate::state::SessionBroadcast>, app_state: Arc<AppState>) -> Self {
+    pub fn new(
+        broadcast: Arc<crate::state::SessionBroadcast>,
+        app_state: Arc<AppState>,
+        session: String,
+    ) -> Self {
         Self {
             broadcast,
             app_state,
+            session,
         }
     }
 
     /// Encode + broadcast in one shot — drops the message on serialize failure
     /// (already logged by `encode_event`).
-    fn send_event(&self, event: &SseEvent) {
+    fn send_event(&self, event: &ServerEvent) {
         if let Some(s) = encode_event(event) {
             self.broadcast.broadcast(s);
         }
@@ -106,31 +416,40 @@ impl SseEmitter {
 
 impl LogSink for SseEmitter {
     fn log(&self, kind: LogKind, message: String) {
-        self.send_event(&SseEvent::Log { kind, message });
+        self.send_event(&ServerEvent::Log { kind, message });
     }
 }
 
 impl StateEmitter for SseEmitter {
-    fn emit_state(&self, update: StateUpdate) {
-        // Garbage-collect orphaned images when we have a full state snapshot
-        if let StateUpdate::Full { ref state } = update {
-            let active_pane_ids: std::collections::HashSet<&str> =
-                state.panes.iter().map(|p| p.tmux_id.as_str()).collect();
-            if let Ok(mut guard) = self.app_state.image_store.try_write() {
-                guard.retain(|(pane_id, _), _| active_pane_ids.contains(pane_id.as_str()));
+    fn emit(&self, event: ServerEvent) {
+        match &event {
+            // Garbage-collect orphaned images when we have a full state snapshot
+            ServerEvent::StateUpdate(update) => {
+                if let StateUpdate::Full { state } = &**update {
+                    let active_pane_ids: std::collections::HashSet<&str> =
+                        state.panes.iter().map(|p| p.tmux_id.as_str()).collect();
+                    if let Ok(mut guard) = self.app_state.image_store.try_write() {
+                        guard.retain(|(pane_id, _), _| active_pane_ids.contains(pane_id.as_str()));
+                    }
+                }
+                self.app_state.metrics.record_state_update();
             }
+            ServerEvent::Bell { pane_id, .. } => hooks::fire(
+                &self.app_state,
+                Hook::new(HookEvent::Bell, &self.session).with("PANE_ID", pane_id),
+            ),
+            _ => {}
         }
-        self.send_event(&SseEvent::StateUpdate(Box::new(update)));
-    }
-
-    fn emit_error(&self, error: String) {
-        self.send_event(&SseEvent::Error { message: error });
+        self.send_event(&event);
     }
 
     fn on_initial_sync_complete(&self) {
         // Broadcast keybindings now that config has been sourced and settings enforced.
-        let keybindings = KeyBindings::current();
-        self.send_event(&SseEvent::KeyBindings(keybindings));
+        self.send_event(&ServerEvent::KeyBindings(tmuxy_core::get_key_bindings()));
+    }
+
+    fn record_metric(&self, metric: MonitorMetric) {
+        self.app_state.metrics.record(metric);
     }
 
     fn store_images(
@@ -147,62 +466,18 @@ impl StateEmitter for SseEmitter {
         }
     }
 
-    fn write_clipboard(&self, pane_id: &str, text: String) {
-        self.send_event(&SseEvent::Clipboard {
-            pane_id: pane_id.to_string(),
-            text,
-        });
-    }
-}
-
-// ============================================
-// SSE Event Types
-// ============================================
-
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct KeyBindings {
-    pub prefix_key: String,
-    pub prefix_bindings: Vec<tmuxy_core::KeyBinding>,
-    pub root_bindings: Vec<tmuxy_core::KeyBinding>,
-}
-
-impl KeyBindings {
-    /// Snapshot the live tmux bindings with the standard fallbacks. The one
-    /// assembly point for the SSE greeting, `on_initial_sync_complete`, and
-    /// `broadcast_keybindings` (previously three identical copies).
-    fn current() -> Self {
-        Self {
-            prefix_key: tmuxy_core::get_prefix_key().unwrap_or_else(|_| "C-b".into()),
-            prefix_bindings: tmuxy_core::get_prefix_bindings().unwrap_or_default(),
-            root_bindings: tmuxy_core::get_root_bindings().unwrap_or_default(),
-        }
+    fn pane_exited(&self, summary: &ExitSummary) {
+        hooks::fire(
+            &self.app_state,
+            Hook::new(HookEvent::PaneExit, &self.session)
+                .with("PANE_ID", &summary.pane_id)
+                .with("WINDOW_ID", &summary.window_id)
+                .with("WINDOW_NAME", &summary.window_name)
+                .with("PANE_COMMAND", &summary.command),
+        );
     }
 }
 
-#[derive(Debug, Serialize, Deserialize)]
-#[serde(tag = "event", content = "data")]
-enum SseEvent {
-    #[serde(rename = "connection-info")]
-    ConnectionInfo {
-        connection_id: u64,
-        default_shell: String,
-    },
-    #[serde(rename = "state-update")]
-    StateUpdate(Box<StateUpdate>),
-    #[serde(rename = "error")]
-    Error { message: String },
-    #[serde(rename = "keybindings")]
-    KeyBindings(KeyBindings),
-    #[serde(rename = "log")]
-    Log { kind: LogKind, message: String },
-    #[serde(rename = "fatal")]
-    Fatal { message: String },
-    /// OSC 52 clipboard request from a terminal application.
-    /// Frontend mirrors the text into the system clipboard via navigator.clipboard.
-    #[serde(rename = "clipboard")]
-    Clipboard { pane_id: String, text: String },
-}
-
 // ============================================
 // Command Types
 // ============================================
@@ -222,6 +497,42 @@ pub struct CommandResponse {
 #[derive(Debug, Deserialize)]
 pub struct SessionQuery {
     session: Option<String>,
+    /// Accessibility color transforms for this connection's cells, e.g.
+    /// `high-contrast,red-green-safe` (see `tmuxy_core::color_transform`).
+    colors: Option<String>,
+    /// `light` when the client theme has a light background.
+    background: Option<String>,
+    /// Only this pane (`%5`): events narrowed to it by a `PaneView`, and
+    /// commands limited to it.
+    pane: Option<String>,
+}
+
+impl SessionQuery {
+    /// The session asked for, defaulting to the standard session name. Any
+    /// existing session can be named, tmuxy's or not (see
+    /// `tmuxy_core::discovery`); names tmux would rewrite are refused.
+    pub(crate) fn session(&self) -> Result<String, String> {
+        let session = self
+            .session
+            .clone()
+            .unwrap_or_else(|| tmuxy_core::DEFAULT_SESSION_NAME.to_string());
+        tmuxy_core::discovery::validate_session_name(&session)?;
+        Ok(session)
+    }
+
+    /// The pane asked for, if any.
+    fn pane(&self) -> Result<Option<&str>, String> {
+        match self.pane.as_deref() {
+            Some(pane)
+                if !pane
+                    .strip_prefix('%')
+                    .is_some_and(|n| !n.is_empty() && n.bytes().all(|b| b.is_ascii_digit())) =>
+            {
+                Err(format!("invalid pane id: {pane}"))
+            }
+            pane => Ok(pane),
+        }
+    }
 }
 
 // ============================================
@@ -232,19 +543,53 @@ pub async fn sse_handler(
     State(state): State<Arc<AppState>>,
     Query(query): Query<SessionQuery>,
     headers: HeaderMap,
-) -> Sse<impl Stream<Item = Result<Event, Infallible>>> {
-    let session = query
-        .session
-        .unwrap_or_else(|| tmuxy_core::DEFAULT_SESSION_NAME.to_string());
+    scope: Option<Extension<Scope>>,
+) -> Response {
+    let (session, pane) = match query.session().and_then(|s| Ok((s, query.pane()?))) {
+        Ok(found) => found,
+        Err(error) => {
+            return (
+                StatusCode::BAD_REQUEST,
+                Json(serde_json::json!({ "error": error })),
+            )
+                .into_response();
+        }
+    };
+    // `?pane=%5`: the stream shows that pane alone, for embedding. A
+    // pane-scoped token only ever sees its pane.
+    let mut pane = pane.map(str::to_string);
+    if let Some(Extension(scope)) = scope {
+        let allowed = scope
+            .permits_session(&session)
+            .and_then(|()| match (&scope.pane, &pane) {
+                (Some(allowed), Some(asked)) if allowed != asked => {
+                    Err(format!("this token is limited to pane {allowed}"))
+                }
+                (Some(allowed), _) => {
+                    pane = Some(allowed.clone());
+                    Ok(())
+                }
+                _ => Ok(()),
+            });
+        if let Err(error) = allowed {
+            return (
+                StatusCode::FORBIDDEN,
+                Json(serde_json::json!({ "error": error })),
+            )
+                .into_response();
+        }
+    }
+    let mut pane_view = pane.map(PaneView::new);
 
     // Browser passes the id of the last event it received via the standard
-    // `Last-Event-Id` header on reconnect. If we can find it in the per-session
-    // ring buffer, we replay the missing events. If the id is older than the
-    // buffer head (or absent), the live stream takes over from the next event.
-    let last_event_id: Option<u64> = headers
+    // `Last-Event-Id` header on reconnect. If the per-session ring buffer
+    // still holds everything after it, we replay the missing events.
+    // Otherwise the monitor re-sends its current state in full; with no
+    // header the live stream takes over from the next event.
+    let last_event_id: Option<String> = headers
         .get("last-event-id")
         .and_then(|v| v.to_str().ok())
-        .and_then(|s| s.parse().ok());
+        .map(str::to_owned);
 
     // Generate unique connection ID
     let conn_id = state.next_conn_id.fetch_add(1, Ordering::SeqCst);
@@ -254,14 +599,45 @@ pub async fn sse_handler(
     // with its own control mode connection, without routing through an existing monitor
     // (which would trigger %session-changed and contaminate the original session's state).
 
+    let colors = ColorTransforms::parse(
+        query.colors.as_deref().unwrap_or_default(),
+        query.background.as_deref() == Some("light"),
+    )
+    .unwrap_or_else(|e| {
+        warn!(conn_id, error = %e, "ignoring color transforms");
+        ColorTransforms::default()
+    });
+
+    // Per-connection frame pacing, set by the client's `set_frame_budget`.
+    let (frame_tx, frame_rx) = watch::channel(None::<Duration>);
+
+    // Per-connection outbox: the session's events, and the replies that
+    // follow this client's commands.
+    let outbox = Arc::new(Outbox::default());
+
+    // Fired by the admin API to drop this client.
+    let disconnect = CancellationToken::new();
+
+    // A saved server may front this session on another host; resolved
+    // before taking the lock since it reads servers.json.
+    let remote_ctx = crate::state::remote_session_ctx(&session);
+
     // Register connection and get/create shared session resources
-    let (session_rx, session_broadcast) = {
+    let (session_rx, session_broadcast, clients) = {
         let mut sessions = state.sessions.write().await;
         let session_conns = sessions
             .entry(session.clone())
-            .or_insert_with(SessionConnections::new);
+            .or_insert_with(|| SessionConnections {
+                remote_ctx,
+                ..SessionConnections::new()
+            });
 
         session_conns.connections.push(conn_id);
+        session_conns.frame_budgets.insert(conn_id, frame_tx);
+        session_conns.outboxes.insert(conn_id, outbox.clone());
+        session_conns
+            .disconnects
+            .insert(conn_id, disconnect.clone());
 
         // Subscribe to shared session state channel
         let session_rx = session_conns.broadcast.subscribe();
@@ -294,15 +670,34 @@ pub async fn sse_handler(
             // Ctrl+C the task isn't joined by `shutdown_signal`; instead
             // `start_monitoring`'s own `shutdown.is_cancelled()` checks break
             // its loop promptly.
-            let handle = tokio::spawn(async move {
-                start_monitoring(monitor_broadcast, monitor_session, monitor_state).await;
-            });
+            let span = info_span!("session", session = %monitor_session);
+            let handle = tokio::spawn(
+                async move {
+                    start_monitoring(monitor_broadcast, monitor_session, monitor_state).await;
+                }
+                .instrument(span),
+            );
             session_conns.monitor_handle = Some(handle);
             info!(%session, "started monitor");
+        } else if session_conns.hibernating {
+            session_conns.hibernating = false;
+            session_conns.wake.notify_one();
+            info!(%session, "waking hibernating monitor");
         }
 
-        (session_rx, session_broadcast)
+        (
+            session_rx,
+            session_broadcast,
+            session_conns.connections.len(),
+        )
     };
+    report_client_count(&state, &session).await;
+    hooks::fire(
+        &state,
+        Hook::new(HookEvent::ClientConnect, &session)
+            .with("CLIENT_ID", conn_id)
+            .with("CLIENTS", clients),
+    );
 
     // Create the SSE stream
     //
@@ -313,6 +708,9 @@ pub async fn sse_handler(
     // and when the generator is dropped, the sender is dropped, signaling the cleanup task.
     let (drop_tx, drop_rx) = tokio::sync::oneshot::channel::<()>();
 
+    // Everything done on behalf of this connection logs under its span.
+    let client_span = info_span!("sse_client", %session, conn_id);
+
     // Spawn cleanup task that fires when the stream is dropped (client disconnect).
     // Tracked in `AppState::join_set` so server shutdown drains it instead of
     // leaving it dangling on Ctrl+C.
@@ -332,118 +730,194 @@ pub async fn sse_handler(
                         // monitor's own shutdown path will tear down the session.
                     }
                 }
-            })
+            }.instrument(client_span.clone()))
+            .await;
+    }
+
+    // Fed by its own task so a slow client never lags the broadcast (see
+    // `Outbox`). The task stops when the stream is dropped.
+    let forward_cancel = state.shutdown.child_token();
+    {
+        let outbox = outbox.clone();
+        let session_broadcast = session_broadcast.clone();
+        let forward_state = state.clone();
+        let forward_session = session.clone();
+        let cancel = forward_cancel.clone();
+        state
+            .spawn(
+                async move {
+                    forward_to_outbox(
+                        session_rx,
+                        &outbox,
+                        &session_broadcast,
+                        &forward_state,
+                        &forward_session,
+                        conn_id,
+                        cancel,
+                    )
+                    .await;
+                }
+                .instrument(client_span.clone()),
+            )
             .await;
     }
 
+    let resend_state = state.clone();
+    let resend_session = session.clone();
     let stream = async_stream::stream! {
         // Keep the drop sender alive for the lifetime of the stream.
         // When this generator is dropped (client disconnect), _drop_guard is dropped,
         // which drops drop_tx, signaling the cleanup task.
         let _drop_guard = drop_tx;
+        let _forward_guard = forward_cancel.drop_guard();
 
         // Send connection info as first event
         let default_shell = std::env::var("SHELL")
             .ok()
             .and_then(|s| s.rsplit('/').next().map(String::from))
             .unwrap_or_else(|| "bash".to_string());
-        let conn_info = SseEvent::ConnectionInfo {
+        let conn_info = ServerEvent::ConnectionInfo {
+            protocol_version: tmuxy_core::PROTOCOL_VERSION,
             connection_id: conn_id,
             default_shell,
         };
         if let Some(s) = encode_event(&conn_info) {
-            yield Ok(Event::default().event("connection-info").data(s));
+            yield Ok::<_, Infallible>(Event::default().event("connection-info").data(s));
         }
 
         // Send keybindings to each new SSE client. For reconnecting clients
         // (monitor already running, config already sourced), this is the only
         // chance to receive them. The monitor also broadcasts updated keybindings
         // via on_initial_sync_complete() after sourcing config for the first time.
-        let keybindings = KeyBindings::current();
-        let kb_event = SseEvent::KeyBindings(keybindings);
+        let keybindings = tmuxy_core::get_key_bindings();
+        let kb_event = ServerEvent::KeyBindings(keybindings);
         if let Some(s) = encode_event(&kb_event) {
             yield Ok(Event::default().event("keybindings").data(s));
         }
 
-        let mut session_rx = session_rx;
-
-        // Last-Event-Id replay: if the client reconnected with a known seq,
-        // dump everything in the ring buffer above that seq before entering the
-        // live loop. If the requested seq is older than the buffer head (or no
-        // header at all), we can't fill the gap from cache alone — the live
-        // stream will just resume from the next event, and the client falls
-        // back to its full state on the next StateUpdate::Full broadcast.
-        // Only advance past events we actually replayed from the ring buffer.
-        // Seeding this from a stale Last-Event-Id would make the live loop's
-        // `seq <= last_replayed` dedupe drop every fresh event when the buffer
-        // can't serve the gap (server restart resets the seq counter, or a
-        // >buffer disconnect), freezing the UI.
-        let mut last_replayed: u64 = 0;
-        let oldest = session_broadcast.oldest_seq();
-        let buffer_can_serve = match (last_event_id, oldest) {
-            (Some(le), Some(old)) => le >= old.saturating_sub(1),
-            _ => false,
-        };
-        if buffer_can_serve {
-            let replay = session_broadcast.replay_since(last_event_id.unwrap_or(0));
-            for (seq, msg) in replay {
-                let event_type = sse_event_type(&msg);
-                last_replayed = seq;
-                yield Ok(Event::default()
-                    .event(event_type)
-                    .id(seq.to_string())
-                    .data(msg));
+        // Last-Event-Id replay: if the client reconnected with an id the ring
+        // buffer can resume from, dump everything after it before entering
+        // the live loop. If it can't fill the gap from cache alone, ask the
+        // monitor to re-send the state in full; it arrives through the live
+        // loop like any other update. With no header at all the live stream
+        // just resumes from the next event.
+        // Only advance past events we actually sent. Seeding this from a
+        // stale Last-Event-Id would make the live loop's `seq <= last_replayed`
+        // dedupe drop every fresh event when the buffer can't serve the gap,
+        // freezing the UI; seeding it with 0 would drop seq 0 — the Full a
+        // freshly started monitor opens with.
+        let mut last_replayed: Option<u64> = None;
+        let replay = match last_event_id.as_deref().map(|id| session_broadcast.replay_for(id)) {
+            // A pane view has to start from a full state to narrow; the
+            // client's last delta seq was the view's, not the session's.
+            _ if pane_view.is_some() => {
+                resend_full(&resend_state, &resend_session, &outbox).await;
+                Vec::new()
+            }
+            Some(Some(replay)) => replay,
+            Some(None) => {
+                debug!(conn_id, "Last-Event-Id too old to replay; requesting a full state");
+                resend_full(&resend_state, &resend_session, &outbox).await;
+                Vec::new()
             }
+            None => Vec::new(),
+        };
+        for (seq, msg) in replay {
+            let Some(msg) = focus_event(msg, pane_view.as_mut()) else {
+                continue;
+            };
+            let msg = transform_event(msg, &colors);
+            let event_type = sse_event_type(&msg);
+            last_replayed = Some(seq);
+            yield Ok(Event::default()
+                .event(event_type)
+                .id(session_broadcast.event_id(seq))
+                .data(msg));
         }
 
+        // Paced delivery: while the client has a frame budget, events queue in
+        // `pending` and go out together once per frame. The first event after
+        // an idle stretch is not delayed — only the ones that follow it.
+        let mut frame_rx = frame_rx;
+        let mut frame_open = true;
+        let mut pending: Vec<TaggedEvent> = Vec::new();
+        let mut next_frame: Option<tokio::time::Instant> = None;
+        let mut last_frame = tokio::time::Instant::now();
+
         loop {
             tokio::select! {
                 // Handle session-specific state changes
-                result = session_rx.recv() => {
-                    match result {
-                        Ok((seq, msg)) => {
-                            // Dedupe against the replay window — broadcast subscription
-                            // happens before we read the ring buffer, so the receiver
-                            // may queue messages already yielded above.
-                            if seq <= last_replayed {
-                                continue;
-                            }
-                            last_replayed = seq;
-                            let event_type = sse_event_type(&msg);
-                            yield Ok(Event::default()
-                                .event(event_type)
-                                .id(seq.to_string())
-                                .data(msg));
-                        }
-                        Err(broadcast::error::RecvError::Lagged(n)) => {
-                            // The subscriber fell behind and the channel dropped
-                            // `n` messages. Replay whatever is still in the ring
-                            // buffer above what we've already sent so the client
-                            // recovers without waiting for the next full snapshot.
-                            // If the gap exceeds the buffer, replay covers the
-                            // buffered tail and the next StateUpdate::Full fills
-                            // the rest. The `seq <= last_replayed` dedup below
-                            // absorbs any overlap with resumed delivery.
-                            warn!(conn_id, lagged = n, "client lagged; replaying ring buffer");
-                            for (seq, msg) in session_broadcast.replay_since(last_replayed) {
-                                let event_type = sse_event_type(&msg);
-                                last_replayed = seq;
-                                yield Ok(Event::default()
-                                    .event(event_type)
-                                    .id(seq.to_string())
-                                    .data(msg));
-                            }
-                        }
-                        Err(broadcast::error::RecvError::Closed) => {
-                            break;
+                event = outbox.recv() => {
+                    let Some((seq, msg)) = event else {
+                        break;
+                    };
+                    // Dedupe against the replay window — the broadcast
+                    // subscription happens before we read the ring buffer, so
+                    // the outbox may hold messages already yielded above. A
+                    // full state resent to this client alone, or a reply,
+                    // carries the tag of the event before it, so these always
+                    // go out; taking a full state twice changes nothing.
+                    if last_replayed.is_some_and(|last| seq <= last) && !is_own_event(&msg) {
+                        continue;
+                    }
+                    last_replayed = last_replayed.max(Some(seq));
+                    let Some(msg) = focus_event(msg, pane_view.as_mut()) else {
+                        continue;
+                    };
+                    let msg = transform_event(msg, &colors);
+                    let interval = *frame_rx.borrow();
+                    if let Some(interval) = interval {
+                        queue_frame_event(&mut pending, (seq, msg));
+                        if next_frame.is_none() {
+                            next_frame = Some((last_frame + interval).max(tokio::time::Instant::now()));
                         }
+                        continue;
+                    }
+                    let event_type = sse_event_type(&msg);
+                    yield Ok(Event::default()
+                        .event(event_type)
+                        .id(session_broadcast.event_id(seq))
+                        .data(msg));
+                }
+                _ = tokio::time::sleep_until(next_frame.unwrap_or_else(tokio::time::Instant::now)), if next_frame.is_some() => {
+                    next_frame = None;
+                    last_frame = tokio::time::Instant::now();
+                    for (seq, msg) in pending.drain(..) {
+                        let event_type = sse_event_type(&msg);
+                        yield Ok(Event::default()
+                            .event(event_type)
+                            .id(session_broadcast.event_id(seq))
+                            .data(msg));
+                    }
+                }
+                _ = disconnect.cancelled() => {
+                    // `fatal` keeps the client from reconnecting straight away.
+                    let event = ServerEvent::Fatal {
+                        message: "Disconnected by the server administrator".to_string(),
+                    };
+                    if let Some(s) = encode_event(&event) {
+                        yield Ok(Event::default().event("fatal").data(s));
+                    }
+                    break;
+                }
+                changed = frame_rx.changed(), if frame_open => {
+                    // Sender dropped: the connection was cleaned up and the
+                    // stream is about to end — stop polling it.
+                    if changed.is_err() {
+                        frame_open = false;
+                    }
+                    // Dropping the budget releases anything still queued.
+                    if frame_rx.borrow().is_none() && !pending.is_empty() {
+                        next_frame = Some(tokio::time::Instant::now());
                     }
                 }
             }
         }
     };
 
-    Sse::new(stream).keep_alive(KeepAlive::default().interval(Duration::from_secs(1)))
+    Sse::new(stream)
+        .keep_alive(KeepAlive::default().interval(Duration::from_secs(1)))
+        .into_response()
 }
 
 // ============================================
@@ -454,27 +928,36 @@ pub async fn commands_handler(
     State(state): State<Arc<AppState>>,
     Query(query): Query<SessionQuery>,
     headers: HeaderMap,
+    client: Option<Extension<CommandClient>>,
+    scope: Option<Extension<Scope>>,
     body: axum::body::Bytes,
 ) -> Response {
     // Session from the query param, defaulting to the standard session name.
-    let session = query
-        .session
-        .unwrap_or_else(|| tmuxy_core::DEFAULT_SESSION_NAME.to_string());
+    let (session, pane) = match query.session().and_then(|s| Ok((s, query.pane()?))) {
+        Ok(found) => found,
+        Err(error) => {
+            return (
+                StatusCode::BAD_REQUEST,
+                Json(CommandResponse {
+                    result: None,
+                    error: Some(error),
+                }),
+            )
+                .into_response();
+        }
+    };
 
     // Connection ID from the header. Every SSE client is handed its own id in
     // the `connection-info` greeting, so a missing header means the caller
     // never opened a stream. Keep it as None rather than defaulting to 0 —
     // 0 is a real allocated id, and header-less callers used to overwrite that
     // connection's viewport entry and skew the min-size computation.
-    let conn_id: Option<u64> = headers
-        .get("x-connection-id")
-        .and_then(|v| v.to_str().ok())
-        .and_then(|s| s.parse().ok());
+    let conn_id = crate::rate_limit::connection_id(&headers);
 
     // Decode into the typed enum. A parse failure still returns 400 with the
     // serde error in the body — the existing wire contract (`{ "error": ... }`)
     // is preserved so the TS adapter keeps working.
-    let cmd: ClientCommand = match ClientCommand::decode(&body) {
+    let mut cmd: ClientCommand = match ClientCommand::decode(&body) {
         Ok(c) => c,
         Err(e) => {
             return (
@@ -488,8 +971,113 @@ pub async fn commands_handler(
         }
     };
 
+    // A pane-scoped token is a single-pane client of its pane, as on the
+    // event stream, whether or not it passes `?pane=`.
+    let pane = match (scope.as_ref().and_then(|s| s.pane.as_deref()), pane) {
+        (Some(allowed), Some(asked)) if allowed != asked => {
+            return (
+                StatusCode::FORBIDDEN,
+                Json(CommandResponse {
+                    result: None,
+                    error: Some(format!("this token is limited to pane {allowed}")),
+                }),
+            )
+                .into_response();
+        }
+        (allowed, asked) => asked.or(allowed),
+    };
+
+    // A single-pane client's viewport is the pane's, so it never sizes the
+    // window.
+    if pane.is_some() && matches!(cmd, ClientCommand::SetClientSize { .. }) {
+        return Json(CommandResponse {
+            result: Some(serde_json::Value::Null),
+            error: None,
+        })
+        .into_response();
+    }
+
+    // A scoped token's commands must stay inside its scope.
+    if let Some(Extension(scope)) = &scope {
+        if let Err(error) = scope.permits(&session, &cmd) {
+            return (
+                StatusCode::FORBIDDEN,
+                Json(CommandResponse {
+                    result: None,
+                    error: Some(error),
+                }),
+            )
+                .into_response();
+        }
+        let targets = scope.session_targets(&cmd);
+        if !targets.is_empty() {
+            if let Some(target) = session_lacks(&state, &session, &targets).await {
+                return (
+                    StatusCode::FORBIDDEN,
+                    Json(CommandResponse {
+                        result: None,
+                        error: Some(format!("{target} is not in session {session}")),
+                    }),
+                )
+                    .into_response();
+            }
+        }
+    }
+
+    // A single-pane client may only touch its pane.
+    if let Some(pane) = pane {
+        let only_pane = Scope {
+            access: Access::Write,
+            pane: Some(pane.to_string()),
+            ..Scope::default()
+        };
+        if only_pane.permits(&session, &cmd).is_err() {
+            return (
+                StatusCode::FORBIDDEN,
+                Json(CommandResponse {
+                    result: None,
+                    error: Some(format!("this client shows only pane {pane}")),
+                }),
+            )
+                .into_response();
+        }
+        if let ClientCommand::GetInitialState { cols, rows } = &mut cmd {
+            (*cols, *rows) = (None, None);
+        }
+    }
+    let snapshot = matches!(cmd, ClientCommand::GetInitialState { .. });
+
+    // Held until the command is done, so a client can't pile up more of one
+    // kind than the monitor can answer.
+    let _in_flight = match client {
+        Some(Extension(client)) => match state.command_limits.begin(client, &cmd) {
+            Some(in_flight) => Some(in_flight),
+            None => {
+                state.metrics.record_command_refused();
+                return crate::rate_limit::refuse(
+                    "too many commands of this kind in flight".to_string(),
+                    None,
+                );
+            }
+        },
+        None => None,
+    };
+
     // Handle the command
-    match handle_command(cmd, &session, &state, conn_id).await {
+    let span = info_span!("command", %session, conn_id);
+    debug!(parent: &span, ?cmd, "handling command");
+    let host = SseHost {
+        state: &state,
+        session: &session,
+        conn_id,
+    };
+    match dispatch(&host, cmd)
+        .instrument(span)
+        .await
+        .and_then(|result| match pane {
+            Some(pane) if snapshot => focus_snapshot(result, pane),
+            _ => Ok(result),
+        }) {
         Ok(result) => (
             StatusCode::OK,
             Json(CommandResponse {
@@ -509,203 +1097,190 @@ pub async fn commands_handler(
     }
 }
 
+/// The first of the window and pane `targets` (`@3`, `%3`) that is not one
+/// of `session`'s, or any of them if the session's panes can't be listed.
+pub(crate) async fn session_lacks<'a>(
+    state: &AppState,
+    session: &str,
+    targets: &[&'a str],
+) -> Option<&'a str> {
+    let args = [
+        "list-panes",
+        "-s",
+        "-t",
+        session,
+        "-F",
+        "#{window_id} #{pane_id}",
+    ]
+    .map(str::to_string)
+    .to_vec();
+    let output = match state
+        .session_call(
+            session,
+            args,
+            "list_panes",
+            tmuxy_core::RetryPolicy::standard(),
+        )
+        .await
+    {
+        Ok(output) => output,
+        Err(e) => {
+            debug!(error = %e, session, "session panes not listed");
+            String::new()
+        }
+    };
+    let ids: HashSet<&str> = output.split_whitespace().collect();
+    targets.iter().copied().find(|target| !ids.contains(target))
+}
+
+/// Narrow a `get_initial_state` result to one pane, as the pane's event
+/// stream shows it.
+fn focus_snapshot(result: serde_json::Value, pane: &str) -> Result<serde_json::Value, String> {
+    let mut state: TmuxState =
+        serde_json::from_value(result).map_err(|e| format!("Failed to read state: {}", e))?;
+    PaneView::new(pane).apply_state(&mut state);
+    serde_json::to_value(state).map_err(|e| format!("Failed to serialize state: {}", e))
+}
+
 // ============================================
-// Command Handler
+// Command Host
 // ============================================
 
-async fn handle_command(
-    cmd: ClientCommand,
-    session: &str,
-    state: &Arc<AppState>,
+/// One `/commands` request's view of the server, for [`dispatch`]: the
+/// session it targets and the SSE connection it came from.
+struct SseHost<'a> {
+    state: &'a Arc<AppState>,
+    session: &'a str,
     conn_id: Option<u64>,
-) -> Result<serde_json::Value, String> {
-    match cmd {
-        ClientCommand::GetInitialState { cols, rows } => {
-            // Apply client size before capturing state
-            if let (Some(c), Some(r)) = (cols, rows) {
-                if c > 0 && r > 0 {
-                    set_client_size(state, session, conn_id, c, r).await;
-                }
-            }
-            // capture_window_state_for_session shells several synchronous tmux
-            // subprocesses; run it off the async worker threads so a slow
-            // capture on connect doesn't stall the runtime under multi-client load.
-            let session_owned = session.to_string();
-            let snapshot = tokio::task::spawn_blocking(move || {
-                tmuxy_core::capture_window_state_for_session(&session_owned)
-            })
-            .await
-            .map_err(|e| format!("capture task failed: {}", e))??;
-            serde_json::to_value(snapshot).map_err(|e| format!("Failed to serialize state: {}", e))
-        }
-        ClientCommand::SetClientSize { cols, rows } => {
-            if cols > 0 && rows > 0 {
-                set_client_size(state, session, conn_id, cols, rows).await;
-            }
-            Ok(serde_json::json!(null))
-        }
-        ClientCommand::RunTmuxCommand { command } => {
-            // Block raw resize-window commands from clients — resize must go through
-            // set_client_size to prevent stale SSE connections from overriding sizes.
-            if command.starts_with("resize-window") || command.starts_with("resizew") {
-                warn!(?conn_id, %command, "blocked resize command (use set_client_size)");
-                return Ok(serde_json::json!(null));
-            }
+}
 
-            // neww crashes tmux 3.5a control mode — use split+break workaround
-            if command.starts_with("new-window") || command.starts_with("neww") {
-                let cmd = build_new_window_command(state, session).await;
-                send_via_control_mode(state, session, &cmd).await?;
-                return Ok(serde_json::json!(null));
-            }
+#[async_trait::async_trait]
+impl CommandHost for SseHost<'_> {
+    fn session(&self) -> &str {
+        self.session
+    }
 
-            // Read-only session/window/pane enumeration is safe to run as a
-            // one-off external subprocess even while control mode is attached
-            // (docs/TMUX.md "Commands Safe to Run"). Run it synchronously and
-            // return stdout: the fire-and-forget control-mode path below can't
-            // return output, so a caller that needs it — the sidebar's sessions
-            // poll runs `list-windows -a` / `list-panes -a` to enumerate every
-            // session on the socket — would otherwise get null. Mirrors the
-            // Tauri `run_tmux_command` path (which already returns stdout).
-            if is_readonly_query(&command) {
-                return executor::run_tmux_command_for_session(session, &command)
-                    .map(|out| serde_json::json!(out))
-                    .map_err(|e| e.to_string());
-            }
+    fn ctx(&self) -> &Arc<Ctx> {
+        &self.state.ctx
+    }
 
-            // Detect source-file commands — keybindings may change
-            let is_source_file =
-                command.starts_with("source-file") || command.starts_with("source ");
+    async fn session_ctx(&self) -> Arc<Ctx> {
+        self.state.session_ctx(self.session).await
+    }
 
-            let command_tx = {
-                let sessions = state.sessions.read().await;
-                sessions
-                    .get(session)
-                    .and_then(|s| s.monitor_command_tx.clone())
-            };
+    async fn send(&self, cmd: MonitorCommand) -> Result<(), String> {
+        send_to_monitor(self.state, self.session, cmd).await
+    }
 
-            if let Some(tx) = command_tx {
-                tx.send(MonitorCommand::RunCommand {
-                    command: command.clone(),
-                })
-                .await
-                .map_err(|e| format!("Monitor channel error: {}", e))?;
-                trace!(?conn_id, %command, "client sent command via control mode");
+    fn tables(&self) -> Arc<StdMutex<TableCache>> {
+        self.state.tables.clone()
+    }
 
-                // After source-file, re-broadcast keybindings (prefix key may have changed)
-                if is_source_file {
-                    tokio::time::sleep(SOURCE_FILE_SETTLE).await;
-                    broadcast_keybindings(state, session).await;
-                }
+    fn searches(&self) -> Arc<PaneSearches> {
+        self.state.searches.clone()
+    }
+
+    fn client_id(&self) -> Result<u64, String> {
+        self.conn_id
+            .ok_or_else(|| "this command requires an x-connection-id".to_string())
+    }
+
+    async fn reply_sink(&self) -> Result<EventSink, String> {
+        let conn_id = self.client_id()?;
+        let outbox = self
+            .state
+            .sessions
+            .read()
+            .await
+            .get(self.session)
+            .and_then(|s| s.outboxes.get(&conn_id).cloned())
+            .ok_or_else(|| format!("No event stream for connection {}", conn_id))?;
+        Ok(Arc::new(move |event| outbox.push_reply(&event)))
+    }
+
+    async fn set_client_size(&self, cols: u32, rows: u32) {
+        set_client_size(self.state, self.session, self.conn_id, cols, rows).await;
+    }
+
+    /// The smallest viewport among the session's clients.
+    async fn viewport(&self) -> Option<(u32, u32)> {
+        let sessions = self.state.sessions.read().await;
+        sessions
+            .get(self.session)
+            .filter(|s| !s.client_sizes.is_empty())
+            .map(|s| compute_min_client_size(&s.client_sizes))
+    }
+
+    async fn key_tables(&self) -> Arc<KeyTables> {
+        session_key_tables(self.state, self.session).await
+    }
+
+    async fn resolve_key(&self, key: &KeyEvent) -> Result<(KeyAction, bool), String> {
+        let conn_id = self
+            .conn_id
+            .ok_or("key_input requires an x-connection-id")?;
+        Ok(resolve_key(self.state, self.session, conn_id, key).await)
+    }
+
+    async fn keybindings_changed(&self) {
+        if let Some(s) = self.state.sessions.write().await.get_mut(self.session) {
+            s.key_tables = None;
+        }
+        broadcast_keybindings(self.state, self.session).await;
+    }
 
-                Ok(serde_json::json!(null))
+    async fn set_focus(&self, focused: bool) -> Result<(), String> {
+        let conn_id = self
+            .conn_id
+            .ok_or("set_focus requires an x-connection-id")?;
+        {
+            let mut sessions = self.state.sessions.write().await;
+            let conns = sessions
+                .get_mut(self.session)
+                .filter(|s| s.connections.contains(&conn_id))
+                .ok_or_else(|| format!("No event stream for connection {}", conn_id))?;
+            if focused {
+                conns.focused.insert(conn_id);
             } else {
-                Err("No monitor connection available".to_string())
+                conns.focused.remove(&conn_id);
             }
         }
-        ClientCommand::GetScrollbackCells {
-            pane_id,
-            start,
-            end,
-        } => {
-            // Route the three queries that build one scrollback response through
-            // the Tower stack — picks up the standard retry policy, a 5s
-            // per-call deadline, and tracing in one place. Capture-pane in
-            // particular sometimes races a pending layout change and returns
-            // transient io::Error; the retry layer absorbs those.
-            let policy = tmuxy_core::RetryPolicy::standard();
-            let width_output = state
-                .tmux_call_with_policy(
-                    vec![
-                        "display-message".into(),
-                        "-t".into(),
-                        pane_id.clone(),
-                        "-p".into(),
-                        "#{pane_width}".into(),
-                    ],
-                    "scrollback:pane_width",
-                    policy,
-                )
-                .await
-                .map_err(|e| format!("Failed to get pane width: {}", e))?;
-            // Don't fall back to a default: a wrong width silently re-wraps
-            // every captured line at the wrong column. Fail so the client can
-            // retry instead of rendering corrupted scrollback.
-            let width: u32 = width_output.trim().parse().map_err(|_| {
-                format!(
-                    "Failed to parse pane width from tmux: {:?}",
-                    width_output.trim()
-                )
-            })?;
-
-            let history_output = state
-                .tmux_call_with_policy(
-                    vec![
-                        "display-message".into(),
-                        "-t".into(),
-                        pane_id.clone(),
-                        "-p".into(),
-                        "#{history_size}".into(),
-                    ],
-                    "scrollback:history_size",
-                    policy,
-                )
-                .await
-                .map_err(|e| format!("Failed to get history size: {}", e))?;
-            let history_size: u32 = history_output.trim().parse().map_err(|_| {
-                format!(
-                    "Failed to parse history size from tmux: {:?}",
-                    history_output.trim()
-                )
-            })?;
-
-            // capture-pane wants the special `-S start -E end` form built
-            // inline so dispatch directly through the stack rather than the
-            // sync `capture_pane_range` helper.
-            let start_s = start.to_string();
-            let end_s = end.to_string();
-            let raw = state
-                .tmux_call_with_policy(
-                    vec![
-                        "capture-pane".into(),
-                        "-t".into(),
-                        pane_id.clone(),
-                        "-p".into(),
-                        "-e".into(),
-                        "-S".into(),
-                        start_s,
-                        "-E".into(),
-                        end_s,
-                    ],
-                    "scrollback:capture",
-                    policy,
-                )
-                .await
-                .map_err(|e| format!("Failed to capture pane range: {}", e))?;
-
-            // Parse into cells
-            let cells = tmuxy_core::parse_scrollback_to_cells(&raw, width);
-
-            Ok(serde_json::json!({
-                "cells": cells,
-                "historySize": history_size,
-                "start": start,
-                "end": end,
-                "width": width
-            }))
-        }
-        ClientCommand::GetThemeSettings => {
-            Ok(tmuxy_core::theme::get_theme_settings(&state.ctx).await)
-        }
-        ClientCommand::SetTheme { name, mode } => {
-            tmuxy_core::theme::set_theme(&state.ctx, &name, mode.as_deref()).await?;
-            Ok(serde_json::json!(null))
-        }
-        ClientCommand::GetThemesList => Ok(tmuxy_core::theme::get_themes_list()),
-        ClientCommand::SetThemeMode { mode } => {
-            tmuxy_core::theme::set_theme_mode(&state.ctx, &mode).await?;
-            Ok(serde_json::json!(null))
-        }
+        report_client_focus(self.state, self.session).await;
+        Ok(())
+    }
+
+    async fn set_frame_budget(&self, fps: u32) -> Result<(), String> {
+        let conn_id = self
+            .conn_id
+            .ok_or("set_frame_budget requires an x-connection-id")?;
+        let interval = (fps > 0).then(|| Duration::from_secs(1) / fps.min(MAX_FRAME_RATE));
+        let sessions = self.state.sessions.read().await;
+        let frame_tx = sessions
+            .get(self.session)
+            .and_then(|s| s.frame_budgets.get(&conn_id))
+            .ok_or_else(|| format!("No event stream for connection {}", conn_id))?;
+        frame_tx.send_replace(interval);
+        debug!(conn_id, fps, "frame budget set");
+        Ok(())
+    }
+
+    fn session_created(&self, name: &str) {
+        hooks::fire(
+            self.state,
+            Hook::new(HookEvent::SessionCreate, name).with("TEMPLATE", "1"),
+        );
+    }
+
+    fn workspace_root(&self) -> std::path::PathBuf {
+        crate::state::find_workspace_root()
+    }
+
+    fn log_level(&self) -> Option<String> {
+        crate::logging::current_log_level()
+    }
+
+    fn set_log_level(&self, directives: &str) -> Result<String, String> {
+        crate::logging::set_log_level(directives)
     }
 }
 
@@ -715,8 +1290,8 @@ async fn handle_command(
 
 /// Re-fetch keybindings from tmux and broadcast to all SSE clients for a session.
 async fn broadcast_keybindings(state: &Arc<AppState>, session: &str) {
-    let keybindings = KeyBindings::current();
-    let kb_event = SseEvent::KeyBindings(keybindings);
+    let keybindings = tmuxy_core::get_key_bindings();
+    let kb_event = ServerEvent::KeyBindings(keybindings);
     let Some(msg) = encode_event(&kb_event) else {
         return;
     };
@@ -727,11 +1302,67 @@ async fn broadcast_keybindings(state: &Arc<AppState>, session: &str) {
     }
 }
 
-/// Send a tmux command through control mode
-async fn send_via_control_mode(
+/// Queue the session's state in full on `outbox`, for a client too far
+/// behind for the ring buffer. Only that client gets it; a monitor still
+/// starting up opens with a full state anyway.
+async fn resend_full(state: &Arc<AppState>, session: &str, outbox: &Outbox) {
+    let (reply, rx) = tokio::sync::oneshot::channel();
+    if let Err(e) = send_to_monitor(state, session, MonitorCommand::GetFullState { reply }).await {
+        debug!(%session, error = %e, "full state not requested");
+        return;
+    }
+    if let Ok(Some((update, through))) = rx.await {
+        outbox.push_full(update, through);
+    }
+}
+
+/// The session's key tables, loaded with `list-keys` on first use.
+async fn session_key_tables(state: &Arc<AppState>, session: &str) -> Arc<KeyTables> {
+    let loaded = state
+        .sessions
+        .read()
+        .await
+        .get(session)
+        .and_then(|s| s.key_tables.clone());
+    if let Some(tables) = loaded {
+        return tables;
+    }
+    // list-keys runs outside the lock; a racing load just wins twice.
+    let tables = Arc::new(tmuxy_core::get_key_tables());
+    if let Some(session_conns) = state.sessions.write().await.get_mut(session) {
+        session_conns.key_tables = Some(tables.clone());
+    }
+    tables
+}
+
+/// Run `key` through connection `conn_id`'s prefix state, returning what to
+/// do with it and whether the connection is left in prefix mode.
+async fn resolve_key(
     state: &Arc<AppState>,
     session: &str,
-    command: &str,
+    conn_id: u64,
+    key: &KeyEvent,
+) -> (KeyAction, bool) {
+    let now = state.ctx.clock.now();
+    let tables = session_key_tables(state, session).await;
+    let mut sessions = state.sessions.write().await;
+    let Some(session_conns) = sessions.get_mut(session) else {
+        return (KeyAction::Send, false);
+    };
+    let prefix_state = session_conns.prefix_states.entry(conn_id).or_default();
+    let action = match binding_key_name(key) {
+        Some(name) => prefix_state.handle(&tables, &name, key.repeat, now),
+        // A lone modifier: nothing to look up, and the prefix stays.
+        None => KeyAction::Send,
+    };
+    (action, prefix_state.in_prefix(now))
+}
+
+/// Hand a command to the session's monitor task
+pub(crate) async fn send_to_monitor(
+    state: &Arc<AppState>,
+    session: &str,
+    cmd: MonitorCommand,
 ) -> Result<(), String> {
     let command_tx = {
         let sessions = state.sessions.read().await;
@@ -741,11 +1372,9 @@ async fn send_via_control_mode(
     };
 
     if let Some(tx) = command_tx {
-        tx.send(MonitorCommand::RunCommand {
-            command: command.to_string(),
-        })
-        .await
-        .map_err(|e| format!("Monitor channel error: {}", e))
+        tx.send(cmd)
+            .await
+            .map_err(|e| format!("Monitor channel error: {}", e))
     } else {
         Err("No monitor connection available".to_string())
     }
@@ -758,48 +1387,6 @@ fn compute_min_client_size(sizes: &HashMap<u64, (u32, u32)>) -> (u32, u32) {
     (min_cols, min_rows)
 }
 
-/// True for read-only tmux queries that are safe to run as a one-off external
-/// subprocess while a control-mode client is attached (docs/TMUX.md). These
-/// return stdout the fire-and-forget control-mode path can't.
-///
-/// The command is interpolated into `sh -c` by `run_tmux_command_for_session`,
-/// so any shell metacharacter can chain a mutating command onto a read
-/// (`list-panes -a && kill-server`, `$(...)`, backticks, pipes, redirection).
-/// We reject the full set of shell control/expansion characters — the only
-/// legitimate callers (the sidebar's `list-* -a -F '…'` poll) use just
-/// alphanumerics, spaces, `-`, single quotes, `#{…}`, `@`, and tabs.
-fn is_readonly_query(command: &str) -> bool {
-    // Any of these lets a mutating command ride along the `sh -c` invocation.
-    const SHELL_METACHARS: &[char] = &[
-        ';', '\n', '\r', '&', '|', '$', '`', '<', '>', '(', ')', '\\',
-    ];
-    if command.contains(SHELL_METACHARS) {
-        return false;
-    }
-    const READONLY_PREFIXES: &[&str] = &["list-windows", "list-panes", "list-sessions"];
-    let head = command.trim_start();
-    READONLY_PREFIXES
-        .iter()
-        .any(|p| head == *p || head.starts_with(&format!("{p} ")))
-}
-
-/// Build the `new-window` rewrite (splitw + breakp + resizew + window-tag).
-/// Resolves the viewport size, then defers to `executor::new_window_rewrite`
-/// so the Tauri app and this server can't drift on the rewrite shape.
-async fn build_new_window_command(state: &Arc<AppState>, session: &str) -> String {
-    let resize = {
-        let sessions = state.sessions.read().await;
-        sessions.get(session).and_then(|s| {
-            if s.client_sizes.is_empty() {
-                None
-            } else {
-                Some(compute_min_client_size(&s.client_sizes))
-            }
-        })
-    };
-    executor::new_window_rewrite(session, resize)
-}
-
 /// Store a client's viewport size and resize the tmux session.
 /// Skips the resize command if the computed minimum is the same as the last resize
 /// to prevent feedback loops when multiple clients have different viewport sizes.
@@ -861,25 +1448,69 @@ async fn set_client_size(
     }
 }
 
+/// Tell the session's monitor how many clients it has, for
+/// `#{tmuxy_clients}` in window status formats. Before the monitor is up
+/// there is no one to tell; `start_monitoring` reports the count then.
+async fn report_client_count(state: &Arc<AppState>, session: &str) {
+    let (command_tx, count) = {
+        let sessions = state.sessions.read().await;
+        match sessions.get(session) {
+            Some(conns) => (conns.monitor_command_tx.clone(), conns.connections.len()),
+            None => return,
+        }
+    };
+    if let Some(tx) = command_tx {
+        let _ = tx.send(MonitorCommand::SetClientCount { count }).await;
+    }
+}
+
+/// Tell the session's monitor whether any client has focus. Like
+/// `report_client_count`, `start_monitoring` reports it to a new monitor.
+async fn report_client_focus(state: &Arc<AppState>, session: &str) {
+    let (command_tx, focused) = {
+        let sessions = state.sessions.read().await;
+        match sessions.get(session) {
+            Some(conns) => (conns.monitor_command_tx.clone(), !conns.focused.is_empty()),
+            None => return,
+        }
+    };
+    if let Some(tx) = command_tx {
+        let _ = tx.send(MonitorCommand::SetClientFocus { focused }).await;
+    }
+}
+
 /// Remove a connection and resize tmux to remaining clients' minimum viewport
 async fn cleanup_connection(state: &Arc<AppState>, session: &str, conn_id: u64) {
-    let (resize_to, command_tx, needs_deferred_cleanup) = {
+    let (resize_to, command_tx, needs_deferred_cleanup, clients) = {
         let mut sessions = state.sessions.write().await;
 
         let mut resize = None;
         let mut cmd_tx = None;
         let mut deferred = false;
+        let mut clients = 0;
 
         if let Some(session_conns) = sessions.get_mut(session) {
             // Remove this connection
             session_conns.connections.retain(|&id| id != conn_id);
+            clients = session_conns.connections.len();
             let had_size = session_conns.client_sizes.remove(&conn_id).is_some();
+            session_conns.frame_budgets.remove(&conn_id);
+            session_conns.outboxes.remove(&conn_id);
+            session_conns.disconnects.remove(&conn_id);
+            session_conns.prefix_states.remove(&conn_id);
+            session_conns.focused.remove(&conn_id);
 
             if session_conns.connections.is_empty() {
-                // Don't immediately kill the monitor — a page reload will reconnect
-                // within a few seconds. Defer cleanup to give new clients a chance.
-                info!(%session, "last client disconnected, deferring monitor cleanup (2s grace period)");
-                deferred = true;
+                if tmuxy_core::control_mode::hibernate_after_from_env().is_some() {
+                    // The monitor keeps the session warm and hibernates itself
+                    // once it goes quiet.
+                    info!(%session, "last client disconnected, monitor will hibernate when idle");
+                } else {
+                    // Don't immediately kill the monitor — a page reload will reconnect
+                    // within a few seconds. Defer cleanup to give new clients a chance.
+                    info!(%session, "last client disconnected, deferring monitor cleanup (2s grace period)");
+                    deferred = true;
+                }
             } else if had_size && !session_conns.client_sizes.is_empty() {
                 // Recompute minimum size for remaining clients
                 let new_min = compute_min_client_size(&session_conns.client_sizes);
@@ -890,8 +1521,17 @@ async fn cleanup_connection(state: &Arc<AppState>, session: &str, conn_id: u64)
             }
         }
 
-        (resize, cmd_tx, deferred)
+        (resize, cmd_tx, deferred, clients)
     };
+    state.searches.forget(conn_id);
+    report_client_count(state, session).await;
+    report_client_focus(state, session).await;
+    hooks::fire(
+        state,
+        Hook::new(HookEvent::ClientDisconnect, session)
+            .with("CLIENT_ID", conn_id)
+            .with("CLIENTS", clients),
+    );
 
     // Defer monitor cleanup: wait 2 seconds, then check if clients reconnected.
     // Tracked in `AppState::join_set` so the grace-period sleep doesn't survive
@@ -933,24 +1573,7 @@ async fn cleanup_connection(state: &Arc<AppState>, session: &str, conn_id: u64)
 
             // Stop the monitor if cleanup proceeded
             if let Some(handle) = monitor_handle {
-                if handle.is_finished() {
-                    debug!("monitor task already finished (session was killed)");
-                } else if let Some(ref tx) = cmd_tx {
-                    info!("sending graceful shutdown to monitor");
-                    let _ = tx.send(MonitorCommand::Shutdown).await;
-                    // Poll for completion instead of fixed sleep
-                    for _ in 0..20 {
-                        if handle.is_finished() {
-                            break;
-                        }
-                        tokio::time::sleep(Duration::from_millis(100)).await;
-                    }
-                    if !handle.is_finished() {
-                        warn!("monitor task still running after graceful shutdown (not aborting)");
-                    } else {
-                        debug!("monitor task finished gracefully");
-                    }
-                }
+                shutdown_monitor(handle, cmd_tx).await;
             }
             })
             .await;
@@ -972,16 +1595,41 @@ async fn cleanup_connection(state: &Arc<AppState>, session: &str, conn_id: u64)
     }
 }
 
+/// Ask a monitor that was taken off its session to shut down, and wait up to
+/// two seconds for its task to finish.
+pub(crate) async fn shutdown_monitor(handle: JoinHandle<()>, cmd_tx: Option<MonitorCommandSender>) {
+    if handle.is_finished() {
+        debug!("monitor task already finished (session was killed)");
+    } else if let Some(ref tx) = cmd_tx {
+        info!("sending graceful shutdown to monitor");
+        let _ = tx.send(MonitorCommand::Shutdown).await;
+        // Poll for completion instead of fixed sleep
+        for _ in 0..20 {
+            if handle.is_finished() {
+                break;
+            }
+            tokio::time::sleep(Duration::from_millis(100)).await;
+        }
+        if !handle.is_finished() {
+            warn!("monitor task still running after graceful shutdown (not aborting)");
+        } else {
+            debug!("monitor task finished gracefully");
+        }
+    }
+}
+
 // ============================================
 // Monitoring (Control Mode)
 // ============================================
 
 /// `has-session` check run off the async worker threads (it shells a
 /// synchronous tmux subprocess, which would otherwise block a tokio worker).
-async fn session_exists(session: &str) -> bool {
+async fn session_exists(transport: &TmuxTransport, session: &str) -> bool {
+    let transport = transport.clone();
     let session = session.to_string();
     tokio::task::spawn_blocking(move || {
-        tmuxy_core::session::tmux_command()
+        transport
+            .command()
             .args(["has-session", "-t", &session])
             .output()
             .map(|o| o.status.success())
@@ -996,18 +1644,44 @@ pub async fn start_monitoring(
     session: String,
     state: Arc<AppState>,
 ) {
-    let emitter = Arc::new(SseEmitter::new(broadcast.clone(), Arc::clone(&state)));
+    let emitter = Arc::new(SseEmitter::new(
+        broadcast.clone(),
+        Arc::clone(&state),
+        session.clone(),
+    ));
     let log_sink: Arc<dyn LogSink> = emitter.clone();
+    // Remote sessions reconnect through the same backoff loop below; ssh's
+    // keepalive turns a dead link into a closed connection it can see.
+    let ctx = state.session_ctx(&session).await;
+    let transport = state.session_transport(&session).await;
 
     let config = MonitorConfig {
         session: session.clone(),
         sync_interval: Duration::from_millis(500),
+        heartbeat_interval: tmuxy_core::control_mode::heartbeat_interval_from_env(),
+        sync_fields: tmuxy_core::control_mode::sync_fields_from_env(),
         create_session: true,
         throttle_interval: Duration::from_millis(32),
         throttle_threshold: 20,
         rate_window: Duration::from_millis(100),
+        output_debounce: Duration::from_millis(16),
+        max_latency: Duration::from_millis(50),
         working_dir: Some(crate::state::find_workspace_root()),
+        stale_pane_notice: tmuxy_core::control_mode::stale_pane_notice_from_env(),
+        bell_policy: tmuxy_core::control_mode::bell_policy_from_env(),
+        terminal_backend: tmuxy_core::control_mode::terminal_backend_from_env(),
+        scrollback_rows: tmuxy_core::control_mode::scrollback_rows_from_env(),
+        accessible_output: tmuxy_core::control_mode::accessible_output_from_env(),
+        dnd_schedule: tmuxy_core::control_mode::dnd_schedule_from_env(),
+        terminal_theme: tmuxy_core::control_mode::terminal_theme_from_env(),
+        usage_db: tmuxy_core::usage::usage_db_from_env(),
+        pause_after: tmuxy_core::control_mode::pause_after_from_env(),
+        auto_resume: tmuxy_core::control_mode::auto_resume_from_env(),
+        output_budget: tmuxy_core::control_mode::output_budget_from_env(),
+        hibernate_after: tmuxy_core::control_mode::hibernate_after_from_env(),
+        liveness_timeout: tmuxy_core::control_mode::liveness_timeout_from_env(),
     };
+    let recreate_lost = tmuxy_core::control_mode::recreate_lost_session_from_env();
 
     let mut backoff = Duration::from_millis(100);
     const MAX_BACKOFF: Duration = Duration::from_secs(10);
@@ -1020,6 +1694,8 @@ pub async fn start_monitoring(
     // Bound consecutive connect failures so we don't hammer a broken tmux
     // forever. Reset on successful long-running monitor.run().
     let mut consecutive_failures: u32 = 0;
+    // Why the last run lost tmux, for the `session-lost` event.
+    let mut lost_reason: Option<String> = None;
     let shutdown = state.shutdown.clone();
 
     loop {
@@ -1055,16 +1731,30 @@ pub async fn start_monitoring(
         // create_session=true to recreate it.
         let mut connect_config = config.clone();
         if !is_first_connect {
-            let exists = session_exists(&session).await;
+            let exists = session_exists(&transport, &session).await;
 
             if !exists {
                 if ever_ran_successfully {
-                    // Session was intentionally destroyed (e.g., kill-session from test cleanup)
-                    info!(%session, "tmux session no longer exists (was running), stopping monitor loop");
-                    break;
+                    let reason = lost_reason
+                        .clone()
+                        .unwrap_or_else(|| "tmux session no longer exists".to_string());
+                    let event = ServerEvent::SessionLost {
+                        reason: reason.clone(),
+                        recreating: recreate_lost,
+                    };
+                    if let Some(s) = encode_event(&event) {
+                        broadcast.broadcast(s);
+                    }
+                    if !recreate_lost {
+                        // Session was intentionally destroyed (e.g., kill-session from test cleanup)
+                        info!(%session, %reason, "tmux session no longer exists (was running), stopping monitor loop");
+                        break;
+                    }
+                    warn!(%session, %reason, "tmux session lost, recreating");
+                } else {
+                    // Session died before ever running — recreate it
+                    warn!(%session, "tmux session died before running, will recreate");
                 }
-                // Session died before ever running — recreate it
-                warn!(%session, "tmux session died before running, will recreate");
                 connect_config.create_session = true;
             } else {
                 // Session exists, just attach
@@ -1072,16 +1762,20 @@ pub async fn start_monitoring(
             }
         }
 
+        // Whether this connect makes the session, for the on-session-create hook.
+        let creating = connect_config.create_session && !session_exists(&transport, &session).await;
+
         // If this session doesn't exist and needs creation, try to route the
         // `new-session -d` through an existing monitor's CC connection. Running
         // external `tmux new-session -d` while a CC client is attached crashes
         // tmux 3.5a. Routing through CC avoids this.
-        if connect_config.create_session && !session_exists(&session).await {
+        // Only local sessions share a tmux server to route through.
+        if creating && !ctx.is_remote() {
             // Find an existing running monitor to route through
             let existing_tx = {
                 let sessions = state.sessions.read().await;
                 sessions.iter().find_map(|(name, conns)| {
-                    if name == &session {
+                    if name == &session || conns.remote_ctx.is_some() {
                         return None;
                     }
                     conns
@@ -1097,17 +1791,27 @@ pub async fn start_monitoring(
                     .as_ref()
                     .map(|d| format!(" -c '{}'", d.display()))
                     .unwrap_or_default();
-                let create_cmd = format!(
+                let mut create_cmd = format!(
                     "new-session -d -s {} -x {} -y {}{}",
-                    session,
+                    executor::tmux_quote(&session),
                     tmuxy_core::control_mode::INITIAL_PTY_COLS,
                     tmuxy_core::control_mode::INITIAL_PTY_ROWS,
-                    working_dir
+                    working_dir,
                 );
+                // The first pane runs the default command, and later ones
+                // follow the session's `default-command`.
+                if let Some(command) = tmuxy_core::session::default_command(&session) {
+                    let command = tmuxy_core::command_line::quote(&command);
+                    create_cmd.push_str(&format!(
+                        " {command} ; set-option -t {} default-command {command}",
+                        executor::tmux_quote(&session),
+                    ));
+                }
                 info!(%session, %via_session, "creating session via existing CC client");
                 let _ = tx
                     .send(tmuxy_core::control_mode::MonitorCommand::RunCommand {
                         command: create_cmd,
+                        reply: None,
                     })
                     .await;
                 // Wait for the session to actually exist before attaching CC.
@@ -1116,7 +1820,7 @@ pub async fn start_monitoring(
                 let mut created = false;
                 for _ in 0..50 {
                     tokio::time::sleep(Duration::from_millis(100)).await;
-                    if session_exists(&session).await {
+                    if session_exists(&transport, &session).await {
                         created = true;
                         info!(%session, "session created successfully via CC");
                         break;
@@ -1132,13 +1836,20 @@ pub async fn start_monitoring(
             }
         }
 
-        match TmuxMonitor::connect(connect_config, Some(&log_sink), state.ctx.clone()).await {
+        match TmuxMonitor::connect(connect_config, Some(&log_sink), ctx.clone()).await {
             Ok((mut monitor, command_tx)) => {
                 // Store command_tx so cleanup_connection can send Shutdown
                 let stored = {
                     let mut sessions = state.sessions.write().await;
                     if let Some(session_conns) = sessions.get_mut(&session) {
                         debug!(%session, "storing command_tx");
+                        // Queued ahead of anything sent once it is stored.
+                        let _ = command_tx.try_send(MonitorCommand::SetClientCount {
+                            count: session_conns.connections.len(),
+                        });
+                        let _ = command_tx.try_send(MonitorCommand::SetClientFocus {
+                            focused: !session_conns.focused.is_empty(),
+                        });
                         session_conns.monitor_command_tx = Some(command_tx);
                         true
                     } else {
@@ -1151,6 +1862,9 @@ pub async fn start_monitoring(
                 if !stored {
                     break;
                 }
+                if creating {
+                    hooks::fire(&state, Hook::new(HookEvent::SessionCreate, &session));
+                }
 
                 backoff = Duration::from_millis(100);
                 let run_start = std::time::Instant::now();
@@ -1161,12 +1875,41 @@ pub async fn start_monitoring(
                     ever_ran_successfully = true;
                     consecutive_failures = 0;
                 }
+                lost_reason = monitor.lost().map(str::to_string);
+                if let Some(reason) = &lost_reason {
+                    warn!(%session, %reason, "monitor lost tmux");
+                }
 
-                {
+                let wake = {
                     let mut sessions = state.sessions.write().await;
-                    if let Some(session_conns) = sessions.get_mut(&session) {
-                        session_conns.monitor_command_tx = None;
+                    match sessions.get_mut(&session) {
+                        Some(session_conns) => {
+                            session_conns.monitor_command_tx = None;
+                            if lost_reason.is_some() {
+                                session_conns.restarts.last_lost = lost_reason.clone();
+                            }
+                            // A client may have connected while the monitor
+                            // was detaching; it finds the monitor awake.
+                            if monitor.hibernated() && session_conns.connections.is_empty() {
+                                session_conns.hibernating = true;
+                                Some(session_conns.wake.clone())
+                            } else {
+                                None
+                            }
+                        }
+                        None => None,
                     }
+                };
+
+                // Detached from tmux until the next client connects.
+                if let Some(wake) = wake {
+                    info!(%session, "monitor hibernating");
+                    tokio::select! {
+                        _ = wake.notified() => info!(%session, "monitor waking"),
+                        _ = shutdown.cancelled() => {}
+                    }
+                    is_first_connect = false;
+                    continue;
                 }
             }
             Err(e) => {
@@ -1196,7 +1939,7 @@ pub async fn start_monitoring(
                         "Unable to connect to tmux after {} attempts; giving up. Last error: {}",
                         MAX_CONSECUTIVE_FAILURES, e
                     );
-                    let event = SseEvent::Fatal {
+                    let event = ServerEvent::Fatal {
                         message: final_msg.clone(),
                     };
                     if let Some(s) = encode_event(&event) {
@@ -1211,6 +1954,12 @@ pub async fn start_monitoring(
         }
 
         is_first_connect = false;
+        if let Some(session_conns) = state.sessions.write().await.get_mut(&session) {
+            let restarts = &mut session_conns.restarts;
+            restarts.count += 1;
+            restarts.consecutive_failures = consecutive_failures;
+            restarts.backoff_ms = backoff.as_millis() as u64;
+        }
         // Cancellable backoff — Ctrl+C during retry shouldn't have to wait out
         // the full sleep before the next loop iteration sees the cancellation.
         tokio::select! {
@@ -1230,7 +1979,7 @@ mod tests {
     /// `event=clipboard` discriminator + `{ pane_id, text }` payload shape.
     #[test]
     fn clipboard_event_serializes_with_expected_shape() {
-        let evt = SseEvent::Clipboard {
+        let evt = ServerEvent::Clipboard {
             pane_id: "%4".to_string(),
             text: "hello world".to_string(),
         };
@@ -1242,34 +1991,364 @@ mod tests {
     }
 
     #[test]
-    fn readonly_query_allows_session_enumeration_reads() {
-        // The exact commands the sidebar sessions poll issues, including the
-        // tab-joined multi-field format (literal tabs, not metacharacters).
-        assert!(is_readonly_query("list-windows -a -F '#{session_name}'"));
-        assert!(is_readonly_query("list-panes -a -F '#{pane_id}'"));
-        assert!(is_readonly_query("list-sessions"));
-        assert!(is_readonly_query(
-            "list-windows -a -F '#{session_name}\t#{window_id}\t#{@tmuxy-window-type}'"
-        ));
+    fn session_query_defaults_and_refuses_rewritten_names() {
+        let query = |session: Option<&str>| SessionQuery {
+            session: session.map(str::to_string),
+            colors: None,
+            background: None,
+            pane: None,
+        };
+        assert_eq!(
+            query(None).session().unwrap(),
+            tmuxy_core::DEFAULT_SESSION_NAME
+        );
+        assert_eq!(query(Some("my proj")).session().unwrap(), "my proj");
+        assert!(query(Some("a:b")).session().is_err());
+        assert!(query(Some("")).session().is_err());
+
+        let pane = |pane: &str| SessionQuery {
+            pane: Some(pane.to_string()),
+            ..query(None)
+        };
+        assert_eq!(pane("%12").pane().unwrap(), Some("%12"));
+        assert!(pane("%").pane().is_err());
+        assert!(pane("12").pane().is_err());
+        assert!(pane("%1;kill-server").pane().is_err());
+    }
+
+    fn tagged(seq: u64, update: &StateUpdate) -> TaggedEvent {
+        let event = ServerEvent::StateUpdate(Box::new(update.clone()));
+        (seq, encode_event(&event).unwrap())
     }
 
     #[test]
-    fn readonly_query_rejects_mutations_and_smuggling() {
-        // Mutating commands must keep flowing through the control-mode channel.
-        assert!(!is_readonly_query("split-window -h"));
-        assert!(!is_readonly_query("kill-session -t foo"));
-        // A read must not carry a compound/multiline mutation past the guard.
-        assert!(!is_readonly_query("list-windows -a ; kill-server"));
-        assert!(!is_readonly_query("list-panes\nkill-session -t foo"));
-        // The command is run via `sh -c`, so every shell metacharacter that can
-        // chain a second command must be rejected, not just `;` and newlines.
-        assert!(!is_readonly_query("list-panes -a && kill-server"));
-        assert!(!is_readonly_query("list-panes -a | sh"));
-        assert!(!is_readonly_query("list-panes -a $(kill-server)"));
-        assert!(!is_readonly_query("list-panes -a `kill-server`"));
-        assert!(!is_readonly_query("list-panes -a > /etc/passwd"));
-        assert!(!is_readonly_query("list-panes -a & kill-server"));
-        // Prefix-only match must not let `list-windows-evil` style names through.
-        assert!(!is_readonly_query("list-windowsX"));
+    fn color_transforms_rewrite_state_update_cells_only() {
+        let red = tmuxy_core::TerminalCell {
+            char: "x".into(),
+            style: Some(tmuxy_core::CellStyle {
+                fg: Some(tmuxy_core::CellColor::Indexed(1)),
+                ..Default::default()
+            }),
+            width: 1,
+        };
+        let mut delta = tmuxy_core::TmuxDelta::new(1);
+        delta.panes = Some(
+            [(
+                "%0".to_string(),
+                Some(tmuxy_core::PaneDelta {
+                    content: Some([(0, vec![red])].into_iter().collect()),
+                    ..Default::default()
+                }),
+            )]
+            .into_iter()
+            .collect(),
+        );
+        let (_, msg) = tagged(1, &StateUpdate::Delta { delta });
+        let colors = ColorTransforms::parse("red-green-safe", false).unwrap();
+
+        let out = transform_event(msg.clone(), &colors);
+        assert_ne!(out, msg);
+        assert!(out.contains(r#""fg":{"r":213,"g":94,"b":0}"#), "{out}");
+        assert_eq!(
+            transform_event(msg.clone(), &ColorTransforms::default()),
+            msg
+        );
+        let log = encode_event(&ServerEvent::Fatal {
+            message: "red".into(),
+        })
+        .unwrap();
+        assert_eq!(transform_event(log.clone(), &colors), log);
+    }
+
+    #[test]
+    fn pane_views_drop_events_about_other_panes() {
+        let mut view = PaneView::new("%2");
+        let bell = |pane: &str| {
+            encode_event(&ServerEvent::Bell {
+                pane_id: pane.to_string(),
+                sound: true,
+            })
+            .unwrap()
+        };
+        assert!(focus_event(bell("%1"), Some(&mut view)).is_none());
+        assert!(focus_event(bell("%2"), Some(&mut view)).is_some());
+        assert!(focus_event(bell("%1"), None).is_some());
+        let drag = encode_event(&ServerEvent::DragTarget(None)).unwrap();
+        assert!(focus_event(drag, Some(&mut view)).is_none());
+        let failed = encode_event(&ServerEvent::CommandError {
+            command: "send-keys -t %1 -l hunter2".to_string(),
+            error: "no such pane".to_string(),
+        })
+        .unwrap();
+        assert!(focus_event(failed.clone(), Some(&mut view)).is_none());
+        assert!(focus_event(failed, None).is_some());
+        let lost = encode_event(&ServerEvent::SessionLost {
+            reason: "killed".to_string(),
+            recreating: false,
+        })
+        .unwrap();
+        assert!(focus_event(lost, Some(&mut view)).is_some());
+
+        let mut delta = tmuxy_core::TmuxDelta::new(1);
+        delta.status_line = Some("[main]".to_string());
+        let (_, msg) = tagged(1, &StateUpdate::Delta { delta });
+        assert!(focus_event(msg, Some(&mut view)).is_none());
+
+        let mut delta = tmuxy_core::TmuxDelta::new(2);
+        delta.panes = Some(
+            [
+                ("%1".to_string(), Some(tmuxy_core::PaneDelta::default())),
+                (
+                    "%2".to_string(),
+                    Some(tmuxy_core::PaneDelta {
+                        cursor_x: Some(4),
+                        ..Default::default()
+                    }),
+                ),
+            ]
+            .into(),
+        );
+        let (_, msg) = tagged(2, &StateUpdate::Delta { delta });
+        let out = focus_event(msg, Some(&mut view)).unwrap();
+        assert!(out.contains(r#""%2""#) && !out.contains(r#""%1""#), "{out}");
+    }
+
+    /// An empty full state update.
+    fn full_state() -> StateUpdate {
+        StateUpdate::Full {
+            state: tmuxy_core::TmuxState {
+                session_name: "tmuxy".to_string(),
+                active_window_id: None,
+                active_pane_id: None,
+                panes: Vec::new(),
+                windows: Vec::new(),
+                total_width: 80,
+                total_height: 24,
+                status_line: String::new(),
+                status_segments: Default::default(),
+                status_lines: Vec::new(),
+                status_position: Default::default(),
+                session_group: None,
+                do_not_disturb: false,
+                theme: Default::default(),
+                ssh_agent: Default::default(),
+                epoch: 0,
+            },
+        }
+    }
+
+    #[test]
+    fn full_state_supersedes_queued_state_updates() {
+        let delta = StateUpdate::Delta {
+            delta: tmuxy_core::TmuxDelta::new(1),
+        };
+        let full = full_state();
+        let log = encode_event(&ServerEvent::Log {
+            kind: LogKind::Info,
+            message: "hi".to_string(),
+        })
+        .unwrap();
+
+        let mut pending = Vec::new();
+        queue_frame_event(&mut pending, tagged(1, &delta));
+        queue_frame_event(&mut pending, (2, log));
+        queue_frame_event(&mut pending, tagged(3, &delta));
+        assert_eq!(pending.len(), 3);
+        assert!(!is_full_state(&pending[0].1));
+
+        queue_frame_event(&mut pending, tagged(4, &full));
+        let seqs: Vec<u64> = pending.iter().map(|(seq, _)| *seq).collect();
+        assert_eq!(seqs, vec![2, 4]);
+        assert!(is_full_state(&pending[1].1));
+    }
+
+    #[test]
+    fn slow_client_outbox_folds_deltas_instead_of_queueing_them() {
+        let delta = |seq: u64, status: &str| {
+            let mut delta = tmuxy_core::TmuxDelta::new(seq);
+            delta.status_line = Some(status.to_string());
+            tagged(seq, &StateUpdate::Delta { delta })
+        };
+        let log = encode_event(&ServerEvent::Log {
+            kind: LogKind::Info,
+            message: "hi".to_string(),
+        })
+        .unwrap();
+
+        let mut events = VecDeque::new();
+        assert!(!push_coalesced(&mut events, delta(1, "one")));
+        push_coalesced(&mut events, (2, log));
+        push_coalesced(&mut events, delta(3, "three"));
+        let seqs: Vec<u64> = events.iter().map(|(seq, _)| *seq).collect();
+        assert_eq!(seqs, vec![2, 3]);
+        match serde_json::from_str::<ServerEvent>(&events[1].1).unwrap() {
+            ServerEvent::StateUpdate(update) => match *update {
+                StateUpdate::Delta { delta } => {
+                    assert_eq!(
+                        (delta.seq, delta.status_line.as_deref()),
+                        (3, Some("three"))
+                    );
+                }
+                other => panic!("expected a delta, got {other:?}"),
+            },
+            other => panic!("expected a state update, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn outbox_overflow_drops_updates_it_cannot_fold() {
+        // Deltas from alternating epochs never fold.
+        let delta = |seq: u64| {
+            let mut delta = tmuxy_core::TmuxDelta::new(seq);
+            delta.epoch = seq % 2;
+            tagged(seq, &StateUpdate::Delta { delta })
+        };
+        let mut events = VecDeque::new();
+        for seq in 0..EVENT_BUFFER_SIZE as u64 {
+            assert!(!push_coalesced(&mut events, delta(seq)));
+        }
+        assert!(push_coalesced(&mut events, delta(EVENT_BUFFER_SIZE as u64)));
+        assert!(events.is_empty());
+    }
+
+    #[test]
+    fn a_resent_full_state_drops_the_deltas_it_includes() {
+        // Each delta from an epoch of its own, so none fold.
+        let delta = |tag: u64, seq: u64| {
+            let mut delta = tmuxy_core::TmuxDelta::new(seq);
+            delta.epoch = tag;
+            tagged(tag, &StateUpdate::Delta { delta })
+        };
+        let outbox = Outbox::default();
+        outbox.push(delta(10, 4));
+        outbox.push_full(full_state(), 5);
+        // Broadcast before the state was taken, so already in it.
+        outbox.push(delta(11, 5));
+        outbox.push(delta(12, 6));
+        outbox.push(delta(13, 5));
+
+        let events = outbox.events.lock().unwrap();
+        let queued: Vec<(u64, Option<u64>)> = events
+            .iter()
+            .map(|(tag, msg)| (*tag, delta_seq(msg)))
+            .collect();
+        // The full state replaced the queued delta and took its tag; past
+        // delta 6 nothing is dropped any more.
+        assert_eq!(queued, vec![(10, None), (12, Some(6)), (13, Some(5))]);
+        assert!(is_full_state(&events[0].1));
+    }
+
+    #[tokio::test]
+    async fn session_tokens_may_not_command_another_sessions_panes() {
+        let (ctx, tmux, _) = tmuxy_core::ctx::test_ctx();
+        tmux.expect(
+            &[
+                "list-panes",
+                "-s",
+                "-t",
+                "work",
+                "-F",
+                "#{window_id} #{pane_id}",
+            ],
+            Ok("@1 %1\n@1 %2\n".into()),
+        );
+        let state = Arc::new(AppState::with_ctx(ctx));
+        let scope = Scope {
+            access: Access::Write,
+            session: Some("work".into()),
+            ..Scope::default()
+        };
+        let send = |pane: &str| {
+            let query = SessionQuery {
+                session: Some("work".into()),
+                colors: None,
+                background: None,
+                pane: None,
+            };
+            let body = serde_json::json!({
+                "cmd": "get_pane_text",
+                "args": { "paneId": pane }
+            });
+            commands_handler(
+                State(state.clone()),
+                Query(query),
+                HeaderMap::new(),
+                None,
+                Some(Extension(scope.clone())),
+                body.to_string().into(),
+            )
+        };
+        // %9 is another session's pane.
+        assert_eq!(send("%9").await.status(), StatusCode::FORBIDDEN);
+        assert_ne!(send("%2").await.status(), StatusCode::FORBIDDEN);
+    }
+
+    #[tokio::test]
+    async fn clients_searching_one_pane_each_get_their_own_results() {
+        let (ctx, tmux, _) = tmuxy_core::ctx::test_ctx();
+        tmux.expect(
+            &["display-message", "-t", "%1", "-p", "#{history_size}"],
+            Ok("5\n".into()),
+        );
+        tmux.expect(&["capture-pane", "-t", "%1", "-p"], Ok("prompt".into()));
+        tmux.expect(
+            &["capture-pane", "-t", "%1", "-p", "-S", "-5", "-E", "-1"],
+            Ok("foo\nbar".into()),
+        );
+        let state = Arc::new(AppState::with_ctx(ctx));
+        // A monitor that masks nothing.
+        let (monitor_tx, mut monitor_rx) = tokio::sync::mpsc::channel(8);
+        tokio::spawn(async move {
+            while let Some(cmd) = monitor_rx.recv().await {
+                if let MonitorCommand::MaskCaptured { content, reply, .. } = cmd {
+                    let _ = reply.send(content);
+                }
+            }
+        });
+        let outboxes = [Arc::new(Outbox::default()), Arc::new(Outbox::default())];
+        state.sessions.write().await.insert(
+            "work".into(),
+            SessionConnections {
+                connections: vec![1, 2],
+                outboxes: HashMap::from([(1, outboxes[0].clone()), (2, outboxes[1].clone())]),
+                monitor_command_tx: Some(monitor_tx),
+                ..SessionConnections::new()
+            },
+        );
+
+        let search = |conn_id: u64, query: &str| {
+            let state = state.clone();
+            let cmd = ClientCommand::decode(
+                serde_json::json!({
+                    "cmd": "pane_search",
+                    "args": { "paneId": "%1", "query": query }
+                })
+                .to_string()
+                .as_bytes(),
+            )
+            .unwrap();
+            async move {
+                let host = SseHost {
+                    state: &state,
+                    session: "work",
+                    conn_id: Some(conn_id),
+                };
+                dispatch(&host, cmd).await.unwrap()["searchId"].as_u64()
+            }
+        };
+        let (first, second) = tokio::join!(search(1, "foo"), search(2, "bar"));
+
+        for (outbox, id, text) in [(&outboxes[0], first, "foo"), (&outboxes[1], second, "bar")] {
+            let (_, msg) = tokio::time::timeout(Duration::from_secs(5), outbox.recv())
+                .await
+                .unwrap()
+                .unwrap();
+            let event: serde_json::Value = serde_json::from_str(&msg).unwrap();
+            assert_eq!(event["event"], "search-results");
+            assert_eq!(event["data"]["search_id"].as_u64(), id);
+            assert_eq!(event["data"]["matches"][0]["text"], text);
+            assert_eq!(event["data"]["done"], true);
+            assert!(outbox.events.lock().unwrap().is_empty());
+        }
     }
 }
@@ -1,20 +1,30 @@
 use axum::{
     body::Body,
-    extract::{Path, Query, State},
+    extract::{DefaultBodyLimit, Path, Query, State},
     http::StatusCode,
     response::{IntoResponse, Response},
     routing::{get, post},
-    Router,
+    Extension, Router,
 };
-use std::collections::{HashMap, VecDeque};
+use serde::Serialize;
+use std::collections::{HashMap, HashSet, VecDeque};
 use std::sync::atomic::{AtomicU64, Ordering};
 use std::sync::{Arc, Mutex as StdMutex};
+use std::time::Duration;
 use tmuxy_core::control_mode::{MonitorCommandSender, StoredImage};
+use tmuxy_core::key_table::{KeyTables, PrefixState};
+use tmuxy_core::pane_search::PaneSearches;
+use tmuxy_core::session::TmuxTransport;
+use tmuxy_core::table::TableCache;
+use tmuxy_core::usage;
 use tmuxy_core::{Ctx, RetryPolicy};
-use tokio::sync::{broadcast, Mutex, RwLock};
+
+use crate::metrics::{Metrics, SessionGauges};
+use crate::rate_limit::{CommandLimits, LimitConfig};
+use crate::tokens::{Scope, TokenStore};
+use tokio::sync::{broadcast, watch, Mutex, Notify, RwLock};
 use tokio::task::{JoinHandle, JoinSet};
 use tokio_util::sync::CancellationToken;
-use tower_http::cors::{Any, CorsLayer};
 
 /// Number of recent broadcast messages retained per session for
 /// `Last-Event-Id` replay and lagged-subscriber recovery. Sized to match the
@@ -24,8 +34,9 @@ use tower_http::cors::{Any, CorsLayer};
 pub const EVENT_BUFFER_SIZE: usize = 100;
 
 /// A broadcast message tagged with its monotonic per-session sequence id.
-/// The id is mirrored as the SSE `id:` field so the browser persists it
-/// across reconnects via the `Last-Event-Id` request header.
+/// The id is mirrored (qualified by the broadcast's epoch, see
+/// `SessionBroadcast::event_id`) as the SSE `id:` field so the browser
+/// persists it across reconnects via the `Last-Event-Id` request header.
 pub type TaggedEvent = (u64, String);
 
 /// Wraps a `broadcast::Sender` with the monotonic `seq` counter and the
@@ -40,6 +51,11 @@ pub struct SessionBroadcast {
     /// so a client that hit `RecvError::Lagged` replays from `recent` (the SSE
     /// handler's `Lagged` arm calls `replay_since`).
     pub tx: broadcast::Sender<TaggedEvent>,
+    /// Distinguishes this broadcast from earlier ones for the same session.
+    /// A session's broadcast is recreated once its last client leaves, and
+    /// its `seq` restarts with it — so a bare seq in a `Last-Event-Id` could
+    /// name an event of a broadcast that no longer exists.
+    pub epoch: u64,
     /// Monotonic per-session counter — `fetch_add(1)` produces the next id.
     pub seq: AtomicU64,
     /// Ring buffer of the most recent `EVENT_BUFFER_SIZE` tagged messages.
@@ -53,6 +69,7 @@ impl SessionBroadcast {
         let (tx, _) = broadcast::channel(EVENT_BUFFER_SIZE);
         Self {
             tx,
+            epoch: tmuxy_core::control_mode::new_epoch(),
             seq: AtomicU64::new(0),
             recent: StdMutex::new(VecDeque::with_capacity(EVENT_BUFFER_SIZE)),
         }
@@ -73,15 +90,54 @@ impl SessionBroadcast {
         id
     }
 
+    /// The SSE `id:` of the event with sequence id `seq`: `<epoch>-<seq>`.
+    pub fn event_id(&self, seq: u64) -> String {
+        format!("{}-{seq}", self.epoch)
+    }
+
     /// Return every buffered event with `seq > since`, in order.
-    /// `since` is the `Last-Event-Id` the reconnecting client sent.
     pub fn replay_since(&self, since: u64) -> Vec<TaggedEvent> {
+        self.replay_after(Some(since))
+    }
+
+    /// Like `replay_since`; `None` means the whole buffer.
+    pub fn replay_after(&self, since: Option<u64>) -> Vec<TaggedEvent> {
         match self.recent.lock() {
-            Ok(buf) => buf.iter().filter(|(s, _)| *s > since).cloned().collect(),
+            Ok(buf) => buf
+                .iter()
+                .filter(|(s, _)| since.is_none_or(|since| *s > since))
+                .cloned()
+                .collect(),
             Err(_) => Vec::new(),
         }
     }
 
+    /// The events a client reconnecting with `last_event_id` missed, or
+    /// `None` if the buffer no longer holds all of them. An id from another
+    /// epoch (or an unparseable one) means the client missed everything this
+    /// broadcast has sent, which the buffer holds only while nothing has
+    /// been evicted.
+    pub fn replay_for(&self, last_event_id: &str) -> Option<Vec<TaggedEvent>> {
+        let oldest = self.oldest_seq()?;
+        let since = last_event_id
+            .split_once('-')
+            .filter(|(epoch, _)| epoch.parse() == Ok(self.epoch))
+            .and_then(|(_, seq)| seq.parse::<u64>().ok());
+        match since {
+            Some(since) if since >= oldest.saturating_sub(1) => Some(self.replay_since(since)),
+            Some(_) => None,
+            None if oldest == 0 => Some(self.replay_after(None)),
+            None => None,
+        }
+    }
+
+    /// Whether some event after `since` (after none, for `None`) has
+    /// already been evicted, so replaying the buffer can't close the gap.
+    pub fn evicted_after(&self, since: Option<u64>) -> bool {
+        let next = since.map_or(0, |since| since + 1);
+        self.oldest_seq().is_some_and(|oldest| oldest > next)
+    }
+
     /// Lowest sequence id still present in the buffer, or `None` if empty.
     /// If the client's `Last-Event-Id` is below this, we can't replay
     /// without gaps and need to send a full state snapshot instead.
@@ -149,6 +205,49 @@ mod broadcast_tests {
         let b = SessionBroadcast::new();
         assert_eq!(b.oldest_seq(), None);
     }
+
+    #[test]
+    fn replay_for_resumes_within_the_epoch_only() {
+        let b = SessionBroadcast::new();
+        assert_eq!(b.replay_for(&b.event_id(0)), None);
+        for i in 0..5 {
+            b.broadcast(format!("m{}", i));
+        }
+        let seqs = |r: Option<Vec<TaggedEvent>>| {
+            r.map(|r| r.into_iter().map(|(s, _)| s).collect::<Vec<_>>())
+        };
+        assert_eq!(seqs(b.replay_for(&b.event_id(2))), Some(vec![3, 4]));
+        assert_eq!(seqs(b.replay_for(&b.event_id(4))), Some(vec![]));
+
+        // An id from an earlier broadcast of the session: the client missed
+        // everything, the Full this one opened with included.
+        let earlier = format!("{}-3", b.epoch - 1);
+        assert_eq!(seqs(b.replay_for(&earlier)), Some(vec![0, 1, 2, 3, 4]));
+        assert_eq!(seqs(b.replay_for("3")), Some(vec![0, 1, 2, 3, 4]));
+
+        for i in 5..(EVENT_BUFFER_SIZE + 5) {
+            b.broadcast(format!("m{}", i));
+        }
+        assert_eq!(b.replay_for(&earlier), None);
+        assert_eq!(b.replay_for(&b.event_id(3)), None);
+        assert_eq!(
+            seqs(b.replay_for(&b.event_id(4))).map(|s| s.len()),
+            Some(EVENT_BUFFER_SIZE)
+        );
+    }
+
+    #[test]
+    fn evicted_after_reports_gaps_the_buffer_cannot_fill() {
+        let b = SessionBroadcast::new();
+        assert!(!b.evicted_after(None));
+        for i in 0..(EVENT_BUFFER_SIZE + 5) {
+            b.broadcast(format!("m{}", i));
+        }
+        assert!(b.evicted_after(None));
+        assert!(b.evicted_after(Some(3)));
+        assert!(!b.evicted_after(Some(4)));
+        assert!(!b.evicted_after(Some(EVENT_BUFFER_SIZE as u64 + 4)));
+    }
 }
 
 /// Build an HTTP response from a status, content-type, and body.
@@ -168,7 +267,7 @@ pub(crate) fn build_response(status: StatusCode, mime: &str, body: impl Into<Bod
 
 /// Convenience: build a `application/json` response from a serializable value.
 /// Serialization errors round-trip as a 500 with a plain-text fallback body.
-fn json_response(status: StatusCode, value: &serde_json::Value) -> Response {
+pub(crate) fn json_response(status: StatusCode, value: &serde_json::Value) -> Response {
     match serde_json::to_string(value) {
         Ok(body) => build_response(status, "application/json", body),
         Err(_) => build_response(
@@ -185,6 +284,23 @@ pub struct SessionConnections {
     pub connections: Vec<u64>,
     /// Each client's reported viewport size (cols, rows) for min-size computation
     pub client_sizes: HashMap<u64, (u32, u32)>,
+    /// Each client's frame interval, read by its SSE stream. `None` delivers
+    /// events as soon as they are broadcast.
+    pub frame_budgets: HashMap<u64, watch::Sender<Option<Duration>>>,
+    /// Each client's event queue, for events only it gets (see
+    /// `CommandHost::reply_sink`).
+    pub(crate) outboxes: HashMap<u64, Arc<crate::sse::Outbox>>,
+    /// Cancelled to end a client's stream from the server side (admin
+    /// disconnect, session stop); the client is told not to reconnect.
+    pub disconnects: HashMap<u64, CancellationToken>,
+    /// Each client's place in the key tables, for `key_input`.
+    pub prefix_states: HashMap<u64, PrefixState>,
+    /// Connections whose window has focus, for panes that asked for focus
+    /// reports (see `set_focus`).
+    pub focused: HashSet<u64>,
+    /// The session's key tables, loaded on the first `key_input` and dropped
+    /// when a `source-file` may have changed them.
+    pub key_tables: Option<Arc<KeyTables>>,
     /// Last resize dimensions sent to tmux (to avoid redundant resize commands)
     pub last_resize: Option<(u32, u32)>,
     /// Sender for commands to the session's monitor (resize, etc.)
@@ -195,6 +311,29 @@ pub struct SessionConnections {
     pub broadcast: Arc<SessionBroadcast>,
     /// Handle to the monitor task (so we can stop it when last client leaves)
     pub monitor_handle: Option<JoinHandle<()>>,
+    /// The monitor hibernated (see `TMUXY_HIBERNATE_AFTER`): its task is
+    /// detached from tmux, waiting on `wake` for the next client.
+    pub hibernating: bool,
+    /// Wakes a hibernating monitor.
+    pub wake: Arc<Notify>,
+    /// Context for the session's tmux when a saved server fronts it on
+    /// another host (see `AppState::session_ctx`).
+    pub remote_ctx: Option<Arc<Ctx>>,
+    /// The monitor task's reconnects so far, for the admin API.
+    pub restarts: MonitorRestarts,
+}
+
+/// How a session's monitor task has been reconnecting to tmux.
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct MonitorRestarts {
+    /// Reconnects since the task started, hibernation wake-ups aside.
+    pub count: u32,
+    /// Failed connects in a row; a healthy run resets it.
+    pub consecutive_failures: u32,
+    /// Wait before the next reconnect, in milliseconds.
+    pub backoff_ms: u64,
+    /// Why the monitor last lost tmux (see `TmuxMonitor::lost`).
+    pub last_lost: Option<String>,
 }
 
 impl Default for SessionConnections {
@@ -202,10 +341,20 @@ impl Default for SessionConnections {
         Self {
             connections: Vec::new(),
             client_sizes: HashMap::new(),
+            frame_budgets: HashMap::new(),
+            outboxes: HashMap::new(),
+            disconnects: HashMap::new(),
+            prefix_states: HashMap::new(),
+            focused: HashSet::new(),
+            key_tables: None,
             last_resize: None,
             monitor_command_tx: None,
             broadcast: Arc::new(SessionBroadcast::new()),
             monitor_handle: None,
+            hibernating: false,
+            wake: Arc::new(Notify::new()),
+            remote_ctx: None,
+            restarts: MonitorRestarts::default(),
         }
     }
 }
@@ -216,6 +365,12 @@ impl SessionConnections {
     }
 }
 
+/// The context for a saved server fronting `session` on another host, if
+/// `servers.json` maps it to one.
+pub fn remote_session_ctx(session: &str) -> Option<Arc<Ctx>> {
+    tmuxy_core::servers::server_for_session(session).map(|server| Ctx::live_on(server.transport()))
+}
+
 pub struct AppState {
     /// Per-session connection tracking
     pub sessions: RwLock<HashMap<String, SessionConnections>>,
@@ -236,12 +391,35 @@ pub struct AppState {
     /// Threaded into `TmuxMonitor` and reused for ad-hoc tmux dispatch via the
     /// Tower stack. Production uses `Ctx::live()`; tests substitute a mock ctx.
     pub ctx: Arc<Ctx>,
+    /// Counters behind `/api/metrics` and `/api/health`.
+    pub metrics: Metrics,
+    /// Shared by every session's commands (see `CommandHost::tables`).
+    pub tables: Arc<StdMutex<TableCache>>,
+    /// Shared by every session's commands (see `CommandHost::searches`).
+    pub searches: Arc<PaneSearches>,
+    /// Rate and in-flight limits on `POST /commands`.
+    pub command_limits: CommandLimits,
+    /// Scoped tokens minted through the admin API.
+    pub tokens: Arc<TokenStore>,
+    /// The clock behind `ctx` that `/api/test/advance-clock` moves.
+    #[cfg(feature = "test-hooks")]
+    pub test_clock: Option<Arc<tmuxy_core::OffsetClock>>,
 }
 
 impl Default for AppState {
+    #[cfg(not(feature = "test-hooks"))]
     fn default() -> Self {
         Self::with_ctx(Ctx::live())
     }
+
+    #[cfg(feature = "test-hooks")]
+    fn default() -> Self {
+        let clock = Arc::new(tmuxy_core::OffsetClock::default());
+        Self {
+            test_clock: Some(clock.clone()),
+            ..Self::with_ctx(Ctx::live_with_clock(clock))
+        }
+    }
 }
 
 impl AppState {
@@ -259,6 +437,13 @@ impl AppState {
             join_set: Mutex::new(JoinSet::new()),
             shutdown: CancellationToken::new(),
             ctx,
+            metrics: Metrics::new(),
+            tables: Arc::default(),
+            searches: Arc::default(),
+            command_limits: CommandLimits::new(LimitConfig::from_env()),
+            tokens: Arc::default(),
+            #[cfg(feature = "test-hooks")]
+            test_clock: None,
         }
     }
 
@@ -274,6 +459,25 @@ impl AppState {
         self.join_set.lock().await.spawn(fut);
     }
 
+    /// Per-session gauges for the metrics and health endpoints.
+    pub async fn session_gauges(&self) -> Vec<SessionGauges> {
+        let sessions = self.sessions.read().await;
+        let mut gauges: Vec<SessionGauges> = sessions
+            .iter()
+            .map(|(name, conns)| SessionGauges {
+                session: name.clone(),
+                clients: conns.connections.len(),
+                queue_depth: conns.broadcast.tx.len(),
+                monitor_running: conns
+                    .monitor_handle
+                    .as_ref()
+                    .is_some_and(|h| !h.is_finished()),
+            })
+            .collect();
+        gauges.sort_by(|a, b| a.session.cmp(&b.session));
+        gauges
+    }
+
     /// Thin wrapper around `Ctx::tmux_call`. Kept for handler ergonomics —
     /// SSE handlers grab `AppState` from axum and would otherwise need to
     /// thread `state.ctx` explicitly into every call site.
@@ -294,60 +498,94 @@ impl AppState {
     ) -> Result<String, tmuxy_core::TmuxError> {
         self.ctx.tmux_call_with_policy(args, op_name, policy).await
     }
+
+    /// The context reaching `session`'s tmux: a saved server's, when one
+    /// fronts the session on another host, otherwise the server's own.
+    pub async fn session_ctx(&self, session: &str) -> Arc<Ctx> {
+        if let Some(conns) = self.sessions.read().await.get(session) {
+            return conns.remote_ctx.clone().unwrap_or_else(|| self.ctx.clone());
+        }
+        remote_session_ctx(session).unwrap_or_else(|| self.ctx.clone())
+    }
+
+    /// How to reach `session`'s tmux: its saved server's ssh transport, or
+    /// the environment's.
+    pub async fn session_transport(&self, session: &str) -> TmuxTransport {
+        self.session_ctx(session)
+            .await
+            .transport
+            .clone()
+            .unwrap_or_else(TmuxTransport::from_env)
+    }
+
+    /// `tmux_call_with_policy` against `session`'s tmux (see `session_ctx`).
+    pub async fn session_call(
+        &self,
+        session: &str,
+        args: Vec<String>,
+        op_name: &str,
+        policy: RetryPolicy,
+    ) -> Result<String, tmuxy_core::TmuxError> {
+        self.session_ctx(session)
+            .await
+            .tmux_call_with_policy(args, op_name, policy)
+            .await
+    }
 }
 
 /// Build the API routes shared between dev server and production CLI.
-/// Returns a Router that needs `.fallback_service(...)` and `.with_state(state)`.
-pub fn api_routes() -> Router<Arc<AppState>> {
-    Router::new()
-        .route("/events", get(crate::sse::sse_handler))
+/// Returns a Router that needs `.fallback_service(...)` and `.with_state(state)`;
+/// CORS is left to the caller's origin policy (see `crate::origin`).
+pub fn api_routes(state: &Arc<AppState>) -> Router<Arc<AppState>> {
+    let commands = Router::new()
         .route("/commands", post(crate::sse::commands_handler))
-        .route("/api/file", get(file_handler))
+        .layer(axum::middleware::from_fn_with_state(
+            state.clone(),
+            crate::rate_limit::limit_commands,
+        ))
+        .layer(DefaultBodyLimit::max(
+            state.command_limits.config().max_body_bytes,
+        ));
+    let router = Router::new()
+        .route("/events", get(crate::sse::sse_handler))
+        .merge(commands)
+        .route("/api/file", get(crate::files::file_handler))
+        .route("/api/markdown", get(crate::files::markdown_handler))
         .route("/api/images/{pane_id}/{image_id}", get(image_handler))
-        .layer(
-            CorsLayer::new()
-                .allow_origin(Any)
-                .allow_methods(Any)
-                .allow_headers(Any),
-        )
+        .route("/api/metrics", get(metrics_handler))
+        .route("/api/health", get(health_handler))
+        .route("/api/stats", get(stats_handler))
+        .route("/api/sessions", get(sessions_handler))
+        .route("/api/snapshot", get(snapshot_handler));
+    #[cfg(feature = "test-hooks")]
+    let router = router.merge(crate::test_hooks::routes());
+    router
 }
 
 // ============================================
 // Internal Handlers
 // ============================================
 
-#[derive(Debug, serde::Deserialize)]
-struct FileQuery {
-    path: String,
-}
-
-async fn file_handler(Query(query): Query<FileQuery>) -> Response {
-    let path = std::path::Path::new(&query.path);
-    let content_type = match path.extension().and_then(|e| e.to_str()) {
-        Some("png") => "image/png",
-        Some("jpg" | "jpeg") => "image/jpeg",
-        Some("gif") => "image/gif",
-        Some("webp") => "image/webp",
-        Some("svg") => "image/svg+xml",
-        Some("bmp") => "image/bmp",
-        Some("ico") => "image/x-icon",
-        _ => "text/plain; charset=utf-8",
-    };
-    match std::fs::read(path) {
-        Ok(content) => build_response(StatusCode::OK, content_type, content),
-        Err(e) => json_response(
-            StatusCode::NOT_FOUND,
-            &serde_json::json!({ "error": format!("{}", e) }),
-        ),
-    }
-}
-
 async fn image_handler(
     State(state): State<Arc<AppState>>,
     Path((pane_id, image_id)): Path<(String, u32)>,
+    scope: Option<Extension<Scope>>,
 ) -> Response {
+    let pane_id = format!("%{}", pane_id);
+    // A pane-scoped token's pane is checked by `tokens::reachable`.
+    if let Some(session) = scope.as_ref().and_then(|Extension(s)| s.session.as_deref()) {
+        if crate::sse::session_lacks(&state, session, &[&pane_id])
+            .await
+            .is_some()
+        {
+            return json_response(
+                StatusCode::FORBIDDEN,
+                &serde_json::json!({ "error": format!("{pane_id} is not in session {session}") }),
+            );
+        }
+    }
     let store = state.image_store.read().await;
-    let key = (format!("%{}", pane_id), image_id);
+    let key = (pane_id, image_id);
     match store.get(&key) {
         Some(img) => Response::builder()
             .status(StatusCode::OK)
@@ -362,6 +600,139 @@ async fn image_handler(
     }
 }
 
+async fn metrics_handler(State(state): State<Arc<AppState>>) -> Response {
+    let gauges = state.session_gauges().await;
+    build_response(
+        StatusCode::OK,
+        "text/plain; version=0.0.4; charset=utf-8",
+        state.metrics.render(&gauges),
+    )
+}
+
+async fn health_handler(State(state): State<Arc<AppState>>) -> Response {
+    let gauges = state.session_gauges().await;
+    json_response(StatusCode::OK, &state.metrics.health(&gauges))
+}
+
+#[derive(Debug, Default, serde::Deserialize)]
+struct StatsQuery {
+    /// Only the last this many days.
+    days: Option<u64>,
+    /// Entries per list.
+    limit: Option<usize>,
+}
+
+async fn stats_handler(Query(query): Query<StatsQuery>) -> Response {
+    let result = tokio::task::spawn_blocking(move || {
+        usage::summarize(
+            &usage::usage_db_path(),
+            query.days,
+            query.limit.unwrap_or(usage::SUMMARY_LIMIT),
+        )
+        .map_err(|e| e.to_string())
+        .and_then(|summary| serde_json::to_value(summary).map_err(|e| e.to_string()))
+    })
+    .await
+    .unwrap_or_else(|e| Err(e.to_string()));
+    match result {
+        Ok(summary) => json_response(StatusCode::OK, &summary),
+        Err(e) => json_response(
+            StatusCode::INTERNAL_SERVER_ERROR,
+            &serde_json::json!({ "error": e }),
+        ),
+    }
+}
+
+/// One session on the tmux server, as `GET /api/sessions` lists it.
+#[derive(Debug, serde::Serialize)]
+struct DiscoveredSession {
+    #[serde(flatten)]
+    info: tmuxy_core::discovery::SessionInfo,
+    /// Browser clients this server has streaming it
+    clients: usize,
+}
+
+/// Every session on the tmux server, not only tmuxy's, for picking one to
+/// attach to with `/events?session=`. No tmux server means no sessions.
+async fn sessions_handler(State(state): State<Arc<AppState>>) -> Response {
+    let output = match state
+        .tmux_call_with_policy(
+            tmuxy_core::discovery::list_sessions_args(),
+            "sessions:list",
+            RetryPolicy::standard(),
+        )
+        .await
+    {
+        Ok(output) => output,
+        Err(e) if tmuxy_core::discovery::is_no_server_error(&e.to_string()) => String::new(),
+        Err(e) => {
+            return json_response(
+                StatusCode::BAD_GATEWAY,
+                &serde_json::json!({ "error": e.to_string() }),
+            );
+        }
+    };
+    let connections = state.sessions.read().await;
+    let sessions: Vec<DiscoveredSession> = tmuxy_core::discovery::parse_sessions(&output)
+        .into_iter()
+        .map(|info| DiscoveredSession {
+            clients: connections
+                .get(&info.name)
+                .map_or(0, |conns| conns.connections.len()),
+            info,
+        })
+        .collect();
+    json_response(StatusCode::OK, &serde_json::json!(sessions))
+}
+
+#[derive(Debug, Default, serde::Deserialize)]
+struct SnapshotQuery {
+    session: Option<String>,
+    /// How long to read the attached client's output, in milliseconds.
+    timeout: Option<u64>,
+}
+
+/// Longest `/api/snapshot` read: the capture holds a blocking thread.
+const MAX_SNAPSHOT_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// The session's screen as tmux itself renders it (`snapshot::capture`).
+async fn snapshot_handler(
+    State(state): State<Arc<AppState>>,
+    Query(query): Query<SnapshotQuery>,
+) -> Response {
+    let session = query
+        .session
+        .unwrap_or_else(|| tmuxy_core::DEFAULT_SESSION_NAME.to_string());
+    if let Err(e) = tmuxy_core::discovery::validate_session_name(&session) {
+        return json_response(StatusCode::BAD_REQUEST, &serde_json::json!({ "error": e }));
+    }
+    let timeout = query
+        .timeout
+        .map(Duration::from_millis)
+        .unwrap_or(tmuxy_core::snapshot::DEFAULT_TIMEOUT)
+        .min(MAX_SNAPSHOT_TIMEOUT);
+    let transport = state.session_transport(&session).await;
+    let result = tokio::task::spawn_blocking(move || {
+        tmuxy_core::snapshot::capture(&transport, &session, timeout)
+    })
+    .await;
+    match result {
+        Ok(Ok(snapshot)) => json_response(StatusCode::OK, &serde_json::json!(snapshot)),
+        Ok(Err(e @ tmuxy_core::TmuxError::SessionNotFound { .. })) => json_response(
+            StatusCode::NOT_FOUND,
+            &serde_json::json!({ "error": e.to_string() }),
+        ),
+        Ok(Err(e)) => json_response(
+            StatusCode::INTERNAL_SERVER_ERROR,
+            &serde_json::json!({ "error": e.to_string() }),
+        ),
+        Err(e) => json_response(
+            StatusCode::INTERNAL_SERVER_ERROR,
+            &serde_json::json!({ "error": e.to_string() }),
+        ),
+    }
+}
+
 /// Find the workspace root (directory with package.json containing "workspaces")
 pub fn find_workspace_root() -> std::path::PathBuf {
     std::env::current_dir()
@@ -387,3 +758,52 @@ pub fn find_workspace_root() -> std::path::PathBuf {
             std::env::current_dir().unwrap_or_else(|_| std::path::PathBuf::from("."))
         })
 }
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used, clippy::expect_used)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn session_tokens_only_fetch_their_sessions_images() {
+        let (ctx, tmux, _) = tmuxy_core::ctx::test_ctx();
+        tmux.expect(
+            &[
+                "list-panes",
+                "-s",
+                "-t",
+                "work",
+                "-F",
+                "#{window_id} #{pane_id}",
+            ],
+            Ok("@1 %1\n".into()),
+        );
+        let state = Arc::new(AppState::with_ctx(ctx));
+        {
+            let mut store = state.image_store.write().await;
+            for pane in ["%1", "%9"] {
+                store.insert(
+                    (pane.to_string(), 1),
+                    StoredImage {
+                        mime_type: "image/png".into(),
+                        data: vec![0],
+                    },
+                );
+            }
+        }
+        let scope = Scope {
+            session: Some("work".into()),
+            ..Scope::default()
+        };
+        let fetch = |pane: &str| {
+            image_handler(
+                State(state.clone()),
+                Path((pane.to_string(), 1)),
+                Some(Extension(scope.clone())),
+            )
+        };
+        assert_eq!(fetch("1").await.status(), StatusCode::OK);
+        // %9 is another session's pane.
+        assert_eq!(fetch("9").await.status(), StatusCode::FORBIDDEN);
+    }
+}
@@ -0,0 +1,253 @@
+//! The sampler behind `tmuxy widget sysmon`: CPU, memory, disk and network
+//! stats for a glanceable dashboard pane.
+//!
+//! Runs in the widget's pane, under the `tmuxy-widget` launcher. Every
+//! interval it writes one text line for plain terminals and the same sample
+//! as JSON on the widget data channel
+//! ([`WIDGET_DATA_OSC`](tmuxy_core::control_mode::WIDGET_DATA_OSC)), which
+//! the frontend's sysmon widget draws.
+
+use serde::Serialize;
+use std::io::Write as _;
+use std::time::{Duration, Instant};
+use sysinfo::{Disks, Networks, System, MINIMUM_CPU_UPDATE_INTERVAL};
+use tmuxy_core::control_mode::WIDGET_DATA_OSC;
+
+/// One sample, as the frontend widget receives it. Sizes are bytes, rates
+/// bytes per second, usage percentages.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Sample {
+    /// Overall CPU usage.
+    pub cpu: f32,
+    /// Usage per logical CPU.
+    pub cpus: Vec<f32>,
+    pub memory: Usage,
+    pub swap: Usage,
+    pub disks: Vec<DiskUsage>,
+    pub network: NetworkRate,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct Usage {
+    pub used: u64,
+    pub total: u64,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct DiskUsage {
+    pub mount: String,
+    pub used: u64,
+    pub total: u64,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct NetworkRate {
+    pub rx: f64,
+    pub tx: f64,
+}
+
+/// Parse an interval as the other widgets take it: `500ms`, `2s`, `1m`,
+/// `1h`, or bare seconds.
+pub fn parse_interval(text: &str) -> Option<Duration> {
+    let text = text.trim();
+    let (number, scale) = if let Some(ms) = text.strip_suffix("ms") {
+        (ms, 0.001)
+    } else if let Some(s) = text.strip_suffix('s') {
+        (s, 1.0)
+    } else if let Some(m) = text.strip_suffix('m') {
+        (m, 60.0)
+    } else if let Some(h) = text.strip_suffix('h') {
+        (h, 3600.0)
+    } else {
+        (text, 1.0)
+    };
+    let value: f64 = number.parse().ok()?;
+    if !value.is_finite() || value <= 0.0 {
+        return None;
+    }
+    Duration::try_from_secs_f64(value * scale).ok()
+}
+
+impl Sample {
+    /// One line for a plain terminal: `cpu 12%  mem 3.1G/15.6G  / 48%  net ↓1.2M/s ↑40.0K/s`.
+    pub fn render(&self) -> String {
+        let mut line = format!(
+            "cpu {:.0}%  mem {}/{}",
+            self.cpu,
+            human(self.memory.used as f64),
+            human(self.memory.total as f64),
+        );
+        for disk in &self.disks {
+            line.push_str(&format!(
+                "  {} {:.0}%",
+                disk.mount,
+                percent(disk.used, disk.total)
+            ));
+        }
+        line.push_str(&format!(
+            "  net ↓{}/s ↑{}/s",
+            human(self.network.rx),
+            human(self.network.tx),
+        ));
+        line
+    }
+}
+
+fn percent(used: u64, total: u64) -> f64 {
+    if total == 0 {
+        0.0
+    } else {
+        used as f64 * 100.0 / total as f64
+    }
+}
+
+/// `1536` → `1.5K`: binary units, one decimal above a kilobyte.
+fn human(bytes: f64) -> String {
+    const UNITS: [&str; 5] = ["K", "M", "G", "T", "P"];
+    if bytes < 1024.0 {
+        return format!("{bytes:.0}B");
+    }
+    let mut value = bytes / 1024.0;
+    let mut unit = 0;
+    while value >= 1024.0 && unit < UNITS.len() - 1 {
+        value /= 1024.0;
+        unit += 1;
+    }
+    format!("{value:.1}{}", UNITS[unit])
+}
+
+struct Sampler {
+    system: System,
+    disks: Disks,
+    networks: Networks,
+    last: Instant,
+}
+
+impl Sampler {
+    fn new() -> Self {
+        let mut system = System::new();
+        // CPU usage is a difference between two refreshes; take the first now
+        // so the first sample already has one to compare against.
+        system.refresh_cpu_usage();
+        Self {
+            system,
+            disks: Disks::new_with_refreshed_list(),
+            networks: Networks::new_with_refreshed_list(),
+            last: Instant::now(),
+        }
+    }
+
+    fn sample(&mut self) -> Sample {
+        self.system.refresh_cpu_usage();
+        self.system.refresh_memory();
+        self.disks.refresh(true);
+        self.networks.refresh(true);
+        let elapsed = self.last.elapsed().as_secs_f64().max(f64::EPSILON);
+        self.last = Instant::now();
+
+        let (rx, tx) = self
+            .networks
+            .iter()
+            .filter(|(name, _)| name.as_str() != "lo")
+            .fold((0u64, 0u64), |(rx, tx), (_, data)| {
+                (rx + data.received(), tx + data.transmitted())
+            });
+        let mut disks: Vec<DiskUsage> = self
+            .disks
+            .iter()
+            .filter(|disk| disk.total_space() > 0)
+            .map(|disk| DiskUsage {
+                mount: disk.mount_point().to_string_lossy().into_owned(),
+                used: disk.total_space().saturating_sub(disk.available_space()),
+                total: disk.total_space(),
+            })
+            .collect();
+        disks.sort_by(|a, b| a.mount.cmp(&b.mount));
+        disks.dedup_by(|a, b| a.mount == b.mount);
+
+        Sample {
+            cpu: self.system.global_cpu_usage(),
+            cpus: self
+                .system
+                .cpus()
+                .iter()
+                .map(|cpu| cpu.cpu_usage())
+                .collect(),
+            memory: Usage {
+                used: self.system.used_memory(),
+                total: self.system.total_memory(),
+            },
+            swap: Usage {
+                used: self.system.used_swap(),
+                total: self.system.total_swap(),
+            },
+            disks,
+            network: NetworkRate {
+                rx: rx as f64 / elapsed,
+                tx: tx as f64 / elapsed,
+            },
+        }
+    }
+}
+
+/// Sample every `interval` until stdout goes away (the widget was closed).
+/// Intervals shorter than sysinfo can measure CPU usage over are raised.
+pub fn run(interval: Duration) -> std::io::Result<()> {
+    let interval = interval.max(MINIMUM_CPU_UPDATE_INTERVAL);
+    let mut sampler = Sampler::new();
+    let mut out = std::io::stdout().lock();
+    loop {
+        std::thread::sleep(interval);
+        let sample = sampler.sample();
+        let json = serde_json::to_string(&sample)?;
+        writeln!(out, "\x1b]{WIDGET_DATA_OSC};{json}\x07{}", sample.render())?;
+        out.flush()?;
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used, clippy::expect_used)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn intervals_take_the_widget_units() {
+        assert_eq!(parse_interval("500ms"), Some(Duration::from_millis(500)));
+        assert_eq!(parse_interval("2s"), Some(Duration::from_secs(2)));
+        assert_eq!(parse_interval("1.5"), Some(Duration::from_millis(1500)));
+        assert_eq!(parse_interval("1m"), Some(Duration::from_secs(60)));
+        assert_eq!(parse_interval("1h"), Some(Duration::from_secs(3600)));
+        for bad in ["", "0s", "-1s", "fast", "2d", "NaN"] {
+            assert_eq!(parse_interval(bad), None, "{bad}");
+        }
+    }
+
+    #[test]
+    fn a_sample_renders_as_one_line() {
+        let sample = Sample {
+            cpu: 12.4,
+            cpus: vec![10.0, 14.8],
+            memory: Usage {
+                used: 3 << 30,
+                total: 16 << 30,
+            },
+            swap: Usage { used: 0, total: 0 },
+            disks: vec![DiskUsage {
+                mount: "/".into(),
+                used: 48,
+                total: 100,
+            }],
+            network: NetworkRate {
+                rx: 1536.0,
+                tx: 12.0,
+            },
+        };
+        assert_eq!(
+            sample.render(),
+            "cpu 12%  mem 3.0G/16.0G  / 48%  net ↓1.5K/s ↑12B/s"
+        );
+        let json = serde_json::to_value(&sample).unwrap();
+        assert_eq!(json["memory"]["total"], 16u64 << 30);
+        assert_eq!(json["disks"][0]["mount"], "/");
+    }
+}
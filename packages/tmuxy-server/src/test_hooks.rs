@@ -0,0 +1,187 @@
+//! Deterministic hooks for the frontend E2E suite under `/api/test`, compiled
+//! in only with the `test-hooks` feature.
+//!
+//! - `POST /api/test/inject-output` `{session, paneId, data}` feeds `data` to
+//!   the pane's aggregator as if tmux had sent it, through the same path as
+//!   real `%output`.
+//! - `POST /api/test/advance-clock` `{ms}` moves the server's clock forward
+//!   and lets every monitor flush what that made due.
+//! - `POST /api/test/freeze-throttle` `{session, frozen}` holds the session's
+//!   pane output until the clock is advanced, instead of flushing it on a
+//!   timer.
+//!
+//! Together they let a test reach a state without racing real tmux output.
+//! A build with this feature lets anyone who can reach the port type into
+//! panes: never ship it.
+
+use axum::{
+    extract::State,
+    http::StatusCode,
+    response::{IntoResponse, Response},
+    routing::post,
+    Json, Router,
+};
+use serde::Deserialize;
+use std::sync::Arc;
+use std::time::Duration;
+use tmuxy_core::control_mode::{MonitorCommand, TestHook};
+use tracing::info;
+
+use crate::state::AppState;
+
+pub fn routes() -> Router<Arc<AppState>> {
+    Router::new()
+        .route("/api/test/inject-output", post(inject_output))
+        .route("/api/test/advance-clock", post(advance_clock))
+        .route("/api/test/freeze-throttle", post(freeze_throttle))
+}
+
+#[derive(Debug, Deserialize)]
+struct InjectOutput {
+    session: String,
+    #[serde(rename = "paneId")]
+    pane_id: String,
+    data: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct AdvanceClock {
+    ms: u64,
+}
+
+#[derive(Debug, Deserialize)]
+struct FreezeThrottle {
+    session: String,
+    frozen: bool,
+}
+
+fn error_response(status: StatusCode, message: &str) -> Response {
+    (status, Json(serde_json::json!({ "error": message }))).into_response()
+}
+
+async fn send_hook(state: &Arc<AppState>, session: &str, hook: TestHook) -> Response {
+    match crate::sse::send_to_monitor(state, session, MonitorCommand::Test(hook)).await {
+        Ok(()) => Json(serde_json::json!(null)).into_response(),
+        Err(e) => error_response(StatusCode::CONFLICT, &e),
+    }
+}
+
+async fn inject_output(
+    State(state): State<Arc<AppState>>,
+    Json(req): Json<InjectOutput>,
+) -> Response {
+    let hook = TestHook::InjectOutput {
+        pane_id: req.pane_id,
+        data: req.data.into_bytes(),
+    };
+    send_hook(&state, &req.session, hook).await
+}
+
+async fn advance_clock(
+    State(state): State<Arc<AppState>>,
+    Json(req): Json<AdvanceClock>,
+) -> Response {
+    let Some(clock) = state.test_clock.as_ref() else {
+        return error_response(
+            StatusCode::CONFLICT,
+            "the server isn't running on the test clock",
+        );
+    };
+    let offset = clock.advance(Duration::from_millis(req.ms));
+    info!(
+        ms = req.ms,
+        offset_ms = offset.as_millis() as u64,
+        "test clock advanced"
+    );
+    let monitors: Vec<_> = state
+        .sessions
+        .read()
+        .await
+        .values()
+        .filter_map(|s| s.monitor_command_tx.clone())
+        .collect();
+    for tx in monitors {
+        let _ = tx.send(MonitorCommand::Test(TestHook::ClockAdvanced)).await;
+    }
+    Json(serde_json::json!({ "offsetMs": offset.as_millis() as u64 })).into_response()
+}
+
+async fn freeze_throttle(
+    State(state): State<Arc<AppState>>,
+    Json(req): Json<FreezeThrottle>,
+) -> Response {
+    send_hook(&state, &req.session, TestHook::FreezeThrottle(req.frozen)).await
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used, clippy::expect_used)]
+mod tests {
+    use super::*;
+    use crate::state::SessionConnections;
+    use axum::body::Body;
+    use axum::http::Request;
+    use tower::ServiceExt;
+
+    fn post_json(uri: &str, body: serde_json::Value) -> Request<Body> {
+        Request::post(uri)
+            .header("content-type", "application/json")
+            .body(Body::from(body.to_string()))
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn hooks_reach_the_sessions_monitor() {
+        let state = Arc::new(AppState::new());
+        let (tx, mut rx) = tokio::sync::mpsc::channel(8);
+        let mut conns = SessionConnections::new();
+        conns.monitor_command_tx = Some(tx);
+        state
+            .sessions
+            .write()
+            .await
+            .insert("e2e".to_string(), conns);
+        let app = routes().with_state(state.clone());
+
+        let before = state.ctx.clock.now();
+        let response = app
+            .clone()
+            .oneshot(post_json(
+                "/api/test/advance-clock",
+                serde_json::json!({ "ms": 1500 }),
+            ))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        assert!(state.ctx.clock.now() >= before + Duration::from_millis(1500));
+        assert!(matches!(
+            rx.recv().await,
+            Some(MonitorCommand::Test(TestHook::ClockAdvanced))
+        ));
+
+        let response = app
+            .clone()
+            .oneshot(post_json(
+                "/api/test/inject-output",
+                serde_json::json!({ "session": "e2e", "paneId": "%0", "data": "\u{1b}[31mred" }),
+            ))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        match rx.recv().await {
+            Some(MonitorCommand::Test(TestHook::InjectOutput { pane_id, data })) => {
+                assert_eq!(pane_id, "%0");
+                assert_eq!(data, b"\x1b[31mred");
+            }
+            other => panic!("expected InjectOutput, got {:?}", other),
+        }
+
+        let response = app
+            .oneshot(post_json(
+                "/api/test/freeze-throttle",
+                serde_json::json!({ "session": "nope", "frozen": true }),
+            ))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::CONFLICT);
+    }
+}
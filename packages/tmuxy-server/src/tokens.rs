@@ -0,0 +1,612 @@
+//! Scoped access tokens: credentials narrower than the server password.
+//!
+//! An admin mints a token with `POST /api/admin/tokens`, giving it a
+//! [`Scope`]: how much it may do ([`Access`]), and optionally one session,
+//! one pane, or an allow-list of commands. `/commands` checks every command
+//! a token sends against its scope; a token may only reach the frontend,
+//! `/events`, `/commands` and pane images, never the admin or file APIs.
+//!
+//! A client presents a token as `Authorization: Bearer <token>`, or as
+//! `?token=<token>` on the page URL; the server then sets it as the
+//! `tmuxy_token` cookie, so the frontend's own requests carry it. The
+//! cookie's `SameSite` is `Strict` unless `TMUXY_TOKEN_SAMESITE` says
+//! otherwise (`none` lets an embedding page on another site use it, and
+//! needs HTTPS).
+//!
+//! Tokens only mean something when the server has a password; they are kept
+//! in memory and lost on restart.
+
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::Mutex;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use axum::http::{header, HeaderMap, Method};
+use base64::Engine as _;
+use serde::{Deserialize, Serialize};
+use tracing::warn;
+
+use tmuxy_protocol::{ClientCommand, OptionRef, OptionScope};
+
+/// The cookie a token is kept in after it arrives on the page URL.
+pub const TOKEN_COOKIE: &str = "tmuxy_token";
+/// `SameSite` for [`TOKEN_COOKIE`]: `strict`, `lax` or `none`.
+pub const TOKEN_SAMESITE_ENV: &str = "TMUXY_TOKEN_SAMESITE";
+
+/// How much a token may do; each level includes the ones before it.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Access {
+    /// Watch, scroll back, and report the client's own size and focus.
+    #[default]
+    View,
+    /// Also open git widgets and remove widgets.
+    Widgets,
+    /// Anything the password allows on `/commands`.
+    Write,
+}
+
+impl fmt::Display for Access {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::View => "view",
+            Self::Widgets => "widgets",
+            Self::Write => "write",
+        })
+    }
+}
+
+impl Access {
+    /// The access a scoped token needs to send `cmd`. Reads and per-client
+    /// settings are `View`; anything not listed needs `Write`.
+    pub fn needed_for(cmd: &ClientCommand) -> Self {
+        match cmd {
+            ClientCommand::GetInitialState { .. }
+            | ClientCommand::SetClientSize { .. }
+            | ClientCommand::SetFrameBudget { .. }
+            | ClientCommand::GetEffectiveBindings
+            | ClientCommand::DragPaneOver { .. }
+            | ClientCommand::SetFocus { .. }
+            | ClientCommand::PreviewSplit { .. }
+            | ClientCommand::GetStalePanes { .. }
+            | ClientCommand::GetExitSummaries
+            | ClientCommand::GetWindowMru
+            | ClientCommand::GetRecentScrollback { .. }
+            | ClientCommand::GetCommandHistory { .. }
+            | ClientCommand::GetScrollbackCells { .. }
+            | ClientCommand::ExportPane { .. }
+            | ClientCommand::GetPaneText { .. }
+            | ClientCommand::PaneSearch { .. }
+            | ClientCommand::GetOption { .. }
+            | ClientCommand::WatchOption { .. }
+            | ClientCommand::GetThemeSettings
+            | ClientCommand::GetThemesList => Self::View,
+            // Not `create_widget` or `update_widget`: a widget spec can carry
+            // a command to run (the metric widget's `source`), run again when
+            // the widget is restored. Nor `table_query`, which reads any path.
+            ClientCommand::CreateGitWidget { .. } | ClientCommand::DestroyWidget { .. } => {
+                Self::Widgets
+            }
+            _ => Self::Write,
+        }
+    }
+}
+
+/// What a token allows.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Scope {
+    #[serde(default)]
+    pub access: Access,
+    /// Only this session.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub session: Option<String>,
+    /// Only commands on this pane, plus reads that name no pane.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pane: Option<String>,
+    /// Only these commands (wire names), on top of `access`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub commands: Option<Vec<String>>,
+}
+
+impl Scope {
+    pub fn permits_session(&self, session: &str) -> Result<(), String> {
+        match &self.session {
+            Some(allowed) if allowed != session => {
+                Err(format!("this token is limited to session {allowed}"))
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// Whether `cmd` may run in `session` under this scope.
+    pub fn permits(&self, session: &str, cmd: &ClientCommand) -> Result<(), String> {
+        self.permits_session(session)?;
+        let name = cmd.name();
+        if let Some(commands) = &self.commands {
+            if !commands.iter().any(|c| c == name) {
+                return Err(format!("this token may not send {name}"));
+            }
+        }
+        let needed = Access::needed_for(cmd);
+        if needed > self.access {
+            return Err(format!("{name} needs {needed} access"));
+        }
+        if let ClientCommand::GetOption { option } | ClientCommand::WatchOption { option } = cmd {
+            self.permits_option(session, option)?;
+        }
+        if let Some(pane) = &self.pane {
+            match cmd.pane_id() {
+                Some(target) if target == pane => {}
+                // Sizing the viewport resizes every pane in the window, and
+                // these read every pane of the session.
+                None if matches!(
+                    cmd,
+                    ClientCommand::SetClientSize { .. }
+                        | ClientCommand::GetStalePanes { .. }
+                        | ClientCommand::GetExitSummaries
+                        | ClientCommand::GetWindowMru
+                ) =>
+                {
+                    return Err(format!("this token is limited to pane {pane}"))
+                }
+                None if needed == Access::View => {}
+                _ => return Err(format!("this token is limited to pane {pane}")),
+            }
+        }
+        Ok(())
+    }
+
+    /// Whether `option` stays inside the token's session and pane. A
+    /// session-scoped token's window or pane target must also be one of the
+    /// session's, which only tmux knows (see [`Self::session_targets`]).
+    fn permits_option(&self, session: &str, option: &OptionRef) -> Result<(), String> {
+        let target = option.target.as_deref();
+        match (option.scope, target) {
+            (OptionScope::Server, _) | (OptionScope::Session, None) => return Ok(()),
+            (OptionScope::Session, Some(target)) if target == session => return Ok(()),
+            (OptionScope::Pane, Some(target)) if self.pane.as_deref() == Some(target) => {
+                return Ok(())
+            }
+            _ => {}
+        }
+        if let Some(pane) = &self.pane {
+            return Err(format!("this token is limited to pane {pane}"));
+        }
+        match (&self.session, target) {
+            (None, _) | (Some(_), None) => Ok(()),
+            (Some(_), Some(target)) if option.scope != OptionScope::Session && is_id(target) => {
+                Ok(())
+            }
+            (Some(allowed), Some(_)) => Err(format!("this token is limited to session {allowed}")),
+        }
+    }
+
+    /// The panes and windows `cmd` names that must be checked to belong to
+    /// the token's session before it runs: its pane, a dragged border's
+    /// window, or a window or pane option's target.
+    pub fn session_targets<'a>(&self, cmd: &'a ClientCommand) -> Vec<&'a str> {
+        if self.session.is_none() || self.pane.is_some() {
+            return Vec::new();
+        }
+        let mut targets: Vec<&str> = cmd.pane_id().into_iter().collect();
+        match cmd {
+            ClientCommand::DragResize { window_id, .. } => targets.push(window_id),
+            ClientCommand::GetOption { option } | ClientCommand::WatchOption { option }
+                if matches!(option.scope, OptionScope::Window | OptionScope::Pane) =>
+            {
+                targets.extend(option.target.as_deref());
+            }
+            _ => {}
+        }
+        targets
+    }
+}
+
+/// Whether `target` is a window (`@3`) or pane (`%3`) id.
+fn is_id(target: &str) -> bool {
+    target
+        .strip_prefix(['@', '%'])
+        .is_some_and(|n| !n.is_empty() && n.bytes().all(|b| b.is_ascii_digit()))
+}
+
+/// A minted token, as the admin API lists it (without its secret).
+#[derive(Debug, Clone, Serialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct TokenInfo {
+    pub id: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub label: Option<String>,
+    pub scope: Scope,
+    /// Unix seconds.
+    pub created_at: u64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub expires_at: Option<u64>,
+}
+
+impl TokenInfo {
+    fn expired(&self, now: u64) -> bool {
+        self.expires_at.is_some_and(|at| now >= at)
+    }
+}
+
+struct Entry {
+    secret: Vec<u8>,
+    info: TokenInfo,
+}
+
+/// Every live token, by id.
+#[derive(Default)]
+pub struct TokenStore {
+    tokens: Mutex<HashMap<String, Entry>>,
+}
+
+fn unix_now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |d| d.as_secs())
+}
+
+fn random<const N: usize>() -> Result<[u8; N], String> {
+    let mut bytes = [0u8; N];
+    getrandom::fill(&mut bytes).map_err(|e| format!("no randomness for a token: {e}"))?;
+    Ok(bytes)
+}
+
+impl TokenStore {
+    /// Mint a token for `scope`, good for `ttl` if given. Returns the token
+    /// — `<id>.<secret>`, shown only this once — and its listing.
+    pub fn mint(
+        &self,
+        scope: Scope,
+        label: Option<String>,
+        ttl: Option<Duration>,
+    ) -> Result<(String, TokenInfo), String> {
+        let id: String = random::<6>()?.iter().map(|b| format!("{b:02x}")).collect();
+        let secret = base64::engine::general_purpose::URL_SAFE_NO_PAD.encode(random::<32>()?);
+        let now = unix_now();
+        let info = TokenInfo {
+            id: id.clone(),
+            label,
+            scope,
+            created_at: now,
+            expires_at: ttl.map(|ttl| now.saturating_add(ttl.as_secs())),
+        };
+        let entry = Entry {
+            secret: secret.as_bytes().to_vec(),
+            info: info.clone(),
+        };
+        self.lock().insert(id.clone(), entry);
+        Ok((format!("{id}.{secret}"), info))
+    }
+
+    /// Live tokens, oldest first.
+    pub fn list(&self) -> Vec<TokenInfo> {
+        let now = unix_now();
+        let mut tokens = self.lock();
+        tokens.retain(|_, entry| !entry.info.expired(now));
+        let mut infos: Vec<TokenInfo> = tokens.values().map(|e| e.info.clone()).collect();
+        infos.sort_by(|a, b| (a.created_at, &a.id).cmp(&(b.created_at, &b.id)));
+        infos
+    }
+
+    /// Revoke the token `id`; false if there was none.
+    pub fn revoke(&self, id: &str) -> bool {
+        self.lock().remove(id).is_some()
+    }
+
+    /// The scope of `token`, if it is live.
+    pub fn verify(&self, token: &str) -> Option<Scope> {
+        let (id, secret) = token.split_once('.')?;
+        let tokens = self.lock();
+        let entry = tokens.get(id)?;
+        (crate::auth::constant_time_eq(secret.as_bytes(), &entry.secret)
+            && !entry.info.expired(unix_now()))
+        .then(|| entry.info.scope.clone())
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, HashMap<String, Entry>> {
+        self.tokens.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+/// The token a request presents, and whether it came on the URL (and so
+/// should be set as the cookie).
+pub fn presented(headers: &HeaderMap, query: Option<&str>) -> Option<(String, bool)> {
+    let bearer = headers
+        .get(header::AUTHORIZATION)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.strip_prefix("Bearer "))
+        .map(|t| t.trim().to_string());
+    let cookie = || {
+        headers
+            .get_all(header::COOKIE)
+            .iter()
+            .filter_map(|v| v.to_str().ok())
+            .flat_map(|v| v.split(';'))
+            .find_map(|pair| {
+                let (name, value) = pair.trim().split_once('=')?;
+                (name == TOKEN_COOKIE).then(|| value.to_string())
+            })
+    };
+    let from_query = || {
+        query?
+            .split('&')
+            .find_map(|pair| pair.strip_prefix("token="))
+            .map(|t| (t.to_string(), true))
+    };
+    bearer
+        .or_else(cookie)
+        .map(|t| (t, false))
+        .or_else(from_query)
+        .filter(|(t, _)| !t.is_empty())
+}
+
+/// Whether a token may reach `path` at all: the frontend, the event stream,
+/// commands and pane images, only its own pane's for a pane-scoped token. A
+/// session-scoped token's image pane is checked by the image handler.
+pub fn reachable(method: &Method, path: &str, scope: &Scope) -> bool {
+    if let Some(image) = path.strip_prefix("/api/images/") {
+        let pane = image.split('/').next().unwrap_or_default();
+        return scope
+            .pane
+            .as_deref()
+            .is_none_or(|allowed| allowed.strip_prefix('%') == Some(pane));
+    }
+    matches!(path, "/events" | "/commands")
+        || (*method == Method::GET && !path.starts_with("/api/"))
+}
+
+/// The `SameSite` attribute for [`TOKEN_COOKIE`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SameSite {
+    Strict,
+    Lax,
+    None,
+}
+
+impl SameSite {
+    /// From [`TOKEN_SAMESITE_ENV`]; `Strict` when unset or unknown.
+    pub fn from_env() -> Self {
+        match std::env::var(TOKEN_SAMESITE_ENV).as_deref().map(str::trim) {
+            Ok("lax") => Self::Lax,
+            Ok("none") => Self::None,
+            Ok("strict") | Ok("") | Err(_) => Self::Strict,
+            Ok(other) => {
+                warn!(
+                    env = TOKEN_SAMESITE_ENV,
+                    value = other,
+                    "expected strict, lax or none"
+                );
+                Self::Strict
+            }
+        }
+    }
+
+    /// The `Set-Cookie` value holding `token`.
+    pub fn cookie(self, token: &str) -> String {
+        let same_site = match self {
+            Self::Strict => "Strict",
+            Self::Lax => "Lax",
+            Self::None => "None; Secure",
+        };
+        format!("{TOKEN_COOKIE}={token}; Path=/; HttpOnly; SameSite={same_site}")
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used, clippy::expect_used)]
+mod tests {
+    use super::*;
+    use axum::http::HeaderValue;
+    use serde_json::json;
+
+    fn cmd(value: serde_json::Value) -> ClientCommand {
+        ClientCommand::decode(value.to_string().as_bytes()).unwrap()
+    }
+
+    #[test]
+    fn tokens_verify_until_revoked_or_expired() {
+        let store = TokenStore::default();
+        let scope = Scope {
+            pane: Some("%3".into()),
+            ..Scope::default()
+        };
+        let (token, info) = store
+            .mint(scope.clone(), Some("embed".into()), None)
+            .unwrap();
+        assert!(token.starts_with(&format!("{}.", info.id)));
+        assert_eq!(store.verify(&token), Some(scope));
+        assert_eq!(store.verify(&format!("{}.wrong", info.id)), None);
+        assert_eq!(store.list(), std::slice::from_ref(&info));
+        assert!(store.revoke(&info.id));
+        assert_eq!(store.verify(&token), None);
+
+        let (expired, _) = store
+            .mint(Scope::default(), None, Some(Duration::ZERO))
+            .unwrap();
+        assert_eq!(store.verify(&expired), None);
+        assert!(store.list().is_empty());
+    }
+
+    #[test]
+    fn reads_need_view_and_everything_else_write() {
+        let history = cmd(json!({ "cmd": "get_command_history", "args": { "paneId": "%3" } }));
+        assert_eq!(Access::needed_for(&history), Access::View);
+        let rerun = cmd(json!({ "cmd": "rerun_command", "args": { "paneId": "%3", "id": 7 } }));
+        assert_eq!(Access::needed_for(&rerun), Access::Write);
+        let new_window = cmd(json!({ "cmd": "new_window_with_command", "args": {} }));
+        assert_eq!(Access::needed_for(&new_window), Access::Write);
+    }
+
+    #[test]
+    fn view_tokens_only_read() {
+        let view = Scope::default();
+        let initial = cmd(json!({ "cmd": "get_initial_state", "args": {} }));
+        assert!(view.permits("main", &initial).is_ok());
+        let keys = cmd(json!({ "cmd": "send_text", "args": { "paneId": "%1", "text": "ls" } }));
+        assert_eq!(
+            view.permits("main", &keys).unwrap_err(),
+            "send_text needs write access"
+        );
+        let widgets = Scope {
+            access: Access::Widgets,
+            session: Some("main".into()),
+            ..Scope::default()
+        };
+        let destroy = cmd(json!({ "cmd": "destroy_widget", "args": { "paneId": "%1" } }));
+        assert!(widgets.permits("main", &destroy).is_ok());
+        assert!(widgets.permits("other", &destroy).is_err());
+        assert!(widgets.permits("main", &keys).is_err());
+        let metric = cmd(json!({
+            "cmd": "create_widget",
+            "args": { "paneId": "%1", "widget": { "kind": "metric", "source": "id" } }
+        }));
+        assert_eq!(
+            widgets.permits("main", &metric).unwrap_err(),
+            "create_widget needs write access"
+        );
+        let table =
+            cmd(json!({ "cmd": "table_query", "args": { "query": { "path": "/etc/passwd" } } }));
+        assert_eq!(
+            widgets.permits("main", &table).unwrap_err(),
+            "table_query needs write access"
+        );
+    }
+
+    #[test]
+    fn pane_tokens_only_touch_their_pane() {
+        let pane = Scope {
+            access: Access::Write,
+            pane: Some("%3".into()),
+            ..Scope::default()
+        };
+        let keys =
+            |p: &str| cmd(json!({ "cmd": "send_text", "args": { "paneId": p, "text": "x" } }));
+        assert!(pane.permits("main", &keys("%3")).is_ok());
+        assert_eq!(
+            pane.permits("main", &keys("%4")).unwrap_err(),
+            "this token is limited to pane %3"
+        );
+        let size = cmd(json!({ "cmd": "set_client_size", "args": { "cols": 80, "rows": 24 } }));
+        assert!(pane.permits("main", &size).is_err());
+        let state = cmd(json!({ "cmd": "get_initial_state", "args": {} }));
+        assert!(pane.permits("main", &state).is_ok());
+        let tmux = cmd(json!({ "cmd": "run_tmux_command", "args": { "command": "kill-server" } }));
+        assert!(pane.permits("main", &tmux).is_err());
+        for read in ["get_exit_summaries", "get_window_mru", "get_stale_panes"] {
+            let read = cmd(json!({ "cmd": read, "args": {} }));
+            assert!(pane.permits("main", &read).is_err());
+        }
+
+        let listed = Scope {
+            commands: Some(vec!["send_text".into()]),
+            ..pane
+        };
+        assert!(listed.permits("main", &keys("%3")).is_ok());
+        assert!(listed.permits("main", &size).is_err());
+    }
+
+    #[test]
+    fn session_tokens_check_the_panes_and_windows_commands_name() {
+        let session = Scope {
+            access: Access::Write,
+            session: Some("main".into()),
+            ..Scope::default()
+        };
+        let keys = cmd(json!({ "cmd": "send_text", "args": { "paneId": "%4", "text": "x" } }));
+        assert_eq!(session.session_targets(&keys), ["%4"]);
+        let drag = cmd(json!({
+            "cmd": "drag_resize",
+            "args": { "windowId": "@2", "startX": 0, "startY": 0, "endX": 1, "endY": 1 }
+        }));
+        assert_eq!(session.session_targets(&drag), ["@2"]);
+        let state = cmd(json!({ "cmd": "get_initial_state", "args": {} }));
+        assert!(session.session_targets(&state).is_empty());
+        // A pane token's pane is checked by `permits` alone.
+        let pane = Scope {
+            pane: Some("%4".into()),
+            ..session
+        };
+        assert!(pane.session_targets(&keys).is_empty());
+    }
+
+    #[test]
+    fn option_reads_stay_in_the_tokens_session_and_pane() {
+        let get = |scope: &str, target: Option<&str>| {
+            cmd(json!({
+                "cmd": "get_option",
+                "args": { "scope": scope, "target": target, "name": "@x" }
+            }))
+        };
+        let pane = Scope {
+            pane: Some("%3".into()),
+            ..Scope::default()
+        };
+        assert!(pane.permits("main", &get("pane", Some("%3"))).is_ok());
+        assert!(pane.permits("main", &get("session", None)).is_ok());
+        assert!(pane.permits("main", &get("server", None)).is_ok());
+        assert!(pane.permits("main", &get("pane", Some("%4"))).is_err());
+        assert!(pane.permits("main", &get("pane", None)).is_err());
+        assert!(pane.permits("main", &get("window", Some("@1"))).is_err());
+        assert!(pane
+            .permits("main", &get("session", Some("other")))
+            .is_err());
+
+        let session = Scope {
+            session: Some("main".into()),
+            ..Scope::default()
+        };
+        assert!(session
+            .permits("main", &get("session", Some("main")))
+            .is_ok());
+        assert!(session
+            .permits("main", &get("session", Some("other")))
+            .is_err());
+        assert!(session
+            .permits("main", &get("window", Some("other:1")))
+            .is_err());
+        let window = get("window", Some("@1"));
+        assert!(session.permits("main", &window).is_ok());
+        assert_eq!(session.session_targets(&window), ["@1"]);
+        assert!(session.session_targets(&get("window", None)).is_empty());
+        assert!(Scope::default().session_targets(&window).is_empty());
+    }
+
+    #[test]
+    fn tokens_come_from_bearer_cookie_or_url() {
+        let mut headers = HeaderMap::new();
+        assert_eq!(
+            presented(&headers, Some("session=a&token=q.1")),
+            Some(("q.1".into(), true))
+        );
+        headers.insert(
+            header::COOKIE,
+            HeaderValue::from_static("theme=dark; tmuxy_token=c.2"),
+        );
+        assert_eq!(
+            presented(&headers, Some("token=q.1")),
+            Some(("c.2".into(), false))
+        );
+        headers.insert(
+            header::AUTHORIZATION,
+            HeaderValue::from_static("Bearer b.3"),
+        );
+        assert_eq!(presented(&headers, None), Some(("b.3".into(), false)));
+
+        let any = Scope::default();
+        assert!(reachable(&Method::POST, "/commands", &any));
+        assert!(reachable(&Method::GET, "/assets/index.js", &any));
+        assert!(!reachable(&Method::GET, "/api/file", &any));
+        assert!(!reachable(&Method::POST, "/api/admin/tokens", &any));
+        assert!(reachable(&Method::GET, "/api/images/4/1", &any));
+
+        let pane = Scope {
+            pane: Some("%3".into()),
+            ..Scope::default()
+        };
+        assert!(reachable(&Method::GET, "/api/images/3/1", &pane));
+        assert!(!reachable(&Method::GET, "/api/images/4/1", &pane));
+        assert!(!reachable(&Method::GET, "/api/images/31/1", &pane));
+    }
+}
@@ -149,11 +149,8 @@ pub fn run_server(args: Vec<String>) {
         Err(e) => e.exit(),
     };
 
-    tokio::runtime::Builder::new_multi_thread()
-        .enable_all()
-        .build()
-        .unwrap()
-        .block_on(server::run(cli.server));
+    // Builds its own runtime, after daemonizing for `start --daemon`.
+    server::run(cli.server);
 }
 
 pub fn print_help() {
@@ -165,14 +162,21 @@ Usage: tmuxy [command] [args...]
 Commands:
   (no args)     Open the desktop GUI application
   gui           Open the desktop GUI application
-  server        Start the web server (--port, --host, --dev)
+  server        Web server: start [--daemon], stop, status, restart
   connect       Add a tmux server (form), or reconnect to one: connect [socket]
   pane          Pane operations (split, kill, select, resize, ...)
   tab           Tab operations (create, kill, select, rename, ...)
   session       Session management (switch, connect)
   widget        Display widgets (image, markdown)
+  view          Open a file in the widget that fits it (csv, markdown, image)
+  md            Print markdown with highlighted code to the terminal
+  metric        Sparkline of a command's numeric output
+  timer         Countdown timer widget that notifies when it's up
+  dashboard     Build a tab of widgets from a dashboard file
+  up            Build a session from a template (windows, splits, commands)
   nav           Navigation (left, right, up, down, next, prev)
   event         Event queue (emit, wait, list)
+  stats         Summary of your command usage (most-run, busiest panes)
   run           Run a raw tmux command safely
 
 Options:
@@ -1,208 +1,224 @@
 use serde_json::Value;
-use std::sync::Arc;
-use tauri::State;
-use tmuxy_core::control_mode::MonitorCommand;
-use tmuxy_core::{executor, Ctx};
+use std::sync::{Arc, Mutex};
+use tauri::{AppHandle, State};
+use tauri_plugin_clipboard_manager::ClipboardExt;
+use tmuxy_core::control_mode::{MonitorCommand, MonitorCommandSender};
+use tmuxy_core::dispatch::{dispatch as dispatch_command, CommandHost, EventSink};
+use tmuxy_core::key_table::{binding_key_name, KeyAction, KeyTables};
+use tmuxy_core::keyboard::KeyEvent;
+use tmuxy_core::pane_search::PaneSearches;
+use tmuxy_core::table::TableCache;
+use tmuxy_core::{clipboard, executor, Ctx};
+use tmuxy_protocol::ClientCommand;
+use tracing::warn;
 
-use crate::monitor::{KeyBindingsState, MonitorState};
+use crate::monitor::{KeyBindingsState, KeyInputState, MonitorState};
 
 use tmuxy_core::session::session_name as get_session;
 
+/// Managed state behind [`CommandHost::tables`].
+#[derive(Default)]
+pub struct TableState(Arc<Mutex<TableCache>>);
+
+/// Managed state behind [`CommandHost::searches`].
+#[derive(Default)]
+pub struct SearchState(Arc<PaneSearches>);
+
+/// Run a client command: everything the frontend sends the SSE server's
+/// `POST /commands` comes here as `{ cmd, args }`, through the same
+/// dispatcher, so the two transports can't drift apart.
 #[tauri::command]
-pub async fn get_initial_state(
-    state: State<'_, MonitorState>,
-    cols: Option<u32>,
-    rows: Option<u32>,
+pub async fn dispatch(
+    app: AppHandle,
+    monitor: State<'_, MonitorState>,
+    keys: State<'_, KeyInputState>,
+    tables: State<'_, TableState>,
+    searches: State<'_, SearchState>,
+    ctx: State<'_, Arc<Ctx>>,
+    cmd: String,
+    args: Option<Value>,
 ) -> Result<Value, String> {
-    // Resize if dimensions provided
-    if let (Some(c), Some(r)) = (cols, rows) {
-        let _ = executor::resize_window(&get_session(), c, r);
-
-        // Cache the viewport size so the FIRST `new-window` after startup sizes
-        // the broken-out window to match the viewport. Otherwise `last_client_size`
-        // stays None until a later resize fires `set_client_size`, and a tab
-        // created before that inherits the half-width post-`splitw` size or the
-        // 200x50 control-mode PTY default — appearing too small until the user
-        // resizes the OS window. The SSE server populates client sizes here too.
-        if let Ok(mut cached) = state.last_client_size.write() {
-            *cached = Some((c, r));
-        }
-    }
+    let args = args.unwrap_or_else(|| serde_json::json!({}));
+    let body = serde_json::json!({ "cmd": cmd, "args": args });
+    let cmd = ClientCommand::decode(body.to_string().as_bytes())
+        .map_err(|e| format!("invalid command payload: {}", e))?;
+    let host = TauriHost {
+        app: &app,
+        monitor: monitor.inner(),
+        keys: keys.inner(),
+        tables: tables.inner(),
+        searches: searches.inner(),
+        ctx: ctx.inner(),
+        session: get_session(),
+    };
+    dispatch_command(&host, cmd).await
+}
 
-    let snapshot = tmuxy_core::capture_window_state_for_session(&get_session())?;
-    serde_json::to_value(snapshot).map_err(|e| e.to_string())
+/// The desktop app as a [`CommandHost`]: one client, whose viewport, prefix
+/// state and focus are the session's.
+struct TauriHost<'a> {
+    app: &'a AppHandle,
+    monitor: &'a MonitorState,
+    keys: &'a KeyInputState,
+    tables: &'a TableState,
+    searches: &'a SearchState,
+    ctx: &'a Arc<Ctx>,
+    session: String,
 }
 
-#[tauri::command]
-pub async fn set_client_size(
-    state: State<'_, MonitorState>,
-    cols: u32,
-    rows: u32,
-) -> Result<(), String> {
-    // Cache the size so the next run_tmux_command("new-window") can size
-    // the broken-out window to match the viewport. Without this the new
-    // window inherits the half-width post-`splitw` size and looks tiny.
-    if let Ok(mut size) = state.last_client_size.write() {
-        *size = Some((cols, rows));
+impl TauriHost<'_> {
+    fn cmd_tx(&self) -> Option<MonitorCommandSender> {
+        self.monitor.cmd_tx.read().ok().and_then(|g| g.clone())
     }
-    executor::resize_window(&get_session(), cols, rows).map_err(Into::into)
 }
 
-#[tauri::command]
-pub async fn split_pane_horizontal() -> Result<(), String> {
-    executor::split_pane_horizontal(&get_session()).map_err(Into::into)
-}
+#[async_trait::async_trait]
+impl CommandHost for TauriHost<'_> {
+    fn session(&self) -> &str {
+        &self.session
+    }
 
-#[tauri::command]
-pub async fn new_window(state: State<'_, MonitorState>) -> Result<(), String> {
-    // Reuse the same CC-routed rewrite as `run_tmux_command("new-window")`
-    // so callers that hit this dedicated command don't slip back into the
-    // external-subprocess path that races with control mode.
-    run_tmux_command(state, "new-window".to_string())
-        .await
-        .map(|_| ())
-}
+    fn ctx(&self) -> &Arc<Ctx> {
+        self.ctx
+    }
 
-#[tauri::command]
-pub async fn run_tmux_command(
-    state: State<'_, MonitorState>,
-    command: String,
-) -> Result<String, String> {
-    // `new-window` (neww) crashes tmux 3.5a control mode when run as an external
-    // subprocess while a control-mode client is attached. Tmuxy's monitor is one
-    // such client. Rewrite to `split-window` + `break-pane -d`, which produces
-    // the same window without the crash. Mirrors the same intercept in the SSE
-    // server (packages/tmuxy-server/src/sse.rs).
-    //
-    // We push the rewrite through the monitor's CC connection — even the
-    // intermediate split-window + break-pane subprocesses race with CC and
-    // can crash the server (surfaced as a TransportError when the Tauri
-    // invoke promise rejects). Going through the same connection that's
-    // already attached avoids the race entirely.
-    let trimmed = command.trim_start();
-    if trimmed.starts_with("new-window") || trimmed.starts_with("neww") {
-        let cmd_tx = state.cmd_tx.read().ok().and_then(|g| g.clone());
-        if let Some(tx) = cmd_tx {
-            let session = get_session();
-            let size = state.last_client_size.read().ok().and_then(|g| *g);
-            // Shared with the SSE server so the rewrite shape and the window
-            // tag can't drift between transports; also quotes the session,
-            // which can contain whitespace when it comes from servers.json.
-            let rewrite = tmuxy_core::executor::new_window_rewrite(&session, size);
-            tx.send(MonitorCommand::RunCommand { command: rewrite })
-                .await
-                .map_err(|e| format!("Monitor channel error: {}", e))?;
-            return Ok(String::new());
-        }
-        // CC connection isn't up yet (very early startup). The external
-        // path is the only option here; if it crashes tmux, the reconnect
-        // loop will recover.
-        executor::new_window(&get_session())?;
-        return Ok(String::new());
+    async fn send(&self, cmd: MonitorCommand) -> Result<(), String> {
+        let Some(tx) = self.cmd_tx() else {
+            return Err("No monitor connection available".to_string());
+        };
+        tx.send(cmd)
+            .await
+            .map_err(|e| format!("Monitor channel error: {}", e))
+    }
+
+    fn tables(&self) -> Arc<Mutex<TableCache>> {
+        self.tables.0.clone()
+    }
+
+    fn searches(&self) -> Arc<PaneSearches> {
+        self.searches.0.clone()
+    }
+
+    /// The app window is the only client.
+    fn client_id(&self) -> Result<u64, String> {
+        Ok(0)
+    }
+
+    async fn reply_sink(&self) -> Result<EventSink, String> {
+        let app = self.app.clone();
+        Ok(Arc::new(move |event| {
+            crate::monitor::emit_event(&app, &event)
+        }))
     }
 
-    // Multi-command batches (newline-joined) — e.g. the multiline-paste sequence
-    // the keyboard actor builds (`send-keys -l 'line1'` / `send-keys Enter` / …)
-    // — MUST go through the control-mode connection. tmux control mode reads each
-    // line as a separate command, executing the batch atomically and in order. An
-    // external `sh -c "tmux <batch>"` subprocess can't: only the first line gets a
-    // `tmux` prefix, so the remaining `send-keys` lines are mangled into the shell
-    // (the literal "send-keys …" text the user sees pasted on every linebreak).
-    // The SSE server already routes these through `MonitorCommand::RunCommand`.
-    if command.contains('\n') {
-        let cmd_tx = state.cmd_tx.read().ok().and_then(|g| g.clone());
-        if let Some(tx) = cmd_tx {
-            tx.send(MonitorCommand::RunCommand { command })
+    /// Cache the size so a `new-window` sizes the broken-out window to the
+    /// viewport, then resize through control mode. The first
+    /// `get_initial_state` can come before control mode is up, when only
+    /// the executor can resize.
+    async fn set_client_size(&self, cols: u32, rows: u32) {
+        if let Ok(mut size) = self.monitor.last_client_size.write() {
+            *size = Some((cols, rows));
+        }
+        if let Some(tx) = self.cmd_tx() {
+            if tx
+                .send(MonitorCommand::ResizeWindow { cols, rows })
                 .await
-                .map_err(|e| format!("Monitor channel error: {}", e))?;
-            return Ok(String::new());
+                .is_ok()
+            {
+                return;
+            }
+        }
+        if let Err(e) = executor::resize_window(&self.session, cols, rows) {
+            warn!(error = %e, cols, rows, "failed to resize window");
         }
-        // CC connection isn't up yet — fall through to the external path, which
-        // at least lands the first line rather than dropping the paste entirely.
     }
 
-    executor::run_tmux_command_for_session(&get_session(), &command).map_err(Into::into)
-}
+    async fn viewport(&self) -> Option<(u32, u32)> {
+        self.monitor.last_client_size.read().ok().and_then(|g| *g)
+    }
 
-/// Fetch a range of scrollback cells for copy mode.
-///
-/// Matches the SSE server's `get_scrollback_cells` command shape so the
-/// frontend can use the same FETCH_SCROLLBACK_CELLS path under Tauri.
-/// Without this command, copy mode in the Tauri build silently fails to
-/// load anything beyond the already-visible pane content.
-#[tauri::command]
-pub async fn get_scrollback_cells(
-    ctx: State<'_, Arc<Ctx>>,
-    pane_id: String,
-    start: i64,
-    end: i64,
-) -> Result<Value, String> {
-    let width_output = ctx
-        .tmux_call(
-            vec![
-                "display-message".into(),
-                "-t".into(),
-                pane_id.clone(),
-                "-p".into(),
-                "#{pane_width}".into(),
-            ],
-            "get_pane_width",
-        )
-        .await
-        .map_err(|e| format!("Failed to get pane width: {}", e))?;
-    let width: u32 = width_output.trim().parse().unwrap_or(80);
-
-    let history_output = ctx
-        .tmux_call(
-            vec![
-                "display-message".into(),
-                "-t".into(),
-                pane_id.clone(),
-                "-p".into(),
-                "#{history_size}".into(),
-            ],
-            "get_history_size",
-        )
-        .await
-        .map_err(|e| format!("Failed to get history size: {}", e))?;
-    let history_size: u32 = history_output.trim().parse().unwrap_or(0);
-
-    let raw = executor::capture_pane_range(&pane_id, start, end)
-        .map_err(|e| format!("Failed to capture pane range: {}", e))?;
-
-    let cells = tmuxy_core::parse_scrollback_to_cells(&raw, width);
+    async fn key_tables(&self) -> Arc<KeyTables> {
+        key_tables(self.keys)
+    }
 
-    Ok(serde_json::json!({
-        "cells": cells,
-        "historySize": history_size,
-        "start": start,
-        "end": end,
-        "width": width,
-    }))
-}
+    async fn resolve_key(&self, key: &KeyEvent) -> Result<(KeyAction, bool), String> {
+        let tables = key_tables(self.keys);
+        let now = self.ctx.clock.now();
+        let mut prefix_state = self.keys.prefix.lock().map_err(|e| e.to_string())?;
+        let action = match binding_key_name(key) {
+            Some(name) => prefix_state.handle(&tables, &name, key.repeat, now),
+            None => KeyAction::Send,
+        };
+        Ok((action, prefix_state.in_prefix(now)))
+    }
 
-#[tauri::command]
-pub async fn get_theme_settings(ctx: State<'_, Arc<Ctx>>) -> Result<Value, String> {
-    Ok(tmuxy_core::theme::get_theme_settings(&ctx).await)
+    async fn keybindings_changed(&self) {
+        crate::monitor::emit_keybindings(self.app);
+    }
+
+    async fn set_focus(&self, focused: bool) -> Result<(), String> {
+        self.send(MonitorCommand::SetClientFocus { focused }).await
+    }
+
+    async fn set_frame_budget(&self, _fps: u32) -> Result<(), String> {
+        Err("The desktop app has no event stream to budget".to_string())
+    }
+
+    fn workspace_root(&self) -> std::path::PathBuf {
+        tmuxy_server::state::find_workspace_root()
+    }
+
+    fn log_level(&self) -> Option<String> {
+        tmuxy_server::logging::current_log_level()
+    }
+
+    fn set_log_level(&self, directives: &str) -> Result<String, String> {
+        tmuxy_server::logging::set_log_level(directives)
+    }
 }
 
-#[tauri::command]
-pub async fn set_theme(
-    ctx: State<'_, Arc<Ctx>>,
-    name: String,
-    mode: Option<String>,
-) -> Result<(), String> {
-    tmuxy_core::theme::set_theme(&ctx, &name, mode.as_deref()).await
+/// The user's key tables, loaded with `list-keys` on first use.
+fn key_tables(keys: &KeyInputState) -> Arc<KeyTables> {
+    if let Some(tables) = keys.tables.read().ok().and_then(|g| g.clone()) {
+        return tables;
+    }
+    let tables = Arc::new(tmuxy_core::get_key_tables());
+    if let Ok(mut guard) = keys.tables.write() {
+        *guard = Some(tables.clone());
+    }
+    tables
 }
 
 #[tauri::command]
-pub async fn set_theme_mode(ctx: State<'_, Arc<Ctx>>, mode: String) -> Result<(), String> {
-    tmuxy_core::theme::set_theme_mode(&ctx, &mode).await
+pub async fn split_pane_horizontal() -> Result<(), String> {
+    executor::split_pane_horizontal(&get_session()).map_err(Into::into)
 }
 
 #[tauri::command]
-pub async fn get_themes_list() -> Result<Value, String> {
-    Ok(tmuxy_core::theme::get_themes_list())
+pub async fn new_window(
+    app: AppHandle,
+    monitor: State<'_, MonitorState>,
+    keys: State<'_, KeyInputState>,
+    tables: State<'_, TableState>,
+    searches: State<'_, SearchState>,
+    ctx: State<'_, Arc<Ctx>>,
+) -> Result<(), String> {
+    // The same split+break rewrite as `run_tmux_command("new-window")`, so
+    // this command can't slip back into an external `new-window`, which
+    // crashes tmux 3.5a with control mode attached.
+    let args = Some(serde_json::json!({ "command": "new-window" }));
+    dispatch(
+        app,
+        monitor,
+        keys,
+        tables,
+        searches,
+        ctx,
+        "run_tmux_command".to_string(),
+        args,
+    )
+    .await
+    .map(|_| ())
 }
 
 #[tauri::command]
@@ -224,6 +240,49 @@ pub fn get_keybindings_snapshot(state: State<'_, KeyBindingsState>) -> Option<Va
     state.0.read().ok().and_then(|guard| guard.clone())
 }
 
+/// Copy to the OS clipboard (arboard, through tauri-plugin-clipboard-manager):
+/// `text` when given, else tmux paste buffer `buffer`, else the most recent
+/// paste buffer.
+#[tauri::command]
+pub async fn clipboard_copy(
+    app: AppHandle,
+    text: Option<String>,
+    buffer: Option<String>,
+) -> Result<(), String> {
+    let text = match text {
+        Some(text) => text,
+        None => clipboard::read_buffer(buffer.as_deref())?,
+    };
+    app.clipboard().write_text(text).map_err(|e| e.to_string())
+}
+
+/// Load the OS clipboard into the `tmuxy-clipboard` paste buffer and, with
+/// `pane_id`, paste it there. Returns the text. Goes through control mode,
+/// so it fails before the monitor connects.
+#[tauri::command]
+pub async fn clipboard_paste(
+    app: AppHandle,
+    monitor: State<'_, MonitorState>,
+    pane_id: Option<String>,
+) -> Result<String, String> {
+    let text = app.clipboard().read_text().map_err(|e| e.to_string())?;
+    let mut command = clipboard::set_buffer_command(&text);
+    if let Some(pane_id) = pane_id {
+        command.push_str(" ; ");
+        command.push_str(&clipboard::paste_buffer_command(&pane_id));
+    }
+    let Some(tx) = monitor.cmd_tx.read().ok().and_then(|g| g.clone()) else {
+        return Err("No monitor connection available".to_string());
+    };
+    tx.send(MonitorCommand::RunCommand {
+        command,
+        reply: None,
+    })
+    .await
+    .map_err(|e| format!("Monitor channel error: {}", e))?;
+    Ok(text)
+}
+
 /// List the saved servers (localhost plus any added via `tmuxy connect`), read
 /// fresh from `~/.config/tmuxy/servers.json`, along with the id of the server
 /// the app is currently attached to. Powers the sidebar server picker — a
@@ -1,6 +1,7 @@
 use tauri::menu::{MenuBuilder, MenuItem, SubmenuBuilder};
 use tauri::Manager;
 use tmuxy_core::{executor, session};
+use tracing::{info, warn};
 
 use crate::commands;
 use crate::monitor;
@@ -118,7 +119,7 @@ fn parse_vibrancy(value: &str) -> Option<tauri::window::Effect> {
         "blur" => Some(Effect::Blur),
         "tabbed" => Some(Effect::Tabbed),
         _ => {
-            eprintln!("Unknown vibrancy type: {}", value);
+            warn!(%value, "unknown vibrancy type");
             None
         }
     }
@@ -152,9 +153,9 @@ fn apply_window_effects(window: &tauri::WebviewWindow) {
             .state(tauri::window::EffectState::Active)
             .build();
         if let Err(e) = window.set_effects(Some(effects)) {
-            eprintln!("Failed to set vibrancy effect: {}", e);
+            warn!(error = %e, "failed to set vibrancy effect");
         } else {
-            println!("Applied vibrancy: {}", name);
+            info!(%name, "applied vibrancy");
         }
     }
 
@@ -644,7 +645,7 @@ fn handle_menu_event(app_handle: &tauri::AppHandle, event: tauri::menu::MenuEven
             // `id` is a fixed literal from the menu definition (not user input);
             // `{id:?}` emits it as a quoted JS string literal.
             if let Err(e) = window.eval(format!("window.tmuxyMenuAction?.({id:?})")) {
-                eprintln!("[menu] Failed to dispatch '{}': {}", id, e);
+                warn!(%id, error = %e, "failed to dispatch menu action");
             }
         }
         return;
@@ -922,6 +923,8 @@ fn show_status_message(app: &tauri::AppHandle, message: &str) {
 
 /// Start the Tauri GUI application.
 pub fn run() {
+    tmuxy_server::init_logging();
+
     #[allow(unused_mut)]
     let mut builder = tauri::Builder::default();
 
@@ -947,11 +950,16 @@ pub fn run() {
         // launched from Finder can grab ~/tmuxy-debug.log without a terminal.
         .plugin(tauri_plugin_clipboard_manager::init())
         .manage(monitor::KeyBindingsState::default())
+        .manage(monitor::KeyInputState::default())
         .manage(monitor::MonitorState::default())
         // Shared execution context — handed to TmuxMonitor on connect AND used
         // by async Tauri commands for retried+timed-out tmux dispatch via the
         // Tower stack. Mirrors AppState::ctx on the server side.
         .manage(tmuxy_core::Ctx::live())
+        // Row indexes behind `table_query`. Mirrors AppState::tables.
+        .manage(commands::TableState::default())
+        // Pane searches in progress. Mirrors AppState::searches.
+        .manage(commands::SearchState::default())
         .setup(|app| {
             // Log environment for debugging Finder vs CLI launch differences
             tmuxy_core::debug_log::log("=== tmuxy starting ===");
@@ -1040,14 +1048,13 @@ pub fn run() {
             let tmux_bin = session::tmux_path();
             let session_name = tmuxy_core::session::session_name();
             tmuxy_core::debug_log::log(&format!("tmux binary: {}", tmux_bin));
-            eprintln!("[tmuxy] tmux binary: {}", tmux_bin);
-            eprintln!("[tmuxy] session name: {}", session_name);
+            info!(tmux = %tmux_bin, session = %session_name, "starting");
 
             // Quick check that tmux is actually runnable
             match std::process::Command::new(tmux_bin).arg("-V").output() {
                 Ok(output) if output.status.success() => {
                     let version = String::from_utf8_lossy(&output.stdout);
-                    eprintln!("[tmuxy] {}", version.trim());
+                    info!(version = %version.trim(), "tmux is runnable");
                 }
                 Ok(output) => {
                     let stderr = String::from_utf8_lossy(&output.stderr);
@@ -1074,7 +1081,7 @@ pub fn run() {
                     Ok(menu) => {
                         let _ = app.set_menu(menu);
                     }
-                    Err(e) => eprintln!("Failed to build app menu: {}", e),
+                    Err(e) => warn!(error = %e, "failed to build app menu"),
                 }
                 app.on_menu_event(handle_menu_event);
             }
@@ -1117,29 +1124,21 @@ pub fn run() {
             Ok(())
         })
         .invoke_handler(tauri::generate_handler![
-            // Core commands
-            commands::get_initial_state,
-            commands::set_client_size,
+            // Every client command, through the dispatcher the SSE server's
+            // `/commands` runs too
+            commands::dispatch,
             // Pane/window operations exercised by the Tauri webdriver test
-            // (the production UI drives these through run_tmux_command).
             commands::split_pane_horizontal,
             commands::new_window,
-            // General
-            commands::run_tmux_command,
             commands::get_key_bindings,
             commands::get_keybindings_snapshot,
-            // Copy mode + themes (mirrors the SSE server's invoke surface so
-            // the React frontend's INVOKE / FETCH_SCROLLBACK_CELLS paths work
-            // identically under Tauri)
-            commands::get_scrollback_cells,
-            commands::get_theme_settings,
-            commands::set_theme,
-            commands::set_theme_mode,
-            commands::get_themes_list,
             // Server picker (desktop-only): list saved tmux servers and
             // live-reconnect to one (localhost socket switch or remote SSH).
             commands::list_servers,
             commands::connect_server,
+            // OS clipboard <-> tmux paste buffers (desktop-only).
+            commands::clipboard_copy,
+            commands::clipboard_paste,
         ])
         .run(tauri::generate_context!())
         .expect("error while running tauri application");
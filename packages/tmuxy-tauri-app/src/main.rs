@@ -21,8 +21,20 @@ fn main() {
         // packaged .app needs no separate `tmuxy-connect` binary on PATH.
         Some("connect") if args.len() == 1 => cli::run_connect_form(),
 
+        // "stats" → the server's usage summary, "md" → its markdown
+        // renderer, and "up" → its template builder, in-process for the
+        // same reason
+        Some("stats" | "md" | "up")
+            if !matches!(args.get(1).map(String::as_str), Some("--help" | "-h")) =>
+        {
+            cli::run_server([vec!["server".to_string()], args].concat());
+        }
+
         // Known CLI nouns → exec the shell dispatcher
-        Some("pane" | "tab" | "session" | "widget" | "nav" | "event" | "run" | "connect") => {
+        Some(
+            "pane" | "tab" | "session" | "widget" | "view" | "metric" | "timer" | "dashboard"
+            | "nav" | "event" | "run" | "connect" | "stats" | "md" | "up",
+        ) => {
             cli::run_cli(args);
         }
 
@@ -1,11 +1,12 @@
-use std::sync::{Arc, RwLock};
+use std::sync::{Arc, Mutex, RwLock};
 use std::time::Duration;
 use tauri::{AppHandle, Emitter, Manager};
 use tmuxy_core::control_mode::{
-    LogKind, LogSink, MonitorCommand, MonitorCommandSender, MonitorConfig, StateEmitter,
-    TmuxMonitor,
+    LogKind, LogSink, MonitorCommand, MonitorCommandSender, MonitorConfig, ServerEvent,
+    StateEmitter, TmuxMonitor,
 };
-use tmuxy_core::StateUpdate;
+use tmuxy_core::key_table::{KeyTables, PrefixState};
+use tracing::warn;
 
 use tmuxy_core::session::session_name as get_session;
 
@@ -41,6 +42,15 @@ impl Default for KeyBindingsState {
     }
 }
 
+/// Key tables and prefix state for `key_input`. The desktop app is a single
+/// client, so it has one `PrefixState`. The tables are loaded on first use
+/// and dropped whenever keybindings are re-emitted.
+#[derive(Default)]
+pub struct KeyInputState {
+    pub tables: RwLock<Option<Arc<KeyTables>>>,
+    pub prefix: Mutex<PrefixState>,
+}
+
 /// Live handle to the running control-mode monitor.
 ///
 /// `cmd_tx` is the channel for issuing tmux mutations through the existing
@@ -50,7 +60,7 @@ impl Default for KeyBindingsState {
 /// app now does the same.
 ///
 /// `last_client_size` is the most recent viewport size the frontend reported.
-/// `run_tmux_command` uses it when rewriting `new-window` so the broken-out
+/// The `new-window` rewrite is sized to it (see `commands.rs`) so the broken-out
 /// window matches the visible viewport instead of inheriting the half-width
 /// post-`splitw` size or the 200x50 control-mode PTY default.
 #[derive(Clone, Default)]
@@ -104,37 +114,33 @@ impl LogSink for TauriEmitter {
         };
         tmuxy_core::debug_log::log(&format!("[monitor {}] {}", label, message));
 
-        let payload = serde_json::json!({ "kind": kind, "message": message });
-        if let Err(e) = self.app.emit("tmux-log", &payload) {
-            eprintln!("Failed to emit log: {}", e);
-        }
+        emit_event(&self.app, &ServerEvent::Log { kind, message });
     }
 }
 
 impl StateEmitter for TauriEmitter {
-    fn emit_state(&self, update: StateUpdate) {
-        if let Err(e) = self.app.emit("tmux-state-update", &update) {
-            eprintln!("Failed to emit state: {}", e);
-        }
-    }
-
-    fn emit_error(&self, error: String) {
-        tmuxy_core::debug_log::log(&format!("[monitor ERR] {}", error));
-        if let Err(e) = self.app.emit("tmux-error", &error) {
-            eprintln!("Failed to emit error: {}", e);
-        }
-    }
-
-    /// Forward an OSC 52 clipboard request to the frontend so it can write the
-    /// payload via the WebView's navigator.clipboard. We could also use the
-    /// tauri-plugin-clipboard-manager directly here, but doing it in the WebView
-    /// keeps focus/transient activation context attached to the renderer, which
-    /// is what some platforms require for clipboard access.
-    fn write_clipboard(&self, pane_id: &str, text: String) {
-        let payload = serde_json::json!({ "pane_id": pane_id, "text": text });
-        if let Err(e) = self.app.emit("tmux-clipboard", &payload) {
-            eprintln!("Failed to emit clipboard: {}", e);
+    /// Emit each event as `tmux-<name>`. OSC 52 clipboard requests (and
+    /// copy-mode copies, which arrive the same way) go to the frontend, which
+    /// writes them via the WebView's navigator.clipboard: doing it in the
+    /// WebView keeps focus/transient activation context attached to the
+    /// renderer, which is what some platforms require for clipboard access.
+    /// That still fails for copies made while the window is in the
+    /// background, so with `@tmuxy-clipboard-sync on` they are also written
+    /// natively.
+    fn emit(&self, event: ServerEvent) {
+        match &event {
+            ServerEvent::Error { message } => {
+                tmuxy_core::debug_log::log(&format!("[monitor ERR] {}", message));
+            }
+            ServerEvent::Clipboard { text, .. } if tmuxy_core::clipboard::sync_enabled() => {
+                use tauri_plugin_clipboard_manager::ClipboardExt;
+                if let Err(e) = self.app.clipboard().write_text(text.clone()) {
+                    warn!(error = %e, "failed to sync clipboard");
+                }
+            }
+            _ => {}
         }
+        emit_event(&self.app, &event);
     }
 
     /// Re-emit keybindings after sync_initial_state has source-file'd
@@ -149,6 +155,21 @@ impl StateEmitter for TauriEmitter {
     }
 }
 
+/// Emit `event` to the frontend as the Tauri event `tmux-<name>`, carrying
+/// its payload (the `data` of the SSE envelope).
+pub fn emit_event(app: &AppHandle, event: &ServerEvent) {
+    let payload = match event.payload() {
+        Ok(payload) => payload,
+        Err(e) => {
+            warn!(event = event.name(), error = %e, "failed to serialize event");
+            return;
+        }
+    };
+    if let Err(e) = app.emit(&format!("tmux-{}", event.name()), payload) {
+        warn!(event = event.name(), error = %e, "failed to emit event");
+    }
+}
+
 /// Start control mode monitoring for tmux state changes
 pub async fn start_monitoring(app: AppHandle, monitor_state: MonitorState) {
     let emitter = Arc::new(TauriEmitter::new(app.clone()));
@@ -164,13 +185,31 @@ pub async fn start_monitoring(app: AppHandle, monitor_state: MonitorState) {
     let mut config = MonitorConfig {
         session,
         sync_interval: Duration::from_millis(500),
+        heartbeat_interval: tmuxy_core::control_mode::heartbeat_interval_from_env(),
+        sync_fields: tmuxy_core::control_mode::sync_fields_from_env(),
         create_session: true,
         // Adaptive throttling: emit immediately for low-frequency events (typing),
         // throttle at 16ms (~60fps) when high-frequency output detected
         throttle_interval: Duration::from_millis(16),
         throttle_threshold: 20,
         rate_window: Duration::from_millis(100),
+        output_debounce: Duration::from_millis(16),
+        max_latency: Duration::from_millis(50),
         working_dir,
+        stale_pane_notice: tmuxy_core::control_mode::stale_pane_notice_from_env(),
+        bell_policy: tmuxy_core::control_mode::bell_policy_from_env(),
+        terminal_backend: tmuxy_core::control_mode::terminal_backend_from_env(),
+        scrollback_rows: tmuxy_core::control_mode::scrollback_rows_from_env(),
+        accessible_output: tmuxy_core::control_mode::accessible_output_from_env(),
+        dnd_schedule: tmuxy_core::control_mode::dnd_schedule_from_env(),
+        terminal_theme: tmuxy_core::control_mode::terminal_theme_from_env(),
+        usage_db: tmuxy_core::usage::usage_db_from_env(),
+        pause_after: tmuxy_core::control_mode::pause_after_from_env(),
+        auto_resume: tmuxy_core::control_mode::auto_resume_from_env(),
+        output_budget: tmuxy_core::control_mode::output_budget_from_env(),
+        // The window is always a client.
+        hibernate_after: None,
+        liveness_timeout: tmuxy_core::control_mode::liveness_timeout_from_env(),
     };
 
     // Reconnect with exponential backoff, bounded by MAX_CONSECUTIVE_FAILURES.
@@ -302,6 +341,18 @@ pub async fn start_monitoring(app: AppHandle, monitor_state: MonitorState) {
                     continue;
                 }
 
+                // The loop below reconnects with `create_session`, so a
+                // session that went away comes back.
+                if let Some(reason) = monitor.lost() {
+                    emit_event(
+                        &app,
+                        &ServerEvent::SessionLost {
+                            reason: reason.to_string(),
+                            recreating: true,
+                        },
+                    );
+                }
+
                 tmuxy_core::debug_log::log(&format!(
                     "[monitor] run() returned after {:?} (failures so far: {})",
                     lived, consecutive_failures
@@ -455,34 +506,31 @@ fn read_global_env(name: &str) -> Option<String> {
 /// The UI should treat this as a non-recoverable state — the monitor loop has
 /// stopped and no further state updates will arrive.
 fn emit_fatal(app: &AppHandle, message: &str) {
-    let payload = serde_json::json!({ "message": message });
-    if let Err(e) = app.emit("tmux-fatal", &payload) {
-        eprintln!("Failed to emit fatal: {}", e);
-    }
+    emit_event(
+        app,
+        &ServerEvent::Fatal {
+            message: message.to_string(),
+        },
+    );
 }
 
 /// Emit keybindings to the frontend after a successful connection.
 ///
 /// Also stores the payload in `KeyBindingsState` so a frontend that connects
 /// after the emit can still retrieve them via `get_keybindings_snapshot`.
-fn emit_keybindings(app: &AppHandle) {
-    let prefix_key = tmuxy_core::get_prefix_key().unwrap_or_else(|_| "C-b".into());
-    let prefix_bindings = tmuxy_core::get_prefix_bindings().unwrap_or_default();
-    let root_bindings = tmuxy_core::get_root_bindings().unwrap_or_default();
-
-    let payload = serde_json::json!({
-        "prefix_key": prefix_key,
-        "prefix_bindings": prefix_bindings,
-        "root_bindings": root_bindings,
-    });
+pub fn emit_keybindings(app: &AppHandle) {
+    let event = ServerEvent::KeyBindings(tmuxy_core::get_key_bindings());
 
     if let Some(state) = app.try_state::<KeyBindingsState>() {
         if let Ok(mut guard) = state.0.write() {
-            *guard = Some(payload.clone());
+            *guard = event.payload().ok();
         }
     }
-
-    if let Err(e) = app.emit("tmux-keybindings", &payload) {
-        eprintln!("Failed to emit keybindings: {}", e);
+    if let Some(state) = app.try_state::<KeyInputState>() {
+        if let Ok(mut guard) = state.tables.write() {
+            *guard = None;
+        }
     }
+
+    emit_event(app, &event);
 }
@@ -23,6 +23,7 @@ import {
   selectFatalError,
   selectLog,
   selectContainerSize,
+  selectStatusPosition,
 } from './machines/AppContext';
 import type { LogEntry } from './machines/types';
 import { initDebugHelpers } from './utils/debug';
@@ -117,6 +118,7 @@ function App({ renderTabline }: { renderTabline?: RenderTabline } = {}) {
   const fatalError = useAppSelector(selectFatalError);
   const log = useAppSelector(selectLog);
   const containerSize = useAppSelector(selectContainerSize);
+  const statusOnTop = useAppSelector(selectStatusPosition) === 'top';
   const isConnecting = useAppState('connecting');
   const send = useAppSend();
   const { requireFocus } = useAppConfig();
@@ -153,6 +155,18 @@ function App({ renderTabline }: { renderTabline?: RenderTabline } = {}) {
     return () => document.removeEventListener('mousedown', handleMouseDown, true);
   }, [requireFocus, send]);
 
+  // Window focus drives focus reports to panes that asked for them (CSI ? 1004 h)
+  useEffect(() => {
+    const handleFocus = () => send({ type: 'WINDOW_FOCUS_CHANGE', focused: true });
+    const handleBlur = () => send({ type: 'WINDOW_FOCUS_CHANGE', focused: false });
+    window.addEventListener('focus', handleFocus);
+    window.addEventListener('blur', handleBlur);
+    return () => {
+      window.removeEventListener('focus', handleFocus);
+      window.removeEventListener('blur', handleBlur);
+    };
+  }, [send]);
+
   // Ready when connected, have panes, AND container is measured
   const isReady = !isConnecting && panes.length > 0 && containerSize.width > 0;
 
@@ -172,6 +186,9 @@ function App({ renderTabline }: { renderTabline?: RenderTabline } = {}) {
   return (
     <div ref={appContainerRef} className="app-container">
       <StatusBar renderTabline={renderTabline} />
+      {/* tmux's status-position: the bar (and any extra status rows) goes
+          above or below the panes; the pane container measures what's left. */}
+      {statusOnTop && <TmuxStatusBar />}
       <div className="app-body">
         {/* Left sidebar: a fixed-width, full-height column when open. As a real
             flex sibling it shrinks the pane container, whose ResizeObserver then
@@ -195,7 +212,7 @@ function App({ renderTabline }: { renderTabline?: RenderTabline } = {}) {
           )}
         </div>
       </div>
-      <TmuxStatusBar />
+      {!statusOnTop && <TmuxStatusBar />}
       {/* Dev-only latency overlay; mounted only when enabled via ?perf /
           localStorage so it and its store subscription cost nothing otherwise. */}
       {latencyTracker.isEnabled() && <PerfHud />}
@@ -1,7 +1,8 @@
 /**
  * FloatPane - Centered floating pane or edge-docked drawer
  *
- * Regular float: centered on screen with backdrop
+ * Regular float: centered on screen with backdrop, or wherever float_move
+ * put it (@tmuxy-float-x/y)
  * Drawer (--left/--right/--top/--bottom): slides from edge, full height or width
  * Backdrop: --bg dim (default), blur, or none
  * Header: hidden with --hide-header
@@ -13,6 +14,7 @@ import React, { useCallback } from 'react';
 import { Modal } from './Modal';
 import { Terminal } from './Terminal';
 import { PaneHeader } from './PaneHeader';
+import { paneCursor, terminalColorStyle } from './terminalShared';
 import {
   useAppSend,
   useAppSelector,
@@ -42,6 +44,7 @@ function FloatPaneInner({ floatState, zIndex = 1001 }: FloatPaneProps) {
     ctx.panes.find((p: TmuxPane) => p.tmuxId === floatState.paneId),
   );
   const focusedFloatPaneId = useAppSelector((ctx) => ctx.focusedFloatPaneId);
+  const terminalTheme = useAppSelector((ctx) => ctx.terminalTheme);
   const isFocused = focusedFloatPaneId === floatState.paneId;
   const { charHeight } = useAppSelector(selectCharSize);
   const { width: containerWidth, height: containerHeight } = useAppSelector(selectContainerSize);
@@ -103,7 +106,12 @@ function FloatPaneInner({ floatState, zIndex = 1001 }: FloatPaneProps) {
       >
         <div
           className="float-content"
-          style={{ width: floatWidth, height: terminalHeight }}
+          style={{
+            width: floatWidth,
+            height: terminalHeight,
+            cursor: paneCursor(pane),
+            ...terminalColorStyle(terminalTheme, pane.colors),
+          }}
           onClick={handleClick}
         >
           <Terminal
@@ -126,12 +134,12 @@ function FloatPaneInner({ floatState, zIndex = 1001 }: FloatPaneProps) {
     );
   }
 
-  // Regular centered float
+  // Regular float: centered unless it has been placed
   const terminalRows = Math.floor(floatState.height / charHeight);
   const floatWidth = floatState.width;
   const floatHeight = floatState.height + headerHeight;
-  const left = Math.max(0, (containerWidth - floatWidth) / 2);
-  const top = Math.max(0, (containerHeight - floatHeight) / 2);
+  const left = floatState.x ?? Math.max(0, (containerWidth - floatWidth) / 2);
+  const top = floatState.y ?? Math.max(0, (containerHeight - floatHeight) / 2);
 
   return (
     <Modal
@@ -155,7 +163,14 @@ function FloatPaneInner({ floatState, zIndex = 1001 }: FloatPaneProps) {
         {!hideHeader && (
           <PaneHeader paneId={floatState.paneId} isFloat onFloatClose={handleClose} />
         )}
-        <div className="float-content" style={{ height: floatState.height }}>
+        <div
+          className="float-content"
+          style={{
+            height: floatState.height,
+            cursor: paneCursor(pane),
+            ...terminalColorStyle(terminalTheme, pane.colors),
+          }}
+        >
           <Terminal
             content={pane.content}
             cursorX={pane.cursorX}
@@ -37,7 +37,7 @@ export const Pane = memo(function Pane({ paneId }: PaneProps) {
   // Pane may not exist during transitions
   if (!pane) return null;
 
-  let widgetInfo = detectWidget(pane.content);
+  let widgetInfo = detectWidget(pane.content, pane.widget);
   if (pane.content.length === 0) {
     widgetInfo = lastWidgetInfoRef.current;
   } else {
@@ -7,6 +7,7 @@
 import { ControlledMenu } from '@szhsin/react-menu';
 import '@szhsin/react-menu/dist/index.css';
 import {
+  useAdapterInvoke,
   useAppSend,
   useAppSelector,
   selectKeyBindings,
@@ -14,6 +15,8 @@ import {
 } from '../machines/AppContext';
 import { executeMenuAction } from './menus/menuActions';
 import { PaneMenuItems } from './menus/PaneMenuItems';
+import { EXPORT_ACTIONS, runExportAction } from '../utils/paneExport';
+import { COPY_TEXT_ACTIONS, runCopyTextAction } from '../utils/paneText';
 import './menus/AppMenu.css';
 
 interface PaneContextMenuProps {
@@ -25,6 +28,7 @@ interface PaneContextMenuProps {
 
 export function PaneContextMenu({ paneId, x, y, onClose }: PaneContextMenuProps) {
   const send = useAppSend();
+  const invoke = useAdapterInvoke();
   const keybindings = useAppSelector(selectKeyBindings);
   const visiblePanes = useAppSelector(selectVisiblePanes);
   const isSinglePane = visiblePanes.length <= 1;
@@ -35,6 +39,10 @@ export function PaneContextMenu({ paneId, x, y, onClose }: PaneContextMenuProps)
       // Don't FOCUS_PANE first — that would switch to a hidden group window
       // and confuse the close script's visibility logic.
       send({ type: 'CLOSE_PANE', paneId });
+    } else if (EXPORT_ACTIONS[actionId]) {
+      runExportAction(invoke, paneId, EXPORT_ACTIONS[actionId]);
+    } else if (COPY_TEXT_ACTIONS[actionId]) {
+      runCopyTextAction(invoke, paneId, COPY_TEXT_ACTIONS[actionId]);
     } else {
       send({ type: 'FOCUS_PANE', paneId });
       executeMenuAction(send, actionId);
@@ -52,6 +52,11 @@ const PaneTab = memo(function PaneTab({
     >
       {icon && <span className="pane-tab-icon pane-tab-icon-static">{icon}</span>}
       <span className="pane-tab-title">{text}</span>
+      {pane.sandbox && (
+        <span className="pane-tab-sandbox" title={`Sandboxed: ${pane.sandbox}`}>
+          {pane.sandbox}
+        </span>
+      )}
     </div>
   );
 });
@@ -1,14 +1,16 @@
 /**
  * SelectionContextMenu - Right-click context menu for selected text.
  *
- * Appears on right-click with Copy, Search Google, and Ask ChatGPT actions.
+ * Appears on right-click with Copy, Copy as HTML, Search Google, and Ask
+ * ChatGPT actions.
  * Uses @szhsin/react-menu ControlledMenu (same pattern as PaneContextMenu).
  */
 
 import { ControlledMenu, MenuItem, MenuDivider } from '@szhsin/react-menu';
 import '@szhsin/react-menu/dist/index.css';
-import { useAppSend } from '../machines/AppContext';
+import { useAdapterInvoke, useAppSend } from '../machines/AppContext';
 import { escapeLiteralText } from '../tmux/keyBatching';
+import { runCopyTextAction, type TextSelection } from '../utils/paneText';
 import './menus/AppMenu.css';
 
 interface SelectionContextMenuProps {
@@ -16,6 +18,8 @@ interface SelectionContextMenuProps {
   x: number;
   y: number;
   selectedText: string;
+  /** The selection in capture-pane line numbers, for the server-side copies. */
+  selection?: TextSelection | null;
   onClose: () => void;
 }
 
@@ -24,9 +28,11 @@ export function SelectionContextMenu({
   x,
   y,
   selectedText,
+  selection,
   onClose,
 }: SelectionContextMenuProps) {
   const send = useAppSend();
+  const invoke = useAdapterInvoke();
 
   const exitAndClose = () => {
     send({ type: 'EXIT_COPY_MODE', paneId });
@@ -43,6 +49,15 @@ export function SelectionContextMenu({
       >
         Copy
       </MenuItem>
+      <MenuItem
+        disabled={!selection}
+        onClick={() => {
+          runCopyTextAction(invoke, paneId, { selection, html: true });
+          exitAndClose();
+        }}
+      >
+        Copy as HTML
+      </MenuItem>
       <MenuItem
         onClick={() => {
           send({ type: 'EXIT_COPY_MODE', paneId });
@@ -13,7 +13,7 @@ import { Cursor } from './Cursor';
 import { LogProfiler } from '../utils/renderLog';
 import type { CursorMode } from './Cursor';
 import type { CellLine, TerminalCell, CellStyle } from '../tmux/types';
-import { cellColorToCss, isWideChar } from './terminalShared';
+import { cellColorToCss, cellTextDecoration, isWideCell } from './terminalShared';
 import { isBlockGlyph, blockGlyphStyle } from './blockGlyphs';
 import { detectUrls } from '../utils/urlDetect';
 
@@ -49,6 +49,18 @@ function styleKey(s: CellStyle | undefined): number {
   if (s.underline) h = (h ^ 4) * 0x01000193;
   if (s.inverse) h = (h ^ 8) * 0x01000193;
   if (s.dim) h = (h ^ 16) * 0x01000193;
+  if (s.strikethrough) h = (h ^ 32) * 0x01000193;
+  if (s.blink) h = (h ^ 64) * 0x01000193;
+  if (s.underline_style) h = (h ^ s.underline_style.charCodeAt(1)) * 0x01000193;
+  if (s.underline_color !== undefined) {
+    if (typeof s.underline_color === 'number') {
+      h = (h ^ (s.underline_color + 768)) * 0x01000193;
+    } else {
+      h = (h ^ (s.underline_color.r + 67072)) * 0x01000193;
+      h = (h ^ (s.underline_color.g + 67328)) * 0x01000193;
+      h = (h ^ (s.underline_color.b + 67584)) * 0x01000193;
+    }
+  }
   if (s.url) {
     for (let i = 0; i < s.url.length; i++) {
       h = (h ^ s.url.charCodeAt(i)) * 0x01000193;
@@ -78,8 +90,12 @@ function buildCellStyle(style: CellStyle): CSSProperties {
   if (style.italic) {
     css.fontStyle = 'italic';
   }
-  if (style.underline) {
-    css.textDecoration = 'underline';
+  const decoration = cellTextDecoration(style);
+  if (decoration) {
+    css.textDecoration = decoration;
+  }
+  if (style.blink) {
+    css.animation = 'terminal-blink 1s step-end infinite';
   }
   if (style.inverse) {
     // Swap fg/bg for inverse
@@ -299,7 +315,7 @@ export const TerminalLine = memo(
         const cellSK = styleKey(cell.s);
         const selected = isCellSelected(i);
         const cellUrlIdx = cell.s?.url ? -1 : urlIdx(i); // skip auto-detect if OSC 8
-        const wide = isWideChar(cell.c);
+        const wide = isWideCell(cell);
         const blockCh = isBlockGlyph(cell.c) ? cell.c : null;
 
         if (
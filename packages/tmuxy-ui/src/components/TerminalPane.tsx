@@ -29,7 +29,9 @@ import {
 import { usePaneMouse, usePaneTouch } from '../hooks';
 import { LogProfiler } from '../utils/renderLog';
 import { isCollapsedPane } from '../constants';
+import { paneCursor, terminalColorStyle } from './terminalShared';
 import { extractSelectedText } from '../utils/copyMode';
+import { selectionForServer, type TextSelection } from '../utils/paneText';
 
 interface TerminalPaneProps {
   paneId: string;
@@ -43,6 +45,7 @@ export function TerminalPane({ paneId }: TerminalPaneProps) {
   const isSinglePane = useIsSinglePane();
   const { charWidth, charHeight } = useAppSelector(selectCharSize);
   const focusedFloatPaneId = useAppSelector((ctx) => ctx.focusedFloatPaneId);
+  const terminalTheme = useAppSelector((ctx) => ctx.terminalTheme);
   const contentRef = useRef<HTMLDivElement>(null);
   const scrollRef = useRef<HTMLDivElement>(null);
   const wrapperRef = useRef<HTMLDivElement>(null);
@@ -54,6 +57,7 @@ export function TerminalPane({ paneId }: TerminalPaneProps) {
     x: number;
     y: number;
     text: string;
+    selection: TextSelection | null;
   } | null>(null);
   const historySize = pane?.historySize ?? 0;
   const paneHeight = pane?.height ?? 24;
@@ -237,7 +241,7 @@ export function TerminalPane({ paneId }: TerminalPaneProps) {
       const cs = snap.context.copyModeStates[paneId];
       if (cs?.selectionMode) {
         const text = extractSelectedText(cs);
-        if (text) setSelectionMenu({ x, y, text });
+        if (text) setSelectionMenu({ x, y, text, selection: selectionForServer(cs) });
       }
     },
     [actor, paneId],
@@ -271,7 +275,12 @@ export function TerminalPane({ paneId }: TerminalPaneProps) {
         // Already have a selection — show menu immediately
         const text = extractSelectedText(copyState);
         if (text) {
-          setSelectionMenu({ x: menuX, y: menuY, text });
+          setSelectionMenu({
+            x: menuX,
+            y: menuY,
+            text,
+            selection: selectionForServer(copyState),
+          });
         }
       } else if (!copyState) {
         // Not in copy mode — enter copy mode, select word, then show menu
@@ -364,11 +373,20 @@ export function TerminalPane({ paneId }: TerminalPaneProps) {
           x={selectionMenu.x}
           y={selectionMenu.y}
           selectedText={selectionMenu.text}
+          selection={selectionMenu.selection}
           onClose={() => setSelectionMenu(null)}
         />
       )}
       {!collapsed && (
-        <div className="pane-content" ref={contentRef} style={{ flex: 1 }}>
+        <div
+          className="pane-content"
+          ref={contentRef}
+          style={{
+            flex: 1,
+            cursor: copyState ? undefined : paneCursor(pane),
+            ...terminalColorStyle(terminalTheme, pane.colors),
+          }}
+        >
           <div
             ref={scrollRef}
             className="pane-scroll-container hide-scrollbar"
@@ -6,6 +6,9 @@
  * - Command mode replaces the whole bar with a full-width prompt input (like tmux prefix+:)
  * - Center shows only temporary display-message output; the raw tmux status
  *   line is NOT rendered — hardcoded hints (left) and host/session (right) stand in
+ * - Right starts with the active window's @tmuxy-status, evaluated by the server
+ * - With tmux's `status` at 2 or more, the rows below the first render under
+ *   the bar as terminal lines
  * - Host is clickable (desktop: open the connect float); session opens the session float
  */
 
@@ -16,15 +19,19 @@ import {
   useAppConfig,
   selectCommandMode,
   selectStatusMessage,
+  selectActiveWindowStatus,
   selectGridDimensions,
   selectSessionName,
   selectKeyBindings,
   selectPrefixActive,
   selectActivePaneCopyMode,
+  selectStatusLines,
 } from '../machines/AppContext';
 import { formatPrefixKey } from './menus/keybindingLabel';
+import { TerminalLine } from './TerminalLine';
 import { isTauri } from '../tmux/adapters';
 import type { KeyBindings } from '../machines/types';
+import type { CellLine } from '../tmux/types';
 
 const PREFIX_HINTS = [
   { key: '-', label: 'split h' },
@@ -123,6 +130,33 @@ function CopyModeHints() {
   );
 }
 
+/** tmux status rows below the first (`status-format[1]` and on). */
+function StatusRows({ lines, gridWidth }: { lines: CellLine[]; gridWidth: number }) {
+  if (lines.length === 0) return null;
+  return (
+    <div className="tmux-status-rows" data-testid="tmux-status-rows">
+      <pre
+        className="terminal-content"
+        style={gridWidth > 0 ? { width: gridWidth } : undefined}
+        aria-hidden="true"
+      >
+        {lines.map((line, i) => (
+          <TerminalLine
+            key={i}
+            line={line}
+            lineIndex={i}
+            cursorX={-1}
+            cursorY={-1}
+            showCursor={false}
+            inMode={false}
+            isActive={false}
+          />
+        ))}
+      </pre>
+    </div>
+  );
+}
+
 function CommandModeInput({
   prompt,
   input,
@@ -175,11 +209,13 @@ function CommandModeInput({
 export function TmuxStatusBar() {
   const commandMode = useAppSelector(selectCommandMode);
   const statusMessage = useAppSelector(selectStatusMessage);
+  const windowStatus = useAppSelector(selectActiveWindowStatus);
   const { totalWidth, charWidth } = useAppSelector(selectGridDimensions);
   const sessionName = useAppSelector(selectSessionName);
   const keybindings = useAppSelector(selectKeyBindings);
   const prefixActive = useAppSelector(selectPrefixActive);
   const inCopyMode = useAppSelector(selectActivePaneCopyMode);
+  const statusLines = useAppSelector(selectStatusLines);
   const send = useAppSend();
   const { isDemo } = useAppConfig();
 
@@ -223,34 +259,42 @@ export function TmuxStatusBar() {
   ) : null;
 
   return (
-    <div className="tmux-status-bar" data-testid="tmux-status-bar">
-      <div
-        className="tmux-statusline-inner"
-        style={gridPixelWidth > 0 ? { width: gridPixelWidth, margin: '0 auto' } : undefined}
-      >
-        <div className="tmux-statusline-left">
-          {inCopyMode ? (
-            <CopyModeHints />
-          ) : (
-            <StatusLineHints keybindings={keybindings} prefixActive={prefixActive} />
-          )}
-        </div>
-        <div className="tmux-statusline-center">{centerContent}</div>
-        <div className="tmux-statusline-right">
-          <span
-            className={`statusline-host${handleHostClick ? ' statusline-clickable' : ''}`}
-            onClick={handleHostClick}
-          >
-            {hostname}
-          </span>
-          <span
-            className={`statusline-session${handleSessionClick ? ' statusline-clickable' : ''}`}
-            onClick={handleSessionClick}
-          >
-            [{sessionName}]
-          </span>
+    <>
+      <div className="tmux-status-bar" data-testid="tmux-status-bar">
+        <div
+          className="tmux-statusline-inner"
+          style={gridPixelWidth > 0 ? { width: gridPixelWidth, margin: '0 auto' } : undefined}
+        >
+          <div className="tmux-statusline-left">
+            {inCopyMode ? (
+              <CopyModeHints />
+            ) : (
+              <StatusLineHints keybindings={keybindings} prefixActive={prefixActive} />
+            )}
+          </div>
+          <div className="tmux-statusline-center">{centerContent}</div>
+          <div className="tmux-statusline-right">
+            {windowStatus && (
+              <span className="statusline-window-status" data-testid="window-status">
+                {windowStatus}
+              </span>
+            )}
+            <span
+              className={`statusline-host${handleHostClick ? ' statusline-clickable' : ''}`}
+              onClick={handleHostClick}
+            >
+              {hostname}
+            </span>
+            <span
+              className={`statusline-session${handleSessionClick ? ' statusline-clickable' : ''}`}
+              onClick={handleSessionClick}
+            >
+              [{sessionName}]
+            </span>
+          </div>
         </div>
       </div>
-    </div>
+      <StatusRows lines={statusLines} gridWidth={gridPixelWidth} />
+    </>
   );
 }
@@ -9,7 +9,9 @@ import { useRef, useEffect } from 'react';
 import { PaneHeader } from './PaneHeader';
 import { getWidget } from './widgets';
 import { getWidgetTitle } from './widgets/getWidgetTitle';
+import { useWidgetData } from './widgets/widgetData';
 import {
+  useAdapterInvoke,
   useAppSend,
   usePane,
   useIsPaneInActiveWindow,
@@ -26,6 +28,8 @@ export function WidgetPane({ paneId, widgetInfo }: WidgetPaneProps) {
   const pane = usePane(paneId);
   const isInActiveWindow = useIsPaneInActiveWindow(paneId);
   const isSinglePane = useIsSinglePane();
+  const data = useWidgetData(paneId);
+  const invoke = useAdapterInvoke();
   const wrapperRef = useRef<HTMLDivElement>(null);
 
   // Vi-key navigation: capture-phase window listener that fires BEFORE
@@ -145,6 +149,9 @@ export function WidgetPane({ paneId, widgetInfo }: WidgetPaneProps) {
           writeStdin={writeStdin}
           width={pane.width}
           height={pane.height}
+          spec={pane.widget ?? undefined}
+          data={data}
+          invoke={invoke}
         />
       </div>
     </div>
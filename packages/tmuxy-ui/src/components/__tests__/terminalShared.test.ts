@@ -0,0 +1,41 @@
+import { describe, it, expect } from 'vitest';
+import { cellColorToCss, paneCursor, terminalColorStyle } from '../terminalShared';
+
+/**
+ * Terminal colours are layered through CSS variables: the CSS theme defines
+ * `--term-*`, and the server theme and a pane's OSC 4/10/11 overrides
+ * redefine them on the pane so every cell picks them up without re-rendering.
+ */
+describe('terminalColorStyle', () => {
+  it('layers pane overrides over the server theme', () => {
+    const style = terminalColorStyle(
+      { foreground: '#c0c0c0', palette: { '1': '#cc6666', '100': '#123456' } },
+      { foreground: '#ffffff', palette: { '1': '#ff0000' } },
+    ) as Record<string, string>;
+    expect(style['--term-foreground']).toBe('#ffffff');
+    expect(style['--term-red']).toBe('#ff0000');
+    expect(style['--term-color-100']).toBe('#123456');
+    // No background override: the pane keeps the layout surface.
+    expect(style.backgroundColor).toBeUndefined();
+  });
+
+  it('paints the background only when one is set', () => {
+    const style = terminalColorStyle({ background: '#1e1e1e' }) as Record<string, string>;
+    expect(style['--term-background']).toBe('#1e1e1e');
+    expect(style.backgroundColor).toBe('var(--term-background)');
+    expect(terminalColorStyle({}, undefined)).toBeUndefined();
+  });
+
+  it('resolves extended palette entries through their variable', () => {
+    expect(cellColorToCss(1)).toBe('var(--term-red)');
+    expect(cellColorToCss(196)).toBe('var(--term-color-196, #ff0000)');
+  });
+});
+
+describe('paneCursor', () => {
+  it("prefers the application's OSC 22 shape, then the mouse-mode arrow", () => {
+    expect(paneCursor({ pointerShape: 'pointer', mouseAnyFlag: true })).toBe('pointer');
+    expect(paneCursor({ mouseAnyFlag: true })).toBe('default');
+    expect(paneCursor({ pointerShape: '', mouseAnyFlag: false })).toBeUndefined();
+  });
+});
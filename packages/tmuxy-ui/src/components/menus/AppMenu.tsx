@@ -15,6 +15,7 @@
 import { Menu, MenuItem, SubMenu, MenuDivider } from '@szhsin/react-menu';
 import '@szhsin/react-menu/dist/index.css';
 import {
+  useAdapterInvoke,
   useAppSend,
   useAppSelector,
   useAppSelectorShallow,
@@ -29,10 +30,13 @@ import {
 import { activeCloseTarget, executeMenuAction } from './menuActions';
 import { PaneMenuItems } from './PaneMenuItems';
 import { KeyLabel } from './KeyLabel';
+import { EXPORT_ACTIONS, runExportAction } from '../../utils/paneExport';
+import { COPY_TEXT_ACTIONS, runCopyTextAction } from '../../utils/paneText';
 import './AppMenu.css';
 
 export function AppMenu() {
   const send = useAppSend();
+  const invoke = useAdapterInvoke();
   const { isDemo } = useAppConfig();
   const keybindings = useAppSelector(selectKeyBindings);
   const isSinglePane = useAppSelector(selectIsSinglePane);
@@ -46,7 +50,16 @@ export function AppMenu() {
   const isSingleWindow = windows.filter((w) => w.windowType === 'tab').length <= 1;
 
   const handleAction = (actionId: string) => {
-    executeMenuAction(send, actionId, activeCloseTarget(activePaneId, focusedFloatPaneId));
+    const target = activeCloseTarget(activePaneId, focusedFloatPaneId);
+    if (EXPORT_ACTIONS[actionId]) {
+      if (target) runExportAction(invoke, target, EXPORT_ACTIONS[actionId]);
+      return;
+    }
+    if (COPY_TEXT_ACTIONS[actionId]) {
+      if (target) runCopyTextAction(invoke, target, COPY_TEXT_ACTIONS[actionId]);
+      return;
+    }
+    executeMenuAction(send, actionId, target);
   };
 
   const menuButton = (
@@ -2,7 +2,7 @@
  * PaneMenuItems - Shared pane menu items used by AppMenu, PaneContextMenu, and PaneHeader icon menu.
  */
 
-import { MenuItem, MenuDivider } from '@szhsin/react-menu';
+import { MenuItem, MenuDivider, SubMenu } from '@szhsin/react-menu';
 import type { KeyBindings } from '../../machines/types';
 import { KeyLabel } from './KeyLabel';
 
@@ -55,7 +55,13 @@ export function PaneMenuItems({ keybindings, isSinglePane, onAction }: PaneMenuI
         Paste
         <KeyLabel keybindings={keybindings} command="paste-buffer" />
       </MenuItem>
+      <MenuItem onClick={() => onAction('pane-copy-text')}>Copy Pane Text</MenuItem>
+      <MenuItem onClick={() => onAction('pane-copy-html')}>Copy Pane as HTML</MenuItem>
       <MenuItem onClick={() => onAction('pane-clear')}>Clear Screen</MenuItem>
+      <SubMenu label="Export as Image">
+        <MenuItem onClick={() => onAction('pane-export-png')}>PNG</MenuItem>
+        <MenuItem onClick={() => onAction('pane-export-svg')}>SVG</MenuItem>
+      </SubMenu>
       <MenuDivider />
       <MenuItem onClick={() => onAction('view-zoom')}>
         Zoom Pane
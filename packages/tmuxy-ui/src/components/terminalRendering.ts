@@ -8,7 +8,7 @@
  */
 
 import type { CellLine, CellStyle, CellColor } from '../tmux/types';
-import { cellColorToCss, isWideChar } from './terminalShared';
+import { cellColorToCss, cellTextDecoration, isWideCell } from './terminalShared';
 import { detectUrls } from '../utils/urlDetect';
 
 // ============================================
@@ -52,12 +52,14 @@ function applyStyleToElement(
   if (style.bold) el.style.fontWeight = 'bold';
   if (style.dim) el.style.opacity = '0.5';
   if (style.italic) el.style.fontStyle = 'italic';
-  if (style.underline) el.style.textDecoration = 'underline';
+  // Hyperlinks are always underlined, keeping any strikethrough alongside.
+  const decoration = cellTextDecoration(style.url ? { ...style, underline: true } : style);
+  if (decoration) el.style.textDecoration = decoration;
+  if (style.blink) el.style.animation = 'terminal-blink 1s step-end infinite';
 
   if (style.url) {
     el.dataset.href = style.url;
     el.style.cursor = 'pointer';
-    el.style.textDecoration = 'underline';
   }
 }
 
@@ -72,6 +74,10 @@ export function stylesMatch(a: CellStyle | undefined, b: CellStyle | undefined):
     (a.dim ?? false) === (b.dim ?? false) &&
     (a.italic ?? false) === (b.italic ?? false) &&
     (a.underline ?? false) === (b.underline ?? false) &&
+    a.underline_style === b.underline_style &&
+    colorEqual(a.underline_color, b.underline_color) &&
+    (a.strikethrough ?? false) === (b.strikethrough ?? false) &&
+    (a.blink ?? false) === (b.blink ?? false) &&
     (a.inverse ?? false) === (b.inverse ?? false) &&
     a.url === b.url
   );
@@ -157,7 +163,7 @@ export function renderLineToDOM(
   let groupStyle = line[0].s;
   let groupSelected = selRange ? 0 >= selRange.startCol && 0 <= selRange.endCol : false;
   let groupUrlIdx = line[0].s?.url ? -1 : urlIdxOf(0);
-  let groupWide = isWideChar(line[0].c);
+  let groupWide = isWideCell(line[0]);
 
   const flush = (end: number) => {
     const text = lineSliceText(line, groupStart, end);
@@ -193,7 +199,7 @@ export function renderLineToDOM(
     const cell = line[i];
     const selected = selRange ? i >= selRange.startCol && i <= selRange.endCol : false;
     const cellUrlIdx = cell.s?.url ? -1 : urlIdxOf(i);
-    const wide = isWideChar(cell.c);
+    const wide = isWideCell(cell);
     if (
       wide ||
       groupWide ||
@@ -11,7 +11,8 @@
  * copy-pasted in both files and had already begun to drift.
  */
 
-import type { CellColor } from '../tmux/types';
+import type { CSSProperties } from 'react';
+import type { CellColor, CellStyle, TerminalCell, TerminalColors } from '../tmux/types';
 
 /**
  * CSS variables for the standard 16 ANSI colors.
@@ -38,16 +39,80 @@ export const STANDARD_16_VARS = [
 
 /**
  * Convert CellColor to a CSS color string. Standard 16 colors go through the
- * theme CSS variables; extended 256 colors and RGB are computed.
+ * theme CSS variables; extended 256 colors go through `--term-color-N`,
+ * falling back to the computed xterm value; RGB is used as is.
  */
 export function cellColorToCss(color: CellColor): string {
   if (typeof color === 'number') {
     if (color < 16) return STANDARD_16_VARS[color];
-    return getAnsi256Color(color);
+    return `var(--term-color-${color}, ${getAnsi256Color(color)})`;
   }
   return `rgb(${color.r}, ${color.g}, ${color.b})`;
 }
 
+/**
+ * Inline style layering terminal colour overrides — the server theme, then a
+ * pane's own OSC 4/10/11 colours — over the CSS theme, by redefining the
+ * `--term-*` variables cells resolve through. A background override also
+ * paints the element, since panes otherwise show the layout surface.
+ * Returns undefined when nothing is overridden.
+ */
+export function terminalColorStyle(
+  ...layers: (TerminalColors | undefined)[]
+): CSSProperties | undefined {
+  const vars: Record<string, string> = {};
+  for (const layer of layers) {
+    if (!layer) continue;
+    if (layer.foreground) vars['--term-foreground'] = layer.foreground;
+    if (layer.background) vars['--term-background'] = layer.background;
+    for (const [key, color] of Object.entries(layer.palette ?? {})) {
+      const index = Number(key);
+      if (!Number.isInteger(index) || index < 0 || index > 255) continue;
+      // STANDARD_16_VARS entries are `var(--term-…)`; strip the `var()`.
+      const name = index < 16 ? STANDARD_16_VARS[index].slice(4, -1) : `--term-color-${index}`;
+      vars[name] = color;
+    }
+  }
+  if (Object.keys(vars).length === 0) return undefined;
+  return {
+    ...vars,
+    ...(vars['--term-background'] && { backgroundColor: 'var(--term-background)' }),
+  } as CSSProperties;
+}
+
+/**
+ * CSS cursor over a pane's terminal: the shape the application asked for
+ * with OSC 22, else the arrow when it reports mouse events (clicks go to it,
+ * not to a text selection), else the browser's own.
+ */
+export function paneCursor(pane: {
+  pointerShape?: string;
+  mouseAnyFlag: boolean;
+}): CSSProperties['cursor'] {
+  if (pane.pointerShape) return pane.pointerShape;
+  return pane.mouseAnyFlag ? 'default' : undefined;
+}
+
+/**
+ * Build the CSS `text-decoration` shorthand for a cell: underline (with its
+ * shape and color) and strikethrough. Returns '' when the cell has no
+ * decoration.
+ */
+export function cellTextDecoration(style: CellStyle): string {
+  const lines: string[] = [];
+  const underline = style.underline ?? false;
+  if (underline) lines.push('underline');
+  if (style.strikethrough) lines.push('line-through');
+  if (lines.length === 0) return '';
+  if (underline && style.underline_style) {
+    lines.push(style.underline_style === 'curly' ? 'wavy' : style.underline_style);
+  }
+  if (underline && style.underline_color !== undefined) {
+    lines.push(cellColorToCss(style.underline_color));
+  }
+  return lines.join(' ');
+}
+
 /**
  * Get ANSI 256 color as hex. Only ever called for index >= 16 —
  * `cellColorToCss` handles 0..15 via the theme CSS-var path before reaching
@@ -106,3 +171,13 @@ export function isWideChar(s: string): boolean {
     (cp >= 0x20000 && cp <= 0x3fffd) // CJK Ext B and beyond
   );
 }
+
+/**
+ * Whether a cell holds a double-width character. The backend reports the
+ * width it laid the cell out with (`w`, from the same unicode-width table tmux
+ * uses); the code-point heuristic only covers content that predates it, such as
+ * demo-mode cells.
+ */
+export function isWideCell(cell: TerminalCell): boolean {
+  return cell.w !== undefined ? cell.w === 2 : isWideChar(cell.c);
+}
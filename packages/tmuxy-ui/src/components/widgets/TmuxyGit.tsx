@@ -0,0 +1,111 @@
+import type { WidgetProps } from './index';
+
+/** A repository's status from `tmuxy-server git-status` (see git_status.rs). */
+export interface GitStatus {
+  root: string;
+  branch: string | null;
+  head: string;
+  upstream: string | null;
+  ahead: number;
+  behind: number;
+  staged: number;
+  modified: number;
+  untracked: number;
+  conflicted: number;
+  files: { path: string; status: string }[];
+}
+
+const COUNTS = ['ahead', 'behind', 'staged', 'modified', 'untracked', 'conflicted'] as const;
+
+/**
+ * The widget's data as a status: `null` when the directory isn't in a
+ * repository (the refresher sends `null` for that), `undefined` until the
+ * first refresh arrives or when the data isn't a status at all.
+ */
+export function parseGitStatus(data: unknown): GitStatus | null | undefined {
+  if (data === null) return null;
+  if (typeof data !== 'object') return undefined;
+  const d = data as Partial<GitStatus>;
+  if (typeof d.root !== 'string' || typeof d.head !== 'string') return undefined;
+  if (COUNTS.some((key) => typeof d[key] !== 'number')) return undefined;
+  const counts = d as Pick<GitStatus, (typeof COUNTS)[number]>;
+  return {
+    root: d.root,
+    branch: typeof d.branch === 'string' ? d.branch : null,
+    head: d.head,
+    upstream: typeof d.upstream === 'string' ? d.upstream : null,
+    ahead: counts.ahead,
+    behind: counts.behind,
+    staged: counts.staged,
+    modified: counts.modified,
+    untracked: counts.untracked,
+    conflicted: counts.conflicted,
+    files: Array.isArray(d.files)
+      ? d.files.filter((f) => typeof f?.path === 'string' && typeof f?.status === 'string')
+      : [],
+  };
+}
+
+/** Branch name, or the commit on a detached HEAD. */
+export function headLabel(status: GitStatus): string {
+  if (status.branch) return status.branch;
+  return status.head ? `(${status.head})` : '(no commits)';
+}
+
+/**
+ * Git widget — branch, ahead/behind and changed files of the repository a
+ * pane is in (see `tmuxy widget git`).
+ */
+export function TmuxyGit({ data, spec }: WidgetProps) {
+  const status = parseGitStatus(data);
+  if (status === undefined) {
+    return <div className="widget-git-empty">Reading repository...</div>;
+  }
+  if (status === null) {
+    return (
+      <div className="widget-git-empty">
+        {spec?.source ? `${spec.source} is not in a git repository` : 'Not a git repository'}
+      </div>
+    );
+  }
+
+  const total = status.staged + status.modified + status.untracked + status.conflicted;
+  return (
+    <div className="widget-git widget-scrollable">
+      <div className="widget-git-header">
+        <span className="widget-git-branch">{headLabel(status)}</span>
+        {status.upstream && <span className="widget-git-upstream">{status.upstream}</span>}
+        {status.ahead > 0 && <span className="widget-git-ahead">↑{status.ahead}</span>}
+        {status.behind > 0 && <span className="widget-git-behind">↓{status.behind}</span>}
+      </div>
+      {total === 0 ? (
+        <div className="widget-git-clean">clean</div>
+      ) : (
+        <>
+          <div className="widget-git-counts">
+            {status.staged > 0 && <span className="widget-git-staged">+{status.staged} staged</span>}
+            {status.modified > 0 && (
+              <span className="widget-git-modified">~{status.modified} modified</span>
+            )}
+            {status.untracked > 0 && (
+              <span className="widget-git-untracked">?{status.untracked} untracked</span>
+            )}
+            {status.conflicted > 0 && (
+              <span className="widget-git-conflicted">!{status.conflicted} conflicted</span>
+            )}
+          </div>
+          <ul className="widget-git-files">
+            {status.files.map((file) => (
+              <li key={file.path}>
+                <span className="widget-git-code">{file.status}</span> {file.path}
+              </li>
+            ))}
+            {status.files.length < total && (
+              <li className="widget-git-more">…and {total - status.files.length} more</li>
+            )}
+          </ul>
+        </>
+      )}
+    </div>
+  );
+}
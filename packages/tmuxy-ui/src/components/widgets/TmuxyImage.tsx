@@ -6,7 +6,7 @@ import type { WidgetProps } from './index';
  * Terminal line wrapping can split a long URL across multiple CellLines,
  * so we join all content lines and extract the last URL-like string.
  */
-export function TmuxyImage({ lines }: WidgetProps) {
+export function TmuxyImage({ lines, spec }: WidgetProps) {
   // Join all content lines (handles terminal line wrapping of long URLs)
   const joined = lines.join('').trim();
 
@@ -33,6 +33,14 @@ export function TmuxyImage({ lines }: WidgetProps) {
     }
   }
 
+  // Nothing recognisable in the output yet: use the source the launcher
+  // tagged the pane with.
+  if (!src && spec?.source) {
+    src = /^https?:\/\//.test(spec.source)
+      ? spec.source
+      : `/api/file?path=${encodeURIComponent(spec.source)}`;
+  }
+
   if (!src) {
     return <div className="widget-image-empty">Waiting for image...</div>;
   }
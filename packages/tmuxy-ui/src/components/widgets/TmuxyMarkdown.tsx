@@ -44,22 +44,62 @@ const components: Components = {
   },
 };
 
+/** Class of the `<pre>` the server renders a mermaid fence's source into. */
+const MERMAID_PRE = /<pre class="tmuxy-mermaid">([\s\S]*?)<\/pre>/g;
+
+function unescapeHtml(text: string): string {
+  return text
+    .replace(/&lt;/g, '<')
+    .replace(/&gt;/g, '>')
+    .replace(/&quot;/g, '"')
+    .replace(/&amp;/g, '&');
+}
+
+/**
+ * Split server-rendered HTML around its mermaid fences, so the charts can be
+ * drawn by MermaidBlock between the HTML parts.
+ */
+export function splitMermaid(html: string): ({ html: string } | { chart: string })[] {
+  const parts: ({ html: string } | { chart: string })[] = [];
+  let last = 0;
+  for (const match of html.matchAll(MERMAID_PRE)) {
+    if (match.index > last) parts.push({ html: html.slice(last, match.index) });
+    parts.push({ chart: unescapeHtml(match[1]).trimEnd() });
+    last = match.index + match[0].length;
+  }
+  if (last < html.length) parts.push({ html: html.slice(last) });
+  return parts;
+}
+
+/**
+ * The file as HTML from the server's markdown pipeline (`/api/markdown`,
+ * the one `tmuxy md` uses), or as raw markdown from `/api/file` when the
+ * server can't render it.
+ */
+async function fetchMarkdown(filePath: string): Promise<{ text: string; html: boolean }> {
+  const path = encodeURIComponent(filePath);
+  const rendered = await fetch(`/api/markdown?path=${path}`);
+  if (rendered.ok) {
+    const html = !!rendered.headers?.get('content-type')?.startsWith('text/html');
+    return { text: await rendered.text(), html };
+  }
+  const raw = await fetch(`/api/file?path=${path}`);
+  if (!raw.ok) throw new Error(`${raw.status} ${raw.statusText}`);
+  return { text: await raw.text(), html: false };
+}
+
 /** Fetch file content, triggered during render when meta changes (no useEffect) */
 function useFetchFile(filePath: string | undefined, seq: string | undefined) {
   const [content, setContent] = useState('');
+  const [html, setHtml] = useState(false);
   const [error, setError] = useState<string | null>(null);
   const lastFetchRef = useRef('');
 
   const fetchKey = `${filePath}:${seq}`;
   if (filePath && fetchKey !== lastFetchRef.current) {
     lastFetchRef.current = fetchKey;
-    const url = `/api/file?path=${encodeURIComponent(filePath)}`;
-    fetch(url)
-      .then((res) => {
-        if (!res.ok) throw new Error(`${res.status} ${res.statusText}`);
-        return res.text();
-      })
-      .then((text) => {
+    fetchMarkdown(filePath)
+      .then(({ text, html }) => {
         // Drop stale/out-of-order responses: if a newer __SEQ__ (or file) has
         // started fetching, lastFetchRef has advanced past this key, so an
         // earlier request resolving late must not clobber the newer content.
@@ -67,6 +107,7 @@ function useFetchFile(filePath: string | undefined, seq: string | undefined) {
         // a later render fires).
         if (lastFetchRef.current !== fetchKey) return;
         setContent(text);
+        setHtml(html);
         setError(null);
       })
       .catch((err) => {
@@ -75,12 +116,12 @@ function useFetchFile(filePath: string | undefined, seq: string | undefined) {
       });
   }
 
-  return { content, error };
+  return { content, html, error };
 }
 
 export function TmuxyMarkdown({ lines }: WidgetProps) {
   const meta = extractMeta(lines);
-  const { content, error } = useFetchFile(meta?.filePath, meta?.seq);
+  const { content, html, error } = useFetchFile(meta?.filePath, meta?.seq);
 
   // When no __FILE__ metadata, render lines directly as inline markdown
   const inlineContent = !meta ? lines.join('\n').trim() : '';
@@ -95,6 +136,21 @@ export function TmuxyMarkdown({ lines }: WidgetProps) {
     return <div className="widget-markdown-empty">Waiting for content...</div>;
   }
 
+  if (meta && html) {
+    // Safe to insert: the server escapes any raw HTML in the markdown.
+    return (
+      <div className="widget-markdown">
+        {splitMermaid(markdown).map((part, i) =>
+          'chart' in part ? (
+            <MermaidBlock key={i} chart={part.chart} />
+          ) : (
+            <div key={i} dangerouslySetInnerHTML={{ __html: part.html }} />
+          ),
+        )}
+      </div>
+    );
+  }
+
   return (
     <div className="widget-markdown">
       <Markdown remarkPlugins={[remarkGfm]} components={components}>
@@ -0,0 +1,71 @@
+import type { WidgetProps } from './index';
+
+const BARS = '▁▂▃▄▅▆▇█';
+const SAMPLES_PATTERN = /__SAMPLES__:(-?[0-9.]+(?:,-?[0-9.]+)*)/g;
+const ERROR_MARKER = '__ERROR__:';
+
+/**
+ * The newest sample buffer in the sampler's output, and its error if that
+ * came later. Every frame carries the whole ring buffer, so only the last
+ * `__SAMPLES__` line matters. Lines are joined first: a long buffer wraps
+ * across terminal rows, and the comma-separated list has no spaces for
+ * `trimEnd` to eat at the wrap points.
+ */
+export function parseMetric(lines: string[]): { samples: number[]; error: string | null } {
+  const joined = lines.join('');
+  let samples: number[] = [];
+  let samplesAt = -1;
+  for (const match of joined.matchAll(SAMPLES_PATTERN)) {
+    samples = match[1].split(',').map(Number);
+    samplesAt = match.index ?? -1;
+  }
+  let error: string | null = null;
+  const errorAt = joined.lastIndexOf(ERROR_MARKER);
+  if (errorAt > samplesAt) {
+    error = joined
+      .slice(errorAt + ERROR_MARKER.length)
+      .split('__')[0]
+      .trim();
+  }
+  return { samples: samples.filter(Number.isFinite), error };
+}
+
+/** A block sparkline of the last `width` samples, scaled to their range. */
+export function sparkline(samples: number[], width: number): string {
+  const shown = samples.slice(-Math.max(1, width));
+  const min = Math.min(...shown);
+  const max = Math.max(...shown);
+  return shown
+    .map((v) => BARS[max === min ? 3 : Math.round(((v - min) / (max - min)) * 7)])
+    .join('');
+}
+
+function format(value: number): string {
+  return Number.isInteger(value) ? String(value) : value.toFixed(2).replace(/\.?0+$/, '');
+}
+
+/**
+ * Metric widget — a sparkline with the current, min and max value of a
+ * sampled command (see `tmuxy metric`).
+ */
+export function TmuxyMetric({ lines, width }: WidgetProps) {
+  const { samples, error } = parseMetric(lines);
+
+  if (samples.length === 0) {
+    return <div className="widget-metric-empty">{error ?? 'Waiting for samples...'}</div>;
+  }
+
+  const current = samples[samples.length - 1];
+  return (
+    <div className="widget-metric">
+      <div className="widget-metric-header">
+        <span className="widget-metric-current">{format(current)}</span>
+        <span className="widget-metric-range">
+          min {format(Math.min(...samples))} · max {format(Math.max(...samples))}
+        </span>
+      </div>
+      <div className="widget-metric-spark">{sparkline(samples, width - 2)}</div>
+      {error && <div className="widget-metric-error">{error}</div>}
+    </div>
+  );
+}
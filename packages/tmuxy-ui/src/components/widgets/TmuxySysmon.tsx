@@ -0,0 +1,118 @@
+import type { WidgetProps } from './index';
+
+interface Usage {
+  used: number;
+  total: number;
+}
+
+/** One sample from `tmuxy-server sysmon` (see `Sample` in sysmon.rs). */
+export interface SysmonSample {
+  cpu: number;
+  cpus: number[];
+  memory: Usage;
+  swap: Usage;
+  disks: (Usage & { mount: string })[];
+  network: { rx: number; tx: number };
+}
+
+const isUsage = (value: unknown): value is Usage =>
+  typeof value === 'object' &&
+  value !== null &&
+  typeof (value as Usage).used === 'number' &&
+  typeof (value as Usage).total === 'number';
+
+/**
+ * The widget's data as a sample, or null until one arrives. Anything that
+ * doesn't look like a sample (another widget's data, an older sampler) is
+ * treated as no sample rather than half-drawn.
+ */
+export function parseSysmon(data: unknown): SysmonSample | null {
+  if (typeof data !== 'object' || data === null) return null;
+  const d = data as Partial<SysmonSample>;
+  if (typeof d.cpu !== 'number' || !isUsage(d.memory) || !isUsage(d.swap)) return null;
+  if (!Array.isArray(d.cpus) || !Array.isArray(d.disks) || typeof d.network !== 'object') {
+    return null;
+  }
+  return {
+    cpu: d.cpu,
+    cpus: d.cpus.filter((v): v is number => typeof v === 'number'),
+    memory: d.memory,
+    swap: d.swap,
+    disks: d.disks.filter((disk) => isUsage(disk) && typeof disk.mount === 'string'),
+    network: { rx: Number(d.network?.rx) || 0, tx: Number(d.network?.tx) || 0 },
+  };
+}
+
+/** `1536` → `1.5K`, as the sampler's text line prints sizes. */
+export function formatBytes(bytes: number): string {
+  const units = ['K', 'M', 'G', 'T', 'P'];
+  if (bytes < 1024) return `${Math.round(bytes)}B`;
+  let value = bytes / 1024;
+  let unit = 0;
+  while (value >= 1024 && unit < units.length - 1) {
+    value /= 1024;
+    unit += 1;
+  }
+  return `${value.toFixed(1)}${units[unit]}`;
+}
+
+const percent = ({ used, total }: Usage) => (total > 0 ? (used / total) * 100 : 0);
+
+function Meter({ label, value, detail }: { label: string; value: number; detail: string }) {
+  const clamped = Math.min(100, Math.max(0, value));
+  return (
+    <div className="widget-sysmon-row">
+      <span className="widget-sysmon-label">{label}</span>
+      <span className="widget-sysmon-bar">
+        <span
+          className={`widget-sysmon-fill${clamped >= 90 ? ' widget-sysmon-fill-high' : ''}`}
+          style={{ width: `${clamped}%` }}
+        />
+      </span>
+      <span className="widget-sysmon-detail">{detail}</span>
+    </div>
+  );
+}
+
+/**
+ * System monitor widget — CPU, memory, swap, disk and network from the
+ * sampler's widget data (see `tmuxy widget sysmon`).
+ */
+export function TmuxySysmon({ data }: WidgetProps) {
+  const sample = parseSysmon(data);
+  if (!sample) {
+    return <div className="widget-sysmon-empty">Waiting for samples...</div>;
+  }
+
+  const usage = (u: Usage) => `${formatBytes(u.used)} / ${formatBytes(u.total)}`;
+  return (
+    <div className="widget-sysmon widget-scrollable">
+      <Meter label="cpu" value={sample.cpu} detail={`${Math.round(sample.cpu)}%`} />
+      {sample.cpus.length > 1 && (
+        <div className="widget-sysmon-cores" aria-label="per-core usage">
+          {sample.cpus.map((v, i) => (
+            <span
+              key={i}
+              className="widget-sysmon-core"
+              title={`cpu${i} ${Math.round(v)}%`}
+              style={{ height: `${Math.min(100, Math.max(0, v))}%` }}
+            />
+          ))}
+        </div>
+      )}
+      <Meter label="mem" value={percent(sample.memory)} detail={usage(sample.memory)} />
+      {sample.swap.total > 0 && (
+        <Meter label="swap" value={percent(sample.swap)} detail={usage(sample.swap)} />
+      )}
+      {sample.disks.map((disk) => (
+        <Meter key={disk.mount} label={disk.mount} value={percent(disk)} detail={usage(disk)} />
+      ))}
+      <div className="widget-sysmon-row">
+        <span className="widget-sysmon-label">net</span>
+        <span className="widget-sysmon-detail">
+          ↓ {formatBytes(sample.network.rx)}/s · ↑ {formatBytes(sample.network.tx)}/s
+        </span>
+      </div>
+    </div>
+  );
+}
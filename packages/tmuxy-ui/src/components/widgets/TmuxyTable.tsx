@@ -0,0 +1,154 @@
+import { useRef, useState } from 'react';
+import type { WidgetProps } from './index';
+
+/** Rows fetched per page. */
+export const PAGE_SIZE = 100;
+
+/** One page of a delimited file, as `table_query` returns it (see table.rs). */
+export interface TablePage {
+  headers: string[];
+  rows: string[][];
+  offset: number;
+  total_rows: number;
+  delimiter: string;
+}
+
+export interface TableSort {
+  column: number;
+  descending: boolean;
+}
+
+/** Clicking a header sorts by it ascending, then descending, then unsorted. */
+export function nextSort(sort: TableSort | null, column: number): TableSort | null {
+  if (!sort || sort.column !== column) return { column, descending: false };
+  if (!sort.descending) return { column, descending: true };
+  return null;
+}
+
+/** `1–100 of 2,345` for the footer. */
+export function pageLabel(page: TablePage): string {
+  if (page.total_rows === 0) return 'no rows';
+  const first = page.offset + 1;
+  const last = page.offset + page.rows.length;
+  return `${first.toLocaleString()}–${last.toLocaleString()} of ${page.total_rows.toLocaleString()}`;
+}
+
+/**
+ * Fetch a page whenever the query (or the file's version from the launcher)
+ * changes, triggered during render like the markdown widget's file fetch.
+ */
+function useTablePage(
+  invoke: WidgetProps['invoke'],
+  path: string | undefined,
+  offset: number,
+  sort: TableSort | null,
+  version: unknown,
+) {
+  const [page, setPage] = useState<TablePage | null>(null);
+  const [error, setError] = useState<string | null>(null);
+  const lastKeyRef = useRef('');
+
+  const key = JSON.stringify([path, offset, sort, version]);
+  if (invoke && path && key !== lastKeyRef.current) {
+    lastKeyRef.current = key;
+    invoke<TablePage>('table_query', {
+      query: { path, offset, limit: PAGE_SIZE, sort },
+    })
+      .then((result) => {
+        // A newer query has started: drop this late answer.
+        if (lastKeyRef.current !== key) return;
+        setPage(result);
+        setError(null);
+      })
+      .catch((err) => {
+        if (lastKeyRef.current !== key) return;
+        setError(String(err));
+      });
+  }
+
+  return { page, error };
+}
+
+/**
+ * Table widget — a CSV/TSV file as a sortable, paged table (see
+ * `tmuxy view data.csv`). The server parses the file; the widget asks it
+ * for one page at a time.
+ */
+export function TmuxyTable({ spec, data, invoke }: WidgetProps) {
+  const [offset, setOffset] = useState(0);
+  const [sort, setSort] = useState<TableSort | null>(null);
+  const version = (data as { version?: unknown } | undefined)?.version;
+  const { page, error } = useTablePage(invoke, spec?.source, offset, sort, version);
+
+  if (!spec?.source) {
+    return <div className="widget-table-empty">No file</div>;
+  }
+  if (error) {
+    return <div className="widget-table-empty">Error: {error}</div>;
+  }
+  if (!page) {
+    return <div className="widget-table-empty">Reading {spec.source}...</div>;
+  }
+
+  const sortBy = (column: number) => {
+    setSort(nextSort(sort, column));
+    setOffset(0);
+  };
+  const hasPrev = page.offset > 0;
+  const hasNext = page.offset + page.rows.length < page.total_rows;
+
+  return (
+    <div className="widget-table">
+      <div className="widget-table-scroll widget-scrollable">
+        <table>
+          <thead>
+            <tr>
+              {page.headers.map((header, column) => (
+                <th
+                  key={column}
+                  onClick={() => sortBy(column)}
+                  aria-sort={
+                    sort?.column === column
+                      ? sort.descending
+                        ? 'descending'
+                        : 'ascending'
+                      : undefined
+                  }
+                >
+                  {header}
+                  {sort?.column === column && (sort.descending ? ' ▼' : ' ▲')}
+                </th>
+              ))}
+            </tr>
+          </thead>
+          <tbody>
+            {page.rows.map((row, i) => (
+              <tr key={page.offset + i}>
+                {page.headers.map((_, column) => (
+                  <td key={column}>{row[column] ?? ''}</td>
+                ))}
+              </tr>
+            ))}
+          </tbody>
+        </table>
+      </div>
+      <div className="widget-table-footer">
+        <button
+          type="button"
+          disabled={!hasPrev}
+          onClick={() => setOffset(Math.max(0, page.offset - PAGE_SIZE))}
+        >
+          ‹ Prev
+        </button>
+        <span className="widget-table-range">{pageLabel(page)}</span>
+        <button
+          type="button"
+          disabled={!hasNext}
+          onClick={() => setOffset(page.offset + PAGE_SIZE)}
+        >
+          Next ›
+        </button>
+      </div>
+    </div>
+  );
+}
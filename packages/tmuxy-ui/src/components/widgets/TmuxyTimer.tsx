@@ -0,0 +1,63 @@
+import type { WidgetProps } from './index';
+
+const TIMER_PATTERN = /__TIMER__:(\d+):(\d+):(running|paused|done)/g;
+
+export type TimerState = 'running' | 'paused' | 'done';
+
+/** The newest `__TIMER__` frame in the timer's output. */
+export function parseTimer(
+  lines: string[],
+): { remaining: number; total: number; state: TimerState } | null {
+  let latest: { remaining: number; total: number; state: TimerState } | null = null;
+  for (const match of lines.join('\n').matchAll(TIMER_PATTERN)) {
+    latest = {
+      remaining: Number(match[1]),
+      total: Number(match[2]),
+      state: match[3] as TimerState,
+    };
+  }
+  return latest;
+}
+
+/** 90 → `1:30`, 5400 → `1:30:00`, as the timer script prints them. */
+export function formatClock(seconds: number): string {
+  const h = Math.floor(seconds / 3600);
+  const m = Math.floor((seconds % 3600) / 60);
+  const s = String(seconds % 60).padStart(2, '0');
+  return h > 0 ? `${h}:${String(m).padStart(2, '0')}:${s}` : `${m}:${s}`;
+}
+
+/**
+ * Timer widget — a large countdown (see `tmuxy timer`). The buttons type the
+ * timer's own keys into the pane, so they do what space/p and r do there.
+ */
+export function TmuxyTimer({ lines, writeStdin }: WidgetProps) {
+  const timer = parseTimer(lines);
+
+  if (!timer) {
+    return <div className="widget-timer-empty">Starting timer...</div>;
+  }
+
+  const { remaining, total, state } = timer;
+  const elapsed = total > 0 ? ((total - remaining) / total) * 100 : 100;
+  return (
+    <div className={`widget-timer widget-timer-${state}`}>
+      <div className="widget-timer-clock" role="timer" aria-live="off">
+        {state === 'done' ? "Time's up" : formatClock(remaining)}
+      </div>
+      <div className="widget-timer-progress">
+        <div className="widget-timer-progress-bar" style={{ width: `${elapsed}%` }} />
+      </div>
+      <div className="widget-timer-controls">
+        {state !== 'done' && (
+          <button type="button" onClick={() => writeStdin('p')}>
+            {state === 'paused' ? 'Resume' : 'Pause'}
+          </button>
+        )}
+        <button type="button" onClick={() => writeStdin('r')}>
+          Restart
+        </button>
+      </div>
+    </div>
+  );
+}
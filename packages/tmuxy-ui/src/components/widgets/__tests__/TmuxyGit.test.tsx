@@ -0,0 +1,80 @@
+import { describe, it, expect, afterEach } from 'vitest';
+import { render, screen, cleanup } from '@testing-library/react';
+import { TmuxyGit, parseGitStatus, headLabel } from '../TmuxyGit';
+import type { WidgetProps } from '../index';
+
+afterEach(cleanup);
+
+const status = {
+  root: '/src/app',
+  branch: 'main',
+  head: '1234567',
+  upstream: 'origin/main',
+  ahead: 2,
+  behind: 0,
+  staged: 1,
+  modified: 1,
+  untracked: 1,
+  conflicted: 0,
+  files: [
+    { path: 'src/lib.rs', status: 'M.' },
+    { path: 'README.md', status: '.M' },
+  ],
+};
+
+function widgetProps(data: unknown): WidgetProps {
+  return {
+    paneId: '%0',
+    widgetName: 'git',
+    lines: [],
+    lastLine: '',
+    rawContent: [],
+    writeStdin: () => {},
+    width: 40,
+    height: 10,
+    spec: { kind: 'git', source: '/src/app' },
+    data,
+  };
+}
+
+describe('parseGitStatus', () => {
+  it('tells no data apart from no repository', () => {
+    expect(parseGitStatus(undefined)).toBeUndefined();
+    expect(parseGitStatus(null)).toBeNull();
+    expect(parseGitStatus({ root: '/x' })).toBeUndefined();
+    expect(parseGitStatus(status)).toEqual(status);
+  });
+});
+
+describe('headLabel', () => {
+  it('falls back to the commit on a detached HEAD', () => {
+    expect(headLabel({ ...status, branch: null })).toBe('(1234567)');
+    expect(headLabel({ ...status, branch: null, head: '' })).toBe('(no commits)');
+  });
+});
+
+describe('TmuxyGit', () => {
+  it('shows the branch, counts and files', () => {
+    render(<TmuxyGit {...widgetProps(status)} />);
+    expect(screen.getByText('main')).toBeTruthy();
+    expect(screen.getByText('↑2')).toBeTruthy();
+    expect(screen.getByText('+1 staged')).toBeTruthy();
+    expect(screen.getByText('?1 untracked')).toBeTruthy();
+    // One untracked file beyond the listed ones.
+    expect(screen.getByText('…and 1 more')).toBeTruthy();
+  });
+
+  it('says when the directory is not in a repository', () => {
+    render(<TmuxyGit {...widgetProps(null)} />);
+    expect(screen.getByText('/src/app is not in a git repository')).toBeTruthy();
+  });
+
+  it('says clean when nothing changed', () => {
+    render(
+      <TmuxyGit
+        {...widgetProps({ ...status, staged: 0, modified: 0, untracked: 0, files: [] })}
+      />,
+    );
+    expect(screen.getByText('clean')).toBeTruthy();
+  });
+});
@@ -1,6 +1,6 @@
 import { describe, it, expect, afterEach, vi } from 'vitest';
 import { render, screen, waitFor, cleanup } from '@testing-library/react';
-import { TmuxyMarkdown } from '../TmuxyMarkdown';
+import { TmuxyMarkdown, splitMermaid } from '../TmuxyMarkdown';
 import type { WidgetProps } from '../index';
 
 function deferred<T>() {
@@ -50,3 +50,37 @@ describe('TmuxyMarkdown out-of-order fetch race', () => {
     expect(screen.getByText('NEWER')).toBeTruthy();
   });
 });
+
+describe('TmuxyMarkdown server rendering', () => {
+  it('renders the HTML from /api/markdown', async () => {
+    const fetchMock = vi.fn().mockResolvedValue({
+      ...fakeResponse('<h1>Rendered</h1><p>by the <code>server</code></p>'),
+      headers: new Headers({ 'content-type': 'text/html; charset=utf-8' }),
+    });
+    vi.stubGlobal('fetch', fetchMock);
+
+    render(<TmuxyMarkdown {...({ lines: linesForSeq('1') } as WidgetProps)} />);
+    await waitFor(() => expect(screen.getByRole('heading', { name: 'Rendered' })).toBeTruthy());
+    expect(fetchMock).toHaveBeenCalledWith('/api/markdown?path=%2Fdoc.md');
+  });
+
+  it('falls back to the raw file when the server cannot render it', async () => {
+    const fetchMock = vi
+      .fn()
+      .mockResolvedValueOnce({ ok: false, status: 404, statusText: 'Not Found' })
+      .mockResolvedValueOnce(fakeResponse('# Raw'));
+    vi.stubGlobal('fetch', fetchMock);
+
+    render(<TmuxyMarkdown {...({ lines: linesForSeq('1') } as WidgetProps)} />);
+    await waitFor(() => expect(screen.getByText('Raw')).toBeTruthy());
+    expect(fetchMock).toHaveBeenLastCalledWith('/api/file?path=%2Fdoc.md');
+  });
+});
+
+describe('splitMermaid', () => {
+  it('cuts mermaid fences out of the HTML', () => {
+    expect(
+      splitMermaid('<p>a</p>\n<pre class="tmuxy-mermaid">A--&gt;B &amp; C\n</pre>\n<p>b</p>'),
+    ).toEqual([{ html: '<p>a</p>\n' }, { chart: 'A-->B & C' }, { html: '\n<p>b</p>' }]);
+  });
+});
@@ -0,0 +1,64 @@
+import { describe, it, expect, afterEach } from 'vitest';
+import { render, screen, cleanup } from '@testing-library/react';
+import { TmuxyMetric, parseMetric, sparkline } from '../TmuxyMetric';
+import type { WidgetProps } from '../index';
+
+afterEach(cleanup);
+
+function widgetProps(lines: string[]): WidgetProps {
+  return {
+    paneId: '%0',
+    widgetName: 'metric',
+    lines,
+    lastLine: lines[lines.length - 1] ?? '',
+    rawContent: [],
+    writeStdin: () => {},
+    width: 40,
+    height: 6,
+  };
+}
+
+describe('parseMetric', () => {
+  it('takes the newest buffer, even when it wrapped across rows', () => {
+    const lines = [
+      '__TITLE__:uptime',
+      '▄  now 1  min 1  max 1',
+      '__SAMPLES__:1',
+      '▁█  now 2.5  min 1  max 2.5',
+      '__SAMPLES__:1,2',
+      '.5,-3',
+    ];
+    expect(parseMetric(lines)).toEqual({ samples: [1, 2.5, -3], error: null });
+  });
+
+  it('reports an error only when it is newer than the samples', () => {
+    expect(parseMetric(['__SAMPLES__:4', '__ERROR__:no number in output: nope']).error).toBe(
+      'no number in output: nope',
+    );
+    expect(parseMetric(['__ERROR__:boom', '__SAMPLES__:4']).error).toBeNull();
+  });
+});
+
+describe('sparkline', () => {
+  it('scales the shown samples to their range', () => {
+    expect(sparkline([0, 7, 14], 10)).toBe('▁▅█');
+    expect(sparkline([5, 5], 10)).toBe('▄▄');
+  });
+
+  it('keeps only the newest samples that fit', () => {
+    expect(sparkline([100, 0, 1], 2)).toBe('▁█');
+  });
+});
+
+describe('TmuxyMetric', () => {
+  it('shows the current value with min and max', () => {
+    render(<TmuxyMetric {...widgetProps(['__SAMPLES__:3,9,4.25'])} />);
+    expect(screen.getByText('4.25')).toBeTruthy();
+    expect(screen.getByText('min 3 · max 9')).toBeTruthy();
+  });
+
+  it('waits for the first sample', () => {
+    render(<TmuxyMetric {...widgetProps(['__TITLE__:uptime'])} />);
+    expect(screen.getByText('Waiting for samples...')).toBeTruthy();
+  });
+});
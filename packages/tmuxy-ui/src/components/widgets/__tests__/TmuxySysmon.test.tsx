@@ -0,0 +1,68 @@
+import { describe, it, expect, afterEach } from 'vitest';
+import { render, screen, cleanup } from '@testing-library/react';
+import { TmuxySysmon, parseSysmon, formatBytes } from '../TmuxySysmon';
+import type { WidgetProps } from '../index';
+
+afterEach(cleanup);
+
+const GIB = 1024 ** 3;
+
+const sample = {
+  cpu: 12.4,
+  cpus: [10, 14.8],
+  memory: { used: 3 * GIB, total: 16 * GIB },
+  swap: { used: 0, total: 0 },
+  disks: [{ mount: '/', used: 48 * GIB, total: 100 * GIB }],
+  network: { rx: 1536, tx: 12 },
+};
+
+function widgetProps(data: unknown): WidgetProps {
+  return {
+    paneId: '%0',
+    widgetName: 'sysmon',
+    lines: [],
+    lastLine: '',
+    rawContent: [],
+    writeStdin: () => {},
+    width: 40,
+    height: 10,
+    data,
+  };
+}
+
+describe('parseSysmon', () => {
+  it('accepts a sampler sample', () => {
+    expect(parseSysmon(sample)).toEqual(sample);
+  });
+
+  it('rejects data that is not a sample', () => {
+    expect(parseSysmon(undefined)).toBeNull();
+    expect(parseSysmon({ cpu: 'high' })).toBeNull();
+    expect(parseSysmon({ ...sample, memory: null })).toBeNull();
+  });
+});
+
+describe('formatBytes', () => {
+  it('uses binary units with one decimal', () => {
+    expect(formatBytes(12)).toBe('12B');
+    expect(formatBytes(1536)).toBe('1.5K');
+    expect(formatBytes(16 * GIB)).toBe('16.0G');
+  });
+});
+
+describe('TmuxySysmon', () => {
+  it('draws cpu, memory, disks and network', () => {
+    render(<TmuxySysmon {...widgetProps(sample)} />);
+    expect(screen.getByText('12%')).toBeTruthy();
+    expect(screen.getByText('3.0G / 16.0G')).toBeTruthy();
+    expect(screen.getByText('48.0G / 100.0G')).toBeTruthy();
+    expect(screen.getByText('↓ 1.5K/s · ↑ 12B/s')).toBeTruthy();
+    // No swap configured, no swap row.
+    expect(screen.queryByText('swap')).toBeNull();
+  });
+
+  it('waits for the first sample', () => {
+    render(<TmuxySysmon {...widgetProps(undefined)} />);
+    expect(screen.getByText('Waiting for samples...')).toBeTruthy();
+  });
+});
@@ -0,0 +1,92 @@
+import { describe, it, expect, afterEach, vi } from 'vitest';
+import { render, screen, cleanup, fireEvent, waitFor } from '@testing-library/react';
+import { TmuxyTable, nextSort, pageLabel, PAGE_SIZE, type TablePage } from '../TmuxyTable';
+import type { WidgetProps } from '../index';
+
+afterEach(cleanup);
+
+const page: TablePage = {
+  headers: ['name', 'qty'],
+  rows: [
+    ['apple', '3'],
+    ['pear', '10'],
+  ],
+  offset: 0,
+  total_rows: 250,
+  delimiter: ',',
+};
+
+function widgetProps(invoke: WidgetProps['invoke']): WidgetProps {
+  return {
+    paneId: '%0',
+    widgetName: 'table',
+    lines: [],
+    lastLine: '',
+    rawContent: [],
+    writeStdin: () => {},
+    width: 40,
+    height: 10,
+    spec: { kind: 'table', source: '/data/fruit.csv' },
+    invoke,
+  };
+}
+
+describe('nextSort', () => {
+  it('cycles a column through ascending, descending and unsorted', () => {
+    const asc = nextSort(null, 1);
+    expect(asc).toEqual({ column: 1, descending: false });
+    const desc = nextSort(asc, 1);
+    expect(desc).toEqual({ column: 1, descending: true });
+    expect(nextSort(desc, 1)).toBeNull();
+    expect(nextSort(desc, 0)).toEqual({ column: 0, descending: false });
+  });
+});
+
+describe('pageLabel', () => {
+  it('shows the rows on the page and the total', () => {
+    expect(pageLabel({ ...page, offset: 100 })).toBe('101–102 of 250');
+    expect(pageLabel({ ...page, rows: [], total_rows: 0 })).toBe('no rows');
+  });
+});
+
+describe('TmuxyTable', () => {
+  it('queries the file and re-queries when a header is clicked', async () => {
+    const invoke = vi.fn().mockResolvedValue(page);
+    render(<TmuxyTable {...widgetProps(invoke as WidgetProps['invoke'])} />);
+
+    expect(await screen.findByText('pear')).toBeTruthy();
+    expect(invoke).toHaveBeenCalledWith('table_query', {
+      query: { path: '/data/fruit.csv', offset: 0, limit: PAGE_SIZE, sort: null },
+    });
+
+    fireEvent.click(screen.getByText('qty'));
+    await waitFor(() =>
+      expect(invoke).toHaveBeenLastCalledWith('table_query', {
+        query: {
+          path: '/data/fruit.csv',
+          offset: 0,
+          limit: PAGE_SIZE,
+          sort: { column: 1, descending: false },
+        },
+      }),
+    );
+  });
+
+  it('pages forward with Next', async () => {
+    const invoke = vi.fn().mockResolvedValue(page);
+    render(<TmuxyTable {...widgetProps(invoke as WidgetProps['invoke'])} />);
+
+    fireEvent.click(await screen.findByText('Next ›'));
+    await waitFor(() =>
+      expect(invoke).toHaveBeenLastCalledWith('table_query', {
+        query: { path: '/data/fruit.csv', offset: PAGE_SIZE, limit: PAGE_SIZE, sort: null },
+      }),
+    );
+  });
+
+  it('shows a failed query', async () => {
+    const invoke = vi.fn().mockRejectedValue('file not found');
+    render(<TmuxyTable {...widgetProps(invoke as WidgetProps['invoke'])} />);
+    expect(await screen.findByText('Error: file not found')).toBeTruthy();
+  });
+});
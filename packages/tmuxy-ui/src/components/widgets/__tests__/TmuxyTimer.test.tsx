@@ -0,0 +1,64 @@
+import { describe, it, expect, afterEach, vi } from 'vitest';
+import { render, screen, cleanup, fireEvent } from '@testing-library/react';
+import { TmuxyTimer, parseTimer, formatClock } from '../TmuxyTimer';
+import type { WidgetProps } from '../index';
+
+afterEach(cleanup);
+
+function widgetProps(lines: string[], writeStdin = () => {}): WidgetProps {
+  return {
+    paneId: '%0',
+    widgetName: 'timer',
+    lines,
+    lastLine: lines[lines.length - 1] ?? '',
+    rawContent: [],
+    writeStdin,
+    width: 40,
+    height: 10,
+  };
+}
+
+describe('parseTimer', () => {
+  it('takes the newest frame', () => {
+    const lines = [
+      '__TITLE__:focus',
+      'focus  25:00  running',
+      '__TIMER__:1500:1500:running',
+      'focus  24:59  paused',
+      '__TIMER__:1499:1500:paused',
+    ];
+    expect(parseTimer(lines)).toEqual({ remaining: 1499, total: 1500, state: 'paused' });
+  });
+
+  it('is null before the first frame', () => {
+    expect(parseTimer(['__TITLE__:focus'])).toBeNull();
+  });
+});
+
+describe('formatClock', () => {
+  it('matches the script', () => {
+    expect(formatClock(90)).toBe('1:30');
+    expect(formatClock(5)).toBe('0:05');
+    expect(formatClock(5400)).toBe('1:30:00');
+  });
+});
+
+describe('TmuxyTimer', () => {
+  it('types the pause and restart keys into the pane', () => {
+    const writeStdin = vi.fn();
+    render(<TmuxyTimer {...widgetProps(['__TIMER__:61:1500:running'], writeStdin)} />);
+    expect(screen.getByText('1:01')).toBeTruthy();
+    fireEvent.click(screen.getByText('Pause'));
+    fireEvent.click(screen.getByText('Restart'));
+    expect(writeStdin.mock.calls).toEqual([['p'], ['r']]);
+  });
+
+  it('offers resume while paused and only restart once done', () => {
+    render(<TmuxyTimer {...widgetProps(['__TIMER__:61:1500:paused'])} />);
+    expect(screen.getByText('Resume')).toBeTruthy();
+    cleanup();
+    render(<TmuxyTimer {...widgetProps(['__TIMER__:0:1500:done'])} />);
+    expect(screen.getByText("Time's up")).toBeTruthy();
+    expect(screen.queryByText('Pause')).toBeNull();
+  });
+});
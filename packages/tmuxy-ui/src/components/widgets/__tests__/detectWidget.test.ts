@@ -0,0 +1,31 @@
+import { describe, it, expect } from 'vitest';
+import { detectWidget, registerWidget } from '../index';
+import type { PaneContent } from '../../../tmux/types';
+
+registerWidget('probe', () => null);
+
+const content = (...lines: string[]): PaneContent =>
+  lines.map((line) => [...line].map((c) => ({ c })));
+
+describe('detectWidget', () => {
+  it('falls back to the marker line', () => {
+    const info = detectWidget(content('$ tmuxy widget probe', '__TMUXY_WIDGET__:probe', 'body'));
+    expect(info).toEqual({ widgetName: 'probe', contentLines: ['body'] });
+  });
+
+  it('prefers the pane spec over the output', () => {
+    // The marker may have scrolled away or not been printed yet.
+    expect(detectWidget(content('body'), { kind: 'probe', source: 'x' })).toEqual({
+      widgetName: 'probe',
+      contentLines: ['body'],
+    });
+    expect(
+      detectWidget(content('__TMUXY_WIDGET__:probe', 'body'), { kind: 'probe' })?.contentLines,
+    ).toEqual(['body']);
+  });
+
+  it('ignores specs for unregistered widget types', () => {
+    expect(detectWidget(content('body'), { kind: 'chart' })).toBeNull();
+    expect(detectWidget(content('body'), null)).toBeNull();
+  });
+});
@@ -1,5 +1,5 @@
 import type { ComponentType } from 'react';
-import type { PaneContent } from '../../tmux/types';
+import type { PaneContent, WidgetSpec } from '../../tmux/types';
 
 export interface WidgetProps {
   paneId: string;
@@ -10,6 +10,12 @@ export interface WidgetProps {
   writeStdin: (data: string) => void;
   width: number;
   height: number;
+  /** The pane's `@tmuxy-widget` spec, when its launcher set one */
+  spec?: WidgetSpec;
+  /** Latest payload the widget process sent on the OSC 7331 data channel */
+  data?: unknown;
+  /** Call a backend command (e.g. `table_query`) and await its result */
+  invoke?: <T>(cmd: string, args?: Record<string, unknown>) => Promise<T>;
 }
 
 // Registry of widget name -> component
@@ -26,9 +32,26 @@ export function getWidget(name: string): ComponentType<WidgetProps> | undefined
 // Detect widget marker from CellLine[]
 const WIDGET_MARKER_PREFIX = '__TMUXY_WIDGET__:';
 
+/**
+ * Classify a pane as a widget. The pane's `@tmuxy-widget` spec decides when
+ * present; the marker line is the fallback for panes whose launcher predates
+ * it (or runs where it can't reach tmux). Either way the content lines are
+ * what follows the marker.
+ */
 export function detectWidget(
   content: PaneContent,
+  spec?: WidgetSpec | null,
 ): { widgetName: string; contentLines: string[] } | null {
+  if (spec && widgetRegistry[spec.kind]) {
+    const text = content.map((line) =>
+      line
+        .map((cell) => cell.c)
+        .join('')
+        .trimEnd(),
+    );
+    const marker = text.findIndex((line) => line.trim().startsWith(WIDGET_MARKER_PREFIX));
+    return { widgetName: spec.kind, contentLines: text.slice(marker + 1) };
+  }
   if (content.length === 0) return null;
 
   // Scan all lines for the marker (it may not be at line 0 if run from a shell)
@@ -1,6 +1,16 @@
 import { registerWidget } from './index';
+import { TmuxyGit } from './TmuxyGit';
 import { TmuxyImage } from './TmuxyImage';
 import { TmuxyMarkdown } from './TmuxyMarkdown';
+import { TmuxyMetric } from './TmuxyMetric';
+import { TmuxySysmon } from './TmuxySysmon';
+import { TmuxyTable } from './TmuxyTable';
+import { TmuxyTimer } from './TmuxyTimer';
 
+registerWidget('git', TmuxyGit);
 registerWidget('image', TmuxyImage);
 registerWidget('markdown', TmuxyMarkdown);
+registerWidget('metric', TmuxyMetric);
+registerWidget('sysmon', TmuxySysmon);
+registerWidget('table', TmuxyTable);
+registerWidget('timer', TmuxyTimer);
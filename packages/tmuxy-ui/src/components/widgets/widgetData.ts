@@ -0,0 +1,33 @@
+/**
+ * Latest widget-data payload per pane.
+ *
+ * Widget processes send structured state as `OSC 7331 ; <json> BEL`; the
+ * backend strips it from the pane's output and forwards it as a
+ * `widget-data` event. Only the newest payload per pane is kept — a widget
+ * that wants history sends it.
+ */
+
+import { useCallback, useSyncExternalStore } from 'react';
+
+const latest = new Map<string, unknown>();
+const listeners = new Set<() => void>();
+
+export function setWidgetData(paneId: string, data: unknown): void {
+  latest.set(paneId, data);
+  listeners.forEach((listener) => listener());
+}
+
+export function getWidgetData(paneId: string): unknown {
+  return latest.get(paneId);
+}
+
+function subscribe(listener: () => void): () => void {
+  listeners.add(listener);
+  return () => listeners.delete(listener);
+}
+
+/** Re-renders when `paneId`'s payload changes. */
+export function useWidgetData(paneId: string): unknown {
+  const getSnapshot = useCallback(() => latest.get(paneId), [paneId]);
+  return useSyncExternalStore(subscribe, getSnapshot, getSnapshot);
+}
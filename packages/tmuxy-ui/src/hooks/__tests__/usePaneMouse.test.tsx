@@ -1,9 +1,11 @@
-import { describe, it, expect } from 'vitest';
+import { describe, it, expect, vi } from 'vitest';
 import { renderHook } from '@testing-library/react';
 import { createRef } from 'react';
 import { usePaneMouse } from '../usePaneMouse';
 import type { AppMachineEvent } from '../../machines/types';
 
+vi.mock('../../utils/haptics', () => ({ haptics: { trigger: () => {} } }));
+
 interface SetupOptions {
   alternateOn?: boolean;
   mouseAnyFlag?: boolean;
@@ -58,11 +60,12 @@ describe('usePaneMouse.handleWheel', () => {
     result.current.handleWheel(wheelEvent(-100));
     const enterCopy = events.find((e) => e.type === 'ENTER_COPY_MODE');
     expect(enterCopy).toBeUndefined();
-    // Should have sent Up arrow keys instead
-    const sendKeys = events.filter(
-      (e) => e.type === 'SEND_COMMAND' && (e as { command: string }).command.includes('Up'),
-    );
-    expect(sendKeys.length).toBeGreaterThan(0);
+    // The wheel goes to the server, which turns it into Up arrow keys
+    expect(events).toContainEqual({
+      type: 'SEND_MOUSE_EVENTS',
+      paneId: '%1',
+      events: [{ kind: 'wheel', lines: -5, x: 0, y: 0 }],
+    });
   });
 
   it('does NOT enter copy mode when mouseAnyFlag is true (nvim with mouse=a)', () => {
@@ -70,14 +73,9 @@ describe('usePaneMouse.handleWheel', () => {
     result.current.handleWheel(wheelEvent(-100));
     const enterCopy = events.find((e) => e.type === 'ENTER_COPY_MODE');
     expect(enterCopy).toBeUndefined();
-    // Should have sent SGR wheel-up events: button 64, injected as raw hex
-    // keys ("1b 5b 3c 36 34" = ESC [ < 6 4).
-    const sgrEvents = events.filter(
-      (e) =>
-        e.type === 'SEND_COMMAND' &&
-        /send-keys -t \S+ -H 1b 5b 3c 36 34/.test((e as { command: string }).command),
-    );
-    expect(sgrEvents.length).toBeGreaterThan(0);
+    // Raw wheel events, never SGR built client-side
+    expect(events.filter((e) => e.type === 'SEND_MOUSE_EVENTS')).toHaveLength(1);
+    expect(events.some((e) => e.type === 'SEND_COMMAND')).toBe(false);
   });
 
   it('does NOT enter copy mode when only mouseAnyFlag is true (apps without alt screen)', () => {
@@ -140,3 +138,30 @@ describe('usePaneMouse.handleWheel', () => {
     expect(events.every((e) => e.type !== 'ENTER_COPY_MODE')).toBe(true);
   });
 });
+
+describe('usePaneMouse mouse tracking', () => {
+  it('forwards clicks as raw events for the server to encode', () => {
+    const { result, events } = setup({ mouseAnyFlag: true });
+    const click = {
+      target: document.createElement('div'),
+      button: 0,
+      shiftKey: false,
+      clientX: 17,
+      clientY: 40,
+    } as unknown as React.MouseEvent;
+    result.current.handleMouseDown(click);
+    result.current.handleMouseUp(click);
+    expect(events.filter((e) => e.type === 'SEND_MOUSE_EVENTS')).toEqual([
+      {
+        type: 'SEND_MOUSE_EVENTS',
+        paneId: '%1',
+        events: [{ kind: 'press', button: 0, x: 2, y: 2 }],
+      },
+      {
+        type: 'SEND_MOUSE_EVENTS',
+        paneId: '%1',
+        events: [{ kind: 'release', button: 0, x: 2, y: 2 }],
+      },
+    ]);
+  });
+});
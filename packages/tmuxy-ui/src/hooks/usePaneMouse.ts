@@ -2,16 +2,19 @@
  * usePaneMouse - Mouse event handler for panes
  *
  * Handles mouse clicks, drags, and wheel events based on pane state:
- * - When mouse_any_flag is true: forward mouse events as SGR sequences to tmux
+ * - When mouse_any_flag is true: forward raw mouse events (`send_mouse_events`)
  * - When mouse_any_flag is false: mouse drag enters client-side copy mode with selection
- * - When alternate_on is true: wheel events send arrow keys
+ * - When alternate_on is true: forward wheel events (`send_mouse_events`)
  * - When not in alternate mode: wheel scroll enters client-side copy mode
  * - Shift+click always focuses the pane regardless of mouse mode
+ *
+ * Forwarded events are routed by the server (tmuxy-core `mouse.rs`) from the
+ * pane's live flags: SGR reports, arrow keys, or copy-mode scrolling.
  */
 
 import { useCallback, useRef, useState, useEffect, type RefObject } from 'react';
 import type { AppMachineEvent } from '../machines/types';
-import { sendScrollLines, sgrMouseCommand } from './scrollUtils';
+import type { PaneMouseEvent } from '../tmux/types';
 import { haptics } from '../utils/haptics';
 
 interface UsePaneMouseOptions {
@@ -67,6 +70,11 @@ export function usePaneMouse(send: (event: AppMachineEvent) => void, options: Us
   // Track mouse button state for drag events
   const mouseButtonRef = useRef<number | null>(null);
 
+  const sendMouse = useCallback(
+    (event: PaneMouseEvent) => send({ type: 'SEND_MOUSE_EVENTS', paneId, events: [event] }),
+    [send, paneId],
+  );
+
   // Track mouse drag state for copy-mode selection
   const dragStartRef = useRef<{ x: number; y: number } | null>(null);
   const lastCellRef = useRef<{ x: number; y: number } | null>(null);
@@ -200,11 +208,7 @@ export function usePaneMouse(send: (event: AppMachineEvent) => void, options: Us
         const cell = pixelToCell(e);
         mouseButtonRef.current = e.button;
 
-        // Send SGR mouse press event
-        send({
-          type: 'SEND_COMMAND',
-          command: sgrMouseCommand(paneId, e.button, cell.x + 1, Math.max(1, cell.y + 1)),
-        });
+        sendMouse({ kind: 'press', button: e.button, x: cell.x, y: Math.max(0, cell.y) });
         return;
       }
 
@@ -233,7 +237,7 @@ export function usePaneMouse(send: (event: AppMachineEvent) => void, options: Us
         document.addEventListener('mouseup', cleanupDrag);
       }
     },
-    [send, paneId, mouseAnyFlag, alternateOn, pixelToCell, cleanupDrag],
+    [send, paneId, mouseAnyFlag, alternateOn, pixelToCell, cleanupDrag, sendMouse],
   );
 
   // Handle mouse up
@@ -244,16 +248,11 @@ export function usePaneMouse(send: (event: AppMachineEvent) => void, options: Us
       if (mouseAnyFlag) {
         const cell = pixelToCell(e);
 
-        // Send SGR mouse release event (lowercase 'm')
-        send({
-          type: 'SEND_COMMAND',
-          command: sgrMouseCommand(
-            paneId,
-            mouseButtonRef.current,
-            cell.x + 1,
-            Math.max(1, cell.y + 1),
-            true,
-          ),
+        sendMouse({
+          kind: 'release',
+          button: mouseButtonRef.current,
+          x: cell.x,
+          y: Math.max(0, cell.y),
         });
         mouseButtonRef.current = null;
         return;
@@ -278,7 +277,7 @@ export function usePaneMouse(send: (event: AppMachineEvent) => void, options: Us
       // Clean up drag state (also removes document mouseup listener)
       cleanupDrag();
     },
-    [send, paneId, mouseAnyFlag, copyModeActive, pixelToCell, cleanupDrag],
+    [send, paneId, mouseAnyFlag, copyModeActive, pixelToCell, cleanupDrag, sendMouse],
   );
 
   // Handle mouse move (for drag)
@@ -286,13 +285,14 @@ export function usePaneMouse(send: (event: AppMachineEvent) => void, options: Us
     (e: React.MouseEvent) => {
       if (mouseButtonRef.current === null) return;
 
-      // Mouse tracking mode: forward SGR drag events
+      // Mouse tracking mode: forward drags (the server coalesces bursts)
       if (mouseAnyFlag) {
         const cell = pixelToCell(e);
-        const dragButton = mouseButtonRef.current + 32;
-        send({
-          type: 'SEND_COMMAND',
-          command: sgrMouseCommand(paneId, dragButton, cell.x + 1, Math.max(1, cell.y + 1)),
+        sendMouse({
+          kind: 'drag',
+          button: mouseButtonRef.current,
+          x: cell.x,
+          y: Math.max(0, cell.y),
         });
         return;
       }
@@ -374,6 +374,7 @@ export function usePaneMouse(send: (event: AppMachineEvent) => void, options: Us
       options.paneHeight,
       startAutoScroll,
       stopAutoScroll,
+      sendMouse,
     ],
   );
 
@@ -431,16 +432,8 @@ export function usePaneMouse(send: (event: AppMachineEvent) => void, options: Us
         if (lines === 0) return;
         wheelRemainder.current -= lines * charHeight;
 
-        const cell = mouseAnyFlag ? pixelToCell(e as unknown as React.MouseEvent) : { x: 0, y: 0 };
-        sendScrollLines({
-          send,
-          paneId,
-          lines,
-          alternateOn,
-          mouseAnyFlag,
-          cellX: cell.x,
-          cellY: cell.y,
-        });
+        const cell = pixelToCell(e as unknown as React.MouseEvent);
+        sendMouse({ kind: 'wheel', lines, x: cell.x, y: Math.max(0, cell.y) });
         return;
       }
 
@@ -489,6 +482,7 @@ export function usePaneMouse(send: (event: AppMachineEvent) => void, options: Us
       scrollRef,
       historySize,
       forwardScrollToParent,
+      sendMouse,
     ],
   );
 
@@ -4,15 +4,14 @@
  * Provides native-feeling touch scrolling for terminal panes on mobile:
  * - Follows the finger with pixel-to-line accumulation
  * - Momentum/inertia scrolling after finger lifts
- * - Routes through the same three scroll modes as wheel events:
- *   1. Alternate screen (vim, less) → arrow keys
- *   2. Mouse tracking → SGR wheel events
- *   3. Normal shell → proxy to scroll container (enters copy mode)
+ * - Routes like wheel events: alternate-screen and mouse-tracking panes get
+ *   wheel events via `send_mouse_events` (the server turns them into arrow
+ *   keys or SGR reports); a normal shell proxies to the scroll container
+ *   (enters copy mode)
  */
 
 import { useCallback, useEffect, useRef, type RefObject } from 'react';
 import type { AppMachineEvent } from '../machines/types';
-import { sendScrollLines } from './scrollUtils';
 import { focusMobileInput } from '../utils/mobileKeyboard';
 import { haptics } from '../utils/haptics';
 
@@ -98,12 +97,10 @@ export function usePaneTouch(options: UsePaneTouchOptions) {
         if (lines === 0) return;
         remainderRef.current -= lines * charHeight;
 
-        sendScrollLines({
-          send,
+        send({
+          type: 'SEND_MOUSE_EVENTS',
           paneId,
-          lines,
-          alternateOn,
-          mouseAnyFlag,
+          events: [{ kind: 'wheel', lines, x: 0, y: 0 }],
         });
       } else {
         // Normal mode: proxy pixel delta to scroll container.
@@ -7,6 +7,7 @@
  * - useAppState('stateName') - check if machine is in a specific state
  * - useIsDragging() - check if drag is in progress
  * - useIsResizing() - check if resize is in progress
+ * - useAdapterInvoke() - call a backend command and await its result
  */
 
 import { createContext, useContext, useMemo, type ReactNode } from 'react';
@@ -90,6 +91,11 @@ export {
   selectKeyBindings,
   selectCommandMode,
   selectStatusMessage,
+  selectActiveWindowStatus,
+  selectStatusSegments,
+  selectStatusLines,
+  selectStatusPosition,
+  selectSessionGroup,
   selectPrefixActive,
   selectActivePaneCopyMode,
   selectThemeName,
@@ -103,6 +109,9 @@ export {
 
 const AppContext = createContext<AppMachineActor | null>(null);
 
+/** The adapter, for components that query the backend rather than drive tmux */
+const AdapterContext = createContext<TmuxAdapter | null>(null);
+
 /**
  * Measure char width from rendered monospace font.
  */
@@ -140,6 +149,7 @@ export function AppProvider({
     const adapter = externalAdapter ?? createAdapter();
     const store = Effect.runSync(makeTmuxStore({ adapter: toEffectAdapter(adapter) }));
     return {
+      adapter,
       tmuxActor: createTmuxActor(adapter),
       tmuxStoreActor: createTmuxStoreActor(store),
       keyboardActor: createKeyboardActor(),
@@ -148,9 +158,10 @@ export function AppProvider({
     };
   }, []);
 
+  const { adapter, ...machineActors } = actors;
   const actorRef = useActorRef(
     appMachine.provide({
-      actors,
+      actors: machineActors,
     }),
   );
 
@@ -183,7 +194,9 @@ export function AppProvider({
 
   return (
     <AppConfigContext.Provider value={config ?? {}}>
-      <AppContext.Provider value={actorRef}>{children}</AppContext.Provider>
+      <AdapterContext.Provider value={adapter}>
+        <AppContext.Provider value={actorRef}>{children}</AppContext.Provider>
+      </AdapterContext.Provider>
     </AppConfigContext.Provider>
   );
 }
@@ -312,6 +325,17 @@ export function useCopyModeState(paneId: string): CopyModeState | undefined {
   return useSelector(actor, (snapshot) => snapshot.context.copyModeStates[paneId]);
 }
 
+/**
+ * Call a backend command and await its result. For read-only queries (a
+ * widget paging through a file, a pane export); anything that changes tmux
+ * state goes through the machine with useAppSend().
+ */
+export function useAdapterInvoke(): <T>(cmd: string, args?: Record<string, unknown>) => Promise<T> {
+  const adapter = useContext(AdapterContext);
+  if (!adapter) throw new Error('useAdapterInvoke must be used within AppProvider');
+  return useMemo(() => adapter.invoke.bind(adapter), [adapter]);
+}
+
 /** Get the app config flags */
 export function useAppConfig(): AppConfig {
   return useContext(AppConfigContext);
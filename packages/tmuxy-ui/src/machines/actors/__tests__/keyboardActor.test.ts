@@ -157,3 +157,79 @@ describe('keyboardActor — active-pane target uses the live snapshot', () => {
     expect(lastSendCommand(events)).toBe("send-keys -t %7 -l 'b'");
   });
 });
+
+describe('keyboardActor — extended keyboard protocols', () => {
+  function spawnWithPanes(panes: Array<{ tmuxId: string; extendedKeys?: boolean }>) {
+    const events: Array<{ type: string; [k: string]: unknown }> = [];
+    const keyboardActor = createKeyboardActor();
+    const parent = createMachine({
+      types: {} as {
+        context: {
+          activePaneId: string;
+          copyModeStates: Record<string, unknown>;
+          panes: typeof panes;
+        };
+        events: { type: string; [k: string]: unknown };
+      },
+      context: { activePaneId: '%3', copyModeStates: {}, panes },
+      invoke: {
+        id: 'keyboard',
+        src: 'keyboardActor',
+        input: ({ self }: { self: AnyActorRef }) => ({ parent: self }),
+      },
+      on: {
+        '*': {
+          actions: ({ event }) => {
+            events.push(event as { type: string; [k: string]: unknown });
+          },
+        },
+      },
+    }).provide({ actors: { keyboardActor }, actions: {} } as never);
+    const actor = createActor(parent);
+    actor.start();
+    return { actor, events };
+  }
+
+  it('sends the raw key when the pane asked for an extended protocol', () => {
+    const { actor, events } = spawnWithPanes([{ tmuxId: '%3', extendedKeys: true }]);
+    pressKey({ key: 'A', code: 'KeyA', ctrlKey: true, shiftKey: true });
+    expect(events.find((e) => e.type === 'SEND_KEY')).toEqual({
+      type: 'SEND_KEY',
+      paneId: '%3',
+      key: {
+        key: 'A',
+        code: 'KeyA',
+        ctrl: true,
+        alt: false,
+        shift: true,
+        meta: false,
+        repeat: false,
+      },
+    });
+    expect(lastSendCommand(events)).toBeUndefined();
+    actor.stop();
+  });
+
+  it('keeps tmux key names for legacy panes', () => {
+    const { actor, events } = spawnWithPanes([{ tmuxId: '%3', extendedKeys: false }]);
+    pressKey({ key: 'Enter', code: 'Enter', ctrlKey: true });
+    expect(events.some((e) => e.type === 'SEND_KEY')).toBe(false);
+    expect(lastSendCommand(events)).toBe('send-keys -t %3 C-Enter');
+    actor.stop();
+  });
+});
+
+describe('keyboardActor — IME composition', () => {
+  it('sends the committed text as send_text, not through send-keys', () => {
+    const { actor, events } = spawnKeyboardActor('%3');
+    window.dispatchEvent(new CompositionEvent('compositionstart'));
+    window.dispatchEvent(new CompositionEvent('compositionend', { data: '日本語' }));
+    expect(events.find((e) => e.type === 'SEND_TEXT')).toEqual({
+      type: 'SEND_TEXT',
+      paneId: '%3',
+      text: '日本語',
+    });
+    expect(lastSendCommand(events)).toBeUndefined();
+    actor.stop();
+  });
+});
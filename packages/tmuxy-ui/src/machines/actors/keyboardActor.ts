@@ -153,16 +153,13 @@ export function createKeyboardActor() {
     // Mobile keyboard: forward typed characters to the active tmux session.
     // keydown handles special keys (Backspace, Enter, arrows) via the existing
     // window listener; this handles printable chars that mobile browsers only
-    // deliver via `input` events.
+    // deliver via `input` events. Sent as `send_text`, like IME commits, so
+    // autocorrected words and emoji arrive intact.
     const cleanupMobileKeyboard = isTouchDevice()
       ? setupMobileKeyboard((text) => {
           if (!enabled) return;
-          const escaped = escapeLiteralText(text);
           const mobileTarget = focusedFloatPaneId ?? realPaneId(activePaneId) ?? sessionName;
-          input.parent.send({
-            type: 'SEND_TMUX_COMMAND',
-            command: `send-keys -t ${mobileTarget} -l ${escaped}`,
-          });
+          input.parent.send({ type: 'SEND_TEXT', paneId: mobileTarget, text });
         })
       : null;
 
@@ -260,13 +257,19 @@ export function createKeyboardActor() {
       // remains the fallback if the read ever throws.
       let liveActivePaneId = activePaneId;
       let liveCopyStates: Record<string, CopyModeState> | undefined;
+      let livePanes: Array<{ tmuxId: string; extendedKeys?: boolean }> | undefined;
       try {
         const snapshot = input.parent.getSnapshot() as {
-          context?: { activePaneId?: string; copyModeStates?: Record<string, CopyModeState> };
+          context?: {
+            activePaneId?: string;
+            copyModeStates?: Record<string, CopyModeState>;
+            panes?: Array<{ tmuxId: string; extendedKeys?: boolean }>;
+          };
         };
         const ctx = snapshot?.context;
         if (ctx?.activePaneId !== undefined) liveActivePaneId = ctx.activePaneId;
         liveCopyStates = ctx?.copyModeStates;
+        livePanes = ctx?.panes;
       } catch (_) {
         /* keep the cached closure values */
       }
@@ -511,6 +514,36 @@ export function createKeyboardActor() {
       // Using activePaneId ensures input reaches the correct pane immediately
       // after an optimistic tab switch (before tmux processes select-window).
       const target = focusedFloatPaneId ?? realPaneId(liveActivePaneId) ?? sessionName;
+
+      // The pane's application asked for modifyOtherKeys or the kitty keyboard
+      // protocol: send the raw key and let the server encode it, since a tmux
+      // key name can't carry chords like Ctrl+Shift+A or Ctrl+Enter.
+      const extendedKeys = livePanes?.some((p) => p.tmuxId === target && p.extendedKeys);
+      if (extendedKeys && !MACOS_OPTION_KEY_MAP[event.key]) {
+        input.parent.send({
+          type: 'SEND_KEY',
+          paneId: target,
+          key: {
+            key: event.key,
+            code: event.code,
+            ctrl: event.ctrlKey,
+            alt: event.altKey,
+            shift: event.shiftKey,
+            meta: event.metaKey,
+            repeat: event.repeat,
+          },
+        });
+        input.parent.send({
+          type: 'KEY_PRESS',
+          key: event.key,
+          ctrlKey: event.ctrlKey,
+          altKey: event.altKey,
+          shiftKey: event.shiftKey,
+          metaKey: event.metaKey,
+        });
+        return;
+      }
+
       // Use literal mode (-l) for single printable chars to avoid tmux syntax interpretation
       let command: string;
       if (event.key.length === 1 && !event.ctrlKey && !event.altKey && !event.metaKey) {
@@ -541,14 +574,15 @@ export function createKeyboardActor() {
     const handleCompositionEnd = (event: CompositionEvent) => {
       isComposing = false;
 
-      // Send the composed text as a literal string
+      // Send the composed text as is. `send_text` quotes and chunks it
+      // server-side, so multibyte text never goes through tmux key-name
+      // parsing or the raw command path.
       const composedText = event.data;
       if (composedText) {
-        // Use -l flag for literal text to avoid key interpretation
-        const escaped = escapeLiteralText(composedText);
         input.parent.send({
-          type: 'SEND_TMUX_COMMAND',
-          command: `send-keys -t ${realPaneId(activePaneId) ?? sessionName} -l ${escaped}`,
+          type: 'SEND_TEXT',
+          paneId: focusedFloatPaneId ?? realPaneId(activePaneId) ?? sessionName,
+          text: composedText,
         });
       }
     };
@@ -2,6 +2,8 @@ import { fromCallback, type AnyActorRef } from 'xstate';
 import { Cause, Effect, Exit, Fiber } from 'effect';
 import type { TmuxAdapter, ServerState, KeyBindings } from '../../tmux/types';
 import { toEffectAdapter, type AdapterError, Schemas } from '../../tmux/effect';
+import { setWidgetData } from '../../components/widgets/widgetData';
+import { setOptionValue } from '../../utils/tmuxOptions';
 
 export type TmuxActorEvent =
   | { type: 'SEND_COMMAND'; command: string }
@@ -156,6 +158,35 @@ export function createTmuxActor(adapter: TmuxAdapter) {
         })
       : () => {};
 
+    // A command that failed in control mode surfaces like any other error,
+    // named the way failed store commands are.
+    const unsubscribeCommandError = adapter.onCommandError
+      ? adapter.onCommandError((command: string, error: string) => {
+          logError(`${command}: ${error}`);
+          parent.send({ type: 'TMUX_ERROR', error: `${command}: ${error}` });
+        })
+      : () => {};
+
+    // A lost session is an error the user should see; one being recreated
+    // comes back through the usual state updates.
+    const unsubscribeSessionLost = adapter.onSessionLost
+      ? adapter.onSessionLost((reason: string, recreating: boolean) => {
+          const error = recreating
+            ? `tmux session lost (${reason}), recreating it`
+            : `tmux session lost: ${reason}`;
+          logError(error);
+          parent.send({ type: 'TMUX_ERROR', error });
+        })
+      : () => {};
+
+    // Widget data goes straight to the widget store; it isn't app state.
+    const unsubscribeWidgetData = adapter.onWidgetData
+      ? adapter.onWidgetData((paneId: string, data: unknown) => setWidgetData(paneId, data))
+      : () => {};
+    const unsubscribeOptionChanged = adapter.onOptionChanged
+      ? adapter.onOptionChanged(setOptionValue)
+      : () => {};
+
     run(eff.connect(), {
       onSuccess: () => {
         logInfo('Connected to tmux backend');
@@ -275,6 +306,7 @@ export function createTmuxActor(adapter: TmuxAdapter) {
         run(
           eff.invoke<string>('run_tmux_command', {
             command: 'show-environment -g TMUXY_SWITCH_TO',
+            output: true,
           }),
           {
             onSuccess: (result) => {
@@ -307,6 +339,10 @@ export function createTmuxActor(adapter: TmuxAdapter) {
       unsubscribeKeyBindings();
       unsubscribeConnectionInfo();
       unsubscribeClipboard();
+      unsubscribeCommandError();
+      unsubscribeSessionLost();
+      unsubscribeWidgetData();
+      unsubscribeOptionChanged();
       // Interrupt any pending scrollback fetches so they don't try to
       // send to a dead parent or hold a reference to the adapter.
       for (const fiber of scrollbackFibers.values()) {
@@ -7,7 +7,7 @@
  * drag, resize, resizeActive, suppressLayoutTransition.
  *
  * MIGRATED HERE (the cleanly-layout-owned events):
- *   SEND_KEYS, CLOSE_PANE, ZOOM_PANE, WRITE_TO_PANE, SELECT_TAB,
+ *   SEND_KEYS, SEND_KEY, SEND_TEXT, SEND_MOUSE_EVENTS, CLOSE_PANE, ZOOM_PANE, WRITE_TO_PANE, SELECT_TAB,
  *   KEY_PRESS, RESIZE_STATE_UPDATE, RESIZE_COMPLETED,
  *   DRAG_STATE_UPDATE.
  *
@@ -49,6 +49,53 @@ export const layoutActions = {
     );
   }),
 
+  layout_sendKeyEvent: enqueueActions<Ctx, Evt, undefined, Evt, never, never, never, never, never>(
+    ({ event, enqueue }) => {
+      if (event.type !== 'SEND_KEY') return;
+      enqueue(
+        sendTo('tmux', {
+          type: 'INVOKE' as const,
+          cmd: 'send_key',
+          args: { paneId: event.paneId, key: event.key },
+        }),
+      );
+    },
+  ),
+
+  layout_sendText: enqueueActions<Ctx, Evt, undefined, Evt, never, never, never, never, never>(
+    ({ event, enqueue }) => {
+      if (event.type !== 'SEND_TEXT') return;
+      enqueue(
+        sendTo('tmux', {
+          type: 'INVOKE' as const,
+          cmd: 'send_text',
+          args: { paneId: event.paneId, text: event.text },
+        }),
+      );
+    },
+  ),
+
+  layout_sendMouseEvents: enqueueActions<
+    Ctx,
+    Evt,
+    undefined,
+    Evt,
+    never,
+    never,
+    never,
+    never,
+    never
+  >(({ event, enqueue }) => {
+    if (event.type !== 'SEND_MOUSE_EVENTS') return;
+    enqueue(
+      sendTo('tmux', {
+        type: 'INVOKE' as const,
+        cmd: 'send_mouse_events',
+        args: { paneId: event.paneId, events: event.events },
+      }),
+    );
+  }),
+
   layout_closePane: enqueueActions<Ctx, Evt, undefined, Evt, never, never, never, never, never>(
     ({ event, context, enqueue }) => {
       if (event.type !== 'CLOSE_PANE') return;
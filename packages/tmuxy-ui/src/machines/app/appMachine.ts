@@ -44,7 +44,15 @@ import {
   STATUS_MESSAGE_DURATION,
 } from './helpers';
 import { applyFontSize } from '../../utils/fontSizeManager';
-import type { CopyModeState, CellLine } from '../../tmux/types';
+import type {
+  CopyModeState,
+  CellLine,
+  SessionGroup,
+  SshAgentStatus,
+  StatusPosition,
+  StatusSegments,
+  TerminalColors,
+} from '../../tmux/types';
 
 import { dragMachine } from '../drag/dragMachine';
 import { resizeMachine } from '../resize/resizeMachine';
@@ -87,7 +95,14 @@ function snapshotFromModel(model: TmuxClientModel): {
   totalWidth: number;
   totalHeight: number;
   statusLine: string;
+  statusSegments: StatusSegments;
+  statusLines: CellLine[];
+  statusPosition: StatusPosition;
+  sessionGroup: SessionGroup | null;
   sessionName: string;
+  doNotDisturb: boolean;
+  terminalTheme: TerminalColors;
+  sshAgent: SshAgentStatus;
 } {
   const d = model.derived;
   // Pass the derived arrays through by REFERENCE — the store already
@@ -101,7 +116,14 @@ function snapshotFromModel(model: TmuxClientModel): {
     totalWidth: d.totalWidth,
     totalHeight: d.totalHeight,
     statusLine: d.statusLine,
+    statusSegments: d.statusSegments,
+    statusLines: d.statusLines as CellLine[],
+    statusPosition: d.statusPosition,
+    sessionGroup: d.sessionGroup as SessionGroup | null,
     sessionName: d.sessionName,
+    doNotDisturb: d.doNotDisturb,
+    terminalTheme: d.terminalTheme,
+    sshAgent: d.sshAgent,
   };
 }
 
@@ -474,6 +496,15 @@ export const appMachine = setup({
       ],
     },
 
+    WINDOW_FOCUS_CHANGE: {
+      guard: ({ context }) => context.connected,
+      actions: sendTo('tmux', ({ event }) => ({
+        type: 'INVOKE' as const,
+        cmd: 'set_focus',
+        args: { focused: event.focused },
+      })),
+    },
+
     // PREFIX_MODE_CHANGE — handled by commandUiState
 
     // Connection info events
@@ -554,6 +585,14 @@ export const appMachine = setup({
           actions: enqueueActions(({ context, enqueue }) => {
             enqueue(assign({ connected: true, error: null }));
             enqueue(sendTo('size', { type: 'CONNECTED' as const }));
+            // Focus is tracked per connection; a new one starts unfocused.
+            enqueue(
+              sendTo('tmux', {
+                type: 'INVOKE' as const,
+                cmd: 'set_focus',
+                args: { focused: document.hasFocus() },
+              }),
+            );
 
             // Fetch theme settings and available themes
             enqueue(sendTo('tmux', { type: 'FETCH_THEME_SETTINGS' as const }));
@@ -680,6 +719,10 @@ export const appMachine = setup({
                   w.floatHeight !== prev.floatHeight ||
                   w.floatBg !== prev.floatBg ||
                   w.floatNoheader !== prev.floatNoheader ||
+                  (w.floatX ?? null) !== (prev.floatX ?? null) ||
+                  (w.floatY ?? null) !== (prev.floatY ?? null) ||
+                  (w.floatZ ?? null) !== (prev.floatZ ?? null) ||
+                  Boolean(w.floatHidden) !== Boolean(prev.floatHidden) ||
                   Boolean(w.zoomed) !== Boolean(prev.zoomed)
                 ) {
                   return true;
@@ -1542,7 +1585,14 @@ export const appMachine = setup({
       on: {
         TMUX_RECONNECTED: {
           target: 'idle',
-          actions: assign({ connected: true, reconnectAttempt: 0, error: null }),
+          actions: [
+            assign({ connected: true, reconnectAttempt: 0, error: null }),
+            sendTo('tmux', () => ({
+              type: 'INVOKE' as const,
+              cmd: 'set_focus',
+              args: { focused: document.hasFocus() },
+            })),
+          ],
         },
         TMUX_DISCONNECTED: {
           target: 'disconnected',
@@ -1566,6 +1616,13 @@ export const appMachine = setup({
               activePaneId: transformed.activePaneId,
               activeWindowId: transformed.activeWindowId,
               statusLine: transformed.statusLine,
+              statusSegments: transformed.statusSegments,
+              statusLines: transformed.statusLines,
+              statusPosition: transformed.statusPosition,
+              sessionGroup: transformed.sessionGroup,
+              doNotDisturb: transformed.doNotDisturb,
+              terminalTheme: transformed.terminalTheme,
+              sshAgent: transformed.sshAgent,
               totalWidth: transformed.totalWidth,
               totalHeight: transformed.totalHeight,
             };
@@ -82,6 +82,7 @@ export const FIELD_OWNERS = {
   suppressLayoutTransition: 'layout',
   groupSwitchPaneIds: 'layout',
   lastUpdateQuiet: 'layout',
+  terminalTheme: 'layout',
 
   // ---- copyMode ----
   copyModeStates: 'copyMode',
@@ -97,6 +98,12 @@ export const FIELD_OWNERS = {
   commandMode: 'commandUi',
   statusMessage: 'commandUi',
   statusLine: 'commandUi',
+  statusSegments: 'commandUi',
+  statusLines: 'commandUi',
+  statusPosition: 'commandUi',
+  sessionGroup: 'commandUi',
+  doNotDisturb: 'commandUi',
+  sshAgent: 'commandUi',
   prefixActive: 'commandUi',
 
   // ---- uiPrefs ----
@@ -138,6 +145,13 @@ export function createInitialContext(): AppMachineContext {
     charHeight: DEFAULT_CHAR_HEIGHT,
     defaultShell: 'bash',
     statusLine: '',
+    statusSegments: { left: [], windows: [], right: [] },
+    statusLines: [],
+    statusPosition: 'bottom',
+    sessionGroup: null,
+    doNotDisturb: false,
+    terminalTheme: {},
+    sshAgent: 'unset',
     containerWidth: 0,
     containerHeight: 0,
     sessions: [],
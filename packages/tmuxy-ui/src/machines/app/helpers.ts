@@ -2,7 +2,15 @@
  * Helper functions for the app machine
  */
 
-import type { ServerState } from '../../tmux/types';
+import type {
+  CellLine,
+  ServerState,
+  SessionGroup,
+  SshAgentStatus,
+  StatusPosition,
+  StatusSegments,
+  TerminalColors,
+} from '../../tmux/types';
 import type { TmuxPane, TmuxWindow } from '../types';
 
 /**
@@ -117,6 +125,10 @@ export function camelize<T>(obj: Record<string, unknown>): T {
   return result as T;
 }
 
+/** Status segments before the first status line, or from an older server */
+const EMPTY_STATUS_SEGMENTS: StatusSegments = { left: [], windows: [], right: [] };
+const EMPTY_STATUS_LINES: CellLine[] = [];
+
 /**
  * Transform server state to client format
  */
@@ -129,6 +141,13 @@ export function transformServerState(payload: ServerState): {
   totalWidth: number;
   totalHeight: number;
   statusLine: string;
+  statusSegments: StatusSegments;
+  statusLines: CellLine[];
+  statusPosition: StatusPosition;
+  sessionGroup: SessionGroup | null;
+  doNotDisturb: boolean;
+  terminalTheme: TerminalColors;
+  sshAgent: SshAgentStatus;
 } {
   return {
     sessionName: payload.session_name,
@@ -141,6 +160,13 @@ export function transformServerState(payload: ServerState): {
     totalWidth: payload.total_width,
     totalHeight: payload.total_height,
     statusLine: payload.status_line,
+    statusSegments: payload.status_segments ?? EMPTY_STATUS_SEGMENTS,
+    statusLines: payload.status_lines ?? EMPTY_STATUS_LINES,
+    statusPosition: payload.status_position ?? 'bottom',
+    sessionGroup: payload.session_group ?? null,
+    doNotDisturb: payload.do_not_disturb ?? false,
+    terminalTheme: payload.theme ?? {},
+    sshAgent: payload.ssh_agent ?? 'unset',
   };
 }
 
@@ -203,14 +229,21 @@ function normalizeWindow(w: TmuxWindow): TmuxWindow {
     floatDrawer: w.floatDrawer ?? null,
     floatBg: w.floatBg ?? null,
     floatNoheader: Boolean(w.floatNoheader),
+    floatX: w.floatX ?? null,
+    floatY: w.floatY ?? null,
+    floatZ: w.floatZ ?? null,
+    floatHidden: Boolean(w.floatHidden),
     zoomed: Boolean(w.zoomed),
+    status: w.status ?? null,
   };
 }
 
 /**
  * Build float pane states from float-typed windows.
- * Float metadata (drawer, backdrop, no-header) is sourced from @tmuxy-float-*
- * options on the window; each float window contains exactly one pane.
+ * Float metadata (drawer, backdrop, no-header, position) is sourced from
+ * @tmuxy-float-* options on the window; each float window contains exactly
+ * one pane. Hidden floats are left out, and the rest are inserted in
+ * @tmuxy-float-z order so the last one is the top of the stack.
  */
 
 import type { DrawerDirection, FloatBackdrop, FloatPaneState } from '../types';
@@ -226,9 +259,11 @@ export function buildFloatPanesFromWindows(
 ): Record<string, FloatPaneState> {
   const floatPanes: Record<string, FloatPaneState> = {};
 
-  for (const window of windows) {
-    if (window.windowType !== 'float') continue;
+  const floats = windows
+    .filter((w) => w.windowType === 'float' && !w.floatHidden)
+    .sort((a, b) => (a.floatZ ?? 0) - (b.floatZ ?? 0));
 
+  for (const window of floats) {
     // A float window contains exactly one pane.
     const pane = panes.find((p) => p.windowId === window.id);
     if (!pane) continue;
@@ -237,6 +272,8 @@ export function buildFloatPanesFromWindows(
     const drawer = (window.floatDrawer as DrawerDirection | null) ?? undefined;
     const backdrop = (window.floatBg as FloatBackdrop | null) ?? undefined;
     const hideHeader = window.floatNoheader || undefined;
+    const x = window.floatX != null ? window.floatX * charWidth : undefined;
+    const y = window.floatY != null ? window.floatY * charHeight : undefined;
     // @tmuxy-float-width/height (in cells) are the float's REQUESTED size —
     // authoritative when present. The pane's tmux size is only a fallback: a
     // single-pane float window can't be shrunk by resize-pane, so on some
@@ -256,6 +293,8 @@ export function buildFloatPanesFromWindows(
         drawer,
         backdrop,
         hideHeader,
+        x,
+        y,
       };
     } else {
       // Default dimensions: requested size, else the pane's actual size. Cap
@@ -275,6 +314,8 @@ export function buildFloatPanesFromWindows(
         drawer,
         backdrop,
         hideHeader,
+        x,
+        y,
       };
     }
   }
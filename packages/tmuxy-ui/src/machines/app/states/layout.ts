@@ -12,7 +12,7 @@
  * stays a passive view of the model.
  *
  * Migrated events (these spread into states.idle.on):
- *   SEND_KEYS, CLOSE_PANE, ZOOM_PANE, WRITE_TO_PANE, SELECT_TAB,
+ *   SEND_KEYS, SEND_KEY, SEND_TEXT, SEND_MOUSE_EVENTS, CLOSE_PANE, ZOOM_PANE, WRITE_TO_PANE, SELECT_TAB,
  *   KEY_PRESS, RESIZE_STATE_UPDATE, RESIZE_COMPLETED,
  *   DRAG_STATE_UPDATE.
  *
@@ -23,6 +23,9 @@
 export const layoutState = {
   on: {
     SEND_KEYS: { actions: 'layout_sendKeysToTmux' },
+    SEND_KEY: { actions: 'layout_sendKeyEvent' },
+    SEND_TEXT: { actions: 'layout_sendText' },
+    SEND_MOUSE_EVENTS: { actions: 'layout_sendMouseEvents' },
     CLOSE_PANE: { actions: 'layout_closePane' },
     ZOOM_PANE: { actions: 'layout_zoomPane' },
     WRITE_TO_PANE: { actions: 'layout_writeToPane' },
@@ -7,6 +7,7 @@ import type {
   SessionTreeNode,
   ServerInfo,
 } from './types';
+import type { CellLine, SessionGroup, StatusPosition, StatusSegments } from '../tmux/types';
 import { createMemoizedSelector, createMemoizedSelectorWithArg } from '../utils/memoize';
 
 // ============================================
@@ -588,6 +589,33 @@ export function selectStatusMessage(context: AppMachineContext) {
   return context.statusMessage;
 }
 
+/** The active window's evaluated @tmuxy-status format, if it has one. */
+export function selectActiveWindowStatus(context: AppMachineContext): string | null {
+  return context.windows.find((w) => w.id === context.activeWindowId)?.status ?? null;
+}
+
+/**
+ * The tmux status line as cells. A click on a window entry maps straight to
+ * its `window_id`.
+ */
+export function selectStatusSegments(context: AppMachineContext): StatusSegments {
+  return context.statusSegments;
+}
+
+/** Status rows below the first, with tmux's `status` set to 2 or more. */
+export function selectStatusLines(context: AppMachineContext): CellLine[] {
+  return context.statusLines;
+}
+
+export function selectStatusPosition(context: AppMachineContext): StatusPosition {
+  return context.statusPosition;
+}
+
+/** The session's group, for showing which sessions share its windows. */
+export function selectSessionGroup(context: AppMachineContext): SessionGroup | null {
+  return context.sessionGroup;
+}
+
 export function selectPrefixActive(context: AppMachineContext): boolean {
   return context.prefixActive;
 }
@@ -8,9 +8,16 @@ import type {
   TmuxPane,
   TmuxWindow,
   ServerState,
+  SessionGroup,
+  SshAgentStatus,
+  StatusPosition,
+  StatusSegments,
+  TerminalColors,
+  CellLine,
   KeyBindings,
   KeyBinding,
   CopyModeState,
+  PaneMouseEvent,
 } from '../tmux/types';
 
 // Re-export domain types
@@ -45,6 +52,9 @@ export interface FloatPaneState {
   backdrop?: FloatBackdrop;
   /** Whether to hide the header bar */
   hideHeader?: boolean;
+  /** Top-left corner in pixels; centered when unset (ignored by drawers) */
+  x?: number;
+  y?: number;
 }
 
 /** Drag operation state */
@@ -184,6 +194,20 @@ export interface AppMachineContext {
   defaultShell: string;
   /** Tmux status line with ANSI escape codes */
   statusLine: string;
+  /** The same status line as cells; window entries carry their window IDs */
+  statusSegments: StatusSegments;
+  /** Status rows below the first (tmux `status` 2 to 5) */
+  statusLines: CellLine[];
+  /** tmux `status-position`: the status bar goes above or below the panes */
+  statusPosition: StatusPosition;
+  /** The session's group (`new-session -t`), or null when it is in none */
+  sessionGroup: SessionGroup | null;
+  /** Session do-not-disturb is in effect (show an indicator) */
+  doNotDisturb: boolean;
+  /** Server's default terminal colours (the `theme` in the server state) */
+  terminalTheme: TerminalColors;
+  /** Whether the session's SSH agent socket is live (see `fix_ssh_agent`) */
+  sshAgent: SshAgentStatus;
   /** Container dimensions for centering calculations */
   containerWidth: number;
   containerHeight: number;
@@ -453,6 +477,30 @@ export type FocusPaneEvent = { type: 'FOCUS_PANE'; paneId: string };
 export type SendCommandEvent = { type: 'SEND_COMMAND'; command: string };
 export type SendKeysEvent = { type: 'SEND_KEYS'; paneId: string; keys: string };
 export type SendTmuxCommandEvent = { type: 'SEND_TMUX_COMMAND'; command: string };
+/** A browser key press, as the server's keyboard protocol encoder takes it */
+export type RawKeyEvent = {
+  key: string;
+  code: string;
+  ctrl: boolean;
+  alt: boolean;
+  shift: boolean;
+  meta: boolean;
+  repeat: boolean;
+};
+/** Key press for a pane using an extended keyboard protocol (`send_key`) */
+export type SendKeyEvent = { type: 'SEND_KEY'; paneId: string; key: RawKeyEvent };
+/** Composed text (IME, emoji, mobile input) typed into a pane as is (`send_text`) */
+export type SendTextEvent = { type: 'SEND_TEXT'; paneId: string; text: string };
+/**
+ * Raw mouse events for a pane (`send_mouse_events`); the server decides from
+ * the pane's live mode whether they become SGR reports, arrow keys or
+ * copy-mode scrolling.
+ */
+export type SendMouseEventsEvent = {
+  type: 'SEND_MOUSE_EVENTS';
+  paneId: string;
+  events: PaneMouseEvent[];
+};
 export type CopySelectionEvent = { type: 'COPY_SELECTION' };
 
 // Semantic pane events (components send intent, machine constructs commands)
@@ -560,6 +608,7 @@ export type ClearStatusMessageEvent = { type: 'CLEAR_STATUS_MESSAGE' };
 // Focus events (for keyboard capture gating)
 export type AppFocusEvent = { type: 'APP_FOCUS' };
 export type AppBlurEvent = { type: 'APP_BLUR' };
+export type WindowFocusChangeEvent = { type: 'WINDOW_FOCUS_CHANGE'; focused: boolean };
 
 // Session events
 export type SwitchSessionEvent = { type: 'SWITCH_SESSION'; sessionName: string };
@@ -637,6 +686,9 @@ export type AppMachineEvent =
   | SendCommandEvent
   | SendKeysEvent
   | SendTmuxCommandEvent
+  | SendKeyEvent
+  | SendTextEvent
+  | SendMouseEventsEvent
   | CopySelectionEvent
   | EnterCopyModeEvent
   | ExitCopyModeEvent
@@ -670,6 +722,7 @@ export type AppMachineEvent =
   | ThemesListReceivedEvent
   | AppFocusEvent
   | AppBlurEvent
+  | WindowFocusChangeEvent
   | PrefixModeChangeEvent
   | SwitchSessionEvent
   | OpenSessionFloatEvent
@@ -9,11 +9,24 @@ import {
   LogEntryKind,
   FatalListener,
   ClipboardListener,
+  BellListener,
+  DragTargetListener,
+  PaneDropTarget,
+  SpokenTextListener,
+  OutputTrimmedListener,
+  PaneNotifyListener,
+  CommandErrorListener,
+  SessionLostListener,
+  WidgetDataListener,
+  OptionChange,
+  OptionChangeListener,
+  SearchResults,
+  SearchResultsListener,
   ServerState,
   StateUpdate,
   KeyBindings,
 } from './types';
-import { handleStateUpdate, isDeltaSeqGap } from './deltaProtocol';
+import { handleStateUpdate, isDeltaFromOtherEpoch, isDeltaSeqGap } from './deltaProtocol';
 import { KeyBatcher } from './keyBatching';
 import { latencyTracker } from './latencyTracker';
 
@@ -31,6 +44,32 @@ function getSessionFromUrl(): string {
   return params.get('session') || 'tmuxy';
 }
 
+/**
+ * Accessibility color transforms (`?colors=high-contrast,red-green-safe`,
+ * plus `background=light` for light themes), forwarded to the event stream so
+ * the server recolors cells for this connection.
+ */
+function getColorParamsFromUrl(): string {
+  if (typeof window === 'undefined') return '';
+  const params = new URLSearchParams(window.location.search);
+  let query = '';
+  for (const key of ['colors', 'background']) {
+    const value = params.get(key);
+    if (value) query += `&${key}=${encodeURIComponent(value)}`;
+  }
+  return query;
+}
+
+/**
+ * Single-pane embed mode (`?pane=%5`), forwarded to the event stream and to
+ * commands so the server narrows both to that pane.
+ */
+function getPaneParamFromUrl(): string {
+  if (typeof window === 'undefined') return '';
+  const pane = new URLSearchParams(window.location.search).get('pane');
+  return pane ? `&pane=${encodeURIComponent(pane)}` : '';
+}
+
 /**
  * HTTP Adapter using SSE for server->client push and POST for client->server commands.
  */
@@ -60,6 +99,16 @@ export class HttpAdapter implements TmuxAdapter {
   private logListeners = new Set<LogListener>();
   private fatalListeners = new Set<FatalListener>();
   private clipboardListeners = new Set<ClipboardListener>();
+  private bellListeners = new Set<BellListener>();
+  private dragTargetListeners = new Set<DragTargetListener>();
+  private spokenTextListeners = new Set<SpokenTextListener>();
+  private outputTrimmedListeners = new Set<OutputTrimmedListener>();
+  private paneNotifyListeners = new Set<PaneNotifyListener>();
+  private commandErrorListeners = new Set<CommandErrorListener>();
+  private sessionLostListeners = new Set<SessionLostListener>();
+  private widgetDataListeners = new Set<WidgetDataListener>();
+  private optionChangeListeners = new Set<OptionChangeListener>();
+  private searchResultsListeners = new Set<SearchResultsListener>();
   private fatal = false;
 
   // Delta protocol state
@@ -108,7 +157,7 @@ export class HttpAdapter implements TmuxAdapter {
       const session = this.getEffectiveSession();
       const protocol = window.location.protocol;
       const host = window.location.host || 'localhost:3853';
-      const eventsUrl = `${protocol}//${host}/events?session=${encodeURIComponent(session)}`;
+      const eventsUrl = `${protocol}//${host}/events?session=${encodeURIComponent(session)}${getColorParamsFromUrl()}${getPaneParamFromUrl()}`;
 
       this.eventSource = new EventSource(eventsUrl);
 
@@ -141,9 +190,13 @@ export class HttpAdapter implements TmuxAdapter {
 
           // Delta seq-gap detection: a dropped or misordered delta would
           // otherwise apply to stale state and silently diverge. On a gap,
-          // refetch a full snapshot instead of applying the delta.
+          // or a delta from a monitor connection other than the one our
+          // state came from, refetch a full snapshot instead of applying it.
           if (update.type === 'delta') {
-            if (isDeltaSeqGap(this.lastDeltaSeq, update.delta)) {
+            if (
+              isDeltaSeqGap(this.lastDeltaSeq, update.delta) ||
+              isDeltaFromOtherEpoch(this.currentState, update.delta)
+            ) {
               this.lastDeltaSeq = null;
               void this.resyncFullState();
               return;
@@ -198,6 +251,110 @@ export class HttpAdapter implements TmuxAdapter {
         }
       });
 
+      // Terminal bell, already rate limited and filtered by the server's
+      // bell policy.
+      this.eventSource.addEventListener('bell', (event: MessageEvent) => {
+        try {
+          const data = JSON.parse(event.data);
+          const payload = data.data || data;
+          this.notifyBell(String(payload.pane_id ?? ''), Boolean(payload.sound));
+        } catch (e) {
+          console.error('Failed to parse bell event:', e);
+        }
+      });
+
+      // Drop-target hint while a pane is dragged; `data` is null to clear it.
+      this.eventSource.addEventListener('drag-target', (event: MessageEvent) => {
+        try {
+          const data = JSON.parse(event.data);
+          this.notifyDragTarget((data.data ?? null) as PaneDropTarget | null);
+        } catch (e) {
+          console.error('Failed to parse drag-target event:', e);
+        }
+      });
+
+      this.eventSource.addEventListener('spoken-text', (event: MessageEvent) => {
+        try {
+          const data = JSON.parse(event.data);
+          const payload = data.data || data;
+          this.notifySpokenText(String(payload.pane_id ?? ''), payload.lines ?? []);
+        } catch (e) {
+          console.error('Failed to parse spoken-text event:', e);
+        }
+      });
+
+      this.eventSource.addEventListener('output-trimmed', (event: MessageEvent) => {
+        try {
+          const data = JSON.parse(event.data);
+          const payload = data.data || data;
+          this.notifyOutputTrimmed(String(payload.pane_id ?? ''), Number(payload.lines ?? 0));
+        } catch (e) {
+          console.error('Failed to parse output-trimmed event:', e);
+        }
+      });
+
+      this.eventSource.addEventListener('pane-notify', (event: MessageEvent) => {
+        try {
+          const data = JSON.parse(event.data);
+          const payload = data.data || data;
+          this.notifyPaneNotify(
+            String(payload.pane_id ?? ''),
+            String(payload.title ?? ''),
+            String(payload.body ?? ''),
+          );
+        } catch (e) {
+          console.error('Failed to parse pane-notify event:', e);
+        }
+      });
+
+      this.eventSource.addEventListener('command-error', (event: MessageEvent) => {
+        try {
+          const data = JSON.parse(event.data);
+          const payload = data.data || data;
+          this.notifyCommandError(String(payload.command ?? ''), String(payload.error ?? ''));
+        } catch (e) {
+          console.error('Failed to parse command-error event:', e);
+        }
+      });
+
+      this.eventSource.addEventListener('session-lost', (event: MessageEvent) => {
+        try {
+          const data = JSON.parse(event.data);
+          const payload = data.data || data;
+          this.notifySessionLost(String(payload.reason ?? ''), Boolean(payload.recreating));
+        } catch (e) {
+          console.error('Failed to parse session-lost event:', e);
+        }
+      });
+
+      this.eventSource.addEventListener('widget-data', (event: MessageEvent) => {
+        try {
+          const data = JSON.parse(event.data);
+          const payload = data.data || data;
+          this.notifyWidgetData(String(payload.pane_id ?? ''), payload.data ?? null);
+        } catch (e) {
+          console.error('Failed to parse widget-data event:', e);
+        }
+      });
+
+      this.eventSource.addEventListener('option-changed', (event: MessageEvent) => {
+        try {
+          const data = JSON.parse(event.data);
+          this.notifyOptionChanged(data.data || data);
+        } catch (e) {
+          console.error('Failed to parse option-changed event:', e);
+        }
+      });
+
+      this.eventSource.addEventListener('search-results', (event: MessageEvent) => {
+        try {
+          const data = JSON.parse(event.data);
+          this.notifySearchResults(data.data || data);
+        } catch (e) {
+          console.error('Failed to parse search-results event:', e);
+        }
+      });
+
       this.eventSource.addEventListener('log', (event: MessageEvent) => {
         try {
           const data = JSON.parse(event.data);
@@ -409,7 +566,7 @@ export class HttpAdapter implements TmuxAdapter {
     const session = this.getEffectiveSession();
     const protocol = window.location.protocol;
     const host = window.location.host || 'localhost:3853';
-    const commandsUrl = `${protocol}//${host}/commands?session=${encodeURIComponent(session)}`;
+    const commandsUrl = `${protocol}//${host}/commands?session=${encodeURIComponent(session)}${getPaneParamFromUrl()}`;
     const connId = String(this.connectionId);
 
     // Chain onto the serial queue so requests go one at a time
@@ -438,7 +595,7 @@ export class HttpAdapter implements TmuxAdapter {
     const session = this.getEffectiveSession();
     const protocol = window.location.protocol;
     const host = window.location.host || 'localhost:3853';
-    const commandsUrl = `${protocol}//${host}/commands?session=${encodeURIComponent(session)}`;
+    const commandsUrl = `${protocol}//${host}/commands?session=${encodeURIComponent(session)}${getPaneParamFromUrl()}`;
 
     const response = await fetch(commandsUrl, {
       method: 'POST',
@@ -507,6 +664,56 @@ export class HttpAdapter implements TmuxAdapter {
     return () => this.clipboardListeners.delete(listener);
   }
 
+  onBell(listener: BellListener): () => void {
+    this.bellListeners.add(listener);
+    return () => this.bellListeners.delete(listener);
+  }
+
+  onDragTarget(listener: DragTargetListener): () => void {
+    this.dragTargetListeners.add(listener);
+    return () => this.dragTargetListeners.delete(listener);
+  }
+
+  onSpokenText(listener: SpokenTextListener): () => void {
+    this.spokenTextListeners.add(listener);
+    return () => this.spokenTextListeners.delete(listener);
+  }
+
+  onOutputTrimmed(listener: OutputTrimmedListener): () => void {
+    this.outputTrimmedListeners.add(listener);
+    return () => this.outputTrimmedListeners.delete(listener);
+  }
+
+  onPaneNotify(listener: PaneNotifyListener): () => void {
+    this.paneNotifyListeners.add(listener);
+    return () => this.paneNotifyListeners.delete(listener);
+  }
+
+  onCommandError(listener: CommandErrorListener): () => void {
+    this.commandErrorListeners.add(listener);
+    return () => this.commandErrorListeners.delete(listener);
+  }
+
+  onSessionLost(listener: SessionLostListener): () => void {
+    this.sessionLostListeners.add(listener);
+    return () => this.sessionLostListeners.delete(listener);
+  }
+
+  onWidgetData(listener: WidgetDataListener): () => void {
+    this.widgetDataListeners.add(listener);
+    return () => this.widgetDataListeners.delete(listener);
+  }
+
+  onOptionChanged(listener: OptionChangeListener): () => void {
+    this.optionChangeListeners.add(listener);
+    return () => this.optionChangeListeners.delete(listener);
+  }
+
+  onSearchResults(listener: SearchResultsListener): () => void {
+    this.searchResultsListeners.add(listener);
+    return () => this.searchResultsListeners.delete(listener);
+  }
+
   async switchSession(newSession: string): Promise<void> {
     this.sessionOverride = newSession;
     this.currentState = null;
@@ -634,4 +841,44 @@ export class HttpAdapter implements TmuxAdapter {
   private notifyClipboard(paneId: string, text: string): void {
     this.clipboardListeners.forEach((listener) => listener(paneId, text));
   }
+
+  private notifyBell(paneId: string, sound: boolean): void {
+    this.bellListeners.forEach((listener) => listener(paneId, sound));
+  }
+
+  private notifyDragTarget(target: PaneDropTarget | null): void {
+    this.dragTargetListeners.forEach((listener) => listener(target));
+  }
+
+  private notifySpokenText(paneId: string, lines: string[]): void {
+    this.spokenTextListeners.forEach((listener) => listener(paneId, lines));
+  }
+
+  private notifyOutputTrimmed(paneId: string, lines: number): void {
+    this.outputTrimmedListeners.forEach((listener) => listener(paneId, lines));
+  }
+
+  private notifyPaneNotify(paneId: string, title: string, body: string): void {
+    this.paneNotifyListeners.forEach((listener) => listener(paneId, title, body));
+  }
+
+  private notifyCommandError(command: string, error: string): void {
+    this.commandErrorListeners.forEach((listener) => listener(command, error));
+  }
+
+  private notifySessionLost(reason: string, recreating: boolean): void {
+    this.sessionLostListeners.forEach((listener) => listener(reason, recreating));
+  }
+
+  private notifyWidgetData(paneId: string, data: unknown): void {
+    this.widgetDataListeners.forEach((listener) => listener(paneId, data));
+  }
+
+  private notifyOptionChanged(change: OptionChange): void {
+    this.optionChangeListeners.forEach((listener) => listener(change));
+  }
+
+  private notifySearchResults(results: SearchResults): void {
+    this.searchResultsListeners.forEach((listener) => listener(results));
+  }
 }
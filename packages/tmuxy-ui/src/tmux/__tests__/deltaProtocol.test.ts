@@ -1,5 +1,10 @@
 import { describe, test, expect } from 'vitest';
-import { handleStateUpdate, applyDelta, isDeltaSeqGap } from '../deltaProtocol';
+import {
+  handleStateUpdate,
+  applyDelta,
+  isDeltaFromOtherEpoch,
+  isDeltaSeqGap,
+} from '../deltaProtocol';
 import type { ServerState, ServerPane, ServerDelta, StateUpdate } from '../types';
 
 describe('isDeltaSeqGap', () => {
@@ -23,6 +28,21 @@ describe('isDeltaSeqGap', () => {
   });
 });
 
+describe('isDeltaFromOtherEpoch', () => {
+  test('rejects a delta from another monitor connection', () => {
+    const state = makeState({ epoch: 100 });
+    expect(isDeltaFromOtherEpoch(state, { seq: 2, epoch: 100 })).toBe(false);
+    expect(isDeltaFromOtherEpoch(state, { seq: 2, epoch: 200 })).toBe(true);
+  });
+
+  test('an unversioned snapshot adopts the first epoch it sees', () => {
+    const state = makeState();
+    expect(isDeltaFromOtherEpoch(state, { seq: 2, epoch: 200 })).toBe(false);
+    expect(applyDelta(state, { seq: 2, epoch: 200 }).epoch).toBe(200);
+    expect(isDeltaFromOtherEpoch(null, { seq: 2, epoch: 200 })).toBe(false);
+  });
+});
+
 function makePane(overrides: Partial<ServerPane> = {}): ServerPane {
   return {
     id: 0,
@@ -137,3 +157,86 @@ describe('applyDelta - content preservation', () => {
     expect(result.panes[0].content[0]).toEqual(newLine);
   });
 });
+
+describe('applyDelta - scroll', () => {
+  test('scroll drops the top rows and appends the new ones', () => {
+    const line = (c: string) => [{ c }];
+    const content = [line('a'), line('b'), line('c')];
+    const state = makeState({ panes: [makePane({ content })] });
+    const result = applyDelta(state, {
+      seq: 1,
+      panes: {
+        '%0': { scroll: { lines: 1, new_rows: [line('d')] } },
+      },
+    });
+
+    expect(result.panes[0].content).toEqual([line('b'), line('c'), line('d')]);
+    // Surviving rows are the same objects, not copies
+    expect(result.panes[0].content[0]).toBe(content[1]);
+  });
+});
+
+describe('applyDelta - window status', () => {
+  test('sets and clears the evaluated @tmuxy-status', () => {
+    const state = makeState();
+    const withStatus = applyDelta(state, {
+      seq: 1,
+      windows: { '@0': { status: 'main +2' } },
+    });
+    expect(withStatus.windows[0].status).toBe('main +2');
+
+    const cleared = applyDelta(withStatus, {
+      seq: 2,
+      windows: { '@0': { status: null } },
+    });
+    expect(cleared.windows[0].status).toBeNull();
+    expect(cleared.windows[0].name).toBe('test');
+  });
+});
+
+describe('applyDelta - status segments', () => {
+  test('replaces the segments and leaves them alone otherwise', () => {
+    const segments = {
+      left: [{ c: '[' }, { c: 's' }, { c: ']' }],
+      windows: [{ window_id: '@0', active: true, cells: [{ c: '0', s: { bold: true } }] }],
+      right: [],
+    };
+    const result = applyDelta(makeState(), { seq: 1, status_segments: segments });
+    expect(result.status_segments?.windows[0].window_id).toBe('@0');
+
+    const unchanged = applyDelta(result, { seq: 2, status_line: 'x' });
+    expect(unchanged.status_segments).toBe(segments);
+  });
+});
+
+describe('applyDelta - status rows', () => {
+  test('applies extra rows and the status position', () => {
+    const state = makeState();
+    const result = applyDelta(state, {
+      seq: 1,
+      status_lines: [[{ c: 'x' }]],
+      status_position: 'top',
+    });
+    expect(result.status_lines).toEqual([[{ c: 'x' }]]);
+    expect(result.status_position).toBe('top');
+
+    const back = applyDelta(result, { seq: 2, status_lines: [], status_position: 'bottom' });
+    expect(back.status_lines).toEqual([]);
+    expect(back.status_position).toBe('bottom');
+  });
+});
+
+describe('applyDelta - session group', () => {
+  test('sets the group and clears it on null', () => {
+    const state = makeState();
+    const group = { name: 'main', sessions: ['main', 'main-2'] };
+    const grouped = applyDelta(state, { seq: 1, session_group: group });
+    expect(grouped.session_group).toEqual(group);
+
+    const kept = applyDelta(grouped, { seq: 2, status_line: 'x' });
+    expect(kept.session_group).toEqual(group);
+
+    const alone = applyDelta(kept, { seq: 3, session_group: null });
+    expect(alone.session_group).toBeUndefined();
+  });
+});
@@ -9,13 +9,26 @@ import {
   LogEntryKind,
   FatalListener,
   ClipboardListener,
+  BellListener,
+  DragTargetListener,
+  PaneDropTarget,
+  SpokenTextListener,
+  OutputTrimmedListener,
+  PaneNotifyListener,
+  CommandErrorListener,
+  SessionLostListener,
+  WidgetDataListener,
+  OptionChange,
+  OptionChangeListener,
+  SearchResults,
+  SearchResultsListener,
   ServerState,
   StateUpdate,
   KeyBindings,
 } from './types';
 import { HttpAdapter } from './HttpAdapter';
 import { DemoAdapter } from './demo/DemoAdapter';
-import { handleStateUpdate, isDeltaSeqGap } from './deltaProtocol';
+import { handleStateUpdate, isDeltaFromOtherEpoch, isDeltaSeqGap } from './deltaProtocol';
 import { KeyBatcher } from './keyBatching';
 import { latencyTracker } from './latencyTracker';
 
@@ -23,6 +36,28 @@ import { latencyTracker } from './latencyTracker';
 // Tauri Adapter
 // ============================================
 
+/**
+ * Desktop-only commands the Tauri app registers on their own. Every other
+ * command is a `ClientCommand`, carried by the app's single `dispatch`
+ * command to the dispatcher the SSE server's `/commands` runs too.
+ */
+const NATIVE_COMMANDS = new Set([
+  'get_keybindings_snapshot',
+  'get_key_bindings',
+  'list_servers',
+  'connect_server',
+  'clipboard_copy',
+  'clipboard_paste',
+]);
+
+async function tauriInvoke<T>(cmd: string, args?: Record<string, unknown>): Promise<T> {
+  const { invoke } = await import('@tauri-apps/api/core');
+  if (NATIVE_COMMANDS.has(cmd)) {
+    return invoke<T>(cmd, args);
+  }
+  return invoke<T>('dispatch', { cmd, args: args ?? {} });
+}
+
 export class TauriAdapter implements TmuxAdapter {
   readonly enumeratesSessions = true;
   private connected = false;
@@ -38,6 +73,16 @@ export class TauriAdapter implements TmuxAdapter {
   private logListeners = new Set<LogListener>();
   private fatalListeners = new Set<FatalListener>();
   private clipboardListeners = new Set<ClipboardListener>();
+  private bellListeners = new Set<BellListener>();
+  private dragTargetListeners = new Set<DragTargetListener>();
+  private spokenTextListeners = new Set<SpokenTextListener>();
+  private outputTrimmedListeners = new Set<OutputTrimmedListener>();
+  private paneNotifyListeners = new Set<PaneNotifyListener>();
+  private commandErrorListeners = new Set<CommandErrorListener>();
+  private sessionLostListeners = new Set<SessionLostListener>();
+  private widgetDataListeners = new Set<WidgetDataListener>();
+  private optionChangeListeners = new Set<OptionChangeListener>();
+  private searchResultsListeners = new Set<SearchResultsListener>();
 
   // Delta protocol state
   private currentState: ServerState | null = null;
@@ -56,7 +101,6 @@ export class TauriAdapter implements TmuxAdapter {
   async connect(): Promise<void> {
     try {
       const { listen } = await import('@tauri-apps/api/event');
-      const { invoke } = await import('@tauri-apps/api/core');
 
       // Initialize key batcher. The flushed batches MUST go through the same
       // serial queue as run_tmux_command: tauri::invoke spawns each call as its
@@ -68,7 +112,7 @@ export class TauriAdapter implements TmuxAdapter {
         latencyTracker.markInput();
         this.sendQueue = this.sendQueue.then(async () => {
           try {
-            await invoke(cmd, args);
+            await tauriInvoke(cmd, args);
           } catch {
             // Ignore errors for fire-and-forget batched commands
           }
@@ -79,10 +123,14 @@ export class TauriAdapter implements TmuxAdapter {
       const unlistenState = await listen<StateUpdate>('tmux-state-update', (event) => {
         const update = event.payload;
 
-        // Delta seq-gap detection (see HttpAdapter): a dropped delta would
-        // apply to stale state and diverge. On a gap, refetch a full snapshot.
+        // Delta seq-gap and epoch detection (see HttpAdapter): a dropped
+        // delta would apply to stale state and diverge. On a gap, refetch a
+        // full snapshot.
         if (update.type === 'delta') {
-          if (isDeltaSeqGap(this.lastDeltaSeq, update.delta)) {
+          if (
+            isDeltaSeqGap(this.lastDeltaSeq, update.delta) ||
+            isDeltaFromOtherEpoch(this.currentState, update.delta)
+          ) {
             this.lastDeltaSeq = null;
             void this.resyncFullState();
             return;
@@ -137,6 +185,80 @@ export class TauriAdapter implements TmuxAdapter {
       );
       this.unlistenFns.push(unlistenClipboard);
 
+      const unlistenBell = await listen<{ pane_id: string; sound: boolean }>(
+        'tmux-bell',
+        (event) => {
+          this.notifyBell(event.payload.pane_id, event.payload.sound);
+        },
+      );
+      this.unlistenFns.push(unlistenBell);
+
+      const unlistenDragTarget = await listen<PaneDropTarget | null>(
+        'tmux-drag-target',
+        (event) => {
+          this.notifyDragTarget(event.payload);
+        },
+      );
+      this.unlistenFns.push(unlistenDragTarget);
+
+      const unlistenSpokenText = await listen<{ pane_id: string; lines: string[] }>(
+        'tmux-spoken-text',
+        (event) => {
+          this.notifySpokenText(event.payload.pane_id, event.payload.lines);
+        },
+      );
+      this.unlistenFns.push(unlistenSpokenText);
+
+      const unlistenOutputTrimmed = await listen<{ pane_id: string; lines: number }>(
+        'tmux-output-trimmed',
+        (event) => {
+          this.notifyOutputTrimmed(event.payload.pane_id, event.payload.lines);
+        },
+      );
+      this.unlistenFns.push(unlistenOutputTrimmed);
+
+      const unlistenPaneNotify = await listen<{ pane_id: string; title: string; body: string }>(
+        'tmux-pane-notify',
+        (event) => {
+          this.notifyPaneNotify(event.payload.pane_id, event.payload.title, event.payload.body);
+        },
+      );
+      this.unlistenFns.push(unlistenPaneNotify);
+
+      const unlistenCommandError = await listen<{ command: string; error: string }>(
+        'tmux-command-error',
+        (event) => {
+          this.notifyCommandError(event.payload.command, event.payload.error);
+        },
+      );
+      this.unlistenFns.push(unlistenCommandError);
+
+      const unlistenSessionLost = await listen<{ reason: string; recreating: boolean }>(
+        'tmux-session-lost',
+        (event) => {
+          this.notifySessionLost(event.payload.reason, event.payload.recreating);
+        },
+      );
+      this.unlistenFns.push(unlistenSessionLost);
+
+      const unlistenWidgetData = await listen<{ pane_id: string; data: unknown }>(
+        'tmux-widget-data',
+        (event) => {
+          this.notifyWidgetData(event.payload.pane_id, event.payload.data);
+        },
+      );
+      this.unlistenFns.push(unlistenWidgetData);
+
+      const unlistenOptionChanged = await listen<OptionChange>('tmux-option-changed', (event) => {
+        this.notifyOptionChanged(event.payload);
+      });
+      this.unlistenFns.push(unlistenOptionChanged);
+
+      const unlistenSearchResults = await listen<SearchResults>('tmux-search-results', (event) => {
+        this.notifySearchResults(event.payload);
+      });
+      this.unlistenFns.push(unlistenSearchResults);
+
       // Backend gave up reconnecting — terminal state, no further events.
       const unlistenFatal = await listen<{ message: string }>('tmux-fatal', (event) => {
         this.connected = false;
@@ -146,8 +268,8 @@ export class TauriAdapter implements TmuxAdapter {
       this.unlistenFns.push(unlistenFatal);
 
       // Listen for errors (emitted by monitor.rs on connection failure)
-      const unlistenError = await listen<string>('tmux-error', (event) => {
-        this.notifyError(event.payload);
+      const unlistenError = await listen<{ message: string }>('tmux-error', (event) => {
+        this.notifyError(event.payload.message);
 
         // If we were connected, we're now reconnecting
         if (this.connected) {
@@ -179,7 +301,7 @@ export class TauriAdapter implements TmuxAdapter {
       // prefix indicator stays hidden and prefix/root bindings are empty,
       // so prefix-key and Ctrl+hjkl silently no-op.
       try {
-        const snapshot = await invoke<KeyBindings | null>('get_keybindings_snapshot');
+        const snapshot = await tauriInvoke<KeyBindings | null>('get_keybindings_snapshot');
         if (snapshot) {
           this.notifyKeyBindings(snapshot);
         }
@@ -226,8 +348,6 @@ export class TauriAdapter implements TmuxAdapter {
   private sendQueue: Promise<void> = Promise.resolve();
 
   async invoke<T>(cmd: string, args?: Record<string, unknown>): Promise<T> {
-    const { invoke } = await import('@tauri-apps/api/core');
-
     // Cache the client size so a seq-gap resync can refetch get_initial_state.
     if (
       (cmd === 'set_client_size' || cmd === 'get_initial_state') &&
@@ -240,7 +360,7 @@ export class TauriAdapter implements TmuxAdapter {
 
     // Special handling for get_initial_state: capture as currentState for delta protocol
     if (cmd === 'get_initial_state') {
-      const result = await invoke<T>(cmd, args);
+      const result = await tauriInvoke<T>(cmd, args);
       this.currentState = result as ServerState;
       this.lastDeltaSeq = null;
       return result;
@@ -264,7 +384,7 @@ export class TauriAdapter implements TmuxAdapter {
       });
       this.sendQueue = this.sendQueue.then(async () => {
         try {
-          const result = await invoke<T>(cmd, args);
+          const result = await tauriInvoke<T>(cmd, args);
           resolveOuter(result);
         } catch (err) {
           rejectOuter(err);
@@ -273,7 +393,7 @@ export class TauriAdapter implements TmuxAdapter {
       return outer;
     }
 
-    return invoke(cmd, args);
+    return tauriInvoke(cmd, args);
   }
 
   onStateChange(listener: StateListener): () => void {
@@ -316,14 +436,63 @@ export class TauriAdapter implements TmuxAdapter {
     return () => this.clipboardListeners.delete(listener);
   }
 
+  onBell(listener: BellListener): () => void {
+    this.bellListeners.add(listener);
+    return () => this.bellListeners.delete(listener);
+  }
+
+  onDragTarget(listener: DragTargetListener): () => void {
+    this.dragTargetListeners.add(listener);
+    return () => this.dragTargetListeners.delete(listener);
+  }
+
+  onSpokenText(listener: SpokenTextListener): () => void {
+    this.spokenTextListeners.add(listener);
+    return () => this.spokenTextListeners.delete(listener);
+  }
+
+  onOutputTrimmed(listener: OutputTrimmedListener): () => void {
+    this.outputTrimmedListeners.add(listener);
+    return () => this.outputTrimmedListeners.delete(listener);
+  }
+
+  onPaneNotify(listener: PaneNotifyListener): () => void {
+    this.paneNotifyListeners.add(listener);
+    return () => this.paneNotifyListeners.delete(listener);
+  }
+
+  onCommandError(listener: CommandErrorListener): () => void {
+    this.commandErrorListeners.add(listener);
+    return () => this.commandErrorListeners.delete(listener);
+  }
+
+  onSessionLost(listener: SessionLostListener): () => void {
+    this.sessionLostListeners.add(listener);
+    return () => this.sessionLostListeners.delete(listener);
+  }
+
+  onWidgetData(listener: WidgetDataListener): () => void {
+    this.widgetDataListeners.add(listener);
+    return () => this.widgetDataListeners.delete(listener);
+  }
+
+  onOptionChanged(listener: OptionChangeListener): () => void {
+    this.optionChangeListeners.add(listener);
+    return () => this.optionChangeListeners.delete(listener);
+  }
+
+  onSearchResults(listener: SearchResultsListener): () => void {
+    this.searchResultsListeners.add(listener);
+    return () => this.searchResultsListeners.delete(listener);
+  }
+
   /**
    * Read-only tmux query that bypasses the mutation serial queue (see
    * TmuxAdapter.queryReadonly) — go straight to the Tauri command instead of
    * chaining onto `sendQueue`, so the sessions poll can't delay window ops.
    */
   async queryReadonly(command: string): Promise<string> {
-    const { invoke } = await import('@tauri-apps/api/core');
-    return invoke<string>('run_tmux_command', { command });
+    return tauriInvoke<string>('run_tmux_command', { command });
   }
 
   private notifyStateChange(state: ServerState) {
@@ -366,6 +535,46 @@ export class TauriAdapter implements TmuxAdapter {
     this.clipboardListeners.forEach((listener) => listener(paneId, text));
   }
 
+  private notifyBell(paneId: string, sound: boolean) {
+    this.bellListeners.forEach((listener) => listener(paneId, sound));
+  }
+
+  private notifyDragTarget(target: PaneDropTarget | null) {
+    this.dragTargetListeners.forEach((listener) => listener(target));
+  }
+
+  private notifySpokenText(paneId: string, lines: string[]) {
+    this.spokenTextListeners.forEach((listener) => listener(paneId, lines));
+  }
+
+  private notifyOutputTrimmed(paneId: string, lines: number) {
+    this.outputTrimmedListeners.forEach((listener) => listener(paneId, lines));
+  }
+
+  private notifyPaneNotify(paneId: string, title: string, body: string) {
+    this.paneNotifyListeners.forEach((listener) => listener(paneId, title, body));
+  }
+
+  private notifyCommandError(command: string, error: string) {
+    this.commandErrorListeners.forEach((listener) => listener(command, error));
+  }
+
+  private notifySessionLost(reason: string, recreating: boolean) {
+    this.sessionLostListeners.forEach((listener) => listener(reason, recreating));
+  }
+
+  private notifyWidgetData(paneId: string, data: unknown) {
+    this.widgetDataListeners.forEach((listener) => listener(paneId, data));
+  }
+
+  private notifyOptionChanged(change: OptionChange) {
+    this.optionChangeListeners.forEach((listener) => listener(change));
+  }
+
+  private notifySearchResults(results: SearchResults) {
+    this.searchResultsListeners.forEach((listener) => listener(results));
+  }
+
   /** Refetch a full snapshot after a delta seq gap (see HttpAdapter). */
   private async resyncFullState(): Promise<void> {
     if (this.resyncing) return;
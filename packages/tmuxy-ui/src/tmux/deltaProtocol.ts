@@ -47,6 +47,19 @@ export function isDeltaSeqGap(prevSeq: number | null, delta: ServerDelta): boole
   return prevSeq !== null && delta.seq !== prevSeq + 1;
 }
 
+/**
+ * Detect a delta from another epoch than the state it would apply to. Every
+ * control-mode connection on the server starts a new epoch and opens with a
+ * full state; after a monitor reconnect the seq can line up by coincidence
+ * while the delta describes a different base, so the adapter should refetch.
+ *
+ * An unversioned state (epoch 0 or absent, e.g. a `get_initial_state`
+ * snapshot) accepts any epoch, and `applyDelta` adopts the delta's.
+ */
+export function isDeltaFromOtherEpoch(state: ServerState | null, delta: ServerDelta): boolean {
+  return !!state?.epoch && !!delta.epoch && state.epoch !== delta.epoch;
+}
+
 /**
  * Handle a StateUpdate (full or delta), returning the new state.
  * Returns null if a delta arrives before any full state.
@@ -110,12 +123,36 @@ export function applyDelta(state: ServerState, delta: ServerDelta): ServerState
   if (delta.status_line !== undefined) {
     newState.status_line = delta.status_line;
   }
+  if (delta.status_segments !== undefined) {
+    newState.status_segments = delta.status_segments;
+  }
+  if (delta.status_lines !== undefined) {
+    newState.status_lines = delta.status_lines;
+  }
+  if (delta.status_position !== undefined) {
+    newState.status_position = delta.status_position;
+  }
+  if (delta.session_group !== undefined) {
+    newState.session_group = delta.session_group ?? undefined;
+  }
   if (delta.total_width !== undefined) {
     newState.total_width = delta.total_width;
   }
   if (delta.total_height !== undefined) {
     newState.total_height = delta.total_height;
   }
+  if (delta.do_not_disturb !== undefined) {
+    newState.do_not_disturb = delta.do_not_disturb;
+  }
+  if (delta.theme !== undefined) {
+    newState.theme = delta.theme;
+  }
+  if (delta.ssh_agent !== undefined) {
+    newState.ssh_agent = delta.ssh_agent;
+  }
+  if (delta.epoch) {
+    newState.epoch = delta.epoch;
+  }
 
   if (delta.panes || delta.new_panes) {
     const paneMap = new Map<string, ServerPane>();
@@ -213,6 +250,10 @@ export function cellLinesEqual(a: CellLine, b: CellLine): boolean {
       (ca.s.dim ?? false) !== (cb.s.dim ?? false) ||
       (ca.s.italic ?? false) !== (cb.s.italic ?? false) ||
       (ca.s.underline ?? false) !== (cb.s.underline ?? false) ||
+      ca.s.underline_style !== cb.s.underline_style ||
+      JSON.stringify(ca.s.underline_color) !== JSON.stringify(cb.s.underline_color) ||
+      (ca.s.strikethrough ?? false) !== (cb.s.strikethrough ?? false) ||
+      (ca.s.blink ?? false) !== (cb.s.blink ?? false) ||
       (ca.s.inverse ?? false) !== (cb.s.inverse ?? false) ||
       ca.s.url !== cb.s.url
     )
@@ -260,8 +301,12 @@ function applyPaneDelta(pane: ServerPane, delta: PaneDelta): ServerPane {
   // is non-empty, preserve existing content. This happens when a pane is resized
   // (vt100 parser reset) but capture-pane refill hasn't arrived yet.
   let mergedContent: PaneContent | undefined;
+  if (delta.scroll !== undefined) {
+    // Surviving rows keep their identity, so React.memo skips them.
+    mergedContent = [...pane.content.slice(delta.scroll.lines), ...delta.scroll.new_rows];
+  }
   if (delta.content !== undefined) {
-    const candidate = mergeSparseContent(pane.content, delta.content);
+    const candidate = mergeSparseContent(mergedContent ?? pane.content, delta.content);
     if (isPaneContentEmpty(candidate) && !isPaneContentEmpty(pane.content)) {
       mergedContent = pane.content;
     } else {
@@ -290,12 +335,23 @@ function applyPaneDelta(pane: ServerPane, delta: PaneDelta): ServerPane {
     ...(delta.mouse_any_flag !== undefined && { mouse_any_flag: delta.mouse_any_flag }),
     ...(delta.paused !== undefined && { paused: delta.paused }),
     ...(delta.history_size !== undefined && { history_size: delta.history_size }),
+    ...(delta.scroll_position !== undefined && { scroll_position: delta.scroll_position }),
     ...(delta.selection_present !== undefined && { selection_present: delta.selection_present }),
     ...(delta.selection_start_x !== undefined && { selection_start_x: delta.selection_start_x }),
     ...(delta.selection_start_y !== undefined && { selection_start_y: delta.selection_start_y }),
+    ...(delta.selection_end_x !== undefined && { selection_end_x: delta.selection_end_x }),
+    ...(delta.selection_end_y !== undefined && { selection_end_y: delta.selection_end_y }),
     ...(delta.images !== undefined && { images: delta.images }),
+    ...(delta.command_blocks !== undefined && { command_blocks: delta.command_blocks }),
     ...(delta.cursor_shape !== undefined && { cursor_shape: delta.cursor_shape }),
     ...(delta.cursor_hidden !== undefined && { cursor_hidden: delta.cursor_hidden }),
+    ...(delta.extended_keys !== undefined && { extended_keys: delta.extended_keys }),
+    ...(delta.colors !== undefined && { colors: delta.colors }),
+    ...(delta.sandbox !== undefined && { sandbox: delta.sandbox }),
+    ...(delta.pointer_shape !== undefined && { pointer_shape: delta.pointer_shape }),
+    ...(delta.widget !== undefined && { widget: delta.widget }),
+    ...(delta.cwd !== undefined && { cwd: delta.cwd }),
+    ...(delta.pid !== undefined && { pid: delta.pid }),
   };
 }
 
@@ -312,6 +368,11 @@ function applyWindowDelta(window: ServerWindow, delta: WindowDelta): ServerWindo
     ...(delta.float_drawer !== undefined && { float_drawer: delta.float_drawer }),
     ...(delta.float_bg !== undefined && { float_bg: delta.float_bg }),
     ...(delta.float_noheader !== undefined && { float_noheader: delta.float_noheader }),
+    ...(delta.float_x !== undefined && { float_x: delta.float_x }),
+    ...(delta.float_y !== undefined && { float_y: delta.float_y }),
+    ...(delta.float_z !== undefined && { float_z: delta.float_z }),
+    ...(delta.float_hidden !== undefined && { float_hidden: delta.float_hidden }),
     ...(delta.zoomed !== undefined && { zoomed: delta.zoomed }),
+    ...(delta.status !== undefined && { status: delta.status }),
   };
 }
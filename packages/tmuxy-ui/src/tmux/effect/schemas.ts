@@ -30,6 +30,10 @@ export const CellStyle = Schema.Struct({
   dim: Schema.optional(Schema.Boolean),
   italic: Schema.optional(Schema.Boolean),
   underline: Schema.optional(Schema.Boolean),
+  underline_style: Schema.optional(Schema.Literal('double', 'curly', 'dotted', 'dashed')),
+  underline_color: Schema.optional(CellColor),
+  strikethrough: Schema.optional(Schema.Boolean),
+  blink: Schema.optional(Schema.Boolean),
   inverse: Schema.optional(Schema.Boolean),
   url: Schema.optional(Schema.String),
 });
@@ -38,6 +42,7 @@ export const CellStyle = Schema.Struct({
 export const TerminalCell = Schema.Struct({
   c: Schema.String,
   s: Schema.optional(CellStyle),
+  w: Schema.optional(Schema.Number),
 });
 
 /** A line of cells. */
@@ -46,6 +51,19 @@ export const CellLine = Schema.Array(TerminalCell);
 /** Full pane content: array of lines. */
 export const PaneContent = Schema.Array(CellLine);
 
+/** The tmux status line as cells: left side, window entries, right side. */
+export const StatusSegments = Schema.Struct({
+  left: CellLine,
+  windows: Schema.Array(
+    Schema.Struct({
+      window_id: Schema.String,
+      active: Schema.Boolean,
+      cells: CellLine,
+    }),
+  ),
+  right: CellLine,
+});
+
 /** Image placement on the terminal grid. */
 export const ServerImagePlacement = Schema.Struct({
   id: Schema.Number,
@@ -60,6 +78,29 @@ export const ServerImagePlacement = Schema.Struct({
   ),
 });
 
+/** Rows a command and its output cover on the screen (OSC 133). */
+export const ServerCommandBlock = Schema.Struct({
+  id: Schema.Number,
+  start_row: Schema.Number,
+  end_row: Schema.Number,
+  exit_status: Schema.optional(Schema.Number),
+  running: Schema.optional(Schema.Boolean),
+});
+
+/** Default colours and palette overrides, each `#rrggbb`. */
+export const TerminalColors = Schema.Struct({
+  foreground: Schema.optional(Schema.String),
+  background: Schema.optional(Schema.String),
+  palette: Schema.optional(Schema.Record({ key: Schema.String, value: Schema.String })),
+});
+
+/** Widget a pane displays (`@tmuxy-widget`). */
+export const WidgetSpec = Schema.Struct({
+  kind: Schema.String,
+  source: Schema.optional(Schema.String),
+  options: Schema.optional(Schema.Record({ key: Schema.String, value: Schema.String })),
+});
+
 /** Full pane snapshot from the server. */
 export const ServerPane = Schema.Struct({
   id: Schema.Number,
@@ -83,12 +124,23 @@ export const ServerPane = Schema.Struct({
   mouse_any_flag: Schema.optional(Schema.Boolean),
   paused: Schema.optional(Schema.Boolean),
   history_size: Schema.optional(Schema.Number),
+  scroll_position: Schema.optional(Schema.Number),
   selection_present: Schema.optional(Schema.Boolean),
   selection_start_x: Schema.optional(Schema.Number),
   selection_start_y: Schema.optional(Schema.Number),
+  selection_end_x: Schema.optional(Schema.Number),
+  selection_end_y: Schema.optional(Schema.Number),
   images: Schema.optional(Schema.Array(ServerImagePlacement)),
+  command_blocks: Schema.optional(Schema.Array(ServerCommandBlock)),
   cursor_shape: Schema.optional(Schema.Number),
   cursor_hidden: Schema.optional(Schema.Boolean),
+  extended_keys: Schema.optional(Schema.Boolean),
+  colors: Schema.optional(TerminalColors),
+  sandbox: Schema.optional(Schema.String),
+  pointer_shape: Schema.optional(Schema.String),
+  widget: Schema.optional(Schema.NullOr(WidgetSpec)),
+  cwd: Schema.optional(Schema.NullOr(Schema.String)),
+  pid: Schema.optional(Schema.NullOr(Schema.Number)),
 });
 
 /** Window type set on @tmuxy-window-type. Null = foreign window. */
@@ -108,6 +160,11 @@ export const ServerWindow = Schema.Struct({
   float_drawer: Schema.optional(Schema.NullOr(Schema.String)),
   float_bg: Schema.optional(Schema.NullOr(Schema.String)),
   float_noheader: Schema.optional(Schema.Boolean),
+  float_x: Schema.optional(Schema.NullOr(Schema.Number)),
+  float_y: Schema.optional(Schema.NullOr(Schema.Number)),
+  float_z: Schema.optional(Schema.NullOr(Schema.Number)),
+  float_hidden: Schema.optional(Schema.Boolean),
+  status: Schema.optional(Schema.NullOr(Schema.String)),
 });
 
 /** Full server state snapshot. */
@@ -120,6 +177,16 @@ export const ServerState = Schema.Struct({
   total_width: Schema.Number,
   total_height: Schema.Number,
   status_line: Schema.String,
+  status_segments: Schema.optional(StatusSegments),
+  status_lines: Schema.optional(Schema.Array(CellLine)),
+  status_position: Schema.optional(Schema.Literal('top', 'bottom')),
+  session_group: Schema.optional(
+    Schema.Struct({ name: Schema.String, sessions: Schema.Array(Schema.String) }),
+  ),
+  do_not_disturb: Schema.optional(Schema.Boolean),
+  theme: Schema.optional(TerminalColors),
+  ssh_agent: Schema.optional(Schema.Literal('unset', 'live', 'dead')),
+  epoch: Schema.optional(Schema.Number),
 });
 
 // Schema-derived TS types. The existing hand-written interfaces in
@@ -0,0 +1,1398 @@
+// Generated by `cargo xtask generate-bindings` from the Rust wire types
+// (tmuxy-protocol's ServerEvent and ClientCommand). Do not edit.
+
+export const PROTOCOL_VERSION = 1;
+
+/** Color representation for terminal cells */
+export type CellColor =
+  /** Indexed color (0-255) */
+  | number
+  /** RGB color */
+  | { b: number; g: number; r: number };
+
+/** Cell style attributes (only present if cell has non-default styling) */
+export interface CellStyle {
+  bg?: CellColor | null;
+  blink?: boolean;
+  bold?: boolean;
+  /** SGR 2: faint/dim text. Apps like Claude Code use this for autosuggestions. */
+  dim?: boolean;
+  fg?: CellColor | null;
+  inverse?: boolean;
+  italic?: boolean;
+  strikethrough?: boolean;
+  /**
+   * Set for every underline shape, so a renderer that ignores
+   * `underline_style` still draws a plain underline.
+   */
+  underline?: boolean;
+  /** SGR 58: underline color (unset means the foreground color). */
+  underline_color?: CellColor | null;
+  /** SGR 4:2..4:5 / SGR 21: underline shape other than a single line. */
+  underline_style?: UnderlineStyle | null;
+  /** OSC 8 hyperlink URL (if cell is part of a hyperlink) */
+  url?: string | null;
+}
+
+/**
+ * All client → server commands the frontend actually sends. The wire JSON
+ * looks like `{ "cmd": "...", "args": { ... } }`. Variants with no fields
+ * require no `args` key; the TS adapter still sends an empty `args` object for
+ * them, which `ClientCommand::decode` strips before deserializing (serde's
+ * adjacently-tagged rules reject a `{}` map for a unit variant on their own).
+ */
+export type ClientCommand =
+  | {
+      args: {
+        cols?: number | null;
+        rows?: number | null;
+      };
+      cmd: 'get_initial_state';
+    }
+  | {
+      args: { cols: number; rows: number };
+      cmd: 'set_client_size';
+    }
+  | {
+      args: {
+        command: string;
+        /**
+         * Wait for the command to run and return its output (the error, if
+         * it failed) instead of `null`.
+         */
+        output?: boolean;
+      };
+      cmd: 'run_tmux_command';
+    }
+  /**
+   * Pace this connection's events to `fps` frames per second; 0 removes
+   * the budget.
+   */
+  | {
+      args: { fps: number };
+      cmd: 'set_frame_budget';
+    }
+  /**
+   * Raw mouse events for one pane; the monitor decides how each reaches
+   * the pane (SGR report, arrow keys, copy-mode scroll).
+   */
+  | {
+      args: { events: MouseEvent[]; paneId: string };
+      cmd: 'send_mouse_events';
+    }
+  /**
+   * One raw key press for a pane whose application asked for an extended
+   * keyboard protocol; the monitor encodes it for that protocol.
+   */
+  | {
+      args: { key: KeyEvent; paneId: string };
+      cmd: 'send_key';
+    }
+  /**
+   * One key press resolved on the server against the user's key tables:
+   * the prefix, a bound command, or a key for the pane. Replies with
+   * whether this connection is now in prefix mode.
+   */
+  | {
+      args: { key: KeyEvent; paneId: string };
+      cmd: 'key_input';
+    }
+  /**
+   * The `tmuxy`, `root` and `prefix` key tables as `key_input` resolves
+   * them, for a help overlay.
+   */
+  | { cmd: 'get_effective_bindings' }
+  /** Composed text (IME, emoji, mobile input) to type into a pane as is. */
+  | {
+      args: { paneId: string; text: string };
+      cmd: 'send_text';
+    }
+  /**
+   * Type command `id` from the pane's command history (see
+   * `get_command_history`) back in and run it. Refused while the pane's
+   * last command is still running.
+   */
+  | {
+      args: { id: number; paneId: string };
+      cmd: 'rerun_command';
+    }
+  /** Run one copy-mode command on a pane, `count` times. */
+  | {
+      args: {
+        action: CopyModeAction;
+        count?: number | null;
+        paneId: string;
+      };
+      cmd: 'copy_mode_action';
+    }
+  /** Scroll a pane's copy-mode view to `offset` lines above the live bottom. */
+  | {
+      args: { offset: number; paneId: string };
+      cmd: 'scroll_to';
+    }
+  /**
+   * Split `pane_id` into a new pane with the same cwd and environment,
+   * optionally re-running its foreground command.
+   */
+  | {
+      args: { paneId: string; rerun?: boolean };
+      cmd: 'duplicate_pane';
+    }
+  /**
+   * Open a tab whose pane starts in `cwd` running `command`; either left
+   * out falls back to the current pane's directory and the session's
+   * default command. With `sandbox`, the command runs inside that
+   * `sandbox-exec` profile and the pane is tagged with it.
+   */
+  | {
+      args: {
+        command?: string | null;
+        cwd?: string | null;
+        sandbox?: string | null;
+      };
+      cmd: 'new_window_with_command';
+    }
+  /**
+   * Split `pane_id`, starting the new pane as `new_window_with_command`
+   * does.
+   */
+  | {
+      args: {
+        command?: string | null;
+        cwd?: string | null;
+        direction: SplitDirection;
+        paneId: string;
+        sandbox?: string | null;
+      };
+      cmd: 'split_with_command';
+    }
+  /** Drag a pane border from one window-relative cell to another. */
+  | {
+      args: {
+        endX: number;
+        endY: number;
+        startX: number;
+        startY: number;
+        windowId: string;
+      };
+      cmd: 'drag_resize';
+    }
+  /** Start dragging `pane_id` to rearrange it (see `drop_pane_at`). */
+  | {
+      args: { paneId: string };
+      cmd: 'begin_pane_drag';
+    }
+  /**
+   * The dragged pane is over a window-relative cell; the server answers
+   * with `drag-target` events as the would-be drop changes.
+   */
+  | {
+      args: { x: number; y: number };
+      cmd: 'drag_pane_over';
+    }
+  /** Drop the dragged pane on a window-relative cell. */
+  | {
+      args: { x: number; y: number };
+      cmd: 'drop_pane_at';
+    }
+  /**
+   * Set the session's do-not-disturb mode: `on`, `off`, or `auto` to
+   * follow the server's quiet hours.
+   */
+  | {
+      args: { mode: DndMode };
+      cmd: 'set_do_not_disturb';
+    }
+  /**
+   * Replace the session's default terminal colours. Not `set_theme`, which
+   * picks the CSS theme.
+   */
+  | {
+      args: { theme: TerminalColors };
+      cmd: 'set_terminal_theme';
+    }
+  /**
+   * Report whether this client's window has focus. Panes that enabled
+   * focus reporting get `CSI I` / `CSI O` as the session gains its first
+   * focused client or loses its last.
+   */
+  | {
+      args: { focused: boolean };
+      cmd: 'set_focus';
+    }
+  /**
+   * Type the keyring secret `name` into a pane. Only the name crosses the
+   * wire; the value is looked up and typed by the backend.
+   */
+  | {
+      args: { name: string; paneId: string };
+      cmd: 'inject_secret';
+    }
+  /**
+   * Let a pane's output flow again after flow control paused it or
+   * `discard_pane_output` turned it off.
+   */
+  | {
+      args: { paneId: string };
+      cmd: 'resume_pane';
+    }
+  /**
+   * Stop a pane's output reaching clients (and, with no other client
+   * reading it, stop tmux reading the pane) until `resume_pane`.
+   */
+  | {
+      args: { paneId: string };
+      cmd: 'discard_pane_output';
+    }
+  /** Re-point the session's `SSH_AUTH_SOCK` at a live agent. */
+  | { cmd: 'fix_ssh_agent' }
+  /**
+   * Retune the session's emit scheduler (throttle, debounce, latency
+   * bound) and periodic sync (heartbeat period, extra pane fields);
+   * answers with the settings now in effect.
+   */
+  | {
+      /**
+       * Runtime overrides for the emit scheduler and the periodic sync, for
+       * debugging high-CPU or laggy sessions without a restart. `None` keeps the
+       * current value.
+       */
+      args: {
+        heartbeat_interval_ms?: number | null;
+        max_latency_ms?: number | null;
+        output_debounce_ms?: number | null;
+        rate_window_ms?: number | null;
+        sync_fields?: SyncField[] | null;
+        throttle_interval_ms?: number | null;
+        throttle_threshold?: number | null;
+      };
+      cmd: 'set_monitor_tuning';
+    }
+  /**
+   * Replace the server's log filter (a level such as `debug`, or
+   * `RUST_LOG` directives); with no `level`, only report the current one.
+   */
+  | {
+      args: { level?: string | null };
+      cmd: 'set_log_level';
+    }
+  /** Split `pane_id` and run `widget` in the new pane. */
+  | {
+      args: { paneId: string; widget: WidgetSpec };
+      cmd: 'create_widget';
+    }
+  /** Split `pane_id` and run a git widget on the repository it is in. */
+  | {
+      args: { paneId: string };
+      cmd: 'create_git_widget';
+    }
+  /**
+   * Build a new session from a session template's text; returns the
+   * session's name, `session` if given, else the one the template names.
+   */
+  | {
+      args: {
+        format?: TemplateFormat;
+        session?: string | null;
+        template: string;
+      };
+      cmd: 'create_from_template';
+    }
+  /** A page of rows from a CSV/TSV file, optionally sorted by a column. */
+  | {
+      args: { query: TableQuery };
+      cmd: 'table_query';
+    }
+  /** Replace the spec of the widget running in `pane_id`. */
+  | {
+      args: { paneId: string; widget: WidgetSpec };
+      cmd: 'update_widget';
+    }
+  /** Stop the widget in `pane_id`, leaving a shell in the pane. */
+  | {
+      args: { paneId: string };
+      cmd: 'destroy_widget';
+    }
+  /** Running containers across docker, podman and kubectl. */
+  | { cmd: 'list_containers' }
+  /**
+   * Split `pane_id` (or, with `newWindow`, open a tab) running a shell
+   * inside a container.
+   */
+  | {
+      /** A shell to open in a container. */
+      args: {
+        /** Container id or name (the pod, for kubectl) */
+        container: string;
+        /** The pod's namespace (kubectl only) */
+        namespace?: string | null;
+        newWindow?: boolean;
+        paneId: string;
+        runtime: ContainerRuntime;
+        /** Program to run; `sh` when not given */
+        shell?: string | null;
+      };
+      cmd: 'open_container_shell';
+    }
+  /**
+   * Geometry splitting a pane would produce, for a live preview overlay.
+   * Nothing is split; `percent` is the new pane's share.
+   */
+  | {
+      args: {
+        direction: SplitDirection;
+        paneId: string;
+        percent?: number;
+      };
+      cmd: 'preview_split';
+    }
+  /** Panes idle for at least `days` days, longest-idle first. */
+  | {
+      args: { days?: number };
+      cmd: 'get_stale_panes';
+    }
+  /** What recently closed panes showed on the way out, most recent first. */
+  | { cmd: 'get_exit_summaries' }
+  /**
+   * Tab windows, most recently visited first, and the window an Alt-Tab
+   * cycle in progress is on.
+   */
+  | { cmd: 'get_window_mru' }
+  /**
+   * Alt-Tab: step through the MRU order while the modifier is held
+   * (`reverse` for Shift), then `release` to settle on the window.
+   */
+  | {
+      args: { release?: boolean; reverse?: boolean };
+      cmd: 'switch_last_window_cycle';
+    }
+  /** Open a float over a window (the active one by default). */
+  | {
+      args: { spec?: FloatSpec };
+      cmd: 'float_create';
+    }
+  /** Hide a float, or show a hidden one on top of the others. */
+  | {
+      args: { paneId: string };
+      cmd: 'float_toggle';
+    }
+  /** Move a float's top-left corner to cell `(x, y)`, rounded to `snap`. */
+  | {
+      args: {
+        paneId: string;
+        snap?: number | null;
+        x: number;
+        y: number;
+      };
+      cmd: 'float_move';
+    }
+  /** Resize a float to `width` x `height` cells, rounded to `snap`. */
+  | {
+      args: {
+        height: number;
+        paneId: string;
+        snap?: number | null;
+        width: number;
+      };
+      cmd: 'float_resize';
+    }
+  | {
+      args: { paneId: string };
+      cmd: 'float_close';
+    }
+  /** Turn a pane into a group with a new tab in its place. */
+  | {
+      args: { paneId: string };
+      cmd: 'group_create';
+    }
+  /** Open a new tab in the group `paneId` belongs to. */
+  | { args: { paneId: string }; cmd: 'group_add' }
+  /** Show a group member in its group's slot. */
+  | {
+      args: { paneId: string };
+      cmd: 'group_switch';
+    }
+  /** Show the tab at `index` of the group `paneId` belongs to. */
+  | {
+      args: { index: number; paneId: string };
+      cmd: 'group_switch_tab';
+    }
+  | {
+      args: { paneId: string };
+      cmd: 'group_close';
+    }
+  /** Move a group member to `index` in its group's tab order. */
+  | {
+      args: { index: number; paneId: string };
+      cmd: 'group_reorder';
+    }
+  /**
+   * Recent history held by the pane's emulator — no tmux round-trip, but
+   * bounded by the server's scrollback setting.
+   */
+  | {
+      args: { lines?: number; paneId: string };
+      cmd: 'get_recent_scrollback';
+    }
+  /**
+   * The commands a pane's shell marked with OSC 133 shell integration,
+   * oldest first: text, exit status, duration, and the prompt and output
+   * lines (same line numbers as capture-pane).
+   */
+  | {
+      args: { paneId: string };
+      cmd: 'get_command_history';
+    }
+  | {
+      args: {
+        end?: number;
+        paneId: string;
+        start?: number;
+      };
+      cmd: 'get_scrollback_cells';
+    }
+  /**
+   * Render a pane as a PNG or SVG for download: the visible area, or the
+   * `start..=end` range when given (same line numbers as capture-pane).
+   */
+  | {
+      args: {
+        end?: number | null;
+        format: ExportFormat;
+        paneId: string;
+        start?: number | null;
+      };
+      cmd: 'export_pane';
+    }
+  /**
+   * A pane as plain text and styled HTML: the visible area, the
+   * `start..=end` range, or a copy-mode selection.
+   */
+  | {
+      args: {
+        end?: number | null;
+        paneId: string;
+        selection?: TextSelection | null;
+        start?: number | null;
+      };
+      cmd: 'get_pane_text';
+    }
+  /**
+   * Search a pane's text. Answers with the visible area's matches; the
+   * history's follow as `search-results` events. An empty query matches
+   * nothing and stops the pane's search in progress.
+   */
+  | {
+      /** What to search for. */
+      args: {
+        /** Match case exactly; by default the search ignores case. */
+        caseSensitive?: boolean;
+        paneId: string;
+        query: string;
+        /** Treat `query` as a regular expression rather than literal text. */
+        regex?: boolean;
+      };
+      cmd: 'pane_search';
+    }
+  /** Read a tmux option; unset options read as "". */
+  | {
+      /** An option, as the frontend names it. */
+      args: {
+        name: string;
+        scope: OptionScope;
+        /**
+         * Session, window (`@3`) or pane (`%3`) to read or set it on. Unset
+         * means the session the client is attached to, or its current window
+         * or pane. Ignored for server options.
+         */
+        target?: string | null;
+      };
+      cmd: 'get_option';
+    }
+  /** Set a tmux option, or unset it when `value` is absent. */
+  | {
+      /** An option, as the frontend names it. */
+      args: {
+        name: string;
+        scope: OptionScope;
+        /**
+         * Session, window (`@3`) or pane (`%3`) to read or set it on. Unset
+         * means the session the client is attached to, or its current window
+         * or pane. Ignored for server options.
+         */
+        target?: string | null;
+        value?: string | null;
+      };
+      cmd: 'set_option';
+    }
+  /** Report the option's changes as `option-changed` events. */
+  | {
+      /** An option, as the frontend names it. */
+      args: {
+        name: string;
+        scope: OptionScope;
+        /**
+         * Session, window (`@3`) or pane (`%3`) to read or set it on. Unset
+         * means the session the client is attached to, or its current window
+         * or pane. Ignored for server options.
+         */
+        target?: string | null;
+      };
+      cmd: 'watch_option';
+    }
+  /**
+   * Create a session in this session's group (the same windows, its own
+   * current window), named `name` or `<session>-<n>`.
+   */
+  | {
+      args: { name?: string | null };
+      cmd: 'create_grouped_session';
+    }
+  | { cmd: 'get_theme_settings' }
+  | {
+      args: { mode?: string | null; name: string };
+      cmd: 'set_theme';
+    }
+  | { cmd: 'get_themes_list' }
+  | {
+      args: { mode: string };
+      cmd: 'set_theme_mode';
+    };
+
+/**
+ * The rows a command and its output cover on a pane's screen, as marked by
+ * the shell's OSC 133 integration.
+ */
+export interface CommandBlock {
+  /**
+   * Last row of the command's output, or of its command line when it
+   * printed nothing; the cursor's row while it runs.
+   */
+  end_row: number;
+  /** `None` while it runs, or when the shell didn't say. */
+  exit_status?: number | null;
+  /** The command's id in `get_command_history`, for `rerun_command`. */
+  id: number;
+  running?: boolean;
+  /**
+   * Row of the command's prompt (visible-area-relative, negative when it
+   * has scrolled off the top).
+   */
+  start_row: number;
+}
+
+/** A container runtime with an `exec` subcommand. */
+export type ContainerRuntime = 'docker' | 'podman' | 'kubectl';
+
+/**
+ * A copy-mode command. Wire form is `{ "name": "<kebab-case>" }`, with an
+ * `"arg"` for the actions that take one (search text, jump target).
+ */
+export type CopyModeAction =
+  /** Enter copy mode (`copy-mode`), a no-op if already in it. */
+  | { name: 'enter' }
+  | { name: 'cancel' }
+  | { name: 'cursor-up' }
+  | { name: 'cursor-down' }
+  | { name: 'cursor-left' }
+  | { name: 'cursor-right' }
+  | { name: 'start-of-line' }
+  | { name: 'end-of-line' }
+  | { name: 'next-word' }
+  | { name: 'next-word-end' }
+  | { name: 'previous-word' }
+  | { name: 'top-line' }
+  | { name: 'middle-line' }
+  | { name: 'bottom-line' }
+  | { name: 'halfpage-up' }
+  | { name: 'halfpage-down' }
+  | { name: 'page-up' }
+  | { name: 'page-down' }
+  | { name: 'scroll-up' }
+  | { name: 'scroll-down' }
+  | { name: 'history-top' }
+  | { name: 'history-bottom' }
+  | { name: 'begin-selection' }
+  | { name: 'select-line' }
+  | { name: 'rectangle-toggle' }
+  | { name: 'clear-selection' }
+  | { name: 'copy-selection' }
+  | { name: 'copy-selection-and-cancel' }
+  | { arg: string; name: 'search-forward' }
+  | { arg: string; name: 'search-backward' }
+  | { name: 'search-again' }
+  | { name: 'search-reverse' }
+  | { arg: string; name: 'jump-forward' }
+  | { arg: string; name: 'jump-backward' };
+
+/** Per-session do-not-disturb setting. */
+export type DndMode =
+  /** Follow the configured schedule; off when there is none. */
+  | 'auto'
+  /** Always on. */
+  | 'on'
+  /** Always off, even inside scheduled hours. */
+  | 'off';
+
+/** Side of a pane a dragged pane is docked against. */
+export type DropEdge = 'left' | 'right' | 'top' | 'bottom';
+
+/** Image format of an export. */
+export type ExportFormat = 'png' | 'svg';
+
+/** Options for a new float; the wire form mirrors `tmuxy pane float`'s flags. */
+export interface FloatSpec {
+  /** Backdrop behind the float: `dim`, `blur` or `none`. */
+  bg?: string | null;
+  /** Run this instead of a shell. */
+  command?: string | null;
+  /** Dock to an edge (`left`, `right`, `top`, `bottom`) as a drawer. */
+  drawer?: string | null;
+  height?: number | null;
+  hideHeader?: boolean;
+  /** Window to float over; the session's active window when unset. */
+  parent?: string | null;
+  snap?: number | null;
+  width?: number | null;
+  /** Position of the top-left corner; centered when unset. */
+  x?: number | null;
+  y?: number | null;
+}
+
+/** An image placement on the terminal grid. */
+export interface ImagePlacement {
+  /** Column where the image starts (0-indexed). */
+  col: number;
+  /** Height in terminal cells. */
+  height_cells: number;
+  /** Unique image ID (auto-incremented within this parser). */
+  id: number;
+  /** Which protocol produced this image. */
+  protocol: ImageProtocol;
+  /** Row where the image starts (0-indexed, screen-relative). */
+  row: number;
+  /** Width in terminal cells. */
+  width_cells: number;
+}
+
+/** Image protocol that produced this image. */
+export type ImageProtocol = 'iterm2' | 'kitty' | 'sixel';
+
+/** One binding of a key table. */
+export interface KeyBinding {
+  command: string;
+  description: string;
+  key: string;
+  /**
+   * Whether this binding has the `-r` (repeat) flag.
+   * Repeat bindings auto-re-enter prefix mode after execution.
+   */
+  repeat: boolean;
+}
+
+/** The prefix key and the bindings of the prefix and root tables. */
+export interface KeyBindings {
+  prefix_bindings: KeyBinding[];
+  prefix_key: string;
+  root_bindings: KeyBinding[];
+}
+
+/** One key press from a client, as the browser reported it. */
+export interface KeyEvent {
+  alt?: boolean;
+  /**
+   * `KeyboardEvent.code`: the physical key (`KeyA`, `Digit1`), which
+   * gives the unshifted key of a shifted chord.
+   */
+  code?: string;
+  ctrl?: boolean;
+  /** `KeyboardEvent.key`: the character typed, or a key name (`ArrowUp`). */
+  key?: string;
+  meta?: boolean;
+  /** Auto-repeat of a held key. */
+  repeat?: boolean;
+  shift?: boolean;
+}
+
+/** What a log entry reports. */
+export type LogKind =
+  /** A command about to be executed (e.g. `tmux has-session -t foo`) */
+  | 'command'
+  /** Output produced by a previously-logged command (stdout/stderr/exit) */
+  | 'output'
+  /** Informational progress message (no associated command) */
+  | 'info'
+  /** A non-fatal error encountered mid-flow */
+  | 'error';
+
+/** One raw mouse event, in 0-indexed pane-relative cell coordinates. */
+export type MouseEvent =
+  | {
+      button: number;
+      kind: 'press';
+      x: number;
+      y: number;
+    }
+  | {
+      button: number;
+      kind: 'release';
+      x: number;
+      y: number;
+    }
+  | {
+      button: number;
+      kind: 'drag';
+      x: number;
+      y: number;
+    }
+  /** Positive `lines` scrolls down, negative scrolls up. */
+  | {
+      kind: 'wheel';
+      lines: number;
+      x: number;
+      y: number;
+    };
+
+/** A watched option's new value. */
+export interface OptionChange {
+  name: string;
+  scope: OptionScope;
+  /** The window or pane whose value changed, for window and pane options. */
+  target?: string | null;
+  value: string;
+}
+
+/** Which options table an option lives in. */
+export type OptionScope = 'server' | 'session' | 'window' | 'pane';
+
+/** Delta update for a single pane (only changed fields) */
+export interface PaneDelta {
+  /** Active state (only if changed) */
+  active?: boolean | null;
+  /** Alternate screen mode (only if changed) */
+  alternate_on?: boolean | null;
+  /** Border title (only if changed) */
+  border_title?: string | null;
+  /** Application colour overrides (only if changed) */
+  colors?: TerminalColors | null;
+  /** Command (only if changed) */
+  command?: string | null;
+  /** Command blocks (only if changed) */
+  command_blocks?: CommandBlock[] | null;
+  /**
+   * Content (only changed lines) - line index → line content
+   * Only lines that differ from the previous state are included.
+   */
+  content?: Record<string, TerminalCell[]> | null;
+  copy_cursor_x?: number | null;
+  copy_cursor_y?: number | null;
+  /** Cursor hidden (only if changed) */
+  cursor_hidden?: boolean | null;
+  /** Cursor shape (only if changed) */
+  cursor_shape?: number | null;
+  /** Cursor position (only if changed) */
+  cursor_x?: number | null;
+  cursor_y?: number | null;
+  /** Working directory (only if changed; `Some(None)` when no longer fetched) */
+  cwd?: string | null;
+  /** Extended keyboard protocol (only if changed) */
+  extended_keys?: boolean | null;
+  height?: number | null;
+  /** History size (only if changed) */
+  history_size?: number | null;
+  /** Image placements (only if changed) */
+  images?: ImagePlacement[] | null;
+  /** Copy mode state (only if changed) */
+  in_mode?: boolean | null;
+  /** Mouse any flag (only if changed) */
+  mouse_any_flag?: boolean | null;
+  /** Flow control pause state (only if changed) */
+  paused?: boolean | null;
+  /** Process id (only if changed; `Some(None)` when no longer fetched) */
+  pid?: number | null;
+  /** Pointer shape (only if changed; empty for the default) */
+  pointer_shape?: string | null;
+  /** Sandbox profile (only if changed; empty when removed) */
+  sandbox?: string | null;
+  /**
+   * Scroll (only when the content moved up with fresh rows below). The
+   * client drops the top `lines` rows and appends `new_rows` *before*
+   * applying `content`, which then only carries rows that changed beyond
+   * the shift — `tail -f` style output costs about one row per line.
+   */
+  scroll?: ScrollDelta | null;
+  /** Copy-mode scroll position (only if changed) */
+  scroll_position?: number | null;
+  /** Selection end X (only if changed) */
+  selection_end_x?: number | null;
+  /** Selection end Y (only if changed) */
+  selection_end_y?: number | null;
+  /** Selection present (only if changed) */
+  selection_present?: boolean | null;
+  /** Selection start X (only if changed) */
+  selection_start_x?: number | null;
+  /** Selection start Y (only if changed) */
+  selection_start_y?: number | null;
+  /** Title (only if changed) */
+  title?: string | null;
+  /** Widget spec (only if changed; `Some(None)` when the widget exited) */
+  widget?: WidgetSpec | null;
+  /** Dimensions (only if changed) */
+  width?: number | null;
+  /** Window ID (only if changed, e.g. after swap-pane across windows) */
+  window_id?: string | null;
+  /** Position (only if changed) */
+  x?: number | null;
+  y?: number | null;
+}
+
+/**
+ * Where a dragged pane lands when dropped at a point; sent to clients as
+ * the live drag-target hint.
+ */
+export type PaneDropTarget =
+  /** Dropped on the middle of `target`: the two panes trade places. */
+  | {
+      action: 'swap';
+      source: string;
+      target: string;
+    }
+  /** Dropped near an edge of `target`: `source` is split in on that side. */
+  | {
+      action: 'join';
+      edge: DropEdge;
+      source: string;
+      target: string;
+    };
+
+/** Content shifted up by `lines` rows; `new_rows` fill the bottom. */
+export interface ScrollDelta {
+  lines: number;
+  new_rows: TerminalCell[][];
+}
+
+/** One match of a pane search. */
+export interface SearchMatch {
+  /** The row's text just after the match, up to 40 characters. */
+  after: string;
+  /** The row's text just before the match, up to 40 characters. */
+  before: string;
+  /** Cell column the match starts at. */
+  col: number;
+  /**
+   * capture-pane line number: negative lines are history, 0 is the first
+   * visible row.
+   */
+  line: number;
+  /** The matched text. */
+  text: string;
+  /** Width of the match in cells. */
+  width: number;
+}
+
+/** An event for clients. */
+export type ServerEvent =
+  /** Sent first on every connection. */
+  | {
+      data: {
+        connection_id: number;
+        default_shell: string;
+        /** The wire format version, `PROTOCOL_VERSION`. */
+        protocol_version: number;
+      };
+      event: 'connection-info';
+    }
+  | { data: StateUpdate; event: 'state-update' }
+  | { data: { message: string }; event: 'error' }
+  | { data: KeyBindings; event: 'keybindings' }
+  | {
+      data: { kind: LogKind; message: string };
+      event: 'log';
+    }
+  /** The host gave up on the session; no further events follow. */
+  | { data: { message: string }; event: 'fatal' }
+  /**
+   * OSC 52 clipboard request from a terminal application.
+   * Frontend mirrors the text into the system clipboard via navigator.clipboard.
+   */
+  | {
+      data: { pane_id: string; text: string };
+      event: 'clipboard';
+    }
+  /** A pane rang the terminal bell; `sound` follows the server's bell policy. */
+  | {
+      data: { pane_id: string; sound: boolean };
+      event: 'bell';
+    }
+  /** Where the pane being dragged would land; `null` clears the hint. */
+  | {
+      data: PaneDropTarget | null;
+      event: 'drag-target';
+    }
+  /** Lines a pane just completed, as plain text for screen readers. */
+  | {
+      data: { lines: string[]; pane_id: string };
+      event: 'spoken-text';
+    }
+  /** A pane over its output budget had `lines` lines skipped in a frame. */
+  | {
+      data: { lines: number; pane_id: string };
+      event: 'output-trimmed';
+    }
+  /**
+   * A pane asked for a desktop notification (OSC 9/777); `title` is
+   * empty for OSC 9.
+   */
+  | {
+      data: {
+        body: string;
+        pane_id: string;
+        title: string;
+      };
+      event: 'pane-notify';
+    }
+  /**
+   * A client's tmux command failed in control mode (`%error`) and no
+   * request was waiting on its output.
+   */
+  | {
+      data: { command: string; error: string };
+      event: 'command-error';
+    }
+  /**
+   * The session's tmux went away under a running monitor; `recreating`
+   * says whether the host is bringing it back.
+   */
+  | {
+      data: { reason: string; recreating: boolean };
+      event: 'session-lost';
+    }
+  /** Data a widget sent on its own OSC channel. */
+  | {
+      data: { data: unknown; pane_id: string };
+      event: 'widget-data';
+    }
+  /** A watched tmux option changed. */
+  | {
+      data: OptionChange;
+      event: 'option-changed';
+    }
+  /**
+   * History matches of a `pane_search`, a chunk at a time from the
+   * bottom up; `done` marks the last chunk.
+   */
+  | {
+      data: {
+        done: boolean;
+        matches: SearchMatch[];
+        pane_id: string;
+        search_id: number;
+      };
+      event: 'search-results';
+    };
+
+/** The session group a session belongs to. */
+export interface SessionGroup {
+  /** Group name (the name of the session the group was made from) */
+  name: string;
+  /** Every session in the group, this one included */
+  sessions: string[];
+}
+
+/**
+ * Orientation of a split, as tmux spells it: `horizontal` (`split-window -h`)
+ * puts the new pane to the right, `vertical` (`-v`) below.
+ */
+export type SplitDirection = 'horizontal' | 'vertical';
+
+/** What the session's `SSH_AUTH_SOCK` points at. */
+export type SshAgentStatus =
+  /** The session has no `SSH_AUTH_SOCK`. */
+  | 'unset'
+  /** An agent answers on it. */
+  | 'live'
+  /** Nothing answers: the connection that forwarded it is gone. */
+  | 'dead';
+
+/** Message type for state updates (full or delta) */
+export type StateUpdate =
+  /** Full state (used for initial sync and reconnection) */
+  | { state: TmuxState; type: 'full' }
+  /** Delta update (used for incremental updates) */
+  | { delta: TmuxDelta; type: 'delta' };
+
+/** Where tmux draws the status line (`status-position`). */
+export type StatusPosition = 'top' | 'bottom';
+
+/** The status line as styled cells, split where the bar is laid out. */
+export interface StatusSegments {
+  /** `status-left`, clipped to `status-left-length` */
+  left: TerminalCell[];
+  /** `status-right`, clipped to `status-right-length` */
+  right: TerminalCell[];
+  /** The window list, in window order */
+  windows: StatusWindow[];
+}
+
+/** One window's entry in the status line's window list. */
+export interface StatusWindow {
+  /**
+   * Whether this is the current window (drawn with
+   * `window-status-current-format`)
+   */
+  active: boolean;
+  cells: TerminalCell[];
+  /** Window ID (e.g., "@1") */
+  window_id: string;
+}
+
+/** A field the periodic sync can fetch for every pane. */
+export type SyncField =
+  /** `#{pane_current_path}` */
+  | 'cwd'
+  /** `#{pane_pid}` */
+  | 'pid';
+
+/** A page of rows from a table file, as the `table_query` command takes it. */
+export interface TableQuery {
+  /**
+   * Rows in the page, capped by the server (`table::MAX_PAGE_ROWS` in
+   * tmuxy-core).
+   */
+  limit?: number;
+  /** First row of the page, after sorting. */
+  offset?: number;
+  /** Absolute path of the file. */
+  path: string;
+  sort?: TableSort | null;
+}
+
+export interface TableSort {
+  /** Zero-based column index. */
+  column: number;
+  descending?: boolean;
+}
+
+/** The file formats a template can be written in. */
+export type TemplateFormat = 'yaml' | 'toml';
+
+/** A single terminal cell with character and optional styling */
+export interface TerminalCell {
+  /** The character(s) in this cell (usually single char, but can be multi-byte) */
+  c: string;
+  /** Style attributes (only present if cell has styling) */
+  s?: CellStyle | null;
+  /**
+   * Display width in columns: 2 for the first cell of a double-width
+   * character (CJK, emoji), 0 for the blank continuation cell after it,
+   * 1 otherwise (only present if not 1)
+   */
+  w?: number;
+}
+
+/**
+ * A set of colour overrides. Every colour is `#rrggbb`; anything left out
+ * falls through to the layer below (pane → server theme → CSS theme).
+ */
+export interface TerminalColors {
+  background?: string | null;
+  foreground?: string | null;
+  /**
+   * Palette entries by index. Keyed by the index in decimal, the shape a
+   * JSON object (and serde-wasm-bindgen) can carry.
+   */
+  palette?: Record<string, string>;
+}
+
+/**
+ * A copy-mode selection in capture-pane line numbers: negative rows are
+ * history, 0 is the first visible row. Columns are inclusive.
+ */
+export interface TextSelection {
+  endCol: number;
+  endRow: number;
+  /** Whole rows, like copy mode's line selection. */
+  lineMode?: boolean;
+  startCol: number;
+  startRow: number;
+}
+
+/** Delta state update - only includes what changed */
+export interface TmuxDelta {
+  /** Active pane changed */
+  active_pane_id?: string | null;
+  /** Active window changed */
+  active_window_id?: string | null;
+  /** Do-not-disturb turned on or off */
+  do_not_disturb?: boolean | null;
+  /** Epoch of the state this delta applies on top of */
+  epoch: number;
+  /** New panes (full data for newly added panes) */
+  new_panes?: TmuxPane[] | null;
+  /** New windows (full data for newly added windows) */
+  new_windows?: TmuxWindow[] | null;
+  /** Changed panes: pane_id -> delta (None = pane removed) */
+  panes?: Record<string, PaneDelta | null> | null;
+  /** Sequence number for ordering */
+  seq: number;
+  /** Session group changed (`Some(None)` when the session left its group) */
+  session_group?: SessionGroup | null;
+  /** SSH agent status changed */
+  ssh_agent?: SshAgentStatus | null;
+  /** Status line changed */
+  status_line?: string | null;
+  /** Extra status rows changed */
+  status_lines?: TerminalCell[][] | null;
+  /** Status position changed */
+  status_position?: StatusPosition | null;
+  /** Status line segments changed */
+  status_segments?: StatusSegments | null;
+  /** Server theme changed */
+  theme?: TerminalColors | null;
+  total_height?: number | null;
+  /** Total dimensions changed */
+  total_width?: number | null;
+  /** Changed windows: window_id -> delta (None = window removed) */
+  windows?: Record<string, WindowDelta | null> | null;
+}
+
+/** A single tmux pane */
+export interface TmuxPane {
+  active: boolean;
+  /**
+   * True if the application is in alternate screen mode (vim, less, htop)
+   * Used to determine scroll behavior (wheel -> arrow keys vs copy mode)
+   */
+  alternate_on: boolean;
+  border_title: string;
+  /** Colours the application set with OSC 4/10/11, layered over `theme` */
+  colors?: TerminalColors;
+  command: string;
+  /**
+   * Commands shown on screen, from the shell's OSC 133 integration, for
+   * exit-status gutters
+   */
+  command_blocks?: CommandBlock[];
+  /**
+   * Rendered cell grid. `Arc`-shared so building a state snapshot, storing
+   * `prev_state`, and diffing unchanged panes never deep-copies the grid —
+   * the cost that made a one-field delta as expensive as a full sync.
+   * Serializes transparently (serde `rc`), so the wire shape is unchanged.
+   */
+  content: TerminalCell[][];
+  copy_cursor_x: number;
+  copy_cursor_y: number;
+  /** Whether the cursor is hidden (DECTCEM mode 25 off / ESC[?25l) */
+  cursor_hidden: boolean;
+  /**
+   * Cursor shape from DECSCUSR: 0/1=block_blink, 2=block, 3=underline_blink, 4=underline, 5=bar_blink, 6=bar
+   */
+  cursor_shape: number;
+  cursor_x: number;
+  cursor_y: number;
+  /** Working directory, when the monitor's sync fetches it (`SyncField::Cwd`) */
+  cwd?: string | null;
+  /**
+   * Whether the application asked for an extended keyboard protocol
+   * (`modifyOtherKeys` or kitty flags); clients then send raw key events
+   */
+  extended_keys: boolean;
+  height: number;
+  /** Number of history lines (scrollback above the visible area) */
+  history_size: number;
+  id: number;
+  /** Image placements on this pane's terminal grid */
+  images?: ImagePlacement[];
+  in_mode: boolean;
+  /**
+   * True if the application has mouse tracking enabled
+   * When true, mouse events should be forwarded as SGR sequences
+   */
+  mouse_any_flag: boolean;
+  /**
+   * True if this pane's output is paused due to flow control
+   * When true, UI should show a pause indicator
+   */
+  paused: boolean;
+  /**
+   * Process id of the pane's command, when the monitor's sync fetches it
+   * (`SyncField::Pid`)
+   */
+  pid?: number | null;
+  /** CSS cursor the application asked for with OSC 22, empty for the default */
+  pointer_shape?: string;
+  /** Sandbox profile the pane's command runs in (`@tmuxy-sandbox`) */
+  sandbox?: string;
+  /**
+   * Lines scrolled back from the live bottom while in copy mode; with
+   * `history_size` this places a scrollbar thumb
+   */
+  scroll_position: number;
+  /**
+   * Selection end X (visible-area-relative column); tracks the copy cursor
+   * while selecting, only meaningful when selection_present
+   */
+  selection_end_x: number;
+  /** Selection end Y (visible-area-relative row, can be negative if off-screen) */
+  selection_end_y: number;
+  /** Whether a selection is active in copy mode */
+  selection_present: boolean;
+  /** Selection start X (visible-area-relative column), only meaningful when selection_present */
+  selection_start_x: number;
+  /** Selection start Y (visible-area-relative row, can be negative if off-screen) */
+  selection_start_y: number;
+  title: string;
+  tmux_id: string;
+  /** Widget the pane displays (`@tmuxy-widget`), `None` for a terminal */
+  widget?: WidgetSpec | null;
+  width: number;
+  window_id: string;
+  x: number;
+  y: number;
+}
+
+/** Full tmux state with all panes and windows */
+export interface TmuxState {
+  /** Active pane ID (e.g., "%0") */
+  active_pane_id: string | null;
+  /** Active window ID (e.g., "@0") */
+  active_window_id: string | null;
+  /** Do-not-disturb is in effect: bells and auto-raise are suppressed */
+  do_not_disturb: boolean;
+  /**
+   * Identifies the aggregator that produced this state. Deltas apply only
+   * on top of a state of the same epoch; 0 means unversioned (a snapshot
+   * captured outside the update stream).
+   */
+  epoch: number;
+  panes: TmuxPane[];
+  /** The session's group (`new-session -t`), if it is in one */
+  session_group?: SessionGroup | null;
+  /** Session name (e.g., "tmuxy") */
+  session_name: string;
+  /** Whether the session's `SSH_AUTH_SOCK` reaches a live agent */
+  ssh_agent: SshAgentStatus;
+  /** Rendered tmux status line with ANSI escape sequences */
+  status_line: string;
+  /**
+   * The status bar's rows below the first, with `status` set to 2 or
+   * more. They are not part of `total_height`: windows are sized to the
+   * pane area (see `resize_window`), so the frontend makes room for them
+   * the same way it does for the first row.
+   */
+  status_lines: TerminalCell[][];
+  /** Whether the status bar sits above or below the panes */
+  status_position: StatusPosition;
+  /**
+   * The same status line as styled cells: left side, window list entries
+   * (with their window IDs) and right side
+   */
+  status_segments: StatusSegments;
+  /** Server's default foreground, background and palette for every pane */
+  theme: TerminalColors;
+  total_height: number;
+  total_width: number;
+  windows: TmuxWindow[];
+}
+
+/** A single tmux window (tab/float/group/foreign) */
+export interface TmuxWindow {
+  active: boolean;
+  /** Float backdrop style (from @tmuxy-float-bg). */
+  float_bg?: string | null;
+  /** Drawer-style float direction (from @tmuxy-float-drawer). */
+  float_drawer?: string | null;
+  /** Float height in cells (from @tmuxy-float-height). */
+  float_height?: number | null;
+  /** True while the float is toggled out of view (from @tmuxy-float-hidden). */
+  float_hidden?: boolean;
+  /** True if the float hides its header chrome (from @tmuxy-float-noheader). */
+  float_noheader?: boolean;
+  /**
+   * Parent window ID for a float (the launcher window) or backdrop (the float).
+   * Sourced from @tmuxy-float-parent.
+   */
+  float_parent?: string | null;
+  /** Float width in cells (from @tmuxy-float-width). */
+  float_width?: number | null;
+  /** Float position in cells (from @tmuxy-float-x/y); None = centered. */
+  float_x?: number | null;
+  float_y?: number | null;
+  /** Stacking order among floats, highest on top (from @tmuxy-float-z). */
+  float_z?: number | null;
+  /** Group pane membership (from @tmuxy-group-panes), e.g. ["%4","%6","%7"]. */
+  group_panes?: string[] | null;
+  /** Window ID (e.g., "@0") */
+  id: string;
+  index: number;
+  name: string;
+  /**
+   * Evaluated `@tmuxy-status` format (see tmuxy-core's `control_mode::window_status`).
+   * None when the window has none, or it came out empty.
+   */
+  status?: string | null;
+  /** Window type as set via @tmuxy-window-type. None = foreign window. */
+  window_type?: WindowType | null;
+  /**
+   * True while a pane in this window is zoomed. tmux hides every other pane
+   * when zoomed; the frontend must not keep painting them underneath.
+   */
+  zoomed: boolean;
+}
+
+/**
+ * Underline shapes beyond the plain single line. Curly is what editors and
+ * LSP clients use for diagnostics.
+ */
+export type UnderlineStyle = 'double' | 'curly' | 'dotted' | 'dashed';
+
+/**
+ * What a widget pane shows: its type, the source it renders, and any
+ * widget-specific options.
+ */
+export interface WidgetSpec {
+  /** Widget type, matching a registered launcher and frontend component. */
+  kind: string;
+  options?: Record<string, string>;
+  /** What the widget renders: a path, a URL, or `-` for stdin. */
+  source?: string;
+}
+
+/** Delta update for a single window (only changed fields) */
+export interface WindowDelta {
+  active?: boolean | null;
+  float_bg?: string | null;
+  float_drawer?: string | null;
+  float_height?: number | null;
+  float_hidden?: boolean | null;
+  float_noheader?: boolean | null;
+  float_parent?: string | null;
+  float_width?: number | null;
+  float_x?: number | null;
+  float_y?: number | null;
+  float_z?: number | null;
+  group_panes?: string[] | null;
+  name?: string | null;
+  status?: string | null;
+  window_type?: WindowType | null;
+  /**
+   * True while this window has a zoomed pane. tmux hides the other panes
+   * entirely when zoomed, so the frontend needs this to do the same.
+   */
+  zoomed?: boolean | null;
+}
+
+/**
+ * Window type discriminator. Set on windows tmuxy created or has adopted.
+ * Windows without a type are foreign and tmuxy ignores them everywhere.
+ */
+export type WindowType =
+  | 'tab'
+  | 'float'
+  | 'float-backdrop'
+  | 'group'
+  /**
+   * The left sidebar's hidden window (runs the `tmuxy tree` TUI). Excluded
+   * from the tab bar like floats/groups; rendered in the UI as a left drawer.
+   */
+  | 'sidebar';
@@ -67,7 +67,14 @@ const snapshot = (over: Partial<TmuxSnapshot> = {}): TmuxSnapshot => ({
   totalWidth: 80,
   totalHeight: 24,
   statusLine: '',
+  statusSegments: { left: [], windows: [], right: [] },
+  statusLines: [],
+  statusPosition: 'bottom',
+  sessionGroup: null,
   sessionName: 'tmuxy',
+  doNotDisturb: false,
+  terminalTheme: {},
+  sshAgent: 'unset',
   ...over,
 });
 
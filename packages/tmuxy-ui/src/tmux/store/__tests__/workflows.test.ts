@@ -249,7 +249,14 @@ describe('TmuxStore — verbatim command preservation', () => {
       totalWidth: 80,
       totalHeight: 24,
       statusLine: '',
+      statusSegments: { left: [], windows: [], right: [] },
+      statusLines: [],
+      statusPosition: 'bottom',
+      sessionGroup: null,
       sessionName: 'tmuxy',
+      doNotDisturb: false,
+      terminalTheme: {},
+      sshAgent: 'unset',
     });
     const op: TmuxOp = { _tag: 'Split', direction: 'vertical' };
     const result = predict(op, m.committed, { defaultShell: 'bash', paneActivationOrder: [] }, 'X');
@@ -359,7 +366,14 @@ describe('TmuxStore — multiple in-flight ops compose', () => {
       totalWidth: 80,
       totalHeight: 24,
       statusLine: '',
+      statusSegments: { left: [], windows: [], right: [] },
+      statusLines: [],
+      statusPosition: 'bottom',
+      sessionGroup: null,
       sessionName: 'tmuxy',
+      doNotDisturb: false,
+      terminalTheme: {},
+      sshAgent: 'unset',
     };
     const m0 = modelFromSnapshot(baseSnap);
     const r1 = predict(
@@ -692,7 +706,14 @@ describe('Op predictions — tmux-output shape', () => {
       totalWidth: 80,
       totalHeight: 24,
       statusLine: '',
+      statusSegments: { left: [], windows: [], right: [] },
+      statusLines: [],
+      statusPosition: 'bottom',
+      sessionGroup: null,
       sessionName: 'tmuxy',
+      doNotDisturb: false,
+      terminalTheme: {},
+      sshAgent: 'unset',
     });
     const r = predict(
       { _tag: 'Split', direction: 'vertical' },
@@ -751,7 +772,14 @@ describe('Op predictions — tmux-output shape', () => {
       totalWidth: 80,
       totalHeight: 24,
       statusLine: '',
+      statusSegments: { left: [], windows: [], right: [] },
+      statusLines: [],
+      statusPosition: 'bottom',
+      sessionGroup: null,
       sessionName: 'tmuxy',
+      doNotDisturb: false,
+      terminalTheme: {},
+      sshAgent: 'unset',
     });
     const r = predict(
       { _tag: 'NewWindow' },
@@ -789,7 +817,14 @@ describe('Op predictions — tmux-output shape', () => {
       totalWidth: 160,
       totalHeight: 48,
       statusLine: '',
+      statusSegments: { left: [], windows: [], right: [] },
+      statusLines: [],
+      statusPosition: 'bottom',
+      sessionGroup: null,
       sessionName: 'tmuxy',
+      doNotDisturb: false,
+      terminalTheme: {},
+      sshAgent: 'unset',
     });
     const r = predict(
       { _tag: 'NewWindow' },
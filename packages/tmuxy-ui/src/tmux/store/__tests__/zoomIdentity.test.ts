@@ -72,3 +72,17 @@ describe('preserveSnapshotIdentity — zoom', () => {
     expect(preserveSnapshotIdentity(prev, next)).toBe(prev);
   });
 });
+
+describe('preserveSnapshotIdentity — float geometry', () => {
+  it('publishes a new window object when a float moves, restacks or hides', () => {
+    const float = (over: Partial<TmuxWindow>) =>
+      win({ windowType: 'float', floatX: 4, floatY: 2, floatZ: 1, ...over });
+    for (const over of [{ floatX: 10 }, { floatZ: 2 }, { floatHidden: true }]) {
+      const prev = snap([float({})]);
+      const next = snap([float(over)]);
+      expect(preserveSnapshotIdentity(prev, next)).not.toBe(prev);
+    }
+    const prev = snap([float({})]);
+    expect(preserveSnapshotIdentity(prev, snap([float({})]))).toBe(prev);
+  });
+});
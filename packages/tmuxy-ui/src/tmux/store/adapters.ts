@@ -62,9 +62,21 @@ function preservePane(prev: TmuxPane, next: TmuxPane): TmuxPane {
     prev.selectionStartY === next.selectionStartY &&
     prev.cursorShape === next.cursorShape &&
     prev.cursorHidden === next.cursorHidden &&
+    prev.extendedKeys === next.extendedKeys &&
+    prev.sandbox === next.sandbox &&
+    prev.pointerShape === next.pointerShape &&
+    prev.cwd === next.cwd &&
+    prev.pid === next.pid &&
     prev.windowId === next.windowId &&
     (prev.images === next.images ||
-      JSON.stringify(prev.images ?? null) === JSON.stringify(next.images ?? null));
+      JSON.stringify(prev.images ?? null) === JSON.stringify(next.images ?? null)) &&
+    (prev.commandBlocks === next.commandBlocks ||
+      JSON.stringify(prev.commandBlocks ?? null) ===
+        JSON.stringify(next.commandBlocks ?? null)) &&
+    (prev.colors === next.colors ||
+      JSON.stringify(prev.colors ?? null) === JSON.stringify(next.colors ?? null)) &&
+    (prev.widget === next.widget ||
+      JSON.stringify(prev.widget ?? null) === JSON.stringify(next.widget ?? null));
 
   if (scalarSame && contentSame) return prev;
   return { ...next, content };
@@ -82,10 +94,15 @@ function preserveWindow(prev: TmuxWindow, next: TmuxWindow): TmuxWindow {
     prev.floatDrawer === next.floatDrawer &&
     prev.floatBg === next.floatBg &&
     prev.floatNoheader === next.floatNoheader &&
+    (prev.floatX ?? null) === (next.floatX ?? null) &&
+    (prev.floatY ?? null) === (next.floatY ?? null) &&
+    (prev.floatZ ?? null) === (next.floatZ ?? null) &&
+    Boolean(prev.floatHidden) === Boolean(next.floatHidden) &&
     // Zoom toggles change nothing else about the window, so omitting it here
     // pins the old object identity and the UI stays stuck in (or out of) zoom
     // until some unrelated field happens to change.
     Boolean(prev.zoomed) === Boolean(next.zoomed) &&
+    (prev.status ?? null) === (next.status ?? null) &&
     (prev.groupPanes === next.groupPanes ||
       (prev.groupPanes?.join(',') ?? null) === (next.groupPanes?.join(',') ?? null));
   return same ? prev : next;
@@ -117,7 +134,14 @@ export function preserveSnapshotIdentity(prev: TmuxSnapshot, next: TmuxSnapshot)
     prev.totalWidth === next.totalWidth &&
     prev.totalHeight === next.totalHeight &&
     prev.statusLine === next.statusLine &&
-    prev.sessionName === next.sessionName;
+    prev.statusSegments === next.statusSegments &&
+    prev.statusLines === next.statusLines &&
+    prev.statusPosition === next.statusPosition &&
+    prev.sessionName === next.sessionName &&
+    prev.doNotDisturb === next.doNotDisturb &&
+    prev.sshAgent === next.sshAgent &&
+    JSON.stringify(prev.sessionGroup) === JSON.stringify(next.sessionGroup) &&
+    JSON.stringify(prev.terminalTheme) === JSON.stringify(next.terminalTheme);
 
   if (panesSame && windowsSame && scalarsSame) return prev;
   return {
@@ -14,7 +14,16 @@
  */
 
 import { Data } from 'effect';
-import type { TmuxPane, TmuxWindow } from '../types';
+import type {
+  CellLine,
+  SessionGroup,
+  SshAgentStatus,
+  StatusPosition,
+  StatusSegments,
+  TerminalColors,
+  TmuxPane,
+  TmuxWindow,
+} from '../types';
 
 // ============================================
 // Snapshot — the data the UI consumes
@@ -33,7 +42,21 @@ export interface TmuxSnapshot {
   readonly totalWidth: number;
   readonly totalHeight: number;
   readonly statusLine: string;
+  /** The status line as cells, window entries carrying their window IDs */
+  readonly statusSegments: StatusSegments;
+  /** Status rows below the first, with tmux's `status` set to 2 or more */
+  readonly statusLines: ReadonlyArray<CellLine>;
+  /** Whether the status bar goes above or below the panes */
+  readonly statusPosition: StatusPosition;
+  /** The session's group, or null when it is in none */
+  readonly sessionGroup: SessionGroup | null;
   readonly sessionName: string;
+  /** Do-not-disturb is in effect for the session */
+  readonly doNotDisturb: boolean;
+  /** Server's default terminal colours, under each pane's own overrides */
+  readonly terminalTheme: TerminalColors;
+  /** Whether the session's SSH agent socket is live */
+  readonly sshAgent: SshAgentStatus;
 }
 
 export const EMPTY_SNAPSHOT: TmuxSnapshot = {
@@ -44,7 +67,14 @@ export const EMPTY_SNAPSHOT: TmuxSnapshot = {
   totalWidth: 0,
   totalHeight: 0,
   statusLine: '',
+  statusSegments: { left: [], windows: [], right: [] },
+  statusLines: [],
+  statusPosition: 'bottom',
+  sessionGroup: null,
   sessionName: '',
+  doNotDisturb: false,
+  terminalTheme: {},
+  sshAgent: 'unset',
 };
 
 /** Branded string so a raw string can't be passed where an OpId is expected. */
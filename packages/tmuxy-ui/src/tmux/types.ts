@@ -1,3 +1,5 @@
+import type * as Wire from './generated/protocol';
+
 // ============================================
 // Tmux Domain Types
 // ============================================
@@ -39,12 +41,53 @@ export interface TmuxPane {
   selectionStartY: number;
   /** Image placements on this pane's terminal grid */
   images?: ImagePlacement[];
+  /** Commands on screen from the shell's OSC 133 integration, for exit-status gutters */
+  commandBlocks?: CommandBlock[];
   /** Cursor shape from DECSCUSR: 0/1=block_blink, 2=block, 3=underline_blink, 4=underline, 5=bar_blink, 6=bar */
   cursorShape: number;
   /** Whether the cursor is hidden (DECTCEM mode 25 off / ESC[?25l) */
   cursorHidden: boolean;
+  /** Whether the application asked for an extended keyboard protocol (modifyOtherKeys / kitty); keys then go out as raw `send_key` events */
+  extendedKeys?: boolean;
+  /** Colours the application set with OSC 4/10/11, layered over the server theme */
+  colors?: TerminalColors;
+  /** Sandbox profile the pane's command runs in (`tmuxy pane float --sandbox`) */
+  sandbox?: string;
+  /** CSS cursor the application asked for with OSC 22; absent for the default */
+  pointerShape?: string;
+  /** Widget the pane displays (`@tmuxy-widget`); absent or null for a terminal */
+  widget?: WidgetSpec | null;
+  /** Working directory, when the server's sync fetches it (`TMUXY_SYNC_FIELDS`) */
+  cwd?: string | null;
+  /** Process id of the pane's command, when the server's sync fetches it */
+  pid?: number | null;
+}
+
+/**
+ * A widget pane's type, source and options, as set by its launcher or the
+ * `create_widget` / `update_widget` commands. Option keys arrive camelized
+ * like every other server key.
+ */
+export interface WidgetSpec {
+  kind: string;
+  source?: string;
+  options?: Record<string, string>;
+}
+
+/**
+ * Default foreground/background and palette overrides, each `#rrggbb`.
+ * Anything unset falls through to the layer below.
+ */
+export interface TerminalColors {
+  foreground?: string;
+  background?: string;
+  /** Palette entries keyed by index (0-255) */
+  palette?: Record<string, string>;
 }
 
+/** Whether the session's SSH_AUTH_SOCK reaches a live agent */
+export type SshAgentStatus = Wire.SshAgentStatus;
+
 /** An image placement on the terminal grid */
 export interface ImagePlacement {
   id: number;
@@ -55,12 +98,25 @@ export interface ImagePlacement {
   protocol: 'iterm2' | 'kitty' | 'sixel';
 }
 
+/** Rows a command and its output cover on a pane's screen (OSC 133) */
+export interface CommandBlock {
+  /** The command's id in `get_command_history`, for `rerun_command` */
+  id: number;
+  /** Row of the command's prompt (visible-area-relative, negative when scrolled off the top) */
+  startRow: number;
+  /** Last row of its output, or of its command line when it printed nothing; the cursor's row while it runs */
+  endRow: number;
+  /** Absent while it runs, or when the shell didn't say */
+  exitStatus?: number;
+  running?: boolean;
+}
+
 /**
  * Window type as set on the tmux window via @tmuxy-window-type.
  * `null` means foreign — tmuxy never created or adopted this window and
  * filters it out everywhere.
  */
-export type WindowType = 'tab' | 'float' | 'float-backdrop' | 'group' | 'sidebar';
+export type WindowType = Wire.WindowType;
 
 export interface TmuxWindow {
   /** Window ID (e.g., "@0") */
@@ -84,9 +140,19 @@ export interface TmuxWindow {
   floatBg: string | null;
   /** True when the float hides its header chrome. */
   floatNoheader: boolean;
+  /** Float position in cells (@tmuxy-float-x/y). Absent = centered. */
+  floatX?: number | null;
+  floatY?: number | null;
+  /** Stacking order among floats, highest on top (@tmuxy-float-z). */
+  floatZ?: number | null;
+  /** True while the float is toggled out of view (@tmuxy-float-hidden). */
+  floatHidden?: boolean;
   /** True while a pane in this window is zoomed (tmux hides the others).
    *  Absent is equivalent to false. */
   zoomed?: boolean;
+  /** Evaluated @tmuxy-status format, shown in the status bar while this
+   *  window is active. Absent when the window has none. */
+  status?: string | null;
 }
 
 // ============================================
@@ -105,6 +171,14 @@ export interface CellStyle {
   dim?: boolean;
   italic?: boolean;
   underline?: boolean;
+  /** Underline shape when not a plain single line (SGR 4:2..4:5, 21) */
+  underline_style?: 'double' | 'curly' | 'dotted' | 'dashed';
+  /** SGR 58: underline color, when it differs from the text color */
+  underline_color?: CellColor;
+  /** SGR 9: crossed-out text */
+  strikethrough?: boolean;
+  /** SGR 5/6: blinking text */
+  blink?: boolean;
   inverse?: boolean;
   /** OSC 8 hyperlink URL */
   url?: string;
@@ -114,6 +188,7 @@ export interface CellStyle {
 export interface TerminalCell {
   c: string; // character
   s?: CellStyle; // style (optional)
+  w?: number; // display width: 2 = wide char, 0 = its continuation cell (absent = 1)
 }
 
 /** A line of terminal cells */
@@ -122,6 +197,31 @@ export type CellLine = TerminalCell[];
 /** Pane content is always structured cells */
 export type PaneContent = CellLine[];
 
+/** One window's entry in the tmux status line's window list */
+export interface StatusWindow {
+  window_id: string;
+  /** Drawn with window-status-current-format */
+  active: boolean;
+  cells: CellLine;
+}
+
+/** Where tmux draws the status bar (`status-position`) */
+export type StatusPosition = Wire.StatusPosition;
+
+/** A session group (`new-session -t`): sessions sharing one set of windows */
+export interface SessionGroup {
+  name: string;
+  /** Every session in the group, this one included */
+  sessions: string[];
+}
+
+/** The tmux status line as styled cells, split where the bar is laid out */
+export interface StatusSegments {
+  left: CellLine;
+  windows: StatusWindow[];
+  right: CellLine;
+}
+
 // ============================================
 // Client-Side Copy Mode Types
 // ============================================
@@ -176,12 +276,23 @@ export interface ServerPane {
   mouse_any_flag?: boolean;
   paused?: boolean;
   history_size?: number;
+  scroll_position?: number;
   selection_present?: boolean;
   selection_start_x?: number;
   selection_start_y?: number;
+  selection_end_x?: number;
+  selection_end_y?: number;
   images?: ServerImagePlacement[];
+  command_blocks?: ServerCommandBlock[];
   cursor_shape?: number;
   cursor_hidden?: boolean;
+  extended_keys?: boolean;
+  colors?: TerminalColors;
+  sandbox?: string;
+  pointer_shape?: string;
+  widget?: WidgetSpec | null;
+  cwd?: string | null;
+  pid?: number | null;
 }
 
 /** Image placement in snake_case from backend */
@@ -194,6 +305,15 @@ export interface ServerImagePlacement {
   protocol: 'iterm2' | 'kitty' | 'sixel';
 }
 
+/** Command block in snake_case from backend */
+export interface ServerCommandBlock {
+  id: number;
+  start_row: number;
+  end_row: number;
+  exit_status?: number;
+  running?: boolean;
+}
+
 export interface ServerWindow {
   id: string;
   index: number;
@@ -207,7 +327,12 @@ export interface ServerWindow {
   float_drawer?: string | null;
   float_bg?: string | null;
   float_noheader?: boolean;
+  float_x?: number | null;
+  float_y?: number | null;
+  float_z?: number | null;
+  float_hidden?: boolean;
   zoomed?: boolean;
+  status?: string | null;
 }
 
 export interface ServerState {
@@ -219,6 +344,21 @@ export interface ServerState {
   total_width: number;
   total_height: number;
   status_line: string;
+  /** The status line as cells; absent from servers that predate it */
+  status_segments?: StatusSegments;
+  /** Status rows below the first (`status 2` to `5`) */
+  status_lines?: CellLine[];
+  status_position?: StatusPosition;
+  /** The session's group; absent when it is in none */
+  session_group?: SessionGroup;
+  /** Do-not-disturb is in effect: bells and auto-raise are suppressed */
+  do_not_disturb?: boolean;
+  /** Server's default terminal colours */
+  theme?: TerminalColors;
+  /** Whether the session's SSH agent socket is live */
+  ssh_agent?: SshAgentStatus;
+  /** Monitor connection that produced this state; 0 or absent if unversioned */
+  epoch?: number;
 }
 
 // ============================================
@@ -229,6 +369,8 @@ export interface PaneDelta {
   window_id?: string;
   /** Sparse line updates: line index → cells (only changed lines) */
   content?: Record<number, CellLine>;
+  /** Pure scroll: drop the top `lines` rows and append `new_rows` */
+  scroll?: { lines: number; new_rows: CellLine[] };
   cursor_x?: number;
   cursor_y?: number;
   width?: number;
@@ -246,12 +388,23 @@ export interface PaneDelta {
   mouse_any_flag?: boolean;
   paused?: boolean;
   history_size?: number;
+  scroll_position?: number;
   selection_present?: boolean;
   selection_start_x?: number;
   selection_start_y?: number;
+  selection_end_x?: number;
+  selection_end_y?: number;
   images?: ServerImagePlacement[];
+  command_blocks?: ServerCommandBlock[];
   cursor_shape?: number;
   cursor_hidden?: boolean;
+  extended_keys?: boolean;
+  colors?: TerminalColors;
+  sandbox?: string;
+  pointer_shape?: string;
+  widget?: WidgetSpec | null;
+  cwd?: string | null;
+  pid?: number | null;
 }
 
 export interface WindowDelta {
@@ -265,11 +418,17 @@ export interface WindowDelta {
   float_drawer?: string | null;
   float_bg?: string | null;
   float_noheader?: boolean;
+  float_x?: number | null;
+  float_y?: number | null;
+  float_z?: number | null;
+  float_hidden?: boolean;
   zoomed?: boolean;
+  status?: string | null;
 }
 
 export interface ServerDelta {
   seq: number;
+  epoch?: number;
   panes?: Record<string, PaneDelta | null>; // null = removed
   windows?: Record<string, WindowDelta | null>; // null = removed
   new_panes?: ServerPane[];
@@ -277,8 +436,16 @@ export interface ServerDelta {
   active_window_id?: string;
   active_pane_id?: string;
   status_line?: string;
+  status_segments?: StatusSegments;
+  status_lines?: CellLine[];
+  status_position?: StatusPosition;
+  /** `null` when the session left its group */
+  session_group?: SessionGroup | null;
   total_width?: number;
   total_height?: number;
+  do_not_disturb?: boolean;
+  theme?: TerminalColors;
+  ssh_agent?: SshAgentStatus;
 }
 
 export type StateUpdate =
@@ -298,9 +465,92 @@ export type ReconnectionListener = (reconnecting: boolean, attempt: number) => v
  * the payload into the system clipboard via `navigator.clipboard.writeText`.
  */
 export type ClipboardListener = (paneId: string, text: string) => void;
+/**
+ * A pane rang the terminal bell (rate limited per pane by the backend).
+ * `sound` is set when the backend's bell policy asks for an audible bell.
+ */
+export type BellListener = (paneId: string, sound: boolean) => void;
+
+/**
+ * Where a pane being dragged (`begin_pane_drag` → `drop_pane_at`) would land:
+ * `swap` trades places with `target`, `join` splits the source in against
+ * `edge` of `target`. Generated from `PaneDropTarget` in Rust.
+ */
+export type PaneDropTarget = Wire.PaneDropTarget;
+
+/** One raw mouse event for `send_mouse_events`, in 0-indexed pane cells. */
+export type PaneMouseEvent = Wire.MouseEvent;
+
+/** Live drop-target hint while dragging a pane; `null` clears it. */
+export type DragTargetListener = (target: PaneDropTarget | null) => void;
+
+/**
+ * Lines of plain text a pane just completed, for an `aria-live` region to
+ * announce. Spinners, redraws and full-screen apps are already filtered out;
+ * only sent when the backend runs with `TMUXY_ACCESSIBLE_OUTPUT`.
+ */
+export type SpokenTextListener = (paneId: string, lines: string[]) => void;
+
+/**
+ * A pane over the backend's output budget (`TMUXY_OUTPUT_BUDGET`) had
+ * `lines` lines scroll by unseen in one frame. The frame itself already
+ * carries an "output trimmed" marker row.
+ */
+export type OutputTrimmedListener = (paneId: string, lines: number) => void;
+
+/**
+ * A pane asked for a desktop notification with OSC 9 (`title` empty) or
+ * OSC 777. Not sent under do-not-disturb.
+ */
+export type PaneNotifyListener = (paneId: string, title: string, body: string) => void;
+
+/**
+ * A tmux command the client sent failed in control mode (`%error`), with
+ * tmux's message (`no space for new pane`). Not sent for commands invoked
+ * with `output: true`: their promise rejects instead.
+ */
+export type CommandErrorListener = (command: string, error: string) => void;
+
+/**
+ * The session's tmux went away while it was being watched (killed, or no
+ * longer answering). `recreating` says whether the backend is bringing the
+ * session back; otherwise no more updates will come.
+ */
+export type SessionLostListener = (reason: string, recreating: boolean) => void;
+
+/**
+ * A JSON payload a widget pane's process wrote as `OSC 7331`. The data
+ * bypasses the pane's screen, so widgets can render structured state instead
+ * of parsing their own output.
+ */
+export type WidgetDataListener = (paneId: string, data: unknown) => void;
+
+export type OptionScope = Wire.OptionScope;
+
+/** A watched tmux option's new value (matches `OptionChange` in Rust). */
+export interface OptionChange {
+  scope: OptionScope;
+  name: string;
+  /** The window or pane it changed on, for window and pane options. */
+  target?: string;
+  value: string;
+}
+
+/** A watched tmux option changed (see `watch_option`). */
+export type OptionChangeListener = (change: OptionChange) => void;
+
+export type SearchMatch = Wire.SearchMatch;
+
+/**
+ * A chunk of history matches for a `pane_search`. Chunks can arrive before
+ * the command's reply; match them to it by `search_id`.
+ */
+export type SearchResults = Extract<Wire.ServerEvent, { event: 'search-results' }>['data'];
+
+export type SearchResultsListener = (results: SearchResults) => void;
 
 /** Streamed progress entry kind from the backend (matches `LogKind` in Rust) */
-export type LogEntryKind = 'command' | 'output' | 'info' | 'error';
+export type LogEntryKind = Wire.LogKind;
 
 export type LogListener = (kind: LogEntryKind, message: string) => void;
 
@@ -328,6 +578,26 @@ export interface TmuxAdapter {
    * by the rest of the app. Returns an unsubscribe function when supported.
    */
   onClipboard?(listener: ClipboardListener): () => void;
+  /** Terminal bell from a pane. Optional, like `onClipboard`. */
+  onBell?(listener: BellListener): () => void;
+  /** Pane drag-and-drop target hints. Optional, like `onClipboard`. */
+  onDragTarget?(listener: DragTargetListener): () => void;
+  /** Screen-reader text stream. Optional, like `onClipboard`. */
+  onSpokenText?(listener: SpokenTextListener): () => void;
+  /** Output budget trims. Optional, like `onClipboard`. */
+  onOutputTrimmed?(listener: OutputTrimmedListener): () => void;
+  /** Pane notifications. Optional, like `onClipboard`. */
+  onPaneNotify?(listener: PaneNotifyListener): () => void;
+  /** Failed control-mode commands. Optional, like `onClipboard`. */
+  onCommandError?(listener: CommandErrorListener): () => void;
+  /** Lost sessions. Optional, like `onClipboard`. */
+  onSessionLost?(listener: SessionLostListener): () => void;
+  /** Widget data channel. Optional, like `onClipboard`. */
+  onWidgetData?(listener: WidgetDataListener): () => void;
+  /** Watched tmux option changes. Optional, like `onClipboard`. */
+  onOptionChanged?(listener: OptionChangeListener): () => void;
+  /** Pane search history matches. Optional, like `onClipboard`. */
+  onSearchResults?(listener: SearchResultsListener): () => void;
   switchSession?(sessionName: string): Promise<void>;
   /**
    * True when the adapter is attached to a real tmux server whose sessions can
@@ -366,3 +636,56 @@ export interface KeyBindings {
 }
 
 export type KeyBindingsListener = (keybindings: KeyBindings) => void;
+
+// ============================================
+// Generated Wire Types
+// ============================================
+
+// ./generated/protocol.ts is generated from the Rust wire types (`cargo xtask
+// generate-bindings`). The unions above alias it. The snake_case interfaces
+// stay hand-written, since they are looser about optional fields for older
+// servers and test fixtures, but each must name exactly the fields its Rust
+// type has: adding or removing a field on one side only fails to compile here.
+type SameKeys<A, B> = [Exclude<keyof A, keyof B>, Exclude<keyof B, keyof A>] extends [never, never]
+  ? true
+  : false;
+const _wireFieldsMatch: [
+  SameKeys<ServerState, Wire.TmuxState>,
+  SameKeys<ServerPane, Wire.TmuxPane>,
+  SameKeys<ServerWindow, Wire.TmuxWindow>,
+  SameKeys<ServerDelta, Wire.TmuxDelta>,
+  SameKeys<PaneDelta, Wire.PaneDelta>,
+  SameKeys<WindowDelta, Wire.WindowDelta>,
+  SameKeys<ServerImagePlacement, Wire.ImagePlacement>,
+  SameKeys<ServerCommandBlock, Wire.CommandBlock>,
+  SameKeys<CellStyle, Wire.CellStyle>,
+  SameKeys<TerminalCell, Wire.TerminalCell>,
+  SameKeys<StatusSegments, Wire.StatusSegments>,
+  SameKeys<StatusWindow, Wire.StatusWindow>,
+  SameKeys<SessionGroup, Wire.SessionGroup>,
+  SameKeys<TerminalColors, Wire.TerminalColors>,
+  SameKeys<WidgetSpec, Wire.WidgetSpec>,
+  SameKeys<OptionChange, Wire.OptionChange>,
+  SameKeys<KeyBinding, Wire.KeyBinding>,
+  SameKeys<KeyBindings, Wire.KeyBindings>,
+] = [
+  true,
+  true,
+  true,
+  true,
+  true,
+  true,
+  true,
+  true,
+  true,
+  true,
+  true,
+  true,
+  true,
+  true,
+  true,
+  true,
+  true,
+  true,
+];
+void _wireFieldsMatch;
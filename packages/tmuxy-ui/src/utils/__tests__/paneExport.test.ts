@@ -0,0 +1,49 @@
+import { describe, it, expect, vi, afterEach } from 'vitest';
+import { exportPane, exportToBlob, EXPORT_ACTIONS } from '../paneExport';
+
+afterEach(() => {
+  vi.restoreAllMocks();
+});
+
+describe('exportToBlob', () => {
+  it('decodes the base64 payload with its mime type', async () => {
+    const blob = exportToBlob({ filename: 'a.svg', mime: 'image/svg+xml', data: btoa('<svg/>') });
+    expect(blob.type).toBe('image/svg+xml');
+    expect(await blob.text()).toBe('<svg/>');
+  });
+});
+
+describe('exportPane', () => {
+  it('asks the server for the pane and downloads it under its filename', async () => {
+    const invoke = vi.fn().mockResolvedValue({
+      filename: 'tmuxy-pane-3-1.png',
+      mime: 'image/png',
+      data: btoa('png'),
+    });
+    URL.createObjectURL = vi.fn(() => 'blob:export');
+    URL.revokeObjectURL = vi.fn();
+    const click = vi.spyOn(HTMLAnchorElement.prototype, 'click').mockImplementation(() => {});
+
+    await exportPane(invoke as Parameters<typeof exportPane>[0], '%3', 'png', {
+      start: -100,
+      end: -1,
+    });
+
+    expect(invoke).toHaveBeenCalledWith('export_pane', {
+      paneId: '%3',
+      format: 'png',
+      start: -100,
+      end: -1,
+    });
+    expect(click).toHaveBeenCalledTimes(1);
+    const link = click.mock.contexts[0] as HTMLAnchorElement;
+    expect(link.download).toBe('tmuxy-pane-3-1.png');
+    expect(link.href).toBe('blob:export');
+  });
+
+  it('maps the menu actions to formats', () => {
+    expect(EXPORT_ACTIONS['pane-export-png']).toBe('png');
+    expect(EXPORT_ACTIONS['pane-export-svg']).toBe('svg');
+    expect(EXPORT_ACTIONS['pane-clear']).toBeUndefined();
+  });
+});
@@ -0,0 +1,86 @@
+import { describe, it, expect, vi, afterEach } from 'vitest';
+import { copyPaneText, selectionForServer } from '../paneText';
+import type { CopyModeState } from '../../tmux/types';
+
+function copyState(extra: Partial<CopyModeState>): CopyModeState {
+  return {
+    lines: new Map(),
+    totalLines: 124,
+    historySize: 100,
+    loadedRanges: [],
+    loading: false,
+    width: 80,
+    height: 24,
+    cursorRow: 0,
+    cursorCol: 0,
+    selectionMode: null,
+    selectionAnchor: null,
+    scrollTop: 0,
+    ...extra,
+  };
+}
+
+afterEach(() => {
+  vi.unstubAllGlobals();
+});
+
+describe('selectionForServer', () => {
+  it('converts absolute rows to capture-pane line numbers', () => {
+    const state = copyState({
+      selectionMode: 'char',
+      selectionAnchor: { row: 98, col: 4 },
+      cursorRow: 101,
+      cursorCol: 7,
+    });
+    expect(selectionForServer(state)).toEqual({
+      startRow: -2,
+      startCol: 4,
+      endRow: 1,
+      endCol: 7,
+      lineMode: false,
+    });
+  });
+
+  it('is null without a selection', () => {
+    expect(selectionForServer(copyState({}))).toBeNull();
+  });
+});
+
+describe('copyPaneText', () => {
+  const result = { text: 'FAIL', html: '<pre><span>FAIL</span></pre>' };
+
+  it('writes HTML and text when ClipboardItem is available', async () => {
+    const write = vi.fn().mockResolvedValue(undefined);
+    vi.stubGlobal('navigator', { clipboard: { write } });
+    vi.stubGlobal(
+      'ClipboardItem',
+      class {
+        constructor(public items: Record<string, Promise<Blob>>) {}
+      },
+    );
+    const invoke = vi.fn().mockResolvedValue(result);
+
+    await copyPaneText(invoke as Parameters<typeof copyPaneText>[0], '%1', { html: true });
+
+    expect(invoke).toHaveBeenCalledWith('get_pane_text', { paneId: '%1' });
+    const item = write.mock.calls[0][0][0] as { items: Record<string, Promise<Blob>> };
+    expect(await (await item.items['text/html']).text()).toBe(result.html);
+    expect(await (await item.items['text/plain']).text()).toBe('FAIL');
+  });
+
+  it('falls back to plain text and passes the selection', async () => {
+    const writeText = vi.fn().mockResolvedValue(undefined);
+    vi.stubGlobal('navigator', { clipboard: { writeText } });
+    vi.stubGlobal('ClipboardItem', undefined);
+    const invoke = vi.fn().mockResolvedValue(result);
+    const selection = { startRow: 0, startCol: 0, endRow: 0, endCol: 3, lineMode: false };
+
+    await copyPaneText(invoke as Parameters<typeof copyPaneText>[0], '%1', {
+      selection,
+      html: true,
+    });
+
+    expect(invoke).toHaveBeenCalledWith('get_pane_text', { paneId: '%1', selection });
+    expect(writeText).toHaveBeenCalledWith('FAIL');
+  });
+});
@@ -0,0 +1,20 @@
+import { describe, it, expect } from 'vitest';
+import { getOptionValue, setOptionValue } from '../tmuxOptions';
+
+describe('tmux option store', () => {
+  it('keys session options without a target', () => {
+    setOptionValue({ scope: 'session', name: 'status-position', value: 'top' });
+    expect(getOptionValue('session', 'status-position')).toBe('top');
+    // A target is meaningless for session options.
+    expect(getOptionValue('session', 'status-position', 'main')).toBe('top');
+  });
+
+  it('keeps pane options per pane', () => {
+    setOptionValue({ scope: 'pane', name: '@tmuxy_note', target: '%1', value: 'a' });
+    setOptionValue({ scope: 'pane', name: '@tmuxy_note', target: '%2', value: 'b' });
+    expect(getOptionValue('pane', '@tmuxy_note', '%1')).toBe('a');
+    expect(getOptionValue('pane', '@tmuxy_note', '%2')).toBe('b');
+    expect(getOptionValue('pane', '@tmuxy_note')).toBeUndefined();
+    expect(getOptionValue('window', '@tmuxy_note', '%1')).toBeUndefined();
+  });
+});
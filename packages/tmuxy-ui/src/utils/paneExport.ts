@@ -0,0 +1,59 @@
+/**
+ * Pane export - download a pane as a PNG or SVG image.
+ *
+ * The server renders the cells (see export.rs) and answers `export_pane`
+ * with the image base64-encoded; both adapters return the same shape, so
+ * the download happens here rather than through an HTTP route.
+ */
+
+export type ExportFormat = 'png' | 'svg';
+
+/** `export_pane`'s answer. */
+export interface PaneExport {
+  filename: string;
+  mime: string;
+  data: string;
+}
+
+type Invoke = <T>(cmd: string, args?: Record<string, unknown>) => Promise<T>;
+
+/** The pane menus' "Export as" items, by action ID. */
+export const EXPORT_ACTIONS: Record<string, ExportFormat> = {
+  'pane-export-png': 'png',
+  'pane-export-svg': 'svg',
+};
+
+/** Decode an export into a Blob. */
+export function exportToBlob(result: PaneExport): Blob {
+  const binary = atob(result.data);
+  const bytes = new Uint8Array(binary.length);
+  for (let i = 0; i < binary.length; i++) bytes[i] = binary.charCodeAt(i);
+  return new Blob([bytes], { type: result.mime });
+}
+
+/**
+ * Render `paneId` on the server and save it. Without a range the image is
+ * the visible area; `start`/`end` are capture-pane line numbers.
+ */
+export async function exportPane(
+  invoke: Invoke,
+  paneId: string,
+  format: ExportFormat,
+  range?: { start: number; end: number },
+): Promise<void> {
+  const result = await invoke<PaneExport>('export_pane', { paneId, format, ...range });
+  const url = URL.createObjectURL(exportToBlob(result));
+  const link = document.createElement('a');
+  link.href = url;
+  link.download = result.filename;
+  link.click();
+  // Revoke after the click has been handled, or some browsers cancel it.
+  setTimeout(() => URL.revokeObjectURL(url), 0);
+}
+
+/** Menu handler: start the export, logging a failure. */
+export function runExportAction(invoke: Invoke, paneId: string, format: ExportFormat): void {
+  exportPane(invoke, paneId, format).catch((err) => {
+    console.warn('[paneExport] export_pane failed:', err);
+  });
+}
@@ -0,0 +1,86 @@
+/**
+ * Pane text - copy a pane or a copy-mode selection as plain text or HTML.
+ *
+ * The server builds both from the cells (`get_pane_text`, see pane_text.rs):
+ * wide characters, wrapped rows and styles come out right without the
+ * frontend piecing text back together.
+ */
+
+import type { CopyModeState } from '../tmux/types';
+
+/** A selection in capture-pane line numbers (negative rows are history). */
+export interface TextSelection {
+  startRow: number;
+  startCol: number;
+  endRow: number;
+  endCol: number;
+  lineMode: boolean;
+}
+
+/** `get_pane_text`'s answer. */
+export interface PaneText {
+  text: string;
+  html: string;
+}
+
+type Invoke = <T>(cmd: string, args?: Record<string, unknown>) => Promise<T>;
+
+/** The pane menus' copy items, by action ID: whether each copies HTML. */
+export const COPY_TEXT_ACTIONS: Record<string, { html: boolean }> = {
+  'pane-copy-text': { html: false },
+  'pane-copy-html': { html: true },
+};
+
+/**
+ * The copy-mode selection in capture-pane line numbers. Copy mode counts
+ * rows from the first history line; tmux counts from the first visible one.
+ */
+export function selectionForServer(state: CopyModeState): TextSelection | null {
+  const { selectionAnchor, selectionMode, cursorRow, cursorCol, historySize } = state;
+  if (!selectionAnchor || !selectionMode) return null;
+  return {
+    startRow: selectionAnchor.row - historySize,
+    startCol: selectionAnchor.col,
+    endRow: cursorRow - historySize,
+    endCol: cursorCol,
+    lineMode: selectionMode === 'line',
+  };
+}
+
+/**
+ * Copy the pane's visible area, or `selection`, to the clipboard. With
+ * `html` the clipboard gets the styled HTML alongside the text, so rich
+ * editors paste the colors and plain ones the text.
+ */
+export async function copyPaneText(
+  invoke: Invoke,
+  paneId: string,
+  { selection, html }: { selection?: TextSelection | null; html: boolean },
+): Promise<void> {
+  const result = invoke<PaneText>('get_pane_text', {
+    paneId,
+    ...(selection ? { selection } : {}),
+  });
+  if (typeof ClipboardItem === 'undefined') {
+    await navigator.clipboard.writeText((await result).text);
+    return;
+  }
+  // Hand the clipboard promises rather than awaiting first: Safari only
+  // allows the write within the click that started it.
+  const blob = (type: string, pick: (r: PaneText) => string) =>
+    result.then((r) => new Blob([pick(r)], { type }));
+  const item: Record<string, Promise<Blob>> = { 'text/plain': blob('text/plain', (r) => r.text) };
+  if (html) item['text/html'] = blob('text/html', (r) => r.html);
+  await navigator.clipboard.write([new ClipboardItem(item)]);
+}
+
+/** Menu handler: start the copy, logging a failure. */
+export function runCopyTextAction(
+  invoke: Invoke,
+  paneId: string,
+  options: { selection?: TextSelection | null; html: boolean },
+): void {
+  copyPaneText(invoke, paneId, options).catch((err) => {
+    console.warn('[paneText] get_pane_text failed:', err);
+  });
+}
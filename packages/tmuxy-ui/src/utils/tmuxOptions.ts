@@ -0,0 +1,80 @@
+/**
+ * tmux options for UI features: read, set and follow them.
+ *
+ * `get_option` / `set_option` read and write one option; `watch_option` asks
+ * the backend to report its changes as `option-changed` events, which the
+ * tmux actor feeds into {@link setOptionValue}. Unset options read as "".
+ * Window and pane options are only followed when a target (`@3`, `%3`) is
+ * given, since changes arrive per window or pane.
+ */
+
+import { useCallback, useEffect, useSyncExternalStore } from 'react';
+import { useAdapterInvoke } from '../machines/AppContext';
+import type { OptionChange, OptionScope } from '../tmux/types';
+
+const values = new Map<string, string>();
+const listeners = new Set<() => void>();
+
+function optionKey(scope: OptionScope, name: string, target?: string): string {
+  const t = scope === 'server' || scope === 'session' ? '' : (target ?? '');
+  return `${scope}\u0000${t}\u0000${name}`;
+}
+
+export function setOptionValue(change: OptionChange): void {
+  const key = optionKey(change.scope, change.name, change.target);
+  if (values.get(key) === change.value) return;
+  values.set(key, change.value);
+  listeners.forEach((listener) => listener());
+}
+
+export function getOptionValue(
+  scope: OptionScope,
+  name: string,
+  target?: string,
+): string | undefined {
+  return values.get(optionKey(scope, name, target));
+}
+
+function subscribe(listener: () => void): () => void {
+  listeners.add(listener);
+  return () => listeners.delete(listener);
+}
+
+/**
+ * An option's current value (undefined until read) and a setter; `null`
+ * unsets it. Reads it once and watches it for as long as it's mounted.
+ */
+export function useTmuxOption(
+  scope: OptionScope,
+  name: string,
+  target?: string,
+): [string | undefined, (value: string | null) => Promise<void>] {
+  const invoke = useAdapterInvoke();
+
+  useEffect(() => {
+    const args = { scope, name, target };
+    invoke<{ value: string }>('get_option', args)
+      .then(({ value }) => setOptionValue({ scope, name, target, value }))
+      .catch((e) => console.warn(`Failed to read tmux option ${name}:`, e));
+    invoke('watch_option', args).catch((e) =>
+      console.warn(`Failed to watch tmux option ${name}:`, e),
+    );
+  }, [invoke, scope, name, target]);
+
+  const getSnapshot = useCallback(
+    () => getOptionValue(scope, name, target),
+    [scope, name, target],
+  );
+  const value = useSyncExternalStore(subscribe, getSnapshot, getSnapshot);
+
+  const set = useCallback(
+    async (next: string | null) => {
+      await invoke('set_option', { scope, name, target, value: next ?? undefined });
+      // Watches report it too, but up to a second later.
+      if (next !== null) setOptionValue({ scope, name, target, value: next });
+    },
+    [invoke, scope, name, target],
+  );
+
+  return [value, set];
+}
@@ -0,0 +1,116 @@
+const fs = require('fs');
+const os = require('os');
+const path = require('path');
+const { runCLI } = require('./helpers/run-cli');
+
+describe('CLI dashboard', () => {
+  let dir;
+
+  const write = (name, body) => {
+    const file = path.join(dir, name);
+    fs.writeFileSync(file, body);
+    return file;
+  };
+
+  beforeEach(() => {
+    dir = fs.mkdtempSync(path.join(os.tmpdir(), 'tmuxy-dashboard-'));
+  });
+
+  afterEach(() => {
+    fs.rmSync(dir, { recursive: true, force: true });
+  });
+
+  test('shows help', () => {
+    const { stdout, exitCode } = runCLI(['dashboard', '--help']);
+    expect(exitCode).toBe(0);
+    expect(stdout).toContain('Usage: tmuxy dashboard');
+  });
+
+  test('errors with no file', () => {
+    const { stderr, exitCode, tmuxCalls } = runCLI(['dashboard']);
+    expect(exitCode).toBe(1);
+    expect(stderr).toContain('dashboard file required');
+    expect(tmuxCalls).toHaveLength(0);
+  });
+
+  test('errors on a missing file', () => {
+    const { stderr, exitCode } = runCLI(['dashboard', path.join(dir, 'nope.toml')]);
+    expect(exitCode).toBe(1);
+    expect(stderr).toContain('dashboard file not found');
+  });
+
+  test('--dry-run prints the grid in row order', () => {
+    const file = write(
+      'ops.toml',
+      [
+        '# on-call board',
+        'name = "ops"',
+        '',
+        '[[widget]]',
+        'type = "logs"',
+        'source = "app.log"',
+        'row = 2',
+        '',
+        '[[widget]]',
+        "type = 'watch'",
+        'command = "df -h | grep \\"/\\""  # disks',
+        'refresh = 5',
+        '',
+        '[[widget]]',
+        'type = "markdown"',
+        'source = "README.md"',
+      ].join('\n'),
+    );
+    const { stdout, exitCode, tmuxCalls } = runCLI(['dashboard', '--dry-run', file]);
+    expect(exitCode).toBe(0);
+    expect(stdout.trim().split('\n')).toEqual([
+      'tab ops',
+      'row 1 col 1 watch df -h | grep "/"',
+      'row 1 col 2 markdown README.md',
+      'row 2 col 1 logs app.log',
+    ]);
+    expect(tmuxCalls).toHaveLength(0);
+  });
+
+  test('names the tab after the file by default', () => {
+    const file = write('board.toml', '[[widget]]\ntype = "image"\nsource = "a.png"\n');
+    const { stdout } = runCLI(['dashboard', '--dry-run', file]);
+    expect(stdout.split('\n')[0]).toBe('tab board');
+  });
+
+  test('reports the line of an unknown widget type', () => {
+    const file = write('bad.toml', '[[widget]]\ntype = "chart"\n');
+    const { stderr, exitCode } = runCLI(['dashboard', '--dry-run', file]);
+    expect(exitCode).toBe(1);
+    expect(stderr).toContain('bad.toml:2: unknown widget type: chart');
+  });
+
+  test('rejects a widget missing what its type runs', () => {
+    const file = write('bad.toml', '[[widget]]\ntype = "watch"\n');
+    const { stderr, exitCode } = runCLI(['dashboard', '--dry-run', file]);
+    expect(exitCode).toBe(1);
+    expect(stderr).toContain('widget 1 (watch) needs a command');
+  });
+
+  test('accepts metric cells with a command', () => {
+    const file = write('m.toml', '[[widget]]\ntype = "metric"\ncommand = "echo 1"\nrefresh = 3\n');
+    const { stdout, exitCode } = runCLI(['dashboard', '--dry-run', file]);
+    expect(exitCode).toBe(0);
+    expect(stdout).toContain('row 1 col 1 metric echo 1');
+  });
+
+  test('rejects a file without widgets', () => {
+    const file = write('empty.toml', 'name = "x"\n');
+    const { stderr, exitCode } = runCLI(['dashboard', file]);
+    expect(exitCode).toBe(1);
+    expect(stderr).toContain('has no [[widget]] tables');
+  });
+
+  test('creates the tab through run-shell', () => {
+    const file = write('ops.toml', '[[widget]]\ntype = "watch"\ncommand = "date"\n');
+    const { tmuxCalls } = runCLI(['dashboard', file]);
+    expect(tmuxCalls.length).toBeGreaterThan(0);
+    expect(tmuxCalls[0].args[0]).toBe('run-shell');
+    expect(tmuxCalls[0].args[1]).toContain("split-window -d -P -F '##{pane_id}'");
+  });
+});
@@ -0,0 +1,43 @@
+const { runCLI } = require('./helpers/run-cli');
+
+describe('CLI metric', () => {
+  // A valid invocation execs the sampler, which never exits on its own; we
+  // test only help and the errors raised before it starts sampling.
+
+  test('shows help', () => {
+    const { stdout, exitCode } = runCLI(['metric', '--help']);
+    expect(exitCode).toBe(0);
+    expect(stdout).toContain('Usage: tmuxy metric <command>');
+  });
+
+  test('errors with no command', () => {
+    const { stderr, exitCode } = runCLI(['metric', '--interval', '5s']);
+    expect(exitCode).toBe(1);
+    expect(stderr).toContain('command required');
+  });
+
+  test('wants the command as a single argument', () => {
+    const { stderr, exitCode } = runCLI(['metric', 'cat', '/proc/loadavg']);
+    expect(exitCode).toBe(1);
+    expect(stderr).toContain('quote the command as one argument');
+  });
+
+  test('rejects an option without a value', () => {
+    const { stderr, exitCode } = runCLI(['metric', 'date', '--interval']);
+    expect(exitCode).toBe(1);
+    expect(stderr).toContain('--interval needs a value');
+  });
+
+  test('rejects a malformed interval', () => {
+    const { stderr, exitCode, tmuxCalls } = runCLI(['metric', 'date', '--interval', '5x']);
+    expect(exitCode).toBe(1);
+    expect(stderr).toContain('invalid interval: 5x');
+    expect(tmuxCalls).toHaveLength(0);
+  });
+
+  test('rejects a zero sample count', () => {
+    const { stderr, exitCode } = runCLI(['metric', 'date', '--samples', '0']);
+    expect(exitCode).toBe(1);
+    expect(stderr).toContain('invalid sample count: 0');
+  });
+});
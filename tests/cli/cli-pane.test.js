@@ -231,6 +231,21 @@ describe('CLI pane subcommands', () => {
     });
   });
 
+  describe('pane float', () => {
+    test('rejects a sandbox profile that could not be tagged safely', () => {
+      const { stderr, exitCode, tmuxCalls } = runCLI([
+        'pane',
+        'float',
+        '--sandbox',
+        'docker:alpine,evil',
+        'ls',
+      ]);
+      expect(exitCode).toBe(2);
+      expect(stderr).toContain("invalid sandbox profile 'docker:alpine,evil'");
+      expect(tmuxCalls).toHaveLength(0);
+    });
+  });
+
   describe('pane unknown', () => {
     test('errors on unknown pane subcommand', () => {
       const { stderr, exitCode } = runCLI(['pane', 'unknown']);
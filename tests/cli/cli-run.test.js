@@ -109,4 +109,76 @@ describe('CLI run escape hatch', () => {
       expect(tmuxCalls[0].args[1]).toMatch(/^tmux -L tmuxy list-panes\s*$/);
     });
   });
+
+  describe('--sandbox', () => {
+    test('runs a split pane command inside the profile and tags the pane', () => {
+      const { exitCode, tmuxCalls } = runCLI([
+        'run',
+        '--sandbox',
+        'bwrap',
+        'split-window',
+        '-h',
+        'make test',
+      ]);
+      expect(exitCode).toBe(0);
+      expect(tmuxCalls).toHaveLength(1);
+      expect(tmuxCalls[0].args[1]).toMatch(
+        /^tmux -L tmuxy split-window '-h' 'bash' '[^']*\/sandbox-exec' 'bwrap' 'sh' '-c' 'make test' \\; set-option -p @tmuxy-sandbox bwrap$/,
+      );
+    });
+
+    test('sandboxes the shell of an intercepted new-window', () => {
+      const { exitCode, tmuxCalls } = runCLI([
+        'run',
+        '--sandbox',
+        'firejail',
+        'new-window',
+        '-n',
+        'jail',
+      ]);
+      expect(exitCode).toBe(0);
+      expect(tmuxCalls).toHaveLength(1);
+      expect(tmuxCalls[0].args[1]).toMatch(
+        /^tmux -L tmuxy splitw 'bash' '[^']*\/sandbox-exec' 'firejail' \\; breakp -n 'jail' \\; set-option -w @tmuxy-window-type tab \\; set-option -p @tmuxy-sandbox firejail$/,
+      );
+    });
+
+    test('refuses commands that do not start a pane', () => {
+      const { stderr, exitCode, tmuxCalls } = runCLI([
+        'run',
+        '--sandbox',
+        'bwrap',
+        'send-keys',
+        'ls',
+      ]);
+      expect(exitCode).toBe(1);
+      expect(stderr).toContain('only applies to split-window and new-window');
+      expect(tmuxCalls).toHaveLength(0);
+    });
+
+    test('refuses a detached split, which would tag the wrong pane', () => {
+      const { stderr, exitCode, tmuxCalls } = runCLI([
+        'run',
+        '--sandbox',
+        'bwrap',
+        'split-window',
+        '-dh',
+      ]);
+      expect(exitCode).toBe(1);
+      expect(stderr).toContain("can't be combined with -d");
+      expect(tmuxCalls).toHaveLength(0);
+    });
+
+    test('rejects a profile that could not be tagged safely', () => {
+      const { stderr, exitCode, tmuxCalls } = runCLI([
+        'run',
+        '--sandbox',
+        'bwrap;kill-server',
+        'split-window',
+      ]);
+      expect(exitCode).toBe(2);
+      expect(stderr).toContain("invalid sandbox profile 'bwrap;kill-server'");
+      expect(tmuxCalls).toHaveLength(0);
+    });
+  });
 });
@@ -0,0 +1,16 @@
+const { runCLI } = require('./helpers/run-cli');
+
+describe('CLI stats', () => {
+  test('shows help', () => {
+    const { stdout, exitCode } = runCLI(['stats', '--help']);
+    expect(exitCode).toBe(0);
+    expect(stdout).toContain('Usage: tmuxy stats');
+    expect(stdout).toContain('TMUXY_USAGE_STATS=off');
+  });
+
+  test('execs the server binary with the stats action', () => {
+    const { stdout, exitCode } = runCLI(['stats', '--days', '7', '--json']);
+    expect(exitCode).toBe(0);
+    expect(stdout).toContain('mock-server-started stats --days 7 --json');
+  });
+});
@@ -0,0 +1,46 @@
+const { runCLI } = require('./helpers/run-cli');
+
+describe('CLI timer', () => {
+  // Without --float a valid invocation execs the countdown in the current
+  // pane, so we test help, the argument errors and the float setup only.
+
+  test('shows help', () => {
+    const { stdout, exitCode } = runCLI(['timer', '--help']);
+    expect(exitCode).toBe(0);
+    expect(stdout).toContain('Usage: tmuxy timer <duration>');
+    expect(stdout).toContain('@tmuxy-notify-webhook');
+  });
+
+  test('errors with no duration', () => {
+    const { stderr, exitCode } = runCLI(['timer', '--label', 'focus']);
+    expect(exitCode).toBe(1);
+    expect(stderr).toContain('duration required');
+  });
+
+  test('rejects a malformed duration before touching tmux', () => {
+    const { stderr, exitCode, tmuxCalls } = runCLI(['timer', '25x', '--float']);
+    expect(exitCode).toBe(1);
+    expect(stderr).toContain('invalid duration: 25x');
+    expect(tmuxCalls).toHaveLength(0);
+  });
+
+  test('rejects a zero duration', () => {
+    const { stderr, exitCode } = runCLI(['timer', '0m']);
+    expect(exitCode).toBe(1);
+    expect(stderr).toContain('invalid duration: 0m');
+  });
+
+  test('rejects --label without a value', () => {
+    const { stderr, exitCode } = runCLI(['timer', '25m', '--label']);
+    expect(exitCode).toBe(1);
+    expect(stderr).toContain('--label needs a value');
+  });
+
+  test('--float opens a float for the timer', () => {
+    // The mock's run-shell never writes the new pane id back, so float-create
+    // fails after its split; the split is what we check for.
+    const { tmuxCalls } = runCLI(['timer', '25m', '--float']);
+    const commands = tmuxCalls.map((call) => call.args.join(' '));
+    expect(commands.some((cmd) => cmd.includes('split-window'))).toBe(true);
+  });
+});
@@ -303,7 +303,7 @@ async function dumpWindows(driver) {
  * Invoke a Tauri command via the frontend's adapter.
  *
  * @param {WebdriverIO.Browser} driver
- * @param {string} command - Tauri command name (e.g., 'run_tmux_command')
+ * @param {string} command - Tauri command name (e.g., 'split_pane_horizontal')
  * @param {Object} args - Command arguments
  * @returns {Promise<*>}
  */
@@ -329,6 +329,19 @@ async function invokeCommand(driver, command, args = {}) {
   );
 }
 
+/**
+ * Send a client command (the `/commands` vocabulary, e.g. 'run_tmux_command')
+ * through the app's `dispatch` command, as the TauriAdapter does.
+ *
+ * @param {WebdriverIO.Browser} driver
+ * @param {string} cmd - Command name
+ * @param {Object} args - Command arguments
+ * @returns {Promise<*>}
+ */
+async function dispatchCommand(driver, cmd, args = {}) {
+  return invokeCommand(driver, 'dispatch', { cmd, args });
+}
+
 /**
  * Wait for pane count to reach expected value.
  *
@@ -384,6 +397,7 @@ module.exports = {
   getPaneCount,
   getRawWindowCount,
   invokeCommand,
+  dispatchCommand,
   waitForPaneCount,
   waitForRawWindowCount,
   KEYS,
@@ -24,6 +24,7 @@ const {
   getPaneCount,
   getRawWindowCount,
   invokeCommand,
+  dispatchCommand,
   waitForPaneCount,
   waitForRawWindowCount,
 } = require('./helpers/wdio-client');
@@ -173,8 +174,9 @@ describe('IPC Commands', () => {
     await setupApp();
 
     // Run a tmux command through the IPC channel
-    const result = await invokeCommand(driver, 'run_tmux_command', {
+    const result = await dispatchCommand(driver, 'run_tmux_command', {
       command: 'display-message -p #{session_name}',
+      output: true,
     });
 
     // The result should contain our session name
@@ -190,7 +192,7 @@ describe('IPC Commands', () => {
   test('run_tmux_command rewrites new-window to splitw+breakp', async () => {
     await setupApp();
 
-    await invokeCommand(driver, 'run_tmux_command', { command: 'new-window' });
+    await dispatchCommand(driver, 'run_tmux_command', { command: 'new-window' });
 
     // The real assertion here is no-crash: a bare `tmux new-window` while
     // control mode is attached crashes tmux 3.5a. If the rewrite worked,
@@ -199,8 +201,9 @@ describe('IPC Commands', () => {
     // set asynchronously from the executor subprocess (after split+breakp) and
     // races the frontend's state snapshot under CI load — flake-prone even
     // though the no-crash invariant we care about is satisfied.
-    const result = await invokeCommand(driver, 'run_tmux_command', {
+    const result = await dispatchCommand(driver, 'run_tmux_command', {
       command: 'display-message -p #{session_name}',
+      output: true,
     });
     expect(result).toContain(sessionName);
   });
@@ -215,7 +218,7 @@ describe('IPC Commands', () => {
 
     const state = await getAppState(driver);
     const paneId = state.panes[0]?.tmuxId ?? '%0';
-    const result = await invokeCommand(driver, 'get_scrollback_cells', {
+    const result = await dispatchCommand(driver, 'get_scrollback_cells', {
       paneId,
       start: -200,
       end: -1,
@@ -0,0 +1,540 @@
+//! TypeScript bindings for the wire types.
+//!
+//! The Rust types derive `schemars::JsonSchema`; this renders their JSON
+//! Schemas as TypeScript, one declaration per named type. Events are
+//! described as the server writes them and commands as it reads them, so a
+//! field the server always sends is required and one it fills in when
+//! missing is optional.
+
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+
+use serde_json::{Map, Value};
+
+const HEADER: &str = "\
+// Generated by `cargo xtask generate-bindings` from the Rust wire types
+// (tmuxy-protocol's ServerEvent and ClientCommand). Do not edit.
+";
+
+/// Longest line a union or object is kept on one line up to, as Prettier
+/// would in the frontend.
+const WIDTH: usize = 100;
+
+/// Where the bindings live in the frontend.
+pub fn path() -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("../packages/tmuxy-ui/src/tmux/generated/protocol.ts")
+}
+
+/// The bindings file.
+pub fn generate() -> Result<String, String> {
+    let events = tmuxy_protocol::schema::server_event();
+    let commands = tmuxy_protocol::schema::client_command();
+
+    let mut decls = BTreeMap::new();
+    for schema in [events, commands] {
+        for (name, def) in definitions(schema.as_value())? {
+            let decl = declaration(&name, &def);
+            if decls.get(&name).is_some_and(|other| *other != decl) {
+                return Err(format!(
+                    "`{name}` has a different shape in events and commands"
+                ));
+            }
+            decls.insert(name, decl);
+        }
+    }
+
+    let mut out = String::from(HEADER);
+    out.push_str(&format!(
+        "\nexport const PROTOCOL_VERSION = {};\n",
+        tmuxy_protocol::PROTOCOL_VERSION
+    ));
+    for decl in decls.values() {
+        out.push('\n');
+        out.push_str(decl);
+    }
+    Ok(out)
+}
+
+/// The root type, named by its title, and every type under `$defs`.
+fn definitions(root: &Value) -> Result<Vec<(String, Value)>, String> {
+    let mut root = root
+        .as_object()
+        .cloned()
+        .ok_or("the root schema is not an object")?;
+    let name = root
+        .remove("title")
+        .and_then(|t| t.as_str().map(String::from))
+        .ok_or("the root schema has no title")?;
+    let defs = match root.remove("$defs") {
+        Some(Value::Object(defs)) => defs,
+        _ => Map::new(),
+    };
+    root.remove("$schema");
+    root.remove("$comment");
+
+    let mut out = vec![(name, Value::Object(root))];
+    out.extend(defs);
+    Ok(out)
+}
+
+/// `export interface` for a plain object, `export type` for anything else.
+fn declaration(name: &str, schema: &Value) -> String {
+    let mut out = doc(schema, 0);
+    if let Some(object) = plain_object(schema) {
+        out.push_str(&format!(
+            "export interface {name} {}\n",
+            render_object(object, 0, false)
+        ));
+        return out;
+    }
+
+    let head = format!("export type {name} =");
+    let members = member_schemas(schema);
+    if members.len() == 1 {
+        return out + &format!("{head} {};\n", ty(schema, 0).text);
+    }
+    let one_line = format!(
+        "{head} {};",
+        union(members.iter().map(|m| ty(m, 0)).collect()).text
+    );
+    let documented = members.iter().any(|m| description(m).is_some());
+    if !documented && !one_line.contains('\n') && one_line.len() <= WIDTH {
+        return out + &one_line + "\n";
+    }
+
+    // One member per line, each under its own description.
+    out.push_str(&head);
+    for member in &members {
+        out.push('\n');
+        if let Some(doc) = description(member) {
+            out.push_str(&comment(&doc, 2));
+        }
+        out.push_str("  | ");
+        out.push_str(&ty(member, 4).at(Prec::Intersection));
+    }
+    out + ";\n"
+}
+
+/// The object's map when `schema` is a non-nullable object with fixed
+/// properties and nothing else.
+fn plain_object(schema: &Value) -> Option<&Map<String, Value>> {
+    let object = schema.as_object()?;
+    let plain = object.get("type") == Some(&Value::from("object"))
+        && object.contains_key("properties")
+        && !["oneOf", "anyOf", "allOf", "$ref"]
+            .iter()
+            .any(|k| object.contains_key(*k))
+        && matches!(
+            object.get("additionalProperties"),
+            None | Some(Value::Bool(false))
+        );
+    plain.then_some(object)
+}
+
+/// The schemas a union is made of, or `schema` alone when it isn't one.
+fn member_schemas(schema: &Value) -> Vec<Value> {
+    let Some(object) = schema.as_object() else {
+        return vec![schema.clone()];
+    };
+    if object.contains_key("properties") || object.contains_key("const") {
+        return vec![schema.clone()];
+    }
+    if let Some(Value::Array(variants)) = object.get("oneOf").or_else(|| object.get("anyOf")) {
+        // An undocumented group of unit variants is an `enum` of its own;
+        // its values are members like any other.
+        return variants
+            .iter()
+            .flat_map(|v| match v.get("description") {
+                None => member_schemas(v),
+                Some(_) => vec![v.clone()],
+            })
+            .collect();
+    }
+    if let Some(Value::Array(values)) = object.get("enum") {
+        return values
+            .iter()
+            .map(|value| serde_json::json!({ "const": value }))
+            .collect();
+    }
+    vec![schema.clone()]
+}
+
+/// How tightly a rendered type binds, for deciding where it needs parens.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+enum Prec {
+    Union,
+    Intersection,
+    Atom,
+}
+
+struct Ts {
+    text: String,
+    prec: Prec,
+}
+
+impl Ts {
+    fn atom(text: impl Into<String>) -> Self {
+        Ts {
+            text: text.into(),
+            prec: Prec::Atom,
+        }
+    }
+
+    /// The text, parenthesized if it binds looser than `prec`.
+    fn at(&self, prec: Prec) -> String {
+        if self.prec < prec {
+            format!("({})", self.text)
+        } else {
+            self.text.clone()
+        }
+    }
+}
+
+fn union(members: Vec<Ts>) -> Ts {
+    let mut texts: Vec<String> = Vec::new();
+    for member in &members {
+        let text = member.at(Prec::Union);
+        if !texts.contains(&text) {
+            texts.push(text);
+        }
+    }
+    match texts.len() {
+        0 => Ts::atom("never"),
+        1 => members
+            .into_iter()
+            .next()
+            .unwrap_or_else(|| Ts::atom("never")),
+        _ => Ts {
+            text: texts.join(" | "),
+            prec: Prec::Union,
+        },
+    }
+}
+
+fn intersection(members: Vec<Ts>) -> Ts {
+    if members.len() == 1 {
+        return members
+            .into_iter()
+            .next()
+            .unwrap_or_else(|| Ts::atom("unknown"));
+    }
+    Ts {
+        text: members
+            .iter()
+            .map(|m| m.at(Prec::Atom))
+            .collect::<Vec<_>>()
+            .join(" & "),
+        prec: Prec::Intersection,
+    }
+}
+
+/// The TypeScript type for `schema`, with multi-line parts indented by
+/// `indent`.
+fn ty(schema: &Value, indent: usize) -> Ts {
+    let object = match schema {
+        Value::Bool(true) => return Ts::atom("unknown"),
+        Value::Bool(false) => return Ts::atom("never"),
+        Value::Object(object) => object,
+        _ => return Ts::atom("unknown"),
+    };
+    if let Some(Value::String(reference)) = object.get("$ref") {
+        return Ts::atom(reference.rsplit('/').next().unwrap_or(reference));
+    }
+    if let Some(value) = object.get("const") {
+        return Ts::atom(literal(value));
+    }
+    if let Some(Value::Array(values)) = object.get("enum") {
+        return union(values.iter().map(|v| Ts::atom(literal(v))).collect());
+    }
+    if let Some(Value::Array(variants)) = object.get("oneOf").or_else(|| object.get("anyOf")) {
+        let variants = union(variants.iter().map(|v| ty(v, indent)).collect());
+        if object.contains_key("properties") {
+            let base = render_object(object, indent, true);
+            return intersection(vec![Ts::atom(base), variants]);
+        }
+        return variants;
+    }
+    if let Some(Value::Array(parts)) = object.get("allOf") {
+        return intersection(parts.iter().map(|p| ty(p, indent)).collect());
+    }
+    match object.get("type") {
+        Some(Value::String(name)) => primitive(name, object, indent),
+        Some(Value::Array(names)) => union(
+            names
+                .iter()
+                .filter_map(Value::as_str)
+                .map(|name| primitive(name, object, indent))
+                .collect(),
+        ),
+        _ if object.contains_key("properties") => Ts::atom(render_object(object, indent, true)),
+        _ => Ts::atom("unknown"),
+    }
+}
+
+fn primitive(name: &str, object: &Map<String, Value>, indent: usize) -> Ts {
+    match name {
+        "string" => Ts::atom("string"),
+        "integer" | "number" => Ts::atom("number"),
+        "boolean" => Ts::atom("boolean"),
+        "null" => Ts::atom("null"),
+        "array" => array(object, indent),
+        "object" if object.contains_key("properties") => {
+            Ts::atom(render_object(object, indent, true))
+        }
+        "object" => match object.get("additionalProperties") {
+            Some(values @ Value::Object(_)) => {
+                Ts::atom(format!("Record<string, {}>", ty(values, indent).text))
+            }
+            _ => Ts::atom("Record<string, unknown>"),
+        },
+        _ => Ts::atom("unknown"),
+    }
+}
+
+fn array(object: &Map<String, Value>, indent: usize) -> Ts {
+    if let Some(Value::Array(items)) = object.get("prefixItems") {
+        let items: Vec<String> = items.iter().map(|i| ty(i, indent).text).collect();
+        return Ts::atom(format!("[{}]", items.join(", ")));
+    }
+    match object.get("items") {
+        Some(items) => Ts::atom(format!("{}[]", ty(items, indent).at(Prec::Atom))),
+        None => Ts::atom("unknown[]"),
+    }
+}
+
+/// An object type's `{ … }`. `inline` objects without documented fields
+/// stay on one line when they fit.
+fn render_object(object: &Map<String, Value>, indent: usize, inline: bool) -> String {
+    let required: Vec<&str> = match object.get("required") {
+        Some(Value::Array(names)) => names.iter().filter_map(Value::as_str).collect(),
+        _ => Vec::new(),
+    };
+    let empty = Map::new();
+    let properties = match object.get("properties") {
+        Some(Value::Object(properties)) => properties,
+        _ => &empty,
+    };
+
+    let field = |name: &String, schema: &Value, indent: usize| {
+        let optional = if required.contains(&name.as_str()) {
+            ""
+        } else {
+            "?"
+        };
+        format!("{}{optional}: {};", key(name), ty(schema, indent).text)
+    };
+
+    let documented = properties.values().any(|p| description(p).is_some());
+    if inline && !documented {
+        let fields: Vec<String> = properties
+            .iter()
+            .map(|(name, schema)| field(name, schema, indent))
+            .collect();
+        let line = format!("{{ {} }}", fields.join(" ").trim_end_matches(';'));
+        if !line.contains('\n') && indent + line.len() <= WIDTH / 2 {
+            return line;
+        }
+    }
+
+    let pad = " ".repeat(indent + 2);
+    let mut out = String::from("{\n");
+    for (name, schema) in properties {
+        if let Some(doc) = description(schema) {
+            out.push_str(&comment(&doc, indent + 2));
+        }
+        out.push_str(&pad);
+        out.push_str(&field(name, schema, indent + 2));
+        out.push('\n');
+    }
+    if let Some(values @ Value::Object(_)) = object.get("additionalProperties") {
+        out.push_str(&format!(
+            "{pad}[key: string]: {};\n",
+            ty(values, indent + 2).text
+        ));
+    }
+    out.push_str(&" ".repeat(indent));
+    out.push('}');
+    out
+}
+
+fn description(schema: &Value) -> Option<String> {
+    schema
+        .get("description")
+        .and_then(Value::as_str)
+        .map(String::from)
+}
+
+/// The JSDoc comment for `schema`'s description, if it has one.
+fn doc(schema: &Value, indent: usize) -> String {
+    description(schema).map_or_else(String::new, |d| comment(&d, indent))
+}
+
+fn comment(text: &str, indent: usize) -> String {
+    let pad = " ".repeat(indent);
+    let text = unlink(text).replace("*/", "*\\/");
+    let one_line = format!("{pad}/** {text} */");
+    if !text.contains('\n') && one_line.len() <= WIDTH {
+        return one_line + "\n";
+    }
+    let mut out = format!("{pad}/**\n");
+    for line in text.lines() {
+        out.push_str(&format!(
+            "{pad} *{}{line}\n",
+            if line.is_empty() { "" } else { " " }
+        ));
+    }
+    out + &format!("{pad} */\n")
+}
+
+/// Rustdoc links (``[`Foo`]``, ``[`Foo`](path)``) as plain code spans.
+fn unlink(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(start) = rest.find("[`") {
+        let Some(len) = rest[start + 1..].find("`]") else {
+            break;
+        };
+        let close = start + len + 2;
+        out.push_str(&rest[..start]);
+        out.push_str(&rest[start + 1..close]);
+        rest = &rest[close + 1..];
+        if rest.starts_with('(') {
+            if let Some(end) = rest.find(')') {
+                rest = &rest[end + 1..];
+            }
+        }
+    }
+    out.push_str(rest);
+    out
+}
+
+/// A property name, quoted unless it is an identifier.
+fn key(name: &str) -> String {
+    let identifier = name
+        .chars()
+        .next()
+        .is_some_and(|c| c.is_ascii_alphabetic() || c == '_' || c == '$')
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '$');
+    if identifier {
+        name.to_string()
+    } else {
+        literal(&Value::from(name))
+    }
+}
+
+fn literal(value: &Value) -> String {
+    match value {
+        Value::String(s) => format!("'{}'", s.replace('\\', "\\\\").replace('\'', "\\'")),
+        other => other.to_string(),
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used, clippy::expect_used)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn schemas_render_as_typescript() {
+        let pane = json!({
+            "description": "A pane.",
+            "type": "object",
+            "properties": {
+                "id": { "type": "integer" },
+                "title": { "description": "Set by the shell.", "type": ["string", "null"] },
+                "panes": {
+                    "type": "object",
+                    "additionalProperties": { "anyOf": [{ "$ref": "#/$defs/Pane" }, { "type": "null" }] }
+                },
+                "cells": { "type": "array", "items": { "type": ["integer", "null"] } }
+            },
+            "required": ["id", "panes"]
+        });
+        assert_eq!(
+            declaration("Pane", &pane),
+            "/** A pane. */\n\
+             export interface Pane {\n  \
+               cells?: (number | null)[];\n  \
+               id: number;\n  \
+               panes: Record<string, Pane | null>;\n  \
+               /** Set by the shell. */\n  \
+               title?: string | null;\n\
+             }\n"
+        );
+
+        let mode = json!({ "type": "string", "enum": ["auto", "on", "off"] });
+        assert_eq!(
+            declaration("DndMode", &mode),
+            "export type DndMode = 'auto' | 'on' | 'off';\n"
+        );
+
+        let event = json!({
+            "oneOf": [
+                {
+                    "description": "A bell.",
+                    "type": "object",
+                    "properties": {
+                        "event": { "type": "string", "const": "bell" },
+                        "data": { "type": "object", "properties": { "sound": { "type": "boolean" } }, "required": ["sound"] }
+                    },
+                    "required": ["event", "data"]
+                },
+                {
+                    "type": "object",
+                    "properties": { "event": { "type": "string", "const": "it's" } },
+                    "required": ["event"]
+                }
+            ]
+        });
+        assert_eq!(
+            declaration("Event", &event),
+            "export type Event =\n  \
+               /** A bell. */\n  \
+               | { data: { sound: boolean }; event: 'bell' }\n  \
+               | { event: 'it\\'s' };\n"
+        );
+    }
+
+    #[test]
+    fn rustdoc_links_become_code_spans() {
+        assert_eq!(
+            unlink("See [`Foo`], [`bar`](crate::bar) and [x]."),
+            "See `Foo`, `bar` and [x]."
+        );
+    }
+
+    #[test]
+    fn checked_in_bindings_are_current() {
+        let checked_in = std::fs::read_to_string(path()).unwrap_or_default();
+        assert!(
+            checked_in == generate().unwrap(),
+            "protocol.ts is stale; run `cargo xtask generate-bindings`"
+        );
+    }
+
+    #[test]
+    fn every_wire_type_is_declared_once() {
+        let bindings = generate().unwrap();
+        for name in [
+            "ServerEvent",
+            "ClientCommand",
+            "TmuxState",
+            "TmuxDelta",
+            "KeyEvent",
+        ] {
+            let declared = [
+                format!("export type {name} ="),
+                format!("export interface {name} {{"),
+            ];
+            let count: usize = declared
+                .iter()
+                .map(|d| bindings.matches(d.as_str()).count())
+                .sum();
+            assert_eq!(count, 1, "{name}");
+        }
+    }
+}
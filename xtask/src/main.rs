@@ -0,0 +1,49 @@
+//! Repository tasks, run as `cargo xtask <task>`.
+//!
+//! - `generate-bindings`: write the frontend's TypeScript wire types from
+//!   the Rust ones (see [`bindings`]). `--check` fails instead of writing
+//!   when the checked-in file is stale.
+
+mod bindings;
+
+use std::process::ExitCode;
+
+const USAGE: &str = "usage: cargo xtask generate-bindings [--check]";
+
+fn main() -> ExitCode {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let args: Vec<&str> = args.iter().map(String::as_str).collect();
+    let result = match args.as_slice() {
+        ["generate-bindings"] => generate_bindings(false),
+        ["generate-bindings", "--check"] => generate_bindings(true),
+        _ => Err(USAGE.to_string()),
+    };
+    match result {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("{e}");
+            ExitCode::FAILURE
+        }
+    }
+}
+
+fn generate_bindings(check: bool) -> Result<(), String> {
+    let path = bindings::path();
+    let current = bindings::generate()?;
+    let checked_in = std::fs::read_to_string(&path).unwrap_or_default();
+    if checked_in == current {
+        return Ok(());
+    }
+    if check {
+        return Err(format!(
+            "{} is stale; run `cargo xtask generate-bindings`",
+            path.display()
+        ));
+    }
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir).map_err(|e| format!("{}: {e}", dir.display()))?;
+    }
+    std::fs::write(&path, current).map_err(|e| format!("{}: {e}", path.display()))?;
+    println!("wrote {}", path.display());
+    Ok(())
+}